@@ -0,0 +1,71 @@
+//! Benchmark de throughput do ThreadPool
+//!
+//! Mede jobs/segundo variando o número de workers, para verificar que o
+//! despacho escala com os cores. Rode com: `cargo bench --bench thread_pool`
+
+use avila_math::os::{num_cpus, ThreadPool};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const JOBS: usize = 200_000;
+
+/// Trabalho sintético por job (~alguns microssegundos)
+fn work(seed: usize) -> u64 {
+    let mut acc = seed as u64;
+    for i in 0..256u64 {
+        acc = acc.wrapping_mul(6364136223846793005).wrapping_add(i);
+    }
+    acc
+}
+
+fn run(threads: usize, work_per_job: bool) -> Duration {
+    let pool = ThreadPool::new(threads);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let start = Instant::now();
+    for i in 0..JOBS {
+        let done = Arc::clone(&done);
+        pool.execute(move || {
+            if work_per_job {
+                black_box(work(i));
+            }
+            done.fetch_add(1, Ordering::Release);
+        });
+    }
+    while done.load(Ordering::Acquire) < JOBS {
+        std::thread::yield_now();
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let max_threads = num_cpus();
+    let mut counts = vec![1];
+    while counts.last().copied().unwrap_or(1) * 2 <= max_threads {
+        counts.push(counts.last().unwrap() * 2);
+    }
+    if *counts.last().unwrap() != max_threads {
+        counts.push(max_threads);
+    }
+
+    println!("ThreadPool throughput ({} jobs)", JOBS);
+    println!("{:>8} {:>16} {:>16}", "threads", "empty jobs/s", "busy jobs/s");
+
+    let mut baseline = None;
+    for threads in counts {
+        let empty = run(threads, false);
+        let busy = run(threads, true);
+        let busy_rate = JOBS as f64 / busy.as_secs_f64();
+        let speedup = busy_rate / *baseline.get_or_insert(busy_rate);
+
+        println!(
+            "{:>8} {:>16.0} {:>16.0}  ({:.2}x)",
+            threads,
+            JOBS as f64 / empty.as_secs_f64(),
+            busy_rate,
+            speedup
+        );
+    }
+}
@@ -4,7 +4,7 @@
 //! input handling e integração com os outros sistemas da biblioteca.
 
 use avila_math::memory::Arena;
-use avila_math::os::{DeltaTime, FpsCounter};
+use avila_math::os::{FpsCounter, GameClock};
 use avila_math::window::{
     Event, EventLoop, InputState, Key, KeyCode, KeyEvent, MouseButton, MouseEvent, Window,
     WindowConfig, WindowEvent,
@@ -17,7 +17,7 @@ pub struct Application {
     event_loop: EventLoop,
     input_state: InputState,
     fps_counter: FpsCounter,
-    delta_time: DeltaTime,
+    game_clock: GameClock,
     frame_arena: Arena,
     running: bool,
 }
@@ -39,7 +39,7 @@ impl Application {
             event_loop,
             input_state,
             fps_counter: FpsCounter::new(),
-            delta_time: DeltaTime::new(),
+            game_clock: GameClock::new(),
             frame_arena: Arena::new(4 * 1024 * 1024), // 4MB para dados temporários
             running: true,
         })
@@ -51,8 +51,8 @@ impl Application {
             // 1. Processa eventos
             self.process_events();
 
-            // 2. Update da aplicação
-            let dt = self.delta_time.update();
+            // 2. Update da aplicação (tempo de jogo: para na pausa, respeita a escala)
+            let dt = self.game_clock.update();
             self.update(dt.as_secs_f32());
 
             // 3. Render
@@ -82,6 +82,7 @@ impl Application {
                 Event::Mouse(mouse_event) => {
                     self.handle_mouse_event(mouse_event);
                 }
+                Event::Touch(_) | Event::Gamepad(_) => {}
                 Event::FrameTick(_) => {}
             }
         }
@@ -127,6 +128,19 @@ impl Application {
                         let _ = self.window.set_fullscreen_borderless();
                     }
                 }
+                Key::Code(KeyCode::P) => {
+                    let paused = self.game_clock.toggle_pause();
+                    println!("{}", if paused { "Paused" } else { "Resumed" });
+                }
+                Key::Code(KeyCode::T) => {
+                    // Alterna câmera lenta
+                    let scale = if self.game_clock.time_scale() < 1.0 {
+                        1.0
+                    } else {
+                        0.25
+                    };
+                    self.game_clock.set_time_scale(scale);
+                }
                 Key::Code(KeyCode::F12) => {
                     println!("FPS: {:.1}", self.fps_counter.fps());
                 }
@@ -167,6 +181,9 @@ impl Application {
                 self.input_state.set_scroll_delta(delta.0, delta.1);
                 println!("Scroll: {:?}", delta);
             }
+            MouseEvent::RawMotion { delta } => {
+                println!("Raw motion: {:?}", delta);
+            }
         }
     }
 
@@ -215,12 +232,23 @@ impl Application {
         self.window.close();
         println!("Final stats:");
         println!("  FPS: {:.1}", self.fps_counter.fps());
+        println!(
+            "  Game time: {:.1}s / real time: {:.1}s",
+            self.game_clock.game_time().as_secs_f32(),
+            self.game_clock.real_time().as_secs_f32()
+        );
     }
 }
 
 fn main() {
-    println!("Window system example (stub - full implementation requires platform layer)");
-    println!("Run tests with: cargo test --example window_app");
+    #[cfg(any(windows, feature = "x11", feature = "wayland"))]
+    _example_main();
+
+    #[cfg(not(any(windows, feature = "x11", feature = "wayland")))]
+    {
+        println!("Window system example (enable the `x11` or `wayland` feature on Linux)");
+        println!("Run tests with: cargo test --example window_app");
+    }
 }
 
 /// Exemplo de uso
@@ -263,6 +291,8 @@ fn _example_main() {
     println!("  WASD - Movement");
     println!("  Right Mouse - Camera rotation");
     println!("  Shift - Sprint");
+    println!("  P - Pause");
+    println!("  T - Toggle slow motion");
     println!("  F11 - Toggle fullscreen");
     println!("  F12 - Print profiler report");
     println!("  Ctrl+F - Print FPS");
@@ -12,19 +12,20 @@
 //! - **Arena**: Alocador linear de alta performance para alocações temporárias
 //! - **Pool**: Alocador de objetos de tamanho fixo com zero fragmentação
 //! - **Stack**: Alocador LIFO para hierarquias
+//! - **Ring**: Alocador circular liberado quadro a quadro, para dados por quadro
 //! - **MemoryManager**: Gerenciador central com estatísticas e profiling
 //!
 //! ## Operating System Abstraction
 //! - **Threading**: Thread pools, task scheduler, sincronização avançada
 //! - **FileSystem**: Operações de arquivo e diretório cross-platform
 //! - **Clock**: Timers, FPS counter, delta time, profiling
-//! - **Network**: TCP/UDP sockets, HTTP client simples
+//! - **Network**: TCP/UDP sockets, HTTP client/server, serialização binária (`Encode`/`Decode`)
 //! - **System**: Informações do sistema, processos, variáveis de ambiente
 //!
 //! ## Exemplo de Uso - Math
 //!
 //! ```rust
-//! use kernel_math::{Vec3, Mat4, Quat, Aabb};
+//! use avila_math::{Vec3, Mat4, Quat, Aabb};
 //!
 //! // Vetores
 //! let v1 = Vec3::new(1.0, 2.0, 3.0);
@@ -49,7 +50,7 @@
 //! ## Exemplo de Uso - Memory
 //!
 //! ```rust
-//! use kernel_math::memory::{Arena, Pool, StackAllocator};
+//! use avila_math::memory::{Arena, Pool, StackAllocator};
 //!
 //! #[derive(Debug, Clone)]
 //! struct MyStruct {
@@ -75,7 +76,7 @@
 //! ## Exemplo de Uso - OS Abstraction
 //!
 //! ```rust,no_run
-//! use kernel_math::os::{ThreadPool, FileSystem, Clock, TcpServer, FpsCounter, DeltaTime};
+//! use avila_math::os::{ThreadPool, FileSystem, Clock, TcpServer, FpsCounter, DeltaTime};
 //! use std::time::Duration;
 //!
 //! // Thread pool para processamento paralelo
@@ -100,6 +101,8 @@
 //! }
 //! ```
 
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
 pub mod aabb;
 pub mod mat4;
 pub mod memory;
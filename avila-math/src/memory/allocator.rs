@@ -0,0 +1,203 @@
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+use super::manager::AllocatorInfo;
+
+/// Interface comum a todos os allocators do kernel (Arena, Pool, Stack, Ring)
+///
+/// Permite que containers e subsistemas sejam parametrizados pela estratégia
+/// de alocação, sem conhecer o allocator concreto:
+///
+/// ```
+/// use avila_math::memory::{Arena, KernelAlloc};
+/// use std::alloc::Layout;
+///
+/// fn scratch<A: KernelAlloc>(alloc: &A) -> bool {
+///     alloc.allocate(Layout::new::<[u32; 16]>()).is_some()
+/// }
+///
+/// let arena = Arena::new(1024);
+/// assert!(scratch(&arena));
+/// ```
+pub trait KernelAlloc {
+    /// Aloca um bloco com o tamanho e alinhamento do layout
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Devolve um bloco ao allocator
+    ///
+    /// Allocators que não suportam liberação individual (ex: Arena) ignoram a
+    /// chamada; a memória volta no próximo `reset`/`restore`.
+    ///
+    /// # Safety
+    /// `ptr` deve ter sido retornado por `allocate` deste mesmo allocator,
+    /// com o mesmo `layout`, e não pode ter sido liberado antes
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Indica se `deallocate` realmente devolve memória
+    fn supports_deallocate(&self) -> bool;
+
+    /// Retorna as estatísticas atuais do allocator
+    fn allocator_info(&self) -> AllocatorInfo;
+}
+
+impl<A: KernelAlloc + ?Sized> KernelAlloc for &A {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        (**self).supports_deallocate()
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        (**self).allocator_info()
+    }
+}
+
+/// Adapter de um `KernelAlloc` para `std::alloc::Allocator` (requer nightly)
+///
+/// Permite usar os allocators do kernel com as coleções da std:
+/// `Vec::new_in(StdAllocator::new(&arena))`.
+#[cfg(feature = "nightly")]
+pub struct StdAllocator<'a, A: KernelAlloc + ?Sized> {
+    inner: &'a A,
+}
+
+#[cfg(feature = "nightly")]
+impl<'a, A: KernelAlloc + ?Sized> StdAllocator<'a, A> {
+    pub fn new(inner: &'a A) -> Self {
+        Self { inner }
+    }
+
+    /// Retorna o allocator subjacente
+    pub fn inner(&self) -> &'a A {
+        self.inner
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<A: KernelAlloc + ?Sized> Clone for StdAllocator<'_, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<A: KernelAlloc + ?Sized> Copy for StdAllocator<'_, A> {}
+
+#[cfg(feature = "nightly")]
+unsafe impl<A: KernelAlloc + ?Sized> std::alloc::Allocator for StdAllocator<'_, A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        let ptr = self
+            .inner
+            .allocate(layout)
+            .ok_or(std::alloc::AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.deallocate(ptr, layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{AllocatorType, Arena, DoubleEndedStack, Pool, StackAllocator};
+
+    fn alloc_and_free<A: KernelAlloc>(alloc: &A) -> AllocatorInfo {
+        let layout = Layout::new::<u64>();
+        let ptr = alloc.allocate(layout).expect("allocation failed");
+        assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+        unsafe { alloc.deallocate(ptr, layout) };
+        alloc.allocator_info()
+    }
+
+    #[test]
+    fn test_arena_kernel_alloc() {
+        let arena = Arena::new(1024);
+        let info = alloc_and_free(&arena);
+
+        assert!(!arena.supports_deallocate());
+        assert_eq!(info.allocator_type, AllocatorType::Arena);
+        assert_eq!(info.allocation_count, 1);
+        assert_eq!(info.active_allocations(), 1);
+        assert_eq!(info.total_capacity, 1024);
+    }
+
+    #[test]
+    fn test_pool_kernel_alloc() {
+        let pool = Pool::for_type::<u64>(8);
+        let info = alloc_and_free(&pool);
+
+        assert!(pool.supports_deallocate());
+        assert_eq!(info.allocator_type, AllocatorType::Pool);
+        assert_eq!(info.active_allocations(), 0);
+
+        // Layout maior que o chunk não cabe no pool
+        assert!(pool.allocate(Layout::new::<[u64; 2]>()).is_none());
+    }
+
+    #[test]
+    fn test_stack_kernel_alloc() {
+        let stack = StackAllocator::new(1024);
+        let info = alloc_and_free(&stack);
+
+        assert!(stack.supports_deallocate());
+        assert_eq!(info.allocator_type, AllocatorType::Stack);
+        assert_eq!(info.active_allocations(), 0);
+        assert_eq!(stack.used(), 0);
+    }
+
+    #[test]
+    fn test_stack_kernel_alloc_over_aligned() {
+        let stack = StackAllocator::new(4096);
+        let double_ended = DoubleEndedStack::new(4096);
+        let allocators: [&dyn KernelAlloc; 2] = [&stack, &double_ended];
+
+        for alloc in allocators {
+            for align in [32, 64] {
+                // Um byte antes desalinha o offset seguinte
+                alloc.allocate(Layout::new::<u8>()).unwrap();
+                let layout = Layout::from_size_align(24, align).unwrap();
+                let ptr = alloc.allocate(layout).expect("allocation failed");
+                assert_eq!(ptr.as_ptr() as usize % align, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_ended_kernel_alloc() {
+        let stack = DoubleEndedStack::new(1024);
+        let info = alloc_and_free(&stack);
+
+        assert_eq!(info.allocator_type, AllocatorType::DoubleEndedStack);
+        assert_eq!(info.allocation_count, 1);
+    }
+
+    #[test]
+    fn test_dyn_kernel_alloc() {
+        let arena = Arena::new(256);
+        let pool = Pool::new(32, 8, 4);
+        let allocators: [&dyn KernelAlloc; 2] = [&arena, &pool];
+
+        for alloc in allocators {
+            assert!(alloc.allocate(Layout::from_size_align(16, 8).unwrap()).is_some());
+        }
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn test_std_allocator_adapter() {
+        let arena = Arena::new(4096);
+        let mut v: Vec<u32, _> = Vec::new_in(StdAllocator::new(&arena));
+        v.extend(0..100);
+
+        assert_eq!(v.iter().sum::<u32>(), 4950);
+        assert!(arena.used() >= 100 * std::mem::size_of::<u32>());
+    }
+}
@@ -1,6 +1,9 @@
 use std::alloc::{alloc, dealloc, Layout};
-use std::cell::Cell;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::allocator::KernelAlloc;
+use super::manager::{AllocatorInfo, AllocatorType};
 
 /// Arena Allocator - aloca memória sequencialmente de um bloco pré-alocado
 /// Ideal para alocações temporárias que são liberadas todas de uma vez
@@ -10,11 +13,16 @@ use std::ptr::NonNull;
 /// - Não suporta free individual, apenas reset completo
 /// - Excelente localidade de cache
 /// - Perfeito para frames em game engines, parsing temporário, etc.
+///
+/// O offset e os contadores são atômicos, então a arena pode ser compartilhada
+/// entre threads (ex: o `Arc` de `MemoryManager::create_arena`).
 pub struct Arena {
     buffer: NonNull<u8>,
     capacity: usize,
-    offset: Cell<usize>,
+    offset: AtomicUsize,
     layout: Layout,
+    total_allocations: AtomicUsize,
+    live_allocations: AtomicUsize,
 }
 
 impl Arena {
@@ -36,8 +44,10 @@ impl Arena {
         Self {
             buffer,
             capacity,
-            offset: Cell::new(0),
+            offset: AtomicUsize::new(0),
             layout,
+            total_allocations: AtomicUsize::new(0),
+            live_allocations: AtomicUsize::new(0),
         }
     }
 
@@ -48,17 +58,31 @@ impl Arena {
 
     /// Aloca um bloco de memória com o tamanho e alinhamento especificados
     pub fn alloc(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
-        let current_offset = self.offset.get();
+        let mut current_offset = self.offset.load(Ordering::Relaxed);
 
-        // Calcula o offset alinhado
-        let aligned_offset = align_up(current_offset, align);
-        let new_offset = aligned_offset.checked_add(size)?;
+        let aligned_offset = loop {
+            // Calcula o offset alinhado
+            let aligned_offset = align_up(current_offset, align);
+            let new_offset = aligned_offset.checked_add(size)?;
 
-        if new_offset > self.capacity {
-            return None; // Arena cheia
-        }
+            if new_offset > self.capacity {
+                return None; // Arena cheia
+            }
 
-        self.offset.set(new_offset);
+            // Reserva o bloco; se outra thread alocou antes, tenta de novo
+            match self.offset.compare_exchange_weak(
+                current_offset,
+                new_offset,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break aligned_offset,
+                Err(actual) => current_offset = actual,
+            }
+        };
+
+        self.total_allocations.fetch_add(1, Ordering::Relaxed);
+        self.live_allocations.fetch_add(1, Ordering::Relaxed);
 
         unsafe {
             let ptr = self.buffer.as_ptr().add(aligned_offset);
@@ -87,12 +111,13 @@ impl Arena {
     /// Reseta a arena, permitindo reutilização da memória
     /// ATENÇÃO: Não chama destructors! Use apenas com tipos Copy ou que não precisam de cleanup
     pub fn reset(&self) {
-        self.offset.set(0);
+        self.offset.store(0, Ordering::Relaxed);
+        self.live_allocations.store(0, Ordering::Relaxed);
     }
 
     /// Retorna a quantidade de memória usada (em bytes)
     pub fn used(&self) -> usize {
-        self.offset.get()
+        self.offset.load(Ordering::Relaxed)
     }
 
     /// Retorna a capacidade total da arena (em bytes)
@@ -110,20 +135,33 @@ impl Arena {
         (self.used() as f32 / self.capacity as f32) * 100.0
     }
 
+    /// Retorna o número total de alocações feitas desde a criação
+    pub fn total_allocations(&self) -> usize {
+        self.total_allocations.load(Ordering::Relaxed)
+    }
+
+    /// Retorna o número de alocações ainda vivas (desde o último reset/restore)
+    pub fn live_allocations(&self) -> usize {
+        self.live_allocations.load(Ordering::Relaxed)
+    }
+
     /// Cria um checkpoint que pode ser usado para liberar memória até esse ponto
     pub fn checkpoint(&self) -> ArenaCheckpoint {
         ArenaCheckpoint {
-            offset: self.offset.get(),
+            offset: self.used(),
+            live_allocations: self.live_allocations.load(Ordering::Relaxed),
         }
     }
 
     /// Restaura a arena para um checkpoint anterior
     pub fn restore(&self, checkpoint: ArenaCheckpoint) {
         assert!(
-            checkpoint.offset <= self.offset.get(),
+            checkpoint.offset <= self.used(),
             "Cannot restore to a checkpoint beyond current offset"
         );
-        self.offset.set(checkpoint.offset);
+        self.offset.store(checkpoint.offset, Ordering::Relaxed);
+        self.live_allocations
+            .store(checkpoint.live_allocations, Ordering::Relaxed);
     }
 }
 
@@ -135,13 +173,44 @@ impl Drop for Arena {
     }
 }
 
+// O buffer só é acessado através de blocos reservados no offset atômico
 unsafe impl Send for Arena {}
 unsafe impl Sync for Arena {}
 
+impl KernelAlloc for Arena {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.alloc(layout.size(), layout.align())
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Arena não libera individualmente - a memória volta no reset/restore
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        false
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        // Lê as vivas antes do total: outra thread pode alocar entre as duas leituras
+        let live = self.live_allocations();
+        let total = self.total_allocations();
+        let used = self.used();
+        AllocatorInfo {
+            allocator_type: AllocatorType::Arena,
+            total_capacity: self.capacity,
+            used,
+            available: self.capacity - used,
+            allocation_count: total,
+            deallocation_count: total.saturating_sub(live),
+        }
+    }
+}
+
 /// Checkpoint para restaurar a arena a um estado anterior
 #[derive(Debug, Clone, Copy)]
 pub struct ArenaCheckpoint {
     offset: usize,
+    live_allocations: usize,
 }
 
 /// Arena com escopo automático - reseta ao sair do escopo
@@ -1,12 +1,28 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::allocator::KernelAlloc;
+use super::arena::Arena;
+use super::pool::Pool;
+use super::ring::RingAllocator;
+use super::stack::{DoubleEndedStack, StackAllocator};
+
+type TrackedAllocator = Weak<dyn KernelAlloc + Send + Sync>;
 
 /// Memory Manager - gerenciador central de memória com estatísticas
 /// Coordena múltiplos allocators e fornece visibilidade sobre uso de memória
 pub struct MemoryManager {
     stats: MemoryStats,
     allocators: HashMap<String, AllocatorInfo>,
+    tracked: HashMap<String, TrackedAllocator>,
 }
 
 impl MemoryManager {
@@ -14,6 +30,7 @@ impl MemoryManager {
         Self {
             stats: MemoryStats::new(),
             allocators: HashMap::new(),
+            tracked: HashMap::new(),
         }
     }
 
@@ -22,6 +39,104 @@ impl MemoryManager {
         self.allocators.insert(name.into(), info);
     }
 
+    /// Cria uma arena rastreada automaticamente pelo manager
+    ///
+    /// As estatísticas são lidas da arena a cada `report()`; o manager guarda
+    /// apenas uma referência fraca, então a arena é liberada quando o último
+    /// `Arc` sai de escopo.
+    pub fn create_arena(&mut self, name: impl Into<String>, capacity: usize) -> Arc<Arena> {
+        let arena = Arc::new(Arena::new(capacity));
+        self.track(name, &arena);
+        arena
+    }
+
+    /// Cria um pool rastreado automaticamente pelo manager
+    pub fn create_pool(
+        &mut self,
+        name: impl Into<String>,
+        chunk_size: usize,
+        chunk_align: usize,
+        chunks_per_block: usize,
+    ) -> Arc<Pool> {
+        let pool = Arc::new(Pool::new(chunk_size, chunk_align, chunks_per_block));
+        self.track(name, &pool);
+        pool
+    }
+
+    /// Cria um pool para um tipo específico, rastreado pelo manager
+    pub fn create_pool_for<T>(&mut self, name: impl Into<String>, chunks_per_block: usize) -> Arc<Pool> {
+        let pool = Arc::new(Pool::for_type::<T>(chunks_per_block));
+        self.track(name, &pool);
+        pool
+    }
+
+    /// Cria um stack allocator rastreado automaticamente pelo manager
+    pub fn create_stack(&mut self, name: impl Into<String>, capacity: usize) -> Arc<StackAllocator> {
+        let stack = Arc::new(StackAllocator::new(capacity));
+        self.track(name, &stack);
+        stack
+    }
+
+    /// Cria uma double-ended stack rastreada automaticamente pelo manager
+    pub fn create_double_ended_stack(
+        &mut self,
+        name: impl Into<String>,
+        capacity: usize,
+    ) -> Arc<DoubleEndedStack> {
+        let stack = Arc::new(DoubleEndedStack::new(capacity));
+        self.track(name, &stack);
+        stack
+    }
+
+    /// Cria um ring allocator rastreado automaticamente pelo manager
+    pub fn create_ring(&mut self, name: impl Into<String>, capacity: usize) -> Arc<RingAllocator> {
+        let ring = Arc::new(RingAllocator::new(capacity));
+        self.track(name, &ring);
+        ring
+    }
+
+    /// Passa a rastrear um allocator já existente (inclusive customizados)
+    pub fn track<A>(&mut self, name: impl Into<String>, allocator: &Arc<A>)
+    where
+        A: KernelAlloc + Send + Sync + 'static,
+    {
+        self.prune_dropped();
+        let allocator: Arc<dyn KernelAlloc + Send + Sync> = allocator.clone();
+        self.tracked.insert(name.into(), Arc::downgrade(&allocator));
+    }
+
+    /// Para de rastrear um allocator
+    pub fn untrack(&mut self, name: &str) -> bool {
+        self.tracked.remove(name).is_some()
+    }
+
+    /// Remove allocators rastreados que já foram destruídos
+    pub fn prune_dropped(&mut self) {
+        self.tracked.retain(|_, allocator| allocator.strong_count() > 0);
+    }
+
+    /// Obtém as estatísticas atuais de um allocator (rastreado ou registrado)
+    pub fn snapshot(&self, name: &str) -> Option<AllocatorInfo> {
+        self.tracked
+            .get(name)
+            .and_then(Weak::upgrade)
+            .map(|allocator| allocator.allocator_info())
+            .or_else(|| self.allocators.get(name).cloned())
+    }
+
+    /// Coleta as estatísticas de todos os allocators, lendo os rastreados ao vivo
+    fn collect_allocators(&self) -> HashMap<String, AllocatorInfo> {
+        let mut allocators = self.allocators.clone();
+
+        for (name, allocator) in &self.tracked {
+            if let Some(allocator) = allocator.upgrade() {
+                allocators.insert(name.clone(), allocator.allocator_info());
+            }
+        }
+
+        allocators
+    }
+
     /// Obtém estatísticas globais de memória
     pub fn global_stats(&self) -> &MemoryStats {
         &self.stats
@@ -39,11 +154,12 @@ impl MemoryManager {
 
     /// Gera um relatório de memória
     pub fn report(&self) -> MemoryReport {
+        let allocators = self.collect_allocators();
         let mut total_allocated = 0;
         let mut total_used = 0;
         let mut total_free = 0;
 
-        for info in self.allocators.values() {
+        for info in allocators.values() {
             total_allocated += info.total_capacity;
             total_used += info.used;
             total_free += info.available;
@@ -51,18 +167,36 @@ impl MemoryManager {
 
         MemoryReport {
             timestamp: Instant::now(),
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
             total_allocated,
             total_used,
             total_free,
-            allocator_count: self.allocators.len(),
-            allocators: self.allocators.clone(),
+            allocator_count: allocators.len(),
+            allocators,
         }
     }
 
+    /// Adiciona um alerta de watermark sobre o uso global de memória
+    pub fn add_watermark<F>(&self, threshold: usize, callback: F)
+    where
+        F: Fn(&WatermarkEvent) + Send + Sync + 'static,
+    {
+        self.stats.add_watermark(threshold, callback);
+    }
+
+    /// Adiciona um watermark que apenas registra o alerta no stderr
+    pub fn add_watermark_log(&self, threshold: usize) {
+        self.stats.add_watermark_log(threshold);
+    }
+
     /// Limpa estatísticas
     pub fn reset_stats(&mut self) {
         self.stats.reset();
         self.allocators.clear();
+        self.tracked.clear();
     }
 }
 
@@ -80,6 +214,25 @@ pub struct MemoryStats {
     total_bytes_deallocated: AtomicUsize,
     peak_memory_usage: AtomicUsize,
     current_memory_usage: AtomicUsize,
+    watermarks: Mutex<Vec<Watermark>>,
+    // Menor threshold ainda não disparado (usize::MAX se nenhum), evita lock no caminho rápido
+    next_watermark: AtomicUsize,
+}
+
+type WatermarkCallback = Arc<dyn Fn(&WatermarkEvent) + Send + Sync>;
+
+struct Watermark {
+    threshold: usize,
+    triggered: bool,
+    callback: WatermarkCallback,
+}
+
+/// Evento disparado quando o uso de memória cruza um watermark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatermarkEvent {
+    pub threshold: usize,
+    pub current_usage: usize,
+    pub peak_usage: usize,
 }
 
 impl MemoryStats {
@@ -91,6 +244,91 @@ impl MemoryStats {
             total_bytes_deallocated: AtomicUsize::new(0),
             peak_memory_usage: AtomicUsize::new(0),
             current_memory_usage: AtomicUsize::new(0),
+            watermarks: Mutex::new(Vec::new()),
+            next_watermark: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    /// Adiciona um watermark: o callback é chamado uma única vez, na primeira
+    /// vez que o uso atual atingir `threshold` bytes (até `rearm_watermarks`)
+    pub fn add_watermark<F>(&self, threshold: usize, callback: F)
+    where
+        F: Fn(&WatermarkEvent) + Send + Sync + 'static,
+    {
+        let mut watermarks = self.watermarks.lock().unwrap();
+        watermarks.push(Watermark {
+            threshold,
+            triggered: false,
+            callback: Arc::new(callback),
+        });
+        self.next_watermark.fetch_min(threshold, Ordering::Relaxed);
+        drop(watermarks);
+
+        // O uso atual pode já estar acima do novo threshold
+        self.check_watermarks(self.current_memory_usage());
+    }
+
+    /// Adiciona um watermark que registra o alerta no stderr
+    pub fn add_watermark_log(&self, threshold: usize) {
+        self.add_watermark(threshold, |event| {
+            eprintln!(
+                "[memory] watermark {} crossed: current {} (peak {})",
+                format::bytes(event.threshold),
+                format::bytes(event.current_usage),
+                format::bytes(event.peak_usage)
+            );
+        });
+    }
+
+    /// Rearma todos os watermarks para dispararem novamente
+    pub fn rearm_watermarks(&self) {
+        let mut watermarks = self.watermarks.lock().unwrap();
+        for watermark in watermarks.iter_mut() {
+            watermark.triggered = false;
+        }
+        let next = watermarks.iter().map(|w| w.threshold).min().unwrap_or(usize::MAX);
+        self.next_watermark.store(next, Ordering::Relaxed);
+    }
+
+    /// Remove todos os watermarks
+    pub fn clear_watermarks(&self) {
+        self.watermarks.lock().unwrap().clear();
+        self.next_watermark.store(usize::MAX, Ordering::Relaxed);
+    }
+
+    fn check_watermarks(&self, current: usize) {
+        if current < self.next_watermark.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut watermarks = self.watermarks.lock().unwrap();
+        let peak_usage = self.peak_memory_usage();
+        let mut fired = Vec::new();
+
+        for watermark in watermarks.iter_mut() {
+            if !watermark.triggered && current >= watermark.threshold {
+                watermark.triggered = true;
+                let event = WatermarkEvent {
+                    threshold: watermark.threshold,
+                    current_usage: current,
+                    peak_usage,
+                };
+                fired.push((Arc::clone(&watermark.callback), event));
+            }
+        }
+
+        let next = watermarks
+            .iter()
+            .filter(|w| !w.triggered)
+            .map(|w| w.threshold)
+            .min()
+            .unwrap_or(usize::MAX);
+        self.next_watermark.store(next, Ordering::Relaxed);
+        drop(watermarks);
+
+        // Callbacks rodam fora do lock - podem registrar alocações sem deadlock
+        for (callback, event) in fired {
+            callback(&event);
         }
     }
 
@@ -113,6 +351,8 @@ impl MemoryStats {
                 Err(x) => peak = x,
             }
         }
+
+        self.check_watermarks(current);
     }
 
     pub fn record_deallocation(&self, size: usize) {
@@ -156,6 +396,7 @@ impl MemoryStats {
         self.total_bytes_deallocated.store(0, Ordering::Relaxed);
         self.peak_memory_usage.store(0, Ordering::Relaxed);
         self.current_memory_usage.store(0, Ordering::Relaxed);
+        self.rearm_watermarks();
     }
 }
 
@@ -167,6 +408,7 @@ impl Default for MemoryStats {
 
 /// Informações sobre um allocator
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AllocatorInfo {
     pub allocator_type: AllocatorType,
     pub total_capacity: usize,
@@ -190,18 +432,27 @@ impl AllocatorInfo {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AllocatorType {
     Arena,
     Pool,
     Stack,
     DoubleEndedStack,
+    Ring,
+    /// Heap de memória de GPU (ex.: avila-renderer)
+    Gpu,
     Custom,
 }
 
 /// Relatório de memória em um ponto no tempo
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MemoryReport {
+    /// Instante monotônico da coleta (não serializado)
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
     pub timestamp: Instant,
+    /// Momento da coleta em milissegundos desde a época Unix
+    pub timestamp_ms: u64,
     pub total_allocated: usize,
     pub total_used: usize,
     pub total_free: usize,
@@ -245,24 +496,58 @@ impl MemoryReport {
         }
     }
 
+    /// Serializa o relatório (incluindo o detalhamento por allocator) em JSON
+    #[cfg(feature = "serde")]
     pub fn to_json(&self) -> String {
-        // Implementação simples - em produção usaria serde
-        format!(
-            r#"{{
-  "timestamp": "{:?}",
-  "total_allocated": {},
-  "total_used": {},
-  "total_free": {},
-  "utilization": {:.2},
-  "allocator_count": {}
-}}"#,
-            self.timestamp,
-            self.total_allocated,
-            self.total_used,
-            self.total_free,
-            self.utilization(),
-            self.allocator_count
-        )
+        serde_json::to_string_pretty(self).expect("MemoryReport is always serializable")
+    }
+
+    /// Serializa o relatório (incluindo o detalhamento por allocator) em JSON
+    ///
+    /// Sem a feature `serde` o JSON é montado à mão, no mesmo formato.
+    #[cfg(not(feature = "serde"))]
+    pub fn to_json(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::from("{\n");
+        let _ = writeln!(out, "  \"timestamp_ms\": {},", self.timestamp_ms);
+        let _ = writeln!(out, "  \"total_allocated\": {},", self.total_allocated);
+        let _ = writeln!(out, "  \"total_used\": {},", self.total_used);
+        let _ = writeln!(out, "  \"total_free\": {},", self.total_free);
+        let _ = writeln!(out, "  \"allocator_count\": {},", self.allocator_count);
+        out.push_str("  \"allocators\": {");
+        for (i, (name, info)) in self.allocators.iter().enumerate() {
+            out.push_str(if i == 0 { "\n    \"" } else { ",\n    \"" });
+            for c in name.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    c if (c as u32) < 0x20 => {
+                        let _ = write!(out, "\\u{:04x}", c as u32);
+                    }
+                    c => out.push(c),
+                }
+            }
+            out.push_str("\": {\n");
+            let _ = writeln!(out, "      \"allocator_type\": \"{:?}\",", info.allocator_type);
+            let _ = writeln!(out, "      \"total_capacity\": {},", info.total_capacity);
+            let _ = writeln!(out, "      \"used\": {},", info.used);
+            let _ = writeln!(out, "      \"available\": {},", info.available);
+            let _ = writeln!(out, "      \"allocation_count\": {},", info.allocation_count);
+            let _ = writeln!(out, "      \"deallocation_count\": {}", info.deallocation_count);
+            out.push_str("    }");
+        }
+        if !self.allocators.is_empty() {
+            out.push_str("\n  ");
+        }
+        out.push_str("}\n}");
+        out
+    }
+
+    /// Reconstrói um relatório a partir do JSON gerado por `to_json`
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
     }
 }
 
@@ -271,6 +556,7 @@ pub struct MemoryProfiler {
     samples: Vec<MemorySample>,
     sample_interval: Duration,
     last_sample: Option<Instant>,
+    started_at: Instant,
 }
 
 impl MemoryProfiler {
@@ -279,6 +565,7 @@ impl MemoryProfiler {
             samples: Vec::new(),
             sample_interval,
             last_sample: None,
+            started_at: Instant::now(),
         }
     }
 
@@ -324,6 +611,78 @@ impl MemoryProfiler {
     pub fn peak_usage(&self) -> Option<usize> {
         self.samples.iter().map(|s| s.peak_usage).max()
     }
+
+    /// Instante de referência para os timestamps exportados
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    /// Exporta as amostras como CSV (tempo em milissegundos desde o início)
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Escreve as amostras como CSV em qualquer writer
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "time_ms,current_usage,peak_usage,total_allocated,total_deallocated,active_allocations"
+        )?;
+
+        for sample in &self.samples {
+            writeln!(
+                writer,
+                "{:.3},{},{},{},{},{}",
+                self.elapsed_micros(sample) as f64 / 1000.0,
+                sample.current_usage,
+                sample.peak_usage,
+                sample.total_allocated,
+                sample.total_deallocated,
+                sample.active_allocations
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Exporta as amostras no formato trace-event JSON (chrome://tracing, Perfetto)
+    pub fn export_chrome_trace<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_chrome_trace(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Escreve as amostras como eventos de contador ("ph": "C") do trace-event format
+    pub fn write_chrome_trace<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let pid = std::process::id();
+
+        writeln!(writer, "{{\"traceEvents\":[")?;
+        for (i, sample) in self.samples.iter().enumerate() {
+            let separator = if i + 1 < self.samples.len() { "," } else { "" };
+            let ts = self.elapsed_micros(sample);
+
+            writeln!(
+                writer,
+                "{{\"name\":\"memory\",\"ph\":\"C\",\"ts\":{},\"pid\":{},\"tid\":0,\"args\":{{\"current_usage\":{},\"peak_usage\":{}}}}},",
+                ts, pid, sample.current_usage, sample.peak_usage
+            )?;
+            writeln!(
+                writer,
+                "{{\"name\":\"allocations\",\"ph\":\"C\",\"ts\":{},\"pid\":{},\"tid\":0,\"args\":{{\"active_allocations\":{}}}}}{}",
+                ts, pid, sample.active_allocations, separator
+            )?;
+        }
+        writeln!(writer, "],\"displayTimeUnit\":\"ms\"}}")
+    }
+
+    fn elapsed_micros(&self, sample: &MemorySample) -> u128 {
+        sample
+            .timestamp
+            .saturating_duration_since(self.started_at)
+            .as_micros()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -387,6 +746,38 @@ mod tests {
         assert_eq!(stats.peak_memory_usage(), 1500); // Peak não muda
     }
 
+    #[test]
+    fn test_watermarks() {
+        let stats = MemoryStats::new();
+        let fired = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&fired);
+        stats.add_watermark(1000, move |event| log.lock().unwrap().push(*event));
+        let log = Arc::clone(&fired);
+        stats.add_watermark(4000, move |event| log.lock().unwrap().push(*event));
+
+        stats.record_allocation(600);
+        assert!(fired.lock().unwrap().is_empty());
+
+        stats.record_allocation(600);
+        stats.record_deallocation(600);
+        stats.record_allocation(600); // Não dispara de novo
+        {
+            let fired = fired.lock().unwrap();
+            assert_eq!(fired.len(), 1);
+            assert_eq!(fired[0].threshold, 1000);
+            assert_eq!(fired[0].current_usage, 1200);
+        }
+
+        stats.record_allocation(5000);
+        assert_eq!(fired.lock().unwrap().len(), 2);
+        assert_eq!(fired.lock().unwrap()[1].threshold, 4000);
+
+        stats.reset();
+        stats.record_allocation(1500);
+        assert_eq!(fired.lock().unwrap().len(), 3);
+    }
+
     #[test]
     fn test_memory_manager() {
         let mut manager = MemoryManager::new();
@@ -405,6 +796,136 @@ mod tests {
         assert_eq!(report.total_allocated, 1024);
     }
 
+    #[test]
+    fn test_tracked_allocators() {
+        let mut manager = MemoryManager::new();
+
+        let arena = manager.create_arena("frame", 1024);
+        let pool = manager.create_pool_for::<u64>("entities", 16);
+
+        arena.alloc(100, 4);
+        pool.alloc().unwrap();
+
+        let report = manager.report();
+        assert_eq!(report.allocator_count, 2);
+        assert_eq!(report.allocators["frame"].used, 100);
+        assert_eq!(report.allocators["frame"].allocation_count, 1);
+        assert_eq!(report.allocators["entities"].active_allocations(), 1);
+
+        // Estatísticas refletem o estado atual sem re-registro manual
+        arena.reset();
+        assert_eq!(manager.snapshot("frame").unwrap().used, 0);
+
+        drop(arena);
+        let report = manager.report();
+        assert_eq!(report.allocator_count, 1);
+        assert!(manager.snapshot("frame").is_none());
+    }
+
+    #[test]
+    fn test_tracked_allocators_across_threads() {
+        let mut manager = MemoryManager::new();
+
+        let arena = manager.create_arena("frame", 64 * 1024);
+        let stack = manager.create_stack("scratch", 64 * 1024);
+        let pool = manager.create_pool_for::<u64>("entities", 16);
+
+        let worker = {
+            let (arena, stack, pool) = (arena.clone(), stack.clone(), pool.clone());
+            std::thread::spawn(move || {
+                for _ in 0..1000 {
+                    arena.alloc(16, 8).unwrap();
+                    let ptr = stack.alloc(16, 8).unwrap();
+                    unsafe { stack.free(ptr) };
+                    pool.alloc().unwrap();
+                }
+            })
+        };
+
+        // Lê as estatísticas enquanto a outra thread aloca
+        while !worker.is_finished() {
+            let report = manager.report();
+            assert!(report.allocators["frame"].used <= 64 * 1024);
+            assert!(report.allocators["entities"].active_allocations() <= 1000);
+        }
+        worker.join().unwrap();
+
+        let report = manager.report();
+        assert_eq!(report.allocators["frame"].allocation_count, 1000);
+        assert_eq!(report.allocators["scratch"].used, 0);
+        assert_eq!(report.allocators["scratch"].allocation_count, 1000);
+        assert_eq!(report.allocators["entities"].active_allocations(), 1000);
+    }
+
+    #[test]
+    fn test_report_to_json() {
+        let mut manager = MemoryManager::new();
+        let arena = manager.create_arena("frame \"main\"", 2048);
+        arena.alloc(512, 8);
+
+        let json = manager.report().to_json();
+        assert!(json.contains("\"frame \\\"main\\\"\": {"));
+        assert!(json.contains("\"allocator_type\": \"Arena\""));
+        assert!(json.contains("\"total_used\": 512"));
+        assert!(json.contains("\"total_capacity\": 2048"));
+
+        let empty = MemoryManager::new().report().to_json();
+        assert!(empty.contains("\"allocators\": {}"));
+        assert!(empty.ends_with("\n}"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json_roundtrip() {
+        let mut manager = MemoryManager::new();
+        let arena = manager.create_arena("frame", 2048);
+        arena.alloc(512, 8);
+
+        let report = manager.report();
+        let json = report.to_json();
+        assert!(json.contains("\"frame\""));
+        assert!(json.contains("\"Arena\""));
+
+        let parsed = MemoryReport::from_json(&json).unwrap();
+        assert_eq!(parsed.timestamp_ms, report.timestamp_ms);
+        assert_eq!(parsed.total_used, 512);
+        assert_eq!(parsed.allocators["frame"].total_capacity, 2048);
+        assert_eq!(parsed.allocators["frame"].allocator_type, AllocatorType::Arena);
+
+        assert!(MemoryReport::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_profiler_export() {
+        let stats = MemoryStats::new();
+        let mut profiler = MemoryProfiler::new(Duration::ZERO);
+
+        stats.record_allocation(1024);
+        profiler.sample(&stats);
+        stats.record_allocation(2048);
+        profiler.sample(&stats);
+
+        let mut csv = Vec::new();
+        profiler.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("time_ms,current_usage"));
+        assert!(lines[2].contains(",3072,3072,"));
+
+        let mut trace = Vec::new();
+        profiler.write_chrome_trace(&mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert_eq!(trace.matches("\"ph\":\"C\"").count(), 4);
+        assert!(trace.trim_end().ends_with("\"displayTimeUnit\":\"ms\"}"));
+
+        let path = std::env::temp_dir().join("avila_memory_profile.csv");
+        profiler.export_csv(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("3072"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format::bytes(512), "512 bytes");
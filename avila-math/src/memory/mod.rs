@@ -1,12 +1,18 @@
+pub mod allocator;
 pub mod arena;
 pub mod pool;
+pub mod ring;
 pub mod stack;
 pub mod manager;
 
+pub use allocator::KernelAlloc;
+#[cfg(feature = "nightly")]
+pub use allocator::StdAllocator;
 pub use arena::{Arena, ArenaCheckpoint, ScopedArena};
 pub use pool::{Pool, PoolStats, TypedPool, PoolBox};
+pub use ring::RingAllocator;
 pub use stack::{StackAllocator, StackMark, ScopedStack, DoubleEndedStack};
 pub use manager::{
     MemoryManager, MemoryStats, AllocatorInfo, AllocatorType,
-    MemoryReport, MemoryProfiler, MemorySample, format,
+    MemoryReport, MemoryProfiler, MemorySample, WatermarkEvent, format,
 };
@@ -1,7 +1,10 @@
 use std::alloc::{alloc, dealloc, Layout};
-use std::cell::RefCell;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use super::allocator::KernelAlloc;
+use super::manager::{AllocatorInfo, AllocatorType};
 
 /// Pool Allocator - gerencia blocos de tamanho fixo
 /// Ideal para alocações frequentes de objetos do mesmo tamanho
@@ -11,16 +14,24 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 /// - Zero fragmentação para objetos de tamanho fixo
 /// - Excelente para gerenciar entidades, partículas, etc.
 /// - Cache-friendly com memória contígua
+///
+/// Os blocos e a free list ficam atrás de um `Mutex`, então o pool pode ser
+/// compartilhado entre threads (ex: o `Arc` de `MemoryManager::create_pool`).
 pub struct Pool {
     chunk_size: usize,
     chunk_align: usize,
     chunks_per_block: usize,
-    blocks: RefCell<Vec<PoolBlock>>,
-    free_list: RefCell<Vec<NonNull<u8>>>,
+    state: Mutex<PoolState>,
     total_allocated: AtomicUsize,
     total_freed: AtomicUsize,
 }
 
+/// Blocos e free list de um `Pool`
+struct PoolState {
+    blocks: Vec<PoolBlock>,
+    free_list: Vec<NonNull<u8>>,
+}
+
 struct PoolBlock {
     memory: NonNull<u8>,
     layout: Layout,
@@ -48,13 +59,19 @@ impl Pool {
             chunk_size,
             chunk_align,
             chunks_per_block,
-            blocks: RefCell::new(Vec::new()),
-            free_list: RefCell::new(Vec::new()),
+            state: Mutex::new(PoolState {
+                blocks: Vec::new(),
+                free_list: Vec::new(),
+            }),
             total_allocated: AtomicUsize::new(0),
             total_freed: AtomicUsize::new(0),
         }
     }
 
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Cria um pool para um tipo específico
     pub fn for_type<T>(chunks_per_block: usize) -> Self {
         Self::new(
@@ -66,17 +83,19 @@ impl Pool {
 
     /// Aloca um chunk do pool
     pub fn alloc(&self) -> Option<NonNull<u8>> {
+        let mut state = self.state();
+
         // Tenta pegar da free list
-        if let Some(ptr) = self.free_list.borrow_mut().pop() {
+        if let Some(ptr) = state.free_list.pop() {
             self.total_allocated.fetch_add(1, Ordering::Relaxed);
             return Some(ptr);
         }
 
         // Se não tem na free list, aloca um novo bloco
-        self.allocate_new_block();
+        self.allocate_new_block(&mut state);
 
         // Tenta novamente
-        if let Some(ptr) = self.free_list.borrow_mut().pop() {
+        if let Some(ptr) = state.free_list.pop() {
             self.total_allocated.fetch_add(1, Ordering::Relaxed);
             return Some(ptr);
         }
@@ -97,7 +116,8 @@ impl Pool {
     /// # Safety
     /// O ponteiro deve ter sido alocado por este pool
     pub unsafe fn free(&self, ptr: NonNull<u8>) {
-        self.free_list.borrow_mut().push(ptr);
+        let mut state = self.state();
+        state.free_list.push(ptr);
         self.total_freed.fetch_add(1, Ordering::Relaxed);
     }
 
@@ -110,7 +130,7 @@ impl Pool {
     }
 
     /// Aloca um novo bloco de memória e adiciona chunks à free list
-    fn allocate_new_block(&self) {
+    fn allocate_new_block(&self, state: &mut PoolState) {
         let block_size = self.chunk_size * self.chunks_per_block;
         let layout =
             Layout::from_size_align(block_size, self.chunk_align).expect("Failed to create layout");
@@ -124,14 +144,13 @@ impl Pool {
             let memory_ptr = NonNull::new_unchecked(memory);
 
             // Adiciona todos os chunks deste bloco à free list
-            let mut free_list = self.free_list.borrow_mut();
             for i in 0..self.chunks_per_block {
                 let chunk_ptr = memory.add(i * self.chunk_size);
-                free_list.push(NonNull::new_unchecked(chunk_ptr));
+                state.free_list.push(NonNull::new_unchecked(chunk_ptr));
             }
 
             // Guarda o bloco para fazer cleanup depois
-            self.blocks.borrow_mut().push(PoolBlock {
+            state.blocks.push(PoolBlock {
                 memory: memory_ptr,
                 layout,
             });
@@ -140,16 +159,17 @@ impl Pool {
 
     /// Retorna estatísticas do pool
     pub fn stats(&self) -> PoolStats {
+        let state = self.state();
         let allocated = self.total_allocated.load(Ordering::Relaxed);
         let freed = self.total_freed.load(Ordering::Relaxed);
         let in_use = allocated - freed;
-        let free_chunks = self.free_list.borrow().len();
-        let total_chunks = self.blocks.borrow().len() * self.chunks_per_block;
+        let free_chunks = state.free_list.len();
+        let total_chunks = state.blocks.len() * self.chunks_per_block;
 
         PoolStats {
             chunk_size: self.chunk_size,
             chunks_per_block: self.chunks_per_block,
-            total_blocks: self.blocks.borrow().len(),
+            total_blocks: state.blocks.len(),
             total_chunks,
             chunks_in_use: in_use,
             chunks_free: free_chunks,
@@ -169,17 +189,48 @@ impl Pool {
 
 impl Drop for Pool {
     fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
         unsafe {
-            for block in self.blocks.borrow_mut().drain(..) {
+            for block in state.blocks.drain(..) {
                 dealloc(block.memory.as_ptr(), block.layout);
             }
         }
     }
 }
 
+// Os chunks só entram e saem da free list com o `Mutex` adquirido
 unsafe impl Send for Pool {}
 unsafe impl Sync for Pool {}
 
+impl KernelAlloc for Pool {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if layout.size() > self.chunk_size || layout.align() > self.chunk_align {
+            return None;
+        }
+        self.alloc()
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        self.free(ptr);
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        true
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        let stats = self.stats();
+        AllocatorInfo {
+            allocator_type: AllocatorType::Pool,
+            total_capacity: stats.memory_reserved,
+            used: stats.memory_used,
+            available: stats.memory_reserved - stats.memory_used,
+            allocation_count: stats.total_allocated,
+            deallocation_count: stats.total_freed,
+        }
+    }
+}
+
 /// Estatísticas de um pool
 #[derive(Debug, Clone)]
 pub struct PoolStats {
@@ -0,0 +1,337 @@
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::collections::VecDeque;
+use std::ptr::NonNull;
+use std::sync::{Mutex, MutexGuard};
+
+use super::allocator::KernelAlloc;
+use super::manager::{AllocatorInfo, AllocatorType};
+
+/// Alinhamento do início do buffer; alinhamentos até este valor valem tanto
+/// para endereços quanto para offsets
+const BASE_ALIGN: usize = 256;
+
+/// Ring Allocator - aloca em um buffer circular, liberando quadro a quadro
+/// Ideal para dados por quadro que vivem alguns quadros (ex: uniforms que a
+/// GPU ainda está lendo)
+///
+/// Características:
+/// - Alocação O(1), sem liberação individual
+/// - `end_frame` marca o fim das alocações de um quadro
+/// - `release_frame` libera o quadro mais antigo ainda vivo
+/// - Uma alocação nunca dá a volta no buffer: pula para o início se não
+///   couber no fim
+///
+/// As posições e os contadores ficam atrás de um `Mutex`, então o ring pode
+/// ser compartilhado entre threads (ex: o `Arc` de
+/// `MemoryManager::create_ring`).
+pub struct RingAllocator {
+    buffer: NonNull<u8>,
+    capacity: usize,
+    layout: Layout,
+    state: Mutex<RingState>,
+}
+
+/// Posições e contadores de um `RingAllocator`
+struct RingState {
+    /// Posições crescentes; `% capacity` dá o offset no buffer
+    head: usize,
+    tail: usize,
+    /// Fim de cada quadro ainda vivo, do mais antigo ao mais novo
+    frames: VecDeque<usize>,
+    total_allocations: usize,
+    released_allocations: usize,
+    /// Alocações de cada quadro vivo e do quadro atual
+    frame_allocations: VecDeque<usize>,
+}
+
+impl RingAllocator {
+    /// Cria um ring allocator com a capacidade especificada, com a memória
+    /// zerada
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Ring capacity must be greater than 0");
+
+        let layout = Layout::from_size_align(capacity, BASE_ALIGN)
+            .expect("Failed to create layout for ring");
+
+        let buffer = unsafe {
+            let ptr = alloc_zeroed(layout);
+            if ptr.is_null() {
+                panic!("Failed to allocate ring memory");
+            }
+            NonNull::new_unchecked(ptr)
+        };
+
+        Self {
+            buffer,
+            capacity,
+            layout,
+            state: Mutex::new(RingState {
+                head: 0,
+                tail: 0,
+                frames: VecDeque::new(),
+                total_allocations: 0,
+                released_allocations: 0,
+                frame_allocations: VecDeque::from([0]),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, RingState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Aloca memória no ring
+    ///
+    /// Retorna `None` se o espaço livre (contíguo) não for suficiente; libere
+    /// quadros com `release_frame`.
+    pub fn alloc(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
+        assert!(
+            align.is_power_of_two() && align <= BASE_ALIGN,
+            "Ring alignment must be a power of two up to 256"
+        );
+        let mut state = self.state();
+        let head = state.head;
+        let offset = head % self.capacity;
+        let aligned = align_up(offset, align);
+
+        // Não cabe no fim: recomeça do início do buffer
+        let (start, physical) = if aligned.checked_add(size)? > self.capacity {
+            (head + (self.capacity - offset), 0)
+        } else {
+            (head + (aligned - offset), aligned)
+        };
+        let end = start.checked_add(size)?;
+        if end - state.tail > self.capacity {
+            return None;
+        }
+
+        state.head = end;
+        state.total_allocations += 1;
+        if let Some(count) = state.frame_allocations.back_mut() {
+            *count += 1;
+        }
+        unsafe { Some(NonNull::new_unchecked(self.buffer.as_ptr().add(physical))) }
+    }
+
+    /// Aloca memória para um tipo específico
+    pub fn alloc_type<T>(&self) -> Option<NonNull<T>> {
+        let layout = Layout::new::<T>();
+        self.alloc(layout.size(), layout.align())
+            .map(|ptr| ptr.cast::<T>())
+    }
+
+    /// Marca o fim das alocações do quadro atual
+    pub fn end_frame(&self) {
+        let mut state = self.state();
+        let head = state.head;
+        state.frames.push_back(head);
+        state.frame_allocations.push_back(0);
+    }
+
+    /// Libera as alocações do quadro mais antigo marcado com `end_frame`
+    ///
+    /// Retorna `false` se não há quadro terminado para liberar.
+    pub fn release_frame(&self) -> bool {
+        let mut state = self.state();
+        let Some(end) = state.frames.pop_front() else {
+            return false;
+        };
+        state.tail = end;
+        let released = state.frame_allocations.pop_front().unwrap_or(0);
+        state.released_allocations += released;
+        true
+    }
+
+    /// Número de quadros terminados ainda não liberados
+    pub fn frames_in_flight(&self) -> usize {
+        self.state().frames.len()
+    }
+
+    /// Offset de um ponteiro retornado por `alloc` em relação ao início do
+    /// buffer
+    pub fn offset_of(&self, ptr: NonNull<u8>) -> usize {
+        let offset = (ptr.as_ptr() as usize).wrapping_sub(self.buffer.as_ptr() as usize);
+        assert!(offset < self.capacity, "Pointer does not belong to this ring");
+        offset
+    }
+
+    /// Início do buffer
+    pub fn as_ptr(&self) -> NonNull<u8> {
+        self.buffer
+    }
+
+    /// Libera tudo, inclusive os quadros vivos, e recomeça do início do
+    /// buffer
+    pub fn reset(&self) {
+        let mut state = self.state();
+        let start = state.head.next_multiple_of(self.capacity);
+        state.head = start;
+        state.tail = start;
+        state.frames.clear();
+        let live: usize = state.frame_allocations.iter().sum();
+        state.frame_allocations.clear();
+        state.frame_allocations.push_back(0);
+        state.released_allocations += live;
+    }
+
+    /// Retorna a quantidade de memória em uso (em bytes), incluindo o
+    /// espaço pulado ao dar a volta
+    pub fn used(&self) -> usize {
+        let state = self.state();
+        state.head - state.tail
+    }
+
+    /// Retorna a capacidade total do ring (em bytes)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Retorna a quantidade de memória disponível (em bytes)
+    pub fn available(&self) -> usize {
+        self.capacity - self.used()
+    }
+
+    /// Retorna o número total de alocações feitas desde a criação
+    pub fn total_allocations(&self) -> usize {
+        self.state().total_allocations
+    }
+
+    /// Retorna o número de alocações ainda vivas
+    pub fn live_allocations(&self) -> usize {
+        let state = self.state();
+        state.total_allocations - state.released_allocations
+    }
+}
+
+impl Drop for RingAllocator {
+    fn drop(&mut self) {
+        unsafe {
+            dealloc(self.buffer.as_ptr(), self.layout);
+        }
+    }
+}
+
+// O buffer só é reservado com o `Mutex` do estado adquirido
+unsafe impl Send for RingAllocator {}
+unsafe impl Sync for RingAllocator {}
+
+impl KernelAlloc for RingAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if layout.align() > BASE_ALIGN {
+            return None;
+        }
+        self.alloc(layout.size(), layout.align())
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Memória volta quadro a quadro em release_frame/reset
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        false
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        // Um único lock, para que os contadores sejam consistentes entre si
+        let state = self.state();
+        let used = state.head - state.tail;
+        AllocatorInfo {
+            allocator_type: AllocatorType::Ring,
+            total_capacity: self.capacity,
+            used,
+            available: self.capacity - used,
+            allocation_count: state.total_allocations,
+            deallocation_count: state.released_allocations,
+        }
+    }
+}
+
+/// Alinha um valor para cima ao múltiplo mais próximo de align
+#[inline]
+fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_alloc_aligned() {
+        let ring = RingAllocator::new(1024);
+
+        let a = ring.alloc(10, 4).unwrap();
+        let b = ring.alloc(10, 256).unwrap();
+        assert_eq!(ring.offset_of(a), 0);
+        assert_eq!(ring.offset_of(b), 256);
+        assert_eq!(b.as_ptr() as usize % 256, 0);
+        assert_eq!(ring.used(), 266);
+        assert_eq!(ring.live_allocations(), 2);
+    }
+
+    #[test]
+    fn test_ring_frames_wrap() {
+        let ring = RingAllocator::new(1024);
+
+        // Quadro 0: [0, 400), quadro 1: [512, 912)
+        ring.alloc(400, 256).unwrap();
+        ring.end_frame();
+        ring.alloc(400, 256).unwrap();
+        ring.end_frame();
+        assert_eq!(ring.frames_in_flight(), 2);
+
+        // Não cabe no fim nem no início, ocupado pelo quadro 0
+        assert!(ring.alloc(300, 256).is_none());
+
+        assert!(ring.release_frame());
+        let c = ring.alloc(300, 256).unwrap();
+        assert_eq!(ring.offset_of(c), 0);
+        assert_eq!(ring.live_allocations(), 2);
+
+        assert!(ring.release_frame());
+        assert!(!ring.release_frame());
+        ring.reset();
+        assert_eq!(ring.used(), 0);
+        assert_eq!(ring.live_allocations(), 0);
+        let d = ring.alloc(1024, 256).unwrap();
+        assert_eq!(ring.offset_of(d), 0);
+    }
+
+    #[test]
+    fn test_ring_allocator_info() {
+        let ring = RingAllocator::new(512);
+        ring.alloc(64, 16).unwrap();
+        ring.end_frame();
+        ring.release_frame();
+
+        let info = ring.allocator_info();
+        assert_eq!(info.allocator_type, AllocatorType::Ring);
+        assert_eq!(info.total_capacity, 512);
+        assert_eq!(info.used, 0);
+        assert_eq!(info.allocation_count, 1);
+        assert_eq!(info.deallocation_count, 1);
+        assert!(!ring.supports_deallocate());
+    }
+
+    #[test]
+    fn test_ring_shared_between_threads() {
+        let ring = std::sync::Arc::new(RingAllocator::new(64 * 1024));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let ring = ring.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        ring.alloc(16, 16).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(ring.live_allocations(), 400);
+        assert_eq!(ring.used(), 400 * 16);
+    }
+}
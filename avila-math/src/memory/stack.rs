@@ -1,6 +1,9 @@
 use std::alloc::{alloc, dealloc, Layout};
 use std::ptr::NonNull;
-use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+
+use super::allocator::KernelAlloc;
+use super::manager::{AllocatorInfo, AllocatorType};
 
 /// Stack Allocator - aloca memória em estilo LIFO (Last In First Out)
 /// Ideal para alocações hierárquicas onde a ordem de liberação é previsível
@@ -10,12 +13,22 @@ use std::cell::Cell;
 /// - Liberação O(1) apenas na ordem correta (LIFO)
 /// - Excelente localidade de cache
 /// - Perfeito para call stacks, processamento hierárquico, etc.
+///
+/// O offset e os contadores ficam atrás de um `Mutex`, então a stack pode ser
+/// compartilhada entre threads (ex: o `Arc` de `MemoryManager::create_stack`).
 pub struct StackAllocator {
     buffer: NonNull<u8>,
     capacity: usize,
-    offset: Cell<usize>,
     layout: Layout,
-    markers: Cell<Vec<StackMarker>>,
+    state: Mutex<StackState>,
+}
+
+/// Offset e contadores de um `StackAllocator`
+struct StackState {
+    offset: usize,
+    markers: Vec<StackMarker>,
+    total_allocations: usize,
+    live_allocations: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,28 +55,43 @@ impl StackAllocator {
         Self {
             buffer,
             capacity,
-            offset: Cell::new(0),
             layout,
-            markers: Cell::new(Vec::new()),
+            state: Mutex::new(StackState {
+                offset: 0,
+                markers: Vec::new(),
+                total_allocations: 0,
+                live_allocations: 0,
+            }),
         }
     }
 
+    fn state(&self) -> MutexGuard<'_, StackState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Cria um stack com capacidade padrão de 512KB
     pub fn with_default_capacity() -> Self {
         Self::new(512 * 1024) // 512KB
     }
 
     /// Aloca memória na stack
+    ///
+    /// O header fica logo antes dos dados, e os dados são alinhados pelo
+    /// endereço real, então alinhamentos maiores que o do buffer (16) também
+    /// são respeitados.
     pub fn alloc(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
-        let current_offset = self.offset.get();
-
-        // Calcula o offset alinhado
-        let aligned_offset = align_up(current_offset, align);
+        let mut state = self.state();
+        let current_offset = state.offset;
+        let base = self.buffer.as_ptr() as usize;
 
-        // Adiciona header para guardar informações da alocação
+        // Header para guardar informações da alocação
         let header_size = std::mem::size_of::<AllocationHeader>();
-        let header_offset = aligned_offset;
-        let data_offset = header_offset + header_size;
+        let align = align.max(std::mem::align_of::<AllocationHeader>());
+
+        // Alinha o endereço dos dados, deixando espaço para o header antes
+        let data_addr = align_up(base + current_offset.checked_add(header_size)?, align);
+        let data_offset = data_addr - base;
+        let header_offset = data_offset - header_size;
 
         let new_offset = data_offset.checked_add(size)?;
 
@@ -80,7 +108,9 @@ impl StackAllocator {
             });
         }
 
-        self.offset.set(new_offset);
+        state.offset = new_offset;
+        state.total_allocations += 1;
+        state.live_allocations += 1;
 
         unsafe {
             let ptr = self.buffer.as_ptr().add(data_offset);
@@ -111,6 +141,12 @@ impl StackAllocator {
     /// # Safety
     /// Deve ser chamado na ordem inversa das alocações (LIFO)
     pub unsafe fn free(&self, ptr: NonNull<u8>) {
+        let mut state = self.state();
+        self.pop(&mut state, ptr);
+    }
+
+    /// Desempilha `ptr` com o lock já adquirido
+    unsafe fn pop(&self, state: &mut StackState, ptr: NonNull<u8>) {
         let data_offset = ptr.as_ptr() as usize - self.buffer.as_ptr() as usize;
         let header_size = std::mem::size_of::<AllocationHeader>();
         let header_offset = data_offset - header_size;
@@ -120,37 +156,66 @@ impl StackAllocator {
 
         // Verifica se é a alocação no topo da stack
         debug_assert!(
-            data_offset + header.size == self.offset.get(),
+            data_offset + header.size == state.offset,
             "Attempted to free allocation that is not at the top of the stack"
         );
 
-        self.offset.set(header.prev_offset);
+        state.offset = header.prev_offset;
+        state.live_allocations = state.live_allocations.saturating_sub(1);
+    }
+
+    /// Verifica se o ponteiro é a alocação no topo da stack
+    pub fn is_top(&self, ptr: NonNull<u8>) -> bool {
+        self.is_top_at(ptr, self.state().offset)
+    }
+
+    fn is_top_at(&self, ptr: NonNull<u8>, offset: usize) -> bool {
+        let base = self.buffer.as_ptr() as usize;
+        let addr = ptr.as_ptr() as usize;
+        let header_size = std::mem::size_of::<AllocationHeader>();
+
+        if addr < base + header_size || addr > base + offset {
+            return false;
+        }
+
+        let data_offset = addr - base;
+        unsafe {
+            let header_ptr =
+                self.buffer.as_ptr().add(data_offset - header_size) as *const AllocationHeader;
+            data_offset + header_ptr.read_unaligned().size == offset
+        }
     }
 
     /// Cria um marcador para a posição atual da stack
     pub fn mark(&self) -> StackMark {
+        let state = self.state();
         StackMark {
-            offset: self.offset.get(),
+            offset: state.offset,
+            live_allocations: state.live_allocations,
         }
     }
 
     /// Libera tudo até o marcador especificado
     pub fn free_to_mark(&self, mark: StackMark) {
+        let mut state = self.state();
         assert!(
-            mark.offset <= self.offset.get(),
+            mark.offset <= state.offset,
             "Cannot free to a mark beyond current offset"
         );
-        self.offset.set(mark.offset);
+        state.offset = mark.offset;
+        state.live_allocations = mark.live_allocations;
     }
 
     /// Limpa toda a stack
     pub fn clear(&self) {
-        self.offset.set(0);
+        let mut state = self.state();
+        state.offset = 0;
+        state.live_allocations = 0;
     }
 
     /// Retorna a quantidade de memória usada (em bytes)
     pub fn used(&self) -> usize {
-        self.offset.get()
+        self.state().offset
     }
 
     /// Retorna a capacidade total da stack (em bytes)
@@ -167,6 +232,16 @@ impl StackAllocator {
     pub fn utilization(&self) -> f32 {
         (self.used() as f32 / self.capacity as f32) * 100.0
     }
+
+    /// Retorna o número total de alocações feitas desde a criação
+    pub fn total_allocations(&self) -> usize {
+        self.state().total_allocations
+    }
+
+    /// Retorna o número de alocações ainda vivas
+    pub fn live_allocations(&self) -> usize {
+        self.state().live_allocations
+    }
 }
 
 impl Drop for StackAllocator {
@@ -177,9 +252,46 @@ impl Drop for StackAllocator {
     }
 }
 
+// O buffer só é acessado com o `Mutex` do estado adquirido
 unsafe impl Send for StackAllocator {}
 unsafe impl Sync for StackAllocator {}
 
+impl KernelAlloc for StackAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.alloc(layout.size(), layout.align())
+    }
+
+    /// Libera o bloco se ele estiver no topo da stack
+    ///
+    /// Liberações fora da ordem LIFO (ex: o bloco antigo num realloc) são
+    /// ignoradas: a memória continua ocupada até o `free_to_mark`/`clear`
+    /// que cobre o bloco.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        // Verifica e desempilha sob o mesmo lock
+        let mut state = self.state();
+        if self.is_top_at(ptr, state.offset) {
+            self.pop(&mut state, ptr);
+        }
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        true
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        // Um único lock, para que os contadores sejam consistentes entre si
+        let state = self.state();
+        AllocatorInfo {
+            allocator_type: AllocatorType::Stack,
+            total_capacity: self.capacity,
+            used: state.offset,
+            available: self.capacity - state.offset,
+            allocation_count: state.total_allocations,
+            deallocation_count: state.total_allocations - state.live_allocations,
+        }
+    }
+}
+
 /// Header armazenado antes de cada alocação
 #[repr(C)]
 struct AllocationHeader {
@@ -191,6 +303,7 @@ struct AllocationHeader {
 #[derive(Debug, Clone, Copy)]
 pub struct StackMark {
     offset: usize,
+    live_allocations: usize,
 }
 
 /// Stack com escopo automático - libera ao sair do escopo
@@ -232,12 +345,23 @@ fn align_up(value: usize, align: usize) -> usize {
 
 /// Double-ended stack - cresce dos dois lados
 /// Útil para separar alocações temporárias de diferentes tipos
+///
+/// Os offsets ficam atrás de um `Mutex`, então a stack pode ser compartilhada
+/// entre threads (ex: o `Arc` de `MemoryManager::create_double_ended_stack`).
 pub struct DoubleEndedStack {
     buffer: NonNull<u8>,
     capacity: usize,
-    bottom_offset: Cell<usize>,
-    top_offset: Cell<usize>,
     layout: Layout,
+    state: Mutex<DoubleEndedState>,
+}
+
+/// Offsets e contadores de uma `DoubleEndedStack`
+struct DoubleEndedState {
+    bottom_offset: usize,
+    top_offset: usize,
+    total_allocations: usize,
+    live_bottom: usize,
+    live_top: usize,
 }
 
 impl DoubleEndedStack {
@@ -258,23 +382,35 @@ impl DoubleEndedStack {
         Self {
             buffer,
             capacity,
-            bottom_offset: Cell::new(0),
-            top_offset: Cell::new(capacity),
             layout,
+            state: Mutex::new(DoubleEndedState {
+                bottom_offset: 0,
+                top_offset: capacity,
+                total_allocations: 0,
+                live_bottom: 0,
+                live_top: 0,
+            }),
         }
     }
 
+    fn state(&self) -> MutexGuard<'_, DoubleEndedState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Aloca do começo (bottom)
     pub fn alloc_bottom(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
-        let current = self.bottom_offset.get();
-        let aligned = align_up(current, align);
+        let mut state = self.state();
+        let base = self.buffer.as_ptr() as usize;
+        let aligned = align_up(base + state.bottom_offset, align) - base;
         let new_offset = aligned.checked_add(size)?;
 
-        if new_offset >= self.top_offset.get() {
+        if new_offset >= state.top_offset {
             return None; // Colidiu com o topo
         }
 
-        self.bottom_offset.set(new_offset);
+        state.bottom_offset = new_offset;
+        state.total_allocations += 1;
+        state.live_bottom += 1;
 
         unsafe {
             let ptr = self.buffer.as_ptr().add(aligned);
@@ -284,15 +420,18 @@ impl DoubleEndedStack {
 
     /// Aloca do final (top)
     pub fn alloc_top(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
-        let current = self.top_offset.get();
-        let new_offset = current.checked_sub(size)?;
-        let aligned = new_offset & !(align - 1);
+        let mut state = self.state();
+        let new_offset = state.top_offset.checked_sub(size)?;
+        let base = self.buffer.as_ptr() as usize;
+        let aligned = ((base + new_offset) & !(align - 1)).checked_sub(base)?;
 
-        if aligned <= self.bottom_offset.get() {
+        if aligned <= state.bottom_offset {
             return None; // Colidiu com o bottom
         }
 
-        self.top_offset.set(aligned);
+        state.top_offset = aligned;
+        state.total_allocations += 1;
+        state.live_top += 1;
 
         unsafe {
             let ptr = self.buffer.as_ptr().add(aligned);
@@ -301,11 +440,15 @@ impl DoubleEndedStack {
     }
 
     pub fn clear_bottom(&self) {
-        self.bottom_offset.set(0);
+        let mut state = self.state();
+        state.bottom_offset = 0;
+        state.live_bottom = 0;
     }
 
     pub fn clear_top(&self) {
-        self.top_offset.set(self.capacity);
+        let mut state = self.state();
+        state.top_offset = self.capacity;
+        state.live_top = 0;
     }
 
     pub fn clear(&self) {
@@ -314,12 +457,54 @@ impl DoubleEndedStack {
     }
 
     pub fn used(&self) -> usize {
-        self.bottom_offset.get() + (self.capacity - self.top_offset.get())
+        let state = self.state();
+        state.bottom_offset + (self.capacity - state.top_offset)
     }
 
     pub fn available(&self) -> usize {
         self.capacity - self.used()
     }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn total_allocations(&self) -> usize {
+        self.state().total_allocations
+    }
+
+    pub fn live_allocations(&self) -> usize {
+        let state = self.state();
+        state.live_bottom + state.live_top
+    }
+}
+
+impl KernelAlloc for DoubleEndedStack {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.alloc_bottom(layout.size(), layout.align())
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Liberação apenas via clear_bottom/clear_top
+    }
+
+    fn supports_deallocate(&self) -> bool {
+        false
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        // Um único lock, para que os contadores sejam consistentes entre si
+        let state = self.state();
+        let used = state.bottom_offset + (self.capacity - state.top_offset);
+        AllocatorInfo {
+            allocator_type: AllocatorType::DoubleEndedStack,
+            total_capacity: self.capacity,
+            used,
+            available: self.capacity - used,
+            allocation_count: state.total_allocations,
+            deallocation_count: state.total_allocations - state.live_bottom - state.live_top,
+        }
+    }
 }
 
 impl Drop for DoubleEndedStack {
@@ -330,6 +515,10 @@ impl Drop for DoubleEndedStack {
     }
 }
 
+// O buffer só é acessado através dos offsets protegidos pelo `Mutex`
+unsafe impl Send for DoubleEndedStack {}
+unsafe impl Sync for DoubleEndedStack {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,6 +543,64 @@ mod tests {
         assert!(stack.used() > 0);
     }
 
+    #[test]
+    fn test_stack_over_aligned() {
+        let stack = StackAllocator::new(1024);
+
+        stack.alloc(1, 1);
+        let ptr32 = stack.alloc(8, 32).unwrap();
+        let ptr64 = stack.alloc(8, 64).unwrap();
+        assert_eq!(ptr32.as_ptr() as usize % 32, 0);
+        assert_eq!(ptr64.as_ptr() as usize % 64, 0);
+
+        // O header continua logo antes dos dados
+        assert!(stack.is_top(ptr64));
+        unsafe { stack.free(ptr64) };
+        assert!(stack.is_top(ptr32));
+        unsafe { stack.free(ptr32) };
+        assert_eq!(stack.live_allocations(), 1);
+    }
+
+    #[test]
+    fn test_double_ended_shared_between_threads() {
+        let stack = std::sync::Arc::new(DoubleEndedStack::new(64 * 1024));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let stack = stack.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        if i % 2 == 0 {
+                            stack.alloc_bottom(16, 8).unwrap();
+                        } else {
+                            stack.alloc_top(16, 8).unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(stack.live_allocations(), 400);
+        assert_eq!(stack.used(), 400 * 16);
+    }
+
+    #[test]
+    fn test_double_ended_over_aligned() {
+        let stack = DoubleEndedStack::new(1024);
+
+        for align in [32, 64] {
+            stack.alloc_bottom(1, 1);
+            let bottom = stack.alloc_bottom(8, align).unwrap();
+            stack.alloc_top(1, 1);
+            let top = stack.alloc_top(8, align).unwrap();
+            assert_eq!(bottom.as_ptr() as usize % align, 0);
+            assert_eq!(top.as_ptr() as usize % align, 0);
+        }
+    }
+
     #[test]
     fn test_stack_mark() {
         let stack = StackAllocator::new(1024);
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
@@ -206,12 +207,135 @@ impl Default for Stopwatch {
     }
 }
 
+/// Janela padrão de frames usada em `FrameStats`
+pub const DEFAULT_STATS_WINDOW: usize = 1000;
+
+/// Estatísticas de tempo de frame sobre a janela recente (para HUDs)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// Frames na janela
+    pub frames: usize,
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub average_fps: f64,
+    /// FPS médio do 1% de frames mais lentos
+    pub low_1_percent_fps: f64,
+    /// FPS médio do 0.1% de frames mais lentos
+    pub low_0_1_percent_fps: f64,
+    /// Frames acima do limite de spike na janela
+    pub spikes: usize,
+}
+
+/// Histórico de tempos de frame compartilhado por `DeltaTime` e `FpsCounter`
+struct FrameHistory {
+    /// (tempo do frame, foi spike)
+    frames: VecDeque<(Duration, bool)>,
+    capacity: usize,
+    sum: Duration,
+    spikes: usize,
+    spike_threshold: Option<Duration>,
+    on_spike: Option<Box<dyn FnMut(Duration) + Send>>,
+}
+
+impl FrameHistory {
+    fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            capacity: DEFAULT_STATS_WINDOW,
+            sum: Duration::ZERO,
+            spikes: 0,
+            spike_threshold: None,
+            on_spike: None,
+        }
+    }
+
+    /// Limite explícito ou, sem ele, o dobro da média (após aquecer a janela)
+    fn threshold(&self) -> Option<Duration> {
+        self.spike_threshold
+            .or_else(|| (self.frames.len() >= 10).then(|| self.sum / self.frames.len() as u32 * 2))
+    }
+
+    fn push(&mut self, frame: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let spike = self.threshold().is_some_and(|limit| frame > limit);
+        if self.frames.len() == self.capacity {
+            self.pop_front();
+        }
+        self.frames.push_back((frame, spike));
+        self.sum += frame;
+        if spike {
+            self.spikes += 1;
+            if let Some(callback) = &mut self.on_spike {
+                callback(frame);
+            }
+        }
+    }
+
+    fn pop_front(&mut self) {
+        if let Some((frame, spike)) = self.frames.pop_front() {
+            self.sum -= frame;
+            self.spikes -= spike as usize;
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.frames.len() > capacity {
+            self.pop_front();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.frames.clear();
+        self.sum = Duration::ZERO;
+        self.spikes = 0;
+    }
+
+    fn stats(&self) -> FrameStats {
+        if self.frames.is_empty() {
+            return FrameStats::default();
+        }
+        let mut sorted: Vec<Duration> = self.frames.iter().map(|&(frame, _)| frame).collect();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+        let fps = |frame: Duration| {
+            if frame.is_zero() {
+                0.0
+            } else {
+                1.0 / frame.as_secs_f64()
+            }
+        };
+        // Média dos `fraction` frames mais lentos (no mínimo um)
+        let low = |fraction: f64| {
+            let count = ((sorted.len() as f64 * fraction).ceil() as usize).max(1);
+            fps(sorted[..count].iter().sum::<Duration>() / count as u32)
+        };
+
+        let average = self.sum / self.frames.len() as u32;
+        FrameStats {
+            frames: sorted.len(),
+            average,
+            min: sorted[sorted.len() - 1],
+            max: sorted[0],
+            average_fps: fps(average),
+            low_1_percent_fps: low(0.01),
+            low_0_1_percent_fps: low(0.001),
+            spikes: self.spikes,
+        }
+    }
+}
+
 /// FPS counter - contador de frames por segundo
 pub struct FpsCounter {
     frame_count: u64,
     last_update: Instant,
+    last_frame: Instant,
     current_fps: f64,
     update_interval: Duration,
+    history: FrameHistory,
 }
 
 impl FpsCounter {
@@ -222,17 +346,23 @@ impl FpsCounter {
 
     /// Cria com intervalo de atualização customizado
     pub fn with_interval(update_interval: Duration) -> Self {
+        let now = Instant::now();
         Self {
             frame_count: 0,
-            last_update: Instant::now(),
+            last_update: now,
+            last_frame: now,
             current_fps: 0.0,
             update_interval,
+            history: FrameHistory::new(),
         }
     }
 
     /// Registra um frame
     pub fn tick(&mut self) {
         self.frame_count += 1;
+        let now = Instant::now();
+        self.history.push(now.duration_since(self.last_frame));
+        self.last_frame = now;
 
         let elapsed = self.last_update.elapsed();
         if elapsed >= self.update_interval {
@@ -247,11 +377,37 @@ impl FpsCounter {
         self.current_fps
     }
 
-    /// Reseta o contador
+    /// Reseta o contador e o histórico de frames
     pub fn reset(&mut self) {
         self.frame_count = 0;
         self.last_update = Instant::now();
+        self.last_frame = self.last_update;
         self.current_fps = 0.0;
+        self.history.clear();
+    }
+
+    /// Estatísticas da janela recente de frames
+    pub fn frame_stats(&self) -> FrameStats {
+        self.history.stats()
+    }
+
+    /// Tamanho da janela de estatísticas em frames (0 desliga o histórico)
+    pub fn set_stats_window(&mut self, frames: usize) {
+        self.history.set_capacity(frames);
+    }
+
+    /// Tempo de frame acima do qual o frame conta como spike; `None` usa o
+    /// dobro da média da janela
+    pub fn set_spike_threshold(&mut self, threshold: Option<Duration>) {
+        self.history.spike_threshold = threshold;
+    }
+
+    /// Chamado com o tempo do frame sempre que houver um spike
+    pub fn on_spike<F>(&mut self, callback: F)
+    where
+        F: FnMut(Duration) + Send + 'static,
+    {
+        self.history.on_spike = Some(Box::new(callback));
     }
 }
 
@@ -267,6 +423,7 @@ pub struct DeltaTime {
     delta: Duration,
     smoothing_factor: f32,
     smoothed_delta: f32,
+    history: FrameHistory,
 }
 
 impl DeltaTime {
@@ -277,6 +434,7 @@ impl DeltaTime {
             delta: Duration::ZERO,
             smoothing_factor: 0.1,
             smoothed_delta: 0.0,
+            history: FrameHistory::new(),
         }
     }
 
@@ -291,6 +449,7 @@ impl DeltaTime {
         self.smoothed_delta =
             self.smoothed_delta * (1.0 - self.smoothing_factor) + current * self.smoothing_factor;
 
+        self.history.push(self.delta);
         self.delta
     }
 
@@ -313,6 +472,30 @@ impl DeltaTime {
     pub fn set_smoothing(&mut self, factor: f32) {
         self.smoothing_factor = factor.clamp(0.0, 1.0);
     }
+
+    /// Estatísticas da janela recente de frames
+    pub fn frame_stats(&self) -> FrameStats {
+        self.history.stats()
+    }
+
+    /// Tamanho da janela de estatísticas em frames (0 desliga o histórico)
+    pub fn set_stats_window(&mut self, frames: usize) {
+        self.history.set_capacity(frames);
+    }
+
+    /// Tempo de frame acima do qual o frame conta como spike; `None` usa o
+    /// dobro da média da janela
+    pub fn set_spike_threshold(&mut self, threshold: Option<Duration>) {
+        self.history.spike_threshold = threshold;
+    }
+
+    /// Chamado com o tempo do frame sempre que houver um spike
+    pub fn on_spike<F>(&mut self, callback: F)
+    where
+        F: FnMut(Duration) + Send + 'static,
+    {
+        self.history.on_spike = Some(Box::new(callback));
+    }
 }
 
 impl Default for DeltaTime {
@@ -321,10 +504,310 @@ impl Default for DeltaTime {
     }
 }
 
+/// Relógio de jogo com pausa e escala de tempo
+///
+/// Mantém dois tempos: o real (sempre avança, para UI e rede) e o de jogo,
+/// que para quando pausado e é multiplicado por `time_scale` (câmera lenta,
+/// aceleração). Deltas reais muito longos (breakpoint, janela arrastada) são
+/// limitados por `max_delta` antes de virar tempo de jogo.
+///
+/// ```
+/// use avila_math::os::GameClock;
+/// use std::time::Duration;
+///
+/// let mut clock = GameClock::new();
+/// clock.set_time_scale(0.5);
+/// assert_eq!(clock.advance(Duration::from_millis(20)), Duration::from_millis(10));
+///
+/// clock.pause();
+/// assert_eq!(clock.advance(Duration::from_millis(20)), Duration::ZERO);
+/// assert_eq!(clock.game_time(), Duration::from_millis(10));
+/// assert_eq!(clock.real_delta(), Duration::from_millis(20));
+/// ```
+pub struct GameClock {
+    clock: Clock,
+    last_update: Duration,
+    real_delta: Duration,
+    game_delta: Duration,
+    game_time: Duration,
+    time_scale: f64,
+    paused: bool,
+    max_delta: Option<Duration>,
+    frame_count: u64,
+}
+
+impl GameClock {
+    /// Cria um relógio rodando, com escala 1.0 e `max_delta` de 250ms
+    pub fn new() -> Self {
+        Self {
+            clock: Clock::new(),
+            last_update: Duration::ZERO,
+            real_delta: Duration::ZERO,
+            game_delta: Duration::ZERO,
+            game_time: Duration::ZERO,
+            time_scale: 1.0,
+            paused: false,
+            max_delta: Some(Duration::from_millis(250)),
+            frame_count: 0,
+        }
+    }
+
+    /// Mede o tempo real desde o último update e retorna o delta de jogo
+    pub fn update(&mut self) -> Duration {
+        let now = self.clock.elapsed();
+        let real_delta = now - self.last_update;
+        self.last_update = now;
+        self.advance(real_delta)
+    }
+
+    /// Avança com um delta real informado (replays, testes) e retorna o
+    /// delta de jogo
+    pub fn advance(&mut self, real_delta: Duration) -> Duration {
+        self.real_delta = real_delta;
+        self.frame_count += 1;
+        self.game_delta = if self.paused {
+            Duration::ZERO
+        } else {
+            let clamped = match self.max_delta {
+                Some(max) => real_delta.min(max),
+                None => real_delta,
+            };
+            clamped.mul_f64(self.time_scale)
+        };
+        self.game_time += self.game_delta;
+        self.game_delta
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Alterna pausa; retorna se ficou pausado
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+        self.paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Multiplicador do tempo de jogo (0.25 = câmera lenta; negativos viram 0)
+    pub fn set_time_scale(&mut self, scale: f64) {
+        self.time_scale = scale.max(0.0);
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Limite do delta real por frame (`None` desliga)
+    pub fn set_max_delta(&mut self, max_delta: Option<Duration>) {
+        self.max_delta = max_delta;
+    }
+
+    /// Delta de jogo do último update
+    pub fn delta(&self) -> Duration {
+        self.game_delta
+    }
+
+    /// Delta de jogo do último update em segundos
+    pub fn delta_secs(&self) -> f32 {
+        self.game_delta.as_secs_f32()
+    }
+
+    /// Delta real do último update (ignora pausa e escala)
+    pub fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    /// Tempo de jogo acumulado
+    pub fn game_time(&self) -> Duration {
+        self.game_time
+    }
+
+    /// Tempo real desde a criação do relógio
+    pub fn real_time(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Número de updates
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Passo fixo para simulação (padrão acumulador)
+///
+/// A cada frame, `advance` recebe o delta real e diz quantos passos fixos
+/// rodar; `alpha` é a fração do próximo passo já acumulada, para interpolar
+/// o estado renderizado entre o passo anterior e o atual.
+///
+/// ```
+/// use avila_math::os::FixedTimestep;
+/// use std::time::Duration;
+///
+/// let mut fixed = FixedTimestep::from_hz(60.0);
+/// let steps = fixed.advance(Duration::from_millis(40));
+/// assert_eq!(steps, 2);
+/// for _ in 0..steps {
+///     // physics.step(fixed.step_secs());
+/// }
+/// let alpha = fixed.alpha();
+/// assert!(alpha > 0.0 && alpha < 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    max_steps: u32,
+    total_steps: u64,
+    dropped_time: Duration,
+}
+
+impl FixedTimestep {
+    /// Cria com o passo dado (ex: 1/60s); no máximo 8 passos por frame
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "fixed timestep must be non-zero");
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            max_steps: 8,
+            total_steps: 0,
+            dropped_time: Duration::ZERO,
+        }
+    }
+
+    /// Cria com uma frequência em Hz (ex: 60.0)
+    pub fn from_hz(hz: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    /// Limita os passos por frame; o tempo excedente é descartado para
+    /// evitar a "espiral da morte" quando a simulação não acompanha
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Acumula o delta do frame e retorna quantos passos fixos rodar
+    pub fn advance(&mut self, frame_delta: Duration) -> u32 {
+        self.accumulator += frame_delta;
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_steps {
+                // Descarta o atraso, mantendo só a fração do próximo passo
+                let remainder = Duration::from_nanos(
+                    (self.accumulator.as_nanos() % self.step.as_nanos()) as u64,
+                );
+                self.dropped_time += self.accumulator - remainder;
+                self.accumulator = remainder;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        self.total_steps += steps as u64;
+        steps
+    }
+
+    /// Roda `update(step)` para cada passo do frame e retorna `alpha`
+    pub fn run(&mut self, frame_delta: Duration, mut update: impl FnMut(Duration)) -> f32 {
+        for _ in 0..self.advance(frame_delta) {
+            update(self.step);
+        }
+        self.alpha()
+    }
+
+    /// Fração (0.0 a 1.0) do próximo passo já acumulada
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+
+    /// Duração de um passo
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Duração de um passo em segundos
+    pub fn step_secs(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// Altera o passo mantendo o tempo acumulado
+    pub fn set_step(&mut self, step: Duration) {
+        assert!(!step.is_zero(), "fixed timestep must be non-zero");
+        self.step = step;
+    }
+
+    /// Tempo acumulado ainda não simulado
+    pub fn accumulator(&self) -> Duration {
+        self.accumulator
+    }
+
+    /// Total de passos executados
+    pub fn total_steps(&self) -> u64 {
+        self.total_steps
+    }
+
+    /// Tempo descartado pelo limite de passos por frame
+    pub fn dropped_time(&self) -> Duration {
+        self.dropped_time
+    }
+
+    /// Zera o acumulador e os contadores
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+        self.total_steps = 0;
+        self.dropped_time = Duration::ZERO;
+    }
+}
+
+/// Eventos de trace guardados por padrão pelo `Profiler`
+pub const DEFAULT_TRACE_CAPACITY: usize = 100_000;
+
+/// Identificador numérico estável da thread atual (para traces)
+fn trace_thread_id() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: u64 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+/// Intervalo medido, com a thread em que ocorreu
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    pub name: String,
+    /// Id da thread (o mesmo para todos os eventos de uma thread)
+    pub thread_id: u64,
+    pub thread_name: Option<String>,
+    /// Início relativo à criação (ou último `clear`) do profiler
+    pub start: Duration,
+    pub duration: Duration,
+}
+
 /// Profiler simples para medir performance
+///
+/// Além das médias por nome, guarda os últimos eventos com thread e horário
+/// para exportar no formato trace-event (`export_chrome_trace`).
 pub struct Profiler {
     measurements: std::collections::HashMap<String, Vec<Duration>>,
     current: Option<(String, Instant)>,
+    origin: Instant,
+    events: std::collections::VecDeque<TraceEvent>,
+    trace_capacity: usize,
 }
 
 impl Profiler {
@@ -332,6 +815,18 @@ impl Profiler {
         Self {
             measurements: std::collections::HashMap::new(),
             current: None,
+            origin: Instant::now(),
+            events: std::collections::VecDeque::new(),
+            trace_capacity: DEFAULT_TRACE_CAPACITY,
+        }
+    }
+
+    /// Limita os eventos de trace guardados (os mais antigos são descartados);
+    /// 0 desliga a captura
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace_capacity = capacity;
+        while self.events.len() > capacity {
+            self.events.pop_front();
         }
     }
 
@@ -343,12 +838,96 @@ impl Profiler {
     /// Termina medição e registra
     pub fn end(&mut self) {
         if let Some((name, start)) = self.current.take() {
-            let duration = start.elapsed();
-            self.measurements
-                .entry(name)
-                .or_insert_with(Vec::new)
-                .push(duration);
+            self.record_span(name, start, start.elapsed());
+        }
+    }
+
+    /// Registra uma duração medida externamente (ex: por outra thread)
+    ///
+    /// O evento de trace é atribuído à thread que chama, terminando agora.
+    pub fn record(&mut self, name: impl Into<String>, duration: Duration) {
+        let start = Instant::now().checked_sub(duration).unwrap_or(self.origin);
+        self.record_span(name, start, duration);
+    }
+
+    /// Registra um intervalo com início conhecido
+    pub fn record_span(&mut self, name: impl Into<String>, start: Instant, duration: Duration) {
+        let name = name.into();
+        if self.trace_capacity > 0 {
+            if self.events.len() == self.trace_capacity {
+                self.events.pop_front();
+            }
+            let thread = std::thread::current();
+            self.events.push_back(TraceEvent {
+                name: name.clone(),
+                thread_id: trace_thread_id(),
+                thread_name: thread.name().map(str::to_string),
+                start: start.saturating_duration_since(self.origin),
+                duration,
+            });
         }
+        self.measurements.entry(name).or_default().push(duration);
+    }
+
+    /// Eventos de trace guardados, em ordem de registro
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+
+    /// Gera o JSON trace-event (chrome://tracing, Perfetto)
+    pub fn to_chrome_trace(&self) -> String {
+        fn escape(out: &mut String, text: &str) {
+            for c in text.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => out.push(c),
+                }
+            }
+        }
+
+        let pid = std::process::id();
+        let mut out = String::from("{\"traceEvents\":[\n");
+        let mut first = true;
+        let mut named_threads = std::collections::HashSet::new();
+        for event in &self.events {
+            if !first {
+                out.push_str(",\n");
+            }
+            first = false;
+
+            // Metadado com o nome da thread, uma vez por thread
+            if let Some(thread_name) = &event.thread_name {
+                if named_threads.insert(event.thread_id) {
+                    out.push_str(&format!(
+                        "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":{},\"args\":{{\"name\":\"",
+                        pid, event.thread_id
+                    ));
+                    escape(&mut out, thread_name);
+                    out.push_str("\"}},\n");
+                }
+            }
+
+            out.push_str("{\"name\":\"");
+            escape(&mut out, &event.name);
+            out.push_str(&format!(
+                "\",\"cat\":\"profiler\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":{},\"tid\":{}}}",
+                event.start.as_nanos() as f64 / 1000.0,
+                event.duration.as_nanos() as f64 / 1000.0,
+                pid,
+                event.thread_id
+            ));
+        }
+        out.push_str("\n],\"displayTimeUnit\":\"ms\"}\n");
+        out
+    }
+
+    /// Salva os eventos como JSON trace-event para abrir no chrome://tracing
+    /// ou no Perfetto
+    pub fn export_chrome_trace<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_chrome_trace())
     }
 
     /// Obtém média de uma medição
@@ -371,10 +950,12 @@ impl Profiler {
             .collect()
     }
 
-    /// Limpa todas as medições
+    /// Limpa todas as medições e eventos
     pub fn clear(&mut self) {
         self.measurements.clear();
         self.current = None;
+        self.events.clear();
+        self.origin = Instant::now();
     }
 }
 
@@ -384,6 +965,608 @@ impl Default for Profiler {
     }
 }
 
+// ============================================================================
+// Data e hora de calendário
+// ============================================================================
+
+/// Dia da semana
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    /// Abreviação em inglês ("Mon", "Tue", ...)
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Weekday::Monday => "Mon",
+            Weekday::Tuesday => "Tue",
+            Weekday::Wednesday => "Wed",
+            Weekday::Thursday => "Thu",
+            Weekday::Friday => "Fri",
+            Weekday::Saturday => "Sat",
+            Weekday::Sunday => "Sun",
+        }
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Dias desde 1970-01-01 para uma data do calendário gregoriano proléptico
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverso de `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Offset do fuso local (em segundos a leste de UTC) no instante dado
+#[cfg(unix)]
+fn local_offset_at(unix_secs: i64) -> i32 {
+    let time = unix_secs as libc::time_t;
+    // SAFETY: localtime_r escreve só em `tm`
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            0
+        } else {
+            tm.tm_gmtoff as i32
+        }
+    }
+}
+
+#[cfg(windows)]
+fn local_offset_at(unix_secs: i64) -> i32 {
+    use windows_sys::Win32::Foundation::SYSTEMTIME;
+    use windows_sys::Win32::System::Time::SystemTimeToTzSpecificLocalTime;
+
+    let utc = DateTime::from_unix(unix_secs, 0);
+    let st = SYSTEMTIME {
+        wYear: utc.year() as u16,
+        wMonth: utc.month() as u16,
+        wDayOfWeek: 0,
+        wDay: utc.day() as u16,
+        wHour: utc.hour() as u16,
+        wMinute: utc.minute() as u16,
+        wSecond: utc.second() as u16,
+        wMilliseconds: 0,
+    };
+    // SAFETY: SYSTEMTIME é POD; fuso nulo = fuso atual do sistema
+    unsafe {
+        let mut local: SYSTEMTIME = std::mem::zeroed();
+        if SystemTimeToTzSpecificLocalTime(std::ptr::null(), &st, &mut local) == 0 {
+            return 0;
+        }
+        let local_secs =
+            days_from_civil(local.wYear as i64, local.wMonth as u32, local.wDay as u32)
+                * SECS_PER_DAY
+                + local.wHour as i64 * 3600
+                + local.wMinute as i64 * 60
+                + local.wSecond as i64;
+        (local_secs - unix_secs) as i32
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn local_offset_at(_unix_secs: i64) -> i32 {
+    0
+}
+
+/// Erro ao interpretar uma data ISO-8601
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTimeParseError {
+    input: String,
+    reason: &'static str,
+}
+
+impl std::fmt::Display for DateTimeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid ISO-8601 date {:?}: {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for DateTimeParseError {}
+
+/// Instante no tempo com offset de fuso, decomposto em calendário gregoriano
+///
+/// Comparações e igualdade consideram só o instante: `12:00Z` e
+/// `09:00-03:00` são iguais.
+#[derive(Debug, Clone, Copy)]
+pub struct DateTime {
+    /// Segundos desde a época Unix (UTC)
+    secs: i64,
+    nanos: u32,
+    /// Segundos a leste de UTC
+    offset: i32,
+}
+
+impl DateTime {
+    /// Data/hora atual em UTC
+    pub fn now_utc() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    /// Data/hora atual no fuso local
+    pub fn now_local() -> Self {
+        Self::now_utc().to_local()
+    }
+
+    /// Cria a partir de segundos e nanossegundos Unix (UTC)
+    pub fn from_unix(secs: i64, nanos: u32) -> Self {
+        let secs = secs + (nanos / 1_000_000_000) as i64;
+        Self {
+            secs,
+            nanos: nanos % 1_000_000_000,
+            offset: 0,
+        }
+    }
+
+    /// Cria a partir de milissegundos Unix (UTC)
+    pub fn from_unix_millis(millis: i64) -> Self {
+        Self::from_unix(
+            millis.div_euclid(1000),
+            (millis.rem_euclid(1000) * 1_000_000) as u32,
+        )
+    }
+
+    /// Converte de `SystemTime` (UTC)
+    pub fn from_system_time(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => Self::from_unix(d.as_secs() as i64, d.subsec_nanos()),
+            Err(e) => {
+                let d = e.duration();
+                let mut secs = -(d.as_secs() as i64);
+                let mut nanos = d.subsec_nanos();
+                if nanos > 0 {
+                    secs -= 1;
+                    nanos = 1_000_000_000 - nanos;
+                }
+                Self::from_unix(secs, nanos)
+            }
+        }
+    }
+
+    /// Data/hora UTC a partir do calendário; `None` se algum campo for inválido
+    pub fn from_ymd_hms(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Option<Self> {
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year as i64, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+        let days = days_from_civil(year as i64, month, day);
+        let secs = days * SECS_PER_DAY + (hour * 3600 + minute * 60 + second) as i64;
+        Some(Self::from_unix(secs, 0))
+    }
+
+    /// Mesmo instante exibido com outro offset (segundos a leste de UTC)
+    pub fn with_offset(self, offset_secs: i32) -> Self {
+        Self {
+            offset: offset_secs,
+            ..self
+        }
+    }
+
+    /// Mesmo instante em UTC
+    pub fn to_utc(self) -> Self {
+        self.with_offset(0)
+    }
+
+    /// Mesmo instante no fuso local (considera horário de verão da data)
+    pub fn to_local(self) -> Self {
+        self.with_offset(local_offset_at(self.secs))
+    }
+
+    /// Offset em segundos a leste de UTC
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Converte para `SystemTime`
+    pub fn to_system_time(&self) -> SystemTime {
+        if self.secs >= 0 {
+            UNIX_EPOCH + Duration::new(self.secs as u64, self.nanos)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(self.secs.unsigned_abs())
+                + Duration::from_nanos(self.nanos as u64)
+        }
+    }
+
+    /// Segundos Unix
+    pub fn unix_timestamp(&self) -> i64 {
+        self.secs
+    }
+
+    /// Milissegundos Unix
+    pub fn unix_timestamp_millis(&self) -> i64 {
+        self.secs * 1000 + (self.nanos / 1_000_000) as i64
+    }
+
+    /// Segundos locais (já com o offset) desde a época
+    fn local_secs(&self) -> i64 {
+        self.secs + self.offset as i64
+    }
+
+    fn date(&self) -> (i64, u32, u32) {
+        civil_from_days(self.local_secs().div_euclid(SECS_PER_DAY))
+    }
+
+    fn secs_of_day(&self) -> u32 {
+        self.local_secs().rem_euclid(SECS_PER_DAY) as u32
+    }
+
+    pub fn year(&self) -> i32 {
+        self.date().0 as i32
+    }
+
+    /// Mês (1-12)
+    pub fn month(&self) -> u32 {
+        self.date().1
+    }
+
+    /// Dia do mês (1-31)
+    pub fn day(&self) -> u32 {
+        self.date().2
+    }
+
+    pub fn hour(&self) -> u32 {
+        self.secs_of_day() / 3600
+    }
+
+    pub fn minute(&self) -> u32 {
+        self.secs_of_day() / 60 % 60
+    }
+
+    pub fn second(&self) -> u32 {
+        self.secs_of_day() % 60
+    }
+
+    pub fn nanosecond(&self) -> u32 {
+        self.nanos
+    }
+
+    pub fn millisecond(&self) -> u32 {
+        self.nanos / 1_000_000
+    }
+
+    /// Dia do ano (1-366)
+    pub fn day_of_year(&self) -> u32 {
+        let (year, month, day) = self.date();
+        (days_from_civil(year, month, day) - days_from_civil(year, 1, 1)) as u32 + 1
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 foi uma quinta-feira
+        match (self.local_secs().div_euclid(SECS_PER_DAY) + 3).rem_euclid(7) {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+
+    /// Soma dias de calendário (pode ser negativo)
+    pub fn add_days(self, days: i64) -> Self {
+        Self {
+            secs: self.secs + days * SECS_PER_DAY,
+            ..self
+        }
+    }
+
+    /// Tempo desde `earlier`; `None` se `earlier` for posterior
+    pub fn duration_since(&self, earlier: &DateTime) -> Option<Duration> {
+        let nanos = self.total_nanos() - earlier.total_nanos();
+        (nanos >= 0).then(|| Duration::from_nanos(nanos as u64))
+    }
+
+    /// Diferença em segundos (negativa se `other` for posterior)
+    pub fn seconds_since(&self, other: &DateTime) -> f64 {
+        (self.total_nanos() - other.total_nanos()) as f64 / 1e9
+    }
+
+    fn total_nanos(&self) -> i128 {
+        self.secs as i128 * 1_000_000_000 + self.nanos as i128
+    }
+
+    fn from_total_nanos(nanos: i128, offset: i32) -> Self {
+        Self {
+            secs: nanos.div_euclid(1_000_000_000) as i64,
+            nanos: nanos.rem_euclid(1_000_000_000) as u32,
+            offset,
+        }
+    }
+
+    /// ISO-8601 / RFC 3339 (`2024-03-05T14:07:09.123Z`, `...-03:00`)
+    ///
+    /// A fração só aparece quando não é zero, com 3, 6 ou 9 dígitos (o
+    /// mínimo que representa os nanossegundos sem perda), então a saída volta
+    /// ao mesmo valor por `parse_iso8601`.
+    pub fn to_iso8601(&self) -> String {
+        let mut out = self.format("%Y-%m-%dT%H:%M:%S");
+        match self.nanos {
+            0 => {}
+            n if n.is_multiple_of(1_000_000) => out.push_str(&format!(".{:03}", n / 1_000_000)),
+            n if n.is_multiple_of(1_000) => out.push_str(&format!(".{:06}", n / 1_000)),
+            n => out.push_str(&format!(".{:09}", n)),
+        }
+        if self.offset == 0 {
+            out.push('Z');
+        } else {
+            out.push_str(&self.offset_string(true));
+        }
+        out
+    }
+
+    fn offset_string(&self, colon: bool) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let abs = self.offset.unsigned_abs();
+        let sep = if colon { ":" } else { "" };
+        format!("{}{:02}{}{:02}", sign, abs / 3600, sep, abs / 60 % 60)
+    }
+
+    /// Formata com um subconjunto do `strftime`
+    ///
+    /// `%Y` ano, `%m` mês, `%d` dia, `%H` hora, `%M` minuto, `%S` segundo,
+    /// `%f` milissegundos, `%j` dia do ano, `%a` dia da semana, `%b` mês
+    /// abreviado, `%z` offset (`+0200`), `%%` literal.
+    pub fn format(&self, pattern: &str) -> String {
+        let (year, month, day) = self.date();
+        let mut out = String::with_capacity(pattern.len() + 16);
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&format!("{:04}", year)),
+                Some('m') => out.push_str(&format!("{:02}", month)),
+                Some('d') => out.push_str(&format!("{:02}", day)),
+                Some('H') => out.push_str(&format!("{:02}", self.hour())),
+                Some('M') => out.push_str(&format!("{:02}", self.minute())),
+                Some('S') => out.push_str(&format!("{:02}", self.second())),
+                Some('f') => out.push_str(&format!("{:03}", self.millisecond())),
+                Some('j') => out.push_str(&format!("{:03}", self.day_of_year())),
+                Some('a') => out.push_str(self.weekday().short_name()),
+                Some('b') => out.push_str(MONTH_NAMES[month as usize - 1]),
+                Some('z') => out.push_str(&self.offset_string(false)),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+
+    /// Interpreta ISO-8601: `2024-03-05`, `2024-03-05T14:07:09`,
+    /// `2024-03-05 14:07:09.5+02:00`, `...Z`, `...-0300`
+    ///
+    /// Sem offset explícito, assume UTC.
+    pub fn parse_iso8601(input: &str) -> Result<Self, DateTimeParseError> {
+        let err = |reason| DateTimeParseError {
+            input: input.to_string(),
+            reason,
+        };
+        let s = input.trim();
+        let bytes = s.as_bytes();
+
+        let number = |range: std::ops::Range<usize>| -> Result<u32, DateTimeParseError> {
+            let part = s.get(range).ok_or_else(|| err("truncated"))?;
+            if !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(err("expected digits"));
+            }
+            part.parse().map_err(|_| err("expected digits"))
+        };
+
+        if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return Err(err("expected YYYY-MM-DD"));
+        }
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+
+        let (mut hour, mut minute, mut second, mut nanos) = (0, 0, 0, 0u32);
+        let mut rest = &s[10..];
+        if let Some(time) = rest.strip_prefix(['T', 't', ' ']) {
+            let tb = time.as_bytes();
+            if tb.len() < 5 || tb[2] != b':' {
+                return Err(err("expected HH:MM"));
+            }
+            let base = 11;
+            hour = number(base..base + 2)?;
+            minute = number(base + 3..base + 5)?;
+            let mut pos = 5;
+            if tb.get(5) == Some(&b':') {
+                second = number(base + 6..base + 8)?;
+                pos = 8;
+            }
+            if matches!(tb.get(pos), Some(b'.') | Some(b',')) {
+                let digits = tb[pos + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                if digits == 0 {
+                    return Err(err("empty fraction"));
+                }
+                let frac = &time[pos + 1..pos + 1 + digits];
+                // Só os nove primeiros dígitos cabem em nanossegundos
+                let mut padded: String = frac.chars().take(9).collect();
+                while padded.len() < 9 {
+                    padded.push('0');
+                }
+                nanos = padded.parse().map_err(|_| err("invalid fraction"))?;
+                pos += 1 + digits;
+            }
+            rest = &time[pos..];
+        } else if !rest.is_empty() {
+            return Err(err("expected 'T' after the date"));
+        }
+
+        let offset = match rest {
+            "" | "Z" | "z" => 0,
+            _ => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return Err(err("invalid UTC offset")),
+                };
+                let digits: String = rest[1..].chars().filter(|&c| c != ':').collect();
+                if digits.len() != 4 && digits.len() != 2 {
+                    return Err(err("invalid UTC offset"));
+                }
+                if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(err("invalid UTC offset"));
+                }
+                let hours: i32 = digits[..2].parse().unwrap();
+                let minutes: i32 = digits
+                    .get(2..)
+                    .filter(|m| !m.is_empty())
+                    .map_or(0, |m| m.parse().unwrap());
+                if hours > 23 || minutes > 59 {
+                    return Err(err("invalid UTC offset"));
+                }
+                sign * (hours * 3600 + minutes * 60)
+            }
+        };
+
+        let local = Self::from_ymd_hms(year as i32, month, day, hour, minute, second)
+            .ok_or_else(|| err("field out of range"))?;
+        Ok(Self {
+            secs: local.secs - offset as i64,
+            nanos,
+            offset,
+        })
+    }
+}
+
+impl PartialEq for DateTime {
+    fn eq(&self, other: &Self) -> bool {
+        (self.secs, self.nanos) == (other.secs, other.nanos)
+    }
+}
+
+impl Eq for DateTime {}
+
+impl std::hash::Hash for DateTime {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.secs, self.nanos).hash(state);
+    }
+}
+
+impl PartialOrd for DateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.secs, self.nanos).cmp(&(other.secs, other.nanos))
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_iso8601())
+    }
+}
+
+impl std::str::FromStr for DateTime {
+    type Err = DateTimeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_iso8601(s)
+    }
+}
+
+impl From<SystemTime> for DateTime {
+    fn from(time: SystemTime) -> Self {
+        Self::from_system_time(time)
+    }
+}
+
+impl std::ops::Add<Duration> for DateTime {
+    type Output = DateTime;
+
+    fn add(self, rhs: Duration) -> DateTime {
+        Self::from_total_nanos(self.total_nanos() + rhs.as_nanos() as i128, self.offset)
+    }
+}
+
+impl std::ops::Sub<Duration> for DateTime {
+    type Output = DateTime;
+
+    fn sub(self, rhs: Duration) -> DateTime {
+        Self::from_total_nanos(self.total_nanos() - rhs.as_nanos() as i128, self.offset)
+    }
+}
+
+impl std::ops::AddAssign<Duration> for DateTime {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign<Duration> for DateTime {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
 /// Helper para sleep
 pub fn sleep(duration: Duration) {
     thread::sleep(duration);
@@ -451,4 +1634,250 @@ mod tests {
         let delta = dt.update();
         assert!(delta.as_millis() >= 16);
     }
+
+    #[test]
+    fn test_frame_stats() {
+        let mut history = FrameHistory::new();
+        assert_eq!(history.stats(), FrameStats::default());
+
+        let spikes = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&spikes);
+        history.on_spike = Some(Box::new(move |frame| seen.lock().unwrap().push(frame)));
+
+        // 995 frames de 10ms e 5 travadas de 50ms
+        for i in 0..1000 {
+            let ms = if i % 200 == 199 { 50 } else { 10 };
+            history.push(Duration::from_millis(ms));
+        }
+        let stats = history.stats();
+        assert_eq!(stats.frames, 1000);
+        assert_eq!(stats.min, Duration::from_millis(10));
+        assert_eq!(stats.max, Duration::from_millis(50));
+        assert_eq!(stats.spikes, 5);
+        assert_eq!(spikes.lock().unwrap().len(), 5);
+        // 1% = 10 frames: 5 de 50ms + 5 de 10ms
+        assert!((stats.low_1_percent_fps - 1.0 / 0.030).abs() < 0.01);
+        assert!((stats.low_0_1_percent_fps - 20.0).abs() < 0.01);
+
+        // Janela menor descarta os frames mais antigos
+        history.set_capacity(100);
+        assert_eq!(history.stats().frames, 100);
+        assert_eq!(history.stats().spikes, 1);
+
+        history.spike_threshold = Some(Duration::from_millis(5));
+        history.push(Duration::from_millis(6));
+        assert_eq!(history.stats().spikes, 2);
+    }
+
+    #[test]
+    fn test_delta_time_stats() {
+        let mut dt = DeltaTime::new();
+        dt.set_spike_threshold(Some(Duration::ZERO));
+        let spikes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let seen = std::sync::Arc::clone(&spikes);
+        dt.on_spike(move |_| {
+            seen.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+        for _ in 0..3 {
+            sleep_ms(1);
+            dt.update();
+        }
+        let stats = dt.frame_stats();
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.spikes, 3);
+        assert!(stats.min >= Duration::from_millis(1));
+        assert_eq!(spikes.load(std::sync::atomic::Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_profiler_chrome_trace() {
+        let profiler = std::sync::Arc::new(std::sync::Mutex::new(Profiler::new()));
+        profiler.lock().unwrap().begin("frame");
+        sleep_ms(2);
+        profiler.lock().unwrap().end();
+
+        let shared = std::sync::Arc::clone(&profiler);
+        thread::Builder::new()
+            .name("loader \"io\"".into())
+            .spawn(move || {
+                shared
+                    .lock()
+                    .unwrap()
+                    .record("load", Duration::from_millis(1))
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let profiler = profiler.lock().unwrap();
+        let events: Vec<_> = profiler.events().collect();
+        assert_eq!(events.len(), 2);
+        assert_ne!(events[0].thread_id, events[1].thread_id);
+        assert!(events[0].duration >= Duration::from_millis(2));
+
+        let json = profiler.to_chrome_trace();
+        assert!(json.starts_with("{\"traceEvents\":["));
+        assert!(json.contains("\"name\":\"frame\",\"cat\":\"profiler\",\"ph\":\"X\""));
+        assert!(json.contains(&format!("\"tid\":{}", events[1].thread_id)));
+        assert!(json.contains("\"args\":{\"name\":\"loader \\\"io\\\"\"}"));
+
+        let path = std::env::temp_dir().join(format!("avila_trace_{}.json", std::process::id()));
+        profiler.export_chrome_trace(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), json);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_game_clock() {
+        let ms = Duration::from_millis;
+        let mut clock = GameClock::new();
+        assert_eq!(clock.advance(ms(16)), ms(16));
+
+        clock.set_time_scale(0.25);
+        assert_eq!(clock.advance(ms(16)), ms(4));
+        assert_eq!(clock.game_time(), ms(20));
+
+        assert!(clock.toggle_pause());
+        assert_eq!(clock.advance(ms(16)), Duration::ZERO);
+        assert_eq!(clock.delta_secs(), 0.0);
+        assert_eq!(clock.real_delta(), ms(16));
+        clock.resume();
+
+        // Travada longa é limitada antes da escala
+        clock.set_time_scale(1.0);
+        assert_eq!(clock.advance(Duration::from_secs(3)), ms(250));
+        clock.set_max_delta(None);
+        assert_eq!(
+            clock.advance(Duration::from_secs(3)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(clock.frame_count(), 5);
+
+        sleep_ms(5);
+        clock.update();
+        assert!(clock.real_delta() >= ms(5));
+        assert!(clock.real_time() >= ms(5));
+    }
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut fixed = FixedTimestep::new(Duration::from_millis(10));
+        assert_eq!(fixed.advance(Duration::from_millis(25)), 2);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(fixed.advance(Duration::from_millis(4)), 0);
+        assert_eq!(fixed.advance(Duration::from_millis(1)), 1);
+        assert_eq!(fixed.accumulator(), Duration::ZERO);
+
+        let mut calls = 0;
+        let alpha = fixed.run(Duration::from_millis(33), |dt| {
+            assert_eq!(dt, Duration::from_millis(10));
+            calls += 1;
+        });
+        assert_eq!(calls, 3);
+        assert!((alpha - 0.3).abs() < 1e-4);
+        assert_eq!(fixed.total_steps(), 6);
+
+        // Travada de 1s: limitado a 4 passos, o resto é descartado
+        let mut fixed = FixedTimestep::new(Duration::from_millis(10)).with_max_steps(4);
+        assert_eq!(fixed.advance(Duration::from_millis(1005)), 4);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(fixed.dropped_time(), Duration::from_millis(960));
+    }
+
+    #[test]
+    fn test_datetime_calendar() {
+        let epoch = DateTime::from_unix(0, 0);
+        assert_eq!((epoch.year(), epoch.month(), epoch.day()), (1970, 1, 1));
+        assert_eq!(epoch.weekday(), Weekday::Thursday);
+
+        // 2024-02-29T12:34:56.789Z
+        let leap = DateTime::from_unix_millis(1_709_210_096_789);
+        assert_eq!((leap.year(), leap.month(), leap.day()), (2024, 2, 29));
+        assert_eq!((leap.hour(), leap.minute(), leap.second()), (12, 34, 56));
+        assert_eq!(leap.millisecond(), 789);
+        assert_eq!(leap.day_of_year(), 60);
+        assert_eq!(leap.to_iso8601(), "2024-02-29T12:34:56.789Z");
+        assert_eq!(
+            DateTime::from_ymd_hms(2024, 2, 29, 12, 34, 56)
+                .unwrap()
+                .unix_timestamp(),
+            1_709_210_096
+        );
+        assert!(DateTime::from_ymd_hms(2023, 2, 29, 0, 0, 0).is_none());
+
+        let before = DateTime::from_ymd_hms(1969, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(before.unix_timestamp(), -1);
+        assert_eq!(DateTime::from_system_time(before.to_system_time()), before);
+    }
+
+    #[test]
+    fn test_datetime_offsets_and_format() {
+        let utc = DateTime::from_ymd_hms(2024, 1, 1, 1, 30, 0).unwrap();
+        let brt = utc.with_offset(-3 * 3600);
+        assert_eq!(brt.to_iso8601(), "2023-12-31T22:30:00-03:00");
+        assert_eq!(brt, utc);
+        assert_eq!(
+            brt.format("%a %d %b %Y %H:%M:%S.%f %z (%j) 100%%"),
+            "Sun 31 Dec 2023 22:30:00.000 -0300 (365) 100%"
+        );
+
+        let local = DateTime::now_local();
+        assert_eq!(local, local.to_utc());
+    }
+
+    #[test]
+    fn test_datetime_parse() {
+        let parsed: DateTime = "2024-03-05T14:07:09.123+02:00".parse().unwrap();
+        assert_eq!(parsed.offset(), 7200);
+        assert_eq!(parsed.to_utc().to_iso8601(), "2024-03-05T12:07:09.123Z");
+        assert_eq!(parsed.to_string(), "2024-03-05T14:07:09.123+02:00");
+
+        let date_only = DateTime::parse_iso8601("2024-03-05").unwrap();
+        assert_eq!(date_only.to_iso8601(), "2024-03-05T00:00:00Z");
+        let spaced = DateTime::parse_iso8601("2024-03-05 14:07-0330").unwrap();
+        assert_eq!(spaced.offset(), -(3 * 3600 + 30 * 60));
+
+        for bad in [
+            "",
+            "2024-3-5",
+            "2024-13-01",
+            "2024-03-05T25:00",
+            "2024-03-05X",
+            "2024-03-05T10:00+5",
+        ] {
+            assert!(DateTime::parse_iso8601(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_datetime_arithmetic() {
+        let start = DateTime::from_ymd_hms(2023, 12, 31, 23, 59, 30).unwrap();
+        let later = start + Duration::from_secs(45);
+        assert_eq!(later.to_iso8601(), "2024-01-01T00:00:15Z");
+        assert_eq!(later.duration_since(&start), Some(Duration::from_secs(45)));
+        assert_eq!(start.duration_since(&later), None);
+        assert_eq!(start.seconds_since(&later), -45.0);
+        assert_eq!(start.add_days(60).to_iso8601(), "2024-02-29T23:59:30Z");
+        assert_eq!(
+            (later - Duration::from_millis(15_500)).to_iso8601(),
+            "2023-12-31T23:59:59.500Z"
+        );
+        assert!(start < later);
+
+        // Frações abaixo do milissegundo não se perdem na volta pelo parse
+        for (nanos, text) in [
+            (1, "2023-12-31T23:59:30.000000001Z"),
+            (250_000, "2023-12-31T23:59:30.000250Z"),
+            (123_456_789, "2023-12-31T23:59:30.123456789Z"),
+        ] {
+            let precise = start + Duration::from_nanos(nanos);
+            assert_eq!(precise.to_iso8601(), text);
+            let parsed = DateTime::parse_iso8601(text).unwrap();
+            assert_eq!(parsed, precise);
+            assert_eq!(
+                parsed.duration_since(&start),
+                Some(Duration::from_nanos(nanos))
+            );
+        }
+    }
 }
@@ -0,0 +1,606 @@
+//! Compressão de dados
+//!
+//! O codec LZ4 é embutido (formato de bloco do LZ4, num frame próprio) e não
+//! tem dependências: é rápido o suficiente para pacotes de rede e assets que
+//! precisam descomprimir durante o carregamento. Deflate e gzip ficam atrás da
+//! feature `deflate`.
+//!
+//! ```
+//! use avila_math::os::compress::{self, Codec};
+//!
+//! let data = b"abcabcabcabcabcabcabcabcabcabcabcabc".repeat(10);
+//! let packed = compress::compress(&data, Codec::Lz4);
+//! assert!(packed.len() < data.len());
+//! assert_eq!(compress::decompress(&packed, Codec::Lz4).unwrap(), data);
+//! ```
+
+use std::io::{self, Read, Write};
+
+/// Algoritmo de compressão
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// LZ4 embutido, em blocos de 64 KiB
+    Lz4,
+    /// Deflate cru (RFC 1951)
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// Gzip (RFC 1952), compatível com `gzip`/`zcat`
+    #[cfg(feature = "deflate")]
+    Gzip,
+}
+
+/// Comprime um buffer inteiro
+pub fn compress(data: &[u8], codec: Codec) -> Vec<u8> {
+    let mut compressor = Compressor::new(Vec::new(), codec);
+    compressor
+        .write_all(data)
+        .and_then(|_| compressor.finish())
+        .expect("writing to a Vec cannot fail")
+}
+
+/// Descomprime um buffer inteiro
+pub fn decompress(data: &[u8], codec: Codec) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    Decompressor::new(data, codec).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+/// Writer que comprime o que recebe antes de repassar para `W`
+///
+/// `finish` precisa ser chamado para escrever o final do stream; o drop
+/// tenta finalizar, mas ignora erros.
+pub struct Compressor<W: Write> {
+    inner: CompressorInner<W>,
+}
+
+enum CompressorInner<W: Write> {
+    Lz4(Lz4Writer<W>),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::write::DeflateEncoder<W>),
+    #[cfg(feature = "deflate")]
+    Gzip(flate2::write::GzEncoder<W>),
+}
+
+impl<W: Write> Compressor<W> {
+    pub fn new(writer: W, codec: Codec) -> Self {
+        let inner = match codec {
+            Codec::Lz4 => CompressorInner::Lz4(Lz4Writer::new(writer)),
+            #[cfg(feature = "deflate")]
+            Codec::Deflate => CompressorInner::Deflate(flate2::write::DeflateEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "deflate")]
+            Codec::Gzip => CompressorInner::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+        };
+        Self { inner }
+    }
+
+    /// Escreve o final do stream e devolve o writer interno
+    pub fn finish(self) -> io::Result<W> {
+        match self.inner {
+            CompressorInner::Lz4(writer) => writer.finish(),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Deflate(writer) => writer.finish(),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Gzip(writer) => writer.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Compressor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            CompressorInner::Lz4(writer) => writer.write(buf),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Deflate(writer) => writer.write(buf),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Gzip(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            CompressorInner::Lz4(writer) => writer.flush(),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Deflate(writer) => writer.flush(),
+            #[cfg(feature = "deflate")]
+            CompressorInner::Gzip(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Reader que descomprime os dados lidos de `R`
+pub struct Decompressor<R: Read> {
+    inner: DecompressorInner<R>,
+}
+
+enum DecompressorInner<R: Read> {
+    Lz4(Lz4Reader<R>),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::read::DeflateDecoder<R>),
+    #[cfg(feature = "deflate")]
+    Gzip(flate2::read::GzDecoder<R>),
+}
+
+impl<R: Read> Decompressor<R> {
+    pub fn new(reader: R, codec: Codec) -> Self {
+        let inner = match codec {
+            Codec::Lz4 => DecompressorInner::Lz4(Lz4Reader::new(reader)),
+            #[cfg(feature = "deflate")]
+            Codec::Deflate => DecompressorInner::Deflate(flate2::read::DeflateDecoder::new(reader)),
+            #[cfg(feature = "deflate")]
+            Codec::Gzip => DecompressorInner::Gzip(flate2::read::GzDecoder::new(reader)),
+        };
+        Self { inner }
+    }
+}
+
+impl<R: Read> Read for Decompressor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            DecompressorInner::Lz4(reader) => reader.read(buf),
+            #[cfg(feature = "deflate")]
+            DecompressorInner::Deflate(reader) => reader.read(buf),
+            #[cfg(feature = "deflate")]
+            DecompressorInner::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Identifica streams do `Lz4Writer` ("AVZ4")
+const LZ4_MAGIC: [u8; 4] = *b"AVZ4";
+/// Tamanho máximo de um bloco descomprimido
+const BLOCK_SIZE: usize = 64 * 1024;
+/// Bit do header de bloco que indica dados sem compressão
+const STORED_FLAG: u32 = 1 << 31;
+
+const MIN_MATCH: usize = 4;
+/// Os últimos 5 bytes de um bloco são sempre literais
+const LAST_LITERALS: usize = 5;
+/// Um match precisa começar pelo menos 12 bytes antes do fim do bloco
+const MF_LIMIT: usize = 12;
+const HASH_LOG: u32 = 12;
+const MAX_OFFSET: usize = 65535;
+
+fn read_u32_le(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+fn write_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+    output.push(length as u8);
+}
+
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], offset: usize, match_len: usize) {
+    let match_code = match_len - MIN_MATCH;
+    let token = ((literals.len().min(15) as u8) << 4) | match_code.min(15) as u8;
+    output.push(token);
+    if literals.len() >= 15 {
+        write_length(output, literals.len() - 15);
+    }
+    output.extend_from_slice(literals);
+    output.extend_from_slice(&(offset as u16).to_le_bytes());
+    if match_code >= 15 {
+        write_length(output, match_code - 15);
+    }
+}
+
+/// Comprime um bloco no formato de bloco do LZ4
+///
+/// A saída é compatível com `LZ4_decompress_safe`; o tamanho original não é
+/// armazenado.
+pub fn lz4_compress_block(input: &[u8]) -> Vec<u8> {
+    let len = input.len();
+    let mut output = Vec::with_capacity(len / 2 + 16);
+    let mut anchor = 0;
+
+    if len > MF_LIMIT {
+        let mut table = vec![0u32; 1 << HASH_LOG];
+        let match_limit = len - MF_LIMIT;
+        let end_limit = len - LAST_LITERALS;
+        let mut pos = 0;
+
+        while pos < match_limit {
+            let sequence = read_u32_le(input, pos);
+            let slot = &mut table[hash(sequence)];
+            let candidate = *slot as usize;
+            *slot = pos as u32;
+
+            if candidate >= pos
+                || pos - candidate > MAX_OFFSET
+                || read_u32_le(input, candidate) != sequence
+            {
+                pos += 1;
+                continue;
+            }
+
+            let mut match_len = MIN_MATCH;
+            while pos + match_len < end_limit
+                && input[candidate + match_len] == input[pos + match_len]
+            {
+                match_len += 1;
+            }
+            // Estende para trás sobre literais ainda não emitidos
+            let (mut start, mut source) = (pos, candidate);
+            while start > anchor && source > 0 && input[start - 1] == input[source - 1] {
+                start -= 1;
+                source -= 1;
+                match_len += 1;
+            }
+
+            write_sequence(
+                &mut output,
+                &input[anchor..start],
+                start - source,
+                match_len,
+            );
+            pos = start + match_len;
+            anchor = pos;
+        }
+    }
+
+    let literals = &input[anchor..];
+    output.push((literals.len().min(15) as u8) << 4);
+    if literals.len() >= 15 {
+        write_length(&mut output, literals.len() - 15);
+    }
+    output.extend_from_slice(literals);
+    output
+}
+
+/// Descomprime um bloco LZ4, falhando se a saída passar de `max_output` bytes
+pub fn lz4_decompress_block(input: &[u8], max_output: usize) -> io::Result<Vec<u8>> {
+    fn corrupt(message: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("lz4: {}", message))
+    }
+
+    fn read_length(input: &[u8], pos: &mut usize, mut length: usize) -> io::Result<usize> {
+        loop {
+            let byte = *input.get(*pos).ok_or_else(|| corrupt("truncated length"))?;
+            *pos += 1;
+            length += byte as usize;
+            if byte != 255 {
+                return Ok(length);
+            }
+        }
+    }
+
+    let mut output = Vec::with_capacity(max_output.min(input.len() * 4));
+    let mut pos = 0;
+
+    loop {
+        let token = *input.get(pos).ok_or_else(|| corrupt("truncated block"))?;
+        pos += 1;
+
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+            literals = read_length(input, &mut pos, literals)?;
+        }
+        let literal_end = pos
+            .checked_add(literals)
+            .filter(|&end| end <= input.len())
+            .ok_or_else(|| corrupt("literals past end of block"))?;
+        if output.len() + literals > max_output {
+            return Err(corrupt("output exceeds limit"));
+        }
+        output.extend_from_slice(&input[pos..literal_end]);
+        pos = literal_end;
+
+        // A última sequência só tem literais
+        if pos == input.len() {
+            return Ok(output);
+        }
+
+        let offset = match input.get(pos..pos + 2) {
+            Some(bytes) => u16::from_le_bytes([bytes[0], bytes[1]]) as usize,
+            None => return Err(corrupt("truncated match offset")),
+        };
+        pos += 2;
+        if offset == 0 || offset > output.len() {
+            return Err(corrupt("match offset out of range"));
+        }
+
+        let mut match_len = (token & 0x0f) as usize;
+        if match_len == 15 {
+            match_len = read_length(input, &mut pos, match_len)?;
+        }
+        match_len += MIN_MATCH;
+        if output.len() + match_len > max_output {
+            return Err(corrupt("output exceeds limit"));
+        }
+
+        // Matches podem sobrepor a própria saída (ex: offset 1 repete um byte)
+        let start = output.len() - offset;
+        for i in 0..match_len {
+            output.push(output[start + i]);
+        }
+    }
+}
+
+/// Writer do stream LZ4 em blocos de 64 KiB
+///
+/// Formato: `"AVZ4"`, blocos com header `u32` little-endian (tamanho, com o
+/// bit 31 indicando bloco sem compressão) e um header zero no final.
+pub struct Lz4Writer<W: Write> {
+    inner: Option<W>,
+    buffer: Vec<u8>,
+    header_written: bool,
+}
+
+impl<W: Write> Lz4Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: Some(inner),
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            header_written: false,
+        }
+    }
+
+    /// Referência ao writer interno
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer already finished")
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer already finished");
+        if !self.header_written {
+            inner.write_all(&LZ4_MAGIC)?;
+            self.header_written = true;
+        }
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let compressed = lz4_compress_block(&self.buffer);
+        if compressed.len() < self.buffer.len() {
+            inner.write_all(&(compressed.len() as u32).to_le_bytes())?;
+            inner.write_all(&compressed)?;
+        } else {
+            inner.write_all(&(self.buffer.len() as u32 | STORED_FLAG).to_le_bytes())?;
+            inner.write_all(&self.buffer)?;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn finish_stream(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        let inner = self.inner.as_mut().expect("writer already finished");
+        inner.write_all(&0u32.to_le_bytes())?;
+        inner.flush()
+    }
+
+    /// Escreve o bloco pendente e o marcador de fim, devolvendo o writer interno
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finish_stream()?;
+        Ok(self.inner.take().expect("writer already finished"))
+    }
+}
+
+impl<W: Write> Write for Lz4Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = BLOCK_SIZE - self.buffer.len();
+        let n = buf.len().min(room);
+        self.buffer.extend_from_slice(&buf[..n]);
+        if self.buffer.len() == BLOCK_SIZE {
+            self.write_block()?;
+        }
+        Ok(n)
+    }
+
+    /// Fecha o bloco atual (menor que 64 KiB) e faz flush do writer interno
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.inner
+            .as_mut()
+            .expect("writer already finished")
+            .flush()
+    }
+}
+
+impl<W: Write> Drop for Lz4Writer<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.finish_stream();
+        }
+    }
+}
+
+/// Reader do stream produzido por `Lz4Writer`
+pub struct Lz4Reader<R: Read> {
+    inner: R,
+    block: Vec<u8>,
+    pos: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> Lz4Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            block: Vec::new(),
+            pos: 0,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Devolve o reader interno (posicionado após o fim do stream, se lido até o fim)
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn next_block(&mut self) -> io::Result<()> {
+        let truncated = |err: io::Error| {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "lz4: truncated stream")
+            } else {
+                err
+            }
+        };
+
+        if !self.started {
+            let mut magic = [0u8; 4];
+            self.inner.read_exact(&mut magic).map_err(truncated)?;
+            if magic != LZ4_MAGIC {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "lz4: not an AVZ4 stream",
+                ));
+            }
+            self.started = true;
+        }
+
+        let mut header = [0u8; 4];
+        self.inner.read_exact(&mut header).map_err(truncated)?;
+        let header = u32::from_le_bytes(header);
+        if header == 0 {
+            self.done = true;
+            return Ok(());
+        }
+
+        let size = (header & !STORED_FLAG) as usize;
+        if size > BLOCK_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "lz4: block larger than 64 KiB",
+            ));
+        }
+        let mut data = vec![0u8; size];
+        self.inner.read_exact(&mut data).map_err(truncated)?;
+
+        self.block = if header & STORED_FLAG != 0 {
+            data
+        } else {
+            lz4_decompress_block(&data, BLOCK_SIZE)?
+        };
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Lz4Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.block.len() {
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.next_block()?;
+        }
+
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dados com trechos repetidos e trechos pseudo-aleatórios
+    fn sample(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|i| {
+                if (i / 1000) % 2 == 0 {
+                    (i % 37) as u8
+                } else {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lz4_block_roundtrip() {
+        for data in [
+            Vec::new(),
+            b"a".to_vec(),
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_vec(),
+            sample(5000),
+            vec![7u8; 70_000],
+        ] {
+            let compressed = lz4_compress_block(&data);
+            let decompressed = lz4_decompress_block(&compressed, data.len()).unwrap();
+            assert_eq!(decompressed, data);
+        }
+
+        // Formato de referência: 1 literal "x" e um match com offset 1
+        let compressed = lz4_compress_block(&[b'x'; 32]);
+        assert_eq!(compressed[0] >> 4, 1);
+        assert_eq!(&compressed[2..4], &[1, 0]);
+    }
+
+    #[test]
+    fn test_lz4_stream_roundtrip() {
+        let data = sample(300_000);
+
+        let mut writer = Lz4Writer::new(Vec::new());
+        for chunk in data.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let compressed = writer.finish().unwrap();
+        assert!(compressed.len() < data.len());
+
+        let mut reader = Lz4Reader::new(compressed.as_slice());
+        let mut output = Vec::new();
+        let mut buf = [0u8; 1000];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(output, data);
+
+        assert_eq!(
+            decompress(&compress(&[], Codec::Lz4), Codec::Lz4).unwrap(),
+            b""
+        );
+    }
+
+    #[test]
+    fn test_lz4_rejects_corrupt_input() {
+        assert!(lz4_decompress_block(&[], 100).is_err());
+        // Offset aponta antes do início da saída
+        assert!(lz4_decompress_block(&[0x10, b'a', 5, 0], 100).is_err());
+        // Saída maior que o limite
+        let compressed = lz4_compress_block(&[0u8; 1000]);
+        assert!(lz4_decompress_block(&compressed, 999).is_err());
+
+        let packed = compress(&sample(10_000), Codec::Lz4);
+        let truncated = decompress(&packed[..packed.len() - 4], Codec::Lz4);
+        assert_eq!(truncated.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(decompress(b"nope", Codec::Lz4).is_err());
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn test_deflate_and_gzip_roundtrip() {
+        let data = sample(50_000);
+        for codec in [Codec::Deflate, Codec::Gzip] {
+            let packed = compress(&data, codec);
+            assert!(packed.len() < data.len());
+            assert_eq!(decompress(&packed, codec).unwrap(), data);
+        }
+        assert_eq!(&compress(b"x", Codec::Gzip)[..2], &[0x1f, 0x8b]);
+    }
+}
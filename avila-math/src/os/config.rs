@@ -0,0 +1,440 @@
+//! Configuração em camadas e arquivos `.env`
+//!
+//! `Config` junta valores de quatro origens, com precedência crescente:
+//! padrões do código < arquivo < variáveis de ambiente < linha de comando.
+//! Todas as chaves são normalizadas para minúsculas separadas por ponto
+//! (`render.width`), seja qual for a origem:
+//!
+//! | Origem   | Exemplo                          |
+//! |----------|----------------------------------|
+//! | Arquivo  | `[render]` + `width = 1280`      |
+//! | Ambiente | `GAME_RENDER__WIDTH=1280`        |
+//! | CLI      | `--render.width=1280`            |
+//!
+//! ```
+//! use avila_math::os::Config;
+//!
+//! let mut config = Config::new();
+//! config.set_default("render.width", 1280);
+//! config.set_default("render.vsync", true);
+//! config.load_str("[render]\nwidth = 1920\n");
+//! config.load_args(["--render.vsync=false"]);
+//!
+//! assert_eq!(config.get::<u32>("render.width").unwrap(), Some(1920));
+//! assert_eq!(config.get_bool("render.vsync").unwrap(), Some(false));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Origem de um valor, da menor para a maior precedência
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigLayer {
+    Defaults,
+    File,
+    Env,
+    Cli,
+}
+
+impl ConfigLayer {
+    const ALL: [ConfigLayer; 4] = [
+        ConfigLayer::Defaults,
+        ConfigLayer::File,
+        ConfigLayer::Env,
+        ConfigLayer::Cli,
+    ];
+}
+
+/// Valor presente mas que não pôde ser convertido para o tipo pedido
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub key: String,
+    pub value: String,
+    pub layer: ConfigLayer,
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "config key '{}' = '{}' ({:?}) is not a valid {}",
+            self.key, self.value, self.layer, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Configuração em camadas (padrões < arquivo < ambiente < CLI)
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    layers: [BTreeMap<String, String>; 4],
+}
+
+/// `Render.Width`, `RENDER__WIDTH` e `render-width` viram `render.width` /
+/// `render_width`
+fn normalize_key(key: &str) -> String {
+    key.trim()
+        .replace("__", ".")
+        .replace('-', "_")
+        .to_ascii_lowercase()
+}
+
+impl Config {
+    /// Cria uma configuração vazia
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn layer_mut(&mut self, layer: ConfigLayer) -> &mut BTreeMap<String, String> {
+        &mut self.layers[layer as usize]
+    }
+
+    /// Define um valor numa camada específica
+    pub fn set_in(&mut self, layer: ConfigLayer, key: &str, value: impl ToString) {
+        self.layer_mut(layer)
+            .insert(normalize_key(key), value.to_string());
+    }
+
+    /// Define o valor padrão de uma chave
+    pub fn set_default(&mut self, key: &str, value: impl ToString) {
+        self.set_in(ConfigLayer::Defaults, key, value);
+    }
+
+    /// Sobrescreve um valor com a maior precedência (ex: console de debug)
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        self.set_in(ConfigLayer::Cli, key, value);
+    }
+
+    /// Lê um arquivo `chave = valor` com seções `[secao]` (formato INI/.env)
+    ///
+    /// Linhas `export CHAVE=valor` e comentários `#`/`;` são aceitos. Um
+    /// arquivo ausente não é erro: retorna `Ok(false)`.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<bool> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                self.load_str(&content);
+                Ok(true)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Lê configuração em texto, na camada de arquivo
+    pub fn load_str(&mut self, content: &str) {
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('[') && line.ends_with(']') {
+                section = normalize_key(&line[1..line.len() - 1]);
+                continue;
+            }
+            if let Some((key, value)) = parse_line(line) {
+                let key = if section.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", section, key)
+                };
+                self.set_in(ConfigLayer::File, &key, value);
+            }
+        }
+    }
+
+    /// Carrega variáveis de ambiente que começam com `prefix`
+    ///
+    /// `GAME_RENDER__WIDTH` com prefixo `GAME_` vira `render.width`.
+    /// Retorna quantas variáveis foram lidas.
+    pub fn load_env(&mut self, prefix: &str) -> usize {
+        self.load_env_from(prefix, std::env::vars())
+    }
+
+    fn load_env_from(
+        &mut self,
+        prefix: &str,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> usize {
+        let mut count = 0;
+        for (name, value) in vars {
+            if let Some(key) = name.strip_prefix(prefix).filter(|key| !key.is_empty()) {
+                self.set_in(ConfigLayer::Env, key, value);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Carrega argumentos de linha de comando
+    ///
+    /// Aceita `--chave=valor`, `--chave valor`, `--flag` (vira `true`) e
+    /// `--no-flag` (vira `false`). Argumentos que não começam com `--` são
+    /// devolvidos, na ordem, para o chamador tratar.
+    pub fn load_args<I, S>(&mut self, args: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut positional = Vec::new();
+        let mut args = args.into_iter().map(Into::into).peekable();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--").filter(|o| !o.is_empty()) else {
+                positional.push(arg);
+                continue;
+            };
+            if let Some((key, value)) = option.split_once('=') {
+                self.set_in(ConfigLayer::Cli, key, value);
+            } else if let Some(value) = args.next_if(|next| !next.starts_with("--")) {
+                self.set_in(ConfigLayer::Cli, option, value);
+            } else if let Some(key) = option.strip_prefix("no-") {
+                self.set_in(ConfigLayer::Cli, key, false);
+            } else {
+                self.set_in(ConfigLayer::Cli, option, true);
+            }
+        }
+        positional
+    }
+
+    /// Carrega `std::env::args()`, ignorando o nome do executável
+    pub fn load_process_args(&mut self) -> Vec<String> {
+        self.load_args(std::env::args().skip(1))
+    }
+
+    fn lookup(&self, key: &str) -> Option<(&str, ConfigLayer)> {
+        let key = normalize_key(key);
+        ConfigLayer::ALL
+            .iter()
+            .rev()
+            .find_map(|&layer| Some((self.layers[layer as usize].get(&key)?.as_str(), layer)))
+    }
+
+    /// Valor bruto, da camada de maior precedência que o define
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.lookup(key).map(|(value, _)| value)
+    }
+
+    /// Camada de onde vem o valor efetivo de uma chave
+    pub fn source(&self, key: &str) -> Option<ConfigLayer> {
+        self.lookup(key).map(|(_, layer)| layer)
+    }
+
+    /// Verifica se alguma camada define a chave
+    pub fn contains(&self, key: &str) -> bool {
+        self.lookup(key).is_some()
+    }
+
+    /// Valor convertido com `FromStr`; `Ok(None)` se a chave não existe
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        let Some((value, layer)) = self.lookup(key) else {
+            return Ok(None);
+        };
+        value.trim().parse().map(Some).map_err(|_| ConfigError {
+            key: normalize_key(key),
+            value: value.to_string(),
+            layer,
+            expected: std::any::type_name::<T>(),
+        })
+    }
+
+    /// Valor convertido, ou `default` se ausente ou inválido
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.get(key).ok().flatten().unwrap_or(default)
+    }
+
+    /// Booleano aceitando `true/false`, `1/0`, `yes/no` e `on/off`
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, ConfigError> {
+        let Some((value, layer)) = self.lookup(key) else {
+            return Ok(None);
+        };
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Some(true)),
+            "false" | "0" | "no" | "off" => Ok(Some(false)),
+            _ => Err(ConfigError {
+                key: normalize_key(key),
+                value: value.to_string(),
+                layer,
+                expected: "bool",
+            }),
+        }
+    }
+
+    /// Lista separada por vírgulas (itens vazios são descartados)
+    pub fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        self.get_str(key).map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+    }
+
+    /// Todas as chaves definidas, em ordem alfabética
+    pub fn keys(&self) -> Vec<&str> {
+        let keys: BTreeSet<&str> = self
+            .layers
+            .iter()
+            .flat_map(|layer| layer.keys().map(String::as_str))
+            .collect();
+        keys.into_iter().collect()
+    }
+
+    /// Chaves e valores efetivos sob `prefix.` (sem o prefixo)
+    pub fn section(&self, prefix: &str) -> BTreeMap<String, String> {
+        let prefix = format!("{}.", normalize_key(prefix));
+        self.keys()
+            .into_iter()
+            .filter_map(|key| {
+                let short = key.strip_prefix(&prefix)?;
+                Some((short.to_string(), self.get_str(key)?.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Interpreta uma linha `chave = valor` / `export CHAVE="valor"`
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() {
+        return None;
+    }
+    Some((key.to_string(), parse_value(value.trim())))
+}
+
+fn parse_value(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"') {
+        // Aspas duplas: escapes \n \t \" \\ ; o resto da linha após a aspa é ignorado
+        let mut result = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => result.push('\n'),
+                    Some('t') => result.push('\t'),
+                    Some('r') => result.push('\r'),
+                    Some(other) => result.push(other),
+                    None => result.push('\\'),
+                },
+                c => result.push(c),
+            }
+        }
+        result
+    } else if let Some(inner) = value.strip_prefix('\'') {
+        // Aspas simples: literal
+        inner.split('\'').next().unwrap_or_default().to_string()
+    } else {
+        // Sem aspas: comentário só depois de espaço (`a#b` é um valor)
+        let end = value.find(" #").unwrap_or(value.len());
+        value[..end].trim_end().to_string()
+    }
+}
+
+/// Lê o conteúdo de um arquivo `.env` como pares (chave, valor), sem
+/// normalizar as chaves
+pub fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    content.lines().filter_map(parse_line).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = parse_dotenv(
+            "# comentário\n\
+             DATABASE_URL=postgres://localhost/db\n\
+             export API_KEY = 'abc#123'\n\
+             GREETING=\"hello\\nworld\" # fim\n\
+             COLOR=blue # inline\n\
+             HASH=a#b\n\
+             EMPTY=\n\
+             invalid line\n",
+        );
+        assert_eq!(
+            vars,
+            [
+                ("DATABASE_URL", "postgres://localhost/db"),
+                ("API_KEY", "abc#123"),
+                ("GREETING", "hello\nworld"),
+                ("COLOR", "blue"),
+                ("HASH", "a#b"),
+                ("EMPTY", ""),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_config_layers() {
+        let mut config = Config::new();
+        config.set_default("render.width", 800);
+        config.set_default("render.height", 600);
+        config.set_default("audio.volume", 0.5);
+        config.set_default("net.port", 7777);
+
+        config.load_str("fullscreen = yes\n[render]\nwidth = 1280\n\n[Audio]\nvolume = 0.8\n");
+        let env = [
+            ("GAME_RENDER__WIDTH", "1920"),
+            ("GAME_NET__PORT", "not-a-port"),
+            ("OTHER_VALUE", "x"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(config.load_env_from("GAME_", env.into_iter()), 2);
+        let rest = config.load_args([
+            "map.bsp",
+            "--audio.volume",
+            "0.2",
+            "--no-fullscreen",
+            "--verbose",
+        ]);
+        assert_eq!(rest, ["map.bsp"]);
+
+        assert_eq!(config.get::<u32>("render.width").unwrap(), Some(1920));
+        assert_eq!(config.source("render.width"), Some(ConfigLayer::Env));
+        assert_eq!(config.get::<u32>("RENDER.HEIGHT").unwrap(), Some(600));
+        assert_eq!(config.get::<f32>("audio.volume").unwrap(), Some(0.2));
+        assert_eq!(config.get_bool("fullscreen").unwrap(), Some(false));
+        assert_eq!(config.get_bool("verbose").unwrap(), Some(true));
+        assert_eq!(config.get::<u32>("missing").unwrap(), None);
+        assert_eq!(config.get_or("missing", 5u32), 5);
+
+        let error = config.get::<u16>("net.port").unwrap_err();
+        assert_eq!(error.layer, ConfigLayer::Env);
+        assert_eq!(config.get_or("net.port", 1u16), 1);
+
+        config.set("render.width", 640);
+        assert_eq!(config.get::<u32>("render.width").unwrap(), Some(640));
+        assert_eq!(
+            config.section("render").into_iter().collect::<Vec<_>>(),
+            [
+                ("height".to_string(), "600".to_string()),
+                ("width".to_string(), "640".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_list() {
+        let mut config = Config::new();
+        config.load_str("plugins = physics, audio,,net\n");
+        assert_eq!(
+            config.get_list("plugins").unwrap(),
+            ["physics", "audio", "net"]
+        );
+        assert!(config
+            .load_file("/nonexistent/avila.cfg")
+            .is_ok_and(|found| !found));
+    }
+}
@@ -0,0 +1,437 @@
+//! Recursos e topologia da CPU
+//!
+//! Detecta em tempo de execução as extensões SIMD disponíveis (SSE/AVX no
+//! x86, NEON no ARM), o número de núcleos físicos e lógicos e os tamanhos de
+//! cache, para escolher caminhos SIMD e dimensionar o sistema de jobs.
+//! As duas consultas são feitas uma vez e guardadas.
+//!
+//! ```
+//! use avila_math::os::SystemInfo;
+//!
+//! let features = SystemInfo::cpu_features();
+//! if features.avx2 && features.fma {
+//!     // caminho AVX2
+//! }
+//!
+//! let topology = SystemInfo::cpu_topology();
+//! assert!(topology.physical_cores <= topology.logical_cores);
+//! assert!(topology.cache_line_size.is_power_of_two());
+//! ```
+
+use std::sync::OnceLock;
+
+/// Extensões de instrução suportadas pela CPU e pelo sistema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuFeatures {
+    pub sse2: bool,
+    pub sse3: bool,
+    pub ssse3: bool,
+    pub sse41: bool,
+    pub sse42: bool,
+    pub popcnt: bool,
+    pub avx: bool,
+    pub avx2: bool,
+    pub fma: bool,
+    pub avx512f: bool,
+    pub neon: bool,
+}
+
+impl CpuFeatures {
+    /// Detecta os recursos da CPU atual (prefira `cpu_features`, que guarda o resultado)
+    pub fn detect() -> Self {
+        #[allow(unused_mut)]
+        let mut features = Self::default();
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            features.sse2 = is_x86_feature_detected!("sse2");
+            features.sse3 = is_x86_feature_detected!("sse3");
+            features.ssse3 = is_x86_feature_detected!("ssse3");
+            features.sse41 = is_x86_feature_detected!("sse4.1");
+            features.sse42 = is_x86_feature_detected!("sse4.2");
+            features.popcnt = is_x86_feature_detected!("popcnt");
+            features.avx = is_x86_feature_detected!("avx");
+            features.avx2 = is_x86_feature_detected!("avx2");
+            features.fma = is_x86_feature_detected!("fma");
+            features.avx512f = is_x86_feature_detected!("avx512f");
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            features.neon = std::arch::is_aarch64_feature_detected!("neon");
+        }
+
+        #[cfg(all(target_arch = "arm", target_feature = "neon"))]
+        {
+            features.neon = true;
+        }
+
+        features
+    }
+
+    /// Largura do maior registrador SIMD utilizável, em bytes (0 = nenhum)
+    pub fn simd_width(&self) -> usize {
+        if self.avx512f {
+            64
+        } else if self.avx {
+            32
+        } else if self.sse2 || self.neon {
+            16
+        } else {
+            0
+        }
+    }
+
+    /// Nomes dos recursos presentes (ex: para logs de diagnóstico)
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.sse2, "sse2"),
+            (self.sse3, "sse3"),
+            (self.ssse3, "ssse3"),
+            (self.sse41, "sse4.1"),
+            (self.sse42, "sse4.2"),
+            (self.popcnt, "popcnt"),
+            (self.avx, "avx"),
+            (self.avx2, "avx2"),
+            (self.fma, "fma"),
+            (self.avx512f, "avx512f"),
+            (self.neon, "neon"),
+        ]
+        .into_iter()
+        .filter_map(|(present, name)| present.then_some(name))
+        .collect()
+    }
+}
+
+/// Recursos da CPU, detectados na primeira chamada
+pub fn cpu_features() -> &'static CpuFeatures {
+    static FEATURES: OnceLock<CpuFeatures> = OnceLock::new();
+    FEATURES.get_or_init(CpuFeatures::detect)
+}
+
+/// Núcleos e caches da máquina
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuTopology {
+    /// Threads de hardware (inclui SMT/Hyper-Threading)
+    pub logical_cores: usize,
+    /// Núcleos físicos
+    pub physical_cores: usize,
+    /// Soquetes/pacotes
+    pub packages: usize,
+    /// Tamanho da linha de cache, em bytes
+    pub cache_line_size: usize,
+    /// Cache L1 de dados por núcleo, em bytes
+    pub l1_data_cache: Option<usize>,
+    pub l2_cache: Option<usize>,
+    pub l3_cache: Option<usize>,
+}
+
+impl CpuTopology {
+    /// Lê a topologia do sistema (prefira `cpu_topology`, que guarda o resultado)
+    pub fn detect() -> Self {
+        let logical = super::threading::num_cpus();
+        let mut topology = imp::detect().unwrap_or(Self {
+            logical_cores: logical,
+            physical_cores: logical,
+            packages: 1,
+            cache_line_size: DEFAULT_CACHE_LINE,
+            l1_data_cache: None,
+            l2_cache: None,
+            l3_cache: None,
+        });
+
+        // Valores inconsistentes (ex: sysfs parcial em containers) não devem
+        // quebrar quem dimensiona threads a partir daqui
+        topology.logical_cores = topology.logical_cores.max(1);
+        topology.physical_cores = topology.physical_cores.clamp(1, topology.logical_cores);
+        topology.packages = topology.packages.clamp(1, topology.physical_cores);
+        if !topology.cache_line_size.is_power_of_two() {
+            topology.cache_line_size = DEFAULT_CACHE_LINE;
+        }
+        topology
+    }
+
+    /// Há mais de uma thread de hardware por núcleo
+    pub fn has_smt(&self) -> bool {
+        self.logical_cores > self.physical_cores
+    }
+
+    /// Workers sugeridos para um pool de jobs: um por núcleo físico, deixando
+    /// um livre para a thread principal
+    pub fn recommended_worker_count(&self) -> usize {
+        self.physical_cores.saturating_sub(1).max(1)
+    }
+}
+
+/// Topologia da CPU, lida na primeira chamada
+pub fn cpu_topology() -> &'static CpuTopology {
+    static TOPOLOGY: OnceLock<CpuTopology> = OnceLock::new();
+    TOPOLOGY.get_or_init(CpuTopology::detect)
+}
+
+/// Apple Silicon usa linhas de 128 bytes; x86 e o resto do ARM, 64
+const DEFAULT_CACHE_LINE: usize = if cfg!(all(target_arch = "aarch64", target_vendor = "apple")) {
+    128
+} else {
+    64
+};
+
+// ============================================================================
+// Linux / Android: sysfs
+// ============================================================================
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use super::CpuTopology;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::Path;
+
+    fn read_trimmed(path: &Path) -> Option<String> {
+        fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    }
+
+    /// Tamanhos como "32K", "1024K" ou "8M"
+    pub fn parse_size(size: &str) -> Option<usize> {
+        let (digits, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            Some((i, _)) => (&size[..i], &size[i..]),
+            None => (size, ""),
+        };
+        let value: usize = digits.parse().ok()?;
+        let multiplier = match unit {
+            "" => 1,
+            "K" => 1024,
+            "M" => 1024 * 1024,
+            "G" => 1024 * 1024 * 1024,
+            _ => return None,
+        };
+        Some(value * multiplier)
+    }
+
+    pub fn detect() -> Option<CpuTopology> {
+        let cpu_dir = Path::new("/sys/devices/system/cpu");
+        let mut logical = 0;
+        let mut cores = HashSet::new();
+        let mut packages = HashSet::new();
+
+        for entry in fs::read_dir(cpu_dir).ok()?.flatten() {
+            let name = entry.file_name();
+            let Some(index) = name.to_str().and_then(|n| n.strip_prefix("cpu")) else {
+                continue;
+            };
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                continue;
+            }
+            let topology = entry.path().join("topology");
+            // CPUs offline não têm o diretório topology
+            let (Some(package), Some(core)) = (
+                read_trimmed(&topology.join("physical_package_id")),
+                read_trimmed(&topology.join("core_id")),
+            ) else {
+                continue;
+            };
+            logical += 1;
+            cores.insert((package.clone(), core));
+            packages.insert(package);
+        }
+        if logical == 0 {
+            return None;
+        }
+
+        let mut topology = CpuTopology {
+            logical_cores: logical,
+            physical_cores: cores.len(),
+            packages: packages.len(),
+            cache_line_size: super::DEFAULT_CACHE_LINE,
+            l1_data_cache: None,
+            l2_cache: None,
+            l3_cache: None,
+        };
+
+        let caches = cpu_dir.join("cpu0/cache");
+        for entry in fs::read_dir(caches).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let level = read_trimmed(&path.join("level"));
+            let kind = read_trimmed(&path.join("type")).unwrap_or_default();
+            let size = read_trimmed(&path.join("size")).and_then(|s| parse_size(&s));
+            match (level.as_deref(), kind.as_str()) {
+                (Some("1"), "Data" | "Unified") => {
+                    topology.l1_data_cache = size;
+                    if let Some(line) =
+                        read_trimmed(&path.join("coherency_line_size")).and_then(|s| s.parse().ok())
+                    {
+                        topology.cache_line_size = line;
+                    }
+                }
+                (Some("2"), _) => topology.l2_cache = size,
+                (Some("3"), _) => topology.l3_cache = size,
+                _ => {}
+            }
+        }
+        Some(topology)
+    }
+}
+
+// ============================================================================
+// macOS / iOS: sysctl hw.*
+// ============================================================================
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod imp {
+    use super::CpuTopology;
+    use std::ffi::CStr;
+
+    fn sysctl_usize(name: &CStr) -> Option<usize> {
+        let mut value = 0u64;
+        let mut size = std::mem::size_of::<u64>();
+        // SAFETY: value/size descrevem um buffer de 8 bytes; valores de 4
+        // bytes preenchem só o início (little-endian)
+        let result = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (result == 0 && value > 0).then_some(value as usize)
+    }
+
+    pub fn detect() -> Option<CpuTopology> {
+        let logical_cores = sysctl_usize(c"hw.logicalcpu")?;
+        Some(CpuTopology {
+            logical_cores,
+            physical_cores: sysctl_usize(c"hw.physicalcpu").unwrap_or(logical_cores),
+            packages: sysctl_usize(c"hw.packages").unwrap_or(1),
+            cache_line_size: sysctl_usize(c"hw.cachelinesize").unwrap_or(super::DEFAULT_CACHE_LINE),
+            l1_data_cache: sysctl_usize(c"hw.l1dcachesize"),
+            l2_cache: sysctl_usize(c"hw.l2cachesize"),
+            l3_cache: sysctl_usize(c"hw.l3cachesize"),
+        })
+    }
+}
+
+// ============================================================================
+// Windows: GetLogicalProcessorInformation
+// ============================================================================
+
+#[cfg(windows)]
+mod imp {
+    use super::CpuTopology;
+    use windows_sys::Win32::System::SystemInformation::{
+        CacheData, CacheUnified, GetLogicalProcessorInformation, RelationCache,
+        RelationProcessorCore, RelationProcessorPackage, SYSTEM_LOGICAL_PROCESSOR_INFORMATION,
+    };
+
+    pub fn detect() -> Option<CpuTopology> {
+        let entry_size = std::mem::size_of::<SYSTEM_LOGICAL_PROCESSOR_INFORMATION>();
+        let mut length = 0u32;
+        // SAFETY: a primeira chamada só consulta o tamanho necessário
+        unsafe { GetLogicalProcessorInformation(std::ptr::null_mut(), &mut length) };
+        if length == 0 {
+            return None;
+        }
+        // SAFETY: a struct é POD; zerada é um valor válido
+        let mut info: Vec<SYSTEM_LOGICAL_PROCESSOR_INFORMATION> =
+            vec![unsafe { std::mem::zeroed() }; length as usize / entry_size + 1];
+        // SAFETY: o buffer tem pelo menos `length` bytes
+        if unsafe { GetLogicalProcessorInformation(info.as_mut_ptr(), &mut length) } == 0 {
+            return None;
+        }
+        info.truncate(length as usize / entry_size);
+
+        let mut topology = CpuTopology {
+            logical_cores: 0,
+            physical_cores: 0,
+            packages: 0,
+            cache_line_size: super::DEFAULT_CACHE_LINE,
+            l1_data_cache: None,
+            l2_cache: None,
+            l3_cache: None,
+        };
+        for entry in &info {
+            match entry.Relationship {
+                RelationProcessorCore => {
+                    topology.physical_cores += 1;
+                    topology.logical_cores += entry.ProcessorMask.count_ones() as usize;
+                }
+                RelationProcessorPackage => topology.packages += 1,
+                RelationCache => {
+                    // SAFETY: para RelationCache o union contém um CACHE_DESCRIPTOR
+                    let cache = unsafe { entry.Anonymous.Cache };
+                    let size = Some(cache.Size as usize);
+                    match cache.Level {
+                        1 if cache.Type == CacheData || cache.Type == CacheUnified => {
+                            topology.l1_data_cache = size;
+                            topology.cache_line_size = cache.LineSize as usize;
+                        }
+                        2 => topology.l2_cache = size,
+                        3 => topology.l3_cache = size,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        (topology.logical_cores > 0).then_some(topology)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    windows
+)))]
+mod imp {
+    pub fn detect() -> Option<super::CpuTopology> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_features() {
+        let features = cpu_features();
+        assert_eq!(*features, CpuFeatures::detect());
+
+        // SSE2 faz parte do x86_64 base; NEON do aarch64
+        #[cfg(target_arch = "x86_64")]
+        assert!(features.sse2 && features.simd_width() >= 16);
+        #[cfg(target_arch = "aarch64")]
+        assert!(features.neon);
+
+        assert_eq!(features.names().is_empty(), features.simd_width() == 0);
+        if features.avx2 {
+            assert!(features.avx && features.names().contains(&"avx2"));
+        }
+    }
+
+    #[test]
+    fn test_cpu_topology() {
+        let topology = cpu_topology();
+        assert!(topology.logical_cores >= 1);
+        assert!(topology.physical_cores >= 1 && topology.physical_cores <= topology.logical_cores);
+        assert!(topology.packages >= 1);
+        assert!(topology.cache_line_size.is_power_of_two());
+        assert!(topology.recommended_worker_count() >= 1);
+        assert_eq!(
+            topology.has_smt(),
+            topology.logical_cores > topology.physical_cores
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_parse_cache_size() {
+        assert_eq!(imp::parse_size("32K"), Some(32 * 1024));
+        assert_eq!(imp::parse_size("8M"), Some(8 * 1024 * 1024));
+        assert_eq!(imp::parse_size("512"), Some(512));
+        assert_eq!(imp::parse_size("3X"), None);
+    }
+}
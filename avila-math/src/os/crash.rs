@@ -0,0 +1,331 @@
+//! Relatórios de crash
+//!
+//! `CrashReporter::install` troca o panic hook por um que monta um
+//! `CrashReport` (mensagem, local, thread, backtrace, informações do sistema e
+//! as últimas linhas registradas com `log_line`), grava o relatório em disco
+//! e, se configurado, envia por POST e mostra uma caixa de erro. O hook
+//! anterior continua sendo chamado depois.
+//!
+//! ```no_run
+//! use avila_math::os::crash::{self, CrashReporter};
+//!
+//! CrashReporter::new("MeuJogo")
+//!     .version("1.4.2")
+//!     .directory("crashes")
+//!     .upload_url("http://crash.example.com/report")
+//!     .show_dialog(true)
+//!     .install();
+//!
+//! crash::log_line("carregando fase 3");
+//! ```
+
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use super::clock::{Clock, DateTime};
+use super::filesystem::FileSystem;
+use super::http::{HttpClient, Method};
+use super::{dialog, metrics, SystemInfo};
+
+/// Linhas de log guardadas por padrão
+pub const DEFAULT_LOG_CAPACITY: usize = 200;
+
+static LOG_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_LOG_CAPACITY);
+
+fn log_buffer() -> &'static Mutex<VecDeque<String>> {
+    static LOG: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+    LOG.get_or_init(Default::default)
+}
+
+/// Registra uma linha para o próximo relatório de crash
+///
+/// Só as últimas linhas são guardadas (ver `CrashReporter::log_capacity`).
+pub fn log_line(line: impl Into<String>) {
+    let capacity = LOG_CAPACITY.load(Ordering::Relaxed);
+    let mut log = log_buffer().lock().unwrap_or_else(|e| e.into_inner());
+    while log.len() >= capacity.max(1) {
+        log.pop_front();
+    }
+    if capacity > 0 {
+        log.push_back(line.into());
+    }
+}
+
+/// Linhas registradas mais recentes, da mais antiga para a mais nova
+pub fn recent_log() -> Vec<String> {
+    let log = log_buffer().lock().unwrap_or_else(|e| e.into_inner());
+    log.iter().cloned().collect()
+}
+
+/// Captura a pilha de chamadas atual, independente de `RUST_BACKTRACE`
+pub fn stack_trace() -> String {
+    Backtrace::force_capture().to_string()
+}
+
+/// Resumo do sistema incluído nos relatórios
+pub fn system_summary() -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "os: {} ({})",
+        SystemInfo::os_name(),
+        SystemInfo::arch()
+    );
+    let _ = writeln!(
+        out,
+        "cpu: {} logical / {} physical cores [{}]",
+        SystemInfo::logical_cores(),
+        SystemInfo::physical_cores(),
+        SystemInfo::cpu_features().names().join(" ")
+    );
+    if let Some(memory) = metrics::memory_info() {
+        let _ = writeln!(
+            out,
+            "memory: {} MiB available of {} MiB",
+            memory.available / (1024 * 1024),
+            memory.total / (1024 * 1024)
+        );
+    }
+    if let Some(rss) = metrics::process_memory_usage() {
+        let _ = writeln!(out, "process memory: {} MiB", rss / (1024 * 1024));
+    }
+    out
+}
+
+/// Dados de um crash
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub app_name: String,
+    pub version: Option<String>,
+    pub message: String,
+    /// `arquivo:linha:coluna` do panic, se conhecido
+    pub location: Option<String>,
+    pub thread: String,
+    /// Segundos desde a época Unix
+    pub timestamp: u64,
+    pub backtrace: String,
+    pub system: String,
+    pub log: Vec<String>,
+}
+
+impl CrashReport {
+    /// Monta um relatório a partir do estado atual (thread, pilha, sistema, log)
+    pub fn capture(app_name: &str, message: impl Into<String>, location: Option<String>) -> Self {
+        let thread = std::thread::current();
+        Self {
+            app_name: app_name.to_string(),
+            version: None,
+            message: message.into(),
+            location,
+            thread: thread.name().unwrap_or("<unnamed>").to_string(),
+            timestamp: Clock::unix_timestamp(),
+            backtrace: stack_trace(),
+            system: system_summary(),
+            log: recent_log(),
+        }
+    }
+
+    fn from_panic(app_name: &str, info: &PanicHookInfo<'_>) -> Self {
+        let payload = info.payload();
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "<non-string panic payload>".to_string()
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        Self::capture(app_name, message, location)
+    }
+
+    /// Nome de arquivo sugerido (`crash-<app>-<timestamp>.txt`)
+    pub fn file_name(&self) -> String {
+        let app: String = self
+            .app_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("crash-{}-{}.txt", app, self.timestamp)
+    }
+
+    /// Texto do relatório
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "=== {} crash report ===", self.app_name);
+        if let Some(version) = &self.version {
+            let _ = writeln!(out, "version: {}", version);
+        }
+        let _ = writeln!(
+            out,
+            "timestamp: {}",
+            DateTime::from_unix(self.timestamp as i64, 0)
+        );
+        let _ = writeln!(out, "thread: {}", self.thread);
+        let _ = writeln!(out, "message: {}", self.message);
+        if let Some(location) = &self.location {
+            let _ = writeln!(out, "location: {}", location);
+        }
+        let _ = writeln!(out, "\n--- system ---\n{}", self.system.trim_end());
+        let _ = writeln!(out, "\n--- backtrace ---\n{}", self.backtrace.trim_end());
+        let _ = writeln!(out, "\n--- log ({} lines) ---", self.log.len());
+        for line in &self.log {
+            let _ = writeln!(out, "{}", line);
+        }
+        out
+    }
+}
+
+/// Configura e instala o panic hook de relatórios
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    app_name: String,
+    version: Option<String>,
+    directory: PathBuf,
+    upload_url: Option<String>,
+    upload_timeout: Duration,
+    show_dialog: bool,
+}
+
+impl CrashReporter {
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            version: None,
+            directory: PathBuf::from("crashes"),
+            upload_url: None,
+            upload_timeout: Duration::from_secs(5),
+            show_dialog: false,
+        }
+    }
+
+    /// Versão do build incluída no relatório
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    /// Diretório dos arquivos de crash (padrão: `crashes`)
+    pub fn directory<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.directory = directory.as_ref().to_path_buf();
+        self
+    }
+
+    /// Envia cada relatório por POST (`text/plain`) para esta URL
+    pub fn upload_url(mut self, url: &str) -> Self {
+        self.upload_url = Some(url.to_string());
+        self
+    }
+
+    /// Tempo máximo do envio (padrão: 5s)
+    pub fn upload_timeout(mut self, timeout: Duration) -> Self {
+        self.upload_timeout = timeout;
+        self
+    }
+
+    /// Mostra uma caixa de erro nativa após gravar o relatório
+    pub fn show_dialog(mut self, show: bool) -> Self {
+        self.show_dialog = show;
+        self
+    }
+
+    /// Quantas linhas de `log_line` guardar (global)
+    pub fn log_capacity(self, capacity: usize) -> Self {
+        LOG_CAPACITY.store(capacity, Ordering::Relaxed);
+        self
+    }
+
+    /// Grava (e envia) um relatório; retorna o caminho do arquivo
+    pub fn report(&self, report: &CrashReport) -> io::Result<PathBuf> {
+        let mut report = report.clone();
+        if report.version.is_none() {
+            report.version = self.version.clone();
+        }
+        let text = report.to_text();
+
+        FileSystem::create_dir_all(&self.directory)?;
+        let path = self.directory.join(report.file_name());
+        FileSystem::write(&path, &text)?;
+
+        if let Some(url) = &self.upload_url {
+            let result = HttpClient::new()
+                .with_timeout(self.upload_timeout)
+                .request(Method::Post, url)
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(text)
+                .send();
+            match result {
+                Ok(response) if response.is_success() => {}
+                Ok(response) => eprintln!("crash report upload failed: HTTP {}", response.status),
+                Err(err) => eprintln!("crash report upload failed: {}", err),
+            }
+        }
+
+        if self.show_dialog {
+            dialog::error_box(
+                &self.app_name,
+                &format!(
+                    "{} crashed: {}\n\nA report was saved to {}",
+                    self.app_name,
+                    report.message,
+                    path.display()
+                ),
+            );
+        }
+        Ok(path)
+    }
+
+    /// Instala o panic hook, encadeando o hook anterior
+    pub fn install(self) {
+        // Evita recursão se o próprio relatório entrar em panic
+        static REPORTING: AtomicBool = AtomicBool::new(false);
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !REPORTING.swap(true, Ordering::SeqCst) {
+                let report = CrashReport::from_panic(&self.app_name, info);
+                match self.report(&report) {
+                    Ok(path) => eprintln!("crash report written to {}", path.display()),
+                    Err(err) => eprintln!("failed to write crash report: {}", err),
+                }
+                REPORTING.store(false, Ordering::SeqCst);
+            }
+            previous(info);
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_written() {
+        log_line("crash test: loading level");
+        let dir = std::env::temp_dir().join(format!("avila_crash_{}", std::process::id()));
+        let reporter = CrashReporter::new("Crash Test")
+            .version("0.1.0")
+            .directory(&dir);
+
+        let report = CrashReport::capture("Crash Test", "boom", Some("main.rs:1:1".into()));
+        assert!(report.file_name().starts_with("crash-Crash_Test-"));
+        let path = reporter.report(&report).unwrap();
+
+        let text = FileSystem::read_to_string(&path).unwrap();
+        assert!(text.contains("message: boom"));
+        assert!(text.contains("version: 0.1.0"));
+        assert!(text.contains("location: main.rs:1:1"));
+        assert!(text.contains("crash test: loading level"));
+        assert!(text.contains("--- backtrace ---"));
+
+        FileSystem::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,814 @@
+//! Caixas de mensagem e diálogos de arquivo nativos
+//!
+//! Bloqueiam a thread que chama até o usuário responder. Cada plataforma usa
+//! o que já vem com o sistema, sem dependências extras:
+//!
+//! - **Windows**: `MessageBoxW` e `GetOpenFileNameW`/`GetSaveFileNameW`
+//! - **macOS**: AppleScript via `osascript`
+//! - **Linux/BSD**: `zenity` ou `kdialog`, o que estiver instalado
+//!
+//! Sem nenhum backend disponível (ex: servidor sem interface gráfica), a
+//! mensagem vai para o stderr e `message_box` devolve a opção mais segura;
+//! os diálogos de arquivo retornam `ErrorKind::Unsupported`.
+//!
+//! ```no_run
+//! use avila_math::os::dialog::{self, DialogResult, FileDialog, MessageButtons};
+//!
+//! let answer = dialog::message_box("Sair", "Salvar antes de sair?", MessageButtons::YesNoCancel);
+//! if answer == DialogResult::Yes {
+//!     let path = FileDialog::new()
+//!         .title("Salvar cena")
+//!         .file_name("cena.scene")
+//!         .filter("Cenas", &["scene"])
+//!         .save_file()
+//!         .unwrap();
+//!     println!("{:?}", path);
+//! }
+//! ```
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Ícone/severidade da mensagem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+/// Botões exibidos na caixa de mensagem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageButtons {
+    #[default]
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+}
+
+impl MessageButtons {
+    /// Resposta usada quando não há como perguntar ao usuário
+    fn fallback(self) -> DialogResult {
+        match self {
+            MessageButtons::Ok => DialogResult::Ok,
+            MessageButtons::OkCancel | MessageButtons::YesNoCancel => DialogResult::Cancel,
+            MessageButtons::YesNo => DialogResult::No,
+        }
+    }
+}
+
+/// Botão escolhido pelo usuário
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogResult {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+/// Mostra uma caixa de mensagem informativa e espera a resposta
+pub fn message_box(title: &str, text: &str, buttons: MessageButtons) -> DialogResult {
+    message_box_with_level(title, text, buttons, MessageLevel::Info)
+}
+
+/// Mostra uma caixa de mensagem com o ícone de `level`
+pub fn message_box_with_level(
+    title: &str,
+    text: &str,
+    buttons: MessageButtons,
+    level: MessageLevel,
+) -> DialogResult {
+    imp::message_box(title, text, buttons, level).unwrap_or_else(|| {
+        eprintln!("[{:?}] {}: {}", level, title, text);
+        buttons.fallback()
+    })
+}
+
+/// Atalho para relatar um erro fatal
+pub fn error_box(title: &str, text: &str) {
+    message_box_with_level(title, text, MessageButtons::Ok, MessageLevel::Error);
+}
+
+/// Filtro de tipos de arquivo (`"Imagens"`, `["png", "jpg"]`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFilter {
+    pub name: String,
+    /// Extensões sem o ponto; `"*"` aceita qualquer arquivo
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    /// Padrões glob (`*.png`) das extensões
+    fn patterns(&self) -> Vec<String> {
+        self.extensions
+            .iter()
+            .map(|ext| {
+                if ext == "*" {
+                    "*".to_string()
+                } else {
+                    format!("*.{}", ext)
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialogKind {
+    Open,
+    OpenMultiple,
+    Save,
+}
+
+/// Diálogo para abrir ou salvar arquivos
+#[derive(Debug, Clone, Default)]
+pub struct FileDialog {
+    title: Option<String>,
+    directory: Option<PathBuf>,
+    file_name: Option<String>,
+    filters: Vec<FileFilter>,
+}
+
+impl FileDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Título da janela
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Diretório inicial
+    pub fn directory<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Nome sugerido (útil em `save_file`)
+    pub fn file_name(mut self, name: &str) -> Self {
+        self.file_name = Some(name.to_string());
+        self
+    }
+
+    /// Adiciona um filtro de extensões; o primeiro é o selecionado
+    pub fn filter(mut self, name: &str, extensions: &[&str]) -> Self {
+        self.filters.push(FileFilter {
+            name: name.to_string(),
+            extensions: extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_string())
+                .collect(),
+        });
+        self
+    }
+
+    /// Escolhe um arquivo existente; `Ok(None)` se o usuário cancelar
+    pub fn pick_file(&self) -> io::Result<Option<PathBuf>> {
+        Ok(imp::file_dialog(self, DialogKind::Open)?.into_iter().next())
+    }
+
+    /// Escolhe vários arquivos existentes; vazio se o usuário cancelar
+    pub fn pick_files(&self) -> io::Result<Vec<PathBuf>> {
+        imp::file_dialog(self, DialogKind::OpenMultiple)
+    }
+
+    /// Escolhe onde salvar; `Ok(None)` se o usuário cancelar
+    pub fn save_file(&self) -> io::Result<Option<PathBuf>> {
+        Ok(imp::file_dialog(self, DialogKind::Save)?.into_iter().next())
+    }
+
+    /// Caminho inicial: diretório + nome sugerido
+    #[allow(dead_code)]
+    fn initial_path(&self) -> Option<PathBuf> {
+        match (&self.directory, &self.file_name) {
+            (Some(dir), Some(name)) => Some(dir.join(name)),
+            (Some(dir), None) => Some(dir.clone()),
+            (None, Some(name)) => Some(PathBuf::from(name)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no native dialog backend available",
+    )
+}
+
+// ============================================================================
+// Linux / BSD: zenity ou kdialog
+// ============================================================================
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+mod imp {
+    use super::*;
+    use crate::os::process::{CapturedOutput, CommandBuilder, StdioMode};
+    use std::sync::OnceLock;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Tool {
+        Zenity,
+        Kdialog,
+    }
+
+    impl Tool {
+        fn program(self) -> &'static str {
+            match self {
+                Tool::Zenity => "zenity",
+                Tool::Kdialog => "kdialog",
+            }
+        }
+    }
+
+    fn available(program: &str) -> bool {
+        CommandBuilder::new(program)
+            .arg("--version")
+            .stdio(StdioMode::Null)
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    fn tool() -> Option<Tool> {
+        static TOOL: OnceLock<Option<Tool>> = OnceLock::new();
+        *TOOL.get_or_init(|| {
+            if std::env::var_os("DISPLAY").is_none()
+                && std::env::var_os("WAYLAND_DISPLAY").is_none()
+            {
+                return None;
+            }
+            let kde = std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.contains("KDE"));
+            let order = if kde {
+                [Tool::Kdialog, Tool::Zenity]
+            } else {
+                [Tool::Zenity, Tool::Kdialog]
+            };
+            order.into_iter().find(|tool| available(tool.program()))
+        })
+    }
+
+    fn run(tool: Tool, args: Vec<String>) -> Option<CapturedOutput> {
+        CommandBuilder::new(tool.program())
+            .args(args)
+            .stdin(StdioMode::Null)
+            .output()
+            .ok()
+    }
+
+    pub fn message_args(
+        tool: Tool,
+        title: &str,
+        text: &str,
+        buttons: MessageButtons,
+        level: MessageLevel,
+    ) -> Vec<String> {
+        let mut args: Vec<String> = match tool {
+            Tool::Zenity => {
+                let mut args = match buttons {
+                    MessageButtons::Ok => vec![match level {
+                        MessageLevel::Info => "--info",
+                        MessageLevel::Warning => "--warning",
+                        MessageLevel::Error => "--error",
+                    }
+                    .to_string()],
+                    MessageButtons::OkCancel => {
+                        vec![
+                            "--question".into(),
+                            "--ok-label=OK".into(),
+                            "--cancel-label=Cancel".into(),
+                        ]
+                    }
+                    MessageButtons::YesNo => {
+                        vec![
+                            "--question".into(),
+                            "--ok-label=Yes".into(),
+                            "--cancel-label=No".into(),
+                        ]
+                    }
+                    MessageButtons::YesNoCancel => vec![
+                        "--question".into(),
+                        "--ok-label=Yes".into(),
+                        "--cancel-label=No".into(),
+                        "--extra-button=Cancel".into(),
+                    ],
+                };
+                args.push("--no-markup".into());
+                args.push(format!("--title={}", title));
+                args.push(format!("--text={}", text));
+                return args;
+            }
+            Tool::Kdialog => match buttons {
+                MessageButtons::Ok => vec![match level {
+                    MessageLevel::Info => "--msgbox",
+                    MessageLevel::Warning => "--sorry",
+                    MessageLevel::Error => "--error",
+                }
+                .to_string()],
+                MessageButtons::OkCancel => vec!["--yesno".into()],
+                MessageButtons::YesNo => vec!["--yesno".into()],
+                MessageButtons::YesNoCancel => vec!["--yesnocancel".into()],
+            },
+        };
+        args.push(text.to_string());
+        if buttons == MessageButtons::OkCancel {
+            args.extend(["--yes-label", "OK", "--no-label", "Cancel"].map(String::from));
+        }
+        args.extend(["--title".to_string(), title.to_string()]);
+        args
+    }
+
+    pub fn message_box(
+        title: &str,
+        text: &str,
+        buttons: MessageButtons,
+        level: MessageLevel,
+    ) -> Option<DialogResult> {
+        let tool = tool()?;
+        let output = run(tool, message_args(tool, title, text, buttons, level))?;
+        let (accept, reject) = match buttons {
+            MessageButtons::Ok | MessageButtons::OkCancel => {
+                (DialogResult::Ok, DialogResult::Cancel)
+            }
+            MessageButtons::YesNo | MessageButtons::YesNoCancel => {
+                (DialogResult::Yes, DialogResult::No)
+            }
+        };
+        match output.code()? {
+            0 => Some(accept),
+            // zenity imprime o rótulo do botão extra
+            1 if output.stdout.trim() == "Cancel" => Some(DialogResult::Cancel),
+            1 => Some(reject),
+            2 if tool == Tool::Kdialog => Some(DialogResult::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn file_args(tool: Tool, dialog: &FileDialog, kind: DialogKind) -> Vec<String> {
+        let title = dialog.title.clone().unwrap_or_else(|| match kind {
+            DialogKind::Save => "Save".to_string(),
+            _ => "Open".to_string(),
+        });
+        let start = dialog.initial_path();
+        match tool {
+            Tool::Zenity => {
+                let mut args = vec!["--file-selection".to_string(), format!("--title={}", title)];
+                match kind {
+                    DialogKind::Save => args.push("--save".into()),
+                    DialogKind::OpenMultiple => {
+                        args.extend(["--multiple".to_string(), "--separator=\n".to_string()])
+                    }
+                    DialogKind::Open => {}
+                }
+                if let Some(start) = start {
+                    let mut start = start.to_string_lossy().into_owned();
+                    // Diretório sem nome sugerido: a barra final abre dentro dele
+                    if dialog.file_name.is_none() && !start.ends_with('/') {
+                        start.push('/');
+                    }
+                    args.push(format!("--filename={}", start));
+                }
+                for filter in &dialog.filters {
+                    args.push(format!(
+                        "--file-filter={} | {}",
+                        filter.name,
+                        filter.patterns().join(" ")
+                    ));
+                }
+                args
+            }
+            Tool::Kdialog => {
+                let mut args = vec![match kind {
+                    DialogKind::Save => "--getsavefilename",
+                    _ => "--getopenfilename",
+                }
+                .to_string()];
+                args.push(
+                    start
+                        .map(|start| start.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| ".".to_string()),
+                );
+                let filters: Vec<String> = dialog
+                    .filters
+                    .iter()
+                    .map(|filter| format!("{}|{}", filter.patterns().join(" "), filter.name))
+                    .collect();
+                args.push(filters.join("\n"));
+                if kind == DialogKind::OpenMultiple {
+                    args.extend(["--multiple".to_string(), "--separate-output".to_string()]);
+                }
+                args.extend(["--title".to_string(), title]);
+                args
+            }
+        }
+    }
+
+    pub fn file_dialog(dialog: &FileDialog, kind: DialogKind) -> io::Result<Vec<PathBuf>> {
+        let tool = tool().ok_or_else(unsupported)?;
+        let output = CommandBuilder::new(tool.program())
+            .args(file_args(tool, dialog, kind))
+            .stdin(StdioMode::Null)
+            .output()?;
+        match output.code() {
+            Some(0) => Ok(output
+                .stdout
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect()),
+            Some(1) => Ok(Vec::new()),
+            _ => Err(io::Error::other(format!(
+                "{} failed: {}",
+                tool.program(),
+                output.stderr.trim()
+            ))),
+        }
+    }
+}
+
+// ============================================================================
+// macOS: AppleScript
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::*;
+    use crate::os::process::{CapturedOutput, CommandBuilder, StdioMode};
+
+    /// String literal do AppleScript
+    pub fn quote(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    fn osascript(script: &str) -> io::Result<CapturedOutput> {
+        CommandBuilder::new("osascript")
+            .args(["-e", script])
+            .stdin(StdioMode::Null)
+            .output()
+    }
+
+    pub fn message_script(
+        title: &str,
+        text: &str,
+        buttons: MessageButtons,
+        level: MessageLevel,
+    ) -> String {
+        let labels: &[&str] = match buttons {
+            MessageButtons::Ok => &["OK"],
+            MessageButtons::OkCancel => &["Cancel", "OK"],
+            MessageButtons::YesNo => &["No", "Yes"],
+            MessageButtons::YesNoCancel => &["Cancel", "No", "Yes"],
+        };
+        let list: Vec<String> = labels.iter().map(|label| quote(label)).collect();
+        let icon = match level {
+            MessageLevel::Info => "note",
+            MessageLevel::Warning => "caution",
+            MessageLevel::Error => "stop",
+        };
+        let mut script = format!(
+            "display dialog {} with title {} buttons {{{}}} default button {} with icon {}",
+            quote(text),
+            quote(title),
+            list.join(", "),
+            quote(labels[labels.len() - 1]),
+            icon
+        );
+        if labels[0] == "Cancel" {
+            script.push_str(" cancel button \"Cancel\"");
+        }
+        script
+    }
+
+    pub fn message_box(
+        title: &str,
+        text: &str,
+        buttons: MessageButtons,
+        level: MessageLevel,
+    ) -> Option<DialogResult> {
+        let output = osascript(&message_script(title, text, buttons, level)).ok()?;
+        if !output.success() {
+            // "Cancel" (ou Esc) gera o erro -128
+            return (buttons != MessageButtons::Ok).then_some(buttons.fallback());
+        }
+        let button = output.stdout.trim().strip_prefix("button returned:")?;
+        match button {
+            "OK" => Some(DialogResult::Ok),
+            "Yes" => Some(DialogResult::Yes),
+            "No" => Some(DialogResult::No),
+            "Cancel" => Some(DialogResult::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn file_script(dialog: &FileDialog, kind: DialogKind) -> String {
+        let prompt = dialog.title.as_deref().unwrap_or(match kind {
+            DialogKind::Save => "Save",
+            _ => "Open",
+        });
+        let mut options = format!(" with prompt {}", quote(prompt));
+        if let Some(dir) = &dialog.directory {
+            options.push_str(&format!(
+                " default location (POSIX file {})",
+                quote(&dir.to_string_lossy())
+            ));
+        }
+
+        match kind {
+            DialogKind::Save => {
+                if let Some(name) = &dialog.file_name {
+                    options.push_str(&format!(" default name {}", quote(name)));
+                }
+                format!("POSIX path of (choose file name{})", options)
+            }
+            DialogKind::Open | DialogKind::OpenMultiple => {
+                let types: Vec<String> = dialog
+                    .filters
+                    .iter()
+                    .flat_map(|filter| filter.extensions.iter())
+                    .filter(|ext| ext.as_str() != "*")
+                    .map(|ext| quote(ext))
+                    .collect();
+                // Um filtro "*" libera todos os tipos
+                let any = dialog
+                    .filters
+                    .iter()
+                    .any(|filter| filter.extensions.iter().any(|ext| ext == "*"));
+                if !types.is_empty() && !any {
+                    options.push_str(&format!(" of type {{{}}}", types.join(", ")));
+                }
+                if kind == DialogKind::Open {
+                    format!("POSIX path of (choose file{})", options)
+                } else {
+                    format!(
+                        "set picked to (choose file{} with multiple selections allowed)\n\
+                         set out to \"\"\n\
+                         repeat with f in picked\n\
+                         set out to out & POSIX path of f & linefeed\n\
+                         end repeat\n\
+                         return out",
+                        options
+                    )
+                }
+            }
+        }
+    }
+
+    pub fn file_dialog(dialog: &FileDialog, kind: DialogKind) -> io::Result<Vec<PathBuf>> {
+        let output = osascript(&file_script(dialog, kind))?;
+        if !output.success() {
+            // -128: usuário cancelou
+            if output.stderr.contains("-128") {
+                return Ok(Vec::new());
+            }
+            return Err(io::Error::other(output.stderr.trim().to_string()));
+        }
+        Ok(output
+            .stdout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+}
+
+// ============================================================================
+// Windows: user32 + comdlg32
+// ============================================================================
+
+#[cfg(windows)]
+mod imp {
+    use super::*;
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use windows_sys::Win32::UI::Controls::Dialogs::{
+        GetOpenFileNameW, GetSaveFileNameW, OFN_ALLOWMULTISELECT, OFN_EXPLORER, OFN_FILEMUSTEXIST,
+        OFN_NOCHANGEDIR, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        MessageBoxW, IDCANCEL, IDNO, IDOK, IDYES, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONWARNING,
+        MB_OK, MB_OKCANCEL, MB_SETFOREGROUND, MB_YESNO, MB_YESNOCANCEL,
+    };
+
+    fn wide(text: &str) -> Vec<u16> {
+        std::ffi::OsStr::new(text)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    pub fn message_box(
+        title: &str,
+        text: &str,
+        buttons: MessageButtons,
+        level: MessageLevel,
+    ) -> Option<DialogResult> {
+        let style = match buttons {
+            MessageButtons::Ok => MB_OK,
+            MessageButtons::OkCancel => MB_OKCANCEL,
+            MessageButtons::YesNo => MB_YESNO,
+            MessageButtons::YesNoCancel => MB_YESNOCANCEL,
+        } | match level {
+            MessageLevel::Info => MB_ICONINFORMATION,
+            MessageLevel::Warning => MB_ICONWARNING,
+            MessageLevel::Error => MB_ICONERROR,
+        } | MB_SETFOREGROUND;
+        let (title, text) = (wide(title), wide(text));
+        // SAFETY: strings terminadas em nulo que vivem durante a chamada
+        let result =
+            unsafe { MessageBoxW(std::ptr::null_mut(), text.as_ptr(), title.as_ptr(), style) };
+        match result {
+            IDOK => Some(DialogResult::Ok),
+            IDCANCEL => Some(DialogResult::Cancel),
+            IDYES => Some(DialogResult::Yes),
+            IDNO => Some(DialogResult::No),
+            _ => None,
+        }
+    }
+
+    /// "Nome\0*.png;*.jpg\0...\0\0"
+    fn filter_string(filters: &[FileFilter]) -> Vec<u16> {
+        let mut filter = Vec::new();
+        for f in filters {
+            filter.extend(std::ffi::OsStr::new(&f.name).encode_wide());
+            filter.push(0);
+            filter.extend(std::ffi::OsStr::new(&f.patterns().join(";")).encode_wide());
+            filter.push(0);
+        }
+        filter.push(0);
+        filter
+    }
+
+    pub fn file_dialog(dialog: &FileDialog, kind: DialogKind) -> io::Result<Vec<PathBuf>> {
+        // Espaço para seleções múltiplas longas
+        let mut buffer = vec![0u16; 32 * 1024];
+        if let Some(name) = &dialog.file_name {
+            let name: Vec<u16> = std::ffi::OsStr::new(name).encode_wide().collect();
+            let len = name.len().min(buffer.len() - 1);
+            buffer[..len].copy_from_slice(&name[..len]);
+        }
+        let filter = (!dialog.filters.is_empty()).then(|| filter_string(&dialog.filters));
+        let title = dialog.title.as_deref().map(wide);
+        let directory: Option<Vec<u16>> = dialog.directory.as_ref().map(|dir| {
+            dir.as_os_str()
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect()
+        });
+
+        // SAFETY: OPENFILENAMEW é POD; zerada é o estado padrão documentado
+        let mut ofn: OPENFILENAMEW = unsafe { std::mem::zeroed() };
+        ofn.lStructSize = std::mem::size_of::<OPENFILENAMEW>() as u32;
+        ofn.lpstrFile = buffer.as_mut_ptr();
+        ofn.nMaxFile = buffer.len() as u32;
+        ofn.lpstrFilter = filter.as_ref().map_or(std::ptr::null(), |f| f.as_ptr());
+        ofn.nFilterIndex = 1;
+        ofn.lpstrTitle = title.as_ref().map_or(std::ptr::null(), |t| t.as_ptr());
+        ofn.lpstrInitialDir = directory.as_ref().map_or(std::ptr::null(), |d| d.as_ptr());
+        ofn.Flags = OFN_EXPLORER | OFN_NOCHANGEDIR | OFN_PATHMUSTEXIST;
+        match kind {
+            DialogKind::Open => ofn.Flags |= OFN_FILEMUSTEXIST,
+            DialogKind::OpenMultiple => ofn.Flags |= OFN_FILEMUSTEXIST | OFN_ALLOWMULTISELECT,
+            DialogKind::Save => ofn.Flags |= OFN_OVERWRITEPROMPT,
+        }
+
+        // SAFETY: todos os ponteiros em `ofn` apontam para buffers vivos até o fim da chamada
+        let ok = unsafe {
+            if kind == DialogKind::Save {
+                GetSaveFileNameW(&mut ofn)
+            } else {
+                GetOpenFileNameW(&mut ofn)
+            }
+        };
+        if ok == 0 {
+            // Cancelado (ou erro do diálogo, que não tem como ser reportado aqui)
+            return Ok(Vec::new());
+        }
+
+        // Seleção múltipla: "dir\0arq1\0arq2\0\0"; simples: "caminho\0"
+        let parts: Vec<PathBuf> = buffer
+            .split(|&c| c == 0)
+            .take_while(|part| !part.is_empty())
+            .map(|part| PathBuf::from(OsString::from_wide(part)))
+            .collect();
+        Ok(match parts.split_first() {
+            Some((dir, files)) if !files.is_empty() => {
+                files.iter().map(|file| dir.join(file)).collect()
+            }
+            _ => parts,
+        })
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use super::*;
+
+    pub fn message_box(
+        _title: &str,
+        _text: &str,
+        _buttons: MessageButtons,
+        _level: MessageLevel,
+    ) -> Option<DialogResult> {
+        None
+    }
+
+    pub fn file_dialog(_dialog: &FileDialog, _kind: DialogKind) -> io::Result<Vec<PathBuf>> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+mod imp {
+    use super::*;
+
+    pub fn message_box(
+        _title: &str,
+        _text: &str,
+        _buttons: MessageButtons,
+        _level: MessageLevel,
+    ) -> Option<DialogResult> {
+        None
+    }
+
+    pub fn file_dialog(_dialog: &FileDialog, _kind: DialogKind) -> io::Result<Vec<PathBuf>> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_results() {
+        assert_eq!(MessageButtons::Ok.fallback(), DialogResult::Ok);
+        assert_eq!(MessageButtons::OkCancel.fallback(), DialogResult::Cancel);
+        assert_eq!(MessageButtons::YesNo.fallback(), DialogResult::No);
+        assert_eq!(MessageButtons::YesNoCancel.fallback(), DialogResult::Cancel);
+    }
+
+    #[test]
+    fn test_file_dialog_builder() {
+        let dialog = FileDialog::new()
+            .directory("/tmp/scenes")
+            .file_name("level.scene")
+            .filter("Scenes", &[".scene", "json"])
+            .filter("All", &["*"]);
+        assert_eq!(dialog.filters[0].patterns(), ["*.scene", "*.json"]);
+        assert_eq!(dialog.filters[1].patterns(), ["*"]);
+        assert_eq!(
+            dialog.initial_path(),
+            Some(PathBuf::from("/tmp/scenes/level.scene"))
+        );
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ))]
+    #[test]
+    fn test_linux_dialog_args() {
+        use imp::{file_args, message_args, Tool};
+
+        let args = message_args(
+            Tool::Zenity,
+            "Erro",
+            "<falhou>",
+            MessageButtons::YesNoCancel,
+            MessageLevel::Error,
+        );
+        assert!(args.contains(&"--extra-button=Cancel".to_string()));
+        assert!(args.contains(&"--no-markup".to_string()));
+        assert_eq!(args.last().unwrap(), "--text=<falhou>");
+
+        let args = message_args(
+            Tool::Kdialog,
+            "T",
+            "x",
+            MessageButtons::Ok,
+            MessageLevel::Warning,
+        );
+        assert_eq!(args, ["--sorry", "x", "--title", "T"]);
+
+        let dialog = FileDialog::new()
+            .directory("/assets")
+            .filter("Imagens", &["png", "jpg"]);
+        let args = file_args(Tool::Zenity, &dialog, DialogKind::OpenMultiple);
+        assert!(args.contains(&"--multiple".to_string()));
+        assert!(args.contains(&"--filename=/assets/".to_string()));
+        assert!(args.contains(&"--file-filter=Imagens | *.png *.jpg".to_string()));
+
+        let args = file_args(Tool::Kdialog, &dialog, DialogKind::Save);
+        assert_eq!(
+            args[..3],
+            ["--getsavefilename", "/assets", "*.png *.jpg|Imagens"]
+        );
+    }
+}
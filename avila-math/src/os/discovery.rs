@@ -0,0 +1,295 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use super::network::{NetworkBuffer, NetworkReader, UdpClient};
+
+/// Identifica datagramas de descoberta ("AVDS")
+const BEACON_MAGIC: u32 = 0x4156_4453;
+const BEACON_VERSION: u8 = 1;
+const MAX_BEACON_SIZE: usize = 1400;
+
+/// Servidor encontrado na rede local
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredServer {
+    /// Endereço de onde o beacon veio
+    pub addr: SocketAddr,
+    /// Payload anunciado (ex: nome da sala, porta do jogo, jogadores)
+    pub payload: Vec<u8>,
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+}
+
+/// Descoberta de servidores na LAN via beacons UDP
+///
+/// Servidores chamam `advertise` para enviar periodicamente um beacon com o
+/// identificador da aplicação e um payload; clientes escutam a mesma porta.
+/// `update` deve ser chamado a cada frame: envia beacons pendentes, recebe os
+/// dos outros e remove servidores que não são vistos há mais que o timeout.
+///
+/// ```no_run
+/// use avila_math::os::Discovery;
+/// use std::time::Duration;
+///
+/// // Servidor
+/// let mut server = Discovery::new("my-game", 47777).unwrap();
+/// server.advertise(b"Sala do Bob;7777".to_vec(), Duration::from_secs(1));
+///
+/// // Cliente
+/// let mut client = Discovery::new("my-game", 47777).unwrap();
+/// loop {
+///     server.update().unwrap();
+///     client.update().unwrap();
+///     for found in client.servers() {
+///         println!("{} -> {:?}", found.addr, found.payload);
+///     }
+/// #   break;
+/// }
+/// ```
+pub struct Discovery {
+    socket: UdpClient,
+    app_id: String,
+    instance_id: u64,
+    target: SocketAddr,
+    timeout: Duration,
+    beacon: Option<Beacon>,
+    servers: HashMap<SocketAddr, DiscoveredServer>,
+}
+
+struct Beacon {
+    packet: Vec<u8>,
+    interval: Duration,
+    last_sent: Option<Instant>,
+}
+
+impl Discovery {
+    /// Escuta a porta em todas as interfaces e anuncia por broadcast na mesma porta
+    ///
+    /// A porta é aberta com reuso de endereço, então várias instâncias na mesma
+    /// máquina podem escutar ao mesmo tempo.
+    pub fn new(app_id: &str, port: u16) -> io::Result<Self> {
+        let discovery = Self::bind(app_id, (Ipv4Addr::UNSPECIFIED, port))?;
+        let port = discovery.local_addr()?.port();
+        Ok(discovery.with_target(SocketAddrV4::new(Ipv4Addr::BROADCAST, port).into()))
+    }
+
+    /// Escuta no endereço dado; o destino dos beacons é o broadcast na porta local
+    pub fn bind<A: ToSocketAddrs>(app_id: &str, addr: A) -> io::Result<Self> {
+        let socket = UdpClient::bind_reusable(addr)?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        let port = socket.local_addr()?.port();
+
+        Ok(Self {
+            socket,
+            app_id: app_id.to_string(),
+            instance_id: RandomState::new().build_hasher().finish(),
+            target: SocketAddrV4::new(Ipv4Addr::BROADCAST, port).into(),
+            timeout: Duration::from_secs(5),
+            beacon: None,
+            servers: HashMap::new(),
+        })
+    }
+
+    /// Define para onde os beacons são enviados (broadcast de sub-rede, unicast, multicast)
+    pub fn with_target(mut self, target: SocketAddr) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Define após quanto tempo sem beacons um servidor é removido (padrão: 5s)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Endereço local do socket de descoberta
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Passa a anunciar este processo com o payload dado a cada `interval`
+    ///
+    /// O payload é truncado no limite de um datagrama seguro (~1400 bytes).
+    pub fn advertise(&mut self, payload: Vec<u8>, interval: Duration) {
+        let mut buffer = NetworkBuffer::with_capacity(64 + payload.len());
+        buffer.write_u32(BEACON_MAGIC);
+        buffer.write_u8(BEACON_VERSION);
+        buffer.write_u64(self.instance_id);
+        buffer.write_string(&self.app_id);
+        let room = MAX_BEACON_SIZE.saturating_sub(buffer.len());
+        buffer.write_bytes(&payload[..payload.len().min(room)]);
+
+        self.beacon = Some(Beacon {
+            packet: buffer.as_bytes().to_vec(),
+            interval,
+            last_sent: None,
+        });
+    }
+
+    /// Para de anunciar
+    pub fn stop_advertising(&mut self) {
+        self.beacon = None;
+    }
+
+    /// Verifica se este processo está anunciando
+    pub fn is_advertising(&self) -> bool {
+        self.beacon.is_some()
+    }
+
+    /// Envia o beacon se estiver na hora, processa os recebidos e remove servidores expirados
+    pub fn update(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+
+        if let Some(beacon) = &mut self.beacon {
+            let due = beacon
+                .last_sent
+                .is_none_or(|sent| now.duration_since(sent) >= beacon.interval);
+            if due {
+                beacon.last_sent = Some(now);
+                // Sem rota para o broadcast não é fatal: tenta de novo no próximo intervalo
+                match self.socket.send_to(&beacon.packet, self.target) {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        let mut buf = [0u8; MAX_BEACON_SIZE + 64];
+        loop {
+            let (size, from) = match self.socket.try_recv_from(&mut buf) {
+                Ok(Some(received)) => received,
+                Ok(None) => break,
+                // ICMP de destino inalcançável de um envio anterior (Windows/Linux)
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => continue,
+                Err(err) => return Err(err),
+            };
+            if let Some(payload) = self.parse_beacon(&buf[..size]) {
+                self.servers
+                    .entry(from)
+                    .and_modify(|server| {
+                        server.payload = payload.to_vec();
+                        server.last_seen = now;
+                    })
+                    .or_insert_with(|| DiscoveredServer {
+                        addr: from,
+                        payload: payload.to_vec(),
+                        first_seen: now,
+                        last_seen: now,
+                    });
+            }
+        }
+
+        let timeout = self.timeout;
+        self.servers
+            .retain(|_, server| now.duration_since(server.last_seen) <= timeout);
+        Ok(())
+    }
+
+    /// Retorna o payload se o datagrama for um beacon válido de outra instância da mesma app
+    fn parse_beacon<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        let mut reader = NetworkReader::new(data);
+        if reader.read_u32().ok()? != BEACON_MAGIC || reader.read_u8().ok()? != BEACON_VERSION {
+            return None;
+        }
+        if reader.read_u64().ok()? == self.instance_id {
+            return None;
+        }
+        if reader.read_str().ok()? != self.app_id {
+            return None;
+        }
+        Some(reader.rest())
+    }
+
+    /// Servidores vistos dentro do timeout, do mais antigo para o mais recente
+    pub fn servers(&self) -> Vec<&DiscoveredServer> {
+        let mut servers: Vec<_> = self.servers.values().collect();
+        servers.sort_by_key(|server| server.first_seen);
+        servers
+    }
+
+    /// Número de servidores conhecidos
+    pub fn server_count(&self) -> usize {
+        self.servers.len()
+    }
+
+    /// Esquece todos os servidores conhecidos
+    pub fn clear(&mut self) {
+        self.servers.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pump(instances: &mut [&mut Discovery]) {
+        for _ in 0..50 {
+            for instance in instances.iter_mut() {
+                instance.update().unwrap();
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+    }
+
+    #[test]
+    fn test_discovery_roundtrip() {
+        let mut client = Discovery::bind("game", "127.0.0.1:0").unwrap();
+        let client_addr = client.local_addr().unwrap();
+
+        let mut server = Discovery::bind("game", "127.0.0.1:0")
+            .unwrap()
+            .with_target(client_addr);
+        server.advertise(b"room-a".to_vec(), Duration::from_millis(5));
+
+        let mut other_app = Discovery::bind("other", "127.0.0.1:0")
+            .unwrap()
+            .with_target(client_addr);
+        other_app.advertise(b"ignored".to_vec(), Duration::from_millis(5));
+
+        pump(&mut [&mut server, &mut other_app, &mut client]);
+
+        let servers = client.servers();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].addr, server.local_addr().unwrap());
+        assert_eq!(servers[0].payload, b"room-a");
+        assert_eq!(server.server_count(), 0);
+    }
+
+    #[test]
+    fn test_discovery_ignores_own_beacons_and_expires() {
+        let mut node = Discovery::bind("game", "127.0.0.1:0")
+            .unwrap()
+            .with_timeout(Duration::from_millis(30));
+        let addr = node.local_addr().unwrap();
+        node = node.with_target(addr);
+        node.advertise(b"self".to_vec(), Duration::ZERO);
+
+        let mut server = Discovery::bind("game", "127.0.0.1:0")
+            .unwrap()
+            .with_target(addr);
+        server.advertise(b"peer".to_vec(), Duration::from_millis(1));
+
+        pump(&mut [&mut server, &mut node]);
+        assert_eq!(node.server_count(), 1);
+        assert_eq!(node.servers()[0].payload, b"peer");
+
+        server.stop_advertising();
+        std::thread::sleep(Duration::from_millis(50));
+        node.update().unwrap();
+        assert_eq!(node.server_count(), 0);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        let discovery = Discovery::bind("game", "127.0.0.1:0").unwrap();
+        assert!(discovery.parse_beacon(b"").is_none());
+        assert!(discovery.parse_beacon(b"not a beacon at all").is_none());
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::threading::{Promise, TaskFuture};
+
+type LookupResult = io::Result<Vec<SocketAddr>>;
+type LookupFn = dyn Fn(&str) -> io::Result<Vec<IpAddr>> + Send + Sync;
+
+/// Resolvedor de nomes com cache e timeout
+///
+/// `to_socket_addrs` bloqueia a thread até o DNS responder, o que pode levar
+/// segundos. O `Resolver` faz cada consulta numa thread própria (que nunca é
+/// esperada: uma consulta travada não segura o drop do resolvedor), guarda os
+/// resultados por um TTL (o sistema não informa o TTL real do registro) e
+/// permite esperar com timeout, receber um `TaskFuture` ou apenas consultar o
+/// cache a cada frame:
+///
+/// ```no_run
+/// use avila_math::os::Resolver;
+/// use std::time::Duration;
+///
+/// let resolver = Resolver::new().with_timeout(Duration::from_secs(2));
+///
+/// // Bloqueante, com timeout
+/// let addrs = resolver.resolve("example.com", 80).unwrap();
+///
+/// // Sem bloquear: dispara a consulta e verifica a cada frame
+/// let lookup = resolver.resolve_async("example.org", 443);
+/// if let Some(addrs) = resolver.lookup_cached("example.org", 443) {
+///     println!("{:?}", addrs);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Resolver {
+    /// Compartilhado com as consultas em andamento
+    state: Arc<Mutex<ResolverState>>,
+    lookup: Arc<LookupFn>,
+    timeout: Duration,
+}
+
+struct ResolverState {
+    ttl: Duration,
+    negative_ttl: Duration,
+    cache: HashMap<String, CacheEntry>,
+    /// Consultas em andamento: cada chamador espera com a sua porta
+    in_flight: HashMap<String, Vec<(u16, Promise<LookupResult>)>>,
+}
+
+struct CacheEntry {
+    result: Result<Vec<IpAddr>, (io::ErrorKind, String)>,
+    expires_at: Instant,
+}
+
+impl CacheEntry {
+    fn to_result(&self, port: u16) -> LookupResult {
+        match &self.result {
+            Ok(ips) => Ok(with_port(ips, port)),
+            Err((kind, message)) => Err(io::Error::new(*kind, message.clone())),
+        }
+    }
+}
+
+fn with_port(ips: &[IpAddr], port: u16) -> Vec<SocketAddr> {
+    ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect()
+}
+
+impl Resolver {
+    /// Cria um resolvedor com TTL de 5 min, cache negativo de 10s e timeout de 5s
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ResolverState {
+                ttl: Duration::from_secs(300),
+                negative_ttl: Duration::from_secs(10),
+                cache: HashMap::new(),
+                in_flight: HashMap::new(),
+            })),
+            lookup: Arc::new(system_lookup),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Resolvedor global do processo, usado pelo `HttpClient`
+    pub fn shared() -> Resolver {
+        static SHARED: OnceLock<Resolver> = OnceLock::new();
+        SHARED.get_or_init(Resolver::new).clone()
+    }
+
+    /// Define por quanto tempo respostas bem-sucedidas ficam no cache
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.state.lock().unwrap().ttl = ttl;
+        self
+    }
+
+    /// Define por quanto tempo falhas ficam no cache (evita repetir consultas lentas)
+    pub fn with_negative_ttl(self, ttl: Duration) -> Self {
+        self.state.lock().unwrap().negative_ttl = ttl;
+        self
+    }
+
+    /// Substitui a consulta ao sistema (`getaddrinfo`) por `lookup`, ex: um
+    /// DNS próprio ou um resolvedor falso em testes
+    pub fn with_lookup<F>(mut self, lookup: F) -> Self
+    where
+        F: Fn(&str) -> io::Result<Vec<IpAddr>> + Send + Sync + 'static,
+    {
+        self.lookup = Arc::new(lookup);
+        self
+    }
+
+    /// Define o timeout usado por `resolve`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Resolve bloqueando no máximo pelo timeout configurado
+    ///
+    /// Em caso de timeout a consulta continua em background e preenche o cache.
+    pub fn resolve(&self, host: &str, port: u16) -> LookupResult {
+        self.resolve_timeout(host, port, self.timeout)
+    }
+
+    /// Resolve bloqueando no máximo por `timeout`
+    pub fn resolve_timeout(&self, host: &str, port: u16, timeout: Duration) -> LookupResult {
+        match self.resolve_async(host, port).wait_timeout(timeout) {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(io::Error::other("resolver thread panicked")),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("DNS lookup for {} timed out", host),
+            )),
+        }
+    }
+
+    /// Dispara a resolução e retorna um future (pronto na hora se estiver no cache)
+    pub fn resolve_async(&self, host: &str, port: u16) -> TaskFuture<LookupResult> {
+        let (future, promise) = TaskFuture::pending();
+
+        if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            promise.complete(Ok(Ok(vec![SocketAddr::new(ip, port)])));
+            return future;
+        }
+
+        let key = host.to_ascii_lowercase();
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(entry) = state.cache.get(&key) {
+            if entry.expires_at > Instant::now() {
+                let result = entry.to_result(port);
+                drop(state);
+                promise.complete(Ok(result));
+                return future;
+            }
+        }
+
+        let waiters = state.in_flight.entry(key.clone()).or_default();
+        waiters.push((port, promise));
+        if waiters.len() == 1 {
+            let shared = Arc::clone(&self.state);
+            let resolve = Arc::clone(&self.lookup);
+            let host = key.clone();
+            drop(state);
+            // Thread solta: ninguém faz join, então uma consulta travada não bloqueia
+            let spawned = thread::Builder::new()
+                .name("avila-dns".to_string())
+                .spawn(move || {
+                    let result = resolve(&host);
+                    finish_lookup(&shared, host, result);
+                });
+            if let Err(err) = spawned {
+                finish_lookup(&self.state, key, Err(err));
+            }
+        }
+
+        future
+    }
+
+    /// Consulta apenas o cache, sem bloquear nem disparar consultas
+    pub fn lookup_cached(&self, host: &str, port: u16) -> Option<Vec<SocketAddr>> {
+        let state = self.state.lock().unwrap();
+        let entry = state.cache.get(&host.to_ascii_lowercase())?;
+        match &entry.result {
+            Ok(ips) if entry.expires_at > Instant::now() => Some(with_port(ips, port)),
+            _ => None,
+        }
+    }
+
+    /// Insere um resultado manualmente (ex: hosts fixos, testes)
+    pub fn insert(&self, host: &str, ips: Vec<IpAddr>) {
+        let mut state = self.state.lock().unwrap();
+        let expires_at = Instant::now() + state.ttl;
+        state.cache.insert(
+            host.to_ascii_lowercase(),
+            CacheEntry {
+                result: Ok(ips),
+                expires_at,
+            },
+        );
+    }
+
+    /// Remove um host do cache
+    pub fn invalidate(&self, host: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.cache.remove(&host.to_ascii_lowercase());
+    }
+
+    /// Esvazia o cache
+    pub fn clear_cache(&self) {
+        self.state.lock().unwrap().cache.clear();
+    }
+
+    /// Número de hosts no cache (incluindo expirados ainda não removidos)
+    pub fn cache_len(&self) -> usize {
+        self.state.lock().unwrap().cache.len()
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Consulta padrão, pelo resolvedor do sistema
+fn system_lookup(host: &str) -> io::Result<Vec<IpAddr>> {
+    (host, 0)
+        .to_socket_addrs()
+        .map(|addrs| {
+            let mut ips: Vec<IpAddr> = Vec::new();
+            for addr in addrs {
+                if !ips.contains(&addr.ip()) {
+                    ips.push(addr.ip());
+                }
+            }
+            ips
+        })
+        .and_then(|ips| {
+            if ips.is_empty() {
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no addresses found for {}", host),
+                ))
+            } else {
+                Ok(ips)
+            }
+        })
+}
+
+/// Guarda o resultado de uma consulta no cache e completa quem esperava por ela
+fn finish_lookup(shared: &Mutex<ResolverState>, host: String, result: io::Result<Vec<IpAddr>>) {
+    let waiters = {
+        let mut state = shared.lock().unwrap();
+        let now = Instant::now();
+        let entry = match result {
+            Ok(ips) => CacheEntry {
+                result: Ok(ips),
+                expires_at: now + state.ttl,
+            },
+            Err(err) => CacheEntry {
+                result: Err((err.kind(), err.to_string())),
+                expires_at: now + state.negative_ttl,
+            },
+        };
+        let waiters = state.in_flight.remove(&host).unwrap_or_default();
+        let waiters: Vec<_> = waiters
+            .into_iter()
+            .map(|(port, promise)| (entry.to_result(port), promise))
+            .collect();
+        state.cache.insert(host, entry);
+        waiters
+    };
+
+    for (result, promise) in waiters {
+        promise.complete(Ok(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_ip_literal_skips_lookup() {
+        let resolver = Resolver::new();
+        let addrs = resolver.resolve("127.0.0.1", 8080).unwrap();
+        assert_eq!(addrs, vec!["127.0.0.1:8080".parse().unwrap()]);
+        let v6 = resolver.resolve("[::1]", 80).unwrap();
+        assert_eq!(v6[0].port(), 80);
+        assert_eq!(resolver.cache_len(), 0);
+    }
+
+    #[test]
+    fn test_localhost_is_cached() {
+        let resolver = Resolver::new();
+        assert!(resolver.lookup_cached("localhost", 80).is_none());
+
+        let addrs = resolver.resolve("LocalHost", 80).unwrap();
+        assert!(addrs.iter().all(|a| a.ip().is_loopback() && a.port() == 80));
+
+        let cached = resolver.lookup_cached("localhost", 9000).unwrap();
+        assert!(cached.iter().all(|a| a.port() == 9000));
+        assert_eq!(resolver.cache_len(), 1);
+
+        resolver.invalidate("localhost");
+        assert!(resolver.lookup_cached("localhost", 80).is_none());
+    }
+
+    #[test]
+    fn test_insert_and_expiry() {
+        let resolver = Resolver::new().with_ttl(Duration::from_millis(20));
+        resolver.insert("game.local", vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5))]);
+
+        let future = resolver.resolve_async("game.local", 7777);
+        assert!(future.is_ready());
+        assert_eq!(
+            future.wait().unwrap().unwrap(),
+            vec!["10.0.0.5:7777".parse().unwrap()]
+        );
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(resolver.lookup_cached("game.local", 7777).is_none());
+    }
+
+    #[test]
+    fn test_failure_is_negatively_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let resolver = Resolver::new().with_lookup(move |host| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no addresses found for {}", host),
+            ))
+        });
+
+        for _ in 0..2 {
+            let err = resolver.resolve("no-such-host.invalid", 80).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(resolver.cache_len(), 1);
+        assert!(resolver.lookup_cached("no-such-host.invalid", 80).is_none());
+    }
+
+    #[test]
+    fn test_hung_lookup_does_not_block_drop() {
+        let (release, hung) = std::sync::mpsc::channel::<()>();
+        let hung = Mutex::new(hung);
+        let resolver = Resolver::new().with_lookup(move |_| {
+            let _ = hung.lock().unwrap().recv();
+            Ok(vec![IpAddr::V4(Ipv4Addr::LOCALHOST)])
+        });
+
+        let result = resolver.resolve_timeout("slow.test", 80, Duration::from_millis(10));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        let started = Instant::now();
+        let background = resolver.clone();
+        drop(resolver);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // A consulta continua e preenche o cache das cópias restantes
+        release.send(()).unwrap();
+        let addrs = background
+            .resolve_async("slow.test", 80)
+            .wait_timeout(Duration::from_secs(5))
+            .ok()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(addrs, vec!["127.0.0.1:80".parse().unwrap()]);
+    }
+}
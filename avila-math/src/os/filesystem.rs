@@ -1,6 +1,12 @@
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use super::hash::{hash_reader, Digest, HashAlgorithm};
+use super::threading::{TaskFuture, ThreadPool};
 
 /// Abstração de filesystem com operações comuns
 pub struct FileSystem;
@@ -27,11 +33,74 @@ impl FileSystem {
         file.write_all(contents.as_ref())
     }
 
+    /// Substitui o conteúdo de um arquivo de forma atômica
+    ///
+    /// Escreve num arquivo temporário no mesmo diretório, faz `fsync` e
+    /// renomeia por cima do destino. Um crash no meio deixa o arquivo antigo
+    /// intacto em vez de um arquivo truncado. Se o destino já existe, suas
+    /// permissões passam para o arquivo novo.
+    pub fn write_atomic<P: AsRef<Path>>(path: P, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp = dir.join(temp_name);
+
+        let result = (|| {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp)?;
+            // Antes de escrever, para o conteúdo nunca ficar mais exposto que o destino
+            if let Ok(metadata) = fs::metadata(path) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.write_all(contents.as_ref())?;
+            file.sync_all()?;
+            drop(file);
+            fs::rename(&temp, path)?;
+            // Persiste a entrada do diretório (o rename em si)
+            #[cfg(unix)]
+            File::open(dir)?.sync_all()?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
     /// Copia arquivo
     pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<u64> {
         fs::copy(from, to)
     }
 
+    /// Calcula o hash de um arquivo lendo em blocos, sem carregá-lo inteiro
+    ///
+    /// ```no_run
+    /// use avila_math::os::{FileSystem, HashAlgorithm};
+    ///
+    /// let digest = FileSystem::hash_file("assets.pak", HashAlgorithm::Sha256).unwrap();
+    /// println!("{}", digest);
+    /// ```
+    pub fn hash_file<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> io::Result<Digest> {
+        hash_reader(File::open(path)?, algorithm)
+    }
+
     /// Move/renomeia arquivo
     pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
         fs::rename(from, to)
@@ -87,6 +156,50 @@ impl FileSystem {
         Ok(FileMetadata::from_std(meta))
     }
 
+    /// Marca/desmarca o arquivo como somente leitura
+    ///
+    /// No unix remove/restaura a escrita para todos (dono, grupo e outros).
+    pub fn set_readonly<P: AsRef<Path>>(path: P, readonly: bool) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut permissions = fs::metadata(path)?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = permissions.mode();
+            permissions.set_mode(if readonly {
+                mode & !0o222
+            } else {
+                mode | 0o200
+            });
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(readonly);
+        fs::set_permissions(path, permissions)
+    }
+
+    /// Define os bits de permissão unix (`0o644`, `0o755`, ...)
+    ///
+    /// No Windows só o bit de escrita do dono é considerado (somente leitura ou não).
+    pub fn set_permissions<P: AsRef<Path>>(path: P, mode: u32) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        }
+        #[cfg(not(unix))]
+        {
+            Self::set_readonly(path, mode & 0o200 == 0)
+        }
+    }
+
+    /// Define a data de modificação (ex: preservar timestamps ao empacotar)
+    pub fn set_modified<P: AsRef<Path>>(path: P, time: SystemTime) -> io::Result<()> {
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_modified(time)
+    }
+
     /// Cria um link simbólico
     #[cfg(unix)]
     pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
@@ -97,9 +210,53 @@ impl FileSystem {
     pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
         std::os::windows::fs::symlink_file(src, dst)
     }
+
+    /// Pool de threads usado pelas operações `*_async`
+    ///
+    /// Separado dos pools de trabalho para que leituras lentas de disco não
+    /// ocupem threads de CPU. Criado no primeiro uso.
+    pub fn io_pool() -> &'static ThreadPool {
+        static IO_POOL: OnceLock<ThreadPool> = OnceLock::new();
+        IO_POOL.get_or_init(|| ThreadPool::new(2))
+    }
+
+    /// Lê um arquivo completo em background
+    ///
+    /// ```no_run
+    /// use avila_math::os::FileSystem;
+    ///
+    /// let texture = FileSystem::read_async("assets/terrain.png");
+    /// // ... continua o frame; depois verifica sem bloquear
+    /// if let Some(Ok(Ok(bytes))) = texture.try_take() {
+    ///     println!("{} bytes", bytes.len());
+    /// }
+    /// ```
+    pub fn read_async<P: AsRef<Path>>(path: P) -> TaskFuture<io::Result<Vec<u8>>> {
+        let path = path.as_ref().to_path_buf();
+        Self::io_pool().submit(move || fs::read(path))
+    }
+
+    /// Lê um arquivo completo como string em background
+    pub fn read_to_string_async<P: AsRef<Path>>(path: P) -> TaskFuture<io::Result<String>> {
+        let path = path.as_ref().to_path_buf();
+        Self::io_pool().submit(move || fs::read_to_string(path))
+    }
+
+    /// Escreve um arquivo (sobrescreve) em background
+    pub fn write_async<P: AsRef<Path>>(
+        path: P,
+        contents: impl Into<Vec<u8>>,
+    ) -> TaskFuture<io::Result<()>> {
+        let path = path.as_ref().to_path_buf();
+        let contents = contents.into();
+        Self::io_pool().submit(move || fs::write(path, contents))
+    }
 }
 
 /// Metadados de arquivo
+///
+/// Timestamps são `None` quando o sistema/filesystem não os registra (ex:
+/// `created` em vários filesystems Linux antigos).
 #[derive(Debug, Clone)]
 pub struct FileMetadata {
     pub size: u64,
@@ -107,18 +264,42 @@ pub struct FileMetadata {
     pub is_dir: bool,
     pub is_symlink: bool,
     pub readonly: bool,
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Bits de permissão unix (`0o755`, etc.); `None` no Windows
+    pub mode: Option<u32>,
 }
 
 impl FileMetadata {
     fn from_std(meta: fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(meta.permissions().mode() & 0o7777)
+        };
+        #[cfg(not(unix))]
+        let mode = None;
+
         Self {
             size: meta.len(),
             is_file: meta.is_file(),
             is_dir: meta.is_dir(),
             is_symlink: meta.is_symlink(),
             readonly: meta.permissions().readonly(),
+            modified: meta.modified().ok(),
+            accessed: meta.accessed().ok(),
+            created: meta.created().ok(),
+            mode,
         }
     }
+
+    /// Verifica se o arquivo foi modificado depois de `time` (ex: cache de assets)
+    ///
+    /// Retorna `true` se o timestamp não estiver disponível.
+    pub fn is_newer_than(&self, time: SystemTime) -> bool {
+        self.modified.is_none_or(|modified| modified > time)
+    }
 }
 
 /// File handle com buffer e operações convenientes
@@ -213,6 +394,292 @@ impl Seek for FileHandle {
     }
 }
 
+/// Arquivo mapeado em memória, somente leitura
+///
+/// O conteúdo é acessado como `&[u8]` sem copiar para o heap; páginas são
+/// carregadas sob demanda pelo sistema. O arquivo não deve ser truncado ou
+/// modificado por outro processo enquanto estiver mapeado.
+pub struct MappedFile {
+    ptr: *const u8,
+    len: usize,
+    path: PathBuf,
+    #[cfg(windows)]
+    mapping: mmap::Handle,
+}
+
+// O mapeamento é somente leitura e não tem estado compartilhado mutável
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Mapeia o arquivo inteiro
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path.as_ref())?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file too large to map"))?;
+        let path = path.as_ref().to_path_buf();
+
+        // Mapeamentos vazios são inválidos em todos os sistemas
+        if len == 0 {
+            return Ok(Self {
+                ptr: std::ptr::NonNull::dangling().as_ptr(),
+                len,
+                path,
+                #[cfg(windows)]
+                mapping: mmap::Handle::null(),
+            });
+        }
+
+        #[cfg(unix)]
+        let ptr = mmap::map(&file, len)?;
+        #[cfg(windows)]
+        let (ptr, mapping) = mmap::map(&file)?;
+        Ok(Self {
+            ptr,
+            len,
+            path,
+            #[cfg(windows)]
+            mapping,
+        })
+    }
+
+    /// Conteúdo do arquivo
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Caminho do arquivo mapeado
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl std::ops::Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl std::fmt::Debug for MappedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedFile")
+            .field("path", &self.path)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            unsafe { mmap::unmap(self.ptr, self.len) };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod mmap {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+
+    pub fn map(file: &File, len: usize) -> io::Result<*const u8> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ptr as *const u8)
+    }
+
+    pub unsafe fn unmap(ptr: *const u8, len: usize) {
+        libc::munmap(ptr as *mut libc::c_void, len);
+    }
+}
+
+#[cfg(windows)]
+mod mmap {
+    use std::fs::File;
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::Memory::{
+        CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_READ,
+        MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READONLY,
+    };
+
+    /// Handle do objeto de mapeamento, fechado no drop
+    pub struct Handle(HANDLE);
+
+    impl Handle {
+        pub fn null() -> Self {
+            Self(ptr::null_mut())
+        }
+    }
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            if !self.0.is_null() {
+                unsafe { CloseHandle(self.0) };
+            }
+        }
+    }
+
+    pub fn map(file: &File) -> io::Result<(*const u8, Handle)> {
+        let mapping = unsafe {
+            CreateFileMappingW(
+                file.as_raw_handle() as HANDLE,
+                ptr::null(),
+                PAGE_READONLY,
+                0,
+                0,
+                ptr::null(),
+            )
+        };
+        if mapping.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let mapping = Handle(mapping);
+
+        let view = unsafe { MapViewOfFile(mapping.0, FILE_MAP_READ, 0, 0, 0) };
+        if view.Value.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((view.Value as *const u8, mapping))
+    }
+
+    pub unsafe fn unmap(ptr: *const u8, _len: usize) {
+        UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS {
+            Value: ptr as *mut _,
+        });
+    }
+}
+
+/// Lock consultivo de arquivo entre processos
+///
+/// Protege arquivos de save/config contra escrita concorrente de outros
+/// processos que também usem o lock. É consultivo: processos que ignoram o
+/// lock ainda podem abrir o arquivo. O lock é liberado no drop.
+///
+/// ```no_run
+/// use avila_math::os::{FileLock, FileSystem};
+///
+/// let _lock = FileLock::exclusive("save.lock").unwrap();
+/// FileSystem::write_atomic("save.dat", b"...").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn open_lock_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+
+    /// Bloqueia até obter o lock exclusivo (cria o arquivo se não existir)
+    pub fn exclusive<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = Self::open_lock_file(path.as_ref())?;
+        file.lock()?;
+        Ok(Self {
+            file,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Bloqueia até obter um lock compartilhado (vários leitores ao mesmo tempo)
+    pub fn shared<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = Self::open_lock_file(path.as_ref())?;
+        file.lock_shared()?;
+        Ok(Self {
+            file,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Tenta o lock exclusivo sem bloquear; `None` se outro processo o detém
+    pub fn try_exclusive<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let file = Self::open_lock_file(path.as_ref())?;
+        Self::try_with(file.try_lock(), file, path.as_ref())
+    }
+
+    /// Tenta um lock compartilhado sem bloquear; `None` se há um lock exclusivo
+    pub fn try_shared<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let file = Self::open_lock_file(path.as_ref())?;
+        Self::try_with(file.try_lock_shared(), file, path.as_ref())
+    }
+
+    fn try_with(
+        result: Result<(), fs::TryLockError>,
+        file: File,
+        path: &Path,
+    ) -> io::Result<Option<Self>> {
+        match result {
+            Ok(()) => Ok(Some(Self {
+                file,
+                path: path.to_path_buf(),
+            })),
+            Err(fs::TryLockError::WouldBlock) => Ok(None),
+            Err(fs::TryLockError::Error(err)) => Err(err),
+        }
+    }
+
+    /// Caminho do arquivo de lock
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Arquivo travado (pode ser lido/escrito enquanto o lock é mantido)
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Libera o lock explicitamente
+    pub fn unlock(self) -> io::Result<()> {
+        self.file.unlock()
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Fechar o arquivo também libera; o erro aqui não tem o que fazer
+        let _ = self.file.unlock();
+    }
+}
+
 /// Path utilities
 pub struct PathUtil;
 
@@ -259,77 +726,385 @@ impl PathUtil {
     }
 }
 
+/// Percorre `root` recursivamente; atalho para `DirectoryWalker::new`
+///
+/// ```no_run
+/// use avila_math::os::walk;
+///
+/// for entry in walk("assets").filter_glob("**/*.png") {
+///     let entry = entry.unwrap();
+///     println!("{} ({} bytes)", entry.path().display(), entry.metadata().size);
+/// }
+/// ```
+pub fn walk<P: AsRef<Path>>(root: P) -> DirectoryWalker {
+    DirectoryWalker::new(root)
+}
+
+/// Entrada encontrada por um `DirectoryWalker`
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    path: PathBuf,
+    depth: usize,
+    metadata: FileMetadata,
+}
+
+impl DirEntry {
+    /// Caminho completo (`root` + caminho relativo)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Profundidade a partir da raiz (filhos diretos têm profundidade 1)
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Metadados da entrada (do alvo, se symlinks forem seguidos)
+    pub fn metadata(&self) -> &FileMetadata {
+        &self.metadata
+    }
+
+    /// Nome do arquivo/diretório
+    pub fn file_name(&self) -> Option<&str> {
+        self.path.file_name().and_then(|n| n.to_str())
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.metadata.is_file
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.metadata.is_dir
+    }
+
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+}
+
+type EntryFilter = Box<dyn FnMut(&DirEntry) -> bool + Send>;
+
 /// Directory walker - itera recursivamente por diretórios
+///
+/// Iterador em profundidade (pré-ordem) sobre `io::Result<DirEntry>`. Erros
+/// ao ler um subdiretório aparecem como um item `Err` e a iteração continua
+/// com o restante da árvore.
 pub struct DirectoryWalker {
-    stack: Vec<PathBuf>,
-    recursive: bool,
+    root: PathBuf,
+    min_depth: usize,
+    max_depth: usize,
+    follow_symlinks: bool,
+    sorted: bool,
+    files_only: bool,
+    globs: Vec<Vec<String>>,
+    extensions: Vec<String>,
+    prune: Option<EntryFilter>,
+    started: bool,
+    /// Entradas ainda não visitadas de cada diretório aberto, com a profundidade
+    stack: Vec<(std::vec::IntoIter<io::Result<fs::DirEntry>>, usize)>,
+    /// Diretórios já visitados via symlink (evita ciclos)
+    visited: Vec<PathBuf>,
+    pending_error: Option<io::Error>,
 }
 
 impl DirectoryWalker {
-    pub fn new<P: AsRef<Path>>(root: P, recursive: bool) -> io::Result<Self> {
-        let root = root.as_ref().to_path_buf();
-        if !root.is_dir() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Path is not a directory",
-            ));
+    /// Cria um walker recursivo, sem seguir symlinks
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            min_depth: 1,
+            max_depth: usize::MAX,
+            follow_symlinks: false,
+            sorted: false,
+            files_only: false,
+            globs: Vec::new(),
+            extensions: Vec::new(),
+            prune: None,
+            started: false,
+            stack: Vec::new(),
+            visited: Vec::new(),
+            pending_error: None,
         }
+    }
 
-        Ok(Self {
-            stack: vec![root],
-            recursive,
-        })
+    /// Com `false`, lista só os filhos diretos da raiz
+    pub fn recursive(self, recursive: bool) -> Self {
+        self.max_depth(if recursive { usize::MAX } else { 1 })
+    }
+
+    /// Não desce além desta profundidade (filhos diretos da raiz = 1)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Não retorna entradas mais rasas que esta profundidade (ainda as percorre)
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Entra em diretórios apontados por symlinks (padrão: não)
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Visita as entradas de cada diretório em ordem alfabética
+    pub fn sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    /// Retorna apenas arquivos (diretórios ainda são percorridos)
+    pub fn files_only(mut self) -> Self {
+        self.files_only = true;
+        self
+    }
+
+    /// Retorna apenas entradas cujo caminho relativo à raiz casa com o glob
+    ///
+    /// Suporta `*`, `?`, `[abc]`/`[a-z]`/`[!a]` dentro de um segmento e `**`
+    /// para qualquer número de diretórios. Separadores são sempre `/`.
+    /// Chamadas repetidas aceitam entradas que casem com qualquer um dos globs.
+    pub fn filter_glob(mut self, pattern: &str) -> Self {
+        self.globs.push(
+            pattern
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+        );
+        self
+    }
+
+    /// Retorna apenas arquivos com a extensão dada (sem o ponto, sem diferenciar maiúsculas)
+    pub fn filter_extension(mut self, extension: &str) -> Self {
+        self.extensions
+            .push(extension.trim_start_matches('.').to_ascii_lowercase());
+        self
     }
 
-    pub fn walk<F>(&mut self, mut callback: F) -> io::Result<()>
+    /// Não retorna nem percorre entradas rejeitadas (ex: pular `.git`, `target`)
+    pub fn filter_entry<F>(mut self, filter: F) -> Self
     where
-        F: FnMut(&Path, &FileMetadata) -> io::Result<bool>,
+        F: FnMut(&DirEntry) -> bool + Send + 'static,
     {
-        while let Some(path) = self.stack.pop() {
-            let entries = fs::read_dir(&path)?;
+        self.prune = Some(Box::new(filter));
+        self
+    }
 
-            for entry in entries {
-                let entry = entry?;
-                let path = entry.path();
-                let meta = FileMetadata::from_std(entry.metadata()?);
+    fn read_dir(&self, dir: &Path) -> io::Result<std::vec::IntoIter<io::Result<fs::DirEntry>>> {
+        let mut entries: Vec<_> = fs::read_dir(dir)?.collect();
+        if self.sorted {
+            entries.sort_by(|a, b| match (a, b) {
+                (Ok(a), Ok(b)) => a.file_name().cmp(&b.file_name()),
+                _ => std::cmp::Ordering::Equal,
+            });
+        }
+        Ok(entries.into_iter())
+    }
 
-                if callback(&path, &meta)? {
-                    if meta.is_dir && self.recursive {
-                        self.stack.push(path);
-                    }
-                }
+    fn matches(&self, entry: &DirEntry) -> bool {
+        if entry.depth < self.min_depth || (self.files_only && !entry.is_file()) {
+            return false;
+        }
+        if !self.extensions.is_empty() {
+            let extension = entry
+                .path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_ascii_lowercase);
+            if !entry.is_file() || !extension.is_some_and(|e| self.extensions.contains(&e)) {
+                return false;
             }
         }
+        if !self.globs.is_empty() {
+            let Ok(relative) = entry.path.strip_prefix(&self.root) else {
+                return false;
+            };
+            let segments: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            return self
+                .globs
+                .iter()
+                .any(|glob| glob_match_segments(glob, &segments));
+        }
+        true
+    }
 
-        Ok(())
+    /// Decide se deve descer no diretório (evitando ciclos de symlinks)
+    fn should_descend(&mut self, entry: &DirEntry) -> bool {
+        if !entry.is_dir() || entry.depth >= self.max_depth {
+            return false;
+        }
+        if !entry.metadata.is_symlink {
+            return true;
+        }
+        match fs::canonicalize(&entry.path) {
+            Ok(target) if !self.visited.contains(&target) => {
+                self.visited.push(target);
+                true
+            }
+            _ => false,
+        }
     }
 }
 
-/// File watcher para monitorar mudanças (simplificado)
-pub struct FileWatcher {
-    path: PathBuf,
-    last_modified: Option<std::time::SystemTime>,
-}
+impl Iterator for DirectoryWalker {
+    type Item = io::Result<DirEntry>;
 
-impl FileWatcher {
-    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let path = path.as_ref().to_path_buf();
-        let last_modified = fs::metadata(&path)?.modified().ok();
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.pending_error.take() {
+            return Some(Err(err));
+        }
+        if !self.started {
+            self.started = true;
+            if self.max_depth == 0 {
+                return None;
+            }
+            if self.follow_symlinks {
+                if let Ok(root) = fs::canonicalize(&self.root) {
+                    self.visited.push(root);
+                }
+            }
+            match self.read_dir(&self.root.clone()) {
+                Ok(entries) => self.stack.push((entries, 1)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
 
-        Ok(Self {
-            path,
-            last_modified,
-        })
+        loop {
+            let (entries, depth) = self.stack.last_mut()?;
+            let depth = *depth;
+            let Some(entry) = entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(meta) => {
+                    let mut metadata = FileMetadata::from_std(meta);
+                    if metadata.is_symlink && self.follow_symlinks {
+                        // Symlinks quebrados mantêm os metadados do próprio link
+                        if let Ok(target) = fs::metadata(&path) {
+                            metadata = FileMetadata::from_std(target);
+                            metadata.is_symlink = true;
+                        }
+                    }
+                    metadata
+                }
+                Err(err) => return Some(Err(err)),
+            };
+            let entry = DirEntry {
+                path,
+                depth,
+                metadata,
+            };
+
+            if let Some(prune) = &mut self.prune {
+                if !prune(&entry) {
+                    continue;
+                }
+            }
+
+            if self.should_descend(&entry) {
+                match self.read_dir(&entry.path) {
+                    Ok(children) => self.stack.push((children, depth + 1)),
+                    // O erro vem depois da própria entrada
+                    Err(err) => self.pending_error = Some(err),
+                }
+            }
+
+            if self.matches(&entry) {
+                return Some(Ok(entry));
+            }
+            if let Some(err) = self.pending_error.take() {
+                return Some(Err(err));
+            }
+        }
     }
+}
 
-    pub fn has_changed(&mut self) -> io::Result<bool> {
-        let current_modified = fs::metadata(&self.path)?.modified().ok();
+/// Verifica se um caminho relativo (com `/`) casa com um glob
+///
+/// Mesma sintaxe de `DirectoryWalker::filter_glob`.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<String> = pattern
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    let path: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    glob_match_segments(&pattern, &path)
+}
 
-        if current_modified != self.last_modified {
-            self.last_modified = current_modified;
-            Ok(true)
-        } else {
-            Ok(false)
+fn glob_match_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| glob_match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                let pattern: Vec<char> = first.chars().collect();
+                let segment: Vec<char> = segment.chars().collect();
+                glob_match_segment(&pattern, &segment) && glob_match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn glob_match_segment(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| glob_match_segment(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && glob_match_segment(&pattern[1..], &text[1..]),
+        Some('[') => {
+            let Some(close) = pattern
+                .iter()
+                .skip(2)
+                .position(|&c| c == ']')
+                .map(|i| i + 2)
+            else {
+                // Sem `]`: trata `[` como literal
+                return text.first() == Some(&'[') && glob_match_segment(&pattern[1..], &text[1..]);
+            };
+            let Some(&c) = text.first() else {
+                return false;
+            };
+            let class = &pattern[1..close];
+            let (negated, class) = match class.first() {
+                Some('!') | Some('^') => (true, &class[1..]),
+                _ => (false, class),
+            };
+            let mut matched = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    matched |= class[i] <= c && c <= class[i + 2];
+                    i += 3;
+                } else {
+                    matched |= class[i] == c;
+                    i += 1;
+                }
+            }
+            matched != negated && glob_match_segment(&pattern[close + 1..], &text[1..])
+        }
+        Some(&literal) => {
+            text.first() == Some(&literal) && glob_match_segment(&pattern[1..], &text[1..])
         }
     }
 }
@@ -371,6 +1146,214 @@ mod tests {
         FileSystem::remove_file(path).unwrap();
     }
 
+    #[test]
+    fn test_async_read_write() {
+        let path = std::env::temp_dir().join(format!("avila_async_{}.txt", std::process::id()));
+
+        FileSystem::write_async(&path, "async content")
+            .wait()
+            .unwrap()
+            .unwrap();
+
+        let text = FileSystem::read_to_string_async(&path);
+        let bytes = FileSystem::read_async(&path).then(|result| result.map(|b| b.len()));
+        assert_eq!(text.wait().unwrap().unwrap(), "async content");
+        assert_eq!(bytes.wait().unwrap().unwrap(), 13);
+
+        FileSystem::remove_file(&path).unwrap();
+        let missing = FileSystem::read_async(&path).wait().unwrap();
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_metadata_times_and_permissions() {
+        let path = std::env::temp_dir().join(format!("avila_meta_{}.txt", std::process::id()));
+        let before = SystemTime::now() - std::time::Duration::from_secs(5);
+        FileSystem::write(&path, "data").unwrap();
+
+        let meta = FileSystem::metadata(&path).unwrap();
+        assert_eq!(meta.size, 4);
+        assert!(meta.modified.unwrap() > before);
+        assert!(meta.accessed.is_some());
+        assert!(meta.is_newer_than(before));
+
+        let old = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        FileSystem::set_modified(&path, old).unwrap();
+        assert_eq!(FileSystem::metadata(&path).unwrap().modified, Some(old));
+
+        FileSystem::set_readonly(&path, true).unwrap();
+        assert!(FileSystem::metadata(&path).unwrap().readonly);
+        FileSystem::set_readonly(&path, false).unwrap();
+        assert!(!FileSystem::metadata(&path).unwrap().readonly);
+
+        #[cfg(unix)]
+        {
+            FileSystem::set_permissions(&path, 0o640).unwrap();
+            assert_eq!(FileSystem::metadata(&path).unwrap().mode, Some(0o640));
+        }
+
+        FileSystem::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("avila_hash_{}.bin", std::process::id()));
+        let data = vec![0x5Au8; 200_000];
+        FileSystem::write(&path, &data).unwrap();
+
+        for algorithm in [
+            HashAlgorithm::Crc32,
+            HashAlgorithm::XxHash64,
+            HashAlgorithm::Sha256,
+        ] {
+            assert_eq!(
+                FileSystem::hash_file(&path, algorithm).unwrap(),
+                crate::os::hash::hash_bytes(&data, algorithm)
+            );
+        }
+
+        FileSystem::remove_file(&path).unwrap();
+        assert!(FileSystem::hash_file(&path, HashAlgorithm::Crc32).is_err());
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("avila_atomic_{}", std::process::id()));
+        FileSystem::create_dir_all(&dir).unwrap();
+        let path = dir.join("save.dat");
+
+        FileSystem::write_atomic(&path, "v1").unwrap();
+        FileSystem::write_atomic(&path, "v2").unwrap();
+        assert_eq!(FileSystem::read_to_string(&path).unwrap(), "v2");
+        // Nenhum temporário fica para trás
+        assert_eq!(FileSystem::read_dir(&dir).unwrap(), vec![path.clone()]);
+
+        // Substituir um arquivo privado não alarga as permissões
+        #[cfg(unix)]
+        {
+            FileSystem::set_permissions(&path, 0o600).unwrap();
+            FileSystem::write_atomic(&path, "v3").unwrap();
+            assert_eq!(FileSystem::metadata(&path).unwrap().mode, Some(0o600));
+            assert_eq!(FileSystem::read_to_string(&path).unwrap(), "v3");
+        }
+
+        assert!(FileSystem::write_atomic(dir.join("missing/x"), "v").is_err());
+        FileSystem::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_file() {
+        let path = std::env::temp_dir().join(format!("avila_mmap_{}.bin", std::process::id()));
+        FileSystem::write(&path, b"mapped bytes").unwrap();
+        let mapped = MappedFile::open(&path).unwrap();
+        assert_eq!(&mapped[..], b"mapped bytes");
+        assert_eq!(mapped.len(), 12);
+        drop(mapped);
+
+        FileSystem::write(&path, b"").unwrap();
+        assert!(MappedFile::open(&path).unwrap().is_empty());
+        FileSystem::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock() {
+        let path = std::env::temp_dir().join(format!("avila_lock_{}.lock", std::process::id()));
+
+        let lock = FileLock::exclusive(&path).unwrap();
+        assert!(FileLock::try_exclusive(&path).unwrap().is_none());
+        assert!(FileLock::try_shared(&path).unwrap().is_none());
+        drop(lock);
+
+        let a = FileLock::try_shared(&path).unwrap().unwrap();
+        let b = FileLock::shared(&path).unwrap();
+        assert!(FileLock::try_exclusive(&path).unwrap().is_none());
+        a.unlock().unwrap();
+        drop(b);
+        assert!(FileLock::try_exclusive(&path).unwrap().is_some());
+
+        FileSystem::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("**/*.png", "a.png"));
+        assert!(glob_match("**/*.png", "textures/ui/a.png"));
+        assert!(!glob_match("**/*.png", "textures/a.jpg"));
+        assert!(glob_match("textures/*.png", "textures/a.png"));
+        assert!(!glob_match("textures/*.png", "textures/ui/a.png"));
+        assert!(glob_match(
+            "shaders/**/lit_?.wgsl",
+            "shaders/pbr/deep/lit_a.wgsl"
+        ));
+        assert!(glob_match("level[0-9].map", "level3.map"));
+        assert!(!glob_match("level[!0-9].map", "level3.map"));
+        assert!(glob_match("**", "any/thing"));
+    }
+
+    #[test]
+    fn test_directory_walker() {
+        let root = std::env::temp_dir().join(format!("avila_walk_{}", std::process::id()));
+        let _ = FileSystem::remove_dir_all(&root);
+        FileSystem::create_dir_all(root.join("textures/ui")).unwrap();
+        FileSystem::create_dir_all(root.join(".git")).unwrap();
+        for file in [
+            "a.png",
+            "textures/b.PNG",
+            "textures/ui/c.png",
+            "textures/d.jpg",
+            ".git/e.png",
+        ] {
+            FileSystem::write(root.join(file), "x").unwrap();
+        }
+        let relative = |walker: DirectoryWalker| -> Vec<String> {
+            walker
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    let path = entry.path().strip_prefix(&root).unwrap().to_path_buf();
+                    path.to_string_lossy().replace('\\', "/")
+                })
+                .collect()
+        };
+
+        let all = relative(walk(&root).sorted());
+        assert_eq!(
+            all,
+            [
+                ".git",
+                ".git/e.png",
+                "a.png",
+                "textures",
+                "textures/b.PNG",
+                "textures/d.jpg",
+                "textures/ui",
+                "textures/ui/c.png"
+            ]
+        );
+
+        let pngs = relative(
+            walk(&root)
+                .sorted()
+                .filter_extension("png")
+                .filter_entry(|e| e.file_name() != Some(".git")),
+        );
+        assert_eq!(pngs, ["a.png", "textures/b.PNG", "textures/ui/c.png"]);
+
+        let globbed = relative(walk(&root).sorted().filter_glob("textures/**/*.png"));
+        assert_eq!(globbed, ["textures/ui/c.png"]);
+
+        let shallow = relative(walk(&root).sorted().recursive(false).files_only());
+        assert_eq!(shallow, ["a.png"]);
+
+        let deep = relative(walk(&root).sorted().min_depth(3));
+        assert_eq!(deep, ["textures/ui/c.png"]);
+
+        let missing: Vec<_> = walk(root.join("missing")).collect();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].is_err());
+
+        FileSystem::remove_dir_all(&root).unwrap();
+    }
+
     #[test]
     fn test_path_util() {
         let path = PathBuf::from("test/dir/file.txt");
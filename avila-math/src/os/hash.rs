@@ -0,0 +1,562 @@
+//! Checksums e hashes
+//!
+//! Três algoritmos sem dependências, todos com interface incremental:
+//!
+//! - **CRC32** (IEEE, o mesmo de zip/gzip/png): checagem rápida de integridade
+//! - **xxHash64**: hash não criptográfico muito rápido, para deduplicação e
+//!   chaves de cache
+//! - **SHA-256**: hash criptográfico, para verificar patches e downloads
+//!
+//! ```
+//! use avila_math::os::hash::{self, HashAlgorithm};
+//!
+//! assert_eq!(hash::crc32(b"123456789"), 0xCBF4_3926);
+//! let digest = hash::hash_bytes(b"abc", HashAlgorithm::Sha256);
+//! assert!(digest.to_hex().starts_with("ba7816bf"));
+//! ```
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Algoritmo de hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Crc32,
+    XxHash64,
+    Sha256,
+}
+
+/// Resultado de um hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Digest {
+    Crc32(u32),
+    XxHash64(u64),
+    Sha256([u8; 32]),
+}
+
+impl Digest {
+    /// Algoritmo que gerou o digest
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Digest::Crc32(_) => HashAlgorithm::Crc32,
+            Digest::XxHash64(_) => HashAlgorithm::XxHash64,
+            Digest::Sha256(_) => HashAlgorithm::Sha256,
+        }
+    }
+
+    /// Bytes do digest (big-endian para os inteiros, como na forma hexadecimal)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Digest::Crc32(value) => value.to_be_bytes().to_vec(),
+            Digest::XxHash64(value) => value.to_be_bytes().to_vec(),
+            Digest::Sha256(bytes) => bytes.to_vec(),
+        }
+    }
+
+    /// Representação hexadecimal em minúsculas
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Digest::Crc32(value) => write!(f, "{:08x}", value),
+            Digest::XxHash64(value) => write!(f, "{:016x}", value),
+            Digest::Sha256(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+        }
+    }
+}
+
+// ============================================================================
+// CRC32
+// ============================================================================
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC32 incremental (polinômio IEEE 802.3)
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = self.state;
+        for &byte in data {
+            crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+        self.state = crc;
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC32 de um buffer
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+// ============================================================================
+// xxHash64
+// ============================================================================
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+fn xxh_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn xxh_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ xxh_round(0, value))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// xxHash64 incremental
+///
+/// Também implementa `std::hash::Hasher`, então serve de hasher para
+/// `HashMap` via `BuildHasherDefault<XxHash64>`.
+#[derive(Debug, Clone)]
+pub struct XxHash64 {
+    seed: u64,
+    acc: [u64; 4],
+    buffer: [u8; 32],
+    buffered: usize,
+    total_len: u64,
+}
+
+impl XxHash64 {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            buffer: [0; 32],
+            buffered: 0,
+            total_len: 0,
+        }
+    }
+
+    fn consume_stripe(acc: &mut [u64; 4], stripe: &[u8]) {
+        for (lane, acc) in acc.iter_mut().enumerate() {
+            *acc = xxh_round(*acc, read_u64(&stripe[lane * 8..]));
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if self.buffered > 0 {
+            let take = data.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 32 {
+                return;
+            }
+            Self::consume_stripe(&mut self.acc, &self.buffer);
+            self.buffered = 0;
+        }
+
+        let mut stripes = data.chunks_exact(32);
+        for stripe in &mut stripes {
+            Self::consume_stripe(&mut self.acc, stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn finish(&self) -> u64 {
+        let [v1, v2, v3, v4] = self.acc;
+        let mut hash = if self.total_len >= 32 {
+            let hash = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.acc.iter().fold(hash, |hash, &v| xxh_merge(hash, v))
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+        hash = hash.wrapping_add(self.total_len);
+
+        let mut tail = &self.buffer[..self.buffered];
+        while tail.len() >= 8 {
+            hash ^= xxh_round(0, read_u64(tail));
+            hash = hash
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            tail = &tail[8..];
+        }
+        if tail.len() >= 4 {
+            let value = u32::from_le_bytes(tail[..4].try_into().unwrap()) as u64;
+            hash ^= value.wrapping_mul(PRIME64_1);
+            hash = hash
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            tail = &tail[4..];
+        }
+        for &byte in tail {
+            hash ^= (byte as u64).wrapping_mul(PRIME64_5);
+            hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(PRIME64_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(PRIME64_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+impl Default for XxHash64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::hash::Hasher for XxHash64 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        XxHash64::finish(self)
+    }
+}
+
+/// xxHash64 de um buffer
+pub fn xxhash64(data: &[u8], seed: u64) -> u64 {
+    let mut hasher = XxHash64::with_seed(seed);
+    hasher.update(data);
+    hasher.finish()
+}
+
+// ============================================================================
+// SHA-256
+// ============================================================================
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 incremental (FIPS 180-4)
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; 64],
+    buffered: usize,
+    total_len: u64,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Self {
+            state: SHA256_INIT,
+            buffer: [0; 64],
+            buffered: 0,
+            total_len: 0,
+        }
+    }
+
+    fn compress(state: &mut [u32; 8], block: &[u8]) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (state, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if self.buffered > 0 {
+            let take = data.len().min(64 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 64 {
+                return;
+            }
+            Self::compress(&mut self.state, &self.buffer);
+            self.buffered = 0;
+        }
+
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            Self::compress(&mut self.state, block);
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn finish(&self) -> [u8; 32] {
+        let mut state = self.state;
+        let mut block = [0u8; 128];
+        block[..self.buffered].copy_from_slice(&self.buffer[..self.buffered]);
+        block[self.buffered] = 0x80;
+
+        // Padding + tamanho em bits; pode ocupar um ou dois blocos
+        let padded = if self.buffered < 56 { 64 } else { 128 };
+        block[padded - 8..padded].copy_from_slice(&(self.total_len * 8).to_be_bytes());
+        for chunk in block[..padded].chunks_exact(64) {
+            Self::compress(&mut state, chunk);
+        }
+
+        let mut digest = [0u8; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// SHA-256 de um buffer
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+// ============================================================================
+// Hasher genérico
+// ============================================================================
+
+/// Hasher incremental para qualquer `HashAlgorithm`
+///
+/// Implementa `Write`, então pode ser usado com `io::copy` ou como destino
+/// de um `Compressor`/`Decompressor`.
+#[derive(Debug, Clone)]
+pub enum StreamHasher {
+    Crc32(Crc32),
+    XxHash64(XxHash64),
+    Sha256(Box<Sha256>),
+}
+
+impl StreamHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Crc32 => StreamHasher::Crc32(Crc32::new()),
+            HashAlgorithm::XxHash64 => StreamHasher::XxHash64(XxHash64::new()),
+            HashAlgorithm::Sha256 => StreamHasher::Sha256(Box::default()),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            StreamHasher::Crc32(hasher) => hasher.update(data),
+            StreamHasher::XxHash64(hasher) => hasher.update(data),
+            StreamHasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finish(&self) -> Digest {
+        match self {
+            StreamHasher::Crc32(hasher) => Digest::Crc32(hasher.finish()),
+            StreamHasher::XxHash64(hasher) => Digest::XxHash64(hasher.finish()),
+            StreamHasher::Sha256(hasher) => Digest::Sha256(hasher.finish()),
+        }
+    }
+}
+
+impl Write for StreamHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hash de um buffer com o algoritmo escolhido
+pub fn hash_bytes(data: &[u8], algorithm: HashAlgorithm) -> Digest {
+    let mut hasher = StreamHasher::new(algorithm);
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// Hash de tudo que o reader produzir, em blocos de 64 KiB
+pub fn hash_reader<R: Read>(mut reader: R, algorithm: HashAlgorithm) -> io::Result<Digest> {
+    let mut hasher = StreamHasher::new(algorithm);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn test_xxhash64() {
+        assert_eq!(xxhash64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxhash64(b"a", 0), 0xD24E_C4F1_A98C_6E5B);
+        assert_eq!(xxhash64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        assert_ne!(xxhash64(b"abc", 1), xxhash64(b"abc", 0));
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            Digest::Sha256(sha256(b"")).to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            Digest::Sha256(sha256(b"abc")).to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // 56 bytes: o padding não cabe no primeiro bloco
+        assert_eq!(
+            Digest::Sha256(sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ))
+            .to_hex(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_incremental_matches_one_shot() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        for algorithm in [
+            HashAlgorithm::Crc32,
+            HashAlgorithm::XxHash64,
+            HashAlgorithm::Sha256,
+        ] {
+            let expected = hash_bytes(&data, algorithm);
+            let mut hasher = StreamHasher::new(algorithm);
+            for chunk in data.chunks(13) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finish(), expected);
+            assert_eq!(hash_reader(&data[..], algorithm).unwrap(), expected);
+            assert_eq!(expected.algorithm(), algorithm);
+        }
+        assert_eq!(Digest::Crc32(0xab).to_hex(), "000000ab");
+    }
+}
@@ -0,0 +1,1928 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::dns::Resolver;
+use super::filesystem::{FileHandle, FileSystem};
+use super::network::{parse_query, percent_decode, TcpClient, TcpServer, Url};
+use super::poll::{Interest, Poller};
+use super::threading::{CancellationToken, ThreadPool};
+
+/// Timeout padrão de conexão/leitura do `HttpClient`
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+const USER_AGENT: &str = concat!("avila-math/", env!("CARGO_PKG_VERSION"));
+/// Maior linha de início ou de header aceita do peer, em bytes
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+/// Máximo de headers (ou trailers) numa mensagem
+const MAX_HEADERS: usize = 100;
+/// Maior corpo de requisição aceito pelo `HttpServer` por padrão
+const DEFAULT_MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Método HTTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+        }
+    }
+
+    /// Repetir a requisição tem o mesmo efeito que enviá-la uma vez (RFC 9110, 9.2.2)
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::Post | Method::Patch)
+    }
+
+    /// Converte a partir do nome (case-sensitive, como no protocolo)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "GET" => Some(Method::Get),
+            "HEAD" => Some(Method::Head),
+            "POST" => Some(Method::Post),
+            "PUT" => Some(Method::Put),
+            "PATCH" => Some(Method::Patch),
+            "DELETE" => Some(Method::Delete),
+            "OPTIONS" => Some(Method::Options),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Resposta HTTP já interpretada
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub reason: String,
+    /// Headers na ordem recebida (nomes preservados como vieram)
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Cria uma resposta vazia com o status dado
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            reason: reason_phrase(status).to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Resposta 200 com o corpo dado
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200).with_body(body)
+    }
+
+    /// Resposta 404
+    pub fn not_found() -> Self {
+        Self::new(404).with_body("Not Found")
+    }
+
+    /// Adiciona um header (builder)
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Define o corpo (builder)
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Define o `Content-Type` (builder)
+    pub fn with_content_type(self, content_type: &str) -> Self {
+        self.with_header("Content-Type", content_type)
+    }
+
+    /// Retorna o primeiro header com o nome dado (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Status 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Status 3xx
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+
+    /// Corpo como texto (UTF-8 com substituição de bytes inválidos)
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+impl fmt::Display for HttpResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.body))
+    }
+}
+
+/// HTTP/1.1 client (sem dependências externas, apenas `http://`)
+///
+/// ```no_run
+/// use avila_math::os::{HttpClient, Method};
+/// use std::time::Duration;
+///
+/// let http = HttpClient::new().with_timeout(Duration::from_secs(5));
+/// let response = http.get("http://example.com/api/data").unwrap();
+/// println!("{} {}", response.status, response.text());
+///
+/// let created = http
+///     .request(Method::Post, "http://example.com/api/items")
+///     .header("Content-Type", "application/json")
+///     .body(r#"{"name":"sword"}"#)
+///     .send()
+///     .unwrap();
+/// assert!(created.is_success());
+/// ```
+///
+/// Conexões são mantidas abertas (`Connection: keep-alive`) e reutilizadas por
+/// host; clones do client compartilham o mesmo pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    timeout: Option<Duration>,
+    max_redirects: usize,
+    default_headers: Vec<(String, String)>,
+    keep_alive: bool,
+    max_idle_per_host: usize,
+    idle_timeout: Duration,
+    pool: Arc<ConnectionPool>,
+}
+
+/// Conexões ociosas por `(host, porta)`
+#[derive(Debug, Default)]
+struct ConnectionPool {
+    idle: Mutex<HashMap<(String, u16), Vec<IdleConnection>>>,
+}
+
+#[derive(Debug)]
+struct IdleConnection {
+    reader: BufReader<TcpStream>,
+    since: Instant,
+}
+
+/// Conexão em uso por uma requisição; volta ao pool em `finish`
+struct HttpConnection {
+    reader: BufReader<TcpStream>,
+    key: (String, u16),
+    /// O servidor aceita reuso e o corpo tem tamanho delimitado
+    reusable: bool,
+}
+
+impl HttpClient {
+    /// Cria um client com timeout de 30s, até 10 redirecionamentos e keep-alive
+    pub fn new() -> Self {
+        Self {
+            timeout: Some(DEFAULT_TIMEOUT),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            default_headers: Vec::new(),
+            keep_alive: true,
+            max_idle_per_host: 4,
+            idle_timeout: Duration::from_secs(30),
+            pool: Arc::new(ConnectionPool::default()),
+        }
+    }
+
+    /// Ativa/desativa conexões persistentes (padrão: ativado)
+    pub fn with_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Define quantas conexões ociosas manter por host (padrão: 4)
+    pub fn with_max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = max;
+        self
+    }
+
+    /// Define por quanto tempo uma conexão ociosa pode ser reutilizada (padrão: 30s)
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Número de conexões ociosas no pool
+    pub fn idle_connections(&self) -> usize {
+        self.pool.idle.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// Fecha todas as conexões ociosas
+    pub fn close_idle_connections(&self) {
+        self.pool.idle.lock().unwrap().clear();
+    }
+
+    fn checkout(&self, key: &(String, u16)) -> Option<BufReader<TcpStream>> {
+        let mut idle = self.pool.idle.lock().unwrap();
+        let connections = idle.get_mut(key)?;
+        connections.retain(|conn| conn.since.elapsed() < self.idle_timeout);
+        while let Some(conn) = connections.pop() {
+            if !peer_closed(conn.reader.get_ref()) {
+                return Some(conn.reader);
+            }
+        }
+        None
+    }
+
+    fn checkin(&self, connection: HttpConnection) {
+        if !connection.reusable || !self.keep_alive {
+            return;
+        }
+        let mut idle = self.pool.idle.lock().unwrap();
+        let connections = idle.entry(connection.key).or_default();
+        if connections.len() < self.max_idle_per_host {
+            connections.push(IdleConnection {
+                reader: connection.reader,
+                since: Instant::now(),
+            });
+        }
+    }
+
+    /// Define o timeout padrão (conexão, leitura e escrita); `None` desativa
+    pub fn with_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Define quantos redirecionamentos seguir (0 desativa)
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Adiciona um header enviado em todas as requisições
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Começa a construir uma requisição
+    pub fn request(&self, method: Method, url: &str) -> HttpRequest<'_> {
+        HttpRequest {
+            client: self,
+            method,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+            timeout: self.timeout,
+            follow_redirects: self.max_redirects > 0,
+        }
+    }
+
+    /// Faz uma requisição GET
+    pub fn get(&self, url: &str) -> io::Result<HttpResponse> {
+        self.request(Method::Get, url).send()
+    }
+
+    /// Faz uma requisição POST com o corpo dado
+    pub fn post(&self, url: &str, body: impl Into<Vec<u8>>) -> io::Result<HttpResponse> {
+        self.request(Method::Post, url).body(body).send()
+    }
+
+    /// Faz uma requisição PUT com o corpo dado
+    pub fn put(&self, url: &str, body: impl Into<Vec<u8>>) -> io::Result<HttpResponse> {
+        self.request(Method::Put, url).body(body).send()
+    }
+
+    /// Faz uma requisição DELETE
+    pub fn delete(&self, url: &str) -> io::Result<HttpResponse> {
+        self.request(Method::Delete, url).send()
+    }
+
+    /// Baixa `url` para `path` gravando em blocos, sem manter o corpo em memória
+    ///
+    /// Se `path` já existir com conteúdo, pede só o restante com `Range` e
+    /// continua de onde parou (servidores sem suporte devolvem 200 e o arquivo
+    /// é reescrito). `progress(baixados, total)` é chamado a cada bloco; `total`
+    /// é `None` se o servidor não informar o tamanho. Retorna o tamanho final.
+    ///
+    /// ```no_run
+    /// use avila_math::os::HttpClient;
+    ///
+    /// let http = HttpClient::new();
+    /// http.download("http://cdn.example.com/patch.pak", "patch.pak", |done, total| {
+    ///     if let Some(total) = total {
+    ///         println!("{:.1}%", done as f64 * 100.0 / total as f64);
+    ///     }
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn download<P, F>(&self, url: &str, path: P, mut progress: F) -> io::Result<u64>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, Option<u64>),
+    {
+        let path = path.as_ref();
+        let existing = if FileSystem::is_file(path) {
+            FileSystem::metadata(path)?.size
+        } else {
+            0
+        };
+
+        let mut request = self.request(Method::Get, url);
+        if existing > 0 {
+            request = request.header("Range", format!("bytes={}-", existing));
+        }
+        let (_, response, mut connection) = request.send_head()?;
+        let content_range = response
+            .header("Content-Range")
+            .and_then(parse_content_range);
+        let content_length = response
+            .header("Content-Length")
+            .and_then(|len| len.parse::<u64>().ok());
+
+        let (start, total, append) = match response.status {
+            206 => match content_range {
+                Some((Some(start), total)) if start == existing => {
+                    (start, total.or(content_length.map(|len| start + len)), true)
+                }
+                _ => return Err(invalid_data("unexpected Content-Range in resumed download")),
+            },
+            200 => (0, content_length, false),
+            // O arquivo local já está completo
+            416 if content_range.is_some_and(|(_, total)| total == Some(existing)) => {
+                progress(existing, Some(existing));
+                return Ok(existing);
+            }
+            status => {
+                return Err(io::Error::other(format!(
+                    "download failed: {} {}",
+                    status, response.reason
+                )))
+            }
+        };
+
+        let file = if append {
+            FileHandle::open_with_options(path, false, true, false, true)?
+        } else {
+            FileHandle::create(path)?
+        };
+        let mut writer = file.writer();
+
+        let mut downloaded = start;
+        progress(downloaded, total);
+        copy_body(
+            &mut connection.reader,
+            &response.headers,
+            true,
+            &mut writer,
+            &mut |n| {
+                downloaded += n;
+                progress(downloaded, total);
+            },
+        )?;
+        writer.flush()?;
+        self.checkin(connection);
+
+        if let Some(total) = total {
+            if downloaded != total {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("download incomplete: {} of {} bytes", downloaded, total),
+                ));
+            }
+        }
+        Ok(downloaded)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Requisição em construção; criada por `HttpClient::request`
+pub struct HttpRequest<'a> {
+    client: &'a HttpClient,
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    timeout: Option<Duration>,
+    follow_redirects: bool,
+}
+
+impl HttpRequest<'_> {
+    /// Adiciona um header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Define o corpo da requisição
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sobrescreve o timeout do client para esta requisição
+    pub fn timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Ativa/desativa o seguimento de redirecionamentos
+    ///
+    /// Redirecionamentos para outro host não recebem `Authorization`,
+    /// `Proxy-Authorization` nem `Cookie`.
+    pub fn follow_redirects(mut self, follow: bool) -> Self {
+        self.follow_redirects = follow;
+        self
+    }
+
+    /// Envia a requisição e lê a resposta completa
+    pub fn send(self) -> io::Result<HttpResponse> {
+        let client = self.client;
+        let (method, mut response, mut connection) = self.send_head()?;
+        if response_has_body(method, response.status) {
+            response.body = read_body(&mut connection.reader, &response.headers, true)?;
+        }
+        client.checkin(connection);
+        Ok(response)
+    }
+
+    /// Envia a requisição seguindo redirecionamentos e lê só status e headers
+    ///
+    /// Retorna o método usado na última requisição e a conexão posicionada no
+    /// início do corpo.
+    fn send_head(self) -> io::Result<(Method, HttpResponse, HttpConnection)> {
+        let mut method = self.method;
+        let mut url = Url::parse(&self.url)?;
+        let mut body = self.body.clone();
+        let mut redirects = 0;
+        let origin = (url.scheme().to_string(), url.authority());
+        let mut cross_origin = false;
+
+        loop {
+            let (response, mut connection) = self.open(method, &url, &body, cross_origin)?;
+
+            let location = match response.header("Location") {
+                Some(location) if self.follow_redirects && response.is_redirect() => location,
+                _ => return Ok((method, response, connection)),
+            };
+
+            // Descarta o corpo do redirecionamento para poder reutilizar a conexão
+            if response_has_body(method, response.status) {
+                copy_body(
+                    &mut connection.reader,
+                    &response.headers,
+                    false,
+                    &mut io::sink(),
+                    &mut |_| {},
+                )?;
+            }
+            self.client.checkin(connection);
+
+            if redirects >= self.client.max_redirects {
+                return Err(io::Error::other(format!(
+                    "too many redirects (max {})",
+                    self.client.max_redirects
+                )));
+            }
+            redirects += 1;
+
+            url = url.join(location)?;
+            // Credenciais só vão para o host que as recebeu originalmente
+            cross_origin |= url.scheme() != origin.0 || url.authority() != origin.1;
+            // 307/308 preservam método e corpo; os demais viram GET
+            if !matches!(response.status, 307 | 308) && method != Method::Head {
+                method = Method::Get;
+                body.clear();
+            }
+        }
+    }
+
+    /// Envia uma requisição e lê status e headers da resposta
+    ///
+    /// Com `strip_credentials`, headers de autenticação e cookies não são
+    /// enviados (redirecionamento para outro host).
+    fn open(
+        &self,
+        method: Method,
+        url: &Url,
+        body: &[u8],
+        strip_credentials: bool,
+    ) -> io::Result<(HttpResponse, HttpConnection)> {
+        if url.scheme() != "http" {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} is not supported by HttpClient", url.scheme()),
+            ));
+        }
+        let key = (url.host().to_string(), url.port_or_default().unwrap_or(80));
+        let keep_alive = self.client.keep_alive;
+
+        let mut request = format!("{} {} HTTP/1.1\r\n", method, url.request_target());
+
+        let headers = self
+            .client
+            .default_headers
+            .iter()
+            .chain(&self.headers)
+            .filter(|(name, _)| !(strip_credentials && is_credential_header(name)));
+        let has = |name: &str| headers.clone().any(|(n, _)| n.eq_ignore_ascii_case(name));
+
+        request.push_str(&format!("Host: {}\r\n", url.authority()));
+        if !has("User-Agent") {
+            request.push_str(&format!("User-Agent: {}\r\n", USER_AGENT));
+        }
+        if !body.is_empty() || matches!(method, Method::Post | Method::Put | Method::Patch) {
+            request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        request.push_str(if keep_alive {
+            "Connection: keep-alive\r\n"
+        } else {
+            "Connection: close\r\n"
+        });
+        for (name, value) in headers.clone() {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+
+        loop {
+            let pooled = if keep_alive {
+                self.client.checkout(&key)
+            } else {
+                None
+            };
+            let reused = pooled.is_some();
+            let mut reader = match pooled {
+                Some(reader) => reader,
+                None => BufReader::new(connect(&key.0, key.1, self.timeout)?),
+            };
+            reader.get_ref().set_read_timeout(self.timeout)?;
+            reader.get_ref().set_write_timeout(self.timeout)?;
+
+            let written = (|| {
+                let stream = reader.get_mut();
+                stream.write_all(request.as_bytes())?;
+                stream.write_all(body)?;
+                stream.flush()
+            })();
+            let sent = written.is_ok();
+            let result = written.and_then(|()| read_response_head(&mut reader));
+
+            match result {
+                Ok((response, persistent)) => {
+                    let delimited = !response_has_body(method, response.status)
+                        || response.header("Content-Length").is_some()
+                        || response.header("Transfer-Encoding").is_some();
+                    let connection = HttpConnection {
+                        reader,
+                        key,
+                        reusable: keep_alive && persistent && delimited,
+                    };
+                    return Ok((response, connection));
+                }
+                // O servidor fechou a conexão ociosa: tenta de novo com uma nova.
+                // Se a requisição foi enviada inteira, o servidor pode tê-la
+                // processado, então só métodos idempotentes são repetidos
+                Err(err)
+                    if reused && is_stale_connection(&err) && (!sent || method.is_idempotent()) =>
+                {
+                    continue
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Erros típicos de escrever/ler numa conexão keep-alive que o servidor já fechou
+fn is_stale_connection(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+    )
+}
+
+/// Headers que identificam o usuário e não devem seguir um redirecionamento para outro host
+fn is_credential_header(name: &str) -> bool {
+    ["Authorization", "Proxy-Authorization", "Cookie"]
+        .iter()
+        .any(|credential| name.eq_ignore_ascii_case(credential))
+}
+
+/// Verifica sem bloquear se o servidor já fechou (ou escreveu algo inesperado
+/// em) uma conexão ociosa
+fn peer_closed(stream: &TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return true;
+    }
+    let closed = match stream.peek(&mut [0u8; 1]) {
+        Err(err) => err.kind() != io::ErrorKind::WouldBlock,
+        Ok(_) => true,
+    };
+    stream.set_nonblocking(false).is_err() || closed
+}
+
+fn connect(host: &str, port: u16, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    let resolver = Resolver::shared();
+    let addrs = match timeout {
+        Some(timeout) => resolver.resolve_timeout(host, port, timeout)?,
+        None => resolver
+            .resolve_async(host, port)
+            .wait()
+            .map_err(|_| io::Error::other("resolver thread panicked"))??,
+    };
+
+    let mut last_error = None;
+    for addr in addrs {
+        let attempt = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+            None => TcpStream::connect(addr),
+        };
+        match attempt {
+            Ok(stream) => {
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)?;
+                return Ok(stream);
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host did not resolve")))
+}
+
+pub(crate) fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Linha ou lista de headers maior que os limites de `read_line`/`read_headers`
+#[derive(Debug)]
+struct HeadTooLarge(&'static str);
+
+impl fmt::Display for HeadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for HeadTooLarge {}
+
+/// Corpo maior que o limite de `read_body_limited`
+#[derive(Debug)]
+struct BodyTooLarge;
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("body too large")
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// Lê uma linha terminada em CRLF (sem o terminador)
+///
+/// Linhas com mais de `MAX_LINE_LENGTH` bytes falham com `InvalidData`.
+pub(crate) fn read_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    let limit = MAX_LINE_LENGTH as u64 + 2;
+    if reader.by_ref().take(limit).read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed",
+        ));
+    }
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.len() > MAX_LINE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            HeadTooLarge("line too long"),
+        ));
+    }
+    Ok(line.to_string())
+}
+
+/// Lê headers até a linha vazia
+///
+/// Mais de `MAX_HEADERS` headers falham com `InvalidData`.
+pub(crate) fn read_headers<R: BufRead>(reader: &mut R) -> io::Result<Vec<(String, String)>> {
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                HeadTooLarge("too many headers"),
+            ));
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid_data(format!("malformed header: {}", line)))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+/// Lê o corpo conforme `Transfer-Encoding`/`Content-Length`
+pub(crate) fn read_body<R: BufRead>(
+    reader: &mut R,
+    headers: &[(String, String)],
+    read_to_end_if_unknown: bool,
+) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    copy_body(
+        reader,
+        headers,
+        read_to_end_if_unknown,
+        &mut body,
+        &mut |_| {},
+    )?;
+    Ok(body)
+}
+
+/// Destino de `read_body_limited`: falha com `BodyTooLarge` ao passar do limite
+struct LimitedBody {
+    body: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedBody {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.body.len() + buf.len() > self.limit {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, BodyTooLarge));
+        }
+        self.body.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Como `read_body` sem ler até o EOF, mas falhando com `InvalidData` se o
+/// corpo passar de `limit` bytes
+fn read_body_limited<R: BufRead>(
+    reader: &mut R,
+    headers: &[(String, String)],
+    limit: usize,
+) -> io::Result<Vec<u8>> {
+    let declared = find_header(headers, "Content-Length").and_then(|l| l.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > limit as u64) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, BodyTooLarge));
+    }
+
+    // Chunked não declara o tamanho: o limite vale para os bytes decodificados
+    let mut body = LimitedBody {
+        body: Vec::new(),
+        limit,
+    };
+    copy_body(reader, headers, false, &mut body, &mut |_| {})?;
+    Ok(body.body)
+}
+
+/// Copia o corpo para `writer` em blocos, chamando `on_bytes` a cada bloco
+///
+/// Retorna o total de bytes do corpo (já decodificado, no caso de chunked).
+pub(crate) fn copy_body<R: BufRead, W: Write>(
+    reader: &mut R,
+    headers: &[(String, String)],
+    read_to_end_if_unknown: bool,
+    writer: &mut W,
+    on_bytes: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    let chunked = find_header(headers, "Transfer-Encoding")
+        .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+    if chunked {
+        return copy_chunked(reader, writer, on_bytes);
+    }
+
+    match find_header(headers, "Content-Length") {
+        Some(length) => {
+            let length: u64 = length
+                .parse()
+                .map_err(|_| invalid_data("invalid Content-Length"))?;
+            copy_exact(reader, length, writer, on_bytes)?;
+            Ok(length)
+        }
+        None if read_to_end_if_unknown => {
+            let mut total = 0;
+            let mut buf = [0u8; 16 * 1024];
+            loop {
+                let n = reader.read(&mut buf)?;
+                if n == 0 {
+                    return Ok(total);
+                }
+                writer.write_all(&buf[..n])?;
+                total += n as u64;
+                on_bytes(n as u64);
+            }
+        }
+        None => Ok(0),
+    }
+}
+
+fn copy_exact<R: BufRead, W: Write>(
+    reader: &mut R,
+    mut remaining: u64,
+    writer: &mut W,
+    on_bytes: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    let mut buf = [0u8; 16 * 1024];
+    while remaining > 0 {
+        let want = remaining.min(buf.len() as u64) as usize;
+        let n = reader.read(&mut buf[..want])?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed before end of body",
+            ));
+        }
+        writer.write_all(&buf[..n])?;
+        remaining -= n as u64;
+        on_bytes(n as u64);
+    }
+    Ok(())
+}
+
+/// Decodifica um corpo com `Transfer-Encoding: chunked`
+fn copy_chunked<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    on_bytes: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    let mut total = 0;
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = u64::from_str_radix(size, 16)
+            .map_err(|_| invalid_data(format!("invalid chunk size: {}", line)))?;
+
+        if size == 0 {
+            // Trailers opcionais até a linha vazia
+            read_headers(reader)?;
+            return Ok(total);
+        }
+
+        copy_exact(reader, size, writer, on_bytes)?;
+        total += size;
+
+        if !read_line(reader)?.is_empty() {
+            return Err(invalid_data("missing CRLF after chunk"));
+        }
+    }
+}
+
+/// Respostas a HEAD, 204 e 304 nunca têm corpo
+fn response_has_body(method: Method, status: u16) -> bool {
+    method != Method::Head && status != 204 && status != 304
+}
+
+/// Lê status e headers de uma resposta, pulando respostas 1xx
+///
+/// Retorna também se a conexão pode continuar aberta segundo a versão e o
+/// header `Connection`.
+fn read_response_head<R: BufRead>(reader: &mut R) -> io::Result<(HttpResponse, bool)> {
+    loop {
+        let status_line = read_line(reader)?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or("");
+        if !version.starts_with("HTTP/") {
+            return Err(invalid_data(format!(
+                "invalid status line: {}",
+                status_line
+            )));
+        }
+        let status: u16 = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid_data(format!("invalid status line: {}", status_line)))?;
+        let reason = parts.next().unwrap_or("").to_string();
+        let headers = read_headers(reader)?;
+
+        // Respostas 1xx (ex: 100 Continue) são seguidas da resposta final
+        if !(100..200).contains(&status) {
+            let connection = find_header(&headers, "Connection").map(|c| c.to_ascii_lowercase());
+            let persistent = match connection.as_deref() {
+                Some("close") => false,
+                Some("keep-alive") => true,
+                _ => version != "HTTP/1.0",
+            };
+            let response = HttpResponse {
+                status,
+                reason,
+                headers,
+                body: Vec::new(),
+            };
+            return Ok((response, persistent));
+        }
+    }
+}
+
+/// Lê status, headers e corpo de uma resposta
+#[cfg(test)]
+fn read_response<R: BufRead>(reader: &mut R, method: Method) -> io::Result<HttpResponse> {
+    let (mut response, _) = read_response_head(reader)?;
+    if response_has_body(method, response.status) {
+        response.body = read_body(reader, &response.headers, true)?;
+    }
+    Ok(response)
+}
+
+/// Interpreta `Content-Range: bytes início-fim/total` (ou `bytes */total`)
+fn parse_content_range(value: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes")?.trim();
+    let (span, total) = range.split_once('/')?;
+    let start = match span.trim() {
+        "*" => None,
+        span => Some(span.split_once('-')?.0.trim().parse().ok()?),
+    };
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start, total))
+}
+
+/// Texto padrão do status HTTP
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        100 => "Continue",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        416 => "Range Not Satisfiable",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+/// Requisição recebida pelo `HttpServer`
+#[derive(Debug, Clone)]
+pub struct ServerRequest {
+    pub method: Method,
+    /// Caminho decodificado (`%20` vira espaço), sem a query string
+    pub path: String,
+    /// Query string sem o `?` (vazia se não houver)
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub remote_addr: SocketAddr,
+}
+
+impl ServerRequest {
+    /// Retorna o primeiro header com o nome dado (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Retorna o valor decodificado de um parâmetro da query string
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        parse_query(&self.query)
+            .into_iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Corpo como texto (UTF-8 com substituição de bytes inválidos)
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+type Handler = dyn Fn(&ServerRequest) -> HttpResponse + Send + Sync;
+
+struct Route {
+    method: Method,
+    /// Caminho exato, ou prefixo quando termina em `*`
+    pattern: String,
+    handler: Arc<Handler>,
+}
+
+impl Route {
+    fn matches_path(&self, path: &str) -> bool {
+        match self.pattern.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.pattern,
+        }
+    }
+}
+
+/// Servidor HTTP/1.1 mínimo sobre `TcpServer`
+///
+/// Rotas são registradas por método e caminho (`/assets/*` casa qualquer
+/// caminho com o prefixo). As conexões são atendidas em um `ThreadPool`; uma
+/// conexão keep-alive ociosa é fechada assim que outra conexão espera por uma
+/// thread. Heads com linhas ou headers demais recebem 431 e corpos acima de
+/// `with_max_body_size` recebem 413:
+///
+/// ```no_run
+/// use avila_math::os::{HttpResponse, HttpServer};
+///
+/// let server = HttpServer::bind("127.0.0.1:8080")
+///     .unwrap()
+///     .get("/metrics", |_| HttpResponse::ok("fps 60"))
+///     .get("/assets/*", |req| HttpResponse::ok(format!("asset {}", req.path)));
+///
+/// let handle = server.spawn().unwrap();
+/// println!("servindo em {}", handle.local_addr());
+/// handle.shutdown();
+/// ```
+pub struct HttpServer {
+    listener: TcpServer,
+    routes: Vec<Route>,
+    threads: usize,
+    read_timeout: Option<Duration>,
+    max_body_size: usize,
+}
+
+impl HttpServer {
+    /// Cria o servidor e faz bind no endereço
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpServer::bind(addr)?,
+            routes: Vec::new(),
+            threads: 4,
+            read_timeout: Some(Duration::from_secs(5)),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        })
+    }
+
+    /// Define quantas threads atendem conexões (padrão: 4)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Define o timeout de leitura por conexão (padrão: 5s); conexões
+    /// keep-alive ociosas são fechadas após esse tempo
+    pub fn with_read_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.read_timeout = timeout.into();
+        self
+    }
+
+    /// Define o maior corpo de requisição aceito (padrão: 16 MiB); acima dele
+    /// o servidor responde 413
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Registra um handler para o método e caminho dados
+    pub fn route<F>(mut self, method: Method, path: &str, handler: F) -> Self
+    where
+        F: Fn(&ServerRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            method,
+            pattern: path.to_string(),
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Registra um handler GET (também atende HEAD)
+    pub fn get<F>(self, path: &str, handler: F) -> Self
+    where
+        F: Fn(&ServerRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route(Method::Get, path, handler)
+    }
+
+    /// Registra um handler POST
+    pub fn post<F>(self, path: &str, handler: F) -> Self
+    where
+        F: Fn(&ServerRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.route(Method::Post, path, handler)
+    }
+
+    /// Retorna o endereço local (útil com porta 0)
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Atende conexões na thread atual até o token ser cancelado
+    pub fn serve(self, shutdown: &CancellationToken) -> io::Result<()> {
+        let poller = Poller::new()?;
+        self.listener.set_nonblocking(true)?;
+        poller.register(&self.listener, 0, Interest::READABLE)?;
+
+        let pool = ThreadPool::new(self.threads);
+        let routes = Arc::new(self.routes);
+        // Conexões aceitas que ainda esperam uma thread
+        let backlog = Arc::new(AtomicUsize::new(0));
+        let mut events = Vec::new();
+
+        while !shutdown.is_cancelled() {
+            // Timeout curto para perceber o cancelamento
+            poller.poll(&mut events, Some(Duration::from_millis(50)))?;
+            if events.is_empty() {
+                continue;
+            }
+
+            while let Some((client, addr)) = self.listener.try_accept()? {
+                client.set_nonblocking(false)?;
+                client.set_read_timeout(self.read_timeout)?;
+                let connection = Connection {
+                    routes: Arc::clone(&routes),
+                    shutdown: shutdown.clone(),
+                    idle_timeout: self.read_timeout,
+                    max_body_size: self.max_body_size,
+                    backlog: Arc::clone(&backlog),
+                };
+                backlog.fetch_add(1, Ordering::SeqCst);
+                pool.execute(move || {
+                    connection.backlog.fetch_sub(1, Ordering::SeqCst);
+                    // Erros de I/O só encerram a conexão
+                    let _ = connection.handle(client, addr);
+                });
+            }
+        }
+
+        poller.deregister(&self.listener)?;
+        Ok(())
+    }
+
+    /// Atende conexões em uma thread própria
+    pub fn spawn(self) -> io::Result<HttpServerHandle> {
+        let local_addr = self.local_addr()?;
+        let token = CancellationToken::new();
+        let serve_token = token.clone();
+        let thread = thread::Builder::new()
+            .name("avila-http-server".to_string())
+            .spawn(move || self.serve(&serve_token))?;
+
+        Ok(HttpServerHandle {
+            local_addr,
+            token,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Handle de um `HttpServer` rodando em background; para o servidor no drop
+pub struct HttpServerHandle {
+    local_addr: SocketAddr,
+    token: CancellationToken,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl HttpServerHandle {
+    /// Endereço em que o servidor está escutando
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Para o servidor e espera a thread de accept terminar
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.token.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for HttpServerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Configuração compartilhada pelas conexões de um `HttpServer`
+struct Connection {
+    routes: Arc<Vec<Route>>,
+    shutdown: CancellationToken,
+    idle_timeout: Option<Duration>,
+    max_body_size: usize,
+    backlog: Arc<AtomicUsize>,
+}
+
+impl Connection {
+    /// Atende requisições de uma conexão até ela fechar (suporta keep-alive)
+    fn handle(&self, client: TcpClient, addr: SocketAddr) -> io::Result<()> {
+        let mut reader = BufReader::new(client);
+        let mut served = false;
+
+        loop {
+            // Entre requisições, a conexão cede a thread se outra estiver esperando
+            if !self.wait_for_request(&mut reader, served)? {
+                return Ok(());
+            }
+            served = true;
+
+            let request = match read_line(&mut reader) {
+                Ok(line) if line.is_empty() => continue,
+                Ok(line) => read_request(&mut reader, &line, addr, self.max_body_size),
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => Err(err),
+                // EOF ou timeout de keep-alive
+                Err(_) => return Ok(()),
+            };
+
+            let (response, keep_alive, head) = match request {
+                Ok((request, keep_alive)) => {
+                    let head = request.method == Method::Head;
+                    (dispatch(&self.routes, &request), keep_alive, head)
+                }
+                Err(err) => (HttpResponse::new(rejection_status(&err)), false, false),
+            };
+
+            write_response(reader.get_mut(), &response, keep_alive, head)?;
+            if !keep_alive {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Espera o início da próxima requisição numa conexão keep-alive
+    ///
+    /// Retorna `false` se o cliente fechou a conexão, se ela ficou ociosa por
+    /// mais que `idle_timeout`, se o servidor está sendo encerrado ou, com
+    /// `yield_when_busy`, se outra conexão espera por uma thread.
+    fn wait_for_request(
+        &self,
+        reader: &mut BufReader<TcpClient>,
+        yield_when_busy: bool,
+    ) -> io::Result<bool> {
+        if !reader.buffer().is_empty() {
+            return Ok(true);
+        }
+
+        // Fatias curtas para perceber o cancelamento sem esperar o timeout inteiro
+        const SLICE: Duration = Duration::from_millis(50);
+        let started = Instant::now();
+        let result = loop {
+            if self.shutdown.is_cancelled() {
+                break Ok(false);
+            }
+            if yield_when_busy && self.backlog.load(Ordering::SeqCst) > 0 {
+                break Ok(false);
+            }
+            let remaining = self
+                .idle_timeout
+                .map(|timeout| timeout.saturating_sub(started.elapsed()));
+            if remaining == Some(Duration::ZERO) {
+                break Ok(false);
+            }
+            let slice = remaining.map_or(SLICE, |remaining| remaining.min(SLICE));
+            reader.get_ref().set_read_timeout(Some(slice))?;
+            match reader.fill_buf() {
+                Ok(buf) => break Ok(!buf.is_empty()),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::Interrupted
+                    ) => {}
+                Err(_) => break Ok(false),
+            }
+        };
+
+        reader.get_ref().set_read_timeout(self.idle_timeout)?;
+        result
+    }
+}
+
+/// Status da resposta a uma requisição que não pôde ser lida
+fn rejection_status(err: &io::Error) -> u16 {
+    match err.get_ref() {
+        Some(inner) if inner.is::<HeadTooLarge>() => 431,
+        Some(inner) if inner.is::<BodyTooLarge>() => 413,
+        _ if err.kind() == io::ErrorKind::Unsupported => 501,
+        _ => 400,
+    }
+}
+
+fn read_request<R: BufRead>(
+    reader: &mut R,
+    request_line: &str,
+    remote_addr: SocketAddr,
+    max_body_size: usize,
+) -> io::Result<(ServerRequest, bool)> {
+    let mut parts = request_line.split(' ');
+    let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/") => {
+            (method, target, version)
+        }
+        _ => return Err(invalid_data("malformed request line")),
+    };
+    let method = Method::parse(method)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "unknown method"))?;
+
+    let headers = read_headers(reader)?;
+    let body = read_body_limited(reader, &headers, max_body_size)?;
+
+    let connection = find_header(&headers, "Connection").map(|c| c.to_ascii_lowercase());
+    let keep_alive = match connection.as_deref() {
+        Some("close") => false,
+        Some("keep-alive") => true,
+        _ => version == "HTTP/1.1",
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let request = ServerRequest {
+        method,
+        path: percent_decode(path),
+        query: query.to_string(),
+        headers,
+        body,
+        remote_addr,
+    };
+    Ok((request, keep_alive))
+}
+
+/// Escolhe a rota e executa o handler (panic vira 500)
+fn dispatch(routes: &[Route], request: &ServerRequest) -> HttpResponse {
+    let method = match request.method {
+        Method::Head => Method::Get,
+        method => method,
+    };
+
+    let mut path_matched = false;
+    for route in routes.iter().filter(|r| r.matches_path(&request.path)) {
+        path_matched = true;
+        if route.method == method {
+            return panic::catch_unwind(AssertUnwindSafe(|| (route.handler)(request)))
+                .unwrap_or_else(|_| HttpResponse::new(500));
+        }
+    }
+
+    if path_matched {
+        HttpResponse::new(405)
+    } else {
+        HttpResponse::not_found()
+    }
+}
+
+fn write_response<W: Write>(
+    writer: &mut W,
+    response: &HttpResponse,
+    keep_alive: bool,
+    head: bool,
+) -> io::Result<()> {
+    let mut out = format!("HTTP/1.1 {} {}\r\n", response.status, response.reason);
+    for (name, value) in &response.headers {
+        let managed = ["Content-Length", "Transfer-Encoding", "Connection"];
+        if !managed.iter().any(|m| name.eq_ignore_ascii_case(m)) {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    out.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    out.push_str(if keep_alive {
+        "Connection: keep-alive\r\n\r\n"
+    } else {
+        "Connection: close\r\n\r\n"
+    });
+
+    writer.write_all(out.as_bytes())?;
+    if !head {
+        writer.write_all(&response.body)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_response_content_length() {
+        let raw = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nX-Test: yes\r\n\r\nhelloEXTRA";
+        let response = read_response(&mut Cursor::new(raw), Method::Get).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.header("x-test"), Some("yes"));
+        assert_eq!(response.body, b"hello");
+        assert!(response.is_success());
+    }
+
+    #[test]
+    fn test_read_response_chunked() {
+        let raw = "HTTP/1.1 100 Continue\r\n\r\n\
+                   HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                   4\r\nWiki\r\n6;ext=1\r\npedia \r\nE\r\nin \r\n\r\nchunks.\r\n0\r\nTrailer: x\r\n\r\n";
+        let response = read_response(&mut Cursor::new(raw), Method::Get).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text(), "Wikipedia in \r\n\r\nchunks.");
+    }
+
+    #[test]
+    fn test_head_limits() {
+        let long_line = format!("X-Long: {}\r\n", "a".repeat(MAX_LINE_LENGTH));
+        let raw = format!("HTTP/1.1 200 OK\r\n{}\r\n", long_line);
+        let err = read_response(&mut Cursor::new(raw), Method::Get).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let many = "X-Header: 1\r\n".repeat(MAX_HEADERS + 1);
+        let raw = format!("HTTP/1.1 200 OK\r\n{}\r\n", many);
+        let err = read_response(&mut Cursor::new(raw), Method::Get).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // No limite ainda é aceito
+        let many = "X-Header: 1\r\n".repeat(MAX_HEADERS);
+        let raw = format!("HTTP/1.1 204 No Content\r\n{}\r\n", many);
+        assert!(read_response(&mut Cursor::new(raw), Method::Get).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_responses() {
+        assert!(read_response(&mut Cursor::new("garbage\r\n\r\n"), Method::Get).is_err());
+        let bad_chunk = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n";
+        assert!(read_response(&mut Cursor::new(bad_chunk), Method::Get).is_err());
+        assert!(HttpClient::new().get("https://example.com").is_err());
+    }
+
+    /// Servidor de teste: responde `responses` em conexões sucessivas e devolve as requisições
+    fn serve(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", server.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (client, _) = server.accept().unwrap();
+                let mut reader = BufReader::new(client);
+                let request_line = read_line(&mut reader).unwrap();
+                let headers = read_headers(&mut reader).unwrap();
+                let body = read_body(&mut reader, &headers, false).unwrap();
+                requests.push(format!(
+                    "{}|{}|{}",
+                    request_line,
+                    find_header(&headers, "X-Custom").unwrap_or(""),
+                    String::from_utf8(body).unwrap()
+                ));
+                reader.into_inner().send_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        (base, handle)
+    }
+
+    #[test]
+    fn test_post_with_headers_and_body() {
+        let (base, handle) = serve(vec![
+            "HTTP/1.1 201 Created\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        ]);
+
+        let response = HttpClient::new()
+            .request(Method::Post, &format!("{}/items", base))
+            .header("X-Custom", "42")
+            .body("payload")
+            .send()
+            .unwrap();
+
+        assert_eq!(response.status, 201);
+        assert_eq!(response.text(), "ok");
+        assert_eq!(
+            handle.join().unwrap(),
+            vec!["POST /items HTTP/1.1|42|payload"]
+        );
+    }
+
+    #[test]
+    fn test_follow_redirects() {
+        let (base, handle) = serve(vec![
+            "HTTP/1.1 303 See Other\r\nLocation: /done\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone",
+        ]);
+
+        let response = HttpClient::new()
+            .post(&format!("{}/submit", base), "form")
+            .unwrap();
+
+        assert_eq!(response.text(), "done");
+        assert_eq!(
+            handle.join().unwrap(),
+            vec!["POST /submit HTTP/1.1||form", "GET /done HTTP/1.1||"]
+        );
+    }
+
+    /// Servidor de teste: responde `responses` em conexões sucessivas e devolve
+    /// os headers de credenciais (`Authorization`, `Cookie`) de cada requisição
+    fn serve_credentials(
+        server: TcpServer,
+        responses: Vec<String>,
+    ) -> std::thread::JoinHandle<Vec<(Option<String>, Option<String>)>> {
+        std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for response in responses {
+                let (client, _) = server.accept().unwrap();
+                let mut reader = BufReader::new(client);
+                read_line(&mut reader).unwrap();
+                let headers = read_headers(&mut reader).unwrap();
+                read_body(&mut reader, &headers, false).unwrap();
+                let header = |name| find_header(&headers, name).map(str::to_string);
+                seen.push((header("Authorization"), header("Cookie")));
+                reader.into_inner().send_all(response.as_bytes()).unwrap();
+            }
+            seen
+        })
+    }
+
+    #[test]
+    fn test_cross_host_redirect_strips_credentials() {
+        let other = TcpServer::bind("127.0.0.1:0").unwrap();
+        let other_base = format!("http://{}", other.local_addr().unwrap());
+        let other = serve_credentials(
+            other,
+            vec!["HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".into()],
+        );
+
+        let origin = TcpServer::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", origin.local_addr().unwrap());
+        let close = "Content-Length: 0\r\nConnection: close\r\n\r\n";
+        let origin = serve_credentials(
+            origin,
+            vec![
+                format!(
+                    "HTTP/1.1 307 Temporary Redirect\r\nLocation: /same\r\n{}",
+                    close
+                ),
+                format!(
+                    "HTTP/1.1 307 Temporary Redirect\r\nLocation: {}/next\r\n{}",
+                    other_base, close
+                ),
+            ],
+        );
+
+        let response = HttpClient::new()
+            .with_header("Cookie", "session=1")
+            .request(Method::Get, &format!("{}/start", base))
+            .header("Authorization", "Bearer secret")
+            .send()
+            .unwrap();
+        assert_eq!(response.status, 200);
+
+        let credentials = (
+            Some("Bearer secret".to_string()),
+            Some("session=1".to_string()),
+        );
+        assert_eq!(
+            origin.join().unwrap(),
+            vec![credentials.clone(), credentials]
+        );
+        assert_eq!(other.join().unwrap(), vec![(None, None)]);
+    }
+
+    #[test]
+    fn test_sent_post_is_not_retried() {
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", server.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (client, _) = server.accept().unwrap();
+            let mut reader = BufReader::new(client);
+            read_line(&mut reader).unwrap();
+            read_headers(&mut reader).unwrap();
+            reader
+                .get_mut()
+                .send_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .unwrap();
+
+            // Recebe o POST inteiro na conexão reaproveitada e fecha sem responder
+            let request_line = read_line(&mut reader).unwrap();
+            let headers = read_headers(&mut reader).unwrap();
+            read_body(&mut reader, &headers, false).unwrap();
+            drop(reader);
+
+            std::thread::sleep(Duration::from_millis(100));
+            server.set_nonblocking(true).unwrap();
+            (request_line, server.try_accept().unwrap().is_some())
+        });
+
+        let http = HttpClient::new();
+        assert_eq!(http.get(&format!("{}/a", base)).unwrap().text(), "ok");
+        assert!(http.post(&format!("{}/b", base), "once").is_err());
+
+        let (request_line, retried) = handle.join().unwrap();
+        assert_eq!(request_line, "POST /b HTTP/1.1");
+        assert!(!retried);
+    }
+
+    #[test]
+    fn test_redirect_limit() {
+        let (base, handle) = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\n\r\n",
+        ]);
+
+        let client = HttpClient::new().with_max_redirects(0);
+        let response = client.get(&format!("{}/loop", base)).unwrap();
+        assert_eq!(response.status, 302);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_server_routes() {
+        let server = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .with_threads(2)
+            .get("/hello", |req| {
+                let name = req.query_param("name").unwrap_or_else(|| "world".into());
+                HttpResponse::ok(format!("hello {}", name)).with_content_type("text/plain")
+            })
+            .post("/echo", |req| {
+                HttpResponse::new(201).with_body(req.body.clone())
+            })
+            .get("/files/*", |req| HttpResponse::ok(req.path.clone()))
+            .get("/panic", |_| panic!("handler failure"));
+        let handle = server.spawn().unwrap();
+        let base = format!("http://{}", handle.local_addr());
+        let http = HttpClient::new();
+
+        let response = http
+            .get(&format!("{}/hello?name=avila%20math", base))
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text(), "hello avila math");
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+
+        let response = http.post(&format!("{}/echo", base), "ping").unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.text(), "ping");
+
+        let response = http.get(&format!("{}/files/a/b.png", base)).unwrap();
+        assert_eq!(response.text(), "/files/a/b.png");
+
+        let head = http
+            .request(Method::Head, &format!("{}/hello", base))
+            .send()
+            .unwrap();
+        assert_eq!(head.status, 200);
+        assert!(head.body.is_empty());
+
+        assert_eq!(http.get(&format!("{}/missing", base)).unwrap().status, 404);
+        assert_eq!(http.delete(&format!("{}/echo", base)).unwrap().status, 405);
+        assert_eq!(http.get(&format!("{}/panic", base)).unwrap().status, 500);
+
+        handle.shutdown();
+    }
+
+    #[test]
+    fn test_http_server_keep_alive() {
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .get("/n", |_| HttpResponse::ok("1"))
+            .spawn()
+            .unwrap();
+
+        let client = TcpClient::connect(handle.local_addr()).unwrap();
+        let mut reader = BufReader::new(client);
+        for _ in 0..2 {
+            reader
+                .get_mut()
+                .send_all(b"GET /n HTTP/1.1\r\nHost: test\r\n\r\n")
+                .unwrap();
+            let response = read_response(&mut reader, Method::Get).unwrap();
+            assert_eq!(response.text(), "1");
+            assert_eq!(response.header("Connection"), Some("keep-alive"));
+        }
+    }
+
+    #[test]
+    fn test_http_server_limits() {
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .with_max_body_size(16)
+            .post("/echo", |req| HttpResponse::ok(req.body.clone()))
+            .spawn()
+            .unwrap();
+
+        let send = |raw: String| {
+            let mut client = TcpClient::connect(handle.local_addr()).unwrap();
+            client.send_all(raw.as_bytes()).unwrap();
+            read_response(&mut BufReader::new(client), Method::Post)
+                .unwrap()
+                .status
+        };
+        let post = |headers: &str, body: &str| {
+            send(format!("POST /echo HTTP/1.1\r\n{}\r\n{}", headers, body))
+        };
+
+        assert_eq!(post("Content-Length: 4\r\n", "ping"), 200);
+        assert_eq!(post("Content-Length: 1000000000\r\n", ""), 413);
+        let chunked = "Transfer-Encoding: chunked\r\n";
+        assert_eq!(
+            post(chunked, "10\r\n0123456789abcdef\r\n1\r\n!\r\n0\r\n\r\n"),
+            413
+        );
+        assert_eq!(post(&"X-Header: 1\r\n".repeat(MAX_HEADERS + 1), ""), 431);
+        let long_target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LENGTH));
+        assert_eq!(send(long_target), 431);
+    }
+
+    #[test]
+    fn test_http_server_idle_connection_yields_thread() {
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .with_threads(1)
+            .with_read_timeout(Duration::from_secs(30))
+            .get("/files/*", |req| HttpResponse::ok(req.path.clone()))
+            .spawn()
+            .unwrap();
+        let url = format!("http://{}/files/a%20b.txt", handle.local_addr());
+
+        // A conexão keep-alive ociosa ocupa a única thread
+        let first = HttpClient::new();
+        assert_eq!(first.get(&url).unwrap().text(), "/files/a b.txt");
+        assert_eq!(first.idle_connections(), 1);
+
+        let started = Instant::now();
+        let second = HttpClient::new().with_timeout(Duration::from_secs(10));
+        assert_eq!(second.get(&url).unwrap().text(), "/files/a b.txt");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // O primeiro client percebe que a conexão foi fechada e reconecta
+        assert_eq!(first.get(&url).unwrap().text(), "/files/a b.txt");
+    }
+
+    #[test]
+    fn test_download_and_resume() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let served = data.clone();
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .get("/patch.pak", move |req| {
+                let start = req
+                    .header("Range")
+                    .and_then(|r| r.strip_prefix("bytes="))
+                    .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+                match start {
+                    None => HttpResponse::ok(served.clone()),
+                    Some(start) if start >= served.len() => HttpResponse::new(416)
+                        .with_header("Content-Range", format!("bytes */{}", served.len())),
+                    Some(start) => HttpResponse::new(206)
+                        .with_header(
+                            "Content-Range",
+                            format!("bytes {}-{}/{}", start, served.len() - 1, served.len()),
+                        )
+                        .with_body(served[start..].to_vec()),
+                }
+            })
+            .spawn()
+            .unwrap();
+        let url = format!("http://{}/patch.pak", handle.local_addr());
+        let path = std::env::temp_dir().join(format!("avila_download_{}.pak", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let http = HttpClient::new();
+
+        let mut last = (0, None);
+        let size = http
+            .download(&url, &path, |done, total| last = (done, total))
+            .unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(last, (data.len() as u64, Some(data.len() as u64)));
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+
+        // Simula um download interrompido na metade
+        std::fs::write(&path, &data[..40_000]).unwrap();
+        let mut first = None;
+        let size = http
+            .download(&url, &path, |done, _| {
+                first.get_or_insert(done);
+            })
+            .unwrap();
+        assert_eq!(first, Some(40_000));
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+
+        // Já completo: o servidor responde 416
+        let size = http.download(&url, &path, |_, _| {}).unwrap();
+        assert_eq!(size, data.len() as u64);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            Some((Some(100), Some(1000)))
+        );
+        assert_eq!(
+            parse_content_range("bytes */1000"),
+            Some((None, Some(1000)))
+        );
+        assert_eq!(parse_content_range("bytes 0-9/*"), Some((Some(0), None)));
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+
+    #[test]
+    fn test_keep_alive_reuses_connections() {
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .get("/peer", |req| HttpResponse::ok(req.remote_addr.to_string()))
+            .get("/moved", |_| {
+                HttpResponse::new(302)
+                    .with_header("Location", "/peer")
+                    .with_body("see /peer")
+            })
+            .spawn()
+            .unwrap();
+        let url = format!("http://{}/peer", handle.local_addr());
+
+        let http = HttpClient::new();
+        let first = http.get(&url).unwrap().text();
+        assert_eq!(http.idle_connections(), 1);
+        assert_eq!(http.get(&url).unwrap().text(), first);
+        // O corpo do redirecionamento é descartado e a conexão reaproveitada
+        let redirected = http
+            .get(&format!("http://{}/moved", handle.local_addr()))
+            .unwrap();
+        assert_eq!(redirected.text(), first);
+        assert_eq!(http.idle_connections(), 1);
+
+        let no_pool = HttpClient::new().with_keep_alive(false);
+        let a = no_pool.get(&url).unwrap().text();
+        let b = no_pool.get(&url).unwrap().text();
+        assert_ne!(a, b);
+        assert_eq!(no_pool.idle_connections(), 0);
+    }
+
+    #[test]
+    fn test_stale_pooled_connection_is_retried() {
+        let handle = HttpServer::bind("127.0.0.1:0")
+            .unwrap()
+            .with_read_timeout(Duration::from_millis(20))
+            .get("/ping", |_| HttpResponse::ok("pong"))
+            .spawn()
+            .unwrap();
+        let url = format!("http://{}/ping", handle.local_addr());
+
+        let http = HttpClient::new();
+        assert_eq!(http.get(&url).unwrap().text(), "pong");
+        assert_eq!(http.idle_connections(), 1);
+
+        // O servidor fecha a conexão ociosa; o client deve reconectar sozinho
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(http.get(&url).unwrap().text(), "pong");
+    }
+}
@@ -0,0 +1,294 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use super::threading::{PoolStats, ThreadPool};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Job system com dependências entre jobs
+///
+/// Cada `schedule` retorna um `JobHandle`; jobs podem declarar que só rodam
+/// depois de outros terminarem (`schedule_after`), formando um DAG executado
+/// no `ThreadPool`:
+///
+/// ```
+/// use avila_math::os::JobSystem;
+///
+/// let jobs = JobSystem::new(2);
+/// let load = jobs.schedule(|| { /* carrega asset */ });
+/// let upload = jobs.schedule_after(&[&load], || { /* envia para a GPU */ });
+///
+/// jobs.wait(&upload);
+/// assert!(jobs.is_done(&load));
+/// ```
+pub struct JobSystem {
+    pool: Arc<ThreadPool>,
+    next_id: AtomicU64,
+}
+
+/// Handle para um job agendado
+#[derive(Clone)]
+pub struct JobHandle {
+    node: Arc<JobNode>,
+}
+
+struct JobNode {
+    id: u64,
+    state: Mutex<JobNodeState>,
+    finished: Condvar,
+}
+
+struct JobNodeState {
+    job: Option<Job>,
+    /// Dependências ainda não concluídas
+    remaining: usize,
+    done: bool,
+    panicked: bool,
+    dependents: Vec<Arc<JobNode>>,
+}
+
+impl JobSystem {
+    /// Cria um job system com seu próprio thread pool
+    pub fn new(num_threads: usize) -> Self {
+        Self::with_pool(Arc::new(ThreadPool::new(num_threads)))
+    }
+
+    /// Cria um job system sobre um thread pool existente
+    pub fn with_pool(pool: Arc<ThreadPool>) -> Self {
+        Self {
+            pool,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Retorna o thread pool usado pelo job system
+    pub fn pool(&self) -> &Arc<ThreadPool> {
+        &self.pool
+    }
+
+    /// Retorna as estatísticas do thread pool subjacente
+    pub fn stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+
+    /// Agenda um job sem dependências
+    pub fn schedule<F>(&self, f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule_after(&[], f)
+    }
+
+    /// Agenda um job que só roda depois de todas as dependências terminarem
+    pub fn schedule_after<F>(&self, dependencies: &[&JobHandle], f: F) -> JobHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let node = Arc::new(JobNode {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            state: Mutex::new(JobNodeState {
+                job: Some(Box::new(f)),
+                // Referência extra durante o registro evita despacho prematuro
+                remaining: 1,
+                done: false,
+                panicked: false,
+                dependents: Vec::new(),
+            }),
+            finished: Condvar::new(),
+        });
+
+        for dependency in dependencies {
+            let mut dep_state = dependency.node.state.lock().unwrap();
+            if !dep_state.done {
+                dep_state.dependents.push(Arc::clone(&node));
+                node.state.lock().unwrap().remaining += 1;
+            }
+        }
+
+        Self::release(&self.pool, &node);
+        JobHandle { node }
+    }
+
+    /// Agrupa vários handles em um único handle que conclui quando todos concluírem
+    pub fn combine(&self, handles: &[&JobHandle]) -> JobHandle {
+        self.schedule_after(handles, || {})
+    }
+
+    /// Bloqueia até o job terminar
+    ///
+    /// Não chame de dentro de um job do mesmo pool com todos os workers ocupados
+    /// esperando: isso pode travar o pool.
+    pub fn wait(&self, handle: &JobHandle) {
+        handle.wait();
+    }
+
+    /// Bloqueia até todos os jobs terminarem
+    pub fn wait_all(&self, handles: &[&JobHandle]) {
+        for handle in handles {
+            handle.wait();
+        }
+    }
+
+    /// Verifica se o job já terminou
+    pub fn is_done(&self, handle: &JobHandle) -> bool {
+        handle.is_done()
+    }
+
+    /// Decrementa o contador de dependências e despacha o job quando chega a zero
+    fn release(pool: &Arc<ThreadPool>, node: &Arc<JobNode>) {
+        let job = {
+            let mut state = node.state.lock().unwrap();
+            state.remaining -= 1;
+            if state.remaining > 0 {
+                return;
+            }
+            state.job.take()
+        };
+
+        if let Some(job) = job {
+            let pool_handle = Arc::clone(pool);
+            let node = Arc::clone(node);
+            pool.execute(move || Self::run(&pool_handle, &node, job));
+        }
+    }
+
+    fn run(pool: &Arc<ThreadPool>, node: &Arc<JobNode>, job: Job) {
+        let panicked = panic::catch_unwind(AssertUnwindSafe(job)).is_err();
+
+        let dependents = {
+            let mut state = node.state.lock().unwrap();
+            state.done = true;
+            state.panicked = panicked;
+            node.finished.notify_all();
+            std::mem::take(&mut state.dependents)
+        };
+
+        for dependent in &dependents {
+            Self::release(pool, dependent);
+        }
+    }
+}
+
+impl JobHandle {
+    /// Identificador único do job dentro do job system
+    pub fn id(&self) -> u64 {
+        self.node.id
+    }
+
+    /// Verifica se o job já terminou
+    pub fn is_done(&self) -> bool {
+        self.node.state.lock().unwrap().done
+    }
+
+    /// Verifica se o job terminou com panic
+    pub fn panicked(&self) -> bool {
+        self.node.state.lock().unwrap().panicked
+    }
+
+    /// Bloqueia até o job terminar
+    pub fn wait(&self) {
+        let mut state = self.node.state.lock().unwrap();
+        while !state.done {
+            state = self.node.finished.wait(state).unwrap();
+        }
+    }
+}
+
+impl std::fmt::Debug for JobHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("id", &self.id())
+            .field("done", &self.is_done())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_job_wait() {
+        let jobs = JobSystem::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let c = Arc::clone(&counter);
+        let handle = jobs.schedule(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+
+        jobs.wait(&handle);
+        assert!(jobs.is_done(&handle));
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_job_dependencies_order() {
+        let jobs = JobSystem::new(4);
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let o = Arc::clone(&order);
+        let a = jobs.schedule(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            o.lock().unwrap().push("a");
+        });
+        let o = Arc::clone(&order);
+        let b = jobs.schedule(move || o.lock().unwrap().push("b"));
+        let o = Arc::clone(&order);
+        let c = jobs.schedule_after(&[&a, &b], move || o.lock().unwrap().push("c"));
+        let o = Arc::clone(&order);
+        let d = jobs.schedule_after(&[&c], move || o.lock().unwrap().push("d"));
+
+        jobs.wait(&d);
+        let order = order.lock().unwrap();
+        assert_eq!(order.len(), 4);
+        assert_eq!(&order[2..], &["c", "d"]);
+    }
+
+    #[test]
+    fn test_dependency_already_done() {
+        let jobs = JobSystem::new(1);
+        let a = jobs.schedule(|| {});
+        jobs.wait(&a);
+
+        let b = jobs.schedule_after(&[&a], || {});
+        b.wait();
+        assert!(b.is_done());
+    }
+
+    #[test]
+    fn test_job_panic_releases_dependents() {
+        let jobs = JobSystem::new(2);
+        let a = jobs.schedule(|| panic!("job failure"));
+        let b = jobs.schedule_after(&[&a], || {});
+
+        jobs.wait(&b);
+        assert!(a.panicked());
+        assert!(!b.panicked());
+    }
+
+    #[test]
+    fn test_combine() {
+        let jobs = JobSystem::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let c = Arc::clone(&counter);
+                jobs.schedule(move || {
+                    c.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        let refs: Vec<_> = handles.iter().collect();
+        let all = jobs.combine(&refs);
+
+        all.wait();
+        assert_eq!(counter.load(Ordering::SeqCst), 10);
+        assert!(jobs.stats().jobs_completed >= 10);
+    }
+}
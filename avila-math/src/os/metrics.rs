@@ -0,0 +1,562 @@
+//! Métricas de recursos do sistema
+//!
+//! Memória física total/disponível, memória residente do processo e uso de
+//! CPU por núcleo, lidos do sistema operacional (`/proc` no Linux, Mach no
+//! macOS, Win32/ntdll no Windows). Complementa as estatísticas internas dos
+//! alocadores em `memory`, que só enxergam o que passou por eles.
+//!
+//! ```no_run
+//! use avila_math::os::{CpuSampler, SystemInfo};
+//! use std::time::Duration;
+//!
+//! if let Some(memory) = SystemInfo::memory_info() {
+//!     println!("RAM: {:.0}% em uso", memory.usage() * 100.0);
+//! }
+//!
+//! let mut cpu = CpuSampler::new();
+//! std::thread::sleep(Duration::from_millis(500));
+//! if let Some(usage) = cpu.sample() {
+//!     println!("CPU: {:.0}% (processo {:.0}%)", usage.total * 100.0, usage.process * 100.0);
+//!     for (core, load) in usage.per_core.iter().enumerate() {
+//!         println!("  núcleo {}: {:.0}%", core, load * 100.0);
+//!     }
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Memória física do sistema, em bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryInfo {
+    pub total: u64,
+    /// Memória que pode ser alocada sem swap (inclui caches liberáveis)
+    pub available: u64,
+}
+
+impl MemoryInfo {
+    /// Memória em uso
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Fração em uso (0.0 - 1.0)
+    pub fn usage(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f32 / self.total as f32
+        }
+    }
+}
+
+/// Lê a memória física do sistema
+pub fn memory_info() -> Option<MemoryInfo> {
+    imp::memory_info()
+}
+
+/// Memória residente (working set) do processo atual, em bytes
+pub fn process_memory_usage() -> Option<u64> {
+    imp::process_memory_usage()
+}
+
+/// Tempos acumulados de um núcleo, em unidades do sistema
+#[derive(Debug, Clone, Copy, Default)]
+struct CoreTimes {
+    busy: u64,
+    total: u64,
+}
+
+/// Uso de CPU entre duas amostras (frações de 0.0 a 1.0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuUsage {
+    pub per_core: Vec<f32>,
+    /// Média de todos os núcleos
+    pub total: f32,
+    /// Uso do processo atual, relativo à capacidade de todos os núcleos
+    pub process: f32,
+    /// Intervalo coberto pela amostra
+    pub interval: Duration,
+}
+
+/// Amostrador de uso de CPU
+///
+/// Cada `sample` compara os contadores do sistema com os da amostra
+/// anterior; chame em intervalos regulares (ex: a cada 0.5s no overlay de
+/// diagnóstico). A primeira amostra é relativa à criação do sampler.
+pub struct CpuSampler {
+    previous: Vec<CoreTimes>,
+    previous_process: Duration,
+    previous_at: Instant,
+}
+
+impl CpuSampler {
+    pub fn new() -> Self {
+        Self {
+            previous: imp::core_times().unwrap_or_default(),
+            previous_process: imp::process_cpu_time().unwrap_or_default(),
+            previous_at: Instant::now(),
+        }
+    }
+
+    /// Uso desde a amostra anterior; `None` se a plataforma não expõe os contadores
+    pub fn sample(&mut self) -> Option<CpuUsage> {
+        let current = imp::core_times()?;
+        let now = Instant::now();
+        let interval = now - self.previous_at;
+
+        let per_core: Vec<f32> = current
+            .iter()
+            .enumerate()
+            .map(|(i, core)| {
+                let previous = self.previous.get(i).copied().unwrap_or_default();
+                let total = core.total.saturating_sub(previous.total);
+                let busy = core.busy.saturating_sub(previous.busy);
+                if total == 0 {
+                    0.0
+                } else {
+                    (busy as f32 / total as f32).clamp(0.0, 1.0)
+                }
+            })
+            .collect();
+        let total = if per_core.is_empty() {
+            0.0
+        } else {
+            per_core.iter().sum::<f32>() / per_core.len() as f32
+        };
+
+        let process_time = imp::process_cpu_time().unwrap_or_default();
+        let process_delta = process_time.saturating_sub(self.previous_process);
+        let capacity = interval.as_secs_f32() * per_core.len().max(1) as f32;
+        let process = if capacity > 0.0 {
+            (process_delta.as_secs_f32() / capacity).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        self.previous = current;
+        self.previous_process = process_time;
+        self.previous_at = now;
+        Some(CpuUsage {
+            per_core,
+            total,
+            process,
+            interval,
+        })
+    }
+}
+
+impl Default for CpuSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Linux / Android: /proc
+// ============================================================================
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use super::CoreTimes;
+    use std::time::Duration;
+
+    fn meminfo_field(meminfo: &str, name: &str) -> Option<u64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        let kb: u64 = line[name.len()..]
+            .trim_start_matches(':')
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()?;
+        Some(kb * 1024)
+    }
+
+    pub fn memory_info() -> Option<super::MemoryInfo> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let total = meminfo_field(&meminfo, "MemTotal")?;
+        // Kernels antigos (< 3.14) não têm MemAvailable
+        let available = meminfo_field(&meminfo, "MemAvailable").or_else(|| {
+            Some(
+                meminfo_field(&meminfo, "MemFree")?
+                    + meminfo_field(&meminfo, "Buffers").unwrap_or(0)
+                    + meminfo_field(&meminfo, "Cached").unwrap_or(0),
+            )
+        })?;
+        Some(super::MemoryInfo { total, available })
+    }
+
+    pub fn process_memory_usage() -> Option<u64> {
+        let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+        let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+        // SAFETY: sysconf não tem pré-condições
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        Some(pages * page_size.max(0) as u64)
+    }
+
+    pub fn core_times() -> Option<Vec<CoreTimes>> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        let cores: Vec<CoreTimes> = stat
+            .lines()
+            .filter(|line| line.starts_with("cpu") && !line.starts_with("cpu "))
+            .map(|line| {
+                // user nice system idle iowait irq softirq steal
+                let fields: Vec<u64> = line
+                    .split_whitespace()
+                    .skip(1)
+                    .take(8)
+                    .map(|field| field.parse().unwrap_or(0))
+                    .collect();
+                let total: u64 = fields.iter().sum();
+                let idle =
+                    fields.get(3).copied().unwrap_or(0) + fields.get(4).copied().unwrap_or(0);
+                CoreTimes {
+                    busy: total - idle,
+                    total,
+                }
+            })
+            .collect();
+        (!cores.is_empty()).then_some(cores)
+    }
+
+    pub fn process_cpu_time() -> Option<Duration> {
+        super::unix_process_cpu_time()
+    }
+}
+
+#[cfg(unix)]
+fn unix_process_cpu_time() -> Option<Duration> {
+    // SAFETY: rusage zerada é um valor válido; getrusage só escreve nela
+    let usage = unsafe {
+        let mut usage: libc::rusage = std::mem::zeroed();
+        if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+            return None;
+        }
+        usage
+    };
+    let timeval = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    Some(timeval(usage.ru_utime) + timeval(usage.ru_stime))
+}
+
+// ============================================================================
+// macOS / iOS: sysctl + Mach
+// ============================================================================
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod imp {
+    use super::CoreTimes;
+    use std::time::Duration;
+
+    type MachPort = u32;
+
+    const PROCESSOR_CPU_LOAD_INFO: i32 = 2;
+    const HOST_VM_INFO64: i32 = 4;
+    // user, system, idle, nice
+    const CPU_STATE_MAX: usize = 4;
+    const CPU_STATE_IDLE: usize = 2;
+
+    extern "C" {
+        static mach_task_self_: MachPort;
+        fn mach_host_self() -> MachPort;
+        fn host_processor_info(
+            host: MachPort,
+            flavor: i32,
+            processor_count: *mut u32,
+            info: *mut *mut i32,
+            info_count: *mut u32,
+        ) -> i32;
+        fn host_statistics64(host: MachPort, flavor: i32, info: *mut i32, count: *mut u32) -> i32;
+        fn vm_deallocate(task: MachPort, address: usize, size: usize) -> i32;
+    }
+
+    fn sysctl_u64(name: &std::ffi::CStr) -> Option<u64> {
+        let mut value = 0u64;
+        let mut size = std::mem::size_of::<u64>();
+        // SAFETY: value/size descrevem um buffer de 8 bytes
+        let result = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (result == 0).then_some(value)
+    }
+
+    pub fn memory_info() -> Option<super::MemoryInfo> {
+        let total = sysctl_u64(c"hw.memsize")?;
+        // SAFETY: vm_statistics64 zerada é válida; count é seu tamanho em i32
+        let stats = unsafe {
+            let mut stats: libc::vm_statistics64 = std::mem::zeroed();
+            let mut count = (std::mem::size_of::<libc::vm_statistics64>() / 4) as u32;
+            let result = host_statistics64(
+                mach_host_self(),
+                HOST_VM_INFO64,
+                &mut stats as *mut _ as *mut i32,
+                &mut count,
+            );
+            if result != 0 {
+                return None;
+            }
+            stats
+        };
+        // SAFETY: sysconf não tem pré-condições
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+        let pages =
+            stats.free_count as u64 + stats.inactive_count as u64 + stats.speculative_count as u64;
+        Some(super::MemoryInfo {
+            total,
+            available: (pages * page_size).min(total),
+        })
+    }
+
+    pub fn process_memory_usage() -> Option<u64> {
+        // SAFETY: proc_taskinfo zerada é válida e o tamanho passado é o dela
+        unsafe {
+            let mut info: libc::proc_taskinfo = std::mem::zeroed();
+            let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+            let written = libc::proc_pidinfo(
+                libc::getpid(),
+                libc::PROC_PIDTASKINFO,
+                0,
+                &mut info as *mut _ as *mut libc::c_void,
+                size,
+            );
+            (written == size).then_some(info.pti_resident_size)
+        }
+    }
+
+    pub fn core_times() -> Option<Vec<CoreTimes>> {
+        let mut count = 0u32;
+        let mut info: *mut i32 = std::ptr::null_mut();
+        let mut info_count = 0u32;
+        // SAFETY: o kernel aloca `info` com `info_count` inteiros, liberados
+        // com vm_deallocate depois da cópia
+        unsafe {
+            let result = host_processor_info(
+                mach_host_self(),
+                PROCESSOR_CPU_LOAD_INFO,
+                &mut count,
+                &mut info,
+                &mut info_count,
+            );
+            if result != 0 || info.is_null() {
+                return None;
+            }
+            let ticks = std::slice::from_raw_parts(info as *const u32, info_count as usize);
+            let cores = ticks
+                .chunks_exact(CPU_STATE_MAX)
+                .take(count as usize)
+                .map(|core| {
+                    let total: u64 = core.iter().map(|&t| t as u64).sum();
+                    CoreTimes {
+                        busy: total - core[CPU_STATE_IDLE] as u64,
+                        total,
+                    }
+                })
+                .collect();
+            vm_deallocate(
+                mach_task_self_,
+                info as usize,
+                info_count as usize * std::mem::size_of::<i32>(),
+            );
+            Some(cores)
+        }
+    }
+
+    pub fn process_cpu_time() -> Option<Duration> {
+        super::unix_process_cpu_time()
+    }
+}
+
+// ============================================================================
+// Windows
+// ============================================================================
+
+#[cfg(windows)]
+mod imp {
+    use super::CoreTimes;
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    /// SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION (tempos em unidades de 100ns)
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct ProcessorPerformance {
+        idle_time: i64,
+        kernel_time: i64,
+        user_time: i64,
+        dpc_time: i64,
+        interrupt_time: i64,
+        interrupt_count: u32,
+    }
+
+    const SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION: u32 = 8;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQuerySystemInformation(
+            class: u32,
+            info: *mut std::ffi::c_void,
+            length: u32,
+            return_length: *mut u32,
+        ) -> i32;
+    }
+
+    pub fn memory_info() -> Option<super::MemoryInfo> {
+        // SAFETY: dwLength precisa ser inicializado antes da chamada
+        unsafe {
+            let mut status: MEMORYSTATUSEX = std::mem::zeroed();
+            status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+            if GlobalMemoryStatusEx(&mut status) == 0 {
+                return None;
+            }
+            Some(super::MemoryInfo {
+                total: status.ullTotalPhys,
+                available: status.ullAvailPhys,
+            })
+        }
+    }
+
+    pub fn process_memory_usage() -> Option<u64> {
+        // SAFETY: counters zerados com o tamanho correto
+        unsafe {
+            let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+            let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+            counters.cb = size;
+            if GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) == 0 {
+                return None;
+            }
+            Some(counters.WorkingSetSize as u64)
+        }
+    }
+
+    pub fn core_times() -> Option<Vec<CoreTimes>> {
+        let count = super::super::threading::num_cpus().max(1);
+        let mut info = vec![ProcessorPerformance::default(); count];
+        let mut returned = 0u32;
+        // SAFETY: o buffer tem `count` entradas do tamanho esperado
+        let status = unsafe {
+            NtQuerySystemInformation(
+                SYSTEM_PROCESSOR_PERFORMANCE_INFORMATION,
+                info.as_mut_ptr() as *mut std::ffi::c_void,
+                (count * std::mem::size_of::<ProcessorPerformance>()) as u32,
+                &mut returned,
+            )
+        };
+        if status < 0 {
+            return None;
+        }
+        info.truncate(returned as usize / std::mem::size_of::<ProcessorPerformance>());
+        Some(
+            info.iter()
+                .map(|core| {
+                    // kernel_time inclui o tempo ocioso
+                    let total = (core.kernel_time + core.user_time).max(0) as u64;
+                    CoreTimes {
+                        busy: total.saturating_sub(core.idle_time.max(0) as u64),
+                        total,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub fn process_cpu_time() -> Option<Duration> {
+        let zero = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+        // SAFETY: os quatro FILETIMEs são válidos para escrita
+        let ok = unsafe {
+            GetProcessTimes(
+                GetCurrentProcess(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        if ok == 0 {
+            return None;
+        }
+        let ticks =
+            |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+        Some(Duration::from_nanos((ticks(kernel) + ticks(user)) * 100))
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    windows
+)))]
+mod imp {
+    use super::CoreTimes;
+    use std::time::Duration;
+
+    pub fn memory_info() -> Option<super::MemoryInfo> {
+        None
+    }
+
+    pub fn process_memory_usage() -> Option<u64> {
+        None
+    }
+
+    pub fn core_times() -> Option<Vec<CoreTimes>> {
+        None
+    }
+
+    pub fn process_cpu_time() -> Option<Duration> {
+        #[cfg(unix)]
+        return super::unix_process_cpu_time();
+        #[cfg(not(unix))]
+        None
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos", windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_info() {
+        let memory = memory_info().unwrap();
+        assert!(memory.total > 0);
+        assert!(memory.available <= memory.total);
+        assert!((0.0..=1.0).contains(&memory.usage()));
+
+        let resident = process_memory_usage().unwrap();
+        assert!(resident > 0 && resident < memory.total);
+    }
+
+    #[test]
+    fn test_cpu_sampler() {
+        let mut sampler = CpuSampler::new();
+        // Gasta um pouco de CPU para o processo aparecer na amostra
+        let start = Instant::now();
+        let mut x = 0u64;
+        while start.elapsed() < Duration::from_millis(30) {
+            x = x.wrapping_mul(31).wrapping_add(std::hint::black_box(7));
+        }
+        std::hint::black_box(x);
+
+        let usage = sampler.sample().unwrap();
+        assert!(!usage.per_core.is_empty());
+        assert!(usage.per_core.iter().all(|load| (0.0..=1.0).contains(load)));
+        assert!((0.0..=1.0).contains(&usage.total));
+        assert!(usage.process > 0.0);
+        assert!(usage.interval >= Duration::from_millis(30));
+    }
+}
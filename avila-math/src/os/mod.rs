@@ -1,17 +1,66 @@
 pub mod clock;
+pub mod compress;
+pub mod config;
+pub mod cpu;
+pub mod crash;
+pub mod dialog;
+pub mod discovery;
+pub mod dns;
 pub mod filesystem;
+pub mod hash;
+pub mod http;
+pub mod jobs;
+pub mod metrics;
 pub mod network;
+pub mod pack;
+pub mod poll;
+pub mod process;
+pub mod signal;
+pub mod task;
 pub mod threading;
-
-pub use clock::{sleep, sleep_ms, Clock, DeltaTime, FpsCounter, Profiler, Stopwatch, Timer};
+pub mod timer_wheel;
+pub mod vfs;
+pub mod watcher;
+pub mod wire;
+
+pub use clock::{
+    sleep, sleep_ms, Clock, DateTime, DateTimeParseError, DeltaTime, FixedTimestep, FpsCounter,
+    FrameStats, GameClock, Profiler, Stopwatch, Timer, TraceEvent, Weekday,
+};
+pub use compress::{Codec, Compressor, Decompressor, Lz4Reader, Lz4Writer};
+pub use config::{Config, ConfigError, ConfigLayer};
+pub use cpu::{CpuFeatures, CpuTopology};
+pub use crash::{CrashReport, CrashReporter};
+pub use dialog::{DialogResult, FileDialog, MessageButtons, MessageLevel};
+pub use discovery::{DiscoveredServer, Discovery};
+pub use dns::Resolver;
 pub use filesystem::{
-    DirectoryWalker, FileHandle, FileMetadata, FileSystem, FileWatcher, PathUtil,
+    glob_match, walk, DirEntry, DirectoryWalker, FileHandle, FileLock, FileMetadata, FileSystem,
+    MappedFile, PathUtil,
+};
+pub use hash::{Crc32, Digest, HashAlgorithm, Sha256, StreamHasher, XxHash64};
+pub use http::{
+    HttpClient, HttpRequest, HttpResponse, HttpServer, HttpServerHandle, Method, ServerRequest,
 };
-pub use network::{HttpClient, IpAddress, Network, NetworkBuffer, TcpClient, TcpServer, UdpClient};
+pub use jobs::{JobHandle, JobSystem};
+pub use metrics::{CpuSampler, CpuUsage, MemoryInfo};
+pub use network::{
+    IpAddress, Network, NetworkBuffer, NetworkReader, TcpClient, TcpServer, TrafficStats,
+    UdpClient, Url,
+};
+pub use pack::{PackBuilder, PackEntry, PackFile};
+pub use poll::{Interest, PollEvent, Pollable, Poller};
+pub use process::{CapturedOutput, ChildProcess, CommandBuilder, ProcessOutput, StdioMode};
+pub use signal::Signal;
 pub use threading::{
-    num_cpus, yield_now, ManagedThread, RwCounter, Semaphore, ShutdownFlag, TaskScheduler,
-    ThreadBarrier, ThreadPool,
+    num_cpus, yield_now, BrokenPromise, CancellationToken, LockMetrics, ManagedThread, PoolStats,
+    RwCounter, RwCounterReadGuard, RwCounterWriteGuard, ScheduledTask, Scope, Semaphore,
+    ShutdownFlag, TaskFuture, TaskScheduler, ThreadBarrier, ThreadPool, WorkerStats,
 };
+pub use timer_wheel::{TimerCallback, TimerId, TimerWheel};
+pub use vfs::Vfs;
+pub use watcher::{FileEvent, FileWatcher};
+pub use wire::{decode_from_slice, encode_to_vec, Decode, Encode};
 
 /// Informações sobre o sistema operacional
 pub struct SystemInfo;
@@ -57,6 +106,56 @@ impl SystemInfo {
         num_cpus()
     }
 
+    /// Extensões SIMD suportadas (SSE/AVX/NEON)
+    pub fn cpu_features() -> &'static CpuFeatures {
+        cpu::cpu_features()
+    }
+
+    /// Núcleos físicos/lógicos e caches
+    pub fn cpu_topology() -> &'static CpuTopology {
+        cpu::cpu_topology()
+    }
+
+    /// Número de núcleos físicos
+    pub fn physical_cores() -> usize {
+        cpu::cpu_topology().physical_cores
+    }
+
+    /// Número de threads de hardware
+    pub fn logical_cores() -> usize {
+        cpu::cpu_topology().logical_cores
+    }
+
+    /// Tamanho da linha de cache, em bytes
+    pub fn cache_line_size() -> usize {
+        cpu::cpu_topology().cache_line_size
+    }
+
+    /// Memória física total, em bytes
+    pub fn total_memory() -> Option<u64> {
+        metrics::memory_info().map(|memory| memory.total)
+    }
+
+    /// Memória física disponível, em bytes
+    pub fn available_memory() -> Option<u64> {
+        metrics::memory_info().map(|memory| memory.available)
+    }
+
+    /// Memória total e disponível numa única leitura
+    pub fn memory_info() -> Option<MemoryInfo> {
+        metrics::memory_info()
+    }
+
+    /// Memória residente do processo atual, em bytes
+    pub fn process_memory_usage() -> Option<u64> {
+        metrics::process_memory_usage()
+    }
+
+    /// Cria um amostrador de uso de CPU por núcleo
+    pub fn cpu_sampler() -> CpuSampler {
+        CpuSampler::new()
+    }
+
     /// Retorna o hostname
     pub fn hostname() -> Option<String> {
         Network::hostname()
@@ -114,6 +213,30 @@ impl Environment {
     pub fn exists(key: &str) -> bool {
         std::env::var(key).is_ok()
     }
+
+    /// Carrega um arquivo `.env` sem sobrescrever variáveis já definidas
+    ///
+    /// Retorna quantas variáveis foram definidas.
+    pub fn load_dotenv<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<usize> {
+        Self::apply_dotenv(path.as_ref(), false)
+    }
+
+    /// Carrega um arquivo `.env`, sobrescrevendo variáveis existentes
+    pub fn load_dotenv_override<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<usize> {
+        Self::apply_dotenv(path.as_ref(), true)
+    }
+
+    fn apply_dotenv(path: &std::path::Path, overwrite: bool) -> std::io::Result<usize> {
+        let content = std::fs::read_to_string(path)?;
+        let mut count = 0;
+        for (key, value) in config::parse_dotenv(&content) {
+            if overwrite || !Self::exists(&key) {
+                Self::set(&key, &value);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
 }
 
 /// Processo
@@ -140,6 +263,14 @@ impl Process {
         std::process::Command::new(command).args(args).spawn()
     }
 
+    /// Executa um comando com stdin/stdout/stderr em pipes
+    pub fn spawn_piped(command: &str, args: &[&str]) -> std::io::Result<ChildProcess> {
+        CommandBuilder::new(command)
+            .args(args)
+            .stdio(StdioMode::Piped)
+            .spawn()
+    }
+
     /// Executa um comando e aguarda
     pub fn run(command: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
         std::process::Command::new(command).args(args).output()
@@ -272,6 +403,24 @@ mod tests {
         assert!(!Environment::exists("TEST_VAR"));
     }
 
+    #[test]
+    fn test_load_dotenv() {
+        let path = std::env::temp_dir().join(format!("avila_dotenv_{}.env", Process::id()));
+        std::fs::write(&path, "AVILA_DOTENV_A=from_file\nAVILA_DOTENV_B=\"two words\"\n").unwrap();
+        Environment::set("AVILA_DOTENV_A", "existing");
+
+        assert_eq!(Environment::load_dotenv(&path).unwrap(), 1);
+        assert_eq!(Environment::get("AVILA_DOTENV_A").unwrap(), "existing");
+        assert_eq!(Environment::get("AVILA_DOTENV_B").unwrap(), "two words");
+
+        assert_eq!(Environment::load_dotenv_override(&path).unwrap(), 2);
+        assert_eq!(Environment::get("AVILA_DOTENV_A").unwrap(), "from_file");
+
+        Environment::remove("AVILA_DOTENV_A");
+        Environment::remove("AVILA_DOTENV_B");
+        std::fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_process() {
         assert!(Process::id() > 0);
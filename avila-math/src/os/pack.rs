@@ -0,0 +1,451 @@
+//! Arquivos de pacote (`.pak`)
+//!
+//! Um `PackFile` junta vários arquivos em um só: cabeçalho, os dados de cada
+//! entrada (opcionalmente comprimidos com LZ4) e um índice no final. A
+//! leitura usa um arquivo mapeado em memória, então entradas sem compressão
+//! são retornadas sem cópia.
+//!
+//! ```no_run
+//! use avila_math::os::{PackBuilder, PackFile};
+//!
+//! let mut builder = PackBuilder::create("assets.pak").unwrap();
+//! builder.add_dir("", "assets").unwrap();
+//! builder.finish().unwrap();
+//!
+//! let pack = PackFile::open("assets.pak").unwrap();
+//! let shader = pack.read_to_string("shaders/lit.wgsl").unwrap();
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::compress::{lz4_compress_block, lz4_decompress_block};
+use super::filesystem::{walk, MappedFile};
+use super::wire::{decode_from_slice, encode_to_vec};
+
+const MAGIC: [u8; 4] = *b"AVPK";
+const VERSION: u16 = 1;
+/// Magic + versão + flags reservadas
+const HEADER_SIZE: u64 = 8;
+/// Offset do índice (u64) + tamanho do índice (u64) + magic
+const TRAILER_SIZE: usize = 20;
+
+const COMPRESSION_NONE: u8 = 0;
+const COMPRESSION_LZ4: u8 = 1;
+
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    name: String,
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compression: u8,
+}
+crate::impl_wire!(IndexEntry {
+    name,
+    offset,
+    stored_size,
+    size,
+    compression,
+});
+
+/// Informações de uma entrada do pacote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackEntry {
+    pub name: String,
+    /// Tamanho descomprimido
+    pub size: u64,
+    /// Tamanho ocupado no pacote
+    pub stored_size: u64,
+    pub compressed: bool,
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Normaliza um caminho virtual: separadores `/`, sem `.`/vazios no meio e sem `..`
+pub(crate) fn normalize_name(name: &str) -> io::Result<String> {
+    let mut segments = Vec::new();
+    for segment in name.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("'..' is not allowed in pack paths: {}", name),
+                ))
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Ok(segments.join("/"))
+}
+
+/// Escreve um `PackFile`
+///
+/// As entradas são gravadas conforme adicionadas; o índice vai no final, em
+/// `finish`. Com compressão (padrão), cada entrada é comprimida com LZ4 se
+/// isso reduzir o tamanho.
+pub struct PackBuilder<W: Write> {
+    writer: W,
+    offset: u64,
+    entries: Vec<IndexEntry>,
+    names: HashMap<String, usize>,
+    compress: bool,
+}
+
+impl PackBuilder<BufWriter<File>> {
+    /// Cria (ou sobrescreve) um pacote no caminho dado
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> PackBuilder<W> {
+    /// Começa um pacote em qualquer writer
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            offset: HEADER_SIZE,
+            entries: Vec::new(),
+            names: HashMap::new(),
+            compress: true,
+        })
+    }
+
+    /// Ativa/desativa a compressão LZ4 das próximas entradas
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Adiciona uma entrada; nomes repetidos são um erro
+    pub fn add(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        let name = normalize_name(name)?;
+        if name.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pack entry name is empty",
+            ));
+        }
+        if self.names.contains_key(&name) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("duplicate pack entry: {}", name),
+            ));
+        }
+
+        let compressed = if self.compress && !data.is_empty() {
+            Some(lz4_compress_block(data)).filter(|c| c.len() < data.len())
+        } else {
+            None
+        };
+        let (stored, compression) = match &compressed {
+            Some(compressed) => (compressed.as_slice(), COMPRESSION_LZ4),
+            None => (data, COMPRESSION_NONE),
+        };
+        self.writer.write_all(stored)?;
+
+        self.names.insert(name.clone(), self.entries.len());
+        self.entries.push(IndexEntry {
+            name,
+            offset: self.offset,
+            stored_size: stored.len() as u64,
+            size: data.len() as u64,
+            compression,
+        });
+        self.offset += stored.len() as u64;
+        Ok(())
+    }
+
+    /// Adiciona o conteúdo de um arquivo do disco
+    pub fn add_file<P: AsRef<Path>>(&mut self, name: &str, path: P) -> io::Result<()> {
+        let data = std::fs::read(path)?;
+        self.add(name, &data)
+    }
+
+    /// Adiciona todos os arquivos de um diretório sob `prefix`, em ordem alfabética
+    ///
+    /// Retorna quantos arquivos foram adicionados.
+    pub fn add_dir<P: AsRef<Path>>(&mut self, prefix: &str, dir: P) -> io::Result<usize> {
+        let dir = dir.as_ref();
+        let mut count = 0;
+        for entry in walk(dir).sorted().files_only() {
+            let entry = entry?;
+            let relative = entry
+                .path()
+                .strip_prefix(dir)
+                .map_err(|_| invalid("walker returned a path outside the directory"))?;
+            let relative = relative
+                .to_str()
+                .ok_or_else(|| invalid(format!("non UTF-8 path: {}", relative.display())))?;
+            self.add_file(&format!("{}/{}", prefix, relative), entry.path())?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Número de entradas adicionadas
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Escreve o índice e devolve o writer
+    pub fn finish(mut self) -> io::Result<W> {
+        let index = encode_to_vec(&self.entries);
+        self.writer.write_all(&index)?;
+        self.writer.write_all(&self.offset.to_le_bytes())?;
+        self.writer.write_all(&(index.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+enum PackData {
+    Mapped(MappedFile),
+    Memory(Vec<u8>),
+}
+
+impl PackData {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            PackData::Mapped(mapped) => mapped.as_bytes(),
+            PackData::Memory(bytes) => bytes,
+        }
+    }
+}
+
+/// Pacote aberto para leitura
+pub struct PackFile {
+    data: PackData,
+    /// Ordenado por nome
+    entries: Vec<IndexEntry>,
+    lookup: HashMap<String, usize>,
+}
+
+impl PackFile {
+    /// Abre um pacote mapeando o arquivo em memória
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(PackData::Mapped(MappedFile::open(path)?))
+    }
+
+    /// Abre um pacote já carregado em memória (ex: embutido com `include_bytes!`)
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        Self::parse(PackData::Memory(bytes))
+    }
+
+    fn parse(data: PackData) -> io::Result<Self> {
+        let bytes = data.bytes();
+        if bytes.len() < HEADER_SIZE as usize + TRAILER_SIZE || bytes[..4] != MAGIC {
+            return Err(invalid("not a pack file"));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(invalid(format!("unsupported pack version {}", version)));
+        }
+
+        let trailer = &bytes[bytes.len() - TRAILER_SIZE..];
+        if trailer[16..] != MAGIC {
+            return Err(invalid("pack trailer is missing (truncated file?)"));
+        }
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        let index_len = u64::from_le_bytes(trailer[8..16].try_into().unwrap());
+        let data_end = (bytes.len() - TRAILER_SIZE) as u64;
+        if index_offset < HEADER_SIZE || index_offset.checked_add(index_len) != Some(data_end) {
+            return Err(invalid("pack index is out of bounds"));
+        }
+
+        let index = &bytes[index_offset as usize..data_end as usize];
+        let mut entries: Vec<IndexEntry> = decode_from_slice(index)?;
+        for entry in &entries {
+            let end = entry.offset.checked_add(entry.stored_size);
+            if entry.offset < HEADER_SIZE || end.is_none_or(|end| end > index_offset) {
+                return Err(invalid(format!("pack entry out of bounds: {}", entry.name)));
+            }
+            if entry.compression > COMPRESSION_LZ4 {
+                return Err(invalid(format!("unknown compression for {}", entry.name)));
+            }
+        }
+
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let lookup = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.name.clone(), i))
+            .collect();
+        Ok(Self {
+            data,
+            entries,
+            lookup,
+        })
+    }
+
+    fn find(&self, name: &str) -> Option<&IndexEntry> {
+        let name = normalize_name(name).ok()?;
+        self.lookup.get(&name).map(|&i| &self.entries[i])
+    }
+
+    /// Verifica se o pacote contém a entrada
+    pub fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Informações de uma entrada
+    pub fn entry(&self, name: &str) -> Option<PackEntry> {
+        self.find(name).map(|entry| PackEntry {
+            name: entry.name.clone(),
+            size: entry.size,
+            stored_size: entry.stored_size,
+            compressed: entry.compression != COMPRESSION_NONE,
+        })
+    }
+
+    /// Nomes das entradas, em ordem alfabética
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// Número de entradas
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Lê uma entrada; sem cópia se ela não estiver comprimida
+    pub fn read(&self, name: &str) -> io::Result<Cow<'_, [u8]>> {
+        let entry = self.find(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found in pack", name),
+            )
+        })?;
+        let start = entry.offset as usize;
+        let stored = &self.data.bytes()[start..start + entry.stored_size as usize];
+
+        match entry.compression {
+            COMPRESSION_LZ4 => {
+                let size = usize::try_from(entry.size)
+                    .map_err(|_| invalid("pack entry too large for this platform"))?;
+                let data = lz4_decompress_block(stored, size)?;
+                if data.len() != size {
+                    return Err(invalid(format!("size mismatch in {}", entry.name)));
+                }
+                Ok(Cow::Owned(data))
+            }
+            _ => Ok(Cow::Borrowed(stored)),
+        }
+    }
+
+    /// Lê uma entrada como texto UTF-8
+    pub fn read_to_string(&self, name: &str) -> io::Result<String> {
+        let bytes = self.read(name)?.into_owned();
+        String::from_utf8(bytes).map_err(|err| invalid(err.to_string()))
+    }
+}
+
+impl std::fmt::Debug for PackFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackFile")
+            .field("entries", &self.entries.len())
+            .field("size", &self.data.bytes().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(compress: bool) -> Vec<u8> {
+        let mut builder = PackBuilder::new(Vec::new())
+            .unwrap()
+            .with_compression(compress);
+        builder
+            .add("shaders/lit.wgsl", &b"fn main() {}\n".repeat(50))
+            .unwrap();
+        builder.add("/textures\\ui/./icon.png", &[1, 2, 3]).unwrap();
+        builder.add("empty.txt", b"").unwrap();
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        for compress in [true, false] {
+            let pack = PackFile::from_bytes(build(compress)).unwrap();
+            assert_eq!(
+                pack.names().collect::<Vec<_>>(),
+                ["empty.txt", "shaders/lit.wgsl", "textures/ui/icon.png"]
+            );
+
+            let shader = pack.entry("shaders/lit.wgsl").unwrap();
+            assert_eq!(shader.size, 650);
+            assert_eq!(shader.compressed, compress);
+            assert_eq!(
+                pack.read_to_string("shaders/lit.wgsl").unwrap(),
+                "fn main() {}\n".repeat(50)
+            );
+
+            // Incompressível: guardado sem compressão e lido sem cópia
+            let icon = pack.read("textures/ui/icon.png").unwrap();
+            assert!(matches!(icon, Cow::Borrowed(&[1, 2, 3])));
+            assert!(pack.read("empty.txt").unwrap().is_empty());
+            assert_eq!(
+                pack.read("missing").unwrap_err().kind(),
+                io::ErrorKind::NotFound
+            );
+        }
+    }
+
+    #[test]
+    fn test_pack_from_disk() {
+        let dir = std::env::temp_dir().join(format!("avila_pack_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src/sub")).unwrap();
+        std::fs::write(dir.join("src/a.txt"), "alpha").unwrap();
+        std::fs::write(dir.join("src/sub/b.txt"), "beta".repeat(100)).unwrap();
+
+        let pack_path = dir.join("data.pak");
+        let mut builder = PackBuilder::create(&pack_path).unwrap();
+        assert_eq!(builder.add_dir("assets", dir.join("src")).unwrap(), 2);
+        let error = builder.add("assets/a.txt", b"again").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        builder.finish().unwrap();
+
+        let pack = PackFile::open(&pack_path).unwrap();
+        assert_eq!(pack.len(), 2);
+        assert_eq!(pack.read_to_string("assets/a.txt").unwrap(), "alpha");
+        assert_eq!(pack.read("assets/sub/b.txt").unwrap().len(), 400);
+        drop(pack);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pack_rejects_corruption() {
+        let bytes = build(true);
+        assert!(PackFile::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+        assert!(PackFile::from_bytes(b"AVPK".to_vec()).is_err());
+
+        let mut bad_index = bytes.clone();
+        let len = bad_index.len();
+        bad_index[len - 20] ^= 0xff;
+        assert!(PackFile::from_bytes(bad_index).is_err());
+
+        assert!(normalize_name("../etc/passwd").is_err());
+        assert_eq!(normalize_name("./a//b\\c").unwrap(), "a/b/c");
+    }
+}
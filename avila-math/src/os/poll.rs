@@ -0,0 +1,604 @@
+//! Notificação de prontidão (readiness) para sockets não bloqueantes
+//!
+//! Um único `Poller` acompanha centenas de conexões numa thread: os sockets
+//! são registrados com um token e `poll` devolve quais ficaram prontos para
+//! leitura/escrita. Backends: epoll (Linux/Android), `poll(2)` (demais Unix)
+//! e `WSAPoll` (Windows). A semântica é level-triggered em todos eles.
+//!
+//! ```no_run
+//! use avila_math::os::{Interest, Poller, TcpServer};
+//! use std::time::Duration;
+//!
+//! let server = TcpServer::bind("127.0.0.1:7777").unwrap();
+//! server.set_nonblocking(true).unwrap();
+//!
+//! let poller = Poller::new().unwrap();
+//! poller.register(&server, 0, Interest::READABLE).unwrap();
+//!
+//! let mut events = Vec::new();
+//! poller.poll(&mut events, Some(Duration::from_millis(100))).unwrap();
+//! for event in &events {
+//!     if event.token == 0 {
+//!         while let Some((client, addr)) = server.try_accept().unwrap() {
+//!             println!("nova conexão de {}", addr);
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::io;
+use std::time::Duration;
+
+use super::network::{TcpClient, TcpServer, UdpClient};
+
+/// Tipos de prontidão que interessam ao registro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interest {
+    readable: bool,
+    writable: bool,
+}
+
+impl Interest {
+    pub const READABLE: Interest = Interest {
+        readable: true,
+        writable: false,
+    };
+    pub const WRITABLE: Interest = Interest {
+        readable: false,
+        writable: true,
+    };
+    pub const BOTH: Interest = Interest {
+        readable: true,
+        writable: true,
+    };
+
+    pub fn is_readable(&self) -> bool {
+        self.readable
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+}
+
+/// Evento de prontidão retornado por `Poller::poll`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollEvent {
+    /// Token informado no registro
+    pub token: usize,
+    pub readable: bool,
+    pub writable: bool,
+    /// O outro lado fechou a conexão
+    pub hangup: bool,
+    pub error: bool,
+}
+
+/// Socket que pode ser registrado num `Poller`
+pub trait Pollable {
+    #[cfg(unix)]
+    fn raw_socket(&self) -> std::os::unix::io::RawFd;
+
+    #[cfg(windows)]
+    fn raw_socket(&self) -> std::os::windows::io::RawSocket;
+}
+
+impl Pollable for TcpServer {
+    #[cfg(unix)]
+    fn raw_socket(&self) -> std::os::unix::io::RawFd {
+        std::os::unix::io::AsRawFd::as_raw_fd(self.listener())
+    }
+
+    #[cfg(windows)]
+    fn raw_socket(&self) -> std::os::windows::io::RawSocket {
+        std::os::windows::io::AsRawSocket::as_raw_socket(self.listener())
+    }
+}
+
+impl Pollable for TcpClient {
+    #[cfg(unix)]
+    fn raw_socket(&self) -> std::os::unix::io::RawFd {
+        std::os::unix::io::AsRawFd::as_raw_fd(self.stream())
+    }
+
+    #[cfg(windows)]
+    fn raw_socket(&self) -> std::os::windows::io::RawSocket {
+        std::os::windows::io::AsRawSocket::as_raw_socket(self.stream())
+    }
+}
+
+impl Pollable for UdpClient {
+    #[cfg(unix)]
+    fn raw_socket(&self) -> std::os::unix::io::RawFd {
+        std::os::unix::io::AsRawFd::as_raw_fd(self.socket())
+    }
+
+    #[cfg(windows)]
+    fn raw_socket(&self) -> std::os::windows::io::RawSocket {
+        std::os::windows::io::AsRawSocket::as_raw_socket(self.socket())
+    }
+}
+
+/// Multiplexador de I/O: espera vários sockets ficarem prontos ao mesmo tempo
+pub struct Poller {
+    backend: sys::Backend,
+}
+
+impl Poller {
+    /// Cria um poller usando o backend nativo da plataforma
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            backend: sys::Backend::new()?,
+        })
+    }
+
+    /// Registra um socket com o token e o interesse dados
+    ///
+    /// O socket deve estar em modo não bloqueante para que as leituras e
+    /// escritas feitas após um evento não travem a thread.
+    pub fn register<S: Pollable + ?Sized>(
+        &self,
+        source: &S,
+        token: usize,
+        interest: Interest,
+    ) -> io::Result<()> {
+        self.backend.register(source.raw_socket(), token, interest)
+    }
+
+    /// Altera token/interesse de um socket já registrado
+    pub fn reregister<S: Pollable + ?Sized>(
+        &self,
+        source: &S,
+        token: usize,
+        interest: Interest,
+    ) -> io::Result<()> {
+        self.backend
+            .reregister(source.raw_socket(), token, interest)
+    }
+
+    /// Remove um socket do poller
+    pub fn deregister<S: Pollable + ?Sized>(&self, source: &S) -> io::Result<()> {
+        self.backend.deregister(source.raw_socket())
+    }
+
+    /// Espera eventos até o timeout (`None` = sem limite)
+    ///
+    /// `events` é limpo e preenchido com os sockets prontos; retorna quantos.
+    /// Uma interrupção por sinal retorna 0 eventos em vez de erro.
+    pub fn poll(
+        &self,
+        events: &mut Vec<PollEvent>,
+        timeout: Option<Duration>,
+    ) -> io::Result<usize> {
+        events.clear();
+        self.backend.poll(events, timeout)?;
+        Ok(events.len())
+    }
+}
+
+/// Converte o timeout em milissegundos, arredondando para cima (-1 = infinito)
+fn timeout_millis(timeout: Option<Duration>) -> i32 {
+    match timeout {
+        None => -1,
+        Some(timeout) => {
+            let millis = timeout.as_nanos().div_ceil(1_000_000);
+            millis.min(i32::MAX as u128) as i32
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    use super::{timeout_millis, Interest, PollEvent};
+    use std::io;
+    use std::os::unix::io::RawFd;
+    use std::time::Duration;
+
+    const MAX_EVENTS: usize = 256;
+
+    pub struct Backend {
+        epfd: RawFd,
+    }
+
+    fn flags(interest: Interest) -> u32 {
+        let mut flags = libc::EPOLLRDHUP as u32;
+        if interest.is_readable() {
+            flags |= libc::EPOLLIN as u32;
+        }
+        if interest.is_writable() {
+            flags |= libc::EPOLLOUT as u32;
+        }
+        flags
+    }
+
+    impl Backend {
+        pub fn new() -> io::Result<Self> {
+            let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+            if epfd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { epfd })
+        }
+
+        fn ctl(&self, op: i32, fd: RawFd, token: usize, interest: Interest) -> io::Result<()> {
+            let mut event = libc::epoll_event {
+                events: flags(interest),
+                u64: token as u64,
+            };
+            if unsafe { libc::epoll_ctl(self.epfd, op, fd, &mut event) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub fn register(&self, fd: RawFd, token: usize, interest: Interest) -> io::Result<()> {
+            self.ctl(libc::EPOLL_CTL_ADD, fd, token, interest)
+        }
+
+        pub fn reregister(&self, fd: RawFd, token: usize, interest: Interest) -> io::Result<()> {
+            self.ctl(libc::EPOLL_CTL_MOD, fd, token, interest)
+        }
+
+        pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+            if unsafe { libc::epoll_ctl(self.epfd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()) }
+                < 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub fn poll(
+            &self,
+            events: &mut Vec<PollEvent>,
+            timeout: Option<Duration>,
+        ) -> io::Result<()> {
+            let mut raw = [libc::epoll_event { events: 0, u64: 0 }; MAX_EVENTS];
+            let count = unsafe {
+                libc::epoll_wait(
+                    self.epfd,
+                    raw.as_mut_ptr(),
+                    MAX_EVENTS as i32,
+                    timeout_millis(timeout),
+                )
+            };
+
+            if count < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::Interrupted => Ok(()),
+                    _ => Err(err),
+                };
+            }
+
+            for event in &raw[..count as usize] {
+                let bits = event.events;
+                let hangup = bits & (libc::EPOLLHUP | libc::EPOLLRDHUP) as u32 != 0;
+                events.push(PollEvent {
+                    token: event.u64 as usize,
+                    readable: bits & libc::EPOLLIN as u32 != 0 || hangup,
+                    writable: bits & libc::EPOLLOUT as u32 != 0,
+                    hangup,
+                    error: bits & libc::EPOLLERR as u32 != 0,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Backend {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.epfd);
+            }
+        }
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+mod sys {
+    use super::{timeout_millis, Interest, PollEvent};
+    use std::collections::HashMap;
+    use std::io;
+    use std::os::unix::io::RawFd;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Backend portátil com `poll(2)`: mantém a tabela de registros em memória
+    pub struct Backend {
+        registrations: Mutex<HashMap<RawFd, (usize, Interest)>>,
+    }
+
+    impl Backend {
+        pub fn new() -> io::Result<Self> {
+            Ok(Self {
+                registrations: Mutex::new(HashMap::new()),
+            })
+        }
+
+        pub fn register(&self, fd: RawFd, token: usize, interest: Interest) -> io::Result<()> {
+            let mut registrations = self.registrations.lock().unwrap();
+            if registrations.contains_key(&fd) {
+                return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+            }
+            registrations.insert(fd, (token, interest));
+            Ok(())
+        }
+
+        pub fn reregister(&self, fd: RawFd, token: usize, interest: Interest) -> io::Result<()> {
+            match self.registrations.lock().unwrap().get_mut(&fd) {
+                Some(entry) => {
+                    *entry = (token, interest);
+                    Ok(())
+                }
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+
+        pub fn deregister(&self, fd: RawFd) -> io::Result<()> {
+            match self.registrations.lock().unwrap().remove(&fd) {
+                Some(_) => Ok(()),
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+
+        pub fn poll(
+            &self,
+            events: &mut Vec<PollEvent>,
+            timeout: Option<Duration>,
+        ) -> io::Result<()> {
+            let (mut fds, tokens): (Vec<libc::pollfd>, Vec<usize>) = self
+                .registrations
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&fd, &(token, interest))| {
+                    let mut flags = 0;
+                    if interest.is_readable() {
+                        flags |= libc::POLLIN;
+                    }
+                    if interest.is_writable() {
+                        flags |= libc::POLLOUT;
+                    }
+                    let pollfd = libc::pollfd {
+                        fd,
+                        events: flags,
+                        revents: 0,
+                    };
+                    (pollfd, token)
+                })
+                .unzip();
+
+            let count = unsafe {
+                libc::poll(
+                    fds.as_mut_ptr(),
+                    fds.len() as libc::nfds_t,
+                    timeout_millis(timeout),
+                )
+            };
+            if count < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::Interrupted => Ok(()),
+                    _ => Err(err),
+                };
+            }
+
+            for (pollfd, token) in fds.iter().zip(tokens) {
+                let bits = pollfd.revents;
+                if bits == 0 {
+                    continue;
+                }
+                let hangup = bits & libc::POLLHUP != 0;
+                events.push(PollEvent {
+                    token,
+                    readable: bits & libc::POLLIN != 0 || hangup,
+                    writable: bits & libc::POLLOUT != 0,
+                    hangup,
+                    error: bits & (libc::POLLERR | libc::POLLNVAL) != 0,
+                });
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::{timeout_millis, Interest, PollEvent};
+    use std::collections::HashMap;
+    use std::io;
+    use std::os::windows::io::RawSocket;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use windows_sys::Win32::Networking::WinSock::{
+        WSAGetLastError, WSAPoll, POLLERR, POLLHUP, POLLNVAL, POLLRDNORM, POLLWRNORM, SOCKET,
+        SOCKET_ERROR, WSAPOLLFD,
+    };
+
+    /// Backend com `WSAPoll`: mantém a tabela de registros em memória
+    pub struct Backend {
+        registrations: Mutex<HashMap<RawSocket, (usize, Interest)>>,
+    }
+
+    impl Backend {
+        pub fn new() -> io::Result<Self> {
+            Ok(Self {
+                registrations: Mutex::new(HashMap::new()),
+            })
+        }
+
+        pub fn register(
+            &self,
+            socket: RawSocket,
+            token: usize,
+            interest: Interest,
+        ) -> io::Result<()> {
+            let mut registrations = self.registrations.lock().unwrap();
+            if registrations.contains_key(&socket) {
+                return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+            }
+            registrations.insert(socket, (token, interest));
+            Ok(())
+        }
+
+        pub fn reregister(
+            &self,
+            socket: RawSocket,
+            token: usize,
+            interest: Interest,
+        ) -> io::Result<()> {
+            match self.registrations.lock().unwrap().get_mut(&socket) {
+                Some(entry) => {
+                    *entry = (token, interest);
+                    Ok(())
+                }
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+
+        pub fn deregister(&self, socket: RawSocket) -> io::Result<()> {
+            match self.registrations.lock().unwrap().remove(&socket) {
+                Some(_) => Ok(()),
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+
+        pub fn poll(
+            &self,
+            events: &mut Vec<PollEvent>,
+            timeout: Option<Duration>,
+        ) -> io::Result<()> {
+            let (mut fds, tokens): (Vec<WSAPOLLFD>, Vec<usize>) = self
+                .registrations
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&socket, &(token, interest))| {
+                    let mut flags = 0;
+                    if interest.is_readable() {
+                        flags |= POLLRDNORM;
+                    }
+                    if interest.is_writable() {
+                        flags |= POLLWRNORM;
+                    }
+                    let pollfd = WSAPOLLFD {
+                        fd: socket as SOCKET,
+                        events: flags,
+                        revents: 0,
+                    };
+                    (pollfd, token)
+                })
+                .unzip();
+
+            if fds.is_empty() {
+                // WSAPoll rejeita conjunto vazio; apenas respeita o timeout
+                if let Some(timeout) = timeout {
+                    std::thread::sleep(timeout);
+                }
+                return Ok(());
+            }
+
+            let count =
+                unsafe { WSAPoll(fds.as_mut_ptr(), fds.len() as u32, timeout_millis(timeout)) };
+            if count == SOCKET_ERROR {
+                return Err(io::Error::from_raw_os_error(unsafe { WSAGetLastError() }));
+            }
+
+            for (pollfd, token) in fds.iter().zip(tokens) {
+                let bits = pollfd.revents;
+                if bits == 0 {
+                    continue;
+                }
+                let hangup = bits & POLLHUP != 0;
+                events.push(PollEvent {
+                    token,
+                    readable: bits & POLLRDNORM != 0 || hangup,
+                    writable: bits & POLLWRNORM != 0,
+                    hangup,
+                    error: bits & (POLLERR | POLLNVAL) != 0,
+                });
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn poll_until(poller: &Poller, token: usize) -> PollEvent {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut events = Vec::new();
+        while Instant::now() < deadline {
+            poller
+                .poll(&mut events, Some(Duration::from_millis(100)))
+                .unwrap();
+            if let Some(event) = events.iter().find(|e| e.token == token) {
+                return *event;
+            }
+        }
+        panic!("no event for token {}", token);
+    }
+
+    #[test]
+    fn test_poll_timeout_without_events() {
+        let poller = Poller::new().unwrap();
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        server.set_nonblocking(true).unwrap();
+        poller.register(&server, 1, Interest::READABLE).unwrap();
+
+        let mut events = Vec::new();
+        let count = poller
+            .poll(&mut events, Some(Duration::from_millis(10)))
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(server.try_accept().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_poll_accept_and_read() {
+        let poller = Poller::new().unwrap();
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        server.set_nonblocking(true).unwrap();
+        poller.register(&server, 0, Interest::READABLE).unwrap();
+
+        let mut client = TcpClient::connect(server.local_addr().unwrap()).unwrap();
+        assert!(poll_until(&poller, 0).readable);
+
+        let (mut conn, _) = server.try_accept().unwrap().expect("pending connection");
+        conn.set_nonblocking(true).unwrap();
+        poller.register(&conn, 1, Interest::READABLE).unwrap();
+
+        let mut buf = [0u8; 16];
+        assert!(conn.try_recv(&mut buf).unwrap().is_none());
+
+        client.send_all(b"ping").unwrap();
+        assert!(poll_until(&poller, 1).readable);
+        assert_eq!(conn.try_recv(&mut buf).unwrap(), Some(4));
+        assert_eq!(&buf[..4], b"ping");
+
+        // Fechar o cliente gera hangup/leitura de 0 bytes
+        drop(client);
+        let event = poll_until(&poller, 1);
+        assert!(event.readable);
+        assert_eq!(conn.try_recv(&mut buf).unwrap(), Some(0));
+
+        poller.deregister(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_poll_writable_and_reregister() {
+        let poller = Poller::new().unwrap();
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        let client = TcpClient::connect(server.local_addr().unwrap()).unwrap();
+        client.set_nonblocking(true).unwrap();
+
+        poller.register(&client, 7, Interest::WRITABLE).unwrap();
+        assert!(poll_until(&poller, 7).writable);
+
+        poller.reregister(&client, 8, Interest::BOTH).unwrap();
+        assert!(poll_until(&poller, 8).writable);
+    }
+}
@@ -0,0 +1,605 @@
+//! Processos filhos interativos
+//!
+//! `ChildProcess` envolve um `std::process::Child` com stdio em pipes: o
+//! stdin pode ser escrito aos poucos e stdout/stderr são lidos por threads
+//! próprias, que entregam as linhas por canal. Assim dá para acompanhar a
+//! saída de um compilador de shaders ou de um baker sem bloquear o frame.
+//!
+//! ```no_run
+//! use avila_math::os::{Process, ProcessOutput};
+//! use std::time::Duration;
+//!
+//! let mut child = Process::spawn_piped("glslc", &["shader.frag", "-o", "shader.spv"]).unwrap();
+//! while let Some(line) = child.recv_output_timeout(Duration::from_millis(100)) {
+//!     match line {
+//!         ProcessOutput::Stdout(line) => println!("{}", line),
+//!         ProcessOutput::Stderr(line) => eprintln!("{}", line),
+//!     }
+//! }
+//! match child.wait_timeout(Duration::from_secs(30)).unwrap() {
+//!     Some(status) => println!("exit: {:?}", status.code()),
+//!     None => child.kill().unwrap(),
+//! }
+//! ```
+
+use std::ffi::{OsStr, OsString};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Uma linha de saída de um processo filho (sem o `\n`/`\r\n` final)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessOutput {
+    Stdout(String),
+    Stderr(String),
+}
+
+impl ProcessOutput {
+    /// Texto da linha
+    pub fn line(&self) -> &str {
+        match self {
+            ProcessOutput::Stdout(line) | ProcessOutput::Stderr(line) => line,
+        }
+    }
+
+    pub fn is_stderr(&self) -> bool {
+        matches!(self, ProcessOutput::Stderr(_))
+    }
+}
+
+/// Saída completa de um processo que terminou
+#[derive(Debug, Clone)]
+pub struct CapturedOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CapturedOutput {
+    /// Código de saída (`None` se o processo foi terminado por sinal)
+    pub fn code(&self) -> Option<i32> {
+        self.status.code()
+    }
+
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+}
+
+/// Processo filho com stdin/stdout/stderr interativos
+///
+/// Só os streams configurados como pipe são capturados; os demais (herdados
+/// ou nulos) simplesmente não geram `ProcessOutput`. O drop não mata o
+/// processo, como em `std::process::Child`.
+pub struct ChildProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    output: Receiver<ProcessOutput>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+fn spawn_reader<R, F>(stream: R, sender: Sender<ProcessOutput>, wrap: F) -> JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    F: Fn(String) -> ProcessOutput + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    while line.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+                        line.pop();
+                    }
+                    let text = String::from_utf8_lossy(&line).into_owned();
+                    if sender.send(wrap(text)).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+impl ChildProcess {
+    /// Assume um `Child` já iniciado, capturando os pipes que ele tiver
+    pub fn from_child(mut child: Child) -> Self {
+        let (sender, output) = mpsc::channel();
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(spawn_reader(stdout, sender.clone(), ProcessOutput::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(spawn_reader(stderr, sender, ProcessOutput::Stderr));
+        }
+        let stdin = child.stdin.take();
+        Self {
+            child,
+            stdin,
+            output,
+            readers,
+        }
+    }
+
+    /// ID do processo no sistema
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Escreve no stdin do processo
+    pub fn write_stdin(&mut self, data: &[u8]) -> io::Result<()> {
+        let stdin = self.stdin.as_mut().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "stdin is not piped or was closed",
+            )
+        })?;
+        stdin.write_all(data)?;
+        stdin.flush()
+    }
+
+    /// Escreve uma linha (acrescenta `\n`) no stdin
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let mut data = Vec::with_capacity(line.len() + 1);
+        data.extend_from_slice(line.as_bytes());
+        data.push(b'\n');
+        self.write_stdin(&data)
+    }
+
+    /// Fecha o stdin, sinalizando EOF para o processo
+    pub fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Próxima linha de saída, sem bloquear
+    pub fn try_recv_output(&self) -> Option<ProcessOutput> {
+        self.output.try_recv().ok()
+    }
+
+    /// Próxima linha de saída, esperando até `timeout`
+    ///
+    /// Retorna `None` no timeout ou quando stdout e stderr já fecharam.
+    pub fn recv_output_timeout(&self, timeout: Duration) -> Option<ProcessOutput> {
+        self.output.recv_timeout(timeout).ok()
+    }
+
+    /// Todas as linhas disponíveis agora, sem bloquear
+    pub fn drain_output(&self) -> Vec<ProcessOutput> {
+        self.output.try_iter().collect()
+    }
+
+    /// Iterador bloqueante sobre a saída, até stdout e stderr fecharem
+    pub fn output_lines(&self) -> impl Iterator<Item = ProcessOutput> + '_ {
+        self.output.iter()
+    }
+
+    /// Verifica se o processo terminou, sem bloquear
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Espera o processo terminar
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.close_stdin();
+        self.child.wait()
+    }
+
+    /// Espera o processo terminar por até `timeout`
+    ///
+    /// Retorna `Ok(None)` se o processo ainda estiver rodando; ele continua
+    /// vivo e pode ser esperado de novo ou morto com `kill`.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = Duration::from_millis(1);
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Ok(Some(status));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(interval.min(deadline - now));
+            interval = (interval * 2).min(Duration::from_millis(50));
+        }
+    }
+
+    /// Mata o processo e aguarda seu fim
+    pub fn kill(&mut self) -> io::Result<()> {
+        match self.child.kill() {
+            Ok(()) => {}
+            // Já terminou
+            Err(err) if err.kind() == io::ErrorKind::InvalidInput => {}
+            Err(err) => return Err(err),
+        }
+        self.child.wait().map(|_| ())
+    }
+
+    /// Espera o processo terminar e junta toda a saída restante
+    pub fn wait_with_output(mut self) -> io::Result<CapturedOutput> {
+        self.close_stdin();
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        for line in self.output.iter() {
+            let target = match &line {
+                ProcessOutput::Stdout(_) => &mut stdout,
+                ProcessOutput::Stderr(_) => &mut stderr,
+            };
+            target.push_str(line.line());
+            target.push('\n');
+        }
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        let status = self.child.wait()?;
+        Ok(CapturedOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Acesso ao `Child` subjacente
+    pub fn child(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl From<Child> for ChildProcess {
+    fn from(child: Child) -> Self {
+        Self::from_child(child)
+    }
+}
+
+impl std::fmt::Debug for ChildProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildProcess")
+            .field("id", &self.child.id())
+            .field("stdin_open", &self.stdin.is_some())
+            .finish()
+    }
+}
+
+/// Destino de um stream de stdio do processo filho
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StdioMode {
+    /// Usa o stdio do processo atual
+    #[default]
+    Inherit,
+    /// Descarta a saída / stdin vazio
+    Null,
+    /// Pipe acessível via `ChildProcess`
+    Piped,
+}
+
+impl StdioMode {
+    fn to_stdio(self) -> Stdio {
+        match self {
+            StdioMode::Inherit => Stdio::inherit(),
+            StdioMode::Null => Stdio::null(),
+            StdioMode::Piped => Stdio::piped(),
+        }
+    }
+}
+
+/// Configuração de um processo antes de iniciá-lo
+///
+/// Cobre o que `Process::spawn(cmd, args)` não expressa: variáveis de
+/// ambiente, diretório de trabalho, destino do stdio e processos
+/// desacoplados (que sobrevivem ao terminal/console do engine).
+///
+/// ```no_run
+/// use avila_math::os::{CommandBuilder, StdioMode};
+///
+/// let output = CommandBuilder::new("baker")
+///     .args(["--level", "forest"])
+///     .env("BAKER_THREADS", "8")
+///     .current_dir("tools")
+///     .output()
+///     .unwrap();
+///
+/// // Servidor dedicado em background, sem prender o terminal
+/// CommandBuilder::new("./server").detached(true).spawn().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandBuilder {
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, Option<OsString>)>,
+    env_clear: bool,
+    current_dir: Option<PathBuf>,
+    stdin: StdioMode,
+    stdout: StdioMode,
+    stderr: StdioMode,
+    detached: bool,
+}
+
+impl CommandBuilder {
+    /// Cria um comando; por padrão herda ambiente, diretório e stdio
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            env_clear: false,
+            current_dir: None,
+            stdin: StdioMode::Inherit,
+            stdout: StdioMode::Inherit,
+            stderr: StdioMode::Inherit,
+            detached: false,
+        }
+    }
+
+    /// Adiciona um argumento
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Adiciona vários argumentos
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        self
+    }
+
+    /// Define uma variável de ambiente para o filho
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.envs.push((
+            key.as_ref().to_os_string(),
+            Some(value.as_ref().to_os_string()),
+        ));
+        self
+    }
+
+    /// Define várias variáveis de ambiente
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        for (key, value) in vars {
+            self = self.env(key, value);
+        }
+        self
+    }
+
+    /// Remove uma variável herdada
+    pub fn env_remove(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.envs.push((key.as_ref().to_os_string(), None));
+        self
+    }
+
+    /// Não herda nenhuma variável do processo atual
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self.envs.clear();
+        self
+    }
+
+    /// Diretório de trabalho do filho
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.current_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn stdin(mut self, mode: StdioMode) -> Self {
+        self.stdin = mode;
+        self
+    }
+
+    pub fn stdout(mut self, mode: StdioMode) -> Self {
+        self.stdout = mode;
+        self
+    }
+
+    pub fn stderr(mut self, mode: StdioMode) -> Self {
+        self.stderr = mode;
+        self
+    }
+
+    /// Mesmo modo para stdin, stdout e stderr
+    pub fn stdio(self, mode: StdioMode) -> Self {
+        self.stdin(mode).stdout(mode).stderr(mode)
+    }
+
+    /// Desacopla o filho do processo atual
+    ///
+    /// No Unix o filho vira líder de uma nova sessão (`setsid`), sem terminal
+    /// de controle; no Windows é criado sem console e em outro grupo de
+    /// processos. Streams em `Inherit` viram `Null`, para não prender o
+    /// terminal do engine.
+    pub fn detached(mut self, detached: bool) -> Self {
+        self.detached = detached;
+        self
+    }
+
+    /// Monta o `std::process::Command` equivalente
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        if self.env_clear {
+            command.env_clear();
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+
+        let stdio = |mode: StdioMode| match mode {
+            StdioMode::Inherit if self.detached => Stdio::null(),
+            mode => mode.to_stdio(),
+        };
+        command
+            .stdin(stdio(self.stdin))
+            .stdout(stdio(self.stdout))
+            .stderr(stdio(self.stderr));
+
+        if self.detached {
+            detach(&mut command);
+        }
+        command
+    }
+
+    /// Inicia o processo
+    pub fn spawn(&self) -> io::Result<ChildProcess> {
+        self.to_command().spawn().map(ChildProcess::from_child)
+    }
+
+    /// Executa e aguarda, capturando stdout e stderr
+    pub fn output(&self) -> io::Result<CapturedOutput> {
+        let mut command = self.to_command();
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        ChildProcess::from_child(command.spawn()?).wait_with_output()
+    }
+
+    /// Executa e aguarda, retornando apenas o status de saída
+    pub fn status(&self) -> io::Result<ExitStatus> {
+        self.spawn()?.wait()
+    }
+}
+
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: setsid é async-signal-safe e não aloca
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    use windows_sys::Win32::System::Threading::{CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS};
+
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_command: &mut Command) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::os::Process;
+
+    #[test]
+    fn test_child_process_stdin_stdout() {
+        let mut child = Process::spawn_piped("cat", &[]).unwrap();
+        child.write_line("hello").unwrap();
+        assert_eq!(
+            child.recv_output_timeout(Duration::from_secs(5)),
+            Some(ProcessOutput::Stdout("hello".into()))
+        );
+        child.write_stdin(b"partial").unwrap();
+        child.close_stdin();
+        assert!(child.write_line("late").is_err());
+
+        let output = child.wait_with_output().unwrap();
+        assert!(output.success());
+        assert_eq!(output.stdout, "partial\n");
+    }
+
+    #[test]
+    fn test_child_process_stderr_and_exit_code() {
+        let child = Process::spawn_piped("sh", &["-c", "echo out; echo err >&2; exit 3"]).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.code(), Some(3));
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+    }
+
+    #[test]
+    fn test_child_process_timeout_and_kill() {
+        let mut child = Process::spawn_piped("sleep", &["10"]).unwrap();
+        assert!(child
+            .wait_timeout(Duration::from_millis(50))
+            .unwrap()
+            .is_none());
+        child.kill().unwrap();
+        assert!(child.try_wait().unwrap().is_some());
+        assert!(child.output_lines().next().is_none());
+        // Matar de novo não é erro
+        child.kill().unwrap();
+    }
+
+    #[test]
+    fn test_command_builder_env_and_cwd() {
+        let dir = std::env::temp_dir();
+        let output = CommandBuilder::new("sh")
+            .args(["-c", "echo \"$AVILA_A-$AVILA_B-$HOME\"; pwd"])
+            .env_clear()
+            .envs([("AVILA_A", "1"), ("AVILA_B", "2")])
+            .env_remove("AVILA_B")
+            .current_dir(&dir)
+            .stdin(StdioMode::Null)
+            .output()
+            .unwrap();
+        assert!(output.success());
+
+        let mut lines = output.stdout.lines();
+        assert_eq!(lines.next(), Some("1--"));
+        let cwd = std::path::PathBuf::from(lines.next().unwrap());
+        assert_eq!(cwd.canonicalize().unwrap(), dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_command_builder_stdio_modes() {
+        let mut child = CommandBuilder::new("sh")
+            .args(["-c", "read line; echo \"got $line\"; echo noise >&2"])
+            .stdin(StdioMode::Piped)
+            .stdout(StdioMode::Piped)
+            .stderr(StdioMode::Null)
+            .spawn()
+            .unwrap();
+        child.write_line("ping").unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.stdout, "got ping\n");
+        assert_eq!(output.stderr, "");
+
+        let status = CommandBuilder::new("sh")
+            .args(["-c", "exit 7"])
+            .stdio(StdioMode::Null)
+            .status()
+            .unwrap();
+        assert_eq!(status.code(), Some(7));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_command_builder_detached() {
+        // Com setsid o shell vira líder da sessão: sid == pid
+        let child = CommandBuilder::new("sh")
+            .args(["-c", "cut -d' ' -f6 /proc/$$/stat"])
+            .stdout(StdioMode::Piped)
+            .detached(true)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.stdout.trim(), pid.to_string());
+    }
+}
@@ -0,0 +1,351 @@
+//! Sinais do sistema (Ctrl-C, SIGTERM)
+//!
+//! Instala handlers para SIGINT/SIGTERM/SIGHUP no Unix e para os eventos de
+//! controle do console no Windows, entregando as notificações por um
+//! `ShutdownFlag` ou por canal. O handler em si só acorda uma thread de
+//! despacho; os ouvintes rodam fora do contexto do sinal.
+//!
+//! Enquanto houver um ouvinte para um sinal, o comportamento padrão (terminar
+//! o processo) deixa de acontecer, então quem se inscreve fica responsável
+//! por encerrar.
+//!
+//! ```no_run
+//! use avila_math::os::signal;
+//! use std::time::Duration;
+//!
+//! let shutdown = signal::shutdown_flag().unwrap();
+//! while !shutdown.is_shutdown() {
+//!     // tick do servidor
+//!     std::thread::sleep(Duration::from_millis(16));
+//! }
+//! println!("salvando e encerrando");
+//! ```
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+
+use super::threading::ShutdownFlag;
+
+/// Sinal de término/interrupção
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// Ctrl-C (SIGINT; `CTRL_C_EVENT`/`CTRL_BREAK_EVENT` no Windows)
+    Interrupt,
+    /// Pedido de término (SIGTERM; fechamento do console, logoff ou
+    /// desligamento no Windows)
+    Terminate,
+    /// Terminal fechado (SIGHUP); nunca é entregue no Windows
+    Hangup,
+}
+
+impl Signal {
+    const ALL: [Signal; 3] = [Signal::Interrupt, Signal::Terminate, Signal::Hangup];
+}
+
+// ============================================================================
+// Registro de ouvintes
+// ============================================================================
+
+enum Sink {
+    Flag(ShutdownFlag),
+    Channel(Sender<Signal>),
+}
+
+struct Listener {
+    signals: Vec<Signal>,
+    sink: Sink,
+}
+
+#[derive(Default)]
+struct Registry {
+    installed: Vec<Signal>,
+    listeners: Vec<Listener>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Entrega um sinal aos ouvintes; retorna se alguém estava inscrito
+fn dispatch(signal: Signal) -> bool {
+    let mut registry = registry().lock().unwrap();
+    let mut handled = false;
+    registry.listeners.retain(|listener| {
+        if !listener.signals.contains(&signal) {
+            return true;
+        }
+        handled = true;
+        match &listener.sink {
+            Sink::Flag(flag) => {
+                flag.shutdown();
+                true
+            }
+            // Receiver descartado: remove o ouvinte
+            Sink::Channel(sender) => sender.send(signal).is_ok(),
+        }
+    });
+    handled
+}
+
+fn register(signals: &[Signal], sink: Sink) -> io::Result<()> {
+    let mut registry = registry().lock().unwrap();
+    for &signal in signals {
+        if !registry.installed.contains(&signal) {
+            imp::install(signal)?;
+            registry.installed.push(signal);
+        }
+    }
+    registry.listeners.push(Listener {
+        signals: signals.to_vec(),
+        sink,
+    });
+    Ok(())
+}
+
+/// Cria um `ShutdownFlag` acionado por Ctrl-C e pedidos de término
+pub fn shutdown_flag() -> io::Result<ShutdownFlag> {
+    let flag = ShutdownFlag::new();
+    notify_flag(&flag, &Signal::ALL)?;
+    Ok(flag)
+}
+
+/// Aciona um `ShutdownFlag` existente quando chegar um dos sinais
+pub fn notify_flag(flag: &ShutdownFlag, signals: &[Signal]) -> io::Result<()> {
+    register(signals, Sink::Flag(flag.clone_handle()))
+}
+
+/// Recebe os sinais escolhidos por canal (útil para contar Ctrl-Cs, por exemplo)
+///
+/// O ouvinte é removido no próximo sinal depois que o `Receiver` for
+/// descartado.
+pub fn subscribe(signals: &[Signal]) -> io::Result<Receiver<Signal>> {
+    let (sender, receiver) = mpsc::channel();
+    register(signals, Sink::Channel(sender))?;
+    Ok(receiver)
+}
+
+/// Todos os sinais suportados pela plataforma atual
+pub fn supported() -> Vec<Signal> {
+    Signal::ALL
+        .into_iter()
+        .filter(|&signal| imp::is_supported(signal))
+        .collect()
+}
+
+// ============================================================================
+// Unix: sigaction + self-pipe
+// ============================================================================
+
+#[cfg(unix)]
+mod imp {
+    use super::{dispatch, Signal};
+    use std::io;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::OnceLock;
+
+    /// Lado de escrita do self-pipe, lido pelo handler
+    static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+    fn number(signal: Signal) -> libc::c_int {
+        match signal {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Hangup => libc::SIGHUP,
+        }
+    }
+
+    pub fn is_supported(_signal: Signal) -> bool {
+        true
+    }
+
+    extern "C" fn handler(signum: libc::c_int) {
+        let fd = PIPE_WRITE.load(Ordering::Relaxed);
+        if fd >= 0 {
+            let byte = signum as u8;
+            // SAFETY: write é async-signal-safe; o pipe é não bloqueante, então
+            // se estiver cheio o sinal é descartado (já há outros pendentes)
+            unsafe {
+                let errno = *errno_location();
+                libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+                *errno_location() = errno;
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe fn errno_location() -> *mut libc::c_int {
+        libc::__errno_location()
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe fn errno_location() -> *mut libc::c_int {
+        libc::__error()
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    )))]
+    unsafe fn errno_location() -> *mut libc::c_int {
+        static mut ERRNO: libc::c_int = 0;
+        std::ptr::addr_of_mut!(ERRNO)
+    }
+
+    /// Cria o self-pipe e a thread que despacha os sinais
+    fn start_dispatcher() -> io::Result<()> {
+        static STARTED: OnceLock<Result<(), io::ErrorKind>> = OnceLock::new();
+        let result = STARTED.get_or_init(|| {
+            let mut fds = [0 as libc::c_int; 2];
+            // SAFETY: fds tem espaço para os dois descritores
+            if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+                return Err(io::Error::last_os_error().kind());
+            }
+            let [read_fd, write_fd] = fds;
+            // SAFETY: descritores recém-criados e válidos
+            unsafe {
+                libc::fcntl(read_fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                libc::fcntl(write_fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                let flags = libc::fcntl(write_fd, libc::F_GETFL);
+                libc::fcntl(write_fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+            PIPE_WRITE.store(write_fd, Ordering::Relaxed);
+
+            std::thread::Builder::new()
+                .name("signal-dispatch".into())
+                .spawn(move || loop {
+                    let mut byte = 0u8;
+                    // SAFETY: lê 1 byte para uma variável local
+                    let n = unsafe {
+                        libc::read(read_fd, &mut byte as *mut u8 as *mut libc::c_void, 1)
+                    };
+                    if n == 1 {
+                        let signal = match byte as libc::c_int {
+                            libc::SIGINT => Signal::Interrupt,
+                            libc::SIGTERM => Signal::Terminate,
+                            libc::SIGHUP => Signal::Hangup,
+                            _ => continue,
+                        };
+                        dispatch(signal);
+                    } else if n == -1
+                        && io::Error::last_os_error().kind() != io::ErrorKind::Interrupted
+                    {
+                        break;
+                    }
+                })
+                .map(|_| ())
+                .map_err(|err| err.kind())
+        });
+        result.map_err(|kind| io::Error::new(kind, "failed to start the signal dispatcher"))
+    }
+
+    pub fn install(signal: Signal) -> io::Result<()> {
+        start_dispatcher()?;
+        // SAFETY: sigaction zerada + máscara vazia; o handler só usa funções
+        // async-signal-safe
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(number(signal), &action, std::ptr::null_mut()) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Windows: SetConsoleCtrlHandler
+// ============================================================================
+
+#[cfg(windows)]
+mod imp {
+    use super::{dispatch, Signal};
+    use std::io;
+    use std::sync::OnceLock;
+    use windows_sys::Win32::System::Console::{
+        SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT,
+        CTRL_SHUTDOWN_EVENT,
+    };
+
+    pub fn is_supported(signal: Signal) -> bool {
+        signal != Signal::Hangup
+    }
+
+    /// Chamado pelo sistema numa thread própria, então pode despachar direto
+    unsafe extern "system" fn handler(ctrl_type: u32) -> i32 {
+        let signal = match ctrl_type {
+            CTRL_C_EVENT | CTRL_BREAK_EVENT => Signal::Interrupt,
+            CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT => Signal::Terminate,
+            _ => return 0,
+        };
+        // Sem ouvintes, deixa o próximo handler (o padrão encerra o processo).
+        // Nos eventos de fechamento o Windows encerra o processo assim que o
+        // handler retorna (ou após alguns segundos), então o desligamento
+        // precisa ser rápido.
+        dispatch(signal) as i32
+    }
+
+    pub fn install(signal: Signal) -> io::Result<()> {
+        if !is_supported(signal) {
+            return Ok(());
+        }
+        static INSTALLED: OnceLock<bool> = OnceLock::new();
+        // SAFETY: handler tem a assinatura de PHANDLER_ROUTINE
+        let ok = *INSTALLED.get_or_init(|| unsafe { SetConsoleCtrlHandler(Some(handler), 1) != 0 });
+        if ok {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use super::Signal;
+    use std::io;
+
+    pub fn is_supported(_signal: Signal) -> bool {
+        false
+    }
+
+    pub fn install(_signal: Signal) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "signals are not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_delivery() {
+        // SIGHUP/SIGTERM: SIGINT fica livre para quem roda os testes
+        let flag = ShutdownFlag::new();
+        notify_flag(&flag, &[Signal::Terminate]).unwrap();
+        let hangups = subscribe(&[Signal::Hangup]).unwrap();
+
+        unsafe { libc::raise(libc::SIGHUP) };
+        assert_eq!(
+            hangups.recv_timeout(Duration::from_secs(5)),
+            Ok(Signal::Hangup)
+        );
+        assert!(!flag.is_shutdown());
+
+        unsafe { libc::raise(libc::SIGTERM) };
+        assert!(flag.wait_timeout(Duration::from_secs(5)));
+        assert_eq!(supported().len(), 3);
+    }
+}
@@ -0,0 +1,674 @@
+//! Executor assíncrono mínimo (`block_on`, `spawn`, timers)
+//!
+//! Dá um lugar para rodar o código async de rede e arquivos do kernel sem
+//! depender de um runtime externo:
+//!
+//! ```
+//! use avila_math::os::task::{self, Executor};
+//! use std::time::Duration;
+//!
+//! let executor = Executor::with_threads(2);
+//! let handle = executor.spawn(async {
+//!     task::sleep(Duration::from_millis(5)).await;
+//!     21 * 2
+//! });
+//!
+//! assert_eq!(task::block_on(handle).unwrap(), 42);
+//! ```
+
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, JoinHandle, Thread};
+use std::time::{Duration, Instant};
+
+use super::threading::{num_cpus, Promise, TaskFuture};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Executa um future até completar, bloqueando a thread atual
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Executa um future no executor global (uma thread por CPU)
+pub fn spawn<F>(future: F) -> TaskFuture<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    static GLOBAL: OnceLock<Executor> = OnceLock::new();
+    GLOBAL
+        .get_or_init(|| Executor::with_threads(num_cpus()))
+        .spawn(future)
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Executor de tarefas assíncronas
+///
+/// `Executor::new()` é single-threaded: as tarefas só rodam enquanto alguém
+/// chama `block_on` ou `run_until_stalled` no executor. `with_threads(n)`
+/// cria `n` workers que executam as tarefas em background.
+///
+/// No drop, tarefas que não terminaram são descartadas e seus handles
+/// recebem `Err` com `BrokenPromise`.
+pub struct Executor {
+    shared: Arc<ExecutorShared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+struct ExecutorShared {
+    queue: Mutex<VecDeque<Arc<Task>>>,
+    available: Condvar,
+    shutdown: AtomicBool,
+    /// Todas as tarefas spawnadas, para falhar as pendentes no drop
+    tasks: Mutex<Vec<Weak<Task>>>,
+}
+
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    shared: Arc<ExecutorShared>,
+    queued: AtomicBool,
+}
+
+impl ExecutorShared {
+    fn push(&self, task: Arc<Task>) {
+        let mut queue = self.queue.lock().unwrap();
+        // Depois do drop do executor ninguém mais drenaria a fila
+        if self.shutdown.load(Ordering::SeqCst) {
+            return;
+        }
+        queue.push_back(task);
+        self.available.notify_one();
+    }
+
+    fn register(&self, task: &Arc<Task>) {
+        let mut tasks = self.tasks.lock().unwrap();
+        // Remove as já terminadas quando o vetor precisaria crescer
+        if tasks.len() == tasks.capacity() {
+            tasks.retain(|task| task.strong_count() > 0);
+        }
+        tasks.push(Arc::downgrade(task));
+    }
+
+    fn worker_loop(&self) {
+        loop {
+            let task = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    if let Some(task) = queue.pop_front() {
+                        break task;
+                    }
+                    if self.shutdown.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    queue = self.available.wait(queue).unwrap();
+                }
+            };
+            task.run();
+        }
+    }
+}
+
+impl Task {
+    fn run(self: Arc<Self>) {
+        self.queued.store(false, Ordering::SeqCst);
+
+        let waker = Waker::from(Arc::clone(&self));
+        let mut cx = Context::from_waker(&waker);
+        let mut slot = self.future.lock().unwrap();
+
+        if let Some(future) = slot.as_mut() {
+            if future.as_mut().poll(&mut cx).is_ready() {
+                *slot = None;
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.queued.swap(true, Ordering::SeqCst) {
+            self.shared.push(Arc::clone(self));
+        }
+    }
+}
+
+/// Waker do future principal em `Executor::block_on`
+struct MainWaker {
+    shared: Arc<ExecutorShared>,
+    woken: AtomicBool,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        let _queue = self.shared.queue.lock().unwrap();
+        self.shared.available.notify_all();
+    }
+}
+
+/// Future de uma tarefa spawnada: repassa o resultado (ou panic) para o `TaskFuture`
+struct Spawned<T> {
+    future: Pin<Box<dyn Future<Output = T> + Send>>,
+    promise: Option<Promise<T>>,
+}
+
+impl<T> Future for Spawned<T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| this.future.as_mut().poll(cx))) {
+            Ok(Poll::Pending) => return Poll::Pending,
+            Ok(Poll::Ready(value)) => Ok(value),
+            Err(payload) => Err(payload),
+        };
+
+        if let Some(promise) = this.promise.take() {
+            promise.complete(result);
+        }
+        Poll::Ready(())
+    }
+}
+
+impl Executor {
+    /// Cria um executor single-threaded, dirigido por `block_on`/`run_until_stalled`
+    pub fn new() -> Self {
+        Self::with_threads(0)
+    }
+
+    /// Cria um executor com `num_threads` workers em background
+    pub fn with_threads(num_threads: usize) -> Self {
+        let shared = Arc::new(ExecutorShared {
+            queue: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            tasks: Mutex::new(Vec::new()),
+        });
+
+        let threads = (0..num_threads)
+            .map(|id| {
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("avila-executor-{}", id))
+                    .spawn(move || shared.worker_loop())
+                    .expect("Failed to spawn executor worker")
+            })
+            .collect();
+
+        Self { shared, threads }
+    }
+
+    /// Retorna o número de workers em background
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Agenda um future no executor e retorna um handle para o resultado
+    ///
+    /// O handle é um `TaskFuture`: pode ser aguardado com `.await`, `wait()`
+    /// ou consultado com `is_ready()`.
+    pub fn spawn<F>(&self, future: F) -> TaskFuture<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (handle, promise) = TaskFuture::pending();
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(Spawned {
+                future: Box::pin(future),
+                promise: Some(promise),
+            }))),
+            shared: Arc::clone(&self.shared),
+            queued: AtomicBool::new(true),
+        });
+
+        self.shared.register(&task);
+        self.shared.push(task);
+        handle
+    }
+
+    /// Executa um future até completar, rodando as tarefas do executor na thread atual
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let main = Arc::new(MainWaker {
+            shared: Arc::clone(&self.shared),
+            woken: AtomicBool::new(true),
+        });
+        let waker = Waker::from(Arc::clone(&main));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+
+        loop {
+            if main.woken.swap(false, Ordering::SeqCst) {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+
+            let task = {
+                let mut queue = self.shared.queue.lock().unwrap();
+                loop {
+                    if let Some(task) = queue.pop_front() {
+                        break Some(task);
+                    }
+                    if main.woken.load(Ordering::SeqCst) {
+                        break None;
+                    }
+                    queue = self.shared.available.wait(queue).unwrap();
+                }
+            };
+
+            if let Some(task) = task {
+                task.run();
+            }
+        }
+    }
+
+    /// Executa tarefas na thread atual até a fila ficar vazia
+    ///
+    /// Retorna quantas tarefas foram executadas.
+    pub fn run_until_stalled(&self) -> usize {
+        let mut executed = 0;
+        loop {
+            let task = self.shared.queue.lock().unwrap().pop_front();
+            match task {
+                Some(task) => {
+                    task.run();
+                    executed += 1;
+                }
+                None => return executed,
+            }
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        {
+            let _queue = self.shared.queue.lock().unwrap();
+            self.shared.available.notify_all();
+        }
+
+        for thread in self.threads.drain(..) {
+            if thread.join().is_err() {
+                eprintln!("Executor worker panicked");
+            }
+        }
+
+        // Quebra o ciclo Task -> ExecutorShared -> fila -> Task
+        self.shared.queue.lock().unwrap().clear();
+
+        // Descarta as tarefas que não terminaram, inclusive as que esperam um
+        // waker: o `Promise` dropado falha o handle com `BrokenPromise`
+        let tasks = std::mem::take(&mut *self.shared.tasks.lock().unwrap());
+        for task in tasks.iter().filter_map(Weak::upgrade) {
+            let future = task.future.lock().unwrap().take();
+            drop(future);
+        }
+    }
+}
+
+/// Future que completa depois de um intervalo
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(Instant::now() + duration)
+}
+
+/// Future que completa num instante específico
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep {
+        deadline,
+        entry: None,
+    }
+}
+
+/// Aplica um timeout a um future
+///
+/// Retorna `Err(Elapsed)` se o future não completar dentro do intervalo.
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        sleep: sleep(duration),
+    }
+}
+
+/// Future retornado por `sleep`/`sleep_until`
+pub struct Sleep {
+    deadline: Instant,
+    entry: Option<Arc<TimerEntry>>,
+}
+
+impl Sleep {
+    /// Instante em que o sleep completa
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        match &self.entry {
+            Some(entry) => {
+                *entry.waker.lock().unwrap() = Some(cx.waker().clone());
+                // O timer pode ter disparado antes do waker ser atualizado
+                if entry.fired.load(Ordering::SeqCst) {
+                    return Poll::Ready(());
+                }
+            }
+            None => {
+                let entry = Arc::new(TimerEntry {
+                    waker: Mutex::new(Some(cx.waker().clone())),
+                    fired: AtomicBool::new(false),
+                });
+                timer_driver().register(self.deadline, Arc::clone(&entry));
+                self.entry = Some(entry);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Future retornado por `timeout`
+pub struct Timeout<F: Future> {
+    future: Pin<Box<F>>,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        match Pin::new(&mut self.sleep).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Erro retornado quando um `timeout` expira
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+struct TimerEntry {
+    waker: Mutex<Option<Waker>>,
+    fired: AtomicBool,
+}
+
+struct ScheduledTimer {
+    deadline: Instant,
+    seq: u64,
+    entry: Arc<TimerEntry>,
+}
+
+impl PartialEq for ScheduledTimer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for ScheduledTimer {}
+
+impl PartialOrd for ScheduledTimer {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledTimer {
+    // Invertido: BinaryHeap é max-heap e queremos o deadline mais próximo no topo
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (other.deadline, other.seq).cmp(&(self.deadline, self.seq))
+    }
+}
+
+/// Thread única que dispara os timers de todos os executores
+struct TimerDriver {
+    timers: Mutex<BinaryHeap<ScheduledTimer>>,
+    changed: Condvar,
+    next_seq: AtomicU64,
+}
+
+fn timer_driver() -> &'static TimerDriver {
+    static DRIVER: OnceLock<&'static TimerDriver> = OnceLock::new();
+    DRIVER.get_or_init(|| {
+        let driver: &'static TimerDriver = Box::leak(Box::new(TimerDriver {
+            timers: Mutex::new(BinaryHeap::new()),
+            changed: Condvar::new(),
+            next_seq: AtomicU64::new(0),
+        }));
+
+        thread::Builder::new()
+            .name("avila-timer".to_string())
+            .spawn(move || driver.run())
+            .expect("Failed to spawn timer thread");
+
+        driver
+    })
+}
+
+impl TimerDriver {
+    fn register(&self, deadline: Instant, entry: Arc<TimerEntry>) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        self.timers.lock().unwrap().push(ScheduledTimer {
+            deadline,
+            seq,
+            entry,
+        });
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut timers = self.timers.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while timers.peek().is_some_and(|timer| timer.deadline <= now) {
+                expired.push(timers.pop().unwrap().entry);
+            }
+
+            if !expired.is_empty() {
+                drop(timers);
+                for entry in expired {
+                    entry.fired.store(true, Ordering::SeqCst);
+                    let waker = entry.waker.lock().unwrap().take();
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+                timers = self.timers.lock().unwrap();
+                continue;
+            }
+
+            timers = match timers.peek().map(|timer| timer.deadline) {
+                Some(deadline) => {
+                    self.changed
+                        .wait_timeout(timers, deadline.saturating_duration_since(now))
+                        .unwrap()
+                        .0
+                }
+                None => self.changed.wait(timers).unwrap(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_block_on_ready() {
+        assert_eq!(block_on(async { 1 + 2 }), 3);
+    }
+
+    #[test]
+    fn test_sleep() {
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let slow = block_on(timeout(
+            Duration::from_millis(10),
+            sleep(Duration::from_secs(10)),
+        ));
+        assert_eq!(slow, Err(Elapsed));
+
+        let fast = block_on(timeout(Duration::from_secs(10), async { 7 }));
+        assert_eq!(fast, Ok(7));
+    }
+
+    #[test]
+    fn test_single_threaded_executor() {
+        let executor = Executor::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let counter = Arc::clone(&counter);
+                executor.spawn(async move {
+                    sleep(Duration::from_millis(5)).await;
+                    counter.fetch_add(i, Ordering::SeqCst);
+                    i
+                })
+            })
+            .collect();
+
+        // Sem workers: nada roda até alguém dirigir o executor
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+        let total = executor.block_on(async {
+            let mut total = 0;
+            for handle in handles {
+                total += handle.await.unwrap();
+            }
+            total
+        });
+
+        assert_eq!(total, 6);
+        assert_eq!(counter.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_drop_fails_pending_handles() {
+        use crate::os::BrokenPromise;
+
+        // Nunca dirigido: a tarefa fica na fila
+        let executor = Executor::new();
+        let queued = executor.spawn(async { 1 });
+        drop(executor);
+        assert!(queued.wait().unwrap_err().is::<BrokenPromise>());
+
+        // Esperando um waker que nunca chega
+        let executor = Executor::with_threads(1);
+        let parked = executor.spawn(std::future::pending::<u32>());
+        let sleeping = executor.spawn(async {
+            sleep(Duration::from_secs(60)).await;
+            2
+        });
+        while executor.shared.queue.lock().unwrap().len() > 0 {
+            thread::yield_now();
+        }
+        drop(executor);
+
+        for handle in [parked, sleeping] {
+            let result = handle.wait_timeout(Duration::from_secs(5)).ok().unwrap();
+            assert!(result.unwrap_err().is::<BrokenPromise>());
+        }
+    }
+
+    #[test]
+    fn test_run_until_stalled() {
+        let executor = Executor::new();
+        let handle = executor.spawn(async { "ready" });
+
+        assert_eq!(executor.run_until_stalled(), 1);
+        assert!(handle.is_ready());
+        assert_eq!(handle.wait().unwrap(), "ready");
+    }
+
+    #[test]
+    fn test_multi_threaded_spawn() {
+        let executor = Executor::with_threads(2);
+        let first = executor.spawn(async { 20 });
+        let second = executor.spawn(async move { first.await.unwrap() + 22 });
+
+        assert_eq!(second.wait().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_spawned_panic() {
+        let executor = Executor::with_threads(1);
+        let handle = executor.spawn(async { panic!("async failure") });
+
+        assert!(block_on(handle).is_err());
+        assert_eq!(block_on(executor.spawn(async { 1 })).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_global_spawn() {
+        assert_eq!(spawn(async { 5 }).wait().unwrap(), 5);
+    }
+}
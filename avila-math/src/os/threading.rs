@@ -1,40 +1,443 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Barrier, Condvar, Mutex, RwLock};
+use std::task::{Context, Poll, Waker};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-/// Thread pool para execução paralela de tarefas
+use super::clock::Profiler;
+
+/// Thread pool com work stealing para execução paralela de tarefas
+///
+/// Cada worker tem sua própria fila (deque): jobs submetidos de dentro de um
+/// worker vão para a fila local (LIFO, cache-friendly) e jobs externos são
+/// distribuídos em round-robin. Workers ociosos roubam do início da fila dos
+/// outros, então não há um lock único serializando o despacho.
 pub struct ThreadPool {
     workers: Vec<Worker>,
-    sender: std::sync::mpsc::Sender<Job>,
-    active_jobs: Arc<AtomicUsize>,
+    shared: Arc<PoolShared>,
 }
 
 type Job = Box<dyn FnOnce() + Send + 'static>;
 
+/// Nome usado no `Profiler` para jobs sem nome
+const DEFAULT_JOB_NAME: &str = "thread_pool.job";
+
+/// Job na fila, com o instante de enfileiramento para medir a espera
+struct QueuedJob {
+    job: Job,
+    enqueued_at: Instant,
+    name: Option<String>,
+}
+
+/// Estado compartilhado entre o pool e seus workers
+struct PoolShared {
+    queues: Vec<Mutex<VecDeque<QueuedJob>>>,
+    next_queue: AtomicUsize,
+    /// Jobs enfileirados que ainda não começaram
+    pending: AtomicUsize,
+    active_jobs: AtomicUsize,
+    sleepers: AtomicUsize,
+    sleep_lock: Mutex<()>,
+    wake: Condvar,
+    /// Notificado quando o pool fica sem jobs pendentes nem ativos
+    idle_lock: Mutex<()>,
+    idle: Condvar,
+    shutdown: AtomicBool,
+    counters: PoolCounters,
+    profiler: Mutex<Option<Arc<Mutex<Profiler>>>>,
+    has_profiler: AtomicBool,
+}
+
+/// Contadores de instrumentação do pool (durações em nanossegundos)
+struct PoolCounters {
+    started_at: Mutex<Instant>,
+    jobs_completed: AtomicU64,
+    task_time: AtomicU64,
+    max_task_time: AtomicU64,
+    queue_wait: AtomicU64,
+    max_queue_wait: AtomicU64,
+    workers: Vec<WorkerCounters>,
+}
+
+#[derive(Default)]
+struct WorkerCounters {
+    jobs_executed: AtomicU64,
+    busy_time: AtomicU64,
+}
+
+impl PoolCounters {
+    fn new(workers: usize) -> Self {
+        Self {
+            started_at: Mutex::new(Instant::now()),
+            jobs_completed: AtomicU64::new(0),
+            task_time: AtomicU64::new(0),
+            max_task_time: AtomicU64::new(0),
+            queue_wait: AtomicU64::new(0),
+            max_queue_wait: AtomicU64::new(0),
+            workers: (0..workers).map(|_| WorkerCounters::default()).collect(),
+        }
+    }
+
+    fn record(&self, worker: Option<usize>, queue_wait: Duration, task_time: Duration) {
+        let wait = queue_wait.as_nanos() as u64;
+        let task = task_time.as_nanos() as u64;
+
+        self.jobs_completed.fetch_add(1, Ordering::Relaxed);
+        self.task_time.fetch_add(task, Ordering::Relaxed);
+        self.max_task_time.fetch_max(task, Ordering::Relaxed);
+        self.queue_wait.fetch_add(wait, Ordering::Relaxed);
+        self.max_queue_wait.fetch_max(wait, Ordering::Relaxed);
+
+        if let Some(counters) = worker.and_then(|index| self.workers.get(index)) {
+            counters.jobs_executed.fetch_add(1, Ordering::Relaxed);
+            counters.busy_time.fetch_add(task, Ordering::Relaxed);
+        }
+    }
+
+    fn reset(&self) {
+        *self.started_at.lock().unwrap() = Instant::now();
+        for counter in [
+            &self.jobs_completed,
+            &self.task_time,
+            &self.max_task_time,
+            &self.queue_wait,
+            &self.max_queue_wait,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        for worker in &self.workers {
+            worker.jobs_executed.store(0, Ordering::Relaxed);
+            worker.busy_time.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Snapshot das estatísticas de um `ThreadPool`
+#[derive(Debug, Clone)]
+pub struct PoolStats {
+    /// Tempo desde a criação do pool (ou do último `reset_stats`)
+    pub uptime: Duration,
+    pub jobs_completed: u64,
+    pub pending_jobs: usize,
+    pub active_jobs: usize,
+    pub total_task_time: Duration,
+    pub max_task_time: Duration,
+    /// Tempo total que os jobs passaram na fila antes de começar
+    pub total_queue_wait: Duration,
+    pub max_queue_wait: Duration,
+    pub workers: Vec<WorkerStats>,
+}
+
+/// Estatísticas de um worker do pool
+#[derive(Debug, Clone)]
+pub struct WorkerStats {
+    pub index: usize,
+    pub jobs_executed: u64,
+    pub busy_time: Duration,
+    /// Fração do uptime em que o worker esteve executando jobs (0.0 - 1.0)
+    pub utilization: f64,
+}
+
+impl PoolStats {
+    /// Duração média de um job
+    pub fn average_task_time(&self) -> Duration {
+        average(self.total_task_time, self.jobs_completed)
+    }
+
+    /// Espera média na fila
+    pub fn average_queue_wait(&self) -> Duration {
+        average(self.total_queue_wait, self.jobs_completed)
+    }
+
+    /// Utilização média dos workers (0.0 - 1.0)
+    pub fn utilization(&self) -> f64 {
+        if self.workers.is_empty() {
+            return 0.0;
+        }
+        self.workers.iter().map(|w| w.utilization).sum::<f64>() / self.workers.len() as f64
+    }
+}
+
+fn average(total: Duration, count: u64) -> Duration {
+    if count == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_nanos((total.as_nanos() / count as u128) as u64)
+    }
+}
+
+/// Registra as métricas do job e decrementa `active_jobs` ao sair, inclusive em panic
+struct ActiveJobGuard<'a> {
+    shared: &'a PoolShared,
+    worker: Option<usize>,
+    name: Option<String>,
+    queue_wait: Duration,
+    started_at: Instant,
+}
+
+impl Drop for ActiveJobGuard<'_> {
+    fn drop(&mut self) {
+        let shared = self.shared;
+        let task_time = self.started_at.elapsed();
+        shared
+            .counters
+            .record(self.worker, self.queue_wait, task_time);
+
+        if shared.has_profiler.load(Ordering::Relaxed) {
+            if let Some(profiler) = shared.profiler.lock().unwrap().as_ref() {
+                let name = self
+                    .name
+                    .take()
+                    .unwrap_or_else(|| DEFAULT_JOB_NAME.to_string());
+                profiler.lock().unwrap().record(name, task_time);
+            }
+        }
+
+        if shared.active_jobs.fetch_sub(1, Ordering::SeqCst) == 1 && shared.is_idle() {
+            let _guard = shared.idle_lock.lock().unwrap();
+            shared.idle.notify_all();
+        }
+    }
+}
+
+thread_local! {
+    /// (id do pool, índice do worker) da thread atual, se for um worker
+    static CURRENT_WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+impl PoolShared {
+    fn id(self: &Arc<Self>) -> usize {
+        Arc::as_ptr(self) as usize
+    }
+
+    fn push(self: &Arc<Self>, job: Job) {
+        self.push_named(job, None);
+    }
+
+    fn push_named(self: &Arc<Self>, job: Job, name: Option<String>) {
+        let job = QueuedJob {
+            job,
+            enqueued_at: Instant::now(),
+            name,
+        };
+        let pool_id = self.id();
+        let index = CURRENT_WORKER
+            .with(|current| match current.get() {
+                Some((id, index)) if id == pool_id => Some(index),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.next_queue.fetch_add(1, Ordering::Relaxed) % self.queues.len());
+
+        self.queues[index].lock().unwrap().push_back(job);
+        self.pending.fetch_add(1, Ordering::SeqCst);
+
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _guard = self.sleep_lock.lock().unwrap();
+            self.wake.notify_one();
+        }
+    }
+
+    /// Procura um job: fila local (LIFO), depois rouba das outras (FIFO)
+    fn find_job(&self, index: usize) -> Option<QueuedJob> {
+        if let Some(job) = self.queues[index].lock().unwrap().pop_back() {
+            return Some(job);
+        }
+
+        let count = self.queues.len();
+        (1..count).find_map(|offset| {
+            let victim = (index + offset) % count;
+            self.queues[victim].lock().unwrap().pop_front()
+        })
+    }
+
+    /// Executa um job; `worker` é o índice do worker, ou `None` se for outra thread
+    fn run_job(&self, queued: QueuedJob, worker: Option<usize>) {
+        let started_at = Instant::now();
+
+        // Conta como ativo antes de sair de "pending" para nunca parecer ocioso
+        self.active_jobs.fetch_add(1, Ordering::SeqCst);
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+        let _active = ActiveJobGuard {
+            shared: self,
+            worker,
+            name: queued.name,
+            queue_wait: started_at - queued.enqueued_at,
+            started_at,
+        };
+
+        // Um panic não pode derrubar o worker: ninguém o recriaria e os jobs
+        // da fila dele ficariam parados. O hook de panic já reportou a mensagem.
+        let _ = panic::catch_unwind(AssertUnwindSafe(queued.job));
+    }
+
+    /// Sem jobs na fila nem executando
+    ///
+    /// Um job retirado da fila continua em `pending` até entrar em
+    /// `active_jobs`, então não existe janela em que ele não seja contado.
+    fn is_idle(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) == 0 && self.active_jobs.load(Ordering::SeqCst) == 0
+    }
+
+    fn worker_loop(self: Arc<Self>, index: usize) {
+        CURRENT_WORKER.with(|current| current.set(Some((self.id(), index))));
+
+        loop {
+            if let Some(job) = self.find_job(index) {
+                self.run_job(job, Some(index));
+                continue;
+            }
+
+            let guard = self.sleep_lock.lock().unwrap();
+            self.sleepers.fetch_add(1, Ordering::SeqCst);
+
+            let pending = self.pending.load(Ordering::SeqCst);
+            if pending == 0 && self.shutdown.load(Ordering::SeqCst) {
+                self.sleepers.fetch_sub(1, Ordering::SeqCst);
+                break;
+            }
+            if pending == 0 {
+                drop(self.wake.wait(guard).unwrap());
+            } else {
+                drop(guard);
+            }
+
+            self.sleepers.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        CURRENT_WORKER.with(|current| current.set(None));
+    }
+}
+
+/// Estado de um `parallel_for` em andamento
+///
+/// Os chunks são reivindicados por um contador atômico; a thread chamadora
+/// também trabalha, então o loop progride mesmo se todos os workers estiverem
+/// ocupados (ou se for chamado de dentro de um worker).
+struct ParallelState {
+    /// Closure com lifetime apagado; só é acessada enquanto o chamador espera
+    task: *const (dyn Fn(usize) + Sync),
+    total: usize,
+    next: AtomicUsize,
+    completed: AtomicUsize,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+    done_lock: Mutex<()>,
+    done: Condvar,
+}
+
+// Seguro: `task` aponta para um `Fn + Sync` que vive até todos os chunks terminarem
+unsafe impl Send for ParallelState {}
+unsafe impl Sync for ParallelState {}
+
+impl ParallelState {
+    fn work(&self) {
+        loop {
+            let chunk = self.next.fetch_add(1, Ordering::SeqCst);
+            if chunk >= self.total {
+                return;
+            }
+
+            // Chunk reivindicado: o chamador ainda está esperando, então `task` é válido
+            let task = unsafe { &*self.task };
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| task(chunk))) {
+                self.panic.lock().unwrap().get_or_insert(payload);
+            }
+
+            if self.completed.fetch_add(1, Ordering::SeqCst) + 1 == self.total {
+                let _guard = self.done_lock.lock().unwrap();
+                self.done.notify_all();
+            }
+        }
+    }
+
+    fn wait(&self) {
+        let mut guard = self.done_lock.lock().unwrap();
+        while self.completed.load(Ordering::SeqCst) < self.total {
+            guard = self.done.wait(guard).unwrap();
+        }
+    }
+}
+
+/// Escopo de `ThreadPool::scope`: jobs spawnados podem emprestar dados de `'env`
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    state: Arc<ScopeState>,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+struct ScopeState {
+    running: Mutex<usize>,
+    done: Condvar,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl ScopeState {
+    fn record_panic(&self, payload: Box<dyn Any + Send>) {
+        self.panic.lock().unwrap().get_or_insert(payload);
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Executa `f` no pool; o escopo só termina depois que `f` terminar
+    pub fn spawn<F>(&'scope self, f: F)
+    where
+        F: FnOnce(&'scope Scope<'scope, 'env>) + Send + 'scope,
+    {
+        *self.state.running.lock().unwrap() += 1;
+
+        let state = Arc::clone(&self.state);
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(self))) {
+                state.record_panic(payload);
+            }
+
+            let mut running = state.running.lock().unwrap();
+            *running -= 1;
+            if *running == 0 {
+                state.done.notify_all();
+            }
+        });
+
+        // Apaga o lifetime: `ThreadPool::scope` só retorna depois que `running` chega a zero
+        let job: Job = unsafe { std::mem::transmute(job) };
+        self.pool.shared.push(job);
+    }
+}
+
 impl ThreadPool {
     /// Cria um novo thread pool com o número especificado de threads
     pub fn new(size: usize) -> Self {
         assert!(size > 0, "Thread pool size must be greater than 0");
 
-        let (sender, receiver) = std::sync::mpsc::channel();
-        let receiver = Arc::new(Mutex::new(receiver));
-        let active_jobs = Arc::new(AtomicUsize::new(0));
+        let shared = Arc::new(PoolShared {
+            queues: (0..size).map(|_| Mutex::new(VecDeque::new())).collect(),
+            next_queue: AtomicUsize::new(0),
+            pending: AtomicUsize::new(0),
+            active_jobs: AtomicUsize::new(0),
+            sleepers: AtomicUsize::new(0),
+            sleep_lock: Mutex::new(()),
+            wake: Condvar::new(),
+            idle_lock: Mutex::new(()),
+            idle: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            counters: PoolCounters::new(size),
+            profiler: Mutex::new(None),
+            has_profiler: AtomicBool::new(false),
+        });
 
-        let mut workers = Vec::with_capacity(size);
-        for id in 0..size {
-            workers.push(Worker::new(
-                id,
-                Arc::clone(&receiver),
-                Arc::clone(&active_jobs),
-            ));
-        }
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&shared)))
+            .collect();
 
-        ThreadPool {
-            workers,
-            sender,
-            active_jobs,
-        }
+        ThreadPool { workers, shared }
     }
 
     /// Cria um thread pool com número de threads baseado nos CPUs disponíveis
@@ -48,10 +451,50 @@ impl ThreadPool {
     where
         F: FnOnce() + Send + 'static,
     {
-        let job = Box::new(f);
-        self.sender
-            .send(job)
-            .expect("Failed to send job to thread pool");
+        self.shared.push(Box::new(f));
+    }
+
+    /// Executa uma tarefa com nome, usado ao encaminhar a duração para o `Profiler`
+    pub fn execute_named<F>(&self, name: impl Into<String>, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.shared.push_named(Box::new(f), Some(name.into()));
+    }
+
+    /// Executa uma tarefa que recebe um token de cancelamento
+    ///
+    /// Se o token já estiver cancelado quando o job começar, ele é descartado
+    /// sem rodar; jobs longos devem consultar `token.is_cancelled()` e abortar.
+    pub fn execute_cancellable<F>(&self, token: &CancellationToken, f: F)
+    where
+        F: FnOnce(&CancellationToken) + Send + 'static,
+    {
+        let token = token.clone();
+        self.execute(move || {
+            if !token.is_cancelled() {
+                f(&token);
+            }
+        });
+    }
+
+    /// Executa uma tarefa no thread pool e retorna um future com o resultado
+    ///
+    /// ```
+    /// use avila_math::os::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let answer = pool.submit(|| 6 * 7).then(|x| x + 1);
+    /// assert_eq!(answer.wait().unwrap(), 43);
+    /// ```
+    pub fn submit<F, T>(&self, f: F) -> TaskFuture<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (future, promise) = TaskFuture::pending();
+        self.execute(move || promise.complete(panic::catch_unwind(AssertUnwindSafe(f))));
+        future
     }
 
     /// Retorna o número de threads no pool
@@ -61,21 +504,520 @@ impl ThreadPool {
 
     /// Retorna o número de jobs ativos
     pub fn active_jobs(&self) -> usize {
-        self.active_jobs.load(Ordering::Relaxed)
+        self.shared.active_jobs.load(Ordering::Relaxed)
+    }
+
+    /// Retorna o número de jobs enfileirados que ainda não começaram
+    pub fn pending_jobs(&self) -> usize {
+        self.shared.pending.load(Ordering::Relaxed)
+    }
+
+    /// Retorna um snapshot das estatísticas do pool
+    pub fn stats(&self) -> PoolStats {
+        let counters = &self.shared.counters;
+        let uptime = counters.started_at.lock().unwrap().elapsed();
+        let nanos = |counter: &AtomicU64| Duration::from_nanos(counter.load(Ordering::Relaxed));
+
+        let workers = counters
+            .workers
+            .iter()
+            .enumerate()
+            .map(|(index, worker)| {
+                let busy_time = nanos(&worker.busy_time);
+                WorkerStats {
+                    index,
+                    jobs_executed: worker.jobs_executed.load(Ordering::Relaxed),
+                    busy_time,
+                    utilization: if uptime.is_zero() {
+                        0.0
+                    } else {
+                        (busy_time.as_secs_f64() / uptime.as_secs_f64()).min(1.0)
+                    },
+                }
+            })
+            .collect();
+
+        PoolStats {
+            uptime,
+            jobs_completed: counters.jobs_completed.load(Ordering::Relaxed),
+            pending_jobs: self.pending_jobs(),
+            active_jobs: self.active_jobs(),
+            total_task_time: nanos(&counters.task_time),
+            max_task_time: nanos(&counters.max_task_time),
+            total_queue_wait: nanos(&counters.queue_wait),
+            max_queue_wait: nanos(&counters.max_queue_wait),
+            workers,
+        }
+    }
+
+    /// Zera as estatísticas e reinicia o uptime
+    pub fn reset_stats(&self) {
+        self.shared.counters.reset();
+    }
+
+    /// Encaminha a duração de cada job para um `Profiler` (ou desliga com `None`)
+    ///
+    /// Jobs de `execute_named` usam o próprio nome; os demais usam `"thread_pool.job"`.
+    /// Cada job vira um evento de trace na thread do worker que o executou.
+    pub fn set_profiler(&self, profiler: Option<Arc<Mutex<Profiler>>>) {
+        let mut current = self.shared.profiler.lock().unwrap();
+        self.shared
+            .has_profiler
+            .store(profiler.is_some(), Ordering::Relaxed);
+        *current = profiler;
     }
 
-    /// Aguarda todas as tarefas terminarem
+    /// Aguarda todas as tarefas (enfileiradas e em execução) terminarem
+    ///
+    /// Não chame de dentro de um job do próprio pool: o job atual conta como
+    /// ativo e a espera nunca termina.
     pub fn join(&self) {
-        while self.active_jobs() > 0 {
-            thread::yield_now();
+        let mut guard = self.shared.idle_lock.lock().unwrap();
+        while !self.shared.is_idle() {
+            guard = self.shared.idle.wait(guard).unwrap();
+        }
+    }
+
+    /// Como `join`, mas desiste depois do timeout
+    ///
+    /// Retorna `true` se o pool ficou ocioso dentro do prazo.
+    pub fn join_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.shared.idle_lock.lock().unwrap();
+        while !self.shared.is_idle() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            guard = self
+                .shared
+                .idle
+                .wait_timeout(guard, deadline - now)
+                .unwrap()
+                .0;
+        }
+        true
+    }
+
+    /// Executa `f(i)` para cada `i` em `range`, dividido em chunks de `chunk_size`
+    ///
+    /// Bloqueia até todas as iterações terminarem; a closure pode emprestar dados
+    /// da pilha do chamador. Um panic em qualquer iteração é propagado aqui.
+    ///
+    /// ```
+    /// use avila_math::os::ThreadPool;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let sum = AtomicUsize::new(0);
+    /// pool.parallel_for(0..1000, 64, |i| {
+    ///     sum.fetch_add(i, Ordering::Relaxed);
+    /// });
+    /// assert_eq!(sum.into_inner(), 499500);
+    /// ```
+    pub fn parallel_for<F>(&self, range: Range<usize>, chunk_size: usize, f: F)
+    where
+        F: Fn(usize) + Sync,
+    {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+
+        let start = range.start;
+        let end = range.end;
+        let len = end.saturating_sub(start);
+        if len == 0 {
+            return;
+        }
+
+        let num_chunks = len.div_ceil(chunk_size);
+        self.run_chunks(num_chunks, &|chunk| {
+            let chunk_start = start + chunk * chunk_size;
+            let chunk_end = (chunk_start + chunk_size).min(end);
+            for i in chunk_start..chunk_end {
+                f(i);
+            }
+        });
+    }
+
+    /// Aplica `f` a cada item em paralelo e retorna os resultados na ordem original
+    ///
+    /// ```
+    /// use avila_math::os::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let squares = pool.parallel_map(&[1, 2, 3, 4], 2, |x| x * x);
+    /// assert_eq!(squares, vec![1, 4, 9, 16]);
+    /// ```
+    pub fn parallel_map<T, R, F>(&self, items: &[T], chunk_size: usize, f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+
+        let chunks: Vec<&[T]> = items.chunks(chunk_size).collect();
+        let results: Vec<Mutex<Vec<R>>> = chunks.iter().map(|_| Mutex::new(Vec::new())).collect();
+
+        self.run_chunks(chunks.len(), &|chunk| {
+            let mapped: Vec<R> = chunks[chunk].iter().map(&f).collect();
+            *results[chunk].lock().unwrap() = mapped;
+        });
+
+        results
+            .into_iter()
+            .flat_map(|slot| slot.into_inner().unwrap())
+            .collect()
+    }
+
+    /// Cria um escopo onde jobs podem emprestar dados da pilha do chamador
+    ///
+    /// Todos os jobs spawnados no escopo terminam antes de `scope` retornar.
+    /// Enquanto espera, a thread atual ajuda executando jobs do pool, então é
+    /// seguro chamar de dentro de um worker. Panics dos jobs do escopo são
+    /// propagados.
+    ///
+    /// ```
+    /// use avila_math::os::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let mut data = vec![1, 2, 3, 4];
+    /// let (left, right) = data.split_at_mut(2);
+    ///
+    /// pool.scope(|s| {
+    ///     s.spawn(|_| left.iter_mut().for_each(|x| *x *= 10));
+    ///     s.spawn(|_| right.iter_mut().for_each(|x| *x += 1));
+    /// });
+    ///
+    /// assert_eq!(data, vec![10, 20, 4, 5]);
+    /// ```
+    pub fn scope<'env, F, R>(&self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                running: Mutex::new(0),
+                done: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            _scope: PhantomData,
+            _env: PhantomData,
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        // Mesmo se `f` entrar em panic, os jobs precisam terminar antes de sair
+        self.help_until_done(&scope.state);
+
+        let payload = scope.state.panic.lock().unwrap().take();
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(_) if payload.is_some() => panic::resume_unwind(payload.unwrap()),
+            Ok(value) => value,
+        }
+    }
+
+    /// Executa jobs do pool na thread atual até o escopo não ter jobs rodando
+    fn help_until_done(&self, state: &ScopeState) {
+        let pool_id = self.shared.id();
+        let worker = CURRENT_WORKER.with(|current| match current.get() {
+            Some((id, index)) if id == pool_id => Some(index),
+            _ => None,
+        });
+        let index = worker.unwrap_or(0);
+
+        loop {
+            // Pode ser um job de fora do escopo: `run_job` contém o panic dele,
+            // e os jobs do escopo registram os seus em `state`
+            if let Some(job) = self.shared.find_job(index) {
+                self.shared.run_job(job, worker);
+                continue;
+            }
+
+            let running = state.running.lock().unwrap();
+            if *running == 0 {
+                return;
+            }
+            // Timeout curto: jobs novos no pool não notificam este condvar
+            drop(
+                state
+                    .done
+                    .wait_timeout(running, Duration::from_millis(1))
+                    .unwrap(),
+            );
+        }
+    }
+
+    /// Distribui `num_chunks` chamadas de `task` entre os workers e a thread atual
+    fn run_chunks(&self, num_chunks: usize, task: &(dyn Fn(usize) + Sync)) {
+        if num_chunks == 0 {
+            return;
+        }
+
+        // Apaga o lifetime: `wait` abaixo garante que nenhum chunk roda depois do retorno
+        let task: &'static (dyn Fn(usize) + Sync) = unsafe { std::mem::transmute(task) };
+        let state = Arc::new(ParallelState {
+            task,
+            total: num_chunks,
+            next: AtomicUsize::new(0),
+            completed: AtomicUsize::new(0),
+            panic: Mutex::new(None),
+            done_lock: Mutex::new(()),
+            done: Condvar::new(),
+        });
+
+        let helpers = self.size().min(num_chunks - 1);
+        for _ in 0..helpers {
+            let state = Arc::clone(&state);
+            self.execute(move || state.work());
+        }
+
+        state.work();
+        state.wait();
+
+        let payload = state.panic.lock().unwrap().take();
+        if let Some(payload) = payload {
+            panic::resume_unwind(payload);
         }
     }
 }
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        // Aguarda todos os jobs terminarem
-        self.join();
+        // Dropado de dentro de um dos próprios workers: não dá para esperar a si mesmo
+        let pool_id = self.shared.id();
+        let inside_worker =
+            CURRENT_WORKER.with(|current| matches!(current.get(), Some((id, _)) if id == pool_id));
+
+        if !inside_worker {
+            // Aguarda todos os jobs terminarem
+            self.join();
+        }
+
+        // Workers drenam as filas restantes e encerram
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        {
+            let _guard = self.shared.sleep_lock.lock().unwrap();
+            self.shared.wake.notify_all();
+        }
+
+        if inside_worker {
+            return;
+        }
+
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                if thread.join().is_err() {
+                    eprintln!("Thread pool worker {} panicked", worker.id);
+                }
+            }
+        }
+    }
+}
+
+/// Resultado futuro de uma tarefa submetida com `ThreadPool::submit`
+///
+/// Pode ser consultado sem bloquear (`is_ready`/`try_take`), aguardado
+/// (`wait`/`wait_timeout`), encadeado (`then`) ou usado com `.await`.
+/// Um panic na tarefa chega como `Err`, igual a `JoinHandle::join`.
+/// Uma tarefa descartada sem rodar chega como `Err` com `BrokenPromise`.
+pub struct TaskFuture<T> {
+    state: Arc<FutureState<T>>,
+}
+
+type Continuation<T> = Box<dyn FnOnce(thread::Result<T>) + Send>;
+
+struct FutureState<T> {
+    slot: Mutex<FutureSlot<T>>,
+    ready: Condvar,
+}
+
+struct FutureSlot<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+    continuation: Option<Continuation<T>>,
+}
+
+/// Lado produtor de um `TaskFuture`
+///
+/// Dropado sem `complete` (job descartado, panic antes de completar), o
+/// future recebe `Err` com um payload `BrokenPromise` em vez de esperar para
+/// sempre.
+pub(crate) struct Promise<T> {
+    state: Option<Arc<FutureState<T>>>,
+}
+
+/// Payload do `Err` de um `TaskFuture` cuja tarefa foi descartada sem completar
+///
+/// ```
+/// use avila_math::os::BrokenPromise;
+/// # let result: std::thread::Result<()> = Err(Box::new(BrokenPromise));
+/// if let Err(payload) = result {
+///     assert!(payload.is::<BrokenPromise>());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrokenPromise;
+
+impl std::fmt::Display for BrokenPromise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "task was dropped before completing")
+    }
+}
+
+impl std::error::Error for BrokenPromise {}
+
+impl<T> Promise<T> {
+    pub(crate) fn complete(mut self, result: thread::Result<T>) {
+        self.fulfil(result);
+    }
+
+    fn fulfil(&mut self, result: thread::Result<T>) {
+        let Some(state) = self.state.take() else {
+            return;
+        };
+
+        let mut slot = state.slot.lock().unwrap();
+        if let Some(continuation) = slot.continuation.take() {
+            drop(slot);
+            continuation(result);
+            return;
+        }
+
+        slot.result = Some(result);
+        let waker = slot.waker.take();
+        state.ready.notify_all();
+        drop(slot);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Promise<T> {
+    fn drop(&mut self) {
+        self.fulfil(Err(Box::new(BrokenPromise)));
+    }
+}
+
+impl<T: Send + 'static> TaskFuture<T> {
+    pub(crate) fn pending() -> (Self, Promise<T>) {
+        let state = Arc::new(FutureState {
+            slot: Mutex::new(FutureSlot {
+                result: None,
+                waker: None,
+                continuation: None,
+            }),
+            ready: Condvar::new(),
+        });
+
+        (
+            Self {
+                state: Arc::clone(&state),
+            },
+            Promise { state: Some(state) },
+        )
+    }
+
+    /// Verifica se o resultado já está disponível
+    pub fn is_ready(&self) -> bool {
+        self.state.slot.lock().unwrap().result.is_some()
+    }
+
+    /// Retira o resultado se já estiver pronto, sem bloquear
+    ///
+    /// Depois de retirado, o resultado não está mais disponível no future.
+    pub fn try_take(&self) -> Option<thread::Result<T>> {
+        self.state.slot.lock().unwrap().result.take()
+    }
+
+    /// Bloqueia até o resultado ficar pronto
+    pub fn wait(self) -> thread::Result<T> {
+        let mut slot = self.state.slot.lock().unwrap();
+        loop {
+            if let Some(result) = slot.result.take() {
+                return result;
+            }
+            slot = self.state.ready.wait(slot).unwrap();
+        }
+    }
+
+    /// Bloqueia até o resultado ficar pronto ou o timeout expirar
+    ///
+    /// Em caso de timeout o próprio future é devolvido em `Err`.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<thread::Result<T>, Self> {
+        let deadline = Instant::now() + timeout;
+        let mut slot = self.state.slot.lock().unwrap();
+        loop {
+            if let Some(result) = slot.result.take() {
+                return Ok(result);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                drop(slot);
+                return Err(self);
+            }
+            slot = self
+                .state
+                .ready
+                .wait_timeout(slot, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Encadeia uma continuação que roda com o resultado desta tarefa
+    ///
+    /// A continuação roda na thread que completar a tarefa (ou na thread atual,
+    /// se já estiver pronta). Panics da tarefa original são repassados sem
+    /// chamar `f`.
+    pub fn then<U, F>(self, f: F) -> TaskFuture<U>
+    where
+        F: FnOnce(T) -> U + Send + 'static,
+        U: Send + 'static,
+    {
+        let (future, promise) = TaskFuture::pending();
+        let continuation = move |result: thread::Result<T>| {
+            promise.complete(
+                result.and_then(|value| panic::catch_unwind(AssertUnwindSafe(|| f(value)))),
+            );
+        };
+
+        let mut slot = self.state.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => {
+                drop(slot);
+                continuation(result);
+            }
+            None => slot.continuation = Some(Box::new(continuation)),
+        }
+
+        future
+    }
+}
+
+impl<T> Future for TaskFuture<T> {
+    type Output = thread::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.state.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TaskFuture<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ready = self.state.slot.lock().unwrap().result.is_some();
+        f.debug_struct("TaskFuture").field("ready", &ready).finish()
     }
 }
 
@@ -85,26 +1027,11 @@ struct Worker {
 }
 
 impl Worker {
-    fn new(
-        id: usize,
-        receiver: Arc<Mutex<std::sync::mpsc::Receiver<Job>>>,
-        active_jobs: Arc<AtomicUsize>,
-    ) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let job = {
-                let receiver = receiver.lock().unwrap();
-                receiver.recv()
-            };
-
-            match job {
-                Ok(job) => {
-                    active_jobs.fetch_add(1, Ordering::Relaxed);
-                    job();
-                    active_jobs.fetch_sub(1, Ordering::Relaxed);
-                }
-                Err(_) => break,
-            }
-        });
+    fn new(id: usize, shared: Arc<PoolShared>) -> Worker {
+        let thread = thread::Builder::new()
+            .name(format!("avila-worker-{}", id))
+            .spawn(move || shared.worker_loop(id))
+            .expect("Failed to spawn thread pool worker");
 
         Worker {
             id,
@@ -114,9 +1041,16 @@ impl Worker {
 }
 
 /// Task scheduler para execução assíncrona
+///
+/// Além das tarefas por prioridade (`schedule` + `run`), suporta tarefas com
+/// atraso (`schedule_after`) e recorrentes (`schedule_every`), disparadas por
+/// uma timer wheel numa thread dedicada e executadas no thread pool.
 pub struct TaskScheduler {
-    pool: ThreadPool,
+    pool: Arc<ThreadPool>,
     tasks: Arc<Mutex<Vec<Task>>>,
+    token: CancellationToken,
+    timers: Arc<SchedulerTimers>,
+    ticker: Option<JoinHandle<()>>,
 }
 
 struct Task {
@@ -125,11 +1059,196 @@ struct Task {
     priority: u8,
 }
 
+/// Resolução padrão da timer wheel do scheduler
+const DEFAULT_TIMER_TICK: Duration = Duration::from_millis(10);
+const TIMER_WHEEL_SLOTS: usize = 256;
+
+/// Handle para uma tarefa com atraso ou recorrente
+#[derive(Debug, Clone)]
+pub struct ScheduledTask {
+    id: u64,
+    token: CancellationToken,
+}
+
+impl ScheduledTask {
+    /// Identificador da tarefa dentro do scheduler
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Cancela a tarefa; execuções já em andamento não são interrompidas
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Verifica se a tarefa foi cancelada
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Token de cancelamento da tarefa (filho do token do scheduler)
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+enum TimedJob {
+    Once(Job),
+    Every {
+        interval: Duration,
+        job: Arc<dyn Fn() + Send + Sync>,
+    },
+}
+
+struct WheelEntry {
+    /// Voltas completas da wheel que faltam antes de disparar
+    rounds: usize,
+    token: CancellationToken,
+    job: TimedJob,
+}
+
+/// Timer wheel (hashed) com inserção e expiração O(1) por tick
+struct SchedulerWheel {
+    slots: Vec<Vec<WheelEntry>>,
+    cursor: usize,
+    tick: Duration,
+    next_tick_at: Instant,
+    len: usize,
+}
+
+impl SchedulerWheel {
+    fn new(tick: Duration) -> Self {
+        Self {
+            slots: (0..TIMER_WHEEL_SLOTS).map(|_| Vec::new()).collect(),
+            cursor: 0,
+            tick,
+            next_tick_at: Instant::now() + tick,
+            len: 0,
+        }
+    }
+
+    fn insert(&mut self, delay: Duration, token: CancellationToken, job: TimedJob) {
+        let now = Instant::now();
+        if self.len == 0 {
+            // Wheel ociosa não avança; reancora o próximo tick
+            self.next_tick_at = now + self.tick;
+        }
+
+        // Número de ticks até o slot, garantindo que nunca dispare antes do atraso
+        let remaining = (now + delay).saturating_duration_since(self.next_tick_at);
+        let ticks = 1 + remaining.as_nanos().div_ceil(self.tick.as_nanos()) as usize;
+
+        let slot_count = self.slots.len();
+        self.slots[(self.cursor + ticks) % slot_count].push(WheelEntry {
+            rounds: (ticks - 1) / slot_count,
+            token,
+            job,
+        });
+        self.len += 1;
+    }
+
+    /// Avança um tick e retorna as entradas expiradas
+    fn advance(&mut self) -> Vec<WheelEntry> {
+        self.cursor = (self.cursor + 1) % self.slots.len();
+        self.next_tick_at += self.tick;
+
+        let mut expired = Vec::new();
+        let slot = std::mem::take(&mut self.slots[self.cursor]);
+        for mut entry in slot {
+            if entry.rounds == 0 {
+                expired.push(entry);
+            } else {
+                entry.rounds -= 1;
+                self.slots[self.cursor].push(entry);
+            }
+        }
+
+        self.len -= expired.len();
+        expired
+    }
+}
+
+struct SchedulerTimers {
+    wheel: Mutex<SchedulerWheel>,
+    changed: Condvar,
+    shutdown: AtomicBool,
+    next_id: AtomicU64,
+}
+
+impl SchedulerTimers {
+    fn run(&self, pool: &ThreadPool) {
+        let mut wheel = self.wheel.lock().unwrap();
+        loop {
+            if self.shutdown.load(Ordering::SeqCst) {
+                return;
+            }
+            if wheel.len == 0 {
+                wheel = self.changed.wait(wheel).unwrap();
+                continue;
+            }
+
+            let now = Instant::now();
+            if now < wheel.next_tick_at {
+                let timeout = wheel.next_tick_at - now;
+                wheel = self.changed.wait_timeout(wheel, timeout).unwrap().0;
+                continue;
+            }
+
+            let mut expired = Vec::new();
+            while wheel.next_tick_at <= now {
+                expired.extend(wheel.advance());
+            }
+
+            for entry in expired {
+                if entry.token.is_cancelled() {
+                    continue;
+                }
+
+                match entry.job {
+                    TimedJob::Once(job) => pool.execute(job),
+                    TimedJob::Every { interval, job } => {
+                        let run = Arc::clone(&job);
+                        pool.execute(move || run());
+                        wheel.insert(interval, entry.token, TimedJob::Every { interval, job });
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl TaskScheduler {
     pub fn new(num_threads: usize) -> Self {
+        Self::with_timer_resolution(num_threads, DEFAULT_TIMER_TICK)
+    }
+
+    /// Cria um scheduler com a resolução (tick) dada para tarefas com atraso
+    pub fn with_timer_resolution(num_threads: usize, tick: Duration) -> Self {
+        assert!(!tick.is_zero(), "Timer resolution must be greater than 0");
+
+        let pool = Arc::new(ThreadPool::new(num_threads));
+        let timers = Arc::new(SchedulerTimers {
+            wheel: Mutex::new(SchedulerWheel::new(tick)),
+            changed: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            next_id: AtomicU64::new(0),
+        });
+
+        let ticker = {
+            let pool = Arc::clone(&pool);
+            let timers = Arc::clone(&timers);
+            thread::Builder::new()
+                .name("avila-scheduler-timer".to_string())
+                .spawn(move || timers.run(&pool))
+                .expect("Failed to spawn scheduler timer thread")
+        };
+
         Self {
-            pool: ThreadPool::new(num_threads),
+            pool,
             tasks: Arc::new(Mutex::new(Vec::new())),
+            token: CancellationToken::new(),
+            timers,
+            ticker: Some(ticker),
         }
     }
 
@@ -146,6 +1265,34 @@ impl TaskScheduler {
         self.tasks.lock().unwrap().push(task);
     }
 
+    /// Agenda uma tarefa que recebe um token de cancelamento
+    ///
+    /// O token é filho do token do scheduler: é cancelado pelo handle retornado
+    /// ou quando o scheduler é destruído.
+    pub fn schedule_cancellable<F>(
+        &self,
+        name: impl Into<String>,
+        priority: u8,
+        f: F,
+    ) -> CancellationToken
+    where
+        F: FnOnce(&CancellationToken) + Send + 'static,
+    {
+        let token = self.token.child_token();
+        let job_token = token.clone();
+        self.schedule(name, priority, move || {
+            if !job_token.is_cancelled() {
+                f(&job_token);
+            }
+        });
+        token
+    }
+
+    /// Token raiz do scheduler, cancelado quando o scheduler é destruído
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
     pub fn run(&self) {
         let mut tasks = self.tasks.lock().unwrap();
         tasks.sort_by(|a, b| b.priority.cmp(&a.priority));
@@ -154,6 +1301,76 @@ impl TaskScheduler {
             self.pool.execute(task.job);
         }
     }
+
+    /// Executa a tarefa uma vez, depois do atraso dado
+    pub fn schedule_after<F>(&self, delay: Duration, f: F) -> ScheduledTask
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.insert_timer(delay, TimedJob::Once(Box::new(f)))
+    }
+
+    /// Executa a tarefa repetidamente a cada `interval`, até ser cancelada
+    pub fn schedule_every<F>(&self, interval: Duration, f: F) -> ScheduledTask
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        assert!(!interval.is_zero(), "Interval must be greater than 0");
+        self.insert_timer(
+            interval,
+            TimedJob::Every {
+                interval,
+                job: Arc::new(f),
+            },
+        )
+    }
+
+    /// Cancela uma tarefa com atraso ou recorrente
+    pub fn cancel(&self, task: &ScheduledTask) {
+        task.cancel();
+    }
+
+    /// Retorna o número de tarefas aguardando na timer wheel
+    ///
+    /// Tarefas canceladas continuam contando até o slot delas expirar.
+    pub fn pending_timers(&self) -> usize {
+        self.timers.wheel.lock().unwrap().len
+    }
+
+    fn insert_timer(&self, delay: Duration, job: TimedJob) -> ScheduledTask {
+        let handle = ScheduledTask {
+            id: self.timers.next_id.fetch_add(1, Ordering::Relaxed),
+            token: self.token.child_token(),
+        };
+
+        self.timers
+            .wheel
+            .lock()
+            .unwrap()
+            .insert(delay, handle.token.clone(), job);
+        self.timers.changed.notify_one();
+
+        handle
+    }
+}
+
+impl Drop for TaskScheduler {
+    fn drop(&mut self) {
+        // Sinaliza jobs em andamento antes do pool esperar por eles
+        self.token.cancel();
+
+        {
+            let _wheel = self.timers.wheel.lock().unwrap();
+            self.timers.shutdown.store(true, Ordering::SeqCst);
+            self.timers.changed.notify_all();
+        }
+
+        if let Some(ticker) = self.ticker.take() {
+            if ticker.join().is_err() {
+                eprintln!("Scheduler timer thread panicked");
+            }
+        }
+    }
 }
 
 /// Thread handle com nome e metadata
@@ -244,34 +1461,191 @@ impl Semaphore {
     }
 }
 
-/// Read-Write Lock com contador de leitores
+/// Read-Write Lock com contador de leitores/escritores e métricas de contenção
+///
+/// `read`/`write` retornam guards RAII: os contadores de ativos só são
+/// decrementados quando o guard é destruído.
 pub struct RwCounter<T> {
     data: RwLock<T>,
     readers: AtomicUsize,
     writers: AtomicUsize,
+    metrics: LockCounters,
+}
+
+#[derive(Default)]
+struct LockCounters {
+    read_acquisitions: AtomicU64,
+    write_acquisitions: AtomicU64,
+    contended_reads: AtomicU64,
+    contended_writes: AtomicU64,
+    read_wait: AtomicU64,
+    write_wait: AtomicU64,
+    max_wait: AtomicU64,
+}
+
+impl LockCounters {
+    fn record_wait(&self, total: &AtomicU64, wait: Duration) {
+        let nanos = wait.as_nanos() as u64;
+        total.fetch_add(nanos, Ordering::Relaxed);
+        self.max_wait.fetch_max(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Snapshot das métricas de contenção de um `RwCounter`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LockMetrics {
+    pub read_acquisitions: u64,
+    pub write_acquisitions: u64,
+    /// Leituras que encontraram o lock ocupado e tiveram que esperar
+    pub contended_reads: u64,
+    pub contended_writes: u64,
+    pub total_read_wait: Duration,
+    pub total_write_wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl LockMetrics {
+    /// Fração das aquisições que tiveram contenção (0.0 - 1.0)
+    pub fn contention_ratio(&self) -> f64 {
+        let total = self.read_acquisitions + self.write_acquisitions;
+        if total == 0 {
+            0.0
+        } else {
+            (self.contended_reads + self.contended_writes) as f64 / total as f64
+        }
+    }
+}
+
+/// Guard de leitura de um `RwCounter`
+pub struct RwCounterReadGuard<'a, T> {
+    guard: std::sync::RwLockReadGuard<'a, T>,
+    readers: &'a AtomicUsize,
+}
+
+/// Guard de escrita de um `RwCounter`
+pub struct RwCounterWriteGuard<'a, T> {
+    guard: std::sync::RwLockWriteGuard<'a, T>,
+    writers: &'a AtomicUsize,
+}
+
+impl<T> std::ops::Deref for RwCounterReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> Drop for RwCounterReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.readers.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
-impl<T> RwCounter<T> {
-    pub fn new(data: T) -> Self {
-        Self {
-            data: RwLock::new(data),
-            readers: AtomicUsize::new(0),
-            writers: AtomicUsize::new(0),
+impl<T> std::ops::Deref for RwCounterWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> std::ops::DerefMut for RwCounterWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for RwCounterWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.writers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<T> RwCounter<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            data: RwLock::new(data),
+            readers: AtomicUsize::new(0),
+            writers: AtomicUsize::new(0),
+            metrics: LockCounters::default(),
+        }
+    }
+
+    pub fn read(&self) -> RwCounterReadGuard<'_, T> {
+        let guard = match self.data.try_read() {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(err)) => panic!("{}", err),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = self.data.read().unwrap();
+                self.metrics.contended_reads.fetch_add(1, Ordering::Relaxed);
+                self.metrics
+                    .record_wait(&self.metrics.read_wait, start.elapsed());
+                guard
+            }
+        };
+
+        self.metrics
+            .read_acquisitions
+            .fetch_add(1, Ordering::Relaxed);
+        self.readers.fetch_add(1, Ordering::Relaxed);
+        RwCounterReadGuard {
+            guard,
+            readers: &self.readers,
+        }
+    }
+
+    pub fn write(&self) -> RwCounterWriteGuard<'_, T> {
+        let guard = match self.data.try_write() {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(err)) => panic!("{}", err),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = self.data.write().unwrap();
+                self.metrics
+                    .contended_writes
+                    .fetch_add(1, Ordering::Relaxed);
+                self.metrics
+                    .record_wait(&self.metrics.write_wait, start.elapsed());
+                guard
+            }
+        };
+
+        self.metrics
+            .write_acquisitions
+            .fetch_add(1, Ordering::Relaxed);
+        self.writers.fetch_add(1, Ordering::Relaxed);
+        RwCounterWriteGuard {
+            guard,
+            writers: &self.writers,
         }
     }
 
-    pub fn read(&self) -> std::sync::RwLockReadGuard<T> {
+    /// Tenta adquirir leitura sem bloquear
+    pub fn try_read(&self) -> Option<RwCounterReadGuard<'_, T>> {
+        let guard = self.data.try_read().ok()?;
+        self.metrics
+            .read_acquisitions
+            .fetch_add(1, Ordering::Relaxed);
         self.readers.fetch_add(1, Ordering::Relaxed);
-        let guard = self.data.read().unwrap();
-        self.readers.fetch_sub(1, Ordering::Relaxed);
-        guard
+        Some(RwCounterReadGuard {
+            guard,
+            readers: &self.readers,
+        })
     }
 
-    pub fn write(&self) -> std::sync::RwLockWriteGuard<T> {
+    /// Tenta adquirir escrita sem bloquear
+    pub fn try_write(&self) -> Option<RwCounterWriteGuard<'_, T>> {
+        let guard = self.data.try_write().ok()?;
+        self.metrics
+            .write_acquisitions
+            .fetch_add(1, Ordering::Relaxed);
         self.writers.fetch_add(1, Ordering::Relaxed);
-        let guard = self.data.write().unwrap();
-        self.writers.fetch_sub(1, Ordering::Relaxed);
-        guard
+        Some(RwCounterWriteGuard {
+            guard,
+            writers: &self.writers,
+        })
     }
 
     pub fn active_readers(&self) -> usize {
@@ -281,6 +1655,38 @@ impl<T> RwCounter<T> {
     pub fn active_writers(&self) -> usize {
         self.writers.load(Ordering::Relaxed)
     }
+
+    /// Retorna um snapshot das métricas de contenção
+    pub fn metrics(&self) -> LockMetrics {
+        let m = &self.metrics;
+        let nanos = |counter: &AtomicU64| Duration::from_nanos(counter.load(Ordering::Relaxed));
+
+        LockMetrics {
+            read_acquisitions: m.read_acquisitions.load(Ordering::Relaxed),
+            write_acquisitions: m.write_acquisitions.load(Ordering::Relaxed),
+            contended_reads: m.contended_reads.load(Ordering::Relaxed),
+            contended_writes: m.contended_writes.load(Ordering::Relaxed),
+            total_read_wait: nanos(&m.read_wait),
+            total_write_wait: nanos(&m.write_wait),
+            max_wait: nanos(&m.max_wait),
+        }
+    }
+
+    /// Zera as métricas de contenção
+    pub fn reset_metrics(&self) {
+        let m = &self.metrics;
+        for counter in [
+            &m.read_acquisitions,
+            &m.write_acquisitions,
+            &m.contended_reads,
+            &m.contended_writes,
+            &m.read_wait,
+            &m.write_wait,
+            &m.max_wait,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
 }
 
 /// Barreira para sincronização de múltiplas threads
@@ -307,28 +1713,64 @@ impl ThreadBarrier {
 }
 
 /// Flag atômica para shutdown
+///
+/// Pode ser acionada por sinais do sistema via `os::signal::shutdown_flag`.
 pub struct ShutdownFlag {
-    flag: Arc<AtomicBool>,
+    inner: Arc<ShutdownInner>,
+}
+
+struct ShutdownInner {
+    flag: AtomicBool,
+    lock: Mutex<()>,
+    condvar: Condvar,
 }
 
 impl ShutdownFlag {
     pub fn new() -> Self {
         Self {
-            flag: Arc::new(AtomicBool::new(false)),
+            inner: Arc::new(ShutdownInner {
+                flag: AtomicBool::new(false),
+                lock: Mutex::new(()),
+                condvar: Condvar::new(),
+            }),
         }
     }
 
     pub fn shutdown(&self) {
-        self.flag.store(true, Ordering::Relaxed);
+        let _guard = self.inner.lock.lock().unwrap();
+        self.inner.flag.store(true, Ordering::Relaxed);
+        self.inner.condvar.notify_all();
     }
 
     pub fn is_shutdown(&self) -> bool {
-        self.flag.load(Ordering::Relaxed)
+        self.inner.flag.load(Ordering::Relaxed)
+    }
+
+    /// Bloqueia até `shutdown` ser chamado
+    pub fn wait(&self) {
+        let mut guard = self.inner.lock.lock().unwrap();
+        while !self.is_shutdown() {
+            guard = self.inner.condvar.wait(guard).unwrap();
+        }
+    }
+
+    /// Espera até `timeout`; retorna se o shutdown foi pedido
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.inner.lock.lock().unwrap();
+        while !self.is_shutdown() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            guard = self.inner.condvar.wait_timeout(guard, deadline - now).unwrap().0;
+        }
+        true
     }
 
     pub fn clone_handle(&self) -> Self {
         Self {
-            flag: Arc::clone(&self.flag),
+            inner: Arc::clone(&self.inner),
         }
     }
 }
@@ -339,6 +1781,98 @@ impl Default for ShutdownFlag {
     }
 }
 
+/// Token de cancelamento hierárquico
+///
+/// Clones compartilham o mesmo estado. Cancelar um token cancela todos os
+/// tokens filhos (`child_token`), mas não o pai, permitindo abortar um
+/// subsistema inteiro ou só uma tarefa:
+///
+/// ```
+/// use avila_math::os::CancellationToken;
+///
+/// let root = CancellationToken::new();
+/// let bake = root.child_token();
+/// let path = root.child_token();
+///
+/// bake.cancel();
+/// assert!(!root.is_cancelled() && !path.is_cancelled());
+///
+/// root.cancel();
+/// assert!(path.is_cancelled());
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<TokenInner>,
+}
+
+struct TokenInner {
+    cancelled: AtomicBool,
+    children: Mutex<Vec<std::sync::Weak<TokenInner>>>,
+}
+
+impl CancellationToken {
+    /// Cria um token raiz não cancelado
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(TokenInner {
+                cancelled: AtomicBool::new(false),
+                children: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Cria um token filho, cancelado junto com este
+    pub fn child_token(&self) -> Self {
+        let child = Self::new();
+        let mut children = self.inner.children.lock().unwrap();
+
+        if self.is_cancelled() {
+            child.inner.cancelled.store(true, Ordering::SeqCst);
+        } else {
+            // Aproveita para descartar filhos já destruídos
+            children.retain(|weak| weak.strong_count() > 0);
+            children.push(Arc::downgrade(&child.inner));
+        }
+
+        child
+    }
+
+    /// Cancela este token e todos os descendentes
+    pub fn cancel(&self) {
+        Self::cancel_inner(&self.inner);
+    }
+
+    fn cancel_inner(inner: &TokenInner) {
+        if inner.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let children = std::mem::take(&mut *inner.children.lock().unwrap());
+        for child in children.iter().filter_map(|weak| weak.upgrade()) {
+            Self::cancel_inner(&child);
+        }
+    }
+
+    /// Verifica se o token foi cancelado
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
 /// Retorna o número de CPUs/cores disponíveis
 pub fn num_cpus() -> usize {
     thread::available_parallelism()
@@ -378,6 +1912,533 @@ mod tests {
         assert_eq!(counter.load(Ordering::Relaxed), 10);
     }
 
+    #[test]
+    fn test_join_waits_for_queued_jobs() {
+        let pool = ThreadPool::new(1);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..5 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(2));
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        // Jobs ainda na fila também contam, não só os ativos
+        pool.join();
+        assert_eq!(counter.load(Ordering::SeqCst), 5);
+        assert_eq!(pool.pending_jobs(), 0);
+        assert_eq!(pool.active_jobs(), 0);
+    }
+
+    #[test]
+    fn test_join_timeout() {
+        let pool = ThreadPool::new(1);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+
+        pool.execute(move || {
+            rx.recv().unwrap();
+        });
+
+        assert!(!pool.join_timeout(Duration::from_millis(10)));
+        tx.send(()).unwrap();
+        assert!(pool.join_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_join_after_panicking_job() {
+        let pool = ThreadPool::new(2);
+        pool.execute(|| panic!("job failure"));
+        pool.execute(|| {});
+
+        assert!(pool.join_timeout(Duration::from_secs(5)));
+        assert_eq!(pool.active_jobs(), 0);
+    }
+
+    #[test]
+    fn test_worker_survives_panicking_job() {
+        let pool = ThreadPool::new(1);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        pool.execute(|| panic!("job failure"));
+        for _ in 0..3 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        assert!(pool.join_timeout(Duration::from_secs(5)));
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+        assert_eq!(pool.submit(|| 7).wait().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_pool_stats() {
+        let pool = ThreadPool::new(2);
+        for _ in 0..6 {
+            pool.execute(|| thread::sleep(Duration::from_millis(5)));
+        }
+        pool.join();
+
+        let stats = pool.stats();
+        assert_eq!(stats.jobs_completed, 6);
+        assert_eq!(stats.pending_jobs, 0);
+        assert!(stats.max_task_time >= Duration::from_millis(5));
+        assert!(stats.average_task_time() >= Duration::from_millis(5));
+        assert!(stats.total_queue_wait >= stats.max_queue_wait);
+        assert_eq!(
+            stats.workers.iter().map(|w| w.jobs_executed).sum::<u64>(),
+            6
+        );
+        assert!(stats.utilization() > 0.0 && stats.utilization() <= 1.0);
+
+        pool.reset_stats();
+        assert_eq!(pool.stats().jobs_completed, 0);
+    }
+
+    #[test]
+    fn test_pool_profiler_forwarding() {
+        let pool = ThreadPool::new(1);
+        let profiler = Arc::new(Mutex::new(Profiler::new()));
+        pool.set_profiler(Some(Arc::clone(&profiler)));
+
+        pool.execute_named("bake_lightmap", || thread::sleep(Duration::from_millis(2)));
+        pool.execute(|| {});
+        pool.join();
+
+        let profiler = profiler.lock().unwrap();
+        assert!(profiler.average("bake_lightmap").unwrap() >= Duration::from_millis(2));
+        assert!(profiler.average(DEFAULT_JOB_NAME).is_some());
+    }
+
+    #[test]
+    fn test_thread_pool_nested_execute() {
+        let pool = Arc::new(ThreadPool::new(4));
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..8 {
+            let inner_pool = Arc::clone(&pool);
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                for _ in 0..8 {
+                    let counter = Arc::clone(&counter);
+                    inner_pool.execute(move || {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            });
+        }
+
+        while counter.load(Ordering::Relaxed) < 64 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(pool.pending_jobs(), 0);
+    }
+
+    #[test]
+    fn test_thread_pool_drop_drains_queue() {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        {
+            let pool = ThreadPool::new(2);
+            for _ in 0..100 {
+                let counter = Arc::clone(&counter);
+                pool.execute(move || {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        }
+
+        assert_eq!(counter.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn test_parallel_for() {
+        let pool = ThreadPool::new(4);
+        let data: Vec<AtomicUsize> = (0..1000).map(|_| AtomicUsize::new(0)).collect();
+
+        pool.parallel_for(0..data.len(), 37, |i| {
+            data[i].fetch_add(i, Ordering::SeqCst);
+        });
+
+        for (i, value) in data.iter().enumerate() {
+            assert_eq!(value.load(Ordering::SeqCst), i);
+        }
+
+        // Range vazio não executa nada
+        pool.parallel_for(5..5, 8, |_| panic!("should not run"));
+    }
+
+    #[test]
+    fn test_parallel_map_preserves_order() {
+        let pool = ThreadPool::new(3);
+        let items: Vec<u64> = (0..257).collect();
+
+        let doubled = pool.parallel_map(&items, 16, |x| x * 2);
+
+        assert_eq!(doubled.len(), items.len());
+        assert!(doubled.iter().zip(&items).all(|(d, x)| *d == x * 2));
+    }
+
+    #[test]
+    fn test_parallel_for_nested_in_worker() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let inner_pool = Arc::clone(&pool);
+        let inner_sum = Arc::clone(&sum);
+        let (tx, rx) = std::sync::mpsc::channel();
+        pool.execute(move || {
+            inner_pool.parallel_for(0..100, 10, |i| {
+                inner_sum.fetch_add(i, Ordering::SeqCst);
+            });
+            tx.send(()).unwrap();
+        });
+
+        rx.recv().unwrap();
+        assert_eq!(sum.load(Ordering::SeqCst), 4950);
+    }
+
+    #[test]
+    fn test_parallel_for_propagates_panic() {
+        let pool = ThreadPool::new(2);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.parallel_for(0..100, 1, |i| {
+                if i == 42 {
+                    panic!("iteration failed");
+                }
+            });
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scope_borrows_stack() {
+        let pool = ThreadPool::new(3);
+        let mut values = vec![0u64; 64];
+        let offset = 7;
+
+        pool.scope(|s| {
+            for (i, chunk) in values.chunks_mut(8).enumerate() {
+                s.spawn(move |_| {
+                    for (j, value) in chunk.iter_mut().enumerate() {
+                        *value = (i * 8 + j) as u64 + offset;
+                    }
+                });
+            }
+        });
+
+        assert!(values
+            .iter()
+            .enumerate()
+            .all(|(i, v)| *v == i as u64 + offset));
+    }
+
+    #[test]
+    fn test_scope_nested_spawn_and_result() {
+        let pool = ThreadPool::new(2);
+        let counter = AtomicUsize::new(0);
+
+        let result = pool.scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|s| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    s.spawn(|_| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    });
+                });
+            }
+            "scoped"
+        });
+
+        assert_eq!(result, "scoped");
+        assert_eq!(counter.load(Ordering::SeqCst), 8);
+    }
+
+    #[test]
+    fn test_scope_inside_worker() {
+        let pool = Arc::new(ThreadPool::new(1));
+        let inner = Arc::clone(&pool);
+
+        let total = pool
+            .submit(move || {
+                let data = [1, 2, 3, 4];
+                let sum = AtomicUsize::new(0);
+                inner.scope(|s| {
+                    for value in &data {
+                        let sum = &sum;
+                        s.spawn(move |_| {
+                            sum.fetch_add(*value, Ordering::SeqCst);
+                        });
+                    }
+                });
+                sum.into_inner()
+            })
+            .wait()
+            .unwrap();
+
+        assert_eq!(total, 10);
+    }
+
+    #[test]
+    fn test_scope_propagates_panic_after_completion() {
+        let pool = ThreadPool::new(2);
+        let finished = AtomicBool::new(false);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                s.spawn(|_| panic!("scoped job failed"));
+                s.spawn(|_| {
+                    thread::sleep(Duration::from_millis(20));
+                    finished.store(true, Ordering::SeqCst);
+                });
+            });
+        }));
+
+        assert!(result.is_err());
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_scope_ignores_unrelated_panics() {
+        let pool = ThreadPool::new(1);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+
+        // Ocupa o único worker, então o job que entra em panic só pode ser
+        // executado pela thread que espera o escopo
+        pool.execute(move || rx.recv().unwrap());
+        pool.execute(|| panic!("unrelated job failed"));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                s.spawn(|_| thread::sleep(Duration::from_millis(20)));
+            })
+        }));
+
+        assert!(result.is_ok());
+        assert_eq!(pool.pending_jobs(), 0);
+        tx.send(()).unwrap();
+        assert!(pool.join_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_submit_wait() {
+        let pool = ThreadPool::new(2);
+        let future = pool.submit(|| (1..=10).sum::<u32>());
+
+        assert_eq!(future.wait().unwrap(), 55);
+    }
+
+    #[test]
+    fn test_submit_poll_and_timeout() {
+        let pool = ThreadPool::new(1);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let future = pool.submit(move || {
+            rx.recv().unwrap();
+            "done"
+        });
+
+        assert!(!future.is_ready());
+        assert!(future.try_take().is_none());
+        let future = future.wait_timeout(Duration::from_millis(10)).unwrap_err();
+
+        tx.send(()).unwrap();
+        let result = future.wait_timeout(Duration::from_secs(5)).ok().unwrap();
+        assert_eq!(result.unwrap(), "done");
+    }
+
+    #[test]
+    fn test_submit_then_chain() {
+        let pool = ThreadPool::new(2);
+        let future = pool.submit(|| 20).then(|x| x + 1).then(|x| x * 2);
+
+        assert_eq!(future.wait().unwrap(), 42);
+
+        // Encadear depois de pronto roda imediatamente
+        let ready = pool.submit(|| 1);
+        while !ready.is_ready() {
+            thread::yield_now();
+        }
+        assert_eq!(ready.then(|x| x + 1).wait().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_submit_panic_propagates() {
+        let pool = ThreadPool::new(1);
+        let future = pool.submit(|| -> u32 { panic!("task failed") });
+        let chained = future.then(|x| x + 1);
+
+        assert!(chained.wait().is_err());
+        assert_eq!(pool.submit(|| 5).wait().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_dropped_promise_breaks_future() {
+        let (future, promise) = TaskFuture::<u32>::pending();
+        let chained = future.then(|x| x + 1);
+        drop(promise);
+
+        let payload = chained.wait().unwrap_err();
+        assert!(payload.is::<BrokenPromise>());
+
+        // Um job descartado sem rodar também completa o future
+        let (future, promise) = TaskFuture::<u32>::pending();
+        let job: Job = Box::new(move || promise.complete(Ok(1)));
+        drop(job);
+        let result = future.wait_timeout(Duration::from_secs(5)).ok().unwrap();
+        assert!(result.unwrap_err().is::<BrokenPromise>());
+    }
+
+    #[test]
+    fn test_schedule_after() {
+        let scheduler = TaskScheduler::with_timer_resolution(2, Duration::from_millis(1));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let start = Instant::now();
+        scheduler.schedule_after(Duration::from_millis(20), move || {
+            tx.send(Instant::now()).unwrap();
+        });
+
+        let fired_at = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(fired_at - start >= Duration::from_millis(20));
+        assert_eq!(scheduler.pending_timers(), 0);
+    }
+
+    #[test]
+    fn test_schedule_after_cancel() {
+        let scheduler = TaskScheduler::with_timer_resolution(1, Duration::from_millis(1));
+        let fired = Arc::new(AtomicBool::new(false));
+
+        let f = Arc::clone(&fired);
+        let task = scheduler.schedule_after(Duration::from_millis(20), move || {
+            f.store(true, Ordering::SeqCst);
+        });
+        scheduler.cancel(&task);
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(task.is_cancelled());
+        assert!(!fired.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_schedule_every() {
+        let scheduler = TaskScheduler::with_timer_resolution(2, Duration::from_millis(1));
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let c = Arc::clone(&counter);
+        let task = scheduler.schedule_every(Duration::from_millis(5), move || {
+            if c.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                tx.send(()).unwrap();
+            }
+        });
+
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        task.cancel();
+        thread::sleep(Duration::from_millis(20));
+
+        let after_cancel = counter.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(30));
+        assert!(after_cancel >= 3);
+        assert_eq!(counter.load(Ordering::SeqCst), after_cancel);
+    }
+
+    #[test]
+    fn test_scheduler_wheel_rounds() {
+        let mut wheel = SchedulerWheel::new(Duration::from_millis(1));
+        let delay = Duration::from_millis(TIMER_WHEEL_SLOTS as u64 * 2);
+        wheel.insert(
+            delay,
+            CancellationToken::new(),
+            TimedJob::Once(Box::new(|| {})),
+        );
+
+        // Precisa de mais de duas voltas completas para expirar
+        let mut ticks = 0;
+        while wheel.advance().is_empty() {
+            ticks += 1;
+            assert!(ticks <= TIMER_WHEEL_SLOTS * 3);
+        }
+        assert!(ticks + 1 >= TIMER_WHEEL_SLOTS * 2);
+        assert_eq!(wheel.len, 0);
+    }
+
+    #[test]
+    fn test_cancellation_token_hierarchy() {
+        let root = CancellationToken::new();
+        let child = root.child_token();
+        let grandchild = child.child_token();
+        let sibling = root.child_token();
+
+        child.cancel();
+        assert!(child.is_cancelled() && grandchild.is_cancelled());
+        assert!(!root.is_cancelled() && !sibling.is_cancelled());
+
+        root.cancel();
+        assert!(sibling.is_cancelled());
+        assert!(root.child_token().is_cancelled());
+    }
+
+    #[test]
+    fn test_execute_cancellable() {
+        let pool = ThreadPool::new(1);
+        let token = CancellationToken::new();
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+
+        pool.execute_cancellable(&token, move |token| {
+            started_tx.send(()).unwrap();
+            let mut iterations = 0u64;
+            while !token.is_cancelled() {
+                iterations += 1;
+                thread::yield_now();
+            }
+            done_tx.send(iterations).unwrap();
+        });
+
+        // Já cancelado quando começar: não roda
+        let skipped = CancellationToken::new();
+        skipped.cancel();
+        let ran = Arc::new(AtomicBool::new(false));
+        let r = Arc::clone(&ran);
+        pool.execute_cancellable(&skipped, move |_| r.store(true, Ordering::SeqCst));
+
+        started_rx.recv().unwrap();
+        token.cancel();
+        done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        drop(pool);
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_scheduler_drop_cancels_jobs() {
+        let scheduler = TaskScheduler::new(1);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+
+        scheduler.schedule_cancellable("bake", 0, move |token| {
+            started_tx.send(()).unwrap();
+            while !token.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            tx.send(()).unwrap();
+        });
+        scheduler.run();
+        let delayed = scheduler.schedule_after(Duration::from_secs(60), || {});
+
+        let token = scheduler.token().clone();
+        started_rx.recv().unwrap();
+        drop(scheduler);
+
+        assert!(token.is_cancelled());
+        assert!(delayed.is_cancelled());
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
     #[test]
     fn test_semaphore() {
         let sem = Semaphore::new(2);
@@ -392,6 +2453,60 @@ mod tests {
         assert_eq!(sem.available(), 1);
     }
 
+    #[test]
+    fn test_rw_counter_guards() {
+        let lock = RwCounter::new(5);
+
+        {
+            let a = lock.read();
+            let b = lock.read();
+            assert_eq!(*a + *b, 10);
+            assert_eq!(lock.active_readers(), 2);
+            assert!(lock.try_write().is_none());
+        }
+        assert_eq!(lock.active_readers(), 0);
+
+        {
+            let mut w = lock.write();
+            *w += 1;
+            assert_eq!(lock.active_writers(), 1);
+            assert!(lock.try_read().is_none());
+        }
+        assert_eq!(lock.active_writers(), 0);
+        assert_eq!(*lock.read(), 6);
+    }
+
+    #[test]
+    fn test_rw_counter_metrics() {
+        let lock = Arc::new(RwCounter::new(0u32));
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+
+        let writer = {
+            let lock = Arc::clone(&lock);
+            thread::spawn(move || {
+                let mut guard = lock.write();
+                locked_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(20));
+                *guard += 1;
+            })
+        };
+
+        locked_rx.recv().unwrap();
+        assert_eq!(*lock.read(), 1);
+        writer.join().unwrap();
+
+        let metrics = lock.metrics();
+        assert_eq!(metrics.write_acquisitions, 1);
+        assert_eq!(metrics.read_acquisitions, 1);
+        assert_eq!(metrics.contended_reads, 1);
+        assert!(metrics.total_read_wait >= Duration::from_millis(10));
+        assert!(metrics.max_wait >= metrics.total_read_wait);
+        assert!((metrics.contention_ratio() - 0.5).abs() < f64::EPSILON);
+
+        lock.reset_metrics();
+        assert_eq!(lock.metrics(), LockMetrics::default());
+    }
+
     #[test]
     fn test_managed_thread() {
         let thread = ManagedThread::spawn("test_thread", || {
@@ -407,7 +2522,13 @@ mod tests {
         let flag = ShutdownFlag::new();
         assert!(!flag.is_shutdown());
 
+        assert!(!flag.wait_timeout(Duration::from_millis(10)));
+
+        let handle = flag.clone_handle();
+        let waiter = std::thread::spawn(move || handle.wait());
         flag.shutdown();
         assert!(flag.is_shutdown());
+        waiter.join().unwrap();
+        assert!(flag.wait_timeout(Duration::from_millis(10)));
     }
 }
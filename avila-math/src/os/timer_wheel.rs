@@ -0,0 +1,346 @@
+//! Timer wheel para muitos timers leves
+//!
+//! Wheel com hash: cada timer cai no slot `prazo % slots` e só é examinado
+//! quando o cursor passa por ele, então inserir, cancelar e avançar um tick
+//! custam O(1) (mais o número de timers daquele slot). A resolução é o
+//! tamanho do tick; um timer nunca dispara antes do atraso pedido.
+//!
+//! A wheel não tem thread própria: quem avança é o chamador, com o tempo real
+//! (`poll`) ou com o tempo de jogo (`advance`), o que permite pausar
+//! cooldowns junto com a simulação.
+//!
+//! ```
+//! use avila_math::os::timer_wheel::TimerWheel;
+//! use std::time::Duration;
+//!
+//! let mut cooldowns = TimerWheel::new(Duration::from_millis(10));
+//! cooldowns.insert(Duration::from_millis(250), "dash");
+//! cooldowns.insert(Duration::from_secs(2), "ultimate");
+//!
+//! let mut ready = Vec::new();
+//! cooldowns.advance(Duration::from_millis(300), |_, ability| ready.push(*ability));
+//! assert_eq!(ready, ["dash"]);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+/// Slots padrão da wheel
+pub const DEFAULT_WHEEL_SLOTS: usize = 512;
+
+/// Callback de timer para `TimerWheel<TimerCallback>`
+pub type TimerCallback = Box<dyn FnMut() + Send>;
+
+/// Identificador de um timer na wheel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(u64);
+
+struct WheelTimer<T> {
+    /// Tick absoluto em que dispara
+    deadline: u64,
+    /// Intervalo em ticks, para timers repetidos
+    interval: Option<u64>,
+    payload: T,
+}
+
+/// Conjunto de timers com inserção e expiração O(1)
+pub struct TimerWheel<T> {
+    slots: Vec<Vec<u64>>,
+    timers: HashMap<u64, WheelTimer<T>>,
+    tick: Duration,
+    /// Ticks completos desde a criação
+    now_ticks: u64,
+    /// Tempo acumulado que ainda não completou um tick
+    pending: Duration,
+    next_id: u64,
+    last_poll: Instant,
+}
+
+impl<T> TimerWheel<T> {
+    /// Cria com a resolução `tick` e `DEFAULT_WHEEL_SLOTS` slots
+    pub fn new(tick: Duration) -> Self {
+        Self::with_slots(tick, DEFAULT_WHEEL_SLOTS)
+    }
+
+    /// Cria com um número de slots específico; mais slots = menos timers
+    /// examinados por tick quando os atrasos são longos
+    pub fn with_slots(tick: Duration, slots: usize) -> Self {
+        assert!(!tick.is_zero(), "timer wheel tick must be non-zero");
+        Self {
+            slots: (0..slots.max(1)).map(|_| Vec::new()).collect(),
+            timers: HashMap::new(),
+            tick,
+            now_ticks: 0,
+            pending: Duration::ZERO,
+            next_id: 0,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Resolução da wheel
+    pub fn tick_duration(&self) -> Duration {
+        self.tick
+    }
+
+    /// Ticks necessários para cobrir `delay` a partir do tempo atual
+    fn ticks_for(&self, delay: Duration) -> u64 {
+        // O tempo parcial já acumulado conta para o primeiro tick
+        let total = delay + self.pending;
+        (total.as_nanos().div_ceil(self.tick.as_nanos()) as u64).max(1)
+    }
+
+    fn schedule(&mut self, delay: Duration, interval: Option<Duration>, payload: T) -> TimerId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let deadline = self.now_ticks + self.ticks_for(delay);
+        let interval =
+            interval.map(|i| (i.as_nanos().div_ceil(self.tick.as_nanos()) as u64).max(1));
+        let slot_count = self.slots.len() as u64;
+        self.slots[(deadline % slot_count) as usize].push(id);
+        self.timers.insert(
+            id,
+            WheelTimer {
+                deadline,
+                interval,
+                payload,
+            },
+        );
+        TimerId(id)
+    }
+
+    /// Agenda um timer que dispara uma vez após `delay`
+    pub fn insert(&mut self, delay: Duration, payload: T) -> TimerId {
+        self.schedule(delay, None, payload)
+    }
+
+    /// Agenda um timer que dispara a cada `interval`
+    pub fn insert_repeating(&mut self, interval: Duration, payload: T) -> TimerId {
+        self.schedule(interval, Some(interval), payload)
+    }
+
+    /// Cancela um timer, devolvendo o payload se ainda estava ativo
+    pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+        // O id fica no slot e é ignorado quando o cursor passar por lá
+        self.timers.remove(&id.0).map(|timer| timer.payload)
+    }
+
+    /// Se o timer ainda está ativo
+    pub fn contains(&self, id: TimerId) -> bool {
+        self.timers.contains_key(&id.0)
+    }
+
+    /// Tempo até o timer disparar
+    pub fn remaining(&self, id: TimerId) -> Option<Duration> {
+        self.timers.get(&id.0).map(|timer| {
+            let ticks = (timer.deadline - self.now_ticks) as u32;
+            (self.tick * ticks).saturating_sub(self.pending)
+        })
+    }
+
+    /// Payload de um timer ativo
+    pub fn get_mut(&mut self, id: TimerId) -> Option<&mut T> {
+        self.timers.get_mut(&id.0).map(|timer| &mut timer.payload)
+    }
+
+    /// Número de timers ativos
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Remove todos os timers
+    pub fn clear(&mut self) {
+        self.timers.clear();
+        for slot in &mut self.slots {
+            slot.clear();
+        }
+    }
+
+    /// Avança o tempo da wheel e chama `on_expire` para cada timer vencido
+    ///
+    /// Retorna quantos timers dispararam. Timers repetidos são reagendados
+    /// depois do callback.
+    pub fn advance<F>(&mut self, elapsed: Duration, mut on_expire: F) -> usize
+    where
+        F: FnMut(TimerId, &mut T),
+    {
+        self.pending += elapsed;
+        let mut fired = 0;
+        while self.pending >= self.tick {
+            self.pending -= self.tick;
+            self.now_ticks += 1;
+            if self.timers.is_empty() {
+                continue;
+            }
+            fired += self.expire_current(&mut on_expire);
+        }
+        fired
+    }
+
+    /// Como `advance`, usando o tempo real desde o último `poll`
+    pub fn poll<F>(&mut self, on_expire: F) -> usize
+    where
+        F: FnMut(TimerId, &mut T),
+    {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_poll);
+        self.last_poll = now;
+        self.advance(elapsed, on_expire)
+    }
+
+    fn expire_current<F>(&mut self, on_expire: &mut F) -> usize
+    where
+        F: FnMut(TimerId, &mut T),
+    {
+        let slot_count = self.slots.len() as u64;
+        let index = (self.now_ticks % slot_count) as usize;
+        let ids = std::mem::take(&mut self.slots[index]);
+        let mut fired = 0;
+
+        for id in ids {
+            let Some(timer) = self.timers.get_mut(&id) else {
+                continue; // cancelado
+            };
+            if timer.deadline > self.now_ticks {
+                // Volta futura da wheel
+                self.slots[index].push(id);
+                continue;
+            }
+
+            on_expire(TimerId(id), &mut timer.payload);
+            fired += 1;
+            match timer.interval {
+                Some(interval) => {
+                    timer.deadline = self.now_ticks + interval;
+                    self.slots[(timer.deadline % slot_count) as usize].push(id);
+                }
+                None => {
+                    self.timers.remove(&id);
+                }
+            }
+        }
+        fired
+    }
+}
+
+impl<T: Clone> TimerWheel<T> {
+    /// Avança e envia `(id, payload)` de cada timer vencido pelo canal
+    ///
+    /// Envios para um receiver já descartado são ignorados.
+    pub fn advance_to_channel(
+        &mut self,
+        elapsed: Duration,
+        sender: &Sender<(TimerId, T)>,
+    ) -> usize {
+        self.advance(elapsed, |id, payload| {
+            let _ = sender.send((id, payload.clone()));
+        })
+    }
+
+    /// Como `advance_to_channel`, usando o tempo real desde o último `poll`
+    pub fn poll_to_channel(&mut self, sender: &Sender<(TimerId, T)>) -> usize {
+        self.poll(|id, payload| {
+            let _ = sender.send((id, payload.clone()));
+        })
+    }
+}
+
+impl TimerWheel<TimerCallback> {
+    /// Agenda um callback único
+    pub fn schedule_once<F>(&mut self, delay: Duration, callback: F) -> TimerId
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.insert(delay, Box::new(callback))
+    }
+
+    /// Agenda um callback periódico
+    pub fn schedule_every<F>(&mut self, interval: Duration, callback: F) -> TimerId
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.insert_repeating(interval, Box::new(callback))
+    }
+
+    /// Avança e executa os callbacks vencidos
+    pub fn run(&mut self, elapsed: Duration) -> usize {
+        self.advance(elapsed, |_, callback| callback())
+    }
+
+    /// Executa os callbacks vencidos desde o último `poll`/`run_pending`
+    pub fn run_pending(&mut self) -> usize {
+        self.poll(|_, callback| callback())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_timer_wheel_expiration() {
+        let mut wheel = TimerWheel::with_slots(MS * 10, 8);
+        let a = wheel.insert(MS * 25, 'a');
+        let b = wheel.insert(MS * 10, 'b');
+        // Mais longo que uma volta da wheel (8 * 10ms)
+        let c = wheel.insert(MS * 200, 'c');
+        let d = wheel.insert(MS * 30, 'd');
+        assert_eq!(wheel.len(), 4);
+        assert_eq!(wheel.cancel(d), Some('d'));
+        assert_eq!(wheel.remaining(a), Some(MS * 30));
+
+        let mut fired = Vec::new();
+        assert_eq!(wheel.advance(MS * 15, |id, p| fired.push((id, *p))), 1);
+        assert_eq!(fired, [(b, 'b')]);
+
+        // Nunca antes do atraso: 'a' (25ms) só no tick de 30ms
+        wheel.advance(MS * 10, |id, p| fired.push((id, *p)));
+        assert_eq!(fired.len(), 1);
+        wheel.advance(MS * 5, |id, p| fired.push((id, *p)));
+        assert_eq!(fired, [(b, 'b'), (a, 'a')]);
+
+        wheel.advance(MS * 160, |id, p| fired.push((id, *p)));
+        assert_eq!(fired.len(), 2);
+        assert!(wheel.contains(c));
+        wheel.advance(MS * 10, |id, p| fired.push((id, *p)));
+        assert_eq!(fired.last(), Some(&(c, 'c')));
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_timer_wheel_repeating_and_channel() {
+        let mut wheel = TimerWheel::new(MS);
+        let id = wheel.insert_repeating(MS * 4, "retransmit");
+        let (tx, rx) = mpsc::channel();
+
+        assert_eq!(wheel.advance_to_channel(MS * 10, &tx), 2);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [(id, "retransmit"); 2]);
+        assert_eq!(wheel.advance_to_channel(MS * 2, &tx), 1);
+        assert_eq!(wheel.cancel(id), Some("retransmit"));
+        assert_eq!(wheel.advance_to_channel(MS * 100, &tx), 0);
+    }
+
+    #[test]
+    fn test_timer_wheel_callbacks() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut wheel: TimerWheel<TimerCallback> = TimerWheel::new(MS);
+        for i in 1..=1000u32 {
+            let count = Arc::clone(&count);
+            wheel.schedule_once(MS * i, move || {
+                count.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert_eq!(wheel.run(MS * 500), 500);
+        assert_eq!(wheel.run(MS * 500), 500);
+        assert_eq!(count.load(Ordering::Relaxed), 1000);
+    }
+}
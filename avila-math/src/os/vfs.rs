@@ -0,0 +1,273 @@
+//! Sistema de arquivos virtual
+//!
+//! Um `Vfs` combina diretórios do disco e `PackFile`s sob caminhos virtuais.
+//! As leituras procuram do último ponto de montagem para o primeiro, então
+//! montar um diretório depois de um pacote permite sobrescrever arquivos
+//! (mods, patches, desenvolvimento sem reempacotar).
+//!
+//! ```no_run
+//! use avila_math::os::Vfs;
+//!
+//! let mut vfs = Vfs::new();
+//! vfs.mount_pack("", "assets.pak").unwrap();
+//! vfs.mount_dir("", "assets");
+//! let shader = vfs.read_to_string("shaders/lit.wgsl").unwrap();
+//! ```
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::filesystem::{walk, FileSystem};
+use super::pack::{normalize_name, PackFile};
+use super::threading::TaskFuture;
+
+#[derive(Debug, Clone)]
+enum Source {
+    Dir(PathBuf),
+    Pack(Arc<PackFile>),
+}
+
+#[derive(Debug, Clone)]
+struct Mount {
+    /// Prefixo virtual normalizado ("" para a raiz)
+    prefix: String,
+    source: Source,
+}
+
+impl Mount {
+    /// Caminho relativo ao ponto de montagem, se `path` estiver dentro dele
+    fn relative<'a>(&self, path: &'a str) -> Option<&'a str> {
+        if self.prefix.is_empty() {
+            return Some(path);
+        }
+        let rest = path.strip_prefix(self.prefix.as_str())?;
+        if rest.is_empty() {
+            Some(rest)
+        } else {
+            rest.strip_prefix('/')
+        }
+    }
+
+    fn virtual_path(&self, relative: &str) -> String {
+        if self.prefix.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", self.prefix, relative)
+        }
+    }
+}
+
+/// Sistema de arquivos virtual sobre diretórios e pacotes
+///
+/// Clonar um `Vfs` é barato: os pacotes são compartilhados.
+#[derive(Debug, Clone, Default)]
+pub struct Vfs {
+    mounts: Vec<Mount>,
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found in vfs", path),
+    )
+}
+
+impl Vfs {
+    /// Cria um VFS vazio
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Monta um diretório do disco sob `prefix`
+    pub fn mount_dir<P: AsRef<Path>>(&mut self, prefix: &str, dir: P) -> io::Result<()> {
+        self.mounts.push(Mount {
+            prefix: normalize_name(prefix)?,
+            source: Source::Dir(dir.as_ref().to_path_buf()),
+        });
+        Ok(())
+    }
+
+    /// Abre e monta um pacote sob `prefix`
+    pub fn mount_pack<P: AsRef<Path>>(&mut self, prefix: &str, path: P) -> io::Result<()> {
+        let pack = PackFile::open(path)?;
+        self.mount_pack_file(prefix, Arc::new(pack))
+    }
+
+    /// Monta um pacote já aberto sob `prefix`
+    pub fn mount_pack_file(&mut self, prefix: &str, pack: Arc<PackFile>) -> io::Result<()> {
+        self.mounts.push(Mount {
+            prefix: normalize_name(prefix)?,
+            source: Source::Pack(pack),
+        });
+        Ok(())
+    }
+
+    /// Remove todos os pontos de montagem
+    pub fn unmount_all(&mut self) {
+        self.mounts.clear();
+    }
+
+    /// Número de pontos de montagem
+    pub fn mount_count(&self) -> usize {
+        self.mounts.len()
+    }
+
+    /// Montagens que podem conter `path`, da mais recente para a mais antiga
+    fn candidates<'a>(&'a self, path: &'a str) -> impl Iterator<Item = (&'a Source, &'a str)> {
+        self.mounts
+            .iter()
+            .rev()
+            .filter_map(move |mount| Some((&mount.source, mount.relative(path)?)))
+    }
+
+    /// Verifica se um arquivo existe em alguma montagem
+    pub fn exists(&self, path: &str) -> bool {
+        let Ok(path) = normalize_name(path) else {
+            return false;
+        };
+        let found = self
+            .candidates(&path)
+            .any(|(source, relative)| match source {
+                Source::Dir(dir) => dir.join(relative).is_file(),
+                Source::Pack(pack) => pack.contains(relative),
+            });
+        found
+    }
+
+    /// Caminho no disco de um arquivo solto, se for de uma montagem de diretório
+    pub fn real_path(&self, path: &str) -> Option<PathBuf> {
+        let path = normalize_name(path).ok()?;
+        for (source, relative) in self.candidates(&path) {
+            match source {
+                Source::Dir(dir) => {
+                    let real = dir.join(relative);
+                    if real.is_file() {
+                        return Some(real);
+                    }
+                }
+                Source::Pack(pack) => {
+                    if pack.contains(relative) {
+                        return None;
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Lê um arquivo da montagem mais recente que o contém
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let path = normalize_name(path)?;
+        for (source, relative) in self.candidates(&path) {
+            match source {
+                Source::Dir(dir) => match FileSystem::read(dir.join(relative)) {
+                    Ok(data) => return Ok(data),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err),
+                },
+                Source::Pack(pack) => {
+                    if pack.contains(relative) {
+                        return pack.read(relative).map(|data| data.into_owned());
+                    }
+                }
+            }
+        }
+        Err(not_found(&path))
+    }
+
+    /// Lê um arquivo como texto UTF-8
+    pub fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// Lê um arquivo no pool de IO de `FileSystem`
+    pub fn read_async(&self, path: &str) -> TaskFuture<io::Result<Vec<u8>>> {
+        let vfs = self.clone();
+        let path = path.to_string();
+        FileSystem::io_pool().submit(move || vfs.read(&path))
+    }
+
+    /// Todos os arquivos visíveis, em ordem alfabética e sem duplicatas
+    pub fn files(&self) -> io::Result<Vec<String>> {
+        let mut files = BTreeSet::new();
+        for mount in &self.mounts {
+            match &mount.source {
+                Source::Dir(dir) => {
+                    if !dir.is_dir() {
+                        continue;
+                    }
+                    for entry in walk(dir).files_only() {
+                        let entry = entry?;
+                        let Ok(relative) = entry.path().strip_prefix(dir) else {
+                            continue;
+                        };
+                        if let Some(relative) = relative.to_str() {
+                            files.insert(mount.virtual_path(&relative.replace('\\', "/")));
+                        }
+                    }
+                }
+                Source::Pack(pack) => {
+                    files.extend(pack.names().map(|name| mount.virtual_path(name)));
+                }
+            }
+        }
+        Ok(files.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os::PackBuilder;
+
+    #[test]
+    fn test_vfs_overlay() {
+        let dir = std::env::temp_dir().join(format!("avila_vfs_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("config.txt"), "loose").unwrap();
+
+        let mut builder = PackBuilder::new(Vec::new()).unwrap();
+        builder.add("config.txt", b"packed").unwrap();
+        builder.add("data/level1.bin", &[7; 32]).unwrap();
+        let pack = Arc::new(PackFile::from_bytes(builder.finish().unwrap()).unwrap());
+
+        let mut vfs = Vfs::new();
+        vfs.mount_pack_file("game", pack).unwrap();
+        assert_eq!(vfs.read_to_string("game/config.txt").unwrap(), "packed");
+        assert!(vfs.real_path("game/config.txt").is_none());
+
+        // Diretório montado depois tem prioridade
+        vfs.mount_dir("game/", &dir).unwrap();
+        assert_eq!(vfs.read_to_string("game/config.txt").unwrap(), "loose");
+        assert_eq!(
+            vfs.real_path("/game/./config.txt"),
+            Some(dir.join("config.txt"))
+        );
+        assert_eq!(vfs.read("game/data/level1.bin").unwrap(), [7; 32]);
+        assert_eq!(
+            vfs.read_async("game/data/level1.bin")
+                .wait()
+                .unwrap()
+                .unwrap()
+                .len(),
+            32
+        );
+
+        assert!(vfs.exists("game/data/level1.bin"));
+        assert!(!vfs.exists("config.txt"));
+        assert!(!vfs.exists("gamedata/level1.bin"));
+        assert_eq!(
+            vfs.read("game/missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            vfs.files().unwrap(),
+            ["game/config.txt", "game/data/level1.bin"]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
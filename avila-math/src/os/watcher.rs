@@ -0,0 +1,1099 @@
+//! Observação de mudanças no filesystem
+//!
+//! O `FileWatcher` usa a API nativa de cada sistema (inotify no Linux,
+//! FSEvents no macOS, `ReadDirectoryChangesW` no Windows) e entrega os eventos
+//! por um canal, sem precisar varrer a árvore a cada frame. Em outros sistemas,
+//! ou com `FileWatcher::polling`, a árvore é varrida periodicamente.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Mudança observada por um `FileWatcher`
+///
+/// Os caminhos são absolutos (a raiz observada é canonicalizada).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileEvent {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Deleted(PathBuf),
+    /// Arquivo movido dentro da árvore observada. Movimentos para fora chegam
+    /// como `Deleted` e para dentro como `Created`.
+    Renamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+impl FileEvent {
+    /// Caminho afetado (o destino, para `Renamed`)
+    pub fn path(&self) -> &Path {
+        match self {
+            FileEvent::Created(path) | FileEvent::Modified(path) | FileEvent::Deleted(path) => path,
+            FileEvent::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Observa um arquivo ou uma árvore de diretórios
+///
+/// ```no_run
+/// use avila_math::os::{FileEvent, FileWatcher};
+///
+/// let watcher = FileWatcher::new("assets/shaders").unwrap();
+///
+/// // A cada frame
+/// for event in watcher.poll_events() {
+///     if let FileEvent::Modified(path) = event {
+///         println!("recompilando {}", path.display());
+///     }
+/// }
+/// ```
+pub struct FileWatcher {
+    path: PathBuf,
+    events: Receiver<FileEvent>,
+    /// Para a observação no drop
+    _backend: Box<dyn Send>,
+}
+
+impl FileWatcher {
+    /// Observa um arquivo ou, recursivamente, um diretório
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::watch(path, true)
+    }
+
+    /// Observa um arquivo ou diretório com o backend nativo
+    ///
+    /// Com `recursive = false` só as entradas diretas do diretório são observadas.
+    pub fn watch<P: AsRef<Path>>(path: P, recursive: bool) -> io::Result<Self> {
+        let (path, target, sink, events) = Self::prepare(path.as_ref(), recursive)?;
+        let backend = native::start(&target.root, target.recursive, sink)?;
+        Ok(Self {
+            path,
+            events,
+            _backend: backend,
+        })
+    }
+
+    /// Observa varrendo a árvore a cada `interval`
+    ///
+    /// Útil em filesystems de rede, onde as APIs nativas não recebem eventos.
+    /// Renomeações chegam como `Deleted` + `Created`.
+    pub fn polling<P: AsRef<Path>>(
+        path: P,
+        recursive: bool,
+        interval: Duration,
+    ) -> io::Result<Self> {
+        let (path, target, sink, events) = Self::prepare(path.as_ref(), recursive)?;
+        let backend = polling::start(&target.root, target.recursive, interval, sink)?;
+        Ok(Self {
+            path,
+            events,
+            _backend: Box::new(backend),
+        })
+    }
+
+    fn prepare(
+        path: &Path,
+        recursive: bool,
+    ) -> io::Result<(PathBuf, WatchTarget, EventSink, Receiver<FileEvent>)> {
+        let path = fs::canonicalize(path)?;
+        // Editores costumam salvar escrevendo um arquivo novo e renomeando por
+        // cima, então um arquivo é observado pelo diretório pai
+        let (target, filter) = if path.is_dir() {
+            let target = WatchTarget {
+                root: path.clone(),
+                recursive,
+            };
+            (target, None)
+        } else {
+            let parent = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+            let target = WatchTarget {
+                root: parent,
+                recursive: false,
+            };
+            (target, Some(path.clone()))
+        };
+
+        let (tx, events) = mpsc::channel();
+        Ok((path, target, EventSink { tx, filter }, events))
+    }
+
+    /// Caminho observado (canonicalizado)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Retorna o próximo evento, se houver, sem bloquear
+    pub fn try_recv(&self) -> Option<FileEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Espera um evento por no máximo `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<FileEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Retira todos os eventos pendentes
+    pub fn poll_events(&self) -> Vec<FileEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// Canal de eventos, para iterar ou bloquear diretamente
+    pub fn events(&self) -> &Receiver<FileEvent> {
+        &self.events
+    }
+
+    /// Descarta os eventos pendentes e retorna se havia algum
+    pub fn has_changed(&mut self) -> io::Result<bool> {
+        Ok(!self.poll_events().is_empty())
+    }
+}
+
+impl std::fmt::Debug for FileWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileWatcher")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+struct WatchTarget {
+    root: PathBuf,
+    recursive: bool,
+}
+
+/// Destino dos eventos de um backend, com o filtro de arquivo único
+#[derive(Clone)]
+struct EventSink {
+    tx: Sender<FileEvent>,
+    filter: Option<PathBuf>,
+}
+
+impl EventSink {
+    /// Retorna `false` se o `FileWatcher` já foi descartado
+    fn emit(&self, event: FileEvent) -> bool {
+        if let Some(filter) = &self.filter {
+            let matches = match &event {
+                FileEvent::Renamed { from, to } => from == filter || to == filter,
+                other => other.path() == filter,
+            };
+            if !matches {
+                return true;
+            }
+        }
+        self.tx.send(event).is_ok()
+    }
+}
+
+/// Thread de observação que é parada e aguardada no drop
+struct WatchThread {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatchThread {
+    fn spawn<F>(f: F) -> io::Result<Self>
+    where
+        F: FnOnce(&AtomicBool) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name("avila-file-watcher".to_string())
+            .spawn(move || f(&thread_stop))?;
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for WatchThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Intervalo em que as threads de observação verificam se devem parar
+const STOP_CHECK: Duration = Duration::from_millis(50);
+
+#[cfg(target_os = "macos")]
+use self::fsevents as native;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use self::inotify as native;
+#[cfg(windows)]
+use self::windows as native;
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+mod native {
+    use super::*;
+
+    pub(super) fn start(
+        root: &Path,
+        recursive: bool,
+        sink: EventSink,
+    ) -> io::Result<Box<dyn Send>> {
+        let backend = polling::start(root, recursive, Duration::from_millis(500), sink)?;
+        Ok(Box::new(backend))
+    }
+}
+
+mod polling {
+    use super::*;
+
+    #[derive(PartialEq)]
+    struct Entry {
+        modified: Option<SystemTime>,
+        len: u64,
+        is_dir: bool,
+    }
+
+    fn scan(root: &Path, recursive: bool, entries: &mut HashMap<PathBuf, Entry>) {
+        let Ok(dir) = fs::read_dir(root) else {
+            return;
+        };
+        for entry in dir.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if recursive && meta.is_dir() {
+                scan(&path, recursive, entries);
+            }
+            entries.insert(
+                path,
+                Entry {
+                    modified: meta.modified().ok(),
+                    len: meta.len(),
+                    is_dir: meta.is_dir(),
+                },
+            );
+        }
+    }
+
+    pub(super) fn start(
+        root: &Path,
+        recursive: bool,
+        interval: Duration,
+        sink: EventSink,
+    ) -> io::Result<WatchThread> {
+        let root = root.to_path_buf();
+        let mut previous = HashMap::new();
+        scan(&root, recursive, &mut previous);
+
+        WatchThread::spawn(move |stop| {
+            let mut waited = Duration::ZERO;
+            while !stop.load(Ordering::Acquire) {
+                thread::sleep(STOP_CHECK.min(interval));
+                waited += STOP_CHECK.min(interval);
+                if waited < interval {
+                    continue;
+                }
+                waited = Duration::ZERO;
+
+                let mut current = HashMap::new();
+                scan(&root, recursive, &mut current);
+
+                let mut events = Vec::new();
+                for (path, entry) in &current {
+                    match previous.get(path) {
+                        None => events.push(FileEvent::Created(path.clone())),
+                        Some(old) if !entry.is_dir && old != entry => {
+                            events.push(FileEvent::Modified(path.clone()))
+                        }
+                        Some(_) => {}
+                    }
+                }
+                for path in previous.keys() {
+                    if !current.contains_key(path) {
+                        events.push(FileEvent::Deleted(path.clone()));
+                    }
+                }
+                previous = current;
+
+                // Pais antes dos filhos, como nos backends nativos
+                events.sort_by(|a, b| a.path().cmp(b.path()));
+                for event in events {
+                    if !sink.emit(event) {
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod inotify {
+    use super::*;
+    use std::ffi::{CString, OsStr};
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+
+    const WATCH_MASK: u32 = libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MODIFY
+        | libc::IN_ATTRIB
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_EXCL_UNLINK;
+
+    struct Inotify {
+        fd: i32,
+        recursive: bool,
+        watches: HashMap<i32, PathBuf>,
+        /// `IN_MOVED_FROM` aguardando o `IN_MOVED_TO` com o mesmo cookie
+        pending_move: Option<(u32, PathBuf)>,
+        sink: EventSink,
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.fd) };
+        }
+    }
+
+    pub(super) fn start(
+        root: &Path,
+        recursive: bool,
+        sink: EventSink,
+    ) -> io::Result<Box<dyn Send>> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut inotify = Inotify {
+            fd,
+            recursive,
+            watches: HashMap::new(),
+            pending_move: None,
+            sink,
+        };
+        // A raiz é registrada antes de retornar para não perder eventos iniciais
+        inotify.add_watch(root)?;
+        if recursive {
+            inotify.add_children(root, false);
+        }
+
+        let thread = WatchThread::spawn(move |stop| inotify.run(stop))?;
+        Ok(Box::new(thread))
+    }
+
+    impl Inotify {
+        fn add_watch(&mut self, dir: &Path) -> io::Result<()> {
+            let path = CString::new(dir.as_os_str().as_bytes())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            let wd = unsafe { libc::inotify_add_watch(self.fd, path.as_ptr(), WATCH_MASK) };
+            if wd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            self.watches.insert(wd, dir.to_path_buf());
+            Ok(())
+        }
+
+        /// Registra os subdiretórios de `dir`; com `announce`, emite `Created`
+        /// para o que já existir (criado antes do watch ser registrado)
+        fn add_children(&mut self, dir: &Path, announce: bool) -> bool {
+            let Ok(entries) = fs::read_dir(dir) else {
+                return true;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+                if announce && !self.sink.emit(FileEvent::Created(path.clone())) {
+                    return false;
+                }
+                // Diretórios sem permissão são ignorados
+                if is_dir && self.add_watch(&path).is_ok() && !self.add_children(&path, announce) {
+                    return false;
+                }
+            }
+            true
+        }
+
+        /// Remove os watches de um diretório que saiu da árvore
+        fn remove_tree(&mut self, dir: &Path) {
+            let fd = self.fd;
+            self.watches.retain(|wd, path| {
+                let inside = path.starts_with(dir);
+                if inside {
+                    unsafe { libc::inotify_rm_watch(fd, *wd) };
+                }
+                !inside
+            });
+        }
+
+        /// Atualiza os caminhos dos watches de um diretório renomeado
+        fn rename_tree(&mut self, from: &Path, to: &Path) {
+            for path in self.watches.values_mut() {
+                if let Ok(rest) = path.strip_prefix(from) {
+                    *path = to.join(rest);
+                }
+            }
+        }
+
+        fn flush_pending_move(&mut self) -> bool {
+            match self.pending_move.take() {
+                Some((_, from)) => {
+                    self.remove_tree(&from);
+                    self.sink.emit(FileEvent::Deleted(from))
+                }
+                None => true,
+            }
+        }
+
+        fn run(mut self, stop: &AtomicBool) {
+            let mut buffer = [0u8; 8192];
+            let header = mem::size_of::<libc::inotify_event>();
+
+            while !stop.load(Ordering::Acquire) {
+                let mut pollfd = libc::pollfd {
+                    fd: self.fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let ready = unsafe { libc::poll(&mut pollfd, 1, STOP_CHECK.as_millis() as i32) };
+                if ready <= 0 {
+                    continue;
+                }
+
+                let read = unsafe { libc::read(self.fd, buffer.as_mut_ptr().cast(), buffer.len()) };
+                if read < 0 {
+                    match io::Error::last_os_error().kind() {
+                        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => continue,
+                        _ => return,
+                    }
+                }
+
+                let mut offset = 0;
+                while offset + header <= read as usize {
+                    let event: libc::inotify_event =
+                        unsafe { std::ptr::read_unaligned(buffer.as_ptr().add(offset).cast()) };
+                    let name = &buffer[offset + header..offset + header + event.len as usize];
+                    offset += header + event.len as usize;
+
+                    let name_len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                    if !self.handle(event, OsStr::from_bytes(&name[..name_len])) {
+                        return;
+                    }
+                }
+
+                // Movido para fora da árvore: o par nunca vai chegar
+                if !self.flush_pending_move() {
+                    return;
+                }
+            }
+        }
+
+        /// Retorna `false` se o `FileWatcher` já foi descartado
+        fn handle(&mut self, event: libc::inotify_event, name: &OsStr) -> bool {
+            if event.mask & libc::IN_IGNORED != 0 {
+                self.watches.remove(&event.wd);
+                return true;
+            }
+            let Some(dir) = self.watches.get(&event.wd) else {
+                return true;
+            };
+            if name.is_empty() {
+                return true;
+            }
+            let path = dir.join(name);
+            let is_dir = event.mask & libc::IN_ISDIR != 0;
+
+            if event.mask & libc::IN_MOVED_FROM != 0 {
+                if !self.flush_pending_move() {
+                    return false;
+                }
+                self.pending_move = Some((event.cookie, path));
+                return true;
+            }
+
+            if event.mask & libc::IN_MOVED_TO != 0 {
+                match self.pending_move.take() {
+                    Some((cookie, from)) if cookie == event.cookie => {
+                        if is_dir {
+                            self.rename_tree(&from, &path);
+                        }
+                        return self.sink.emit(FileEvent::Renamed { from, to: path });
+                    }
+                    pending => {
+                        self.pending_move = pending;
+                        if !self.flush_pending_move() {
+                            return false;
+                        }
+                    }
+                }
+                return self.created(path, is_dir);
+            }
+
+            if event.mask & libc::IN_CREATE != 0 {
+                return self.created(path, is_dir);
+            }
+            if event.mask & libc::IN_DELETE != 0 {
+                return self.sink.emit(FileEvent::Deleted(path));
+            }
+            if event.mask & (libc::IN_MODIFY | libc::IN_ATTRIB) != 0 && !is_dir {
+                return self.sink.emit(FileEvent::Modified(path));
+            }
+            true
+        }
+
+        fn created(&mut self, path: PathBuf, is_dir: bool) -> bool {
+            if !self.sink.emit(FileEvent::Created(path.clone())) {
+                return false;
+            }
+            if is_dir && self.recursive && self.add_watch(&path).is_ok() {
+                return self.add_children(&path, true);
+            }
+            true
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod fsevents {
+    use super::*;
+    use std::ffi::{c_char, c_void, CStr, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::ptr;
+    use std::sync::Mutex;
+
+    type CFRef = *const c_void;
+    type FSEventStreamRef = *mut c_void;
+    type DispatchQueue = *mut c_void;
+
+    type FSEventStreamCallback = extern "C" fn(
+        stream: FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const u32,
+        ids: *const u64,
+    );
+
+    #[repr(C)]
+    struct FSEventStreamContext {
+        version: isize,
+        info: *mut c_void,
+        retain: Option<extern "C" fn(*const c_void) -> *const c_void>,
+        release: Option<extern "C" fn(*const c_void)>,
+        copy_description: Option<extern "C" fn(*const c_void) -> CFRef>,
+    }
+
+    #[repr(C)]
+    struct CFArrayCallBacks {
+        _private: [u8; 0],
+    }
+
+    const UTF8: u32 = 0x0800_0100;
+    const SINCE_NOW: u64 = u64::MAX;
+    const FLAG_NO_DEFER: u32 = 0x02;
+    const FLAG_FILE_EVENTS: u32 = 0x10;
+
+    const ITEM_CREATED: u32 = 0x100;
+    const ITEM_REMOVED: u32 = 0x200;
+    const ITEM_INODE_META_MOD: u32 = 0x400;
+    const ITEM_RENAMED: u32 = 0x800;
+    const ITEM_MODIFIED: u32 = 0x1000;
+    const ITEM_IS_DIR: u32 = 0x20000;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFTypeArrayCallBacks: CFArrayCallBacks;
+        fn CFStringCreateWithBytes(
+            alloc: CFRef,
+            bytes: *const u8,
+            len: isize,
+            encoding: u32,
+            external: u8,
+        ) -> CFRef;
+        fn CFArrayCreate(
+            alloc: CFRef,
+            values: *const CFRef,
+            len: isize,
+            callbacks: *const CFArrayCallBacks,
+        ) -> CFRef;
+        fn CFRelease(cf: CFRef);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn FSEventStreamCreate(
+            alloc: CFRef,
+            callback: FSEventStreamCallback,
+            context: *const FSEventStreamContext,
+            paths: CFRef,
+            since_when: u64,
+            latency: f64,
+            flags: u32,
+        ) -> FSEventStreamRef;
+        fn FSEventStreamSetDispatchQueue(stream: FSEventStreamRef, queue: DispatchQueue);
+        fn FSEventStreamStart(stream: FSEventStreamRef) -> u8;
+        fn FSEventStreamStop(stream: FSEventStreamRef);
+        fn FSEventStreamInvalidate(stream: FSEventStreamRef);
+        fn FSEventStreamRelease(stream: FSEventStreamRef);
+    }
+
+    extern "C" {
+        fn dispatch_queue_create(label: *const c_char, attr: *const c_void) -> DispatchQueue;
+        fn dispatch_release(object: *mut c_void);
+    }
+
+    /// Estado acessado pelo callback, na fila serial do stream
+    struct CallbackState {
+        root: PathBuf,
+        recursive: bool,
+        sink: EventSink,
+        pending_rename: Mutex<Option<PathBuf>>,
+    }
+
+    extern "C" fn retain_state(info: *const c_void) -> *const c_void {
+        unsafe { Arc::increment_strong_count(info as *const CallbackState) };
+        info
+    }
+
+    extern "C" fn release_state(info: *const c_void) {
+        unsafe { Arc::decrement_strong_count(info as *const CallbackState) };
+    }
+
+    extern "C" fn callback(
+        _stream: FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const u32,
+        _ids: *const u64,
+    ) {
+        let state = unsafe { &*(info as *const CallbackState) };
+        let paths = unsafe { std::slice::from_raw_parts(paths as *const *const c_char, count) };
+        let flags = unsafe { std::slice::from_raw_parts(flags, count) };
+        let mut pending = state.pending_rename.lock().unwrap();
+
+        for (&path, &flags) in paths.iter().zip(flags) {
+            let bytes = unsafe { CStr::from_ptr(path) }.to_bytes();
+            let path = PathBuf::from(OsStr::from_bytes(bytes));
+            if path == state.root {
+                continue;
+            }
+            if !state.recursive && path.parent() != Some(state.root.as_path()) {
+                continue;
+            }
+
+            let exists = path.symlink_metadata().is_ok();
+            let event = if flags & ITEM_RENAMED != 0 {
+                match (pending.take(), exists) {
+                    (Some(from), true) => FileEvent::Renamed { from, to: path },
+                    (Some(from), false) => {
+                        state.sink.emit(FileEvent::Deleted(from));
+                        *pending = Some(path);
+                        continue;
+                    }
+                    (None, true) => FileEvent::Created(path),
+                    (None, false) => {
+                        *pending = Some(path);
+                        continue;
+                    }
+                }
+            } else if flags & ITEM_REMOVED != 0 && !exists {
+                FileEvent::Deleted(path)
+            } else if flags & ITEM_CREATED != 0 && exists {
+                FileEvent::Created(path)
+            } else if flags & (ITEM_MODIFIED | ITEM_INODE_META_MOD) != 0 && flags & ITEM_IS_DIR == 0
+            {
+                FileEvent::Modified(path)
+            } else {
+                continue;
+            };
+            state.sink.emit(event);
+        }
+
+        // Renomeado para fora da árvore
+        if let Some(from) = pending.take() {
+            state.sink.emit(FileEvent::Deleted(from));
+        }
+    }
+
+    struct EventStream {
+        stream: FSEventStreamRef,
+        queue: DispatchQueue,
+    }
+
+    // O stream e a fila são objetos thread-safe do sistema
+    unsafe impl Send for EventStream {}
+
+    impl Drop for EventStream {
+        fn drop(&mut self) {
+            unsafe {
+                FSEventStreamStop(self.stream);
+                FSEventStreamInvalidate(self.stream);
+                FSEventStreamRelease(self.stream);
+                dispatch_release(self.queue);
+            }
+        }
+    }
+
+    pub(super) fn start(
+        root: &Path,
+        recursive: bool,
+        sink: EventSink,
+    ) -> io::Result<Box<dyn Send>> {
+        let state = Arc::new(CallbackState {
+            root: root.to_path_buf(),
+            recursive,
+            sink,
+            pending_rename: Mutex::new(None),
+        });
+        // O stream segura a própria referência via retain/release
+        let context = FSEventStreamContext {
+            version: 0,
+            info: Arc::as_ptr(&state) as *mut c_void,
+            retain: Some(retain_state),
+            release: Some(release_state),
+            copy_description: None,
+        };
+
+        let bytes = root.as_os_str().as_bytes();
+        unsafe {
+            let path =
+                CFStringCreateWithBytes(ptr::null(), bytes.as_ptr(), bytes.len() as isize, UTF8, 0);
+            let paths = CFArrayCreate(ptr::null(), &path, 1, &kCFTypeArrayCallBacks);
+            let stream = FSEventStreamCreate(
+                ptr::null(),
+                callback,
+                &context,
+                paths,
+                SINCE_NOW,
+                0.05,
+                FLAG_FILE_EVENTS | FLAG_NO_DEFER,
+            );
+            CFRelease(paths);
+            CFRelease(path);
+            if stream.is_null() {
+                return Err(io::Error::other("FSEventStreamCreate failed"));
+            }
+
+            let queue = dispatch_queue_create(c"avila-file-watcher".as_ptr(), ptr::null());
+            let stream = EventStream { stream, queue };
+            FSEventStreamSetDispatchQueue(stream.stream, stream.queue);
+            if FSEventStreamStart(stream.stream) == 0 {
+                return Err(io::Error::other("FSEventStreamStart failed"));
+            }
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use std::ffi::OsString;
+    use std::mem;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, HANDLE, INVALID_HANDLE_VALUE, WAIT_OBJECT_0,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, ReadDirectoryChangesW, FILE_ACTION_ADDED, FILE_ACTION_MODIFIED,
+        FILE_ACTION_REMOVED, FILE_ACTION_RENAMED_NEW_NAME, FILE_ACTION_RENAMED_OLD_NAME,
+        FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OVERLAPPED, FILE_LIST_DIRECTORY,
+        FILE_NOTIFY_CHANGE_DIR_NAME, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+        FILE_NOTIFY_CHANGE_SIZE, FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE, FILE_SHARE_READ,
+        FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+    use windows_sys::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
+
+    const NOTIFY_FILTER: u32 = FILE_NOTIFY_CHANGE_FILE_NAME
+        | FILE_NOTIFY_CHANGE_DIR_NAME
+        | FILE_NOTIFY_CHANGE_LAST_WRITE
+        | FILE_NOTIFY_CHANGE_SIZE;
+
+    struct Handle(HANDLE);
+
+    // Handles do kernel podem ser usados de qualquer thread
+    unsafe impl Send for Handle {}
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    struct DirWatch {
+        dir: Handle,
+        event: Handle,
+        /// Em heap: o kernel escreve nele enquanto a leitura está pendente
+        overlapped: Box<OVERLAPPED>,
+        buffer: Vec<u32>,
+        root: PathBuf,
+        recursive: bool,
+        pending_rename: Option<PathBuf>,
+        sink: EventSink,
+    }
+
+    // Só a thread de observação acessa o OVERLAPPED
+    unsafe impl Send for DirWatch {}
+
+    pub(super) fn start(
+        root: &Path,
+        recursive: bool,
+        sink: EventSink,
+    ) -> io::Result<Box<dyn Send>> {
+        let wide: Vec<u16> = root.as_os_str().encode_wide().chain(Some(0)).collect();
+        let dir = unsafe {
+            CreateFileW(
+                wide.as_ptr(),
+                FILE_LIST_DIRECTORY,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                ptr::null(),
+                OPEN_EXISTING,
+                FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
+                ptr::null_mut(),
+            )
+        };
+        if dir == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let dir = Handle(dir);
+
+        let event = unsafe { CreateEventW(ptr::null(), 1, 0, ptr::null()) };
+        if event.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut watch = DirWatch {
+            dir,
+            event: Handle(event),
+            overlapped: Box::new(unsafe { mem::zeroed() }),
+            buffer: vec![0u32; 16 * 1024],
+            root: root.to_path_buf(),
+            recursive,
+            pending_rename: None,
+            sink,
+        };
+        // A primeira leitura é registrada antes de retornar para não perder eventos
+        watch.issue()?;
+
+        let thread = WatchThread::spawn(move |stop| watch.run(stop))?;
+        Ok(Box::new(thread))
+    }
+
+    impl DirWatch {
+        fn issue(&mut self) -> io::Result<()> {
+            *self.overlapped = unsafe { mem::zeroed() };
+            self.overlapped.hEvent = self.event.0;
+            let ok = unsafe {
+                ReadDirectoryChangesW(
+                    self.dir.0,
+                    self.buffer.as_mut_ptr().cast(),
+                    (self.buffer.len() * mem::size_of::<u32>()) as u32,
+                    self.recursive as i32,
+                    NOTIFY_FILTER,
+                    ptr::null_mut(),
+                    &mut *self.overlapped,
+                    None,
+                )
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        fn run(mut self, stop: &AtomicBool) {
+            loop {
+                if stop.load(Ordering::Acquire) {
+                    // O buffer só pode ser liberado depois que o kernel soltar a leitura
+                    let mut transferred = 0;
+                    unsafe {
+                        CancelIoEx(self.dir.0, &*self.overlapped);
+                        GetOverlappedResult(self.dir.0, &*self.overlapped, &mut transferred, 1);
+                    }
+                    return;
+                }
+
+                let wait =
+                    unsafe { WaitForSingleObject(self.event.0, STOP_CHECK.as_millis() as u32) };
+                if wait != WAIT_OBJECT_0 {
+                    continue;
+                }
+
+                let mut transferred = 0;
+                let ok = unsafe {
+                    GetOverlappedResult(self.dir.0, &*self.overlapped, &mut transferred, 0)
+                };
+                // Diretório removido ou handle inválido
+                if ok == 0 {
+                    return;
+                }
+                // Zero bytes indica que o buffer transbordou e eventos foram perdidos
+                if transferred > 0 && !self.parse(transferred as usize) {
+                    return;
+                }
+                if self.issue().is_err() {
+                    return;
+                }
+            }
+        }
+
+        /// Retorna `false` se o `FileWatcher` já foi descartado
+        fn parse(&mut self, len: usize) -> bool {
+            let base = self.buffer.as_ptr() as *const u8;
+            let mut offset = 0;
+            loop {
+                let (next, action, name) = unsafe {
+                    let info = base.add(offset) as *const FILE_NOTIFY_INFORMATION;
+                    let name_ptr = ptr::addr_of!((*info).FileName) as *const u16;
+                    let name_len = (*info).FileNameLength as usize / 2;
+                    let name = std::slice::from_raw_parts(name_ptr, name_len);
+                    (
+                        (*info).NextEntryOffset as usize,
+                        (*info).Action,
+                        OsString::from_wide(name),
+                    )
+                };
+                let path = self.root.join(name);
+
+                let event = match action {
+                    FILE_ACTION_ADDED => Some(FileEvent::Created(path)),
+                    FILE_ACTION_REMOVED => Some(FileEvent::Deleted(path)),
+                    // Diretórios também recebem MODIFIED quando o conteúdo muda
+                    FILE_ACTION_MODIFIED if !path.is_dir() => Some(FileEvent::Modified(path)),
+                    FILE_ACTION_RENAMED_OLD_NAME => {
+                        self.pending_rename = Some(path);
+                        None
+                    }
+                    FILE_ACTION_RENAMED_NEW_NAME => Some(match self.pending_rename.take() {
+                        Some(from) => FileEvent::Renamed { from, to: path },
+                        None => FileEvent::Created(path),
+                    }),
+                    _ => None,
+                };
+                if let Some(event) = event {
+                    if !self.sink.emit(event) {
+                        return false;
+                    }
+                }
+
+                if next == 0 || offset + next >= len {
+                    return true;
+                }
+                offset += next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("avila_watch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    /// Coleta eventos até `done` aceitar a lista ou o tempo acabar
+    fn collect(watcher: &FileWatcher, done: impl Fn(&[FileEvent]) -> bool) -> Vec<FileEvent> {
+        let mut events = Vec::new();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !done(&events) && std::time::Instant::now() < deadline {
+            if let Some(event) = watcher.recv_timeout(Duration::from_millis(20)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_native_watcher_tree_events() {
+        let dir = temp_dir("native");
+        let watcher = FileWatcher::new(&dir).unwrap();
+
+        let sub = dir.join("shaders");
+        fs::create_dir(&sub).unwrap();
+        let file = sub.join("lit.wgsl");
+        fs::write(&file, "a").unwrap();
+        let events = collect(&watcher, |e| e.contains(&FileEvent::Created(file.clone())));
+        assert!(events.contains(&FileEvent::Created(sub.clone())));
+        assert!(events.contains(&FileEvent::Created(file.clone())));
+
+        fs::write(&file, "ab").unwrap();
+        let events = collect(&watcher, |e| e.contains(&FileEvent::Modified(file.clone())));
+        assert!(events.contains(&FileEvent::Modified(file.clone())));
+
+        let renamed = sub.join("unlit.wgsl");
+        fs::rename(&file, &renamed).unwrap();
+        let expected = FileEvent::Renamed {
+            from: file.clone(),
+            to: renamed.clone(),
+        };
+        let events = collect(&watcher, |e| e.contains(&expected));
+        assert!(events.contains(&expected), "{:?}", events);
+
+        fs::remove_file(&renamed).unwrap();
+        let events = collect(&watcher, |e| {
+            e.contains(&FileEvent::Deleted(renamed.clone()))
+        });
+        assert!(events.contains(&FileEvent::Deleted(renamed.clone())));
+
+        drop(watcher);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_single_file_filter_and_polling() {
+        let dir = temp_dir("single");
+        let target = dir.join("config.toml");
+        let other = dir.join("other.txt");
+        fs::write(&target, "v1").unwrap();
+
+        let mut native = FileWatcher::new(&target).unwrap();
+        let polling = FileWatcher::polling(&dir, true, Duration::from_millis(20)).unwrap();
+
+        fs::write(&other, "x").unwrap();
+        // Tamanho diferente garante a detecção mesmo com mtime de baixa resolução
+        fs::write(&target, "version 2").unwrap();
+
+        let events = collect(&native, |e| {
+            e.contains(&FileEvent::Modified(target.clone()))
+        });
+        assert!(events.iter().all(|e| e.path() == target));
+        assert!(!events.is_empty());
+
+        let events = collect(&polling, |e| {
+            e.contains(&FileEvent::Created(other.clone()))
+                && e.contains(&FileEvent::Modified(target.clone()))
+        });
+        assert!(events.contains(&FileEvent::Created(other.clone())));
+        assert!(events.contains(&FileEvent::Modified(target.clone())));
+
+        assert!(native.has_changed().is_ok());
+        fs::remove_file(&other).unwrap();
+        let events = collect(&polling, |e| e.contains(&FileEvent::Deleted(other.clone())));
+        assert!(events.contains(&FileEvent::Deleted(other.clone())));
+
+        drop((native, polling));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
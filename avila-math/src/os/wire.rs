@@ -0,0 +1,351 @@
+//! Formato binário para mensagens de rede
+//!
+//! `Encode`/`Decode` escrevem e leem valores de `NetworkBuffer`/`NetworkReader`
+//! com um formato fixo: inteiros e floats em big-endian, comprimentos de
+//! coleções como varint, `Option` com um byte de tag. Structs próprias
+//! ganham as duas impls com a macro `impl_wire!`:
+//!
+//! ```
+//! use avila_math::impl_wire;
+//! use avila_math::os::{Decode, Encode, NetworkBuffer};
+//! use avila_math::{Quat, Vec3};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct PlayerState {
+//!     id: u32,
+//!     position: Vec3,
+//!     rotation: Quat,
+//!     name: String,
+//! }
+//! impl_wire!(PlayerState { id, position, rotation, name });
+//!
+//! let state = PlayerState {
+//!     id: 7,
+//!     position: Vec3::new(1.0, 2.0, 3.0),
+//!     rotation: Quat::IDENTITY,
+//!     name: "ana".to_string(),
+//! };
+//!
+//! let mut buffer = NetworkBuffer::new();
+//! buffer.encode(&state);
+//! let decoded: PlayerState = buffer.reader().decode().unwrap();
+//! assert_eq!(decoded, state);
+//! ```
+
+use std::io;
+
+use super::network::{NetworkBuffer, NetworkReader};
+use crate::{Aabb, Mat4, Quat, Vec3, Vec4};
+
+/// Tipo que pode ser escrito em um `NetworkBuffer`
+pub trait Encode {
+    fn encode(&self, buffer: &mut NetworkBuffer);
+}
+
+/// Tipo que pode ser lido de um `NetworkReader`
+pub trait Decode: Sized {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self>;
+}
+
+impl NetworkBuffer {
+    /// Escreve um valor usando sua impl de `Encode`
+    pub fn encode<T: Encode + ?Sized>(&mut self, value: &T) {
+        value.encode(self);
+    }
+}
+
+impl NetworkReader<'_> {
+    /// Lê um valor usando sua impl de `Decode`
+    pub fn decode<T: Decode>(&mut self) -> io::Result<T> {
+        T::decode(self)
+    }
+}
+
+/// Serializa um valor em um vetor de bytes
+pub fn encode_to_vec<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut buffer = NetworkBuffer::new();
+    value.encode(&mut buffer);
+    buffer.as_bytes().to_vec()
+}
+
+/// Desserializa um valor, exigindo que todos os bytes sejam consumidos
+pub fn decode_from_slice<T: Decode>(bytes: &[u8]) -> io::Result<T> {
+    let mut reader = NetworkReader::new(bytes);
+    let value = T::decode(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} trailing bytes after message", reader.remaining()),
+        ));
+    }
+    Ok(value)
+}
+
+/// Implementa `Encode` e `Decode` para uma struct com campos nomeados,
+/// serializando os campos na ordem listada
+#[macro_export]
+macro_rules! impl_wire {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::os::Encode for $ty {
+            fn encode(&self, buffer: &mut $crate::os::NetworkBuffer) {
+                $( $crate::os::Encode::encode(&self.$field, buffer); )*
+            }
+        }
+
+        impl $crate::os::Decode for $ty {
+            fn decode(
+                reader: &mut $crate::os::NetworkReader<'_>,
+            ) -> ::std::io::Result<Self> {
+                Ok(Self {
+                    $( $field: $crate::os::Decode::decode(reader)?, )*
+                })
+            }
+        }
+    };
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $write:ident, $read:ident;)*) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self, buffer: &mut NetworkBuffer) {
+                    buffer.$write(*self);
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+                    reader.$read()
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive! {
+    u8 => write_u8, read_u8;
+    u16 => write_u16, read_u16;
+    u32 => write_u32, read_u32;
+    u64 => write_u64, read_u64;
+    i8 => write_i8, read_i8;
+    i16 => write_i16, read_i16;
+    i32 => write_i32, read_i32;
+    i64 => write_i64, read_i64;
+    f32 => write_f32, read_f32;
+    f64 => write_f64, read_f64;
+    bool => write_bool, read_bool;
+}
+
+fn encode_len(len: usize, buffer: &mut NetworkBuffer) {
+    buffer.write_varint(len as u64);
+}
+
+fn decode_len(reader: &mut NetworkReader<'_>) -> io::Result<usize> {
+    let len = reader.read_varint()?;
+    usize::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "length does not fit in usize"))
+}
+
+impl Encode for str {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        encode_len(self.len(), buffer);
+        buffer.write_bytes(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        self.as_str().encode(buffer);
+    }
+}
+
+impl Decode for String {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+        let len = decode_len(reader)?;
+        let bytes = reader.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        encode_len(self.len(), buffer);
+        for item in self {
+            item.encode(buffer);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        self.as_slice().encode(buffer);
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+        let len = decode_len(reader)?;
+        // Um comprimento malicioso não deve reservar mais que o restante da mensagem
+        let mut items = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            items.push(T::decode(reader)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        for item in self {
+            item.encode(buffer);
+        }
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::decode(reader)?);
+        }
+        Ok(items
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("exactly N items were decoded")))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        match self {
+            Some(value) => {
+                buffer.write_bool(true);
+                value.encode(buffer);
+            }
+            None => buffer.write_bool(false),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+        if reader.read_bool()? {
+            T::decode(reader).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        (**self).encode(buffer);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    fn encode(&self, buffer: &mut NetworkBuffer) {
+        (**self).encode(buffer);
+    }
+}
+
+impl<T: Decode> Decode for Box<T> {
+    fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+        T::decode(reader).map(Box::new)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode(&self, buffer: &mut NetworkBuffer) {
+                let ($($name,)+) = self;
+                $( $name.encode(buffer); )+
+            }
+        }
+
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            fn decode(reader: &mut NetworkReader<'_>) -> io::Result<Self> {
+                Ok(($($name::decode(reader)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
+impl_wire!(Vec3 { x, y, z });
+impl_wire!(Vec4 { x, y, z, w });
+impl_wire!(Quat { x, y, z, w });
+impl_wire!(Mat4 { cols });
+impl_wire!(Aabb { min, max });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Snapshot {
+        tick: u64,
+        entities: Vec<(u32, Vec3)>,
+        camera: Option<Mat4>,
+        bounds: Aabb,
+        tags: [u8; 3],
+    }
+    impl_wire!(Snapshot {
+        tick,
+        entities,
+        camera,
+        bounds,
+        tags,
+    });
+
+    #[test]
+    fn test_roundtrip_composite() {
+        let snapshot = Snapshot {
+            tick: 1234,
+            entities: vec![(1, Vec3::new(1.0, 0.0, -1.0)), (2, Vec3::ONE)],
+            camera: Some(Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0))),
+            bounds: Aabb::from_center_size(Vec3::ZERO, Vec3::ONE),
+            tags: [1, 2, 3],
+        };
+
+        let bytes = encode_to_vec(&snapshot);
+        let decoded: Snapshot = decode_from_slice(&bytes).unwrap();
+        assert_eq!(decoded, snapshot);
+    }
+
+    #[test]
+    fn test_wire_format_is_stable() {
+        let mut buffer = NetworkBuffer::new();
+        buffer.encode(&(1u16, "ab", Some(Vec3::new(1.0, 0.0, 0.0))));
+        assert_eq!(
+            buffer.as_bytes(),
+            &[0, 1, 2, b'a', b'b', 1, 0x3f, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        // Mensagem truncada
+        let bytes = encode_to_vec(&Quat::IDENTITY);
+        assert!(decode_from_slice::<Quat>(&bytes[..10]).is_err());
+
+        // Bytes sobrando
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(decode_from_slice::<Quat>(&extra).is_err());
+
+        // Comprimento enorme não aloca nem entra em pânico
+        let mut buffer = NetworkBuffer::new();
+        buffer.write_varint(u32::MAX as u64);
+        assert!(decode_from_slice::<Vec<u64>>(buffer.as_bytes()).is_err());
+
+        // Tag de Option inválida
+        assert!(decode_from_slice::<Option<u8>>(&[2, 0]).is_err());
+    }
+}
@@ -0,0 +1,557 @@
+//! Mapeamento de ações
+//!
+//! `ActionMap` liga ações lógicas ("Jump", "Move") a entradas físicas:
+//! teclas, botões do mouse e do gamepad e eixos analógicos. O jogo consulta
+//! a ação, não a tecla, e os bindings podem mudar em runtime (menu de
+//! controles) ou ser salvos em JSON.
+//!
+//! Cada `InputBinding` produz um valor de 0 a 1: teclas e botões valem 0 ou
+//! 1, eixos analógicos a magnitude na direção pedida. Uma ação com vários
+//! bindings usa o de maior magnitude, então teclado e gamepad convivem sem
+//! somar.
+
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::gamepad::{Gamepad, GamepadAxis, GamepadButton, GamepadId, Gamepads};
+use super::input::{InputState, Key, KeyCode, MouseButton};
+
+/// Sentido de um eixo analógico
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Entrada física com valor de 0 a 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButton),
+    /// Metade de um eixo (ex.: stick para a esquerda, gatilho)
+    GamepadAxis {
+        axis: GamepadAxis,
+        direction: AxisDirection,
+    },
+}
+
+impl InputBinding {
+    /// Valor atual; gamepads contam pelo maior valor entre eles
+    fn value(&self, input: &InputState, pads: &[&Gamepad]) -> f32 {
+        let pressed = |down: bool| if down { 1.0 } else { 0.0 };
+        match *self {
+            Self::Key(code) => pressed(input.is_keycode_pressed(code)),
+            Self::Mouse(button) => pressed(input.is_button_pressed(button)),
+            Self::GamepadButton(button) => pressed(pads.iter().any(|pad| pad.is_pressed(button))),
+            Self::GamepadAxis { axis, direction } => pads
+                .iter()
+                .map(|pad| match direction {
+                    AxisDirection::Positive => pad.axis(axis),
+                    AxisDirection::Negative => -pad.axis(axis),
+                })
+                .fold(0.0, f32::max),
+        }
+    }
+
+    /// Primeira entrada ativa no momento, para capturar um novo binding
+    ///
+    /// Teclado tem prioridade sobre mouse, e botões sobre eixos; eixos
+    /// precisam passar da metade do curso.
+    pub fn detect(input: &InputState, gamepads: Option<&Gamepads>) -> Option<Self> {
+        let key = input.pressed_keys().find_map(|key| match key {
+            Key::Code(code) => Some(Self::Key(code)),
+            Key::Character(_) => None,
+        });
+        if key.is_some() {
+            return key;
+        }
+        if let Some(button) = input.pressed_buttons().next() {
+            return Some(Self::Mouse(button));
+        }
+
+        let pads: Vec<&Gamepad> = gamepads.into_iter().flat_map(Gamepads::iter).collect();
+        let button = GamepadButton::ALL
+            .into_iter()
+            .find(|button| pads.iter().any(|pad| pad.is_pressed(*button)));
+        if let Some(button) = button {
+            return Some(Self::GamepadButton(button));
+        }
+        GamepadAxis::ALL.into_iter().find_map(|axis| {
+            let value = pads
+                .iter()
+                .map(|pad| pad.axis(axis))
+                .max_by(|a, b| a.abs().total_cmp(&b.abs()))?;
+            let direction = if value >= 0.5 {
+                AxisDirection::Positive
+            } else if value <= -0.5 {
+                AxisDirection::Negative
+            } else {
+                return None;
+            };
+            Some(Self::GamepadAxis { axis, direction })
+        })
+    }
+}
+
+/// Como uma ação combina entradas
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActionBinding {
+    /// Valor de 0 a 1
+    Button(InputBinding),
+    /// Valor de -1 a 1 (`positive` - `negative`)
+    Axis {
+        negative: InputBinding,
+        positive: InputBinding,
+    },
+    /// Vetor 2D com comprimento até 1; Y positivo para baixo, como nos sticks
+    Axis2 {
+        up: InputBinding,
+        down: InputBinding,
+        left: InputBinding,
+        right: InputBinding,
+    },
+}
+
+impl ActionBinding {
+    pub fn key(code: KeyCode) -> Self {
+        Self::Button(InputBinding::Key(code))
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self::Button(InputBinding::Mouse(button))
+    }
+
+    pub fn gamepad(button: GamepadButton) -> Self {
+        Self::Button(InputBinding::GamepadButton(button))
+    }
+
+    /// Eixo formado por duas teclas (ex.: A/D)
+    pub fn key_axis(negative: KeyCode, positive: KeyCode) -> Self {
+        Self::Axis {
+            negative: InputBinding::Key(negative),
+            positive: InputBinding::Key(positive),
+        }
+    }
+
+    /// Eixo analógico inteiro do gamepad
+    pub fn gamepad_axis(axis: GamepadAxis) -> Self {
+        Self::Axis {
+            negative: InputBinding::GamepadAxis {
+                axis,
+                direction: AxisDirection::Negative,
+            },
+            positive: InputBinding::GamepadAxis {
+                axis,
+                direction: AxisDirection::Positive,
+            },
+        }
+    }
+
+    /// Vetor 2D a partir de quatro teclas
+    pub fn keys2(up: KeyCode, down: KeyCode, left: KeyCode, right: KeyCode) -> Self {
+        Self::Axis2 {
+            up: InputBinding::Key(up),
+            down: InputBinding::Key(down),
+            left: InputBinding::Key(left),
+            right: InputBinding::Key(right),
+        }
+    }
+
+    pub fn wasd() -> Self {
+        Self::keys2(KeyCode::W, KeyCode::S, KeyCode::A, KeyCode::D)
+    }
+
+    pub fn arrows() -> Self {
+        Self::keys2(
+            KeyCode::ArrowUp,
+            KeyCode::ArrowDown,
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowRight,
+        )
+    }
+
+    pub fn dpad() -> Self {
+        Self::Axis2 {
+            up: InputBinding::GamepadButton(GamepadButton::DPadUp),
+            down: InputBinding::GamepadButton(GamepadButton::DPadDown),
+            left: InputBinding::GamepadButton(GamepadButton::DPadLeft),
+            right: InputBinding::GamepadButton(GamepadButton::DPadRight),
+        }
+    }
+
+    pub fn left_stick() -> Self {
+        Self::stick(GamepadAxis::LeftStickX, GamepadAxis::LeftStickY)
+    }
+
+    pub fn right_stick() -> Self {
+        Self::stick(GamepadAxis::RightStickX, GamepadAxis::RightStickY)
+    }
+
+    fn stick(x: GamepadAxis, y: GamepadAxis) -> Self {
+        let half = |axis, direction| InputBinding::GamepadAxis { axis, direction };
+        Self::Axis2 {
+            up: half(y, AxisDirection::Negative),
+            down: half(y, AxisDirection::Positive),
+            left: half(x, AxisDirection::Negative),
+            right: half(x, AxisDirection::Positive),
+        }
+    }
+
+    /// Valor como vetor; ações de uma dimensão só usam `x`
+    fn value(&self, input: &InputState, pads: &[&Gamepad]) -> (f32, f32) {
+        match self {
+            Self::Button(binding) => (binding.value(input, pads), 0.0),
+            Self::Axis { negative, positive } => (
+                positive.value(input, pads) - negative.value(input, pads),
+                0.0,
+            ),
+            Self::Axis2 {
+                up,
+                down,
+                left,
+                right,
+            } => {
+                let x = right.value(input, pads) - left.value(input, pads);
+                let y = down.value(input, pads) - up.value(input, pads);
+                // Diagonal no teclado não anda mais rápido que o stick
+                let length = (x * x + y * y).sqrt();
+                if length > 1.0 {
+                    (x / length, y / length)
+                } else {
+                    (x, y)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ActionState {
+    value: (f32, f32),
+    pressed: bool,
+    was_pressed: bool,
+}
+
+fn magnitude((x, y): (f32, f32)) -> f32 {
+    (x * x + y * y).sqrt()
+}
+
+/// Ações lógicas e seus bindings
+///
+/// Chame `update` uma vez por frame, depois de processar os eventos, e
+/// consulte as ações pelo nome. Ações sem binding (ou desconhecidas) valem
+/// zero e nunca estão pressionadas.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActionMap {
+    bindings: BTreeMap<String, Vec<ActionBinding>>,
+    /// Magnitude a partir da qual a ação conta como pressionada
+    press_threshold: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gamepad: Option<GamepadId>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: HashMap<String, ActionState>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+            press_threshold: 0.5,
+            gamepad: None,
+            state: HashMap::new(),
+        }
+    }
+
+    /// Acrescenta um binding à ação (criando a ação se preciso)
+    pub fn bind(&mut self, action: &str, binding: ActionBinding) -> &mut Self {
+        let bindings = self.bindings.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Remove um binding; retorna `false` se a ação não o tinha
+    pub fn unbind(&mut self, action: &str, binding: &ActionBinding) -> bool {
+        let Some(bindings) = self.bindings.get_mut(action) else {
+            return false;
+        };
+        let len = bindings.len();
+        bindings.retain(|b| b != binding);
+        bindings.len() != len
+    }
+
+    /// Substitui todos os bindings da ação
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<ActionBinding>) {
+        self.bindings.insert(action.to_string(), bindings);
+    }
+
+    /// Troca um binding por outro, mantendo a posição
+    pub fn rebind(&mut self, action: &str, old: &ActionBinding, new: ActionBinding) -> bool {
+        let Some(slot) = self
+            .bindings
+            .get_mut(action)
+            .and_then(|bindings| bindings.iter_mut().find(|b| *b == old))
+        else {
+            return false;
+        };
+        *slot = new;
+        true
+    }
+
+    /// Remove a ação e seus bindings
+    pub fn remove_action(&mut self, action: &str) {
+        self.bindings.remove(action);
+        self.state.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[ActionBinding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nomes das ações, em ordem alfabética
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(String::as_str)
+    }
+
+    pub fn press_threshold(&self) -> f32 {
+        self.press_threshold
+    }
+
+    pub fn set_press_threshold(&mut self, threshold: f32) {
+        self.press_threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Restringe os bindings de gamepad a um controle (`None` = todos)
+    pub fn set_gamepad(&mut self, gamepad: Option<GamepadId>) {
+        self.gamepad = gamepad;
+    }
+
+    /// Recalcula todas as ações a partir do estado atual do input
+    pub fn update(&mut self, input: &InputState, gamepads: Option<&Gamepads>) {
+        let pads: Vec<&Gamepad> = gamepads
+            .into_iter()
+            .flat_map(Gamepads::iter)
+            .filter(|pad| self.gamepad.is_none_or(|id| pad.id() == id))
+            .collect();
+
+        self.state
+            .retain(|action, _| self.bindings.contains_key(action));
+        for (action, bindings) in &self.bindings {
+            let value = bindings
+                .iter()
+                .map(|binding| binding.value(input, &pads))
+                .max_by(|a, b| magnitude(*a).total_cmp(&magnitude(*b)))
+                .unwrap_or((0.0, 0.0));
+            let state = self.state.entry(action.clone()).or_default();
+            state.was_pressed = state.pressed;
+            state.value = value;
+            state.pressed = magnitude(value) >= self.press_threshold;
+        }
+    }
+
+    fn state(&self, action: &str) -> ActionState {
+        self.state.get(action).copied().unwrap_or_default()
+    }
+
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.state(action).pressed
+    }
+
+    /// Pressionada neste `update` e solta no anterior
+    pub fn just_pressed(&self, action: &str) -> bool {
+        let state = self.state(action);
+        state.pressed && !state.was_pressed
+    }
+
+    /// Solta neste `update` e pressionada no anterior
+    pub fn just_released(&self, action: &str) -> bool {
+        let state = self.state(action);
+        !state.pressed && state.was_pressed
+    }
+
+    /// Valor de ações de botão (0..1) ou de eixo (-1..1)
+    pub fn value(&self, action: &str) -> f32 {
+        self.state(action).value.0
+    }
+
+    /// Valor de ações 2D
+    pub fn axis2(&self, action: &str) -> (f32, f32) {
+        self.state(action).value
+    }
+
+    /// Serializa os bindings em JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("ActionMap is always serializable")
+    }
+
+    /// Carrega bindings salvos por `to_json`
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::gamepad::RawGamepad;
+
+    fn pads_with(buttons: u32, axes: [f32; 6]) -> Gamepads {
+        let mut gamepads = Gamepads::new();
+        let raw = RawGamepad {
+            key: 1,
+            name: "Pad".to_string(),
+            buttons,
+            axes,
+        };
+        gamepads.apply(&[raw], &mut Vec::new());
+        gamepads
+    }
+
+    #[test]
+    fn test_button_action() {
+        let mut map = ActionMap::new();
+        map.bind("Jump", ActionBinding::key(KeyCode::Space))
+            .bind("Jump", ActionBinding::gamepad(GamepadButton::South));
+
+        let mut input = InputState::new();
+        map.update(&input, None);
+        assert!(!map.is_pressed("Jump"));
+
+        input.press_key(Key::Code(KeyCode::Space));
+        map.update(&input, None);
+        assert!(map.is_pressed("Jump"));
+        assert!(map.just_pressed("Jump"));
+        assert_eq!(map.value("Jump"), 1.0);
+
+        map.update(&input, None);
+        assert!(map.is_pressed("Jump"));
+        assert!(!map.just_pressed("Jump"));
+
+        input.release_key(Key::Code(KeyCode::Space));
+        map.update(&input, None);
+        assert!(map.just_released("Jump"));
+
+        let gamepads = pads_with(GamepadButton::South.bit(), [0.0; 6]);
+        map.update(&input, Some(&gamepads));
+        assert!(map.just_pressed("Jump"));
+
+        assert!(!map.is_pressed("Unknown"));
+    }
+
+    #[test]
+    fn test_wasd_composition() {
+        let mut map = ActionMap::new();
+        map.bind("Move", ActionBinding::wasd());
+
+        let mut input = InputState::new();
+        input.press_key(Key::Code(KeyCode::W));
+        map.update(&input, None);
+        assert_eq!(map.axis2("Move"), (0.0, -1.0));
+
+        // Diagonal normalizada
+        input.press_key(Key::Code(KeyCode::D));
+        map.update(&input, None);
+        let (x, y) = map.axis2("Move");
+        assert!((x - 0.70710677).abs() < 1e-6 && (y + 0.70710677).abs() < 1e-6);
+
+        // Direções opostas se anulam
+        input.press_key(Key::Code(KeyCode::S));
+        map.update(&input, None);
+        assert_eq!(map.axis2("Move"), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_keyboard_and_stick_use_largest() {
+        let mut map = ActionMap::new();
+        map.bind("Move", ActionBinding::wasd())
+            .bind("Move", ActionBinding::left_stick())
+            .bind(
+                "Steer",
+                ActionBinding::gamepad_axis(GamepadAxis::LeftStickX),
+            );
+
+        let input = InputState::new();
+        let gamepads = pads_with(0, [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        map.update(&input, Some(&gamepads));
+        let (x, y) = map.axis2("Move");
+        assert!((x + 1.0).abs() < 1e-6 && y == 0.0);
+        assert!((map.value("Steer") + 1.0).abs() < 1e-6);
+
+        // Outro controle selecionado: o stick não conta
+        map.set_gamepad(Some(GamepadId(99)));
+        map.update(&input, Some(&gamepads));
+        assert_eq!(map.axis2("Move"), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_rebinding() {
+        let mut map = ActionMap::new();
+        map.bind("Fire", ActionBinding::mouse(MouseButton::Left));
+        assert!(map.rebind(
+            "Fire",
+            &ActionBinding::mouse(MouseButton::Left),
+            ActionBinding::key(KeyCode::F),
+        ));
+        assert_eq!(map.bindings("Fire"), &[ActionBinding::key(KeyCode::F)]);
+        assert!(!map.unbind("Fire", &ActionBinding::mouse(MouseButton::Left)));
+        assert!(map.unbind("Fire", &ActionBinding::key(KeyCode::F)));
+        assert!(map.bindings("Fire").is_empty());
+
+        let mut input = InputState::new();
+        assert_eq!(InputBinding::detect(&input, None), None);
+        input.press_button(MouseButton::Right);
+        assert_eq!(
+            InputBinding::detect(&input, None),
+            Some(InputBinding::Mouse(MouseButton::Right))
+        );
+        input.press_key(Key::Code(KeyCode::Q));
+        assert_eq!(
+            InputBinding::detect(&input, None),
+            Some(InputBinding::Key(KeyCode::Q))
+        );
+
+        let gamepads = pads_with(0, [0.0, 0.0, 0.0, 0.0, 0.0, 0.9]);
+        assert_eq!(
+            InputBinding::detect(&InputState::new(), Some(&gamepads)),
+            Some(InputBinding::GamepadAxis {
+                axis: GamepadAxis::RightTrigger,
+                direction: AxisDirection::Positive,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_roundtrip() {
+        let mut map = ActionMap::new();
+        map.bind("Move", ActionBinding::wasd())
+            .bind("Jump", ActionBinding::gamepad(GamepadButton::South));
+        map.set_press_threshold(0.3);
+
+        let json = map.to_json();
+        assert!(json.contains("\"Jump\""));
+
+        let parsed = ActionMap::from_json(&json).unwrap();
+        assert_eq!(parsed.bindings("Move"), &[ActionBinding::wasd()]);
+        assert_eq!(parsed.press_threshold(), 0.3);
+        assert_eq!(parsed.actions().collect::<Vec<_>>(), vec!["Jump", "Move"]);
+
+        assert!(ActionMap::from_json("[]").is_err());
+    }
+}
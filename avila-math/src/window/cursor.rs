@@ -0,0 +1,177 @@
+//! Cursores do sistema, cursores customizados e captura do ponteiro
+//!
+//! `CursorIcon` é o conjunto padrão que todos os backends oferecem (os nomes
+//! seguem o CSS, como nos temas de cursor do freedesktop). `CursorImage` é um
+//! cursor RGBA do usuário e `CursorGrabMode` define se o ponteiro pode sair
+//! da janela.
+
+use super::icon::check_rgba;
+use super::WindowError;
+
+/// Cursor padrão do sistema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorIcon {
+    /// Seta
+    #[default]
+    Default,
+    /// Mão (links, botões)
+    Pointer,
+    /// Barra de texto
+    Text,
+    Crosshair,
+    /// Ocupado, sem aceitar input
+    Wait,
+    /// Ocupado, mas ainda interativo (seta com ampulheta)
+    Progress,
+    Help,
+    NotAllowed,
+    /// Mover em qualquer direção
+    Move,
+    /// Mão aberta (algo pode ser arrastado)
+    Grab,
+    /// Mão fechada (arrastando)
+    Grabbing,
+    /// Redimensionar na horizontal
+    EwResize,
+    /// Redimensionar na vertical
+    NsResize,
+    /// Redimensionar na diagonal ↗↙
+    NeswResize,
+    /// Redimensionar na diagonal ↖↘
+    NwseResize,
+}
+
+impl CursorIcon {
+    /// Todos os ícones
+    pub const ALL: [CursorIcon; 15] = [
+        Self::Default,
+        Self::Pointer,
+        Self::Text,
+        Self::Crosshair,
+        Self::Wait,
+        Self::Progress,
+        Self::Help,
+        Self::NotAllowed,
+        Self::Move,
+        Self::Grab,
+        Self::Grabbing,
+        Self::EwResize,
+        Self::NsResize,
+        Self::NeswResize,
+        Self::NwseResize,
+    ];
+
+    /// Nome CSS do cursor (também o nome nos temas do freedesktop)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Pointer => "pointer",
+            Self::Text => "text",
+            Self::Crosshair => "crosshair",
+            Self::Wait => "wait",
+            Self::Progress => "progress",
+            Self::Help => "help",
+            Self::NotAllowed => "not-allowed",
+            Self::Move => "move",
+            Self::Grab => "grab",
+            Self::Grabbing => "grabbing",
+            Self::EwResize => "ew-resize",
+            Self::NsResize => "ns-resize",
+            Self::NeswResize => "nesw-resize",
+            Self::NwseResize => "nwse-resize",
+        }
+    }
+}
+
+/// Cursor customizado em RGBA (8 bits por canal, sem pré-multiplicação)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot: (u32, u32),
+}
+
+impl CursorImage {
+    /// Cria o cursor; `hotspot` é o pixel que aponta, dentro da imagem
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: (u32, u32),
+    ) -> Result<Self, WindowError> {
+        check_rgba(&rgba, width, height, "cursor")?;
+        if hotspot.0 >= width || hotspot.1 >= height {
+            return Err(WindowError::InvalidImage(format!(
+                "hotspot ({}, {}) outside of the {}x{} image",
+                hotspot.0, hotspot.1, width, height
+            )));
+        }
+        Ok(Self {
+            rgba,
+            width,
+            height,
+            hotspot,
+        })
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn hotspot(&self) -> (u32, u32) {
+        self.hotspot
+    }
+}
+
+/// Quanto o ponteiro fica preso à janela
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorGrabMode {
+    /// Livre
+    #[default]
+    None,
+    /// Não sai da área cliente enquanto a janela tem foco
+    Confined,
+    /// Parado na posição atual: `CursorMoved` mantém a posição e traz só o
+    /// `delta` (câmera em primeira pessoa)
+    Locked,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_icon_names() {
+        assert_eq!(CursorIcon::default(), CursorIcon::Default);
+        assert_eq!(CursorIcon::NotAllowed.name(), "not-allowed");
+
+        let mut names: Vec<_> = CursorIcon::ALL.iter().map(|icon| icon.name()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), CursorIcon::ALL.len());
+    }
+
+    #[test]
+    fn test_cursor_image() {
+        let image = CursorImage::from_rgba(vec![255; 16 * 16 * 4], 16, 16, (8, 8)).unwrap();
+        assert_eq!((image.width(), image.height()), (16, 16));
+        assert_eq!(image.hotspot(), (8, 8));
+        assert_eq!(image.rgba().len(), 1024);
+    }
+
+    #[test]
+    fn test_invalid_cursor_image() {
+        assert!(CursorImage::from_rgba(vec![0; 15], 2, 2, (0, 0)).is_err());
+        assert!(CursorImage::from_rgba(Vec::new(), 0, 0, (0, 0)).is_err());
+        assert!(CursorImage::from_rgba(vec![0; 16], 2, 2, (2, 0)).is_err());
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Gerencia todos os eventos: input, resize, close, focus, etc.
 
+use std::time::Instant;
+
+use super::gamepad::GamepadEvent;
 use super::input::{Key, ModifierKeys, MouseButton};
+use super::platform;
 use super::{WindowPosition, WindowSize};
 
 /// Evento da janela
@@ -14,7 +18,12 @@ pub enum Event {
     Keyboard(KeyEvent),
     /// Evento de mouse
     Mouse(MouseEvent),
-    /// Tick do frame (usado para game loop)
+    /// Evento de touch (um por dedo)
+    Touch(TouchEvent),
+    /// Evento de gamepad (gerado por `Gamepads::update`)
+    Gamepad(GamepadEvent),
+    /// Tick do frame (usado para game loop); no `EventLoop::run` vem depois
+    /// de cada lote de eventos, com os segundos desde o tick anterior
     FrameTick(f64),
 }
 
@@ -43,8 +52,13 @@ pub enum WindowEvent {
     CursorLeft,
     /// Frame buffer redimensionado (pode diferir do tamanho da janela em high DPI)
     FramebufferResized(u32, u32),
-    /// Scale factor mudou (high DPI)
-    ScaleFactorChanged(f32),
+    /// Scale factor mudou (high DPI); `new_inner_size` é o novo tamanho da
+    /// área cliente em pixels, já ajustado conforme a `ScalePolicy`.
+    /// `Resized` e `FramebufferResized` vêm em seguida se ele mudar
+    ScaleFactorChanged {
+        scale_factor: f32,
+        new_inner_size: WindowSize,
+    },
     /// Arquivos foram arrastados para a janela
     DroppedFile(String),
     /// Hover de arquivos sobre a janela
@@ -125,6 +139,11 @@ pub enum MouseEvent {
         delta: (f64, f64),
         position: (f64, f64),
     },
+    /// Movimento cru do mouse, sem aceleração nem limite da tela
+    ///
+    /// Só chega com `Window::set_raw_mouse_motion(true)`; o delta é em
+    /// unidades do dispositivo (mickeys), não em pixels.
+    RawMotion { delta: (f64, f64) },
 }
 
 impl MouseEvent {
@@ -134,11 +153,73 @@ impl MouseEvent {
             Self::ButtonReleased { position, .. } => Some(*position),
             Self::CursorMoved { position, .. } => Some(*position),
             Self::Scrolled { position, .. } => Some(*position),
+            Self::RawMotion { .. } => None,
+        }
+    }
+}
+
+/// Fase de um toque
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    /// Dedo encostou na tela
+    Started,
+    /// Dedo moveu
+    Moved,
+    /// Dedo saiu da tela
+    Ended,
+    /// O sistema cancelou o toque (gesto do sistema, janela perdeu o toque)
+    Cancelled,
+}
+
+/// Evento de touch
+///
+/// `id` identifica o dedo do `Started` até o `Ended`/`Cancelled` e pode ser
+/// reutilizado depois. A posição é lógica, relativa à área cliente.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchEvent {
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub position: (f64, f64),
+    /// Pressão normalizada (0.0 a 1.0); 1.0 quando o dispositivo não reporta
+    pub pressure: f32,
+}
+
+impl TouchEvent {
+    pub fn new(id: u64, phase: TouchPhase, position: (f64, f64)) -> Self {
+        Self {
+            id,
+            phase,
+            position,
+            pressure: 1.0,
         }
     }
+
+    pub fn with_pressure(mut self, pressure: f32) -> Self {
+        self.pressure = pressure.clamp(0.0, 1.0);
+        self
+    }
+
+    /// O dedo deixou de existir (`Ended` ou `Cancelled`)
+    pub fn is_finished(&self) -> bool {
+        matches!(self.phase, TouchPhase::Ended | TouchPhase::Cancelled)
+    }
 }
 
 /// Event loop para processar eventos
+/// Como o `EventLoop::run` espera entre uma iteração e outra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Não espera: uma iteração atrás da outra (jogos)
+    Poll,
+    /// Dorme até chegar um evento (ferramentas, editores)
+    Wait,
+    /// Dorme até chegar um evento ou até o instante; depois dele se comporta
+    /// como `Poll`, então o handler deve marcar o próximo
+    WaitUntil(Instant),
+    /// Encerra o `run` ao fim da iteração
+    Exit,
+}
+
 pub struct EventLoop {
     events: Vec<Event>,
     running: bool,
@@ -165,16 +246,56 @@ impl EventLoop {
 
     /// Processa eventos pendentes
     pub fn poll_events(&mut self) -> impl Iterator<Item = Event> + '_ {
-        // Em uma implementação real, aqui pegaria eventos do sistema
+        platform::pump_events(Some(Instant::now()), &mut self.events);
         self.events.drain(..)
     }
 
     /// Aguarda por eventos (blocking)
+    ///
+    /// Não bloqueia se já houver eventos injetados ou nenhuma janela nativa
+    /// aberta nesta thread.
     pub fn wait_events(&mut self) -> impl Iterator<Item = Event> + '_ {
-        // Em uma implementação real, aqui aguardaria eventos do sistema
+        platform::pump_events(None, &mut self.events);
         self.events.drain(..)
     }
 
+    /// Roda o loop até o handler definir `ControlFlow::Exit`
+    ///
+    /// O handler recebe cada evento e, ao fim de cada iteração, um
+    /// `Event::FrameTick`; o `ControlFlow` pode ser trocado a qualquer
+    /// momento. Sem janelas nativas não há o que esperar: `Wait` se comporta
+    /// como `Poll` e `WaitUntil` só dorme até o instante.
+    pub fn run<F>(&mut self, mut control_flow: ControlFlow, mut handler: F)
+    where
+        F: FnMut(Event, &mut ControlFlow),
+    {
+        let mut last_tick = Instant::now();
+        while self.running && control_flow != ControlFlow::Exit {
+            let deadline = match control_flow {
+                ControlFlow::Wait => None,
+                ControlFlow::WaitUntil(deadline) => Some(deadline),
+                ControlFlow::Poll | ControlFlow::Exit => Some(Instant::now()),
+            };
+            platform::pump_events(deadline, &mut self.events);
+            if self.events.is_empty() {
+                if let Some(deadline) = deadline {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if !remaining.is_zero() {
+                        std::thread::sleep(remaining);
+                    }
+                }
+            }
+
+            for event in std::mem::take(&mut self.events) {
+                handler(event, &mut control_flow);
+            }
+            let now = Instant::now();
+            let delta = now.duration_since(last_tick).as_secs_f64();
+            last_tick = now;
+            handler(Event::FrameTick(delta), &mut control_flow);
+        }
+    }
+
     /// Injeta um evento (útil para testes)
     pub fn push_event(&mut self, event: Event) {
         self.events.push(event);
@@ -243,6 +364,50 @@ mod tests {
         assert_eq!(event_loop.pending_count(), 0);
     }
 
+    #[test]
+    fn test_event_loop_run() {
+        let mut event_loop = EventLoop::new();
+        event_loop.push_event(Event::Window(WindowEvent::Focused));
+        event_loop.push_event(Event::Window(WindowEvent::Closed));
+
+        let mut received = Vec::new();
+        let mut ticks = 0;
+        event_loop.run(ControlFlow::Poll, |event, control_flow| match event {
+            Event::FrameTick(delta) => {
+                assert!(delta >= 0.0);
+                ticks += 1;
+                if ticks == 3 {
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
+            event => received.push(event),
+        });
+
+        assert_eq!(ticks, 3);
+        assert_eq!(
+            received,
+            vec![
+                Event::Window(WindowEvent::Focused),
+                Event::Window(WindowEvent::Closed)
+            ]
+        );
+        assert_eq!(event_loop.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_event_loop_wait_until() {
+        let mut event_loop = EventLoop::new();
+        let start = Instant::now();
+        let deadline = start + std::time::Duration::from_millis(20);
+
+        event_loop.run(ControlFlow::WaitUntil(deadline), |event, control_flow| {
+            if let Event::FrameTick(_) = event {
+                *control_flow = ControlFlow::Exit;
+            }
+        });
+        assert!(start.elapsed() >= std::time::Duration::from_millis(20));
+    }
+
     #[test]
     fn test_key_event() {
         let key_event = KeyEvent::new(Key::Code(KeyCode::A), KeyState::Pressed)
@@ -262,6 +427,19 @@ mod tests {
         };
 
         assert_eq!(mouse_event.position(), Some((100.0, 200.0)));
+
+        let raw = MouseEvent::RawMotion { delta: (3.0, -1.0) };
+        assert_eq!(raw.position(), None);
+    }
+
+    #[test]
+    fn test_touch_event() {
+        let touch = TouchEvent::new(3, TouchPhase::Started, (10.0, 20.0)).with_pressure(1.5);
+        assert_eq!(touch.pressure, 1.0);
+        assert!(!touch.is_finished());
+
+        let ended = TouchEvent::new(3, TouchPhase::Cancelled, (10.0, 20.0));
+        assert!(ended.is_finished());
     }
 
     #[test]
@@ -0,0 +1,511 @@
+//! Gamepads e controles
+//!
+//! `Gamepads` consulta o backend da plataforma (XInput no Windows, evdev no
+//! Linux) e transforma as mudanças de estado em `Event::Gamepad`:
+//! conexão/desconexão, botões e eixos já com dead zone aplicada.
+//!
+//! Os botões seguem o layout padrão (Xbox): `South` é A/Cruz, `East` é
+//! B/Círculo. Eixos dos sticks vão de -1 a 1 com Y positivo para baixo;
+//! gatilhos vão de 0 a 1. Plataformas sem backend (macOS, por enquanto)
+//! nunca listam controles.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::events::{Event, EventLoop};
+use super::platform;
+
+/// Identificador estável de um controle enquanto estiver conectado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub u32);
+
+/// Botões do layout padrão
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadButton {
+    /// A (Xbox), Cruz (PlayStation), B (Nintendo)
+    South,
+    /// B (Xbox), Círculo (PlayStation), A (Nintendo)
+    East,
+    /// X (Xbox), Quadrado (PlayStation), Y (Nintendo)
+    West,
+    /// Y (Xbox), Triângulo (PlayStation), X (Nintendo)
+    North,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    Guide,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    /// Todos os botões, na ordem dos bits do estado
+    pub const ALL: [GamepadButton; 15] = [
+        Self::South,
+        Self::East,
+        Self::West,
+        Self::North,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::Select,
+        Self::Start,
+        Self::Guide,
+        Self::LeftStick,
+        Self::RightStick,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+
+    pub(crate) const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Eixos analógicos do layout padrão
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// Todos os eixos, na ordem do array de estado
+    pub const ALL: [GamepadAxis; 6] = [
+        Self::LeftStickX,
+        Self::LeftStickY,
+        Self::RightStickX,
+        Self::RightStickY,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+    ];
+
+    pub fn is_trigger(&self) -> bool {
+        matches!(self, Self::LeftTrigger | Self::RightTrigger)
+    }
+}
+
+/// Evento de gamepad
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadEvent {
+    /// Controle conectado (também emitido para os já conectados no primeiro `update`)
+    Connected { id: GamepadId, name: String },
+    /// Controle desconectado; o id não é reutilizado
+    Disconnected { id: GamepadId },
+    ButtonPressed {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    ButtonReleased {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    /// Eixo mudou (valor já com dead zone)
+    AxisMoved {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl GamepadEvent {
+    pub fn id(&self) -> GamepadId {
+        match self {
+            Self::Connected { id, .. }
+            | Self::Disconnected { id }
+            | Self::ButtonPressed { id, .. }
+            | Self::ButtonReleased { id, .. }
+            | Self::AxisMoved { id, .. } => *id,
+        }
+    }
+}
+
+/// Configuração de dead zone e sensibilidade
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadSettings {
+    /// Dead zone radial dos sticks (0..1)
+    pub stick_dead_zone: f32,
+    /// Dead zone dos gatilhos (0..1)
+    pub trigger_dead_zone: f32,
+    /// Variação mínima para emitir `AxisMoved`
+    pub axis_threshold: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            stick_dead_zone: 0.15,
+            trigger_dead_zone: 0.05,
+            axis_threshold: 0.01,
+        }
+    }
+}
+
+/// Dead zone radial com reescala: fora da zona o valor volta a cobrir 0..1
+pub fn apply_stick_dead_zone(x: f32, y: f32, dead_zone: f32) -> (f32, f32) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude <= dead_zone || dead_zone >= 1.0 {
+        return (0.0, 0.0);
+    }
+    let scaled = ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    let factor = scaled / magnitude;
+    (x * factor, y * factor)
+}
+
+/// Dead zone linear com reescala para gatilhos
+pub fn apply_trigger_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value <= dead_zone || dead_zone >= 1.0 {
+        return 0.0;
+    }
+    ((value - dead_zone) / (1.0 - dead_zone)).min(1.0)
+}
+
+/// Estado bruto de um controle lido pelo backend
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RawGamepad {
+    /// Chave do dispositivo no backend (slot XInput, `dev_t` no evdev)
+    pub(crate) key: u64,
+    pub(crate) name: String,
+    /// Bits de `GamepadButton::bit`
+    pub(crate) buttons: u32,
+    /// Na ordem de `GamepadAxis::ALL`, sem dead zone
+    pub(crate) axes: [f32; 6],
+}
+
+/// Estado de um controle conectado
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gamepad {
+    id: GamepadId,
+    key: u64,
+    name: String,
+    buttons: u32,
+    axes: [f32; 6],
+}
+
+impl Gamepad {
+    pub fn id(&self) -> GamepadId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons & button.bit() != 0
+    }
+
+    /// Valor do eixo com dead zone aplicada
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn left_stick(&self) -> (f32, f32) {
+        (
+            self.axis(GamepadAxis::LeftStickX),
+            self.axis(GamepadAxis::LeftStickY),
+        )
+    }
+
+    pub fn right_stick(&self) -> (f32, f32) {
+        (
+            self.axis(GamepadAxis::RightStickX),
+            self.axis(GamepadAxis::RightStickY),
+        )
+    }
+}
+
+/// Enumeração e polling de controles
+pub struct Gamepads {
+    backend: platform::GamepadBackend,
+    settings: GamepadSettings,
+    pads: Vec<Gamepad>,
+    next_id: u32,
+    raw: Vec<RawGamepad>,
+}
+
+impl Gamepads {
+    /// Cria o gerenciador com as configurações padrão
+    pub fn new() -> Self {
+        Self::with_settings(GamepadSettings::default())
+    }
+
+    pub fn with_settings(settings: GamepadSettings) -> Self {
+        Self {
+            backend: platform::GamepadBackend::new(),
+            settings,
+            pads: Vec::new(),
+            next_id: 0,
+            raw: Vec::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &GamepadSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: GamepadSettings) {
+        self.settings = settings;
+    }
+
+    /// Lê os controles e injeta as mudanças no event loop
+    ///
+    /// Chame uma vez por frame, antes de `poll_events`.
+    pub fn update(&mut self, event_loop: &mut EventLoop) {
+        let mut raw = std::mem::take(&mut self.raw);
+        raw.clear();
+        self.backend.poll(&mut raw);
+
+        let mut events = Vec::new();
+        self.apply(&raw, &mut events);
+        for event in events {
+            event_loop.push_event(Event::Gamepad(event));
+        }
+        self.raw = raw;
+    }
+
+    /// Controle pelo id, se ainda estiver conectado
+    pub fn get(&self, id: GamepadId) -> Option<&Gamepad> {
+        self.pads.iter().find(|pad| pad.id == id)
+    }
+
+    /// Controles conectados, em ordem de conexão
+    pub fn iter(&self) -> impl Iterator<Item = &Gamepad> {
+        self.pads.iter()
+    }
+
+    pub fn connected_count(&self) -> usize {
+        self.pads.len()
+    }
+
+    /// Compara o estado bruto com o anterior e gera os eventos
+    pub(crate) fn apply(&mut self, raw: &[RawGamepad], out: &mut Vec<GamepadEvent>) {
+        self.pads.retain(|pad| {
+            let connected = raw.iter().any(|r| r.key == pad.key);
+            if !connected {
+                out.push(GamepadEvent::Disconnected { id: pad.id });
+            }
+            connected
+        });
+
+        for state in raw {
+            let index = match self.pads.iter().position(|pad| pad.key == state.key) {
+                Some(index) => index,
+                None => {
+                    let id = GamepadId(self.next_id);
+                    self.next_id += 1;
+                    out.push(GamepadEvent::Connected {
+                        id,
+                        name: state.name.clone(),
+                    });
+                    self.pads.push(Gamepad {
+                        id,
+                        key: state.key,
+                        name: state.name.clone(),
+                        buttons: 0,
+                        axes: [0.0; 6],
+                    });
+                    self.pads.len() - 1
+                }
+            };
+            let pad = &mut self.pads[index];
+
+            let changed = pad.buttons ^ state.buttons;
+            for button in GamepadButton::ALL {
+                if changed & button.bit() == 0 {
+                    continue;
+                }
+                let id = pad.id;
+                out.push(if state.buttons & button.bit() != 0 {
+                    GamepadEvent::ButtonPressed { id, button }
+                } else {
+                    GamepadEvent::ButtonReleased { id, button }
+                });
+            }
+            pad.buttons = state.buttons;
+
+            let axes = self.settings.filter(&state.axes);
+            for axis in GamepadAxis::ALL {
+                let value = axes[axis as usize];
+                let previous = pad.axes[axis as usize];
+                // Voltar ao repouso sempre gera evento, mesmo abaixo do limiar
+                let moved = (value - previous).abs() >= self.settings.axis_threshold
+                    || (value == 0.0 && previous != 0.0);
+                if moved {
+                    pad.axes[axis as usize] = value;
+                    out.push(GamepadEvent::AxisMoved {
+                        id: pad.id,
+                        axis,
+                        value,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl GamepadSettings {
+    /// Aplica as dead zones aos eixos brutos
+    fn filter(&self, axes: &[f32; 6]) -> [f32; 6] {
+        let (lx, ly) = apply_stick_dead_zone(axes[0], axes[1], self.stick_dead_zone);
+        let (rx, ry) = apply_stick_dead_zone(axes[2], axes[3], self.stick_dead_zone);
+        [
+            lx,
+            ly,
+            rx,
+            ry,
+            apply_trigger_dead_zone(axes[4], self.trigger_dead_zone),
+            apply_trigger_dead_zone(axes[5], self.trigger_dead_zone),
+        ]
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(key: u64, buttons: u32, axes: [f32; 6]) -> RawGamepad {
+        RawGamepad {
+            key,
+            name: format!("Pad {}", key),
+            buttons,
+            axes,
+        }
+    }
+
+    #[test]
+    fn test_stick_dead_zone() {
+        assert_eq!(apply_stick_dead_zone(0.1, 0.05, 0.15), (0.0, 0.0));
+
+        let (x, y) = apply_stick_dead_zone(1.0, 0.0, 0.15);
+        assert!((x - 1.0).abs() < 1e-6 && y == 0.0);
+
+        // Direção preservada, magnitude reescalada
+        let (x, y) = apply_stick_dead_zone(0.3, 0.4, 0.2);
+        assert!(((x * x + y * y).sqrt() - 0.375).abs() < 1e-6);
+        assert!((x / y - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_trigger_dead_zone() {
+        assert_eq!(apply_trigger_dead_zone(0.04, 0.05), 0.0);
+        assert_eq!(apply_trigger_dead_zone(1.0, 0.05), 1.0);
+        assert!((apply_trigger_dead_zone(0.525, 0.05) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_connect_and_disconnect() {
+        let mut gamepads = Gamepads::new();
+        let mut events = Vec::new();
+
+        gamepads.apply(&[raw(7, 0, [0.0; 6])], &mut events);
+        assert_eq!(
+            events,
+            vec![GamepadEvent::Connected {
+                id: GamepadId(0),
+                name: "Pad 7".to_string()
+            }]
+        );
+        assert_eq!(gamepads.connected_count(), 1);
+
+        events.clear();
+        gamepads.apply(&[], &mut events);
+        assert_eq!(
+            events,
+            vec![GamepadEvent::Disconnected { id: GamepadId(0) }]
+        );
+        assert!(gamepads.get(GamepadId(0)).is_none());
+
+        // Reconectar gera um id novo
+        events.clear();
+        gamepads.apply(&[raw(7, 0, [0.0; 6])], &mut events);
+        assert_eq!(events[0].id(), GamepadId(1));
+    }
+
+    #[test]
+    fn test_button_events() {
+        let mut gamepads = Gamepads::new();
+        let mut events = Vec::new();
+        gamepads.apply(&[raw(1, 0, [0.0; 6])], &mut events);
+
+        events.clear();
+        let pressed = GamepadButton::South.bit() | GamepadButton::Start.bit();
+        gamepads.apply(&[raw(1, pressed, [0.0; 6])], &mut events);
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&GamepadEvent::ButtonPressed {
+            id: GamepadId(0),
+            button: GamepadButton::South
+        }));
+        let pad = gamepads.get(GamepadId(0)).unwrap();
+        assert!(pad.is_pressed(GamepadButton::Start));
+        assert!(!pad.is_pressed(GamepadButton::East));
+
+        events.clear();
+        gamepads.apply(&[raw(1, GamepadButton::Start.bit(), [0.0; 6])], &mut events);
+        assert_eq!(
+            events,
+            vec![GamepadEvent::ButtonReleased {
+                id: GamepadId(0),
+                button: GamepadButton::South
+            }]
+        );
+    }
+
+    #[test]
+    fn test_axis_events() {
+        let mut gamepads = Gamepads::new();
+        let mut events = Vec::new();
+        gamepads.apply(&[raw(1, 0, [0.0; 6])], &mut events);
+
+        // Ruído dentro da dead zone não gera eventos
+        events.clear();
+        gamepads.apply(
+            &[raw(1, 0, [0.05, -0.05, 0.0, 0.0, 0.01, 0.0])],
+            &mut events,
+        );
+        assert!(events.is_empty());
+
+        gamepads.apply(&[raw(1, 0, [1.0, 0.0, 0.0, 0.0, 0.0, 1.0])], &mut events);
+        assert_eq!(events.len(), 2);
+        let pad = gamepads.get(GamepadId(0)).unwrap();
+        assert!((pad.left_stick().0 - 1.0).abs() < 1e-6);
+        assert_eq!(pad.axis(GamepadAxis::RightTrigger), 1.0);
+
+        events.clear();
+        gamepads.apply(&[raw(1, 0, [0.0; 6])], &mut events);
+        assert!(events.contains(&GamepadEvent::AxisMoved {
+            id: GamepadId(0),
+            axis: GamepadAxis::LeftStickX,
+            value: 0.0
+        }));
+    }
+
+    #[test]
+    fn test_update_feeds_event_loop() {
+        let mut gamepads = Gamepads::new();
+        let mut event_loop = EventLoop::new();
+        gamepads.update(&mut event_loop);
+        // Sem controles (CI) nada é injetado; com controles, só eventos de gamepad
+        assert!(event_loop
+            .poll_events()
+            .all(|event| matches!(event, Event::Gamepad(_))));
+    }
+}
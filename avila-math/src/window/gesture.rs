@@ -0,0 +1,346 @@
+//! Reconhecimento de gestos de touch
+//!
+//! Transforma a sequência de `TouchEvent` em gestos simples: tap (um dedo,
+//! rápido e parado), pan (arrastar com um ou mais dedos) e pinch (variação
+//! da distância entre os dois primeiros dedos). Pan e pinch são incrementais:
+//! cada gesto traz só a variação desde o anterior.
+
+use std::time::{Duration, Instant};
+
+use super::events::{TouchEvent, TouchPhase};
+
+/// Gesto reconhecido
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Toque rápido com um dedo, sem arrastar
+    Tap { position: (f64, f64) },
+    /// Arraste; `position` é o centro dos dedos
+    Pan {
+        delta: (f64, f64),
+        position: (f64, f64),
+        touches: usize,
+    },
+    /// Pinça com dois dedos; `scale` > 1.0 afasta, < 1.0 aproxima
+    Pinch { scale: f64, center: (f64, f64) },
+}
+
+/// Limiares do reconhecedor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSettings {
+    /// Distância máxima (lógica) de um tap; acima dela começa o pan
+    pub tap_max_distance: f64,
+    /// Duração máxima de um tap
+    pub tap_max_duration: Duration,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_distance: 10.0,
+            tap_max_duration: Duration::from_millis(300),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    id: u64,
+    position: (f64, f64),
+}
+
+/// Reconhecedor de gestos
+///
+/// Uma "sequência" vai do primeiro dedo encostar até o último sair; tap só
+/// é reconhecido se a sequência inteira teve um único dedo.
+pub struct GestureRecognizer {
+    settings: GestureSettings,
+    /// Dedos na ordem em que encostaram
+    points: Vec<Point>,
+    sequence_start: Option<(Instant, (f64, f64))>,
+    /// Sequência teve mais de um dedo, foi cancelada ou virou pan
+    tap_failed: bool,
+    panning: bool,
+    last_center: (f64, f64),
+    last_distance: f64,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::with_settings(GestureSettings::default())
+    }
+
+    pub fn with_settings(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            points: Vec::new(),
+            sequence_start: None,
+            tap_failed: false,
+            panning: false,
+            last_center: (0.0, 0.0),
+            last_distance: 0.0,
+        }
+    }
+
+    pub fn settings(&self) -> &GestureSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: GestureSettings) {
+        self.settings = settings;
+    }
+
+    /// Número de dedos na tela
+    pub fn active_touches(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Processa um evento ocorrido em `time`, acrescentando os gestos em `out`
+    pub fn process(&mut self, event: &TouchEvent, time: Instant, out: &mut Vec<Gesture>) {
+        match event.phase {
+            TouchPhase::Started => {
+                if self.points.iter().any(|p| p.id == event.id) {
+                    return;
+                }
+                if self.points.is_empty() {
+                    self.sequence_start = Some((time, event.position));
+                    self.tap_failed = false;
+                    self.panning = false;
+                } else {
+                    self.tap_failed = true;
+                }
+                self.points.push(Point {
+                    id: event.id,
+                    position: event.position,
+                });
+                self.reset_baseline();
+            }
+            TouchPhase::Moved => {
+                let Some(point) = self.points.iter_mut().find(|p| p.id == event.id) else {
+                    return;
+                };
+                point.position = event.position;
+
+                if self.points.len() == 1 && !self.panning {
+                    let Some((_, start)) = self.sequence_start else {
+                        return;
+                    };
+                    if distance(start, event.position) <= self.settings.tap_max_distance {
+                        return;
+                    }
+                    // O primeiro pan cobre o trecho dentro do limiar
+                    self.panning = true;
+                    self.tap_failed = true;
+                    self.last_center = start;
+                }
+                self.emit_motion(out);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                let Some(index) = self.points.iter().position(|p| p.id == event.id) else {
+                    return;
+                };
+                self.points.remove(index);
+
+                if event.phase == TouchPhase::Cancelled {
+                    self.tap_failed = true;
+                } else if self.points.is_empty() && !self.tap_failed {
+                    if let Some((start_time, start)) = self.sequence_start {
+                        let quick = time.saturating_duration_since(start_time)
+                            <= self.settings.tap_max_duration;
+                        let still =
+                            distance(start, event.position) <= self.settings.tap_max_distance;
+                        if quick && still {
+                            out.push(Gesture::Tap {
+                                position: event.position,
+                            });
+                        }
+                    }
+                }
+
+                if self.points.is_empty() {
+                    self.sequence_start = None;
+                    self.panning = false;
+                } else {
+                    self.reset_baseline();
+                }
+            }
+        }
+    }
+
+    /// Esquece todos os dedos (ex.: janela perdeu o foco)
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.sequence_start = None;
+        self.tap_failed = false;
+        self.panning = false;
+    }
+
+    fn emit_motion(&mut self, out: &mut Vec<Gesture>) {
+        let center = self.center();
+        let delta = (center.0 - self.last_center.0, center.1 - self.last_center.1);
+        if delta != (0.0, 0.0) {
+            self.panning = true;
+            out.push(Gesture::Pan {
+                delta,
+                position: center,
+                touches: self.points.len(),
+            });
+        }
+        self.last_center = center;
+
+        if self.points.len() >= 2 {
+            let spread = self.spread();
+            if self.last_distance > 0.0 && spread != self.last_distance {
+                out.push(Gesture::Pinch {
+                    scale: spread / self.last_distance,
+                    center,
+                });
+            }
+            self.last_distance = spread;
+        }
+    }
+
+    /// Recomeça pan/pinch a partir da posição atual quando o número de
+    /// dedos muda, senão o centro "pula"
+    fn reset_baseline(&mut self) {
+        self.last_center = self.center();
+        self.last_distance = self.spread();
+    }
+
+    fn center(&self) -> (f64, f64) {
+        if self.points.is_empty() {
+            return (0.0, 0.0);
+        }
+        let n = self.points.len() as f64;
+        let (x, y) = self
+            .points
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p.position.0, y + p.position.1));
+        (x / n, y / n)
+    }
+
+    fn spread(&self) -> f64 {
+        match self.points.as_slice() {
+            [a, b, ..] => distance(a.position, b.position),
+            _ => 0.0,
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> TouchEvent {
+        TouchEvent::new(id, phase, (x, y))
+    }
+
+    #[test]
+    fn test_tap() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        recognizer.process(&touch(1, TouchPhase::Started, 50.0, 50.0), t0, &mut out);
+        recognizer.process(&touch(1, TouchPhase::Moved, 53.0, 52.0), t0, &mut out);
+        recognizer.process(
+            &touch(1, TouchPhase::Ended, 53.0, 52.0),
+            t0 + Duration::from_millis(100),
+            &mut out,
+        );
+        assert_eq!(
+            out,
+            vec![Gesture::Tap {
+                position: (53.0, 52.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_slow_press_is_not_tap() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        recognizer.process(&touch(1, TouchPhase::Started, 0.0, 0.0), t0, &mut out);
+        recognizer.process(
+            &touch(1, TouchPhase::Ended, 0.0, 0.0),
+            t0 + Duration::from_secs(1),
+            &mut out,
+        );
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_pan() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        recognizer.process(&touch(1, TouchPhase::Started, 0.0, 0.0), t0, &mut out);
+        recognizer.process(&touch(1, TouchPhase::Moved, 5.0, 0.0), t0, &mut out);
+        assert!(out.is_empty());
+
+        recognizer.process(&touch(1, TouchPhase::Moved, 20.0, 0.0), t0, &mut out);
+        recognizer.process(&touch(1, TouchPhase::Moved, 30.0, 5.0), t0, &mut out);
+        recognizer.process(&touch(1, TouchPhase::Ended, 30.0, 5.0), t0, &mut out);
+
+        let total = out.iter().fold((0.0, 0.0), |acc, gesture| match gesture {
+            Gesture::Pan { delta, .. } => (acc.0 + delta.0, acc.1 + delta.1),
+            other => panic!("unexpected gesture {:?}", other),
+        });
+        assert_eq!(total, (30.0, 5.0));
+    }
+
+    #[test]
+    fn test_pinch() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        recognizer.process(&touch(1, TouchPhase::Started, 100.0, 100.0), t0, &mut out);
+        recognizer.process(&touch(2, TouchPhase::Started, 200.0, 100.0), t0, &mut out);
+        assert_eq!(recognizer.active_touches(), 2);
+
+        // Dedos se afastam simetricamente: os pans se anulam
+        recognizer.process(&touch(1, TouchPhase::Moved, 50.0, 100.0), t0, &mut out);
+        recognizer.process(&touch(2, TouchPhase::Moved, 250.0, 100.0), t0, &mut out);
+
+        let scale: f64 = out
+            .iter()
+            .filter_map(|gesture| match gesture {
+                Gesture::Pinch { scale, .. } => Some(*scale),
+                _ => None,
+            })
+            .product();
+        assert!((scale - 2.0).abs() < 1e-9);
+
+        // Terminar com dois dedos nunca gera tap
+        out.clear();
+        recognizer.process(&touch(1, TouchPhase::Ended, 50.0, 100.0), t0, &mut out);
+        recognizer.process(&touch(2, TouchPhase::Ended, 250.0, 100.0), t0, &mut out);
+        assert!(out.is_empty());
+        assert_eq!(recognizer.active_touches(), 0);
+    }
+
+    #[test]
+    fn test_cancelled_is_not_tap() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        recognizer.process(&touch(1, TouchPhase::Started, 0.0, 0.0), t0, &mut out);
+        recognizer.process(&touch(1, TouchPhase::Cancelled, 0.0, 0.0), t0, &mut out);
+        assert!(out.is_empty());
+        assert_eq!(recognizer.active_touches(), 0);
+    }
+}
@@ -0,0 +1,156 @@
+//! Handles nativos da janela
+//!
+//! Ponteiros e ids crus que renderers (o avila-renderer ou crates externos)
+//! precisam para criar superfícies e swapchains. Com a feature
+//! `raw-window-handle`, `Window` também implementa `HasWindowHandle` e
+//! `HasDisplayHandle` da crate `raw-window-handle` 0.6.
+
+use std::ffi::c_void;
+
+/// Handle nativo de uma janela e da conexão com o sistema de janelas
+///
+/// Os ponteiros valem enquanto a `Window` existir e só podem ser usados na
+/// thread que a criou.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeHandle {
+    /// `HWND` e `HINSTANCE` do módulo que registrou a classe
+    Win32 {
+        hwnd: *mut c_void,
+        hinstance: *mut c_void,
+    },
+    /// `xcb_connection_t*`, id da janela e número da tela
+    Xcb {
+        connection: *mut c_void,
+        window: u32,
+        screen: i32,
+    },
+    /// `wl_display*` e `wl_surface*`
+    Wayland {
+        display: *mut c_void,
+        surface: *mut c_void,
+    },
+}
+
+#[cfg(feature = "raw-window-handle")]
+mod raw {
+    use std::num::NonZeroU32;
+    use std::ptr::NonNull;
+
+    use raw_window_handle::{
+        DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle,
+        RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle, Win32WindowHandle,
+        WindowHandle, WindowsDisplayHandle, XcbDisplayHandle, XcbWindowHandle,
+    };
+
+    use super::NativeHandle;
+    use crate::window::Window;
+
+    impl NativeHandle {
+        /// Converte para o `RawWindowHandle` da crate `raw-window-handle`
+        pub fn raw_window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+            match *self {
+                NativeHandle::Win32 { hwnd, hinstance } => {
+                    let hwnd = std::num::NonZeroIsize::new(hwnd as isize)
+                        .ok_or(HandleError::Unavailable)?;
+                    let mut handle = Win32WindowHandle::new(hwnd);
+                    handle.hinstance = std::num::NonZeroIsize::new(hinstance as isize);
+                    Ok(RawWindowHandle::Win32(handle))
+                }
+                NativeHandle::Xcb { window, .. } => {
+                    let window = NonZeroU32::new(window).ok_or(HandleError::Unavailable)?;
+                    Ok(RawWindowHandle::Xcb(XcbWindowHandle::new(window)))
+                }
+                NativeHandle::Wayland { surface, .. } => {
+                    let surface = NonNull::new(surface).ok_or(HandleError::Unavailable)?;
+                    Ok(RawWindowHandle::Wayland(WaylandWindowHandle::new(surface)))
+                }
+            }
+        }
+
+        /// Converte para o `RawDisplayHandle` da crate `raw-window-handle`
+        pub fn raw_display_handle(&self) -> Result<RawDisplayHandle, HandleError> {
+            match *self {
+                NativeHandle::Win32 { .. } => {
+                    Ok(RawDisplayHandle::Windows(WindowsDisplayHandle::new()))
+                }
+                NativeHandle::Xcb {
+                    connection, screen, ..
+                } => Ok(RawDisplayHandle::Xcb(XcbDisplayHandle::new(
+                    NonNull::new(connection),
+                    screen,
+                ))),
+                NativeHandle::Wayland { display, .. } => {
+                    let display = NonNull::new(display).ok_or(HandleError::Unavailable)?;
+                    Ok(RawDisplayHandle::Wayland(WaylandDisplayHandle::new(
+                        display,
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Janelas headless não têm handle (`HandleError::Unavailable`)
+    impl HasWindowHandle for Window {
+        fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+            let raw = self
+                .native_handle()
+                .ok_or(HandleError::Unavailable)?
+                .raw_window_handle()?;
+            // SAFETY: a janela nativa vive enquanto o empréstimo de `self`
+            Ok(unsafe { WindowHandle::borrow_raw(raw) })
+        }
+    }
+
+    impl HasDisplayHandle for Window {
+        fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+            let raw = self
+                .native_handle()
+                .ok_or(HandleError::Unavailable)?
+                .raw_display_handle()?;
+            // SAFETY: a conexão é mantida viva pela janela nativa
+            Ok(unsafe { DisplayHandle::borrow_raw(raw) })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "raw-window-handle")]
+    use super::*;
+    use crate::window::{Window, WindowConfig};
+
+    #[test]
+    fn test_headless_has_no_handle() {
+        let window = Window::headless(WindowConfig::default());
+        assert!(window.native_handle().is_none());
+    }
+
+    #[cfg(feature = "raw-window-handle")]
+    #[test]
+    fn test_raw_handles() {
+        use raw_window_handle::{HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+
+        let window = Window::headless(WindowConfig::default());
+        assert!(window.window_handle().is_err());
+
+        let handle = NativeHandle::Xcb {
+            connection: std::ptr::null_mut(),
+            window: 42,
+            screen: 0,
+        };
+        match handle.raw_window_handle().unwrap() {
+            RawWindowHandle::Xcb(xcb) => assert_eq!(xcb.window.get(), 42),
+            other => panic!("unexpected handle {:?}", other),
+        }
+        assert!(matches!(
+            handle.raw_display_handle().unwrap(),
+            RawDisplayHandle::Xcb(_)
+        ));
+
+        let wayland = NativeHandle::Wayland {
+            display: std::ptr::null_mut(),
+            surface: std::ptr::null_mut(),
+        };
+        assert!(wayland.raw_window_handle().is_err());
+    }
+}
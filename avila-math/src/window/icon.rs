@@ -0,0 +1,76 @@
+//! Ícone da janela (barra de título, taskbar, alt-tab)
+
+use super::WindowError;
+
+/// Ícone em RGBA (8 bits por canal, sem pré-multiplicação)
+///
+/// Os backends redimensionam conforme o uso; 32x32 ou 64x64 cobrem a
+/// maioria dos casos.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowIcon {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl WindowIcon {
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, WindowError> {
+        check_rgba(&rgba, width, height, "icon")?;
+        Ok(Self {
+            rgba,
+            width,
+            height,
+        })
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Valida o tamanho de uma imagem RGBA (ícones e cursores)
+pub(super) fn check_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    what: &str,
+) -> Result<(), WindowError> {
+    if width == 0 || height == 0 || width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(WindowError::InvalidImage(format!(
+            "invalid {} size {}x{}",
+            what, width, height
+        )));
+    }
+    let expected = width as usize * height as usize * 4;
+    if rgba.len() != expected {
+        return Err(WindowError::InvalidImage(format!(
+            "expected {} bytes of RGBA data, got {}",
+            expected,
+            rgba.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_icon() {
+        let icon = WindowIcon::from_rgba(vec![255; 32 * 16 * 4], 32, 16).unwrap();
+        assert_eq!((icon.width(), icon.height()), (32, 16));
+        assert_eq!(icon.rgba().len(), 2048);
+
+        assert!(WindowIcon::from_rgba(vec![0; 15], 2, 2).is_err());
+        assert!(WindowIcon::from_rgba(Vec::new(), 0, 0).is_err());
+    }
+}
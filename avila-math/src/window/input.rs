@@ -1,8 +1,17 @@
-//! Sistema de input (teclado e mouse)
+//! Sistema de input (teclado, mouse e touch)
 //!
 //! Define teclas, botões do mouse e estados de input
 
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::events::{TouchEvent, TouchPhase};
+use super::gesture::{Gesture, GestureRecognizer, GestureSettings};
 
 /// Representa uma tecla ou código de tecla
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -15,6 +24,7 @@ pub enum Key {
 
 /// Códigos de teclas (baseado em layout físico)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum KeyCode {
     // Letras
     A,
@@ -264,6 +274,7 @@ impl KeyCode {
 
 /// Botões do mouse
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MouseButton {
     Left,
     Right,
@@ -360,61 +371,180 @@ impl ModifierKeys {
     }
 }
 
-/// Estado do input (rastreia teclas e botões pressionados)
+/// Dedo na tela
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Touch {
+    pub id: u64,
+    pub position: (f64, f64),
+    /// Onde o dedo encostou
+    pub start_position: (f64, f64),
+    pub pressure: f32,
+}
+
+/// Estado de teclas ou botões com as transições do frame atual
+struct Transitions<T> {
+    /// Entradas pressionadas e o instante em que foram pressionadas
+    held: HashMap<T, Instant>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash> Transitions<T> {
+    fn new() -> Self {
+        Self {
+            held: HashMap::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+
+    /// Auto-repeat não conta como nova transição
+    fn press(&mut self, input: T, time: Instant) {
+        if let Entry::Vacant(entry) = self.held.entry(input) {
+            entry.insert(time);
+            self.pressed.insert(input);
+        }
+    }
+
+    fn release(&mut self, input: T) {
+        if self.held.remove(&input).is_some() {
+            self.released.insert(input);
+        }
+    }
+
+    fn is_held(&self, input: T) -> bool {
+        self.held.contains_key(&input)
+    }
+
+    fn hold_duration(&self, input: T, now: Instant) -> Option<Duration> {
+        self.held
+            .get(&input)
+            .map(|since| now.saturating_duration_since(*since))
+    }
+
+    fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    fn clear(&mut self) {
+        self.held.clear();
+        self.end_frame();
+    }
+}
+
+/// Estado do input (rastreia teclas, botões e toques)
+///
+/// Além do estado atual, guarda as transições desde o último `end_frame`:
+/// uma tecla pressionada e solta no mesmo frame aparece nas duas.
 pub struct InputState {
-    pressed_keys: HashSet<Key>,
-    pressed_buttons: HashSet<MouseButton>,
+    keys: Transitions<Key>,
+    buttons: Transitions<MouseButton>,
     cursor_position: (f64, f64),
     scroll_delta: (f64, f64),
     modifiers: ModifierKeys,
+    touches: Vec<Touch>,
+    recognizer: GestureRecognizer,
+    gestures: Vec<Gesture>,
 }
 
 impl InputState {
     pub fn new() -> Self {
         Self {
-            pressed_keys: HashSet::new(),
-            pressed_buttons: HashSet::new(),
+            keys: Transitions::new(),
+            buttons: Transitions::new(),
             cursor_position: (0.0, 0.0),
             scroll_delta: (0.0, 0.0),
             modifiers: ModifierKeys::empty(),
+            touches: Vec::new(),
+            recognizer: GestureRecognizer::new(),
+            gestures: Vec::new(),
         }
     }
 
     /// Marca uma tecla como pressionada
     pub fn press_key(&mut self, key: Key) {
-        self.pressed_keys.insert(key);
+        self.keys.press(key, Instant::now());
         self.update_modifiers_from_key(key, true);
     }
 
     /// Marca uma tecla como solta
     pub fn release_key(&mut self, key: Key) {
-        self.pressed_keys.remove(&key);
+        self.keys.release(key);
         self.update_modifiers_from_key(key, false);
     }
 
     /// Verifica se uma tecla está pressionada
     pub fn is_key_pressed(&self, key: Key) -> bool {
-        self.pressed_keys.contains(&key)
+        self.keys.is_held(key)
     }
 
     /// Verifica se um código de tecla está pressionado
     pub fn is_keycode_pressed(&self, keycode: KeyCode) -> bool {
-        self.pressed_keys.contains(&Key::Code(keycode))
+        self.keys.is_held(Key::Code(keycode))
+    }
+
+    /// A tecla foi pressionada desde o último `end_frame`
+    pub fn was_pressed_this_frame(&self, key: Key) -> bool {
+        self.keys.pressed.contains(&key)
+    }
+
+    /// A tecla foi solta desde o último `end_frame`
+    pub fn was_released_this_frame(&self, key: Key) -> bool {
+        self.keys.released.contains(&key)
+    }
+
+    pub fn was_keycode_pressed_this_frame(&self, keycode: KeyCode) -> bool {
+        self.was_pressed_this_frame(Key::Code(keycode))
+    }
+
+    pub fn was_keycode_released_this_frame(&self, keycode: KeyCode) -> bool {
+        self.was_released_this_frame(Key::Code(keycode))
+    }
+
+    /// Há quanto tempo a tecla está pressionada (`None` se estiver solta)
+    pub fn key_hold_duration(&self, key: Key) -> Option<Duration> {
+        self.keys.hold_duration(key, Instant::now())
+    }
+
+    /// Teclas pressionadas, em ordem arbitrária
+    pub fn pressed_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.keys.held.keys().copied()
     }
 
     /// Marca um botão do mouse como pressionado
     pub fn press_button(&mut self, button: MouseButton) {
-        self.pressed_buttons.insert(button);
+        self.buttons.press(button, Instant::now());
     }
 
     /// Marca um botão do mouse como solto
     pub fn release_button(&mut self, button: MouseButton) {
-        self.pressed_buttons.remove(&button);
+        self.buttons.release(button);
     }
 
     /// Verifica se um botão do mouse está pressionado
     pub fn is_button_pressed(&self, button: MouseButton) -> bool {
-        self.pressed_buttons.contains(&button)
+        self.buttons.is_held(button)
+    }
+
+    /// O botão foi pressionado desde o último `end_frame`
+    pub fn was_button_pressed_this_frame(&self, button: MouseButton) -> bool {
+        self.buttons.pressed.contains(&button)
+    }
+
+    /// O botão foi solto desde o último `end_frame`
+    pub fn was_button_released_this_frame(&self, button: MouseButton) -> bool {
+        self.buttons.released.contains(&button)
+    }
+
+    /// Há quanto tempo o botão está pressionado (`None` se estiver solto)
+    pub fn button_hold_duration(&self, button: MouseButton) -> Option<Duration> {
+        self.buttons.hold_duration(button, Instant::now())
+    }
+
+    /// Botões do mouse pressionados, em ordem arbitrária
+    pub fn pressed_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.buttons.held.keys().copied()
     }
 
     /// Define a posição do cursor
@@ -447,12 +577,83 @@ impl InputState {
         self.modifiers
     }
 
+    /// Atualiza os toques e o reconhecimento de gestos
+    pub fn handle_touch(&mut self, event: &TouchEvent) {
+        self.handle_touch_at(event, Instant::now());
+    }
+
+    /// Como `handle_touch`, com o instante do evento explícito
+    pub fn handle_touch_at(&mut self, event: &TouchEvent, time: Instant) {
+        let index = self.touches.iter().position(|t| t.id == event.id);
+        match (event.phase, index) {
+            (TouchPhase::Started, None) => self.touches.push(Touch {
+                id: event.id,
+                position: event.position,
+                start_position: event.position,
+                pressure: event.pressure,
+            }),
+            (TouchPhase::Moved, Some(index)) => {
+                let touch = &mut self.touches[index];
+                touch.position = event.position;
+                touch.pressure = event.pressure;
+            }
+            (TouchPhase::Ended | TouchPhase::Cancelled, Some(index)) => {
+                self.touches.remove(index);
+            }
+            _ => {}
+        }
+        self.recognizer.process(event, time, &mut self.gestures);
+    }
+
+    /// Dedos na tela, na ordem em que encostaram
+    pub fn touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
+    /// Estado de um dedo
+    pub fn touch(&self, id: u64) -> Option<&Touch> {
+        self.touches.iter().find(|t| t.id == id)
+    }
+
+    /// Número de dedos na tela
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+
+    /// Gestos reconhecidos desde o último `clear_gestures`
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    /// Descarta os gestos acumulados (deve ser chamado a cada frame)
+    pub fn clear_gestures(&mut self) {
+        self.gestures.clear();
+    }
+
+    /// Limiares do reconhecimento de gestos
+    pub fn set_gesture_settings(&mut self, settings: GestureSettings) {
+        self.recognizer.set_settings(settings);
+    }
+
+    /// Fecha o frame: descarta transições, scroll e gestos acumulados
+    ///
+    /// Chame no fim de cada frame, depois da lógica que consulta o input.
+    pub fn end_frame(&mut self) {
+        self.keys.end_frame();
+        self.buttons.end_frame();
+        self.reset_scroll_delta();
+        self.clear_gestures();
+    }
+
     /// Limpa todo o estado
     pub fn clear(&mut self) {
-        self.pressed_keys.clear();
-        self.pressed_buttons.clear();
+        self.keys.clear();
+        self.buttons.clear();
         self.scroll_delta = (0.0, 0.0);
         self.modifiers = ModifierKeys::empty();
+        self.touches.clear();
+        self.recognizer.reset();
+        self.gestures.clear();
     }
 
     fn update_modifiers_from_key(&mut self, key: Key, pressed: bool) {
@@ -566,6 +767,82 @@ mod tests {
         assert_eq!(state.cursor_position(), (100.0, 200.0));
     }
 
+    #[test]
+    fn test_frame_transitions() {
+        let mut state = InputState::new();
+        let space = Key::Code(KeyCode::Space);
+
+        state.press_key(space);
+        assert!(state.was_pressed_this_frame(space));
+        assert!(state.key_hold_duration(space).is_some());
+
+        // Auto-repeat no frame seguinte não é uma nova transição
+        state.end_frame();
+        state.press_key(space);
+        assert!(!state.was_keycode_pressed_this_frame(KeyCode::Space));
+        assert!(state.is_key_pressed(space));
+
+        state.release_key(space);
+        assert!(state.was_keycode_released_this_frame(KeyCode::Space));
+        assert_eq!(state.key_hold_duration(space), None);
+
+        state.end_frame();
+        assert!(!state.was_released_this_frame(space));
+    }
+
+    #[test]
+    fn test_click_within_one_frame() {
+        let mut state = InputState::new();
+
+        state.press_button(MouseButton::Left);
+        state.release_button(MouseButton::Left);
+        assert!(state.was_button_pressed_this_frame(MouseButton::Left));
+        assert!(state.was_button_released_this_frame(MouseButton::Left));
+        assert!(!state.is_button_pressed(MouseButton::Left));
+        assert_eq!(state.button_hold_duration(MouseButton::Left), None);
+
+        // Soltar sem ter pressionado (ex.: foco chegou no meio do clique)
+        state.end_frame();
+        state.release_button(MouseButton::Right);
+        assert!(!state.was_button_released_this_frame(MouseButton::Right));
+    }
+
+    #[test]
+    fn test_touch_tracking() {
+        let mut state = InputState::new();
+
+        state.handle_touch(&TouchEvent::new(1, TouchPhase::Started, (10.0, 10.0)));
+        state.handle_touch(&TouchEvent::new(2, TouchPhase::Started, (50.0, 10.0)));
+        state.handle_touch(&TouchEvent::new(1, TouchPhase::Moved, (12.0, 14.0)).with_pressure(0.5));
+        assert_eq!(state.touch_count(), 2);
+
+        let touch = state.touch(1).unwrap();
+        assert_eq!(touch.position, (12.0, 14.0));
+        assert_eq!(touch.start_position, (10.0, 10.0));
+        assert_eq!(touch.pressure, 0.5);
+
+        state.handle_touch(&TouchEvent::new(1, TouchPhase::Ended, (12.0, 14.0)));
+        assert_eq!(state.touch_count(), 1);
+        assert_eq!(state.touches()[0].id, 2);
+    }
+
+    #[test]
+    fn test_touch_gestures() {
+        let mut state = InputState::new();
+
+        state.handle_touch(&TouchEvent::new(7, TouchPhase::Started, (30.0, 40.0)));
+        state.handle_touch(&TouchEvent::new(7, TouchPhase::Ended, (30.0, 40.0)));
+        assert_eq!(
+            state.gestures(),
+            &[Gesture::Tap {
+                position: (30.0, 40.0)
+            }]
+        );
+
+        state.clear_gestures();
+        assert!(state.gestures().is_empty());
+    }
+
     #[test]
     fn test_scroll_delta() {
         let mut state = InputState::new();
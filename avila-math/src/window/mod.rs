@@ -4,20 +4,47 @@
 //! - Criação e gerenciamento de janelas
 //! - Fullscreen e modos de display
 //! - Eventos (input, resize, close, etc)
-//! - Input de teclado e mouse
-//! - Cursor management
+//! - Input de teclado, mouse, touch e gamepads
+//! - Mapeamento de ações (bindings reconfiguráveis)
+//! - Cursor (ícones do sistema, imagens customizadas e captura)
+//! - Ícone, opacidade e janela sempre no topo
 //! - Multi-monitor support
+//! - Persistência de posição, tamanho e modo entre execuções
 
 use std::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub mod action;
+pub mod cursor;
 pub mod events;
+pub mod gamepad;
+pub mod gesture;
+mod handle;
+pub mod icon;
 pub mod input;
-
-pub use events::{Event, EventLoop, KeyEvent, KeyState, MouseEvent, WindowEvent};
-pub use input::{InputState, Key, KeyCode, ModifierKeys, MouseButton};
+mod platform;
+pub mod state;
+
+pub use action::{ActionBinding, ActionMap, AxisDirection, InputBinding};
+pub use cursor::{CursorGrabMode, CursorIcon, CursorImage};
+pub use events::{
+    ControlFlow, Event, EventLoop, KeyEvent, KeyState, MouseEvent, TouchEvent, TouchPhase,
+    WindowEvent,
+};
+pub use gamepad::{
+    Gamepad, GamepadAxis, GamepadButton, GamepadEvent, GamepadId, GamepadSettings, Gamepads,
+};
+pub use gesture::{Gesture, GestureRecognizer, GestureSettings};
+pub use handle::NativeHandle;
+pub use icon::WindowIcon;
+pub use input::{InputState, Key, KeyCode, ModifierKeys, MouseButton, Touch};
+pub use state::WindowState;
 
 /// Posição da janela
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WindowPosition {
     pub x: i32,
     pub y: i32,
@@ -36,6 +63,7 @@ impl WindowPosition {
 
 /// Tamanho da janela
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WindowSize {
     pub width: u32,
     pub height: u32,
@@ -49,10 +77,62 @@ impl WindowSize {
     pub fn aspect_ratio(&self) -> f32 {
         self.width as f32 / self.height as f32
     }
+
+    /// Converte um tamanho lógico em pixels físicos
+    pub fn to_physical(&self, scale_factor: f32) -> WindowSize {
+        self.scaled(scale_factor as f64)
+    }
+
+    /// Converte um tamanho em pixels físicos em lógico
+    pub fn to_logical(&self, scale_factor: f32) -> WindowSize {
+        self.scaled(1.0 / scale_factor as f64)
+    }
+
+    fn scaled(&self, factor: f64) -> WindowSize {
+        WindowSize::new(
+            ((self.width as f64 * factor).round() as u32).max(1),
+            ((self.height as f64 * factor).round() as u32).max(1),
+        )
+    }
+}
+
+/// Unidade dos tamanhos do `WindowConfig` (`size`, `min_size`, `max_size`)
+/// e de `Window::set_size`
+///
+/// Eventos e `Window::size` são sempre lógicos; `FramebufferResized` e
+/// `Window::physical_size` são sempre físicos, independente da política.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScalePolicy {
+    /// Pixels lógicos: a janela acompanha a escala do sistema (1280x720 a
+    /// 150% ocupa 1920x1080 pixels) e é redimensionada quando ela muda
+    #[default]
+    Logical,
+    /// Pixels físicos: a janela mantém o tamanho em pixels quando a escala
+    /// muda (jogos que renderizam numa resolução fixa)
+    Physical,
+}
+
+impl ScalePolicy {
+    /// Tamanho em pixels físicos de um tamanho nesta unidade
+    pub fn to_physical(self, size: WindowSize, scale_factor: f32) -> WindowSize {
+        match self {
+            Self::Logical => size.to_physical(scale_factor),
+            Self::Physical => size,
+        }
+    }
+
+    /// Tamanho lógico de um tamanho nesta unidade
+    pub fn to_logical(self, size: WindowSize, scale_factor: f32) -> WindowSize {
+        match self {
+            Self::Logical => size,
+            Self::Physical => size.to_logical(scale_factor),
+        }
+    }
 }
 
 /// Modo de exibição da janela
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DisplayMode {
     /// Janela normal com bordas e barra de título
     Windowed,
@@ -77,6 +157,7 @@ pub struct WindowConfig {
     pub vsync: bool,
     pub min_size: Option<WindowSize>,
     pub max_size: Option<WindowSize>,
+    pub scale_policy: ScalePolicy,
 }
 
 impl Default for WindowConfig {
@@ -92,6 +173,7 @@ impl Default for WindowConfig {
             vsync: true,
             min_size: None,
             max_size: None,
+            scale_policy: ScalePolicy::Logical,
         }
     }
 }
@@ -138,6 +220,12 @@ impl WindowConfig {
         self.vsync = vsync;
         self
     }
+
+    /// Define em que unidade os tamanhos da configuração são dados
+    pub fn with_scale_policy(mut self, policy: ScalePolicy) -> Self {
+        self.scale_policy = policy;
+        self
+    }
 }
 
 /// Informações do monitor
@@ -152,26 +240,49 @@ pub struct MonitorInfo {
 }
 
 /// Handle da janela (abstração cross-platform)
+///
+/// Em plataformas com backend nativo (Win32, X11, Wayland) `new` abre uma
+/// janela real, presa à thread que a criou; nas demais, e em `headless`, a
+/// janela é só estado em memória.
 pub struct Window {
     config: WindowConfig,
     is_open: bool,
     is_focused: bool,
     cursor_visible: bool,
+    /// `None` enquanto um `CursorImage` estiver ativo
+    cursor_icon: Option<CursorIcon>,
+    cursor_grab: CursorGrabMode,
+    raw_mouse_motion: bool,
     cursor_position: (f64, f64),
+    opacity: f32,
+    always_on_top: bool,
+    native: Option<platform::NativeWindow>,
 }
 
 impl Window {
     /// Cria uma nova janela
     pub fn new(config: WindowConfig) -> Result<Self, WindowError> {
-        // Em uma implementação real, aqui criaria a janela nativa
-        // (Win32 API, X11, Wayland, Cocoa, etc.)
-        Ok(Self {
+        let native = platform::NativeWindow::create(&config)?;
+        let mut window = Self::headless(config);
+        window.native = native;
+        Ok(window)
+    }
+
+    /// Cria uma janela sem janela nativa (testes, servidores)
+    pub fn headless(config: WindowConfig) -> Self {
+        Self {
             config,
             is_open: true,
             is_focused: true,
             cursor_visible: true,
+            cursor_icon: Some(CursorIcon::Default),
+            cursor_grab: CursorGrabMode::None,
+            raw_mouse_motion: false,
             cursor_position: (0.0, 0.0),
-        })
+            opacity: 1.0,
+            always_on_top: false,
+            native: None,
+        }
     }
 
     /// Cria uma janela com configuração padrão
@@ -179,24 +290,41 @@ impl Window {
         Self::new(WindowConfig::default())
     }
 
+    /// Verifica se a janela é só estado em memória, sem janela nativa
+    pub fn is_headless(&self) -> bool {
+        self.native.is_none()
+    }
+
+    /// Handle nativo para criar superfícies gráficas (`None` se headless)
+    pub fn native_handle(&self) -> Option<NativeHandle> {
+        self.native.as_ref().map(|native| native.native_handle())
+    }
+
     /// Verifica se a janela está aberta
     pub fn is_open(&self) -> bool {
         self.is_open
     }
 
-    /// Fecha a janela
+    /// Fecha a janela, destruindo a janela nativa
     pub fn close(&mut self) {
+        self.native = None;
         self.is_open = false;
     }
 
     /// Verifica se a janela tem foco
     pub fn is_focused(&self) -> bool {
-        self.is_focused
+        match &self.native {
+            Some(native) => native.is_focused(),
+            None => self.is_focused,
+        }
     }
 
     /// Define o foco da janela
     pub fn set_focused(&mut self, focused: bool) {
         self.is_focused = focused;
+        if let (true, Some(native)) = (focused, &self.native) {
+            native.focus();
+        }
     }
 
     /// Retorna o título da janela
@@ -207,14 +335,33 @@ impl Window {
     /// Define o título da janela
     pub fn set_title(&mut self, title: impl Into<String>) {
         self.config.title = title.into();
+        if let Some(native) = &self.native {
+            native.set_title(&self.config.title);
+        }
     }
 
-    /// Retorna o tamanho da janela
+    /// Retorna o tamanho lógico da área cliente
     pub fn size(&self) -> WindowSize {
-        self.config.size
+        match &self.native {
+            Some(native) => native.inner_size(),
+            None => self.config.size,
+        }
+    }
+
+    /// Retorna o tamanho da área cliente em pixels (tamanho do framebuffer)
+    pub fn physical_size(&self) -> WindowSize {
+        match &self.native {
+            Some(native) => native.physical_inner_size(),
+            None => self.config.size,
+        }
     }
 
-    /// Define o tamanho da janela
+    /// Política de escala da janela
+    pub fn scale_policy(&self) -> ScalePolicy {
+        self.config.scale_policy
+    }
+
+    /// Define o tamanho da janela, na unidade da `ScalePolicy`
     pub fn set_size(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
         if let Some(min) = self.config.min_size {
             if width < min.width || height < min.height {
@@ -227,22 +374,34 @@ impl Window {
             }
         }
         self.config.size = WindowSize::new(width, height);
+        if let Some(native) = &self.native {
+            native.set_inner_size(self.config.size);
+        }
         Ok(())
     }
 
     /// Retorna a posição da janela
     pub fn position(&self) -> WindowPosition {
-        self.config.position
+        match &self.native {
+            Some(native) => native.outer_position(),
+            None => self.config.position,
+        }
     }
 
     /// Define a posição da janela
     pub fn set_position(&mut self, x: i32, y: i32) {
         self.config.position = WindowPosition::new(x, y);
+        if let Some(native) = &self.native {
+            native.set_outer_position(self.config.position);
+        }
     }
 
     /// Centraliza a janela no monitor
     pub fn center(&mut self) {
         self.config.position = WindowPosition::CENTERED;
+        if let Some(native) = &self.native {
+            native.center();
+        }
     }
 
     /// Retorna o modo de exibição
@@ -252,6 +411,9 @@ impl Window {
 
     /// Define o modo de exibição
     pub fn set_display_mode(&mut self, mode: DisplayMode) -> Result<(), WindowError> {
+        if let Some(native) = &self.native {
+            native.set_display_mode(mode)?;
+        }
         self.config.display_mode = mode;
         Ok(())
     }
@@ -286,7 +448,9 @@ impl Window {
 
     /// Minimiza a janela
     pub fn minimize(&mut self) {
-        // Implementação específica da plataforma
+        if let Some(native) = &self.native {
+            native.minimize();
+        }
     }
 
     /// Restaura o tamanho normal da janela
@@ -297,11 +461,17 @@ impl Window {
     /// Mostra o cursor
     pub fn show_cursor(&mut self) {
         self.cursor_visible = true;
+        if let Some(native) = &self.native {
+            native.set_cursor_visible(true);
+        }
     }
 
     /// Esconde o cursor
     pub fn hide_cursor(&mut self) {
         self.cursor_visible = false;
+        if let Some(native) = &self.native {
+            native.set_cursor_visible(false);
+        }
     }
 
     /// Verifica se o cursor está visível
@@ -312,16 +482,136 @@ impl Window {
     /// Define a posição do cursor
     pub fn set_cursor_position(&mut self, x: f64, y: f64) {
         self.cursor_position = (x, y);
+        if let Some(native) = &self.native {
+            native.set_cursor_position(x, y);
+        }
     }
 
     /// Retorna a posição do cursor
     pub fn cursor_position(&self) -> (f64, f64) {
-        self.cursor_position
+        match &self.native {
+            Some(native) => native.cursor_position(),
+            None => self.cursor_position,
+        }
+    }
+
+    /// Usa um cursor do sistema
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = Some(icon);
+        if let Some(native) = &self.native {
+            native.set_cursor_icon(icon);
+        }
+    }
+
+    /// Cursor do sistema atual (`None` se um cursor customizado estiver ativo)
+    pub fn cursor_icon(&self) -> Option<CursorIcon> {
+        self.cursor_icon
+    }
+
+    /// Usa um cursor customizado; `set_cursor_icon` volta aos do sistema
+    pub fn set_cursor_image(&mut self, image: &CursorImage) -> Result<(), WindowError> {
+        if let Some(native) = &self.native {
+            native.set_cursor_image(image)?;
+        }
+        self.cursor_icon = None;
+        Ok(())
+    }
+
+    /// Define se o cursor fica livre, preso à área cliente ou travado
+    ///
+    /// Retorna erro se a plataforma não suportar o modo; nesse caso o modo
+    /// anterior continua valendo.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        if let Some(native) = &self.native {
+            native.set_cursor_grab(mode)?;
+        }
+        self.cursor_grab = mode;
+        Ok(())
+    }
+
+    /// Modo de captura atual
+    pub fn cursor_grab_mode(&self) -> CursorGrabMode {
+        self.cursor_grab
     }
 
-    /// Captura o cursor (trava na janela)
+    /// Atalho para `set_cursor_grab`: `Confined` ou `None`
     pub fn grab_cursor(&mut self, grab: bool) {
-        // Implementação específica da plataforma
+        let mode = if grab {
+            CursorGrabMode::Confined
+        } else {
+            CursorGrabMode::None
+        };
+        // Sem suporte o cursor só continua livre
+        let _ = self.set_cursor_grab(mode);
+    }
+
+    /// Verifica se o cursor está capturado (`Confined` ou `Locked`)
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.cursor_grab != CursorGrabMode::None
+    }
+
+    /// Liga `MouseEvent::RawMotion` enquanto a janela tiver o foco
+    ///
+    /// Os deltas de `CursorMoved` passam pela aceleração do sistema; para
+    /// câmera FPS use os crus, normalmente junto com `CursorGrabMode::Locked`.
+    /// Retorna erro se a plataforma não tiver input cru.
+    pub fn set_raw_mouse_motion(&mut self, enabled: bool) -> Result<(), WindowError> {
+        if let Some(native) = &self.native {
+            native.set_raw_mouse_motion(enabled)?;
+        }
+        self.raw_mouse_motion = enabled;
+        Ok(())
+    }
+
+    /// Verifica se `MouseEvent::RawMotion` está ligado
+    pub fn is_raw_mouse_motion(&self) -> bool {
+        self.raw_mouse_motion
+    }
+
+    /// Define o ícone da janela; `None` volta ao ícone padrão
+    pub fn set_icon(&mut self, icon: Option<&WindowIcon>) {
+        if let Some(native) = &self.native {
+            native.set_icon(icon);
+        }
+    }
+
+    /// Define a opacidade da janela inteira (0.0 = invisível, 1.0 = opaca)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        let opacity = if opacity.is_nan() {
+            1.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+        self.opacity = opacity;
+        if let Some(native) = &self.native {
+            native.set_opacity(opacity);
+        }
+    }
+
+    /// Opacidade atual
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Mantém a janela acima das demais (overlays, ferramentas)
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.always_on_top = always_on_top;
+        if let Some(native) = &self.native {
+            native.set_always_on_top(always_on_top);
+        }
+    }
+
+    /// Verifica se a janela está sempre no topo
+    pub fn is_always_on_top(&self) -> bool {
+        self.always_on_top
+    }
+
+    /// Fator de escala do monitor atual (1.0 = 96 DPI)
+    pub fn scale_factor(&self) -> f32 {
+        match &self.native {
+            Some(native) => native.scale_factor(),
+            None => 1.0,
+        }
     }
 
     /// Ativa/desativa VSync
@@ -336,21 +626,25 @@ impl Window {
 
     /// Solicita atenção do usuário (taskbar flash, etc)
     pub fn request_attention(&self) {
-        // Implementação específica da plataforma
+        if let Some(native) = &self.native {
+            native.request_attention();
+        }
     }
 
     /// Lista todos os monitores disponíveis
     pub fn available_monitors() -> Vec<MonitorInfo> {
-        // Implementação específica da plataforma
-        // Por enquanto retorna um monitor fictício
-        vec![MonitorInfo {
-            name: "Primary Monitor".to_string(),
-            size: WindowSize::new(1920, 1080),
-            position: WindowPosition::new(0, 0),
-            refresh_rate: 60,
-            scale_factor: 1.0,
-            is_primary: true,
-        }]
+        match platform::available_monitors() {
+            Some(monitors) if !monitors.is_empty() => monitors,
+            // Sem backend nativo retorna um monitor fictício
+            _ => vec![MonitorInfo {
+                name: "Primary Monitor".to_string(),
+                size: WindowSize::new(1920, 1080),
+                position: WindowPosition::new(0, 0),
+                refresh_rate: 60,
+                scale_factor: 1.0,
+                is_primary: true,
+            }],
+        }
     }
 
     /// Retorna o monitor primário
@@ -362,13 +656,74 @@ impl Window {
 
     /// Retorna o monitor atual
     pub fn current_monitor(&self) -> Option<MonitorInfo> {
-        Self::primary_monitor()
+        self.native
+            .as_ref()
+            .and_then(|native| native.current_monitor())
+            .or_else(Self::primary_monitor)
     }
 
     /// Move a janela para outro monitor
     pub fn move_to_monitor(&mut self, monitor: &MonitorInfo) {
         self.set_position(monitor.position.x, monitor.position.y);
     }
+
+    /// Captura posição, tamanho, monitor e modo para reabrir a janela igual
+    ///
+    /// Maximizada ou em fullscreen, guarda a última geometria em janela
+    /// conhecida (a do `WindowConfig` ou de `set_size`/`set_position`).
+    pub fn save_state(&self) -> WindowState {
+        let display_mode = match (&self.native, self.config.display_mode) {
+            // O usuário pode maximizar/restaurar pela barra de título
+            (Some(native), DisplayMode::Windowed | DisplayMode::Maximized) => {
+                if native.is_maximized() {
+                    DisplayMode::Maximized
+                } else {
+                    DisplayMode::Windowed
+                }
+            }
+            (_, mode) => mode,
+        };
+        let (position, size) = if display_mode == DisplayMode::Windowed {
+            let size = match self.config.scale_policy {
+                ScalePolicy::Logical => self.size(),
+                ScalePolicy::Physical => self.physical_size(),
+            };
+            (self.position(), size)
+        } else {
+            (self.config.position, self.config.size)
+        };
+        WindowState {
+            position,
+            size,
+            monitor: self.current_monitor().map(|monitor| monitor.name),
+            display_mode,
+        }
+    }
+
+    /// Reaplica um estado salvo por `save_state`
+    ///
+    /// Se a posição não cair mais em nenhum monitor a janela vai para o
+    /// monitor salvo ou, sem ele, é centralizada.
+    pub fn restore_state(&mut self, state: &WindowState) -> Result<(), WindowError> {
+        if self.config.display_mode != DisplayMode::Windowed {
+            self.set_display_mode(DisplayMode::Windowed)?;
+        }
+        self.set_size(state.size.width, state.size.height)?;
+
+        let monitors = Self::available_monitors();
+        if let Some(position) = state.visible_position(&monitors) {
+            self.set_position(position.x, position.y);
+        } else if let Some(monitor) = state.find_monitor(&monitors) {
+            self.move_to_monitor(monitor);
+        } else {
+            self.center();
+        }
+
+        if state.display_mode != DisplayMode::Windowed {
+            self.set_display_mode(state.display_mode)?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for Window {
@@ -384,6 +739,7 @@ pub enum WindowError {
     CreationFailed(String),
     InvalidSize,
     InvalidPosition,
+    InvalidImage(String),
     DisplayModeNotSupported,
     MonitorNotFound,
     PlatformError(String),
@@ -395,6 +751,7 @@ impl fmt::Display for WindowError {
             Self::CreationFailed(msg) => write!(f, "Window creation failed: {}", msg),
             Self::InvalidSize => write!(f, "Invalid window size"),
             Self::InvalidPosition => write!(f, "Invalid window position"),
+            Self::InvalidImage(msg) => write!(f, "Invalid image: {}", msg),
             Self::DisplayModeNotSupported => write!(f, "Display mode not supported"),
             Self::MonitorNotFound => write!(f, "Monitor not found"),
             Self::PlatformError(msg) => write!(f, "Platform error: {}", msg),
@@ -414,7 +771,7 @@ mod tests {
             .with_size(800, 600)
             .resizable(false);
 
-        let window = Window::new(config).unwrap();
+        let window = Window::headless(config);
         assert!(window.is_open());
         assert_eq!(window.title(), "Test Window");
         assert_eq!(window.size().width, 800);
@@ -423,7 +780,7 @@ mod tests {
 
     #[test]
     fn test_window_display_modes() {
-        let mut window = Window::default_window().unwrap();
+        let mut window = Window::headless(WindowConfig::default());
 
         assert!(!window.is_fullscreen());
 
@@ -437,7 +794,7 @@ mod tests {
 
     #[test]
     fn test_window_size() {
-        let mut window = Window::default_window().unwrap();
+        let mut window = Window::headless(WindowConfig::default());
 
         window.set_size(1920, 1080).unwrap();
         let size = window.size();
@@ -447,7 +804,7 @@ mod tests {
 
     #[test]
     fn test_cursor_management() {
-        let mut window = Window::default_window().unwrap();
+        let mut window = Window::headless(WindowConfig::default());
 
         assert!(window.is_cursor_visible());
 
@@ -456,6 +813,91 @@ mod tests {
 
         window.show_cursor();
         assert!(window.is_cursor_visible());
+
+        assert_eq!(window.cursor_icon(), Some(CursorIcon::Default));
+        window.set_cursor_icon(CursorIcon::Pointer);
+        assert_eq!(window.cursor_icon(), Some(CursorIcon::Pointer));
+
+        let image = CursorImage::from_rgba(vec![0; 4 * 4 * 4], 4, 4, (0, 0)).unwrap();
+        window.set_cursor_image(&image).unwrap();
+        assert_eq!(window.cursor_icon(), None);
+    }
+
+    #[test]
+    fn test_cursor_grab_modes() {
+        let mut window = Window::headless(WindowConfig::default());
+        assert_eq!(window.cursor_grab_mode(), CursorGrabMode::None);
+
+        window.set_cursor_grab(CursorGrabMode::Locked).unwrap();
+        assert!(window.is_cursor_grabbed());
+
+        window.grab_cursor(false);
+        assert_eq!(window.cursor_grab_mode(), CursorGrabMode::None);
+        window.grab_cursor(true);
+        assert_eq!(window.cursor_grab_mode(), CursorGrabMode::Confined);
+
+        assert!(!window.is_raw_mouse_motion());
+        window.set_raw_mouse_motion(true).unwrap();
+        assert!(window.is_raw_mouse_motion());
+    }
+
+    #[test]
+    fn test_window_appearance() {
+        let mut window = Window::headless(WindowConfig::default());
+        assert_eq!(window.opacity(), 1.0);
+        assert!(!window.is_always_on_top());
+
+        window.set_opacity(0.5);
+        assert_eq!(window.opacity(), 0.5);
+        window.set_opacity(2.0);
+        assert_eq!(window.opacity(), 1.0);
+        window.set_opacity(f32::NAN);
+        assert_eq!(window.opacity(), 1.0);
+
+        window.set_always_on_top(true);
+        assert!(window.is_always_on_top());
+
+        let icon = WindowIcon::from_rgba(vec![0; 16 * 16 * 4], 16, 16).unwrap();
+        window.set_icon(Some(&icon));
+        window.set_icon(None);
+    }
+
+    #[test]
+    fn test_headless_window() {
+        let mut window = Window::headless(WindowConfig::new("Server"));
+        assert!(window.is_headless());
+        assert_eq!(window.scale_factor(), 1.0);
+
+        window.grab_cursor(true);
+        assert!(window.is_cursor_grabbed());
+        window.set_cursor_position(10.0, 20.0);
+        assert_eq!(window.cursor_position(), (10.0, 20.0));
+
+        window.close();
+        assert!(!window.is_open());
+    }
+
+    #[test]
+    fn test_scale_policy() {
+        let size = WindowSize::new(1280, 720);
+        assert_eq!(size.to_physical(1.25), WindowSize::new(1600, 900));
+        assert_eq!(size.to_physical(1.5).to_logical(1.5), size);
+
+        let logical = ScalePolicy::Logical;
+        assert_eq!(logical.to_physical(size, 1.5), WindowSize::new(1920, 1080));
+        assert_eq!(logical.to_logical(size, 1.5), size);
+
+        let physical = ScalePolicy::Physical;
+        assert_eq!(physical.to_physical(size, 1.5), size);
+        assert_eq!(physical.to_logical(size, 1.25), WindowSize::new(1024, 576));
+
+        let window = Window::headless(
+            WindowConfig::default()
+                .with_size(800, 600)
+                .with_scale_policy(ScalePolicy::Physical),
+        );
+        assert_eq!(window.scale_policy(), ScalePolicy::Physical);
+        assert_eq!(window.physical_size(), window.size());
     }
 
     #[test]
@@ -0,0 +1,82 @@
+//! Carregamento de bibliotecas do sistema em tempo de execução
+//!
+//! Os backends Linux abrem libxcb/libwayland com `dlopen` em vez de linkar:
+//! o binário roda em máquinas sem uma delas e cai para outro backend (ou
+//! headless) quando a biblioteca não existe.
+
+use std::ffi::{c_void, CString};
+
+/// Handle de `dlopen`, fechado no drop
+pub(super) struct Library(*mut c_void);
+
+impl Library {
+    /// Abre o primeiro nome que existir
+    pub(super) fn open(names: &[&str]) -> Option<Self> {
+        names.iter().find_map(|name| {
+            let name = CString::new(*name).ok()?;
+            let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+            (!handle.is_null()).then_some(Self(handle))
+        })
+    }
+
+    /// Endereço de um símbolo
+    pub(super) fn address(&self, name: &str) -> Option<*mut c_void> {
+        let name = CString::new(name).ok()?;
+        let address = unsafe { libc::dlsym(self.0, name.as_ptr()) };
+        (!address.is_null()).then_some(address)
+    }
+
+    /// Símbolo convertido para o tipo de ponteiro `T`
+    ///
+    /// # Safety
+    ///
+    /// `T` precisa ser um ponteiro (de função ou dado) com a assinatura real
+    /// do símbolo.
+    pub(super) unsafe fn symbol<T: Copy>(&self, name: &str) -> Option<T> {
+        debug_assert_eq!(std::mem::size_of::<T>(), std::mem::size_of::<*mut c_void>());
+        self.address(name)
+            .map(|address| std::mem::transmute_copy(&address))
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.0);
+        }
+    }
+}
+
+/// Declara uma tabela de funções de uma biblioteca carregada com `dlopen`
+///
+/// Todas as funções precisam existir; `load` retorna `None` se a biblioteca
+/// ou qualquer símbolo faltar. Funções variádicas terminam com `; ...`.
+macro_rules! dynamic_library {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident [$($lib:literal),+ $(,)?] {
+            $(fn $func:ident($($arg:ty),* $(; $dots:tt)?) $(-> $ret:ty)?;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[allow(non_snake_case)]
+        $vis struct $name {
+            _library: $crate::window::platform::dynlib::Library,
+            $($func: unsafe extern "C" fn($($arg),* $(, $dots)?) $(-> $ret)?,)*
+        }
+
+        impl $name {
+            fn load() -> Option<Self> {
+                let library = $crate::window::platform::dynlib::Library::open(&[$($lib),+])?;
+                unsafe {
+                    Some(Self {
+                        $($func: library.symbol(stringify!($func))?,)*
+                        _library: library,
+                    })
+                }
+            }
+        }
+    };
+}
+
+pub(super) use dynamic_library;
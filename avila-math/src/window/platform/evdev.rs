@@ -0,0 +1,142 @@
+//! Códigos de tecla do evdev (Linux)
+//!
+//! X11 (keycode - 8) e Wayland entregam o código físico do kernel, então os
+//! dois backends usam a mesma tabela. `KeyCode` é posicional: a tecla à
+//! direita do Tab é `Q` em qualquer layout.
+
+use crate::window::input::KeyCode;
+
+/// Código evdev (`KEY_*` de `linux/input-event-codes.h`) para `KeyCode`
+pub(super) fn keycode_from_evdev(code: u32) -> Option<KeyCode> {
+    use KeyCode::*;
+
+    const FUNCTION_LOW: [KeyCode; 10] = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10];
+    const FUNCTION_HIGH: [KeyCode; 12] =
+        [F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24];
+
+    let key = match code {
+        1 => Escape,
+        2 => Key1,
+        3 => Key2,
+        4 => Key3,
+        5 => Key4,
+        6 => Key5,
+        7 => Key6,
+        8 => Key7,
+        9 => Key8,
+        10 => Key9,
+        11 => Key0,
+        12 => Minus,
+        13 => Equal,
+        14 => Backspace,
+        15 => Tab,
+        16 => Q,
+        17 => W,
+        18 => E,
+        19 => R,
+        20 => T,
+        21 => Y,
+        22 => U,
+        23 => I,
+        24 => O,
+        25 => P,
+        26 => BracketLeft,
+        27 => BracketRight,
+        28 => Enter,
+        29 => ControlLeft,
+        30 => A,
+        31 => S,
+        32 => D,
+        33 => F,
+        34 => G,
+        35 => H,
+        36 => J,
+        37 => K,
+        38 => L,
+        39 => Semicolon,
+        40 => Quote,
+        41 => Backquote,
+        42 => ShiftLeft,
+        43 => Backslash,
+        44 => Z,
+        45 => X,
+        46 => C,
+        47 => V,
+        48 => B,
+        49 => N,
+        50 => M,
+        51 => Comma,
+        52 => Period,
+        53 => Slash,
+        54 => ShiftRight,
+        55 => NumpadMultiply,
+        56 => AltLeft,
+        57 => Space,
+        58 => CapsLock,
+        59..=68 => FUNCTION_LOW[(code - 59) as usize],
+        69 => NumLock,
+        70 => ScrollLock,
+        71 => Numpad7,
+        72 => Numpad8,
+        73 => Numpad9,
+        74 => NumpadSubtract,
+        75 => Numpad4,
+        76 => Numpad5,
+        77 => Numpad6,
+        78 => NumpadAdd,
+        79 => Numpad1,
+        80 => Numpad2,
+        81 => Numpad3,
+        82 => Numpad0,
+        83 => NumpadDecimal,
+        87 => F11,
+        88 => F12,
+        96 => NumpadEnter,
+        97 => ControlRight,
+        98 => NumpadDivide,
+        99 => PrintScreen,
+        100 => AltRight,
+        102 => Home,
+        103 => ArrowUp,
+        104 => PageUp,
+        105 => ArrowLeft,
+        106 => ArrowRight,
+        107 => End,
+        108 => ArrowDown,
+        109 => PageDown,
+        110 => Insert,
+        111 => Delete,
+        113 => VolumeMute,
+        114 => VolumeDown,
+        115 => VolumeUp,
+        119 => Pause,
+        125 => MetaLeft,
+        126 => MetaRight,
+        127 => ContextMenu,
+        163 => MediaTrackNext,
+        164 => MediaPlayPause,
+        165 => MediaTrackPrevious,
+        166 => MediaStop,
+        183..=194 => FUNCTION_HIGH[(code - 183) as usize],
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keycode_from_evdev() {
+        assert_eq!(keycode_from_evdev(1), Some(KeyCode::Escape));
+        assert_eq!(keycode_from_evdev(16), Some(KeyCode::Q));
+        assert_eq!(keycode_from_evdev(30), Some(KeyCode::A));
+        assert_eq!(keycode_from_evdev(59), Some(KeyCode::F1));
+        assert_eq!(keycode_from_evdev(88), Some(KeyCode::F12));
+        assert_eq!(keycode_from_evdev(194), Some(KeyCode::F24));
+        assert_eq!(keycode_from_evdev(103), Some(KeyCode::ArrowUp));
+        assert_eq!(keycode_from_evdev(0), None);
+        assert_eq!(keycode_from_evdev(500), None);
+    }
+}
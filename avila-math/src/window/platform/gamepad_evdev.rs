@@ -0,0 +1,365 @@
+//! Gamepads via evdev (Linux)
+//!
+//! Varre `/dev/input/event*` a cada `SCAN_INTERVAL` procurando dispositivos
+//! com botões da classe gamepad (`BTN_GAMEPAD`) e lê os eventos sem
+//! bloquear. A chave do controle é o `dev_t` do nó. Sem permissão de leitura
+//! (usuário fora do grupo `input`) o dispositivo é ignorado.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::window::gamepad::{GamepadButton, RawGamepad};
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const SYN_DROPPED: u16 = 3;
+
+const BTN_GAMEPAD: u16 = 0x130;
+const BTN_TL2: u16 = 0x138;
+const BTN_TR2: u16 = 0x139;
+const KEY_MAX: usize = 0x2ff;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_GAS: u16 = 0x09;
+const ABS_BRAKE: u16 = 0x0a;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+const ABS_COUNT: usize = ABS_HAT0Y as usize + 1;
+
+/// Eixos lidos, na ordem de `GamepadAxis::ALL`
+const AXIS_CODES: [(u16, usize); 8] = [
+    (ABS_X, 0),
+    (ABS_Y, 1),
+    (ABS_RX, 2),
+    (ABS_RY, 3),
+    (ABS_Z, 4),
+    (ABS_RZ, 5),
+    (ABS_BRAKE, 4),
+    (ABS_GAS, 5),
+];
+
+// ioctls de linux/input.h: _IOC(_IOC_READ, 'E', nr, size)
+const fn ioc_read(nr: u32, size: usize) -> u32 {
+    (2 << 30) | ((size as u32) << 16) | ((b'E' as u32) << 8) | nr
+}
+
+const fn eviocgname(len: usize) -> u32 {
+    ioc_read(0x06, len)
+}
+
+const fn eviocgkey(len: usize) -> u32 {
+    ioc_read(0x18, len)
+}
+
+const fn eviocgbit(event_type: u16, len: usize) -> u32 {
+    ioc_read(0x20 + event_type as u32, len)
+}
+
+const fn eviocgabs(axis: u16) -> u32 {
+    ioc_read(
+        0x40 + axis as u32,
+        std::mem::size_of::<libc::input_absinfo>(),
+    )
+}
+
+/// Código `BTN_*` para botão do layout padrão
+fn button_from_code(code: u16) -> Option<GamepadButton> {
+    Some(match code {
+        0x130 => GamepadButton::South,
+        0x131 => GamepadButton::East,
+        0x133 => GamepadButton::North,
+        0x134 => GamepadButton::West,
+        0x136 => GamepadButton::LeftBumper,
+        0x137 => GamepadButton::RightBumper,
+        0x13a => GamepadButton::Select,
+        0x13b => GamepadButton::Start,
+        0x13c => GamepadButton::Guide,
+        0x13d => GamepadButton::LeftStick,
+        0x13e => GamepadButton::RightStick,
+        0x220 => GamepadButton::DPadUp,
+        0x221 => GamepadButton::DPadDown,
+        0x222 => GamepadButton::DPadLeft,
+        0x223 => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+/// Valor bruto para -1..1 (sticks) ou 0..1 (gatilhos)
+fn normalize(value: i32, min: i32, max: i32, signed: bool) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    let unit = (value.clamp(min, max) - min) as f32 / (max - min) as f32;
+    if signed {
+        unit * 2.0 - 1.0
+    } else {
+        unit
+    }
+}
+
+fn test_bit(bits: &[u8], bit: usize) -> bool {
+    bits.get(bit / 8)
+        .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+struct Device {
+    file: File,
+    path: PathBuf,
+    key: u64,
+    name: String,
+    /// Faixa de cada `ABS_*` suportado
+    ranges: [Option<(i32, i32)>; ABS_COUNT],
+    buttons: u32,
+    axes: [f32; 6],
+    hat: (i32, i32),
+}
+
+impl Device {
+    fn open(path: PathBuf) -> Option<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(&path)
+            .ok()?;
+        let fd = file.as_raw_fd();
+
+        let mut keys = [0u8; KEY_MAX / 8 + 1];
+        let mut abs = [0u8; 8];
+        unsafe {
+            if libc::ioctl(fd, eviocgbit(EV_KEY, keys.len()) as _, keys.as_mut_ptr()) < 0 {
+                return None;
+            }
+            libc::ioctl(fd, eviocgbit(EV_ABS, abs.len()) as _, abs.as_mut_ptr());
+        }
+        if !test_bit(&keys, BTN_GAMEPAD as usize) {
+            return None;
+        }
+
+        let mut name = [0u8; 128];
+        let len = unsafe { libc::ioctl(fd, eviocgname(name.len()) as _, name.as_mut_ptr()) };
+        let name = if len > 0 {
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            String::from_utf8_lossy(&name[..end]).into_owned()
+        } else {
+            "Gamepad".to_string()
+        };
+
+        let mut ranges = [None; ABS_COUNT];
+        for (code, range) in ranges.iter_mut().enumerate() {
+            if test_bit(&abs, code) {
+                let mut info: libc::input_absinfo = unsafe { std::mem::zeroed() };
+                if unsafe { libc::ioctl(fd, eviocgabs(code as u16) as _, &mut info) } >= 0 {
+                    *range = Some((info.minimum, info.maximum));
+                }
+            }
+        }
+
+        let key = fs::metadata(&path).ok()?.rdev();
+        let mut device = Self {
+            file,
+            path,
+            key,
+            name,
+            ranges,
+            buttons: 0,
+            axes: [0.0; 6],
+            hat: (0, 0),
+        };
+        device.resync();
+        Some(device)
+    }
+
+    /// Relê o estado completo (abertura e `SYN_DROPPED`)
+    fn resync(&mut self) {
+        let fd = self.file.as_raw_fd();
+        let mut keys = [0u8; KEY_MAX / 8 + 1];
+        if unsafe { libc::ioctl(fd, eviocgkey(keys.len()) as _, keys.as_mut_ptr()) } >= 0 {
+            self.buttons = 0;
+            for code in 0x130..=0x223u16 {
+                if test_bit(&keys, code as usize) {
+                    self.set_key(code, true);
+                }
+            }
+        }
+        for code in 0..ABS_COUNT as u16 {
+            if self.ranges[code as usize].is_none() {
+                continue;
+            }
+            let mut info: libc::input_absinfo = unsafe { std::mem::zeroed() };
+            if unsafe { libc::ioctl(fd, eviocgabs(code) as _, &mut info) } >= 0 {
+                self.set_abs(code, info.value);
+            }
+        }
+    }
+
+    fn set_key(&mut self, code: u16, pressed: bool) {
+        if let Some(button) = button_from_code(code) {
+            if pressed {
+                self.buttons |= button.bit();
+            } else {
+                self.buttons &= !button.bit();
+            }
+            return;
+        }
+        // Gatilhos digitais, quando não há eixo analógico
+        let (axis, analog) = match code {
+            BTN_TL2 => (4, [ABS_Z, ABS_BRAKE]),
+            BTN_TR2 => (5, [ABS_RZ, ABS_GAS]),
+            _ => return,
+        };
+        if analog.iter().all(|&a| self.ranges[a as usize].is_none()) {
+            self.axes[axis] = if pressed { 1.0 } else { 0.0 };
+        }
+    }
+
+    fn set_abs(&mut self, code: u16, value: i32) {
+        match code {
+            ABS_HAT0X => self.hat.0 = value.signum(),
+            ABS_HAT0Y => self.hat.1 = value.signum(),
+            _ => {
+                let Some(&(_, axis)) = AXIS_CODES.iter().find(|(c, _)| *c == code) else {
+                    return;
+                };
+                let Some((min, max)) = self.ranges[code as usize] else {
+                    return;
+                };
+                self.axes[axis] = normalize(value, min, max, axis < 4);
+                return;
+            }
+        }
+        let dpad = [
+            (self.hat.1 < 0, GamepadButton::DPadUp),
+            (self.hat.1 > 0, GamepadButton::DPadDown),
+            (self.hat.0 < 0, GamepadButton::DPadLeft),
+            (self.hat.0 > 0, GamepadButton::DPadRight),
+        ];
+        for (pressed, button) in dpad {
+            if pressed {
+                self.buttons |= button.bit();
+            } else {
+                self.buttons &= !button.bit();
+            }
+        }
+    }
+
+    /// Consome os eventos pendentes; `false` se o dispositivo sumiu
+    fn read_events(&mut self) -> bool {
+        const EVENT_SIZE: usize = std::mem::size_of::<libc::input_event>();
+        let mut buffer = [0u8; EVENT_SIZE * 64];
+        loop {
+            let read = match self.file.read(&mut buffer) {
+                Ok(0) => return true,
+                Ok(read) => read,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return true,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            };
+            for chunk in buffer[..read].chunks_exact(EVENT_SIZE) {
+                let event: libc::input_event =
+                    unsafe { std::ptr::read_unaligned(chunk.as_ptr().cast()) };
+                match event.type_ {
+                    EV_KEY => self.set_key(event.code, event.value != 0),
+                    EV_ABS => self.set_abs(event.code, event.value),
+                    EV_SYN if event.code == SYN_DROPPED => self.resync(),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct GamepadBackend {
+    devices: Vec<Device>,
+    last_scan: Option<Instant>,
+}
+
+impl GamepadBackend {
+    pub(crate) fn new() -> Self {
+        Self {
+            devices: Vec::new(),
+            last_scan: None,
+        }
+    }
+
+    pub(crate) fn poll(&mut self, out: &mut Vec<RawGamepad>) {
+        let now = Instant::now();
+        if self
+            .last_scan
+            .is_none_or(|last| now.duration_since(last) >= SCAN_INTERVAL)
+        {
+            self.last_scan = Some(now);
+            self.scan();
+        }
+
+        self.devices.retain_mut(|device| device.read_events());
+        out.extend(self.devices.iter().map(|device| RawGamepad {
+            key: device.key,
+            name: device.name.clone(),
+            buttons: device.buttons,
+            axes: device.axes,
+        }));
+    }
+
+    /// Abre os nós `event*` novos que forem gamepads
+    fn scan(&mut self) {
+        let Ok(entries) = fs::read_dir("/dev/input") else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_event = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"));
+            if !is_event || self.devices.iter().any(|device| device.path == path) {
+                continue;
+            }
+            if let Some(device) = Device::open(path) {
+                self.devices.push(device);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_from_code() {
+        assert_eq!(button_from_code(0x130), Some(GamepadButton::South));
+        assert_eq!(button_from_code(0x13b), Some(GamepadButton::Start));
+        assert_eq!(button_from_code(0x223), Some(GamepadButton::DPadRight));
+        assert_eq!(button_from_code(0x120), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(-32768, -32768, 32767, true), -1.0);
+        assert_eq!(normalize(32767, -32768, 32767, true), 1.0);
+        assert_eq!(normalize(0, 0, 255, false), 0.0);
+        assert_eq!(normalize(255, 0, 255, false), 1.0);
+        assert_eq!(normalize(300, 0, 255, false), 1.0);
+        assert_eq!(normalize(5, 0, 0, true), 0.0);
+    }
+
+    #[test]
+    fn test_ioctl_numbers() {
+        // Valores de linux/input.h em x86_64
+        assert_eq!(eviocgname(256), 0x8100_4506);
+        assert_eq!(eviocgbit(EV_KEY, 96), 0x8060_4521);
+        assert_eq!(eviocgabs(ABS_X), 0x8018_4540);
+    }
+}
@@ -0,0 +1,13 @@
+//! Plataformas sem backend de gamepad: nenhum controle é listado
+
+use crate::window::gamepad::RawGamepad;
+
+pub(crate) struct GamepadBackend;
+
+impl GamepadBackend {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    pub(crate) fn poll(&mut self, _out: &mut Vec<RawGamepad>) {}
+}
@@ -0,0 +1,123 @@
+//! Gamepads via XInput (Windows)
+//!
+//! Quatro slots fixos; a chave do controle é o número do slot. Consultar um
+//! slot vazio custa caro, então slots desconectados só são testados a cada
+//! `RESCAN_INTERVAL` polls. O botão Guide não é exposto pelo XInput público.
+
+use windows_sys::Win32::UI::Input::XboxController::{XInputGetState, XINPUT_STATE};
+
+use crate::window::gamepad::{GamepadButton, RawGamepad};
+
+const SLOTS: usize = 4;
+const RESCAN_INTERVAL: u32 = 120;
+const ERROR_SUCCESS: u32 = 0;
+
+const XINPUT_DPAD_UP: u16 = 0x0001;
+const XINPUT_DPAD_DOWN: u16 = 0x0002;
+const XINPUT_DPAD_LEFT: u16 = 0x0004;
+const XINPUT_DPAD_RIGHT: u16 = 0x0008;
+const XINPUT_START: u16 = 0x0010;
+const XINPUT_BACK: u16 = 0x0020;
+const XINPUT_LEFT_THUMB: u16 = 0x0040;
+const XINPUT_RIGHT_THUMB: u16 = 0x0080;
+const XINPUT_LEFT_SHOULDER: u16 = 0x0100;
+const XINPUT_RIGHT_SHOULDER: u16 = 0x0200;
+const XINPUT_A: u16 = 0x1000;
+const XINPUT_B: u16 = 0x2000;
+const XINPUT_X: u16 = 0x4000;
+const XINPUT_Y: u16 = 0x8000;
+
+const BUTTON_MAP: [(u16, GamepadButton); 14] = [
+    (XINPUT_A, GamepadButton::South),
+    (XINPUT_B, GamepadButton::East),
+    (XINPUT_X, GamepadButton::West),
+    (XINPUT_Y, GamepadButton::North),
+    (XINPUT_LEFT_SHOULDER, GamepadButton::LeftBumper),
+    (XINPUT_RIGHT_SHOULDER, GamepadButton::RightBumper),
+    (XINPUT_BACK, GamepadButton::Select),
+    (XINPUT_START, GamepadButton::Start),
+    (XINPUT_LEFT_THUMB, GamepadButton::LeftStick),
+    (XINPUT_RIGHT_THUMB, GamepadButton::RightStick),
+    (XINPUT_DPAD_UP, GamepadButton::DPadUp),
+    (XINPUT_DPAD_DOWN, GamepadButton::DPadDown),
+    (XINPUT_DPAD_LEFT, GamepadButton::DPadLeft),
+    (XINPUT_DPAD_RIGHT, GamepadButton::DPadRight),
+];
+
+pub(crate) struct GamepadBackend {
+    connected: [bool; SLOTS],
+    polls: u32,
+}
+
+impl GamepadBackend {
+    pub(crate) fn new() -> Self {
+        Self {
+            connected: [false; SLOTS],
+            polls: 0,
+        }
+    }
+
+    pub(crate) fn poll(&mut self, out: &mut Vec<RawGamepad>) {
+        let rescan = self.polls % RESCAN_INTERVAL == 0;
+        self.polls = self.polls.wrapping_add(1);
+
+        for slot in 0..SLOTS {
+            if !self.connected[slot] && !rescan {
+                continue;
+            }
+            let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+            let connected = unsafe { XInputGetState(slot as u32, &mut state) } == ERROR_SUCCESS;
+            self.connected[slot] = connected;
+            if connected {
+                let pad = &state.Gamepad;
+                out.push(RawGamepad {
+                    key: slot as u64,
+                    name: format!("XInput Controller {}", slot + 1),
+                    buttons: buttons_from_xinput(pad.wButtons),
+                    axes: [
+                        thumb(pad.sThumbLX),
+                        // XInput usa Y positivo para cima
+                        -thumb(pad.sThumbLY),
+                        thumb(pad.sThumbRX),
+                        -thumb(pad.sThumbRY),
+                        pad.bLeftTrigger as f32 / 255.0,
+                        pad.bRightTrigger as f32 / 255.0,
+                    ],
+                });
+            }
+        }
+    }
+}
+
+fn buttons_from_xinput(buttons: u16) -> u32 {
+    BUTTON_MAP
+        .iter()
+        .filter(|(flag, _)| buttons & flag != 0)
+        .fold(0, |bits, (_, button)| bits | button.bit())
+}
+
+fn thumb(value: i16) -> f32 {
+    (value as f32 / 32767.0).max(-1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buttons_from_xinput() {
+        let bits = buttons_from_xinput(XINPUT_A | XINPUT_DPAD_LEFT);
+        assert_eq!(
+            bits,
+            GamepadButton::South.bit() | GamepadButton::DPadLeft.bit()
+        );
+        assert_eq!(buttons_from_xinput(0), 0);
+    }
+
+    #[test]
+    fn test_thumb() {
+        assert_eq!(thumb(32767), 1.0);
+        assert_eq!(thumb(-32768), -1.0);
+        assert_eq!(thumb(0), 0.0);
+    }
+}
@@ -0,0 +1,131 @@
+//! Backend vazio para plataformas sem suporte nativo
+//!
+//! `NativeWindow` é um tipo sem valores: `create` sempre retorna `None` e os
+//! métodos existem só para manter a interface igual à dos outros backends.
+
+use std::time::Instant;
+
+use crate::window::events::Event;
+use crate::window::{
+    CursorGrabMode, CursorIcon, CursorImage, DisplayMode, MonitorInfo, NativeHandle, WindowConfig,
+    WindowError, WindowIcon, WindowPosition, WindowSize,
+};
+
+pub(crate) enum NativeWindow {}
+
+impl NativeWindow {
+    pub(crate) fn create(_config: &WindowConfig) -> Result<Option<Self>, WindowError> {
+        Ok(None)
+    }
+
+    pub(crate) fn set_title(&self, _title: &str) {
+        match *self {}
+    }
+
+    pub(crate) fn inner_size(&self) -> WindowSize {
+        match *self {}
+    }
+
+    pub(crate) fn physical_inner_size(&self) -> WindowSize {
+        match *self {}
+    }
+
+    pub(crate) fn set_inner_size(&self, _size: WindowSize) {
+        match *self {}
+    }
+
+    pub(crate) fn outer_position(&self) -> WindowPosition {
+        match *self {}
+    }
+
+    pub(crate) fn set_outer_position(&self, _position: WindowPosition) {
+        match *self {}
+    }
+
+    pub(crate) fn center(&self) {
+        match *self {}
+    }
+
+    pub(crate) fn set_display_mode(&self, _mode: DisplayMode) -> Result<(), WindowError> {
+        match *self {}
+    }
+
+    pub(crate) fn minimize(&self) {
+        match *self {}
+    }
+
+    pub(crate) fn focus(&self) {
+        match *self {}
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        match *self {}
+    }
+
+    pub(crate) fn is_maximized(&self) -> bool {
+        match *self {}
+    }
+
+    pub(crate) fn set_cursor_visible(&self, _visible: bool) {
+        match *self {}
+    }
+
+    pub(crate) fn cursor_position(&self) -> (f64, f64) {
+        match *self {}
+    }
+
+    pub(crate) fn set_cursor_position(&self, _x: f64, _y: f64) {
+        match *self {}
+    }
+
+    pub(crate) fn set_cursor_icon(&self, _icon: CursorIcon) {
+        match *self {}
+    }
+
+    pub(crate) fn set_cursor_image(&self, _image: &CursorImage) -> Result<(), WindowError> {
+        match *self {}
+    }
+
+    pub(crate) fn set_raw_mouse_motion(&self, _enabled: bool) -> Result<(), WindowError> {
+        match *self {}
+    }
+
+    pub(crate) fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), WindowError> {
+        match *self {}
+    }
+
+    pub(crate) fn set_icon(&self, _icon: Option<&WindowIcon>) {
+        match *self {}
+    }
+
+    pub(crate) fn set_opacity(&self, _opacity: f32) {
+        match *self {}
+    }
+
+    pub(crate) fn set_always_on_top(&self, _always_on_top: bool) {
+        match *self {}
+    }
+
+    pub(crate) fn request_attention(&self) {
+        match *self {}
+    }
+
+    pub(crate) fn scale_factor(&self) -> f32 {
+        match *self {}
+    }
+
+    pub(crate) fn current_monitor(&self) -> Option<MonitorInfo> {
+        match *self {}
+    }
+
+    pub(crate) fn native_handle(&self) -> NativeHandle {
+        match *self {}
+    }
+}
+
+/// Sem sistema de janelas não há eventos nativos
+pub(crate) fn pump_events(_deadline: Option<Instant>, _out: &mut Vec<Event>) {}
+
+pub(crate) fn available_monitors() -> Option<Vec<MonitorInfo>> {
+    None
+}
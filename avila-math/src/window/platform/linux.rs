@@ -0,0 +1,256 @@
+//! Seleção do backend Linux em tempo de execução
+//!
+//! Com `WAYLAND_DISPLAY` definido tenta o Wayland primeiro; se falhar (ou
+//! só o X estiver disponível) usa X11, inclusive via XWayland. Sem nenhum
+//! display a janela fica headless, como nas outras plataformas sem backend.
+
+use std::ffi::c_int;
+use std::time::Instant;
+
+use crate::window::events::Event;
+use crate::window::{
+    CursorGrabMode, CursorIcon, CursorImage, DisplayMode, MonitorInfo, NativeHandle, WindowConfig,
+    WindowError, WindowIcon, WindowPosition, WindowSize,
+};
+
+#[cfg(feature = "wayland")]
+use super::wayland::{self, WaylandWindow};
+#[cfg(feature = "x11")]
+use super::x11::{self, X11Window};
+
+pub(crate) enum NativeWindow {
+    #[cfg(feature = "x11")]
+    X11(X11Window),
+    #[cfg(feature = "wayland")]
+    Wayland(WaylandWindow),
+}
+
+/// Encaminha a chamada para o backend da janela
+macro_rules! dispatch {
+    ($self:ident, $window:ident => $call:expr) => {
+        match $self {
+            #[cfg(feature = "x11")]
+            NativeWindow::X11($window) => $call,
+            #[cfg(feature = "wayland")]
+            NativeWindow::Wayland($window) => $call,
+        }
+    };
+}
+
+impl NativeWindow {
+    pub(crate) fn create(config: &WindowConfig) -> Result<Option<Self>, WindowError> {
+        #[cfg(feature = "wayland")]
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match WaylandWindow::create(config) {
+                Ok(window) => return Ok(Some(Self::Wayland(window))),
+                Err(error) if !cfg!(feature = "x11") || std::env::var_os("DISPLAY").is_none() => {
+                    return Err(error)
+                }
+                Err(_) => {}
+            }
+        }
+        #[cfg(feature = "x11")]
+        if std::env::var_os("DISPLAY").is_some() {
+            return X11Window::create(config).map(|window| Some(Self::X11(window)));
+        }
+        let _ = config;
+        Ok(None)
+    }
+
+    pub(crate) fn set_title(&self, title: &str) {
+        dispatch!(self, w => w.set_title(title))
+    }
+
+    pub(crate) fn inner_size(&self) -> WindowSize {
+        dispatch!(self, w => w.inner_size())
+    }
+
+    pub(crate) fn physical_inner_size(&self) -> WindowSize {
+        dispatch!(self, w => w.physical_inner_size())
+    }
+
+    pub(crate) fn set_inner_size(&self, size: WindowSize) {
+        dispatch!(self, w => w.set_inner_size(size))
+    }
+
+    pub(crate) fn outer_position(&self) -> WindowPosition {
+        dispatch!(self, w => w.outer_position())
+    }
+
+    pub(crate) fn set_outer_position(&self, position: WindowPosition) {
+        dispatch!(self, w => w.set_outer_position(position))
+    }
+
+    pub(crate) fn center(&self) {
+        dispatch!(self, w => w.center())
+    }
+
+    pub(crate) fn set_display_mode(&self, mode: DisplayMode) -> Result<(), WindowError> {
+        dispatch!(self, w => w.set_display_mode(mode))
+    }
+
+    pub(crate) fn minimize(&self) {
+        dispatch!(self, w => w.minimize())
+    }
+
+    pub(crate) fn focus(&self) {
+        dispatch!(self, w => w.focus())
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        dispatch!(self, w => w.is_focused())
+    }
+
+    pub(crate) fn is_maximized(&self) -> bool {
+        dispatch!(self, w => w.is_maximized())
+    }
+
+    pub(crate) fn set_cursor_visible(&self, visible: bool) {
+        dispatch!(self, w => w.set_cursor_visible(visible))
+    }
+
+    pub(crate) fn cursor_position(&self) -> (f64, f64) {
+        dispatch!(self, w => w.cursor_position())
+    }
+
+    pub(crate) fn set_cursor_position(&self, x: f64, y: f64) {
+        dispatch!(self, w => w.set_cursor_position(x, y))
+    }
+
+    pub(crate) fn set_cursor_icon(&self, icon: CursorIcon) {
+        dispatch!(self, w => w.set_cursor_icon(icon))
+    }
+
+    pub(crate) fn set_cursor_image(&self, image: &CursorImage) -> Result<(), WindowError> {
+        dispatch!(self, w => w.set_cursor_image(image))
+    }
+
+    pub(crate) fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), WindowError> {
+        dispatch!(self, w => w.set_raw_mouse_motion(enabled))
+    }
+
+    pub(crate) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        dispatch!(self, w => w.set_cursor_grab(mode))
+    }
+
+    pub(crate) fn set_icon(&self, icon: Option<&WindowIcon>) {
+        dispatch!(self, w => w.set_icon(icon))
+    }
+
+    pub(crate) fn set_opacity(&self, opacity: f32) {
+        dispatch!(self, w => w.set_opacity(opacity))
+    }
+
+    pub(crate) fn set_always_on_top(&self, always_on_top: bool) {
+        dispatch!(self, w => w.set_always_on_top(always_on_top))
+    }
+
+    pub(crate) fn request_attention(&self) {
+        dispatch!(self, w => w.request_attention())
+    }
+
+    pub(crate) fn scale_factor(&self) -> f32 {
+        dispatch!(self, w => w.scale_factor())
+    }
+
+    pub(crate) fn current_monitor(&self) -> Option<MonitorInfo> {
+        dispatch!(self, w => w.current_monitor())
+    }
+
+    pub(crate) fn native_handle(&self) -> NativeHandle {
+        dispatch!(self, w => w.native_handle())
+    }
+}
+
+/// Bombeia os dois backends; só o primeiro com janelas abertas bloqueia
+pub(crate) fn pump_events(deadline: Option<Instant>, out: &mut Vec<Event>) {
+    #[cfg(feature = "wayland")]
+    wayland::pump_events(deadline, out);
+    #[cfg(feature = "x11")]
+    x11::pump_events(
+        if out.is_empty() {
+            deadline
+        } else {
+            Some(Instant::now())
+        },
+        out,
+    );
+}
+
+/// Timeout do `poll` até o `deadline` (-1 = sem limite), arredondado para
+/// cima para não acordar antes da hora
+pub(super) fn poll_timeout(deadline: Option<Instant>) -> c_int {
+    match deadline {
+        Some(deadline) => {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            remaining
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .min(c_int::MAX as u128) as c_int
+        }
+        None => -1,
+    }
+}
+
+pub(crate) fn available_monitors() -> Option<Vec<MonitorInfo>> {
+    #[cfg(feature = "wayland")]
+    if let Some(monitors) = wayland::available_monitors() {
+        return Some(monitors);
+    }
+    #[cfg(feature = "x11")]
+    if let Some(monitors) = x11::available_monitors() {
+        return Some(monitors);
+    }
+    None
+}
+
+// Utilidades de cursor comuns ao X11 e ao Wayland
+
+/// Nome do cursor nos temas antigos do X (fallback do nome CSS)
+pub(super) fn legacy_cursor_name(icon: CursorIcon) -> &'static str {
+    match icon {
+        CursorIcon::Default => "left_ptr",
+        CursorIcon::Pointer => "hand2",
+        CursorIcon::Text => "xterm",
+        CursorIcon::Crosshair => "cross",
+        CursorIcon::Wait => "watch",
+        CursorIcon::Progress => "left_ptr_watch",
+        CursorIcon::Help => "question_arrow",
+        CursorIcon::NotAllowed => "crossed_circle",
+        CursorIcon::Move => "fleur",
+        CursorIcon::Grab => "hand1",
+        CursorIcon::Grabbing => "fleur",
+        CursorIcon::EwResize => "sb_h_double_arrow",
+        CursorIcon::NsResize => "sb_v_double_arrow",
+        CursorIcon::NeswResize => "fd_double_arrow",
+        CursorIcon::NwseResize => "bd_double_arrow",
+    }
+}
+
+/// Pixels ARGB pré-multiplicados (formato do Render e do `wl_shm`)
+pub(super) fn premultiplied_argb(rgba: &[u8]) -> Vec<u32> {
+    rgba.chunks_exact(4)
+        .map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+            (alpha << 24)
+                | (premultiply(pixel[0]) << 16)
+                | (premultiply(pixel[1]) << 8)
+                | premultiply(pixel[2])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_premultiplied_argb() {
+        let rgba = vec![255, 0, 0, 255, 255, 255, 255, 128, 10, 20, 30, 0];
+        assert_eq!(
+            premultiplied_argb(&rgba),
+            vec![0xFFFF_0000, 0x8080_8080, 0x0000_0000]
+        );
+    }
+}
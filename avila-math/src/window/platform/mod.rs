@@ -0,0 +1,52 @@
+//! Backends nativos do sistema de janelas
+//!
+//! Todos expõem a mesma interface interna: `NativeWindow` (criada a partir de
+//! um `WindowConfig`), `pump_events` (usado pelo `EventLoop`; espera eventos
+//! até o `deadline`, ou sem limite com `None`) e `available_monitors`. Plataformas sem backend usam `headless`, em que
+//! nenhuma `NativeWindow` é criada e a `Window` vira apenas estado em memória.
+//! No Linux os backends X11 e Wayland são opt-in (features `x11`/`wayland`).
+
+#[cfg(windows)]
+mod win32;
+#[cfg(windows)]
+pub(crate) use win32::{available_monitors, pump_events, NativeWindow};
+
+#[cfg(all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland")))]
+mod dynlib;
+#[cfg(all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland")))]
+mod evdev;
+#[cfg(all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland")))]
+mod linux;
+#[cfg(all(unix, not(target_os = "macos"), feature = "wayland"))]
+mod wayland;
+#[cfg(all(unix, not(target_os = "macos"), feature = "x11"))]
+mod x11;
+#[cfg(all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland")))]
+pub(crate) use linux::{available_monitors, pump_events, NativeWindow};
+
+#[cfg(not(any(
+    windows,
+    all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland"))
+)))]
+mod headless;
+#[cfg(not(any(
+    windows,
+    all(unix, not(target_os = "macos"), any(feature = "x11", feature = "wayland"))
+)))]
+pub(crate) use headless::{available_monitors, pump_events, NativeWindow};
+
+// Gamepads independem do backend de janela
+#[cfg(windows)]
+mod gamepad_xinput;
+#[cfg(windows)]
+pub(crate) use gamepad_xinput::GamepadBackend;
+
+#[cfg(target_os = "linux")]
+mod gamepad_evdev;
+#[cfg(target_os = "linux")]
+pub(crate) use gamepad_evdev::GamepadBackend;
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod gamepad_none;
+#[cfg(not(any(windows, target_os = "linux")))]
+pub(crate) use gamepad_none::GamepadBackend;
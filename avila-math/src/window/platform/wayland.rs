@@ -0,0 +1,2466 @@
+//! Backend Wayland (xdg-shell)
+//!
+//! Uma conexão por thread com os globals do compositor (wl_compositor,
+//! xdg_wm_base, wl_seat, wl_output, wl_shm e, se existir,
+//! zxdg_decoration_manager_v1). Os listeners empilham eventos numa fila que
+//! `pump_events` entrega ao `EventLoop`.
+//!
+//! No Wayland tudo é lógico: `FramebufferResized` traz o tamanho multiplicado
+//! pelo scale do output. A superfície só aparece depois que o renderer anexar
+//! o primeiro buffer. O protocolo não expõe posição da janela, warp nem foco
+//! programático; esses métodos não fazem nada. Captura do cursor usa
+//! zwp_pointer_constraints_v1 e, no modo `Locked`, os deltas vêm de
+//! zwp_relative_pointer_v1; sem eles `set_cursor_grab` retorna erro.
+//! `RawMotion` usa os deltas sem aceleração do mesmo protocolo e só chega
+//! com o ponteiro sobre a janela.
+//!
+//! Ícone e opacidade dependem de xdg_toplevel_icon_v1 e wp_alpha_modifier_v1
+//! e são ignorados sem eles; "sempre no topo" não existe no protocolo.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::ptr::{null, null_mut};
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+use super::dynlib::{dynamic_library, Library};
+use super::evdev::keycode_from_evdev;
+use super::linux::{legacy_cursor_name, poll_timeout, premultiplied_argb};
+use crate::window::events::{
+    Event, KeyEvent, KeyState, MouseEvent, TouchEvent, TouchPhase, WindowEvent,
+};
+use crate::window::input::{Key, ModifierKeys, MouseButton};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CursorImage, DisplayMode, MonitorInfo, NativeHandle, ScalePolicy,
+    WindowConfig, WindowError, WindowIcon, WindowPosition, WindowSize,
+};
+
+type Proxy = c_void;
+type Fixed = i32;
+
+#[repr(C)]
+struct WlMessage {
+    name: *const c_char,
+    signature: *const c_char,
+    types: *const *const WlInterface,
+}
+
+#[repr(C)]
+struct WlInterface {
+    name: *const c_char,
+    version: c_int,
+    method_count: c_int,
+    methods: *const WlMessage,
+    event_count: c_int,
+    events: *const WlMessage,
+}
+
+#[repr(C)]
+struct WlArray {
+    size: usize,
+    alloc: usize,
+    data: *mut c_void,
+}
+
+#[repr(C)]
+struct WlCursor {
+    image_count: u32,
+    images: *mut *mut WlCursorImage,
+    name: *mut c_char,
+}
+
+#[repr(C)]
+struct WlCursorImage {
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+    delay: u32,
+}
+
+dynamic_library! {
+    struct WaylandClient ["libwayland-client.so.0", "libwayland-client.so"] {
+        fn wl_display_connect(*const c_char) -> *mut Proxy;
+        fn wl_display_disconnect(*mut Proxy);
+        fn wl_display_get_fd(*mut Proxy) -> c_int;
+        fn wl_display_roundtrip(*mut Proxy) -> c_int;
+        fn wl_display_dispatch_pending(*mut Proxy) -> c_int;
+        fn wl_display_prepare_read(*mut Proxy) -> c_int;
+        fn wl_display_read_events(*mut Proxy) -> c_int;
+        fn wl_display_cancel_read(*mut Proxy);
+        fn wl_display_flush(*mut Proxy) -> c_int;
+        fn wl_proxy_marshal_flags(*mut Proxy, u32, *const WlInterface, u32, u32; ...) -> *mut Proxy;
+        fn wl_proxy_add_listener(*mut Proxy, *const c_void, *mut c_void) -> c_int;
+        fn wl_proxy_get_version(*mut Proxy) -> u32;
+        fn wl_proxy_destroy(*mut Proxy);
+    }
+}
+
+dynamic_library! {
+    struct WaylandCursor ["libwayland-cursor.so.0", "libwayland-cursor.so"] {
+        fn wl_cursor_theme_load(*const c_char, c_int, *mut Proxy) -> *mut c_void;
+        fn wl_cursor_theme_destroy(*mut c_void);
+        fn wl_cursor_theme_get_cursor(*mut c_void, *const c_char) -> *mut WlCursor;
+        fn wl_cursor_image_get_buffer(*mut WlCursorImage) -> *mut Proxy;
+    }
+}
+
+/// Interfaces do protocolo core exportadas pela libwayland-client
+struct CoreInterfaces {
+    _library: Library,
+    registry: *const WlInterface,
+    compositor: *const WlInterface,
+    surface: *const WlInterface,
+    seat: *const WlInterface,
+    pointer: *const WlInterface,
+    keyboard: *const WlInterface,
+    touch: *const WlInterface,
+    output: *const WlInterface,
+    shm: *const WlInterface,
+    shm_pool: *const WlInterface,
+    buffer: *const WlInterface,
+}
+
+impl CoreInterfaces {
+    fn load() -> Option<Self> {
+        let library = Library::open(&["libwayland-client.so.0", "libwayland-client.so"])?;
+        let interface = |name: &str| library.address(name).map(|a| a as *const WlInterface);
+        Some(Self {
+            registry: interface("wl_registry_interface")?,
+            compositor: interface("wl_compositor_interface")?,
+            surface: interface("wl_surface_interface")?,
+            seat: interface("wl_seat_interface")?,
+            pointer: interface("wl_pointer_interface")?,
+            keyboard: interface("wl_keyboard_interface")?,
+            touch: interface("wl_touch_interface")?,
+            output: interface("wl_output_interface")?,
+            shm: interface("wl_shm_interface")?,
+            shm_pool: interface("wl_shm_pool_interface")?,
+            buffer: interface("wl_buffer_interface")?,
+            _library: library,
+        })
+    }
+}
+
+// xdg-shell, xdg-decoration, pointer-constraints, relative-pointer,
+// xdg-toplevel-icon e alpha-modifier não fazem parte da libwayland; as
+// tabelas de mensagens vêm dos XMLs dos protocolos. `types` só é consultado para
+// validar objetos recebidos, então todas as mensagens usam uma lista nula.
+
+struct SyncTypes([*const WlInterface; 4]);
+unsafe impl Sync for SyncTypes {}
+
+struct SyncMessages<const N: usize>([WlMessage; N]);
+unsafe impl<const N: usize> Sync for SyncMessages<N> {}
+
+struct SyncInterface(WlInterface);
+unsafe impl Sync for SyncInterface {}
+
+static NO_TYPES: SyncTypes = SyncTypes([null(); 4]);
+
+const fn message(name: &'static CStr, signature: &'static CStr) -> WlMessage {
+    WlMessage {
+        name: name.as_ptr(),
+        signature: signature.as_ptr(),
+        types: NO_TYPES.0.as_ptr(),
+    }
+}
+
+static XDG_WM_BASE_REQUESTS: SyncMessages<4> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"create_positioner", c"n"),
+    message(c"get_xdg_surface", c"no"),
+    message(c"pong", c"u"),
+]);
+static XDG_WM_BASE_EVENTS: SyncMessages<1> = SyncMessages([message(c"ping", c"u")]);
+static XDG_WM_BASE_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"xdg_wm_base".as_ptr(),
+    version: 1,
+    method_count: 4,
+    methods: XDG_WM_BASE_REQUESTS.0.as_ptr(),
+    event_count: 1,
+    events: XDG_WM_BASE_EVENTS.0.as_ptr(),
+});
+
+static XDG_SURFACE_REQUESTS: SyncMessages<5> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"get_toplevel", c"n"),
+    message(c"get_popup", c"n?oo"),
+    message(c"set_window_geometry", c"iiii"),
+    message(c"ack_configure", c"u"),
+]);
+static XDG_SURFACE_EVENTS: SyncMessages<1> = SyncMessages([message(c"configure", c"u")]);
+static XDG_SURFACE_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"xdg_surface".as_ptr(),
+    version: 1,
+    method_count: 5,
+    methods: XDG_SURFACE_REQUESTS.0.as_ptr(),
+    event_count: 1,
+    events: XDG_SURFACE_EVENTS.0.as_ptr(),
+});
+
+static XDG_TOPLEVEL_REQUESTS: SyncMessages<14> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"set_parent", c"?o"),
+    message(c"set_title", c"s"),
+    message(c"set_app_id", c"s"),
+    message(c"show_window_menu", c"ouii"),
+    message(c"move", c"ou"),
+    message(c"resize", c"ouu"),
+    message(c"set_max_size", c"ii"),
+    message(c"set_min_size", c"ii"),
+    message(c"set_maximized", c""),
+    message(c"unset_maximized", c""),
+    message(c"set_fullscreen", c"?o"),
+    message(c"unset_fullscreen", c""),
+    message(c"set_minimized", c""),
+]);
+static XDG_TOPLEVEL_EVENTS: SyncMessages<2> =
+    SyncMessages([message(c"configure", c"iia"), message(c"close", c"")]);
+static XDG_TOPLEVEL_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"xdg_toplevel".as_ptr(),
+    version: 1,
+    method_count: 14,
+    methods: XDG_TOPLEVEL_REQUESTS.0.as_ptr(),
+    event_count: 2,
+    events: XDG_TOPLEVEL_EVENTS.0.as_ptr(),
+});
+
+static DECORATION_MANAGER_REQUESTS: SyncMessages<2> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"get_toplevel_decoration", c"no"),
+]);
+static DECORATION_MANAGER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zxdg_decoration_manager_v1".as_ptr(),
+    version: 1,
+    method_count: 2,
+    methods: DECORATION_MANAGER_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+static DECORATION_REQUESTS: SyncMessages<3> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"set_mode", c"u"),
+    message(c"unset_mode", c""),
+]);
+static DECORATION_EVENTS: SyncMessages<1> = SyncMessages([message(c"configure", c"u")]);
+static DECORATION_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zxdg_toplevel_decoration_v1".as_ptr(),
+    version: 1,
+    method_count: 3,
+    methods: DECORATION_REQUESTS.0.as_ptr(),
+    event_count: 1,
+    events: DECORATION_EVENTS.0.as_ptr(),
+});
+
+static POINTER_CONSTRAINTS_REQUESTS: SyncMessages<3> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"lock_pointer", c"noo?ou"),
+    message(c"confine_pointer", c"noo?ou"),
+]);
+static POINTER_CONSTRAINTS_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zwp_pointer_constraints_v1".as_ptr(),
+    version: 1,
+    method_count: 3,
+    methods: POINTER_CONSTRAINTS_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+static LOCKED_POINTER_REQUESTS: SyncMessages<3> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"set_cursor_position_hint", c"ff"),
+    message(c"set_region", c"?o"),
+]);
+static LOCKED_POINTER_EVENTS: SyncMessages<2> =
+    SyncMessages([message(c"locked", c""), message(c"unlocked", c"")]);
+static LOCKED_POINTER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zwp_locked_pointer_v1".as_ptr(),
+    version: 1,
+    method_count: 3,
+    methods: LOCKED_POINTER_REQUESTS.0.as_ptr(),
+    event_count: 2,
+    events: LOCKED_POINTER_EVENTS.0.as_ptr(),
+});
+
+static CONFINED_POINTER_REQUESTS: SyncMessages<2> =
+    SyncMessages([message(c"destroy", c""), message(c"set_region", c"?o")]);
+static CONFINED_POINTER_EVENTS: SyncMessages<2> =
+    SyncMessages([message(c"confined", c""), message(c"unconfined", c"")]);
+static CONFINED_POINTER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zwp_confined_pointer_v1".as_ptr(),
+    version: 1,
+    method_count: 2,
+    methods: CONFINED_POINTER_REQUESTS.0.as_ptr(),
+    event_count: 2,
+    events: CONFINED_POINTER_EVENTS.0.as_ptr(),
+});
+
+static RELATIVE_POINTER_MANAGER_REQUESTS: SyncMessages<2> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"get_relative_pointer", c"no"),
+]);
+static RELATIVE_POINTER_MANAGER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zwp_relative_pointer_manager_v1".as_ptr(),
+    version: 1,
+    method_count: 2,
+    methods: RELATIVE_POINTER_MANAGER_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+static RELATIVE_POINTER_REQUESTS: SyncMessages<1> = SyncMessages([message(c"destroy", c"")]);
+static RELATIVE_POINTER_EVENTS: SyncMessages<1> =
+    SyncMessages([message(c"relative_motion", c"uuffff")]);
+static RELATIVE_POINTER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"zwp_relative_pointer_v1".as_ptr(),
+    version: 1,
+    method_count: 1,
+    methods: RELATIVE_POINTER_REQUESTS.0.as_ptr(),
+    event_count: 1,
+    events: RELATIVE_POINTER_EVENTS.0.as_ptr(),
+});
+
+static TOPLEVEL_ICON_MANAGER_REQUESTS: SyncMessages<3> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"create_icon", c"n"),
+    message(c"set_icon", c"o?o"),
+]);
+static TOPLEVEL_ICON_MANAGER_EVENTS: SyncMessages<2> =
+    SyncMessages([message(c"icon_size", c"i"), message(c"done", c"")]);
+static TOPLEVEL_ICON_MANAGER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"xdg_toplevel_icon_manager_v1".as_ptr(),
+    version: 1,
+    method_count: 3,
+    methods: TOPLEVEL_ICON_MANAGER_REQUESTS.0.as_ptr(),
+    event_count: 2,
+    events: TOPLEVEL_ICON_MANAGER_EVENTS.0.as_ptr(),
+});
+
+static TOPLEVEL_ICON_REQUESTS: SyncMessages<3> = SyncMessages([
+    message(c"destroy", c""),
+    message(c"set_name", c"s"),
+    message(c"add_buffer", c"oi"),
+]);
+static TOPLEVEL_ICON_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"xdg_toplevel_icon_v1".as_ptr(),
+    version: 1,
+    method_count: 3,
+    methods: TOPLEVEL_ICON_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+static ALPHA_MODIFIER_REQUESTS: SyncMessages<2> =
+    SyncMessages([message(c"destroy", c""), message(c"get_surface", c"no")]);
+static ALPHA_MODIFIER_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"wp_alpha_modifier_v1".as_ptr(),
+    version: 1,
+    method_count: 2,
+    methods: ALPHA_MODIFIER_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+static ALPHA_MODIFIER_SURFACE_REQUESTS: SyncMessages<2> =
+    SyncMessages([message(c"destroy", c""), message(c"set_multiplier", c"u")]);
+static ALPHA_MODIFIER_SURFACE_INTERFACE: SyncInterface = SyncInterface(WlInterface {
+    name: c"wp_alpha_modifier_surface_v1".as_ptr(),
+    version: 1,
+    method_count: 2,
+    methods: ALPHA_MODIFIER_SURFACE_REQUESTS.0.as_ptr(),
+    event_count: 0,
+    events: null(),
+});
+
+const MARSHAL_DESTROY: u32 = 1;
+
+const WL_DISPLAY_GET_REGISTRY: u32 = 1;
+const WL_REGISTRY_BIND: u32 = 0;
+const WL_COMPOSITOR_CREATE_SURFACE: u32 = 0;
+const WL_SURFACE_DESTROY: u32 = 0;
+const WL_SURFACE_ATTACH: u32 = 1;
+const WL_SURFACE_DAMAGE: u32 = 2;
+const WL_SURFACE_COMMIT: u32 = 6;
+const WL_SURFACE_SET_BUFFER_SCALE: u32 = 8;
+const WL_SEAT_GET_POINTER: u32 = 0;
+const WL_SEAT_GET_KEYBOARD: u32 = 1;
+const WL_SEAT_GET_TOUCH: u32 = 2;
+const WL_SHM_CREATE_POOL: u32 = 0;
+const WL_SHM_POOL_CREATE_BUFFER: u32 = 0;
+const WL_SHM_POOL_DESTROY: u32 = 1;
+const WL_BUFFER_DESTROY: u32 = 0;
+const WL_POINTER_SET_CURSOR: u32 = 0;
+const WL_POINTER_RELEASE: u32 = 1;
+const WL_KEYBOARD_RELEASE: u32 = 1;
+const WL_TOUCH_RELEASE: u32 = 0;
+const XDG_WM_BASE_GET_XDG_SURFACE: u32 = 2;
+const XDG_WM_BASE_PONG: u32 = 3;
+const XDG_SURFACE_DESTROY: u32 = 0;
+const XDG_SURFACE_GET_TOPLEVEL: u32 = 1;
+const XDG_SURFACE_ACK_CONFIGURE: u32 = 4;
+const XDG_TOPLEVEL_DESTROY: u32 = 0;
+const XDG_TOPLEVEL_SET_TITLE: u32 = 2;
+const XDG_TOPLEVEL_SET_MAX_SIZE: u32 = 7;
+const XDG_TOPLEVEL_SET_MIN_SIZE: u32 = 8;
+const XDG_TOPLEVEL_SET_MAXIMIZED: u32 = 9;
+const XDG_TOPLEVEL_UNSET_MAXIMIZED: u32 = 10;
+const XDG_TOPLEVEL_SET_FULLSCREEN: u32 = 11;
+const XDG_TOPLEVEL_UNSET_FULLSCREEN: u32 = 12;
+const XDG_TOPLEVEL_SET_MINIMIZED: u32 = 13;
+const DECORATION_MANAGER_GET_TOPLEVEL_DECORATION: u32 = 1;
+const DECORATION_DESTROY: u32 = 0;
+const DECORATION_SET_MODE: u32 = 1;
+const POINTER_CONSTRAINTS_DESTROY: u32 = 0;
+const POINTER_CONSTRAINTS_LOCK_POINTER: u32 = 1;
+const POINTER_CONSTRAINTS_CONFINE_POINTER: u32 = 2;
+/// Mesmo opcode em `zwp_locked_pointer_v1` e `zwp_confined_pointer_v1`
+const POINTER_CONSTRAINT_DESTROY: u32 = 0;
+const RELATIVE_POINTER_MANAGER_DESTROY: u32 = 0;
+const RELATIVE_POINTER_MANAGER_GET_RELATIVE_POINTER: u32 = 1;
+const RELATIVE_POINTER_DESTROY: u32 = 0;
+const TOPLEVEL_ICON_MANAGER_DESTROY: u32 = 0;
+const TOPLEVEL_ICON_MANAGER_CREATE_ICON: u32 = 1;
+const TOPLEVEL_ICON_MANAGER_SET_ICON: u32 = 2;
+const TOPLEVEL_ICON_DESTROY: u32 = 0;
+const TOPLEVEL_ICON_ADD_BUFFER: u32 = 2;
+const ALPHA_MODIFIER_DESTROY: u32 = 0;
+const ALPHA_MODIFIER_GET_SURFACE: u32 = 1;
+const ALPHA_MODIFIER_SURFACE_DESTROY: u32 = 0;
+const ALPHA_MODIFIER_SURFACE_SET_MULTIPLIER: u32 = 1;
+
+const DECORATION_MODE_CLIENT_SIDE: u32 = 1;
+const DECORATION_MODE_SERVER_SIDE: u32 = 2;
+/// A restrição volta a valer quando o ponteiro reentra na superfície
+const CONSTRAINT_LIFETIME_PERSISTENT: u32 = 2;
+const WL_SHM_FORMAT_ARGB8888: u32 = 0;
+const XDG_TOPLEVEL_STATE_MAXIMIZED: u32 = 1;
+const XDG_TOPLEVEL_STATE_FULLSCREEN: u32 = 2;
+const SEAT_CAPABILITY_POINTER: u32 = 1;
+const SEAT_CAPABILITY_KEYBOARD: u32 = 2;
+const SEAT_CAPABILITY_TOUCH: u32 = 4;
+const OUTPUT_MODE_CURRENT: u32 = 1;
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+/// Unidades de `wl_pointer.axis` por "clique" da roda
+const AXIS_UNITS_PER_STEP: f64 = 10.0;
+
+fn fixed_to_f64(value: Fixed) -> f64 {
+    value as f64 / 256.0
+}
+
+/// Output anunciado pelo compositor
+struct Output {
+    global: u32,
+    proxy: *mut Proxy,
+    name: String,
+    position: (i32, i32),
+    size: (u32, u32),
+    refresh_rate: u32,
+    scale: i32,
+}
+
+impl Output {
+    fn info(&self, is_primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            name: self.name.clone(),
+            size: WindowSize::new(self.size.0, self.size.1),
+            position: WindowPosition::new(self.position.0, self.position.1),
+            refresh_rate: self.refresh_rate,
+            scale_factor: self.scale as f32,
+            is_primary,
+        }
+    }
+}
+
+/// Conexão com o compositor compartilhada pelas janelas da thread
+struct WaylandConnection {
+    client: WaylandClient,
+    interfaces: CoreInterfaces,
+    cursor_library: Option<WaylandCursor>,
+    display: *mut Proxy,
+    registry: Cell<*mut Proxy>,
+    compositor: Cell<*mut Proxy>,
+    wm_base: Cell<*mut Proxy>,
+    seat: Cell<*mut Proxy>,
+    pointer: Cell<*mut Proxy>,
+    keyboard: Cell<*mut Proxy>,
+    touch: Cell<*mut Proxy>,
+    shm: Cell<*mut Proxy>,
+    decoration_manager: Cell<*mut Proxy>,
+    pointer_constraints: Cell<*mut Proxy>,
+    relative_pointer_manager: Cell<*mut Proxy>,
+    relative_pointer: Cell<*mut Proxy>,
+    toplevel_icon_manager: Cell<*mut Proxy>,
+    alpha_modifier: Cell<*mut Proxy>,
+    outputs: RefCell<Vec<Output>>,
+    /// Janelas indexadas pelo endereço da `wl_surface`
+    windows: RefCell<HashMap<usize, Rc<WaylandState>>>,
+    pointer_focus: Cell<usize>,
+    pointer_serial: Cell<u32>,
+    keyboard_focus: Cell<usize>,
+    modifiers: Cell<ModifierKeys>,
+    /// Última posição de cada dedo ativo (`up` não traz posição)
+    touch_points: RefCell<HashMap<i32, (f64, f64)>>,
+    cursor_theme: Cell<*mut c_void>,
+    cursor_surface: Cell<*mut Proxy>,
+    queue: RefCell<Vec<Event>>,
+}
+
+thread_local! {
+    static CONNECTION: RefCell<Weak<WaylandConnection>> = const { RefCell::new(Weak::new()) };
+}
+
+fn connection() -> Result<Rc<WaylandConnection>, String> {
+    if let Some(connection) = CONNECTION.with(|c| c.borrow().upgrade()) {
+        return Ok(connection);
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err("WAYLAND_DISPLAY is not set".to_string());
+    }
+    let client = WaylandClient::load().ok_or_else(|| "libwayland-client not found".to_string())?;
+    let interfaces =
+        CoreInterfaces::load().ok_or_else(|| "libwayland-client not found".to_string())?;
+
+    let display = unsafe { (client.wl_display_connect)(null()) };
+    if display.is_null() {
+        return Err("cannot connect to the Wayland compositor".to_string());
+    }
+    let connection = Rc::new(WaylandConnection {
+        client,
+        interfaces,
+        cursor_library: WaylandCursor::load(),
+        display,
+        registry: Cell::new(null_mut()),
+        compositor: Cell::new(null_mut()),
+        wm_base: Cell::new(null_mut()),
+        seat: Cell::new(null_mut()),
+        pointer: Cell::new(null_mut()),
+        keyboard: Cell::new(null_mut()),
+        touch: Cell::new(null_mut()),
+        shm: Cell::new(null_mut()),
+        decoration_manager: Cell::new(null_mut()),
+        pointer_constraints: Cell::new(null_mut()),
+        relative_pointer_manager: Cell::new(null_mut()),
+        relative_pointer: Cell::new(null_mut()),
+        toplevel_icon_manager: Cell::new(null_mut()),
+        alpha_modifier: Cell::new(null_mut()),
+        outputs: RefCell::new(Vec::new()),
+        windows: RefCell::new(HashMap::new()),
+        pointer_focus: Cell::new(0),
+        pointer_serial: Cell::new(0),
+        keyboard_focus: Cell::new(0),
+        modifiers: Cell::new(ModifierKeys::empty()),
+        touch_points: RefCell::new(HashMap::new()),
+        cursor_theme: Cell::new(null_mut()),
+        cursor_surface: Cell::new(null_mut()),
+        queue: RefCell::new(Vec::new()),
+    });
+
+    unsafe {
+        let c = &connection.client;
+        let registry = (c.wl_proxy_marshal_flags)(
+            display,
+            WL_DISPLAY_GET_REGISTRY,
+            connection.interfaces.registry,
+            (c.wl_proxy_get_version)(display),
+            0,
+            null_mut::<c_void>(),
+        );
+        connection.registry.set(registry);
+        (c.wl_proxy_add_listener)(
+            registry,
+            &REGISTRY_LISTENER as *const RegistryListener as *const c_void,
+            Rc::as_ptr(&connection) as *mut c_void,
+        );
+        // Primeiro round trip anuncia os globals, o segundo entrega os
+        // eventos iniciais dos objetos recém-ligados (outputs, seat)
+        (c.wl_display_roundtrip)(display);
+        (c.wl_display_roundtrip)(display);
+    }
+
+    if connection.compositor.get().is_null() || connection.wm_base.get().is_null() {
+        return Err("compositor does not support xdg-shell".to_string());
+    }
+    CONNECTION.with(|c| *c.borrow_mut() = Rc::downgrade(&connection));
+    Ok(connection)
+}
+
+impl WaylandConnection {
+    unsafe fn bind(&self, name: u32, interface: *const WlInterface, version: u32) -> *mut Proxy {
+        (self.client.wl_proxy_marshal_flags)(
+            self.registry.get(),
+            WL_REGISTRY_BIND,
+            interface,
+            version,
+            0,
+            name,
+            (*interface).name,
+            version,
+            null_mut::<c_void>(),
+        )
+    }
+
+    /// Request sem argumentos
+    unsafe fn request(&self, proxy: *mut Proxy, opcode: u32) {
+        let version = (self.client.wl_proxy_get_version)(proxy);
+        (self.client.wl_proxy_marshal_flags)(proxy, opcode, null(), version, 0);
+    }
+
+    /// Request destrutor: envia e libera o proxy
+    unsafe fn destroy(&self, proxy: *mut Proxy, opcode: u32) {
+        if proxy.is_null() {
+            return;
+        }
+        let version = (self.client.wl_proxy_get_version)(proxy);
+        (self.client.wl_proxy_marshal_flags)(proxy, opcode, null(), version, MARSHAL_DESTROY);
+    }
+
+    unsafe fn create_child(
+        &self,
+        parent: *mut Proxy,
+        opcode: u32,
+        interface: *const WlInterface,
+    ) -> *mut Proxy {
+        let version = (self.client.wl_proxy_get_version)(parent);
+        (self.client.wl_proxy_marshal_flags)(
+            parent,
+            opcode,
+            interface,
+            version,
+            0,
+            null_mut::<c_void>(),
+        )
+    }
+
+    fn flush(&self) {
+        unsafe {
+            (self.client.wl_display_flush)(self.display);
+        }
+    }
+
+    fn push(&self, event: Event) {
+        self.queue.borrow_mut().push(event);
+    }
+
+    fn window(&self, surface: *mut Proxy) -> Option<Rc<WaylandState>> {
+        self.windows.borrow().get(&(surface as usize)).cloned()
+    }
+
+    fn output_scale(&self, output: *mut Proxy) -> i32 {
+        self.outputs
+            .borrow()
+            .iter()
+            .find(|o| o.proxy == output)
+            .map_or(1, |o| o.scale)
+    }
+
+    fn monitors(&self) -> Vec<MonitorInfo> {
+        self.outputs
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, output)| output.info(index == 0))
+            .collect()
+    }
+
+    /// Aplica o cursor da janela (ou nenhum) sob o ponteiro
+    unsafe fn apply_cursor(&self, state: &WaylandState) {
+        let pointer = self.pointer.get();
+        if pointer.is_null() {
+            return;
+        }
+        let version = (self.client.wl_proxy_get_version)(pointer);
+        let serial = self.pointer_serial.get();
+        let visible = state.cursor_visible.get();
+        let image = if !visible {
+            None
+        } else if let Some(custom) = state.custom_cursor.get() {
+            self.attach_cursor(custom)
+        } else {
+            self.theme_cursor(state.cursor_icon.get())
+                .and_then(|cursor| self.attach_cursor(cursor))
+        };
+        match image {
+            Some((surface, hotspot)) => {
+                (self.client.wl_proxy_marshal_flags)(
+                    pointer,
+                    WL_POINTER_SET_CURSOR,
+                    null(),
+                    version,
+                    0,
+                    serial,
+                    surface,
+                    hotspot.0,
+                    hotspot.1,
+                );
+            }
+            None if !visible => {
+                (self.client.wl_proxy_marshal_flags)(
+                    pointer,
+                    WL_POINTER_SET_CURSOR,
+                    null(),
+                    version,
+                    0,
+                    serial,
+                    null_mut::<c_void>(),
+                    0,
+                    0,
+                );
+            }
+            // Sem libwayland-cursor o compositor mantém o cursor atual
+            None => {}
+        }
+    }
+
+    /// Buffer do tema de cursor para o ícone
+    unsafe fn theme_cursor(&self, icon: CursorIcon) -> Option<CursorBuffer> {
+        let library = self.cursor_library.as_ref()?;
+        if self.shm.get().is_null() {
+            return None;
+        }
+        if self.cursor_theme.get().is_null() {
+            let size = std::env::var("XCURSOR_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(24);
+            let theme_name = std::env::var("XCURSOR_THEME")
+                .ok()
+                .and_then(|name| CString::new(name).ok());
+            let theme = (library.wl_cursor_theme_load)(
+                theme_name.as_ref().map_or(null(), |name| name.as_ptr()),
+                size,
+                self.shm.get(),
+            );
+            self.cursor_theme.set(theme);
+        }
+        let theme = self.cursor_theme.get();
+        if theme.is_null() {
+            return None;
+        }
+
+        // Nome CSS, nome antigo e, se o tema não tiver nenhum, a seta
+        let cursor = [icon.name(), legacy_cursor_name(icon), "left_ptr"]
+            .into_iter()
+            .filter_map(|name| CString::new(name).ok())
+            .map(|name| (library.wl_cursor_theme_get_cursor)(theme, name.as_ptr()))
+            .find(|cursor| !cursor.is_null() && (**cursor).image_count > 0)?;
+        let image = *(*cursor).images;
+        let buffer = (library.wl_cursor_image_get_buffer)(image);
+        if buffer.is_null() {
+            return None;
+        }
+        Some(CursorBuffer {
+            buffer,
+            size: ((*image).width as i32, (*image).height as i32),
+            hotspot: ((*image).hotspot_x as i32, (*image).hotspot_y as i32),
+        })
+    }
+
+    /// Anexa o buffer à superfície de cursor compartilhada
+    unsafe fn attach_cursor(&self, cursor: CursorBuffer) -> Option<(*mut Proxy, (i32, i32))> {
+        if self.cursor_surface.get().is_null() {
+            let surface = self.create_child(
+                self.compositor.get(),
+                WL_COMPOSITOR_CREATE_SURFACE,
+                self.interfaces.surface,
+            );
+            self.cursor_surface.set(surface);
+        }
+        let surface = self.cursor_surface.get();
+        if surface.is_null() {
+            return None;
+        }
+        let version = (self.client.wl_proxy_get_version)(surface);
+        let marshal = self.client.wl_proxy_marshal_flags;
+        marshal(
+            surface,
+            WL_SURFACE_ATTACH,
+            null(),
+            version,
+            0,
+            cursor.buffer,
+            0,
+            0,
+        );
+        marshal(
+            surface,
+            WL_SURFACE_DAMAGE,
+            null(),
+            version,
+            0,
+            0,
+            0,
+            cursor.size.0,
+            cursor.size.1,
+        );
+        self.request(surface, WL_SURFACE_COMMIT);
+        Some((surface, cursor.hotspot))
+    }
+
+    /// `wl_buffer` ARGB8888 com os pixels, num memfd
+    unsafe fn create_shm_buffer(
+        &self,
+        pixels: &[u32],
+        width: i32,
+        height: i32,
+    ) -> Option<*mut Proxy> {
+        let shm = self.shm.get();
+        if shm.is_null() {
+            return None;
+        }
+        let bytes: Vec<u8> = pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes())
+            .collect();
+        let fd = libc::memfd_create(c"avila-shm".as_ptr(), libc::MFD_CLOEXEC);
+        if fd < 0 {
+            return None;
+        }
+        let written = libc::write(fd, bytes.as_ptr() as *const c_void, bytes.len());
+        if written != bytes.len() as isize {
+            libc::close(fd);
+            return None;
+        }
+
+        let c = &self.client;
+        let pool = (c.wl_proxy_marshal_flags)(
+            shm,
+            WL_SHM_CREATE_POOL,
+            self.interfaces.shm_pool,
+            (c.wl_proxy_get_version)(shm),
+            0,
+            null_mut::<c_void>(),
+            fd,
+            bytes.len() as i32,
+        );
+        // O compositor recebe uma cópia do fd
+        libc::close(fd);
+        if pool.is_null() {
+            return None;
+        }
+        let buffer = (c.wl_proxy_marshal_flags)(
+            pool,
+            WL_SHM_POOL_CREATE_BUFFER,
+            self.interfaces.buffer,
+            (c.wl_proxy_get_version)(pool),
+            0,
+            null_mut::<c_void>(),
+            0,
+            width,
+            height,
+            width * 4,
+            WL_SHM_FORMAT_ARGB8888,
+        );
+        // O buffer continua válido depois que o pool é destruído
+        self.destroy(pool, WL_SHM_POOL_DESTROY);
+        (!buffer.is_null()).then_some(buffer)
+    }
+
+    /// `zwp_relative_pointer_v1` do ponteiro do seat, criado sob demanda
+    unsafe fn relative_pointer(&self) -> *mut Proxy {
+        let manager = self.relative_pointer_manager.get();
+        let pointer = self.pointer.get();
+        if self.relative_pointer.get().is_null() && !manager.is_null() && !pointer.is_null() {
+            let relative = (self.client.wl_proxy_marshal_flags)(
+                manager,
+                RELATIVE_POINTER_MANAGER_GET_RELATIVE_POINTER,
+                &RELATIVE_POINTER_INTERFACE.0,
+                (self.client.wl_proxy_get_version)(manager),
+                0,
+                null_mut::<c_void>(),
+                pointer,
+            );
+            (self.client.wl_proxy_add_listener)(
+                relative,
+                &RELATIVE_POINTER_LISTENER as *const RelativePointerListener as *const c_void,
+                self as *const Self as *mut c_void,
+            );
+            self.relative_pointer.set(relative);
+        }
+        self.relative_pointer.get()
+    }
+}
+
+impl Drop for WaylandConnection {
+    fn drop(&mut self) {
+        unsafe {
+            let c = &self.client;
+            if let Some(library) = &self.cursor_library {
+                if !self.cursor_theme.get().is_null() {
+                    (library.wl_cursor_theme_destroy)(self.cursor_theme.get());
+                }
+            }
+            self.destroy(self.cursor_surface.get(), WL_SURFACE_DESTROY);
+            self.destroy(self.relative_pointer.get(), RELATIVE_POINTER_DESTROY);
+            self.destroy(
+                self.relative_pointer_manager.get(),
+                RELATIVE_POINTER_MANAGER_DESTROY,
+            );
+            self.destroy(self.pointer_constraints.get(), POINTER_CONSTRAINTS_DESTROY);
+            self.destroy(
+                self.toplevel_icon_manager.get(),
+                TOPLEVEL_ICON_MANAGER_DESTROY,
+            );
+            self.destroy(self.alpha_modifier.get(), ALPHA_MODIFIER_DESTROY);
+            for proxy in [
+                self.pointer.get(),
+                self.keyboard.get(),
+                self.touch.get(),
+                self.seat.get(),
+                self.shm.get(),
+                self.decoration_manager.get(),
+                self.wm_base.get(),
+                self.compositor.get(),
+                self.registry.get(),
+            ] {
+                if !proxy.is_null() {
+                    (c.wl_proxy_destroy)(proxy);
+                }
+            }
+            for output in self.outputs.borrow().iter() {
+                (c.wl_proxy_destroy)(output.proxy);
+            }
+            (c.wl_display_disconnect)(self.display);
+        }
+    }
+}
+
+// Listeners: structs de ponteiros de função na ordem dos eventos do protocolo
+
+#[repr(C)]
+struct RegistryListener {
+    global: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *const c_char, u32),
+    global_remove: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+static REGISTRY_LISTENER: RegistryListener = RegistryListener {
+    global: registry_global,
+    global_remove: registry_global_remove,
+};
+
+unsafe extern "C" fn registry_global(
+    data: *mut c_void,
+    _registry: *mut Proxy,
+    name: u32,
+    interface: *const c_char,
+    version: u32,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let interfaces = &connection.interfaces;
+    match CStr::from_ptr(interface).to_bytes() {
+        b"wl_compositor" => {
+            let proxy = connection.bind(name, interfaces.compositor, version.min(4));
+            connection.compositor.set(proxy);
+        }
+        b"xdg_wm_base" => {
+            let proxy = connection.bind(name, &XDG_WM_BASE_INTERFACE.0, 1);
+            (connection.client.wl_proxy_add_listener)(
+                proxy,
+                &WM_BASE_LISTENER as *const WmBaseListener as *const c_void,
+                data,
+            );
+            connection.wm_base.set(proxy);
+        }
+        b"wl_seat" if connection.seat.get().is_null() => {
+            let proxy = connection.bind(name, interfaces.seat, version.min(5));
+            (connection.client.wl_proxy_add_listener)(
+                proxy,
+                &SEAT_LISTENER as *const SeatListener as *const c_void,
+                data,
+            );
+            connection.seat.set(proxy);
+        }
+        b"wl_output" => {
+            let proxy = connection.bind(name, interfaces.output, version.min(3));
+            connection.outputs.borrow_mut().push(Output {
+                global: name,
+                proxy,
+                name: format!("Wayland output {}", name),
+                position: (0, 0),
+                size: (0, 0),
+                refresh_rate: 60,
+                scale: 1,
+            });
+            (connection.client.wl_proxy_add_listener)(
+                proxy,
+                &OUTPUT_LISTENER as *const OutputListener as *const c_void,
+                data,
+            );
+        }
+        b"wl_shm" => {
+            let proxy = connection.bind(name, interfaces.shm, 1);
+            connection.shm.set(proxy);
+        }
+        b"zxdg_decoration_manager_v1" => {
+            let proxy = connection.bind(name, &DECORATION_MANAGER_INTERFACE.0, 1);
+            connection.decoration_manager.set(proxy);
+        }
+        b"zwp_pointer_constraints_v1" => {
+            let proxy = connection.bind(name, &POINTER_CONSTRAINTS_INTERFACE.0, 1);
+            connection.pointer_constraints.set(proxy);
+        }
+        b"zwp_relative_pointer_manager_v1" => {
+            let proxy = connection.bind(name, &RELATIVE_POINTER_MANAGER_INTERFACE.0, 1);
+            connection.relative_pointer_manager.set(proxy);
+        }
+        b"xdg_toplevel_icon_manager_v1" => {
+            let proxy = connection.bind(name, &TOPLEVEL_ICON_MANAGER_INTERFACE.0, 1);
+            connection.toplevel_icon_manager.set(proxy);
+        }
+        b"wp_alpha_modifier_v1" => {
+            let proxy = connection.bind(name, &ALPHA_MODIFIER_INTERFACE.0, 1);
+            connection.alpha_modifier.set(proxy);
+        }
+        _ => {}
+    }
+}
+
+unsafe extern "C" fn registry_global_remove(data: *mut c_void, _registry: *mut Proxy, name: u32) {
+    let connection = &*(data as *const WaylandConnection);
+    let mut outputs = connection.outputs.borrow_mut();
+    if let Some(index) = outputs.iter().position(|o| o.global == name) {
+        let output = outputs.remove(index);
+        (connection.client.wl_proxy_destroy)(output.proxy);
+    }
+}
+
+#[repr(C)]
+struct WmBaseListener {
+    ping: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+static WM_BASE_LISTENER: WmBaseListener = WmBaseListener { ping: wm_base_ping };
+
+unsafe extern "C" fn wm_base_ping(data: *mut c_void, wm_base: *mut Proxy, serial: u32) {
+    let connection = &*(data as *const WaylandConnection);
+    let version = (connection.client.wl_proxy_get_version)(wm_base);
+    (connection.client.wl_proxy_marshal_flags)(
+        wm_base,
+        XDG_WM_BASE_PONG,
+        null(),
+        version,
+        0,
+        serial,
+    );
+}
+
+#[repr(C)]
+struct OutputListener {
+    geometry: unsafe extern "C" fn(
+        *mut c_void,
+        *mut Proxy,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        *const c_char,
+        *const c_char,
+        i32,
+    ),
+    mode: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32, i32, i32),
+    done: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+    scale: unsafe extern "C" fn(*mut c_void, *mut Proxy, i32),
+}
+
+static OUTPUT_LISTENER: OutputListener = OutputListener {
+    geometry: output_geometry,
+    mode: output_mode,
+    done: output_done,
+    scale: output_scale,
+};
+
+unsafe fn with_output(data: *mut c_void, proxy: *mut Proxy, f: impl FnOnce(&mut Output)) {
+    let connection = &*(data as *const WaylandConnection);
+    if let Some(output) = connection
+        .outputs
+        .borrow_mut()
+        .iter_mut()
+        .find(|o| o.proxy == proxy)
+    {
+        f(output);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn output_geometry(
+    data: *mut c_void,
+    output: *mut Proxy,
+    x: i32,
+    y: i32,
+    _physical_width: i32,
+    _physical_height: i32,
+    _subpixel: i32,
+    make: *const c_char,
+    model: *const c_char,
+    _transform: i32,
+) {
+    let make = CStr::from_ptr(make).to_string_lossy();
+    let model = CStr::from_ptr(model).to_string_lossy();
+    with_output(data, output, |o| {
+        o.position = (x, y);
+        o.name = format!("{} {}", make, model).trim().to_string();
+    });
+}
+
+unsafe extern "C" fn output_mode(
+    data: *mut c_void,
+    output: *mut Proxy,
+    flags: u32,
+    width: i32,
+    height: i32,
+    refresh: i32,
+) {
+    if flags & OUTPUT_MODE_CURRENT == 0 {
+        return;
+    }
+    with_output(data, output, |o| {
+        o.size = (width.max(0) as u32, height.max(0) as u32);
+        // mHz
+        o.refresh_rate = (refresh.max(0) as u32).div_ceil(1000);
+    });
+}
+
+unsafe extern "C" fn output_done(_data: *mut c_void, _output: *mut Proxy) {}
+
+unsafe extern "C" fn output_scale(data: *mut c_void, output: *mut Proxy, factor: i32) {
+    with_output(data, output, |o| o.scale = factor.max(1));
+}
+
+#[repr(C)]
+struct SeatListener {
+    capabilities: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+    name: unsafe extern "C" fn(*mut c_void, *mut Proxy, *const c_char),
+}
+
+static SEAT_LISTENER: SeatListener = SeatListener {
+    capabilities: seat_capabilities,
+    name: seat_name,
+};
+
+unsafe extern "C" fn seat_capabilities(data: *mut c_void, seat: *mut Proxy, capabilities: u32) {
+    let connection = &*(data as *const WaylandConnection);
+    let c = &connection.client;
+
+    let has_pointer = capabilities & SEAT_CAPABILITY_POINTER != 0;
+    if has_pointer && connection.pointer.get().is_null() {
+        let pointer =
+            connection.create_child(seat, WL_SEAT_GET_POINTER, connection.interfaces.pointer);
+        (c.wl_proxy_add_listener)(
+            pointer,
+            &POINTER_LISTENER as *const PointerListener as *const c_void,
+            data,
+        );
+        connection.pointer.set(pointer);
+    } else if !has_pointer && !connection.pointer.get().is_null() {
+        connection.destroy(
+            connection.relative_pointer.replace(null_mut()),
+            RELATIVE_POINTER_DESTROY,
+        );
+        release_input(
+            connection,
+            connection.pointer.replace(null_mut()),
+            WL_POINTER_RELEASE,
+        );
+    }
+
+    let has_keyboard = capabilities & SEAT_CAPABILITY_KEYBOARD != 0;
+    if has_keyboard && connection.keyboard.get().is_null() {
+        let keyboard =
+            connection.create_child(seat, WL_SEAT_GET_KEYBOARD, connection.interfaces.keyboard);
+        (c.wl_proxy_add_listener)(
+            keyboard,
+            &KEYBOARD_LISTENER as *const KeyboardListener as *const c_void,
+            data,
+        );
+        connection.keyboard.set(keyboard);
+    } else if !has_keyboard && !connection.keyboard.get().is_null() {
+        release_input(
+            connection,
+            connection.keyboard.replace(null_mut()),
+            WL_KEYBOARD_RELEASE,
+        );
+    }
+
+    let has_touch = capabilities & SEAT_CAPABILITY_TOUCH != 0;
+    if has_touch && connection.touch.get().is_null() {
+        let touch = connection.create_child(seat, WL_SEAT_GET_TOUCH, connection.interfaces.touch);
+        (c.wl_proxy_add_listener)(
+            touch,
+            &TOUCH_LISTENER as *const TouchListener as *const c_void,
+            data,
+        );
+        connection.touch.set(touch);
+    } else if !has_touch && !connection.touch.get().is_null() {
+        release_input(
+            connection,
+            connection.touch.replace(null_mut()),
+            WL_TOUCH_RELEASE,
+        );
+        touch_cancel(data, null_mut());
+    }
+}
+
+/// `release` existe a partir da versão 3; antes só dá para destruir o proxy
+unsafe fn release_input(connection: &WaylandConnection, proxy: *mut Proxy, opcode: u32) {
+    if (connection.client.wl_proxy_get_version)(proxy) >= 3 {
+        connection.destroy(proxy, opcode);
+    } else {
+        (connection.client.wl_proxy_destroy)(proxy);
+    }
+}
+
+unsafe extern "C" fn seat_name(_data: *mut c_void, _seat: *mut Proxy, _name: *const c_char) {}
+
+#[repr(C)]
+struct PointerListener {
+    enter: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy, Fixed, Fixed),
+    leave: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy),
+    motion: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, Fixed, Fixed),
+    button: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32),
+    axis: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, Fixed),
+    frame: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+    axis_source: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+    axis_stop: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32),
+    axis_discrete: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32),
+}
+
+static POINTER_LISTENER: PointerListener = PointerListener {
+    enter: pointer_enter,
+    leave: pointer_leave,
+    motion: pointer_motion,
+    button: pointer_button,
+    axis: pointer_axis,
+    frame: pointer_frame,
+    axis_source: pointer_axis_source,
+    axis_stop: pointer_axis_stop,
+    axis_discrete: pointer_axis_discrete,
+};
+
+unsafe extern "C" fn pointer_enter(
+    data: *mut c_void,
+    _pointer: *mut Proxy,
+    serial: u32,
+    surface: *mut Proxy,
+    x: Fixed,
+    y: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(surface) else {
+        return;
+    };
+    connection.pointer_focus.set(surface as usize);
+    connection.pointer_serial.set(serial);
+    connection.apply_cursor(&state);
+    state
+        .cursor_position
+        .set((fixed_to_f64(x), fixed_to_f64(y)));
+    connection.push(Event::Window(WindowEvent::CursorEntered));
+}
+
+unsafe extern "C" fn pointer_leave(
+    data: *mut c_void,
+    _pointer: *mut Proxy,
+    _serial: u32,
+    surface: *mut Proxy,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    if connection.pointer_focus.get() == surface as usize {
+        connection.pointer_focus.set(0);
+        connection.push(Event::Window(WindowEvent::CursorLeft));
+    }
+}
+
+unsafe extern "C" fn pointer_motion(
+    data: *mut c_void,
+    _pointer: *mut Proxy,
+    _time: u32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(connection.pointer_focus.get() as *mut Proxy) else {
+        return;
+    };
+    let position = (fixed_to_f64(x), fixed_to_f64(y));
+    let last = state.cursor_position.replace(position);
+    connection.push(Event::Mouse(MouseEvent::CursorMoved {
+        position,
+        delta: (position.0 - last.0, position.1 - last.1),
+    }));
+}
+
+unsafe extern "C" fn pointer_button(
+    data: *mut c_void,
+    _pointer: *mut Proxy,
+    serial: u32,
+    _time: u32,
+    button: u32,
+    pressed: u32,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(connection.pointer_focus.get() as *mut Proxy) else {
+        return;
+    };
+    connection.pointer_serial.set(serial);
+    let button = match button {
+        BTN_LEFT => MouseButton::Left,
+        BTN_RIGHT => MouseButton::Right,
+        BTN_MIDDLE => MouseButton::Middle,
+        BTN_SIDE => MouseButton::Back,
+        BTN_EXTRA => MouseButton::Forward,
+        other => MouseButton::Other(other.saturating_sub(BTN_LEFT) as u8),
+    };
+    let position = state.cursor_position.get();
+    let modifiers = connection.modifiers.get();
+    connection.push(Event::Mouse(if pressed != 0 {
+        MouseEvent::ButtonPressed {
+            button,
+            position,
+            modifiers,
+        }
+    } else {
+        MouseEvent::ButtonReleased {
+            button,
+            position,
+            modifiers,
+        }
+    }));
+}
+
+unsafe extern "C" fn pointer_axis(
+    data: *mut c_void,
+    _pointer: *mut Proxy,
+    _time: u32,
+    axis: u32,
+    value: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(connection.pointer_focus.get() as *mut Proxy) else {
+        return;
+    };
+    // Positivo = para baixo/direita; a roda para cima vira delta positivo
+    let steps = fixed_to_f64(value) / AXIS_UNITS_PER_STEP;
+    let delta = if axis == 0 {
+        (0.0, -steps)
+    } else {
+        (steps, 0.0)
+    };
+    connection.push(Event::Mouse(MouseEvent::Scrolled {
+        delta,
+        position: state.cursor_position.get(),
+    }));
+}
+
+unsafe extern "C" fn pointer_frame(_data: *mut c_void, _pointer: *mut Proxy) {}
+
+unsafe extern "C" fn pointer_axis_source(_data: *mut c_void, _pointer: *mut Proxy, _source: u32) {}
+
+unsafe extern "C" fn pointer_axis_stop(
+    _data: *mut c_void,
+    _pointer: *mut Proxy,
+    _time: u32,
+    _axis: u32,
+) {
+}
+
+unsafe extern "C" fn pointer_axis_discrete(
+    _data: *mut c_void,
+    _pointer: *mut Proxy,
+    _axis: u32,
+    _discrete: i32,
+) {
+}
+
+#[repr(C)]
+struct RelativePointerListener {
+    relative_motion:
+        unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, Fixed, Fixed, Fixed, Fixed),
+}
+
+static RELATIVE_POINTER_LISTENER: RelativePointerListener = RelativePointerListener {
+    relative_motion: relative_pointer_motion,
+};
+
+/// Com o ponteiro travado não há `wl_pointer.motion`; o movimento vem daqui
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn relative_pointer_motion(
+    data: *mut c_void,
+    _relative_pointer: *mut Proxy,
+    _utime_hi: u32,
+    _utime_lo: u32,
+    dx: Fixed,
+    dy: Fixed,
+    dx_unaccelerated: Fixed,
+    dy_unaccelerated: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(connection.pointer_focus.get() as *mut Proxy) else {
+        return;
+    };
+    if state.raw_motion.get() {
+        connection.push(Event::Mouse(MouseEvent::RawMotion {
+            delta: (
+                fixed_to_f64(dx_unaccelerated),
+                fixed_to_f64(dy_unaccelerated),
+            ),
+        }));
+    }
+    if state.cursor_grab.get() != CursorGrabMode::Locked {
+        return;
+    }
+    connection.push(Event::Mouse(MouseEvent::CursorMoved {
+        position: state.cursor_position.get(),
+        delta: (fixed_to_f64(dx), fixed_to_f64(dy)),
+    }));
+}
+
+#[repr(C)]
+struct TouchListener {
+    down: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, *mut Proxy, i32, Fixed, Fixed),
+    up: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, i32),
+    motion: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32, Fixed, Fixed),
+    frame: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+    cancel: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+}
+
+static TOUCH_LISTENER: TouchListener = TouchListener {
+    down: touch_down,
+    up: touch_up,
+    motion: touch_motion,
+    frame: touch_frame,
+    cancel: touch_cancel,
+};
+
+/// Ids do wl_touch são `int32` não negativos
+fn touch_event(id: i32, phase: TouchPhase, position: (f64, f64)) -> Event {
+    Event::Touch(TouchEvent::new(id as u32 as u64, phase, position))
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn touch_down(
+    data: *mut c_void,
+    _touch: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    surface: *mut Proxy,
+    id: i32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    if connection.window(surface).is_none() {
+        return;
+    }
+    let position = (fixed_to_f64(x), fixed_to_f64(y));
+    connection.touch_points.borrow_mut().insert(id, position);
+    connection.push(touch_event(id, TouchPhase::Started, position));
+}
+
+unsafe extern "C" fn touch_up(
+    data: *mut c_void,
+    _touch: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    id: i32,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(position) = connection.touch_points.borrow_mut().remove(&id) else {
+        return;
+    };
+    connection.push(touch_event(id, TouchPhase::Ended, position));
+}
+
+unsafe extern "C" fn touch_motion(
+    data: *mut c_void,
+    _touch: *mut Proxy,
+    _time: u32,
+    id: i32,
+    x: Fixed,
+    y: Fixed,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let position = (fixed_to_f64(x), fixed_to_f64(y));
+    match connection.touch_points.borrow_mut().get_mut(&id) {
+        Some(point) => *point = position,
+        None => return,
+    }
+    connection.push(touch_event(id, TouchPhase::Moved, position));
+}
+
+unsafe extern "C" fn touch_frame(_data: *mut c_void, _touch: *mut Proxy) {}
+
+/// O compositor assumiu os toques (gesto do sistema): todos são cancelados
+unsafe extern "C" fn touch_cancel(data: *mut c_void, _touch: *mut Proxy) {
+    let connection = &*(data as *const WaylandConnection);
+    let points: Vec<_> = connection.touch_points.borrow_mut().drain().collect();
+    for (id, position) in points {
+        connection.push(touch_event(id, TouchPhase::Cancelled, position));
+    }
+}
+
+#[repr(C)]
+struct KeyboardListener {
+    keymap: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, i32, u32),
+    enter: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy, *mut WlArray),
+    leave: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, *mut Proxy),
+    key: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32),
+    modifiers: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32, u32, u32, u32, u32),
+    repeat_info: unsafe extern "C" fn(*mut c_void, *mut Proxy, i32, i32),
+}
+
+static KEYBOARD_LISTENER: KeyboardListener = KeyboardListener {
+    keymap: keyboard_keymap,
+    enter: keyboard_enter,
+    leave: keyboard_leave,
+    key: keyboard_key,
+    modifiers: keyboard_modifiers,
+    repeat_info: keyboard_repeat_info,
+};
+
+/// O keymap xkb não é usado: `KeyCode` vem direto do código evdev
+unsafe extern "C" fn keyboard_keymap(
+    _data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _format: u32,
+    fd: i32,
+    _size: u32,
+) {
+    libc::close(fd);
+}
+
+unsafe extern "C" fn keyboard_enter(
+    data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _serial: u32,
+    surface: *mut Proxy,
+    _keys: *mut WlArray,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    let Some(state) = connection.window(surface) else {
+        return;
+    };
+    connection.keyboard_focus.set(surface as usize);
+    state.focused.set(true);
+    connection.push(Event::Window(WindowEvent::Focused));
+}
+
+unsafe extern "C" fn keyboard_leave(
+    data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _serial: u32,
+    surface: *mut Proxy,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    if connection.keyboard_focus.get() != surface as usize {
+        return;
+    }
+    connection.keyboard_focus.set(0);
+    if let Some(state) = connection.window(surface) {
+        state.focused.set(false);
+    }
+    connection.push(Event::Window(WindowEvent::Unfocused));
+}
+
+unsafe extern "C" fn keyboard_key(
+    data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _serial: u32,
+    _time: u32,
+    key: u32,
+    pressed: u32,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    if connection.keyboard_focus.get() == 0 {
+        return;
+    }
+    let Some(code) = keycode_from_evdev(key) else {
+        return;
+    };
+    // O Wayland não repete teclas; o cliente teria que gerar a repetição
+    connection.push(Event::Keyboard(KeyEvent {
+        key: Key::Code(code),
+        scancode: key,
+        state: if pressed != 0 {
+            KeyState::Pressed
+        } else {
+            KeyState::Released
+        },
+        modifiers: connection.modifiers.get(),
+        repeat: false,
+    }));
+}
+
+unsafe extern "C" fn keyboard_modifiers(
+    data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _serial: u32,
+    depressed: u32,
+    latched: u32,
+    _locked: u32,
+    _group: u32,
+) {
+    let connection = &*(data as *const WaylandConnection);
+    // Máscaras dos modificadores reais do xkb: Shift, Control, Mod1, Mod4
+    let mask = depressed | latched;
+    connection.modifiers.set(ModifierKeys::new(
+        mask & 0x01 != 0,
+        mask & 0x04 != 0,
+        mask & 0x08 != 0,
+        mask & 0x40 != 0,
+    ));
+}
+
+unsafe extern "C" fn keyboard_repeat_info(
+    _data: *mut c_void,
+    _keyboard: *mut Proxy,
+    _rate: i32,
+    _delay: i32,
+) {
+}
+
+#[repr(C)]
+struct SurfaceListener {
+    enter: unsafe extern "C" fn(*mut c_void, *mut Proxy, *mut Proxy),
+    leave: unsafe extern "C" fn(*mut c_void, *mut Proxy, *mut Proxy),
+}
+
+static SURFACE_LISTENER: SurfaceListener = SurfaceListener {
+    enter: surface_enter,
+    leave: surface_leave,
+};
+
+unsafe extern "C" fn surface_enter(data: *mut c_void, _surface: *mut Proxy, output: *mut Proxy) {
+    let state = &*(data as *const WaylandState);
+    state.outputs.borrow_mut().push(output as usize);
+    state.update_scale();
+}
+
+unsafe extern "C" fn surface_leave(data: *mut c_void, _surface: *mut Proxy, output: *mut Proxy) {
+    let state = &*(data as *const WaylandState);
+    state.outputs.borrow_mut().retain(|o| *o != output as usize);
+    state.update_scale();
+}
+
+#[repr(C)]
+struct XdgSurfaceListener {
+    configure: unsafe extern "C" fn(*mut c_void, *mut Proxy, u32),
+}
+
+static XDG_SURFACE_LISTENER: XdgSurfaceListener = XdgSurfaceListener {
+    configure: xdg_surface_configure,
+};
+
+unsafe extern "C" fn xdg_surface_configure(
+    data: *mut c_void,
+    xdg_surface: *mut Proxy,
+    serial: u32,
+) {
+    let state = &*(data as *const WaylandState);
+    let connection = &*state.connection;
+    let version = (connection.client.wl_proxy_get_version)(xdg_surface);
+    (connection.client.wl_proxy_marshal_flags)(
+        xdg_surface,
+        XDG_SURFACE_ACK_CONFIGURE,
+        null(),
+        version,
+        0,
+        serial,
+    );
+    state.apply_configure();
+    connection.request(state.surface, WL_SURFACE_COMMIT);
+}
+
+#[repr(C)]
+struct ToplevelListener {
+    configure: unsafe extern "C" fn(*mut c_void, *mut Proxy, i32, i32, *mut WlArray),
+    close: unsafe extern "C" fn(*mut c_void, *mut Proxy),
+}
+
+static TOPLEVEL_LISTENER: ToplevelListener = ToplevelListener {
+    configure: toplevel_configure,
+    close: toplevel_close,
+};
+
+unsafe extern "C" fn toplevel_configure(
+    data: *mut c_void,
+    _toplevel: *mut Proxy,
+    width: i32,
+    height: i32,
+    states: *mut WlArray,
+) {
+    let state = &*(data as *const WaylandState);
+    // 0x0 = o cliente escolhe o tamanho
+    if width > 0 && height > 0 {
+        state.pending_size.set(Some((width as u32, height as u32)));
+    }
+    let states = if states.is_null() || (*states).data.is_null() {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts((*states).data as *const u32, (*states).size / 4)
+    };
+    state
+        .pending_maximized
+        .set(states.contains(&XDG_TOPLEVEL_STATE_MAXIMIZED));
+    state
+        .pending_fullscreen
+        .set(states.contains(&XDG_TOPLEVEL_STATE_FULLSCREEN));
+}
+
+unsafe extern "C" fn toplevel_close(data: *mut c_void, _toplevel: *mut Proxy) {
+    let state = &*(data as *const WaylandState);
+    (*state.connection).push(Event::Window(WindowEvent::Closed));
+}
+
+/// Buffer de cursor (do tema ou customizado) com tamanho e hotspot
+#[derive(Clone, Copy)]
+struct CursorBuffer {
+    buffer: *mut Proxy,
+    size: (i32, i32),
+    hotspot: (i32, i32),
+}
+
+/// Ícone enviado ao compositor; o buffer vive enquanto o ícone estiver em uso
+#[derive(Clone, Copy)]
+struct ToplevelIcon {
+    icon: *mut Proxy,
+    buffer: *mut Proxy,
+}
+
+/// Pixels do ícone centralizados num quadrado (o protocolo só aceita
+/// buffers quadrados)
+fn square_icon(icon: &WindowIcon) -> (Vec<u32>, i32) {
+    let (width, height) = (icon.width() as usize, icon.height() as usize);
+    let size = width.max(height);
+    let (left, top) = ((size - width) / 2, (size - height) / 2);
+    let argb = premultiplied_argb(icon.rgba());
+    let mut pixels = vec![0; size * size];
+    for (y, row) in argb.chunks_exact(width).enumerate() {
+        let start = (top + y) * size + left;
+        pixels[start..start + width].copy_from_slice(row);
+    }
+    (pixels, size as i32)
+}
+
+/// Estado por janela; endereço estável (Rc) usado como `data` dos listeners
+struct WaylandState {
+    connection: *const WaylandConnection,
+    surface: *mut Proxy,
+    /// Tamanho lógico atual
+    size: Cell<(u32, u32)>,
+    pending_size: Cell<Option<(u32, u32)>>,
+    pending_maximized: Cell<bool>,
+    pending_fullscreen: Cell<bool>,
+    maximized: Cell<bool>,
+    fullscreen: Cell<bool>,
+    configured: Cell<bool>,
+    scale: Cell<i32>,
+    scale_policy: ScalePolicy,
+    focused: Cell<bool>,
+    cursor_visible: Cell<bool>,
+    cursor_icon: Cell<CursorIcon>,
+    /// Tem prioridade sobre `cursor_icon`
+    custom_cursor: Cell<Option<CursorBuffer>>,
+    cursor_grab: Cell<CursorGrabMode>,
+    raw_motion: Cell<bool>,
+    cursor_position: Cell<(f64, f64)>,
+    /// Outputs em que a superfície está
+    outputs: RefCell<Vec<usize>>,
+}
+
+impl WaylandState {
+    fn push_resize(&self) {
+        let connection = unsafe { &*self.connection };
+        let (width, height) = self.size.get();
+        let scale = self.scale.get() as u32;
+        connection.push(Event::Window(WindowEvent::Resized(WindowSize::new(
+            width, height,
+        ))));
+        connection.push(Event::Window(WindowEvent::FramebufferResized(
+            width * scale,
+            height * scale,
+        )));
+    }
+
+    fn apply_configure(&self) {
+        let connection = unsafe { &*self.connection };
+        let first = !self.configured.replace(true);
+        let resized = match self.pending_size.take() {
+            Some(size) => self.size.replace(size) != size,
+            None => false,
+        };
+        if first || resized {
+            self.push_resize();
+        }
+
+        let maximized = self.pending_maximized.get();
+        if self.maximized.replace(maximized) != maximized {
+            connection.push(Event::Window(if maximized {
+                WindowEvent::Maximized
+            } else {
+                WindowEvent::Restored
+            }));
+        }
+        self.fullscreen.set(self.pending_fullscreen.get());
+    }
+
+    /// Escala = maior scale entre os outputs em que a superfície está
+    fn update_scale(&self) {
+        let connection = unsafe { &*self.connection };
+        let scale = self
+            .outputs
+            .borrow()
+            .iter()
+            .map(|output| connection.output_scale(*output as *mut Proxy))
+            .max()
+            .unwrap_or(1);
+        let old_scale = self.scale.replace(scale);
+        if old_scale == scale {
+            return;
+        }
+        unsafe {
+            let version = (connection.client.wl_proxy_get_version)(self.surface);
+            if version >= 3 {
+                (connection.client.wl_proxy_marshal_flags)(
+                    self.surface,
+                    WL_SURFACE_SET_BUFFER_SCALE,
+                    null(),
+                    version,
+                    0,
+                    scale,
+                );
+            }
+        }
+        // Com `Physical` a janela flutuante mantém o tamanho em pixels
+        let (width, height) = self.size.get();
+        let current = WindowSize::new(width, height);
+        let floating = !self.maximized.get() && !self.fullscreen.get();
+        let logical = if self.scale_policy == ScalePolicy::Physical && floating {
+            current
+                .to_physical(old_scale as f32)
+                .to_logical(scale as f32)
+        } else {
+            current
+        };
+        let new_inner_size = logical.to_physical(scale as f32);
+        connection.push(Event::Window(WindowEvent::ScaleFactorChanged {
+            scale_factor: scale as f32,
+            new_inner_size,
+        }));
+        if logical != current {
+            self.size.set((logical.width, logical.height));
+            self.push_resize();
+        } else {
+            connection.push(Event::Window(WindowEvent::FramebufferResized(
+                new_inner_size.width,
+                new_inner_size.height,
+            )));
+        }
+    }
+}
+
+/// Janela Wayland (xdg_toplevel)
+pub(crate) struct WaylandWindow {
+    connection: Rc<WaylandConnection>,
+    state: Rc<WaylandState>,
+    xdg_surface: *mut Proxy,
+    toplevel: *mut Proxy,
+    decoration: *mut Proxy,
+    /// `zwp_locked_pointer_v1` ou `zwp_confined_pointer_v1` ativo
+    constraint: Cell<*mut Proxy>,
+    icon: Cell<Option<ToplevelIcon>>,
+    /// `wp_alpha_modifier_surface_v1`, criado no primeiro `set_opacity`
+    alpha_surface: Cell<*mut Proxy>,
+    resizable: bool,
+    min_size: Option<WindowSize>,
+    max_size: Option<WindowSize>,
+}
+
+impl WaylandWindow {
+    pub(super) fn create(config: &WindowConfig) -> Result<Self, WindowError> {
+        let connection = connection().map_err(WindowError::CreationFailed)?;
+        unsafe {
+            let surface = connection.create_child(
+                connection.compositor.get(),
+                WL_COMPOSITOR_CREATE_SURFACE,
+                connection.interfaces.surface,
+            );
+            if surface.is_null() {
+                return Err(WindowError::CreationFailed(
+                    "wl_compositor.create_surface failed".to_string(),
+                ));
+            }
+            let state = Rc::new(WaylandState {
+                connection: Rc::as_ptr(&connection),
+                surface,
+                size: Cell::new((config.size.width.max(1), config.size.height.max(1))),
+                pending_size: Cell::new(None),
+                pending_maximized: Cell::new(false),
+                pending_fullscreen: Cell::new(false),
+                maximized: Cell::new(false),
+                fullscreen: Cell::new(false),
+                configured: Cell::new(false),
+                scale: Cell::new(1),
+                scale_policy: config.scale_policy,
+                focused: Cell::new(false),
+                cursor_visible: Cell::new(true),
+                cursor_icon: Cell::new(CursorIcon::Default),
+                custom_cursor: Cell::new(None),
+                cursor_grab: Cell::new(CursorGrabMode::None),
+                raw_motion: Cell::new(false),
+                cursor_position: Cell::new((0.0, 0.0)),
+                outputs: RefCell::new(Vec::new()),
+            });
+            let data = Rc::as_ptr(&state) as *mut c_void;
+            let c = &connection.client;
+            (c.wl_proxy_add_listener)(
+                surface,
+                &SURFACE_LISTENER as *const SurfaceListener as *const c_void,
+                data,
+            );
+
+            let wm_base = connection.wm_base.get();
+            let version = (c.wl_proxy_get_version)(wm_base);
+            let xdg_surface = (c.wl_proxy_marshal_flags)(
+                wm_base,
+                XDG_WM_BASE_GET_XDG_SURFACE,
+                &XDG_SURFACE_INTERFACE.0,
+                version,
+                0,
+                null_mut::<c_void>(),
+                surface,
+            );
+            (c.wl_proxy_add_listener)(
+                xdg_surface,
+                &XDG_SURFACE_LISTENER as *const XdgSurfaceListener as *const c_void,
+                data,
+            );
+            let toplevel = connection.create_child(
+                xdg_surface,
+                XDG_SURFACE_GET_TOPLEVEL,
+                &XDG_TOPLEVEL_INTERFACE.0,
+            );
+            (c.wl_proxy_add_listener)(
+                toplevel,
+                &TOPLEVEL_LISTENER as *const ToplevelListener as *const c_void,
+                data,
+            );
+
+            // Sem o protocolo de decoração o compositor decide (GNOME não
+            // desenha bordas para clientes)
+            let mut decoration = null_mut();
+            let manager = connection.decoration_manager.get();
+            if !manager.is_null() {
+                let version = (c.wl_proxy_get_version)(manager);
+                decoration = (c.wl_proxy_marshal_flags)(
+                    manager,
+                    DECORATION_MANAGER_GET_TOPLEVEL_DECORATION,
+                    &DECORATION_INTERFACE.0,
+                    version,
+                    0,
+                    null_mut::<c_void>(),
+                    toplevel,
+                );
+                let mode = if config.decorated {
+                    DECORATION_MODE_SERVER_SIDE
+                } else {
+                    DECORATION_MODE_CLIENT_SIDE
+                };
+                let version = (c.wl_proxy_get_version)(decoration);
+                (c.wl_proxy_marshal_flags)(
+                    decoration,
+                    DECORATION_SET_MODE,
+                    null(),
+                    version,
+                    0,
+                    mode,
+                );
+            }
+
+            connection
+                .windows
+                .borrow_mut()
+                .insert(surface as usize, Rc::clone(&state));
+            let window = Self {
+                connection,
+                state,
+                xdg_surface,
+                toplevel,
+                decoration,
+                constraint: Cell::new(null_mut()),
+                icon: Cell::new(None),
+                alpha_surface: Cell::new(null_mut()),
+                resizable: config.resizable,
+                min_size: config.min_size,
+                max_size: config.max_size,
+            };
+            window.set_title(&config.title);
+            window.set_size_limits(config.size);
+            match config.display_mode {
+                DisplayMode::Windowed => {}
+                mode => window.set_display_mode(mode)?,
+            }
+
+            // Commit sem buffer pede o primeiro configure
+            window.connection.request(surface, WL_SURFACE_COMMIT);
+            (window.connection.client.wl_display_roundtrip)(window.connection.display);
+            Ok(window)
+        }
+    }
+
+    fn set_size_limits(&self, size: WindowSize) {
+        let (min, max) = if self.resizable {
+            (self.min_size, self.max_size)
+        } else {
+            (Some(size), Some(size))
+        };
+        let (min, max) = (min.map(|s| self.logical(s)), max.map(|s| self.logical(s)));
+        let c = &self.connection.client;
+        unsafe {
+            let version = (c.wl_proxy_get_version)(self.toplevel);
+            let (min_width, min_height) = min.map_or((0, 0), |s| (s.width as i32, s.height as i32));
+            let (max_width, max_height) = max.map_or((0, 0), |s| (s.width as i32, s.height as i32));
+            (c.wl_proxy_marshal_flags)(
+                self.toplevel,
+                XDG_TOPLEVEL_SET_MIN_SIZE,
+                null(),
+                version,
+                0,
+                min_width,
+                min_height,
+            );
+            (c.wl_proxy_marshal_flags)(
+                self.toplevel,
+                XDG_TOPLEVEL_SET_MAX_SIZE,
+                null(),
+                version,
+                0,
+                max_width,
+                max_height,
+            );
+        }
+    }
+
+    pub(super) fn set_title(&self, title: &str) {
+        let Ok(title) = CString::new(title) else {
+            return;
+        };
+        let c = &self.connection.client;
+        unsafe {
+            let version = (c.wl_proxy_get_version)(self.toplevel);
+            (c.wl_proxy_marshal_flags)(
+                self.toplevel,
+                XDG_TOPLEVEL_SET_TITLE,
+                null(),
+                version,
+                0,
+                title.as_ptr(),
+            );
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn inner_size(&self) -> WindowSize {
+        let (width, height) = self.state.size.get();
+        WindowSize::new(width, height)
+    }
+
+    pub(super) fn physical_inner_size(&self) -> WindowSize {
+        self.inner_size().to_physical(self.state.scale.get() as f32)
+    }
+
+    /// Tamanho lógico de um tamanho na unidade da `ScalePolicy`
+    fn logical(&self, size: WindowSize) -> WindowSize {
+        self.state
+            .scale_policy
+            .to_logical(size, self.state.scale.get() as f32)
+    }
+
+    /// No Wayland o cliente escolhe o próprio tamanho quando não está
+    /// maximizado nem em fullscreen; o compositor não manda configure
+    pub(super) fn set_inner_size(&self, size: WindowSize) {
+        if !self.resizable {
+            self.set_size_limits(size);
+        }
+        let size = self.logical(size);
+        let size = (size.width.max(1), size.height.max(1));
+        if self.state.size.replace(size) != size {
+            self.state.push_resize();
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn outer_position(&self) -> WindowPosition {
+        WindowPosition::new(0, 0)
+    }
+
+    pub(super) fn set_outer_position(&self, _position: WindowPosition) {}
+
+    pub(super) fn center(&self) {}
+
+    pub(super) fn set_display_mode(&self, mode: DisplayMode) -> Result<(), WindowError> {
+        unsafe {
+            match mode {
+                DisplayMode::Windowed => {
+                    self.connection
+                        .request(self.toplevel, XDG_TOPLEVEL_UNSET_FULLSCREEN);
+                    self.connection
+                        .request(self.toplevel, XDG_TOPLEVEL_UNSET_MAXIMIZED);
+                }
+                DisplayMode::Maximized => {
+                    self.connection
+                        .request(self.toplevel, XDG_TOPLEVEL_UNSET_FULLSCREEN);
+                    self.connection
+                        .request(self.toplevel, XDG_TOPLEVEL_SET_MAXIMIZED);
+                }
+                DisplayMode::FullscreenBorderless | DisplayMode::FullscreenExclusive => {
+                    let c = &self.connection.client;
+                    let version = (c.wl_proxy_get_version)(self.toplevel);
+                    // Output nulo: o compositor escolhe o monitor
+                    (c.wl_proxy_marshal_flags)(
+                        self.toplevel,
+                        XDG_TOPLEVEL_SET_FULLSCREEN,
+                        null(),
+                        version,
+                        0,
+                        null_mut::<c_void>(),
+                    );
+                }
+            }
+        }
+        self.connection.flush();
+        Ok(())
+    }
+
+    pub(super) fn minimize(&self) {
+        unsafe {
+            self.connection
+                .request(self.toplevel, XDG_TOPLEVEL_SET_MINIMIZED);
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn focus(&self) {}
+
+    pub(super) fn is_focused(&self) -> bool {
+        self.state.focused.get()
+    }
+
+    pub(super) fn is_maximized(&self) -> bool {
+        self.state.maximized.get()
+    }
+
+    pub(super) fn set_cursor_visible(&self, visible: bool) {
+        self.state.cursor_visible.set(visible);
+        self.refresh_cursor();
+    }
+
+    /// Reaplica o cursor se o ponteiro estiver sobre a janela
+    fn refresh_cursor(&self) {
+        if self.connection.pointer_focus.get() == self.state.surface as usize {
+            unsafe {
+                self.connection.apply_cursor(&self.state);
+            }
+            self.connection.flush();
+        }
+    }
+
+    pub(super) fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.state.cursor_icon.set(icon);
+        let old = self.state.custom_cursor.take();
+        self.refresh_cursor();
+        self.destroy_custom_cursor(old);
+    }
+
+    pub(super) fn set_cursor_image(&self, image: &CursorImage) -> Result<(), WindowError> {
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        let buffer = unsafe {
+            self.connection
+                .create_shm_buffer(&premultiplied_argb(image.rgba()), width, height)
+        }
+        .ok_or_else(|| {
+            WindowError::PlatformError("cannot create wl_shm buffer for the cursor".to_string())
+        })?;
+        let (hotspot_x, hotspot_y) = image.hotspot();
+        let old = self.state.custom_cursor.replace(Some(CursorBuffer {
+            buffer,
+            size: (width, height),
+            hotspot: (hotspot_x as i32, hotspot_y as i32),
+        }));
+        self.refresh_cursor();
+        self.destroy_custom_cursor(old);
+        Ok(())
+    }
+
+    /// Libera um buffer depois que o novo cursor já foi anexado
+    fn destroy_custom_cursor(&self, cursor: Option<CursorBuffer>) {
+        if let Some(cursor) = cursor {
+            unsafe {
+                self.connection.destroy(cursor.buffer, WL_BUFFER_DESTROY);
+            }
+            self.connection.flush();
+        }
+    }
+
+    pub(super) fn cursor_position(&self) -> (f64, f64) {
+        self.state.cursor_position.get()
+    }
+
+    pub(super) fn set_cursor_position(&self, x: f64, y: f64) {
+        self.state.cursor_position.set((x, y));
+    }
+
+    pub(super) fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), WindowError> {
+        if enabled && unsafe { self.connection.relative_pointer() }.is_null() {
+            return Err(WindowError::PlatformError(
+                "compositor does not support relative pointer motion".to_string(),
+            ));
+        }
+        self.state.raw_motion.set(enabled);
+        self.connection.flush();
+        Ok(())
+    }
+
+    pub(super) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        let connection = &self.connection;
+        let constraints = connection.pointer_constraints.get();
+        let pointer = connection.pointer.get();
+        if mode != CursorGrabMode::None && (constraints.is_null() || pointer.is_null()) {
+            return Err(WindowError::PlatformError(
+                "compositor does not support pointer constraints".to_string(),
+            ));
+        }
+        unsafe {
+            if mode == CursorGrabMode::Locked && connection.relative_pointer().is_null() {
+                return Err(WindowError::PlatformError(
+                    "compositor does not support relative pointer motion".to_string(),
+                ));
+            }
+            connection.destroy(
+                self.constraint.replace(null_mut()),
+                POINTER_CONSTRAINT_DESTROY,
+            );
+            let request = match mode {
+                CursorGrabMode::None => None,
+                CursorGrabMode::Confined => Some((
+                    POINTER_CONSTRAINTS_CONFINE_POINTER,
+                    &CONFINED_POINTER_INTERFACE.0,
+                )),
+                CursorGrabMode::Locked => Some((
+                    POINTER_CONSTRAINTS_LOCK_POINTER,
+                    &LOCKED_POINTER_INTERFACE.0,
+                )),
+            };
+            if let Some((opcode, interface)) = request {
+                // Região nula: a superfície inteira
+                let constraint = (connection.client.wl_proxy_marshal_flags)(
+                    constraints,
+                    opcode,
+                    interface,
+                    (connection.client.wl_proxy_get_version)(constraints),
+                    0,
+                    null_mut::<c_void>(),
+                    self.state.surface,
+                    pointer,
+                    null_mut::<c_void>(),
+                    CONSTRAINT_LIFETIME_PERSISTENT,
+                );
+                self.constraint.set(constraint);
+            }
+        }
+        self.state.cursor_grab.set(mode);
+        connection.flush();
+        Ok(())
+    }
+
+    pub(super) fn set_icon(&self, icon: Option<&WindowIcon>) {
+        let connection = &self.connection;
+        let manager = connection.toplevel_icon_manager.get();
+        if manager.is_null() {
+            return;
+        }
+        let c = &connection.client;
+        unsafe {
+            let icon = match icon {
+                Some(icon) => {
+                    let (pixels, size) = square_icon(icon);
+                    let Some(buffer) = connection.create_shm_buffer(&pixels, size, size) else {
+                        return;
+                    };
+                    let proxy = connection.create_child(
+                        manager,
+                        TOPLEVEL_ICON_MANAGER_CREATE_ICON,
+                        &TOPLEVEL_ICON_INTERFACE.0,
+                    );
+                    (c.wl_proxy_marshal_flags)(
+                        proxy,
+                        TOPLEVEL_ICON_ADD_BUFFER,
+                        null(),
+                        (c.wl_proxy_get_version)(proxy),
+                        0,
+                        buffer,
+                        1,
+                    );
+                    Some(ToplevelIcon {
+                        icon: proxy,
+                        buffer,
+                    })
+                }
+                None => None,
+            };
+            (c.wl_proxy_marshal_flags)(
+                manager,
+                TOPLEVEL_ICON_MANAGER_SET_ICON,
+                null(),
+                (c.wl_proxy_get_version)(manager),
+                0,
+                self.toplevel,
+                icon.map_or(null_mut(), |icon| icon.icon),
+            );
+            self.destroy_icon(self.icon.replace(icon));
+        }
+        connection.flush();
+    }
+
+    unsafe fn destroy_icon(&self, icon: Option<ToplevelIcon>) {
+        if let Some(icon) = icon {
+            self.connection.destroy(icon.icon, TOPLEVEL_ICON_DESTROY);
+            self.connection.destroy(icon.buffer, WL_BUFFER_DESTROY);
+        }
+    }
+
+    /// Multiplicador de alpha aplicado pelo compositor no próximo commit
+    pub(super) fn set_opacity(&self, opacity: f32) {
+        let connection = &self.connection;
+        let manager = connection.alpha_modifier.get();
+        if manager.is_null() {
+            return;
+        }
+        let c = &connection.client;
+        unsafe {
+            if self.alpha_surface.get().is_null() {
+                let proxy = (c.wl_proxy_marshal_flags)(
+                    manager,
+                    ALPHA_MODIFIER_GET_SURFACE,
+                    &ALPHA_MODIFIER_SURFACE_INTERFACE.0,
+                    (c.wl_proxy_get_version)(manager),
+                    0,
+                    null_mut::<c_void>(),
+                    self.state.surface,
+                );
+                self.alpha_surface.set(proxy);
+            }
+            let alpha_surface = self.alpha_surface.get();
+            let factor = (opacity as f64 * u32::MAX as f64).round() as u32;
+            (c.wl_proxy_marshal_flags)(
+                alpha_surface,
+                ALPHA_MODIFIER_SURFACE_SET_MULTIPLIER,
+                null(),
+                (c.wl_proxy_get_version)(alpha_surface),
+                0,
+                factor,
+            );
+            connection.request(self.state.surface, WL_SURFACE_COMMIT);
+        }
+        connection.flush();
+    }
+
+    pub(super) fn set_always_on_top(&self, _always_on_top: bool) {}
+
+    pub(super) fn request_attention(&self) {}
+
+    pub(super) fn scale_factor(&self) -> f32 {
+        self.state.scale.get() as f32
+    }
+
+    pub(super) fn current_monitor(&self) -> Option<MonitorInfo> {
+        let current = self.state.outputs.borrow().first().copied();
+        let outputs = self.connection.outputs.borrow();
+        let index = current
+            .and_then(|proxy| outputs.iter().position(|o| o.proxy as usize == proxy))
+            .unwrap_or(0);
+        outputs.get(index).map(|output| output.info(index == 0))
+    }
+
+    pub(super) fn native_handle(&self) -> NativeHandle {
+        NativeHandle::Wayland {
+            display: self.connection.display,
+            surface: self.state.surface,
+        }
+    }
+}
+
+impl Drop for WaylandWindow {
+    fn drop(&mut self) {
+        let connection = &self.connection;
+        let surface = self.state.surface as usize;
+        connection.windows.borrow_mut().remove(&surface);
+        if connection.pointer_focus.get() == surface {
+            connection.pointer_focus.set(0);
+        }
+        if connection.keyboard_focus.get() == surface {
+            connection.keyboard_focus.set(0);
+        }
+        unsafe {
+            connection.destroy(self.constraint.get(), POINTER_CONSTRAINT_DESTROY);
+            connection.destroy(self.alpha_surface.get(), ALPHA_MODIFIER_SURFACE_DESTROY);
+            self.destroy_icon(self.icon.take());
+            if let Some(cursor) = self.state.custom_cursor.take() {
+                connection.destroy(cursor.buffer, WL_BUFFER_DESTROY);
+            }
+            connection.destroy(self.decoration, DECORATION_DESTROY);
+            connection.destroy(self.toplevel, XDG_TOPLEVEL_DESTROY);
+            connection.destroy(self.xdg_surface, XDG_SURFACE_DESTROY);
+            connection.destroy(self.state.surface, WL_SURFACE_DESTROY);
+        }
+        connection.flush();
+    }
+}
+
+/// Lê e despacha os eventos do compositor; com janelas abertas espera até
+/// o `deadline` se nenhum evento tiver chegado
+pub(super) fn pump_events(deadline: Option<Instant>, out: &mut Vec<Event>) {
+    let Some(connection) = CONNECTION.with(|c| c.borrow().upgrade()) else {
+        return;
+    };
+    let c = &connection.client;
+    let display = connection.display;
+    let has_windows = !connection.windows.borrow().is_empty();
+    loop {
+        unsafe {
+            while (c.wl_display_prepare_read)(display) != 0 {
+                (c.wl_display_dispatch_pending)(display);
+            }
+            (c.wl_display_flush)(display);
+
+            let blocking = has_windows && out.is_empty() && connection.queue.borrow().is_empty();
+            let mut fd = libc::pollfd {
+                fd: (c.wl_display_get_fd)(display),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = if blocking { poll_timeout(deadline) } else { 0 };
+            if libc::poll(&mut fd, 1, timeout) > 0 {
+                (c.wl_display_read_events)(display);
+            } else {
+                (c.wl_display_cancel_read)(display);
+            }
+            (c.wl_display_dispatch_pending)(display);
+        }
+        out.append(&mut connection.queue.borrow_mut());
+        let expired = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if expired || !has_windows || !out.is_empty() {
+            break;
+        }
+    }
+}
+
+pub(super) fn available_monitors() -> Option<Vec<MonitorInfo>> {
+    connection().ok().map(|connection| connection.monitors())
+}
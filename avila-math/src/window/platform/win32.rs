@@ -0,0 +1,1581 @@
+//! Backend Win32
+//!
+//! Uma classe de janela é registrada por processo. Cada `NativeWindow` guarda
+//! um `WindowState` no heap, com o ponteiro em `GWLP_USERDATA`, e a `wndproc`
+//! traduz as mensagens em `Event`s numa fila da thread, drenada por
+//! `pump_events`. Por isso a janela só pode ser usada na thread que a criou.
+//!
+//! O processo roda com DPI awareness per-monitor v2: tamanhos da área
+//! cliente e posições do cursor são lógicos (pixels / scale factor), enquanto
+//! posições da janela e dos monitores são em pixels da área de trabalho.
+//! `FramebufferResized` traz o tamanho físico. Ao mudar de monitor, com
+//! `ScalePolicy::Logical` a janela usa o retângulo sugerido pelo Windows;
+//! com `Physical` mantém o tamanho da área cliente em pixels.
+//!
+//! Toques chegam como `WM_POINTER*` e viram `Event::Touch`; as mensagens
+//! seguem para o `DefWindowProcW`, que continua gerando o mouse emulado.
+//!
+//! O cursor da área cliente é aplicado no `WM_SETCURSOR`. No modo `Locked`
+//! o cursor fica confinado e volta ao ponto travado a cada `WM_MOUSEMOVE`.
+//! A opacidade usa uma janela em camadas (`WS_EX_LAYERED`), ligada só
+//! enquanto for menor que 1.0.
+//!
+//! `RawMotion` vem do `WM_INPUT`. O registro do mouse no Raw Input é por
+//! processo, então fica ativo enquanto alguma janela da thread o quiser e
+//! cada janela filtra pelo próprio estado.
+
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::io;
+use std::mem::{size_of, zeroed};
+use std::ptr::{null, null_mut};
+use std::sync::{Once, OnceLock};
+use std::time::Instant;
+
+use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+use windows_sys::Win32::Graphics::Gdi::{
+    ClientToScreen, CreateBitmap, CreateDIBSection, DeleteObject, EnumDisplayMonitors,
+    EnumDisplaySettingsW, GetMonitorInfoW, MonitorFromWindow, ScreenToClient, BITMAPINFO,
+    BITMAPINFOHEADER, DEVMODEW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::UI::HiDpi::{
+    AdjustWindowRectExForDpi, GetDpiForMonitor, GetDpiForSystem, GetDpiForWindow,
+    SetProcessDpiAwarenessContext,
+};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyState, ReleaseCapture, SetCapture, TrackMouseEvent, TRACKMOUSEEVENT,
+};
+use windows_sys::Win32::UI::Input::Pointer::{
+    GetPointerTouchInfo, GetPointerType, POINTER_TOUCH_INFO,
+};
+use windows_sys::Win32::UI::Input::{
+    GetRawInputData, RegisterRawInputDevices, RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER,
+};
+use windows_sys::Win32::UI::Shell::{DragFinish, DragQueryFileW, HDROP};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    ClipCursor, CreateIconIndirect, CreateWindowExW, DefWindowProcW, DestroyIcon, DestroyWindow,
+    DispatchMessageW, FlashWindowEx, GetClientRect, GetWindowRect, LoadCursorW,
+    MsgWaitForMultipleObjectsEx, PeekMessageW, RegisterClassExW, SendMessageW, SetCursor,
+    SetCursorPos, SetForegroundWindow, SetLayeredWindowAttributes, SetWindowPos, SetWindowTextW,
+    ShowWindow, TranslateMessage, CREATESTRUCTW, FLASHWINFO, HCURSOR, HICON, ICONINFO, MINMAXINFO,
+    MSG, WNDCLASSEXW,
+};
+#[cfg(target_pointer_width = "64")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowLongPtrW, SetWindowLongPtrW};
+#[cfg(target_pointer_width = "32")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowLongW, SetWindowLongW};
+
+use crate::window::events::{
+    Event, KeyEvent, KeyState, MouseEvent, TouchEvent, TouchPhase, WindowEvent,
+};
+use crate::window::input::{Key, KeyCode, ModifierKeys, MouseButton};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CursorImage, DisplayMode, MonitorInfo, NativeHandle, ScalePolicy,
+    WindowConfig, WindowError, WindowIcon, WindowPosition, WindowSize,
+};
+
+const WM_DESTROY: u32 = 0x0002;
+const WM_MOVE: u32 = 0x0003;
+const WM_SIZE: u32 = 0x0005;
+const WM_SETFOCUS: u32 = 0x0007;
+const WM_KILLFOCUS: u32 = 0x0008;
+const WM_CLOSE: u32 = 0x0010;
+const WM_SETCURSOR: u32 = 0x0020;
+const WM_GETMINMAXINFO: u32 = 0x0024;
+const WM_SETICON: u32 = 0x0080;
+const WM_NCCREATE: u32 = 0x0081;
+const WM_NCDESTROY: u32 = 0x0082;
+const WM_INPUT: u32 = 0x00FF;
+const WM_KEYDOWN: u32 = 0x0100;
+const WM_KEYUP: u32 = 0x0101;
+const WM_SYSKEYDOWN: u32 = 0x0104;
+const WM_SYSKEYUP: u32 = 0x0105;
+const WM_MOUSEMOVE: u32 = 0x0200;
+const WM_LBUTTONDOWN: u32 = 0x0201;
+const WM_LBUTTONUP: u32 = 0x0202;
+const WM_RBUTTONDOWN: u32 = 0x0204;
+const WM_RBUTTONUP: u32 = 0x0205;
+const WM_MBUTTONDOWN: u32 = 0x0207;
+const WM_MBUTTONUP: u32 = 0x0208;
+const WM_MOUSEWHEEL: u32 = 0x020A;
+const WM_XBUTTONDOWN: u32 = 0x020B;
+const WM_XBUTTONUP: u32 = 0x020C;
+const WM_MOUSEHWHEEL: u32 = 0x020E;
+const WM_DROPFILES: u32 = 0x0233;
+const WM_POINTERUPDATE: u32 = 0x0245;
+const WM_POINTERDOWN: u32 = 0x0246;
+const WM_POINTERUP: u32 = 0x0247;
+const WM_MOUSELEAVE: u32 = 0x02A3;
+const WM_DPICHANGED: u32 = 0x02E0;
+
+const SIZE_MINIMIZED: usize = 1;
+const SIZE_MAXIMIZED: usize = 2;
+const HTCLIENT: isize = 1;
+const WHEEL_DELTA: f64 = 120.0;
+const PT_TOUCH: i32 = 2;
+const POINTER_FLAG_INCONTACT: u32 = 0x0004;
+const POINTER_FLAG_CANCELED: u32 = 0x8000;
+const TOUCH_MASK_PRESSURE: u32 = 0x0004;
+/// Faixa de `POINTER_TOUCH_INFO::pressure`
+const MAX_TOUCH_PRESSURE: f32 = 1024.0;
+
+const WS_MAXIMIZEBOX: u32 = 0x0001_0000;
+const WS_MINIMIZEBOX: u32 = 0x0002_0000;
+const WS_THICKFRAME: u32 = 0x0004_0000;
+const WS_SYSMENU: u32 = 0x0008_0000;
+const WS_CAPTION: u32 = 0x00C0_0000;
+const WS_CLIPCHILDREN: u32 = 0x0200_0000;
+const WS_CLIPSIBLINGS: u32 = 0x0400_0000;
+const WS_VISIBLE: u32 = 0x1000_0000;
+const WS_POPUP: u32 = 0x8000_0000;
+const WS_EX_ACCEPTFILES: u32 = 0x0000_0010;
+const WS_EX_APPWINDOW: u32 = 0x0004_0000;
+const WS_EX_LAYERED: u32 = 0x0008_0000;
+
+const SW_SHOW: i32 = 5;
+const SW_MAXIMIZE: i32 = 3;
+const SW_MINIMIZE: i32 = 6;
+const SW_RESTORE: i32 = 9;
+
+const SWP_NOSIZE: u32 = 0x0001;
+const SWP_NOMOVE: u32 = 0x0002;
+const SWP_NOZORDER: u32 = 0x0004;
+const SWP_NOACTIVATE: u32 = 0x0010;
+const SWP_FRAMECHANGED: u32 = 0x0020;
+const SWP_NOOWNERZORDER: u32 = 0x0200;
+const HWND_TOPMOST: isize = -1;
+const HWND_NOTOPMOST: isize = -2;
+
+const GWL_STYLE: i32 = -16;
+const GWL_EXSTYLE: i32 = -20;
+const GWLP_USERDATA: i32 = -21;
+const CS_HREDRAW: u32 = 0x0002;
+const CS_VREDRAW: u32 = 0x0001;
+const CS_OWNDC: u32 = 0x0020;
+const IDC_ARROW: usize = 32512;
+const IDC_IBEAM: usize = 32513;
+const IDC_WAIT: usize = 32514;
+const IDC_CROSS: usize = 32515;
+const IDC_SIZENWSE: usize = 32642;
+const IDC_SIZENESW: usize = 32643;
+const IDC_SIZEWE: usize = 32644;
+const IDC_SIZENS: usize = 32645;
+const IDC_SIZEALL: usize = 32646;
+const IDC_NO: usize = 32648;
+const IDC_HAND: usize = 32649;
+const IDC_APPSTARTING: usize = 32650;
+const IDC_HELP: usize = 32651;
+const ICON_SMALL: usize = 0;
+const ICON_BIG: usize = 1;
+const LWA_ALPHA: u32 = 0x0000_0002;
+const BI_RGB: u32 = 0;
+const DIB_RGB_COLORS: u32 = 0;
+const PM_REMOVE: u32 = 0x0001;
+const QS_ALLINPUT: u32 = 0x04FF;
+const MWMO_INPUTAVAILABLE: u32 = 0x0004;
+const INFINITE: u32 = 0xFFFF_FFFF;
+const FLASHW_ALL: u32 = 0x0003;
+const FLASHW_TIMERNOFG: u32 = 0x000C;
+const TME_LEAVE: u32 = 0x0002;
+const MONITOR_DEFAULTTONEAREST: u32 = 2;
+const MONITORINFOF_PRIMARY: u32 = 1;
+const ENUM_CURRENT_SETTINGS: u32 = 0xFFFF_FFFF;
+const MDT_EFFECTIVE_DPI: i32 = 0;
+const DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2: isize = -4;
+const USER_DEFAULT_SCREEN_DPI: u32 = 96;
+const RID_INPUT: u32 = 0x1000_0003;
+const RIM_TYPEMOUSE: u32 = 0;
+const RIDEV_REMOVE: u32 = 0x0000_0001;
+const MOUSE_MOVE_ABSOLUTE: u16 = 0x0001;
+const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
+const HID_USAGE_GENERIC_MOUSE: u16 = 0x02;
+
+thread_local! {
+    /// Eventos gerados pela `wndproc`, na ordem em que chegaram
+    static EVENT_QUEUE: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    /// Janelas vivas desta thread; sem nenhuma, `pump_events` não bloqueia
+    static LIVE_WINDOWS: Cell<usize> = const { Cell::new(0) };
+    /// Janelas com `RawMotion` ligado; o mouse fica registrado enquanto > 0
+    static RAW_MOTION_WINDOWS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn push_event(event: Event) {
+    EVENT_QUEUE.with(|queue| queue.borrow_mut().push(event));
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+fn loword(value: usize) -> u32 {
+    (value & 0xFFFF) as u32
+}
+
+fn hiword(value: usize) -> u32 {
+    ((value >> 16) & 0xFFFF) as u32
+}
+
+/// Coordenadas com sinal empacotadas em um `LPARAM`
+fn point_from_lparam(lparam: LPARAM) -> (i32, i32) {
+    let x = (lparam & 0xFFFF) as u16 as i16 as i32;
+    let y = ((lparam >> 16) & 0xFFFF) as u16 as i16 as i32;
+    (x, y)
+}
+
+unsafe fn get_window_long(hwnd: HWND, index: i32) -> isize {
+    #[cfg(target_pointer_width = "64")]
+    {
+        GetWindowLongPtrW(hwnd, index)
+    }
+    #[cfg(target_pointer_width = "32")]
+    {
+        GetWindowLongW(hwnd, index) as isize
+    }
+}
+
+unsafe fn set_window_long(hwnd: HWND, index: i32, value: isize) -> isize {
+    #[cfg(target_pointer_width = "64")]
+    {
+        SetWindowLongPtrW(hwnd, index, value)
+    }
+    #[cfg(target_pointer_width = "32")]
+    {
+        SetWindowLongW(hwnd, index, value as i32) as isize
+    }
+}
+
+/// Estado compartilhado entre a `NativeWindow` e a `wndproc`
+///
+/// Só `Cell`s: a `wndproc` é reentrante (`SetWindowPos` entrega `WM_SIZE`
+/// antes de retornar), então nenhum empréstimo pode atravessar uma chamada
+/// ao Win32.
+struct WindowState {
+    dpi: Cell<u32>,
+    focused: Cell<bool>,
+    minimized: Cell<bool>,
+    maximized: Cell<bool>,
+    cursor_visible: Cell<bool>,
+    /// Cursor da área cliente quando visível
+    cursor: Cell<HCURSOR>,
+    /// Cursor criado a partir de um `CursorImage` (destruído ao trocar)
+    custom_cursor: Cell<HCURSOR>,
+    cursor_grab: Cell<CursorGrabMode>,
+    raw_motion: Cell<bool>,
+    /// Ícone criado a partir de um `WindowIcon` (destruído ao trocar)
+    icon: Cell<HICON>,
+    /// Ponto (físico, relativo à área cliente) do modo `Locked`
+    lock_position: Cell<(i32, i32)>,
+    cursor_inside: Cell<bool>,
+    cursor_position: Cell<(f64, f64)>,
+    min_size: Option<WindowSize>,
+    max_size: Option<WindowSize>,
+    scale_policy: ScalePolicy,
+    /// Estilo e retângulo salvos ao entrar em fullscreen
+    saved_placement: Cell<Option<(u32, RECT)>>,
+}
+
+impl WindowState {
+    fn scale(&self) -> f64 {
+        self.dpi.get() as f64 / USER_DEFAULT_SCREEN_DPI as f64
+    }
+
+    fn logical(&self, x: i32, y: i32) -> (f64, f64) {
+        let scale = self.scale();
+        (x as f64 / scale, y as f64 / scale)
+    }
+
+    fn physical_client_size(&self, hwnd: HWND) -> WindowSize {
+        let mut rect: RECT = unsafe { zeroed() };
+        unsafe {
+            GetClientRect(hwnd, &mut rect);
+        }
+        WindowSize::new(
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        )
+    }
+
+    fn grabbed(&self) -> bool {
+        self.cursor_grab.get() != CursorGrabMode::None
+    }
+}
+
+fn window_style(config: &WindowConfig) -> u32 {
+    let mut style = WS_CLIPCHILDREN | WS_CLIPSIBLINGS | WS_SYSMENU | WS_MINIMIZEBOX;
+    if config.decorated {
+        style |= WS_CAPTION;
+    } else {
+        style |= WS_POPUP;
+    }
+    if config.resizable {
+        style |= WS_THICKFRAME | WS_MAXIMIZEBOX;
+    }
+    style
+}
+
+/// Tamanho externo (com bordas) para uma área cliente lógica
+/// Bordas e barra de título somadas, em pixels
+fn frame_size(style: u32, ex_style: u32, dpi: u32) -> (i32, i32) {
+    let mut rect: RECT = unsafe { zeroed() };
+    unsafe {
+        AdjustWindowRectExForDpi(&mut rect, style, 0, ex_style, dpi);
+    }
+    (rect.right - rect.left, rect.bottom - rect.top)
+}
+
+/// Tamanho da janela com bordas para uma área cliente na unidade da política
+fn outer_size(
+    size: WindowSize,
+    policy: ScalePolicy,
+    style: u32,
+    ex_style: u32,
+    dpi: u32,
+) -> (i32, i32) {
+    let size = policy.to_physical(size, dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32);
+    let (frame_width, frame_height) = frame_size(style, ex_style, dpi);
+    (
+        size.width as i32 + frame_width,
+        size.height as i32 + frame_height,
+    )
+}
+
+fn enable_dpi_awareness() {
+    static DPI_AWARENESS: Once = Once::new();
+    DPI_AWARENESS.call_once(|| unsafe {
+        // Falha se um manifesto já definiu o modo; nesse caso vale o manifesto
+        SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2 as _);
+    });
+}
+
+/// Registra a classe de janela uma vez e retorna seu nome
+fn window_class() -> Result<&'static [u16], WindowError> {
+    static CLASS: OnceLock<Result<Vec<u16>, String>> = OnceLock::new();
+    let class = CLASS.get_or_init(|| unsafe {
+        let name = wide("AvilaWindowClass");
+        let class = WNDCLASSEXW {
+            cbSize: size_of::<WNDCLASSEXW>() as u32,
+            style: CS_HREDRAW | CS_VREDRAW | CS_OWNDC,
+            lpfnWndProc: Some(wndproc),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: GetModuleHandleW(null()),
+            hIcon: null_mut(),
+            hCursor: LoadCursorW(null_mut(), IDC_ARROW as *const u16),
+            hbrBackground: null_mut(),
+            lpszMenuName: null(),
+            lpszClassName: name.as_ptr(),
+            hIconSm: null_mut(),
+        };
+        if RegisterClassExW(&class) == 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(name)
+    });
+    match class {
+        Ok(name) => Ok(name),
+        Err(msg) => Err(WindowError::CreationFailed(msg.clone())),
+    }
+}
+
+/// Janela nativa Win32
+pub(crate) struct NativeWindow {
+    hwnd: HWND,
+    /// Endereço estável apontado por `GWLP_USERDATA`
+    state: Box<WindowState>,
+}
+
+impl NativeWindow {
+    pub(crate) fn create(config: &WindowConfig) -> Result<Option<Self>, WindowError> {
+        enable_dpi_awareness();
+        let class = window_class()?;
+
+        let style = window_style(config);
+        let ex_style = WS_EX_APPWINDOW | WS_EX_ACCEPTFILES;
+        // DPI estimado antes da janela existir; corrigido abaixo se ela abrir
+        // em outro monitor
+        let dpi = unsafe { GetDpiForSystem() };
+        let (width, height) = outer_size(config.size, config.scale_policy, style, ex_style, dpi);
+        let (x, y) = if config.position == WindowPosition::CENTERED {
+            let (screen_width, screen_height) = primary_screen_size();
+            ((screen_width - width) / 2, (screen_height - height) / 2)
+        } else {
+            (config.position.x, config.position.y)
+        };
+
+        let state = Box::new(WindowState {
+            dpi: Cell::new(dpi),
+            focused: Cell::new(false),
+            minimized: Cell::new(false),
+            maximized: Cell::new(false),
+            cursor_visible: Cell::new(true),
+            cursor: Cell::new(unsafe { LoadCursorW(null_mut(), IDC_ARROW as *const u16) }),
+            custom_cursor: Cell::new(null_mut()),
+            cursor_grab: Cell::new(CursorGrabMode::None),
+            raw_motion: Cell::new(false),
+            icon: Cell::new(null_mut()),
+            lock_position: Cell::new((0, 0)),
+            cursor_inside: Cell::new(false),
+            cursor_position: Cell::new((0.0, 0.0)),
+            min_size: config.min_size,
+            max_size: config.max_size,
+            scale_policy: config.scale_policy,
+            saved_placement: Cell::new(None),
+        });
+
+        let title = wide(&config.title);
+        let hwnd = unsafe {
+            CreateWindowExW(
+                ex_style,
+                class.as_ptr(),
+                title.as_ptr(),
+                style,
+                x,
+                y,
+                width,
+                height,
+                null_mut(),
+                null_mut(),
+                GetModuleHandleW(null()),
+                &*state as *const WindowState as *const c_void,
+            )
+        };
+        if hwnd.is_null() {
+            return Err(WindowError::CreationFailed(
+                io::Error::last_os_error().to_string(),
+            ));
+        }
+        LIVE_WINDOWS.with(|live| live.set(live.get() + 1));
+
+        let window = Self { hwnd, state };
+        let actual_dpi = unsafe { GetDpiForWindow(hwnd) };
+        if actual_dpi != 0 && actual_dpi != dpi {
+            window.state.dpi.set(actual_dpi);
+            window.set_inner_size(config.size);
+        }
+
+        match config.display_mode {
+            DisplayMode::Windowed => unsafe {
+                ShowWindow(hwnd, SW_SHOW);
+            },
+            DisplayMode::Maximized => unsafe {
+                ShowWindow(hwnd, SW_MAXIMIZE);
+            },
+            mode => {
+                unsafe {
+                    ShowWindow(hwnd, SW_SHOW);
+                }
+                window.set_display_mode(mode)?;
+            }
+        }
+        Ok(Some(window))
+    }
+
+    pub(crate) fn set_title(&self, title: &str) {
+        let title = wide(title);
+        unsafe {
+            SetWindowTextW(self.hwnd, title.as_ptr());
+        }
+    }
+
+    pub(crate) fn inner_size(&self) -> WindowSize {
+        let size = self.physical_inner_size();
+        let (width, height) = self.state.logical(size.width as i32, size.height as i32);
+        WindowSize::new(width.round() as u32, height.round() as u32)
+    }
+
+    pub(crate) fn physical_inner_size(&self) -> WindowSize {
+        self.state.physical_client_size(self.hwnd)
+    }
+
+    pub(crate) fn set_inner_size(&self, size: WindowSize) {
+        unsafe {
+            let style = get_window_long(self.hwnd, GWL_STYLE) as u32;
+            let (width, height) = outer_size(
+                size,
+                self.state.scale_policy,
+                style,
+                WS_EX_APPWINDOW | WS_EX_ACCEPTFILES,
+                self.state.dpi.get(),
+            );
+            SetWindowPos(
+                self.hwnd,
+                null_mut(),
+                0,
+                0,
+                width,
+                height,
+                SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    pub(crate) fn outer_position(&self) -> WindowPosition {
+        let mut rect: RECT = unsafe { zeroed() };
+        unsafe {
+            GetWindowRect(self.hwnd, &mut rect);
+        }
+        WindowPosition::new(rect.left, rect.top)
+    }
+
+    pub(crate) fn set_outer_position(&self, position: WindowPosition) {
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                null_mut(),
+                position.x,
+                position.y,
+                0,
+                0,
+                SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    /// Centraliza na área de trabalho do monitor onde a janela está
+    pub(crate) fn center(&self) {
+        let Some(work) = monitor_rect(self.hwnd, false) else {
+            return;
+        };
+        let mut rect: RECT = unsafe { zeroed() };
+        unsafe {
+            GetWindowRect(self.hwnd, &mut rect);
+        }
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        self.set_outer_position(WindowPosition::new(
+            work.left + (work.right - work.left - width) / 2,
+            work.top + (work.bottom - work.top - height) / 2,
+        ));
+    }
+
+    /// Fullscreen exclusivo também usa uma janela popup do tamanho do
+    /// monitor: com swapchains flip-model o DWM a promove a independent flip,
+    /// sem trocar o modo de vídeo do monitor
+    pub(crate) fn set_display_mode(&self, mode: DisplayMode) -> Result<(), WindowError> {
+        match mode {
+            DisplayMode::Windowed => {
+                self.leave_fullscreen();
+                unsafe {
+                    ShowWindow(self.hwnd, SW_RESTORE);
+                }
+            }
+            DisplayMode::Maximized => {
+                self.leave_fullscreen();
+                unsafe {
+                    ShowWindow(self.hwnd, SW_MAXIMIZE);
+                }
+            }
+            DisplayMode::FullscreenBorderless | DisplayMode::FullscreenExclusive => {
+                let monitor = monitor_rect(self.hwnd, true).ok_or(WindowError::MonitorNotFound)?;
+                unsafe {
+                    if self.state.saved_placement.get().is_none() {
+                        let mut rect: RECT = zeroed();
+                        GetWindowRect(self.hwnd, &mut rect);
+                        let style = get_window_long(self.hwnd, GWL_STYLE) as u32;
+                        self.state.saved_placement.set(Some((style, rect)));
+                    }
+                    set_window_long(self.hwnd, GWL_STYLE, (WS_POPUP | WS_VISIBLE) as isize);
+                    SetWindowPos(
+                        self.hwnd,
+                        null_mut(),
+                        monitor.left,
+                        monitor.top,
+                        monitor.right - monitor.left,
+                        monitor.bottom - monitor.top,
+                        SWP_FRAMECHANGED | SWP_NOOWNERZORDER,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn leave_fullscreen(&self) {
+        let Some((style, rect)) = self.state.saved_placement.take() else {
+            return;
+        };
+        unsafe {
+            set_window_long(self.hwnd, GWL_STYLE, style as isize);
+            SetWindowPos(
+                self.hwnd,
+                null_mut(),
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_FRAMECHANGED | SWP_NOOWNERZORDER | SWP_NOZORDER,
+            );
+        }
+    }
+
+    pub(crate) fn minimize(&self) {
+        unsafe {
+            ShowWindow(self.hwnd, SW_MINIMIZE);
+        }
+    }
+
+    pub(crate) fn focus(&self) {
+        unsafe {
+            SetForegroundWindow(self.hwnd);
+        }
+    }
+
+    pub(crate) fn is_focused(&self) -> bool {
+        self.state.focused.get()
+    }
+
+    pub(crate) fn is_maximized(&self) -> bool {
+        self.state.maximized.get()
+    }
+
+    pub(crate) fn set_cursor_visible(&self, visible: bool) {
+        self.state.cursor_visible.set(visible);
+        self.update_cursor();
+    }
+
+    /// Aplica o cursor sem esperar o próximo WM_SETCURSOR
+    fn update_cursor(&self) {
+        if self.state.cursor_inside.get() {
+            unsafe {
+                if self.state.cursor_visible.get() {
+                    SetCursor(self.state.cursor.get());
+                } else {
+                    SetCursor(null_mut());
+                }
+            }
+        }
+    }
+
+    /// Troca o cursor; `owned` é destruído na próxima troca
+    fn replace_cursor(&self, cursor: HCURSOR, owned: HCURSOR) {
+        self.state.cursor.set(cursor);
+        self.update_cursor();
+        let old = self.state.custom_cursor.replace(owned);
+        if !old.is_null() {
+            unsafe {
+                DestroyIcon(old);
+            }
+        }
+    }
+
+    pub(crate) fn set_cursor_icon(&self, icon: CursorIcon) {
+        let cursor = unsafe { LoadCursorW(null_mut(), system_cursor(icon) as *const u16) };
+        self.replace_cursor(cursor, null_mut());
+    }
+
+    pub(crate) fn set_cursor_image(&self, image: &CursorImage) -> Result<(), WindowError> {
+        let cursor = unsafe {
+            create_icon(
+                image.rgba(),
+                image.width(),
+                image.height(),
+                Some(image.hotspot()),
+            )
+        }
+        .ok_or_else(|| WindowError::PlatformError(io::Error::last_os_error().to_string()))?;
+        self.replace_cursor(cursor, cursor);
+        Ok(())
+    }
+
+    pub(crate) fn cursor_position(&self) -> (f64, f64) {
+        self.state.cursor_position.get()
+    }
+
+    pub(crate) fn set_cursor_position(&self, x: f64, y: f64) {
+        // O WM_MOUSEMOVE gerado pelo warp chega com delta zero
+        self.state.cursor_position.set((x, y));
+        let scale = self.state.scale();
+        let mut point = POINT {
+            x: (x * scale).round() as i32,
+            y: (y * scale).round() as i32,
+        };
+        unsafe {
+            ClientToScreen(self.hwnd, &mut point);
+            SetCursorPos(point.x, point.y);
+        }
+    }
+
+    pub(crate) fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), WindowError> {
+        if self.state.raw_motion.get() == enabled {
+            return Ok(());
+        }
+        let count = RAW_MOTION_WINDOWS.with(|count| count.get());
+        // Só o primeiro a ligar e o último a desligar mexem no registro
+        if (enabled && count == 0) || (!enabled && count == 1) {
+            register_raw_mouse(enabled)?;
+        }
+        RAW_MOTION_WINDOWS.with(|count| {
+            count.set(if enabled {
+                count.get() + 1
+            } else {
+                count.get() - 1
+            })
+        });
+        self.state.raw_motion.set(enabled);
+        Ok(())
+    }
+
+    pub(crate) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        if mode == CursorGrabMode::Locked {
+            let (x, y) = self.state.cursor_position.get();
+            let scale = self.state.scale();
+            self.state
+                .lock_position
+                .set(((x * scale).round() as i32, (y * scale).round() as i32));
+        }
+        self.state.cursor_grab.set(mode);
+        if self.state.grabbed() && self.state.focused.get() {
+            clip_cursor_to_client(self.hwnd);
+        } else if !self.state.grabbed() {
+            unsafe {
+                ClipCursor(null());
+            }
+        }
+        Ok(())
+    }
+
+    /// O mesmo ícone serve para a barra de título e para a taskbar; o
+    /// Windows redimensiona
+    pub(crate) fn set_icon(&self, icon: Option<&WindowIcon>) {
+        unsafe {
+            let handle = match icon {
+                Some(icon) => match create_icon(icon.rgba(), icon.width(), icon.height(), None) {
+                    Some(handle) => handle,
+                    None => return,
+                },
+                // Nulo volta ao ícone da classe
+                None => null_mut(),
+            };
+            SendMessageW(self.hwnd, WM_SETICON, ICON_SMALL, handle as LPARAM);
+            SendMessageW(self.hwnd, WM_SETICON, ICON_BIG, handle as LPARAM);
+            let old = self.state.icon.replace(handle);
+            if !old.is_null() {
+                DestroyIcon(old);
+            }
+        }
+    }
+
+    pub(crate) fn set_opacity(&self, opacity: f32) {
+        unsafe {
+            let ex_style = get_window_long(self.hwnd, GWL_EXSTYLE) as u32;
+            if opacity < 1.0 {
+                set_window_long(self.hwnd, GWL_EXSTYLE, (ex_style | WS_EX_LAYERED) as isize);
+                let alpha = (opacity * 255.0).round() as u8;
+                SetLayeredWindowAttributes(self.hwnd, 0, alpha, LWA_ALPHA);
+            } else if ex_style & WS_EX_LAYERED != 0 {
+                set_window_long(self.hwnd, GWL_EXSTYLE, (ex_style & !WS_EX_LAYERED) as isize);
+            }
+        }
+    }
+
+    pub(crate) fn set_always_on_top(&self, always_on_top: bool) {
+        let insert_after = if always_on_top {
+            HWND_TOPMOST
+        } else {
+            HWND_NOTOPMOST
+        };
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                insert_after as HWND,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    pub(crate) fn request_attention(&self) {
+        let info = FLASHWINFO {
+            cbSize: size_of::<FLASHWINFO>() as u32,
+            hwnd: self.hwnd,
+            dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+            uCount: 0,
+            dwTimeout: 0,
+        };
+        unsafe {
+            FlashWindowEx(&info);
+        }
+    }
+
+    pub(crate) fn scale_factor(&self) -> f32 {
+        self.state.scale() as f32
+    }
+
+    pub(crate) fn current_monitor(&self) -> Option<MonitorInfo> {
+        unsafe { monitor_info(MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST)) }
+    }
+
+    pub(crate) fn native_handle(&self) -> NativeHandle {
+        NativeHandle::Win32 {
+            hwnd: self.hwnd,
+            hinstance: unsafe { GetModuleHandleW(null()) },
+        }
+    }
+}
+
+impl Drop for NativeWindow {
+    fn drop(&mut self) {
+        unsafe {
+            if self.state.grabbed() {
+                ClipCursor(null());
+            }
+            if self.state.raw_motion.get() {
+                let _ = self.set_raw_mouse_motion(false);
+            }
+            // WM_NCDESTROY zera GWLP_USERDATA antes do `state` ser liberado
+            DestroyWindow(self.hwnd);
+            if !self.state.custom_cursor.get().is_null() {
+                DestroyIcon(self.state.custom_cursor.get());
+            }
+            if !self.state.icon.get().is_null() {
+                DestroyIcon(self.state.icon.get());
+            }
+        }
+        LIVE_WINDOWS.with(|live| live.set(live.get().saturating_sub(1)));
+    }
+}
+
+/// Registra (ou remove) o mouse no Raw Input; sem janela alvo o
+/// `WM_INPUT` vai para a janela com foco
+fn register_raw_mouse(enabled: bool) -> Result<(), WindowError> {
+    let device = RAWINPUTDEVICE {
+        usUsagePage: HID_USAGE_PAGE_GENERIC,
+        usUsage: HID_USAGE_GENERIC_MOUSE,
+        dwFlags: if enabled { 0 } else { RIDEV_REMOVE },
+        hwndTarget: null_mut(),
+    };
+    let registered =
+        unsafe { RegisterRawInputDevices(&device, 1, size_of::<RAWINPUTDEVICE>() as u32) };
+    if registered == 0 {
+        return Err(WindowError::PlatformError(format!(
+            "RegisterRawInputDevices failed: {}",
+            io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+/// Delta relativo de um `WM_INPUT` do mouse (tablets mandam posições
+/// absolutas e são ignorados)
+unsafe fn raw_mouse_delta(lparam: LPARAM) -> Option<(f64, f64)> {
+    let mut input: RAWINPUT = zeroed();
+    let mut size = size_of::<RAWINPUT>() as u32;
+    let read = GetRawInputData(
+        lparam as _,
+        RID_INPUT,
+        &mut input as *mut RAWINPUT as *mut c_void,
+        &mut size,
+        size_of::<RAWINPUTHEADER>() as u32,
+    );
+    if read == u32::MAX || input.header.dwType != RIM_TYPEMOUSE {
+        return None;
+    }
+    let mouse = &input.data.mouse;
+    if mouse.usFlags & MOUSE_MOVE_ABSOLUTE != 0 || (mouse.lLastX == 0 && mouse.lLastY == 0) {
+        return None;
+    }
+    Some((mouse.lLastX as f64, mouse.lLastY as f64))
+}
+
+fn clip_cursor_to_client(hwnd: HWND) {
+    unsafe {
+        let mut rect: RECT = zeroed();
+        GetClientRect(hwnd, &mut rect);
+        let mut top_left = POINT {
+            x: rect.left,
+            y: rect.top,
+        };
+        let mut bottom_right = POINT {
+            x: rect.right,
+            y: rect.bottom,
+        };
+        ClientToScreen(hwnd, &mut top_left);
+        ClientToScreen(hwnd, &mut bottom_right);
+        let clip = RECT {
+            left: top_left.x,
+            top: top_left.y,
+            right: bottom_right.x,
+            bottom: bottom_right.y,
+        };
+        ClipCursor(&clip);
+    }
+}
+
+fn system_cursor(icon: CursorIcon) -> usize {
+    match icon {
+        CursorIcon::Default => IDC_ARROW,
+        CursorIcon::Pointer | CursorIcon::Grab => IDC_HAND,
+        CursorIcon::Text => IDC_IBEAM,
+        CursorIcon::Crosshair => IDC_CROSS,
+        CursorIcon::Wait => IDC_WAIT,
+        CursorIcon::Progress => IDC_APPSTARTING,
+        CursorIcon::Help => IDC_HELP,
+        CursorIcon::NotAllowed => IDC_NO,
+        CursorIcon::Move | CursorIcon::Grabbing => IDC_SIZEALL,
+        CursorIcon::EwResize => IDC_SIZEWE,
+        CursorIcon::NsResize => IDC_SIZENS,
+        CursorIcon::NeswResize => IDC_SIZENESW,
+        CursorIcon::NwseResize => IDC_SIZENWSE,
+    }
+}
+
+/// Ícone de 32 bits com alpha (BGRA, sem pré-multiplicação); com `hotspot`
+/// vira um cursor
+unsafe fn create_icon(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    hotspot: Option<(u32, u32)>,
+) -> Option<HICON> {
+    let (width, height) = (width as i32, height as i32);
+    let mut info: BITMAPINFO = zeroed();
+    info.bmiHeader = BITMAPINFOHEADER {
+        biSize: size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width,
+        // Negativo: linhas de cima para baixo, como no RGBA
+        biHeight: -height,
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB,
+        ..zeroed()
+    };
+    let mut bits = null_mut();
+    let color = CreateDIBSection(null_mut(), &info, DIB_RGB_COLORS, &mut bits, null_mut(), 0);
+    if color.is_null() {
+        return None;
+    }
+    let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, rgba.len());
+    for (dst, src) in pixels.chunks_exact_mut(4).zip(rgba.chunks_exact(4)) {
+        dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+    }
+    // Com alpha no bitmap de cor a máscara é ignorada, mas precisa existir
+    let mask_bits = vec![0u8; (width as usize).div_ceil(16) * 2 * height as usize];
+    let mask = CreateBitmap(width, height, 1, 1, mask_bits.as_ptr() as *const c_void);
+
+    let (hotspot_x, hotspot_y) = hotspot.unwrap_or((0, 0));
+    let icon_info = ICONINFO {
+        fIcon: hotspot.is_none() as i32,
+        xHotspot: hotspot_x,
+        yHotspot: hotspot_y,
+        hbmMask: mask,
+        hbmColor: color,
+    };
+    let icon = CreateIconIndirect(&icon_info);
+    DeleteObject(color);
+    DeleteObject(mask);
+    (!icon.is_null()).then_some(icon)
+}
+
+/// Retângulo do monitor da janela (`full`) ou só a área de trabalho
+fn monitor_rect(hwnd: HWND, full: bool) -> Option<RECT> {
+    unsafe {
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info: MONITORINFO = zeroed();
+        info.cbSize = size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut info) == 0 {
+            return None;
+        }
+        Some(if full { info.rcMonitor } else { info.rcWork })
+    }
+}
+
+fn primary_screen_size() -> (i32, i32) {
+    let primary = available_monitors()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|m| m.is_primary);
+    match primary {
+        Some(monitor) => (monitor.size.width as i32, monitor.size.height as i32),
+        None => (1920, 1080),
+    }
+}
+
+unsafe fn monitor_info(monitor: HMONITOR) -> Option<MonitorInfo> {
+    let mut info: MONITORINFOEXW = zeroed();
+    info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+    if GetMonitorInfoW(
+        monitor,
+        &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+    ) == 0
+    {
+        return None;
+    }
+
+    let device = &info.szDevice;
+    let len = device.iter().position(|&c| c == 0).unwrap_or(device.len());
+    let mut mode: DEVMODEW = zeroed();
+    mode.dmSize = size_of::<DEVMODEW>() as u16;
+    let refresh_rate =
+        if EnumDisplaySettingsW(device.as_ptr(), ENUM_CURRENT_SETTINGS, &mut mode) != 0 {
+            mode.dmDisplayFrequency
+        } else {
+            60
+        };
+    let (mut dpi_x, mut dpi_y) = (USER_DEFAULT_SCREEN_DPI, USER_DEFAULT_SCREEN_DPI);
+    GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+
+    let rect = info.monitorInfo.rcMonitor;
+    Some(MonitorInfo {
+        name: String::from_utf16_lossy(&device[..len]),
+        size: WindowSize::new(
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        ),
+        position: WindowPosition::new(rect.left, rect.top),
+        refresh_rate,
+        scale_factor: dpi_x as f32 / USER_DEFAULT_SCREEN_DPI as f32,
+        is_primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+    })
+}
+
+unsafe extern "system" fn enum_monitor(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    data: LPARAM,
+) -> i32 {
+    let monitors = &mut *(data as *mut Vec<MonitorInfo>);
+    if let Some(info) = monitor_info(monitor) {
+        monitors.push(info);
+    }
+    1
+}
+
+pub(crate) fn available_monitors() -> Option<Vec<MonitorInfo>> {
+    enable_dpi_awareness();
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(
+            null_mut(),
+            null(),
+            Some(enum_monitor),
+            &mut monitors as *mut Vec<MonitorInfo> as LPARAM,
+        );
+    }
+    Some(monitors)
+}
+
+/// Despacha as mensagens da thread e move os eventos gerados para `out`
+///
+/// Espera até surgir pelo menos um evento ou chegar o `deadline` (`None`
+/// espera sem limite), a não ser que já haja eventos pendentes ou nenhuma
+/// janela viva.
+pub(crate) fn pump_events(deadline: Option<Instant>, out: &mut Vec<Event>) {
+    unsafe {
+        let mut msg: MSG = zeroed();
+        loop {
+            while PeekMessageW(&mut msg, null_mut(), 0, 0, PM_REMOVE) != 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+            let queued = EVENT_QUEUE.with(|queue| !queue.borrow().is_empty());
+            let live = LIVE_WINDOWS.with(Cell::get) > 0;
+            if queued || !out.is_empty() || !live {
+                break;
+            }
+            // Nenhuma mensagem gerou evento ainda (WM_PAINT, timers...)
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    remaining
+                        .as_nanos()
+                        .div_ceil(1_000_000)
+                        .min(INFINITE as u128 - 1) as u32
+                }
+                None => INFINITE,
+            };
+            MsgWaitForMultipleObjectsEx(0, null(), timeout, QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+        }
+    }
+    EVENT_QUEUE.with(|queue| out.append(&mut queue.borrow_mut()));
+}
+
+unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == WM_NCCREATE {
+        let create = &*(lparam as *const CREATESTRUCTW);
+        set_window_long(hwnd, GWLP_USERDATA, create.lpCreateParams as isize);
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    let state = get_window_long(hwnd, GWLP_USERDATA) as *const WindowState;
+    if state.is_null() {
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+    if msg == WM_NCDESTROY {
+        set_window_long(hwnd, GWLP_USERDATA, 0);
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    match handle_message(&*state, hwnd, msg, wparam, lparam) {
+        Some(result) => result,
+        None => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Traduz uma mensagem; `None` deixa o `DefWindowProcW` tratá-la
+unsafe fn handle_message(
+    state: &WindowState,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> Option<LRESULT> {
+    match msg {
+        // A janela só é destruída quando a `Window` for fechada ou descartada
+        WM_CLOSE => push_event(Event::Window(WindowEvent::Closed)),
+        WM_DESTROY => {
+            if state.grabbed() {
+                ClipCursor(null());
+            }
+        }
+        WM_SIZE => {
+            let (width, height) = (loword(lparam as usize), hiword(lparam as usize));
+            if wparam == SIZE_MINIMIZED {
+                state.minimized.set(true);
+                push_event(Event::Window(WindowEvent::Minimized));
+                return Some(0);
+            }
+            if wparam == SIZE_MAXIMIZED {
+                state.maximized.set(true);
+                push_event(Event::Window(WindowEvent::Maximized));
+            } else if state.minimized.get() || state.maximized.get() {
+                state.maximized.set(false);
+                push_event(Event::Window(WindowEvent::Restored));
+            }
+            state.minimized.set(false);
+
+            let (logical_width, logical_height) = state.logical(width as i32, height as i32);
+            push_event(Event::Window(WindowEvent::Resized(WindowSize::new(
+                logical_width.round() as u32,
+                logical_height.round() as u32,
+            ))));
+            push_event(Event::Window(WindowEvent::FramebufferResized(
+                width, height,
+            )));
+            if state.grabbed() && state.focused.get() {
+                clip_cursor_to_client(hwnd);
+            }
+        }
+        WM_MOVE => {
+            let (x, y) = point_from_lparam(lparam);
+            push_event(Event::Window(WindowEvent::Moved(WindowPosition::new(x, y))));
+            if state.grabbed() && state.focused.get() {
+                clip_cursor_to_client(hwnd);
+            }
+        }
+        WM_SETFOCUS => {
+            state.focused.set(true);
+            if state.grabbed() {
+                clip_cursor_to_client(hwnd);
+            }
+            push_event(Event::Window(WindowEvent::Focused));
+        }
+        WM_KILLFOCUS => {
+            state.focused.set(false);
+            // Não prende o cursor enquanto outra aplicação tem o foco
+            if state.grabbed() {
+                ClipCursor(null());
+            }
+            push_event(Event::Window(WindowEvent::Unfocused));
+        }
+        WM_MOUSEMOVE => {
+            if !state.cursor_inside.get() {
+                state.cursor_inside.set(true);
+                let mut track = TRACKMOUSEEVENT {
+                    cbSize: size_of::<TRACKMOUSEEVENT>() as u32,
+                    dwFlags: TME_LEAVE,
+                    hwndTrack: hwnd,
+                    dwHoverTime: 0,
+                };
+                TrackMouseEvent(&mut track);
+                push_event(Event::Window(WindowEvent::CursorEntered));
+            }
+            let (x, y) = point_from_lparam(lparam);
+            let position = state.logical(x, y);
+            if state.cursor_grab.get() == CursorGrabMode::Locked && state.focused.get() {
+                let (lock_x, lock_y) = state.lock_position.get();
+                // O SetCursorPos de volta gera um WM_MOUSEMOVE no próprio ponto
+                if (x, y) == (lock_x, lock_y) {
+                    return Some(0);
+                }
+                let mut point = POINT {
+                    x: lock_x,
+                    y: lock_y,
+                };
+                ClientToScreen(hwnd, &mut point);
+                SetCursorPos(point.x, point.y);
+                let lock = state.logical(lock_x, lock_y);
+                state.cursor_position.set(lock);
+                push_event(Event::Mouse(MouseEvent::CursorMoved {
+                    position: lock,
+                    delta: (position.0 - lock.0, position.1 - lock.1),
+                }));
+                return Some(0);
+            }
+            let last = state.cursor_position.replace(position);
+            push_event(Event::Mouse(MouseEvent::CursorMoved {
+                position,
+                delta: (position.0 - last.0, position.1 - last.1),
+            }));
+        }
+        WM_MOUSELEAVE => {
+            state.cursor_inside.set(false);
+            push_event(Event::Window(WindowEvent::CursorLeft));
+        }
+        WM_LBUTTONDOWN | WM_LBUTTONUP => mouse_button(
+            state,
+            hwnd,
+            MouseButton::Left,
+            msg == WM_LBUTTONDOWN,
+            lparam,
+        ),
+        WM_RBUTTONDOWN | WM_RBUTTONUP => mouse_button(
+            state,
+            hwnd,
+            MouseButton::Right,
+            msg == WM_RBUTTONDOWN,
+            lparam,
+        ),
+        WM_MBUTTONDOWN | WM_MBUTTONUP => mouse_button(
+            state,
+            hwnd,
+            MouseButton::Middle,
+            msg == WM_MBUTTONDOWN,
+            lparam,
+        ),
+        WM_XBUTTONDOWN | WM_XBUTTONUP => {
+            let button = match hiword(wparam) {
+                1 => MouseButton::Back,
+                2 => MouseButton::Forward,
+                other => MouseButton::Other(other as u8),
+            };
+            mouse_button(state, hwnd, button, msg == WM_XBUTTONDOWN, lparam);
+            // XBUTTON espera TRUE quando tratado
+            return Some(1);
+        }
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+            let amount = hiword(wparam) as u16 as i16 as f64 / WHEEL_DELTA;
+            let delta = if msg == WM_MOUSEWHEEL {
+                (0.0, amount)
+            } else {
+                (amount, 0.0)
+            };
+            push_event(Event::Mouse(MouseEvent::Scrolled {
+                delta,
+                position: state.cursor_position.get(),
+            }));
+        }
+        WM_INPUT => {
+            if state.raw_motion.get() {
+                if let Some(delta) = raw_mouse_delta(lparam) {
+                    push_event(Event::Mouse(MouseEvent::RawMotion { delta }));
+                }
+            }
+            // O DefWindowProcW libera o buffer do WM_INPUT
+            return None;
+        }
+        WM_POINTERDOWN | WM_POINTERUPDATE | WM_POINTERUP => {
+            touch_input(state, hwnd, msg, wparam);
+            // Caneta e mouse seguem o caminho normal; o toque também gera
+            // o mouse emulado para quem só trata cliques
+            return None;
+        }
+        WM_KEYDOWN | WM_SYSKEYDOWN | WM_KEYUP | WM_SYSKEYUP => {
+            let pressed = msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN;
+            let scancode = ((lparam >> 16) & 0xFF) as u32;
+            let extended = (lparam >> 24) & 1 != 0;
+            if let Some(code) = keycode_from_vk(wparam as u32, scancode, extended) {
+                push_event(Event::Keyboard(KeyEvent {
+                    key: Key::Code(code),
+                    scancode: if extended {
+                        scancode | 0xE000
+                    } else {
+                        scancode
+                    },
+                    state: if pressed {
+                        KeyState::Pressed
+                    } else {
+                        KeyState::Released
+                    },
+                    modifiers: current_modifiers(),
+                    repeat: pressed && (lparam >> 30) & 1 != 0,
+                }));
+            }
+            // Teclas de sistema seguem para o Windows (Alt+F4, menu da janela)
+            if msg == WM_SYSKEYDOWN || msg == WM_SYSKEYUP {
+                return None;
+            }
+        }
+        WM_GETMINMAXINFO => {
+            let info = &mut *(lparam as *mut MINMAXINFO);
+            let style = get_window_long(hwnd, GWL_STYLE) as u32;
+            let ex_style = WS_EX_APPWINDOW | WS_EX_ACCEPTFILES;
+            if let Some(min) = state.min_size {
+                let (width, height) =
+                    outer_size(min, state.scale_policy, style, ex_style, state.dpi.get());
+                info.ptMinTrackSize = POINT {
+                    x: width,
+                    y: height,
+                };
+            }
+            if let Some(max) = state.max_size {
+                let (width, height) =
+                    outer_size(max, state.scale_policy, style, ex_style, state.dpi.get());
+                info.ptMaxTrackSize = POINT {
+                    x: width,
+                    y: height,
+                };
+            }
+        }
+        WM_DPICHANGED => {
+            let dpi = loword(wparam);
+            let current = state.physical_client_size(hwnd);
+            // Antes do SetWindowPos, para o WM_SIZE já usar a nova escala
+            state.dpi.set(dpi);
+            let suggested = &*(lparam as *const RECT);
+            let style = get_window_long(hwnd, GWL_STYLE) as u32;
+            let ex_style = WS_EX_APPWINDOW | WS_EX_ACCEPTFILES;
+            let (frame_width, frame_height) = frame_size(style, ex_style, dpi);
+            let (width, height) = match state.scale_policy {
+                ScalePolicy::Logical => (
+                    suggested.right - suggested.left,
+                    suggested.bottom - suggested.top,
+                ),
+                ScalePolicy::Physical => (
+                    current.width as i32 + frame_width,
+                    current.height as i32 + frame_height,
+                ),
+            };
+            // Antes do WM_SIZE gerado pelo SetWindowPos
+            push_event(Event::Window(WindowEvent::ScaleFactorChanged {
+                scale_factor: dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32,
+                new_inner_size: WindowSize::new(
+                    (width - frame_width).max(0) as u32,
+                    (height - frame_height).max(0) as u32,
+                ),
+            }));
+            SetWindowPos(
+                hwnd,
+                null_mut(),
+                suggested.left,
+                suggested.top,
+                width,
+                height,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+        WM_SETCURSOR => {
+            // Fora da área cliente (bordas) o Windows escolhe o cursor
+            if (lparam & 0xFFFF) != HTCLIENT {
+                return None;
+            }
+            if state.cursor_visible.get() {
+                SetCursor(state.cursor.get());
+            } else {
+                SetCursor(null_mut());
+            }
+            return Some(1);
+        }
+        WM_DROPFILES => {
+            let hdrop = wparam as HDROP;
+            let count = DragQueryFileW(hdrop, u32::MAX, null_mut(), 0);
+            for index in 0..count {
+                let len = DragQueryFileW(hdrop, index, null_mut(), 0) as usize;
+                let mut buffer = vec![0u16; len + 1];
+                DragQueryFileW(hdrop, index, buffer.as_mut_ptr(), buffer.len() as u32);
+                push_event(Event::Window(WindowEvent::DroppedFile(
+                    String::from_utf16_lossy(&buffer[..len]),
+                )));
+            }
+            DragFinish(hdrop);
+        }
+        _ => return None,
+    }
+    Some(0)
+}
+
+unsafe fn mouse_button(
+    state: &WindowState,
+    hwnd: HWND,
+    button: MouseButton,
+    pressed: bool,
+    lparam: LPARAM,
+) {
+    let (x, y) = point_from_lparam(lparam);
+    let position = state.logical(x, y);
+    state.cursor_position.set(position);
+    let modifiers = current_modifiers();
+
+    // Captura para receber o "solta" mesmo fora da janela
+    if pressed {
+        SetCapture(hwnd);
+        push_event(Event::Mouse(MouseEvent::ButtonPressed {
+            button,
+            position,
+            modifiers,
+        }));
+    } else {
+        ReleaseCapture();
+        push_event(Event::Mouse(MouseEvent::ButtonReleased {
+            button,
+            position,
+            modifiers,
+        }));
+    }
+}
+
+/// `WM_POINTER*` de um dedo vira `Event::Touch`
+unsafe fn touch_input(state: &WindowState, hwnd: HWND, msg: u32, wparam: WPARAM) {
+    let id = loword(wparam);
+    let mut kind = 0;
+    if GetPointerType(id, &mut kind) == 0 || kind != PT_TOUCH {
+        return;
+    }
+    let mut info: POINTER_TOUCH_INFO = zeroed();
+    if GetPointerTouchInfo(id, &mut info) == 0 {
+        return;
+    }
+    let flags = info.pointerInfo.pointerFlags;
+    let phase = if flags & POINTER_FLAG_CANCELED != 0 {
+        TouchPhase::Cancelled
+    } else {
+        match msg {
+            WM_POINTERDOWN => TouchPhase::Started,
+            WM_POINTERUP => TouchPhase::Ended,
+            _ if flags & POINTER_FLAG_INCONTACT == 0 => return,
+            _ => TouchPhase::Moved,
+        }
+    };
+
+    let mut point = info.pointerInfo.ptPixelLocation;
+    ScreenToClient(hwnd, &mut point);
+    let mut event = TouchEvent::new(id as u64, phase, state.logical(point.x, point.y));
+    if info.touchMask & TOUCH_MASK_PRESSURE != 0 {
+        event = event.with_pressure(info.pressure as f32 / MAX_TOUCH_PRESSURE);
+    }
+    push_event(Event::Touch(event));
+}
+
+fn current_modifiers() -> ModifierKeys {
+    let down = |vk: i32| unsafe { GetKeyState(vk) } < 0;
+    ModifierKeys::new(down(0x10), down(0x11), down(0x12), down(0x5B) || down(0x5C))
+}
+
+/// Virtual-key code do Win32 para `KeyCode`
+///
+/// Shift, Ctrl, Alt e Enter chegam com o mesmo VK dos dois lados; o lado
+/// vem do scancode (Shift) ou do bit de tecla estendida.
+fn keycode_from_vk(vk: u32, scancode: u32, extended: bool) -> Option<KeyCode> {
+    use KeyCode::*;
+
+    const LETTERS: [KeyCode; 26] = [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    ];
+    const DIGITS: [KeyCode; 10] = [Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+    const NUMPAD: [KeyCode; 10] = [
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    ];
+    const FUNCTION: [KeyCode; 24] = [
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20,
+        F21, F22, F23, F24,
+    ];
+
+    let code = match vk {
+        0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+        0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+        0x60..=0x69 => NUMPAD[(vk - 0x60) as usize],
+        0x70..=0x87 => FUNCTION[(vk - 0x70) as usize],
+        0x08 => Backspace,
+        0x09 => Tab,
+        0x0D if extended => NumpadEnter,
+        0x0D => Enter,
+        0x10 if scancode == 0x36 => ShiftRight,
+        0x10 => ShiftLeft,
+        0x11 if extended => ControlRight,
+        0x11 => ControlLeft,
+        0x12 if extended => AltRight,
+        0x12 => AltLeft,
+        0x13 => Pause,
+        0x14 => CapsLock,
+        0x1B => Escape,
+        0x20 => Space,
+        0x21 => PageUp,
+        0x22 => PageDown,
+        0x23 => End,
+        0x24 => Home,
+        0x25 => ArrowLeft,
+        0x26 => ArrowUp,
+        0x27 => ArrowRight,
+        0x28 => ArrowDown,
+        0x2C => PrintScreen,
+        0x2D => Insert,
+        0x2E => Delete,
+        0x5B => MetaLeft,
+        0x5C => MetaRight,
+        0x5D => ContextMenu,
+        0x6A => NumpadMultiply,
+        0x6B => NumpadAdd,
+        0x6D => NumpadSubtract,
+        0x6E => NumpadDecimal,
+        0x6F => NumpadDivide,
+        0x90 => NumLock,
+        0x91 => ScrollLock,
+        0xAD => VolumeMute,
+        0xAE => VolumeDown,
+        0xAF => VolumeUp,
+        0xB0 => MediaTrackNext,
+        0xB1 => MediaTrackPrevious,
+        0xB2 => MediaStop,
+        0xB3 => MediaPlayPause,
+        0xBA => Semicolon,
+        0xBB => Equal,
+        0xBC => Comma,
+        0xBD => Minus,
+        0xBE => Period,
+        0xBF => Slash,
+        0xC0 => Backquote,
+        0xDB => BracketLeft,
+        0xDC => Backslash,
+        0xDD => BracketRight,
+        0xDE => Quote,
+        _ => return None,
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keycode_from_vk() {
+        assert_eq!(keycode_from_vk(0x41, 0x1E, false), Some(KeyCode::A));
+        assert_eq!(keycode_from_vk(0x39, 0x0A, false), Some(KeyCode::Key9));
+        assert_eq!(keycode_from_vk(0x87, 0, false), Some(KeyCode::F24));
+        assert_eq!(keycode_from_vk(0x10, 0x2A, false), Some(KeyCode::ShiftLeft));
+        assert_eq!(
+            keycode_from_vk(0x10, 0x36, false),
+            Some(KeyCode::ShiftRight)
+        );
+        assert_eq!(
+            keycode_from_vk(0x0D, 0x1C, true),
+            Some(KeyCode::NumpadEnter)
+        );
+        assert_eq!(keycode_from_vk(0x12, 0x38, true), Some(KeyCode::AltRight));
+        assert_eq!(keycode_from_vk(0xFF, 0, false), None);
+    }
+
+    #[test]
+    fn test_point_from_lparam_is_signed() {
+        // Monitores à esquerda do primário têm coordenadas negativas
+        let lparam = ((20u32 << 16) | (-5i16 as u16 as u32)) as LPARAM;
+        assert_eq!(point_from_lparam(lparam), (-5, 20));
+    }
+}
@@ -0,0 +1,1981 @@
+//! Backend X11 (XCB)
+//!
+//! Uma conexão por thread, aberta na primeira janela e fechada quando a
+//! última é descartada. `pump_events` lê a fila do XCB e traduz cada evento
+//! para a janela de destino.
+//!
+//! Tamanhos da área cliente e posições do cursor são lógicos: o fator de
+//! escala vem de `Xft.dpi` (como no GTK/Qt), vale para todas as janelas e
+//! não muda, então não há `ScaleFactorChanged`.
+//! O monitor listado é a tela do X inteira; `transparent` é ignorado (usa o
+//! visual da raiz). Touch vem do XInput 2.2 quando a libxcb-xinput existe;
+//! sem ela a janela só recebe os cliques emulados pelo servidor.
+//! `RawMotion` usa o `XI_RawMotion` da janela raiz, entregue à janela com
+//! foco que o tiver ligado.
+//!
+//! Cursores do sistema vêm do tema via libxcb-cursor ou, sem ela, da fonte
+//! `cursor` do servidor; cursores customizados precisam da extensão Render.
+//! O modo `Locked` confina o ponteiro e o devolve ao ponto travado a cada
+//! movimento.
+//!
+//! Ícone, opacidade e "sempre no topo" são propriedades EWMH; dependem do
+//! window manager (a opacidade, de um compositor).
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::ptr::{null, null_mut};
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+use super::dynlib::dynamic_library;
+use super::evdev::keycode_from_evdev;
+use super::linux::{legacy_cursor_name, poll_timeout, premultiplied_argb};
+use crate::window::events::{
+    Event, KeyEvent, KeyState, MouseEvent, TouchEvent, TouchPhase, WindowEvent,
+};
+use crate::window::input::{Key, ModifierKeys, MouseButton};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CursorImage, DisplayMode, MonitorInfo, NativeHandle, ScalePolicy,
+    WindowConfig, WindowError, WindowIcon, WindowPosition, WindowSize,
+};
+
+type XcbConnection = c_void;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Cookie {
+    sequence: u32,
+}
+
+#[repr(C)]
+struct Screen {
+    root: u32,
+    default_colormap: u32,
+    white_pixel: u32,
+    black_pixel: u32,
+    current_input_masks: u32,
+    width_in_pixels: u16,
+    height_in_pixels: u16,
+    width_in_millimeters: u16,
+    height_in_millimeters: u16,
+    min_installed_maps: u16,
+    max_installed_maps: u16,
+    root_visual: u32,
+    backing_stores: u8,
+    save_unders: u8,
+    root_depth: u8,
+    allowed_depths_len: u8,
+}
+
+#[repr(C)]
+struct ScreenIterator {
+    data: *mut Screen,
+    rem: c_int,
+    index: c_int,
+}
+
+#[repr(C)]
+struct GenericEvent {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    pad: [u32; 7],
+    full_sequence: u32,
+}
+
+/// Layout comum de KeyPress, ButtonPress, MotionNotify e EnterNotify
+#[repr(C)]
+struct InputEvent {
+    response_type: u8,
+    detail: u8,
+    sequence: u16,
+    time: u32,
+    root: u32,
+    event: u32,
+    child: u32,
+    root_x: i16,
+    root_y: i16,
+    event_x: i16,
+    event_y: i16,
+    state: u16,
+    /// `same_screen` nos eventos de input, `mode` no EnterNotify
+    mode: u8,
+    pad0: u8,
+}
+
+#[repr(C)]
+struct FocusEvent {
+    response_type: u8,
+    detail: u8,
+    sequence: u16,
+    event: u32,
+    mode: u8,
+    pad0: [u8; 3],
+}
+
+#[repr(C)]
+struct ConfigureEvent {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    event: u32,
+    window: u32,
+    above_sibling: u32,
+    x: i16,
+    y: i16,
+    width: u16,
+    height: u16,
+    border_width: u16,
+    override_redirect: u8,
+    pad1: u8,
+}
+
+#[repr(C)]
+struct ClientMessageEvent {
+    response_type: u8,
+    format: u8,
+    sequence: u16,
+    window: u32,
+    message_type: u32,
+    data: [u32; 5],
+}
+
+#[repr(C)]
+struct PropertyEvent {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    window: u32,
+    atom: u32,
+    time: u32,
+    state: u8,
+    pad1: [u8; 3],
+}
+
+/// `xcb_input_touch_{begin,update,end}_event_t` até `flags`
+#[repr(C)]
+struct XiTouchEvent {
+    response_type: u8,
+    extension: u8,
+    sequence: u16,
+    length: u32,
+    event_type: u16,
+    deviceid: u16,
+    time: u32,
+    /// Id do toque
+    detail: u32,
+    root: u32,
+    event: u32,
+    child: u32,
+    full_sequence: u32,
+    /// Coordenadas em ponto fixo 16.16
+    root_x: i32,
+    root_y: i32,
+    event_x: i32,
+    event_y: i32,
+    buttons_len: u16,
+    valuators_len: u16,
+    sourceid: u16,
+    pad0: u16,
+    flags: u32,
+}
+
+/// `xcb_input_raw_motion_event_t`; depois dele vêm a máscara de valuators
+/// (`valuators_len` u32), os valores acelerados e os crus (`Fp3232`)
+#[repr(C)]
+struct XiRawEvent {
+    response_type: u8,
+    extension: u8,
+    sequence: u16,
+    length: u32,
+    event_type: u16,
+    deviceid: u16,
+    time: u32,
+    detail: u32,
+    sourceid: u16,
+    valuators_len: u16,
+    flags: u32,
+    pad0: [u8; 4],
+    full_sequence: u32,
+}
+
+/// Ponto fixo 32.32 do XInput 2
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Fp3232 {
+    integral: i32,
+    frac: u32,
+}
+
+impl Fp3232 {
+    fn to_f64(self) -> f64 {
+        self.integral as f64 + self.frac as f64 / 4_294_967_296.0
+    }
+}
+
+#[repr(C)]
+struct XiEventMask {
+    deviceid: u16,
+    mask_len: u16,
+    mask: u32,
+}
+
+#[repr(C)]
+struct QueryExtensionReply {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    length: u32,
+    present: u8,
+    major_opcode: u8,
+    first_event: u8,
+    first_error: u8,
+}
+
+/// `xcb_render_pictforminfo_t`
+#[repr(C)]
+struct PictFormInfo {
+    id: u32,
+    kind: u8,
+    depth: u8,
+    pad0: [u8; 2],
+    red_shift: u16,
+    red_mask: u16,
+    green_shift: u16,
+    green_mask: u16,
+    blue_shift: u16,
+    blue_mask: u16,
+    alpha_shift: u16,
+    alpha_mask: u16,
+    colormap: u32,
+}
+
+impl PictFormInfo {
+    /// PICT_FORMAT_TYPE_DIRECT com a32r8g8b8
+    fn is_argb32(&self) -> bool {
+        self.kind == 1
+            && self.depth == 32
+            && (self.alpha_shift, self.alpha_mask) == (24, 0xFF)
+            && (self.red_shift, self.red_mask) == (16, 0xFF)
+            && (self.green_shift, self.green_mask) == (8, 0xFF)
+            && (self.blue_shift, self.blue_mask) == (0, 0xFF)
+    }
+}
+
+#[repr(C)]
+struct XiQueryVersionReply {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    length: u32,
+    major_version: u16,
+    minor_version: u16,
+}
+
+#[repr(C)]
+struct InternAtomReply {
+    response_type: u8,
+    pad0: u8,
+    sequence: u16,
+    length: u32,
+    atom: u32,
+}
+
+#[repr(C)]
+struct TranslateReply {
+    response_type: u8,
+    same_screen: u8,
+    sequence: u16,
+    length: u32,
+    child: u32,
+    dst_x: i16,
+    dst_y: i16,
+}
+
+#[repr(C)]
+struct PropertyReply {
+    response_type: u8,
+    format: u8,
+    sequence: u16,
+    length: u32,
+    property_type: u32,
+    bytes_after: u32,
+    value_len: u32,
+    pad0: [u8; 12],
+}
+
+dynamic_library! {
+    struct Xcb ["libxcb.so.1", "libxcb.so"] {
+        fn xcb_connect(*const c_char, *mut c_int) -> *mut XcbConnection;
+        fn xcb_connection_has_error(*mut XcbConnection) -> c_int;
+        fn xcb_disconnect(*mut XcbConnection);
+        fn xcb_get_setup(*mut XcbConnection) -> *const c_void;
+        fn xcb_setup_roots_iterator(*const c_void) -> ScreenIterator;
+        fn xcb_screen_next(*mut ScreenIterator);
+        fn xcb_generate_id(*mut XcbConnection) -> u32;
+        fn xcb_create_window(
+            *mut XcbConnection, u8, u32, u32, i16, i16, u16, u16, u16, u16, u32, u32, *const c_void
+        ) -> Cookie;
+        fn xcb_destroy_window(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_map_window(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_configure_window(*mut XcbConnection, u32, u16, *const c_void) -> Cookie;
+        fn xcb_change_property(
+            *mut XcbConnection, u8, u32, u32, u32, u8, u32, *const c_void
+        ) -> Cookie;
+        fn xcb_delete_property(*mut XcbConnection, u32, u32) -> Cookie;
+        fn xcb_change_window_attributes(*mut XcbConnection, u32, u32, *const c_void) -> Cookie;
+        fn xcb_intern_atom(*mut XcbConnection, u8, u16, *const c_char) -> Cookie;
+        fn xcb_intern_atom_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut InternAtomReply;
+        fn xcb_translate_coordinates(*mut XcbConnection, u32, u32, i16, i16) -> Cookie;
+        fn xcb_translate_coordinates_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut TranslateReply;
+        fn xcb_get_property(*mut XcbConnection, u8, u32, u32, u32, u32, u32) -> Cookie;
+        fn xcb_get_property_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut PropertyReply;
+        fn xcb_get_property_value(*const PropertyReply) -> *mut c_void;
+        fn xcb_get_property_value_length(*const PropertyReply) -> c_int;
+        fn xcb_send_event(*mut XcbConnection, u8, u32, u32, *const c_char) -> Cookie;
+        fn xcb_warp_pointer(
+            *mut XcbConnection, u32, u32, i16, i16, u16, u16, i16, i16
+        ) -> Cookie;
+        fn xcb_grab_pointer(*mut XcbConnection, u8, u32, u16, u8, u8, u32, u32, u32) -> Cookie;
+        fn xcb_ungrab_pointer(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_create_pixmap(*mut XcbConnection, u8, u32, u32, u16, u16) -> Cookie;
+        fn xcb_free_pixmap(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_create_cursor(
+            *mut XcbConnection, u32, u32, u32, u16, u16, u16, u16, u16, u16, u16, u16
+        ) -> Cookie;
+        fn xcb_free_cursor(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_open_font(*mut XcbConnection, u32, u16, *const c_char) -> Cookie;
+        fn xcb_close_font(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_create_glyph_cursor(
+            *mut XcbConnection, u32, u32, u32, u16, u16, u16, u16, u16, u16, u16, u16
+        ) -> Cookie;
+        fn xcb_create_gc(*mut XcbConnection, u32, u32, u32, *const c_void) -> Cookie;
+        fn xcb_free_gc(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_put_image(
+            *mut XcbConnection, u8, u32, u32, u16, u16, i16, i16, u8, u8, u32, *const u8
+        ) -> Cookie;
+        fn xcb_discard_reply(*mut XcbConnection, u32);
+        fn xcb_query_extension(*mut XcbConnection, u16, *const c_char) -> Cookie;
+        fn xcb_query_extension_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut QueryExtensionReply;
+        fn xcb_flush(*mut XcbConnection) -> c_int;
+        fn xcb_get_file_descriptor(*mut XcbConnection) -> c_int;
+        fn xcb_poll_for_event(*mut XcbConnection) -> *mut GenericEvent;
+        fn xcb_poll_for_queued_event(*mut XcbConnection) -> *mut GenericEvent;
+        fn xcb_wait_for_event(*mut XcbConnection) -> *mut GenericEvent;
+    }
+}
+
+dynamic_library! {
+    struct XcbInput ["libxcb-xinput.so.0", "libxcb-xinput.so"] {
+        fn xcb_input_xi_query_version(*mut XcbConnection, u16, u16) -> Cookie;
+        fn xcb_input_xi_query_version_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut XiQueryVersionReply;
+        fn xcb_input_xi_select_events(
+            *mut XcbConnection, u32, u16, *const XiEventMask
+        ) -> Cookie;
+    }
+}
+
+dynamic_library! {
+    struct XcbCursor ["libxcb-cursor.so.0", "libxcb-cursor.so"] {
+        fn xcb_cursor_context_new(*mut XcbConnection, *mut Screen, *mut *mut c_void) -> c_int;
+        fn xcb_cursor_load_cursor(*mut c_void, *const c_char) -> u32;
+        fn xcb_cursor_context_free(*mut c_void);
+    }
+}
+
+dynamic_library! {
+    struct XcbRender ["libxcb-render.so.0", "libxcb-render.so"] {
+        fn xcb_render_query_pict_formats(*mut XcbConnection) -> Cookie;
+        fn xcb_render_query_pict_formats_reply(
+            *mut XcbConnection, Cookie, *mut *mut c_void
+        ) -> *mut c_void;
+        fn xcb_render_query_pict_formats_formats(*const c_void) -> *const PictFormInfo;
+        fn xcb_render_query_pict_formats_formats_length(*const c_void) -> c_int;
+        fn xcb_render_create_picture(
+            *mut XcbConnection, u32, u32, u32, u32, *const c_void
+        ) -> Cookie;
+        fn xcb_render_free_picture(*mut XcbConnection, u32) -> Cookie;
+        fn xcb_render_create_cursor(*mut XcbConnection, u32, u32, u16, u16) -> Cookie;
+    }
+}
+
+const KEY_PRESS: u8 = 2;
+const KEY_RELEASE: u8 = 3;
+const BUTTON_PRESS: u8 = 4;
+const BUTTON_RELEASE: u8 = 5;
+const MOTION_NOTIFY: u8 = 6;
+const ENTER_NOTIFY: u8 = 7;
+const LEAVE_NOTIFY: u8 = 8;
+const FOCUS_IN: u8 = 9;
+const FOCUS_OUT: u8 = 10;
+const CONFIGURE_NOTIFY: u8 = 22;
+const PROPERTY_NOTIFY: u8 = 28;
+const CLIENT_MESSAGE: u8 = 33;
+const GE_GENERIC: u8 = 35;
+
+const XI_RAW_MOTION: u16 = 17;
+const XI_TOUCH_BEGIN: u16 = 18;
+const XI_TOUCH_UPDATE: u16 = 19;
+const XI_TOUCH_END: u16 = 20;
+const XI_ALL_MASTER_DEVICES: u16 = 1;
+/// Dedo levantado, mas o toque ainda pertence a outro cliente (gesto do WM)
+const XI_TOUCH_PENDING_END: u32 = 1 << 16;
+
+const EVENT_MASK_KEY_PRESS: u32 = 0x1;
+const EVENT_MASK_KEY_RELEASE: u32 = 0x2;
+const EVENT_MASK_BUTTON_PRESS: u32 = 0x4;
+const EVENT_MASK_BUTTON_RELEASE: u32 = 0x8;
+const EVENT_MASK_ENTER_WINDOW: u32 = 0x10;
+const EVENT_MASK_LEAVE_WINDOW: u32 = 0x20;
+const EVENT_MASK_POINTER_MOTION: u32 = 0x40;
+const EVENT_MASK_STRUCTURE_NOTIFY: u32 = 0x2_0000;
+const EVENT_MASK_SUBSTRUCTURE_NOTIFY: u32 = 0x8_0000;
+const EVENT_MASK_SUBSTRUCTURE_REDIRECT: u32 = 0x10_0000;
+const EVENT_MASK_FOCUS_CHANGE: u32 = 0x20_0000;
+const EVENT_MASK_PROPERTY_CHANGE: u32 = 0x40_0000;
+
+const WINDOW_EVENTS: u32 = EVENT_MASK_KEY_PRESS
+    | EVENT_MASK_KEY_RELEASE
+    | EVENT_MASK_BUTTON_PRESS
+    | EVENT_MASK_BUTTON_RELEASE
+    | EVENT_MASK_ENTER_WINDOW
+    | EVENT_MASK_LEAVE_WINDOW
+    | EVENT_MASK_POINTER_MOTION
+    | EVENT_MASK_STRUCTURE_NOTIFY
+    | EVENT_MASK_FOCUS_CHANGE
+    | EVENT_MASK_PROPERTY_CHANGE;
+
+const CW_BACK_PIXEL: u32 = 0x2;
+const CW_EVENT_MASK: u32 = 0x800;
+const CW_CURSOR: u32 = 0x4000;
+const CONFIG_WINDOW_X: u16 = 0x1;
+const CONFIG_WINDOW_Y: u16 = 0x2;
+const CONFIG_WINDOW_WIDTH: u16 = 0x4;
+const CONFIG_WINDOW_HEIGHT: u16 = 0x8;
+const WINDOW_CLASS_INPUT_OUTPUT: u16 = 1;
+const PROP_MODE_REPLACE: u8 = 0;
+const GRAB_MODE_ASYNC: u8 = 1;
+const NOTIFY_MODE_NORMAL: u8 = 0;
+const NOTIFY_MODE_WHILE_GRABBED: u8 = 3;
+const ICONIC_STATE: u32 = 3;
+const IMAGE_FORMAT_Z_PIXMAP: u8 = 2;
+
+const ATOM_ATOM: u32 = 4;
+const ATOM_CARDINAL: u32 = 6;
+const ATOM_RESOURCE_MANAGER: u32 = 23;
+const ATOM_STRING: u32 = 31;
+const ATOM_WM_NAME: u32 = 39;
+const ATOM_WM_NORMAL_HINTS: u32 = 40;
+const ATOM_WM_SIZE_HINTS: u32 = 41;
+
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+/// Átomos usados pelo backend, na ordem de `ATOM_NAMES`
+#[derive(Clone, Copy)]
+enum Atom {
+    WmProtocols,
+    WmDeleteWindow,
+    WmChangeState,
+    NetWmName,
+    Utf8String,
+    NetWmState,
+    NetWmStateFullscreen,
+    NetWmStateMaximizedVert,
+    NetWmStateMaximizedHorz,
+    NetWmStateHidden,
+    NetWmStateDemandsAttention,
+    NetWmStateAbove,
+    NetActiveWindow,
+    NetWmIcon,
+    NetWmWindowOpacity,
+    MotifWmHints,
+}
+
+const ATOM_NAMES: [&str; 16] = [
+    "WM_PROTOCOLS",
+    "WM_DELETE_WINDOW",
+    "WM_CHANGE_STATE",
+    "_NET_WM_NAME",
+    "UTF8_STRING",
+    "_NET_WM_STATE",
+    "_NET_WM_STATE_FULLSCREEN",
+    "_NET_WM_STATE_MAXIMIZED_VERT",
+    "_NET_WM_STATE_MAXIMIZED_HORZ",
+    "_NET_WM_STATE_HIDDEN",
+    "_NET_WM_STATE_DEMANDS_ATTENTION",
+    "_NET_WM_STATE_ABOVE",
+    "_NET_ACTIVE_WINDOW",
+    "_NET_WM_ICON",
+    "_NET_WM_WINDOW_OPACITY",
+    "_MOTIF_WM_HINTS",
+];
+
+struct ScreenInfo {
+    number: i32,
+    root: u32,
+    black_pixel: u32,
+    width: u16,
+    height: u16,
+}
+
+/// Extensão XInput 2.2 (touch e movimento cru)
+struct XInput2 {
+    xcb_input: XcbInput,
+    opcode: u8,
+}
+
+impl XInput2 {
+    unsafe fn load(xcb: &Xcb, raw: *mut XcbConnection) -> Option<Self> {
+        const NAME: &str = "XInputExtension";
+        let cookie =
+            (xcb.xcb_query_extension)(raw, NAME.len() as u16, NAME.as_ptr() as *const c_char);
+        let reply = (xcb.xcb_query_extension_reply)(raw, cookie, null_mut());
+        if reply.is_null() {
+            return None;
+        }
+        let (present, opcode) = ((*reply).present != 0, (*reply).major_opcode);
+        libc::free(reply as *mut c_void);
+        if !present {
+            return None;
+        }
+
+        let xcb_input = XcbInput::load()?;
+        let cookie = (xcb_input.xcb_input_xi_query_version)(raw, 2, 2);
+        let reply = (xcb_input.xcb_input_xi_query_version_reply)(raw, cookie, null_mut());
+        if reply.is_null() {
+            return None;
+        }
+        let version = ((*reply).major_version, (*reply).minor_version);
+        libc::free(reply as *mut c_void);
+        (version >= (2, 2)).then_some(Self { xcb_input, opcode })
+    }
+}
+
+/// Contexto da libxcb-cursor (tema do usuário: Xcursor.theme, XCURSOR_*)
+struct CursorContext {
+    xcb_cursor: XcbCursor,
+    context: *mut c_void,
+}
+
+/// Conexão XCB compartilhada pelas janelas da thread
+struct X11Connection {
+    xcb: Xcb,
+    xinput: Option<XInput2>,
+    cursor_context: Option<CursorContext>,
+    render: Option<XcbRender>,
+    raw: *mut XcbConnection,
+    screen: ScreenInfo,
+    atoms: [u32; ATOM_NAMES.len()],
+    scale: f64,
+    windows: RefCell<HashMap<u32, Rc<X11State>>>,
+    /// Evento lido à frente ao detectar auto-repeat, processado na próxima
+    /// chamada de `next_event`
+    pending: Cell<*mut GenericEvent>,
+}
+
+thread_local! {
+    static CONNECTION: RefCell<Weak<X11Connection>> = const { RefCell::new(Weak::new()) };
+}
+
+/// Conexão da thread, abrindo uma nova se necessário
+fn connection() -> Result<Rc<X11Connection>, String> {
+    if let Some(connection) = CONNECTION.with(|c| c.borrow().upgrade()) {
+        return Ok(connection);
+    }
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err("DISPLAY is not set".to_string());
+    }
+    let xcb = Xcb::load().ok_or_else(|| "libxcb not found".to_string())?;
+    let connection = Rc::new(unsafe { X11Connection::open(xcb)? });
+    CONNECTION.with(|c| *c.borrow_mut() = Rc::downgrade(&connection));
+    Ok(connection)
+}
+
+impl X11Connection {
+    unsafe fn open(xcb: Xcb) -> Result<Self, String> {
+        let mut screen_number: c_int = 0;
+        let raw = (xcb.xcb_connect)(null(), &mut screen_number);
+        if (xcb.xcb_connection_has_error)(raw) != 0 {
+            (xcb.xcb_disconnect)(raw);
+            return Err("cannot connect to the X server".to_string());
+        }
+
+        let mut roots = (xcb.xcb_setup_roots_iterator)((xcb.xcb_get_setup)(raw));
+        for _ in 0..screen_number {
+            (xcb.xcb_screen_next)(&mut roots);
+        }
+        if roots.data.is_null() {
+            (xcb.xcb_disconnect)(raw);
+            return Err(format!("X screen {} not found", screen_number));
+        }
+        let root = &*roots.data;
+        let screen = ScreenInfo {
+            number: screen_number,
+            root: root.root,
+            black_pixel: root.black_pixel,
+            width: root.width_in_pixels,
+            height: root.height_in_pixels,
+        };
+
+        // Todos os pedidos antes das respostas: um round trip só
+        let cookies: Vec<Cookie> = ATOM_NAMES
+            .iter()
+            .map(|name| {
+                (xcb.xcb_intern_atom)(raw, 0, name.len() as u16, name.as_ptr() as *const c_char)
+            })
+            .collect();
+        let mut atoms = [0u32; ATOM_NAMES.len()];
+        for (atom, cookie) in atoms.iter_mut().zip(cookies) {
+            let reply = (xcb.xcb_intern_atom_reply)(raw, cookie, null_mut());
+            if !reply.is_null() {
+                *atom = (*reply).atom;
+                libc::free(reply as *mut c_void);
+            }
+        }
+
+        let xinput = XInput2::load(&xcb, raw);
+        let cursor_context = XcbCursor::load().and_then(|xcb_cursor| {
+            let mut context = null_mut();
+            ((xcb_cursor.xcb_cursor_context_new)(raw, roots.data, &mut context) >= 0).then_some(
+                CursorContext {
+                    xcb_cursor,
+                    context,
+                },
+            )
+        });
+        let mut connection = Self {
+            xcb,
+            xinput,
+            cursor_context,
+            render: XcbRender::load(),
+            raw,
+            screen,
+            atoms,
+            scale: 1.0,
+            windows: RefCell::new(HashMap::new()),
+            pending: Cell::new(null_mut()),
+        };
+        let resources = connection.property(
+            connection.screen.root,
+            ATOM_RESOURCE_MANAGER,
+            ATOM_STRING,
+            16 * 1024,
+        );
+        if let Some(dpi) = parse_xft_dpi(&String::from_utf8_lossy(&resources)) {
+            connection.scale = dpi / 96.0;
+        }
+        Ok(connection)
+    }
+
+    fn atom(&self, atom: Atom) -> u32 {
+        self.atoms[atom as usize]
+    }
+
+    fn flush(&self) {
+        unsafe {
+            (self.xcb.xcb_flush)(self.raw);
+        }
+    }
+
+    /// Valor bruto de uma propriedade (até `max_bytes`)
+    fn property(&self, window: u32, property: u32, kind: u32, max_bytes: u32) -> Vec<u8> {
+        unsafe {
+            let cookie = (self.xcb.xcb_get_property)(
+                self.raw,
+                0,
+                window,
+                property,
+                kind,
+                0,
+                max_bytes.div_ceil(4),
+            );
+            let reply = (self.xcb.xcb_get_property_reply)(self.raw, cookie, null_mut());
+            if reply.is_null() {
+                return Vec::new();
+            }
+            let value = (self.xcb.xcb_get_property_value)(reply) as *const u8;
+            let len = (self.xcb.xcb_get_property_value_length)(reply).max(0) as usize;
+            let bytes = std::slice::from_raw_parts(value, len).to_vec();
+            libc::free(reply as *mut c_void);
+            bytes
+        }
+    }
+
+    fn change_property(&self, window: u32, property: u32, kind: u32, format: u8, data: &[u8]) {
+        let len = data.len() as u32 / (format as u32 / 8);
+        unsafe {
+            (self.xcb.xcb_change_property)(
+                self.raw,
+                PROP_MODE_REPLACE,
+                window,
+                property,
+                kind,
+                format,
+                len,
+                data.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    fn change_property32(&self, window: u32, property: u32, kind: u32, data: &[u32]) {
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        self.change_property(window, property, kind, 32, &bytes);
+    }
+
+    fn delete_property(&self, window: u32, property: u32) {
+        unsafe {
+            (self.xcb.xcb_delete_property)(self.raw, window, property);
+        }
+    }
+
+    /// Client message para o window manager (EWMH)
+    fn send_to_wm(&self, window: u32, message_type: u32, data: [u32; 5]) {
+        let event = ClientMessageEvent {
+            response_type: CLIENT_MESSAGE,
+            format: 32,
+            sequence: 0,
+            window,
+            message_type,
+            data,
+        };
+        unsafe {
+            (self.xcb.xcb_send_event)(
+                self.raw,
+                0,
+                self.screen.root,
+                EVENT_MASK_SUBSTRUCTURE_REDIRECT | EVENT_MASK_SUBSTRUCTURE_NOTIFY,
+                &event as *const ClientMessageEvent as *const c_char,
+            );
+        }
+    }
+
+    fn root_position(&self, window: u32) -> Option<(i32, i32)> {
+        unsafe {
+            let cookie =
+                (self.xcb.xcb_translate_coordinates)(self.raw, window, self.screen.root, 0, 0);
+            let reply = (self.xcb.xcb_translate_coordinates_reply)(self.raw, cookie, null_mut());
+            if reply.is_null() {
+                return None;
+            }
+            let position = ((*reply).dst_x as i32, (*reply).dst_y as i32);
+            libc::free(reply as *mut c_void);
+            Some(position)
+        }
+    }
+
+    fn monitor(&self) -> MonitorInfo {
+        MonitorInfo {
+            name: format!("X11 screen {}", self.screen.number),
+            size: WindowSize::new(self.screen.width as u32, self.screen.height as u32),
+            position: WindowPosition::new(0, 0),
+            refresh_rate: 60,
+            scale_factor: self.scale as f32,
+            is_primary: true,
+        }
+    }
+
+    /// Próximo evento, esperando até `timeout` ms (-1 = sem limite)
+    unsafe fn next_event(&self, timeout: c_int) -> *mut GenericEvent {
+        let pending = self.pending.replace(null_mut());
+        if !pending.is_null() {
+            return pending;
+        }
+        if timeout < 0 {
+            return (self.xcb.xcb_wait_for_event)(self.raw);
+        }
+        let event = (self.xcb.xcb_poll_for_event)(self.raw);
+        if !event.is_null() || timeout == 0 {
+            return event;
+        }
+        let mut fd = libc::pollfd {
+            fd: (self.xcb.xcb_get_file_descriptor)(self.raw),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        libc::poll(&mut fd, 1, timeout);
+        (self.xcb.xcb_poll_for_event)(self.raw)
+    }
+
+    unsafe fn handle_event(&self, event: *mut GenericEvent, out: &mut Vec<Event>) {
+        let kind = (*event).response_type & 0x7F;
+        match kind {
+            KEY_PRESS | KEY_RELEASE | BUTTON_PRESS | BUTTON_RELEASE | MOTION_NOTIFY
+            | ENTER_NOTIFY | LEAVE_NOTIFY => {
+                let input = &*(event as *const InputEvent);
+                if let Some(state) = self.window_state(input.event) {
+                    self.handle_input(&state, kind, input, out);
+                }
+            }
+            FOCUS_IN | FOCUS_OUT => {
+                let focus = &*(event as *const FocusEvent);
+                // Grabs de teclado geram pares falsos de foco
+                if focus.mode != NOTIFY_MODE_NORMAL && focus.mode != NOTIFY_MODE_WHILE_GRABBED {
+                    return;
+                }
+                let Some(state) = self.window_state(focus.event) else {
+                    return;
+                };
+                let focused = kind == FOCUS_IN;
+                if state.focused.replace(focused) == focused {
+                    return;
+                }
+                if state.cursor_grab.get() != CursorGrabMode::None {
+                    self.set_pointer_grab(focus.event, focused);
+                }
+                out.push(Event::Window(if focused {
+                    WindowEvent::Focused
+                } else {
+                    WindowEvent::Unfocused
+                }));
+            }
+            CONFIGURE_NOTIFY => {
+                let configure = &*(event as *const ConfigureEvent);
+                let Some(state) = self.window_state(configure.window) else {
+                    return;
+                };
+                let size = (configure.width as u32, configure.height as u32);
+                if state.size.replace(size) != size {
+                    out.push(Event::Window(WindowEvent::Resized(state.logical_size())));
+                    out.push(Event::Window(WindowEvent::FramebufferResized(
+                        size.0, size.1,
+                    )));
+                }
+                // Com window manager a janela é reparentada e x/y são
+                // relativos à moldura; a posição real vem da raiz
+                if let Some(position) = self.root_position(configure.window) {
+                    if state.position.replace(position) != position {
+                        out.push(Event::Window(WindowEvent::Moved(WindowPosition::new(
+                            position.0, position.1,
+                        ))));
+                    }
+                }
+            }
+            PROPERTY_NOTIFY => {
+                let property = &*(event as *const PropertyEvent);
+                if property.atom != self.atom(Atom::NetWmState) {
+                    return;
+                }
+                if let Some(state) = self.window_state(property.window) {
+                    self.update_wm_state(property.window, &state, out);
+                }
+            }
+            GE_GENERIC => {
+                let touch = &*(event as *const XiTouchEvent);
+                let Some(xinput) = &self.xinput else {
+                    return;
+                };
+                if xinput.opcode != touch.extension {
+                    return;
+                }
+                if touch.event_type == XI_RAW_MOTION {
+                    self.handle_raw_motion(&*(event as *const XiRawEvent), out);
+                } else {
+                    self.handle_touch(touch, out);
+                }
+            }
+            CLIENT_MESSAGE => {
+                let message = &*(event as *const ClientMessageEvent);
+                if message.message_type == self.atom(Atom::WmProtocols)
+                    && message.data[0] == self.atom(Atom::WmDeleteWindow)
+                    && self.window_state(message.window).is_some()
+                {
+                    out.push(Event::Window(WindowEvent::Closed));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    unsafe fn handle_input(
+        &self,
+        state: &X11State,
+        kind: u8,
+        input: &InputEvent,
+        out: &mut Vec<Event>,
+    ) {
+        let modifiers = modifiers_from_state(input.state);
+        let position = state.logical(input.event_x as i32, input.event_y as i32);
+        match kind {
+            KEY_PRESS | KEY_RELEASE => {
+                let code = (input.detail as u32).saturating_sub(8);
+                let Some(key) = keycode_from_evdev(code) else {
+                    return;
+                };
+                let mut pressed = kind == KEY_PRESS;
+                let mut repeat = false;
+                if !pressed {
+                    // Auto-repeat do X chega como Release + Press com o
+                    // mesmo timestamp; vira um Press com `repeat`
+                    let next = (self.xcb.xcb_poll_for_queued_event)(self.raw);
+                    if !next.is_null() {
+                        let next_input = &*(next as *const InputEvent);
+                        if next_input.response_type & 0x7F == KEY_PRESS
+                            && next_input.detail == input.detail
+                            && next_input.time == input.time
+                        {
+                            libc::free(next as *mut c_void);
+                            pressed = true;
+                            repeat = true;
+                        } else {
+                            self.pending.set(next);
+                        }
+                    }
+                }
+                out.push(Event::Keyboard(KeyEvent {
+                    key: Key::Code(key),
+                    scancode: code,
+                    state: if pressed {
+                        KeyState::Pressed
+                    } else {
+                        KeyState::Released
+                    },
+                    modifiers,
+                    repeat,
+                }));
+            }
+            BUTTON_PRESS | BUTTON_RELEASE => {
+                state.cursor_position.set(position);
+                let pressed = kind == BUTTON_PRESS;
+                // Botões 4-7 são a roda do mouse; só o press importa
+                let scroll = match input.detail {
+                    4 => Some((0.0, 1.0)),
+                    5 => Some((0.0, -1.0)),
+                    6 => Some((-1.0, 0.0)),
+                    7 => Some((1.0, 0.0)),
+                    _ => None,
+                };
+                if let Some(delta) = scroll {
+                    if pressed {
+                        out.push(Event::Mouse(MouseEvent::Scrolled { delta, position }));
+                    }
+                    return;
+                }
+                let button = match input.detail {
+                    1 => MouseButton::Left,
+                    2 => MouseButton::Middle,
+                    3 => MouseButton::Right,
+                    8 => MouseButton::Back,
+                    9 => MouseButton::Forward,
+                    other => MouseButton::Other(other),
+                };
+                out.push(Event::Mouse(if pressed {
+                    MouseEvent::ButtonPressed {
+                        button,
+                        position,
+                        modifiers,
+                    }
+                } else {
+                    MouseEvent::ButtonReleased {
+                        button,
+                        position,
+                        modifiers,
+                    }
+                }));
+            }
+            MOTION_NOTIFY if state.cursor_grab.get() == CursorGrabMode::Locked => {
+                let (lock_x, lock_y) = state.lock_position.get();
+                // O warp de volta gera um MotionNotify no próprio ponto
+                if (input.event_x, input.event_y) == (lock_x, lock_y) {
+                    return;
+                }
+                (self.xcb.xcb_warp_pointer)(self.raw, 0, input.event, 0, 0, 0, 0, lock_x, lock_y);
+                let lock = state.logical(lock_x as i32, lock_y as i32);
+                state.cursor_position.set(lock);
+                out.push(Event::Mouse(MouseEvent::CursorMoved {
+                    position: lock,
+                    delta: (position.0 - lock.0, position.1 - lock.1),
+                }));
+            }
+            MOTION_NOTIFY => {
+                let last = state.cursor_position.replace(position);
+                out.push(Event::Mouse(MouseEvent::CursorMoved {
+                    position,
+                    delta: (position.0 - last.0, position.1 - last.1),
+                }));
+            }
+            ENTER_NOTIFY | LEAVE_NOTIFY => {
+                // Enter/Leave gerados por grab do ponteiro não movem o cursor
+                if input.mode != NOTIFY_MODE_NORMAL {
+                    return;
+                }
+                let inside = kind == ENTER_NOTIFY;
+                if state.cursor_inside.replace(inside) == inside {
+                    return;
+                }
+                if inside {
+                    state.cursor_position.set(position);
+                    out.push(Event::Window(WindowEvent::CursorEntered));
+                } else {
+                    out.push(Event::Window(WindowEvent::CursorLeft));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_touch(&self, touch: &XiTouchEvent, out: &mut Vec<Event>) {
+        let phase = match touch.event_type {
+            XI_TOUCH_BEGIN => TouchPhase::Started,
+            // O dedo já saiu; o TouchEnd chega quando o dono do toque decidir
+            XI_TOUCH_UPDATE if touch.flags & XI_TOUCH_PENDING_END != 0 => return,
+            XI_TOUCH_UPDATE => TouchPhase::Moved,
+            XI_TOUCH_END => TouchPhase::Ended,
+            _ => return,
+        };
+        let Some(state) = self.window_state(touch.event) else {
+            return;
+        };
+        let position = (
+            touch.event_x as f64 / 65536.0 / state.scale,
+            touch.event_y as f64 / 65536.0 / state.scale,
+        );
+        out.push(Event::Touch(TouchEvent::new(
+            touch.detail as u64,
+            phase,
+            position,
+        )));
+    }
+
+    /// Emite `RawMotion` se a janela com foco tiver pedido movimento cru
+    unsafe fn handle_raw_motion(&self, raw: &XiRawEvent, out: &mut Vec<Event>) {
+        let wanted = self
+            .windows
+            .borrow()
+            .values()
+            .any(|state| state.focused.get() && state.raw_motion.get());
+        if !wanted {
+            return;
+        }
+        let delta = raw_motion_delta(raw);
+        if delta != (0.0, 0.0) {
+            out.push(Event::Mouse(MouseEvent::RawMotion { delta }));
+        }
+    }
+
+    /// Seleciona `XI_RawMotion` na raiz enquanto alguma janela o quiser
+    fn update_raw_motion(&self) {
+        let Some(xinput) = &self.xinput else {
+            return;
+        };
+        let wanted = self
+            .windows
+            .borrow()
+            .values()
+            .any(|state| state.raw_motion.get());
+        let mask = XiEventMask {
+            deviceid: XI_ALL_MASTER_DEVICES,
+            mask_len: 1,
+            mask: if wanted { 1 << XI_RAW_MOTION } else { 0 },
+        };
+        unsafe {
+            (xinput.xcb_input.xcb_input_xi_select_events)(self.raw, self.screen.root, 1, &mask);
+        }
+        self.flush();
+    }
+
+    /// Pede os eventos de touch do XInput 2 para a janela
+    fn select_touch_events(&self, window: u32) {
+        let Some(xinput) = &self.xinput else {
+            return;
+        };
+        let mask = XiEventMask {
+            deviceid: XI_ALL_MASTER_DEVICES,
+            mask_len: 1,
+            mask: (1 << XI_TOUCH_BEGIN) | (1 << XI_TOUCH_UPDATE) | (1 << XI_TOUCH_END),
+        };
+        unsafe {
+            (xinput.xcb_input.xcb_input_xi_select_events)(self.raw, window, 1, &mask);
+        }
+    }
+
+    /// Lê `_NET_WM_STATE` e emite Minimized/Maximized/Restored nas mudanças
+    fn update_wm_state(&self, window: u32, state: &X11State, out: &mut Vec<Event>) {
+        let bytes = self.property(window, self.atom(Atom::NetWmState), ATOM_ATOM, 256);
+        let atoms: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        let hidden = atoms.contains(&self.atom(Atom::NetWmStateHidden));
+        let maximized = atoms.contains(&self.atom(Atom::NetWmStateMaximizedVert))
+            && atoms.contains(&self.atom(Atom::NetWmStateMaximizedHorz));
+
+        let was_hidden = state.minimized.replace(hidden);
+        let was_maximized = state.maximized.replace(maximized);
+        if hidden && !was_hidden {
+            out.push(Event::Window(WindowEvent::Minimized));
+        } else if maximized && !was_maximized {
+            out.push(Event::Window(WindowEvent::Maximized));
+        } else if (was_hidden && !hidden) || (was_maximized && !maximized) {
+            out.push(Event::Window(WindowEvent::Restored));
+        }
+    }
+
+    fn set_pointer_grab(&self, window: u32, grab: bool) {
+        unsafe {
+            if grab {
+                let mask =
+                    EVENT_MASK_BUTTON_PRESS | EVENT_MASK_BUTTON_RELEASE | EVENT_MASK_POINTER_MOTION;
+                let cookie = (self.xcb.xcb_grab_pointer)(
+                    self.raw,
+                    1,
+                    window,
+                    mask as u16,
+                    GRAB_MODE_ASYNC,
+                    GRAB_MODE_ASYNC,
+                    window,
+                    0,
+                    0,
+                );
+                (self.xcb.xcb_discard_reply)(self.raw, cookie.sequence);
+            } else {
+                (self.xcb.xcb_ungrab_pointer)(self.raw, 0);
+            }
+        }
+        self.flush();
+    }
+
+    fn window_state(&self, window: u32) -> Option<Rc<X11State>> {
+        self.windows.borrow().get(&window).cloned()
+    }
+
+    /// Cursor do tema (nome CSS, depois o nome antigo) ou da fonte `cursor`
+    fn load_cursor(&self, icon: CursorIcon) -> u32 {
+        if let Some(cursors) = &self.cursor_context {
+            for name in [icon.name(), legacy_cursor_name(icon)] {
+                let Ok(name) = CString::new(name) else {
+                    continue;
+                };
+                let cursor = unsafe {
+                    (cursors.xcb_cursor.xcb_cursor_load_cursor)(cursors.context, name.as_ptr())
+                };
+                if cursor != 0 {
+                    return cursor;
+                }
+            }
+        }
+        self.glyph_cursor(cursor_glyph(icon))
+    }
+
+    fn glyph_cursor(&self, glyph: u16) -> u32 {
+        const FONT: &str = "cursor";
+        let xcb = &self.xcb;
+        unsafe {
+            let font = (xcb.xcb_generate_id)(self.raw);
+            (xcb.xcb_open_font)(
+                self.raw,
+                font,
+                FONT.len() as u16,
+                FONT.as_ptr() as *const c_char,
+            );
+            let cursor = (xcb.xcb_generate_id)(self.raw);
+            // Máscara é o glifo seguinte; preto sobre branco
+            (xcb.xcb_create_glyph_cursor)(
+                self.raw,
+                cursor,
+                font,
+                font,
+                glyph,
+                glyph + 1,
+                0,
+                0,
+                0,
+                0xFFFF,
+                0xFFFF,
+                0xFFFF,
+            );
+            (xcb.xcb_close_font)(self.raw, font);
+            cursor
+        }
+    }
+
+    /// Cursor ARGB via Render; `None` sem a extensão ou o formato a8r8g8b8
+    fn image_cursor(&self, image: &CursorImage) -> Option<u32> {
+        let render = self.render.as_ref()?;
+        let xcb = &self.xcb;
+        unsafe {
+            let cookie = (render.xcb_render_query_pict_formats)(self.raw);
+            let reply = (render.xcb_render_query_pict_formats_reply)(self.raw, cookie, null_mut());
+            if reply.is_null() {
+                return None;
+            }
+            let formats = std::slice::from_raw_parts(
+                (render.xcb_render_query_pict_formats_formats)(reply),
+                (render.xcb_render_query_pict_formats_formats_length)(reply).max(0) as usize,
+            );
+            let format = formats.iter().find(|f| f.is_argb32()).map(|f| f.id);
+            libc::free(reply);
+            let format = format?;
+
+            let (width, height) = (image.width() as u16, image.height() as u16);
+            // ZPixmap na ordem de bytes local (servidor na mesma arquitetura)
+            let pixels: Vec<u8> = premultiplied_argb(image.rgba())
+                .into_iter()
+                .flat_map(u32::to_ne_bytes)
+                .collect();
+
+            let pixmap = (xcb.xcb_generate_id)(self.raw);
+            (xcb.xcb_create_pixmap)(self.raw, 32, pixmap, self.screen.root, width, height);
+            let gc = (xcb.xcb_generate_id)(self.raw);
+            (xcb.xcb_create_gc)(self.raw, gc, pixmap, 0, null());
+            (xcb.xcb_put_image)(
+                self.raw,
+                IMAGE_FORMAT_Z_PIXMAP,
+                pixmap,
+                gc,
+                width,
+                height,
+                0,
+                0,
+                0,
+                32,
+                pixels.len() as u32,
+                pixels.as_ptr(),
+            );
+            let picture = (xcb.xcb_generate_id)(self.raw);
+            (render.xcb_render_create_picture)(self.raw, picture, pixmap, format, 0, null());
+            let cursor = (xcb.xcb_generate_id)(self.raw);
+            let (hotspot_x, hotspot_y) = image.hotspot();
+            (render.xcb_render_create_cursor)(
+                self.raw,
+                cursor,
+                picture,
+                hotspot_x as u16,
+                hotspot_y as u16,
+            );
+            (render.xcb_render_free_picture)(self.raw, picture);
+            (xcb.xcb_free_gc)(self.raw, gc);
+            (xcb.xcb_free_pixmap)(self.raw, pixmap);
+            Some(cursor)
+        }
+    }
+}
+
+/// Glifo da fonte `cursor` (X11/cursorfont.h)
+fn cursor_glyph(icon: CursorIcon) -> u16 {
+    match icon {
+        CursorIcon::Default => 68,
+        CursorIcon::Pointer => 60,
+        CursorIcon::Text => 152,
+        CursorIcon::Crosshair => 34,
+        CursorIcon::Wait | CursorIcon::Progress => 150,
+        CursorIcon::Help => 92,
+        CursorIcon::NotAllowed => 0,
+        CursorIcon::Move | CursorIcon::Grabbing => 52,
+        CursorIcon::Grab => 58,
+        CursorIcon::EwResize => 108,
+        CursorIcon::NsResize => 116,
+        CursorIcon::NeswResize => 12,
+        CursorIcon::NwseResize => 14,
+    }
+}
+
+impl Drop for X11Connection {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(cursors) = &self.cursor_context {
+                (cursors.xcb_cursor.xcb_cursor_context_free)(cursors.context);
+            }
+            libc::free(self.pending.get() as *mut c_void);
+            (self.xcb.xcb_disconnect)(self.raw);
+        }
+    }
+}
+
+/// `Xft.dpi` do RESOURCE_MANAGER (xrdb)
+fn parse_xft_dpi(resources: &str) -> Option<f64> {
+    resources.lines().find_map(|line| {
+        let value = line.strip_prefix("Xft.dpi:")?;
+        value.trim().parse::<f64>().ok().filter(|dpi| *dpi > 0.0)
+    })
+}
+
+fn modifiers_from_state(state: u16) -> ModifierKeys {
+    // Shift, Control, Mod1 (Alt) e Mod4 (Super) no mapeamento padrão
+    ModifierKeys::new(
+        state & 0x01 != 0,
+        state & 0x04 != 0,
+        state & 0x08 != 0,
+        state & 0x40 != 0,
+    )
+}
+
+/// Estado por janela atualizado pelos eventos
+struct X11State {
+    scale: f64,
+    focused: Cell<bool>,
+    minimized: Cell<bool>,
+    maximized: Cell<bool>,
+    cursor_grab: Cell<CursorGrabMode>,
+    raw_motion: Cell<bool>,
+    /// Ponto (físico) em que o cursor fica no modo `Locked`
+    lock_position: Cell<(i16, i16)>,
+    cursor_inside: Cell<bool>,
+    cursor_position: Cell<(f64, f64)>,
+    /// Tamanho físico da área cliente
+    size: Cell<(u32, u32)>,
+    position: Cell<(i32, i32)>,
+}
+
+impl X11State {
+    fn logical(&self, x: i32, y: i32) -> (f64, f64) {
+        (x as f64 / self.scale, y as f64 / self.scale)
+    }
+
+    fn logical_size(&self) -> WindowSize {
+        let (width, height) = self.size.get();
+        WindowSize::new(
+            (width as f64 / self.scale).round() as u32,
+            (height as f64 / self.scale).round() as u32,
+        )
+    }
+}
+
+/// Tamanho físico de um tamanho na unidade da `ScalePolicy`
+fn physical(size: WindowSize, scale: f64, policy: ScalePolicy) -> (u32, u32) {
+    let size = policy.to_physical(size, scale as f32);
+    (size.width.max(1), size.height.max(1))
+}
+
+/// Janela X11
+pub(crate) struct X11Window {
+    connection: Rc<X11Connection>,
+    id: u32,
+    state: Rc<X11State>,
+    resizable: bool,
+    min_size: Option<WindowSize>,
+    max_size: Option<WindowSize>,
+    scale_policy: ScalePolicy,
+    /// Cursor 1x1 transparente, criado na primeira vez que o cursor é escondido
+    hidden_cursor: Cell<u32>,
+    /// Cursor da janela quando visível (0 = o da janela raiz)
+    cursor: Cell<u32>,
+    cursor_visible: Cell<bool>,
+}
+
+impl X11Window {
+    pub(super) fn create(config: &WindowConfig) -> Result<Self, WindowError> {
+        let connection = connection().map_err(WindowError::CreationFailed)?;
+        let scale = connection.scale;
+        let (width, height) = physical(config.size, scale, config.scale_policy);
+        let explicit_position = config.position != WindowPosition::CENTERED;
+        let (x, y) = if explicit_position {
+            (config.position.x, config.position.y)
+        } else {
+            (
+                (connection.screen.width as i32 - width as i32) / 2,
+                (connection.screen.height as i32 - height as i32) / 2,
+            )
+        };
+
+        let id = unsafe {
+            let id = (connection.xcb.xcb_generate_id)(connection.raw);
+            let values = [connection.screen.black_pixel, WINDOW_EVENTS];
+            (connection.xcb.xcb_create_window)(
+                connection.raw,
+                0,
+                id,
+                connection.screen.root,
+                x as i16,
+                y as i16,
+                width as u16,
+                height as u16,
+                0,
+                WINDOW_CLASS_INPUT_OUTPUT,
+                0,
+                CW_BACK_PIXEL | CW_EVENT_MASK,
+                values.as_ptr() as *const c_void,
+            );
+            id
+        };
+
+        let state = Rc::new(X11State {
+            scale,
+            focused: Cell::new(false),
+            minimized: Cell::new(false),
+            maximized: Cell::new(false),
+            cursor_grab: Cell::new(CursorGrabMode::None),
+            raw_motion: Cell::new(false),
+            lock_position: Cell::new((0, 0)),
+            cursor_inside: Cell::new(false),
+            cursor_position: Cell::new((0.0, 0.0)),
+            size: Cell::new((width, height)),
+            position: Cell::new((x, y)),
+        });
+        connection
+            .windows
+            .borrow_mut()
+            .insert(id, Rc::clone(&state));
+
+        let window = Self {
+            connection,
+            id,
+            state,
+            resizable: config.resizable,
+            min_size: config.min_size,
+            max_size: config.max_size,
+            scale_policy: config.scale_policy,
+            hidden_cursor: Cell::new(0),
+            cursor: Cell::new(0),
+            cursor_visible: Cell::new(true),
+        };
+
+        let connection = &window.connection;
+        connection.change_property32(
+            id,
+            connection.atom(Atom::WmProtocols),
+            ATOM_ATOM,
+            &[connection.atom(Atom::WmDeleteWindow)],
+        );
+        connection.select_touch_events(id);
+        window.set_title(&config.title);
+        window.set_size_hints(config.size, explicit_position);
+        if !config.decorated {
+            // flags = MWM_HINTS_DECORATIONS, decorations = 0
+            connection.change_property32(
+                id,
+                connection.atom(Atom::MotifWmHints),
+                connection.atom(Atom::MotifWmHints),
+                &[2, 0, 0, 0, 0],
+            );
+        }
+
+        // Antes do map o estado inicial vai direto na propriedade
+        let initial_state: &[u32] = match config.display_mode {
+            DisplayMode::Windowed => &[],
+            DisplayMode::Maximized => &[
+                connection.atom(Atom::NetWmStateMaximizedVert),
+                connection.atom(Atom::NetWmStateMaximizedHorz),
+            ],
+            DisplayMode::FullscreenBorderless | DisplayMode::FullscreenExclusive => {
+                &[connection.atom(Atom::NetWmStateFullscreen)]
+            }
+        };
+        connection.change_property32(
+            id,
+            connection.atom(Atom::NetWmState),
+            ATOM_ATOM,
+            initial_state,
+        );
+
+        unsafe {
+            (connection.xcb.xcb_map_window)(connection.raw, id);
+        }
+        connection.flush();
+        Ok(window)
+    }
+
+    /// WM_NORMAL_HINTS: tamanho mínimo/máximo e posição pedida
+    fn set_size_hints(&self, size: WindowSize, explicit_position: bool) {
+        const US_POSITION: u32 = 1;
+        const P_POSITION: u32 = 4;
+        const P_SIZE: u32 = 8;
+        const P_MIN_SIZE: u32 = 16;
+        const P_MAX_SIZE: u32 = 32;
+
+        let scale = self.state.scale;
+        let mut hints = [0u32; 18];
+        hints[0] = P_SIZE;
+        if explicit_position {
+            hints[0] |= US_POSITION | P_POSITION;
+        }
+        let policy = self.scale_policy;
+        let (width, height) = physical(size, scale, policy);
+        hints[3] = width;
+        hints[4] = height;
+
+        // Sem redimensionamento: mínimo = máximo = tamanho atual
+        let (min, max) = if self.resizable {
+            (self.min_size, self.max_size)
+        } else {
+            (Some(size), Some(size))
+        };
+        if let Some(min) = min {
+            hints[0] |= P_MIN_SIZE;
+            (hints[5], hints[6]) = physical(min, scale, policy);
+        }
+        if let Some(max) = max {
+            hints[0] |= P_MAX_SIZE;
+            (hints[7], hints[8]) = physical(max, scale, policy);
+        }
+        self.connection.change_property32(
+            self.id,
+            ATOM_WM_NORMAL_HINTS,
+            ATOM_WM_SIZE_HINTS,
+            &hints,
+        );
+    }
+
+    pub(super) fn set_title(&self, title: &str) {
+        let connection = &self.connection;
+        connection.change_property(self.id, ATOM_WM_NAME, ATOM_STRING, 8, title.as_bytes());
+        connection.change_property(
+            self.id,
+            connection.atom(Atom::NetWmName),
+            connection.atom(Atom::Utf8String),
+            8,
+            title.as_bytes(),
+        );
+        connection.flush();
+    }
+
+    pub(super) fn inner_size(&self) -> WindowSize {
+        self.state.logical_size()
+    }
+
+    pub(super) fn physical_inner_size(&self) -> WindowSize {
+        let (width, height) = self.state.size.get();
+        WindowSize::new(width, height)
+    }
+
+    pub(super) fn set_inner_size(&self, size: WindowSize) {
+        if !self.resizable {
+            self.set_size_hints(size, false);
+        }
+        let (width, height) = physical(size, self.state.scale, self.scale_policy);
+        let values = [width, height];
+        unsafe {
+            (self.connection.xcb.xcb_configure_window)(
+                self.connection.raw,
+                self.id,
+                CONFIG_WINDOW_WIDTH | CONFIG_WINDOW_HEIGHT,
+                values.as_ptr() as *const c_void,
+            );
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn outer_position(&self) -> WindowPosition {
+        let (x, y) = self.state.position.get();
+        WindowPosition::new(x, y)
+    }
+
+    pub(super) fn set_outer_position(&self, position: WindowPosition) {
+        let values = [position.x as u32, position.y as u32];
+        unsafe {
+            (self.connection.xcb.xcb_configure_window)(
+                self.connection.raw,
+                self.id,
+                CONFIG_WINDOW_X | CONFIG_WINDOW_Y,
+                values.as_ptr() as *const c_void,
+            );
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn center(&self) {
+        let (width, height) = self.state.size.get();
+        let screen = &self.connection.screen;
+        self.set_outer_position(WindowPosition::new(
+            (screen.width as i32 - width as i32) / 2,
+            (screen.height as i32 - height as i32) / 2,
+        ));
+    }
+
+    fn change_wm_state(&self, action: u32, first: Atom, second: Option<Atom>) {
+        let connection = &self.connection;
+        connection.send_to_wm(
+            self.id,
+            connection.atom(Atom::NetWmState),
+            [
+                action,
+                connection.atom(first),
+                second.map_or(0, |atom| connection.atom(atom)),
+                1,
+                0,
+            ],
+        );
+    }
+
+    /// Fullscreen exclusivo usa o mesmo `_NET_WM_STATE_FULLSCREEN`; o
+    /// compositor desliga a composição para janelas fullscreen
+    pub(super) fn set_display_mode(&self, mode: DisplayMode) -> Result<(), WindowError> {
+        let maximized = (
+            Atom::NetWmStateMaximizedVert,
+            Some(Atom::NetWmStateMaximizedHorz),
+        );
+        match mode {
+            DisplayMode::Windowed => {
+                self.change_wm_state(NET_WM_STATE_REMOVE, Atom::NetWmStateFullscreen, None);
+                self.change_wm_state(NET_WM_STATE_REMOVE, maximized.0, maximized.1);
+            }
+            DisplayMode::Maximized => {
+                self.change_wm_state(NET_WM_STATE_REMOVE, Atom::NetWmStateFullscreen, None);
+                self.change_wm_state(NET_WM_STATE_ADD, maximized.0, maximized.1);
+            }
+            DisplayMode::FullscreenBorderless | DisplayMode::FullscreenExclusive => {
+                self.change_wm_state(NET_WM_STATE_ADD, Atom::NetWmStateFullscreen, None);
+            }
+        }
+        self.connection.flush();
+        Ok(())
+    }
+
+    pub(super) fn minimize(&self) {
+        let connection = &self.connection;
+        connection.send_to_wm(
+            self.id,
+            connection.atom(Atom::WmChangeState),
+            [ICONIC_STATE, 0, 0, 0, 0],
+        );
+        connection.flush();
+    }
+
+    pub(super) fn focus(&self) {
+        let connection = &self.connection;
+        // source = 1 (aplicação), timestamp = CurrentTime
+        connection.send_to_wm(
+            self.id,
+            connection.atom(Atom::NetActiveWindow),
+            [1, 0, 0, 0, 0],
+        );
+        connection.flush();
+    }
+
+    pub(super) fn is_focused(&self) -> bool {
+        self.state.focused.get()
+    }
+
+    pub(super) fn is_maximized(&self) -> bool {
+        self.state.maximized.get()
+    }
+
+    pub(super) fn set_cursor_visible(&self, visible: bool) {
+        self.cursor_visible.set(visible);
+        self.apply_cursor();
+    }
+
+    fn apply_cursor(&self) {
+        let connection = &self.connection;
+        let cursor = if self.cursor_visible.get() {
+            self.cursor.get()
+        } else {
+            self.hidden_cursor()
+        };
+        unsafe {
+            (connection.xcb.xcb_change_window_attributes)(
+                connection.raw,
+                self.id,
+                CW_CURSOR,
+                [cursor].as_ptr() as *const c_void,
+            );
+        }
+        connection.flush();
+    }
+
+    fn hidden_cursor(&self) -> u32 {
+        if self.hidden_cursor.get() != 0 {
+            return self.hidden_cursor.get();
+        }
+        let connection = &self.connection;
+        unsafe {
+            let xcb = &connection.xcb;
+            let pixmap = (xcb.xcb_generate_id)(connection.raw);
+            (xcb.xcb_create_pixmap)(connection.raw, 1, pixmap, self.id, 1, 1);
+            let cursor = (xcb.xcb_generate_id)(connection.raw);
+            (xcb.xcb_create_cursor)(
+                connection.raw,
+                cursor,
+                pixmap,
+                pixmap,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            );
+            (xcb.xcb_free_pixmap)(connection.raw, pixmap);
+            self.hidden_cursor.set(cursor);
+            cursor
+        }
+    }
+
+    /// Troca o cursor da janela, liberando o anterior
+    fn replace_cursor(&self, cursor: u32) {
+        let old = self.cursor.replace(cursor);
+        self.apply_cursor();
+        if old != 0 {
+            unsafe {
+                (self.connection.xcb.xcb_free_cursor)(self.connection.raw, old);
+            }
+            self.connection.flush();
+        }
+    }
+
+    pub(super) fn set_cursor_icon(&self, icon: CursorIcon) {
+        let cursor = match icon {
+            CursorIcon::Default => 0,
+            icon => self.connection.load_cursor(icon),
+        };
+        self.replace_cursor(cursor);
+    }
+
+    pub(super) fn set_cursor_image(&self, image: &CursorImage) -> Result<(), WindowError> {
+        let cursor = self.connection.image_cursor(image).ok_or_else(|| {
+            WindowError::PlatformError("X server does not support ARGB cursors".to_string())
+        })?;
+        self.replace_cursor(cursor);
+        Ok(())
+    }
+
+    pub(super) fn cursor_position(&self) -> (f64, f64) {
+        self.state.cursor_position.get()
+    }
+
+    pub(super) fn set_cursor_position(&self, x: f64, y: f64) {
+        // O MotionNotify gerado pelo warp chega com delta zero
+        self.state.cursor_position.set((x, y));
+        let scale = self.state.scale;
+        unsafe {
+            (self.connection.xcb.xcb_warp_pointer)(
+                self.connection.raw,
+                0,
+                self.id,
+                0,
+                0,
+                0,
+                0,
+                (x * scale).round() as i16,
+                (y * scale).round() as i16,
+            );
+        }
+        self.connection.flush();
+    }
+
+    pub(super) fn set_raw_mouse_motion(&self, enabled: bool) -> Result<(), WindowError> {
+        if self.connection.xinput.is_none() {
+            return Err(WindowError::PlatformError(
+                "raw mouse motion requires XInput 2.2".to_string(),
+            ));
+        }
+        self.state.raw_motion.set(enabled);
+        self.connection.update_raw_motion();
+        Ok(())
+    }
+
+    pub(super) fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        if mode == CursorGrabMode::Locked {
+            let (x, y) = self.state.cursor_position.get();
+            let scale = self.state.scale;
+            self.state
+                .lock_position
+                .set(((x * scale).round() as i16, (y * scale).round() as i16));
+        }
+        self.state.cursor_grab.set(mode);
+        let grab = mode != CursorGrabMode::None;
+        if !grab || self.state.focused.get() {
+            self.connection.set_pointer_grab(self.id, grab);
+        }
+        Ok(())
+    }
+
+    pub(super) fn request_attention(&self) {
+        self.change_wm_state(NET_WM_STATE_ADD, Atom::NetWmStateDemandsAttention, None);
+        self.connection.flush();
+    }
+
+    /// `_NET_WM_ICON`: largura, altura e os pixels em ARGB sem
+    /// pré-multiplicação
+    pub(super) fn set_icon(&self, icon: Option<&WindowIcon>) {
+        let connection = &self.connection;
+        let property = connection.atom(Atom::NetWmIcon);
+        match icon {
+            Some(icon) => {
+                let mut data = Vec::with_capacity(2 + icon.rgba().len() / 4);
+                data.extend([icon.width(), icon.height()]);
+                data.extend(
+                    icon.rgba()
+                        .chunks_exact(4)
+                        .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]])),
+                );
+                connection.change_property32(self.id, property, ATOM_CARDINAL, &data);
+            }
+            None => connection.delete_property(self.id, property),
+        }
+        connection.flush();
+    }
+
+    /// `_NET_WM_WINDOW_OPACITY`, aplicada pelo compositor (sem compositor
+    /// a janela continua opaca)
+    pub(super) fn set_opacity(&self, opacity: f32) {
+        let connection = &self.connection;
+        let property = connection.atom(Atom::NetWmWindowOpacity);
+        if opacity >= 1.0 {
+            connection.delete_property(self.id, property);
+        } else {
+            let value = (opacity as f64 * u32::MAX as f64).round() as u32;
+            connection.change_property32(self.id, property, ATOM_CARDINAL, &[value]);
+        }
+        connection.flush();
+    }
+
+    pub(super) fn set_always_on_top(&self, always_on_top: bool) {
+        let action = if always_on_top {
+            NET_WM_STATE_ADD
+        } else {
+            NET_WM_STATE_REMOVE
+        };
+        self.change_wm_state(action, Atom::NetWmStateAbove, None);
+        self.connection.flush();
+    }
+
+    pub(super) fn scale_factor(&self) -> f32 {
+        self.state.scale as f32
+    }
+
+    pub(super) fn current_monitor(&self) -> Option<MonitorInfo> {
+        Some(self.connection.monitor())
+    }
+
+    pub(super) fn native_handle(&self) -> NativeHandle {
+        NativeHandle::Xcb {
+            connection: self.connection.raw,
+            window: self.id,
+            screen: self.connection.screen.number,
+        }
+    }
+}
+
+impl Drop for X11Window {
+    fn drop(&mut self) {
+        let connection = &self.connection;
+        if self.state.cursor_grab.get() != CursorGrabMode::None {
+            connection.set_pointer_grab(self.id, false);
+        }
+        unsafe {
+            for cursor in [self.hidden_cursor.get(), self.cursor.get()] {
+                if cursor != 0 {
+                    (connection.xcb.xcb_free_cursor)(connection.raw, cursor);
+                }
+            }
+            (connection.xcb.xcb_destroy_window)(connection.raw, self.id);
+        }
+        connection.flush();
+        connection.windows.borrow_mut().remove(&self.id);
+        if self.state.raw_motion.get() {
+            connection.update_raw_motion();
+        }
+    }
+}
+
+/// Delta cru (x, y) de um `XI_RawMotion`: valuators 0 e 1 da lista de
+/// valores não acelerados
+unsafe fn raw_motion_delta(raw: &XiRawEvent) -> (f64, f64) {
+    let mask_ptr = (raw as *const XiRawEvent).add(1) as *const u32;
+    let mask = std::slice::from_raw_parts(mask_ptr, raw.valuators_len as usize);
+    let count: usize = mask.iter().map(|bits| bits.count_ones() as usize).sum();
+    // Os valores acelerados vêm primeiro
+    let raw_values = (mask_ptr.add(mask.len()) as *const Fp3232).add(count);
+
+    let mut delta = [0.0; 2];
+    let mut index = 0;
+    for (axis, value) in delta.iter_mut().enumerate() {
+        if mask.first().is_some_and(|bits| bits & (1 << axis) != 0) {
+            *value = raw_values.add(index).read_unaligned().to_f64();
+            index += 1;
+        }
+    }
+    (delta[0], delta[1])
+}
+
+/// Traduz os eventos da conexão da thread; sem janelas abertas nunca bloqueia
+pub(super) fn pump_events(deadline: Option<Instant>, out: &mut Vec<Event>) {
+    let Some(connection) = CONNECTION.with(|c| c.borrow().upgrade()) else {
+        return;
+    };
+    connection.flush();
+    let has_windows = !connection.windows.borrow().is_empty();
+    unsafe {
+        loop {
+            let timeout = if has_windows && out.is_empty() {
+                poll_timeout(deadline)
+            } else {
+                0
+            };
+            let event = connection.next_event(timeout);
+            if event.is_null() {
+                break;
+            }
+            connection.handle_event(event, out);
+            libc::free(event as *mut c_void);
+        }
+    }
+}
+
+/// Tela do X da conexão atual (ou de uma conexão temporária)
+pub(super) fn available_monitors() -> Option<Vec<MonitorInfo>> {
+    connection()
+        .ok()
+        .map(|connection| vec![connection.monitor()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xft_dpi() {
+        let resources = "Xcursor.size:\t24\nXft.dpi:\t144\nXft.antialias:\t1\n";
+        assert_eq!(parse_xft_dpi(resources), Some(144.0));
+        assert_eq!(parse_xft_dpi("Xft.dpi: 0"), None);
+        assert_eq!(parse_xft_dpi("Xcursor.theme: Adwaita"), None);
+    }
+
+    #[test]
+    fn test_xi_touch_event_layout() {
+        use std::mem::offset_of;
+
+        assert_eq!(offset_of!(XiTouchEvent, detail), 16);
+        assert_eq!(offset_of!(XiTouchEvent, event), 24);
+        assert_eq!(offset_of!(XiTouchEvent, event_x), 44);
+        assert_eq!(offset_of!(XiTouchEvent, flags), 60);
+    }
+
+    #[test]
+    fn test_pict_format_layout() {
+        assert_eq!(std::mem::size_of::<PictFormInfo>(), 28);
+    }
+
+    #[test]
+    fn test_raw_motion_delta() {
+        assert_eq!(std::mem::size_of::<XiRawEvent>(), 36);
+
+        // Cabeçalho (9 u32), máscara com os eixos 0 e 1, acelerados, crus
+        let mut buffer = [0u32; 9 + 1 + 4 + 4];
+        buffer[5] = 1 << 16; // valuators_len
+        buffer[9] = 0b11;
+        buffer[10..14].copy_from_slice(&[6, 0, (-4i32) as u32, 0]);
+        buffer[14..18].copy_from_slice(&[3, 1 << 31, (-2i32) as u32, 0]);
+        let raw = unsafe { &*(buffer.as_ptr() as *const XiRawEvent) };
+        assert_eq!(unsafe { raw_motion_delta(raw) }, (3.5, -2.0));
+
+        // Só o eixo y
+        buffer[9] = 0b10;
+        buffer[10..12].copy_from_slice(&[(-4i32) as u32, 0]);
+        buffer[12..14].copy_from_slice(&[(-2i32) as u32, 0]);
+        assert_eq!(unsafe { raw_motion_delta(raw) }, (0.0, -2.0));
+    }
+
+    #[test]
+    fn test_modifiers_from_state() {
+        let modifiers = modifiers_from_state(0x01 | 0x04);
+        assert!(modifiers.has_shift());
+        assert!(modifiers.has_ctrl());
+        assert!(!modifiers.has_alt());
+        assert!(modifiers_from_state(0x40).contains(ModifierKeys::META));
+    }
+}
@@ -0,0 +1,153 @@
+//! Persistência da geometria da janela entre execuções
+//!
+//! `Window::save_state` captura posição, tamanho, monitor e modo de exibição;
+//! `Window::restore_state` reaplica, trazendo a janela de volta para a tela
+//! se o monitor em que ela estava não existir mais.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{DisplayMode, MonitorInfo, WindowPosition, WindowSize};
+
+/// Estado salvo de uma janela
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowState {
+    /// Posição em janela (`WindowPosition::CENTERED` se nunca foi definida)
+    pub position: WindowPosition,
+    /// Tamanho em janela, na unidade da `ScalePolicy`
+    pub size: WindowSize,
+    /// Nome do monitor em que a janela estava
+    pub monitor: Option<String>,
+    pub display_mode: DisplayMode,
+}
+
+impl WindowState {
+    /// Posição salva, se o canto superior esquerdo ainda cair num monitor
+    pub fn visible_position(&self, monitors: &[MonitorInfo]) -> Option<WindowPosition> {
+        if self.position == WindowPosition::CENTERED {
+            return None;
+        }
+        let WindowPosition { x, y } = self.position;
+        monitors
+            .iter()
+            .any(|monitor| {
+                let left = monitor.position.x as i64;
+                let top = monitor.position.y as i64;
+                (left..left + monitor.size.width as i64).contains(&(x as i64))
+                    && (top..top + monitor.size.height as i64).contains(&(y as i64))
+            })
+            .then_some(self.position)
+    }
+
+    /// Monitor salvo, se ainda estiver conectado
+    pub fn find_monitor<'a>(&self, monitors: &'a [MonitorInfo]) -> Option<&'a MonitorInfo> {
+        let name = self.monitor.as_deref()?;
+        monitors.iter().find(|monitor| monitor.name == name)
+    }
+
+    /// Serializa o estado em JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("WindowState is always serializable")
+    }
+
+    /// Carrega um estado salvo por `to_json`
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::{Window, WindowConfig};
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![
+            MonitorInfo {
+                name: "Left".to_string(),
+                size: WindowSize::new(1920, 1080),
+                position: WindowPosition::new(0, 0),
+                refresh_rate: 60,
+                scale_factor: 1.0,
+                is_primary: true,
+            },
+            MonitorInfo {
+                name: "Right".to_string(),
+                size: WindowSize::new(2560, 1440),
+                position: WindowPosition::new(1920, 0),
+                refresh_rate: 144,
+                scale_factor: 1.25,
+                is_primary: false,
+            },
+        ]
+    }
+
+    fn state(x: i32, y: i32, monitor: &str) -> WindowState {
+        WindowState {
+            position: WindowPosition::new(x, y),
+            size: WindowSize::new(800, 600),
+            monitor: Some(monitor.to_string()),
+            display_mode: DisplayMode::Windowed,
+        }
+    }
+
+    #[test]
+    fn test_visible_position() {
+        let monitors = monitors();
+        assert!(state(2000, 100, "Right")
+            .visible_position(&monitors)
+            .is_some());
+        assert!(state(5000, 100, "Right")
+            .visible_position(&monitors)
+            .is_none());
+        assert!(state(-50, 100, "Left")
+            .visible_position(&monitors)
+            .is_none());
+
+        assert_eq!(
+            state(0, 0, "Right")
+                .find_monitor(&monitors)
+                .unwrap()
+                .refresh_rate,
+            144
+        );
+        assert!(state(0, 0, "Gone").find_monitor(&monitors).is_none());
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let mut window = Window::headless(WindowConfig::default());
+        window.set_size(1024, 768).unwrap();
+        window.set_position(100, 50);
+        window.maximize().unwrap();
+
+        let saved = window.save_state();
+        assert_eq!(saved.size, WindowSize::new(1024, 768));
+        assert_eq!(saved.position, WindowPosition::new(100, 50));
+        assert_eq!(saved.display_mode, DisplayMode::Maximized);
+
+        let mut reopened = Window::headless(WindowConfig::default());
+        reopened.restore_state(&saved).unwrap();
+        assert_eq!(reopened.size(), saved.size);
+        assert_eq!(reopened.position(), saved.position);
+        assert_eq!(reopened.display_mode(), DisplayMode::Maximized);
+    }
+
+    #[test]
+    fn test_restore_offscreen() {
+        let mut window = Window::headless(WindowConfig::default());
+        window.restore_state(&state(-5000, -5000, "Gone")).unwrap();
+        assert_eq!(window.position(), WindowPosition::CENTERED);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_window_state_json() {
+        let saved = state(10, 20, "Left");
+        let restored = WindowState::from_json(&saved.to_json()).unwrap();
+        assert_eq!(restored, saved);
+    }
+}
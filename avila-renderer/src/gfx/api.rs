@@ -6,7 +6,43 @@
 //! This module defines the core graphics API that is backend-agnostic.
 //! All engine systems (scene, materials, rendering passes) only see these types.
 
+use avila_math::memory::{AllocatorInfo, AllocatorType};
+use avila_math::os::ThreadPool;
 use std::fmt;
+use std::time::Duration;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Why the GPU refused an operation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GfxError {
+    /// Video or host memory is exhausted
+    OutOfMemory,
+    /// The description is invalid or unsupported; the message says why
+    InvalidDesc(String),
+    /// The device was lost (driver reset, GPU removed); recreate it
+    DeviceLost,
+    /// Shader compilation or program linking failed; carries the compiler log
+    ShaderCompileError(String),
+    /// The backend cannot do what was asked, e.g. present to a window
+    Unsupported(String),
+}
+
+impl fmt::Display for GfxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GfxError::OutOfMemory => write!(f, "out of GPU memory"),
+            GfxError::InvalidDesc(reason) => write!(f, "invalid description: {}", reason),
+            GfxError::DeviceLost => write!(f, "GPU device lost"),
+            GfxError::ShaderCompileError(log) => write!(f, "shader compilation failed: {}", log),
+            GfxError::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GfxError {}
 
 // ============================================================================
 // Texture Types
@@ -63,7 +99,7 @@ impl TextureFormat {
 }
 
 /// Texture usage flags (can be combined)
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TextureUsage(u32);
 
 impl TextureUsage {
@@ -92,7 +128,7 @@ impl std::ops::BitOr for TextureUsage {
 }
 
 /// Texture dimension type
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TextureDimension {
     D1,
     D2,
@@ -101,7 +137,7 @@ pub enum TextureDimension {
 }
 
 /// Texture description for creation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TextureDesc {
     pub width: u32,
     pub height: u32,
@@ -129,6 +165,11 @@ impl TextureDesc {
         }
     }
 
+    /// Give the texture `mip_levels` mip levels
+    ///
+    /// When a texture `GpuDevice::generate_mipmaps` supports is created
+    /// with initial data, levels above 0 are generated from it; otherwise
+    /// fill them with `generate_mipmaps` or `update_texture`.
     pub fn with_mips(mut self, mip_levels: u32) -> Self {
         self.mip_levels = mip_levels;
         self
@@ -140,6 +181,49 @@ impl TextureDesc {
     }
 }
 
+/// Box of texels inside one mip level of a texture
+///
+/// `z`/`depth` select slices of a 3D texture and must be `0`/`1` otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+}
+
+impl TextureRegion {
+    pub fn new_2d(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            z: 0,
+            width,
+            height,
+            depth: 1,
+        }
+    }
+
+    /// The whole of mip level `mip` of a texture
+    pub fn full(desc: &TextureDesc, mip: u32) -> Self {
+        let extent = |size: u32| (size >> mip).max(1);
+        Self {
+            x: 0,
+            y: 0,
+            z: 0,
+            width: extent(desc.width),
+            height: extent(desc.height),
+            depth: if desc.dimension == TextureDimension::D3 {
+                extent(desc.depth)
+            } else {
+                1
+            },
+        }
+    }
+}
+
 /// Opaque handle to a GPU texture
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct TextureHandle(pub u32);
@@ -148,12 +232,101 @@ impl TextureHandle {
     pub const INVALID: Self = Self(u32::MAX);
 }
 
+// ============================================================================
+// Sampler Types
+// ============================================================================
+
+/// Texel filtering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+/// How texture coordinates outside 0..1 wrap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressMode {
+    Repeat,
+    MirrorRepeat,
+    ClampToEdge,
+}
+
+/// Sampler description for creation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDesc {
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    /// Filtering between mip levels
+    pub mip_filter: FilterMode,
+    pub address_u: AddressMode,
+    pub address_v: AddressMode,
+    pub address_w: AddressMode,
+    /// 1 disables anisotropic filtering; up to 16 with all filters linear
+    pub max_anisotropy: u16,
+    /// Depth comparison for shadow maps; `None` returns the texel values
+    pub compare: Option<CompareFunction>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::linear(AddressMode::Repeat)
+    }
+}
+
+impl SamplerDesc {
+    /// Trilinear filtering
+    pub fn linear(address: AddressMode) -> Self {
+        Self {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mip_filter: FilterMode::Linear,
+            address_u: address,
+            address_v: address,
+            address_w: address,
+            max_anisotropy: 1,
+            compare: None,
+        }
+    }
+
+    /// Point sampling
+    pub fn nearest(address: AddressMode) -> Self {
+        Self {
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mip_filter: FilterMode::Nearest,
+            ..Self::linear(address)
+        }
+    }
+
+    /// Percentage-closer filtering of a shadow map
+    pub fn shadow(compare: CompareFunction) -> Self {
+        Self {
+            compare: Some(compare),
+            ..Self::linear(AddressMode::ClampToEdge)
+        }
+    }
+
+    /// Anisotropic filtering with up to `max_anisotropy` samples
+    pub fn with_anisotropy(mut self, max_anisotropy: u16) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+}
+
+/// Opaque handle to a sampler
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SamplerHandle(pub u32);
+
+impl SamplerHandle {
+    pub const INVALID: Self = Self(u32::MAX);
+}
+
 // ============================================================================
 // Buffer Types
 // ============================================================================
 
 /// Buffer usage type
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BufferUsage {
     Vertex,
     Index,
@@ -165,7 +338,7 @@ pub enum BufferUsage {
 }
 
 /// Buffer description for creation
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BufferDesc {
     pub size: usize,
     pub usage: BufferUsage,
@@ -204,6 +377,15 @@ impl BufferDesc {
             cpu_visible: false,
         }
     }
+
+    /// Source of copies, written by the CPU
+    pub fn staging(size: usize) -> Self {
+        Self {
+            size,
+            usage: BufferUsage::TransferSrc,
+            cpu_visible: true,
+        }
+    }
 }
 
 /// Opaque handle to a GPU buffer
@@ -245,12 +427,119 @@ impl ShaderHandle {
     pub const INVALID: Self = Self(u32::MAX);
 }
 
+// ============================================================================
+// Bind Group Types
+// ============================================================================
+
+/// Kind of resource a bind group layout slot takes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingType {
+    /// A buffer created with `BufferUsage::Uniform`
+    UniformBuffer,
+    /// A buffer created with `BufferUsage::Storage`
+    StorageBuffer { read_only: bool },
+    /// A texture created with `TextureUsage::SAMPLED`; `array` is a 2D
+    /// texture with several layers, `comparison` a depth texture read
+    /// through a comparison sampler
+    Texture {
+        dimension: TextureDimension,
+        array: bool,
+        comparison: bool,
+    },
+    /// A sampler; `comparison` takes samplers with a compare function
+    Sampler { comparison: bool },
+    /// Mip 0 of a texture created with `TextureUsage::STORAGE`, read and
+    /// written per texel; `format` is `Rgba8`, `Rgba16f` or `Rgba32f`, and
+    /// the texture is a single layer (no arrays or cube maps)
+    StorageTexture {
+        format: TextureFormat,
+        dimension: TextureDimension,
+        access: StorageTextureAccess,
+    },
+}
+
+/// What a shader may do with a storage texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageTextureAccess {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+/// One slot of a bind group layout, visible to every stage
+///
+/// Vertex shaders do not see writable storage buffers and storage textures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BindGroupLayoutEntry {
+    pub binding: u32,
+    pub ty: BindingType,
+}
+
+/// Bind group layout description for creation
+///
+/// OpenGL has no bind groups: buffers go to the uniform or storage buffer
+/// binding point `binding`, textures to texture unit `binding`, storage
+/// textures to image unit `binding`, and a sampler at `binding + 1` of a
+/// texture is bound to the texture's unit.
+/// Bindings of the same kind must therefore be unique across all the
+/// groups a pipeline uses.
+#[derive(Clone, Debug, Default)]
+pub struct BindGroupLayoutDesc {
+    pub entries: Vec<BindGroupLayoutEntry>,
+}
+
+/// Opaque handle to a bind group layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BindGroupLayoutHandle(pub u32);
+
+impl BindGroupLayoutHandle {
+    pub const INVALID: Self = Self(u32::MAX);
+}
+
+/// Resource bound to one slot of a bind group
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingResource {
+    /// `size` of `None` binds the rest of the buffer
+    Buffer {
+        buffer: BufferHandle,
+        offset: u64,
+        size: Option<u64>,
+    },
+    Texture(TextureHandle),
+    Sampler(SamplerHandle),
+}
+
+/// Resource for the layout slot `binding`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BindGroupEntry {
+    pub binding: u32,
+    pub resource: BindingResource,
+}
+
+/// Bind group description for creation
+///
+/// Every slot of `layout` needs exactly one entry. The group holds on to
+/// the handles, not the resources: destroy the group before what it binds.
+#[derive(Clone, Debug)]
+pub struct BindGroupDesc {
+    pub layout: BindGroupLayoutHandle,
+    pub entries: Vec<BindGroupEntry>,
+}
+
+/// Opaque handle to a bind group
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BindGroupHandle(pub u32);
+
+impl BindGroupHandle {
+    pub const INVALID: Self = Self(u32::MAX);
+}
+
 // ============================================================================
 // Pipeline Types
 // ============================================================================
 
 /// Vertex attribute format
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum VertexFormat {
     Float,
     Float2,
@@ -274,22 +563,80 @@ impl VertexFormat {
 }
 
 /// Vertex attribute description
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct VertexAttribute {
     pub format: VertexFormat,
     pub offset: u32,
     pub location: u32,
 }
 
+/// How often a vertex buffer moves on to its next element
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VertexStepMode {
+    /// Once per vertex
+    #[default]
+    Vertex,
+    /// Once per instance, for per-instance data such as transforms
+    Instance,
+}
+
+/// Layout of a vertex buffer after the first one of a `VertexLayout`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexBufferLayout {
+    pub stride: u32,
+    pub step_mode: VertexStepMode,
+    pub attributes: Vec<VertexAttribute>,
+}
+
 /// Vertex buffer layout
-#[derive(Clone, Debug)]
+///
+/// `stride` and `attributes` describe the per-vertex buffer bound at slot
+/// 0; `buffers` describes the ones at slots 1, 2 and on, such as
+/// per-instance data. A location is fed by one buffer only.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct VertexLayout {
     pub stride: u32,
     pub attributes: Vec<VertexAttribute>,
+    pub buffers: Vec<VertexBufferLayout>,
+}
+
+impl VertexLayout {
+    /// Layout with a single per-vertex buffer
+    pub fn new(stride: u32, attributes: Vec<VertexAttribute>) -> Self {
+        Self {
+            stride,
+            attributes,
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Add a buffer at the next slot, read once per instance
+    pub fn with_instance_buffer(mut self, stride: u32, attributes: Vec<VertexAttribute>) -> Self {
+        self.buffers.push(VertexBufferLayout {
+            stride,
+            step_mode: VertexStepMode::Instance,
+            attributes,
+        });
+        self
+    }
+
+    /// Stride, step mode and attributes of every buffer, slot 0 first
+    pub fn slots(&self) -> impl Iterator<Item = (u32, VertexStepMode, &[VertexAttribute])> {
+        std::iter::once((self.stride, VertexStepMode::Vertex, &self.attributes[..])).chain(
+            self.buffers
+                .iter()
+                .map(|buffer| (buffer.stride, buffer.step_mode, &buffer.attributes[..])),
+        )
+    }
+
+    /// Attributes of every buffer
+    pub fn all_attributes(&self) -> impl Iterator<Item = &VertexAttribute> {
+        self.slots().flat_map(|(_, _, attributes)| attributes)
+    }
 }
 
 /// Primitive topology
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PrimitiveTopology {
     TriangleList,
     TriangleStrip,
@@ -299,7 +646,7 @@ pub enum PrimitiveTopology {
 }
 
 /// Comparison function for depth/stencil tests
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CompareFunction {
     Never,
     Less,
@@ -312,7 +659,7 @@ pub enum CompareFunction {
 }
 
 /// Blend factor
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BlendFactor {
     Zero,
     One,
@@ -327,7 +674,7 @@ pub enum BlendFactor {
 }
 
 /// Blend operation
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BlendOp {
     Add,
     Subtract,
@@ -337,7 +684,7 @@ pub enum BlendOp {
 }
 
 /// Blend state for a color attachment
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct BlendState {
     pub enabled: bool,
     pub src_color: BlendFactor,
@@ -375,7 +722,7 @@ impl BlendState {
 }
 
 /// Depth/stencil state
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct DepthStencilState {
     pub depth_test_enabled: bool,
     pub depth_write_enabled: bool,
@@ -393,27 +740,27 @@ impl Default for DepthStencilState {
 }
 
 /// Rasterizer state
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct RasterizerState {
     pub cull_mode: CullMode,
     pub front_face: FrontFace,
     pub polygon_mode: PolygonMode,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CullMode {
     None,
     Front,
     Back,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum FrontFace {
     Clockwise,
     CounterClockwise,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PolygonMode {
     Fill,
     Line,
@@ -430,8 +777,51 @@ impl Default for RasterizerState {
     }
 }
 
+/// Highest bind set a pipeline layout may use, plus one
+pub const MAX_BIND_SETS: u32 = 4;
+
+/// A sampled texture in a pipeline layout, bound with `bind_texture`
+///
+/// Backends that keep textures and samplers apart (wgpu) put the sampler at
+/// `binding + 1`, so texture bindings in a set are at least two apart.
+/// OpenGL has no sets: the texture unit is `binding`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureBinding {
+    pub set: u32,
+    pub binding: u32,
+    pub dimension: TextureDimension,
+    /// A 2D texture with several array layers
+    pub array: bool,
+    /// A depth texture read through a comparison sampler
+    pub comparison: bool,
+}
+
+impl TextureBinding {
+    /// A 2D color texture
+    pub fn new_2d(set: u32, binding: u32) -> Self {
+        Self {
+            set,
+            binding,
+            dimension: TextureDimension::D2,
+            array: false,
+            comparison: false,
+        }
+    }
+}
+
+/// Resources a pipeline's shaders read, visible to every stage
+///
+/// `bind_groups[i]` is the layout of set `i`, bound with `bind_group`.
+/// `textures` describes sets past those, bound one texture at a time with
+/// `bind_texture`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PipelineLayoutDesc {
+    pub bind_groups: Vec<BindGroupLayoutHandle>,
+    pub textures: Vec<TextureBinding>,
+}
+
 /// Graphics pipeline description
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PipelineDesc {
     pub vertex_shader: ShaderHandle,
     pub fragment_shader: ShaderHandle,
@@ -442,6 +832,10 @@ pub struct PipelineDesc {
     pub blend_states: Vec<BlendState>,
     pub color_formats: Vec<TextureFormat>,
     pub depth_format: Option<TextureFormat>,
+    /// Samples of the attachments it draws to (1 = no MSAA); draws in a
+    /// render pass with another sample count are skipped
+    pub sample_count: u32,
+    pub layout: PipelineLayoutDesc,
 }
 
 /// Opaque handle to a graphics pipeline
@@ -452,10 +846,40 @@ impl PipelineHandle {
     pub const INVALID: Self = Self(u32::MAX);
 }
 
+/// Compute pipeline description
+#[derive(Clone, Debug)]
+pub struct ComputePipelineDesc {
+    pub shader: ShaderHandle,
+    pub layout: PipelineLayoutDesc,
+}
+
+/// Opaque handle to a compute pipeline
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ComputePipelineHandle(pub u32);
+
+impl ComputePipelineHandle {
+    pub const INVALID: Self = Self(u32::MAX);
+}
+
 // ============================================================================
 // Command Recording
 // ============================================================================
 
+/// Alignment of the distance between rows in `copy_buffer_to_texture`
+pub const COPY_ROW_ALIGNMENT: u32 = 256;
+
+/// Where the texels of `copy_buffer_to_texture` are in the source buffer
+///
+/// Rows and slices follow each other as in `update_texture`, but each row
+/// starts `bytes_per_row` after the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TexelBufferLayout {
+    /// Start of the first row, a multiple of the texel (or block) size
+    pub offset: u64,
+    /// Multiple of `COPY_ROW_ALIGNMENT`, at least one row of the region
+    pub bytes_per_row: u32,
+}
+
 /// Viewport for rendering
 #[derive(Clone, Copy, Debug)]
 pub struct Viewport {
@@ -516,18 +940,47 @@ impl Default for ClearDepthStencil {
     }
 }
 
+/// What a render pass does with an attachment when it begins
+#[derive(Clone, Copy, Debug)]
+pub enum LoadOp<T> {
+    /// Keep the contents from before the pass
+    Load,
+    /// Fill the attachment with a value
+    Clear(T),
+    /// Start from undefined contents, for passes that cover every pixel;
+    /// tile-based GPUs then skip reading the attachment from memory
+    DontCare,
+}
+
+/// What a render pass does with an attachment when it ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreOp {
+    /// Write the rendered contents to the texture
+    Store,
+    /// Nothing reads the contents after the pass, so they may be dropped
+    DontCare,
+    /// Average the samples of a multisampled color attachment into a
+    /// single-sampled texture of the same format and size; the multisampled
+    /// contents are dropped
+    Resolve(TextureHandle),
+}
+
 /// Render pass color attachment
 #[derive(Clone, Debug)]
 pub struct ColorAttachment {
     pub texture: TextureHandle,
-    pub clear: Option<ClearColor>,
+    pub load: LoadOp<ClearColor>,
+    pub store: StoreOp,
 }
 
 /// Render pass depth attachment
+///
+/// Depth attachments cannot be resolved.
 #[derive(Clone, Debug)]
 pub struct DepthAttachment {
     pub texture: TextureHandle,
-    pub clear: Option<ClearDepthStencil>,
+    pub load: LoadOp<ClearDepthStencil>,
+    pub store: StoreOp,
 }
 
 /// Render pass description
@@ -538,26 +991,91 @@ pub struct RenderPassDesc {
 }
 
 /// Command list for recording GPU commands
+///
+/// `GpuDevice::begin_frame` gives the list of a frame. Lists made with
+/// `CommandList::new` record on any thread without the device, and join the
+/// frame's list with `execute_commands` or are submitted after it.
 pub struct CommandList {
     // Internal implementation hidden from API users
     pub(crate) commands: Vec<Command>,
+    /// Whether the last render pass recorded is still open
+    in_render_pass: bool,
 }
 
 impl CommandList {
-    pub(crate) fn new() -> Self {
+    /// An empty list, such as a secondary list for a worker thread
+    pub fn new() -> Self {
         Self {
             commands: Vec::new(),
+            in_render_pass: false,
+        }
+    }
+
+    /// Record `count` lists on the threads of `pool`, `record(i, list)`
+    /// filling list `i`; the lists come back in index order
+    pub fn record_parallel(
+        pool: &ThreadPool,
+        count: usize,
+        record: impl Fn(usize, &mut CommandList) + Sync,
+    ) -> Vec<CommandList> {
+        let indices: Vec<usize> = (0..count).collect();
+        pool.parallel_map(&indices, 1, |&index| {
+            let mut list = CommandList::new();
+            record(index, &mut list);
+            list
+        })
+    }
+
+    /// Append the commands of `secondary`, as if they were recorded here
+    ///
+    /// Inside a render pass the secondary list draws into that pass, so it
+    /// may not begin or end passes, dispatch compute work or copy. Bindings
+    /// carry over in both directions: bind what the secondary list's draws
+    /// need at its start.
+    pub fn execute_commands(&mut self, secondary: CommandList) -> Result<(), GfxError> {
+        if self.in_render_pass {
+            let outside_pass = secondary.commands.iter().any(|command| {
+                matches!(
+                    command,
+                    Command::BeginRenderPass(_)
+                        | Command::EndRenderPass
+                        | Command::BindComputePipeline(_)
+                        | Command::Dispatch { .. }
+                        | Command::CopyBuffer { .. }
+                        | Command::CopyBufferToTexture { .. }
+                )
+            });
+            if outside_pass {
+                return Err(GfxError::InvalidDesc(
+                    "a list executed inside a render pass cannot begin or end passes, \
+                     dispatch compute work or copy"
+                        .to_string(),
+                ));
+            }
+        }
+        let last_pass_command = secondary.commands.iter().rev().find(|command| {
+            matches!(
+                command,
+                Command::BeginRenderPass(_) | Command::EndRenderPass
+            )
+        });
+        if let Some(command) = last_pass_command {
+            self.in_render_pass = matches!(command, Command::BeginRenderPass(_));
         }
+        self.commands.extend(secondary.commands);
+        Ok(())
     }
 
     /// Begin a render pass
     pub fn begin_render_pass(&mut self, desc: RenderPassDesc) {
         self.commands.push(Command::BeginRenderPass(desc));
+        self.in_render_pass = true;
     }
 
     /// End the current render pass
     pub fn end_render_pass(&mut self) {
         self.commands.push(Command::EndRenderPass);
+        self.in_render_pass = false;
     }
 
     /// Bind a graphics pipeline
@@ -584,6 +1102,36 @@ impl CommandList {
         });
     }
 
+    /// Bind a texture and the sampler to read it with
+    ///
+    /// `set` and `binding` name a `TextureBinding` of the bound pipeline's
+    /// layout; the texture needs `TextureUsage::SAMPLED`. Bindings last
+    /// until a render pass begins or ends, so outside render passes they
+    /// apply to dispatches.
+    pub fn bind_texture(
+        &mut self,
+        set: u32,
+        binding: u32,
+        texture: TextureHandle,
+        sampler: SamplerHandle,
+    ) {
+        self.commands.push(Command::BindTexture {
+            set,
+            binding,
+            texture,
+            sampler,
+        });
+    }
+
+    /// Bind a bind group as set `index`
+    ///
+    /// The group's layout must be `bind_groups[index]` of the bound
+    /// pipeline's layout. Bindings last until a render pass begins or ends,
+    /// so outside render passes they apply to dispatches.
+    pub fn bind_group(&mut self, index: u32, group: BindGroupHandle) {
+        self.commands.push(Command::BindGroup { index, group });
+    }
+
     /// Bind index buffer
     pub fn bind_index_buffer(&mut self, buffer: BufferHandle, offset: u64, index_type: IndexType) {
         self.commands.push(Command::BindIndexBuffer {
@@ -626,6 +1174,81 @@ impl CommandList {
             first_instance,
         });
     }
+
+    /// Bind a compute pipeline for the following dispatches
+    ///
+    /// Compute work is recorded outside render passes.
+    pub fn bind_compute_pipeline(&mut self, pipeline: ComputePipelineHandle) {
+        self.commands.push(Command::BindComputePipeline(pipeline));
+    }
+
+    /// Run `x * y * z` workgroups of the bound compute pipeline
+    ///
+    /// Later commands see what the dispatch wrote.
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+        self.commands.push(Command::Dispatch { x, y, z });
+    }
+
+    /// Record the GPU time when the work before this point is done
+    ///
+    /// The timestamp starts the section `name`, which lasts until the next
+    /// timestamp of the frame; results come back a few frames later from
+    /// `GpuDevice::read_timestamps`. Allowed inside and outside render
+    /// passes.
+    pub fn write_timestamp(&mut self, name: &str) {
+        self.commands
+            .push(Command::WriteTimestamp(name.to_string()));
+    }
+
+    /// Copy `size` bytes from `src` at `src_offset` to `dst` at `dst_offset`
+    ///
+    /// Copies are recorded outside render passes; offsets and size are
+    /// multiples of 4. Later commands see the copied data.
+    pub fn copy_buffer(
+        &mut self,
+        src: BufferHandle,
+        src_offset: u64,
+        dst: BufferHandle,
+        dst_offset: u64,
+        size: u64,
+    ) {
+        self.commands.push(Command::CopyBuffer {
+            src,
+            src_offset,
+            dst,
+            dst_offset,
+            size,
+        });
+    }
+
+    /// Copy texels from `src` into `region` of one mip level and array layer
+    ///
+    /// Copies are recorded outside render passes; the texture needs
+    /// `TextureUsage::TRANSFER_DST`, as for `update_texture`.
+    pub fn copy_buffer_to_texture(
+        &mut self,
+        src: BufferHandle,
+        layout: TexelBufferLayout,
+        texture: TextureHandle,
+        mip: u32,
+        layer: u32,
+        region: TextureRegion,
+    ) {
+        self.commands.push(Command::CopyBufferToTexture {
+            src,
+            layout,
+            texture,
+            mip,
+            layer,
+            region,
+        });
+    }
+}
+
+impl Default for CommandList {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -652,6 +1275,16 @@ pub(crate) enum Command {
         offset: u64,
         index_type: IndexType,
     },
+    BindGroup {
+        index: u32,
+        group: BindGroupHandle,
+    },
+    BindTexture {
+        set: u32,
+        binding: u32,
+        texture: TextureHandle,
+        sampler: SamplerHandle,
+    },
     Draw {
         vertex_count: u32,
         instance_count: u32,
@@ -665,20 +1298,214 @@ pub(crate) enum Command {
         vertex_offset: i32,
         first_instance: u32,
     },
+    BindComputePipeline(ComputePipelineHandle),
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    WriteTimestamp(String),
+    CopyBuffer {
+        src: BufferHandle,
+        src_offset: u64,
+        dst: BufferHandle,
+        dst_offset: u64,
+        size: u64,
+    },
+    CopyBufferToTexture {
+        src: BufferHandle,
+        layout: TexelBufferLayout,
+        texture: TextureHandle,
+        mip: u32,
+        layer: u32,
+        region: TextureRegion,
+    },
+}
+
+/// Point in the work submitted to the GPU, from `GpuDevice::signal_fence`
+///
+/// Later fences have larger values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FenceValue(pub u64);
+
+/// Change in the device or swapchain that the application must react to,
+/// from `GpuDevice::poll_events`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// The swapchain was recreated at a new size, in physical pixels;
+    /// recreate the targets that follow it
+    SwapchainResized { width: u32, height: u32 },
+    /// The GPU device was lost (driver reset, GPU removed); every handle
+    /// created before is invalid
+    Lost,
+    /// A new device replaced the lost one, with a new swapchain; recreate
+    /// the resources before recording
+    Restored,
+}
+
+/// GPU timestamps written during one frame, in the order they were written
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameTimestamps {
+    /// Index of the frame: the number of `present` calls before it
+    pub frame: u64,
+    /// Name and GPU time in nanoseconds; only differences between them
+    /// mean anything
+    pub timestamps: Vec<(String, u64)>,
+}
+
+impl FrameTimestamps {
+    /// Name and duration of each section, from one timestamp to the next
+    ///
+    /// The last timestamp only ends the section before it.
+    pub fn sections(&self) -> Vec<(String, Duration)> {
+        self.timestamps
+            .windows(2)
+            .map(|pair| {
+                let nanos = pair[1].1.saturating_sub(pair[0].1);
+                (pair[0].0.clone(), Duration::from_nanos(nanos))
+            })
+            .collect()
+    }
 }
 
 // ============================================================================
 // Renderer Configuration
 // ============================================================================
 
+/// Native graphics API used by the device
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// No GPU work; every call is logged
+    #[default]
+    Null,
+    /// CPU rasterizer into in-memory textures, for tests without a GPU.
+    /// Shaders are not run; see the backend module for what it draws
+    Software,
+    /// OpenGL 4.5 core, or GLES 3.2 where desktop GL is missing
+    /// (requires the `opengl` feature)
+    OpenGl,
+    /// wgpu on whatever it finds: Vulkan, D3D12, Metal or GL
+    /// (requires the `wgpu` feature)
+    Wgpu,
+}
+
+/// Which GPU to prefer when several are available
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AdapterPreference {
+    /// Discrete GPU
+    #[default]
+    HighPerformance,
+    /// Integrated GPU, to save battery
+    LowPower,
+}
+
+/// How presented images reach the screen
+///
+/// A mode the surface does not support falls back to the closest one it
+/// does; `Fifo` is always supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// Wait for vertical blank; `present` blocks when every image is queued
+    #[default]
+    Fifo,
+    /// Replace the queued image at each `present`; no tearing, no blocking
+    Mailbox,
+    /// Show images right away, tearing included; the lowest latency
+    Immediate,
+}
+
+/// Kind of device behind an adapter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdapterType {
+    Discrete,
+    Integrated,
+    /// GPU of a virtual machine
+    Virtual,
+    /// Software rendering
+    Cpu,
+    Unknown,
+}
+
+/// Description of a GPU a backend can render with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdapterInfo {
+    pub backend: Backend,
+    pub name: String,
+    pub vendor: String,
+    /// Dedicated video memory in bytes, if the API reports it
+    pub vram: Option<u64>,
+    pub adapter_type: AdapterType,
+}
+
+/// Device memory a resource is placed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryHeap {
+    /// Fastest for the GPU: textures and buffers the CPU does not map
+    DeviceLocal,
+    /// Mappable by the CPU: buffers with `cpu_visible`
+    HostVisible,
+}
+
+impl MemoryHeap {
+    pub const ALL: [Self; 2] = [Self::DeviceLocal, Self::HostVisible];
+
+    /// Name of the heap in a `MemoryManager` report
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryHeap::DeviceLocal => "gpu.device_local",
+            MemoryHeap::HostVisible => "gpu.host_visible",
+        }
+    }
+}
+
+/// Statistics of one heap of the device memory allocator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeapStats {
+    pub heap: MemoryHeap,
+    /// Most bytes the heap may reserve, `u64::MAX` when unlimited
+    pub budget: u64,
+    /// Bytes reserved from the device, in blocks and dedicated allocations
+    pub reserved: u64,
+    /// Bytes of `reserved` occupied by resources, alignment included
+    pub used: u64,
+    /// Blocks shared by sub-allocated resources
+    pub block_count: usize,
+    /// Resources large enough to get memory of their own
+    pub dedicated_count: usize,
+    pub allocation_count: usize,
+    pub deallocation_count: usize,
+}
+
+impl HeapStats {
+    /// The statistics as the kernel `MemoryManager` reports them
+    pub fn allocator_info(&self) -> AllocatorInfo {
+        AllocatorInfo {
+            allocator_type: AllocatorType::Gpu,
+            total_capacity: self.reserved as usize,
+            used: self.used as usize,
+            available: (self.reserved - self.used) as usize,
+            allocation_count: self.allocation_count,
+            deallocation_count: self.deallocation_count,
+        }
+    }
+}
+
 /// Renderer configuration
 #[derive(Clone, Debug)]
 pub struct RendererConfig {
     pub width: u32,
     pub height: u32,
-    pub vsync: bool,
-    pub msaa_samples: u32, // 1, 2, 4, 8
+    pub present_mode: PresentMode,
+    /// Frames the CPU may record while the GPU works on earlier ones (at
+    /// least 1); see `GpuDevice::wait_for_frame`
+    pub frames_in_flight: u32,
+    /// Samples of `GpuDevice::get_msaa_texture` (1 = no MSAA)
+    pub msaa_samples: u32,
+    /// Render the scene in `Rgba16f` and tonemap it (`PostProcessStack`);
+    /// the swapchain is `Rgba16f` too
     pub hdr: bool,
+    pub backend: Backend,
+    pub adapter_preference: AdapterPreference,
 }
 
 impl Default for RendererConfig {
@@ -686,9 +1513,23 @@ impl Default for RendererConfig {
         Self {
             width: 1280,
             height: 720,
-            vsync: true,
+            present_mode: PresentMode::Fifo,
+            frames_in_flight: 2,
             msaa_samples: 1,
             hdr: false,
+            backend: Backend::Null,
+            adapter_preference: AdapterPreference::HighPerformance,
+        }
+    }
+}
+
+impl RendererConfig {
+    /// Format of the swapchain images: `Rgba16f` with `hdr`, else `Bgra8`
+    pub fn swapchain_format(&self) -> TextureFormat {
+        if self.hdr {
+            TextureFormat::Rgba16f
+        } else {
+            TextureFormat::Bgra8
         }
     }
 }
@@ -703,31 +1544,137 @@ impl Default for RendererConfig {
 /// Backend implementations provide concrete implementations.
 pub trait GpuDevice {
     // Resource creation
-    fn create_texture(&mut self, desc: &TextureDesc) -> TextureHandle;
-    fn create_buffer(&mut self, desc: &BufferDesc, initial_data: Option<&[u8]>) -> BufferHandle;
-    fn create_shader(&mut self, desc: &ShaderDesc) -> ShaderHandle;
-    fn create_pipeline(&mut self, desc: &PipelineDesc) -> PipelineHandle;
+    fn create_texture(
+        &mut self,
+        desc: &TextureDesc,
+        initial_data: Option<&[u8]>,
+    ) -> Result<TextureHandle, GfxError>;
+    fn create_buffer(
+        &mut self,
+        desc: &BufferDesc,
+        initial_data: Option<&[u8]>,
+    ) -> Result<BufferHandle, GfxError>;
+    fn create_shader(&mut self, desc: &ShaderDesc) -> Result<ShaderHandle, GfxError>;
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineHandle, GfxError>;
+    fn create_compute_pipeline(
+        &mut self,
+        desc: &ComputePipelineDesc,
+    ) -> Result<ComputePipelineHandle, GfxError>;
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerHandle, GfxError>;
+    fn create_bind_group_layout(
+        &mut self,
+        desc: &BindGroupLayoutDesc,
+    ) -> Result<BindGroupLayoutHandle, GfxError>;
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupHandle, GfxError>;
 
     // Resource destruction
     fn destroy_texture(&mut self, handle: TextureHandle);
     fn destroy_buffer(&mut self, handle: BufferHandle);
     fn destroy_shader(&mut self, handle: ShaderHandle);
     fn destroy_pipeline(&mut self, handle: PipelineHandle);
+    fn destroy_compute_pipeline(&mut self, handle: ComputePipelineHandle);
+    fn destroy_sampler(&mut self, handle: SamplerHandle);
+    fn destroy_bind_group_layout(&mut self, handle: BindGroupLayoutHandle);
+    fn destroy_bind_group(&mut self, handle: BindGroupHandle);
 
     // Buffer operations
     fn update_buffer(&mut self, buffer: BufferHandle, offset: usize, data: &[u8]);
     fn map_buffer(&mut self, buffer: BufferHandle) -> *mut u8;
     fn unmap_buffer(&mut self, buffer: BufferHandle);
 
+    // Texture operations
+    /// Copy texels into `region` of one mip level and array layer
+    ///
+    /// `data` holds tightly packed rows, top row first (rows of 4x4 blocks
+    /// for compressed formats); 3D slices follow each other. The texture
+    /// needs `TextureUsage::TRANSFER_DST`. `create_texture` takes the same
+    /// layout for mip 0 of every layer, layer after layer.
+    fn update_texture(
+        &mut self,
+        texture: TextureHandle,
+        mip: u32,
+        layer: u32,
+        region: TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError>;
+    /// Fill every mip level above 0 from mip 0, after the work submitted
+    /// so far
+    ///
+    /// Each texel is the average of the 2x2 texels under it in the level
+    /// above. Works on 2D and cube textures with uncompressed color
+    /// formats and a single sample; `create_texture` calls it for textures
+    /// with initial data and several mip levels.
+    fn generate_mipmaps(&mut self, texture: TextureHandle) -> Result<(), GfxError>;
+    /// Copy mip 0 of every layer back to the CPU
+    ///
+    /// Waits for the work submitted so far to finish. The bytes follow the
+    /// `update_texture` layout, layer after layer; rendered images come
+    /// back top row first on every backend. The texture needs
+    /// `TextureUsage::TRANSFER_SRC` and a single sample.
+    fn read_texture(&mut self, texture: TextureHandle) -> Result<Vec<u8>, GfxError>;
+
     // Command recording and submission
+    /// Start a frame: wait for a frame slot (`wait_for_frame`), then
+    /// acquire the next swapchain image
     fn begin_frame(&mut self) -> CommandList;
     fn submit(&mut self, cmd: CommandList);
     fn present(&mut self);
 
     // Swapchain operations
     fn get_swapchain_texture(&self) -> TextureHandle;
+    /// Multisampled color target with the size and format of the swapchain
+    /// images and `RendererConfig::msaa_samples` samples, recreated on
+    /// resize; draw to it and resolve it into the swapchain texture with
+    /// `StoreOp::Resolve`. `TextureHandle::INVALID` without MSAA.
+    fn get_msaa_texture(&self) -> TextureHandle;
+    /// Recreate the swapchain at `width` x `height`
+    ///
+    /// A zero size (a minimized window) keeps the swapchain but stops
+    /// acquiring and presenting images until a nonzero size comes back.
     fn resize(&mut self, width: u32, height: u32);
+    /// Recreate the swapchain with another present mode
+    fn set_present_mode(&mut self, mode: PresentMode);
 
     // Synchronization
     fn wait_idle(&mut self);
+    /// Block until fewer than `RendererConfig::frames_in_flight` presented
+    /// frames are unfinished on the GPU
+    ///
+    /// `begin_frame` calls it; call it earlier, before reading input, to
+    /// keep the input of a frame as fresh as the frame slot allows.
+    fn wait_for_frame(&mut self);
+    /// Fence for the work submitted so far, to tell when the GPU is done
+    /// with the memory it reads
+    fn signal_fence(&mut self) -> FenceValue;
+    /// Whether the GPU has finished the work before `fence`; never waits
+    fn fence_reached(&mut self, fence: FenceValue) -> bool;
+
+    // Device events
+    /// Events since the last call, oldest first
+    ///
+    /// Call it once per frame, after `begin_frame`. A lost device is
+    /// replaced there, reported as `Lost` then `Restored`; fences from
+    /// before the loss count as reached.
+    fn poll_events(&mut self) -> Vec<DeviceEvent>;
+
+    // Profiling
+    /// Timestamps of the frames whose GPU work finished since the last call,
+    /// oldest first
+    ///
+    /// A frame is ready a few `present` calls after its own, and is waited
+    /// for if it falls further behind. Empty on backends without timestamp
+    /// queries; timestamps past the first 256 of a frame are dropped.
+    fn read_timestamps(&mut self) -> Vec<FrameTimestamps>;
+
+    // Pipeline cache
+    /// Contents of the backend's pipeline cache, to store between runs
+    ///
+    /// Empty on backends without one; `PipelineCache::save` writes it to disk.
+    fn pipeline_cache_data(&mut self) -> Vec<u8>;
+    /// Seed the backend's pipeline cache with `pipeline_cache_data` of an
+    /// earlier run, before creating pipelines
+    ///
+    /// Returns false, ignoring `data`, if it is from another backend or
+    /// adapter or is damaged.
+    fn load_pipeline_cache_data(&mut self, data: &[u8]) -> bool;
 }
@@ -0,0 +1,341 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Device memory allocator
+//!
+//! Resources are sub-allocated from large blocks, one list of blocks per
+//! `MemoryHeap`; a resource of at least half a block gets a dedicated
+//! allocation instead. Each heap has a budget, and reserving a block or a
+//! dedicated allocation past it fails with `GfxError::OutOfMemory`.
+//!
+//! OpenGL and wgpu place resources in memory themselves, and the software
+//! backend keeps them in host memory, so no backend binds resources to these
+//! offsets yet: the allocator accounts for the memory an explicit API
+//! (Vulkan, D3D12) would reserve, and enforces the budget on it.
+
+use std::alloc::Layout;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+
+use avila_math::memory::{AllocatorInfo, KernelAlloc};
+
+use crate::gfx::api::{GfxError, HeapStats, MemoryHeap};
+
+/// Size of the blocks resources are sub-allocated from
+pub(super) const BLOCK_SIZE: u64 = 64 << 20;
+
+/// Where a resource lives; returned to `DeviceAllocator::free`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Allocation {
+    heap: MemoryHeap,
+    /// Index of the block, `None` for a dedicated allocation
+    block: Option<usize>,
+    /// Start of the range taken from the block, before alignment
+    start: u64,
+    /// Offset of the resource inside its block
+    offset: u64,
+    size: u64,
+}
+
+/// Block lists and budget of every `MemoryHeap`
+pub(super) struct DeviceAllocator {
+    heaps: [Heap; 2],
+}
+
+impl DeviceAllocator {
+    /// Allocator with the device-local heap limited to `vram` bytes, if known
+    pub(super) fn new(vram: Option<u64>) -> Self {
+        Self {
+            heaps: [
+                Heap::new(MemoryHeap::DeviceLocal, vram.unwrap_or(u64::MAX)),
+                Heap::new(MemoryHeap::HostVisible, u64::MAX),
+            ],
+        }
+    }
+
+    /// Place `size` bytes aligned to `alignment` (a power of two) in `heap`
+    pub(super) fn allocate(
+        &mut self,
+        heap: MemoryHeap,
+        size: u64,
+        alignment: u64,
+    ) -> Result<Allocation, GfxError> {
+        let allocation = self.place(heap, size, alignment);
+        self.publish(heap);
+        allocation
+    }
+
+    fn place(
+        &mut self,
+        heap: MemoryHeap,
+        size: u64,
+        alignment: u64,
+    ) -> Result<Allocation, GfxError> {
+        let size = size.max(1);
+        let state = &mut self.heaps[heap as usize];
+        if size >= BLOCK_SIZE / 2 {
+            state.reserve(size)?;
+            state.dedicated += size;
+            state.dedicated_count += 1;
+            state.allocation_count += 1;
+            return Ok(Allocation {
+                heap,
+                block: None,
+                start: 0,
+                offset: 0,
+                size,
+            });
+        }
+
+        let found = state
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, block)| {
+                let block = block.as_mut()?;
+                let (start, offset) = block.take(size, alignment)?;
+                Some((index, start, offset))
+            });
+        let (index, start, offset) = match found {
+            Some(found) => found,
+            None => {
+                // A smaller block when the budget has no room for a full one
+                let block_size = BLOCK_SIZE.min(state.budget.saturating_sub(state.reserved));
+                if block_size < size {
+                    return Err(GfxError::OutOfMemory);
+                }
+                state.reserve(block_size)?;
+                let mut block = Block::new(block_size);
+                let (start, offset) = block
+                    .take(size, alignment)
+                    .expect("new block fits the allocation");
+                let index = match state.blocks.iter().position(Option::is_none) {
+                    Some(index) => index,
+                    None => {
+                        state.blocks.push(None);
+                        state.blocks.len() - 1
+                    }
+                };
+                state.blocks[index] = Some(block);
+                (index, start, offset)
+            }
+        };
+        state.allocation_count += 1;
+        Ok(Allocation {
+            heap,
+            block: Some(index),
+            start,
+            offset,
+            size,
+        })
+    }
+
+    /// Return the memory of a resource; empty blocks go back to the device
+    pub(super) fn free(&mut self, allocation: Allocation) {
+        self.give_back(allocation);
+        self.publish(allocation.heap);
+    }
+
+    fn give_back(&mut self, allocation: Allocation) {
+        let state = &mut self.heaps[allocation.heap as usize];
+        state.deallocation_count += 1;
+        let Some(index) = allocation.block else {
+            state.reserved -= allocation.size;
+            state.dedicated -= allocation.size;
+            state.dedicated_count -= 1;
+            return;
+        };
+
+        let end = allocation.offset + allocation.size;
+        let block = state.blocks[index]
+            .as_mut()
+            .expect("allocation in a live block");
+        block.give_back(allocation.start, end);
+        if block.used == 0 {
+            state.reserved -= block.size;
+            state.blocks[index] = None;
+        }
+    }
+
+    /// Limit `heap` to `budget` bytes; what is already reserved stays
+    pub(super) fn set_budget(&mut self, heap: MemoryHeap, budget: u64) {
+        self.heaps[heap as usize].budget = budget;
+        self.publish(heap);
+    }
+
+    /// Forget every allocation, as after a device loss; budgets and the
+    /// reports handed out by `report` stay
+    pub(super) fn reset(&mut self) {
+        for heap in MemoryHeap::ALL {
+            let state = &mut self.heaps[heap as usize];
+            let report = state.report.clone();
+            *state = Heap::new(heap, state.budget);
+            state.report = report;
+            self.publish(heap);
+        }
+    }
+
+    /// Live statistics of `heap`, for `MemoryManager::track`
+    pub(super) fn report(&self, heap: MemoryHeap) -> &Arc<HeapReport> {
+        &self.heaps[heap as usize].report
+    }
+
+    /// Copy the statistics of `heap` to its report
+    fn publish(&self, heap: MemoryHeap) {
+        let stats = self.stats(heap);
+        *self.heaps[heap as usize]
+            .report
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = stats;
+    }
+
+    pub(super) fn stats(&self, heap: MemoryHeap) -> HeapStats {
+        let state = &self.heaps[heap as usize];
+        let block_used: u64 = state.blocks.iter().flatten().map(|block| block.used).sum();
+        HeapStats {
+            heap,
+            budget: state.budget,
+            reserved: state.reserved,
+            used: state.dedicated + block_used,
+            block_count: state.blocks.iter().flatten().count(),
+            dedicated_count: state.dedicated_count,
+            allocation_count: state.allocation_count,
+            deallocation_count: state.deallocation_count,
+        }
+    }
+}
+
+struct Heap {
+    budget: u64,
+    /// Blocks and dedicated allocations
+    reserved: u64,
+    /// Bytes of dedicated allocations; blocks count their own use
+    dedicated: u64,
+    /// Freed blocks leave `None` so indices in allocations stay valid
+    blocks: Vec<Option<Block>>,
+    dedicated_count: usize,
+    allocation_count: usize,
+    deallocation_count: usize,
+    report: Arc<HeapReport>,
+}
+
+impl Heap {
+    fn new(heap: MemoryHeap, budget: u64) -> Self {
+        Self {
+            budget,
+            reserved: 0,
+            dedicated: 0,
+            blocks: Vec::new(),
+            dedicated_count: 0,
+            allocation_count: 0,
+            deallocation_count: 0,
+            report: Arc::new(HeapReport {
+                stats: Mutex::new(HeapStats {
+                    heap,
+                    budget,
+                    reserved: 0,
+                    used: 0,
+                    block_count: 0,
+                    dedicated_count: 0,
+                    allocation_count: 0,
+                    deallocation_count: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Count `size` more bytes against the budget
+    fn reserve(&mut self, size: u64) -> Result<(), GfxError> {
+        if size > self.budget.saturating_sub(self.reserved) {
+            return Err(GfxError::OutOfMemory);
+        }
+        self.reserved += size;
+        Ok(())
+    }
+}
+
+/// Statistics of a heap as of its last change, shared with `MemoryManager`
+///
+/// Device memory is not addressable by the CPU, so `allocate` always fails;
+/// the report only exists so the manager can read the heap at `report()`.
+pub(super) struct HeapReport {
+    stats: Mutex<HeapStats>,
+}
+
+impl KernelAlloc for HeapReport {
+    fn allocate(&self, _layout: Layout) -> Option<NonNull<u8>> {
+        None
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+
+    fn supports_deallocate(&self) -> bool {
+        false
+    }
+
+    fn allocator_info(&self) -> AllocatorInfo {
+        self.stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .allocator_info()
+    }
+}
+
+/// Memory block with a first-fit free list
+struct Block {
+    size: u64,
+    used: u64,
+    /// Free ranges as `(start, end)`, sorted and never adjacent
+    free: Vec<(u64, u64)>,
+}
+
+impl Block {
+    fn new(size: u64) -> Self {
+        Self {
+            size,
+            used: 0,
+            free: vec![(0, size)],
+        }
+    }
+
+    /// Take the first free range that fits; returns its start and the
+    /// aligned offset of the allocation
+    fn take(&mut self, size: u64, alignment: u64) -> Option<(u64, u64)> {
+        let (index, start, offset) =
+            self.free
+                .iter()
+                .enumerate()
+                .find_map(|(index, &(start, end))| {
+                    let offset = start.next_multiple_of(alignment);
+                    (offset + size <= end).then_some((index, start, offset))
+                })?;
+        let end = offset + size;
+        if end == self.free[index].1 {
+            self.free.remove(index);
+        } else {
+            self.free[index].0 = end;
+        }
+        self.used += end - start;
+        Some((start, offset))
+    }
+
+    /// Free `start..end`, merging it with the ranges around it
+    fn give_back(&mut self, start: u64, end: u64) {
+        self.used -= end - start;
+        let index = self
+            .free
+            .partition_point(|&(free_start, _)| free_start < start);
+        let merges_next = index < self.free.len() && self.free[index].0 == end;
+        let merges_previous = index > 0 && self.free[index - 1].1 == start;
+        match (merges_previous, merges_next) {
+            (true, true) => {
+                self.free[index - 1].1 = self.free[index].1;
+                self.free.remove(index);
+            }
+            (true, false) => self.free[index - 1].1 = end,
+            (false, true) => self.free[index].0 = start,
+            (false, false) => self.free.insert(index, (start, end)),
+        }
+    }
+}
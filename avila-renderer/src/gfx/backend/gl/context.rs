@@ -0,0 +1,629 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! GL context creation
+//!
+//! EGL on Unix (X11 through `EGL_EXT_platform_xcb`, Wayland through
+//! `wl_egl_window`, surfaceless Mesa when offscreen) and WGL on Windows.
+//! Both libraries are loaded at runtime so the crate links without them.
+
+use avila_math::window::NativeHandle;
+use std::ffi::c_void;
+
+#[cfg(unix)]
+pub(super) use egl::GlContext;
+#[cfg(windows)]
+pub(super) use wgl::GlContext;
+
+/// Desktop GL version requested from both EGL and WGL
+const GL_VERSION: (i32, i32) = (4, 5);
+
+#[cfg(unix)]
+mod egl {
+    use super::*;
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    /// Fallback when desktop GL is unavailable
+    const GLES_VERSION: (i32, i32) = (3, 2);
+
+    type EglDisplay = *mut c_void;
+    type EglConfig = *mut c_void;
+    type EglContext = *mut c_void;
+    type EglSurface = *mut c_void;
+
+    const EGL_NONE: i32 = 0x3038;
+    const EGL_EXTENSIONS: i32 = 0x3055;
+    const EGL_SURFACE_TYPE: i32 = 0x3033;
+    const EGL_WINDOW_BIT: i32 = 0x0004;
+    const EGL_RENDERABLE_TYPE: i32 = 0x3040;
+    const EGL_OPENGL_BIT: i32 = 0x0008;
+    const EGL_OPENGL_ES3_BIT: i32 = 0x0040;
+    const EGL_RED_SIZE: i32 = 0x3024;
+    const EGL_GREEN_SIZE: i32 = 0x3023;
+    const EGL_BLUE_SIZE: i32 = 0x3022;
+    const EGL_ALPHA_SIZE: i32 = 0x3021;
+    const EGL_OPENGL_API: u32 = 0x30A2;
+    const EGL_OPENGL_ES_API: u32 = 0x30A0;
+    const EGL_CONTEXT_MAJOR_VERSION: i32 = 0x3098;
+    const EGL_CONTEXT_MINOR_VERSION: i32 = 0x30FB;
+    const EGL_CONTEXT_OPENGL_PROFILE_MASK: i32 = 0x30FD;
+    const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: i32 = 0x0001;
+    const EGL_PLATFORM_WAYLAND_KHR: u32 = 0x31D8;
+    const EGL_PLATFORM_XCB_EXT: u32 = 0x31DC;
+    const EGL_PLATFORM_XCB_SCREEN_EXT: isize = 0x31DE;
+    const EGL_PLATFORM_SURFACELESS_MESA: u32 = 0x31DD;
+
+    /// Library opened with `dlopen`, closed on drop
+    struct Library(*mut c_void);
+
+    impl Library {
+        fn open(names: &[&str]) -> Result<Self, String> {
+            for name in names {
+                let name = CString::new(*name).unwrap();
+                let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW) };
+                if !handle.is_null() {
+                    return Ok(Self(handle));
+                }
+            }
+            Err(format!("could not load {}", names[0]))
+        }
+
+        /// Look up `name` as a function pointer of type `T`
+        ///
+        /// # Safety
+        /// `T` must be the `extern "C"` signature of the symbol.
+        unsafe fn symbol<T: Copy>(&self, name: &str) -> Result<T, String> {
+            let cname = CString::new(name).unwrap();
+            let symbol = libc::dlsym(self.0, cname.as_ptr());
+            if symbol.is_null() {
+                return Err(format!("missing symbol {}", name));
+            }
+            Ok(std::mem::transmute_copy(&symbol))
+        }
+    }
+
+    impl Drop for Library {
+        fn drop(&mut self) {
+            unsafe { libc::dlclose(self.0) };
+        }
+    }
+
+    /// EGL 1.5 entry points
+    struct Egl {
+        get_proc_address: unsafe extern "C" fn(*const c_char) -> *const c_void,
+        get_error: unsafe extern "C" fn() -> i32,
+        query_string: unsafe extern "C" fn(EglDisplay, i32) -> *const c_char,
+        get_platform_display: unsafe extern "C" fn(u32, *mut c_void, *const isize) -> EglDisplay,
+        initialize: unsafe extern "C" fn(EglDisplay, *mut i32, *mut i32) -> u32,
+        terminate: unsafe extern "C" fn(EglDisplay) -> u32,
+        bind_api: unsafe extern "C" fn(u32) -> u32,
+        choose_config:
+            unsafe extern "C" fn(EglDisplay, *const i32, *mut EglConfig, i32, *mut i32) -> u32,
+        create_context:
+            unsafe extern "C" fn(EglDisplay, EglConfig, EglContext, *const i32) -> EglContext,
+        destroy_context: unsafe extern "C" fn(EglDisplay, EglContext) -> u32,
+        create_platform_window_surface:
+            unsafe extern "C" fn(EglDisplay, EglConfig, *mut c_void, *const isize) -> EglSurface,
+        destroy_surface: unsafe extern "C" fn(EglDisplay, EglSurface) -> u32,
+        make_current: unsafe extern "C" fn(EglDisplay, EglSurface, EglSurface, EglContext) -> u32,
+        swap_buffers: unsafe extern "C" fn(EglDisplay, EglSurface) -> u32,
+        swap_interval: unsafe extern "C" fn(EglDisplay, i32) -> u32,
+        // Keeps the function pointers above valid
+        _library: Library,
+    }
+
+    impl Egl {
+        fn load() -> Result<Self, String> {
+            let library = Library::open(&["libEGL.so.1", "libEGL.so"])?;
+            unsafe {
+                Ok(Self {
+                    get_proc_address: library.symbol("eglGetProcAddress")?,
+                    get_error: library.symbol("eglGetError")?,
+                    query_string: library.symbol("eglQueryString")?,
+                    get_platform_display: library.symbol("eglGetPlatformDisplay")?,
+                    initialize: library.symbol("eglInitialize")?,
+                    terminate: library.symbol("eglTerminate")?,
+                    bind_api: library.symbol("eglBindAPI")?,
+                    choose_config: library.symbol("eglChooseConfig")?,
+                    create_context: library.symbol("eglCreateContext")?,
+                    destroy_context: library.symbol("eglDestroyContext")?,
+                    create_platform_window_surface: library
+                        .symbol("eglCreatePlatformWindowSurface")?,
+                    destroy_surface: library.symbol("eglDestroySurface")?,
+                    make_current: library.symbol("eglMakeCurrent")?,
+                    swap_buffers: library.symbol("eglSwapBuffers")?,
+                    swap_interval: library.symbol("eglSwapInterval")?,
+                    _library: library,
+                })
+            }
+        }
+
+        fn error(&self, call: &str) -> String {
+            format!("{} failed (EGL error {:#x})", call, unsafe {
+                (self.get_error)()
+            })
+        }
+    }
+
+    /// `wl_egl_window` wrapping a `wl_surface`
+    struct WaylandWindow {
+        window: *mut c_void,
+        resize: unsafe extern "C" fn(*mut c_void, i32, i32, i32, i32),
+        destroy: unsafe extern "C" fn(*mut c_void),
+        _library: Library,
+    }
+
+    impl WaylandWindow {
+        fn create(surface: *mut c_void, width: u32, height: u32) -> Result<Self, String> {
+            let library = Library::open(&["libwayland-egl.so.1", "libwayland-egl.so"])?;
+            unsafe {
+                let create: unsafe extern "C" fn(*mut c_void, i32, i32) -> *mut c_void =
+                    library.symbol("wl_egl_window_create")?;
+                let window = create(surface, width as i32, height as i32);
+                if window.is_null() {
+                    return Err("wl_egl_window_create failed".to_string());
+                }
+                Ok(Self {
+                    window,
+                    resize: library.symbol("wl_egl_window_resize")?,
+                    destroy: library.symbol("wl_egl_window_destroy")?,
+                    _library: library,
+                })
+            }
+        }
+    }
+
+    impl Drop for WaylandWindow {
+        fn drop(&mut self) {
+            unsafe { (self.destroy)(self.window) };
+        }
+    }
+
+    /// Current EGL context, with a window surface unless offscreen
+    pub(in crate::gfx::backend) struct GlContext {
+        egl: Egl,
+        display: EglDisplay,
+        context: EglContext,
+        surface: EglSurface,
+        // Dropped after the surface (fields drop after `Drop::drop`)
+        wayland_window: Option<WaylandWindow>,
+        // `EGL_EXT_platform_xcb` takes a pointer to the window id
+        xcb_window: Option<Box<u32>>,
+        embedded: bool,
+    }
+
+    impl GlContext {
+        pub(in crate::gfx::backend) fn create(
+            window: Option<&NativeHandle>,
+            size: (u32, u32),
+            vsync: bool,
+        ) -> Result<Self, String> {
+            let egl = Egl::load()?;
+            let client_extensions = unsafe {
+                let extensions = (egl.query_string)(ptr::null_mut(), EGL_EXTENSIONS);
+                if extensions.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(extensions).to_string_lossy().into_owned()
+                }
+            };
+            let require = |extension: &str| {
+                if client_extensions.split(' ').any(|e| e == extension) {
+                    Ok(())
+                } else {
+                    Err(format!("{} not supported", extension))
+                }
+            };
+
+            let (platform, native_display, display_attribs) = match window {
+                Some(NativeHandle::Xcb {
+                    connection, screen, ..
+                }) => {
+                    require("EGL_EXT_platform_xcb")?;
+                    let attribs = vec![
+                        EGL_PLATFORM_XCB_SCREEN_EXT,
+                        *screen as isize,
+                        EGL_NONE as isize,
+                    ];
+                    (EGL_PLATFORM_XCB_EXT, *connection, attribs)
+                }
+                Some(NativeHandle::Wayland { display, .. }) => {
+                    require("EGL_KHR_platform_wayland")?;
+                    (EGL_PLATFORM_WAYLAND_KHR, *display, vec![EGL_NONE as isize])
+                }
+                Some(NativeHandle::Win32 { .. }) => {
+                    return Err("Win32 window handle on a Unix build".to_string())
+                }
+                None => {
+                    require("EGL_MESA_platform_surfaceless")?;
+                    (
+                        EGL_PLATFORM_SURFACELESS_MESA,
+                        ptr::null_mut(),
+                        vec![EGL_NONE as isize],
+                    )
+                }
+            };
+
+            let display = unsafe {
+                (egl.get_platform_display)(platform, native_display, display_attribs.as_ptr())
+            };
+            if display.is_null() {
+                return Err(egl.error("eglGetPlatformDisplay"));
+            }
+            let (mut major, mut minor) = (0, 0);
+            if unsafe { (egl.initialize)(display, &mut major, &mut minor) } == 0 {
+                return Err(egl.error("eglInitialize"));
+            }
+
+            let surface_type = if window.is_some() { EGL_WINDOW_BIT } else { 0 };
+            let attempts = [
+                (
+                    EGL_OPENGL_API,
+                    EGL_OPENGL_BIT,
+                    vec![
+                        EGL_CONTEXT_MAJOR_VERSION,
+                        GL_VERSION.0,
+                        EGL_CONTEXT_MINOR_VERSION,
+                        GL_VERSION.1,
+                        EGL_CONTEXT_OPENGL_PROFILE_MASK,
+                        EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                        EGL_NONE,
+                    ],
+                ),
+                (
+                    EGL_OPENGL_ES_API,
+                    EGL_OPENGL_ES3_BIT,
+                    vec![
+                        EGL_CONTEXT_MAJOR_VERSION,
+                        GLES_VERSION.0,
+                        EGL_CONTEXT_MINOR_VERSION,
+                        GLES_VERSION.1,
+                        EGL_NONE,
+                    ],
+                ),
+            ];
+
+            let mut chosen = None;
+            for (api, renderable, context_attribs) in &attempts {
+                if unsafe { (egl.bind_api)(*api) } == 0 {
+                    continue;
+                }
+                let config_attribs = [
+                    EGL_SURFACE_TYPE,
+                    surface_type,
+                    EGL_RENDERABLE_TYPE,
+                    *renderable,
+                    EGL_RED_SIZE,
+                    8,
+                    EGL_GREEN_SIZE,
+                    8,
+                    EGL_BLUE_SIZE,
+                    8,
+                    EGL_ALPHA_SIZE,
+                    8,
+                    EGL_NONE,
+                ];
+                let mut config = ptr::null_mut();
+                let mut count = 0;
+                let found = unsafe {
+                    (egl.choose_config)(
+                        display,
+                        config_attribs.as_ptr(),
+                        &mut config,
+                        1,
+                        &mut count,
+                    )
+                };
+                if found == 0 || count == 0 {
+                    continue;
+                }
+                let context = unsafe {
+                    (egl.create_context)(display, config, ptr::null_mut(), context_attribs.as_ptr())
+                };
+                if !context.is_null() {
+                    chosen = Some((config, context, *api == EGL_OPENGL_ES_API));
+                    break;
+                }
+            }
+            let Some((config, context, embedded)) = chosen else {
+                let err = egl.error("eglCreateContext");
+                unsafe { (egl.terminate)(display) };
+                return Err(format!(
+                    "no GL {}.{} or GLES {}.{} context: {}",
+                    GL_VERSION.0, GL_VERSION.1, GLES_VERSION.0, GLES_VERSION.1, err
+                ));
+            };
+
+            let mut gl_context = Self {
+                egl,
+                display,
+                context,
+                surface: ptr::null_mut(),
+                wayland_window: None,
+                xcb_window: None,
+                embedded,
+            };
+
+            let native_window = match window {
+                Some(NativeHandle::Xcb { window, .. }) => {
+                    let window = gl_context.xcb_window.insert(Box::new(*window));
+                    Some(&mut **window as *mut u32 as *mut c_void)
+                }
+                Some(NativeHandle::Wayland { surface, .. }) => {
+                    let window = WaylandWindow::create(*surface, size.0, size.1)?;
+                    Some(gl_context.wayland_window.insert(window).window)
+                }
+                _ => None,
+            };
+            if let Some(native_window) = native_window {
+                gl_context.surface = unsafe {
+                    (gl_context.egl.create_platform_window_surface)(
+                        display,
+                        config,
+                        native_window,
+                        ptr::null(),
+                    )
+                };
+                if gl_context.surface.is_null() {
+                    return Err(gl_context.egl.error("eglCreatePlatformWindowSurface"));
+                }
+            }
+
+            let surface = gl_context.surface;
+            if unsafe { (gl_context.egl.make_current)(display, surface, surface, context) } == 0 {
+                return Err(gl_context.egl.error("eglMakeCurrent"));
+            }
+            if !surface.is_null() {
+                gl_context.set_vsync(vsync);
+            }
+            Ok(gl_context)
+        }
+
+        pub(in crate::gfx::backend) fn get_proc_address(&self, name: &str) -> *const c_void {
+            let name = CString::new(name).unwrap();
+            unsafe { (self.egl.get_proc_address)(name.as_ptr()) }
+        }
+
+        /// Whether the context is GLES rather than desktop GL
+        pub(in crate::gfx::backend) fn is_embedded(&self) -> bool {
+            self.embedded
+        }
+
+        pub(in crate::gfx::backend) fn has_surface(&self) -> bool {
+            !self.surface.is_null()
+        }
+
+        /// Match the window surface to a new framebuffer size
+        ///
+        /// X11 surfaces follow the window on their own; Wayland needs the
+        /// `wl_egl_window` resized.
+        pub(in crate::gfx::backend) fn resize(&self, width: u32, height: u32) {
+            if let Some(window) = &self.wayland_window {
+                unsafe { (window.resize)(window.window, width as i32, height as i32, 0, 0) };
+            }
+        }
+
+        pub(in crate::gfx::backend) fn set_vsync(&self, vsync: bool) {
+            unsafe { (self.egl.swap_interval)(self.display, vsync as i32) };
+        }
+
+        pub(in crate::gfx::backend) fn swap_buffers(&self) {
+            if !self.surface.is_null() {
+                unsafe { (self.egl.swap_buffers)(self.display, self.surface) };
+            }
+        }
+    }
+
+    impl Drop for GlContext {
+        fn drop(&mut self) {
+            unsafe {
+                let none = ptr::null_mut();
+                (self.egl.make_current)(self.display, none, none, none);
+                if !self.surface.is_null() {
+                    (self.egl.destroy_surface)(self.display, self.surface);
+                }
+                (self.egl.destroy_context)(self.display, self.context);
+                (self.egl.terminate)(self.display);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod wgl {
+    use super::*;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{HMODULE, HWND};
+    use windows_sys::Win32::Graphics::Gdi::{GetDC, ReleaseDC, HDC};
+    use windows_sys::Win32::Graphics::OpenGL::{
+        wglCreateContext, wglDeleteContext, wglGetProcAddress, wglMakeCurrent, ChoosePixelFormat,
+        SetPixelFormat, SwapBuffers, HGLRC, PIXELFORMATDESCRIPTOR,
+    };
+    use windows_sys::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryA};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{CreateWindowExW, DestroyWindow};
+
+    const PFD_DOUBLEBUFFER: u32 = 0x0000_0001;
+    const PFD_DRAW_TO_WINDOW: u32 = 0x0000_0004;
+    const PFD_SUPPORT_OPENGL: u32 = 0x0000_0020;
+    const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
+    const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
+    const WGL_CONTEXT_PROFILE_MASK_ARB: i32 = 0x9126;
+    const WGL_CONTEXT_CORE_PROFILE_BIT_ARB: i32 = 0x0001;
+
+    type CreateContextAttribs = unsafe extern "system" fn(HDC, HGLRC, *const i32) -> HGLRC;
+    type SwapInterval = unsafe extern "system" fn(i32) -> i32;
+
+    /// Current WGL context on a window DC
+    ///
+    /// Offscreen devices render through a hidden window so the same pixel
+    /// format path works without a surface.
+    pub(in crate::gfx::backend) struct GlContext {
+        hwnd: HWND,
+        hdc: HDC,
+        context: HGLRC,
+        opengl32: HMODULE,
+        swap_interval: Option<SwapInterval>,
+        /// The hidden window is ours to destroy
+        owns_window: bool,
+    }
+
+    impl GlContext {
+        pub(in crate::gfx::backend) fn create(
+            window: Option<&NativeHandle>,
+            _size: (u32, u32),
+            vsync: bool,
+        ) -> Result<Self, String> {
+            let (hwnd, owns_window) = match window {
+                Some(NativeHandle::Win32 { hwnd, .. }) => (*hwnd as HWND, false),
+                Some(_) => return Err("non-Win32 window handle on Windows".to_string()),
+                None => {
+                    let class: Vec<u16> = "STATIC\0".encode_utf16().collect();
+                    let hwnd = unsafe {
+                        CreateWindowExW(
+                            0,
+                            class.as_ptr(),
+                            ptr::null(),
+                            0,
+                            0,
+                            0,
+                            1,
+                            1,
+                            ptr::null_mut(),
+                            ptr::null_mut(),
+                            ptr::null_mut(),
+                            ptr::null(),
+                        )
+                    };
+                    if hwnd.is_null() {
+                        return Err("could not create the offscreen GL window".to_string());
+                    }
+                    (hwnd, true)
+                }
+            };
+
+            let mut gl_context = Self {
+                hwnd,
+                hdc: unsafe { GetDC(hwnd) },
+                context: ptr::null_mut(),
+                opengl32: unsafe { LoadLibraryA(c"opengl32.dll".as_ptr() as _) },
+                swap_interval: None,
+                owns_window,
+            };
+
+            unsafe {
+                let mut pfd: PIXELFORMATDESCRIPTOR = std::mem::zeroed();
+                pfd.nSize = std::mem::size_of::<PIXELFORMATDESCRIPTOR>() as u16;
+                pfd.nVersion = 1;
+                pfd.dwFlags = (PFD_DRAW_TO_WINDOW | PFD_SUPPORT_OPENGL | PFD_DOUBLEBUFFER) as _;
+                pfd.cColorBits = 32;
+                pfd.cAlphaBits = 8;
+                let format = ChoosePixelFormat(gl_context.hdc, &pfd);
+                if format == 0 || SetPixelFormat(gl_context.hdc, format, &pfd) == 0 {
+                    return Err("no suitable pixel format".to_string());
+                }
+
+                // wglCreateContextAttribsARB is only reachable through a
+                // current legacy context
+                let legacy = wglCreateContext(gl_context.hdc);
+                if legacy.is_null() || wglMakeCurrent(gl_context.hdc, legacy) == 0 {
+                    return Err("wglCreateContext failed".to_string());
+                }
+                let create_attribs: Option<CreateContextAttribs> =
+                    gl_context.wgl_function("wglCreateContextAttribsARB");
+                gl_context.swap_interval = gl_context.wgl_function("wglSwapIntervalEXT");
+
+                let context = create_attribs.map_or(ptr::null_mut(), |create| {
+                    let attribs = [
+                        WGL_CONTEXT_MAJOR_VERSION_ARB,
+                        GL_VERSION.0,
+                        WGL_CONTEXT_MINOR_VERSION_ARB,
+                        GL_VERSION.1,
+                        WGL_CONTEXT_PROFILE_MASK_ARB,
+                        WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+                        0,
+                    ];
+                    create(gl_context.hdc, ptr::null_mut(), attribs.as_ptr())
+                });
+                wglMakeCurrent(gl_context.hdc, ptr::null_mut());
+                wglDeleteContext(legacy);
+                if context.is_null() {
+                    return Err(format!(
+                        "no GL {}.{} core context",
+                        GL_VERSION.0, GL_VERSION.1
+                    ));
+                }
+                gl_context.context = context;
+                if wglMakeCurrent(gl_context.hdc, context) == 0 {
+                    return Err("wglMakeCurrent failed".to_string());
+                }
+            }
+            if !owns_window {
+                gl_context.set_vsync(vsync);
+            }
+            Ok(gl_context)
+        }
+
+        /// Look up a WGL extension function
+        ///
+        /// # Safety
+        /// `T` must be the `extern "system"` signature of the function.
+        unsafe fn wgl_function<T: Copy>(&self, name: &str) -> Option<T> {
+            let address = self.get_proc_address(name);
+            (!address.is_null()).then(|| std::mem::transmute_copy(&address))
+        }
+
+        pub(in crate::gfx::backend) fn get_proc_address(&self, name: &str) -> *const c_void {
+            let name = format!("{}\0", name);
+            unsafe {
+                // wglGetProcAddress only knows extension and post-1.1
+                // functions; it may also return small sentinel values
+                let address =
+                    wglGetProcAddress(name.as_ptr()).map_or(ptr::null(), |f| f as *const c_void);
+                if !matches!(address as isize, -1..=3) {
+                    return address;
+                }
+                GetProcAddress(self.opengl32, name.as_ptr())
+                    .map_or(ptr::null(), |f| f as *const c_void)
+            }
+        }
+
+        pub(in crate::gfx::backend) fn is_embedded(&self) -> bool {
+            false
+        }
+
+        pub(in crate::gfx::backend) fn has_surface(&self) -> bool {
+            !self.owns_window
+        }
+
+        /// The default framebuffer follows the window size on its own
+        pub(in crate::gfx::backend) fn resize(&self, _width: u32, _height: u32) {}
+
+        pub(in crate::gfx::backend) fn set_vsync(&self, vsync: bool) {
+            if let Some(swap_interval) = self.swap_interval {
+                unsafe { swap_interval(vsync as i32) };
+            }
+        }
+
+        pub(in crate::gfx::backend) fn swap_buffers(&self) {
+            if !self.owns_window {
+                unsafe { SwapBuffers(self.hdc) };
+            }
+        }
+    }
+
+    impl Drop for GlContext {
+        fn drop(&mut self) {
+            unsafe {
+                wglMakeCurrent(self.hdc, ptr::null_mut());
+                if !self.context.is_null() {
+                    wglDeleteContext(self.context);
+                }
+                ReleaseDC(self.hwnd, self.hdc);
+                if self.owns_window {
+                    DestroyWindow(self.hwnd);
+                }
+            }
+        }
+    }
+}
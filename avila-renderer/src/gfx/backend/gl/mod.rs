@@ -0,0 +1,2714 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! OpenGL backend
+//!
+//! Targets OpenGL 4.5 core and falls back to GLES 3.2. On 4.5 buffers,
+//! textures, vertex arrays and framebuffers go through direct state access;
+//! GLES uses the bind-to-edit equivalents.
+//!
+//! - Native handles are GL object names (0 means creation failed)
+//! - Shaders are GLSL source; programs are linked per pipeline
+//! - Each pipeline owns a VAO with its vertex layout on binding 0; vertex
+//!   and index buffers are attached at draw time
+//! - Bind sets do not exist: a texture binding is the texture unit, and the
+//!   sampler object goes to the same unit. Bind groups are lists of indexed
+//!   buffer and texture bindings applied at draw time; a sampler at
+//!   `binding + 1` of a texture goes to the texture's unit, and storage
+//!   textures go to image unit `binding`
+//! - Compute pipelines are bare programs; each dispatch binds the recorded
+//!   resources and ends with a full memory barrier
+//! - Render passes draw into cached FBOs; viewports and scissors keep the
+//!   top-left origin of the other backends
+//! - Swapchain images are textures, blitted to the window on present
+//! - Readback flips rows so rendered images come back top row first;
+//!   uploads are not flipped, so sampling matches the other backends
+//! - Timestamps are `glQueryCounter` queries; GLES has none
+//! - The pipeline cache holds program binaries, keyed by the sources of
+//!   their shaders and tied to the driver version
+
+mod context;
+
+use super::*;
+use avila_math::os::hash::{xxhash64, XxHash64};
+use context::GlContext;
+use glow::HasContext;
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+
+/// First word of a SPIR-V module
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+// S3TC / BPTC, not part of the core headers
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const COMPRESSED_RGBA_BPTC_UNORM: u32 = 0x8E8C;
+
+/// `glTexSubImage1D`, which glow does not wrap
+type TexSubImage1d = unsafe extern "system" fn(
+    target: u32,
+    level: i32,
+    x_offset: i32,
+    width: i32,
+    format: u32,
+    ty: u32,
+    pixels: *const std::ffi::c_void,
+);
+
+/// `glGetGraphicsResetStatus`, which glow does not wrap
+type GetGraphicsResetStatus = unsafe extern "system" fn() -> u32;
+
+/// Native device on a GL context
+pub(super) struct GlDevice {
+    gl: glow::Context,
+    /// OpenGL 4.5+: use direct state access
+    dsa: bool,
+    textures: HashMap<u32, GlTexture>,
+    buffers: HashMap<u32, GlBuffer>,
+    pipelines: HashMap<u32, GlPipeline>,
+    compute_pipelines: HashMap<u32, glow::Program>,
+    samplers: HashMap<u32, glow::Sampler>,
+    bind_groups: HashMap<u64, Vec<GlBinding>>,
+    next_bind_group: u64,
+    swapchains: HashMap<u64, Vec<u32>>,
+    next_swapchain: u64,
+    /// Hash of the stage and source of each shader, which keys the binaries
+    /// of the programs linked from it
+    shader_keys: HashMap<u32, u64>,
+    /// Program binaries by the keys of their shaders; `None` where the
+    /// driver offers no binary format
+    program_binaries: Option<HashMap<u64, glow::ProgramBinary>>,
+    /// Timestamp query objects of each query pool
+    query_pools: HashMap<u64, Vec<glow::Query>>,
+    next_query_pool: u64,
+    /// Sync objects of the fences not yet reached, oldest first
+    fences: VecDeque<(u64, glow::Fence)>,
+    /// Largest fence value reached
+    completed_fence: u64,
+    /// Framebuffers by attachments (color textures, depth texture)
+    framebuffers: HashMap<(Vec<u32>, Option<u32>), glow::Framebuffer>,
+    /// Scratch framebuffer for readbacks, resolves and blits on present
+    blit_framebuffer: Option<glow::Framebuffer>,
+    state: CommandState,
+    adapter: AdapterInfo,
+    /// 0 without anisotropic filtering support
+    max_anisotropy: f32,
+    /// Desktop GL only; GLES has no 1D textures
+    tex_sub_image_1d: Option<TexSubImage1d>,
+    /// Reports resets of contexts created with reset notification; `None`
+    /// where the driver lacks robustness
+    get_graphics_reset_status: Option<GetGraphicsResetStatus>,
+    // Declared last: GL objects above are released while it is current
+    context: GlContext,
+}
+
+struct GlTexture {
+    raw: glow::Texture,
+    target: u32,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+struct GlBuffer {
+    raw: glow::Buffer,
+    size: usize,
+}
+
+/// Texels of an upload: in memory, or in the bound `PIXEL_UNPACK_BUFFER`
+#[derive(Clone, Copy)]
+enum TexelSource<'a> {
+    Slice(&'a [u8]),
+    Buffer { offset: u32, len: u32 },
+}
+
+/// Linked program plus fixed-function state
+struct GlPipeline {
+    program: glow::Program,
+    vao: glow::VertexArray,
+    /// Stride of each vertex buffer slot
+    strides: Vec<u32>,
+    mode: u32,
+    rasterizer: RasterizerState,
+    depth_stencil: DepthStencilState,
+    blend_states: Vec<BlendState>,
+}
+
+/// One slot of a bind group, by GL name
+enum GlBinding {
+    /// `glBindBufferRange` of `target` at `index`
+    Buffer {
+        target: u32,
+        index: u32,
+        buffer: u32,
+        offset: i32,
+        size: i32,
+    },
+    /// Texture and sampler (0 for none) on a texture unit
+    Texture {
+        unit: u32,
+        texture: u32,
+        sampler: u32,
+    },
+    /// Mip 0 of a texture on an image unit
+    Image {
+        unit: u32,
+        texture: u32,
+        access: u32,
+    },
+}
+
+/// Bindings recorded between draws
+#[derive(Default)]
+struct CommandState {
+    /// Height of the current render target, for flipping rectangles
+    target_height: u32,
+    pipeline: Option<u32>,
+    vertex_buffers: HashMap<u32, (u32, u64)>,
+    index_buffer: Option<(u32, u64, IndexType)>,
+    /// Texture and sampler names by texture unit
+    textures: HashMap<u32, (u32, u32)>,
+    /// Buffer name, offset and size by target and binding index
+    buffers: HashMap<(u32, u32), (u32, i32, i32)>,
+    /// Texture name and access by image unit
+    images: HashMap<u32, (u32, u32)>,
+    compute_pipeline: Option<u32>,
+    /// Framebuffer of the current render pass
+    framebuffer: Option<glow::Framebuffer>,
+    /// Color attachment index and resolve target name of each resolve
+    resolves: Vec<(u32, u32)>,
+    /// Attachments whose contents the current render pass drops
+    discards: Vec<u32>,
+}
+
+impl CommandState {
+    /// Forget resource bindings, which last until a render pass begins or ends
+    fn clear_bindings(&mut self) {
+        self.textures.clear();
+        self.buffers.clear();
+        self.images.clear();
+    }
+
+    /// Record the bindings of a bind group, replacing what is on their slots
+    fn bind(&mut self, bindings: &[GlBinding]) {
+        for binding in bindings {
+            match *binding {
+                GlBinding::Buffer {
+                    target,
+                    index,
+                    buffer,
+                    offset,
+                    size,
+                } => {
+                    self.buffers.insert((target, index), (buffer, offset, size));
+                }
+                GlBinding::Texture {
+                    unit,
+                    texture,
+                    sampler,
+                } => {
+                    self.textures.insert(unit, (texture, sampler));
+                }
+                GlBinding::Image {
+                    unit,
+                    texture,
+                    access,
+                } => {
+                    self.images.insert(unit, (texture, access));
+                }
+            }
+        }
+    }
+}
+
+impl GlDevice {
+    /// Create a context on `window`, or an offscreen one
+    ///
+    /// GL has no say in which GPU runs the context, so
+    /// `config.adapter_preference` is left to the driver (`DRI_PRIME`, the
+    /// Optimus profile and the like).
+    pub(super) fn create(
+        config: &RendererConfig,
+        window: Option<&NativeHandle>,
+    ) -> Result<Self, String> {
+        // GL has no mailbox: only FIFO waits for vertical blank
+        let vsync = config.present_mode == PresentMode::Fifo;
+        let context = GlContext::create(window, (config.width, config.height), vsync)?;
+        let gl =
+            unsafe { glow::Context::from_loader_function(|name| context.get_proc_address(name)) };
+        let version = gl.version();
+        let dsa = !context.is_embedded() && (version.major, version.minor) >= (4, 5);
+        println!(
+            "Creating OpenGL device: {} ({})",
+            version.vendor_info,
+            if dsa { "DSA" } else { "bind-to-edit" }
+        );
+
+        let adapter = query_adapter(&gl);
+        let tex_sub_image_1d = context.get_proc_address("glTexSubImage1D");
+        let tex_sub_image_1d = (!tex_sub_image_1d.is_null() && !context.is_embedded()).then(|| {
+            // SAFETY: the pointer comes from the GL loader under its GL name
+            unsafe {
+                std::mem::transmute::<*const std::ffi::c_void, TexSubImage1d>(tex_sub_image_1d)
+            }
+        });
+        let get_graphics_reset_status = ["glGetGraphicsResetStatus", "glGetGraphicsResetStatusKHR"]
+            .into_iter()
+            .map(|name| context.get_proc_address(name))
+            .find(|address| !address.is_null())
+            .map(|address| {
+                // SAFETY: as above
+                unsafe {
+                    std::mem::transmute::<*const std::ffi::c_void, GetGraphicsResetStatus>(address)
+                }
+            });
+        let extensions = gl.supported_extensions();
+        let max_anisotropy = if (version.major, version.minor) >= (4, 6)
+            || extensions.contains("GL_ARB_texture_filter_anisotropic")
+            || extensions.contains("GL_EXT_texture_filter_anisotropic")
+        {
+            unsafe { gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY) }
+        } else {
+            0.0
+        };
+        let binary_formats = unsafe { gl.get_parameter_i32(glow::NUM_PROGRAM_BINARY_FORMATS) };
+        let program_binaries = (binary_formats > 0).then(HashMap::new);
+        // Uploads and readbacks are tightly packed
+        unsafe {
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        }
+
+        Ok(Self {
+            gl,
+            dsa,
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+            pipelines: HashMap::new(),
+            compute_pipelines: HashMap::new(),
+            samplers: HashMap::new(),
+            bind_groups: HashMap::new(),
+            next_bind_group: 1,
+            swapchains: HashMap::new(),
+            next_swapchain: 1,
+            shader_keys: HashMap::new(),
+            program_binaries,
+            query_pools: HashMap::new(),
+            next_query_pool: 1,
+            fences: VecDeque::new(),
+            completed_fence: 0,
+            framebuffers: HashMap::new(),
+            blit_framebuffer: None,
+            state: CommandState::default(),
+            adapter,
+            max_anisotropy,
+            tex_sub_image_1d,
+            get_graphics_reset_status,
+            context,
+        })
+    }
+
+    /// `glTexSubImage*` (or the compressed variant) of one mip and layer;
+    /// `format` is the pixel transfer format of uncompressed texels
+    unsafe fn tex_sub_image(
+        &self,
+        texture: &GlTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+        format: u32,
+        texels: TexelSource,
+    ) -> Result<(), GfxError> {
+        let gl = &self.gl;
+        let (_, ty) = pixel_transfer_format(texture.format);
+        let compressed = texture.format.is_compressed();
+        let internal = internal_format(texture.format);
+        let (x, y, z) = (region.x as i32, region.y as i32, region.z as i32);
+        let (width, height, depth) = (
+            region.width as i32,
+            region.height as i32,
+            region.depth as i32,
+        );
+        let level = mip as i32;
+        let (pixels, compressed_pixels) = match texels {
+            TexelSource::Slice(data) => (
+                glow::PixelUnpackData::Slice(Some(data)),
+                glow::CompressedPixelUnpackData::Slice(data),
+            ),
+            TexelSource::Buffer { offset, len } => (
+                glow::PixelUnpackData::BufferOffset(offset),
+                glow::CompressedPixelUnpackData::BufferRange(offset..offset + len),
+            ),
+        };
+
+        gl.bind_texture(texture.target, Some(texture.raw));
+        match texture.target {
+            glow::TEXTURE_1D => {
+                let Some(tex_sub_image_1d) = self.tex_sub_image_1d else {
+                    gl.bind_texture(texture.target, None);
+                    return Err(GfxError::InvalidDesc(
+                        "glTexSubImage1D is not available".to_string(),
+                    ));
+                };
+                // With an unpack buffer bound the pointer is an offset in it
+                let pointer = match texels {
+                    TexelSource::Slice(data) => data.as_ptr().cast(),
+                    TexelSource::Buffer { offset, .. } => offset as usize as *const _,
+                };
+                tex_sub_image_1d(texture.target, level, x, width, format, ty, pointer);
+            }
+            glow::TEXTURE_2D | glow::TEXTURE_CUBE_MAP => {
+                let target = if texture.target == glow::TEXTURE_CUBE_MAP {
+                    glow::TEXTURE_CUBE_MAP_POSITIVE_X + layer
+                } else {
+                    glow::TEXTURE_2D
+                };
+                if compressed {
+                    gl.compressed_tex_sub_image_2d(
+                        target,
+                        level,
+                        x,
+                        y,
+                        width,
+                        height,
+                        internal,
+                        compressed_pixels,
+                    );
+                } else {
+                    gl.tex_sub_image_2d(target, level, x, y, width, height, format, ty, pixels);
+                }
+            }
+            _ => {
+                // Array layers and 3D slices are both the Z axis
+                let z = if texture.target == glow::TEXTURE_3D {
+                    z
+                } else {
+                    layer as i32
+                };
+                if compressed {
+                    gl.compressed_tex_sub_image_3d(
+                        texture.target,
+                        level,
+                        x,
+                        y,
+                        z,
+                        width,
+                        height,
+                        depth,
+                        internal,
+                        compressed_pixels,
+                    );
+                } else {
+                    gl.tex_sub_image_3d(
+                        texture.target,
+                        level,
+                        x,
+                        y,
+                        z,
+                        width,
+                        height,
+                        depth,
+                        format,
+                        ty,
+                        pixels,
+                    );
+                }
+            }
+        }
+        gl.bind_texture(texture.target, None);
+        Ok(())
+    }
+
+    /// Link `shaders` into a program, from the binary of an earlier link of
+    /// the same sources when there is one
+    fn link_program(&mut self, shaders: &[glow::Shader]) -> Result<glow::Program, GfxError> {
+        let mut key = XxHash64::new();
+        for shader in shaders {
+            let shader_key = self.shader_keys.get(&shader.0.get()).copied();
+            key.update(&shader_key.unwrap_or_default().to_le_bytes());
+        }
+        let key = key.finish();
+
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            if let Some(binary) = self.program_binaries.as_ref().and_then(|b| b.get(&key)) {
+                let program = gl.create_program().map_err(|_| name_error(gl))?;
+                gl.program_binary(program, binary);
+                if gl.get_program_link_status(program) {
+                    return Ok(program);
+                }
+                // The driver changed since the binary was made; link again
+                gl.delete_program(program);
+                take_error(gl);
+            }
+
+            let program = gl.create_program().map_err(|_| name_error(gl))?;
+            for &shader in shaders {
+                gl.attach_shader(program, shader);
+            }
+            if self.program_binaries.is_some() {
+                gl.program_binary_retrievable_hint(program, true);
+            }
+            gl.link_program(program);
+            for &shader in shaders {
+                gl.detach_shader(program, shader);
+            }
+            if !gl.get_program_link_status(program) {
+                let log = gl.get_program_info_log(program);
+                gl.delete_program(program);
+                return Err(GfxError::ShaderCompileError(log));
+            }
+            if let Some(binaries) = &mut self.program_binaries {
+                match gl.get_program_binary(program) {
+                    Some(binary) if !binary.buffer.is_empty() => {
+                        binaries.insert(key, binary);
+                    }
+                    _ => {
+                        take_error(gl);
+                    }
+                }
+            }
+            Ok(program)
+        }
+    }
+
+    fn create_gl_texture(&mut self, desc: &TextureDesc) -> Result<u32, GfxError> {
+        let gl = &self.gl;
+        take_error(gl);
+        let levels = desc.mip_levels.max(1) as i32;
+        let format = internal_format(desc.format);
+        let (width, height) = (desc.width as i32, desc.height as i32);
+        let layers = desc.array_layers.max(1) as i32;
+
+        let target = match desc.dimension {
+            // GLES has no 1D textures
+            TextureDimension::D1 if self.context.is_embedded() => glow::TEXTURE_2D,
+            TextureDimension::D1 => glow::TEXTURE_1D,
+            TextureDimension::D2 if desc.samples > 1 => glow::TEXTURE_2D_MULTISAMPLE,
+            TextureDimension::D2 if layers > 1 => glow::TEXTURE_2D_ARRAY,
+            TextureDimension::D2 => glow::TEXTURE_2D,
+            TextureDimension::D3 => glow::TEXTURE_3D,
+            TextureDimension::Cube => glow::TEXTURE_CUBE_MAP,
+        };
+
+        unsafe {
+            let raw = match target {
+                glow::TEXTURE_2D | glow::TEXTURE_CUBE_MAP if self.dsa => {
+                    let raw = gl
+                        .create_named_texture(target)
+                        .map_err(|_| name_error(gl))?;
+                    gl.texture_storage_2d(raw, levels, format, width, height);
+                    raw
+                }
+                glow::TEXTURE_2D_ARRAY | glow::TEXTURE_3D if self.dsa => {
+                    let depth = if target == glow::TEXTURE_3D {
+                        desc.depth.max(1) as i32
+                    } else {
+                        layers
+                    };
+                    let raw = gl
+                        .create_named_texture(target)
+                        .map_err(|_| name_error(gl))?;
+                    gl.texture_storage_3d(raw, levels, format, width, height, depth);
+                    raw
+                }
+                _ => {
+                    let raw = gl.create_texture().map_err(|_| name_error(gl))?;
+                    gl.bind_texture(target, Some(raw));
+                    match target {
+                        glow::TEXTURE_1D => gl.tex_storage_1d(target, levels, format, width),
+                        glow::TEXTURE_2D_MULTISAMPLE => gl.tex_storage_2d_multisample(
+                            target,
+                            desc.samples as i32,
+                            format,
+                            width,
+                            height,
+                            true,
+                        ),
+                        glow::TEXTURE_2D_ARRAY => {
+                            gl.tex_storage_3d(target, levels, format, width, height, layers)
+                        }
+                        glow::TEXTURE_3D => gl.tex_storage_3d(
+                            target,
+                            levels,
+                            format,
+                            width,
+                            height,
+                            desc.depth.max(1) as i32,
+                        ),
+                        _ => gl.tex_storage_2d(target, levels, format, width, height.max(1)),
+                    }
+                    gl.bind_texture(target, None);
+                    raw
+                }
+            };
+            if let Some(err) = take_error(gl) {
+                gl.delete_texture(raw);
+                return Err(err);
+            }
+
+            self.textures.insert(
+                raw.0.get(),
+                GlTexture {
+                    raw,
+                    target,
+                    format: desc.format,
+                    width: desc.width,
+                    height: desc.height,
+                },
+            );
+            Ok(raw.0.get())
+        }
+    }
+
+    fn delete_gl_texture(&mut self, name: u32) {
+        let Some(texture) = self.textures.remove(&name) else {
+            return;
+        };
+        // Framebuffers that referenced the texture are incomplete now
+        let gl = &self.gl;
+        self.framebuffers.retain(|(colors, depth), framebuffer| {
+            let uses = colors.contains(&name) || *depth == Some(name);
+            if uses {
+                unsafe { gl.delete_framebuffer(*framebuffer) };
+            }
+            !uses
+        });
+        unsafe { gl.delete_texture(texture.raw) };
+    }
+
+    /// Framebuffer with the given attachments, created on first use
+    /// Framebuffer attachment point of depth texture `name`
+    fn depth_attachment_point(&self, name: u32) -> u32 {
+        match self.textures.get(&name).map(|t| t.format) {
+            Some(TextureFormat::Depth24Stencil8) => glow::DEPTH_STENCIL_ATTACHMENT,
+            _ => glow::DEPTH_ATTACHMENT,
+        }
+    }
+
+    /// The scratch framebuffer, created on first use
+    fn scratch_framebuffer(&mut self) -> Option<glow::Framebuffer> {
+        if self.blit_framebuffer.is_none() {
+            self.blit_framebuffer = unsafe { self.gl.create_framebuffer() }.ok();
+        }
+        self.blit_framebuffer
+    }
+
+    /// Blit color attachments of `framebuffer` into their resolve targets;
+    /// GL averages the samples of a multisampled read framebuffer
+    fn resolve(&mut self, framebuffer: glow::Framebuffer, resolves: &[(u32, u32)]) {
+        let Some(scratch) = self.scratch_framebuffer() else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(framebuffer));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(scratch));
+            gl.disable(glow::SCISSOR_TEST);
+            for &(index, target) in resolves {
+                let Some(texture) = self.textures.get(&target) else {
+                    continue;
+                };
+                let (width, height) = (texture.width as i32, texture.height as i32);
+                gl.read_buffer(glow::COLOR_ATTACHMENT0 + index);
+                gl.framebuffer_texture_2d(
+                    glow::DRAW_FRAMEBUFFER,
+                    glow::COLOR_ATTACHMENT0,
+                    texture.target,
+                    Some(texture.raw),
+                    0,
+                );
+                gl.blit_framebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    glow::COLOR_BUFFER_BIT,
+                    glow::NEAREST,
+                );
+            }
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+    }
+
+    fn framebuffer(&mut self, colors: Vec<u32>, depth: Option<u32>) -> Option<glow::Framebuffer> {
+        let key = (colors, depth);
+        if let Some(framebuffer) = self.framebuffers.get(&key) {
+            return Some(*framebuffer);
+        }
+
+        let gl = &self.gl;
+        let (colors, depth) = &key;
+        let framebuffer = unsafe {
+            let framebuffer = if self.dsa {
+                gl.create_named_framebuffer()
+            } else {
+                gl.create_framebuffer()
+            }
+            .ok()?;
+            if !self.dsa {
+                gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            }
+
+            let mut attachments = colors
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (glow::COLOR_ATTACHMENT0 + i as u32, *name))
+                .collect::<Vec<_>>();
+            if let Some(name) = depth {
+                attachments.push((self.depth_attachment_point(*name), *name));
+            }
+            for (attachment, name) in attachments {
+                let raw = self.textures.get(&name).map(|t| t.raw);
+                if self.dsa {
+                    gl.named_framebuffer_texture(Some(framebuffer), attachment, raw, 0);
+                } else {
+                    gl.framebuffer_texture(glow::FRAMEBUFFER, attachment, raw, 0);
+                }
+            }
+
+            let draw_buffers = (0..colors.len() as u32)
+                .map(|i| glow::COLOR_ATTACHMENT0 + i)
+                .collect::<Vec<_>>();
+            let status = if self.dsa {
+                gl.named_framebuffer_draw_buffers(Some(framebuffer), &draw_buffers);
+                gl.check_named_framebuffer_status(Some(framebuffer), glow::FRAMEBUFFER)
+            } else {
+                gl.draw_buffers(&draw_buffers);
+                gl.check_framebuffer_status(glow::FRAMEBUFFER)
+            };
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                eprintln!("Incomplete framebuffer (status {:#x})", status);
+                gl.delete_framebuffer(framebuffer);
+                return None;
+            }
+            framebuffer
+        };
+        self.framebuffers.insert(key, framebuffer);
+        Some(framebuffer)
+    }
+
+    /// Flip a top-left origin rectangle to GL's bottom-left origin
+    fn flip_y(&self, y: i32, height: i32) -> i32 {
+        self.state.target_height as i32 - (y + height)
+    }
+
+    /// Attach the recorded vertex and index buffers to the pipeline's VAO
+    /// and bind the recorded resources
+    fn prepare_draw(&self) -> Option<&GlPipeline> {
+        let pipeline = self.pipelines.get(&self.state.pipeline?)?;
+        let gl = &self.gl;
+        unsafe {
+            for (slot, (name, offset)) in &self.state.vertex_buffers {
+                let raw = self.buffers.get(name).map(|b| b.raw);
+                let stride = pipeline.strides.get(*slot as usize).copied().unwrap_or(0);
+                if self.dsa {
+                    gl.vertex_array_vertex_buffer(
+                        pipeline.vao,
+                        *slot,
+                        raw,
+                        *offset as i32,
+                        stride as i32,
+                    );
+                } else {
+                    gl.bind_vertex_buffer(*slot, raw, *offset as i32, stride as i32);
+                }
+            }
+            if let Some((name, _, _)) = self.state.index_buffer {
+                let raw = self.buffers.get(&name).map(|b| b.raw);
+                if self.dsa {
+                    gl.vertex_array_element_buffer(pipeline.vao, raw);
+                } else {
+                    gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, raw);
+                }
+            }
+        }
+        self.bind_resources();
+        Some(pipeline)
+    }
+
+    /// Bind the recorded textures, images and uniform and storage buffers
+    fn bind_resources(&self) {
+        let gl = &self.gl;
+        unsafe {
+            for (unit, (texture, sampler)) in &self.state.textures {
+                let Some(texture) = self.textures.get(texture) else {
+                    continue;
+                };
+                if self.dsa {
+                    gl.bind_texture_unit(*unit, Some(texture.raw));
+                } else {
+                    gl.active_texture(glow::TEXTURE0 + unit);
+                    gl.bind_texture(texture.target, Some(texture.raw));
+                }
+                gl.bind_sampler(*unit, self.samplers.get(sampler).copied());
+            }
+            for ((target, index), (name, offset, size)) in &self.state.buffers {
+                let raw = self.buffers.get(name).map(|b| b.raw);
+                gl.bind_buffer_range(*target, *index, raw, *offset, *size);
+            }
+            for (unit, (texture, access)) in &self.state.images {
+                let Some(texture) = self.textures.get(texture) else {
+                    continue;
+                };
+                gl.bind_image_texture(
+                    *unit,
+                    Some(texture.raw),
+                    0,
+                    texture.target == glow::TEXTURE_3D,
+                    0,
+                    *access,
+                    internal_format(texture.format),
+                );
+            }
+            // Bind-to-edit uploads use unit 0
+            if !self.dsa {
+                gl.active_texture(glow::TEXTURE0);
+            }
+        }
+    }
+}
+
+/// The GPU an offscreen context lands on
+pub(super) fn enumerate_adapters() -> Vec<AdapterInfo> {
+    match GlDevice::create(&RendererConfig::default(), None) {
+        Ok(device) => vec![device.adapter.clone()],
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Drain the GL error queue and translate the first error
+///
+/// Called before creating an object to drop errors left by earlier calls,
+/// and after it to see whether the creation failed.
+fn take_error(gl: &glow::Context) -> Option<GfxError> {
+    let mut first = None;
+    loop {
+        let error = unsafe { gl.get_error() };
+        let translated = match error {
+            glow::NO_ERROR => return first,
+            glow::OUT_OF_MEMORY => GfxError::OutOfMemory,
+            // Reported on every call from now on, so stop draining
+            glow::CONTEXT_LOST => return first.or(Some(GfxError::DeviceLost)),
+            glow::INVALID_ENUM => GfxError::InvalidDesc("GL_INVALID_ENUM".to_string()),
+            glow::INVALID_VALUE => GfxError::InvalidDesc("GL_INVALID_VALUE".to_string()),
+            glow::INVALID_OPERATION => GfxError::InvalidDesc("GL_INVALID_OPERATION".to_string()),
+            _ => GfxError::InvalidDesc(format!("GL error {:#06x}", error)),
+        };
+        first.get_or_insert(translated);
+    }
+}
+
+/// Error for a `glCreate*`/`glGen*` that returned no name
+fn name_error(gl: &glow::Context) -> GfxError {
+    take_error(gl).unwrap_or(GfxError::OutOfMemory)
+}
+
+/// Append the key and binary of one program to a pipeline cache blob
+fn put_program_binary(data: &mut Vec<u8>, key: u64, binary: &glow::ProgramBinary) {
+    data.extend_from_slice(&key.to_le_bytes());
+    data.extend_from_slice(&binary.format.to_le_bytes());
+    data.extend_from_slice(&(binary.buffer.len() as u32).to_le_bytes());
+    data.extend_from_slice(&binary.buffer);
+}
+
+/// Split the key and binary of one program off a pipeline cache blob
+fn take_program_binary(data: &mut &[u8]) -> Option<(u64, glow::ProgramBinary)> {
+    let key = u64::from_le_bytes(take_bytes(data, 8)?.try_into().ok()?);
+    let format = take_u32(data)?;
+    let len = take_u32(data)?;
+    let buffer = take_bytes(data, len as usize)?.to_vec();
+    Some((key, glow::ProgramBinary { buffer, format }))
+}
+
+fn query_adapter(gl: &glow::Context) -> AdapterInfo {
+    // GL_NVX_gpu_memory_info: GPU_MEMORY_INFO_DEDICATED_VIDMEM_NVX, in KiB
+    const DEDICATED_VIDMEM_NVX: u32 = 0x9047;
+
+    let (name, vendor) = unsafe {
+        (
+            gl.get_parameter_string(glow::RENDERER),
+            gl.get_parameter_string(glow::VENDOR),
+        )
+    };
+    let vram = gl
+        .supported_extensions()
+        .contains("GL_NVX_gpu_memory_info")
+        .then(|| unsafe { gl.get_parameter_i32(DEDICATED_VIDMEM_NVX) } as u64 * 1024);
+    // GL does not say what kind of device it is; recognize the usual
+    // software and virtual renderers by name
+    let lower = name.to_lowercase();
+    let adapter_type = if [
+        "llvmpipe",
+        "softpipe",
+        "swiftshader",
+        "software",
+        "gdi generic",
+    ]
+    .iter()
+    .any(|s| lower.contains(s))
+    {
+        AdapterType::Cpu
+    } else if ["virgl", "svga3d", "virtio"]
+        .iter()
+        .any(|s| lower.contains(s))
+    {
+        AdapterType::Virtual
+    } else {
+        AdapterType::Unknown
+    };
+    AdapterInfo {
+        backend: Backend::OpenGl,
+        name,
+        vendor,
+        vram,
+        adapter_type,
+    }
+}
+
+impl NativeDevice for GlDevice {
+    fn adapter_info_native(&self) -> AdapterInfo {
+        self.adapter.clone()
+    }
+
+    fn create_texture_native(&mut self, desc: &TextureDesc) -> Result<NativeTexture, GfxError> {
+        let name = self.create_gl_texture(desc)?;
+        Ok(NativeTexture {
+            handle: name as u64,
+        })
+    }
+
+    fn create_buffer_native(
+        &mut self,
+        desc: &BufferDesc,
+        data: Option<&[u8]>,
+    ) -> Result<NativeBuffer, GfxError> {
+        let gl = &self.gl;
+        let usage = if desc.cpu_visible {
+            glow::DYNAMIC_DRAW
+        } else {
+            glow::STATIC_DRAW
+        };
+
+        let raw = unsafe {
+            take_error(gl);
+            let raw = if self.dsa {
+                gl.create_named_buffer()
+            } else {
+                gl.create_buffer()
+            }
+            .map_err(|_| name_error(gl))?;
+            if self.dsa {
+                gl.named_buffer_data_size(raw, desc.size as i32, usage);
+                if let Some(data) = data {
+                    gl.named_buffer_sub_data_u8_slice(raw, 0, data);
+                }
+            } else {
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(raw));
+                gl.buffer_data_size(glow::COPY_WRITE_BUFFER, desc.size as i32, usage);
+                if let Some(data) = data {
+                    gl.buffer_sub_data_u8_slice(glow::COPY_WRITE_BUFFER, 0, data);
+                }
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+            }
+            if let Some(err) = take_error(gl) {
+                gl.delete_buffer(raw);
+                return Err(err);
+            }
+            raw
+        };
+
+        self.buffers.insert(
+            raw.0.get(),
+            GlBuffer {
+                raw,
+                size: desc.size,
+            },
+        );
+        Ok(NativeBuffer {
+            handle: raw.0.get() as u64,
+        })
+    }
+
+    fn create_shader_native(&mut self, desc: &ShaderDesc) -> Result<NativeShader, GfxError> {
+        if desc.code.len() >= 4
+            && u32::from_le_bytes([desc.code[0], desc.code[1], desc.code[2], desc.code[3]])
+                == SPIRV_MAGIC
+        {
+            return Err(GfxError::InvalidDesc(
+                "OpenGL backend takes GLSL source, not SPIR-V".to_string(),
+            ));
+        }
+        let Ok(source) = std::str::from_utf8(&desc.code) else {
+            return Err(GfxError::InvalidDesc(
+                "shader source is not valid UTF-8".to_string(),
+            ));
+        };
+        let stage = match desc.stage {
+            ShaderStage::Vertex => glow::VERTEX_SHADER,
+            ShaderStage::Fragment => glow::FRAGMENT_SHADER,
+            ShaderStage::Compute => glow::COMPUTE_SHADER,
+            ShaderStage::Geometry => glow::GEOMETRY_SHADER,
+            ShaderStage::TessControl => glow::TESS_CONTROL_SHADER,
+            ShaderStage::TessEvaluation => glow::TESS_EVALUATION_SHADER,
+        };
+
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            let shader = gl.create_shader(stage).map_err(|_| name_error(gl))?;
+            gl.shader_source(shader, source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                let log = gl.get_shader_info_log(shader);
+                gl.delete_shader(shader);
+                return Err(GfxError::ShaderCompileError(log));
+            }
+            self.shader_keys
+                .insert(shader.0.get(), xxhash64(source.as_bytes(), stage as u64));
+            Ok(NativeShader {
+                handle: shader.0.get() as u64,
+            })
+        }
+    }
+
+    fn create_pipeline_native(
+        &mut self,
+        desc: &PipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        let shader = |handle: ShaderHandle| {
+            shaders
+                .get(handle.0)
+                .and_then(|shader| NonZeroU32::new(shader.native.handle as u32))
+                .map(glow::NativeShader)
+                .ok_or_else(|| GfxError::InvalidDesc(format!("unknown shader {:?}", handle)))
+        };
+        let vertex = shader(desc.vertex_shader)?;
+        let fragment = shader(desc.fragment_shader)?;
+
+        let program = self.link_program(&[vertex, fragment])?;
+        let gl = &self.gl;
+        unsafe {
+            let vao = match if self.dsa {
+                gl.create_named_vertex_array()
+            } else {
+                gl.create_vertex_array()
+            } {
+                Ok(vao) => vao,
+                Err(_) => {
+                    gl.delete_program(program);
+                    return Err(name_error(gl));
+                }
+            };
+            if !self.dsa {
+                gl.bind_vertex_array(Some(vao));
+            }
+            for (slot, (_, step_mode, attributes)) in desc.vertex_layout.slots().enumerate() {
+                let slot = slot as u32;
+                for attribute in attributes {
+                    let (size, integer) = match attribute.format {
+                        VertexFormat::Float => (1, false),
+                        VertexFormat::Float2 => (2, false),
+                        VertexFormat::Float3 => (3, false),
+                        VertexFormat::Float4 => (4, false),
+                        VertexFormat::UInt => (1, true),
+                        VertexFormat::UInt2 => (2, true),
+                        VertexFormat::UInt3 => (3, true),
+                        VertexFormat::UInt4 => (4, true),
+                    };
+                    let location = attribute.location;
+                    if self.dsa {
+                        gl.enable_vertex_array_attrib(vao, location);
+                        if integer {
+                            gl.vertex_array_attrib_format_i32(
+                                vao,
+                                location,
+                                size,
+                                glow::UNSIGNED_INT,
+                                attribute.offset,
+                            );
+                        } else {
+                            gl.vertex_array_attrib_format_f32(
+                                vao,
+                                location,
+                                size,
+                                glow::FLOAT,
+                                false,
+                                attribute.offset,
+                            );
+                        }
+                        gl.vertex_array_attrib_binding_f32(vao, location, slot);
+                    } else {
+                        gl.enable_vertex_attrib_array(location);
+                        if integer {
+                            gl.vertex_attrib_format_i32(
+                                location,
+                                size,
+                                glow::UNSIGNED_INT,
+                                attribute.offset,
+                            );
+                        } else {
+                            gl.vertex_attrib_format_f32(
+                                location,
+                                size,
+                                glow::FLOAT,
+                                false,
+                                attribute.offset,
+                            );
+                        }
+                        gl.vertex_attrib_binding(location, slot);
+                    }
+                }
+                if step_mode == VertexStepMode::Instance {
+                    // glow has no glVertexArrayBindingDivisor: bind the VAO
+                    if self.dsa {
+                        gl.bind_vertex_array(Some(vao));
+                    }
+                    gl.vertex_binding_divisor(slot, 1);
+                    if self.dsa {
+                        gl.bind_vertex_array(None);
+                    }
+                }
+            }
+            if !self.dsa {
+                gl.bind_vertex_array(None);
+            }
+
+            let mode = match desc.topology {
+                PrimitiveTopology::TriangleList => glow::TRIANGLES,
+                PrimitiveTopology::TriangleStrip => glow::TRIANGLE_STRIP,
+                PrimitiveTopology::LineList => glow::LINES,
+                PrimitiveTopology::LineStrip => glow::LINE_STRIP,
+                PrimitiveTopology::PointList => glow::POINTS,
+            };
+            self.pipelines.insert(
+                program.0.get(),
+                GlPipeline {
+                    program,
+                    vao,
+                    strides: desc
+                        .vertex_layout
+                        .slots()
+                        .map(|(stride, _, _)| stride)
+                        .collect(),
+                    mode,
+                    rasterizer: desc.rasterizer,
+                    depth_stencil: desc.depth_stencil,
+                    blend_states: desc.blend_states.clone(),
+                },
+            );
+            Ok(NativePipeline {
+                handle: program.0.get() as u64,
+            })
+        }
+    }
+
+    fn create_compute_pipeline_native(
+        &mut self,
+        desc: &ComputePipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        let shader = shaders
+            .get(desc.shader.0)
+            .and_then(|shader| NonZeroU32::new(shader.native.handle as u32))
+            .map(glow::NativeShader)
+            .ok_or_else(|| GfxError::InvalidDesc(format!("unknown shader {:?}", desc.shader)))?;
+
+        let program = self.link_program(&[shader])?;
+        self.compute_pipelines.insert(program.0.get(), program);
+        Ok(NativePipeline {
+            handle: program.0.get() as u64,
+        })
+    }
+
+    fn create_sampler_native(&mut self, desc: &SamplerDesc) -> Result<NativeSampler, GfxError> {
+        let filter = |filter: FilterMode| match filter {
+            FilterMode::Nearest => glow::NEAREST,
+            FilterMode::Linear => glow::LINEAR,
+        };
+        let min_filter = match (desc.min_filter, desc.mip_filter) {
+            (FilterMode::Nearest, FilterMode::Nearest) => glow::NEAREST_MIPMAP_NEAREST,
+            (FilterMode::Nearest, FilterMode::Linear) => glow::NEAREST_MIPMAP_LINEAR,
+            (FilterMode::Linear, FilterMode::Nearest) => glow::LINEAR_MIPMAP_NEAREST,
+            (FilterMode::Linear, FilterMode::Linear) => glow::LINEAR_MIPMAP_LINEAR,
+        };
+        let wrap = |mode: AddressMode| match mode {
+            AddressMode::Repeat => glow::REPEAT,
+            AddressMode::MirrorRepeat => glow::MIRRORED_REPEAT,
+            AddressMode::ClampToEdge => glow::CLAMP_TO_EDGE,
+        };
+
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            let sampler = gl.create_sampler().map_err(|_| name_error(gl))?;
+            let parameters = [
+                (glow::TEXTURE_MIN_FILTER, min_filter),
+                (glow::TEXTURE_MAG_FILTER, filter(desc.mag_filter)),
+                (glow::TEXTURE_WRAP_S, wrap(desc.address_u)),
+                (glow::TEXTURE_WRAP_T, wrap(desc.address_v)),
+                (glow::TEXTURE_WRAP_R, wrap(desc.address_w)),
+            ];
+            for (name, value) in parameters {
+                gl.sampler_parameter_i32(sampler, name, value as i32);
+            }
+            // Without the extension the request degrades to plain filtering
+            if desc.max_anisotropy > 1 && self.max_anisotropy > 0.0 {
+                gl.sampler_parameter_f32(
+                    sampler,
+                    glow::TEXTURE_MAX_ANISOTROPY,
+                    (desc.max_anisotropy as f32).min(self.max_anisotropy),
+                );
+            }
+            if let Some(compare) = desc.compare {
+                gl.sampler_parameter_i32(
+                    sampler,
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                gl.sampler_parameter_i32(
+                    sampler,
+                    glow::TEXTURE_COMPARE_FUNC,
+                    compare_function(compare) as i32,
+                );
+            }
+            if let Some(error) = take_error(gl) {
+                gl.delete_sampler(sampler);
+                return Err(error);
+            }
+            let name = sampler.0.get();
+            self.samplers.insert(name, sampler);
+            Ok(NativeSampler {
+                handle: name as u64,
+            })
+        }
+    }
+
+    fn create_bind_group_layout_native(
+        &mut self,
+        _desc: &BindGroupLayoutDesc,
+    ) -> Result<NativeBindGroupLayout, GfxError> {
+        // GL has no layout objects; the layout is read with each group
+        Ok(NativeBindGroupLayout { handle: 0 })
+    }
+
+    fn create_bind_group_native(
+        &mut self,
+        _layout: NativeBindGroupLayout,
+        desc: &BindGroupLayoutDesc,
+        entries: &[NativeBindGroupEntry],
+    ) -> Result<NativeBindGroup, GfxError> {
+        let bindings = gl_bindings(desc, entries);
+        let handle = self.next_bind_group;
+        self.next_bind_group += 1;
+        self.bind_groups.insert(handle, bindings);
+        Ok(NativeBindGroup { handle })
+    }
+
+    fn destroy_texture_native(&mut self, texture: NativeTexture) {
+        self.delete_gl_texture(texture.handle as u32);
+    }
+
+    fn destroy_buffer_native(&mut self, buffer: NativeBuffer) {
+        if let Some(buffer) = self.buffers.remove(&(buffer.handle as u32)) {
+            unsafe { self.gl.delete_buffer(buffer.raw) };
+        }
+    }
+
+    fn destroy_shader_native(&mut self, shader: NativeShader) {
+        if let Some(name) = NonZeroU32::new(shader.handle as u32) {
+            unsafe { self.gl.delete_shader(glow::NativeShader(name)) };
+        }
+        self.shader_keys.remove(&(shader.handle as u32));
+    }
+
+    fn destroy_pipeline_native(&mut self, pipeline: NativePipeline) {
+        let name = pipeline.handle as u32;
+        if let Some(pipeline) = self.pipelines.remove(&name) {
+            unsafe {
+                self.gl.delete_vertex_array(pipeline.vao);
+                self.gl.delete_program(pipeline.program);
+            }
+            if self.state.pipeline == Some(name) {
+                self.state.pipeline = None;
+            }
+        }
+    }
+
+    fn destroy_compute_pipeline_native(&mut self, pipeline: NativePipeline) {
+        let name = pipeline.handle as u32;
+        if let Some(program) = self.compute_pipelines.remove(&name) {
+            unsafe { self.gl.delete_program(program) };
+            if self.state.compute_pipeline == Some(name) {
+                self.state.compute_pipeline = None;
+            }
+        }
+    }
+
+    fn destroy_sampler_native(&mut self, sampler: NativeSampler) {
+        if let Some(sampler) = self.samplers.remove(&(sampler.handle as u32)) {
+            unsafe { self.gl.delete_sampler(sampler) };
+        }
+    }
+
+    fn destroy_bind_group_layout_native(&mut self, _layout: NativeBindGroupLayout) {}
+
+    fn destroy_bind_group_native(&mut self, group: NativeBindGroup) {
+        self.bind_groups.remove(&group.handle);
+    }
+
+    fn update_buffer_native(&mut self, buffer: NativeBuffer, offset: usize, data: &[u8]) {
+        let Some(buffer) = self.buffers.get(&(buffer.handle as u32)) else {
+            return;
+        };
+        if offset + data.len() > buffer.size {
+            eprintln!(
+                "Buffer update of {} bytes at {} overflows {} bytes",
+                data.len(),
+                offset,
+                buffer.size
+            );
+            return;
+        }
+        let gl = &self.gl;
+        unsafe {
+            if self.dsa {
+                gl.named_buffer_sub_data_u8_slice(buffer.raw, offset as i32, data);
+            } else {
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer.raw));
+                gl.buffer_sub_data_u8_slice(glow::COPY_WRITE_BUFFER, offset as i32, data);
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+            }
+        }
+    }
+
+    fn map_buffer_native(&mut self, buffer: NativeBuffer) -> *mut u8 {
+        let Some(buffer) = self.buffers.get(&(buffer.handle as u32)) else {
+            return std::ptr::null_mut();
+        };
+        // Stays bound until unmap; nothing else uses COPY_WRITE_BUFFER
+        // across calls
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer.raw));
+            gl.map_buffer_range(
+                glow::COPY_WRITE_BUFFER,
+                0,
+                buffer.size as i32,
+                glow::MAP_READ_BIT | glow::MAP_WRITE_BIT,
+            )
+        }
+    }
+
+    fn unmap_buffer_native(&mut self, buffer: NativeBuffer) {
+        let Some(buffer) = self.buffers.get(&(buffer.handle as u32)) else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer.raw));
+            gl.unmap_buffer(glow::COPY_WRITE_BUFFER);
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+        }
+    }
+
+    fn update_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        let Some(texture) = self.textures.get(&(texture.handle as u32)) else {
+            return Err(GfxError::InvalidDesc("unknown texture".to_string()));
+        };
+        let (mut format, _) = pixel_transfer_format(texture.format);
+        // GLES has no BGRA uploads; swizzle on the CPU
+        let swizzled;
+        let data = if texture.format == TextureFormat::Bgra8 && self.context.is_embedded() {
+            swizzled = data
+                .chunks_exact(4)
+                .flat_map(|texel| [texel[2], texel[1], texel[0], texel[3]])
+                .collect::<Vec<u8>>();
+            format = glow::RGBA;
+            &swizzled[..]
+        } else {
+            data
+        };
+
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            self.tex_sub_image(
+                texture,
+                mip,
+                layer,
+                region,
+                format,
+                TexelSource::Slice(data),
+            )?;
+            take_error(gl).map_or(Ok(()), Err)
+        }
+    }
+
+    fn generate_mipmaps_native(
+        &mut self,
+        texture: NativeTexture,
+        _desc: &TextureDesc,
+    ) -> Result<(), GfxError> {
+        let Some(texture) = self.textures.get(&(texture.handle as u32)) else {
+            return Err(GfxError::InvalidDesc("unknown texture".to_string()));
+        };
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            gl.bind_texture(texture.target, Some(texture.raw));
+            gl.generate_mipmap(texture.target);
+            gl.bind_texture(texture.target, None);
+            take_error(gl).map_or(Ok(()), Err)
+        }
+    }
+
+    fn read_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<Vec<u8>, GfxError> {
+        let framebuffer = self
+            .scratch_framebuffer()
+            .ok_or_else(|| name_error(&self.gl))?;
+        let Some(texture) = self.textures.get(&(texture.handle as u32)) else {
+            return Err(GfxError::InvalidDesc("unknown texture".to_string()));
+        };
+        if texture.format.is_depth() || texture.format.is_compressed() {
+            return Err(GfxError::InvalidDesc(format!(
+                "{:?} textures cannot be read back with OpenGL",
+                texture.format
+            )));
+        }
+        let (mut format, ty) = pixel_transfer_format(texture.format);
+        // GLES has no BGRA readback; swizzle on the CPU
+        let swizzle = texture.format == TextureFormat::Bgra8 && self.context.is_embedded();
+        if swizzle {
+            format = glow::RGBA;
+        }
+        let row_bytes = (texture.width * texture.format.bytes_per_pixel()) as usize;
+        let image_bytes = row_bytes * texture.height as usize;
+        let slices = if texture.target == glow::TEXTURE_3D {
+            desc.depth.max(1)
+        } else {
+            layer_count(desc).max(1)
+        };
+        let mut pixels = vec![0; image_bytes * slices as usize];
+
+        // glReadPixels returns once the texture holds the submitted work
+        let gl = &self.gl;
+        unsafe {
+            take_error(gl);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(framebuffer));
+            for (slice, image) in pixels.chunks_exact_mut(image_bytes).enumerate() {
+                let (attachment, raw) = (glow::COLOR_ATTACHMENT0, Some(texture.raw));
+                match texture.target {
+                    glow::TEXTURE_2D_ARRAY | glow::TEXTURE_3D => gl.framebuffer_texture_layer(
+                        glow::READ_FRAMEBUFFER,
+                        attachment,
+                        raw,
+                        0,
+                        slice as i32,
+                    ),
+                    glow::TEXTURE_CUBE_MAP => gl.framebuffer_texture_2d(
+                        glow::READ_FRAMEBUFFER,
+                        attachment,
+                        glow::TEXTURE_CUBE_MAP_POSITIVE_X + slice as u32,
+                        raw,
+                        0,
+                    ),
+                    glow::TEXTURE_1D => {
+                        gl.framebuffer_texture(glow::READ_FRAMEBUFFER, attachment, raw, 0)
+                    }
+                    target => gl.framebuffer_texture_2d(
+                        glow::READ_FRAMEBUFFER,
+                        attachment,
+                        target,
+                        raw,
+                        0,
+                    ),
+                }
+                gl.read_pixels(
+                    0,
+                    0,
+                    texture.width as i32,
+                    texture.height as i32,
+                    format,
+                    ty,
+                    glow::PixelPackData::Slice(Some(image)),
+                );
+            }
+            gl.framebuffer_texture_2d(
+                glow::READ_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                None,
+                0,
+            );
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+            if let Some(err) = take_error(gl) {
+                return Err(err);
+            }
+        }
+
+        // Rendering puts the top row at the top of GL's bottom-up rows
+        let mut pixels = pixels
+            .chunks_exact(image_bytes)
+            .flat_map(|image| image.chunks_exact(row_bytes).rev().flatten())
+            .copied()
+            .collect::<Vec<u8>>();
+        if swizzle {
+            for texel in pixels.chunks_exact_mut(4) {
+                texel.swap(0, 2);
+            }
+        }
+        Ok(pixels)
+    }
+
+    fn begin_frame_native(&mut self) {
+        self.state = CommandState::default();
+    }
+
+    fn begin_render_pass_native(
+        &mut self,
+        desc: &RenderPassDesc,
+        textures: &ResourcePool<TextureResource>,
+    ) {
+        let name = |handle: TextureHandle| {
+            textures
+                .get(handle.0)
+                .map_or(0, |texture| texture.native.handle as u32)
+        };
+        let colors = desc
+            .color_attachments
+            .iter()
+            .map(|attachment| name(attachment.texture))
+            .collect::<Vec<_>>();
+        let depth = desc
+            .depth_attachment
+            .as_ref()
+            .map(|attachment| name(attachment.texture));
+
+        let size = colors
+            .iter()
+            .chain(depth.iter())
+            .find_map(|name| self.textures.get(name))
+            .map(|texture| (texture.width, texture.height));
+        let Some((width, height)) = size else {
+            eprintln!("Render pass has no valid attachments");
+            return;
+        };
+        let Some(framebuffer) = self.framebuffer(colors, depth) else {
+            return;
+        };
+        self.state.target_height = height;
+        self.state.clear_bindings();
+
+        // Attachments that start undefined, and what happens at the end
+        let mut undefined = Vec::new();
+        let mut resolves = Vec::new();
+        let mut discards = Vec::new();
+        for (i, attachment) in desc.color_attachments.iter().enumerate() {
+            let point = glow::COLOR_ATTACHMENT0 + i as u32;
+            if matches!(attachment.load, LoadOp::DontCare) {
+                undefined.push(point);
+            }
+            match attachment.store {
+                StoreOp::Store => {}
+                StoreOp::DontCare => discards.push(point),
+                StoreOp::Resolve(target) => {
+                    resolves.push((i as u32, name(target)));
+                    discards.push(point);
+                }
+            }
+        }
+        if let (Some(attachment), Some(depth)) = (&desc.depth_attachment, depth) {
+            let point = self.depth_attachment_point(depth);
+            if matches!(attachment.load, LoadOp::DontCare) {
+                undefined.push(point);
+            }
+            if attachment.store != StoreOp::Store {
+                discards.push(point);
+            }
+        }
+        self.state.framebuffer = Some(framebuffer);
+        self.state.resolves = resolves;
+        self.state.discards = discards;
+
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(framebuffer));
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.depth_range_f32(0.0, 1.0);
+            // Clears honor the scissor test and write masks
+            gl.disable(glow::SCISSOR_TEST);
+            gl.color_mask(true, true, true, true);
+            gl.depth_mask(true);
+            if !undefined.is_empty() {
+                gl.invalidate_framebuffer(glow::DRAW_FRAMEBUFFER, &undefined);
+            }
+            for (i, attachment) in desc.color_attachments.iter().enumerate() {
+                if let LoadOp::Clear(c) = attachment.load {
+                    gl.clear_buffer_f32_slice(glow::COLOR, i as u32, &[c.r, c.g, c.b, c.a]);
+                }
+            }
+            let depth_clear = desc.depth_attachment.as_ref().and_then(|d| match d.load {
+                LoadOp::Clear(clear) => Some(clear),
+                _ => None,
+            });
+            if let Some(clear) = depth_clear {
+                gl.clear_buffer_depth_stencil(
+                    glow::DEPTH_STENCIL,
+                    0,
+                    clear.depth,
+                    clear.stencil as i32,
+                );
+            }
+        }
+    }
+
+    fn end_render_pass_native(&mut self) {
+        self.state.clear_bindings();
+        let resolves = std::mem::take(&mut self.state.resolves);
+        let discards = std::mem::take(&mut self.state.discards);
+        if let Some(framebuffer) = self.state.framebuffer.take() {
+            if !resolves.is_empty() {
+                self.resolve(framebuffer, &resolves);
+            }
+            if !discards.is_empty() {
+                unsafe {
+                    self.gl
+                        .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(framebuffer));
+                    self.gl
+                        .invalidate_framebuffer(glow::DRAW_FRAMEBUFFER, &discards);
+                }
+            }
+        }
+        unsafe { self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None) };
+    }
+
+    fn bind_pipeline_native(&mut self, pipeline: NativePipeline) {
+        let name = pipeline.handle as u32;
+        let Some(pipeline) = self.pipelines.get(&name) else {
+            return;
+        };
+        self.state.pipeline = Some(name);
+
+        let gl = &self.gl;
+        unsafe {
+            gl.use_program(Some(pipeline.program));
+            gl.bind_vertex_array(Some(pipeline.vao));
+
+            let rasterizer = &pipeline.rasterizer;
+            match rasterizer.cull_mode {
+                CullMode::None => gl.disable(glow::CULL_FACE),
+                CullMode::Front => {
+                    gl.enable(glow::CULL_FACE);
+                    gl.cull_face(glow::FRONT);
+                }
+                CullMode::Back => {
+                    gl.enable(glow::CULL_FACE);
+                    gl.cull_face(glow::BACK);
+                }
+            }
+            gl.front_face(match rasterizer.front_face {
+                FrontFace::Clockwise => glow::CW,
+                FrontFace::CounterClockwise => glow::CCW,
+            });
+            if !self.context.is_embedded() {
+                gl.polygon_mode(
+                    glow::FRONT_AND_BACK,
+                    match rasterizer.polygon_mode {
+                        PolygonMode::Fill => glow::FILL,
+                        PolygonMode::Line => glow::LINE,
+                        PolygonMode::Point => glow::POINT,
+                    },
+                );
+            }
+
+            let depth = &pipeline.depth_stencil;
+            if depth.depth_test_enabled {
+                gl.enable(glow::DEPTH_TEST);
+                gl.depth_func(compare_function(depth.depth_compare));
+            } else {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            gl.depth_mask(depth.depth_write_enabled);
+
+            for (i, blend) in pipeline.blend_states.iter().enumerate() {
+                let i = i as u32;
+                if !blend.enabled {
+                    gl.disable_draw_buffer(glow::BLEND, i);
+                    continue;
+                }
+                gl.enable_draw_buffer(glow::BLEND, i);
+                gl.blend_func_separate_draw_buffer(
+                    i,
+                    blend_factor(blend.src_color),
+                    blend_factor(blend.dst_color),
+                    blend_factor(blend.src_alpha),
+                    blend_factor(blend.dst_alpha),
+                );
+                gl.blend_equation_separate_draw_buffer(
+                    i,
+                    blend_op(blend.color_op),
+                    blend_op(blend.alpha_op),
+                );
+            }
+            if pipeline.blend_states.is_empty() {
+                gl.disable(glow::BLEND);
+            }
+        }
+    }
+
+    fn set_viewport_native(&mut self, viewport: &Viewport) {
+        let y = self.flip_y(viewport.y as i32, viewport.height as i32);
+        unsafe {
+            self.gl.viewport(
+                viewport.x as i32,
+                y,
+                viewport.width as i32,
+                viewport.height as i32,
+            );
+            self.gl
+                .depth_range_f32(viewport.min_depth, viewport.max_depth);
+        }
+    }
+
+    fn set_scissor_native(&mut self, scissor: &Rect) {
+        let y = self.flip_y(scissor.y, scissor.height as i32);
+        unsafe {
+            self.gl.enable(glow::SCISSOR_TEST);
+            self.gl
+                .scissor(scissor.x, y, scissor.width as i32, scissor.height as i32);
+        }
+    }
+
+    fn bind_vertex_buffer_native(&mut self, slot: u32, buffer: NativeBuffer, offset: u64) {
+        self.state
+            .vertex_buffers
+            .insert(slot, (buffer.handle as u32, offset));
+    }
+
+    fn bind_index_buffer_native(
+        &mut self,
+        buffer: NativeBuffer,
+        offset: u64,
+        index_type: IndexType,
+    ) {
+        self.state.index_buffer = Some((buffer.handle as u32, offset, index_type));
+    }
+
+    fn bind_group_native(&mut self, _index: u32, group: NativeBindGroup) {
+        if let Some(bindings) = self.bind_groups.get(&group.handle) {
+            self.state.bind(bindings);
+        }
+    }
+
+    fn bind_texture_native(
+        &mut self,
+        _set: u32,
+        binding: u32,
+        texture: NativeTexture,
+        sampler: NativeSampler,
+    ) {
+        self.state
+            .textures
+            .insert(binding, (texture.handle as u32, sampler.handle as u32));
+    }
+
+    fn draw_native(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        let embedded = self.context.is_embedded();
+        let Some(pipeline) = self.prepare_draw() else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            if embedded {
+                // GLES has no base instance
+                gl.draw_arrays_instanced(
+                    pipeline.mode,
+                    first_vertex as i32,
+                    vertex_count as i32,
+                    instance_count as i32,
+                );
+            } else {
+                gl.draw_arrays_instanced_base_instance(
+                    pipeline.mode,
+                    first_vertex as i32,
+                    vertex_count as i32,
+                    instance_count as i32,
+                    first_instance,
+                );
+            }
+        }
+    }
+
+    fn draw_indexed_native(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        let embedded = self.context.is_embedded();
+        let Some((_, offset, index_type)) = self.state.index_buffer else {
+            return;
+        };
+        let Some(pipeline) = self.prepare_draw() else {
+            return;
+        };
+        let (element_type, index_size) = match index_type {
+            IndexType::UInt16 => (glow::UNSIGNED_SHORT, 2),
+            IndexType::UInt32 => (glow::UNSIGNED_INT, 4),
+        };
+        let offset = (offset + first_index as u64 * index_size) as i32;
+        let gl = &self.gl;
+        unsafe {
+            if embedded {
+                gl.draw_elements_instanced_base_vertex(
+                    pipeline.mode,
+                    index_count as i32,
+                    element_type,
+                    offset,
+                    instance_count as i32,
+                    vertex_offset,
+                );
+            } else {
+                gl.draw_elements_instanced_base_vertex_base_instance(
+                    pipeline.mode,
+                    index_count as i32,
+                    element_type,
+                    offset,
+                    instance_count as i32,
+                    vertex_offset,
+                    first_instance,
+                );
+            }
+        }
+    }
+
+    fn bind_compute_pipeline_native(&mut self, pipeline: NativePipeline) {
+        let name = pipeline.handle as u32;
+        if self.compute_pipelines.contains_key(&name) {
+            self.state.compute_pipeline = Some(name);
+        }
+    }
+
+    fn dispatch_native(&mut self, x: u32, y: u32, z: u32) {
+        let Some(program) = self
+            .state
+            .compute_pipeline
+            .and_then(|name| self.compute_pipelines.get(&name))
+        else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            gl.use_program(Some(*program));
+            self.bind_resources();
+            gl.dispatch_compute(x, y, z);
+        }
+    }
+
+    fn create_surface_native(&mut self, _window: &NativeHandle) -> Result<NativeSurface, GfxError> {
+        // The context already owns the window surface
+        if !self.context.has_surface() {
+            return Err(GfxError::Unsupported(
+                "the GL context has no surface for the window".to_string(),
+            ));
+        }
+        Ok(NativeSurface { handle: 0 })
+    }
+
+    fn create_swapchain_native(
+        &mut self,
+        surface: Option<NativeSurface>,
+        desc: &TextureDesc,
+        image_count: u32,
+        present_mode: PresentMode,
+    ) -> NativeSwapchain {
+        if surface.is_some() {
+            self.context.resize(desc.width, desc.height);
+            self.context.set_vsync(present_mode == PresentMode::Fifo);
+        }
+        let images = (0..image_count)
+            .filter_map(|_| match self.create_gl_texture(desc) {
+                Ok(name) => Some(name),
+                Err(err) => {
+                    eprintln!("Failed to create swapchain image: {}", err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let handle = self.next_swapchain;
+        self.next_swapchain += 1;
+        let image_count = images.len() as u32;
+        self.swapchains.insert(handle, images);
+        NativeSwapchain {
+            handle,
+            image_count,
+            next_image: 0,
+        }
+    }
+
+    fn swapchain_images_native(&mut self, swapchain: &NativeSwapchain) -> Vec<NativeTexture> {
+        self.swapchains
+            .get(&swapchain.handle)
+            .into_iter()
+            .flatten()
+            .map(|name| NativeTexture {
+                handle: *name as u64,
+            })
+            .collect()
+    }
+
+    fn acquire_next_image_native(&mut self, swapchain: &mut NativeSwapchain) -> Option<u32> {
+        if swapchain.image_count == 0 {
+            return Some(0);
+        }
+        // The default framebuffer follows the window, so the images never
+        // go out of date
+        let index = swapchain.next_image;
+        swapchain.next_image = (index + 1) % swapchain.image_count;
+        Some(index)
+    }
+
+    fn destroy_swapchain_native(&mut self, swapchain: NativeSwapchain) {
+        for name in self
+            .swapchains
+            .remove(&swapchain.handle)
+            .unwrap_or_default()
+        {
+            self.delete_gl_texture(name);
+        }
+    }
+
+    fn present_native(&mut self, swapchain: &NativeSwapchain, image_index: u32) {
+        if !self.context.has_surface() {
+            unsafe { self.gl.flush() };
+            return;
+        }
+        let Some(framebuffer) = self.scratch_framebuffer() else {
+            return;
+        };
+        let Some(texture) = self
+            .swapchains
+            .get(&swapchain.handle)
+            .and_then(|images| images.get(image_index as usize))
+            .and_then(|name| self.textures.get(name))
+        else {
+            return;
+        };
+
+        let gl = &self.gl;
+        unsafe {
+            let (width, height) = (texture.width as i32, texture.height as i32);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::READ_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                texture.target,
+                Some(texture.raw),
+                0,
+            );
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+            gl.disable(glow::SCISSOR_TEST);
+            gl.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        self.context.swap_buffers();
+    }
+
+    fn wait_idle_native(&mut self) {
+        unsafe { self.gl.finish() };
+    }
+
+    fn device_lost_native(&mut self) -> bool {
+        self.get_graphics_reset_status
+            .is_some_and(|status| unsafe { status() } != glow::NO_ERROR)
+    }
+
+    fn copy_buffer_native(
+        &mut self,
+        src: NativeBuffer,
+        src_offset: u64,
+        dst: NativeBuffer,
+        dst_offset: u64,
+        size: u64,
+    ) {
+        let (Some(src), Some(dst)) = (
+            self.buffers.get(&(src.handle as u32)),
+            self.buffers.get(&(dst.handle as u32)),
+        ) else {
+            return;
+        };
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_buffer(glow::COPY_READ_BUFFER, Some(src.raw));
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(dst.raw));
+            gl.copy_buffer_sub_data(
+                glow::COPY_READ_BUFFER,
+                glow::COPY_WRITE_BUFFER,
+                src_offset as i32,
+                dst_offset as i32,
+                size as i32,
+            );
+            gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+        }
+    }
+
+    fn copy_buffer_to_texture_native(
+        &mut self,
+        src: NativeBuffer,
+        layout: TexelBufferLayout,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+    ) -> Result<(), GfxError> {
+        let (Some(buffer), Some(gl_texture)) = (
+            self.buffers.get(&(src.handle as u32)),
+            self.textures.get(&(texture.handle as u32)),
+        ) else {
+            return Err(GfxError::InvalidDesc(
+                "unknown buffer or texture".to_string(),
+            ));
+        };
+        let format = gl_texture.format;
+        let (row_bytes, rows) = region_layout(format, region.width, region.height);
+        let rows = rows * region.depth as usize;
+        let pitch = layout.bytes_per_row as usize;
+        let gl = &self.gl;
+
+        if format == TextureFormat::Bgra8 && self.context.is_embedded() {
+            // Swizzled on the CPU: read the texels back and upload them
+            let span = pitch * (rows - 1) + row_bytes;
+            let data = unsafe {
+                gl.bind_buffer(glow::COPY_READ_BUFFER, Some(buffer.raw));
+                let mapped = gl.map_buffer_range(
+                    glow::COPY_READ_BUFFER,
+                    layout.offset as i32,
+                    span as i32,
+                    glow::MAP_READ_BIT,
+                );
+                let data = (!mapped.is_null()).then(|| {
+                    let mapped = std::slice::from_raw_parts(mapped, span);
+                    (0..rows)
+                        .flat_map(|row| &mapped[row * pitch..row * pitch + row_bytes])
+                        .copied()
+                        .collect::<Vec<u8>>()
+                });
+                if data.is_some() {
+                    gl.unmap_buffer(glow::COPY_READ_BUFFER);
+                }
+                gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+                data
+            };
+            let data = data.ok_or_else(|| {
+                GfxError::InvalidDesc("staging buffer could not be mapped".to_string())
+            })?;
+            return self.update_texture_native(texture, mip, layer, region, &data);
+        }
+
+        unsafe {
+            take_error(gl);
+            gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(buffer.raw));
+            let uploaded = if format.is_compressed() {
+                // Compressed row lengths need GL 4.2 and are missing from
+                // GLES; upload one row of blocks at a time
+                (0..rows).try_for_each(|row| {
+                    let block_rows = region.height.div_ceil(4) as usize;
+                    let (slice, block_row) = (row / block_rows, (row % block_rows) as u32);
+                    let y = region.y + block_row * 4;
+                    let row_region = TextureRegion {
+                        y,
+                        z: region.z + slice as u32,
+                        height: (region.y + region.height - y).min(4),
+                        depth: 1,
+                        ..*region
+                    };
+                    let texels = TexelSource::Buffer {
+                        offset: (layout.offset as usize + row * pitch) as u32,
+                        len: row_bytes as u32,
+                    };
+                    self.tex_sub_image(gl_texture, mip, layer, &row_region, 0, texels)
+                })
+            } else {
+                let (transfer_format, _) = pixel_transfer_format(format);
+                let row_length = layout.bytes_per_row / format.bytes_per_pixel();
+                gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, row_length as i32);
+                let texels = TexelSource::Buffer {
+                    offset: layout.offset as u32,
+                    len: (pitch * (rows - 1) + row_bytes) as u32,
+                };
+                let uploaded =
+                    self.tex_sub_image(gl_texture, mip, layer, region, transfer_format, texels);
+                gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+                uploaded
+            };
+            gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, None);
+            uploaded?;
+            take_error(gl).map_or(Ok(()), Err)
+        }
+    }
+
+    fn signal_fence_native(&mut self, value: u64) {
+        let gl = &self.gl;
+        unsafe {
+            match gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) {
+                Ok(fence) => {
+                    // Without a flush the fence may never reach the GPU
+                    gl.flush();
+                    self.fences.push_back((value, fence));
+                }
+                Err(_) => {
+                    gl.finish();
+                    self.completed_fence = value;
+                }
+            }
+        }
+    }
+
+    fn completed_fence_native(&mut self) -> u64 {
+        let gl = &self.gl;
+        while let Some(&(value, fence)) = self.fences.front() {
+            if unsafe { gl.get_sync_status(fence) } != glow::SIGNALED {
+                break;
+            }
+            unsafe { gl.delete_sync(fence) };
+            self.fences.pop_front();
+            self.completed_fence = value;
+        }
+        self.completed_fence
+    }
+
+    fn wait_fence_native(&mut self, value: u64) {
+        // Fences are reached in order: wait for the last one up to `value`
+        let Some(&(_, fence)) = self
+            .fences
+            .iter()
+            .take_while(|&&(fence, _)| fence <= value)
+            .last()
+        else {
+            return;
+        };
+        // The fence was flushed when signaled; the timeout is in nanoseconds
+        while unsafe { self.gl.client_wait_sync(fence, 0, i32::MAX) } == glow::TIMEOUT_EXPIRED {}
+        self.completed_fence_native();
+    }
+
+    fn barrier_native(&mut self, barriers: &[Barrier]) {
+        // Only image and storage buffer writes are incoherent; the driver
+        // orders everything else
+        let bits = barriers
+            .iter()
+            .filter(|barrier| barrier.before == ResourceState::ShaderWrite)
+            .fold(0, |bits, barrier| {
+                let texture = matches!(barrier.resource, NativeResource::Texture(_));
+                bits | match (barrier.after, texture) {
+                    (ResourceState::ShaderRead, true) => {
+                        glow::TEXTURE_FETCH_BARRIER_BIT | glow::SHADER_IMAGE_ACCESS_BARRIER_BIT
+                    }
+                    (ResourceState::ShaderRead, false) => {
+                        glow::UNIFORM_BARRIER_BIT | glow::SHADER_STORAGE_BARRIER_BIT
+                    }
+                    (ResourceState::ShaderWrite, true) => glow::SHADER_IMAGE_ACCESS_BARRIER_BIT,
+                    (ResourceState::ShaderWrite, false) => glow::SHADER_STORAGE_BARRIER_BIT,
+                    (ResourceState::VertexInput, _) => {
+                        glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT | glow::ELEMENT_ARRAY_BARRIER_BIT
+                    }
+                    (
+                        ResourceState::ColorAttachment
+                        | ResourceState::DepthAttachment
+                        | ResourceState::Present,
+                        _,
+                    ) => glow::FRAMEBUFFER_BARRIER_BIT,
+                    (ResourceState::TransferSrc | ResourceState::TransferDst, true) => {
+                        glow::TEXTURE_UPDATE_BARRIER_BIT | glow::PIXEL_BUFFER_BARRIER_BIT
+                    }
+                    (ResourceState::TransferSrc | ResourceState::TransferDst, false) => {
+                        glow::BUFFER_UPDATE_BARRIER_BIT
+                    }
+                    (ResourceState::HostAccess, _) => {
+                        glow::BUFFER_UPDATE_BARRIER_BIT | glow::CLIENT_MAPPED_BUFFER_BARRIER_BIT
+                    }
+                    (ResourceState::Undefined, _) => 0,
+                }
+            });
+        if bits != 0 {
+            unsafe { self.gl.memory_barrier(bits) };
+        }
+    }
+
+    fn create_query_pool_native(&mut self, count: u32) -> Option<NativeQueryPool> {
+        if self.context.is_embedded() {
+            return None;
+        }
+        let mut queries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            match unsafe { self.gl.create_query() } {
+                Ok(query) => queries.push(query),
+                Err(_) => {
+                    for query in queries {
+                        unsafe { self.gl.delete_query(query) };
+                    }
+                    return None;
+                }
+            }
+        }
+        let handle = self.next_query_pool;
+        self.next_query_pool += 1;
+        self.query_pools.insert(handle, queries);
+        Some(NativeQueryPool { handle })
+    }
+
+    fn write_timestamp_native(&mut self, pool: NativeQueryPool, index: u32) {
+        if let Some(&query) = self
+            .query_pools
+            .get(&pool.handle)
+            .and_then(|queries| queries.get(index as usize))
+        {
+            unsafe { self.gl.query_counter(query, glow::TIMESTAMP) };
+        }
+    }
+
+    fn resolve_query_pool_native(&mut self, _pool: NativeQueryPool, _count: u32) {
+        // Results only become available once the queries reach the GPU
+        unsafe { self.gl.flush() };
+    }
+
+    fn read_query_pool_native(&mut self, pool: NativeQueryPool, count: u32) -> Option<Vec<u64>> {
+        let queries = self.query_pools.get(&pool.handle)?.get(..count as usize)?;
+        let gl = &self.gl;
+        // Queries complete in order, so the last one answers for all
+        let last = *queries.last()?;
+        if unsafe { gl.get_query_parameter_u32(last, glow::QUERY_RESULT_AVAILABLE) } == 0 {
+            return None;
+        }
+        let times = queries
+            .iter()
+            .map(|&query| {
+                let mut time = 0u64;
+                // With no query buffer bound the "offset" is the address to
+                // write to
+                unsafe {
+                    gl.get_query_parameter_u64_with_offset(
+                        query,
+                        glow::QUERY_RESULT,
+                        &mut time as *mut u64 as usize,
+                    )
+                };
+                time
+            })
+            .collect();
+        Some(times)
+    }
+
+    fn pipeline_cache_data_native(&mut self) -> Vec<u8> {
+        let Some(binaries) = self.program_binaries.as_ref().filter(|b| !b.is_empty()) else {
+            return Vec::new();
+        };
+        // Driver version, then key, format, length and bytes of each binary
+        let version = unsafe { self.gl.get_parameter_string(glow::VERSION) };
+        let mut data = Vec::new();
+        data.extend_from_slice(&(version.len() as u32).to_le_bytes());
+        data.extend_from_slice(version.as_bytes());
+        for (key, binary) in binaries {
+            put_program_binary(&mut data, *key, binary);
+        }
+        data
+    }
+
+    fn load_pipeline_cache_native(&mut self, mut data: &[u8]) -> bool {
+        let version = unsafe { self.gl.get_parameter_string(glow::VERSION) };
+        let Some(binaries) = &mut self.program_binaries else {
+            return false;
+        };
+        let Some(len) = take_u32(&mut data) else {
+            return false;
+        };
+        if take_bytes(&mut data, len as usize) != Some(version.as_bytes()) {
+            return false;
+        }
+        let mut loaded = Vec::new();
+        while !data.is_empty() {
+            match take_program_binary(&mut data) {
+                Some(entry) => loaded.push(entry),
+                None => return false,
+            }
+        }
+        for (key, binary) in loaded {
+            binaries.entry(key).or_insert(binary);
+        }
+        true
+    }
+}
+
+impl Drop for GlDevice {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        unsafe {
+            for (_, framebuffer) in self.framebuffers.drain() {
+                gl.delete_framebuffer(framebuffer);
+            }
+            if let Some(framebuffer) = self.blit_framebuffer.take() {
+                gl.delete_framebuffer(framebuffer);
+            }
+            for query in self.query_pools.drain().flat_map(|(_, queries)| queries) {
+                gl.delete_query(query);
+            }
+            for (_, fence) in self.fences.drain(..) {
+                gl.delete_sync(fence);
+            }
+            for (_, pipeline) in self.pipelines.drain() {
+                gl.delete_vertex_array(pipeline.vao);
+                gl.delete_program(pipeline.program);
+            }
+            for (_, program) in self.compute_pipelines.drain() {
+                gl.delete_program(program);
+            }
+            for (_, sampler) in self.samplers.drain() {
+                gl.delete_sampler(sampler);
+            }
+            for (_, buffer) in self.buffers.drain() {
+                gl.delete_buffer(buffer.raw);
+            }
+            for (_, texture) in self.textures.drain() {
+                gl.delete_texture(texture.raw);
+            }
+        }
+    }
+}
+
+/// GL binding points of a bind group's resources
+fn gl_bindings(desc: &BindGroupLayoutDesc, entries: &[NativeBindGroupEntry]) -> Vec<GlBinding> {
+    let mut bindings = Vec::new();
+    for (slot, entry) in desc.entries.iter().zip(entries) {
+        match (slot.ty, entry.resource) {
+            (
+                ty,
+                NativeBindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                },
+            ) => bindings.push(GlBinding::Buffer {
+                target: match ty {
+                    BindingType::UniformBuffer => glow::UNIFORM_BUFFER,
+                    _ => glow::SHADER_STORAGE_BUFFER,
+                },
+                index: entry.binding,
+                buffer: buffer.handle as u32,
+                offset: offset as i32,
+                size: size as i32,
+            }),
+            (
+                BindingType::StorageTexture { access, .. },
+                NativeBindingResource::Texture(texture),
+            ) => {
+                bindings.push(GlBinding::Image {
+                    unit: entry.binding,
+                    texture: texture.handle as u32,
+                    access: match access {
+                        StorageTextureAccess::ReadOnly => glow::READ_ONLY,
+                        StorageTextureAccess::WriteOnly => glow::WRITE_ONLY,
+                        StorageTextureAccess::ReadWrite => glow::READ_WRITE,
+                    },
+                });
+            }
+            (_, NativeBindingResource::Texture(texture)) => {
+                let sampler = entries.iter().find_map(|other| match other.resource {
+                    NativeBindingResource::Sampler(sampler)
+                        if other.binding == entry.binding + 1 =>
+                    {
+                        Some(sampler.handle as u32)
+                    }
+                    _ => None,
+                });
+                bindings.push(GlBinding::Texture {
+                    unit: entry.binding,
+                    texture: texture.handle as u32,
+                    sampler: sampler.unwrap_or(0),
+                });
+            }
+            // Bound along with its texture
+            (_, NativeBindingResource::Sampler(_)) => {}
+        }
+    }
+    bindings
+}
+
+fn internal_format(format: TextureFormat) -> u32 {
+    match format {
+        // GL has no BGRA storage; swizzling happens on upload
+        TextureFormat::Rgba8 | TextureFormat::Bgra8 => glow::RGBA8,
+        TextureFormat::Rgba8Srgb => glow::SRGB8_ALPHA8,
+        TextureFormat::Rgba16f => glow::RGBA16F,
+        TextureFormat::Rgba32f => glow::RGBA32F,
+        TextureFormat::Depth24 => glow::DEPTH_COMPONENT24,
+        TextureFormat::Depth32f => glow::DEPTH_COMPONENT32F,
+        TextureFormat::Depth24Stencil8 => glow::DEPTH24_STENCIL8,
+        TextureFormat::Bc1 => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+        TextureFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+        TextureFormat::Bc7 => COMPRESSED_RGBA_BPTC_UNORM,
+    }
+}
+
+/// Client-side format and type of texel data (uncompressed formats)
+fn pixel_transfer_format(format: TextureFormat) -> (u32, u32) {
+    match format {
+        TextureFormat::Rgba8 | TextureFormat::Rgba8Srgb => (glow::RGBA, glow::UNSIGNED_BYTE),
+        TextureFormat::Bgra8 => (glow::BGRA, glow::UNSIGNED_BYTE),
+        TextureFormat::Rgba16f => (glow::RGBA, glow::HALF_FLOAT),
+        TextureFormat::Rgba32f => (glow::RGBA, glow::FLOAT),
+        TextureFormat::Depth24 => (glow::DEPTH_COMPONENT, glow::UNSIGNED_INT),
+        TextureFormat::Depth32f => (glow::DEPTH_COMPONENT, glow::FLOAT),
+        TextureFormat::Depth24Stencil8 => (glow::DEPTH_STENCIL, glow::UNSIGNED_INT_24_8),
+        // Compressed uploads pass the internal format instead
+        TextureFormat::Bc1 | TextureFormat::Bc3 | TextureFormat::Bc7 => (0, 0),
+    }
+}
+
+fn compare_function(compare: CompareFunction) -> u32 {
+    match compare {
+        CompareFunction::Never => glow::NEVER,
+        CompareFunction::Less => glow::LESS,
+        CompareFunction::Equal => glow::EQUAL,
+        CompareFunction::LessEqual => glow::LEQUAL,
+        CompareFunction::Greater => glow::GREATER,
+        CompareFunction::NotEqual => glow::NOTEQUAL,
+        CompareFunction::GreaterEqual => glow::GEQUAL,
+        CompareFunction::Always => glow::ALWAYS,
+    }
+}
+
+fn blend_factor(factor: BlendFactor) -> u32 {
+    match factor {
+        BlendFactor::Zero => glow::ZERO,
+        BlendFactor::One => glow::ONE,
+        BlendFactor::SrcColor => glow::SRC_COLOR,
+        BlendFactor::OneMinusSrcColor => glow::ONE_MINUS_SRC_COLOR,
+        BlendFactor::DstColor => glow::DST_COLOR,
+        BlendFactor::OneMinusDstColor => glow::ONE_MINUS_DST_COLOR,
+        BlendFactor::SrcAlpha => glow::SRC_ALPHA,
+        BlendFactor::OneMinusSrcAlpha => glow::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::DstAlpha => glow::DST_ALPHA,
+        BlendFactor::OneMinusDstAlpha => glow::ONE_MINUS_DST_ALPHA,
+    }
+}
+
+fn blend_op(op: BlendOp) -> u32 {
+    match op {
+        BlendOp::Add => glow::FUNC_ADD,
+        BlendOp::Subtract => glow::FUNC_SUBTRACT,
+        BlendOp::ReverseSubtract => glow::FUNC_REVERSE_SUBTRACT,
+        BlendOp::Min => glow::MIN,
+        BlendOp::Max => glow::MAX,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(binding: u32, resource: NativeBindingResource) -> NativeBindGroupEntry {
+        NativeBindGroupEntry { binding, resource }
+    }
+
+    fn buffer(handle: u64) -> NativeBindingResource {
+        NativeBindingResource::Buffer {
+            buffer: NativeBuffer { handle },
+            offset: 256,
+            size: 64,
+        }
+    }
+
+    #[test]
+    fn test_format_translation() {
+        assert_eq!(internal_format(TextureFormat::Bgra8), glow::RGBA8);
+        assert_eq!(
+            internal_format(TextureFormat::Rgba8Srgb),
+            glow::SRGB8_ALPHA8
+        );
+        assert_eq!(
+            internal_format(TextureFormat::Depth24Stencil8),
+            glow::DEPTH24_STENCIL8
+        );
+        assert_eq!(
+            internal_format(TextureFormat::Bc7),
+            COMPRESSED_RGBA_BPTC_UNORM
+        );
+
+        assert_eq!(
+            pixel_transfer_format(TextureFormat::Bgra8),
+            (glow::BGRA, glow::UNSIGNED_BYTE)
+        );
+        assert_eq!(
+            pixel_transfer_format(TextureFormat::Rgba16f),
+            (glow::RGBA, glow::HALF_FLOAT)
+        );
+        assert_eq!(
+            pixel_transfer_format(TextureFormat::Depth24Stencil8),
+            (glow::DEPTH_STENCIL, glow::UNSIGNED_INT_24_8)
+        );
+        assert_eq!(pixel_transfer_format(TextureFormat::Bc1), (0, 0));
+    }
+
+    #[test]
+    fn test_state_translation() {
+        assert_eq!(compare_function(CompareFunction::LessEqual), glow::LEQUAL);
+        assert_eq!(
+            compare_function(CompareFunction::GreaterEqual),
+            glow::GEQUAL
+        );
+        assert_eq!(
+            blend_factor(BlendFactor::OneMinusSrcAlpha),
+            glow::ONE_MINUS_SRC_ALPHA
+        );
+        assert_eq!(blend_factor(BlendFactor::DstColor), glow::DST_COLOR);
+        assert_eq!(
+            blend_op(BlendOp::ReverseSubtract),
+            glow::FUNC_REVERSE_SUBTRACT
+        );
+        assert_eq!(blend_op(BlendOp::Min), glow::MIN);
+    }
+
+    #[test]
+    fn test_bind_group_bindings() {
+        let desc = BindGroupLayoutDesc {
+            entries: vec![
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    ty: BindingType::UniformBuffer,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    ty: BindingType::StorageBuffer { read_only: true },
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    ty: BindingType::Texture {
+                        dimension: TextureDimension::D2,
+                        array: false,
+                        comparison: false,
+                    },
+                },
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    ty: BindingType::Sampler { comparison: false },
+                },
+                BindGroupLayoutEntry {
+                    binding: 4,
+                    ty: BindingType::StorageTexture {
+                        format: TextureFormat::Rgba8,
+                        dimension: TextureDimension::D2,
+                        access: StorageTextureAccess::WriteOnly,
+                    },
+                },
+            ],
+        };
+        let entries = [
+            entry(0, buffer(7)),
+            entry(1, buffer(8)),
+            entry(
+                2,
+                NativeBindingResource::Texture(NativeTexture { handle: 9 }),
+            ),
+            entry(
+                3,
+                NativeBindingResource::Sampler(NativeSampler { handle: 10 }),
+            ),
+            entry(
+                4,
+                NativeBindingResource::Texture(NativeTexture { handle: 11 }),
+            ),
+        ];
+
+        let bindings = gl_bindings(&desc, &entries);
+        // The sampler rides along with the texture before it
+        assert_eq!(bindings.len(), 4);
+        assert!(matches!(
+            bindings[0],
+            GlBinding::Buffer {
+                target: glow::UNIFORM_BUFFER,
+                index: 0,
+                buffer: 7,
+                offset: 256,
+                size: 64,
+            }
+        ));
+        assert!(matches!(
+            bindings[1],
+            GlBinding::Buffer {
+                target: glow::SHADER_STORAGE_BUFFER,
+                index: 1,
+                buffer: 8,
+                ..
+            }
+        ));
+        assert!(matches!(
+            bindings[2],
+            GlBinding::Texture {
+                unit: 2,
+                texture: 9,
+                sampler: 10,
+            }
+        ));
+        assert!(matches!(
+            bindings[3],
+            GlBinding::Image {
+                unit: 4,
+                texture: 11,
+                access: glow::WRITE_ONLY,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_texture_without_sampler() {
+        let desc = BindGroupLayoutDesc {
+            entries: vec![BindGroupLayoutEntry {
+                binding: 5,
+                ty: BindingType::Texture {
+                    dimension: TextureDimension::D2,
+                    array: false,
+                    comparison: false,
+                },
+            }],
+        };
+        let entries = [entry(
+            5,
+            NativeBindingResource::Texture(NativeTexture { handle: 3 }),
+        )];
+        assert!(matches!(
+            gl_bindings(&desc, &entries)[..],
+            [GlBinding::Texture {
+                unit: 5,
+                texture: 3,
+                sampler: 0,
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_command_state_bindings() {
+        let mut state = CommandState {
+            pipeline: Some(1),
+            ..Default::default()
+        };
+        state.vertex_buffers.insert(0, (4, 0));
+
+        state.bind(&[
+            GlBinding::Buffer {
+                target: glow::UNIFORM_BUFFER,
+                index: 0,
+                buffer: 7,
+                offset: 0,
+                size: 64,
+            },
+            GlBinding::Texture {
+                unit: 2,
+                texture: 9,
+                sampler: 10,
+            },
+        ]);
+        // A later group replaces what is on the same slots only
+        state.bind(&[
+            GlBinding::Texture {
+                unit: 2,
+                texture: 12,
+                sampler: 0,
+            },
+            GlBinding::Image {
+                unit: 1,
+                texture: 13,
+                access: glow::READ_WRITE,
+            },
+        ]);
+        assert_eq!(
+            state.buffers.get(&(glow::UNIFORM_BUFFER, 0)),
+            Some(&(7, 0, 64))
+        );
+        assert_eq!(state.textures.get(&2), Some(&(12, 0)));
+        assert_eq!(state.images.get(&1), Some(&(13, glow::READ_WRITE)));
+
+        // Resource bindings end with the render pass; the rest stays
+        state.clear_bindings();
+        assert!(state.buffers.is_empty());
+        assert!(state.textures.is_empty());
+        assert!(state.images.is_empty());
+        assert_eq!(state.pipeline, Some(1));
+        assert_eq!(state.vertex_buffers.get(&0), Some(&(4, 0)));
+    }
+
+    #[test]
+    fn test_program_binary_blob() {
+        let mut data = Vec::new();
+        put_program_binary(
+            &mut data,
+            42,
+            &glow::ProgramBinary {
+                buffer: vec![1, 2, 3],
+                format: 0x8741,
+            },
+        );
+        put_program_binary(
+            &mut data,
+            43,
+            &glow::ProgramBinary {
+                buffer: Vec::new(),
+                format: 7,
+            },
+        );
+
+        let mut rest = &data[..];
+        let (key, binary) = take_program_binary(&mut rest).unwrap();
+        assert_eq!(
+            (key, binary.format, binary.buffer),
+            (42, 0x8741, vec![1, 2, 3])
+        );
+        let (key, binary) = take_program_binary(&mut rest).unwrap();
+        assert_eq!((key, binary.format, binary.buffer.len()), (43, 7, 0));
+        assert!(rest.is_empty());
+
+        // A truncated binary is rejected
+        let mut truncated = &data[..data.len() - 21];
+        assert!(take_program_binary(&mut truncated).is_none());
+    }
+}
@@ -5,10 +5,46 @@
 //!
 //! This module provides a concrete implementation of the GpuDevice trait.
 //! It wraps the native graphics API (Vulkan/D3D12/Metal/OpenGL) and translates
-//! Avila's API to backend-specific calls.
+//! Avila's API to backend-specific calls. `RendererConfig::backend` picks the
+//! native implementation:
+//!
+//! - `null` - logs calls, creates nothing (default)
+//! - `software` - CPU rasterizer into in-memory textures
+//! - `gl` - OpenGL 4.5 / GLES 3.2 (feature `opengl`)
+//! - `webgpu` - wgpu (feature `wgpu`)
 
 use crate::gfx::api::*;
-use std::collections::HashMap;
+use crate::gfx::png;
+use crate::gfx::reflection::{is_spirv, ShaderReflection};
+use allocator::{Allocation, DeviceAllocator};
+use avila_math::memory::MemoryManager;
+use avila_math::os::hash::crc32;
+use avila_math::os::FileSystem;
+use avila_math::window::{NativeHandle, Window, WindowEvent};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::path::Path;
+
+mod allocator;
+#[cfg(feature = "opengl")]
+mod gl;
+mod null;
+mod software;
+#[cfg(feature = "wgpu")]
+mod webgpu;
+
+/// Placement alignment of buffers and textures in device memory
+const BUFFER_ALIGNMENT: u64 = 256;
+const TEXTURE_ALIGNMENT: u64 = 4096;
+
+/// Frames whose timestamps can wait for the GPU at once, and the
+/// timestamps each of them holds
+const TIMESTAMP_FRAMES: u64 = 3;
+const MAX_TIMESTAMPS: u32 = 256;
+
+/// Start of `pipeline_cache_data` blobs, and their layout version
+const PIPELINE_CACHE_MAGIC: &[u8; 4] = b"AVPC";
+const PIPELINE_CACHE_VERSION: u32 = 1;
 
 /// Backend GPU device implementation
 pub struct BackendDevice {
@@ -19,73 +55,646 @@ pub struct BackendDevice {
     buffers: ResourcePool<BufferResource>,
     shaders: ResourcePool<ShaderResource>,
     pipelines: ResourcePool<PipelineResource>,
+    compute_pipelines: ResourcePool<ComputePipelineResource>,
+    samplers: ResourcePool<SamplerResource>,
+    bind_group_layouts: ResourcePool<BindGroupLayoutResource>,
+    bind_groups: ResourcePool<BindGroupResource>,
+
+    // Device memory of textures and buffers
+    allocator: DeviceAllocator,
 
-    // Native API handles (todo: implement per backend)
-    // For Vulkan: VkInstance, VkDevice, VkQueue, VkSwapchain, etc.
-    // For now: stubs
-    native_device: NativeDevice,
+    // Native API (selected by `RendererConfig::backend`)
+    native_device: Box<dyn NativeDevice>,
+
+    // Presentation
+    swapchain: Swapchain,
 
     // Frame synchronization
     current_frame: u64,
+    /// Value of the last `signal_fence`
+    last_fence: u64,
+    /// Fence after each presented frame the GPU may still be on, oldest
+    /// first
+    frame_fences: VecDeque<FenceValue>,
+    /// Fences up to this value were signaled on a lost device and count as
+    /// reached
+    lost_fences: u64,
+
+    // Device loss: set by `simulate_device_loss`, while the native device
+    // reports real losses; events wait for `poll_events`
+    device_lost: bool,
+    events: Vec<DeviceEvent>,
+
+    // GPU timestamps: one query pool per frame in flight (none without
+    // backend support), and the frames read back but not yet handed out
+    timestamp_frames: Vec<TimestampFrame>,
+    finished_timestamps: Vec<FrameTimestamps>,
 }
 
 impl BackendDevice {
+    /// Create a device with an offscreen swapchain of `config.width` x `config.height`
     pub fn new(config: RendererConfig) -> Self {
-        let native_device = NativeDevice::create(&config);
+        let native_device = create_native_device(&config, None);
+        Self::from_native(config, native_device, None, None)
+    }
 
-        Self {
+    /// Create a device presenting to a native window
+    ///
+    /// With `None` the swapchain images are still allocated and rotated, but
+    /// `present` has nowhere to show them. Fails with
+    /// `GfxError::Unsupported` when the backend cannot present to `window`.
+    pub fn with_surface(
+        config: RendererConfig,
+        window: Option<NativeHandle>,
+    ) -> Result<Self, GfxError> {
+        let mut native_device = create_native_device(&config, window.as_ref());
+        let surface = window
+            .map(|handle| native_device.create_surface_native(&handle))
+            .transpose()?;
+        Ok(Self::from_native(config, native_device, window, surface))
+    }
+
+    fn from_native(
+        mut config: RendererConfig,
+        mut native_device: Box<dyn NativeDevice>,
+        window: Option<NativeHandle>,
+        surface: Option<NativeSurface>,
+    ) -> Self {
+        config.frames_in_flight = config.frames_in_flight.max(1);
+        let vram = native_device.adapter_info_native().vram;
+        let timestamp_frames = create_timestamp_frames(native_device.as_mut());
+
+        let mut device = Self {
             config,
             textures: ResourcePool::new(),
             buffers: ResourcePool::new(),
             shaders: ResourcePool::new(),
             pipelines: ResourcePool::new(),
+            compute_pipelines: ResourcePool::new(),
+            samplers: ResourcePool::new(),
+            bind_group_layouts: ResourcePool::new(),
+            bind_groups: ResourcePool::new(),
+            allocator: DeviceAllocator::new(vram),
             native_device,
+            swapchain: Swapchain {
+                window,
+                surface,
+                native: None,
+                images: Vec::new(),
+                current_image: 0,
+                msaa_texture: None,
+                suspended: false,
+            },
             current_frame: 0,
+            last_fence: 0,
+            frame_fences: VecDeque::new(),
+            lost_fences: 0,
+            device_lost: false,
+            events: Vec::new(),
+            timestamp_frames,
+            finished_timestamps: Vec::new(),
+        };
+        device.create_swapchain();
+        device
+    }
+
+    /// Native window the swapchain presents to (`None` when offscreen)
+    pub fn surface_window(&self) -> Option<NativeHandle> {
+        self.swapchain.window
+    }
+
+    /// Configuration the device runs with; `width` and `height` follow
+    /// `resize`
+    pub fn config(&self) -> &RendererConfig {
+        &self.config
+    }
+
+    /// Adapter the device ended up on, after any fallback to the null backend
+    pub fn adapter_info(&self) -> AdapterInfo {
+        self.native_device.adapter_info_native()
+    }
+
+    /// Statistics of every device memory heap
+    pub fn memory_stats(&self) -> Vec<HeapStats> {
+        MemoryHeap::ALL
+            .iter()
+            .map(|&heap| self.allocator.stats(heap))
+            .collect()
+    }
+
+    /// Track the device memory heaps in `manager`, under `MemoryHeap::name`
+    ///
+    /// Every `report()` of the manager reads the heaps as they are then, for
+    /// as long as the device lives.
+    pub fn report_memory(&self, manager: &mut MemoryManager) {
+        for heap in MemoryHeap::ALL {
+            manager.track(heap.name(), self.allocator.report(heap));
+        }
+    }
+
+    /// Limit how many bytes `heap` may reserve
+    ///
+    /// The device-local heap starts at the adapter's `vram` when it is
+    /// reported; otherwise heaps are unlimited. Creating a texture or buffer
+    /// that needs memory past the budget fails with `GfxError::OutOfMemory`.
+    pub fn set_memory_budget(&mut self, heap: MemoryHeap, bytes: u64) {
+        self.allocator.set_budget(heap, bytes);
+    }
+
+    /// Apply window events that affect presentation
+    ///
+    /// `FramebufferResized` recreates the swapchain at the new size in
+    /// physical pixels. `Minimized` suspends presentation until `Restored`
+    /// or `Maximized`; other events are ignored.
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::FramebufferResized(width, height) => self.resize(width, height),
+            WindowEvent::Minimized => self.swapchain.suspended = true,
+            WindowEvent::Restored | WindowEvent::Maximized => self.swapchain.suspended = false,
+            _ => {}
+        }
+    }
+
+    /// Treat the device as lost at the next `begin_frame` or `poll_events`
+    ///
+    /// Exercises the recovery a driver reset goes through: the native device
+    /// is recreated and `DeviceEvent::Lost` and `DeviceEvent::Restored` are
+    /// reported.
+    pub fn simulate_device_loss(&mut self) {
+        self.device_lost = true;
+    }
+
+    /// Save the current swapchain image as a PNG file
+    ///
+    /// Call it after `submit` and before `present`, while the image still
+    /// holds the frame. HDR (`Rgba16f`) images are clamped to 0..1 and
+    /// encoded as sRGB, without tone mapping. The file is replaced atomically.
+    pub fn capture_swapchain_to_png(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let texture = self.get_swapchain_texture();
+        let desc = match self.textures.get(texture.0) {
+            Some(resource) => resource.desc.clone(),
+            None => {
+                return Err(io::Error::other(GfxError::InvalidDesc(
+                    "no swapchain image to capture".to_string(),
+                )))
+            }
+        };
+        let pixels = self.read_texture(texture).map_err(io::Error::other)?;
+        let rgba = rgba8_pixels(desc.format, &pixels).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("cannot convert {:?} to PNG", desc.format),
+            )
+        })?;
+        FileSystem::write_atomic(path, png::encode_rgba8(desc.width, desc.height, &rgba))
+    }
+
+    /// Swapchain image description for the current configuration
+    fn swapchain_desc(&self) -> TextureDesc {
+        TextureDesc::new_2d(
+            self.config.width,
+            self.config.height,
+            self.config.swapchain_format(),
+            TextureUsage::COLOR_ATTACHMENT | TextureUsage::TRANSFER_SRC,
+        )
+    }
+
+    /// Create the native swapchain and register its images as textures
+    fn create_swapchain(&mut self) {
+        let desc = self.swapchain_desc();
+        // One image more than the frames in flight, for the one on screen
+        let native = self.native_device.create_swapchain_native(
+            self.swapchain.surface,
+            &desc,
+            self.config.frames_in_flight + 1,
+            self.config.present_mode,
+        );
+
+        self.swapchain.images = self
+            .native_device
+            .swapchain_images_native(&native)
+            .into_iter()
+            .map(|native| {
+                TextureHandle(self.textures.allocate(TextureResource {
+                    desc: desc.clone(),
+                    native,
+                    memory: None,
+                    state: ResourceState::Undefined,
+                }))
+            })
+            .collect();
+        self.swapchain.native = Some(native);
+        self.swapchain.current_image = 0;
+
+        if self.config.msaa_samples > 1 {
+            let samples = self.config.msaa_samples;
+            let msaa = TextureDesc {
+                usage: TextureUsage::COLOR_ATTACHMENT,
+                ..desc
+            }
+            .with_msaa(samples);
+            match self.create_texture(&msaa, None) {
+                Ok(texture) => self.swapchain.msaa_texture = Some(texture),
+                Err(err) => {
+                    eprintln!(
+                        "MSAA target with {} samples unavailable: {}; rendering without MSAA",
+                        samples, err
+                    );
+                    self.config.msaa_samples = 1;
+                }
+            }
+        }
+    }
+
+    /// Acquire the image for this frame, recreating the swapchain once if
+    /// the surface no longer matches it
+    fn acquire_image(&mut self) {
+        for attempt in 0..2 {
+            let Some(native) = &mut self.swapchain.native else {
+                return;
+            };
+            if let Some(index) = self.native_device.acquire_next_image_native(native) {
+                self.swapchain.current_image = index;
+                return;
+            }
+            if attempt == 0 {
+                self.native_device.wait_idle_native();
+                self.destroy_swapchain();
+                self.create_swapchain();
+            }
+        }
+        eprintln!("Swapchain out of date after recreation; the frame is not shown");
+    }
+
+    /// Replace a lost native device, and the swapchain on it
+    ///
+    /// The handle pools are emptied: their native objects went with the old
+    /// device. Memory budgets carry over.
+    fn restore_device(&mut self) {
+        eprintln!("GPU device lost; recreating it");
+        self.events.push(DeviceEvent::Lost);
+        // Release the old device, and its context, before making a new one
+        self.native_device = Box::new(null::NullDevice::create(&self.config));
+        self.native_device = create_native_device(&self.config, self.swapchain.window.as_ref());
+        self.swapchain.surface = self.swapchain.window.and_then(|handle| {
+            match self.native_device.create_surface_native(&handle) {
+                Ok(surface) => Some(surface),
+                Err(err) => {
+                    eprintln!(
+                        "Window surface lost with the device: {}; rendering offscreen",
+                        err
+                    );
+                    None
+                }
+            }
+        });
+        self.swapchain.native = None;
+        self.swapchain.images.clear();
+        self.swapchain.msaa_texture = None;
+
+        self.textures = ResourcePool::new();
+        self.buffers = ResourcePool::new();
+        self.shaders = ResourcePool::new();
+        self.pipelines = ResourcePool::new();
+        self.compute_pipelines = ResourcePool::new();
+        self.samplers = ResourcePool::new();
+        self.bind_group_layouts = ResourcePool::new();
+        self.bind_groups = ResourcePool::new();
+        self.allocator.reset();
+        self.timestamp_frames = create_timestamp_frames(self.native_device.as_mut());
+        self.finished_timestamps.clear();
+        self.frame_fences.clear();
+        self.lost_fences = self.last_fence;
+
+        self.device_lost = false;
+        self.create_swapchain();
+        self.events.push(DeviceEvent::Restored);
+    }
+
+    /// Restore the device if it was lost since the last check
+    fn check_device_lost(&mut self) {
+        if self.device_lost || self.native_device.device_lost_native() {
+            self.restore_device();
+        }
+    }
+
+    /// Release the swapchain images; they belong to the swapchain, not the pool
+    fn destroy_swapchain(&mut self) {
+        if let Some(texture) = self.swapchain.msaa_texture.take() {
+            self.destroy_texture(texture);
+        }
+        for image in self.swapchain.images.drain(..) {
+            self.textures.free(image.0);
+        }
+        if let Some(native) = self.swapchain.native.take() {
+            self.native_device.destroy_swapchain_native(native);
+        }
+    }
+
+    /// Resources each render pass of `commands` uses, one list per pass
+    fn render_pass_uses(&self, commands: &[Command]) -> Vec<Vec<(TrackedResource, ResourceState)>> {
+        let mut passes = Vec::new();
+        let mut current: Option<Vec<_>> = None;
+        for command in commands {
+            let uses = match (command, &mut current) {
+                (Command::BeginRenderPass(desc), _) => {
+                    passes.extend(current.take());
+                    let mut uses = Vec::new();
+                    for attachment in &desc.color_attachments {
+                        let texture = TrackedResource::Texture(attachment.texture);
+                        add_use(&mut uses, texture, ResourceState::ColorAttachment);
+                        if let StoreOp::Resolve(target) = attachment.store {
+                            let target = TrackedResource::Texture(target);
+                            add_use(&mut uses, target, ResourceState::ColorAttachment);
+                        }
+                    }
+                    if let Some(attachment) = &desc.depth_attachment {
+                        let texture = TrackedResource::Texture(attachment.texture);
+                        add_use(&mut uses, texture, ResourceState::DepthAttachment);
+                    }
+                    current = Some(uses);
+                    continue;
+                }
+                (Command::EndRenderPass, _) => {
+                    passes.extend(current.take());
+                    continue;
+                }
+                (_, None) => continue,
+                (_, Some(uses)) => uses,
+            };
+            match *command {
+                Command::BindVertexBuffer { buffer, .. }
+                | Command::BindIndexBuffer { buffer, .. } => {
+                    add_use(
+                        uses,
+                        TrackedResource::Buffer(buffer),
+                        ResourceState::VertexInput,
+                    );
+                }
+                Command::BindTexture { texture, .. } => {
+                    add_use(
+                        uses,
+                        TrackedResource::Texture(texture),
+                        ResourceState::ShaderRead,
+                    );
+                }
+                Command::BindGroup { group, .. } => {
+                    if let Some(resource) = self.bind_groups.get(group.0) {
+                        for &(tracked, state) in &resource.uses {
+                            add_use(uses, tracked, state);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        passes.extend(current);
+        passes
+    }
+
+    /// Move resources to the states of their next use, with a barrier
+    /// wherever that is a hazard
+    /// Write a timestamp into the query pool of the current frame
+    fn write_timestamp(&mut self, name: String) {
+        if self.timestamp_frames.is_empty() {
+            return;
+        }
+        let slot = (self.current_frame % TIMESTAMP_FRAMES) as usize;
+        // The pool still holds a frame the GPU has not caught up with
+        if self.timestamp_frames[slot].pending {
+            self.collect_timestamps(slot, true);
+        }
+        let frame = &mut self.timestamp_frames[slot];
+        if frame.frame != self.current_frame {
+            frame.frame = self.current_frame;
+            frame.names.clear();
+        }
+        let index = frame.names.len() as u32;
+        if index < MAX_TIMESTAMPS {
+            frame.names.push(name);
+            self.native_device.write_timestamp_native(frame.pool, index);
+        }
+    }
+
+    /// Move the timestamps of a resolved frame to `finished_timestamps`;
+    /// false if the GPU is not done with them
+    ///
+    /// With `wait` the GPU is waited for, and results it still does not
+    /// have are dropped.
+    fn collect_timestamps(&mut self, slot: usize, wait: bool) -> bool {
+        let frame = &mut self.timestamp_frames[slot];
+        let count = frame.names.len() as u32;
+        let mut times = self.native_device.read_query_pool_native(frame.pool, count);
+        if times.is_none() && wait {
+            self.native_device.wait_idle_native();
+            times = self.native_device.read_query_pool_native(frame.pool, count);
+        }
+        if times.is_none() && !wait {
+            return false;
+        }
+        frame.pending = false;
+        let names = std::mem::take(&mut frame.names);
+        if let Some(times) = times {
+            self.finished_timestamps.push(FrameTimestamps {
+                frame: frame.frame,
+                timestamps: names.into_iter().zip(times).collect(),
+            });
+        }
+        true
+    }
+
+    /// Adapter a pipeline cache blob is only valid for
+    fn pipeline_cache_identity(&self) -> String {
+        let info = self.native_device.adapter_info_native();
+        format!("{:?}\n{}\n{}", info.backend, info.vendor, info.name)
+    }
+
+    fn transition(&mut self, uses: &[(TrackedResource, ResourceState)]) {
+        let mut barriers = Vec::new();
+        for &(tracked, after) in uses {
+            let (resource, state) = match tracked {
+                TrackedResource::Texture(handle) => match self.textures.get_mut(handle.0) {
+                    Some(texture) => (NativeResource::Texture(texture.native), &mut texture.state),
+                    None => continue,
+                },
+                TrackedResource::Buffer(handle) => match self.buffers.get_mut(handle.0) {
+                    // A buffer has no layout, so nothing to do before its first use
+                    Some(buffer) if buffer.state == ResourceState::Undefined => {
+                        buffer.state = after;
+                        continue;
+                    }
+                    Some(buffer) => (NativeResource::Buffer(buffer.native), &mut buffer.state),
+                    None => continue,
+                },
+            };
+            let before = std::mem::replace(state, after);
+            if before != after || before == ResourceState::ShaderWrite {
+                barriers.push(Barrier {
+                    resource,
+                    before,
+                    after,
+                });
+            }
+        }
+        if !barriers.is_empty() {
+            self.native_device.barrier_native(&barriers);
         }
     }
 }
 
 impl GpuDevice for BackendDevice {
-    fn create_texture(&mut self, desc: &TextureDesc) -> TextureHandle {
-        let native_texture = self.native_device.create_texture_native(desc);
+    fn create_texture(
+        &mut self,
+        desc: &TextureDesc,
+        initial_data: Option<&[u8]>,
+    ) -> Result<TextureHandle, GfxError> {
+        let mut desc = desc.clone();
+        if initial_data.is_some() {
+            desc.usage = desc.usage | TextureUsage::TRANSFER_DST;
+        }
+        validate_texture(&desc)?;
+        let layers = layer_count(&desc);
+        let region = TextureRegion::full(&desc, 0);
+        if let Some(data) = initial_data {
+            if data.len() % layers as usize != 0 {
+                return Err(GfxError::InvalidDesc(format!(
+                    "{} bytes of initial data do not split into {} layers",
+                    data.len(),
+                    layers
+                )));
+            }
+            validate_texture_update(&desc, 0, 0, &region, data.len() / layers as usize)?;
+        }
+
+        let memory = self.allocator.allocate(
+            MemoryHeap::DeviceLocal,
+            texture_size(&desc),
+            TEXTURE_ALIGNMENT,
+        )?;
+        let native_texture = match self.native_device.create_texture_native(&desc) {
+            Ok(native) => native,
+            Err(err) => {
+                self.allocator.free(memory);
+                return Err(err);
+            }
+        };
+        if let Some(data) = initial_data {
+            let layer_size = data.len() / layers as usize;
+            for (layer, chunk) in data.chunks_exact(layer_size).enumerate() {
+                let uploaded = self.native_device.update_texture_native(
+                    native_texture,
+                    0,
+                    layer as u32,
+                    &region,
+                    chunk,
+                );
+                if let Err(err) = uploaded {
+                    self.native_device.destroy_texture_native(native_texture);
+                    self.allocator.free(memory);
+                    return Err(err);
+                }
+            }
+            if desc.mip_levels > 1 && validate_mipmaps(&desc).is_ok() {
+                let generated = self
+                    .native_device
+                    .generate_mipmaps_native(native_texture, &desc);
+                if let Err(err) = generated {
+                    self.native_device.destroy_texture_native(native_texture);
+                    self.allocator.free(memory);
+                    return Err(err);
+                }
+            }
+        }
 
         let resource = TextureResource {
-            desc: desc.clone(),
+            desc,
             native: native_texture,
+            memory: Some(memory),
+            state: if initial_data.is_some() {
+                ResourceState::TransferDst
+            } else {
+                ResourceState::Undefined
+            },
         };
 
         let id = self.textures.allocate(resource);
-        TextureHandle(id)
+        Ok(TextureHandle(id))
     }
 
-    fn create_buffer(&mut self, desc: &BufferDesc, initial_data: Option<&[u8]>) -> BufferHandle {
-        let native_buffer = self.native_device.create_buffer_native(desc, initial_data);
+    fn create_buffer(
+        &mut self,
+        desc: &BufferDesc,
+        initial_data: Option<&[u8]>,
+    ) -> Result<BufferHandle, GfxError> {
+        if desc.size == 0 {
+            return Err(GfxError::InvalidDesc("buffer size is zero".to_string()));
+        }
+        if let Some(data) = initial_data {
+            if data.len() > desc.size {
+                return Err(GfxError::InvalidDesc(format!(
+                    "{} bytes of initial data for a {} byte buffer",
+                    data.len(),
+                    desc.size
+                )));
+            }
+        }
+        let heap = if desc.cpu_visible {
+            MemoryHeap::HostVisible
+        } else {
+            MemoryHeap::DeviceLocal
+        };
+        let memory = self
+            .allocator
+            .allocate(heap, desc.size as u64, BUFFER_ALIGNMENT)?;
+        let native_buffer = match self.native_device.create_buffer_native(desc, initial_data) {
+            Ok(native) => native,
+            Err(err) => {
+                self.allocator.free(memory);
+                return Err(err);
+            }
+        };
 
         let resource = BufferResource {
             desc: desc.clone(),
             native: native_buffer,
+            memory,
+            state: if initial_data.is_some() {
+                ResourceState::TransferDst
+            } else {
+                ResourceState::Undefined
+            },
         };
 
         let id = self.buffers.allocate(resource);
-        BufferHandle(id)
+        Ok(BufferHandle(id))
     }
 
-    fn create_shader(&mut self, desc: &ShaderDesc) -> ShaderHandle {
-        let native_shader = self.native_device.create_shader_native(desc);
+    fn create_shader(&mut self, desc: &ShaderDesc) -> Result<ShaderHandle, GfxError> {
+        // Pipelines made from SPIR-V are checked against what it declares
+        let reflection = if is_spirv(&desc.code) {
+            Some(ShaderReflection::new(desc)?)
+        } else {
+            None
+        };
+        let native_shader = self.native_device.create_shader_native(desc)?;
 
         let resource = ShaderResource {
             desc: desc.clone(),
             native: native_shader,
+            reflection,
         };
 
         let id = self.shaders.allocate(resource);
-        ShaderHandle(id)
+        Ok(ShaderHandle(id))
     }
 
-    fn create_pipeline(&mut self, desc: &PipelineDesc) -> PipelineHandle {
-        let native_pipeline = self
-            .native_device
-            .create_pipeline_native(desc, &self.shaders);
+    fn create_pipeline(&mut self, desc: &PipelineDesc) -> Result<PipelineHandle, GfxError> {
+        validate_pipeline(desc, &self.shaders, &self.bind_group_layouts)?;
+        let native_pipeline = self.native_device.create_pipeline_native(
+            desc,
+            &self.shaders,
+            &self.bind_group_layouts,
+        )?;
 
         let resource = PipelineResource {
             desc: desc.clone(),
@@ -93,18 +702,101 @@ impl GpuDevice for BackendDevice {
         };
 
         let id = self.pipelines.allocate(resource);
-        PipelineHandle(id)
+        Ok(PipelineHandle(id))
+    }
+
+    fn create_compute_pipeline(
+        &mut self,
+        desc: &ComputePipelineDesc,
+    ) -> Result<ComputePipelineHandle, GfxError> {
+        validate_compute_pipeline(desc, &self.shaders, &self.bind_group_layouts)?;
+        let native_pipeline = self.native_device.create_compute_pipeline_native(
+            desc,
+            &self.shaders,
+            &self.bind_group_layouts,
+        )?;
+
+        let resource = ComputePipelineResource {
+            native: native_pipeline,
+        };
+
+        let id = self.compute_pipelines.allocate(resource);
+        Ok(ComputePipelineHandle(id))
+    }
+
+    fn create_sampler(&mut self, desc: &SamplerDesc) -> Result<SamplerHandle, GfxError> {
+        validate_sampler(desc)?;
+        let native_sampler = self.native_device.create_sampler_native(desc)?;
+
+        let resource = SamplerResource {
+            desc: *desc,
+            native: native_sampler,
+        };
+
+        let id = self.samplers.allocate(resource);
+        Ok(SamplerHandle(id))
+    }
+
+    fn create_bind_group_layout(
+        &mut self,
+        desc: &BindGroupLayoutDesc,
+    ) -> Result<BindGroupLayoutHandle, GfxError> {
+        validate_bind_group_layout(desc)?;
+        let native_layout = self.native_device.create_bind_group_layout_native(desc)?;
+
+        let resource = BindGroupLayoutResource {
+            desc: desc.clone(),
+            native: native_layout,
+        };
+
+        let id = self.bind_group_layouts.allocate(resource);
+        Ok(BindGroupLayoutHandle(id))
+    }
+
+    fn create_bind_group(&mut self, desc: &BindGroupDesc) -> Result<BindGroupHandle, GfxError> {
+        let Some(layout) = self.bind_group_layouts.get(desc.layout.0) else {
+            return Err(GfxError::InvalidDesc(format!(
+                "unknown bind group layout {:?}",
+                desc.layout
+            )));
+        };
+        let entries = resolve_bind_group(
+            desc,
+            &layout.desc,
+            &self.textures,
+            &self.buffers,
+            &self.samplers,
+        )?;
+        let uses = bind_group_uses(desc, &layout.desc);
+        let native_group =
+            self.native_device
+                .create_bind_group_native(layout.native, &layout.desc, &entries)?;
+
+        let resource = BindGroupResource {
+            native: native_group,
+            uses,
+        };
+
+        let id = self.bind_groups.allocate(resource);
+        Ok(BindGroupHandle(id))
     }
 
     fn destroy_texture(&mut self, handle: TextureHandle) {
+        if self.swapchain.images.contains(&handle) || self.swapchain.msaa_texture == Some(handle) {
+            return;
+        }
         if let Some(resource) = self.textures.free(handle.0) {
             self.native_device.destroy_texture_native(resource.native);
+            if let Some(memory) = resource.memory {
+                self.allocator.free(memory);
+            }
         }
     }
 
     fn destroy_buffer(&mut self, handle: BufferHandle) {
         if let Some(resource) = self.buffers.free(handle.0) {
             self.native_device.destroy_buffer_native(resource.native);
+            self.allocator.free(resource.memory);
         }
     }
 
@@ -120,7 +812,35 @@ impl GpuDevice for BackendDevice {
         }
     }
 
+    fn destroy_compute_pipeline(&mut self, handle: ComputePipelineHandle) {
+        if let Some(resource) = self.compute_pipelines.free(handle.0) {
+            self.native_device
+                .destroy_compute_pipeline_native(resource.native);
+        }
+    }
+
+    fn destroy_sampler(&mut self, handle: SamplerHandle) {
+        if let Some(resource) = self.samplers.free(handle.0) {
+            self.native_device.destroy_sampler_native(resource.native);
+        }
+    }
+
+    fn destroy_bind_group_layout(&mut self, handle: BindGroupLayoutHandle) {
+        if let Some(resource) = self.bind_group_layouts.free(handle.0) {
+            self.native_device
+                .destroy_bind_group_layout_native(resource.native);
+        }
+    }
+
+    fn destroy_bind_group(&mut self, handle: BindGroupHandle) {
+        if let Some(resource) = self.bind_groups.free(handle.0) {
+            self.native_device
+                .destroy_bind_group_native(resource.native);
+        }
+    }
+
     fn update_buffer(&mut self, buffer: BufferHandle, offset: usize, data: &[u8]) {
+        self.transition(&[(TrackedResource::Buffer(buffer), ResourceState::TransferDst)]);
         if let Some(resource) = self.buffers.get(buffer.0) {
             self.native_device
                 .update_buffer_native(resource.native, offset, data);
@@ -128,6 +848,7 @@ impl GpuDevice for BackendDevice {
     }
 
     fn map_buffer(&mut self, buffer: BufferHandle) -> *mut u8 {
+        self.transition(&[(TrackedResource::Buffer(buffer), ResourceState::HostAccess)]);
         if let Some(resource) = self.buffers.get(buffer.0) {
             self.native_device.map_buffer_native(resource.native)
         } else {
@@ -141,27 +862,137 @@ impl GpuDevice for BackendDevice {
         }
     }
 
+    fn update_texture(
+        &mut self,
+        texture: TextureHandle,
+        mip: u32,
+        layer: u32,
+        region: TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        let resource = self
+            .textures
+            .get(texture.0)
+            .ok_or_else(|| GfxError::InvalidDesc(format!("unknown texture {:?}", texture)))?;
+        validate_texture_update(&resource.desc, mip, layer, &region, data.len())?;
+        let native = resource.native;
+        self.transition(&[(
+            TrackedResource::Texture(texture),
+            ResourceState::TransferDst,
+        )]);
+        self.native_device
+            .update_texture_native(native, mip, layer, &region, data)
+    }
+
+    fn generate_mipmaps(&mut self, texture: TextureHandle) -> Result<(), GfxError> {
+        let resource = self
+            .textures
+            .get(texture.0)
+            .ok_or_else(|| GfxError::InvalidDesc(format!("unknown texture {:?}", texture)))?;
+        validate_mipmaps(&resource.desc)?;
+        if resource.desc.mip_levels == 1 {
+            return Ok(());
+        }
+        let (native, desc) = (resource.native, resource.desc.clone());
+        self.transition(&[(
+            TrackedResource::Texture(texture),
+            ResourceState::TransferDst,
+        )]);
+        self.native_device.generate_mipmaps_native(native, &desc)
+    }
+
+    fn read_texture(&mut self, texture: TextureHandle) -> Result<Vec<u8>, GfxError> {
+        let resource = self
+            .textures
+            .get(texture.0)
+            .ok_or_else(|| GfxError::InvalidDesc(format!("unknown texture {:?}", texture)))?;
+        let desc = &resource.desc;
+        if !desc.usage.contains(TextureUsage::TRANSFER_SRC) {
+            return Err(GfxError::InvalidDesc(
+                "reading a texture needs TextureUsage::TRANSFER_SRC".to_string(),
+            ));
+        }
+        if desc.samples > 1 {
+            return Err(GfxError::InvalidDesc(
+                "multisampled textures cannot be read back".to_string(),
+            ));
+        }
+        let (native, desc) = (resource.native, desc.clone());
+        self.transition(&[(
+            TrackedResource::Texture(texture),
+            ResourceState::TransferSrc,
+        )]);
+        self.native_device.read_texture_native(native, &desc)
+    }
+
     fn begin_frame(&mut self) -> CommandList {
+        self.check_device_lost();
+        self.wait_for_frame();
         self.native_device.begin_frame_native();
+        if !self.swapchain.suspended {
+            self.acquire_image();
+        }
         CommandList::new()
     }
 
     fn submit(&mut self, cmd: CommandList) {
+        // Barriers cannot go inside a render pass, so each pass transitions
+        // everything it uses up front
+        let mut pass_uses = self.render_pass_uses(&cmd.commands).into_iter();
+        let mut compute_groups = [None; MAX_BIND_SETS as usize];
+
         // Translate Avila commands to native API calls
+        let mut in_render_pass = false;
+        // An invalid render pass is dropped up to its end
+        let mut skipping_pass = false;
+        // Sample count of the current render pass, and whether the bound
+        // pipeline draws with another one
+        let mut pass_samples = 1;
+        let mut wrong_samples = false;
         for command in cmd.commands {
+            if skipping_pass && !matches!(command, Command::EndRenderPass) {
+                continue;
+            }
             match command {
                 Command::BeginRenderPass(desc) => {
+                    let uses = pass_uses.next().unwrap_or_default();
+                    if let Err(err) = validate_render_pass(&desc, &self.textures) {
+                        eprintln!("Render pass skipped: {}", err);
+                        skipping_pass = true;
+                        continue;
+                    }
+                    self.transition(&uses);
                     self.native_device
                         .begin_render_pass_native(&desc, &self.textures);
+                    pass_samples = attachment_samples(&desc, &self.textures)
+                        .first()
+                        .copied()
+                        .unwrap_or(1);
+                    wrong_samples = false;
+                    in_render_pass = true;
+                    compute_groups = [None; MAX_BIND_SETS as usize];
                 }
+                Command::EndRenderPass if skipping_pass => skipping_pass = false,
                 Command::EndRenderPass => {
                     self.native_device.end_render_pass_native();
+                    in_render_pass = false;
+                    compute_groups = [None; MAX_BIND_SETS as usize];
                 }
                 Command::BindPipeline(handle) => {
                     if let Some(resource) = self.pipelines.get(handle.0) {
+                        wrong_samples = resource.desc.sample_count != pass_samples;
+                        if wrong_samples {
+                            eprintln!(
+                                "Pipeline with {} samples bound in a render pass with {}; \
+                                 its draws are skipped",
+                                resource.desc.sample_count, pass_samples
+                            );
+                            continue;
+                        }
                         self.native_device.bind_pipeline_native(resource.native);
                     }
                 }
+                Command::Draw { .. } | Command::DrawIndexed { .. } if wrong_samples => {}
                 Command::SetViewport(viewport) => {
                     self.native_device.set_viewport_native(&viewport);
                 }
@@ -191,6 +1022,43 @@ impl GpuDevice for BackendDevice {
                         );
                     }
                 }
+                Command::BindGroup { index, group } => {
+                    if index >= MAX_BIND_SETS {
+                        eprintln!("Bind group bound at set {} past the last set", index);
+                        continue;
+                    }
+                    if let Some(resource) = self.bind_groups.get(group.0) {
+                        self.native_device.bind_group_native(index, resource.native);
+                        if !in_render_pass {
+                            compute_groups[index as usize] = Some(group);
+                        }
+                    }
+                }
+                Command::BindTexture {
+                    set,
+                    binding,
+                    texture,
+                    sampler,
+                } => {
+                    let (Some(texture), Some(sampler)) =
+                        (self.textures.get(texture.0), self.samplers.get(sampler.0))
+                    else {
+                        continue;
+                    };
+                    if !texture.desc.usage.contains(TextureUsage::SAMPLED) {
+                        eprintln!(
+                            "Texture bound at set {} binding {} lacks TextureUsage::SAMPLED",
+                            set, binding
+                        );
+                        continue;
+                    }
+                    self.native_device.bind_texture_native(
+                        set,
+                        binding,
+                        texture.native,
+                        sampler.native,
+                    );
+                }
                 Command::Draw {
                     vertex_count,
                     instance_count,
@@ -219,30 +1087,1101 @@ impl GpuDevice for BackendDevice {
                         first_instance,
                     );
                 }
+                Command::BindComputePipeline(_) | Command::Dispatch { .. } if in_render_pass => {
+                    eprintln!("Compute command recorded inside a render pass; skipped");
+                }
+                Command::BindComputePipeline(handle) => {
+                    if let Some(resource) = self.compute_pipelines.get(handle.0) {
+                        self.native_device
+                            .bind_compute_pipeline_native(resource.native);
+                    }
+                }
+                Command::Dispatch { x, y, z } => {
+                    if x > 0 && y > 0 && z > 0 {
+                        let mut uses = Vec::new();
+                        for group in compute_groups.iter().flatten() {
+                            if let Some(resource) = self.bind_groups.get(group.0) {
+                                for &(tracked, state) in &resource.uses {
+                                    add_use(&mut uses, tracked, state);
+                                }
+                            }
+                        }
+                        self.transition(&uses);
+                        self.native_device.dispatch_native(x, y, z);
+                    }
+                }
+                Command::WriteTimestamp(name) => self.write_timestamp(name),
+                Command::CopyBuffer { .. } | Command::CopyBufferToTexture { .. }
+                    if in_render_pass =>
+                {
+                    eprintln!("Copy recorded inside a render pass; skipped");
+                }
+                Command::CopyBuffer {
+                    src,
+                    src_offset,
+                    dst,
+                    dst_offset,
+                    size,
+                } => {
+                    let (Some(source), Some(target)) =
+                        (self.buffers.get(src.0), self.buffers.get(dst.0))
+                    else {
+                        continue;
+                    };
+                    let checked = if src == dst {
+                        Err(GfxError::InvalidDesc(
+                            "source and target are the same buffer".to_string(),
+                        ))
+                    } else {
+                        validate_buffer_copy(
+                            [
+                                (source.desc.size, src_offset),
+                                (target.desc.size, dst_offset),
+                            ],
+                            size,
+                        )
+                    };
+                    if let Err(err) = checked {
+                        eprintln!("Buffer copy skipped: {}", err);
+                        continue;
+                    }
+                    let (source, target) = (source.native, target.native);
+                    self.transition(&[
+                        (TrackedResource::Buffer(src), ResourceState::TransferSrc),
+                        (TrackedResource::Buffer(dst), ResourceState::TransferDst),
+                    ]);
+                    self.native_device
+                        .copy_buffer_native(source, src_offset, target, dst_offset, size);
+                }
+                Command::CopyBufferToTexture {
+                    src,
+                    layout,
+                    texture,
+                    mip,
+                    layer,
+                    region,
+                } => {
+                    let (Some(source), Some(target)) =
+                        (self.buffers.get(src.0), self.textures.get(texture.0))
+                    else {
+                        continue;
+                    };
+                    let checked = validate_texture_copy(
+                        &target.desc,
+                        source.desc.size,
+                        layout,
+                        mip,
+                        layer,
+                        &region,
+                    );
+                    if let Err(err) = checked {
+                        eprintln!("Texture copy skipped: {}", err);
+                        continue;
+                    }
+                    let (source, target) = (source.native, target.native);
+                    self.transition(&[
+                        (TrackedResource::Buffer(src), ResourceState::TransferSrc),
+                        (
+                            TrackedResource::Texture(texture),
+                            ResourceState::TransferDst,
+                        ),
+                    ]);
+                    let copied = self
+                        .native_device
+                        .copy_buffer_to_texture_native(source, layout, target, mip, layer, &region);
+                    if let Err(err) = copied {
+                        eprintln!("Texture copy failed: {}", err);
+                    }
+                }
             }
         }
+        self.native_device.submit_native();
     }
 
     fn present(&mut self) {
-        self.native_device.present_native();
+        let image = self.get_swapchain_texture();
+        self.transition(&[(TrackedResource::Texture(image), ResourceState::Present)]);
+        match &self.swapchain.native {
+            Some(native) if !self.swapchain.suspended => {
+                self.native_device
+                    .present_native(native, self.swapchain.current_image);
+            }
+            _ => {}
+        }
+        let fence = self.signal_fence();
+        self.frame_fences.push_back(fence);
+        let slot = (self.current_frame % TIMESTAMP_FRAMES) as usize;
+        if let Some(frame) = self.timestamp_frames.get_mut(slot) {
+            if frame.frame == self.current_frame && !frame.names.is_empty() && !frame.pending {
+                self.native_device
+                    .resolve_query_pool_native(frame.pool, frame.names.len() as u32);
+                frame.pending = true;
+            }
+        }
         self.current_frame += 1;
     }
 
     fn get_swapchain_texture(&self) -> TextureHandle {
-        // Return handle to current swapchain image
-        // For now: stub
-        TextureHandle(0)
+        self.swapchain
+            .images
+            .get(self.swapchain.current_image as usize)
+            .copied()
+            .unwrap_or(TextureHandle::INVALID)
+    }
+
+    fn get_msaa_texture(&self) -> TextureHandle {
+        self.swapchain
+            .msaa_texture
+            .unwrap_or(TextureHandle::INVALID)
     }
 
     fn resize(&mut self, width: u32, height: u32) {
+        // A minimized window reports 0x0; keep the old swapchain until it
+        // comes back
+        self.swapchain.suspended = width == 0 || height == 0;
+        if self.swapchain.suspended {
+            return;
+        }
+        if width == self.config.width && height == self.config.height {
+            return;
+        }
         self.config.width = width;
         self.config.height = height;
-        self.native_device.resize_native(width, height);
+
+        self.native_device.wait_idle_native();
+        self.destroy_swapchain();
+        self.create_swapchain();
+        self.events
+            .push(DeviceEvent::SwapchainResized { width, height });
+    }
+
+    fn set_present_mode(&mut self, mode: PresentMode) {
+        if mode == self.config.present_mode {
+            return;
+        }
+        self.config.present_mode = mode;
+        self.native_device.wait_idle_native();
+        self.destroy_swapchain();
+        self.create_swapchain();
     }
 
     fn wait_idle(&mut self) {
         self.native_device.wait_idle_native();
     }
+
+    fn wait_for_frame(&mut self) {
+        while self.frame_fences.len() >= self.config.frames_in_flight as usize {
+            let Some(fence) = self.frame_fences.pop_front() else {
+                break;
+            };
+            if !self.fence_reached(fence) {
+                self.native_device.wait_fence_native(fence.0);
+            }
+        }
+    }
+
+    fn signal_fence(&mut self) -> FenceValue {
+        self.last_fence += 1;
+        self.native_device.signal_fence_native(self.last_fence);
+        FenceValue(self.last_fence)
+    }
+
+    fn fence_reached(&mut self, fence: FenceValue) -> bool {
+        fence.0 <= self.lost_fences || fence.0 <= self.native_device.completed_fence_native()
+    }
+
+    fn poll_events(&mut self) -> Vec<DeviceEvent> {
+        self.check_device_lost();
+        std::mem::take(&mut self.events)
+    }
+
+    fn read_timestamps(&mut self) -> Vec<FrameTimestamps> {
+        // Frames finish in order, so stop at the first that has not
+        let mut slots: Vec<usize> = (0..self.timestamp_frames.len())
+            .filter(|&slot| self.timestamp_frames[slot].pending)
+            .collect();
+        slots.sort_by_key(|&slot| self.timestamp_frames[slot].frame);
+        for slot in slots {
+            if !self.collect_timestamps(slot, false) {
+                break;
+            }
+        }
+        std::mem::take(&mut self.finished_timestamps)
+    }
+
+    fn pipeline_cache_data(&mut self) -> Vec<u8> {
+        let native = self.native_device.pipeline_cache_data_native();
+        if native.is_empty() {
+            return native;
+        }
+        // Magic, version, identity, checksum, then the native blob
+        let identity = self.pipeline_cache_identity();
+        let mut data = Vec::with_capacity(16 + identity.len() + native.len());
+        data.extend_from_slice(PIPELINE_CACHE_MAGIC);
+        data.extend_from_slice(&PIPELINE_CACHE_VERSION.to_le_bytes());
+        data.extend_from_slice(&(identity.len() as u32).to_le_bytes());
+        data.extend_from_slice(identity.as_bytes());
+        data.extend_from_slice(&crc32(&native).to_le_bytes());
+        data.extend_from_slice(&native);
+        data
+    }
+
+    fn load_pipeline_cache_data(&mut self, data: &[u8]) -> bool {
+        match pipeline_cache_payload(data, &self.pipeline_cache_identity()) {
+            Some(native) => self.native_device.load_pipeline_cache_native(native),
+            None => false,
+        }
+    }
+}
+
+/// Sample count of each live attachment of a render pass
+/// Native blob of a `pipeline_cache_data` blob made on the adapter
+/// `identity`, if it is intact
+fn pipeline_cache_payload<'a>(mut data: &'a [u8], identity: &str) -> Option<&'a [u8]> {
+    if take_bytes(&mut data, 4)? != PIPELINE_CACHE_MAGIC
+        || take_u32(&mut data)? != PIPELINE_CACHE_VERSION
+    {
+        return None;
+    }
+    let identity_len = take_u32(&mut data)? as usize;
+    if take_bytes(&mut data, identity_len)? != identity.as_bytes() {
+        return None;
+    }
+    let checksum = take_u32(&mut data)?;
+    (crc32(data) == checksum).then_some(data)
+}
+
+/// Split `len` bytes off the front of `data`
+fn take_bytes<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Some(head)
+}
+
+/// Split a little-endian `u32` off the front of `data`
+fn take_u32(data: &mut &[u8]) -> Option<u32> {
+    let bytes = take_bytes(data, 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn attachment_samples(desc: &RenderPassDesc, textures: &ResourcePool<TextureResource>) -> Vec<u32> {
+    desc.color_attachments
+        .iter()
+        .map(|attachment| attachment.texture)
+        .chain(
+            desc.depth_attachment
+                .as_ref()
+                .map(|attachment| attachment.texture),
+        )
+        .filter_map(|texture| textures.get(texture.0))
+        .map(|texture| texture.desc.samples)
+        .collect()
+}
+
+/// Attachments and store ops every backend can honor
+fn validate_render_pass(
+    desc: &RenderPassDesc,
+    textures: &ResourcePool<TextureResource>,
+) -> Result<(), GfxError> {
+    let samples = attachment_samples(desc, textures);
+    if samples.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(GfxError::InvalidDesc(format!(
+            "attachments have different sample counts: {:?}",
+            samples
+        )));
+    }
+    if desc
+        .depth_attachment
+        .as_ref()
+        .is_some_and(|attachment| matches!(attachment.store, StoreOp::Resolve(_)))
+    {
+        return Err(GfxError::InvalidDesc(
+            "depth attachments cannot be resolved".to_string(),
+        ));
+    }
+    for attachment in &desc.color_attachments {
+        let StoreOp::Resolve(target) = attachment.store else {
+            continue;
+        };
+        let texture = |handle: TextureHandle| {
+            textures
+                .get(handle.0)
+                .map(|texture| &texture.desc)
+                .ok_or_else(|| GfxError::InvalidDesc(format!("unknown texture {:?}", handle)))
+        };
+        let (source, target) = (texture(attachment.texture)?, texture(target)?);
+        if source.samples <= 1 || target.samples != 1 {
+            return Err(GfxError::InvalidDesc(format!(
+                "resolving needs a multisampled attachment and a single-sampled target, \
+                 not {} and {} samples",
+                source.samples, target.samples
+            )));
+        }
+        // Multisampled textures have a single mip level
+        let shape = |desc: &TextureDesc| {
+            let size = (desc.width, desc.height, desc.array_layers, desc.mip_levels);
+            (desc.dimension, desc.format, size)
+        };
+        if shape(source) != shape(target) {
+            return Err(GfxError::InvalidDesc(
+                "a resolve target needs the format, size and mip count of its attachment"
+                    .to_string(),
+            ));
+        }
+        if !target.usage.contains(TextureUsage::COLOR_ATTACHMENT) {
+            return Err(GfxError::InvalidDesc(
+                "a resolve target needs TextureUsage::COLOR_ATTACHMENT".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks every backend needs, so they fail the same way
+fn validate_texture(desc: &TextureDesc) -> Result<(), GfxError> {
+    let invalid = |reason: &str| Err(GfxError::InvalidDesc(reason.to_string()));
+    if desc.width == 0 || desc.height == 0 || desc.depth == 0 || desc.array_layers == 0 {
+        return invalid("texture size is zero");
+    }
+    let max_mips = 32 - desc.width.max(desc.height).max(desc.depth).leading_zeros();
+    if desc.mip_levels == 0 || desc.mip_levels > max_mips {
+        return Err(GfxError::InvalidDesc(format!(
+            "{} mip levels requested, a {}x{}x{} texture has at most {}",
+            desc.mip_levels, desc.width, desc.height, desc.depth, max_mips
+        )));
+    }
+    if !desc.samples.is_power_of_two() || desc.samples > 64 {
+        return invalid("sample count must be a power of two up to 64");
+    }
+    if desc.samples > 1 && (desc.mip_levels > 1 || desc.dimension != TextureDimension::D2) {
+        return invalid("multisampled textures must be 2D with a single mip level");
+    }
+    if desc.dimension == TextureDimension::Cube && desc.width != desc.height {
+        return invalid("cube map faces must be square");
+    }
+    if desc.format.is_compressed()
+        && (desc.usage.contains(TextureUsage::COLOR_ATTACHMENT)
+            || desc.usage.contains(TextureUsage::DEPTH_ATTACHMENT))
+    {
+        return invalid("compressed formats cannot be render targets");
+    }
+    if desc.format.is_depth() && desc.usage.contains(TextureUsage::COLOR_ATTACHMENT) {
+        return invalid("depth formats cannot be color attachments");
+    }
+    if !desc.format.is_depth() && desc.usage.contains(TextureUsage::DEPTH_ATTACHMENT) {
+        return invalid("depth attachments need a depth format");
+    }
+    Ok(())
+}
+
+/// Checks an upload of `len` bytes against the texture it targets
+fn validate_texture_update(
+    desc: &TextureDesc,
+    mip: u32,
+    layer: u32,
+    region: &TextureRegion,
+    len: usize,
+) -> Result<(), GfxError> {
+    let invalid = |reason: String| Err(GfxError::InvalidDesc(reason));
+    if !desc.usage.contains(TextureUsage::TRANSFER_DST) {
+        return invalid("texture was not created with TRANSFER_DST".to_string());
+    }
+    if desc.format.is_depth() || desc.samples > 1 {
+        return invalid("depth and multisampled textures are written by render passes".to_string());
+    }
+    if mip >= desc.mip_levels || layer >= layer_count(desc) {
+        return invalid(format!(
+            "mip {} layer {} is outside a texture with {} mips and {} layers",
+            mip,
+            layer,
+            desc.mip_levels,
+            layer_count(desc)
+        ));
+    }
+    let extent = TextureRegion::full(desc, mip);
+    let fits = |offset: u32, size: u32, limit: u32| {
+        size > 0 && offset.checked_add(size).is_some_and(|end| end <= limit)
+    };
+    if !fits(region.x, region.width, extent.width)
+        || !fits(region.y, region.height, extent.height)
+        || !fits(region.z, region.depth, extent.depth)
+    {
+        return invalid(format!(
+            "region {:?} is empty or outside the {}x{}x{} mip {}",
+            region, extent.width, extent.height, extent.depth, mip
+        ));
+    }
+    if desc.format.is_compressed() {
+        // Whole blocks only, except where the mip ends mid-block
+        let aligned = |offset: u32, size: u32, limit: u32| {
+            offset.is_multiple_of(4)
+                && ((offset + size).is_multiple_of(4) || offset + size == limit)
+        };
+        if !aligned(region.x, region.width, extent.width)
+            || !aligned(region.y, region.height, extent.height)
+        {
+            return invalid(format!("region {:?} is not aligned to 4x4 blocks", region));
+        }
+    }
+    let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+    let expected = row_bytes * rows * region.depth as usize;
+    if len != expected {
+        return invalid(format!(
+            "{} bytes of texel data for a region of {} bytes",
+            len, expected
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a copy of `size` bytes between two buffers, given as the size of
+/// each and the offset in it
+fn validate_buffer_copy(ranges: [(usize, u64); 2], size: u64) -> Result<(), GfxError> {
+    let invalid = |reason: String| Err(GfxError::InvalidDesc(reason));
+    for (buffer_size, offset) in ranges {
+        if !offset.is_multiple_of(4) || !size.is_multiple_of(4) {
+            return invalid(format!(
+                "{} bytes at {} are not aligned to 4 bytes",
+                size, offset
+            ));
+        }
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > buffer_size as u64)
+        {
+            return invalid(format!(
+                "{} bytes at {} overflow a {} byte buffer",
+                size, offset, buffer_size
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a copy from a buffer of `buffer_size` bytes into a texture
+fn validate_texture_copy(
+    desc: &TextureDesc,
+    buffer_size: usize,
+    layout: TexelBufferLayout,
+    mip: u32,
+    layer: u32,
+    region: &TextureRegion,
+) -> Result<(), GfxError> {
+    let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+    let size = row_bytes * rows * region.depth as usize;
+    validate_texture_update(desc, mip, layer, region, size)?;
+    let invalid = |reason: String| Err(GfxError::InvalidDesc(reason));
+    let pitch = layout.bytes_per_row;
+    if !pitch.is_multiple_of(COPY_ROW_ALIGNMENT) || (pitch as usize) < row_bytes {
+        return invalid(format!(
+            "rows {} bytes apart are not aligned to {} bytes or overlap rows of {} bytes",
+            pitch, COPY_ROW_ALIGNMENT, row_bytes
+        ));
+    }
+    if !layout
+        .offset
+        .is_multiple_of(desc.format.bytes_per_pixel() as u64)
+    {
+        return invalid(format!(
+            "texels at {} are not aligned to {} bytes",
+            layout.offset,
+            desc.format.bytes_per_pixel()
+        ));
+    }
+    // The last row ends the copy, without the padding of the others
+    let span = pitch as u64 * (rows as u64 * region.depth as u64 - 1) + row_bytes as u64;
+    if layout
+        .offset
+        .checked_add(span)
+        .is_none_or(|end| end > buffer_size as u64)
+    {
+        return invalid(format!(
+            "{} bytes of texels at {} overflow a {} byte buffer",
+            span, layout.offset, buffer_size
+        ));
+    }
+    Ok(())
+}
+
+fn validate_mipmaps(desc: &TextureDesc) -> Result<(), GfxError> {
+    if !matches!(
+        desc.dimension,
+        TextureDimension::D2 | TextureDimension::Cube
+    ) {
+        return Err(GfxError::InvalidDesc(format!(
+            "mipmaps cannot be generated for {:?} textures",
+            desc.dimension
+        )));
+    }
+    if desc.format.is_compressed() || desc.format.is_depth() || desc.samples > 1 {
+        return Err(GfxError::InvalidDesc(format!(
+            "mipmaps cannot be generated for {:?} textures with {} samples",
+            desc.format, desc.samples
+        )));
+    }
+    Ok(())
+}
+
+/// Convert color texels to RGBA8 for a PNG
+///
+/// Float formats hold linear values and are encoded as sRGB.
+fn rgba8_pixels(format: TextureFormat, pixels: &[u8]) -> Option<Vec<u8>> {
+    match format {
+        TextureFormat::Rgba8 | TextureFormat::Rgba8Srgb => Some(pixels.to_vec()),
+        TextureFormat::Bgra8 => Some(
+            pixels
+                .chunks_exact(4)
+                .flat_map(|texel| [texel[2], texel[1], texel[0], texel[3]])
+                .collect(),
+        ),
+        TextureFormat::Rgba16f | TextureFormat::Rgba32f => {
+            let size = format.bytes_per_pixel() as usize;
+            let mut rgba = vec![0; pixels.len() / size * 4];
+            for (texel, out) in pixels.chunks_exact(size).zip(rgba.chunks_exact_mut(4)) {
+                let color = software::read_color(format, texel);
+                software::write_color(TextureFormat::Rgba8Srgb, out, color);
+            }
+            Some(rgba)
+        }
+        _ => None,
+    }
+}
+
+/// Array layers of a texture; a cube map has at least its six faces
+pub(crate) fn layer_count(desc: &TextureDesc) -> u32 {
+    match desc.dimension {
+        TextureDimension::Cube => desc.array_layers.max(6),
+        TextureDimension::D3 => 1,
+        _ => desc.array_layers,
+    }
+}
+
+/// Bytes of device memory a texture needs: every mip of every layer and
+/// sample, saturating for sizes no device has
+pub(crate) fn texture_size(desc: &TextureDesc) -> u64 {
+    let depth = if desc.dimension == TextureDimension::D3 {
+        desc.depth
+    } else {
+        1
+    };
+    let mip_size: u64 = (0..desc.mip_levels)
+        .map(|mip| {
+            let (row, rows) = region_layout(
+                desc.format,
+                (desc.width >> mip).max(1),
+                (desc.height >> mip).max(1),
+            );
+            (row as u64)
+                .saturating_mul(rows as u64)
+                .saturating_mul((depth >> mip).max(1) as u64)
+        })
+        .fold(0, u64::saturating_add);
+    mip_size
+        .saturating_mul(layer_count(desc) as u64)
+        .saturating_mul(desc.samples.max(1) as u64)
+}
+
+/// Bytes per row and number of rows of a `width` x `height` image; compressed
+/// formats count rows of 4x4 blocks
+pub(crate) fn region_layout(format: TextureFormat, width: u32, height: u32) -> (usize, usize) {
+    let bytes = format.bytes_per_pixel() as usize;
+    if format.is_compressed() {
+        (
+            width.div_ceil(4) as usize * bytes,
+            height.div_ceil(4) as usize,
+        )
+    } else {
+        (width as usize * bytes, height as usize)
+    }
+}
+
+fn validate_pipeline(
+    desc: &PipelineDesc,
+    shaders: &ResourcePool<ShaderResource>,
+    bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+) -> Result<(), GfxError> {
+    for (handle, stage) in [
+        (desc.vertex_shader, ShaderStage::Vertex),
+        (desc.fragment_shader, ShaderStage::Fragment),
+    ] {
+        match shaders.get(handle.0) {
+            Some(shader) if shader.desc.stage == stage => {}
+            Some(shader) => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "{:?} shader bound as the {:?} stage",
+                    shader.desc.stage, stage
+                )))
+            }
+            None => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "unknown {:?} shader {:?}",
+                    stage, handle
+                )))
+            }
+        }
+    }
+    let mut locations = HashSet::new();
+    for (stride, _, attributes) in desc.vertex_layout.slots() {
+        for attribute in attributes {
+            if stride != 0 && attribute.offset + attribute.format.size() > stride {
+                return Err(GfxError::InvalidDesc(format!(
+                    "vertex attribute {} ends past the {} byte stride",
+                    attribute.location, stride
+                )));
+            }
+            if !locations.insert(attribute.location) {
+                return Err(GfxError::InvalidDesc(format!(
+                    "vertex location {} is fed by more than one attribute",
+                    attribute.location
+                )));
+            }
+        }
+    }
+
+    if !desc.sample_count.is_power_of_two() || desc.sample_count > 64 {
+        return Err(GfxError::InvalidDesc(
+            "sample count must be a power of two up to 64".to_string(),
+        ));
+    }
+
+    validate_layout(&desc.layout, bind_group_layouts)?;
+    for handle in [desc.vertex_shader, desc.fragment_shader] {
+        if let Some(reflection) = shaders.get(handle.0).and_then(|s| s.reflection.as_ref()) {
+            reflection.validate_vertex_layout(&desc.vertex_layout)?;
+            validate_shader_bindings(reflection, &desc.layout, bind_group_layouts)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_compute_pipeline(
+    desc: &ComputePipelineDesc,
+    shaders: &ResourcePool<ShaderResource>,
+    bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+) -> Result<(), GfxError> {
+    match shaders.get(desc.shader.0) {
+        Some(shader) if shader.desc.stage == ShaderStage::Compute => {}
+        Some(shader) => {
+            return Err(GfxError::InvalidDesc(format!(
+                "{:?} shader bound as the Compute stage",
+                shader.desc.stage
+            )))
+        }
+        None => {
+            return Err(GfxError::InvalidDesc(format!(
+                "unknown Compute shader {:?}",
+                desc.shader
+            )))
+        }
+    }
+    validate_layout(&desc.layout, bind_group_layouts)?;
+    if let Some(reflection) = shaders
+        .get(desc.shader.0)
+        .and_then(|s| s.reflection.as_ref())
+    {
+        validate_shader_bindings(reflection, &desc.layout, bind_group_layouts)?;
+    }
+    Ok(())
+}
+
+/// Check that `layout` has a slot of the right type for every resource a
+/// SPIR-V shader binds
+fn validate_shader_bindings(
+    reflection: &ShaderReflection,
+    layout: &PipelineLayoutDesc,
+    bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+) -> Result<(), GfxError> {
+    for binding in &reflection.bindings {
+        let slot = if (binding.set as usize) < layout.bind_groups.len() {
+            bind_group_layouts
+                .get(layout.bind_groups[binding.set as usize].0)
+                .and_then(|group| {
+                    group
+                        .desc
+                        .entries
+                        .iter()
+                        .find(|entry| entry.binding == binding.binding)
+                })
+                .map(|entry| entry.ty)
+        } else {
+            // Sets past the bind groups hold textures and their samplers
+            layout.textures.iter().find_map(|texture| {
+                if texture.set != binding.set {
+                    None
+                } else if texture.binding == binding.binding {
+                    Some(BindingType::Texture {
+                        dimension: texture.dimension,
+                        array: texture.array,
+                        comparison: texture.comparison,
+                    })
+                } else if texture.binding + 1 == binding.binding {
+                    Some(BindingType::Sampler {
+                        comparison: texture.comparison,
+                    })
+                } else {
+                    None
+                }
+            })
+        };
+        match slot {
+            Some(ty) if binding.fits(ty) => {}
+            Some(ty) => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "binding {} of set {} is {:?} in the pipeline layout but {:?} in the {:?} shader",
+                    binding.binding, binding.set, ty, binding.ty, reflection.stage
+                )))
+            }
+            None => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "{:?} shader binds binding {} of set {}, which the pipeline layout does not provide",
+                    reflection.stage, binding.binding, binding.set
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks of a pipeline layout shared by render and compute pipelines
+fn validate_layout(
+    layout: &PipelineLayoutDesc,
+    bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+) -> Result<(), GfxError> {
+    let group_count = layout.bind_groups.len() as u32;
+    if group_count > MAX_BIND_SETS {
+        return Err(GfxError::InvalidDesc(format!(
+            "{} bind group layouts; sets go up to {}",
+            group_count,
+            MAX_BIND_SETS - 1
+        )));
+    }
+    if let Some(group) = layout
+        .bind_groups
+        .iter()
+        .find(|group| bind_group_layouts.get(group.0).is_none())
+    {
+        return Err(GfxError::InvalidDesc(format!(
+            "unknown bind group layout {:?}",
+            group
+        )));
+    }
+
+    let textures = &layout.textures;
+    for (i, texture) in textures.iter().enumerate() {
+        if texture.set >= MAX_BIND_SETS {
+            return Err(GfxError::InvalidDesc(format!(
+                "texture binding in set {}; sets go up to {}",
+                texture.set,
+                MAX_BIND_SETS - 1
+            )));
+        }
+        if texture.set < group_count {
+            return Err(GfxError::InvalidDesc(format!(
+                "texture binding in set {}, which a bind group layout describes",
+                texture.set
+            )));
+        }
+        if texture.array && texture.dimension != TextureDimension::D2 {
+            return Err(GfxError::InvalidDesc(format!(
+                "{:?} texture binding {} cannot be an array",
+                texture.dimension, texture.binding
+            )));
+        }
+        // The sampler may take the binding after the texture
+        if let Some(other) = textures[..i]
+            .iter()
+            .find(|other| other.set == texture.set && other.binding.abs_diff(texture.binding) < 2)
+        {
+            return Err(GfxError::InvalidDesc(format!(
+                "texture bindings {} and {} of set {} overlap with their samplers",
+                other.binding, texture.binding, texture.set
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_bind_group_layout(desc: &BindGroupLayoutDesc) -> Result<(), GfxError> {
+    for (i, entry) in desc.entries.iter().enumerate() {
+        if desc.entries[..i]
+            .iter()
+            .any(|other| other.binding == entry.binding)
+        {
+            return Err(GfxError::InvalidDesc(format!(
+                "binding {} appears twice in the bind group layout",
+                entry.binding
+            )));
+        }
+        match entry.ty {
+            BindingType::Texture {
+                dimension, array, ..
+            } if array && dimension != TextureDimension::D2 => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "{:?} texture binding {} cannot be an array",
+                    dimension, entry.binding
+                )));
+            }
+            BindingType::StorageTexture {
+                format, dimension, ..
+            } if dimension == TextureDimension::Cube
+                || !matches!(
+                    format,
+                    TextureFormat::Rgba8 | TextureFormat::Rgba16f | TextureFormat::Rgba32f
+                ) =>
+            {
+                return Err(GfxError::InvalidDesc(format!(
+                    "storage texture binding {} cannot be a {:?} {:?} texture",
+                    entry.binding, format, dimension
+                )));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check `desc` against its layout and resolve its handles, in layout order
+/// Resources a bind group hands to shaders, and how they are used
+fn bind_group_uses(
+    desc: &BindGroupDesc,
+    layout: &BindGroupLayoutDesc,
+) -> Vec<(TrackedResource, ResourceState)> {
+    let mut uses = Vec::new();
+    for entry in &desc.entries {
+        let Some(slot) = layout
+            .entries
+            .iter()
+            .find(|slot| slot.binding == entry.binding)
+        else {
+            continue;
+        };
+        let state = match slot.ty {
+            BindingType::StorageBuffer { read_only: false }
+            | BindingType::StorageTexture {
+                access: StorageTextureAccess::WriteOnly | StorageTextureAccess::ReadWrite,
+                ..
+            } => ResourceState::ShaderWrite,
+            _ => ResourceState::ShaderRead,
+        };
+        match entry.resource {
+            BindingResource::Buffer { buffer, .. } => {
+                add_use(&mut uses, TrackedResource::Buffer(buffer), state)
+            }
+            BindingResource::Texture(texture) => {
+                add_use(&mut uses, TrackedResource::Texture(texture), state)
+            }
+            BindingResource::Sampler(_) => {}
+        }
+    }
+    uses
+}
+
+/// Add a use of `resource`, merging it with an earlier use in the same pass
+fn add_use(
+    uses: &mut Vec<(TrackedResource, ResourceState)>,
+    resource: TrackedResource,
+    state: ResourceState,
+) {
+    match uses.iter_mut().find(|(tracked, _)| *tracked == resource) {
+        Some((_, earlier)) => *earlier = earlier.merge(state),
+        None => uses.push((resource, state)),
+    }
+}
+
+fn resolve_bind_group(
+    desc: &BindGroupDesc,
+    layout: &BindGroupLayoutDesc,
+    textures: &ResourcePool<TextureResource>,
+    buffers: &ResourcePool<BufferResource>,
+    samplers: &ResourcePool<SamplerResource>,
+) -> Result<Vec<NativeBindGroupEntry>, GfxError> {
+    if let Some(entry) = desc.entries.iter().find(|entry| {
+        !layout
+            .entries
+            .iter()
+            .any(|slot| slot.binding == entry.binding)
+    }) {
+        return Err(GfxError::InvalidDesc(format!(
+            "binding {} is not in the bind group layout",
+            entry.binding
+        )));
+    }
+
+    let mut resolved = Vec::with_capacity(layout.entries.len());
+    for slot in &layout.entries {
+        let binding = slot.binding;
+        let mut matching = desc.entries.iter().filter(|entry| entry.binding == binding);
+        let entry = match (matching.next(), matching.next()) {
+            (Some(entry), None) => entry,
+            (None, _) => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "binding {} of the layout has no resource",
+                    binding
+                )))
+            }
+            _ => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "binding {} has more than one resource",
+                    binding
+                )))
+            }
+        };
+
+        let resource = match (slot.ty, entry.resource) {
+            (
+                BindingType::UniformBuffer | BindingType::StorageBuffer { .. },
+                BindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                },
+            ) => {
+                let Some(resource) = buffers.get(buffer.0) else {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "unknown buffer {:?} at binding {}",
+                        buffer, binding
+                    )));
+                };
+                let usage = match slot.ty {
+                    BindingType::UniformBuffer => BufferUsage::Uniform,
+                    _ => BufferUsage::Storage,
+                };
+                if resource.desc.usage != usage {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "{:?} buffer at binding {} needs BufferUsage::{:?}",
+                        resource.desc.usage, binding, usage
+                    )));
+                }
+                let buffer_size = resource.desc.size as u64;
+                let size = size.unwrap_or(buffer_size.saturating_sub(offset));
+                if size == 0 || offset.saturating_add(size) > buffer_size {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "range of {} bytes at {} of binding {} is empty or past the {} byte buffer",
+                        size, offset, binding, buffer_size
+                    )));
+                }
+                NativeBindingResource::Buffer {
+                    buffer: resource.native,
+                    offset,
+                    size,
+                }
+            }
+            (
+                BindingType::Texture {
+                    dimension,
+                    array,
+                    comparison,
+                },
+                BindingResource::Texture(texture),
+            ) => {
+                let Some(resource) = textures.get(texture.0) else {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "unknown texture {:?} at binding {}",
+                        texture, binding
+                    )));
+                };
+                let texture = &resource.desc;
+                if !texture.usage.contains(TextureUsage::SAMPLED) {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "texture at binding {} lacks TextureUsage::SAMPLED",
+                        binding
+                    )));
+                }
+                let layered = texture.dimension == TextureDimension::D2 && texture.array_layers > 1;
+                if texture.dimension != dimension || layered != array || texture.samples > 1 {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "texture at binding {} does not match the layout's {:?}{} binding",
+                        binding,
+                        dimension,
+                        if array { " array" } else { "" }
+                    )));
+                }
+                if comparison && !texture.format.is_depth() {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "comparison binding {} needs a depth texture, not {:?}",
+                        binding, texture.format
+                    )));
+                }
+                NativeBindingResource::Texture(resource.native)
+            }
+            (
+                BindingType::StorageTexture {
+                    format, dimension, ..
+                },
+                BindingResource::Texture(texture),
+            ) => {
+                let Some(resource) = textures.get(texture.0) else {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "unknown texture {:?} at binding {}",
+                        texture, binding
+                    )));
+                };
+                let texture = &resource.desc;
+                if !texture.usage.contains(TextureUsage::STORAGE) {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "texture at binding {} lacks TextureUsage::STORAGE",
+                        binding
+                    )));
+                }
+                if texture.format != format
+                    || texture.dimension != dimension
+                    || layer_count(texture) > 1
+                    || texture.samples > 1
+                {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "texture at binding {} does not match the layout's {:?} {:?} storage binding",
+                        binding, dimension, format
+                    )));
+                }
+                NativeBindingResource::Texture(resource.native)
+            }
+            (BindingType::Sampler { comparison }, BindingResource::Sampler(sampler)) => {
+                let Some(resource) = samplers.get(sampler.0) else {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "unknown sampler {:?} at binding {}",
+                        sampler, binding
+                    )));
+                };
+                if resource.desc.compare.is_some() != comparison {
+                    return Err(GfxError::InvalidDesc(format!(
+                        "sampler at binding {} {} a compare function",
+                        binding,
+                        if comparison { "needs" } else { "cannot have" }
+                    )));
+                }
+                NativeBindingResource::Sampler(resource.native)
+            }
+            (ty, resource) => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "binding {} takes {:?}, not {:?}",
+                    binding, ty, resource
+                )))
+            }
+        };
+        resolved.push(NativeBindGroupEntry { binding, resource });
+    }
+    Ok(resolved)
+}
+
+fn validate_sampler(desc: &SamplerDesc) -> Result<(), GfxError> {
+    if !(1..=16).contains(&desc.max_anisotropy) {
+        return Err(GfxError::InvalidDesc(format!(
+            "max anisotropy {} is outside 1..=16",
+            desc.max_anisotropy
+        )));
+    }
+    let linear = [desc.mag_filter, desc.min_filter, desc.mip_filter]
+        .iter()
+        .all(|filter| *filter == FilterMode::Linear);
+    if desc.max_anisotropy > 1 && !linear {
+        return Err(GfxError::InvalidDesc(
+            "anisotropic filtering needs linear filters".to_string(),
+        ));
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -252,16 +2191,23 @@ impl GpuDevice for BackendDevice {
 struct TextureResource {
     desc: TextureDesc,
     native: NativeTexture,
+    /// `None` for swapchain images, which the swapchain owns
+    memory: Option<Allocation>,
+    state: ResourceState,
 }
 
 struct BufferResource {
     desc: BufferDesc,
     native: NativeBuffer,
+    memory: Allocation,
+    state: ResourceState,
 }
 
 struct ShaderResource {
     desc: ShaderDesc,
     native: NativeShader,
+    /// What the shader declares, for SPIR-V
+    reflection: Option<ShaderReflection>,
 }
 
 struct PipelineResource {
@@ -269,6 +2215,112 @@ struct PipelineResource {
     native: NativePipeline,
 }
 
+struct ComputePipelineResource {
+    native: NativePipeline,
+}
+
+struct SamplerResource {
+    desc: SamplerDesc,
+    native: NativeSampler,
+}
+
+struct BindGroupLayoutResource {
+    desc: BindGroupLayoutDesc,
+    native: NativeBindGroupLayout,
+}
+
+struct BindGroupResource {
+    native: NativeBindGroup,
+    /// Textures and buffers in the group, for state tracking
+    uses: Vec<(TrackedResource, ResourceState)>,
+}
+
+/// How a texture or buffer was last used
+///
+/// Moving to another state, or writing from shaders again, is a hazard that
+/// needs a barrier on APIs that do not track resources themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResourceState {
+    /// Contents not needed: new textures and buffers
+    Undefined,
+    ColorAttachment,
+    DepthAttachment,
+    /// Vertex or index buffer
+    VertexInput,
+    /// Sampled textures, uniform buffers, read-only storage
+    ShaderRead,
+    /// Writable storage buffers and textures
+    ShaderWrite,
+    TransferSrc,
+    TransferDst,
+    /// Mapped for the CPU
+    HostAccess,
+    Present,
+}
+
+impl ResourceState {
+    /// One state for two uses in the same pass: writes win over reads, and
+    /// shader reads over vertex input
+    fn merge(self, other: Self) -> Self {
+        let rank = |state: Self| match state {
+            ResourceState::ShaderWrite => 3,
+            ResourceState::ColorAttachment | ResourceState::DepthAttachment => 2,
+            ResourceState::ShaderRead => 1,
+            _ => 0,
+        };
+        if rank(other) > rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TrackedResource {
+    Texture(TextureHandle),
+    Buffer(BufferHandle),
+}
+
+/// Transition of one resource between two uses
+#[derive(Clone, Copy)]
+struct Barrier {
+    resource: NativeResource,
+    before: ResourceState,
+    after: ResourceState,
+}
+
+#[derive(Clone, Copy)]
+enum NativeResource {
+    Texture(NativeTexture),
+    Buffer(NativeBuffer),
+}
+
+/// Presentation state: the images are ordinary entries in the texture pool
+/// Timestamp queries of one frame in flight
+struct TimestampFrame {
+    pool: NativeQueryPool,
+    /// Frame the names belong to
+    frame: u64,
+    /// Name of each query written, in order
+    names: Vec<String>,
+    /// Resolved at `present`, waiting to be read back
+    pending: bool,
+}
+
+struct Swapchain {
+    window: Option<NativeHandle>,
+    surface: Option<NativeSurface>,
+    native: Option<NativeSwapchain>,
+    images: Vec<TextureHandle>,
+    /// Index of the image acquired for the current frame
+    current_image: u32,
+    /// Multisampled target resolved into the images, with MSAA on
+    msaa_texture: Option<TextureHandle>,
+    /// The window is minimized: no image is acquired or presented
+    suspended: bool,
+}
+
 /// Generic resource pool with slot allocation
 struct ResourcePool<T> {
     resources: HashMap<u32, T>,
@@ -317,154 +2369,287 @@ impl<T> ResourcePool<T> {
 }
 
 // ============================================================================
-// Native API Stubs (to be implemented per backend)
+// Native Backend Interface
 // ============================================================================
 
-/// Native device wrapper
+/// Native side of a backend: translates resources and commands to one API
 ///
-/// TODO: Implement per backend:
-/// - Vulkan: VkInstance, VkDevice, VkQueue, VkSwapchain, VkCommandPool
-/// - D3D12: ID3D12Device, ID3D12CommandQueue, IDXGISwapChain
-/// - Metal: MTLDevice, MTLCommandQueue
-/// - OpenGL: Context, FBO management
-struct NativeDevice {
-    // Backend-specific fields will go here
-}
-
-impl NativeDevice {
-    fn create(_config: &RendererConfig) -> Self {
-        // TODO: Initialize native graphics API
-        println!("Creating native device (stub)");
-        Self {}
-    }
-
-    fn create_texture_native(&mut self, desc: &TextureDesc) -> NativeTexture {
-        println!(
-            "Creating texture: {}x{} {:?}",
-            desc.width, desc.height, desc.format
-        );
-        NativeTexture { handle: 0 }
-    }
-
-    fn create_buffer_native(&mut self, desc: &BufferDesc, _data: Option<&[u8]>) -> NativeBuffer {
-        println!("Creating buffer: {} bytes, {:?}", desc.size, desc.usage);
-        NativeBuffer { handle: 0 }
-    }
-
-    fn create_shader_native(&mut self, desc: &ShaderDesc) -> NativeShader {
-        println!(
-            "Creating shader: {:?}, {} bytes",
-            desc.stage,
-            desc.code.len()
-        );
-        NativeShader { handle: 0 }
-    }
+/// `BackendDevice` owns the handle pools and the swapchain bookkeeping; an
+/// implementation only creates native objects and replays commands.
+trait NativeDevice {
+    fn adapter_info_native(&self) -> AdapterInfo;
 
+    fn create_texture_native(&mut self, desc: &TextureDesc) -> Result<NativeTexture, GfxError>;
+    fn create_buffer_native(
+        &mut self,
+        desc: &BufferDesc,
+        data: Option<&[u8]>,
+    ) -> Result<NativeBuffer, GfxError>;
+    fn create_shader_native(&mut self, desc: &ShaderDesc) -> Result<NativeShader, GfxError>;
     fn create_pipeline_native(
         &mut self,
-        _desc: &PipelineDesc,
-        _shaders: &ResourcePool<ShaderResource>,
-    ) -> NativePipeline {
-        println!("Creating pipeline (stub)");
-        NativePipeline { handle: 0 }
-    }
-
-    fn destroy_texture_native(&mut self, _texture: NativeTexture) {}
-    fn destroy_buffer_native(&mut self, _buffer: NativeBuffer) {}
-    fn destroy_shader_native(&mut self, _shader: NativeShader) {}
-    fn destroy_pipeline_native(&mut self, _pipeline: NativePipeline) {}
-
-    fn update_buffer_native(&mut self, _buffer: NativeBuffer, _offset: usize, data: &[u8]) {
-        println!("Updating buffer with {} bytes", data.len());
-    }
-
-    fn map_buffer_native(&mut self, _buffer: NativeBuffer) -> *mut u8 {
-        std::ptr::null_mut()
-    }
-
-    fn unmap_buffer_native(&mut self, _buffer: NativeBuffer) {}
+        desc: &PipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError>;
+    fn create_compute_pipeline_native(
+        &mut self,
+        desc: &ComputePipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError>;
+    fn create_sampler_native(&mut self, desc: &SamplerDesc) -> Result<NativeSampler, GfxError>;
+    fn create_bind_group_layout_native(
+        &mut self,
+        desc: &BindGroupLayoutDesc,
+    ) -> Result<NativeBindGroupLayout, GfxError>;
+    /// `entries` follow the order of `desc.entries`
+    fn create_bind_group_native(
+        &mut self,
+        layout: NativeBindGroupLayout,
+        desc: &BindGroupLayoutDesc,
+        entries: &[NativeBindGroupEntry],
+    ) -> Result<NativeBindGroup, GfxError>;
+
+    fn destroy_texture_native(&mut self, texture: NativeTexture);
+    fn destroy_buffer_native(&mut self, buffer: NativeBuffer);
+    fn destroy_shader_native(&mut self, shader: NativeShader);
+    fn destroy_pipeline_native(&mut self, pipeline: NativePipeline);
+    fn destroy_compute_pipeline_native(&mut self, pipeline: NativePipeline);
+    fn destroy_sampler_native(&mut self, sampler: NativeSampler);
+    fn destroy_bind_group_layout_native(&mut self, layout: NativeBindGroupLayout);
+    fn destroy_bind_group_native(&mut self, group: NativeBindGroup);
+
+    fn update_buffer_native(&mut self, buffer: NativeBuffer, offset: usize, data: &[u8]);
+    fn map_buffer_native(&mut self, buffer: NativeBuffer) -> *mut u8;
+    fn unmap_buffer_native(&mut self, buffer: NativeBuffer);
+
+    /// Upload texels; the request has been validated against the description
+    fn update_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError>;
+
+    /// Fill mips 1 and up of every layer from mip 0, after pending work;
+    /// `desc` has passed `validate_mipmaps` and has several mip levels
+    fn generate_mipmaps_native(
+        &mut self,
+        texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<(), GfxError>;
 
-    fn begin_frame_native(&mut self) {
-        println!("Begin frame");
-    }
+    /// Contents of mip 0 of every layer, once pending work has finished
+    fn read_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<Vec<u8>, GfxError>;
 
+    fn begin_frame_native(&mut self);
     fn begin_render_pass_native(
         &mut self,
-        _desc: &RenderPassDesc,
-        _textures: &ResourcePool<TextureResource>,
-    ) {
-        println!("Begin render pass");
-    }
-
-    fn end_render_pass_native(&mut self) {
-        println!("End render pass");
-    }
-
-    fn bind_pipeline_native(&mut self, _pipeline: NativePipeline) {
-        println!("Bind pipeline");
-    }
-
-    fn set_viewport_native(&mut self, viewport: &Viewport) {
-        println!("Set viewport: {}x{}", viewport.width, viewport.height);
-    }
-
-    fn set_scissor_native(&mut self, _scissor: &Rect) {
-        println!("Set scissor");
-    }
-
-    fn bind_vertex_buffer_native(&mut self, slot: u32, _buffer: NativeBuffer, _offset: u64) {
-        println!("Bind vertex buffer at slot {}", slot);
-    }
-
+        desc: &RenderPassDesc,
+        textures: &ResourcePool<TextureResource>,
+    );
+    fn end_render_pass_native(&mut self);
+    fn bind_pipeline_native(&mut self, pipeline: NativePipeline);
+    fn set_viewport_native(&mut self, viewport: &Viewport);
+    fn set_scissor_native(&mut self, scissor: &Rect);
+    fn bind_vertex_buffer_native(&mut self, slot: u32, buffer: NativeBuffer, offset: u64);
     fn bind_index_buffer_native(
         &mut self,
-        _buffer: NativeBuffer,
-        _offset: u64,
-        _index_type: IndexType,
-    ) {
-        println!("Bind index buffer");
-    }
-
+        buffer: NativeBuffer,
+        offset: u64,
+        index_type: IndexType,
+    );
+    fn bind_group_native(&mut self, index: u32, group: NativeBindGroup);
+    fn bind_texture_native(
+        &mut self,
+        set: u32,
+        binding: u32,
+        texture: NativeTexture,
+        sampler: NativeSampler,
+    );
     fn draw_native(
         &mut self,
         vertex_count: u32,
         instance_count: u32,
-        _first_vertex: u32,
-        _first_instance: u32,
-    ) {
-        println!(
-            "Draw: {} vertices, {} instances",
-            vertex_count, instance_count
-        );
-    }
-
+        first_vertex: u32,
+        first_instance: u32,
+    );
     fn draw_indexed_native(
         &mut self,
         index_count: u32,
         instance_count: u32,
-        _first_index: u32,
-        _vertex_offset: i32,
-        _first_instance: u32,
-    ) {
-        println!(
-            "Draw indexed: {} indices, {} instances",
-            index_count, instance_count
-        );
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    );
+    /// Only called outside render passes
+    fn bind_compute_pipeline_native(&mut self, pipeline: NativePipeline);
+    /// Only called outside render passes, with every count above zero
+    fn dispatch_native(&mut self, x: u32, y: u32, z: u32);
+
+    /// `GfxError::Unsupported` when the backend cannot present to `window`
+    fn create_surface_native(&mut self, window: &NativeHandle) -> Result<NativeSurface, GfxError>;
+    fn create_swapchain_native(
+        &mut self,
+        surface: Option<NativeSurface>,
+        desc: &TextureDesc,
+        image_count: u32,
+        present_mode: PresentMode,
+    ) -> NativeSwapchain;
+    fn swapchain_images_native(&mut self, swapchain: &NativeSwapchain) -> Vec<NativeTexture>;
+    /// Index of the next image; `None` when the surface no longer matches
+    /// the swapchain (resized, or lost by the window system) and the
+    /// swapchain must be recreated
+    fn acquire_next_image_native(&mut self, swapchain: &mut NativeSwapchain) -> Option<u32>;
+    fn destroy_swapchain_native(&mut self, swapchain: NativeSwapchain);
+    fn present_native(&mut self, swapchain: &NativeSwapchain, image_index: u32);
+
+    fn wait_idle_native(&mut self);
+
+    /// Whether the device was lost (driver reset, GPU removed); the device
+    /// is then dropped and created again
+    ///
+    /// Backends that cannot lose their device need nothing here.
+    fn device_lost_native(&mut self) -> bool {
+        false
     }
 
-    fn present_native(&mut self) {
-        println!("Present");
+    /// Copy between two buffers outside render passes; the ranges have
+    /// passed `validate_buffer_copy`
+    fn copy_buffer_native(
+        &mut self,
+        src: NativeBuffer,
+        src_offset: u64,
+        dst: NativeBuffer,
+        dst_offset: u64,
+        size: u64,
+    );
+    /// Copy texels from a buffer outside render passes; the copy has passed
+    /// `validate_texture_copy`
+    fn copy_buffer_to_texture_native(
+        &mut self,
+        src: NativeBuffer,
+        layout: TexelBufferLayout,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+    ) -> Result<(), GfxError>;
+
+    /// Mark the work submitted so far with `value`, larger than any before
+    ///
+    /// Backends that finish work before `submit_native` returns need
+    /// nothing here.
+    fn signal_fence_native(&mut self, _value: u64) {}
+    /// Largest fence value whose work has finished; never blocks
+    fn completed_fence_native(&mut self) -> u64 {
+        u64::MAX
+    }
+    /// Block until `completed_fence_native` reaches `value`
+    fn wait_fence_native(&mut self, _value: u64) {
+        self.wait_idle_native();
     }
 
-    fn resize_native(&mut self, width: u32, height: u32) {
-        println!("Resize: {}x{}", width, height);
+    /// End of a `submit`: hand the recorded work to the GPU
+    ///
+    /// Backends that execute commands as they are replayed need nothing here.
+    fn submit_native(&mut self) {}
+
+    /// Make earlier work on each resource visible to its next use, outside
+    /// render passes; `before` is `Undefined` when the contents may be dropped
+    ///
+    /// Backends whose API tracks hazards itself (wgpu) or that run commands
+    /// in order (software) need nothing here.
+    fn barrier_native(&mut self, _barriers: &[Barrier]) {}
+
+    /// Pool of `count` timestamp queries, kept for the device's lifetime;
+    /// `None` where the backend cannot time GPU work
+    fn create_query_pool_native(&mut self, _count: u32) -> Option<NativeQueryPool> {
+        None
+    }
+    /// Write the GPU time into query `index` once the work before it is
+    /// done; called inside and outside render passes
+    fn write_timestamp_native(&mut self, _pool: NativeQueryPool, _index: u32) {}
+    /// After the last timestamp of a frame: start fetching queries `0..count`
+    fn resolve_query_pool_native(&mut self, _pool: NativeQueryPool, _count: u32) {}
+    /// Queries `0..count` in nanoseconds, or `None` while the GPU has not
+    /// written them; never blocks
+    fn read_query_pool_native(&mut self, _pool: NativeQueryPool, _count: u32) -> Option<Vec<u64>> {
+        None
     }
 
-    fn wait_idle_native(&mut self) {
-        println!("Wait idle");
+    /// Contents of the native pipeline cache; empty without one
+    fn pipeline_cache_data_native(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+    /// Seed the native pipeline cache with `pipeline_cache_data_native` of
+    /// an earlier run on the same adapter; false if it cannot be used
+    fn load_pipeline_cache_native(&mut self, _data: &[u8]) -> bool {
+        false
     }
 }
 
+/// One timestamp query pool per frame in flight, none without backend
+/// support
+fn create_timestamp_frames(native_device: &mut dyn NativeDevice) -> Vec<TimestampFrame> {
+    (0..TIMESTAMP_FRAMES)
+        .map_while(|_| native_device.create_query_pool_native(MAX_TIMESTAMPS))
+        .map(|pool| TimestampFrame {
+            pool,
+            frame: 0,
+            names: Vec::new(),
+            pending: false,
+        })
+        .collect()
+}
+
+/// Create the native device for `config.backend`
+///
+/// A backend that is compiled out or fails to initialize falls back to the
+/// null backend, so a device is always returned.
+fn create_native_device(
+    config: &RendererConfig,
+    window: Option<&NativeHandle>,
+) -> Box<dyn NativeDevice> {
+    match config.backend {
+        Backend::Null => {}
+        Backend::Software => return Box::new(software::SoftwareDevice::create(config)),
+        #[cfg(feature = "opengl")]
+        Backend::OpenGl => match gl::GlDevice::create(config, window) {
+            Ok(device) => return Box::new(device),
+            Err(err) => eprintln!(
+                "OpenGL backend unavailable: {}; using the null backend",
+                err
+            ),
+        },
+        #[cfg(not(feature = "opengl"))]
+        Backend::OpenGl => {
+            let _ = window;
+            eprintln!("OpenGL backend not compiled in (enable the `opengl` feature); using the null backend");
+        }
+        #[cfg(feature = "wgpu")]
+        Backend::Wgpu => match webgpu::WgpuDevice::create(config, window) {
+            Ok(device) => return Box::new(device),
+            Err(err) => eprintln!("wgpu backend unavailable: {}; using the null backend", err),
+        },
+        #[cfg(not(feature = "wgpu"))]
+        Backend::Wgpu => {
+            let _ = window;
+            eprintln!(
+                "wgpu backend not compiled in (enable the `wgpu` feature); using the null backend"
+            );
+        }
+    }
+    Box::new(null::NullDevice::create(config))
+}
+
 // Native handles (opaque, backend-specific)
 #[derive(Clone, Copy)]
 struct NativeTexture {
@@ -486,11 +2671,91 @@ struct NativePipeline {
     handle: u64, // VkPipeline, ID3D12PipelineState*, MTLRenderPipelineState, GLuint, etc.
 }
 
+#[derive(Clone, Copy)]
+struct NativeSampler {
+    handle: u64, // VkSampler, D3D12 sampler descriptor, MTLSamplerState, GLuint, etc.
+}
+
+#[derive(Clone, Copy)]
+struct NativeBindGroupLayout {
+    handle: u64, // VkDescriptorSetLayout, D3D12 root parameter, MTLArgumentEncoder, etc.
+}
+
+#[derive(Clone, Copy)]
+struct NativeBindGroup {
+    handle: u64, // VkDescriptorSet, D3D12 descriptor table, MTLBuffer argument buffer, etc.
+}
+
+/// `BindGroupEntry` with its handles resolved and buffer size filled in
+#[derive(Clone, Copy)]
+struct NativeBindGroupEntry {
+    binding: u32,
+    resource: NativeBindingResource,
+}
+
+#[derive(Clone, Copy)]
+enum NativeBindingResource {
+    Buffer {
+        buffer: NativeBuffer,
+        offset: u64,
+        size: u64,
+    },
+    Texture(NativeTexture),
+    Sampler(NativeSampler),
+}
+
+#[derive(Clone, Copy)]
+struct NativeSurface {
+    handle: u64, // VkSurfaceKHR, HWND-bound DXGI target, CAMetalLayer, EGLSurface, etc.
+}
+
+#[derive(Clone, Copy)]
+struct NativeQueryPool {
+    handle: u64, // VkQueryPool, ID3D12QueryHeap, MTLCounterSampleBuffer, GL query names, etc.
+}
+
+struct NativeSwapchain {
+    handle: u64, // VkSwapchainKHR, IDXGISwapChain*, etc.
+    image_count: u32,
+    next_image: u32,
+}
+
 // ============================================================================
 // Public API for creating device
 // ============================================================================
 
+/// List the adapters of every compiled-in backend
+///
+/// Backends that fail to initialize contribute nothing. The null backend is
+/// not an adapter and is never listed.
+pub fn enumerate_adapters() -> Vec<AdapterInfo> {
+    let adapters = std::iter::empty();
+    #[cfg(feature = "wgpu")]
+    let adapters = adapters.chain(webgpu::enumerate_adapters());
+    #[cfg(feature = "opengl")]
+    let adapters = adapters.chain(gl::enumerate_adapters());
+    adapters.chain([software::adapter_info()]).collect()
+}
+
 /// Create a GPU device with the given configuration
 pub fn create_device(config: RendererConfig) -> BackendDevice {
     BackendDevice::new(config)
 }
+
+/// Create a GPU device whose swapchain presents to `window`
+///
+/// The swapchain takes the window's framebuffer size (physical pixels), not
+/// `config.width`/`config.height`. Feed window events to
+/// [`BackendDevice::handle_window_event`] to keep it in sync. A headless
+/// window gets an offscreen swapchain; a backend that cannot present to the
+/// window fails with `GfxError::Unsupported`.
+pub fn create_device_with_window(
+    mut config: RendererConfig,
+    window: &Window,
+) -> Result<BackendDevice, GfxError> {
+    let size = window.size();
+    let scale = window.scale_factor();
+    config.width = ((size.width as f32 * scale).round() as u32).max(1);
+    config.height = ((size.height as f32 * scale).round() as u32).max(1);
+    BackendDevice::with_surface(config, window.native_handle())
+}
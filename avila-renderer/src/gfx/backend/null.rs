@@ -0,0 +1,463 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Null backend
+//!
+//! Creates no GPU objects and only logs what it is asked to do. Used when no
+//! other backend is selected or available.
+
+use super::*;
+
+/// Native device that logs every call
+pub(super) struct NullDevice {}
+
+impl NullDevice {
+    pub(super) fn create(_config: &RendererConfig) -> Self {
+        println!("Creating native device (stub)");
+        Self {}
+    }
+}
+
+impl NativeDevice for NullDevice {
+    fn adapter_info_native(&self) -> AdapterInfo {
+        AdapterInfo {
+            backend: Backend::Null,
+            name: "Null device".to_string(),
+            vendor: String::new(),
+            vram: None,
+            adapter_type: AdapterType::Unknown,
+        }
+    }
+
+    fn create_texture_native(&mut self, desc: &TextureDesc) -> Result<NativeTexture, GfxError> {
+        println!(
+            "Creating texture: {}x{} {:?}",
+            desc.width, desc.height, desc.format
+        );
+        Ok(NativeTexture { handle: 0 })
+    }
+
+    fn create_buffer_native(
+        &mut self,
+        desc: &BufferDesc,
+        _data: Option<&[u8]>,
+    ) -> Result<NativeBuffer, GfxError> {
+        println!("Creating buffer: {} bytes, {:?}", desc.size, desc.usage);
+        Ok(NativeBuffer { handle: 0 })
+    }
+
+    fn create_shader_native(&mut self, desc: &ShaderDesc) -> Result<NativeShader, GfxError> {
+        println!(
+            "Creating shader: {:?}, {} bytes",
+            desc.stage,
+            desc.code.len()
+        );
+        Ok(NativeShader { handle: 0 })
+    }
+
+    fn create_pipeline_native(
+        &mut self,
+        _desc: &PipelineDesc,
+        _shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        println!("Creating pipeline (stub)");
+        Ok(NativePipeline { handle: 0 })
+    }
+
+    fn create_compute_pipeline_native(
+        &mut self,
+        _desc: &ComputePipelineDesc,
+        _shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        println!("Creating compute pipeline (stub)");
+        Ok(NativePipeline { handle: 0 })
+    }
+
+    fn create_sampler_native(&mut self, desc: &SamplerDesc) -> Result<NativeSampler, GfxError> {
+        println!("Creating sampler: {:?} filtering", desc.min_filter);
+        Ok(NativeSampler { handle: 0 })
+    }
+
+    fn create_bind_group_layout_native(
+        &mut self,
+        desc: &BindGroupLayoutDesc,
+    ) -> Result<NativeBindGroupLayout, GfxError> {
+        println!(
+            "Creating bind group layout: {} bindings",
+            desc.entries.len()
+        );
+        Ok(NativeBindGroupLayout { handle: 0 })
+    }
+
+    fn create_bind_group_native(
+        &mut self,
+        layout: NativeBindGroupLayout,
+        _desc: &BindGroupLayoutDesc,
+        entries: &[NativeBindGroupEntry],
+    ) -> Result<NativeBindGroup, GfxError> {
+        println!(
+            "Creating bind group: {} resources, layout {}",
+            entries.len(),
+            layout.handle
+        );
+        for entry in entries {
+            match entry.resource {
+                NativeBindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } => println!(
+                    "  binding {}: buffer {} [{}..{}]",
+                    entry.binding,
+                    buffer.handle,
+                    offset,
+                    offset + size
+                ),
+                NativeBindingResource::Texture(texture) => {
+                    println!("  binding {}: texture {}", entry.binding, texture.handle)
+                }
+                NativeBindingResource::Sampler(sampler) => {
+                    println!("  binding {}: sampler {}", entry.binding, sampler.handle)
+                }
+            }
+        }
+        Ok(NativeBindGroup { handle: 0 })
+    }
+
+    fn destroy_texture_native(&mut self, _texture: NativeTexture) {}
+    fn destroy_buffer_native(&mut self, _buffer: NativeBuffer) {}
+    fn destroy_shader_native(&mut self, _shader: NativeShader) {}
+    fn destroy_pipeline_native(&mut self, _pipeline: NativePipeline) {}
+    fn destroy_compute_pipeline_native(&mut self, _pipeline: NativePipeline) {}
+    fn destroy_sampler_native(&mut self, _sampler: NativeSampler) {}
+    fn destroy_bind_group_layout_native(&mut self, _layout: NativeBindGroupLayout) {}
+    fn destroy_bind_group_native(&mut self, _group: NativeBindGroup) {}
+
+    fn update_buffer_native(&mut self, _buffer: NativeBuffer, _offset: usize, data: &[u8]) {
+        println!("Updating buffer with {} bytes", data.len());
+    }
+
+    fn map_buffer_native(&mut self, _buffer: NativeBuffer) -> *mut u8 {
+        std::ptr::null_mut()
+    }
+
+    fn unmap_buffer_native(&mut self, _buffer: NativeBuffer) {}
+
+    fn update_texture_native(
+        &mut self,
+        _texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        _region: &TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        println!(
+            "Updating texture mip {} layer {} with {} bytes",
+            mip,
+            layer,
+            data.len()
+        );
+        Ok(())
+    }
+
+    fn generate_mipmaps_native(
+        &mut self,
+        _texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<(), GfxError> {
+        println!("Generating {} mip levels", desc.mip_levels - 1);
+        Ok(())
+    }
+
+    fn read_texture_native(
+        &mut self,
+        _texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<Vec<u8>, GfxError> {
+        println!("Reading texture {}x{}", desc.width, desc.height);
+        // Nothing is ever drawn, so every texel reads as zero
+        let region = TextureRegion::full(desc, 0);
+        let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+        let slices = region.depth as usize * layer_count(desc) as usize;
+        Ok(vec![0; row_bytes * rows * slices])
+    }
+
+    fn begin_frame_native(&mut self) {
+        println!("Begin frame");
+    }
+
+    fn begin_render_pass_native(
+        &mut self,
+        _desc: &RenderPassDesc,
+        _textures: &ResourcePool<TextureResource>,
+    ) {
+        println!("Begin render pass");
+    }
+
+    fn end_render_pass_native(&mut self) {
+        println!("End render pass");
+    }
+
+    fn bind_pipeline_native(&mut self, _pipeline: NativePipeline) {
+        println!("Bind pipeline");
+    }
+
+    fn set_viewport_native(&mut self, viewport: &Viewport) {
+        println!("Set viewport: {}x{}", viewport.width, viewport.height);
+    }
+
+    fn set_scissor_native(&mut self, _scissor: &Rect) {
+        println!("Set scissor");
+    }
+
+    fn bind_vertex_buffer_native(&mut self, slot: u32, _buffer: NativeBuffer, _offset: u64) {
+        println!("Bind vertex buffer at slot {}", slot);
+    }
+
+    fn bind_index_buffer_native(
+        &mut self,
+        _buffer: NativeBuffer,
+        _offset: u64,
+        _index_type: IndexType,
+    ) {
+        println!("Bind index buffer");
+    }
+
+    fn bind_group_native(&mut self, index: u32, _group: NativeBindGroup) {
+        println!("Bind group to set {}", index);
+    }
+
+    fn bind_texture_native(
+        &mut self,
+        set: u32,
+        binding: u32,
+        _texture: NativeTexture,
+        _sampler: NativeSampler,
+    ) {
+        println!("Bind texture to set {} binding {}", set, binding);
+    }
+
+    fn draw_native(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        _first_vertex: u32,
+        _first_instance: u32,
+    ) {
+        println!(
+            "Draw: {} vertices, {} instances",
+            vertex_count, instance_count
+        );
+    }
+
+    fn draw_indexed_native(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        _first_index: u32,
+        _vertex_offset: i32,
+        _first_instance: u32,
+    ) {
+        println!(
+            "Draw indexed: {} indices, {} instances",
+            index_count, instance_count
+        );
+    }
+
+    fn bind_compute_pipeline_native(&mut self, _pipeline: NativePipeline) {
+        println!("Binding compute pipeline");
+    }
+
+    fn dispatch_native(&mut self, x: u32, y: u32, z: u32) {
+        println!("Dispatch: {}x{}x{} workgroups", x, y, z);
+    }
+
+    fn create_surface_native(&mut self, window: &NativeHandle) -> Result<NativeSurface, GfxError> {
+        println!("Creating surface for {:?}", window);
+        let handle = match *window {
+            NativeHandle::Win32 { hwnd, .. } => hwnd as u64,
+            NativeHandle::Xcb { window, .. } => window as u64,
+            NativeHandle::Wayland { surface, .. } => surface as u64,
+        };
+        Ok(NativeSurface { handle })
+    }
+
+    fn create_swapchain_native(
+        &mut self,
+        surface: Option<NativeSurface>,
+        desc: &TextureDesc,
+        image_count: u32,
+        present_mode: PresentMode,
+    ) -> NativeSwapchain {
+        println!(
+            "Creating swapchain: {}x{} {:?}, {} images, {:?}, {}",
+            desc.width,
+            desc.height,
+            desc.format,
+            image_count,
+            present_mode,
+            match surface {
+                Some(surface) => format!("surface {:#x}", surface.handle),
+                None => "offscreen".to_string(),
+            }
+        );
+        NativeSwapchain {
+            handle: 0,
+            image_count,
+            next_image: 0,
+        }
+    }
+
+    fn swapchain_images_native(&mut self, swapchain: &NativeSwapchain) -> Vec<NativeTexture> {
+        (0..swapchain.image_count)
+            .map(|_| NativeTexture { handle: 0 })
+            .collect()
+    }
+
+    fn acquire_next_image_native(&mut self, swapchain: &mut NativeSwapchain) -> Option<u32> {
+        let index = swapchain.next_image;
+        swapchain.next_image = (index + 1) % swapchain.image_count;
+        Some(index)
+    }
+
+    fn destroy_swapchain_native(&mut self, _swapchain: NativeSwapchain) {}
+
+    fn present_native(&mut self, _swapchain: &NativeSwapchain, image_index: u32) {
+        println!("Present image {}", image_index);
+    }
+
+    fn wait_idle_native(&mut self) {
+        println!("Wait idle");
+    }
+
+    fn copy_buffer_native(
+        &mut self,
+        _src: NativeBuffer,
+        _src_offset: u64,
+        _dst: NativeBuffer,
+        _dst_offset: u64,
+        size: u64,
+    ) {
+        println!("Copying {} bytes between buffers", size);
+    }
+
+    fn copy_buffer_to_texture_native(
+        &mut self,
+        _src: NativeBuffer,
+        _layout: TexelBufferLayout,
+        _texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+    ) -> Result<(), GfxError> {
+        println!(
+            "Copying {}x{}x{} texels from a buffer to texture mip {} layer {}",
+            region.width, region.height, region.depth, mip, layer
+        );
+        Ok(())
+    }
+
+    fn barrier_native(&mut self, barriers: &[Barrier]) {
+        for barrier in barriers {
+            let (kind, handle) = match barrier.resource {
+                NativeResource::Texture(texture) => ("texture", texture.handle),
+                NativeResource::Buffer(buffer) => ("buffer", buffer.handle),
+            };
+            println!(
+                "Barrier on {} {}: {:?} -> {:?}",
+                kind, handle, barrier.before, barrier.after
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> NativeHandle {
+        NativeHandle::Xcb {
+            connection: std::ptr::null_mut(),
+            window: 42,
+            screen: 0,
+        }
+    }
+
+    fn frame(device: &mut BackendDevice) -> TextureHandle {
+        let cmd = device.begin_frame();
+        let image = device.get_swapchain_texture();
+        device.submit(cmd);
+        device.present();
+        image
+    }
+
+    #[test]
+    fn test_null_is_not_an_adapter() {
+        let device = create_device(RendererConfig {
+            backend: Backend::Null,
+            adapter_preference: AdapterPreference::LowPower,
+            ..Default::default()
+        });
+        assert_eq!(device.adapter_info().backend, Backend::Null);
+        assert!(enumerate_adapters()
+            .iter()
+            .all(|adapter| adapter.backend != Backend::Null));
+    }
+
+    #[cfg(not(all(feature = "wgpu", feature = "opengl")))]
+    #[test]
+    fn test_compiled_out_backend_falls_back() {
+        #[cfg(not(feature = "wgpu"))]
+        let backend = Backend::Wgpu;
+        #[cfg(all(feature = "wgpu", not(feature = "opengl")))]
+        let backend = Backend::OpenGl;
+
+        let device = create_device(RendererConfig {
+            backend,
+            ..Default::default()
+        });
+        assert_eq!(device.adapter_info().backend, Backend::Null);
+        assert!(enumerate_adapters()
+            .iter()
+            .all(|adapter| adapter.backend != backend));
+    }
+
+    #[test]
+    fn test_window_swapchain() {
+        let config = RendererConfig {
+            width: 64,
+            height: 32,
+            backend: Backend::Null,
+            ..Default::default()
+        };
+        let mut device = BackendDevice::with_surface(config, Some(window())).unwrap();
+        assert_eq!(device.surface_window(), Some(window()));
+
+        // Presenting rotates through the images
+        let images: Vec<_> = (0..3).map(|_| frame(&mut device)).collect();
+        assert!(images.iter().all(|image| *image != TextureHandle::INVALID));
+        assert_ne!(images[0], images[1]);
+        assert!(device.poll_events().is_empty());
+
+        device.handle_window_event(&WindowEvent::FramebufferResized(128, 64));
+        assert_eq!((device.config().width, device.config().height), (128, 64));
+        assert_eq!(
+            device.poll_events(),
+            vec![DeviceEvent::SwapchainResized {
+                width: 128,
+                height: 64
+            }]
+        );
+        assert_ne!(frame(&mut device), TextureHandle::INVALID);
+
+        // Minimized: frames go on without presenting, and the size is kept
+        device.handle_window_event(&WindowEvent::FramebufferResized(0, 0));
+        frame(&mut device);
+        assert_eq!((device.config().width, device.config().height), (128, 64));
+        device.handle_window_event(&WindowEvent::FramebufferResized(128, 64));
+        assert!(device.poll_events().is_empty());
+        assert_eq!(device.surface_window(), Some(window()));
+    }
+}
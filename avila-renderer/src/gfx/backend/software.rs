@@ -0,0 +1,3348 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Software backend
+//!
+//! Executes command lists on the CPU into textures kept in memory, so render
+//! passes can be checked pixel by pixel without a GPU (see
+//! [`GpuDevice::read_texture`]).
+//!
+//! Shaders are not run. Instead every pipeline behaves like a fixed-function
+//! pass-through:
+//!
+//! - Attribute location 0 is the clip-space position (`Float` to `Float4`;
+//!   missing components default to `z = 0`, `w = 1`)
+//! - Attribute location 1, if present, is the color (`Float3` or `Float4`),
+//!   interpolated perspective-correctly; otherwise opaque white
+//! - Attribute location 2, if present, is a texture coordinate: the color is
+//!   multiplied by the texture bound at set 0, binding 0, read from layer 0
+//!   with the sampler's filters and address modes. The mip level is chosen
+//!   once per triangle, from its texture coordinates without perspective;
+//!   lines and points read mip 0. Anisotropy and comparison are ignored;
+//!   depth texels read as `(d, d, d, 1)` and compressed ones as zero
+//! - A bind group at set 0 supplies that texture if it has one at binding 0
+//!   and a sampler at binding 1; buffers and storage textures in bind groups
+//!   are not read
+//! - Compute pipelines cannot be created
+//! - Each location is read from the vertex buffer slot whose layout has it,
+//!   per vertex or per instance; instances without per-instance attributes
+//!   are drawn on top of each other
+//!
+//! Rasterization follows the WebGPU conventions: depth range 0..1, the
+//! framebuffer origin at the top-left, pixel centers at `.5` and the top-left
+//! fill rule. Primitives with a vertex behind the eye (`w <= 0`) are dropped
+//! instead of clipped. Only mip 0 is rendered to; depth formats are stored
+//! (and read back) as `f32`, and the stencil of `Depth24Stencil8` is ignored.
+//! Multisampled textures hold one sample per pixel, so a resolve copies the
+//! attachment; `DontCare` load and store ops keep the contents. Timestamps
+//! are CPU times taken as the commands are replayed.
+
+use super::*;
+use std::ops::Range;
+use std::time::Instant;
+
+/// Native device rasterizing on the CPU
+pub(super) struct SoftwareDevice {
+    textures: HashMap<u64, SoftwareTexture>,
+    buffers: HashMap<u64, Vec<u8>>,
+    pipelines: HashMap<u64, PipelineDesc>,
+    samplers: HashMap<u64, SamplerDesc>,
+    /// Texture and sampler at bindings 0 and 1 of each bind group, if any
+    bind_groups: HashMap<u64, Option<(u64, u64)>>,
+    swapchains: HashMap<u64, Vec<u64>>,
+    /// Timestamp queries, in nanoseconds since `epoch`
+    query_pools: HashMap<u64, Vec<u64>>,
+    epoch: Instant,
+    next_handle: u64,
+    state: CommandState,
+}
+
+struct SoftwareTexture {
+    desc: TextureDesc,
+    /// Every layer (or 3D slice) of each mip level, one after the other
+    mips: Vec<Vec<u8>>,
+}
+
+/// Render state between `begin_render_pass` and `end_render_pass`
+#[derive(Default)]
+struct CommandState {
+    color_attachments: Vec<u64>,
+    depth_attachment: Option<u64>,
+    /// Attachments to copy into their resolve targets when the pass ends
+    resolves: Vec<(u64, u64)>,
+    target_width: u32,
+    target_height: u32,
+    viewport: Option<Viewport>,
+    scissor: Option<Rect>,
+    pipeline: Option<u64>,
+    /// Buffer and offset bound at each vertex buffer slot
+    vertex_buffers: HashMap<u32, (u64, u64)>,
+    index_buffer: Option<(u64, u64, IndexType)>,
+    /// Texture and sampler at set 0, binding 0
+    texture: Option<(u64, u64)>,
+}
+
+/// Copy of the texture a draw samples, so it can be read while the
+/// attachments are written
+struct Sampled {
+    desc: TextureDesc,
+    /// Layer 0 of each mip level
+    mips: Vec<Vec<u8>>,
+    sampler: SamplerDesc,
+}
+
+/// Vertex after the viewport transform
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    /// 1 / clip w, for perspective-correct interpolation
+    inv_w: f32,
+    /// Color premultiplied by `inv_w`
+    color: [f32; 4],
+    /// Texture coordinate premultiplied by `inv_w`
+    uv: [f32; 2],
+}
+
+impl SoftwareDevice {
+    pub(super) fn create(_config: &RendererConfig) -> Self {
+        Self {
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+            pipelines: HashMap::new(),
+            samplers: HashMap::new(),
+            bind_groups: HashMap::new(),
+            swapchains: HashMap::new(),
+            query_pools: HashMap::new(),
+            epoch: Instant::now(),
+            next_handle: 1,
+            state: CommandState::default(),
+        }
+    }
+
+    fn allocate_handle(&mut self) -> u64 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+
+    /// Run one draw over the vertex indices produced by `indices`
+    fn draw_vertices(&mut self, indices: impl Iterator<Item = u32>, instances: Range<u32>) {
+        let Some(pipeline) = self.state.pipeline.and_then(|p| self.pipelines.get(&p)) else {
+            return;
+        };
+        let indices: Vec<u32> = indices.collect();
+        let per_instance = pipeline
+            .vertex_layout
+            .slots()
+            .any(|(_, step_mode, _)| step_mode == VertexStepMode::Instance);
+        // Without per-instance data every instance is the same: fetch once
+        // and draw it `repeat` times
+        let (fetched_instances, repeat) = if per_instance {
+            (instances.clone(), 1)
+        } else {
+            (instances.start..instances.start + 1, instances.len())
+        };
+        let fetched: Vec<Vec<Option<ScreenVertex>>> = fetched_instances
+            .map(|instance| {
+                indices
+                    .iter()
+                    .map(|&index| self.fetch_vertex(pipeline, index, instance))
+                    .collect()
+            })
+            .collect();
+        let textured = pipeline
+            .vertex_layout
+            .all_attributes()
+            .any(|attribute| attribute.location == 2);
+        let sampled = self
+            .state
+            .texture
+            .filter(|_| textured)
+            .and_then(|(texture, sampler)| {
+                let texture = self.textures.get(&texture)?;
+                let desc = &texture.desc;
+                let mips = (0..desc.mip_levels)
+                    .map(|mip| {
+                        let extent = TextureRegion::full(desc, mip);
+                        let (row_bytes, rows) =
+                            region_layout(desc.format, extent.width, extent.height);
+                        Some(texture.mips[mip as usize].get(..row_bytes * rows)?.to_vec())
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Sampled {
+                    desc: desc.clone(),
+                    mips,
+                    sampler: *self.samplers.get(&sampler)?,
+                })
+            });
+
+        let mut raster =
+            Rasterizer::new(&mut self.textures, &self.state, pipeline, sampled.as_ref());
+        for vertices in &fetched {
+            for _ in 0..repeat {
+                raster.primitives(vertices);
+            }
+        }
+    }
+
+    /// Read vertex `index` of instance `instance` from the bound vertex
+    /// buffers and run the viewport transform
+    fn fetch_vertex(
+        &self,
+        pipeline: &PipelineDesc,
+        index: u32,
+        instance: u32,
+    ) -> Option<ScreenVertex> {
+        let mut position = [0.0, 0.0, 0.0, 1.0];
+        let mut color = [1.0; 4];
+        let mut uv = [0.0; 2];
+        let mut has_position = false;
+        for (slot, (stride, step_mode, attributes)) in pipeline.vertex_layout.slots().enumerate()
+        {
+            if attributes.is_empty() {
+                continue;
+            }
+            let (buffer, offset) = *self.state.vertex_buffers.get(&(slot as u32))?;
+            let data = self.buffers.get(&buffer)?;
+            let element = match step_mode {
+                VertexStepMode::Vertex => index,
+                VertexStepMode::Instance => instance,
+            };
+            let base = offset as usize + element as usize * stride as usize;
+            for attribute in attributes {
+                let target: &mut [f32] = match attribute.location {
+                    0 => {
+                        has_position = true;
+                        &mut position
+                    }
+                    1 => &mut color,
+                    2 => &mut uv,
+                    _ => continue,
+                };
+                let start = base + attribute.offset as usize;
+                let bytes = data.get(start..start + attribute.format.size() as usize)?;
+                for (component, chunk) in target.iter_mut().zip(bytes.chunks_exact(4)) {
+                    let raw = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                    *component = match attribute.format {
+                        VertexFormat::Float
+                        | VertexFormat::Float2
+                        | VertexFormat::Float3
+                        | VertexFormat::Float4 => f32::from_le_bytes(raw),
+                        _ => u32::from_le_bytes(raw) as f32,
+                    };
+                }
+            }
+        }
+        if !has_position || position[3] <= 0.0 {
+            return None;
+        }
+
+        let viewport = self.state.viewport.unwrap_or(Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: self.state.target_width as f32,
+            height: self.state.target_height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        });
+        let inv_w = 1.0 / position[3];
+        let ndc = [
+            position[0] * inv_w,
+            position[1] * inv_w,
+            position[2] * inv_w,
+        ];
+        Some(ScreenVertex {
+            x: viewport.x + (ndc[0] + 1.0) * 0.5 * viewport.width,
+            y: viewport.y + (1.0 - ndc[1]) * 0.5 * viewport.height,
+            z: viewport.min_depth + ndc[2] * (viewport.max_depth - viewport.min_depth),
+            inv_w,
+            color: color.map(|c| c * inv_w),
+            uv: uv.map(|c| c * inv_w),
+        })
+    }
+
+    /// Vertex indices of an indexed draw, or `None` without an index buffer
+    fn read_indices(&self, count: u32, first: u32, vertex_offset: i32) -> Option<Vec<u32>> {
+        let (buffer, offset, index_type) = self.state.index_buffer?;
+        let data = self.buffers.get(&buffer)?;
+        let size = match index_type {
+            IndexType::UInt16 => 2,
+            IndexType::UInt32 => 4,
+        };
+        let start = offset as usize + first as usize * size;
+        let bytes = data.get(start..start + count as usize * size)?;
+        Some(
+            bytes
+                .chunks_exact(size)
+                .map(|chunk| {
+                    let index = match index_type {
+                        IndexType::UInt16 => u16::from_le_bytes([chunk[0], chunk[1]]) as u32,
+                        IndexType::UInt32 => {
+                            u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                        }
+                    };
+                    (index as i64 + vertex_offset as i64) as u32
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The one adapter of the software backend
+pub(super) fn adapter_info() -> AdapterInfo {
+    AdapterInfo {
+        backend: Backend::Software,
+        name: "Avila software rasterizer".to_string(),
+        vendor: "Avila".to_string(),
+        vram: None,
+        adapter_type: AdapterType::Cpu,
+    }
+}
+
+impl NativeDevice for SoftwareDevice {
+    fn adapter_info_native(&self) -> AdapterInfo {
+        adapter_info()
+    }
+
+    fn create_texture_native(&mut self, desc: &TextureDesc) -> Result<NativeTexture, GfxError> {
+        let layers = layer_count(desc) as usize;
+        let mips = (0..desc.mip_levels)
+            .map(|mip| {
+                let extent = TextureRegion::full(desc, mip);
+                let (row_bytes, rows) = region_layout(desc.format, extent.width, extent.height);
+                [rows, extent.depth as usize, layers]
+                    .iter()
+                    .try_fold(row_bytes, |size, n| size.checked_mul(*n))
+                    .ok_or(GfxError::OutOfMemory)
+                    .and_then(zeroed)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let handle = self.allocate_handle();
+        self.textures.insert(
+            handle,
+            SoftwareTexture {
+                desc: desc.clone(),
+                mips,
+            },
+        );
+        Ok(NativeTexture { handle })
+    }
+
+    fn create_buffer_native(
+        &mut self,
+        desc: &BufferDesc,
+        data: Option<&[u8]>,
+    ) -> Result<NativeBuffer, GfxError> {
+        let mut contents = zeroed(desc.size)?;
+        if let Some(data) = data {
+            contents[..data.len()].copy_from_slice(data);
+        }
+        let handle = self.allocate_handle();
+        self.buffers.insert(handle, contents);
+        Ok(NativeBuffer { handle })
+    }
+
+    fn create_shader_native(&mut self, _desc: &ShaderDesc) -> Result<NativeShader, GfxError> {
+        // Nothing to compile: pipelines are fixed-function
+        Ok(NativeShader {
+            handle: self.allocate_handle(),
+        })
+    }
+
+    fn create_pipeline_native(
+        &mut self,
+        desc: &PipelineDesc,
+        _shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        let handle = self.allocate_handle();
+        self.pipelines.insert(handle, desc.clone());
+        Ok(NativePipeline { handle })
+    }
+
+    fn create_compute_pipeline_native(
+        &mut self,
+        _desc: &ComputePipelineDesc,
+        _shaders: &ResourcePool<ShaderResource>,
+        _bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        Err(GfxError::InvalidDesc(
+            "the software backend does not run compute shaders".to_string(),
+        ))
+    }
+
+    fn create_sampler_native(&mut self, desc: &SamplerDesc) -> Result<NativeSampler, GfxError> {
+        let handle = self.allocate_handle();
+        self.samplers.insert(handle, *desc);
+        Ok(NativeSampler { handle })
+    }
+
+    fn create_bind_group_layout_native(
+        &mut self,
+        _desc: &BindGroupLayoutDesc,
+    ) -> Result<NativeBindGroupLayout, GfxError> {
+        Ok(NativeBindGroupLayout {
+            handle: self.allocate_handle(),
+        })
+    }
+
+    fn create_bind_group_native(
+        &mut self,
+        _layout: NativeBindGroupLayout,
+        desc: &BindGroupLayoutDesc,
+        entries: &[NativeBindGroupEntry],
+    ) -> Result<NativeBindGroup, GfxError> {
+        // Storage textures are not sampled
+        let resource = |binding: u32| {
+            desc.entries
+                .iter()
+                .zip(entries)
+                .find(|(slot, entry)| {
+                    entry.binding == binding
+                        && !matches!(slot.ty, BindingType::StorageTexture { .. })
+                })
+                .map(|(_, entry)| entry.resource)
+        };
+        let texture = match (resource(0), resource(1)) {
+            (
+                Some(NativeBindingResource::Texture(texture)),
+                Some(NativeBindingResource::Sampler(sampler)),
+            ) => Some((texture.handle, sampler.handle)),
+            _ => None,
+        };
+        let handle = self.allocate_handle();
+        self.bind_groups.insert(handle, texture);
+        Ok(NativeBindGroup { handle })
+    }
+
+    fn destroy_texture_native(&mut self, texture: NativeTexture) {
+        self.textures.remove(&texture.handle);
+    }
+
+    fn destroy_buffer_native(&mut self, buffer: NativeBuffer) {
+        self.buffers.remove(&buffer.handle);
+    }
+
+    fn destroy_shader_native(&mut self, _shader: NativeShader) {}
+
+    fn destroy_pipeline_native(&mut self, pipeline: NativePipeline) {
+        self.pipelines.remove(&pipeline.handle);
+    }
+
+    fn destroy_compute_pipeline_native(&mut self, _pipeline: NativePipeline) {}
+
+    fn destroy_sampler_native(&mut self, sampler: NativeSampler) {
+        self.samplers.remove(&sampler.handle);
+    }
+
+    fn destroy_bind_group_layout_native(&mut self, _layout: NativeBindGroupLayout) {}
+
+    fn destroy_bind_group_native(&mut self, group: NativeBindGroup) {
+        self.bind_groups.remove(&group.handle);
+    }
+
+    fn update_buffer_native(&mut self, buffer: NativeBuffer, offset: usize, data: &[u8]) {
+        if let Some(contents) = self.buffers.get_mut(&buffer.handle) {
+            match contents.get_mut(offset..offset + data.len()) {
+                Some(range) => range.copy_from_slice(data),
+                None => eprintln!(
+                    "Buffer update of {} bytes at {} is out of bounds ({} bytes)",
+                    data.len(),
+                    offset,
+                    contents.len()
+                ),
+            }
+        }
+    }
+
+    fn map_buffer_native(&mut self, buffer: NativeBuffer) -> *mut u8 {
+        // Buffers never grow, so the pointer stays valid until destroyed
+        self.buffers
+            .get_mut(&buffer.handle)
+            .map_or(std::ptr::null_mut(), |contents| contents.as_mut_ptr())
+    }
+
+    fn unmap_buffer_native(&mut self, _buffer: NativeBuffer) {}
+
+    fn update_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        let SoftwareTexture { desc, mips } = self
+            .textures
+            .get_mut(&texture.handle)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))?;
+        let extent = TextureRegion::full(desc, mip);
+        let (mip_row_bytes, mip_rows) = region_layout(desc.format, extent.width, extent.height);
+        let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+        // Offsets are block-aligned for compressed formats
+        let (x_bytes, y_rows) = region_layout(desc.format, region.x, region.y);
+        let slice_bytes = mip_row_bytes * mip_rows;
+        let layer_start = layer as usize * slice_bytes * extent.depth as usize;
+
+        let storage = &mut mips[mip as usize];
+        for (i, row) in data.chunks_exact(row_bytes).enumerate() {
+            let z = region.z as usize + i / rows;
+            let y = y_rows + i % rows;
+            let start = layer_start + z * slice_bytes + y * mip_row_bytes + x_bytes;
+            storage[start..start + row_bytes].copy_from_slice(row);
+        }
+        Ok(())
+    }
+
+    fn generate_mipmaps_native(
+        &mut self,
+        texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<(), GfxError> {
+        let texture = self
+            .textures
+            .get_mut(&texture.handle)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))?;
+        let size = desc.format.bytes_per_pixel() as usize;
+        let layers = layer_count(desc) as usize;
+        for mip in 1..texture.mips.len() {
+            let src_extent = TextureRegion::full(desc, mip as u32 - 1);
+            let dst_extent = TextureRegion::full(desc, mip as u32);
+            let (width, height) = (src_extent.width as usize, src_extent.height as usize);
+            let src_layer = width * height * size;
+            let dst_layer = (dst_extent.width * dst_extent.height) as usize * size;
+            let (above, below) = texture.mips.split_at_mut(mip);
+            for layer in 0..layers {
+                downsample(
+                    desc.format,
+                    &above[mip - 1][layer * src_layer..][..src_layer],
+                    width,
+                    height,
+                    &mut below[0][layer * dst_layer..][..dst_layer],
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn read_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        _desc: &TextureDesc,
+    ) -> Result<Vec<u8>, GfxError> {
+        self.textures
+            .get(&texture.handle)
+            .map(|texture| texture.mips[0].clone())
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))
+    }
+
+    fn begin_frame_native(&mut self) {}
+
+    fn begin_render_pass_native(
+        &mut self,
+        desc: &RenderPassDesc,
+        textures: &ResourcePool<TextureResource>,
+    ) {
+        self.state = CommandState::default();
+
+        for attachment in &desc.color_attachments {
+            let Some(resource) = textures.get(attachment.texture.0) else {
+                continue;
+            };
+            let Some(texture) = self.textures.get_mut(&resource.native.handle) else {
+                continue;
+            };
+            if let LoadOp::Clear(clear) = attachment.load {
+                let format = texture.desc.format;
+                let mut pixel = vec![0; format.bytes_per_pixel() as usize];
+                write_color(format, &mut pixel, [clear.r, clear.g, clear.b, clear.a]);
+                for chunk in texture.mips[0].chunks_exact_mut(pixel.len()) {
+                    chunk.copy_from_slice(&pixel);
+                }
+            }
+            self.state.target_width = texture.desc.width;
+            self.state.target_height = texture.desc.height;
+            self.state.color_attachments.push(resource.native.handle);
+            if let StoreOp::Resolve(target) = attachment.store {
+                if let Some(target) = textures.get(target.0) {
+                    let resolve = (resource.native.handle, target.native.handle);
+                    self.state.resolves.push(resolve);
+                }
+            }
+        }
+
+        if let Some(attachment) = &desc.depth_attachment {
+            if let Some(resource) = textures.get(attachment.texture.0) {
+                if let Some(texture) = self.textures.get_mut(&resource.native.handle) {
+                    if let LoadOp::Clear(clear) = attachment.load {
+                        let depth = clear.depth.to_le_bytes();
+                        for chunk in texture.mips[0].chunks_exact_mut(4) {
+                            chunk.copy_from_slice(&depth);
+                        }
+                    }
+                    self.state.target_width = texture.desc.width;
+                    self.state.target_height = texture.desc.height;
+                    self.state.depth_attachment = Some(resource.native.handle);
+                }
+            }
+        }
+    }
+
+    fn end_render_pass_native(&mut self) {
+        for (source, target) in std::mem::take(&mut self.state.resolves) {
+            let Some(pixels) = self.textures.get(&source).map(|t| t.mips[0].clone()) else {
+                continue;
+            };
+            if let Some(target) = self.textures.get_mut(&target) {
+                target.mips[0] = pixels;
+            }
+        }
+        self.state = CommandState::default();
+    }
+
+    fn bind_pipeline_native(&mut self, pipeline: NativePipeline) {
+        self.state.pipeline = Some(pipeline.handle);
+    }
+
+    fn set_viewport_native(&mut self, viewport: &Viewport) {
+        self.state.viewport = Some(*viewport);
+    }
+
+    fn set_scissor_native(&mut self, scissor: &Rect) {
+        self.state.scissor = Some(*scissor);
+    }
+
+    fn bind_vertex_buffer_native(&mut self, slot: u32, buffer: NativeBuffer, offset: u64) {
+        self.state
+            .vertex_buffers
+            .insert(slot, (buffer.handle, offset));
+    }
+
+    fn bind_index_buffer_native(
+        &mut self,
+        buffer: NativeBuffer,
+        offset: u64,
+        index_type: IndexType,
+    ) {
+        self.state.index_buffer = Some((buffer.handle, offset, index_type));
+    }
+
+    fn bind_group_native(&mut self, index: u32, group: NativeBindGroup) {
+        if let (0, Some(Some(texture))) = (index, self.bind_groups.get(&group.handle)) {
+            self.state.texture = Some(*texture);
+        }
+    }
+
+    fn bind_texture_native(
+        &mut self,
+        set: u32,
+        binding: u32,
+        texture: NativeTexture,
+        sampler: NativeSampler,
+    ) {
+        if (set, binding) == (0, 0) {
+            self.state.texture = Some((texture.handle, sampler.handle));
+        }
+    }
+
+    fn draw_native(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        self.draw_vertices(
+            first_vertex..first_vertex + vertex_count,
+            first_instance..first_instance + instance_count,
+        );
+    }
+
+    fn draw_indexed_native(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        match self.read_indices(index_count, first_index, vertex_offset) {
+            Some(indices) => self.draw_vertices(
+                indices.into_iter(),
+                first_instance..first_instance + instance_count,
+            ),
+            None => eprintln!("Indexed draw without a valid index buffer"),
+        }
+    }
+
+    // No compute pipeline can be created, so there is nothing to bind or run
+    fn bind_compute_pipeline_native(&mut self, _pipeline: NativePipeline) {}
+
+    fn dispatch_native(&mut self, _x: u32, _y: u32, _z: u32) {}
+
+    fn create_surface_native(&mut self, _window: &NativeHandle) -> Result<NativeSurface, GfxError> {
+        Err(GfxError::Unsupported(
+            "the software backend cannot present to a window".to_string(),
+        ))
+    }
+
+    fn create_swapchain_native(
+        &mut self,
+        _surface: Option<NativeSurface>,
+        desc: &TextureDesc,
+        image_count: u32,
+        _present_mode: PresentMode,
+    ) -> NativeSwapchain {
+        let images = (0..image_count)
+            .filter_map(|_| match self.create_texture_native(desc) {
+                Ok(texture) => Some(texture.handle),
+                Err(err) => {
+                    eprintln!("Failed to create swapchain image: {}", err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let handle = self.allocate_handle();
+        let image_count = images.len() as u32;
+        self.swapchains.insert(handle, images);
+        NativeSwapchain {
+            handle,
+            image_count,
+            next_image: 0,
+        }
+    }
+
+    fn swapchain_images_native(&mut self, swapchain: &NativeSwapchain) -> Vec<NativeTexture> {
+        self.swapchains
+            .get(&swapchain.handle)
+            .map(|images| {
+                images
+                    .iter()
+                    .map(|&handle| NativeTexture { handle })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn acquire_next_image_native(&mut self, swapchain: &mut NativeSwapchain) -> Option<u32> {
+        let index = swapchain.next_image;
+        swapchain.next_image = (index + 1) % swapchain.image_count.max(1);
+        Some(index)
+    }
+
+    fn destroy_swapchain_native(&mut self, swapchain: NativeSwapchain) {
+        for handle in self
+            .swapchains
+            .remove(&swapchain.handle)
+            .unwrap_or_default()
+        {
+            self.textures.remove(&handle);
+        }
+    }
+
+    fn present_native(&mut self, _swapchain: &NativeSwapchain, _image_index: u32) {}
+
+    fn wait_idle_native(&mut self) {}
+
+    fn copy_buffer_native(
+        &mut self,
+        src: NativeBuffer,
+        src_offset: u64,
+        dst: NativeBuffer,
+        dst_offset: u64,
+        size: u64,
+    ) {
+        let Some(contents) = self.buffers.get(&src.handle) else {
+            return;
+        };
+        let data = contents[src_offset as usize..][..size as usize].to_vec();
+        self.update_buffer_native(dst, dst_offset as usize, &data);
+    }
+
+    fn copy_buffer_to_texture_native(
+        &mut self,
+        src: NativeBuffer,
+        layout: TexelBufferLayout,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+    ) -> Result<(), GfxError> {
+        let format = self
+            .textures
+            .get(&texture.handle)
+            .map(|texture| texture.desc.format)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))?;
+        let contents = self
+            .buffers
+            .get(&src.handle)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown buffer".to_string()))?;
+        // Drop the row padding and upload as `update_texture` would
+        let (row_bytes, rows) = region_layout(format, region.width, region.height);
+        let data = (0..rows * region.depth as usize)
+            .flat_map(|row| {
+                let start = layout.offset as usize + row * layout.bytes_per_row as usize;
+                &contents[start..start + row_bytes]
+            })
+            .copied()
+            .collect::<Vec<u8>>();
+        self.update_texture_native(texture, mip, layer, region, &data)
+    }
+
+    fn create_query_pool_native(&mut self, count: u32) -> Option<NativeQueryPool> {
+        let handle = self.allocate_handle();
+        self.query_pools.insert(handle, vec![0; count as usize]);
+        Some(NativeQueryPool { handle })
+    }
+
+    fn write_timestamp_native(&mut self, pool: NativeQueryPool, index: u32) {
+        let now = self.epoch.elapsed().as_nanos() as u64;
+        if let Some(query) = self
+            .query_pools
+            .get_mut(&pool.handle)
+            .and_then(|queries| queries.get_mut(index as usize))
+        {
+            *query = now;
+        }
+    }
+
+    fn read_query_pool_native(&mut self, pool: NativeQueryPool, count: u32) -> Option<Vec<u64>> {
+        let queries = self.query_pools.get(&pool.handle)?;
+        queries.get(..count as usize).map(<[u64]>::to_vec)
+    }
+}
+
+/// Zero-filled storage, failing instead of aborting when memory runs out
+fn zeroed(size: usize) -> Result<Vec<u8>, GfxError> {
+    let mut data = Vec::new();
+    data.try_reserve_exact(size)
+        .map_err(|_| GfxError::OutOfMemory)?;
+    data.resize(size, 0);
+    Ok(data)
+}
+
+// ============================================================================
+// Rasterization
+// ============================================================================
+
+/// Writes the fragments of one draw into the pass attachments
+struct Rasterizer<'a> {
+    textures: &'a mut HashMap<u64, SoftwareTexture>,
+    state: &'a CommandState,
+    pipeline: &'a PipelineDesc,
+    sampled: Option<&'a Sampled>,
+    /// Level of detail of the primitive being drawn
+    lod: f32,
+    /// Pixels that may be written: target, viewport and scissor combined
+    /// (x0, y0, x1, y1), end exclusive
+    bounds: (i32, i32, i32, i32),
+}
+
+impl<'a> Rasterizer<'a> {
+    fn new(
+        textures: &'a mut HashMap<u64, SoftwareTexture>,
+        state: &'a CommandState,
+        pipeline: &'a PipelineDesc,
+        sampled: Option<&'a Sampled>,
+    ) -> Self {
+        let mut bounds = (0, 0, state.target_width as i32, state.target_height as i32);
+        if let Some(viewport) = state.viewport {
+            bounds = intersect(
+                bounds,
+                (
+                    viewport.x.floor() as i32,
+                    viewport.y.floor() as i32,
+                    (viewport.x + viewport.width).ceil() as i32,
+                    (viewport.y + viewport.height).ceil() as i32,
+                ),
+            );
+        }
+        if let Some(scissor) = state.scissor {
+            bounds = intersect(
+                bounds,
+                (
+                    scissor.x,
+                    scissor.y,
+                    scissor.x + scissor.width as i32,
+                    scissor.y + scissor.height as i32,
+                ),
+            );
+        }
+        Self {
+            textures,
+            state,
+            pipeline,
+            sampled,
+            lod: 0.0,
+            bounds,
+        }
+    }
+
+    /// Assemble primitives for the pipeline topology and rasterize them
+    ///
+    /// A `None` vertex (unreadable or behind the eye) drops every primitive
+    /// that uses it.
+    fn primitives(&mut self, vertices: &[Option<ScreenVertex>]) {
+        match self.pipeline.topology {
+            PrimitiveTopology::TriangleList => {
+                for triangle in vertices.chunks_exact(3) {
+                    if let [Some(a), Some(b), Some(c)] = *triangle {
+                        self.polygon(a, b, c);
+                    }
+                }
+            }
+            PrimitiveTopology::TriangleStrip => {
+                for (i, triangle) in vertices.windows(3).enumerate() {
+                    if let [Some(a), Some(b), Some(c)] = *triangle {
+                        // Every other triangle is reversed to keep the winding
+                        if i % 2 == 0 {
+                            self.polygon(a, b, c);
+                        } else {
+                            self.polygon(b, a, c);
+                        }
+                    }
+                }
+            }
+            PrimitiveTopology::LineList => {
+                for line in vertices.chunks_exact(2) {
+                    if let [Some(a), Some(b)] = *line {
+                        self.line(a, b);
+                    }
+                }
+            }
+            PrimitiveTopology::LineStrip => {
+                for line in vertices.windows(2) {
+                    if let [Some(a), Some(b)] = *line {
+                        self.line(a, b);
+                    }
+                }
+            }
+            PrimitiveTopology::PointList => {
+                for vertex in vertices.iter().flatten() {
+                    self.point(*vertex);
+                }
+            }
+        }
+    }
+
+    /// Cull a triangle and rasterize it with the pipeline polygon mode
+    fn polygon(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex) {
+        // The framebuffer Y axis points down, so a counter-clockwise triangle
+        // in NDC has a negative area here
+        let area = edge(&a, &b, c.x, c.y);
+        if area == 0.0 {
+            return;
+        }
+        let counter_clockwise = area < 0.0;
+        let front = counter_clockwise
+            == (self.pipeline.rasterizer.front_face == FrontFace::CounterClockwise);
+        let culled = match self.pipeline.rasterizer.cull_mode {
+            CullMode::None => false,
+            CullMode::Front => front,
+            CullMode::Back => !front,
+        };
+        if culled {
+            return;
+        }
+
+        match self.pipeline.rasterizer.polygon_mode {
+            PolygonMode::Fill => self.triangle(a, b, c),
+            PolygonMode::Line => {
+                self.line(a, b);
+                self.line(b, c);
+                self.line(c, a);
+            }
+            PolygonMode::Point => {
+                self.point(a);
+                self.point(b);
+                self.point(c);
+            }
+        }
+    }
+
+    /// Fill a triangle, sampling at pixel centers with the top-left rule
+    fn triangle(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex) {
+        // Order the vertices so the area is positive
+        let (a, b, c) = if edge(&a, &b, c.x, c.y) < 0.0 {
+            (a, c, b)
+        } else {
+            (a, b, c)
+        };
+        let area = edge(&a, &b, c.x, c.y);
+        self.lod = self
+            .sampled
+            .map_or(0.0, |sampled| sampled.lod(&a, &b, &c, area));
+
+        let (x0, y0, x1, y1) = intersect(
+            self.bounds,
+            (
+                a.x.min(b.x).min(c.x).floor() as i32,
+                a.y.min(b.y).min(c.y).floor() as i32,
+                a.x.max(b.x).max(c.x).ceil() as i32,
+                a.y.max(b.y).max(c.y).ceil() as i32,
+            ),
+        );
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(&b, &c, px, py);
+                let wb = edge(&c, &a, px, py);
+                let wc = edge(&a, &b, px, py);
+                if !covers(wa, &b, &c) || !covers(wb, &c, &a) || !covers(wc, &a, &b) {
+                    continue;
+                }
+                let (la, lb, lc) = (wa / area, wb / area, wc / area);
+                let z = la * a.z + lb * b.z + lc * c.z;
+                let inv_w = la * a.inv_w + lb * b.inv_w + lc * c.inv_w;
+                let color = std::array::from_fn(|i| {
+                    (la * a.color[i] + lb * b.color[i] + lc * c.color[i]) / inv_w
+                });
+                let uv =
+                    std::array::from_fn(|i| (la * a.uv[i] + lb * b.uv[i] + lc * c.uv[i]) / inv_w);
+                self.fragment(x, y, z, color, uv);
+            }
+        }
+    }
+
+    /// Draw a line one pixel per step along its major axis, excluding the
+    /// last pixel so strips do not touch shared vertices twice
+    fn line(&mut self, a: ScreenVertex, b: ScreenVertex) {
+        self.lod = 0.0;
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let steps = dx.abs().max(dy.abs()).round() as u32;
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+            let color =
+                std::array::from_fn(|i| (a.color[i] + (b.color[i] - a.color[i]) * t) / inv_w);
+            let uv = std::array::from_fn(|i| (a.uv[i] + (b.uv[i] - a.uv[i]) * t) / inv_w);
+            self.fragment(
+                (a.x + dx * t).floor() as i32,
+                (a.y + dy * t).floor() as i32,
+                a.z + (b.z - a.z) * t,
+                color,
+                uv,
+            );
+        }
+    }
+
+    fn point(&mut self, vertex: ScreenVertex) {
+        self.lod = 0.0;
+        let color = vertex.color.map(|c| c / vertex.inv_w);
+        let uv = vertex.uv.map(|c| c / vertex.inv_w);
+        self.fragment(
+            vertex.x.floor() as i32,
+            vertex.y.floor() as i32,
+            vertex.z,
+            color,
+            uv,
+        );
+    }
+
+    /// Texture, depth test, blend and write one fragment
+    fn fragment(&mut self, x: i32, y: i32, z: f32, color: [f32; 4], uv: [f32; 2]) {
+        let (x0, y0, x1, y1) = self.bounds;
+        if x < x0 || y < y0 || x >= x1 || y >= y1 || !(0.0..=1.0).contains(&z) {
+            return;
+        }
+        let color = match self.sampled {
+            Some(sampled) => {
+                let texel = sampled.sample(uv, self.lod);
+                std::array::from_fn(|i| color[i] * texel[i])
+            }
+            None => color,
+        };
+        let pixel = y as usize * self.state.target_width as usize + x as usize;
+
+        let depth_stencil = self.pipeline.depth_stencil;
+        if let Some(depth) = self
+            .state
+            .depth_attachment
+            .and_then(|handle| self.textures.get_mut(&handle))
+        {
+            if depth_stencil.depth_test_enabled {
+                let Some(stored) = depth.mips[0].get_mut(pixel * 4..pixel * 4 + 4) else {
+                    return;
+                };
+                let dst = f32::from_le_bytes([stored[0], stored[1], stored[2], stored[3]]);
+                if !compare(depth_stencil.depth_compare, z, dst) {
+                    return;
+                }
+                if depth_stencil.depth_write_enabled {
+                    stored.copy_from_slice(&z.to_le_bytes());
+                }
+            }
+        }
+
+        for (i, handle) in self.state.color_attachments.iter().enumerate() {
+            let Some(texture) = self.textures.get_mut(handle) else {
+                continue;
+            };
+            let format = texture.desc.format;
+            let size = format.bytes_per_pixel() as usize;
+            let Some(stored) = texture.mips[0].get_mut(pixel * size..(pixel + 1) * size) else {
+                continue;
+            };
+            let state = self
+                .pipeline
+                .blend_states
+                .get(i)
+                .copied()
+                .unwrap_or_default();
+            let value = if state.enabled {
+                blend(&state, color, read_color(format, stored))
+            } else {
+                color
+            };
+            write_color(format, stored, value);
+        }
+    }
+}
+
+impl Sampled {
+    /// Level of detail of a triangle with the given area: log2 of the
+    /// texels crossed per pixel along the steeper screen axis
+    fn lod(&self, a: &ScreenVertex, b: &ScreenVertex, c: &ScreenVertex, area: f32) -> f32 {
+        let uv = |vertex: &ScreenVertex| vertex.uv.map(|t| t / vertex.inv_w);
+        let (ta, tb, tc) = (uv(a), uv(b), uv(c));
+        let size = [self.desc.width as f32, self.desc.height as f32];
+        // Texel coordinate derivatives along x and y
+        let gradient = |i: usize| {
+            let (db, dc) = ((tb[i] - ta[i]) * size[i], (tc[i] - ta[i]) * size[i]);
+            (
+                (db * (c.y - a.y) - dc * (b.y - a.y)) / area,
+                (dc * (b.x - a.x) - db * (c.x - a.x)) / area,
+            )
+        };
+        let ((dudx, dudy), (dvdx, dvdy)) = (gradient(0), gradient(1));
+        dudx.hypot(dvdx).max(dudy.hypot(dvdy)).log2()
+    }
+
+    fn sample(&self, uv: [f32; 2], lod: f32) -> [f32; 4] {
+        if lod.is_nan() || lod <= 0.0 {
+            return self.sample_mip(0, uv, self.sampler.mag_filter);
+        }
+        let lod = lod.min((self.mips.len() - 1) as f32);
+        let filter = self.sampler.min_filter;
+        match self.sampler.mip_filter {
+            FilterMode::Nearest => self.sample_mip((lod + 0.5) as usize, uv, filter),
+            FilterMode::Linear => {
+                let mip = lod.floor();
+                let near = self.sample_mip(mip as usize, uv, filter);
+                if mip == lod {
+                    return near;
+                }
+                let far = self.sample_mip(mip as usize + 1, uv, filter);
+                lerp(near, far, lod - mip)
+            }
+        }
+    }
+
+    fn sample_mip(&self, mip: usize, [u, v]: [f32; 2], filter: FilterMode) -> [f32; 4] {
+        let extent = TextureRegion::full(&self.desc, mip as u32);
+        let (width, height) = (extent.width as i32, extent.height as i32);
+        let (address_u, address_v) = (self.sampler.address_u, self.sampler.address_v);
+        let texel = |x: i32, y: i32| self.texel(mip, width, x, y);
+        match filter {
+            FilterMode::Nearest => texel(
+                wrap(address_u, (u * width as f32).floor() as i32, width),
+                wrap(address_v, (v * height as f32).floor() as i32, height),
+            ),
+            FilterMode::Linear => {
+                // Texel centers sit at .5
+                let (x, y) = (u * width as f32 - 0.5, v * height as f32 - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let xs = [x0 as i32, x0 as i32 + 1].map(|x| wrap(address_u, x, width));
+                let ys = [y0 as i32, y0 as i32 + 1].map(|y| wrap(address_v, y, height));
+                let top = lerp(texel(xs[0], ys[0]), texel(xs[1], ys[0]), fx);
+                let bottom = lerp(texel(xs[0], ys[1]), texel(xs[1], ys[1]), fx);
+                lerp(top, bottom, fy)
+            }
+        }
+    }
+
+    fn texel(&self, mip: usize, width: i32, x: i32, y: i32) -> [f32; 4] {
+        let format = self.desc.format;
+        let size = format.bytes_per_pixel() as usize;
+        let start = (y as usize * width as usize + x as usize) * size;
+        match self.mips[mip].get(start..start + size) {
+            Some(bytes) if format.is_depth() => {
+                let depth = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                [depth, depth, depth, 1.0]
+            }
+            Some(bytes) => read_color(format, bytes),
+            None => [0.0; 4],
+        }
+    }
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Average 2x2 blocks of `src` (`width` x `height` texels) into the next
+/// mip level, repeating the last row or column of odd sizes
+fn downsample(format: TextureFormat, src: &[u8], width: usize, height: usize, dst: &mut [u8]) {
+    let size = format.bytes_per_pixel() as usize;
+    let (dst_width, dst_height) = ((width / 2).max(1), (height / 2).max(1));
+    for y in 0..dst_height {
+        for x in 0..dst_width {
+            let mut sum = [0.0; 4];
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (sx, sy) = ((2 * x + dx).min(width - 1), (2 * y + dy).min(height - 1));
+                let start = (sy * width + sx) * size;
+                let texel = read_color(format, &src[start..start + size]);
+                sum = std::array::from_fn(|i| sum[i] + texel[i]);
+            }
+            let start = (y * dst_width + x) * size;
+            write_color(format, &mut dst[start..start + size], sum.map(|c| c / 4.0));
+        }
+    }
+}
+
+/// Texel index `i` of a `size` texel axis after wrapping
+fn wrap(mode: AddressMode, i: i32, size: i32) -> i32 {
+    match mode {
+        AddressMode::Repeat => i.rem_euclid(size),
+        AddressMode::MirrorRepeat => {
+            let i = i.rem_euclid(2 * size);
+            if i < size {
+                i
+            } else {
+                2 * size - 1 - i
+            }
+        }
+        AddressMode::ClampToEdge => i.clamp(0, size - 1),
+    }
+}
+
+/// Signed area of the parallelogram (a, b, p); positive when `p` is right of
+/// `a -> b` with the Y axis pointing down
+fn edge(a: &ScreenVertex, b: &ScreenVertex, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+/// Top-left rule: samples exactly on an edge belong to top and left edges only
+fn covers(weight: f32, a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    if weight != 0.0 {
+        return weight > 0.0;
+    }
+    let top = a.y == b.y && b.x > a.x;
+    let left = b.y < a.y;
+    top || left
+}
+
+fn intersect(a: (i32, i32, i32, i32), b: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+    (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3))
+}
+
+fn compare(function: CompareFunction, src: f32, dst: f32) -> bool {
+    match function {
+        CompareFunction::Never => false,
+        CompareFunction::Less => src < dst,
+        CompareFunction::Equal => src == dst,
+        CompareFunction::LessEqual => src <= dst,
+        CompareFunction::Greater => src > dst,
+        CompareFunction::NotEqual => src != dst,
+        CompareFunction::GreaterEqual => src >= dst,
+        CompareFunction::Always => true,
+    }
+}
+
+fn blend(state: &BlendState, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+    let factor = |factor: BlendFactor| -> [f32; 4] {
+        match factor {
+            BlendFactor::Zero => [0.0; 4],
+            BlendFactor::One => [1.0; 4],
+            BlendFactor::SrcColor => src,
+            BlendFactor::OneMinusSrcColor => src.map(|c| 1.0 - c),
+            BlendFactor::DstColor => dst,
+            BlendFactor::OneMinusDstColor => dst.map(|c| 1.0 - c),
+            BlendFactor::SrcAlpha => [src[3]; 4],
+            BlendFactor::OneMinusSrcAlpha => [1.0 - src[3]; 4],
+            BlendFactor::DstAlpha => [dst[3]; 4],
+            BlendFactor::OneMinusDstAlpha => [1.0 - dst[3]; 4],
+        }
+    };
+    let apply = |op: BlendOp, s: f32, fs: f32, d: f32, fd: f32| match op {
+        BlendOp::Add => s * fs + d * fd,
+        BlendOp::Subtract => s * fs - d * fd,
+        BlendOp::ReverseSubtract => d * fd - s * fs,
+        BlendOp::Min => s.min(d),
+        BlendOp::Max => s.max(d),
+    };
+
+    let (src_color, dst_color) = (factor(state.src_color), factor(state.dst_color));
+    let (src_alpha, dst_alpha) = (factor(state.src_alpha), factor(state.dst_alpha));
+    let mut out = [0.0; 4];
+    for i in 0..3 {
+        out[i] = apply(state.color_op, src[i], src_color[i], dst[i], dst_color[i]);
+    }
+    out[3] = apply(state.alpha_op, src[3], src_alpha[3], dst[3], dst_alpha[3]);
+    out
+}
+
+// ============================================================================
+// Pixel Formats
+// ============================================================================
+
+pub(super) fn write_color(format: TextureFormat, bytes: &mut [u8], color: [f32; 4]) {
+    let unorm = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    match format {
+        TextureFormat::Rgba8 => {
+            bytes.copy_from_slice(&color.map(unorm));
+        }
+        TextureFormat::Rgba8Srgb => {
+            let [r, g, b, a] = color;
+            bytes.copy_from_slice(&[
+                unorm(linear_to_srgb(r)),
+                unorm(linear_to_srgb(g)),
+                unorm(linear_to_srgb(b)),
+                unorm(a),
+            ]);
+        }
+        TextureFormat::Bgra8 => {
+            let [r, g, b, a] = color.map(unorm);
+            bytes.copy_from_slice(&[b, g, r, a]);
+        }
+        TextureFormat::Rgba16f => {
+            for (chunk, c) in bytes.chunks_exact_mut(2).zip(color) {
+                chunk.copy_from_slice(&f32_to_f16(c).to_le_bytes());
+            }
+        }
+        TextureFormat::Rgba32f => {
+            for (chunk, c) in bytes.chunks_exact_mut(4).zip(color) {
+                chunk.copy_from_slice(&c.to_le_bytes());
+            }
+        }
+        // Depth is written by the depth test; compressed formats are not
+        // renderable
+        _ => {}
+    }
+}
+
+pub(super) fn read_color(format: TextureFormat, bytes: &[u8]) -> [f32; 4] {
+    let unorm = |c: u8| c as f32 / 255.0;
+    match format {
+        TextureFormat::Rgba8 => [
+            unorm(bytes[0]),
+            unorm(bytes[1]),
+            unorm(bytes[2]),
+            unorm(bytes[3]),
+        ],
+        TextureFormat::Rgba8Srgb => [
+            srgb_to_linear(unorm(bytes[0])),
+            srgb_to_linear(unorm(bytes[1])),
+            srgb_to_linear(unorm(bytes[2])),
+            unorm(bytes[3]),
+        ],
+        TextureFormat::Bgra8 => [
+            unorm(bytes[2]),
+            unorm(bytes[1]),
+            unorm(bytes[0]),
+            unorm(bytes[3]),
+        ],
+        TextureFormat::Rgba16f => std::array::from_fn(|i| {
+            f16_to_f32(u16::from_le_bytes([bytes[2 * i], bytes[2 * i + 1]]))
+        }),
+        TextureFormat::Rgba32f => std::array::from_fn(|i| {
+            f32::from_le_bytes([
+                bytes[4 * i],
+                bytes[4 * i + 1],
+                bytes[4 * i + 2],
+                bytes[4 * i + 3],
+            ])
+        }),
+        _ => [0.0; 4],
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// IEEE 754 binary16, rounding to nearest
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    if value.is_nan() {
+        return sign | 0x7e00;
+    }
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent >= 31 {
+        return sign | 0x7c00;
+    }
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        // Subnormal
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let half = (mantissa >> shift) + ((mantissa >> (shift - 1)) & 1);
+        return sign | half as u16;
+    }
+    // A carry out of the mantissa correctly bumps the exponent (up to inf)
+    let half = ((exponent as u32) << 10 | mantissa >> 13) + ((mantissa >> 12) & 1);
+    sign | half.min(0x7c00) as u16
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => sign * f32::INFINITY,
+        31 => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::pipeline_cache::PipelineCache;
+    use crate::gfx::profiler::GpuProfiler;
+    use crate::gfx::uniform_allocator::DynamicUniformAllocator;
+    use crate::gfx::upload::UploadContext;
+    use avila_math::memory::{AllocatorType, MemoryManager};
+    use avila_math::os::{Profiler, ThreadPool};
+
+    const SIZE: u32 = 8;
+
+    fn device() -> BackendDevice {
+        create_device(RendererConfig {
+            width: SIZE,
+            height: SIZE,
+            backend: Backend::Software,
+            ..Default::default()
+        })
+    }
+
+    fn target(device: &mut BackendDevice) -> TextureHandle {
+        device
+            .create_texture(
+                &TextureDesc::new_2d(
+                    SIZE,
+                    SIZE,
+                    TextureFormat::Rgba8,
+                    TextureUsage::COLOR_ATTACHMENT | TextureUsage::TRANSFER_SRC,
+                ),
+                None,
+            )
+            .unwrap()
+    }
+
+    /// Pipeline taking `Float2` positions and `Float4` colors
+    fn pipeline(device: &mut BackendDevice, topology: PrimitiveTopology) -> PipelineDesc {
+        let vertex_shader = device
+            .create_shader(&ShaderDesc {
+                stage: ShaderStage::Vertex,
+                entry_point: "main".into(),
+                code: Vec::new(),
+            })
+            .unwrap();
+        let fragment_shader = device
+            .create_shader(&ShaderDesc {
+                stage: ShaderStage::Fragment,
+                entry_point: "main".into(),
+                code: Vec::new(),
+            })
+            .unwrap();
+        PipelineDesc {
+            vertex_shader,
+            fragment_shader,
+            vertex_layout: VertexLayout {
+                stride: 24,
+                attributes: vec![
+                    VertexAttribute {
+                        format: VertexFormat::Float2,
+                        offset: 0,
+                        location: 0,
+                    },
+                    VertexAttribute {
+                        format: VertexFormat::Float4,
+                        offset: 8,
+                        location: 1,
+                    },
+                ],
+                buffers: Vec::new(),
+            },
+            topology,
+            rasterizer: RasterizerState::default(),
+            depth_stencil: DepthStencilState {
+                depth_test_enabled: false,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+            },
+            blend_states: vec![BlendState::default()],
+            color_formats: vec![TextureFormat::Rgba8],
+            depth_format: None,
+            sample_count: 1,
+            layout: PipelineLayoutDesc::default(),
+        }
+    }
+
+    /// Full-screen quad sampling `texels` (RGBA8, `width` wide, with a full
+    /// mip chain generated from them) through `sampler`, with `uv` running
+    /// from `(0, 0)` at the top left to `uv_max`
+    ///
+    /// The texture is bound with `bind_texture`, or in a bind group next to
+    /// its sampler if `bind_group` is set.
+    fn textured_quad(
+        device: &mut BackendDevice,
+        (width, texels): (u32, &[u8]),
+        sampler: SamplerDesc,
+        uv_max: f32,
+        bind_group: bool,
+    ) -> TextureHandle {
+        let target = target(device);
+        let height = texels.len() as u32 / 4 / width;
+        let mip_levels = u32::BITS - width.max(height).leading_zeros();
+        let texture = device
+            .create_texture(
+                &TextureDesc::new_2d(width, height, TextureFormat::Rgba8, TextureUsage::SAMPLED)
+                    .with_mips(mip_levels),
+                Some(texels),
+            )
+            .unwrap();
+        let sampler = device.create_sampler(&sampler).unwrap();
+
+        let mut desc = pipeline(device, PrimitiveTopology::TriangleList);
+        desc.vertex_layout.stride = 32;
+        desc.vertex_layout.attributes.push(VertexAttribute {
+            format: VertexFormat::Float2,
+            offset: 24,
+            location: 2,
+        });
+        let group = if bind_group {
+            let layout = device
+                .create_bind_group_layout(&BindGroupLayoutDesc {
+                    entries: vec![
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            ty: BindingType::Texture {
+                                dimension: TextureDimension::D2,
+                                array: false,
+                                comparison: false,
+                            },
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            ty: BindingType::Sampler { comparison: false },
+                        },
+                    ],
+                })
+                .unwrap();
+            desc.layout.bind_groups.push(layout);
+            let group = device
+                .create_bind_group(&BindGroupDesc {
+                    layout,
+                    entries: vec![
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(sampler),
+                        },
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Texture(texture),
+                        },
+                    ],
+                })
+                .unwrap();
+            Some(group)
+        } else {
+            desc.layout.textures.push(TextureBinding::new_2d(0, 0));
+            None
+        };
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let corner = |x: f32, y: f32| {
+            let uv = [(x + 1.0) / 2.0 * uv_max, (1.0 - y) / 2.0 * uv_max];
+            [x, y, 1.0, 1.0, 1.0, 1.0, uv[0], uv[1]]
+        };
+        let bytes: Vec<u8> = [
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, 1.0),
+        ]
+        .iter()
+        .flatten()
+        .flat_map(|f| f.to_le_bytes())
+        .collect();
+        let buffer = device
+            .create_buffer(&BufferDesc::vertex(bytes.len()), Some(&bytes))
+            .unwrap();
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(target));
+        cmd.bind_pipeline(pipeline);
+        match group {
+            Some(group) => cmd.bind_group(0, group),
+            None => cmd.bind_texture(0, 0, texture, sampler),
+        }
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(6, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+        target
+    }
+
+    fn vertices(device: &mut BackendDevice, data: &[[f32; 6]]) -> BufferHandle {
+        let bytes: Vec<u8> = data
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        device
+            .create_buffer(&BufferDesc::vertex(bytes.len()), Some(&bytes))
+            .unwrap()
+    }
+
+    fn pass(texture: TextureHandle) -> RenderPassDesc {
+        RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture,
+                load: LoadOp::Clear(ClearColor::BLACK),
+                store: StoreOp::Store,
+            }],
+            depth_attachment: None,
+        }
+    }
+
+    /// One character per pixel: `.` black, `R`/`G`/`B`/`W` for saturated
+    /// colors, `?` otherwise
+    fn image(device: &mut BackendDevice, texture: TextureHandle) -> Vec<String> {
+        let pixels = device.read_texture(texture).expect("software readback");
+        pixels
+            .chunks_exact(4 * SIZE as usize)
+            .map(|row| {
+                row.chunks_exact(4)
+                    .map(|p| match (p[0], p[1], p[2]) {
+                        (0, 0, 0) => '.',
+                        (255, 0, 0) => 'R',
+                        (0, 255, 0) => 'G',
+                        (0, 0, 255) => 'B',
+                        (255, 255, 255) => 'W',
+                        _ => '?',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    /// 2x2 RGBA8 texture: red and green on top, blue and white below
+    #[rustfmt::skip]
+    const CHECKER: [u8; 16] = [
+        255, 0, 0, 255,     0, 255, 0, 255,
+        0, 0, 255, 255,     255, 255, 255, 255,
+    ];
+
+    fn vertex(x: f32, y: f32, color: [f32; 4]) -> [f32; 6] {
+        [x, y, color[0], color[1], color[2], color[3]]
+    }
+
+    #[test]
+    fn test_software_adapter() {
+        let adapters = enumerate_adapters();
+        let software: Vec<_> = adapters
+            .iter()
+            .filter(|adapter| adapter.backend == Backend::Software)
+            .collect();
+        assert_eq!(software, [&adapter_info()]);
+        assert_eq!(software[0].adapter_type, AdapterType::Cpu);
+        // Listed after the GPUs
+        assert_eq!(adapters.last(), Some(&adapter_info()));
+
+        // The only adapter of its backend, whatever the preference
+        for adapter_preference in [
+            AdapterPreference::HighPerformance,
+            AdapterPreference::LowPower,
+        ] {
+            let device = create_device(RendererConfig {
+                backend: Backend::Software,
+                adapter_preference,
+                ..Default::default()
+            });
+            assert_eq!(device.adapter_info(), adapter_info());
+        }
+    }
+
+    #[test]
+    fn test_window_unsupported() {
+        let window = avila_math::window::NativeHandle::Xcb {
+            connection: std::ptr::null_mut(),
+            window: 42,
+            screen: 0,
+        };
+        let config = RendererConfig {
+            backend: Backend::Software,
+            ..Default::default()
+        };
+        assert!(matches!(
+            BackendDevice::with_surface(config, Some(window)),
+            Err(GfxError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture,
+                load: LoadOp::Clear(ClearColor::WHITE),
+                store: StoreOp::Store,
+            }],
+            depth_attachment: None,
+        });
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(image(&mut device, texture), vec!["WWWWWWWW"; 8]);
+    }
+
+    #[test]
+    fn test_load_store_ops() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let attachment = |texture, load, store| RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture,
+                load,
+                store,
+            }],
+            depth_attachment: None,
+        };
+        let run = |device: &mut BackendDevice, desc: RenderPassDesc| {
+            let mut cmd = device.begin_frame();
+            cmd.begin_render_pass(desc);
+            cmd.end_render_pass();
+            device.submit(cmd);
+        };
+
+        run(&mut device, pass(texture));
+        let white = LoadOp::Clear(ClearColor::WHITE);
+        run(&mut device, attachment(texture, white, StoreOp::DontCare));
+        run(
+            &mut device,
+            attachment(texture, LoadOp::Load, StoreOp::Store),
+        );
+        // Contents left undefined stay as they were in the software backend
+        assert_eq!(image(&mut device, texture), vec!["WWWWWWWW"; 8]);
+
+        let msaa = TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            TextureFormat::Rgba8,
+            TextureUsage::COLOR_ATTACHMENT,
+        )
+        .with_msaa(4);
+        let multisampled = device.create_texture(&msaa, None).unwrap();
+        let red = LoadOp::Clear(ClearColor {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        });
+        let resolve = StoreOp::Resolve(texture);
+        run(&mut device, attachment(multisampled, red, resolve));
+        assert_eq!(image(&mut device, texture), vec!["RRRRRRRR"; 8]);
+
+        // Invalid resolves skip the whole pass
+        let small = TextureDesc::new_2d(4, 4, TextureFormat::Rgba8, TextureUsage::COLOR_ATTACHMENT)
+            .with_msaa(4);
+        let small = device.create_texture(&small, None).unwrap();
+        let depth = TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            TextureFormat::Depth32f,
+            TextureUsage::DEPTH_ATTACHMENT,
+        );
+        let depth = device.create_texture(&depth, None).unwrap();
+        for desc in [
+            attachment(small, white, resolve),
+            attachment(texture, white, StoreOp::Resolve(multisampled)),
+            attachment(multisampled, white, StoreOp::Resolve(TextureHandle(99))),
+            RenderPassDesc {
+                depth_attachment: Some(DepthAttachment {
+                    texture: depth,
+                    load: LoadOp::Clear(ClearDepthStencil::default()),
+                    store: StoreOp::Resolve(depth),
+                }),
+                ..pass(texture)
+            },
+        ] {
+            assert!(validate_render_pass(&desc, &device.textures).is_err());
+            run(&mut device, desc);
+        }
+        assert_eq!(image(&mut device, texture), vec!["RRRRRRRR"; 8]);
+    }
+
+    #[test]
+    fn test_msaa() {
+        let mut device = create_device(RendererConfig {
+            width: SIZE,
+            height: SIZE,
+            msaa_samples: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let msaa = device.get_msaa_texture();
+        assert_eq!(device.textures.get(msaa.0).unwrap().desc.samples, 4);
+        // The device owns it, like the swapchain images
+        device.destroy_texture(msaa);
+        assert!(device.textures.get(msaa.0).is_some());
+
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        desc.color_formats = vec![TextureFormat::Bgra8];
+        let invalid = PipelineDesc {
+            sample_count: 3,
+            ..desc.clone()
+        };
+        assert!(device.create_pipeline(&invalid).is_err());
+        let single = device.create_pipeline(&desc).unwrap();
+        desc.sample_count = 4;
+        let multi = device.create_pipeline(&desc).unwrap();
+        const WHITE: [f32; 4] = [1.0; 4];
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, WHITE),
+                vertex(3.0, -1.0, WHITE),
+                vertex(-1.0, 3.0, WHITE),
+            ],
+        );
+        let draw = |device: &mut BackendDevice, pipeline| {
+            let mut cmd = device.begin_frame();
+            let swapchain = device.get_swapchain_texture();
+            cmd.begin_render_pass(RenderPassDesc {
+                color_attachments: vec![ColorAttachment {
+                    texture: msaa,
+                    load: LoadOp::Clear(ClearColor::BLACK),
+                    store: StoreOp::Resolve(swapchain),
+                }],
+                depth_attachment: None,
+            });
+            cmd.bind_pipeline(pipeline);
+            cmd.bind_vertex_buffer(0, buffer, 0);
+            cmd.draw(3, 1, 0, 0);
+            cmd.end_render_pass();
+            device.submit(cmd);
+            image(device, swapchain)
+        };
+        // A single-sampled pipeline does not draw to the multisampled target
+        assert_eq!(draw(&mut device, single), vec!["........"; 8]);
+        assert_eq!(draw(&mut device, multi), vec!["WWWWWWWW"; 8]);
+
+        let mixed = RenderPassDesc {
+            color_attachments: vec![
+                pass(msaa).color_attachments[0].clone(),
+                pass(target(&mut device)).color_attachments[0].clone(),
+            ],
+            depth_attachment: None,
+        };
+        assert!(validate_render_pass(&mixed, &device.textures).is_err());
+
+        // The target follows the swapchain size
+        device.resize(2 * SIZE, SIZE);
+        let resized = device.get_msaa_texture();
+        let desc = &device.textures.get(resized.0).unwrap().desc;
+        assert_eq!((desc.width, desc.height, desc.samples), (2 * SIZE, SIZE, 4));
+
+        let mut device = create_device(RendererConfig {
+            msaa_samples: 3,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        assert_eq!(device.get_msaa_texture(), TextureHandle::INVALID);
+        assert_eq!(device.config.msaa_samples, 1);
+        device.resize(SIZE, SIZE);
+        assert_eq!(device.get_msaa_texture(), TextureHandle::INVALID);
+    }
+
+    #[test]
+    fn test_secondary_command_lists() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        // One horizontal strip of two rows per list, top to bottom
+        let colors = [RED, [0.0, 1.0, 0.0, 1.0], BLUE, [1.0; 4]];
+        let strips: Vec<BufferHandle> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| {
+                let top = 1.0 - i as f32 * 0.5;
+                let bottom = top - 0.5;
+                vertices(
+                    &mut device,
+                    &[
+                        vertex(-1.0, bottom, color),
+                        vertex(1.0, bottom, color),
+                        vertex(1.0, top, color),
+                        vertex(-1.0, bottom, color),
+                        vertex(1.0, top, color),
+                        vertex(-1.0, top, color),
+                    ],
+                )
+            })
+            .collect();
+
+        let pool = ThreadPool::new(4);
+        let lists = CommandList::record_parallel(&pool, strips.len(), |i, list| {
+            list.bind_pipeline(pipeline);
+            list.bind_vertex_buffer(0, strips[i], 0);
+            list.draw(6, 1, 0, 0);
+        });
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        for list in lists {
+            cmd.execute_commands(list).unwrap();
+        }
+
+        // Compute work and passes cannot nest in a render pass
+        let mut compute = CommandList::new();
+        compute.dispatch(1, 1, 1);
+        assert!(cmd.execute_commands(compute).is_err());
+        let mut nested = CommandList::new();
+        nested.begin_render_pass(pass(texture));
+        assert!(cmd.execute_commands(nested).is_err());
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        let strips = image(&mut device, texture);
+        for (rows, expected) in strips
+            .chunks(2)
+            .zip(["RRRRRRRR", "GGGGGGGG", "BBBBBBBB", "WWWWWWWW"])
+        {
+            assert_eq!(rows, [expected; 2]);
+        }
+
+        // Outside a render pass a secondary list may hold whole passes, and
+        // one left open keeps the primary list inside it
+        let mut cmd = device.begin_frame();
+        let mut clear = CommandList::new();
+        clear.begin_render_pass(pass(texture));
+        cmd.execute_commands(clear).unwrap();
+        let mut compute = CommandList::new();
+        compute.dispatch(1, 1, 1);
+        assert!(cmd.execute_commands(compute).is_err());
+        cmd.end_render_pass();
+        device.submit(cmd);
+        assert_eq!(image(&mut device, texture), vec!["........"; 8]);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let triangle = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, RED),
+                vertex(1.0, -1.0, RED),
+                vertex(-1.0, 1.0, RED),
+            ],
+        );
+        let record_frame = |device: &mut BackendDevice| {
+            let mut cmd = device.begin_frame();
+            cmd.write_timestamp("clear");
+            cmd.begin_render_pass(pass(texture));
+            cmd.end_render_pass();
+            cmd.write_timestamp("draw");
+            cmd.begin_render_pass(pass(texture));
+            cmd.bind_pipeline(pipeline);
+            cmd.bind_vertex_buffer(0, triangle, 0);
+            cmd.draw(3, 1, 0, 0);
+            cmd.write_timestamp("end");
+            cmd.end_render_pass();
+            device.submit(cmd);
+        };
+
+        // Results come back once the frame is presented
+        record_frame(&mut device);
+        assert!(device.read_timestamps().is_empty());
+        device.present();
+        let frames = device.read_timestamps();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].frame, 0);
+        let names: Vec<&str> = frames[0]
+            .timestamps
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["clear", "draw", "end"]);
+        assert!(frames[0]
+            .timestamps
+            .windows(2)
+            .all(|pair| pair[0].1 <= pair[1].1));
+        let sections: Vec<String> = frames[0]
+            .sections()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(sections, ["clear", "draw"]);
+        assert!(device.read_timestamps().is_empty());
+
+        // Frames left unread past the pools in flight are kept, in order
+        for _ in 0..5 {
+            record_frame(&mut device);
+            device.present();
+        }
+        let frames: Vec<u64> = device.read_timestamps().iter().map(|f| f.frame).collect();
+        assert_eq!(frames, [1, 2, 3, 4, 5]);
+
+        // A frame without timestamps has nothing to read
+        device.present();
+        assert!(device.read_timestamps().is_empty());
+
+        let mut gpu_profiler = GpuProfiler::new();
+        let mut profiler = Profiler::new();
+        record_frame(&mut device);
+        device.present();
+        gpu_profiler.update(&mut device, &mut profiler);
+        assert_eq!(gpu_profiler.last_frame().map(|f| f.frame), Some(7));
+        assert!(gpu_profiler.frame_time().is_some());
+        for name in ["gpu/clear", "gpu/draw", "gpu/frame"] {
+            assert!(profiler.average(name).is_some(), "{} not recorded", name);
+        }
+        assert!(profiler.average("gpu/end").is_none());
+    }
+
+    #[test]
+    fn test_pipeline_cache() {
+        let mut device = device();
+        let triangles = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let mut cache = PipelineCache::new();
+
+        let first = cache.get_or_create(&mut device, &triangles).unwrap();
+        let second = cache
+            .get_or_create(&mut device, &triangles.clone())
+            .unwrap();
+        assert_eq!(first, second);
+        let lines = PipelineDesc {
+            topology: PrimitiveTopology::LineList,
+            ..triangles.clone()
+        };
+        let third = cache.get_or_create(&mut device, &lines).unwrap();
+        assert_ne!(first, third);
+
+        // Failures count as misses but are not kept
+        let broken = PipelineDesc {
+            vertex_shader: ShaderHandle::INVALID,
+            ..triangles.clone()
+        };
+        assert!(cache.get_or_create(&mut device, &broken).is_err());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.pipelines), (1, 3, 2));
+        assert_eq!(stats.hit_rate(), 0.25);
+
+        cache.clear(&mut device);
+        assert_eq!(cache.stats().pipelines, 0);
+        assert!(device.pipelines.get(first.0).is_none());
+        assert!(device.pipelines.get(third.0).is_none());
+
+        // The software backend has no native cache to persist
+        let path = std::env::temp_dir().join(format!("avila-pipelines-{}", std::process::id()));
+        cache.save(&mut device, &path).unwrap();
+        assert!(!path.exists());
+        assert!(!cache.load(&mut device, &path).unwrap());
+
+        // Blobs are only handed to the backend they came from, intact
+        let identity = device.pipeline_cache_identity();
+        let native = b"native cache".to_vec();
+        let mut blob = PIPELINE_CACHE_MAGIC.to_vec();
+        blob.extend_from_slice(&PIPELINE_CACHE_VERSION.to_le_bytes());
+        blob.extend_from_slice(&(identity.len() as u32).to_le_bytes());
+        blob.extend_from_slice(identity.as_bytes());
+        blob.extend_from_slice(&crc32(&native).to_le_bytes());
+        blob.extend_from_slice(&native);
+        assert_eq!(pipeline_cache_payload(&blob, &identity), Some(&native[..]));
+        assert_eq!(pipeline_cache_payload(&blob, "Wgpu\nother\nadapter"), None);
+        let last = blob.len() - 1;
+        blob[last] ^= 1;
+        assert_eq!(pipeline_cache_payload(&blob, &identity), None);
+        assert_eq!(pipeline_cache_payload(&blob[..8], &identity), None);
+        assert!(!device.load_pipeline_cache_data(b"not a pipeline cache"));
+    }
+
+    #[test]
+    fn test_spirv_reflection() {
+        let mut device = device();
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let spirv = ShaderDesc {
+            stage: ShaderStage::Vertex,
+            entry_point: "main".into(),
+            code: crate::gfx::reflection::tests::vertex_module(),
+        };
+        desc.vertex_shader = device.create_shader(&spirv).unwrap();
+        let reflection = device
+            .shaders
+            .get(desc.vertex_shader.0)
+            .unwrap()
+            .reflection
+            .clone();
+        let reflection = reflection.unwrap();
+
+        // The shader binds resources the empty layout does not provide
+        let err = device.create_pipeline(&desc).unwrap_err();
+        assert!(err.to_string().contains("binding 0 of set 0"));
+
+        desc.layout =
+            ShaderReflection::create_pipeline_layout(&mut device, &[&reflection]).unwrap();
+        assert_eq!(desc.layout.bind_groups.len(), 2);
+        device.create_pipeline(&desc).unwrap();
+
+        // Attributes of the wrong type or missing fail at creation
+        desc.vertex_layout.attributes[1].format = VertexFormat::UInt4;
+        assert!(device.create_pipeline(&desc).is_err());
+        desc.vertex_layout.attributes.truncate(1);
+        assert!(device.create_pipeline(&desc).is_err());
+
+        // As do bind group layouts that do not match
+        desc.vertex_layout = reflection.vertex_layout().unwrap();
+        device.destroy_bind_group_layout(desc.layout.bind_groups[1]);
+        desc.layout.bind_groups[1] = device
+            .create_bind_group_layout(&BindGroupLayoutDesc {
+                entries: vec![BindGroupLayoutEntry {
+                    binding: 0,
+                    ty: BindingType::UniformBuffer,
+                }],
+            })
+            .unwrap();
+        let err = device.create_pipeline(&desc).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("binding 0 of set 1 is UniformBuffer"));
+
+        // A set past the bind groups takes the texture and its sampler
+        desc.layout.bind_groups.truncate(1);
+        desc.layout.textures = vec![TextureBinding::new_2d(1, 0)];
+        device.create_pipeline(&desc).unwrap();
+
+        // SPIR-V without the entry point is rejected up front
+        let missing = ShaderDesc {
+            entry_point: "other".into(),
+            ..spirv
+        };
+        assert!(device.create_shader(&missing).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_uniforms() {
+        let mut device = device();
+        let mut uniforms = DynamicUniformAllocator::new(&mut device, 1024).unwrap();
+
+        let first = uniforms.allocate(&[1, 2, 3]).unwrap();
+        let second = uniforms.allocate(&[4; 16]).unwrap();
+        assert_eq!((first.offset, first.size), (0, 3));
+        assert_eq!((second.offset, second.size), (256, 16));
+        assert_eq!(
+            second.binding(),
+            BindingResource::Buffer {
+                buffer: uniforms.buffer(),
+                offset: 256,
+                size: Some(16),
+            }
+        );
+
+        // Nothing reaches the buffer before the frame ends
+        let contents = device.map_buffer(uniforms.buffer());
+        assert_eq!(unsafe { *contents }, 0);
+        device.unmap_buffer(uniforms.buffer());
+        uniforms.end_frame(&mut device);
+        let contents = device.map_buffer(uniforms.buffer());
+        let bytes = unsafe { std::slice::from_raw_parts(contents, 1024) };
+        assert_eq!(&bytes[..4], &[1, 2, 3, 0]);
+        assert_eq!(&bytes[256..272], &[4; 16]);
+        device.unmap_buffer(uniforms.buffer());
+
+        // Frames in flight keep their slices until three newer frames end
+        uniforms.allocate(&[5; 4]).unwrap();
+        uniforms.end_frame(&mut device);
+        uniforms.allocate(&[6; 4]).unwrap();
+        assert_eq!(uniforms.allocate(&[7; 4]), Err(GfxError::OutOfMemory));
+        uniforms.end_frame(&mut device);
+        uniforms.end_frame(&mut device);
+        let reused = uniforms.allocate(&[8; 4]).unwrap();
+        assert_eq!(reused.offset, 0);
+        assert_eq!(uniforms.allocator_info().allocation_count, 5);
+
+        uniforms.destroy(&mut device);
+    }
+
+    #[test]
+    fn test_upload_context() {
+        let mut device = device();
+        let mut uploads = UploadContext::new(&mut device, 1024).unwrap();
+
+        let buffer = uploads
+            .create_buffer(&mut device, &BufferDesc::vertex(6), &[1, 2, 3, 4, 5, 6])
+            .unwrap();
+        uploads
+            .upload_buffer(&mut device, buffer, 4, &[9; 4])
+            .unwrap();
+        let desc = TextureDesc::new_2d(
+            2,
+            2,
+            TextureFormat::Rgba8,
+            TextureUsage::SAMPLED | TextureUsage::TRANSFER_SRC,
+        );
+        let texels = (0..16).collect::<Vec<u8>>();
+        let texture = uploads.create_texture(&mut device, &desc, &texels).unwrap();
+
+        // Nothing is copied before the flush
+        assert_eq!(device.read_texture(texture).unwrap(), vec![0; 16]);
+        uploads.flush(&mut device);
+        assert_eq!(device.read_texture(texture).unwrap(), texels);
+        let contents = device.map_buffer(buffer);
+        let bytes = unsafe { std::slice::from_raw_parts(contents, 8) };
+        assert_eq!(bytes, &[1, 2, 3, 4, 9, 9, 9, 9]);
+        device.unmap_buffer(buffer);
+
+        // The staging space comes back once the GPU reaches the fence
+        assert_eq!(uploads.flushes_in_flight(), 1);
+        uploads.recycle(&mut device);
+        assert_eq!(uploads.flushes_in_flight(), 0);
+        assert_eq!(uploads.allocator_info().used, 0);
+
+        // A full ring flushes what it holds and waits for the GPU
+        let large = device
+            .create_buffer(&BufferDesc::storage(1024), None)
+            .unwrap();
+        uploads
+            .upload_buffer(&mut device, large, 0, &[1; 600])
+            .unwrap();
+        uploads
+            .upload_buffer(&mut device, large, 600, &[2; 1000 - 600])
+            .unwrap();
+        uploads
+            .upload_buffer(&mut device, large, 0, &[3; 1000])
+            .unwrap();
+        let contents = device.map_buffer(large);
+        let bytes = unsafe { std::slice::from_raw_parts(contents, 1000) };
+        assert_eq!((bytes[0], bytes[599], bytes[600]), (1, 1, 2));
+        device.unmap_buffer(large);
+        uploads.flush(&mut device);
+        let contents = device.map_buffer(large);
+        assert_eq!(unsafe { *contents.add(999) }, 3);
+        device.unmap_buffer(large);
+
+        assert_eq!(
+            uploads.upload_buffer(&mut device, large, 0, &[0; 2048]),
+            Err(GfxError::OutOfMemory)
+        );
+        assert!(matches!(
+            uploads.upload_buffer(&mut device, large, 2, &[0; 4]),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        // Rows that are not 256 bytes apart are rejected
+        let mut cmd = device.begin_frame();
+        let layout = TexelBufferLayout {
+            offset: 0,
+            bytes_per_row: 8,
+        };
+        let region = TextureRegion::full(&desc, 0);
+        cmd.copy_buffer_to_texture(large, layout, texture, 0, 0, region);
+        device.submit(cmd);
+        assert_eq!(device.read_texture(texture).unwrap(), texels);
+
+        uploads.destroy(&mut device);
+    }
+
+    #[test]
+    fn test_triangle() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        // Counter-clockwise, covering the lower-left half; the diagonal is a
+        // right edge, so the pixel centers on it stay empty
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, 1.0, RED),
+                vertex(-1.0, -1.0, RED),
+                vertex(1.0, -1.0, RED),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "........", "R.......", "RR......", "RRR.....", "RRRR....", "RRRRR...", "RRRRRR..",
+                "RRRRRRR.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_instance_attributes() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        // Colors come from a second buffer, one per instance
+        let color = desc.vertex_layout.attributes.pop().unwrap();
+        desc.vertex_layout = desc
+            .vertex_layout
+            .with_instance_buffer(16, vec![VertexAttribute { offset: 0, ..color }]);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, 1.0, [0.0; 4]),
+                vertex(-1.0, -1.0, [0.0; 4]),
+                vertex(1.0, -1.0, [0.0; 4]),
+            ],
+        );
+        let colors: Vec<u8> = [RED, BLUE]
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let instances = device
+            .create_buffer(&BufferDesc::vertex(colors.len()), Some(&colors))
+            .unwrap();
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.bind_vertex_buffer(1, instances, 0);
+        cmd.draw(3, 1, 0, 1);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "........", "B.......", "BB......", "BBB.....", "BBBB....", "BBBBB...", "BBBBBB..",
+                "BBBBBBB.",
+            ]
+        );
+
+        // Two attributes feeding one location are rejected
+        desc.vertex_layout.buffers[0].attributes[0].location = 0;
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+    }
+
+    #[test]
+    fn test_back_face_culled() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        // Clockwise
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, 1.0, RED),
+                vertex(1.0, -1.0, RED),
+                vertex(-1.0, -1.0, RED),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(image(&mut device, texture), vec!["........"; 8]);
+    }
+
+    #[test]
+    fn test_indexed_quad_with_scissor() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, BLUE),
+                vertex(1.0, -1.0, BLUE),
+                vertex(1.0, 1.0, BLUE),
+                vertex(-1.0, 1.0, BLUE),
+            ],
+        );
+        let indices: Vec<u8> = [0u16, 1, 2, 0, 2, 3]
+            .iter()
+            .flat_map(|i| i.to_le_bytes())
+            .collect();
+        let index_buffer = device
+            .create_buffer(&BufferDesc::index(indices.len()), Some(&indices))
+            .unwrap();
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.set_scissor(Rect {
+            x: 2,
+            y: 1,
+            width: 4,
+            height: 3,
+        });
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.bind_index_buffer(index_buffer, 0, IndexType::UInt16);
+        cmd.draw_indexed(6, 1, 0, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "........", "..BBBB..", "..BBBB..", "..BBBB..", "........", "........", "........",
+                "........",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depth_test() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let depth = device
+            .create_texture(
+                &TextureDesc::new_2d(
+                    SIZE,
+                    SIZE,
+                    TextureFormat::Depth32f,
+                    TextureUsage::DEPTH_ATTACHMENT | TextureUsage::TRANSFER_SRC,
+                ),
+                None,
+            )
+            .unwrap();
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        desc.vertex_layout.attributes[0].format = VertexFormat::Float3;
+        desc.vertex_layout.attributes[1].offset = 12;
+        desc.vertex_layout.stride = 28;
+        desc.depth_stencil = DepthStencilState::default();
+        desc.depth_format = Some(TextureFormat::Depth32f);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+
+        // Full-screen quads: a far blue one drawn after a near red one
+        let quad = |z: f32, color: [f32; 4]| {
+            [
+                (-1.0, -1.0),
+                (1.0, -1.0),
+                (1.0, 1.0),
+                (-1.0, -1.0),
+                (1.0, 1.0),
+                (-1.0, 1.0),
+            ]
+            .map(|(x, y)| [x, y, z, color[0], color[1], color[2], color[3]])
+        };
+        let data: Vec<u8> = quad(0.25, RED)
+            .iter()
+            .chain(quad(0.75, BLUE).iter())
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let buffer = device
+            .create_buffer(&BufferDesc::vertex(data.len()), Some(&data))
+            .unwrap();
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture,
+                load: LoadOp::Clear(ClearColor::BLACK),
+                store: StoreOp::Store,
+            }],
+            depth_attachment: Some(DepthAttachment {
+                texture: depth,
+                load: LoadOp::Clear(ClearDepthStencil::default()),
+                store: StoreOp::Store,
+            }),
+        });
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(12, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(image(&mut device, texture), vec!["RRRRRRRR"; 8]);
+        let depth = device.read_texture(depth).unwrap();
+        assert_eq!(
+            f32::from_le_bytes([depth[0], depth[1], depth[2], depth[3]]),
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_alpha_blending() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        desc.blend_states = vec![BlendState::ALPHA_BLENDING];
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let color = [1.0, 1.0, 1.0, 0.5];
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, color),
+                vertex(3.0, -1.0, color),
+                vertex(-1.0, 3.0, color),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        let pixels = device.read_texture(texture).unwrap();
+        assert!(pixels.chunks_exact(4).all(|p| p == [128, 128, 128, 128]));
+    }
+
+    #[test]
+    fn test_line_strip() {
+        let mut device = device();
+        let texture = target(&mut device);
+        let desc = pipeline(&mut device, PrimitiveTopology::LineStrip);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        // Pixel centers (0.5, 0.5) -> (6.5, 0.5) -> (6.5, 6.5)
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-0.875, 0.875, RED),
+                vertex(0.625, 0.875, RED),
+                vertex(0.625, -0.625, RED),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(texture));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "RRRRRRR.", "......R.", "......R.", "......R.", "......R.", "......R.", "........",
+                "........",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swapchain_bgra() {
+        let mut device = device();
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, RED),
+                vertex(3.0, -1.0, RED),
+                vertex(-1.0, 3.0, RED),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        let swapchain = device.get_swapchain_texture();
+        cmd.begin_render_pass(pass(swapchain));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+        device.present();
+
+        let pixels = device.read_texture(swapchain).unwrap();
+        assert_eq!(pixels.len(), (SIZE * SIZE * 4) as usize);
+        assert!(pixels.chunks_exact(4).all(|p| p == [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_capture_swapchain_to_png() {
+        let mut device = device();
+        let desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        let pipeline = device.create_pipeline(&desc).unwrap();
+        let buffer = vertices(
+            &mut device,
+            &[
+                vertex(-1.0, -1.0, RED),
+                vertex(3.0, -1.0, RED),
+                vertex(-1.0, 3.0, RED),
+            ],
+        );
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(device.get_swapchain_texture()));
+        cmd.bind_pipeline(pipeline);
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.draw(3, 1, 0, 0);
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        let path = std::env::temp_dir().join(format!("avila-capture-{}.png", std::process::id()));
+        device.capture_swapchain_to_png(&path).unwrap();
+        let png = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[16..24], [0, 0, 0, SIZE as u8, 0, 0, 0, SIZE as u8]);
+        // First scanline of the stored deflate block: filter byte, then RGBA
+        let row = &png[48..48 + 1 + SIZE as usize * 4];
+        assert_eq!(row[0], 0);
+        assert!(row[1..].chunks_exact(4).all(|p| p == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_invalid_desc() {
+        let mut device = device();
+        let empty = TextureDesc::new_2d(0, 4, TextureFormat::Rgba8, TextureUsage::SAMPLED);
+        assert!(matches!(
+            device.create_texture(&empty, None),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        let mips =
+            TextureDesc::new_2d(4, 4, TextureFormat::Rgba8, TextureUsage::SAMPLED).with_mips(4);
+        assert!(matches!(
+            device.create_texture(&mips, None),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        assert!(matches!(
+            device.create_buffer(&BufferDesc::vertex(4), Some(&[0; 8])),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        let sampled = device
+            .create_texture(
+                &TextureDesc::new_2d(4, 4, TextureFormat::Rgba8, TextureUsage::SAMPLED),
+                None,
+            )
+            .unwrap();
+        assert!(matches!(
+            device.read_texture(sampled),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        // Stages swapped
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        std::mem::swap(&mut desc.vertex_shader, &mut desc.fragment_shader);
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+    }
+
+    #[test]
+    fn test_textured_quad_address_modes() {
+        let mut device = device();
+        let repeat = SamplerDesc::nearest(AddressMode::Repeat);
+        let texture = textured_quad(&mut device, (2, &CHECKER), repeat, 2.0, false);
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "RRGGRRGG", "RRGGRRGG", "BBWWBBWW", "BBWWBBWW", "RRGGRRGG", "RRGGRRGG", "BBWWBBWW",
+                "BBWWBBWW",
+            ]
+        );
+
+        let clamp = SamplerDesc::nearest(AddressMode::ClampToEdge);
+        let texture = textured_quad(&mut device, (2, &CHECKER), clamp, 2.0, false);
+        assert_eq!(
+            image(&mut device, texture),
+            vec![
+                "RRGGGGGG", "RRGGGGGG", "BBWWWWWW", "BBWWWWWW", "BBWWWWWW", "BBWWWWWW", "BBWWWWWW",
+                "BBWWWWWW",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_textured_quad_linear() {
+        const RAMP: [u8; 8] = [0, 0, 0, 255, 255, 255, 255, 255];
+
+        let mut device = device();
+        let linear = SamplerDesc::linear(AddressMode::ClampToEdge);
+        let texture = textured_quad(&mut device, (2, &RAMP), linear, 1.0, false);
+        let pixels = device.read_texture(texture).unwrap();
+        let reds: Vec<u8> = pixels[..4 * SIZE as usize]
+            .iter()
+            .step_by(4)
+            .copied()
+            .collect();
+        assert_eq!(reds, [0, 0, 32, 96, 159, 223, 255, 255]);
+    }
+
+    #[test]
+    fn test_textured_quad_minified() {
+        // 32x32 red and blue stripes, drawn at 8x8 from mip 2
+        let stripes: Vec<u8> = (0..32 * 32)
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 255]
+                }
+            })
+            .collect();
+
+        let mut device = device();
+        for sampler in [
+            SamplerDesc::nearest(AddressMode::Repeat),
+            SamplerDesc::linear(AddressMode::Repeat),
+        ] {
+            let texture = textured_quad(&mut device, (32, &stripes), sampler, 1.0, false);
+            let pixels = device.read_texture(texture).unwrap();
+            assert!(pixels.chunks_exact(4).all(|p| p == [128, 0, 128, 255]));
+        }
+    }
+
+    #[test]
+    fn test_generate_mipmaps() {
+        let mut device = device();
+        let desc = TextureDesc::new_2d(4, 4, TextureFormat::Rgba8, TextureUsage::SAMPLED);
+        let texture = device.create_texture(&desc, None).unwrap();
+        assert!(device.generate_mipmaps(texture).is_ok());
+        let texture = device
+            .create_texture(&desc.clone().with_mips(3), None)
+            .unwrap();
+        assert!(device.generate_mipmaps(texture).is_ok());
+
+        let mut volume = desc.with_mips(3);
+        volume.dimension = TextureDimension::D3;
+        let depth = TextureDesc::new_2d(
+            4,
+            4,
+            TextureFormat::Depth32f,
+            TextureUsage::DEPTH_ATTACHMENT,
+        )
+        .with_mips(3);
+        for desc in [volume, depth] {
+            let texture = device.create_texture(&desc, None).unwrap();
+            assert!(matches!(
+                device.generate_mipmaps(texture),
+                Err(GfxError::InvalidDesc(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_bind_group_texture() {
+        let mut device = device();
+        let sampler = SamplerDesc::nearest(AddressMode::Repeat);
+        let bound = textured_quad(&mut device, (2, &CHECKER), sampler, 2.0, false);
+        let grouped = textured_quad(&mut device, (2, &CHECKER), sampler, 2.0, true);
+        let image_bound = image(&mut device, bound);
+        assert_eq!(image_bound[0], "RRGGRRGG");
+        assert_eq!(image(&mut device, grouped), image_bound);
+    }
+
+    #[test]
+    fn test_invalid_bind_group() {
+        let mut device = device();
+        let uniform = |binding| BindGroupLayoutEntry {
+            binding,
+            ty: BindingType::UniformBuffer,
+        };
+        assert!(matches!(
+            device.create_bind_group_layout(&BindGroupLayoutDesc {
+                entries: vec![uniform(0), uniform(0)],
+            }),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        let layout = device
+            .create_bind_group_layout(&BindGroupLayoutDesc {
+                entries: vec![
+                    uniform(0),
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        ty: BindingType::Sampler { comparison: false },
+                    },
+                ],
+            })
+            .unwrap();
+        let buffer = device
+            .create_buffer(&BufferDesc::uniform(64), None)
+            .unwrap();
+        let vertex_buffer = device.create_buffer(&BufferDesc::vertex(64), None).unwrap();
+        let sampler = device
+            .create_sampler(&SamplerDesc::linear(AddressMode::Repeat))
+            .unwrap();
+        let shadow = device
+            .create_sampler(&SamplerDesc::shadow(CompareFunction::Less))
+            .unwrap();
+        let entries = |buffer, offset, size, sampler| {
+            vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer {
+                        buffer,
+                        offset,
+                        size,
+                    },
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+            ]
+        };
+        let mut group = |entries| device.create_bind_group(&BindGroupDesc { layout, entries });
+
+        assert!(group(entries(buffer, 0, None, sampler)).is_ok());
+        assert!(group(entries(buffer, 32, Some(32), sampler)).is_ok());
+        for invalid in [
+            // Range past the end
+            entries(buffer, 32, Some(64), sampler),
+            // Not a uniform buffer
+            entries(vertex_buffer, 0, None, sampler),
+            // Comparison sampler in a plain sampler slot
+            entries(buffer, 0, None, shadow),
+            // Missing slot
+            entries(buffer, 0, None, sampler)[..1].to_vec(),
+            // Sampler in a buffer slot
+            vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+            ],
+        ] {
+            assert!(matches!(group(invalid), Err(GfxError::InvalidDesc(_))));
+        }
+
+        // Set 0 belongs to the bind group layout
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        desc.layout.bind_groups = vec![layout];
+        desc.layout.textures = vec![TextureBinding::new_2d(0, 0)];
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        desc.layout.textures = vec![TextureBinding::new_2d(1, 0)];
+        assert!(device.create_pipeline(&desc).is_ok());
+        desc.layout.bind_groups = vec![BindGroupLayoutHandle::INVALID];
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_sampler_and_layout() {
+        let mut device = device();
+        let sampler = SamplerDesc {
+            max_anisotropy: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            device.create_sampler(&sampler),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        // Anisotropic filtering needs linear filters
+        let sampler = SamplerDesc::nearest(AddressMode::Repeat).with_anisotropy(8);
+        assert!(matches!(
+            device.create_sampler(&sampler),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        // Binding 1 is taken by the sampler of binding 0 on wgpu
+        let mut desc = pipeline(&mut device, PrimitiveTopology::TriangleList);
+        desc.layout.textures = vec![TextureBinding::new_2d(0, 0), TextureBinding::new_2d(0, 1)];
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        desc.layout.textures = vec![TextureBinding::new_2d(MAX_BIND_SETS, 0)];
+        assert!(matches!(
+            device.create_pipeline(&desc),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        desc.layout.textures = vec![TextureBinding::new_2d(0, 0), TextureBinding::new_2d(1, 0)];
+        assert!(device.create_pipeline(&desc).is_ok());
+    }
+
+    #[test]
+    fn test_compute_pipeline_and_storage_texture() {
+        let mut device = device();
+        let fragment_shader =
+            pipeline(&mut device, PrimitiveTopology::TriangleList).fragment_shader;
+        let compute_shader = device
+            .create_shader(&ShaderDesc {
+                stage: ShaderStage::Compute,
+                entry_point: "main".into(),
+                code: Vec::new(),
+            })
+            .unwrap();
+        let compute = |device: &mut BackendDevice, shader| {
+            device.create_compute_pipeline(&ComputePipelineDesc {
+                shader,
+                layout: PipelineLayoutDesc::default(),
+            })
+        };
+        assert!(matches!(
+            compute(&mut device, fragment_shader),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        // Valid, but the software backend does not run compute shaders
+        assert!(matches!(
+            compute(&mut device, compute_shader),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        let storage = |format, dimension| BindGroupLayoutDesc {
+            entries: vec![BindGroupLayoutEntry {
+                binding: 0,
+                ty: BindingType::StorageTexture {
+                    format,
+                    dimension,
+                    access: StorageTextureAccess::WriteOnly,
+                },
+            }],
+        };
+        for (format, dimension) in [
+            (TextureFormat::Bgra8, TextureDimension::D2),
+            (TextureFormat::Rgba8, TextureDimension::Cube),
+        ] {
+            assert!(matches!(
+                device.create_bind_group_layout(&storage(format, dimension)),
+                Err(GfxError::InvalidDesc(_))
+            ));
+        }
+
+        let layout = device
+            .create_bind_group_layout(&storage(TextureFormat::Rgba8, TextureDimension::D2))
+            .unwrap();
+        let texture = |device: &mut BackendDevice, format, usage, layers| {
+            let desc = TextureDesc {
+                array_layers: layers,
+                ..TextureDesc::new_2d(4, 4, format, usage)
+            };
+            device.create_texture(&desc, None).unwrap()
+        };
+        let valid = texture(&mut device, TextureFormat::Rgba8, TextureUsage::STORAGE, 1);
+        let invalid = [
+            texture(&mut device, TextureFormat::Rgba8, TextureUsage::SAMPLED, 1),
+            texture(
+                &mut device,
+                TextureFormat::Rgba16f,
+                TextureUsage::STORAGE,
+                1,
+            ),
+            texture(&mut device, TextureFormat::Rgba8, TextureUsage::STORAGE, 2),
+        ];
+        let mut group = |texture| {
+            device.create_bind_group(&BindGroupDesc {
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Texture(texture),
+                }],
+            })
+        };
+        assert!(group(valid).is_ok());
+        for texture in invalid {
+            assert!(matches!(group(texture), Err(GfxError::InvalidDesc(_))));
+        }
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let mut device = device();
+        let desc = TextureDesc::new_2d(
+            u32::MAX,
+            u32::MAX,
+            TextureFormat::Rgba32f,
+            TextureUsage::SAMPLED,
+        );
+        assert_eq!(
+            device.create_texture(&desc, None),
+            Err(GfxError::OutOfMemory)
+        );
+    }
+
+    #[test]
+    fn test_resource_state_tracking() {
+        let mut device = device();
+        let texture_state = |device: &BackendDevice, texture: TextureHandle| {
+            device.textures.get(texture.0).unwrap().state
+        };
+        let buffer_state = |device: &BackendDevice, buffer: BufferHandle| {
+            device.buffers.get(buffer.0).unwrap().state
+        };
+
+        let desc = TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            TextureFormat::Rgba8,
+            TextureUsage::COLOR_ATTACHMENT | TextureUsage::SAMPLED,
+        );
+        let first = device.create_texture(&desc, None).unwrap();
+        assert_eq!(texture_state(&device, first), ResourceState::Undefined);
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(first));
+        cmd.end_render_pass();
+        device.submit(cmd);
+        assert_eq!(
+            texture_state(&device, first),
+            ResourceState::ColorAttachment
+        );
+
+        // Render target -> sampled, for a pass that also reads a buffer
+        // both as vertices and from shaders
+        let second = target(&mut device);
+        let buffer = device
+            .create_buffer(&BufferDesc::storage(64), None)
+            .unwrap();
+        let layout = device
+            .create_bind_group_layout(&BindGroupLayoutDesc {
+                entries: vec![BindGroupLayoutEntry {
+                    binding: 0,
+                    ty: BindingType::StorageBuffer { read_only: true },
+                }],
+            })
+            .unwrap();
+        let group = device
+            .create_bind_group(&BindGroupDesc {
+                layout,
+                entries: vec![BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer {
+                        buffer,
+                        offset: 0,
+                        size: None,
+                    },
+                }],
+            })
+            .unwrap();
+        let sampler = device
+            .create_sampler(&SamplerDesc::nearest(AddressMode::ClampToEdge))
+            .unwrap();
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(pass(second));
+        cmd.bind_vertex_buffer(0, buffer, 0);
+        cmd.bind_group(0, group);
+        cmd.bind_texture(1, 0, first, sampler);
+        cmd.end_render_pass();
+        device.submit(cmd);
+        assert_eq!(texture_state(&device, first), ResourceState::ShaderRead);
+        assert_eq!(
+            texture_state(&device, second),
+            ResourceState::ColorAttachment
+        );
+        assert_eq!(buffer_state(&device, buffer), ResourceState::ShaderRead);
+
+        device.read_texture(second).unwrap();
+        assert_eq!(texture_state(&device, second), ResourceState::TransferSrc);
+        device.update_buffer(buffer, 0, &[0; 4]);
+        assert_eq!(buffer_state(&device, buffer), ResourceState::TransferDst);
+
+        let image = device.get_swapchain_texture();
+        device.present();
+        assert_eq!(texture_state(&device, image), ResourceState::Present);
+    }
+
+    #[test]
+    fn test_memory_allocator() {
+        let mut device = device();
+        let heap = |device: &BackendDevice, heap: MemoryHeap| {
+            device
+                .memory_stats()
+                .into_iter()
+                .find(|stats| stats.heap == heap)
+                .unwrap()
+        };
+
+        // Small buffers share a 64 MiB block, 256-byte aligned
+        let first = device
+            .create_buffer(&BufferDesc::vertex(100), None)
+            .unwrap();
+        let second = device
+            .create_buffer(&BufferDesc::vertex(100), None)
+            .unwrap();
+        let uniform = device
+            .create_buffer(&BufferDesc::uniform(64), None)
+            .unwrap();
+        let stats = heap(&device, MemoryHeap::DeviceLocal);
+        assert_eq!((stats.block_count, stats.dedicated_count), (1, 0));
+        assert_eq!((stats.reserved, stats.used), (64 << 20, 356));
+        assert_eq!(heap(&device, MemoryHeap::HostVisible).block_count, 1);
+
+        // 64 MiB of texels get memory of their own
+        let desc = TextureDesc::new_2d(4096, 4096, TextureFormat::Rgba8, TextureUsage::SAMPLED);
+        let large = device.create_texture(&desc, None).unwrap();
+        let stats = heap(&device, MemoryHeap::DeviceLocal);
+        assert_eq!((stats.block_count, stats.dedicated_count), (1, 1));
+        assert_eq!(stats.reserved, 128 << 20);
+
+        device.destroy_buffer(first);
+        assert_eq!(heap(&device, MemoryHeap::DeviceLocal).block_count, 1);
+        device.destroy_buffer(second);
+        device.destroy_buffer(uniform);
+        device.destroy_texture(large);
+        for stats in device.memory_stats() {
+            assert_eq!((stats.reserved, stats.used, stats.block_count), (0, 0, 0));
+            assert_eq!(stats.allocation_count, stats.deallocation_count);
+        }
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let mut device = device();
+        device.set_memory_budget(MemoryHeap::DeviceLocal, 1 << 20);
+        let desc = TextureDesc::new_2d(512, 512, TextureFormat::Rgba8, TextureUsage::SAMPLED);
+        let texture = device.create_texture(&desc, None).unwrap();
+        assert!(matches!(
+            device.create_buffer(&BufferDesc::vertex(16), None),
+            Err(GfxError::OutOfMemory)
+        ));
+        // Mappable buffers come from the other heap
+        assert!(device.create_buffer(&BufferDesc::uniform(16), None).is_ok());
+
+        let mut manager = MemoryManager::new();
+        device.report_memory(&mut manager);
+        let report = manager.report();
+        let info = &report.allocators[MemoryHeap::DeviceLocal.name()];
+        assert_eq!(info.allocator_type, AllocatorType::Gpu);
+        assert_eq!((info.total_capacity, info.used), (1 << 20, 1 << 20));
+
+        device.destroy_texture(texture);
+        assert!(device.create_buffer(&BufferDesc::vertex(16), None).is_ok());
+
+        // The manager reads the heaps live, without registering them again
+        let report = manager.report();
+        let info = &report.allocators[MemoryHeap::DeviceLocal.name()];
+        assert_eq!((info.allocation_count, info.deallocation_count), (2, 1));
+        assert_eq!(info.active_allocations(), 1);
+
+        drop(device);
+        assert_eq!(manager.report().allocator_count, 0);
+    }
+
+    #[test]
+    fn test_texture_upload() {
+        let mut device = device();
+        let desc = TextureDesc::new_2d(
+            4,
+            4,
+            TextureFormat::Rgba8,
+            TextureUsage::SAMPLED | TextureUsage::TRANSFER_SRC,
+        );
+        let initial: Vec<u8> = (0..64).collect();
+        let texture = device.create_texture(&desc, Some(&initial)).unwrap();
+        assert_eq!(device.read_texture(texture).unwrap(), initial);
+
+        // 2x2 block in the middle
+        device
+            .update_texture(texture, 0, 0, TextureRegion::new_2d(1, 1, 2, 2), &[255; 16])
+            .unwrap();
+        let pixels = device.read_texture(texture).unwrap();
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let (x, y) = (i % 4, i / 4);
+            if (1..3).contains(&x) && (1..3).contains(&y) {
+                assert_eq!(pixel, [255; 4]);
+            } else {
+                assert_eq!(pixel, &initial[i * 4..i * 4 + 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_texture_upload_rejected() {
+        let mut device = device();
+        let desc = TextureDesc::new_2d(
+            4,
+            4,
+            TextureFormat::Rgba8,
+            TextureUsage::SAMPLED | TextureUsage::TRANSFER_DST,
+        );
+        assert!(matches!(
+            device.create_texture(&desc, Some(&[0; 63])),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        let texture = device.create_texture(&desc, None).unwrap();
+        let region = TextureRegion::new_2d(3, 3, 2, 2);
+        assert!(matches!(
+            device.update_texture(texture, 0, 0, region, &[0; 16]),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        let region = TextureRegion::new_2d(0, 0, 2, 2);
+        assert!(matches!(
+            device.update_texture(texture, 0, 0, region, &[0; 12]),
+            Err(GfxError::InvalidDesc(_))
+        ));
+        assert!(matches!(
+            device.update_texture(texture, 1, 0, region, &[0; 16]),
+            Err(GfxError::InvalidDesc(_))
+        ));
+
+        // Without TRANSFER_DST
+        let target = target(&mut device);
+        assert!(matches!(
+            device.update_texture(target, 0, 0, region, &[0; 16]),
+            Err(GfxError::InvalidDesc(_))
+        ));
+    }
+
+    #[test]
+    fn test_resize_minimize_and_device_loss() {
+        let mut device = device();
+        let buffer = device
+            .create_buffer(&BufferDesc::uniform(16), None)
+            .unwrap();
+        let fence = device.signal_fence();
+
+        // A minimized window keeps its swapchain and stops presenting
+        let first = device.get_swapchain_texture();
+        device.handle_window_event(&WindowEvent::FramebufferResized(0, 0));
+        for _ in 0..2 {
+            device.begin_frame();
+            device.present();
+        }
+        assert_eq!(device.get_swapchain_texture(), first);
+        assert!(device.poll_events().is_empty());
+
+        device.handle_window_event(&WindowEvent::FramebufferResized(2 * SIZE, SIZE));
+        assert_eq!(
+            device.poll_events(),
+            vec![DeviceEvent::SwapchainResized {
+                width: 2 * SIZE,
+                height: SIZE
+            }]
+        );
+        let desc = &device
+            .textures
+            .get(device.get_swapchain_texture().0)
+            .unwrap()
+            .desc;
+        assert_eq!((desc.width, desc.height), (2 * SIZE, SIZE));
+
+        // Recovery empties the pools and keeps the swapchain size
+        let mut manager = MemoryManager::new();
+        device.report_memory(&mut manager);
+        device.simulate_device_loss();
+        device.begin_frame();
+        assert_eq!(
+            device.poll_events(),
+            vec![DeviceEvent::Lost, DeviceEvent::Restored]
+        );
+        assert!(device.buffers.get(buffer.0).is_none());
+        assert!(device.fence_reached(fence));
+        let desc = &device
+            .textures
+            .get(device.get_swapchain_texture().0)
+            .unwrap()
+            .desc;
+        assert_eq!((desc.width, desc.height), (2 * SIZE, SIZE));
+        // The heaps stay tracked across the new allocator state
+        for stats in device.memory_stats() {
+            let info = manager.snapshot(stats.heap.name()).unwrap();
+            assert_eq!(info.used, stats.used as usize);
+            assert_eq!(info.allocation_count, stats.allocation_count);
+        }
+
+        // Resources created afterwards work as before
+        let target = target(&mut device);
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture: target,
+                load: LoadOp::Clear(ClearColor::WHITE),
+                store: StoreOp::Store,
+            }],
+            depth_attachment: None,
+        });
+        cmd.end_render_pass();
+        device.submit(cmd);
+        assert!(image(&mut device, target)
+            .iter()
+            .all(|row| !row.contains('.')));
+        assert!(device.poll_events().is_empty());
+    }
+
+    #[test]
+    fn test_frames_in_flight_and_present_mode() {
+        let mut device = create_device(RendererConfig {
+            width: SIZE,
+            height: SIZE,
+            backend: Backend::Software,
+            present_mode: PresentMode::Mailbox,
+            frames_in_flight: 0,
+            ..Default::default()
+        });
+        // At least one frame, and an image more for the screen
+        assert_eq!(device.config.frames_in_flight, 1);
+        assert_eq!(device.swapchain.images.len(), 2);
+
+        // Each frame waits for the one before
+        for _ in 0..3 {
+            device.begin_frame();
+            assert!(device.frame_fences.is_empty());
+            device.present();
+            assert_eq!(device.frame_fences.len(), 1);
+        }
+        device.wait_for_frame();
+        assert!(device.frame_fences.is_empty());
+
+        device.set_present_mode(PresentMode::Immediate);
+        assert_eq!(device.config.present_mode, PresentMode::Immediate);
+        assert_eq!(device.swapchain.images.len(), 2);
+    }
+
+    #[test]
+    fn test_f16_round_trip() {
+        for value in [0.0, 1.0, -2.5, 0.5, 65504.0, 2f32.powi(-24)] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+        }
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+    }
+}
@@ -0,0 +1,2379 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! wgpu backend
+//!
+//! Runs on whatever wgpu picks for the platform (Vulkan, D3D12, Metal or
+//! GL), which also makes it the reference the native backends are checked
+//! against.
+//!
+//! - Shaders may be SPIR-V, GLSL (`#version` first) or WGSL
+//! - Commands are recorded into one encoder per `submit`
+//! - With a window, each acquired surface frame stands in for the
+//!   swapchain image of that frame; offscreen the images are plain textures
+//! - `map_buffer` reads the buffer back into a CPU copy that `unmap_buffer`
+//!   writes out again
+//! - Bind groups map one to one; the sets a pipeline layout describes with
+//!   `textures` are bind groups too, with the sampler of a texture at
+//!   `binding + 1`, built at draw time from the bound textures and cached
+//! - Timestamps need the adapter to support them inside encoders and
+//!   passes; they are resolved into a buffer that is mapped without waiting
+//! - Render and compute pipelines go through a `wgpu::PipelineCache` where
+//!   the adapter has one (Vulkan)
+
+use super::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// First word of a SPIR-V module
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// Draws one mip level from the level above: each fragment averages the
+/// 2x2 texels under it, repeating the last row or column of odd sizes
+const MIPMAP_SHADER: &str = r#"
+@group(0) @binding(0) var source: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // One triangle covering the target
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let last = vec2<i32>(textureDimensions(source)) - 1;
+    let base = vec2<i32>(position.xy) * 2;
+    let sum = textureLoad(source, min(base, last), 0)
+        + textureLoad(source, min(base + vec2<i32>(1, 0), last), 0)
+        + textureLoad(source, min(base + vec2<i32>(0, 1), last), 0)
+        + textureLoad(source, min(base + vec2<i32>(1, 1), last), 0);
+    return sum * 0.25;
+}
+"#;
+
+/// Pipeline, set and the texture and sampler handles bound in it
+type BindGroupKey = (u64, u32, Vec<(u64, u64)>);
+
+/// Native device on a wgpu device and queue
+pub(super) struct WgpuDevice {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    adapter: wgpu::Adapter,
+    surface: Option<wgpu::Surface<'static>>,
+    surface_config: Option<wgpu::SurfaceConfiguration>,
+    /// Surface texture acquired for the current frame
+    frame: Option<wgpu::SurfaceTexture>,
+    textures: HashMap<u64, WgpuTexture>,
+    buffers: HashMap<u64, WgpuBuffer>,
+    shaders: HashMap<u64, WgpuShader>,
+    pipelines: HashMap<u64, WgpuPipeline>,
+    compute_pipelines: HashMap<u64, WgpuComputePipeline>,
+    samplers: HashMap<u64, wgpu::Sampler>,
+    bind_group_layouts: HashMap<u64, wgpu::BindGroupLayout>,
+    bind_groups: HashMap<u64, wgpu::BindGroup>,
+    /// Bind groups made for `bind_texture`
+    texture_bind_groups: HashMap<BindGroupKey, wgpu::BindGroup>,
+    /// `MIPMAP_SHADER` pipelines and their bind group layout, by format
+    mipmap_pipelines: HashMap<wgpu::TextureFormat, (wgpu::RenderPipeline, wgpu::BindGroupLayout)>,
+    /// Texture handles of each swapchain
+    swapchains: HashMap<u64, Vec<u64>>,
+    next_handle: u64,
+    encoder: Option<wgpu::CommandEncoder>,
+    pass: Option<wgpu::RenderPass<'static>>,
+    /// Pipeline bound in the current pass
+    pipeline: Option<u64>,
+    /// Texture and sampler handles by set and binding, until a pass begins
+    /// or ends
+    bound_textures: HashMap<(u32, u32), (u64, u64)>,
+    /// Compute pipeline for dispatches outside passes
+    compute_pipeline: Option<u64>,
+    /// Bind group handles by set, for dispatches outside passes
+    compute_bind_groups: HashMap<u32, u64>,
+    /// Set by the device-lost callback
+    lost: Arc<AtomicBool>,
+    /// `None` where the adapter lacks `Features::PIPELINE_CACHE`
+    pipeline_cache: Option<wgpu::PipelineCache>,
+    query_pools: HashMap<u64, WgpuQueryPool>,
+    /// Nanoseconds per timestamp tick
+    timestamp_period: f32,
+    /// Largest fence value reached, set by work-done callbacks
+    completed_fence: Arc<AtomicU64>,
+    /// Submission of each fence not known to be reached, oldest first
+    fence_submissions: VecDeque<(u64, wgpu::SubmissionIndex)>,
+    // Surfaces must not outlive the instance
+    _instance: wgpu::Instance,
+}
+
+struct WgpuTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+struct WgpuBuffer {
+    buffer: wgpu::Buffer,
+    /// CPU copy handed out by `map_buffer`
+    mapped: Option<Vec<u8>>,
+}
+
+struct WgpuQueryPool {
+    set: wgpu::QuerySet,
+    /// Raw ticks, resolved from `set`
+    resolve: wgpu::Buffer,
+    /// Copy of `resolve` mapped for reading
+    readback: wgpu::Buffer,
+    /// Set once `readback` is mapped
+    mapped: Arc<AtomicBool>,
+}
+
+struct WgpuShader {
+    module: wgpu::ShaderModule,
+    entry_point: String,
+}
+
+struct WgpuPipeline {
+    pipeline: wgpu::RenderPipeline,
+    texture_sets: TextureSets,
+}
+
+struct WgpuComputePipeline {
+    pipeline: wgpu::ComputePipeline,
+    texture_sets: TextureSets,
+}
+
+/// Layouts generated for the `TextureBinding`s of a pipeline
+struct TextureSets {
+    /// Set of the first texture layout, after the bind group layouts
+    first_set: u32,
+    /// One per set from `first_set` up to the highest texture set
+    layouts: Vec<wgpu::BindGroupLayout>,
+    textures: Vec<TextureBinding>,
+}
+
+impl WgpuDevice {
+    pub(super) fn create(
+        config: &RendererConfig,
+        window: Option<&NativeHandle>,
+    ) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+        let surface = match window {
+            Some(handle) => {
+                let target = wgpu::SurfaceTargetUnsafe::RawHandle {
+                    raw_display_handle: handle.raw_display_handle().map_err(|e| e.to_string())?,
+                    raw_window_handle: handle.raw_window_handle().map_err(|e| e.to_string())?,
+                };
+                // SAFETY: the window outlives the device (see `NativeHandle`)
+                let surface =
+                    unsafe { instance.create_surface_unsafe(target) }.map_err(|e| e.to_string())?;
+                Some(surface)
+            }
+            None => None,
+        };
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: power_preference(config.adapter_preference),
+            force_fallback_adapter: false,
+            compatible_surface: surface.as_ref(),
+        }))
+        .map_err(|e| e.to_string())?;
+
+        let optional_features = wgpu::Features::TEXTURE_COMPRESSION_BC
+            | wgpu::Features::POLYGON_MODE_LINE
+            | wgpu::Features::POLYGON_MODE_POINT
+            // Read-write storage textures beyond the single-channel formats
+            | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+            | wgpu::Features::TIMESTAMP_QUERY
+            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS
+            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES
+            | wgpu::Features::PIPELINE_CACHE;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("avila"),
+            required_features: adapter.features() & optional_features,
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .map_err(|e| e.to_string())?;
+        // Validation errors are reported, not fatal, like the other backends
+        device.on_uncaptured_error(Box::new(|error| eprintln!("wgpu error: {}", error)));
+        let lost = Arc::new(AtomicBool::new(false));
+        let flag = lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // `Destroyed` is our own teardown, not a loss
+            if reason != wgpu::DeviceLostReason::Destroyed {
+                eprintln!("wgpu device lost: {}", message);
+                flag.store(true, Ordering::Relaxed);
+            }
+        });
+
+        let info = adapter.get_info();
+        println!("Creating wgpu device: {} ({:?})", info.name, info.backend);
+        let pipeline_cache = create_pipeline_cache(&device, None);
+
+        Ok(Self {
+            device,
+            adapter,
+            surface,
+            surface_config: None,
+            frame: None,
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+            shaders: HashMap::new(),
+            pipelines: HashMap::new(),
+            compute_pipelines: HashMap::new(),
+            samplers: HashMap::new(),
+            bind_group_layouts: HashMap::new(),
+            bind_groups: HashMap::new(),
+            texture_bind_groups: HashMap::new(),
+            mipmap_pipelines: HashMap::new(),
+            swapchains: HashMap::new(),
+            next_handle: 1,
+            encoder: None,
+            pass: None,
+            pipeline: None,
+            bound_textures: HashMap::new(),
+            compute_pipeline: None,
+            compute_bind_groups: HashMap::new(),
+            lost,
+            pipeline_cache,
+            query_pools: HashMap::new(),
+            timestamp_period: queue.get_timestamp_period(),
+            completed_fence: Arc::new(AtomicU64::new(0)),
+            fence_submissions: VecDeque::new(),
+            queue,
+            _instance: instance,
+        })
+    }
+
+    fn next_handle(&mut self) -> u64 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+
+    /// Run `create`, turning the errors it raises into a `GfxError`
+    fn validated<T>(&self, create: impl FnOnce(&wgpu::Device) -> T) -> Result<T, GfxError> {
+        if self.lost.load(Ordering::Relaxed) {
+            return Err(GfxError::DeviceLost);
+        }
+        let filters = [
+            wgpu::ErrorFilter::OutOfMemory,
+            wgpu::ErrorFilter::Validation,
+            wgpu::ErrorFilter::Internal,
+        ];
+        for filter in filters {
+            self.device.push_error_scope(filter);
+        }
+        let value = create(&self.device);
+        let mut result = Ok(value);
+        for _ in filters {
+            let error = pollster::block_on(self.device.pop_error_scope());
+            if let (Some(error), Ok(_)) = (error, &result) {
+                result = Err(match error {
+                    wgpu::Error::OutOfMemory { .. } => GfxError::OutOfMemory,
+                    wgpu::Error::Validation { description, .. }
+                    | wgpu::Error::Internal { description, .. } => {
+                        GfxError::InvalidDesc(description)
+                    }
+                });
+            }
+        }
+        result
+    }
+
+    fn create_wgpu_texture(&self, desc: &TextureDesc) -> Result<WgpuTexture, GfxError> {
+        let (dimension, depth_or_array_layers, view_dimension) = match desc.dimension {
+            TextureDimension::D1 => (wgpu::TextureDimension::D1, 1, None),
+            TextureDimension::D2 if desc.array_layers > 1 => (
+                wgpu::TextureDimension::D2,
+                desc.array_layers,
+                Some(wgpu::TextureViewDimension::D2Array),
+            ),
+            TextureDimension::D2 => (wgpu::TextureDimension::D2, 1, None),
+            TextureDimension::D3 => (wgpu::TextureDimension::D3, desc.depth.max(1), None),
+            TextureDimension::Cube => (
+                wgpu::TextureDimension::D2,
+                desc.array_layers.max(6),
+                Some(wgpu::TextureViewDimension::Cube),
+            ),
+        };
+
+        let mut usage = texture_usage(desc.usage);
+        // Mip levels are generated by rendering into them
+        if desc.mip_levels > 1 && validate_mipmaps(desc).is_ok() {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        }
+        let texture = self.validated(|device| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: desc.width,
+                    height: desc.height,
+                    depth_or_array_layers,
+                },
+                mip_level_count: desc.mip_levels.max(1),
+                sample_count: desc.samples.max(1),
+                dimension,
+                format: texture_format(desc.format),
+                usage,
+                view_formats: &[],
+            })
+        })?;
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: view_dimension,
+            ..Default::default()
+        });
+        Ok(WgpuTexture { texture, view })
+    }
+
+    /// Set the bind groups of the bound pipeline's texture sets
+    ///
+    /// Returns false, and the draw is skipped, if the pipeline reads a
+    /// binding that has nothing bound.
+    fn prepare_draw(&mut self) -> bool {
+        let Some(handle) = self.pipeline else {
+            return true;
+        };
+        let Some(groups) = self.texture_bind_groups(handle) else {
+            return false;
+        };
+        if let Some(pass) = &mut self.pass {
+            for (set, key) in &groups {
+                pass.set_bind_group(*set, &self.texture_bind_groups[key], &[]);
+            }
+        }
+        true
+    }
+
+    /// Keys of the bind groups for the texture sets of pipeline `handle`,
+    /// made from the bound textures on first use
+    ///
+    /// Returns `None` if the pipeline reads a binding that has nothing bound.
+    fn texture_bind_groups(&mut self, handle: u64) -> Option<Vec<(u32, BindGroupKey)>> {
+        let sets = match self.pipelines.get(&handle) {
+            Some(pipeline) => &pipeline.texture_sets,
+            None => match self.compute_pipelines.get(&handle) {
+                Some(pipeline) => &pipeline.texture_sets,
+                None => return Some(Vec::new()),
+            },
+        };
+        let mut keys = Vec::new();
+        for (set, layout) in (sets.first_set..).zip(&sets.layouts) {
+            let textures = || sets.textures.iter().filter(move |t| t.set == set);
+            let mut bound = Vec::new();
+            for texture in textures() {
+                let Some(&binding) = self.bound_textures.get(&(set, texture.binding)) else {
+                    eprintln!(
+                        "Skipped: no texture bound at set {}, binding {}",
+                        set, texture.binding
+                    );
+                    return None;
+                };
+                bound.push(binding);
+            }
+
+            let key = (handle, set, bound);
+            if !self.texture_bind_groups.contains_key(&key) {
+                let mut entries = Vec::new();
+                for (texture, (view, sampler)) in textures().zip(&key.2) {
+                    let (Some(view), Some(sampler)) =
+                        (self.textures.get(view), self.samplers.get(sampler))
+                    else {
+                        return None;
+                    };
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: texture.binding,
+                        resource: wgpu::BindingResource::TextureView(&view.view),
+                    });
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: texture.binding + 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    });
+                }
+                let group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout,
+                    entries: &entries,
+                });
+                self.texture_bind_groups.insert(key.clone(), group);
+            }
+            keys.push((set, key));
+        }
+        Some(keys)
+    }
+
+    /// Pipeline layout with the bind group layouts of `desc` followed by
+    /// generated layouts for its texture sets
+    fn pipeline_layout(
+        &self,
+        desc: &PipelineLayoutDesc,
+        bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<(wgpu::PipelineLayout, TextureSets), GfxError> {
+        let mut layouts = Vec::new();
+        for handle in &desc.bind_groups {
+            let layout = bind_group_layouts
+                .get(handle.0)
+                .and_then(|layout| self.bind_group_layouts.get(&layout.native.handle))
+                .ok_or_else(|| {
+                    GfxError::InvalidDesc(format!("unknown bind group layout {:?}", handle))
+                })?;
+            layouts.push(layout);
+        }
+        let textures = &desc.textures;
+        let first_set = layouts.len() as u32;
+        let set_count = textures.iter().map(|t| t.set + 1).max().unwrap_or(0);
+        let texture_layouts = (first_set..set_count)
+            .map(|set| {
+                let entries = textures
+                    .iter()
+                    .filter(|t| t.set == set)
+                    .flat_map(texture_layout_entries)
+                    .collect::<Vec<_>>();
+                self.validated(|device| {
+                    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &entries,
+                    })
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let layout = self.validated(|device| {
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &layouts
+                    .into_iter()
+                    .chain(&texture_layouts)
+                    .collect::<Vec<_>>(),
+                push_constant_ranges: &[],
+            })
+        })?;
+        Ok((
+            layout,
+            TextureSets {
+                first_set,
+                layouts: texture_layouts,
+                textures: textures.clone(),
+            },
+        ))
+    }
+
+    /// Submit everything recorded so far
+    fn flush(&mut self) {
+        self.pass = None;
+        if let Some(encoder) = self.encoder.take() {
+            self.queue.submit([encoder.finish()]);
+        }
+    }
+
+    /// Copy `len` bytes at `offset` back from the GPU (both 4-byte aligned)
+    fn read_buffer(&mut self, handle: u64, offset: u64, len: u64) -> Option<Vec<u8>> {
+        self.flush();
+        let buffer = &self.buffers.get(&handle)?.buffer;
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: len,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(buffer, offset, &staging, 0, len);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
+            eprintln!("Buffer readback failed: {}", err);
+            return None;
+        }
+        let data = slice.get_mapped_range().to_vec();
+        staging.unmap();
+        Some(data)
+    }
+
+    /// (Re)configure the window surface for the swapchain images
+    fn configure_surface(&mut self, desc: &TextureDesc, image_count: u32, mode: PresentMode) {
+        let Some(surface) = &self.surface else {
+            return;
+        };
+        let caps = surface.get_capabilities(&self.adapter);
+        let format = texture_format(desc.format);
+        // Surfaces often only offer the sRGB variant; render through a
+        // view in the requested format
+        let Some(surface_format) = [
+            format,
+            format.add_srgb_suffix(),
+            format.remove_srgb_suffix(),
+        ]
+        .into_iter()
+        .find(|f| caps.formats.contains(f)) else {
+            eprintln!(
+                "Surface does not support {:?} (supported: {:?})",
+                format, caps.formats
+            );
+            return;
+        };
+
+        // Fall back as `AutoNoVsync` does, ending at the always supported FIFO
+        let fallbacks: &[wgpu::PresentMode] = match mode {
+            PresentMode::Fifo => &[],
+            PresentMode::Mailbox => &[wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate],
+            PresentMode::Immediate => &[wgpu::PresentMode::Immediate, wgpu::PresentMode::Mailbox],
+        };
+        let present_mode = fallbacks
+            .iter()
+            .copied()
+            .find(|mode| caps.present_modes.contains(mode))
+            .unwrap_or(wgpu::PresentMode::Fifo);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (caps.usages & wgpu::TextureUsages::COPY_SRC),
+            format: surface_format,
+            width: desc.width,
+            height: desc.height,
+            present_mode,
+            desired_maximum_frame_latency: image_count.saturating_sub(1).max(1),
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: if surface_format == format {
+                vec![]
+            } else {
+                vec![format]
+            },
+        };
+        surface.configure(&self.device, &config);
+        self.surface_config = Some(config);
+    }
+}
+
+/// Adapters wgpu finds on every API it was built with
+pub(super) fn enumerate_adapters() -> Vec<AdapterInfo> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+    instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .map(adapter_info)
+        .collect()
+}
+
+fn power_preference(preference: AdapterPreference) -> wgpu::PowerPreference {
+    match preference {
+        AdapterPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        AdapterPreference::LowPower => wgpu::PowerPreference::LowPower,
+    }
+}
+
+fn adapter_info(adapter: &wgpu::Adapter) -> AdapterInfo {
+    let info = adapter.get_info();
+    let vendor = match info.vendor {
+        0x1002 => "AMD".to_string(),
+        0x106b => "Apple".to_string(),
+        0x10de => "NVIDIA".to_string(),
+        0x13b5 => "ARM".to_string(),
+        0x5143 => "Qualcomm".to_string(),
+        0x8086 => "Intel".to_string(),
+        0x10005 => "Mesa".to_string(),
+        _ if !info.driver.is_empty() => info.driver.clone(),
+        id => format!("{:#06x}", id),
+    };
+    AdapterInfo {
+        backend: Backend::Wgpu,
+        name: format!("{} ({:?})", info.name, info.backend),
+        vendor,
+        // wgpu does not expose memory sizes
+        vram: None,
+        adapter_type: match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => AdapterType::Discrete,
+            wgpu::DeviceType::IntegratedGpu => AdapterType::Integrated,
+            wgpu::DeviceType::VirtualGpu => AdapterType::Virtual,
+            wgpu::DeviceType::Cpu => AdapterType::Cpu,
+            wgpu::DeviceType::Other => AdapterType::Unknown,
+        },
+    }
+}
+
+impl NativeDevice for WgpuDevice {
+    fn adapter_info_native(&self) -> AdapterInfo {
+        adapter_info(&self.adapter)
+    }
+
+    fn create_texture_native(&mut self, desc: &TextureDesc) -> Result<NativeTexture, GfxError> {
+        let texture = self.create_wgpu_texture(desc)?;
+        let handle = self.next_handle();
+        self.textures.insert(handle, texture);
+        Ok(NativeTexture { handle })
+    }
+
+    fn create_buffer_native(
+        &mut self,
+        desc: &BufferDesc,
+        data: Option<&[u8]>,
+    ) -> Result<NativeBuffer, GfxError> {
+        // Copies and queue writes work in 4-byte units
+        let size = (desc.size as u64).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let usage =
+            buffer_usage(desc.usage) | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST;
+        let buffer = self.validated(|device| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size,
+                usage,
+                mapped_at_creation: data.is_some(),
+            })
+        })?;
+        if let Some(data) = data {
+            buffer.slice(..).get_mapped_range_mut()[..data.len()].copy_from_slice(data);
+            buffer.unmap();
+        }
+
+        let handle = self.next_handle();
+        self.buffers.insert(
+            handle,
+            WgpuBuffer {
+                buffer,
+                mapped: None,
+            },
+        );
+        Ok(NativeBuffer { handle })
+    }
+
+    fn create_shader_native(&mut self, desc: &ShaderDesc) -> Result<NativeShader, GfxError> {
+        let is_spirv = desc.code.len() >= 4
+            && u32::from_le_bytes([desc.code[0], desc.code[1], desc.code[2], desc.code[3]])
+                == SPIRV_MAGIC;
+        let source = if is_spirv {
+            if !desc.code.len().is_multiple_of(4) {
+                return Err(GfxError::InvalidDesc(
+                    "SPIR-V module size is not a multiple of 4".to_string(),
+                ));
+            }
+            wgpu::util::make_spirv(&desc.code)
+        } else {
+            let Ok(text) = std::str::from_utf8(&desc.code) else {
+                return Err(GfxError::InvalidDesc(
+                    "shader source is not valid UTF-8".to_string(),
+                ));
+            };
+            if text.trim_start().starts_with("#version") {
+                let stage = match desc.stage {
+                    ShaderStage::Vertex => wgpu::naga::ShaderStage::Vertex,
+                    ShaderStage::Fragment => wgpu::naga::ShaderStage::Fragment,
+                    ShaderStage::Compute => wgpu::naga::ShaderStage::Compute,
+                    stage => {
+                        return Err(GfxError::InvalidDesc(format!(
+                            "wgpu has no {:?} shaders",
+                            stage
+                        )))
+                    }
+                };
+                wgpu::ShaderSource::Glsl {
+                    shader: text.into(),
+                    stage,
+                    defines: &[],
+                }
+            } else {
+                wgpu::ShaderSource::Wgsl(text.into())
+            }
+        };
+
+        let module = self
+            .validated(|device| {
+                device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(&desc.entry_point),
+                    source,
+                })
+            })
+            .map_err(|err| match err {
+                // Parse and validation failures of the module itself
+                GfxError::InvalidDesc(log) => GfxError::ShaderCompileError(log),
+                err => err,
+            })?;
+        let handle = self.next_handle();
+        self.shaders.insert(
+            handle,
+            WgpuShader {
+                module,
+                entry_point: desc.entry_point.clone(),
+            },
+        );
+        Ok(NativeShader { handle })
+    }
+
+    fn create_pipeline_native(
+        &mut self,
+        desc: &PipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        let shader = |handle: ShaderHandle| {
+            shaders
+                .get(handle.0)
+                .and_then(|shader| self.shaders.get(&shader.native.handle))
+                .ok_or_else(|| GfxError::InvalidDesc(format!("unknown shader {:?}", handle)))
+        };
+        let vertex = shader(desc.vertex_shader)?;
+        let fragment = shader(desc.fragment_shader)?;
+
+        let slots = desc
+            .vertex_layout
+            .slots()
+            .map(|(stride, step_mode, attributes)| {
+                let attributes = attributes
+                    .iter()
+                    .map(|attribute| wgpu::VertexAttribute {
+                        format: vertex_format(attribute.format),
+                        offset: attribute.offset as u64,
+                        shader_location: attribute.location,
+                    })
+                    .collect::<Vec<_>>();
+                let step_mode = match step_mode {
+                    VertexStepMode::Vertex => wgpu::VertexStepMode::Vertex,
+                    VertexStepMode::Instance => wgpu::VertexStepMode::Instance,
+                };
+                (stride, step_mode, attributes)
+            })
+            .collect::<Vec<_>>();
+        let mut vertex_buffers = slots
+            .iter()
+            .map(|(stride, step_mode, attributes)| wgpu::VertexBufferLayout {
+                array_stride: *stride as u64,
+                step_mode: *step_mode,
+                attributes,
+            })
+            .collect::<Vec<_>>();
+        // A layout without attributes uses no vertex buffer
+        while vertex_buffers
+            .last()
+            .is_some_and(|buffer| buffer.attributes.is_empty())
+        {
+            vertex_buffers.pop();
+        }
+
+        let targets = desc
+            .color_formats
+            .iter()
+            .enumerate()
+            .map(|(i, format)| {
+                Some(wgpu::ColorTargetState {
+                    format: texture_format(*format),
+                    blend: desc
+                        .blend_states
+                        .get(i)
+                        .filter(|blend| blend.enabled)
+                        .map(blend_state),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            })
+            .collect::<Vec<_>>();
+        let depth = &desc.depth_stencil;
+        let depth_stencil = desc.depth_format.map(|format| wgpu::DepthStencilState {
+            format: texture_format(format),
+            depth_write_enabled: depth.depth_test_enabled && depth.depth_write_enabled,
+            depth_compare: if depth.depth_test_enabled {
+                compare_function(depth.depth_compare)
+            } else {
+                wgpu::CompareFunction::Always
+            },
+            stencil: Default::default(),
+            bias: Default::default(),
+        });
+        let rasterizer = &desc.rasterizer;
+        let (layout, texture_sets) = self.pipeline_layout(&desc.layout, bind_group_layouts)?;
+
+        let cache = self.pipeline_cache.as_ref();
+        let pipeline = self.validated(|device| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &vertex.module,
+                    entry_point: Some(&vertex.entry_point),
+                    compilation_options: Default::default(),
+                    buffers: &vertex_buffers,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: match desc.topology {
+                        PrimitiveTopology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+                        PrimitiveTopology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+                        PrimitiveTopology::LineList => wgpu::PrimitiveTopology::LineList,
+                        PrimitiveTopology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+                        PrimitiveTopology::PointList => wgpu::PrimitiveTopology::PointList,
+                    },
+                    strip_index_format: None,
+                    front_face: match rasterizer.front_face {
+                        FrontFace::Clockwise => wgpu::FrontFace::Cw,
+                        FrontFace::CounterClockwise => wgpu::FrontFace::Ccw,
+                    },
+                    cull_mode: match rasterizer.cull_mode {
+                        CullMode::None => None,
+                        CullMode::Front => Some(wgpu::Face::Front),
+                        CullMode::Back => Some(wgpu::Face::Back),
+                    },
+                    unclipped_depth: false,
+                    polygon_mode: match rasterizer.polygon_mode {
+                        PolygonMode::Fill => wgpu::PolygonMode::Fill,
+                        PolygonMode::Line => wgpu::PolygonMode::Line,
+                        PolygonMode::Point => wgpu::PolygonMode::Point,
+                    },
+                    conservative: false,
+                },
+                depth_stencil,
+                multisample: wgpu::MultisampleState {
+                    count: desc.sample_count,
+                    ..Default::default()
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &fragment.module,
+                    entry_point: Some(&fragment.entry_point),
+                    compilation_options: Default::default(),
+                    targets: &targets,
+                }),
+                multiview: None,
+                cache,
+            })
+        })?;
+
+        let handle = self.next_handle();
+        self.pipelines.insert(
+            handle,
+            WgpuPipeline {
+                pipeline,
+                texture_sets,
+            },
+        );
+        Ok(NativePipeline { handle })
+    }
+
+    fn create_compute_pipeline_native(
+        &mut self,
+        desc: &ComputePipelineDesc,
+        shaders: &ResourcePool<ShaderResource>,
+        bind_group_layouts: &ResourcePool<BindGroupLayoutResource>,
+    ) -> Result<NativePipeline, GfxError> {
+        let shader = shaders
+            .get(desc.shader.0)
+            .and_then(|shader| self.shaders.get(&shader.native.handle))
+            .ok_or_else(|| GfxError::InvalidDesc(format!("unknown shader {:?}", desc.shader)))?;
+        let (layout, texture_sets) = self.pipeline_layout(&desc.layout, bind_group_layouts)?;
+        let cache = self.pipeline_cache.as_ref();
+        let pipeline = self.validated(|device| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&layout),
+                module: &shader.module,
+                entry_point: Some(&shader.entry_point),
+                compilation_options: Default::default(),
+                cache,
+            })
+        })?;
+
+        let handle = self.next_handle();
+        self.compute_pipelines.insert(
+            handle,
+            WgpuComputePipeline {
+                pipeline,
+                texture_sets,
+            },
+        );
+        Ok(NativePipeline { handle })
+    }
+
+    fn create_sampler_native(&mut self, desc: &SamplerDesc) -> Result<NativeSampler, GfxError> {
+        let address_mode = |mode: AddressMode| match mode {
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        };
+        let filter = |filter: FilterMode| match filter {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        };
+        let sampler = self.validated(|device| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: None,
+                address_mode_u: address_mode(desc.address_u),
+                address_mode_v: address_mode(desc.address_v),
+                address_mode_w: address_mode(desc.address_w),
+                mag_filter: filter(desc.mag_filter),
+                min_filter: filter(desc.min_filter),
+                mipmap_filter: filter(desc.mip_filter),
+                compare: desc.compare.map(compare_function),
+                anisotropy_clamp: desc.max_anisotropy,
+                ..Default::default()
+            })
+        })?;
+        let handle = self.next_handle();
+        self.samplers.insert(handle, sampler);
+        Ok(NativeSampler { handle })
+    }
+
+    fn create_bind_group_layout_native(
+        &mut self,
+        desc: &BindGroupLayoutDesc,
+    ) -> Result<NativeBindGroupLayout, GfxError> {
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| layout_entry(entry.binding, entry.ty))
+            .collect::<Vec<_>>();
+        let layout = self.validated(|device| {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &entries,
+            })
+        })?;
+        let handle = self.next_handle();
+        self.bind_group_layouts.insert(handle, layout);
+        Ok(NativeBindGroupLayout { handle })
+    }
+
+    fn create_bind_group_native(
+        &mut self,
+        layout: NativeBindGroupLayout,
+        desc: &BindGroupLayoutDesc,
+        entries: &[NativeBindGroupEntry],
+    ) -> Result<NativeBindGroup, GfxError> {
+        let Some(layout) = self.bind_group_layouts.get(&layout.handle) else {
+            return Err(GfxError::InvalidDesc(
+                "bind group layout is gone".to_string(),
+            ));
+        };
+        // Storage bindings see a view of mip 0 alone
+        let storage_views = desc
+            .entries
+            .iter()
+            .zip(entries)
+            .map(|(slot, entry)| match (slot.ty, entry.resource) {
+                (BindingType::StorageTexture { .. }, NativeBindingResource::Texture(texture)) => {
+                    self.textures.get(&texture.handle).map(|texture| {
+                        texture.texture.create_view(&wgpu::TextureViewDescriptor {
+                            mip_level_count: Some(1),
+                            ..Default::default()
+                        })
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut resolved = Vec::with_capacity(entries.len());
+        for (entry, storage_view) in entries.iter().zip(&storage_views) {
+            let resource = match entry.resource {
+                NativeBindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } => self.buffers.get(&buffer.handle).map(|buffer| {
+                    wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer.buffer,
+                        offset,
+                        size: wgpu::BufferSize::new(size),
+                    })
+                }),
+                NativeBindingResource::Texture(texture) => storage_view
+                    .as_ref()
+                    .or_else(|| self.textures.get(&texture.handle).map(|t| &t.view))
+                    .map(wgpu::BindingResource::TextureView),
+                NativeBindingResource::Sampler(sampler) => self
+                    .samplers
+                    .get(&sampler.handle)
+                    .map(wgpu::BindingResource::Sampler),
+            };
+            let Some(resource) = resource else {
+                return Err(GfxError::InvalidDesc(format!(
+                    "resource at binding {} is gone",
+                    entry.binding
+                )));
+            };
+            resolved.push(wgpu::BindGroupEntry {
+                binding: entry.binding,
+                resource,
+            });
+        }
+        let group = self.validated(|device| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout,
+                entries: &resolved,
+            })
+        })?;
+        let handle = self.next_handle();
+        self.bind_groups.insert(handle, group);
+        Ok(NativeBindGroup { handle })
+    }
+
+    fn destroy_texture_native(&mut self, texture: NativeTexture) {
+        if let Some(texture) = self.textures.remove(&texture.handle) {
+            texture.texture.destroy();
+        }
+        self.texture_bind_groups
+            .retain(|(_, _, bound), _| bound.iter().all(|(t, _)| *t != texture.handle));
+    }
+
+    fn destroy_buffer_native(&mut self, buffer: NativeBuffer) {
+        if let Some(buffer) = self.buffers.remove(&buffer.handle) {
+            buffer.buffer.destroy();
+        }
+    }
+
+    fn destroy_shader_native(&mut self, shader: NativeShader) {
+        self.shaders.remove(&shader.handle);
+    }
+
+    fn destroy_pipeline_native(&mut self, pipeline: NativePipeline) {
+        self.pipelines.remove(&pipeline.handle);
+        self.texture_bind_groups
+            .retain(|(handle, _, _), _| *handle != pipeline.handle);
+    }
+
+    fn destroy_compute_pipeline_native(&mut self, pipeline: NativePipeline) {
+        self.compute_pipelines.remove(&pipeline.handle);
+        self.texture_bind_groups
+            .retain(|(handle, _, _), _| *handle != pipeline.handle);
+        if self.compute_pipeline == Some(pipeline.handle) {
+            self.compute_pipeline = None;
+        }
+    }
+
+    fn destroy_sampler_native(&mut self, sampler: NativeSampler) {
+        self.samplers.remove(&sampler.handle);
+        self.texture_bind_groups
+            .retain(|(_, _, bound), _| bound.iter().all(|(_, s)| *s != sampler.handle));
+    }
+
+    fn destroy_bind_group_layout_native(&mut self, layout: NativeBindGroupLayout) {
+        self.bind_group_layouts.remove(&layout.handle);
+    }
+
+    fn destroy_bind_group_native(&mut self, group: NativeBindGroup) {
+        self.bind_groups.remove(&group.handle);
+    }
+
+    fn update_buffer_native(&mut self, buffer: NativeBuffer, offset: usize, data: &[u8]) {
+        let Some(size) = self.buffers.get(&buffer.handle).map(|b| b.buffer.size()) else {
+            return;
+        };
+        let (offset, end) = (offset as u64, (offset + data.len()) as u64);
+        if end > size {
+            eprintln!(
+                "Buffer update of {} bytes at {} overflows {} bytes",
+                data.len(),
+                offset,
+                size
+            );
+            return;
+        }
+
+        // Queue writes are 4-byte aligned; widen the range and keep the
+        // bytes around the update
+        let alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+        let start = offset / alignment * alignment;
+        let aligned_end = end.next_multiple_of(alignment);
+        if start == offset && aligned_end == end {
+            let buffer = &self.buffers[&buffer.handle].buffer;
+            self.queue.write_buffer(buffer, offset, data);
+            return;
+        }
+        let Some(mut bytes) = self.read_buffer(buffer.handle, start, aligned_end - start) else {
+            return;
+        };
+        let at = (offset - start) as usize;
+        bytes[at..at + data.len()].copy_from_slice(data);
+        let buffer = &self.buffers[&buffer.handle].buffer;
+        self.queue.write_buffer(buffer, start, &bytes);
+    }
+
+    fn map_buffer_native(&mut self, buffer: NativeBuffer) -> *mut u8 {
+        let Some(size) = self.buffers.get(&buffer.handle).map(|b| b.buffer.size()) else {
+            return std::ptr::null_mut();
+        };
+        let Some(data) = self.read_buffer(buffer.handle, 0, size) else {
+            return std::ptr::null_mut();
+        };
+        let buffer = self.buffers.get_mut(&buffer.handle).unwrap();
+        buffer.mapped.insert(data).as_mut_ptr()
+    }
+
+    fn unmap_buffer_native(&mut self, buffer: NativeBuffer) {
+        if let Some(buffer) = self.buffers.get_mut(&buffer.handle) {
+            if let Some(data) = buffer.mapped.take() {
+                self.queue.write_buffer(&buffer.buffer, 0, &data);
+            }
+        }
+    }
+
+    fn update_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        let texture = self
+            .textures
+            .get(&texture.handle)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))?;
+        // Swapchain frames have no TextureDesc here; the wgpu format is enough
+        let format = texture.texture.format();
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format.block_copy_size(None).unwrap_or(0);
+        let row_bytes = region.width.div_ceil(block_width) * block_size;
+        let rows = region.height.div_ceil(block_height);
+        let z = if texture.texture.dimension() == wgpu::TextureDimension::D3 {
+            region.z
+        } else {
+            layer
+        };
+        // The queue stages the data and copies it before the next submit
+        self.validated(|_| {
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture.texture,
+                    mip_level: mip,
+                    origin: wgpu::Origin3d {
+                        x: region.x,
+                        y: region.y,
+                        z,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(row_bytes),
+                    rows_per_image: Some(rows),
+                },
+                wgpu::Extent3d {
+                    width: region.width,
+                    height: region.height,
+                    depth_or_array_layers: region.depth,
+                },
+            )
+        })
+    }
+
+    fn generate_mipmaps_native(
+        &mut self,
+        texture: NativeTexture,
+        desc: &TextureDesc,
+    ) -> Result<(), GfxError> {
+        let format = texture_format(desc.format);
+        if !self.mipmap_pipelines.contains_key(&format) {
+            let pipeline = self.validated(|device| mipmap_pipeline(device, format))?;
+            self.mipmap_pipelines.insert(format, pipeline);
+        }
+        let (pipeline, layout) = &self.mipmap_pipelines[&format];
+        let Some(texture) = self.textures.get(&texture.handle) else {
+            return Err(GfxError::InvalidDesc("unknown texture".to_string()));
+        };
+
+        let encoder = self
+            .encoder
+            .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()));
+        for layer in 0..layer_count(desc) {
+            let view = |mip: u32| {
+                texture.texture.create_view(&wgpu::TextureViewDescriptor {
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_mip_level: mip,
+                    mip_level_count: Some(1),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            };
+            for mip in 1..desc.mip_levels {
+                let (source, target) = (view(mip - 1), view(mip));
+                let group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    }],
+                });
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("mipmap"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    ..Default::default()
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_texture_native(
+        &mut self,
+        texture: NativeTexture,
+        _desc: &TextureDesc,
+    ) -> Result<Vec<u8>, GfxError> {
+        // The copy has to come after everything recorded so far
+        self.flush();
+        let texture = &self
+            .textures
+            .get(&texture.handle)
+            .ok_or_else(|| GfxError::InvalidDesc("unknown texture".to_string()))?
+            .texture;
+        let format = texture.format();
+        let Some(block_size) = format.block_copy_size(None) else {
+            return Err(GfxError::InvalidDesc(format!(
+                "{:?} cannot be copied to a buffer",
+                format
+            )));
+        };
+        let (block_width, block_height) = format.block_dimensions();
+        let size = texture.size();
+        let row_bytes = size.width.div_ceil(block_width) * block_size;
+        let rows = size.height.div_ceil(block_height);
+        // Buffer rows of a copy are padded to 256 bytes
+        let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let staging = self.validated(|device| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("texture readback"),
+                size: padded_row_bytes as u64 * rows as u64 * size.depth_or_array_layers as u64,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        })?;
+        self.validated(|device| {
+            let mut encoder = device.create_command_encoder(&Default::default());
+            encoder.copy_texture_to_buffer(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &staging,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_row_bytes),
+                        rows_per_image: Some(rows),
+                    },
+                },
+                size,
+            );
+            self.queue.submit([encoder.finish()]);
+        })?;
+
+        // Block until the copy has landed in the mapped buffer
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
+            eprintln!("Texture readback failed: {}", err);
+            return Err(GfxError::DeviceLost);
+        }
+        if !matches!(receiver.try_recv(), Ok(Ok(()))) {
+            return Err(GfxError::DeviceLost);
+        }
+
+        let mapped = slice.get_mapped_range();
+        let pixels = mapped
+            .chunks_exact(padded_row_bytes as usize)
+            .flat_map(|row| &row[..row_bytes as usize])
+            .copied()
+            .collect();
+        drop(mapped);
+        staging.unmap();
+        Ok(pixels)
+    }
+
+    fn begin_frame_native(&mut self) {}
+
+    fn begin_render_pass_native(
+        &mut self,
+        desc: &RenderPassDesc,
+        textures: &ResourcePool<TextureResource>,
+    ) {
+        self.end_render_pass_native();
+        self.pipeline = None;
+        let view = |handle: TextureHandle| {
+            textures
+                .get(handle.0)
+                .and_then(|texture| self.textures.get(&texture.native.handle))
+                .map(|texture| &texture.view)
+        };
+
+        let mut color_attachments = Vec::new();
+        for attachment in &desc.color_attachments {
+            let resolve_target = match attachment.store {
+                StoreOp::Resolve(target) => match view(target) {
+                    Some(view) => Some(view),
+                    None => {
+                        eprintln!("Render pass resolve target is not a live texture");
+                        return;
+                    }
+                },
+                _ => None,
+            };
+            let Some(view) = view(attachment.texture) else {
+                eprintln!("Render pass color attachment is not a live texture");
+                return;
+            };
+            color_attachments.push(Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: load_op(attachment.load, wgpu::Color::TRANSPARENT, |c| wgpu::Color {
+                        r: c.r as f64,
+                        g: c.g as f64,
+                        b: c.b as f64,
+                        a: c.a as f64,
+                    }),
+                    store: store_op(attachment.store),
+                },
+            }));
+        }
+
+        let mut depth_stencil_attachment = None;
+        if let Some(attachment) = &desc.depth_attachment {
+            let Some(view) = view(attachment.texture) else {
+                eprintln!("Render pass depth attachment is not a live texture");
+                return;
+            };
+            let has_stencil = textures
+                .get(attachment.texture.0)
+                .is_some_and(|texture| texture.desc.format == TextureFormat::Depth24Stencil8);
+            depth_stencil_attachment = Some(wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: load_op(attachment.load, 1.0, |c| c.depth),
+                    store: store_op(attachment.store),
+                }),
+                stencil_ops: has_stencil.then(|| wgpu::Operations {
+                    load: load_op(attachment.load, 0, |c| c.stencil),
+                    store: store_op(attachment.store),
+                }),
+            });
+        }
+
+        let encoder = self
+            .encoder
+            .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()));
+        let pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &color_attachments,
+            depth_stencil_attachment,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.pass = Some(pass.forget_lifetime());
+    }
+
+    fn end_render_pass_native(&mut self) {
+        self.pass = None;
+        self.bound_textures.clear();
+        self.compute_bind_groups.clear();
+    }
+
+    fn bind_pipeline_native(&mut self, pipeline: NativePipeline) {
+        if let (Some(pass), Some(wgpu_pipeline)) =
+            (&mut self.pass, self.pipelines.get(&pipeline.handle))
+        {
+            pass.set_pipeline(&wgpu_pipeline.pipeline);
+            self.pipeline = Some(pipeline.handle);
+        }
+    }
+
+    fn set_viewport_native(&mut self, viewport: &Viewport) {
+        if let Some(pass) = &mut self.pass {
+            pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                viewport.min_depth,
+                viewport.max_depth,
+            );
+        }
+    }
+
+    fn set_scissor_native(&mut self, scissor: &Rect) {
+        if let Some(pass) = &mut self.pass {
+            pass.set_scissor_rect(
+                scissor.x.max(0) as u32,
+                scissor.y.max(0) as u32,
+                scissor.width,
+                scissor.height,
+            );
+        }
+    }
+
+    fn bind_vertex_buffer_native(&mut self, slot: u32, buffer: NativeBuffer, offset: u64) {
+        if let (Some(pass), Some(buffer)) = (&mut self.pass, self.buffers.get(&buffer.handle)) {
+            pass.set_vertex_buffer(slot, buffer.buffer.slice(offset..));
+        }
+    }
+
+    fn bind_index_buffer_native(
+        &mut self,
+        buffer: NativeBuffer,
+        offset: u64,
+        index_type: IndexType,
+    ) {
+        let format = match index_type {
+            IndexType::UInt16 => wgpu::IndexFormat::Uint16,
+            IndexType::UInt32 => wgpu::IndexFormat::Uint32,
+        };
+        if let (Some(pass), Some(buffer)) = (&mut self.pass, self.buffers.get(&buffer.handle)) {
+            pass.set_index_buffer(buffer.buffer.slice(offset..), format);
+        }
+    }
+
+    fn bind_group_native(&mut self, index: u32, group: NativeBindGroup) {
+        let Some(pass) = &mut self.pass else {
+            self.compute_bind_groups.insert(index, group.handle);
+            return;
+        };
+        if let Some(group) = self.bind_groups.get(&group.handle) {
+            pass.set_bind_group(index, group, &[]);
+        }
+    }
+
+    fn bind_texture_native(
+        &mut self,
+        set: u32,
+        binding: u32,
+        texture: NativeTexture,
+        sampler: NativeSampler,
+    ) {
+        self.bound_textures
+            .insert((set, binding), (texture.handle, sampler.handle));
+    }
+
+    fn draw_native(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        if !self.prepare_draw() {
+            return;
+        }
+        if let Some(pass) = &mut self.pass {
+            pass.draw(
+                first_vertex..first_vertex + vertex_count,
+                first_instance..first_instance + instance_count,
+            );
+        }
+    }
+
+    fn draw_indexed_native(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        if !self.prepare_draw() {
+            return;
+        }
+        if let Some(pass) = &mut self.pass {
+            pass.draw_indexed(
+                first_index..first_index + index_count,
+                vertex_offset,
+                first_instance..first_instance + instance_count,
+            );
+        }
+    }
+
+    fn bind_compute_pipeline_native(&mut self, pipeline: NativePipeline) {
+        if self.compute_pipelines.contains_key(&pipeline.handle) {
+            self.compute_pipeline = Some(pipeline.handle);
+        }
+    }
+
+    fn dispatch_native(&mut self, x: u32, y: u32, z: u32) {
+        let Some(handle) = self.compute_pipeline else {
+            return;
+        };
+        let Some(texture_groups) = self.texture_bind_groups(handle) else {
+            return;
+        };
+        let Some(pipeline) = self.compute_pipelines.get(&handle) else {
+            return;
+        };
+        // One pass per dispatch; wgpu orders the accesses between passes
+        let encoder = self
+            .encoder
+            .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()));
+        let mut pass = encoder.begin_compute_pass(&Default::default());
+        pass.set_pipeline(&pipeline.pipeline);
+        for (index, group) in &self.compute_bind_groups {
+            if let Some(group) = self.bind_groups.get(group) {
+                pass.set_bind_group(*index, group, &[]);
+            }
+        }
+        for (set, key) in &texture_groups {
+            pass.set_bind_group(*set, &self.texture_bind_groups[key], &[]);
+        }
+        pass.dispatch_workgroups(x, y, z);
+    }
+
+    fn create_surface_native(&mut self, _window: &NativeHandle) -> Result<NativeSurface, GfxError> {
+        // Created with the device; the adapter has to be able to present to it
+        if self.surface.is_none() {
+            return Err(GfxError::Unsupported(
+                "the wgpu adapter cannot present to the window".to_string(),
+            ));
+        }
+        Ok(NativeSurface { handle: 0 })
+    }
+
+    fn create_swapchain_native(
+        &mut self,
+        surface: Option<NativeSurface>,
+        desc: &TextureDesc,
+        image_count: u32,
+        present_mode: PresentMode,
+    ) -> NativeSwapchain {
+        let presents = surface.is_some() && self.surface.is_some();
+        if presents {
+            self.configure_surface(desc, image_count, present_mode);
+        }
+        let images = (0..image_count)
+            .filter_map(|_| {
+                // Window images are filled in by acquire
+                let texture = if presents {
+                    None
+                } else {
+                    match self.create_wgpu_texture(desc) {
+                        Ok(texture) => Some(texture),
+                        Err(err) => {
+                            eprintln!("Failed to create swapchain image: {}", err);
+                            return None;
+                        }
+                    }
+                };
+                let handle = self.next_handle();
+                if let Some(texture) = texture {
+                    self.textures.insert(handle, texture);
+                }
+                Some(handle)
+            })
+            .collect::<Vec<_>>();
+
+        let handle = self.next_handle();
+        let image_count = images.len() as u32;
+        self.swapchains.insert(handle, images);
+        NativeSwapchain {
+            handle,
+            image_count,
+            next_image: 0,
+        }
+    }
+
+    fn swapchain_images_native(&mut self, swapchain: &NativeSwapchain) -> Vec<NativeTexture> {
+        self.swapchains
+            .get(&swapchain.handle)
+            .into_iter()
+            .flatten()
+            .map(|handle| NativeTexture { handle: *handle })
+            .collect()
+    }
+
+    fn acquire_next_image_native(&mut self, swapchain: &mut NativeSwapchain) -> Option<u32> {
+        if swapchain.image_count == 0 {
+            return Some(0);
+        }
+        let index = swapchain.next_image;
+        swapchain.next_image = (index + 1) % swapchain.image_count;
+
+        if let Some(surface) = &self.surface {
+            let image = self.swapchains[&swapchain.handle][index as usize];
+            let frame = match surface.get_current_texture() {
+                Ok(frame) => Some(frame),
+                // Resized or lost by the window system: reconfigure
+                Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => return None,
+                Err(err) => {
+                    eprintln!("Failed to acquire surface frame: {}", err);
+                    None
+                }
+            };
+            if let Some(frame) = frame {
+                let format = self
+                    .surface_config
+                    .as_ref()
+                    .and_then(|config| config.view_formats.first().copied());
+                let view = frame.texture.create_view(&wgpu::TextureViewDescriptor {
+                    format,
+                    ..Default::default()
+                });
+                self.textures.insert(
+                    image,
+                    WgpuTexture {
+                        texture: frame.texture.clone(),
+                        view,
+                    },
+                );
+                self.frame = Some(frame);
+            }
+        }
+        Some(index)
+    }
+
+    fn destroy_swapchain_native(&mut self, swapchain: NativeSwapchain) {
+        self.flush();
+        self.frame = None;
+        for handle in self
+            .swapchains
+            .remove(&swapchain.handle)
+            .unwrap_or_default()
+        {
+            if let Some(texture) = self.textures.remove(&handle) {
+                if self.surface.is_none() {
+                    texture.texture.destroy();
+                }
+            }
+        }
+    }
+
+    fn present_native(&mut self, swapchain: &NativeSwapchain, image_index: u32) {
+        self.flush();
+        if let Some(frame) = self.frame.take() {
+            if let Some(image) = self
+                .swapchains
+                .get(&swapchain.handle)
+                .and_then(|images| images.get(image_index as usize))
+            {
+                self.textures.remove(image);
+            }
+            frame.present();
+        }
+    }
+
+    fn wait_idle_native(&mut self) {
+        self.flush();
+        if let Err(err) = self.device.poll(wgpu::PollType::Wait) {
+            eprintln!("Wait idle failed: {}", err);
+        }
+    }
+
+    fn device_lost_native(&mut self) -> bool {
+        // The lost callback runs from polls
+        let _ = self.device.poll(wgpu::PollType::Poll);
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    fn submit_native(&mut self) {
+        self.flush();
+    }
+
+    fn copy_buffer_native(
+        &mut self,
+        src: NativeBuffer,
+        src_offset: u64,
+        dst: NativeBuffer,
+        dst_offset: u64,
+        size: u64,
+    ) {
+        let (Some(src), Some(dst)) = (self.buffers.get(&src.handle), self.buffers.get(&dst.handle))
+        else {
+            return;
+        };
+        self.encoder
+            .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()))
+            .copy_buffer_to_buffer(&src.buffer, src_offset, &dst.buffer, dst_offset, size);
+    }
+
+    fn copy_buffer_to_texture_native(
+        &mut self,
+        src: NativeBuffer,
+        layout: TexelBufferLayout,
+        texture: NativeTexture,
+        mip: u32,
+        layer: u32,
+        region: &TextureRegion,
+    ) -> Result<(), GfxError> {
+        let (Some(buffer), Some(texture)) = (
+            self.buffers.get(&src.handle),
+            self.textures.get(&texture.handle),
+        ) else {
+            return Err(GfxError::InvalidDesc(
+                "unknown buffer or texture".to_string(),
+            ));
+        };
+        let (_, block_height) = texture.texture.format().block_dimensions();
+        let z = if texture.texture.dimension() == wgpu::TextureDimension::D3 {
+            region.z
+        } else {
+            layer
+        };
+        self.encoder
+            .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()))
+            .copy_buffer_to_texture(
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &buffer.buffer,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset: layout.offset,
+                        bytes_per_row: Some(layout.bytes_per_row),
+                        rows_per_image: Some(region.height.div_ceil(block_height)),
+                    },
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture.texture,
+                    mip_level: mip,
+                    origin: wgpu::Origin3d {
+                        x: region.x,
+                        y: region.y,
+                        z,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width: region.width,
+                    height: region.height,
+                    depth_or_array_layers: region.depth,
+                },
+            );
+        Ok(())
+    }
+
+    fn signal_fence_native(&mut self, value: u64) {
+        // The callback follows the last submission, so submit what is recorded
+        self.flush();
+        let completed = self.completed_fence.clone();
+        self.queue.on_submitted_work_done(move || {
+            completed.fetch_max(value, Ordering::Release);
+        });
+        // An empty submission marks the point to wait for
+        let index = self.queue.submit([]);
+        self.fence_submissions.push_back((value, index));
+    }
+
+    fn completed_fence_native(&mut self) -> u64 {
+        if let Err(err) = self.device.poll(wgpu::PollType::Poll) {
+            eprintln!("Polling the device failed: {}", err);
+        }
+        let completed = self.completed_fence.load(Ordering::Acquire);
+        while self
+            .fence_submissions
+            .front()
+            .is_some_and(|&(value, _)| value <= completed)
+        {
+            self.fence_submissions.pop_front();
+        }
+        completed
+    }
+
+    fn wait_fence_native(&mut self, value: u64) {
+        let mut index = None;
+        while self
+            .fence_submissions
+            .front()
+            .is_some_and(|&(fence, _)| fence <= value)
+        {
+            index = self.fence_submissions.pop_front().map(|(_, index)| index);
+        }
+        if let Some(index) = index {
+            if let Err(err) = self
+                .device
+                .poll(wgpu::PollType::WaitForSubmissionIndex(index))
+            {
+                eprintln!("Waiting for a fence failed: {}", err);
+            }
+        }
+    }
+
+    fn pipeline_cache_data_native(&mut self) -> Vec<u8> {
+        self.pipeline_cache
+            .as_ref()
+            .and_then(wgpu::PipelineCache::get_data)
+            .unwrap_or_default()
+    }
+
+    fn load_pipeline_cache_native(&mut self, data: &[u8]) -> bool {
+        if self.pipeline_cache.is_none() {
+            return false;
+        }
+        self.pipeline_cache = create_pipeline_cache(&self.device, Some(data));
+        true
+    }
+
+    fn create_query_pool_native(&mut self, count: u32) -> Option<NativeQueryPool> {
+        let needed = wgpu::Features::TIMESTAMP_QUERY
+            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS
+            | wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES;
+        if !self.device.features().contains(needed) {
+            return None;
+        }
+        let set = self.device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        let resolve = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let handle = self.next_handle();
+        self.query_pools.insert(
+            handle,
+            WgpuQueryPool {
+                set,
+                resolve,
+                readback,
+                mapped: Arc::new(AtomicBool::new(false)),
+            },
+        );
+        Some(NativeQueryPool { handle })
+    }
+
+    fn write_timestamp_native(&mut self, pool: NativeQueryPool, index: u32) {
+        let Some(pool) = self.query_pools.get(&pool.handle) else {
+            return;
+        };
+        match &mut self.pass {
+            Some(pass) => pass.write_timestamp(&pool.set, index),
+            None => self
+                .encoder
+                .get_or_insert_with(|| self.device.create_command_encoder(&Default::default()))
+                .write_timestamp(&pool.set, index),
+        }
+    }
+
+    fn resolve_query_pool_native(&mut self, pool: NativeQueryPool, count: u32) {
+        let Some(pool) = self.query_pools.get(&pool.handle) else {
+            return;
+        };
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.resolve_query_set(&pool.set, 0..count, &pool.resolve, 0);
+        encoder.copy_buffer_to_buffer(&pool.resolve, 0, &pool.readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+        let mapped = pool.mapped.clone();
+        pool.readback
+            .slice(..size)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                mapped.store(result.is_ok(), Ordering::Release);
+            });
+    }
+
+    fn read_query_pool_native(&mut self, pool: NativeQueryPool, count: u32) -> Option<Vec<u64>> {
+        if let Err(err) = self.device.poll(wgpu::PollType::Poll) {
+            eprintln!("Timestamp readback failed: {}", err);
+            return None;
+        }
+        let pool = self.query_pools.get(&pool.handle)?;
+        if !pool.mapped.swap(false, Ordering::Acquire) {
+            return None;
+        }
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        let times = pool
+            .readback
+            .slice(..size)
+            .get_mapped_range()
+            .chunks_exact(8)
+            .map(|ticks| {
+                let ticks = u64::from_le_bytes(ticks.try_into().unwrap());
+                (ticks as f64 * self.timestamp_period as f64) as u64
+            })
+            .collect();
+        pool.readback.unmap();
+        Some(times)
+    }
+}
+
+/// Pipeline cache seeded with `data`, if the device supports one
+fn create_pipeline_cache(
+    device: &wgpu::Device,
+    data: Option<&[u8]>,
+) -> Option<wgpu::PipelineCache> {
+    if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+        return None;
+    }
+    // SAFETY: `data` comes from `get_data` of an earlier run, checked by
+    // `BackendDevice` to be intact and from this adapter; with `fallback`
+    // wgpu starts empty if the driver rejects it
+    let cache = unsafe {
+        device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+            label: Some("avila"),
+            data,
+            fallback: true,
+        })
+    };
+    Some(cache)
+}
+
+fn texture_format(format: TextureFormat) -> wgpu::TextureFormat {
+    match format {
+        TextureFormat::Rgba8 => wgpu::TextureFormat::Rgba8Unorm,
+        TextureFormat::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+        TextureFormat::Rgba16f => wgpu::TextureFormat::Rgba16Float,
+        TextureFormat::Rgba32f => wgpu::TextureFormat::Rgba32Float,
+        TextureFormat::Bgra8 => wgpu::TextureFormat::Bgra8Unorm,
+        TextureFormat::Depth24 => wgpu::TextureFormat::Depth24Plus,
+        TextureFormat::Depth32f => wgpu::TextureFormat::Depth32Float,
+        TextureFormat::Depth24Stencil8 => wgpu::TextureFormat::Depth24PlusStencil8,
+        TextureFormat::Bc1 => wgpu::TextureFormat::Bc1RgbaUnorm,
+        TextureFormat::Bc3 => wgpu::TextureFormat::Bc3RgbaUnorm,
+        TextureFormat::Bc7 => wgpu::TextureFormat::Bc7RgbaUnorm,
+    }
+}
+
+/// `MIPMAP_SHADER` pipeline rendering to `format`, with its bind group layout
+fn mipmap_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("mipmap"),
+        source: wgpu::ShaderSource::Wgsl(MIPMAP_SHADER.into()),
+    });
+    // Loads only, so unfilterable formats work too
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("mipmap"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("mipmap"),
+        bind_group_layouts: &[&layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("mipmap"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &module,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        primitive: Default::default(),
+        depth_stencil: None,
+        multisample: Default::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &module,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(format.into())],
+        }),
+        multiview: None,
+        cache: None,
+    });
+    (pipeline, layout)
+}
+
+/// Layout entries of a texture and its sampler at `binding + 1`
+fn texture_layout_entries(texture: &TextureBinding) -> [wgpu::BindGroupLayoutEntry; 2] {
+    [
+        layout_entry(
+            texture.binding,
+            BindingType::Texture {
+                dimension: texture.dimension,
+                array: texture.array,
+                comparison: texture.comparison,
+            },
+        ),
+        layout_entry(
+            texture.binding + 1,
+            BindingType::Sampler {
+                comparison: texture.comparison,
+            },
+        ),
+    ]
+}
+
+fn layout_entry(binding: u32, ty: BindingType) -> wgpu::BindGroupLayoutEntry {
+    let ty = match ty {
+        BindingType::UniformBuffer => wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        BindingType::StorageBuffer { read_only } => wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        BindingType::Texture {
+            dimension,
+            array,
+            comparison,
+        } => wgpu::BindingType::Texture {
+            sample_type: if comparison {
+                wgpu::TextureSampleType::Depth
+            } else {
+                wgpu::TextureSampleType::Float { filterable: true }
+            },
+            view_dimension: match dimension {
+                TextureDimension::D1 => wgpu::TextureViewDimension::D1,
+                TextureDimension::D2 if array => wgpu::TextureViewDimension::D2Array,
+                TextureDimension::D2 => wgpu::TextureViewDimension::D2,
+                TextureDimension::D3 => wgpu::TextureViewDimension::D3,
+                TextureDimension::Cube => wgpu::TextureViewDimension::Cube,
+            },
+            multisampled: false,
+        },
+        BindingType::Sampler { comparison } => wgpu::BindingType::Sampler(if comparison {
+            wgpu::SamplerBindingType::Comparison
+        } else {
+            wgpu::SamplerBindingType::Filtering
+        }),
+        BindingType::StorageTexture {
+            format,
+            dimension,
+            access,
+        } => wgpu::BindingType::StorageTexture {
+            access: match access {
+                StorageTextureAccess::ReadOnly => wgpu::StorageTextureAccess::ReadOnly,
+                StorageTextureAccess::WriteOnly => wgpu::StorageTextureAccess::WriteOnly,
+                StorageTextureAccess::ReadWrite => wgpu::StorageTextureAccess::ReadWrite,
+            },
+            format: texture_format(format),
+            view_dimension: match dimension {
+                TextureDimension::D1 => wgpu::TextureViewDimension::D1,
+                TextureDimension::D3 => wgpu::TextureViewDimension::D3,
+                _ => wgpu::TextureViewDimension::D2,
+            },
+        },
+    };
+    // Vertex shaders cannot write to storage
+    let visibility = match ty {
+        wgpu::BindingType::StorageTexture { .. }
+        | wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: false },
+            ..
+        } => wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+        _ => wgpu::ShaderStages::VERTEX_FRAGMENT | wgpu::ShaderStages::COMPUTE,
+    };
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty,
+        count: None,
+    }
+}
+
+/// wgpu has no "don't care" load; clearing to `dont_care` is as cheap
+fn load_op<T: Copy, V>(load: LoadOp<T>, dont_care: V, value: impl Fn(T) -> V) -> wgpu::LoadOp<V> {
+    match load {
+        LoadOp::Load => wgpu::LoadOp::Load,
+        LoadOp::Clear(clear) => wgpu::LoadOp::Clear(value(clear)),
+        LoadOp::DontCare => wgpu::LoadOp::Clear(dont_care),
+    }
+}
+
+/// A resolve writes `resolve_target` and drops the multisampled contents
+fn store_op(store: StoreOp) -> wgpu::StoreOp {
+    match store {
+        StoreOp::Store => wgpu::StoreOp::Store,
+        StoreOp::DontCare | StoreOp::Resolve(_) => wgpu::StoreOp::Discard,
+    }
+}
+
+fn texture_usage(usage: TextureUsage) -> wgpu::TextureUsages {
+    let mut usages = wgpu::TextureUsages::empty();
+    for (flag, wgpu_flag) in [
+        (
+            TextureUsage::COLOR_ATTACHMENT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        ),
+        (
+            TextureUsage::DEPTH_ATTACHMENT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        ),
+        (TextureUsage::SAMPLED, wgpu::TextureUsages::TEXTURE_BINDING),
+        (TextureUsage::STORAGE, wgpu::TextureUsages::STORAGE_BINDING),
+        (TextureUsage::TRANSFER_SRC, wgpu::TextureUsages::COPY_SRC),
+        (TextureUsage::TRANSFER_DST, wgpu::TextureUsages::COPY_DST),
+    ] {
+        if usage.contains(flag) {
+            usages |= wgpu_flag;
+        }
+    }
+    // wgpu rejects textures without any usage
+    if usages.is_empty() {
+        usages = wgpu::TextureUsages::TEXTURE_BINDING;
+    }
+    usages
+}
+
+fn buffer_usage(usage: BufferUsage) -> wgpu::BufferUsages {
+    match usage {
+        BufferUsage::Vertex => wgpu::BufferUsages::VERTEX,
+        BufferUsage::Index => wgpu::BufferUsages::INDEX,
+        BufferUsage::Uniform => wgpu::BufferUsages::UNIFORM,
+        BufferUsage::Storage => wgpu::BufferUsages::STORAGE,
+        BufferUsage::Indirect => wgpu::BufferUsages::INDIRECT,
+        BufferUsage::TransferSrc | BufferUsage::TransferDst => wgpu::BufferUsages::empty(),
+    }
+}
+
+fn vertex_format(format: VertexFormat) -> wgpu::VertexFormat {
+    match format {
+        VertexFormat::Float => wgpu::VertexFormat::Float32,
+        VertexFormat::Float2 => wgpu::VertexFormat::Float32x2,
+        VertexFormat::Float3 => wgpu::VertexFormat::Float32x3,
+        VertexFormat::Float4 => wgpu::VertexFormat::Float32x4,
+        VertexFormat::UInt => wgpu::VertexFormat::Uint32,
+        VertexFormat::UInt2 => wgpu::VertexFormat::Uint32x2,
+        VertexFormat::UInt3 => wgpu::VertexFormat::Uint32x3,
+        VertexFormat::UInt4 => wgpu::VertexFormat::Uint32x4,
+    }
+}
+
+fn compare_function(compare: CompareFunction) -> wgpu::CompareFunction {
+    match compare {
+        CompareFunction::Never => wgpu::CompareFunction::Never,
+        CompareFunction::Less => wgpu::CompareFunction::Less,
+        CompareFunction::Equal => wgpu::CompareFunction::Equal,
+        CompareFunction::LessEqual => wgpu::CompareFunction::LessEqual,
+        CompareFunction::Greater => wgpu::CompareFunction::Greater,
+        CompareFunction::NotEqual => wgpu::CompareFunction::NotEqual,
+        CompareFunction::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+        CompareFunction::Always => wgpu::CompareFunction::Always,
+    }
+}
+
+fn blend_state(blend: &BlendState) -> wgpu::BlendState {
+    let factor = |factor: BlendFactor| match factor {
+        BlendFactor::Zero => wgpu::BlendFactor::Zero,
+        BlendFactor::One => wgpu::BlendFactor::One,
+        BlendFactor::SrcColor => wgpu::BlendFactor::Src,
+        BlendFactor::OneMinusSrcColor => wgpu::BlendFactor::OneMinusSrc,
+        BlendFactor::DstColor => wgpu::BlendFactor::Dst,
+        BlendFactor::OneMinusDstColor => wgpu::BlendFactor::OneMinusDst,
+        BlendFactor::SrcAlpha => wgpu::BlendFactor::SrcAlpha,
+        BlendFactor::OneMinusSrcAlpha => wgpu::BlendFactor::OneMinusSrcAlpha,
+        BlendFactor::DstAlpha => wgpu::BlendFactor::DstAlpha,
+        BlendFactor::OneMinusDstAlpha => wgpu::BlendFactor::OneMinusDstAlpha,
+    };
+    let operation = |op: BlendOp| match op {
+        BlendOp::Add => wgpu::BlendOperation::Add,
+        BlendOp::Subtract => wgpu::BlendOperation::Subtract,
+        BlendOp::ReverseSubtract => wgpu::BlendOperation::ReverseSubtract,
+        BlendOp::Min => wgpu::BlendOperation::Min,
+        BlendOp::Max => wgpu::BlendOperation::Max,
+    };
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor: factor(blend.src_color),
+            dst_factor: factor(blend.dst_color),
+            operation: operation(blend.color_op),
+        },
+        alpha: wgpu::BlendComponent {
+            src_factor: factor(blend.src_alpha),
+            dst_factor: factor(blend.dst_alpha),
+            operation: operation(blend.alpha_op),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_translation() {
+        assert_eq!(
+            texture_format(TextureFormat::Rgba8Srgb),
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        );
+        assert_eq!(
+            texture_format(TextureFormat::Bgra8),
+            wgpu::TextureFormat::Bgra8Unorm
+        );
+        assert_eq!(
+            texture_format(TextureFormat::Depth24Stencil8),
+            wgpu::TextureFormat::Depth24PlusStencil8
+        );
+        assert_eq!(
+            texture_format(TextureFormat::Bc7),
+            wgpu::TextureFormat::Bc7RgbaUnorm
+        );
+        assert_eq!(
+            vertex_format(VertexFormat::Float3),
+            wgpu::VertexFormat::Float32x3
+        );
+        assert_eq!(
+            vertex_format(VertexFormat::UInt),
+            wgpu::VertexFormat::Uint32
+        );
+        assert_eq!(
+            compare_function(CompareFunction::GreaterEqual),
+            wgpu::CompareFunction::GreaterEqual
+        );
+    }
+
+    #[test]
+    fn test_power_preference() {
+        assert_eq!(
+            power_preference(AdapterPreference::HighPerformance),
+            wgpu::PowerPreference::HighPerformance
+        );
+        assert_eq!(
+            power_preference(AdapterPreference::LowPower),
+            wgpu::PowerPreference::LowPower
+        );
+        assert_eq!(
+            power_preference(AdapterPreference::default()),
+            wgpu::PowerPreference::HighPerformance
+        );
+    }
+
+    #[test]
+    fn test_usage_translation() {
+        assert_eq!(
+            texture_usage(TextureUsage::COLOR_ATTACHMENT | TextureUsage::TRANSFER_SRC),
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        );
+        assert_eq!(
+            texture_usage(TextureUsage::SAMPLED | TextureUsage::STORAGE),
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING
+        );
+        // wgpu rejects textures without any usage
+        assert_eq!(
+            texture_usage(TextureUsage::NONE),
+            wgpu::TextureUsages::TEXTURE_BINDING
+        );
+        assert_eq!(
+            buffer_usage(BufferUsage::Uniform),
+            wgpu::BufferUsages::UNIFORM
+        );
+        assert_eq!(
+            buffer_usage(BufferUsage::TransferSrc),
+            wgpu::BufferUsages::empty()
+        );
+    }
+
+    #[test]
+    fn test_pass_ops() {
+        assert_eq!(
+            load_op(LoadOp::Clear(0.5), 1.0, |depth| depth),
+            wgpu::LoadOp::Clear(0.5)
+        );
+        assert_eq!(
+            load_op(LoadOp::DontCare, 1.0, |depth| depth),
+            wgpu::LoadOp::Clear(1.0)
+        );
+        assert_eq!(
+            load_op(LoadOp::<f32>::Load, 1.0, |depth| depth),
+            wgpu::LoadOp::Load
+        );
+        assert_eq!(store_op(StoreOp::Store), wgpu::StoreOp::Store);
+        assert_eq!(
+            store_op(StoreOp::Resolve(TextureHandle(3))),
+            wgpu::StoreOp::Discard
+        );
+    }
+
+    #[test]
+    fn test_blend_state() {
+        let blend = blend_state(&BlendState::ALPHA_BLENDING);
+        assert_eq!(blend.color.src_factor, wgpu::BlendFactor::SrcAlpha);
+        assert_eq!(blend.color.dst_factor, wgpu::BlendFactor::OneMinusSrcAlpha);
+        assert_eq!(blend.color.operation, wgpu::BlendOperation::Add);
+    }
+
+    #[test]
+    fn test_layout_entries() {
+        let uniform = layout_entry(0, BindingType::UniformBuffer);
+        assert_eq!(uniform.binding, 0);
+        assert!(uniform.visibility.contains(wgpu::ShaderStages::VERTEX));
+
+        // Vertex shaders cannot write to storage
+        let storage = layout_entry(1, BindingType::StorageBuffer { read_only: false });
+        assert!(!storage.visibility.contains(wgpu::ShaderStages::VERTEX));
+        let read_only = layout_entry(1, BindingType::StorageBuffer { read_only: true });
+        assert!(read_only.visibility.contains(wgpu::ShaderStages::VERTEX));
+
+        let image = layout_entry(
+            2,
+            BindingType::StorageTexture {
+                format: TextureFormat::Rgba16f,
+                dimension: TextureDimension::D2,
+                access: StorageTextureAccess::WriteOnly,
+            },
+        );
+        assert_eq!(
+            image.ty,
+            wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format: wgpu::TextureFormat::Rgba16Float,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            }
+        );
+
+        let [texture, sampler] = texture_layout_entries(&TextureBinding {
+            set: 0,
+            binding: 4,
+            dimension: TextureDimension::D2,
+            array: true,
+            comparison: true,
+        });
+        assert_eq!(texture.binding, 4);
+        assert_eq!(
+            texture.ty,
+            wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Depth,
+                view_dimension: wgpu::TextureViewDimension::D2Array,
+                multisampled: false,
+            }
+        );
+        assert_eq!(sampler.binding, 5);
+        assert_eq!(
+            sampler.ty,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)
+        );
+    }
+
+    /// Needs a GPU or a software adapter (lavapipe, WARP)
+    #[test]
+    #[ignore]
+    fn test_headless_adapter() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Wgpu,
+            ..Default::default()
+        });
+        assert_eq!(device.adapter_info().backend, Backend::Wgpu);
+
+        let texels: Vec<u8> = (0..4 * 4 * 4).map(|i| i as u8).collect();
+        let texture = device
+            .create_texture(
+                &TextureDesc::new_2d(
+                    4,
+                    4,
+                    TextureFormat::Rgba8,
+                    TextureUsage::SAMPLED | TextureUsage::TRANSFER_SRC,
+                ),
+                Some(&texels),
+            )
+            .unwrap();
+        assert_eq!(device.read_texture(texture).unwrap(), texels);
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cameras, view frusta and picking rays
+//!
+//! A `Camera` is placed with a kernel `Vec3` position and `Quat` rotation
+//! and looks down its local -Z axis with +Y up, as `Mat4::look_at_rh`
+//! does. Its projections follow the clip space of the backends: right
+//! handed, y up and depth from 0 at the near plane to 1 at the far plane,
+//! so they differ from `Mat4::perspective_rh`, whose depth spans -1..1.
+//!
+//! Screen positions are in pixels from the top left of the viewport, the
+//! framebuffer origin of every backend.
+
+use avila_math::{Aabb, Mat4, Quat, Vec3, Vec4};
+
+/// How a camera maps view space to clip space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Vertical field of view in radians
+    Perspective { fov_y: f32 },
+    /// Height of the view volume in world units
+    Orthographic { height: f32 },
+}
+
+/// Camera with a transform and a projection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    /// Rotation from the camera's space, looking down -Z, to the world's
+    pub rotation: Quat,
+    pub projection: Projection,
+    /// Width over height of the viewport
+    pub aspect: f32,
+    /// Distances to the clipping planes, both positive
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    /// Perspective camera at the origin looking down -Z
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Self {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            projection: Projection::Perspective { fov_y },
+            aspect,
+            near,
+            far,
+        }
+    }
+
+    /// Orthographic camera at the origin looking down -Z, seeing `height`
+    /// world units vertically
+    pub fn orthographic(height: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Orthographic { height },
+            ..Self::perspective(0.0, aspect, near, far)
+        }
+    }
+
+    /// Move the camera to `position`
+    pub fn with_position(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Turn the camera toward `target`
+    pub fn looking_at(mut self, target: Vec3, up: Vec3) -> Self {
+        self.look_at(target, up);
+        self
+    }
+
+    /// Turn the camera toward `target`, keeping `up` above it
+    ///
+    /// Does nothing when `target` is the camera's position or `up` is
+    /// parallel to the view direction.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let forward = (target - self.position).normalize();
+        let right = forward.cross(up).normalize();
+        if forward == Vec3::ZERO || right == Vec3::ZERO {
+            return;
+        }
+        self.rotation = rotation_from_axes(right, right.cross(forward), -forward);
+    }
+
+    /// Match the aspect ratio of a viewport in pixels
+    pub fn set_viewport(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height.max(1) as f32;
+    }
+
+    /// Direction the camera looks in
+    pub fn forward(&self) -> Vec3 {
+        self.rotation.rotate_vec3(-Vec3::Z)
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation.rotate_vec3(Vec3::X)
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation.rotate_vec3(Vec3::Y)
+    }
+
+    /// Transform from the camera's space to the world's
+    pub fn transform(&self) -> Mat4 {
+        Mat4::from_translation(self.position) * self.rotation.to_mat4()
+    }
+
+    /// Transform from the world's space to the camera's
+    pub fn view(&self) -> Mat4 {
+        let rotation = self.rotation.normalize();
+        let inverse = Quat::from_xyzw(-rotation.x, -rotation.y, -rotation.z, rotation.w);
+        inverse.to_mat4() * Mat4::from_translation(-self.position)
+    }
+
+    /// Transform from the camera's space to clip space
+    pub fn projection_matrix(&self) -> Mat4 {
+        let depth = 1.0 / (self.near - self.far);
+        match self.projection {
+            Projection::Perspective { fov_y } => {
+                let y = 1.0 / (fov_y / 2.0).tan();
+                Mat4::from_cols(
+                    Vec4::new(y / self.aspect, 0.0, 0.0, 0.0),
+                    Vec4::new(0.0, y, 0.0, 0.0),
+                    Vec4::new(0.0, 0.0, self.far * depth, -1.0),
+                    Vec4::new(0.0, 0.0, self.near * self.far * depth, 0.0),
+                )
+            }
+            Projection::Orthographic { height } => Mat4::from_cols(
+                Vec4::new(2.0 / (height * self.aspect), 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, depth, 0.0),
+                Vec4::new(0.0, 0.0, self.near * depth, 1.0),
+            ),
+        }
+    }
+
+    /// Transform from the world's space to clip space
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view()
+    }
+
+    /// Volume the camera sees, for culling
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&self.view_projection())
+    }
+
+    /// World-space corners of the part of the view between the distances
+    /// `near` and `far`: the near face, then the far face, each from the
+    /// bottom left counterclockwise
+    pub fn frustum_corners(&self, near: f32, far: f32) -> [Vec3; 8] {
+        let half_height = |distance: f32| match self.projection {
+            Projection::Perspective { fov_y } => (fov_y / 2.0).tan() * distance,
+            Projection::Orthographic { height } => height / 2.0,
+        };
+        let mut corners = [Vec3::ZERO; 8];
+        for (face, distance) in [near, far].into_iter().enumerate() {
+            let y = half_height(distance);
+            let x = y * self.aspect;
+            let face_corners = [(-x, -y), (x, -y), (x, y), (-x, y)];
+            for (corner, (cx, cy)) in face_corners.into_iter().enumerate() {
+                corners[face * 4 + corner] =
+                    self.position + self.rotation.rotate_vec3(Vec3::new(cx, cy, -distance));
+            }
+        }
+        corners
+    }
+
+    /// Ray through the pixel at `screen` of a `width` x `height` viewport,
+    /// starting on the near plane
+    pub fn screen_to_ray(&self, screen: [f32; 2], width: u32, height: u32) -> Ray {
+        let x = screen[0] / width.max(1) as f32 * 2.0 - 1.0;
+        let y = 1.0 - screen[1] / height.max(1) as f32 * 2.0;
+        let (origin, direction) = match self.projection {
+            Projection::Perspective { fov_y } => {
+                let half = (fov_y / 2.0).tan();
+                let direction = Vec3::new(x * half * self.aspect, y * half, -1.0);
+                (direction * self.near, direction)
+            }
+            Projection::Orthographic { height } => {
+                let half = height / 2.0;
+                let origin = Vec3::new(x * half * self.aspect, y * half, -self.near);
+                (origin, -Vec3::Z)
+            }
+        };
+        Ray::new(
+            self.position + self.rotation.rotate_vec3(origin),
+            self.rotation.rotate_vec3(direction),
+        )
+    }
+
+    /// Pixel of a `width` x `height` viewport that `point` projects to,
+    /// with its depth in `z`; `None` behind the camera
+    pub fn world_to_screen(&self, point: Vec3, width: u32, height: u32) -> Option<Vec3> {
+        let clip = self.view_projection() * Vec4::new(point.x, point.y, point.z, 1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some(Vec3::new(
+            (clip.x / clip.w + 1.0) / 2.0 * width as f32,
+            (1.0 - clip.y / clip.w) / 2.0 * height as f32,
+            clip.z / clip.w,
+        ))
+    }
+}
+
+/// Half-line from `origin` along `direction`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Unit length
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Ray along `direction`, normalized
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Point `distance` along the ray
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance to where the ray enters `aabb`; 0 when it starts inside
+    pub fn intersect_aabb(&self, aabb: Aabb) -> Option<f32> {
+        aabb.intersect_ray(self.origin, self.direction)
+            .map(|(enter, _)| enter)
+    }
+
+    /// Distance to where the ray crosses `plane`, from either side
+    pub fn intersect_plane(&self, plane: Plane) -> Option<f32> {
+        let facing = plane.normal.dot(self.direction);
+        if facing.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = -plane.signed_distance(self.origin) / facing;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+/// Plane of the points `p` with `normal.dot(p) + distance == 0`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// Unit length, toward the positive side
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Plane through `point` facing `normal`
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// Distance of `point` from the plane, negative behind it
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+
+    /// Plane of `a * x + b * y + c * z + d >= 0`, normalized
+    fn from_coefficients(coefficients: Vec4) -> Self {
+        let normal = Vec3::new(coefficients.x, coefficients.y, coefficients.z);
+        let length = normal.length();
+        Self {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+}
+
+/// Convex volume bounded by six planes facing inward
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Frustum of the points a view-projection matrix maps inside clip
+    /// space, with depth 0..1
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let c = view_projection.cols;
+        let row = |i: usize| {
+            let component = |v: Vec4| [v.x, v.y, v.z, v.w][i];
+            Vec4::new(
+                component(c[0]),
+                component(c[1]),
+                component(c[2]),
+                component(c[3]),
+            )
+        };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, z, w - z].map(Plane::from_coefficients),
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Whether the sphere may be inside; spheres near the corners can
+    /// pass without being
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(center) >= -radius)
+    }
+
+    /// Whether the box may be inside; boxes near the corners can pass
+    /// without being
+    pub fn intersects_aabb(&self, aabb: Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Corner furthest along the normal
+            let corner = Vec3::new(
+                if plane.normal.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.normal.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.normal.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
+
+/// Rotation whose local X, Y and Z axes are the given orthonormal axes
+fn rotation_from_axes(x: Vec3, y: Vec3, z: Vec3) -> Quat {
+    let trace = x.x + y.y + z.z;
+    let quat = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Quat::from_xyzw((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s, s / 4.0)
+    } else if x.x > y.y && x.x > z.z {
+        let s = (1.0 + x.x - y.y - z.z).sqrt() * 2.0;
+        Quat::from_xyzw(s / 4.0, (y.x + x.y) / s, (z.x + x.z) / s, (y.z - z.y) / s)
+    } else if y.y > z.z {
+        let s = (1.0 + y.y - x.x - z.z).sqrt() * 2.0;
+        Quat::from_xyzw((y.x + x.y) / s, s / 4.0, (z.y + y.z) / s, (z.x - x.z) / s)
+    } else {
+        let s = (1.0 + z.z - x.x - y.y).sqrt() * 2.0;
+        Quat::from_xyzw((z.x + x.z) / s, (z.y + y.z) / s, s / 4.0, (x.y - y.x) / s)
+    };
+    quat.normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn test_look_at_matches_math() {
+        let eye = Vec3::new(3.0, 2.0, 5.0);
+        let target = Vec3::new(-1.0, 0.5, 0.0);
+        let camera = Camera::perspective(1.0, 1.5, 0.1, 100.0)
+            .with_position(eye)
+            .looking_at(target, Vec3::Y);
+        assert!(close(camera.forward(), (target - eye).normalize()));
+        let expected = Mat4::look_at_rh(eye, target, Vec3::Y);
+        for point in [Vec3::ZERO, Vec3::new(1.0, -2.0, 3.0), target] {
+            assert!(close(
+                camera.view().transform_point3(point),
+                expected.transform_point3(point)
+            ));
+            assert!(close(
+                camera
+                    .transform()
+                    .transform_point3(camera.view().transform_point3(point)),
+                point
+            ));
+        }
+    }
+
+    #[test]
+    fn test_projection_depth_range() {
+        for camera in [
+            Camera::perspective(FRAC_PI_2, 2.0, 0.5, 50.0),
+            Camera::orthographic(4.0, 2.0, 0.5, 50.0),
+        ] {
+            let projection = camera.projection_matrix();
+            let near = projection.transform_point3(Vec3::new(0.0, 0.0, -0.5));
+            let far = projection.transform_point3(Vec3::new(0.0, 0.0, -50.0));
+            assert!((near.z - 0.0).abs() < 1e-5, "{:?}", camera.projection);
+            assert!((far.z - 1.0).abs() < 1e-5, "{:?}", camera.projection);
+        }
+        // 90 degrees: the top of the view is as high as it is far
+        let camera = Camera::perspective(FRAC_PI_2, 2.0, 0.5, 50.0);
+        let top = camera
+            .projection_matrix()
+            .transform_point3(Vec3::new(2.0, 1.0, -1.0));
+        assert!(close(
+            Vec3::new(top.x, top.y, 0.0),
+            Vec3::new(1.0, 1.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn test_frustum_culling() {
+        let camera = Camera::perspective(FRAC_PI_2, 1.0, 1.0, 10.0);
+        let frustum = camera.frustum();
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 5.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -11.0)));
+        assert!(!frustum.contains_point(Vec3::new(6.0, 0.0, -5.0)));
+        assert!(frustum.intersects_sphere(Vec3::new(6.0, 0.0, -5.0), 1.0));
+        assert!(!frustum.intersects_sphere(Vec3::new(0.0, 0.0, 2.0), 1.0));
+        assert!(frustum.intersects_aabb(Aabb::new(
+            Vec3::new(4.0, -1.0, -5.0),
+            Vec3::new(8.0, 1.0, -4.0)
+        )));
+        assert!(!frustum.intersects_aabb(Aabb::new(
+            Vec3::new(6.0, -1.0, -5.0),
+            Vec3::new(8.0, 1.0, -4.0)
+        )));
+    }
+
+    #[test]
+    fn test_screen_rays_round_trip() {
+        for camera in [
+            Camera::perspective(1.2, 16.0 / 9.0, 0.1, 100.0),
+            Camera::orthographic(10.0, 16.0 / 9.0, 0.1, 100.0),
+        ] {
+            let camera = camera
+                .with_position(Vec3::new(1.0, 4.0, 8.0))
+                .looking_at(Vec3::ZERO, Vec3::Y);
+            let center = camera.screen_to_ray([960.0, 540.0], 1920, 1080);
+            assert!(close(center.direction, camera.forward()));
+
+            let pixel = [300.0, 200.0];
+            let ray = camera.screen_to_ray(pixel, 1920, 1080);
+            let screen = camera.world_to_screen(ray.at(7.0), 1920, 1080).unwrap();
+            assert!(
+                (screen.x - pixel[0]).abs() < 0.01,
+                "{:?}",
+                camera.projection
+            );
+            assert!(
+                (screen.y - pixel[1]).abs() < 0.01,
+                "{:?}",
+                camera.projection
+            );
+            let start = camera.world_to_screen(ray.origin, 1920, 1080).unwrap();
+            assert!(start.z.abs() < 1e-4, "{:?}", camera.projection);
+        }
+
+        let camera =
+            Camera::perspective(1.0, 1.0, 0.1, 100.0).with_position(Vec3::new(0.0, 0.0, 5.0));
+        assert!(camera
+            .world_to_screen(Vec3::new(0.0, 0.0, 10.0), 8, 8)
+            .is_none());
+        let ray = camera.screen_to_ray([4.0, 4.0], 8, 8);
+        let unit = Aabb::new(-Vec3::ONE, Vec3::ONE);
+        assert!((ray.at(ray.intersect_aabb(unit).unwrap()).z - 1.0).abs() < 1e-4);
+        let ground = Plane::from_point_normal(Vec3::new(0.0, 0.0, -2.0), Vec3::Z);
+        assert!((ray.intersect_plane(ground).unwrap() - 6.9).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,1139 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! egui integration (feature `egui`)
+//!
+//! `EguiInput` is the window half: it turns kernel window events into the
+//! `egui::RawInput` of the next frame and applies egui's cursor and
+//! clipboard output to the window. `EguiRenderer` is the renderer half: it
+//! uploads the textures egui asks for (the font atlas first) and records
+//! the tessellated meshes into a `CommandList`, inside a render pass the
+//! caller has begun.
+//!
+//! Vertices are converted to clip space on the CPU and keep egui's colors:
+//! premultiplied alpha in gamma space. On an sRGB target the fragment
+//! shader converts them to linear so the hardware encoding gives egui's
+//! colors back; the software backend runs no shaders and skips that. The
+//! vertex layout (position, color and texture coordinate at locations 0 to
+//! 2) is the one the software backend draws, so the UI renders there too.
+//!
+//! The window module reports keys but no composed text, so typed text comes
+//! from `Key::Character` where a platform sends it and otherwise from the
+//! key codes read with a US layout. The clipboard lives inside `EguiInput`:
+//! egui's copies land there and Ctrl+V (Cmd+V on macOS) pastes them back.
+
+use crate::gfx::api::{
+    AddressMode, Backend, BlendFactor, BlendOp, BlendState, BufferDesc, BufferHandle, CommandList,
+    CullMode, DepthStencilState, FilterMode, GfxError, GpuDevice, IndexType, PipelineDesc,
+    PipelineHandle, PipelineLayoutDesc, PrimitiveTopology, RasterizerState, Rect, SamplerDesc,
+    SamplerHandle, ShaderDesc, ShaderHandle, ShaderStage, TextureBinding, TextureDesc,
+    TextureFormat, TextureHandle, TextureRegion, TextureUsage, VertexAttribute, VertexFormat,
+    VertexLayout, Viewport,
+};
+use crate::gfx::backend::BackendDevice;
+use avila_math::window::{
+    CursorIcon, Event, Key, KeyCode, KeyEvent, ModifierKeys, MouseButton, MouseEvent, TouchEvent,
+    TouchPhase, Window, WindowEvent,
+};
+use egui::epaint::{ImageData, ImageDelta, Primitive};
+use egui::{
+    ClippedPrimitive, OutputCommand, PlatformOutput, Pos2, RawInput, TextureFilter, TextureId,
+    TextureOptions, TextureWrapMode, TexturesDelta, Vec2, ViewportId,
+};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Bytes per UI vertex: clip position, color and texture coordinate
+const VERTEX_STRIDE: usize = 32;
+
+const VERTEX_SHADER: &str = "#version 450
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 uv;
+layout(location = 0) out vec4 v_color;
+layout(location = 1) out vec2 v_uv;
+
+void main() {
+    v_color = color;
+    v_uv = uv;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+";
+
+/// Fragment shader with the texture declared for OpenGL or for wgpu, and
+/// the output converted to linear for sRGB targets
+fn fragment_shader(backend: Backend, linear_output: bool) -> String {
+    let (declaration, sample) = if backend == Backend::OpenGl {
+        (
+            "layout(binding = 0) uniform sampler2D ui_texture;",
+            "texture(ui_texture, v_uv)",
+        )
+    } else {
+        (
+            "layout(set = 0, binding = 0) uniform texture2D ui_texture;
+layout(set = 0, binding = 1) uniform sampler ui_sampler;",
+            "texture(sampler2D(ui_texture, ui_sampler), v_uv)",
+        )
+    };
+    format!(
+        "#version 450
+layout(location = 0) in vec4 v_color;
+layout(location = 1) in vec2 v_uv;
+layout(location = 0) out vec4 out_color;
+{declaration}
+
+const bool LINEAR_OUTPUT = {linear_output};
+
+vec3 linear_from_gamma(vec3 c) {{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), c));
+}}
+
+void main() {{
+    vec4 color = v_color * {sample};
+    if (LINEAR_OUTPUT) {{
+        color.rgb = linear_from_gamma(color.rgb);
+    }}
+    out_color = color;
+}}
+"
+    )
+}
+
+/// A texture egui draws with
+struct UiTexture {
+    texture: TextureHandle,
+    sampler: SamplerHandle,
+    /// Whether the renderer created it (egui's textures) rather than the
+    /// application (`register_texture`)
+    owned: bool,
+}
+
+/// Draws egui's output through a `CommandList`
+pub struct EguiRenderer {
+    vertex_shader: ShaderHandle,
+    fragment_shader: ShaderHandle,
+    pipeline: PipelineHandle,
+    vertex_buffer: BufferHandle,
+    vertex_capacity: usize,
+    index_buffer: BufferHandle,
+    index_capacity: usize,
+    textures: HashMap<TextureId, UiTexture>,
+    samplers: Vec<(TextureOptions, SamplerHandle)>,
+    /// Textures egui freed with the last delta, destroyed on the next one
+    pending_free: Vec<TextureId>,
+    /// Textures egui replaced, destroyed on the next delta
+    retired: Vec<TextureHandle>,
+    next_user_texture: u64,
+}
+
+impl EguiRenderer {
+    /// Renderer drawing to color attachments of `color_format` with a
+    /// single sample
+    pub fn new(device: &mut BackendDevice, color_format: TextureFormat) -> Result<Self, GfxError> {
+        let backend = device.adapter_info().backend;
+        let linear_output = color_format == TextureFormat::Rgba8Srgb;
+
+        let vertex_shader = device.create_shader(&ShaderDesc {
+            stage: ShaderStage::Vertex,
+            entry_point: "main".to_string(),
+            code: VERTEX_SHADER.as_bytes().to_vec(),
+        })?;
+        let fragment_shader = match device.create_shader(&ShaderDesc {
+            stage: ShaderStage::Fragment,
+            entry_point: "main".to_string(),
+            code: fragment_shader(backend, linear_output).into_bytes(),
+        }) {
+            Ok(shader) => shader,
+            Err(err) => {
+                device.destroy_shader(vertex_shader);
+                return Err(err);
+            }
+        };
+
+        let attribute = |location, format, offset| VertexAttribute {
+            format,
+            offset,
+            location,
+        };
+        let pipeline = device.create_pipeline(&PipelineDesc {
+            vertex_shader,
+            fragment_shader,
+            vertex_layout: VertexLayout {
+                stride: VERTEX_STRIDE as u32,
+                attributes: vec![
+                    attribute(0, VertexFormat::Float2, 0),
+                    attribute(1, VertexFormat::Float4, 8),
+                    attribute(2, VertexFormat::Float2, 24),
+                ],
+                buffers: Vec::new(),
+            },
+            topology: PrimitiveTopology::TriangleList,
+            rasterizer: RasterizerState {
+                cull_mode: CullMode::None,
+                ..Default::default()
+            },
+            depth_stencil: DepthStencilState {
+                depth_test_enabled: false,
+                depth_write_enabled: false,
+                ..Default::default()
+            },
+            // Premultiplied alpha
+            blend_states: vec![BlendState {
+                enabled: true,
+                src_color: BlendFactor::One,
+                dst_color: BlendFactor::OneMinusSrcAlpha,
+                color_op: BlendOp::Add,
+                src_alpha: BlendFactor::OneMinusDstAlpha,
+                dst_alpha: BlendFactor::One,
+                alpha_op: BlendOp::Add,
+            }],
+            color_formats: vec![color_format],
+            depth_format: None,
+            sample_count: 1,
+            layout: PipelineLayoutDesc {
+                bind_groups: Vec::new(),
+                textures: vec![TextureBinding::new_2d(0, 0)],
+            },
+        });
+        let pipeline = match pipeline {
+            Ok(pipeline) => pipeline,
+            Err(err) => {
+                device.destroy_shader(vertex_shader);
+                device.destroy_shader(fragment_shader);
+                return Err(err);
+            }
+        };
+
+        Ok(Self {
+            vertex_shader,
+            fragment_shader,
+            pipeline,
+            vertex_buffer: BufferHandle::INVALID,
+            vertex_capacity: 0,
+            index_buffer: BufferHandle::INVALID,
+            index_capacity: 0,
+            textures: HashMap::new(),
+            samplers: Vec::new(),
+            pending_free: Vec::new(),
+            retired: Vec::new(),
+            next_user_texture: 0,
+        })
+    }
+
+    /// Apply egui's texture changes of a frame, before rendering it
+    ///
+    /// Takes `FullOutput::textures_delta`, which egui expects to be
+    /// consumed. Textures the previous delta freed, and those replaced
+    /// since, are destroyed now: the frame that last drew them has been
+    /// recorded.
+    pub fn update_textures(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        mut delta: TexturesDelta,
+    ) -> Result<(), GfxError> {
+        for id in self.pending_free.drain(..) {
+            if let Some(texture) = self.textures.remove(&id) {
+                if texture.owned {
+                    device.destroy_texture(texture.texture);
+                }
+            }
+        }
+        for texture in self.retired.drain(..) {
+            device.destroy_texture(texture);
+        }
+
+        let set = std::mem::take(&mut delta.set);
+        self.pending_free.extend(delta.free.drain());
+        for (id, image_deltas) in set {
+            for image_delta in &image_deltas {
+                self.set_texture(device, id, image_delta)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_texture(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        id: TextureId,
+        delta: &ImageDelta,
+    ) -> Result<(), GfxError> {
+        let ImageData::Color(image) = &delta.image;
+        let [width, height] = image.size;
+        let pixels: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+        let sampler = self.sampler(device, delta.options)?;
+
+        match (delta.pos, self.textures.get_mut(&id)) {
+            (Some([x, y]), Some(texture)) => {
+                let region = TextureRegion::new_2d(x as u32, y as u32, width as u32, height as u32);
+                device.update_texture(texture.texture, 0, 0, region, &pixels)?;
+                texture.sampler = sampler;
+            }
+            (Some(_), None) => {
+                return Err(GfxError::InvalidDesc(format!(
+                    "egui updated texture {:?} before creating it",
+                    id
+                )));
+            }
+            (None, _) => {
+                let desc = TextureDesc::new_2d(
+                    width as u32,
+                    height as u32,
+                    TextureFormat::Rgba8,
+                    TextureUsage::SAMPLED | TextureUsage::TRANSFER_DST,
+                );
+                let texture = device.create_texture(&desc, Some(&pixels))?;
+                let old = self.textures.insert(
+                    id,
+                    UiTexture {
+                        texture,
+                        sampler,
+                        owned: true,
+                    },
+                );
+                if let Some(old) = old.filter(|old| old.owned) {
+                    self.retired.push(old.texture);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sampler for egui's texture options, created on first use
+    fn sampler(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        options: TextureOptions,
+    ) -> Result<SamplerHandle, GfxError> {
+        if let Some(&(_, sampler)) = self.samplers.iter().find(|(o, _)| *o == options) {
+            return Ok(sampler);
+        }
+        let filter = |filter: TextureFilter| match filter {
+            TextureFilter::Nearest => FilterMode::Nearest,
+            TextureFilter::Linear => FilterMode::Linear,
+        };
+        let address = match options.wrap_mode {
+            TextureWrapMode::ClampToEdge => AddressMode::ClampToEdge,
+            TextureWrapMode::Repeat => AddressMode::Repeat,
+            TextureWrapMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        };
+        let sampler = device.create_sampler(&SamplerDesc {
+            mag_filter: filter(options.magnification),
+            min_filter: filter(options.minification),
+            mip_filter: filter(options.mipmap_mode.unwrap_or(TextureFilter::Nearest)),
+            ..SamplerDesc::linear(address)
+        })?;
+        self.samplers.push((options, sampler));
+        Ok(sampler)
+    }
+
+    /// Let egui draw an application texture, as `egui::Image` or
+    /// `Painter::image` with the returned id
+    ///
+    /// The texture needs `TextureUsage::SAMPLED` and stays owned by the
+    /// caller; colors are read as premultiplied alpha.
+    pub fn register_texture(
+        &mut self,
+        texture: TextureHandle,
+        sampler: SamplerHandle,
+    ) -> TextureId {
+        let id = TextureId::User(self.next_user_texture);
+        self.next_user_texture += 1;
+        self.textures.insert(
+            id,
+            UiTexture {
+                texture,
+                sampler,
+                owned: false,
+            },
+        );
+        id
+    }
+
+    /// Point a registered texture id at another texture (a resized render
+    /// target, say)
+    pub fn update_user_texture(
+        &mut self,
+        id: TextureId,
+        texture: TextureHandle,
+        sampler: SamplerHandle,
+    ) {
+        if let Some(entry) = self.textures.get_mut(&id).filter(|entry| !entry.owned) {
+            entry.texture = texture;
+            entry.sampler = sampler;
+        }
+    }
+
+    /// Forget a texture given to `register_texture`; it is not destroyed
+    pub fn unregister_texture(&mut self, id: TextureId) {
+        if self.textures.get(&id).is_some_and(|texture| !texture.owned) {
+            self.textures.remove(&id);
+        }
+    }
+
+    /// Record the draws of egui's tessellated output into `cmd`
+    ///
+    /// Call it inside a render pass on a `width` x `height` pixel target of
+    /// the renderer's color format. Viewport and scissor are left set to
+    /// the UI's; paint callbacks and meshes with unknown textures are
+    /// skipped.
+    pub fn render(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        cmd: &mut CommandList,
+        primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), GfxError> {
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let to_clip = Vec2::new(
+            2.0 * pixels_per_point / width as f32,
+            -2.0 * pixels_per_point / height as f32,
+        );
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut draws = Vec::new();
+        for clipped in primitives {
+            let Primitive::Mesh(mesh) = &clipped.primitive else {
+                continue;
+            };
+            let Some(texture) = self.textures.get(&mesh.texture_id) else {
+                continue;
+            };
+            let Some(scissor) = scissor(clipped.clip_rect, pixels_per_point, width, height) else {
+                continue;
+            };
+            if mesh.indices.is_empty() {
+                continue;
+            }
+
+            let first_index = indices.len() as u32;
+            let vertex_offset = (vertices.len() / VERTEX_STRIDE) as i32;
+            for vertex in &mesh.vertices {
+                let color = vertex.color.to_array().map(|c| c as f32 / 255.0);
+                let values = [
+                    vertex.pos.x * to_clip.x - 1.0,
+                    vertex.pos.y * to_clip.y + 1.0,
+                    color[0],
+                    color[1],
+                    color[2],
+                    color[3],
+                    vertex.uv.x,
+                    vertex.uv.y,
+                ];
+                vertices.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+            }
+            indices.extend(mesh.indices.iter().flat_map(|i| i.to_le_bytes()));
+            draws.push((
+                scissor,
+                texture.texture,
+                texture.sampler,
+                first_index,
+                mesh.indices.len() as u32,
+                vertex_offset,
+            ));
+        }
+        if draws.is_empty() {
+            return Ok(());
+        }
+
+        Self::upload(
+            device,
+            &mut self.vertex_buffer,
+            &mut self.vertex_capacity,
+            BufferDesc::vertex,
+            &vertices,
+        )?;
+        Self::upload(
+            device,
+            &mut self.index_buffer,
+            &mut self.index_capacity,
+            BufferDesc::index,
+            &indices,
+        )?;
+
+        cmd.bind_pipeline(self.pipeline);
+        cmd.set_viewport(Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        });
+        cmd.bind_vertex_buffer(0, self.vertex_buffer, 0);
+        cmd.bind_index_buffer(self.index_buffer, 0, IndexType::UInt32);
+        for (scissor, texture, sampler, first_index, index_count, vertex_offset) in draws {
+            cmd.set_scissor(scissor);
+            cmd.bind_texture(0, 0, texture, sampler);
+            cmd.draw_indexed(index_count, 1, first_index, vertex_offset, 0);
+        }
+        Ok(())
+    }
+
+    /// Write `data` to a buffer, replacing it with a larger one when it
+    /// does not fit
+    fn upload(
+        device: &mut dyn GpuDevice,
+        buffer: &mut BufferHandle,
+        capacity: &mut usize,
+        desc: fn(usize) -> BufferDesc,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        if data.len() > *capacity {
+            let size = data.len().next_power_of_two().max(64 * 1024);
+            let new_buffer = device.create_buffer(&desc(size), None)?;
+            if *buffer != BufferHandle::INVALID {
+                device.destroy_buffer(*buffer);
+            }
+            *buffer = new_buffer;
+            *capacity = size;
+        }
+        device.update_buffer(*buffer, 0, data);
+        Ok(())
+    }
+
+    /// Destroy the pipeline, buffers, samplers and egui's textures
+    ///
+    /// Textures given to `register_texture` are left to their owner.
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        for texture in self.textures.into_values().filter(|t| t.owned) {
+            device.destroy_texture(texture.texture);
+        }
+        for texture in self.retired {
+            device.destroy_texture(texture);
+        }
+        for (_, sampler) in self.samplers {
+            device.destroy_sampler(sampler);
+        }
+        for buffer in [self.vertex_buffer, self.index_buffer] {
+            if buffer != BufferHandle::INVALID {
+                device.destroy_buffer(buffer);
+            }
+        }
+        device.destroy_pipeline(self.pipeline);
+        device.destroy_shader(self.vertex_shader);
+        device.destroy_shader(self.fragment_shader);
+    }
+}
+
+/// Clip rectangle in points as a scissor in pixels, `None` when empty
+fn scissor(clip: egui::Rect, pixels_per_point: f32, width: u32, height: u32) -> Option<Rect> {
+    let x0 = (clip.min.x * pixels_per_point)
+        .round()
+        .clamp(0.0, width as f32) as u32;
+    let y0 = (clip.min.y * pixels_per_point)
+        .round()
+        .clamp(0.0, height as f32) as u32;
+    let x1 = (clip.max.x * pixels_per_point)
+        .round()
+        .clamp(0.0, width as f32) as u32;
+    let y1 = (clip.max.y * pixels_per_point)
+        .round()
+        .clamp(0.0, height as f32) as u32;
+    (x1 > x0 && y1 > y0).then(|| Rect {
+        x: x0 as i32,
+        y: y0 as i32,
+        width: x1 - x0,
+        height: y1 - y0,
+    })
+}
+
+/// Feeds window events to egui and egui's platform output to the window
+pub struct EguiInput {
+    start: Instant,
+    events: Vec<egui::Event>,
+    /// Logical size of the window's client area
+    screen_size: Vec2,
+    pixels_per_point: f32,
+    focused: bool,
+    modifiers: egui::Modifiers,
+    pointer: Pos2,
+    /// Touch that also drives the pointer, for widgets that only know the
+    /// mouse
+    pointer_touch: Option<u64>,
+    clipboard: String,
+    cursor_icon: Option<egui::CursorIcon>,
+}
+
+impl EguiInput {
+    pub fn new(window: &Window) -> Self {
+        let size = window.size();
+        Self {
+            start: Instant::now(),
+            events: Vec::new(),
+            screen_size: Vec2::new(size.width as f32, size.height as f32),
+            pixels_per_point: window.scale_factor(),
+            focused: window.is_focused(),
+            modifiers: egui::Modifiers::default(),
+            pointer: Pos2::ZERO,
+            pointer_touch: None,
+            clipboard: String::new(),
+            cursor_icon: None,
+        }
+    }
+
+    /// Translate a window event for the next `take_input`
+    ///
+    /// Whether egui wants the event, rather than the game, is for the
+    /// `egui::Context` to say: `wants_pointer_input`, `wants_keyboard_input`.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Window(event) => self.handle_window_event(event),
+            Event::Keyboard(event) => self.handle_key(event),
+            Event::Mouse(event) => self.handle_mouse(event),
+            Event::Touch(event) => self.handle_touch(event),
+            Event::Gamepad(_) | Event::FrameTick(_) => {}
+        }
+    }
+
+    fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => {
+                self.screen_size = Vec2::new(size.width as f32, size.height as f32);
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.pixels_per_point = *scale_factor;
+            }
+            WindowEvent::Focused | WindowEvent::Unfocused => {
+                self.focused = *event == WindowEvent::Focused;
+                self.events.push(egui::Event::WindowFocused(self.focused));
+            }
+            WindowEvent::CursorLeft => self.events.push(egui::Event::PointerGone),
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent) {
+        self.modifiers = modifiers(event.modifiers);
+        let pressed = event.is_pressed();
+        let code = match event.key {
+            Key::Character(c) => {
+                if pressed && !c.is_control() && !self.modifiers.command {
+                    self.events.push(egui::Event::Text(c.to_string()));
+                }
+                return;
+            }
+            Key::Code(code) => code,
+        };
+
+        if let Some(key) = egui_key(code) {
+            self.events.push(egui::Event::Key {
+                key,
+                physical_key: Some(key),
+                pressed,
+                repeat: event.repeat,
+                modifiers: self.modifiers,
+            });
+        }
+        if !pressed {
+            return;
+        }
+        if self.modifiers.command {
+            match code {
+                KeyCode::C => self.events.push(egui::Event::Copy),
+                KeyCode::X => self.events.push(egui::Event::Cut),
+                KeyCode::V if !self.clipboard.is_empty() => {
+                    self.events.push(egui::Event::Paste(self.clipboard.clone()));
+                }
+                _ => {}
+            }
+        } else if !self.modifiers.ctrl && !self.modifiers.alt && !self.modifiers.mac_cmd {
+            if let Some(c) = us_layout_char(code, self.modifiers.shift) {
+                self.events.push(egui::Event::Text(c.to_string()));
+            }
+        }
+    }
+
+    fn handle_mouse(&mut self, event: &MouseEvent) {
+        match *event {
+            MouseEvent::ButtonPressed {
+                button,
+                position,
+                modifiers: keys,
+            }
+            | MouseEvent::ButtonReleased {
+                button,
+                position,
+                modifiers: keys,
+            } => {
+                self.modifiers = modifiers(keys);
+                let Some(button) = pointer_button(button) else {
+                    return;
+                };
+                self.pointer = pos(position);
+                self.events.push(egui::Event::PointerButton {
+                    pos: self.pointer,
+                    button,
+                    pressed: matches!(event, MouseEvent::ButtonPressed { .. }),
+                    modifiers: self.modifiers,
+                });
+            }
+            MouseEvent::CursorMoved { position, .. } => {
+                self.pointer = pos(position);
+                self.events.push(egui::Event::PointerMoved(self.pointer));
+            }
+            MouseEvent::Scrolled { delta, .. } => {
+                self.events.push(egui::Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Line,
+                    delta: Vec2::new(delta.0 as f32, delta.1 as f32),
+                    phase: egui::TouchPhase::Move,
+                    modifiers: self.modifiers,
+                });
+            }
+            MouseEvent::RawMotion { .. } => {}
+        }
+    }
+
+    fn handle_touch(&mut self, event: &TouchEvent) {
+        let position = pos(event.position);
+        let phase = match event.phase {
+            TouchPhase::Started => egui::TouchPhase::Start,
+            TouchPhase::Moved => egui::TouchPhase::Move,
+            TouchPhase::Ended => egui::TouchPhase::End,
+            TouchPhase::Cancelled => egui::TouchPhase::Cancel,
+        };
+        self.events.push(egui::Event::Touch {
+            device_id: egui::TouchDeviceId(0),
+            id: egui::TouchId(event.id),
+            phase,
+            pos: position,
+            force: Some(event.pressure),
+        });
+
+        // The first finger down also moves and clicks the pointer
+        if event.phase == TouchPhase::Started && self.pointer_touch.is_none() {
+            self.pointer_touch = Some(event.id);
+        }
+        if self.pointer_touch != Some(event.id) {
+            return;
+        }
+        self.pointer = position;
+        self.events.push(egui::Event::PointerMoved(position));
+        match event.phase {
+            TouchPhase::Started | TouchPhase::Ended => {
+                self.events.push(egui::Event::PointerButton {
+                    pos: position,
+                    button: egui::PointerButton::Primary,
+                    pressed: event.phase == TouchPhase::Started,
+                    modifiers: self.modifiers,
+                });
+            }
+            TouchPhase::Moved | TouchPhase::Cancelled => {}
+        }
+        if event.is_finished() {
+            self.pointer_touch = None;
+            self.events.push(egui::Event::PointerGone);
+        }
+    }
+
+    /// Input for the next `egui::Context::run`, with the events since the
+    /// last call
+    pub fn take_input(&mut self) -> RawInput {
+        let mut input = RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, self.screen_size)),
+            time: Some(self.start.elapsed().as_secs_f64()),
+            events: std::mem::take(&mut self.events),
+            focused: self.focused,
+            ..Default::default()
+        };
+        input
+            .viewports
+            .entry(ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        input
+    }
+
+    /// Apply egui's cursor icon to the window and keep the text it copied
+    pub fn handle_output(&mut self, window: &mut Window, output: &PlatformOutput) {
+        for command in &output.commands {
+            if let OutputCommand::CopyText(text) = command {
+                self.clipboard = text.clone();
+            }
+        }
+        if self.cursor_icon == Some(output.cursor_icon) {
+            return;
+        }
+        self.cursor_icon = Some(output.cursor_icon);
+        match cursor_icon(output.cursor_icon) {
+            Some(icon) => {
+                window.show_cursor();
+                window.set_cursor_icon(icon);
+            }
+            None => window.hide_cursor(),
+        }
+    }
+
+    /// Text egui copied last, or `set_clipboard` put there
+    pub fn clipboard(&self) -> &str {
+        &self.clipboard
+    }
+
+    /// Text the next Ctrl+V pastes
+    pub fn set_clipboard(&mut self, text: impl Into<String>) {
+        self.clipboard = text.into();
+    }
+}
+
+fn pos(position: (f64, f64)) -> Pos2 {
+    Pos2::new(position.0 as f32, position.1 as f32)
+}
+
+fn modifiers(keys: ModifierKeys) -> egui::Modifiers {
+    let mac = cfg!(target_os = "macos");
+    egui::Modifiers {
+        alt: keys.has_alt(),
+        ctrl: keys.has_ctrl(),
+        shift: keys.has_shift(),
+        mac_cmd: mac && keys.has_meta(),
+        command: if mac {
+            keys.has_meta()
+        } else {
+            keys.has_ctrl()
+        },
+    }
+}
+
+fn pointer_button(button: MouseButton) -> Option<egui::PointerButton> {
+    match button {
+        MouseButton::Left => Some(egui::PointerButton::Primary),
+        MouseButton::Right => Some(egui::PointerButton::Secondary),
+        MouseButton::Middle => Some(egui::PointerButton::Middle),
+        MouseButton::Back => Some(egui::PointerButton::Extra1),
+        MouseButton::Forward => Some(egui::PointerButton::Extra2),
+        MouseButton::Other(_) => None,
+    }
+}
+
+/// System cursor for egui's, `None` to hide it
+fn cursor_icon(icon: egui::CursorIcon) -> Option<CursorIcon> {
+    use egui::CursorIcon as Egui;
+    Some(match icon {
+        Egui::None => return None,
+        Egui::PointingHand => CursorIcon::Pointer,
+        Egui::Text | Egui::VerticalText => CursorIcon::Text,
+        Egui::Crosshair | Egui::Cell => CursorIcon::Crosshair,
+        Egui::Wait => CursorIcon::Wait,
+        Egui::Progress => CursorIcon::Progress,
+        Egui::Help => CursorIcon::Help,
+        Egui::NotAllowed | Egui::NoDrop => CursorIcon::NotAllowed,
+        Egui::Move | Egui::AllScroll => CursorIcon::Move,
+        Egui::Grab => CursorIcon::Grab,
+        Egui::Grabbing => CursorIcon::Grabbing,
+        Egui::ResizeHorizontal | Egui::ResizeEast | Egui::ResizeWest | Egui::ResizeColumn => {
+            CursorIcon::EwResize
+        }
+        Egui::ResizeVertical | Egui::ResizeNorth | Egui::ResizeSouth | Egui::ResizeRow => {
+            CursorIcon::NsResize
+        }
+        Egui::ResizeNeSw | Egui::ResizeNorthEast | Egui::ResizeSouthWest => CursorIcon::NeswResize,
+        Egui::ResizeNwSe | Egui::ResizeNorthWest | Egui::ResizeSouthEast => CursorIcon::NwseResize,
+        _ => CursorIcon::Default,
+    })
+}
+
+fn egui_key(code: KeyCode) -> Option<egui::Key> {
+    use egui::Key as K;
+    Some(match code {
+        KeyCode::A => K::A,
+        KeyCode::B => K::B,
+        KeyCode::C => K::C,
+        KeyCode::D => K::D,
+        KeyCode::E => K::E,
+        KeyCode::F => K::F,
+        KeyCode::G => K::G,
+        KeyCode::H => K::H,
+        KeyCode::I => K::I,
+        KeyCode::J => K::J,
+        KeyCode::K => K::K,
+        KeyCode::L => K::L,
+        KeyCode::M => K::M,
+        KeyCode::N => K::N,
+        KeyCode::O => K::O,
+        KeyCode::P => K::P,
+        KeyCode::Q => K::Q,
+        KeyCode::R => K::R,
+        KeyCode::S => K::S,
+        KeyCode::T => K::T,
+        KeyCode::U => K::U,
+        KeyCode::V => K::V,
+        KeyCode::W => K::W,
+        KeyCode::X => K::X,
+        KeyCode::Y => K::Y,
+        KeyCode::Z => K::Z,
+        KeyCode::Key0 | KeyCode::Numpad0 => K::Num0,
+        KeyCode::Key1 | KeyCode::Numpad1 => K::Num1,
+        KeyCode::Key2 | KeyCode::Numpad2 => K::Num2,
+        KeyCode::Key3 | KeyCode::Numpad3 => K::Num3,
+        KeyCode::Key4 | KeyCode::Numpad4 => K::Num4,
+        KeyCode::Key5 | KeyCode::Numpad5 => K::Num5,
+        KeyCode::Key6 | KeyCode::Numpad6 => K::Num6,
+        KeyCode::Key7 | KeyCode::Numpad7 => K::Num7,
+        KeyCode::Key8 | KeyCode::Numpad8 => K::Num8,
+        KeyCode::Key9 | KeyCode::Numpad9 => K::Num9,
+        KeyCode::F1 => K::F1,
+        KeyCode::F2 => K::F2,
+        KeyCode::F3 => K::F3,
+        KeyCode::F4 => K::F4,
+        KeyCode::F5 => K::F5,
+        KeyCode::F6 => K::F6,
+        KeyCode::F7 => K::F7,
+        KeyCode::F8 => K::F8,
+        KeyCode::F9 => K::F9,
+        KeyCode::F10 => K::F10,
+        KeyCode::F11 => K::F11,
+        KeyCode::F12 => K::F12,
+        KeyCode::ArrowUp => K::ArrowUp,
+        KeyCode::ArrowDown => K::ArrowDown,
+        KeyCode::ArrowLeft => K::ArrowLeft,
+        KeyCode::ArrowRight => K::ArrowRight,
+        KeyCode::Home => K::Home,
+        KeyCode::End => K::End,
+        KeyCode::PageUp => K::PageUp,
+        KeyCode::PageDown => K::PageDown,
+        KeyCode::Insert => K::Insert,
+        KeyCode::Delete => K::Delete,
+        KeyCode::Backspace => K::Backspace,
+        KeyCode::Enter | KeyCode::NumpadEnter => K::Enter,
+        KeyCode::Tab => K::Tab,
+        KeyCode::Space => K::Space,
+        KeyCode::Escape => K::Escape,
+        KeyCode::Minus | KeyCode::NumpadSubtract => K::Minus,
+        KeyCode::Equal => K::Equals,
+        KeyCode::NumpadAdd => K::Plus,
+        KeyCode::BracketLeft => K::OpenBracket,
+        KeyCode::BracketRight => K::CloseBracket,
+        KeyCode::Backslash => K::Backslash,
+        KeyCode::Semicolon => K::Semicolon,
+        KeyCode::Quote => K::Quote,
+        KeyCode::Comma => K::Comma,
+        KeyCode::Period | KeyCode::NumpadDecimal => K::Period,
+        KeyCode::Slash | KeyCode::NumpadDivide => K::Slash,
+        KeyCode::Backquote => K::Backtick,
+        _ => return None,
+    })
+}
+
+/// Character a key types on a US layout
+fn us_layout_char(code: KeyCode, shift: bool) -> Option<char> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::A,
+        KeyCode::B,
+        KeyCode::C,
+        KeyCode::D,
+        KeyCode::E,
+        KeyCode::F,
+        KeyCode::G,
+        KeyCode::H,
+        KeyCode::I,
+        KeyCode::J,
+        KeyCode::K,
+        KeyCode::L,
+        KeyCode::M,
+        KeyCode::N,
+        KeyCode::O,
+        KeyCode::P,
+        KeyCode::Q,
+        KeyCode::R,
+        KeyCode::S,
+        KeyCode::T,
+        KeyCode::U,
+        KeyCode::V,
+        KeyCode::W,
+        KeyCode::X,
+        KeyCode::Y,
+        KeyCode::Z,
+    ];
+    if let Some(index) = LETTERS.iter().position(|&letter| letter == code) {
+        let c = (b'a' + index as u8) as char;
+        return Some(if shift { c.to_ascii_uppercase() } else { c });
+    }
+    let (plain, shifted) = match code {
+        KeyCode::Key0 => ('0', ')'),
+        KeyCode::Key1 => ('1', '!'),
+        KeyCode::Key2 => ('2', '@'),
+        KeyCode::Key3 => ('3', '#'),
+        KeyCode::Key4 => ('4', '$'),
+        KeyCode::Key5 => ('5', '%'),
+        KeyCode::Key6 => ('6', '^'),
+        KeyCode::Key7 => ('7', '&'),
+        KeyCode::Key8 => ('8', '*'),
+        KeyCode::Key9 => ('9', '('),
+        KeyCode::Minus => ('-', '_'),
+        KeyCode::Equal => ('=', '+'),
+        KeyCode::BracketLeft => ('[', '{'),
+        KeyCode::BracketRight => (']', '}'),
+        KeyCode::Backslash => ('\\', '|'),
+        KeyCode::Semicolon => (';', ':'),
+        KeyCode::Quote => ('\'', '"'),
+        KeyCode::Comma => (',', '<'),
+        KeyCode::Period => ('.', '>'),
+        KeyCode::Slash => ('/', '?'),
+        KeyCode::Backquote => ('`', '~'),
+        KeyCode::Space => (' ', ' '),
+        KeyCode::Numpad0 => ('0', '0'),
+        KeyCode::Numpad1 => ('1', '1'),
+        KeyCode::Numpad2 => ('2', '2'),
+        KeyCode::Numpad3 => ('3', '3'),
+        KeyCode::Numpad4 => ('4', '4'),
+        KeyCode::Numpad5 => ('5', '5'),
+        KeyCode::Numpad6 => ('6', '6'),
+        KeyCode::Numpad7 => ('7', '7'),
+        KeyCode::Numpad8 => ('8', '8'),
+        KeyCode::Numpad9 => ('9', '9'),
+        KeyCode::NumpadAdd => ('+', '+'),
+        KeyCode::NumpadSubtract => ('-', '-'),
+        KeyCode::NumpadMultiply => ('*', '*'),
+        KeyCode::NumpadDivide => ('/', '/'),
+        KeyCode::NumpadDecimal => ('.', '.'),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { plain })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{
+        ClearColor, ColorAttachment, LoadOp, RenderPassDesc, RendererConfig, StoreOp,
+    };
+    use crate::gfx::backend::create_device;
+    use avila_math::window::{KeyState, WindowConfig};
+
+    #[test]
+    fn test_render_ui() {
+        let mut device = create_device(RendererConfig {
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let mut renderer = EguiRenderer::new(&mut device, TextureFormat::Rgba8).unwrap();
+        let target = device
+            .create_texture(
+                &TextureDesc::new_2d(
+                    64,
+                    64,
+                    TextureFormat::Rgba8,
+                    TextureUsage::COLOR_ATTACHMENT | TextureUsage::TRANSFER_SRC,
+                ),
+                None,
+            )
+            .unwrap();
+
+        let ctx = egui::Context::default();
+        let input = RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, Vec2::splat(64.0))),
+            ..Default::default()
+        };
+        let output = ctx.run_ui(input, |ui| {
+            let rect = egui::Rect::from_min_size(Pos2::new(8.0, 8.0), Vec2::splat(16.0));
+            ui.painter().rect_filled(rect, 0.0, egui::Color32::RED);
+        });
+        // The font atlas comes with the first frame
+        assert!(output
+            .textures_delta
+            .set
+            .iter()
+            .any(|(id, _)| *id == TextureId::default()));
+        renderer
+            .update_textures(&mut device, output.textures_delta)
+            .unwrap();
+        let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        let mut cmd = device.begin_frame();
+        cmd.begin_render_pass(RenderPassDesc {
+            color_attachments: vec![ColorAttachment {
+                texture: target,
+                load: LoadOp::Clear(ClearColor::BLACK),
+                store: StoreOp::Store,
+            }],
+            depth_attachment: None,
+        });
+        renderer
+            .render(
+                &mut device,
+                &mut cmd,
+                &primitives,
+                output.pixels_per_point,
+                64,
+                64,
+            )
+            .unwrap();
+        cmd.end_render_pass();
+        device.submit(cmd);
+
+        let pixels = device.read_texture(target).unwrap();
+        let pixel = |x: usize, y: usize| &pixels[(y * 64 + x) * 4..][..4];
+        assert_eq!(pixel(16, 16), [255, 0, 0, 255]);
+        assert_eq!(pixel(4, 4), [0, 0, 0, 255]);
+        assert_eq!(pixel(40, 40), [0, 0, 0, 255]);
+
+        renderer.destroy(&mut device);
+    }
+
+    #[test]
+    fn test_input_translation() {
+        let mut window = Window::headless(WindowConfig::new("ui").with_size(800, 600));
+        let mut input = EguiInput::new(&window);
+
+        let shift = ModifierKeys::SHIFT;
+        input.handle_event(&Event::Keyboard(
+            KeyEvent::new(Key::Code(KeyCode::A), KeyState::Pressed).with_modifiers(shift),
+        ));
+        input.handle_event(&Event::Mouse(MouseEvent::ButtonPressed {
+            button: MouseButton::Left,
+            position: (10.0, 20.0),
+            modifiers: ModifierKeys::NONE,
+        }));
+        input.handle_event(&Event::Window(WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: window.size(),
+        }));
+
+        let raw = input.take_input();
+        assert_eq!(raw.screen_rect.unwrap().size(), Vec2::new(800.0, 600.0));
+        assert_eq!(
+            raw.viewports[&ViewportId::ROOT].native_pixels_per_point,
+            Some(2.0)
+        );
+        assert!(matches!(
+            raw.events[0],
+            egui::Event::Key { key: egui::Key::A, pressed: true, modifiers, .. } if modifiers.shift
+        ));
+        assert_eq!(raw.events[1], egui::Event::Text("A".to_string()));
+        assert!(matches!(
+            raw.events[2],
+            egui::Event::PointerButton {
+                button: egui::PointerButton::Primary,
+                pressed: true,
+                ..
+            }
+        ));
+        assert!(input.take_input().events.is_empty());
+
+        // Copied text comes back on Ctrl+V
+        let output = PlatformOutput {
+            commands: vec![OutputCommand::CopyText("hello".to_string())],
+            cursor_icon: egui::CursorIcon::Text,
+            ..Default::default()
+        };
+        input.handle_output(&mut window, &output);
+        assert_eq!(window.cursor_icon(), Some(CursorIcon::Text));
+        let command = if cfg!(target_os = "macos") {
+            ModifierKeys::META
+        } else {
+            ModifierKeys::CTRL
+        };
+        input.handle_event(&Event::Keyboard(
+            KeyEvent::new(Key::Code(KeyCode::V), KeyState::Pressed).with_modifiers(command),
+        ));
+        assert!(input
+            .take_input()
+            .events
+            .contains(&egui::Event::Paste("hello".to_string())));
+    }
+}
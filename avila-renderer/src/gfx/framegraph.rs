@@ -7,7 +7,64 @@
 //! Inspired by Frostbite's FrameGraph and modern rendering techniques.
 
 use crate::gfx::api::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Why a frame graph does not compile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameGraphError {
+    /// A pass uses a resource the graph never created or imported
+    UnknownResource { pass: String, resource: String },
+    /// A pass reads a transient resource that no pass writes
+    NeverWritten { pass: String, resource: String },
+    /// Several passes write the same resource
+    MultipleWriters {
+        resource: String,
+        passes: Vec<String>,
+    },
+    /// Passes that depend on each other in a loop: each one reads the
+    /// resource at the same index in `resources`, written by the pass before
+    /// it (the last pass for the first)
+    Cycle {
+        passes: Vec<String>,
+        resources: Vec<String>,
+    },
+}
+
+impl fmt::Display for FrameGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameGraphError::UnknownResource { pass, resource } => {
+                write!(f, "pass '{}' uses unknown resource '{}'", pass, resource)
+            }
+            FrameGraphError::NeverWritten { pass, resource } => write!(
+                f,
+                "pass '{}' reads '{}', which no pass writes",
+                pass, resource
+            ),
+            FrameGraphError::MultipleWriters { resource, passes } => write!(
+                f,
+                "resource '{}' is written by several passes: '{}'",
+                resource,
+                passes.join("', '")
+            ),
+            FrameGraphError::Cycle { passes, resources } => {
+                let links: Vec<String> = (0..passes.len())
+                    .map(|i| {
+                        let next = (i + 1) % passes.len();
+                        format!(
+                            "'{}' writes '{}' read by '{}'",
+                            passes[i], resources[next], passes[next]
+                        )
+                    })
+                    .collect();
+                write!(f, "passes form a cycle: {}", links.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameGraphError {}
 
 /// Frame graph builder for declaring rendering passes
 pub struct FrameGraphBuilder {
@@ -26,36 +83,89 @@ impl FrameGraphBuilder {
     }
 
     /// Create a transient texture resource
+    ///
+    /// The compiled graph creates it on its first `execute` and reuses it in
+    /// later ones.
     pub fn create_texture(&mut self, name: &str, desc: TextureDesc) -> ResourceId {
-        let id = ResourceId::new(name);
-        self.resources.insert(
-            name.to_string(),
-            ResourceNode {
-                id: id.clone(),
-                desc: ResourceDesc::Texture(desc),
-                producer: None,
-                consumers: Vec::new(),
+        self.add_texture(name, desc, None)
+    }
+
+    /// Create a transient texture the size of the swapchain
+    ///
+    /// `desc.width` and `desc.height` are the current size;
+    /// `CompiledFrameGraph::resize` changes them.
+    pub fn create_screen_texture(&mut self, name: &str, desc: TextureDesc) -> ResourceId {
+        self.add_texture(name, desc, Some(1))
+    }
+
+    /// Create a transient texture `1 / divisor` the size of the swapchain,
+    /// such as a half-resolution blur target
+    ///
+    /// `desc.width` and `desc.height` are the current size, already divided;
+    /// `CompiledFrameGraph::resize` divides the new size, rounding down to
+    /// at least one texel.
+    pub fn create_scaled_screen_texture(
+        &mut self,
+        name: &str,
+        desc: TextureDesc,
+        divisor: u32,
+    ) -> ResourceId {
+        self.add_texture(name, desc, Some(divisor.max(1)))
+    }
+
+    fn add_texture(
+        &mut self,
+        name: &str,
+        desc: TextureDesc,
+        screen_divisor: Option<u32>,
+    ) -> ResourceId {
+        self.add_resource(
+            name,
+            ResourceDesc::Texture {
+                desc,
+                screen_divisor,
             },
-        );
-        id
+        )
+    }
+
+    /// Create a transient buffer resource, such as the output of a compute
+    /// pass another pass reads
+    ///
+    /// Like transient textures, it is created on the first `execute` and
+    /// reused in later ones.
+    pub fn create_buffer(&mut self, name: &str, desc: BufferDesc) -> ResourceId {
+        self.add_resource(name, ResourceDesc::Buffer { desc })
     }
 
     /// Import an external texture (e.g., swapchain)
     pub fn import_texture(&mut self, name: &str, handle: TextureHandle) -> ResourceId {
+        self.add_resource(
+            name,
+            ResourceDesc::Imported(ResourceHandle::Texture(handle)),
+        )
+    }
+
+    /// Import an external buffer (e.g., instance data filled by the CPU)
+    pub fn import_buffer(&mut self, name: &str, handle: BufferHandle) -> ResourceId {
+        self.add_resource(name, ResourceDesc::Imported(ResourceHandle::Buffer(handle)))
+    }
+
+    fn add_resource(&mut self, name: &str, desc: ResourceDesc) -> ResourceId {
         let id = ResourceId::new(name);
         self.resources.insert(
             name.to_string(),
             ResourceNode {
                 id: id.clone(),
-                desc: ResourceDesc::Imported(ImportedResource::Texture(handle)),
-                producer: None,
-                consumers: Vec::new(),
+                desc,
             },
         );
         id
     }
 
     /// Add a rendering pass
+    ///
+    /// Passes may be added in any order; `compile` runs each one after the
+    /// passes writing what it reads.
     pub fn add_pass(
         &mut self,
         name: &str,
@@ -69,46 +179,196 @@ impl FrameGraphBuilder {
             pass_id,
             reads: Vec::new(),
             writes: Vec::new(),
+            side_effect: false,
         };
 
         setup(&mut builder);
 
-        // Register this pass as producer/consumer of resources
-        for read in &builder.reads {
-            if let Some(resource) = self.resources.get_mut(&read.0) {
-                resource.consumers.push(pass_id);
-            }
-        }
-
-        for write in &builder.writes {
-            if let Some(resource) = self.resources.get_mut(&write.0) {
-                if resource.producer.is_some() {
-                    panic!("Resource '{}' already has a producer", write.0);
-                }
-                resource.producer = Some(pass_id);
-            }
-        }
-
         self.passes.push(PassNode {
             id: pass_id,
             name: name.to_string(),
             reads: builder.reads,
             writes: builder.writes,
+            side_effect: builder.side_effect,
             execute,
         });
 
         pass_id
     }
 
-    /// Compile the frame graph and return an executable version
-    pub fn compile(self) -> CompiledFrameGraph {
-        // TODO: Topological sort, culling unused passes, barrier insertion
-        println!("Compiling frame graph with {} passes", self.passes.len());
+    /// Validate the graph, order the passes after their dependencies and
+    /// drop the ones nothing needs
+    ///
+    /// A pass is needed when it writes an imported resource, is marked with
+    /// `PassBuilder::side_effect`, or writes something a needed pass reads.
+    /// Transient resources only culled passes use are not created. Among
+    /// passes that do not depend on each other, declaration order is kept.
+    pub fn compile(self) -> Result<CompiledFrameGraph, FrameGraphError> {
+        // Writer of each resource
+        let mut writers: HashMap<&str, usize> = HashMap::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            for resource in pass.reads.iter().chain(&pass.writes) {
+                if !self.resources.contains_key(&resource.0) {
+                    return Err(FrameGraphError::UnknownResource {
+                        pass: pass.name.clone(),
+                        resource: resource.0.clone(),
+                    });
+                }
+            }
+            for resource in &pass.writes {
+                if let Some(&writer) = writers.get(resource.0.as_str()) {
+                    if writer != index {
+                        return Err(FrameGraphError::MultipleWriters {
+                            resource: resource.0.clone(),
+                            passes: vec![self.passes[writer].name.clone(), pass.name.clone()],
+                        });
+                    }
+                }
+                writers.insert(&resource.0, index);
+            }
+        }
+        for pass in &self.passes {
+            for resource in &pass.reads {
+                let transient =
+                    !matches!(self.resources[&resource.0].desc, ResourceDesc::Imported(_));
+                if transient && !writers.contains_key(resource.0.as_str()) {
+                    return Err(FrameGraphError::NeverWritten {
+                        pass: pass.name.clone(),
+                        resource: resource.0.clone(),
+                    });
+                }
+            }
+        }
+
+        // Passes each pass depends on, with the resource linking them
+        let dependencies: Vec<Vec<(usize, &str)>> = self
+            .passes
+            .iter()
+            .map(|pass| {
+                pass.reads
+                    .iter()
+                    .filter_map(|resource| {
+                        let name = resource.0.as_str();
+                        writers.get(name).map(|&writer| (writer, name))
+                    })
+                    .collect()
+            })
+            .collect();
+        let order = topological_order(&self.passes, &dependencies)?;
+
+        // Walk back from the passes with visible results
+        let mut needed = vec![false; self.passes.len()];
+        let mut stack: Vec<usize> = (0..self.passes.len())
+            .filter(|&index| {
+                let pass = &self.passes[index];
+                pass.side_effect
+                    || pass.writes.iter().any(|resource| {
+                        matches!(self.resources[&resource.0].desc, ResourceDesc::Imported(_))
+                    })
+            })
+            .collect();
+        while let Some(index) = stack.pop() {
+            if !std::mem::replace(&mut needed[index], true) {
+                stack.extend(dependencies[index].iter().map(|&(writer, _)| writer));
+            }
+        }
+
+        let used: HashSet<String> = self
+            .passes
+            .iter()
+            .zip(&needed)
+            .filter(|(_, &needed)| needed)
+            .flat_map(|(pass, _)| pass.reads.iter().chain(&pass.writes))
+            .map(|resource| resource.0.clone())
+            .collect();
+        let mut passes: Vec<Option<PassNode>> = self.passes.into_iter().map(Some).collect();
+        let passes = order
+            .into_iter()
+            .filter(|&index| needed[index])
+            .filter_map(|index| passes[index].take())
+            .collect();
+        let resources = self
+            .resources
+            .into_iter()
+            .filter(|(name, _)| used.contains(name))
+            .collect();
+
+        Ok(CompiledFrameGraph {
+            passes,
+            resources,
+            cache: HashMap::new(),
+        })
+    }
+}
+
+/// Kahn's algorithm, taking the earliest declared pass among the ready ones
+fn topological_order(
+    passes: &[PassNode],
+    dependencies: &[Vec<(usize, &str)>],
+) -> Result<Vec<usize>, FrameGraphError> {
+    let mut waiting: Vec<usize> = dependencies
+        .iter()
+        .map(|dependencies| dependencies.len())
+        .collect();
+    let mut dependents = vec![Vec::new(); passes.len()];
+    for (index, dependencies) in dependencies.iter().enumerate() {
+        for &(writer, _) in dependencies {
+            dependents[writer].push(index);
+        }
+    }
+
+    let mut ready: std::collections::BTreeSet<usize> = (0..passes.len())
+        .filter(|&index| waiting[index] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(passes.len());
+    while let Some(index) = ready.pop_first() {
+        order.push(index);
+        for &dependent in &dependents[index] {
+            waiting[dependent] -= 1;
+            if waiting[dependent] == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+    if order.len() == passes.len() {
+        return Ok(order);
+    }
 
-        CompiledFrameGraph {
-            passes: self.passes,
-            resources: self.resources,
+    // Every pass left waits on another one left: follow those links back
+    // from any of them until a pass repeats
+    let mut path = vec![(0..passes.len())
+        .find(|&index| waiting[index] > 0)
+        .expect("a pass is left")];
+    let mut links = Vec::new();
+    loop {
+        let current = *path.last().expect("path is never empty");
+        let &(writer, resource) = dependencies[current]
+            .iter()
+            .find(|&&(writer, _)| waiting[writer] > 0)
+            .expect("a waiting pass depends on another waiting pass");
+        links.push(resource);
+        if let Some(start) = path.iter().position(|&index| index == writer) {
+            // Each pass of path[start..] reads links[i] from the next one;
+            // reversed, each reads from the one before it
+            let mut cycle: Vec<usize> = path[start..].to_vec();
+            let mut resources: Vec<&str> = links[start..].to_vec();
+            cycle.reverse();
+            resources.reverse();
+            // Start at the pass declared first
+            let first = (0..cycle.len())
+                .min_by_key(|&i| cycle[i])
+                .expect("cycle is never empty");
+            cycle.rotate_left(first);
+            resources.rotate_left(first);
+            return Err(FrameGraphError::Cycle {
+                passes: cycle
+                    .iter()
+                    .map(|&index| passes[index].name.clone())
+                    .collect(),
+                resources: resources.into_iter().map(str::to_string).collect(),
+            });
         }
+        path.push(writer);
     }
 }
 
@@ -123,6 +383,7 @@ pub struct PassBuilder {
     pass_id: PassId,
     reads: Vec<ResourceId>,
     writes: Vec<ResourceId>,
+    side_effect: bool,
 }
 
 impl PassBuilder {
@@ -135,6 +396,12 @@ impl PassBuilder {
     pub fn write(&mut self, resource: &ResourceId) {
         self.writes.push(resource.clone());
     }
+
+    /// Keep the pass even when nothing reads what it writes, for work seen
+    /// outside the graph (readbacks, buffer updates)
+    pub fn side_effect(&mut self) {
+        self.side_effect = true;
+    }
 }
 
 /// Pass execution callback
@@ -143,60 +410,176 @@ pub type PassExecuteFn = Box<dyn Fn(&mut CommandList, &PassResources)>;
 /// Pass resources available during execution
 pub struct PassResources {
     textures: HashMap<String, TextureHandle>,
+    buffers: HashMap<String, BufferHandle>,
 }
 
 impl PassResources {
     pub fn get_texture(&self, name: &str) -> TextureHandle {
         *self.textures.get(name).unwrap_or(&TextureHandle::INVALID)
     }
+
+    pub fn get_buffer(&self, name: &str) -> BufferHandle {
+        *self.buffers.get(name).unwrap_or(&BufferHandle::INVALID)
+    }
 }
 
 /// Compiled frame graph ready for execution
+///
+/// Execute it every frame: transient textures and buffers are created once
+/// and kept between executes, keyed by their description. Call `release`
+/// before dropping the graph to destroy them.
 pub struct CompiledFrameGraph {
     passes: Vec<PassNode>,
     resources: HashMap<String, ResourceNode>,
+    /// Transient resources not in use, by description
+    cache: HashMap<TransientDesc, Vec<ResourceHandle>>,
 }
 
 impl CompiledFrameGraph {
+    /// Names of the passes that run, in execution order
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.name.as_str()).collect()
+    }
+
+    /// Point an imported texture at another handle, such as this frame's
+    /// swapchain image; `false` if the graph imports no texture `name`
+    pub fn import_texture(&mut self, name: &str, handle: TextureHandle) -> bool {
+        match self.resources.get_mut(name) {
+            Some(ResourceNode {
+                desc: ResourceDesc::Imported(ResourceHandle::Texture(imported)),
+                ..
+            }) => {
+                *imported = handle;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Point an imported buffer at another handle; `false` if the graph
+    /// imports no buffer `name`
+    pub fn import_buffer(&mut self, name: &str, handle: BufferHandle) -> bool {
+        match self.resources.get_mut(name) {
+            Some(ResourceNode {
+                desc: ResourceDesc::Imported(ResourceHandle::Buffer(imported)),
+                ..
+            }) => {
+                *imported = handle;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Follow a swapchain resize: screen-sized textures take the new size,
+    /// or their fraction of it
+    ///
+    /// Cached textures of the old size are destroyed by the next `execute`.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        for resource in self.resources.values_mut() {
+            if let ResourceDesc::Texture {
+                desc,
+                screen_divisor: Some(divisor),
+            } = &mut resource.desc
+            {
+                desc.width = (width / *divisor).max(1);
+                desc.height = (height / *divisor).max(1);
+            }
+        }
+    }
+
     /// Execute the frame graph
-    pub fn execute(&self, device: &mut dyn GpuDevice) {
+    ///
+    /// Transient resources come from the cache when one with the same
+    /// description is free, and go back to it afterwards; cached resources no
+    /// transient describes any more are destroyed first. Transients with the
+    /// same description may trade textures or buffers, so none keeps its
+    /// contents from one execute to the next. If a transient resource cannot
+    /// be created, no pass runs.
+    pub fn execute(&mut self, device: &mut dyn GpuDevice) -> Result<(), GfxError> {
         println!("Executing frame graph with {} passes", self.passes.len());
 
-        // Allocate transient resources
-        let mut allocated_textures: HashMap<String, TextureHandle> = HashMap::new();
+        // Drop what no resource asks for any more, such as textures of the
+        // size before a resize
+        let mut wanted: HashMap<TransientDesc, usize> = HashMap::new();
+        for resource in self.resources.values() {
+            if let Some(desc) = resource.desc.transient() {
+                *wanted.entry(desc).or_default() += 1;
+            }
+        }
+        for (desc, handles) in &mut self.cache {
+            let keep = wanted.get(desc).copied().unwrap_or(0);
+            for handle in handles.drain(keep.min(handles.len())..) {
+                handle.destroy(device);
+            }
+        }
+        self.cache.retain(|_, handles| !handles.is_empty());
 
+        // Allocate transient resources
+        let mut pass_resources = PassResources {
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+        };
+        let mut transients = Vec::new();
         for (name, resource) in &self.resources {
-            match &resource.desc {
-                ResourceDesc::Texture(desc) => {
-                    let handle = device.create_texture(desc);
-                    allocated_textures.insert(name.clone(), handle);
+            let handle = match &resource.desc {
+                ResourceDesc::Imported(handle) => *handle,
+                transient => {
+                    let desc = transient.transient().expect("resource is not imported");
+                    let cached = self.cache.get_mut(&desc).and_then(Vec::pop);
+                    let handle = match cached {
+                        Some(handle) => handle,
+                        None => match desc.create(device) {
+                            Ok(handle) => handle,
+                            Err(err) => {
+                                Self::recycle(&mut self.cache, transients);
+                                return Err(err);
+                            }
+                        },
+                    };
+                    transients.push((desc, handle));
+                    handle
                 }
-                ResourceDesc::Imported(ImportedResource::Texture(handle)) => {
-                    allocated_textures.insert(name.clone(), *handle);
+            };
+            match handle {
+                ResourceHandle::Texture(handle) => {
+                    pass_resources.textures.insert(name.clone(), handle);
+                }
+                ResourceHandle::Buffer(handle) => {
+                    pass_resources.buffers.insert(name.clone(), handle);
                 }
             }
         }
 
-        // Execute passes in order
+        // Execute passes in dependency order
         for pass in &self.passes {
             println!("  Pass: {}", pass.name);
 
-            let pass_resources = PassResources {
-                textures: allocated_textures.clone(),
-            };
-
             let mut cmd = device.begin_frame();
             (pass.execute)(&mut cmd, &pass_resources);
             device.submit(cmd);
         }
 
-        // Cleanup transient resources
-        for (name, handle) in allocated_textures {
-            if let Some(resource) = self.resources.get(&name) {
-                if !matches!(resource.desc, ResourceDesc::Imported(_)) {
-                    device.destroy_texture(handle);
-                }
-            }
+        Self::recycle(&mut self.cache, transients);
+        Ok(())
+    }
+
+    /// Destroy the cached transient resources; the next `execute` creates
+    /// them again
+    pub fn release(&mut self, device: &mut dyn GpuDevice) {
+        for handle in self.cache.drain().flat_map(|(_, handles)| handles) {
+            handle.destroy(device);
+        }
+    }
+
+    /// Put transient resources back in the cache; imported ones belong to
+    /// the caller and never enter it
+    fn recycle(
+        cache: &mut HashMap<TransientDesc, Vec<ResourceHandle>>,
+        transients: Vec<(TransientDesc, ResourceHandle)>,
+    ) {
+        for (desc, handle) in transients {
+            cache.entry(desc).or_default().push(handle);
         }
     }
 }
@@ -212,6 +595,11 @@ impl ResourceId {
     fn new(name: &str) -> Self {
         Self(name.to_string())
     }
+
+    /// Name the resource was declared with, for `PassResources` lookups
+    pub fn name(&self) -> &str {
+        &self.0
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -222,23 +610,72 @@ struct PassNode {
     name: String,
     reads: Vec<ResourceId>,
     writes: Vec<ResourceId>,
+    side_effect: bool,
     execute: PassExecuteFn,
 }
 
 struct ResourceNode {
     id: ResourceId,
     desc: ResourceDesc,
-    producer: Option<PassId>,
-    consumers: Vec<PassId>,
 }
 
 enum ResourceDesc {
+    /// Textures with a `screen_divisor` follow `CompiledFrameGraph::resize`
+    Texture {
+        desc: TextureDesc,
+        screen_divisor: Option<u32>,
+    },
+    /// Created and reused by the compiled graph like transient textures
+    Buffer {
+        desc: BufferDesc,
+    },
+    Imported(ResourceHandle),
+}
+
+impl ResourceDesc {
+    /// Cache key of a transient resource, `None` for imported ones
+    fn transient(&self) -> Option<TransientDesc> {
+        match self {
+            ResourceDesc::Texture { desc, .. } => Some(TransientDesc::Texture(desc.clone())),
+            ResourceDesc::Buffer { desc } => Some(TransientDesc::Buffer(desc.clone())),
+            ResourceDesc::Imported(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum TransientDesc {
     Texture(TextureDesc),
-    Imported(ImportedResource),
+    Buffer(BufferDesc),
+}
+
+impl TransientDesc {
+    fn create(&self, device: &mut dyn GpuDevice) -> Result<ResourceHandle, GfxError> {
+        match self {
+            TransientDesc::Texture(desc) => device
+                .create_texture(desc, None)
+                .map(ResourceHandle::Texture),
+            TransientDesc::Buffer(desc) => {
+                device.create_buffer(desc, None).map(ResourceHandle::Buffer)
+            }
+        }
+    }
 }
 
-enum ImportedResource {
+/// Texture or buffer of a resource, transient or imported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResourceHandle {
     Texture(TextureHandle),
+    Buffer(BufferHandle),
+}
+
+impl ResourceHandle {
+    fn destroy(self, device: &mut dyn GpuDevice) {
+        match self {
+            ResourceHandle::Texture(handle) => device.destroy_texture(handle),
+            ResourceHandle::Buffer(handle) => device.destroy_buffer(handle),
+        }
+    }
 }
 
 // ============================================================================
@@ -292,7 +729,361 @@ enum ImportedResource {
 ///     }),
 /// );
 ///
-/// let compiled = fg.compile();
-/// compiled.execute(&mut device);
+/// let mut compiled = fg.compile()?;
+/// compiled.execute(&mut device)?;
 /// ```
 pub fn _example() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::backend::{create_device, BackendDevice};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn target() -> TextureDesc {
+        TextureDesc::new_2d(
+            4,
+            4,
+            TextureFormat::Rgba8,
+            TextureUsage::COLOR_ATTACHMENT | TextureUsage::SAMPLED,
+        )
+    }
+
+    /// Add a pass that does nothing when executed
+    fn pass(fg: &mut FrameGraphBuilder, name: &str, reads: &[&ResourceId], writes: &[&ResourceId]) {
+        fg.add_pass(
+            name,
+            |pass| {
+                for &resource in reads {
+                    pass.read(resource);
+                }
+                for &resource in writes {
+                    pass.write(resource);
+                }
+            },
+            Box::new(|_, _| {}),
+        );
+    }
+
+    #[test]
+    fn test_compile_orders_by_dependencies() {
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        let gbuffer = fg.create_texture("gbuffer", target());
+        let ssao = fg.create_texture("ssao", target());
+        let lit = fg.create_texture("lit", target());
+
+        // Declared consumers first
+        pass(&mut fg, "composite", &[&lit], &[&backbuffer]);
+        pass(&mut fg, "lighting", &[&gbuffer, &ssao], &[&lit]);
+        pass(&mut fg, "ssao", &[&gbuffer], &[&ssao]);
+        pass(&mut fg, "gbuffer", &[], &[&gbuffer]);
+
+        let compiled = fg.compile().unwrap();
+        assert_eq!(
+            compiled.pass_names(),
+            ["gbuffer", "ssao", "lighting", "composite"]
+        );
+    }
+
+    #[test]
+    fn test_compile_keeps_declaration_order_of_independent_passes() {
+        let mut fg = FrameGraphBuilder::new();
+        let outputs: Vec<ResourceId> = (0..3)
+            .map(|i| fg.import_texture(&format!("output{}", i), TextureHandle(i)))
+            .collect();
+        for (name, output) in ["c", "a", "b"].into_iter().zip(&outputs) {
+            pass(&mut fg, name, &[], &[output]);
+        }
+        assert_eq!(fg.compile().unwrap().pass_names(), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_compile_culls_unused_passes() {
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        let scene = fg.create_texture("scene", target());
+        let debug = fg.create_texture("debug", target());
+        let histogram = fg.create_texture("histogram", target());
+        let unread = fg.create_texture("unread", target());
+
+        pass(&mut fg, "scene", &[], &[&scene]);
+        pass(&mut fg, "debug_overlay", &[&scene], &[&debug]);
+        pass(&mut fg, "debug_blur", &[&debug], &[&unread]);
+        pass(&mut fg, "present", &[&scene], &[&backbuffer]);
+        pass(&mut fg, "side_effect_free", &[], &[]);
+        fg.add_pass(
+            "readback",
+            |pass| {
+                pass.write(&histogram);
+                pass.side_effect();
+            },
+            Box::new(|_, _| {}),
+        );
+
+        let compiled = fg.compile().unwrap();
+        assert_eq!(compiled.pass_names(), ["scene", "present", "readback"]);
+        let mut resources: Vec<&str> = compiled.resources.keys().map(String::as_str).collect();
+        resources.sort_unstable();
+        assert_eq!(resources, ["backbuffer", "histogram", "scene"]);
+    }
+
+    #[test]
+    fn test_compile_detects_cycles() {
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        let a = fg.create_texture("a", target());
+        let b = fg.create_texture("b", target());
+        let c = fg.create_texture("c", target());
+
+        pass(&mut fg, "first", &[&c], &[&a]);
+        pass(&mut fg, "second", &[&a], &[&b]);
+        pass(&mut fg, "third", &[&b], &[&c]);
+        pass(&mut fg, "present", &[&a], &[&backbuffer]);
+
+        let err = fg.compile().err().unwrap();
+        assert_eq!(
+            err,
+            FrameGraphError::Cycle {
+                passes: vec!["first".into(), "second".into(), "third".into()],
+                resources: vec!["c".into(), "a".into(), "b".into()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "passes form a cycle: 'first' writes 'a' read by 'second', \
+             'second' writes 'b' read by 'third', 'third' writes 'c' read by 'first'"
+        );
+    }
+
+    #[test]
+    fn test_compile_detects_self_dependency() {
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        pass(&mut fg, "feedback", &[&backbuffer], &[&backbuffer]);
+        assert_eq!(
+            fg.compile().err(),
+            Some(FrameGraphError::Cycle {
+                passes: vec!["feedback".to_string()],
+                resources: vec!["backbuffer".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_compile_validation() {
+        let mut other = FrameGraphBuilder::new();
+        let foreign = other.create_texture("foreign", target());
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        pass(&mut fg, "present", &[&foreign], &[&backbuffer]);
+        assert_eq!(
+            fg.compile().err(),
+            Some(FrameGraphError::UnknownResource {
+                pass: "present".to_string(),
+                resource: "foreign".to_string(),
+            })
+        );
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        let empty = fg.create_texture("empty", target());
+        pass(&mut fg, "present", &[&empty], &[&backbuffer]);
+        assert_eq!(
+            fg.compile().err(),
+            Some(FrameGraphError::NeverWritten {
+                pass: "present".to_string(),
+                resource: "empty".to_string(),
+            })
+        );
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        pass(&mut fg, "scene", &[], &[&backbuffer]);
+        pass(&mut fg, "ui", &[], &[&backbuffer]);
+        assert_eq!(
+            fg.compile().err(),
+            Some(FrameGraphError::MultipleWriters {
+                resource: "backbuffer".to_string(),
+                passes: vec!["scene".to_string(), "ui".to_string()],
+            })
+        );
+
+        // Imported textures may be read without a writer
+        let mut fg = FrameGraphBuilder::new();
+        let history = fg.import_texture("history", TextureHandle(1));
+        let backbuffer = fg.import_texture("backbuffer", TextureHandle(0));
+        pass(&mut fg, "taa", &[&history], &[&backbuffer]);
+        assert!(fg.compile().is_ok());
+    }
+
+    #[test]
+    fn test_execute_runs_passes_in_order() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let ran = Rc::new(RefCell::new(Vec::new()));
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", device.get_swapchain_texture());
+        let scene = fg.create_texture("scene", target());
+        for (name, reads, writes) in [
+            ("present", vec![scene.clone()], vec![backbuffer.clone()]),
+            ("scene", vec![], vec![scene.clone()]),
+        ] {
+            let ran = ran.clone();
+            fg.add_pass(
+                name,
+                |pass| {
+                    reads.iter().for_each(|resource| pass.read(resource));
+                    writes.iter().for_each(|resource| pass.write(resource));
+                },
+                Box::new(move |_, resources| {
+                    assert_ne!(resources.get_texture("scene"), TextureHandle::INVALID);
+                    ran.borrow_mut().push(name);
+                }),
+            );
+        }
+
+        fg.compile().unwrap().execute(&mut device).unwrap();
+        assert_eq!(*ran.borrow(), ["scene", "present"]);
+    }
+
+    #[test]
+    fn test_execute_reuses_transients_until_resize() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", device.get_swapchain_texture());
+        let scene = fg.create_screen_texture("scene", target());
+        let mut lut_desc = target();
+        lut_desc.format = TextureFormat::Rgba16f;
+        let lut = fg.create_texture("lut", lut_desc);
+        pass(&mut fg, "lut", &[], &[&lut]);
+        let recorded = seen.clone();
+        fg.add_pass(
+            "scene",
+            |pass| {
+                pass.read(&lut);
+                pass.write(&scene);
+            },
+            Box::new(move |_, resources| {
+                let textures = (resources.get_texture("scene"), resources.get_texture("lut"));
+                recorded.borrow_mut().push(textures);
+            }),
+        );
+        pass(&mut fg, "present", &[&scene], &[&backbuffer]);
+        let mut compiled = fg.compile().unwrap();
+        let counts = |device: &BackendDevice| {
+            let stats = &device.memory_stats()[0];
+            (stats.allocation_count, stats.deallocation_count)
+        };
+
+        compiled.execute(&mut device).unwrap();
+        compiled.execute(&mut device).unwrap();
+        assert_eq!(counts(&device), (2, 0));
+        assert_eq!(seen.borrow()[0], seen.borrow()[1]);
+
+        // Only the screen-sized texture is recreated
+        compiled.resize(8, 8);
+        compiled.execute(&mut device).unwrap();
+        assert_eq!(counts(&device), (3, 1));
+        let (first, last) = (seen.borrow()[0], seen.borrow()[2]);
+        assert_eq!(first.1, last.1);
+
+        assert!(compiled.import_texture("backbuffer", TextureHandle(7)));
+        assert!(!compiled.import_texture("scene", TextureHandle(7)));
+        assert!(!compiled.import_texture("missing", TextureHandle(7)));
+
+        compiled.release(&mut device);
+        assert_eq!(counts(&device), (3, 3));
+    }
+
+    #[test]
+    fn test_buffer_resources() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let instances = device
+            .create_buffer(&BufferDesc::storage(256), None)
+            .unwrap();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let mut fg = FrameGraphBuilder::new();
+        let backbuffer = fg.import_texture("backbuffer", device.get_swapchain_texture());
+        let imported = fg.import_buffer("instances", instances);
+        let visible = fg.create_buffer("visible", BufferDesc::storage(256));
+        let stats = fg.create_buffer("stats", BufferDesc::storage(16));
+        let recorded = seen.clone();
+        fg.add_pass(
+            "cull",
+            |pass| {
+                pass.read(&imported);
+                pass.write(&visible);
+            },
+            Box::new(move |_, resources| {
+                let buffers = (
+                    resources.get_buffer("instances"),
+                    resources.get_buffer("visible"),
+                );
+                recorded.borrow_mut().push(buffers);
+            }),
+        );
+        pass(&mut fg, "draw", &[&visible], &[&backbuffer]);
+        pass(&mut fg, "count", &[&visible], &[&stats]);
+
+        // A transient buffer nothing writes cannot be read
+        let mut invalid = FrameGraphBuilder::new();
+        let empty = invalid.create_buffer("empty", BufferDesc::storage(16));
+        let output = invalid.import_buffer("output", instances);
+        pass(&mut invalid, "copy", &[&empty], &[&output]);
+        assert_eq!(
+            invalid.compile().err(),
+            Some(FrameGraphError::NeverWritten {
+                pass: "copy".to_string(),
+                resource: "empty".to_string(),
+            })
+        );
+
+        let mut compiled = fg.compile().unwrap();
+        assert_eq!(compiled.pass_names(), ["cull", "draw"]);
+        let allocations = |device: &BackendDevice| {
+            let stats = &device.memory_stats()[0];
+            (stats.allocation_count, stats.deallocation_count)
+        };
+        let before = allocations(&device);
+
+        compiled.execute(&mut device).unwrap();
+        compiled.execute(&mut device).unwrap();
+        // Only "visible" is created, once; "stats" belongs to a culled pass
+        assert_eq!(allocations(&device), (before.0 + 1, before.1));
+        let (first, second) = (seen.borrow()[0], seen.borrow()[1]);
+        assert_eq!(first, second);
+        assert_eq!(first.0, instances);
+        assert_ne!(first.1, BufferHandle::INVALID);
+
+        assert!(compiled.import_buffer("instances", BufferHandle(7)));
+        assert!(!compiled.import_buffer("visible", BufferHandle(7)));
+        assert!(!compiled.import_buffer("backbuffer", BufferHandle(7)));
+        assert!(!compiled.import_texture("instances", TextureHandle(7)));
+        compiled.execute(&mut device).unwrap();
+        assert_eq!(seen.borrow()[2].0, BufferHandle(7));
+
+        compiled.release(&mut device);
+        assert_eq!(allocations(&device), (before.0 + 1, before.1 + 1));
+    }
+}
@@ -0,0 +1,756 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! glTF 2.0 loader (feature `gltf`)
+//!
+//! Reads a `.gltf` or `.glb` file with its buffers and images and creates
+//! renderer resources from it: each mesh primitive becomes a `GpuMesh` in
+//! the `Mesh` vertex layout, each image a mipmapped texture and each
+//! material a `Material` with the metallic-roughness parameters.
+//!
+//! Node transforms are kept as kernel `Vec3`/`Quat` translation, rotation
+//! and scale; `GltfScene::world_transforms` combines them down the
+//! hierarchy. Images are converted to RGBA8; those a material reads as
+//! color (base color and emissive) are created as `Rgba8Srgb`, the others
+//! as linear `Rgba8`.
+//!
+//! Triangle strips and fans are converted to lists; points and lines are
+//! skipped. Missing normals are computed flat, as the specification asks,
+//! and missing tangents from the texture coordinates. Animations, skins
+//! and morph targets are not loaded.
+
+use crate::gfx::api::{
+    AddressMode, FilterMode, GfxError, GpuDevice, SamplerDesc, SamplerHandle, TextureDesc,
+    TextureFormat, TextureHandle, TextureUsage,
+};
+use crate::gfx::mesh::{GpuMesh, Mesh, Vertex};
+use avila_math::{Aabb, Mat4, Quat, Vec3, Vec4};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Why a glTF file does not load
+#[derive(Debug)]
+pub enum GltfError {
+    /// The file, one of its buffers or images could not be read or decoded
+    Import(gltf::Error),
+    /// The file is valid glTF but describes something unsupported
+    Unsupported(String),
+    /// Creating a resource failed
+    Gfx(GfxError),
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Import(err) => write!(f, "glTF import failed: {}", err),
+            GltfError::Unsupported(reason) => write!(f, "unsupported glTF: {}", reason),
+            GltfError::Gfx(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<gltf::Error> for GltfError {
+    fn from(err: gltf::Error) -> Self {
+        GltfError::Import(err)
+    }
+}
+
+impl From<GfxError> for GltfError {
+    fn from(err: GfxError) -> Self {
+        GltfError::Gfx(err)
+    }
+}
+
+/// How a material's alpha is used
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AlphaMode {
+    /// Alpha is ignored
+    #[default]
+    Opaque,
+    /// Fragments with alpha below the cutoff are discarded
+    Mask(f32),
+    /// Blended over what is behind
+    Blend,
+}
+
+/// A material's use of a texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureRef {
+    /// Index into `GltfScene::textures`
+    pub texture: usize,
+    /// Texture coordinate set; the loaded meshes carry set 0 only
+    pub tex_coord: u32,
+}
+
+/// PBR metallic-roughness material
+///
+/// Factors multiply the texels of the matching texture, as in glTF. The
+/// default is glTF's default material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: Option<String>,
+    /// Linear RGBA
+    pub base_color_factor: Vec4,
+    pub base_color_texture: Option<TextureRef>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    /// Roughness in green, metalness in blue
+    pub metallic_roughness_texture: Option<TextureRef>,
+    /// Tangent-space normals
+    pub normal_texture: Option<TextureRef>,
+    pub normal_scale: f32,
+    /// Ambient occlusion in red
+    pub occlusion_texture: Option<TextureRef>,
+    pub occlusion_strength: f32,
+    /// Linear RGB
+    pub emissive_factor: Vec3,
+    pub emissive_texture: Option<TextureRef>,
+    pub alpha_mode: AlphaMode,
+    /// Both faces are lit and neither is culled
+    pub double_sided: bool,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: None,
+            base_color_factor: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive_factor: Vec3::ZERO,
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+        }
+    }
+}
+
+/// Texture and sampler pair a material reads
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GltfTexture {
+    pub texture: TextureHandle,
+    pub sampler: SamplerHandle,
+}
+
+/// One draw of a mesh: triangles with one material
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Primitive {
+    pub mesh: GpuMesh,
+    /// Index into `GltfScene::materials`; `None` draws with
+    /// `Material::default()`
+    pub material: Option<usize>,
+    /// Bounds of the vertices, in the space of the node
+    pub bounds: Aabb,
+}
+
+/// Mesh of a glTF file, instanced by the nodes that name it
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+}
+
+/// Node of the transform hierarchy
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: Option<String>,
+    /// Transform relative to the parent
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    /// Index into `GltfScene::meshes`
+    pub mesh: Option<usize>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl Node {
+    /// Transform from the node's space to its parent's: scale, then
+    /// rotation, then translation
+    pub fn local_transform(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * self.rotation.to_mat4()
+            * Mat4::from_scale(self.scale)
+    }
+}
+
+/// Resources and hierarchy loaded from a glTF file
+///
+/// Indices into the vectors are those of the file. Call `destroy` to free
+/// the GPU resources.
+#[derive(Debug, Default)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<Material>,
+    pub textures: Vec<GltfTexture>,
+    pub nodes: Vec<Node>,
+    /// Root nodes of the default scene (else of the first), in order
+    pub roots: Vec<usize>,
+    /// Each image and sampler once, however many textures share it
+    images: Vec<TextureHandle>,
+    samplers: Vec<SamplerHandle>,
+}
+
+impl GltfScene {
+    /// Load a `.gltf` file and the buffers and images next to it, or a
+    /// `.glb` file
+    pub fn load(device: &mut dyn GpuDevice, path: impl AsRef<Path>) -> Result<Self, GltfError> {
+        let (document, buffers, images) = gltf::import(path)?;
+        Self::create(device, &document, &buffers, &images)
+    }
+
+    /// Load glTF or GLB data held in memory; buffers and images must be
+    /// embedded
+    pub fn load_slice(device: &mut dyn GpuDevice, data: &[u8]) -> Result<Self, GltfError> {
+        let (document, buffers, images) = gltf::import_slice(data)?;
+        Self::create(device, &document, &buffers, &images)
+    }
+
+    /// Transform of every node from its space to the scene's, by index
+    pub fn world_transforms(&self) -> Vec<Mat4> {
+        let mut world = vec![Mat4::IDENTITY; self.nodes.len()];
+        let mut stack: Vec<(usize, Mat4)> = (0..self.nodes.len())
+            .filter(|&node| self.nodes[node].parent.is_none())
+            .map(|node| (node, Mat4::IDENTITY))
+            .collect();
+        while let Some((node, parent)) = stack.pop() {
+            world[node] = parent * self.nodes[node].local_transform();
+            for &child in &self.nodes[node].children {
+                stack.push((child, world[node]));
+            }
+        }
+        world
+    }
+
+    /// Destroy the buffers, textures and samplers
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        for mesh in self.meshes {
+            for primitive in mesh.primitives {
+                primitive.mesh.destroy(device);
+            }
+        }
+        for texture in self.images {
+            device.destroy_texture(texture);
+        }
+        for sampler in self.samplers {
+            device.destroy_sampler(sampler);
+        }
+    }
+
+    fn create(
+        device: &mut dyn GpuDevice,
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> Result<Self, GltfError> {
+        let mut scene = Self::default();
+        match scene.fill(device, document, buffers, images) {
+            Ok(()) => Ok(scene),
+            Err(err) => {
+                scene.destroy(device);
+                Err(err)
+            }
+        }
+    }
+
+    fn fill(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> Result<(), GltfError> {
+        self.materials = document.materials().map(|m| material(&m)).collect();
+
+        // Textures read as color are sRGB
+        let mut srgb = vec![false; document.textures().len()];
+        for material in &self.materials {
+            for texture in [material.base_color_texture, material.emissive_texture]
+                .into_iter()
+                .flatten()
+            {
+                srgb[texture.texture] = true;
+            }
+        }
+        let mut created_images = HashMap::new();
+        let mut created_samplers = HashMap::new();
+        for texture in document.textures() {
+            let source = texture.source().index();
+            let is_srgb = srgb[texture.index()];
+            let image = match created_images.get(&(source, is_srgb)) {
+                Some(&image) => image,
+                None => {
+                    let data = images.get(source).ok_or_else(|| {
+                        GltfError::Unsupported(format!("image {} was not decoded", source))
+                    })?;
+                    let image = create_image(device, data, is_srgb)?;
+                    self.images.push(image);
+                    created_images.insert((source, is_srgb), image);
+                    image
+                }
+            };
+            let gltf_sampler = texture.sampler();
+            let sampler = match created_samplers.get(&gltf_sampler.index()) {
+                Some(&sampler) => sampler,
+                None => {
+                    let sampler = device.create_sampler(&sampler_desc(&gltf_sampler))?;
+                    self.samplers.push(sampler);
+                    created_samplers.insert(gltf_sampler.index(), sampler);
+                    sampler
+                }
+            };
+            self.textures.push(GltfTexture {
+                texture: image,
+                sampler,
+            });
+        }
+
+        for gltf_mesh in document.meshes() {
+            let mut mesh = GltfMesh {
+                name: gltf_mesh.name().map(str::to_owned),
+                primitives: Vec::new(),
+            };
+            for primitive in gltf_mesh.primitives() {
+                let Some(cpu) = primitive_mesh(&primitive, buffers)? else {
+                    continue;
+                };
+                mesh.primitives.push(Primitive {
+                    mesh: cpu.upload(device)?,
+                    material: primitive.material().index(),
+                    bounds: cpu.bounds(),
+                });
+            }
+            self.meshes.push(mesh);
+        }
+
+        self.nodes = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                let [x, y, z, w] = rotation;
+                Node {
+                    name: node.name().map(str::to_owned),
+                    translation: vec3(translation),
+                    rotation: Quat::from_xyzw(x, y, z, w),
+                    scale: vec3(scale),
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    parent: None,
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect();
+        for node in 0..self.nodes.len() {
+            for child in self.nodes[node].children.clone() {
+                self.nodes[child].parent = Some(node);
+            }
+        }
+        if let Some(scene) = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            self.roots = scene.nodes().map(|node| node.index()).collect();
+        }
+        Ok(())
+    }
+}
+
+fn material(material: &gltf::Material) -> Material {
+    let texture_ref = |info: gltf::texture::Info| TextureRef {
+        texture: info.texture().index(),
+        tex_coord: info.tex_coord(),
+    };
+    let pbr = material.pbr_metallic_roughness();
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+    Material {
+        name: material.name().map(str::to_owned),
+        base_color_factor: vec4(pbr.base_color_factor()),
+        base_color_texture: pbr.base_color_texture().map(texture_ref),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr.metallic_roughness_texture().map(texture_ref),
+        normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+        normal_texture: normal.map(|normal| TextureRef {
+            texture: normal.texture().index(),
+            tex_coord: normal.tex_coord(),
+        }),
+        occlusion_strength: occlusion
+            .as_ref()
+            .map_or(1.0, |occlusion| occlusion.strength()),
+        occlusion_texture: occlusion.map(|occlusion| TextureRef {
+            texture: occlusion.texture().index(),
+            tex_coord: occlusion.tex_coord(),
+        }),
+        emissive_factor: vec3(material.emissive_factor()),
+        emissive_texture: material.emissive_texture().map(texture_ref),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => {
+                AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+            }
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        },
+        double_sided: material.double_sided(),
+    }
+}
+
+/// Triangle list of a primitive; `None` for points and lines
+fn primitive_mesh(
+    primitive: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+) -> Result<Option<Mesh>, GltfError> {
+    let mode = primitive.mode();
+    if !matches!(
+        mode,
+        gltf::mesh::Mode::Triangles
+            | gltf::mesh::Mode::TriangleStrip
+            | gltf::mesh::Mode::TriangleFan
+    ) {
+        eprintln!("Skipped a glTF primitive of {:?}", mode);
+        return Ok(None);
+    }
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+    let positions: Vec<Vec3> = reader
+        .read_positions()
+        .ok_or_else(|| GltfError::Unsupported("primitive without positions".to_string()))?
+        .map(vec3)
+        .collect();
+    let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+        Some(uvs) => uvs.into_f32().collect(),
+        None => vec![[0.0; 2]; positions.len()],
+    };
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    if uvs.len() != positions.len() || indices.iter().any(|&i| i as usize >= positions.len()) {
+        return Err(GltfError::Unsupported(
+            "primitive attributes or indices out of range".to_string(),
+        ));
+    }
+    let indices = match mode {
+        gltf::mesh::Mode::TriangleStrip => (0..indices.len().saturating_sub(2))
+            .flat_map(|i| match i % 2 {
+                0 => [indices[i], indices[i + 1], indices[i + 2]],
+                _ => [indices[i + 1], indices[i], indices[i + 2]],
+            })
+            .collect(),
+        gltf::mesh::Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+            .flat_map(|i| [indices[0], indices[i], indices[i + 1]])
+            .collect(),
+        _ => indices,
+    };
+
+    let mut mesh = match reader.read_normals() {
+        Some(normals) => {
+            let normals: Vec<Vec3> = normals.map(vec3).collect();
+            if normals.len() != positions.len() {
+                return Err(GltfError::Unsupported(
+                    "primitive normals out of range".to_string(),
+                ));
+            }
+            Mesh {
+                vertices: (0..positions.len())
+                    .map(|i| Vertex::new(positions[i], normals[i], uvs[i]))
+                    .collect(),
+                indices,
+            }
+        }
+        // Flat normals: each triangle gets vertices of its own
+        None => {
+            let mut vertices = Vec::with_capacity(indices.len());
+            for triangle in indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| positions[triangle[k] as usize]);
+                let normal = (b - a).cross(c - a).normalize();
+                vertices.extend(
+                    triangle
+                        .iter()
+                        .map(|&i| Vertex::new(positions[i as usize], normal, uvs[i as usize])),
+                );
+            }
+            Mesh {
+                indices: (0..vertices.len() as u32).collect(),
+                vertices,
+            }
+        }
+    };
+
+    match reader.read_tangents() {
+        Some(tangents) if primitive.get(&gltf::Semantic::Normals).is_some() => {
+            for (vertex, tangent) in mesh.vertices.iter_mut().zip(tangents) {
+                vertex.tangent = vec4(tangent);
+            }
+        }
+        _ => mesh.compute_tangents(),
+    }
+    Ok(Some(mesh))
+}
+
+/// Mipmapped RGBA8 texture of a decoded image
+fn create_image(
+    device: &mut dyn GpuDevice,
+    image: &gltf::image::Data,
+    srgb: bool,
+) -> Result<TextureHandle, GfxError> {
+    use gltf::image::Format;
+    let texels = image.width as usize * image.height as usize;
+    let (channels, bytes) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    if image.pixels.len() != texels * channels * bytes {
+        return Err(GfxError::InvalidDesc(format!(
+            "{} bytes of pixels for a {}x{} {:?} image",
+            image.pixels.len(),
+            image.width,
+            image.height,
+            image.format
+        )));
+    }
+    let channel = |texel: &[u8], c: usize| -> u8 {
+        let value = &texel[c * bytes..(c + 1) * bytes];
+        match bytes {
+            1 => value[0],
+            // Little-endian: the high byte comes second
+            2 => value[1],
+            _ => {
+                let value = f32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            }
+        }
+    };
+    let mut rgba = Vec::with_capacity(texels * 4);
+    for texel in image.pixels.chunks_exact(channels * bytes) {
+        let rgba_texel = match channels {
+            1 => {
+                let r = channel(texel, 0);
+                [r, r, r, 255]
+            }
+            2 => [channel(texel, 0), channel(texel, 1), 0, 255],
+            3 => [channel(texel, 0), channel(texel, 1), channel(texel, 2), 255],
+            _ => [0, 1, 2, 3].map(|c| channel(texel, c)),
+        };
+        rgba.extend_from_slice(&rgba_texel);
+    }
+    let format = if srgb {
+        TextureFormat::Rgba8Srgb
+    } else {
+        TextureFormat::Rgba8
+    };
+    let mip_levels = u32::BITS - image.width.max(image.height).leading_zeros();
+    let desc = TextureDesc::new_2d(image.width, image.height, format, TextureUsage::SAMPLED)
+        .with_mips(mip_levels);
+    device.create_texture(&desc, Some(&rgba))
+}
+
+fn sampler_desc(sampler: &gltf::texture::Sampler) -> SamplerDesc {
+    use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+    let address = |mode| match mode {
+        WrappingMode::Repeat => AddressMode::Repeat,
+        WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+    };
+    // Filters the file leaves open are linear
+    let (min_filter, mip_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            (FilterMode::Nearest, FilterMode::Nearest)
+        }
+        Some(MinFilter::NearestMipmapLinear) => (FilterMode::Nearest, FilterMode::Linear),
+        Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapNearest) => {
+            (FilterMode::Linear, FilterMode::Nearest)
+        }
+        Some(MinFilter::LinearMipmapLinear) | None => (FilterMode::Linear, FilterMode::Linear),
+    };
+    SamplerDesc {
+        mag_filter: match sampler.mag_filter() {
+            Some(MagFilter::Nearest) => FilterMode::Nearest,
+            Some(MagFilter::Linear) | None => FilterMode::Linear,
+        },
+        min_filter,
+        mip_filter,
+        address_u: address(sampler.wrap_s()),
+        address_v: address(sampler.wrap_t()),
+        address_w: AddressMode::Repeat,
+        ..SamplerDesc::default()
+    }
+}
+
+fn vec3([x, y, z]: [f32; 3]) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+fn vec4([x, y, z, w]: [f32; 4]) -> Vec4 {
+    Vec4::new(x, y, z, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{Backend, RendererConfig};
+    use crate::gfx::backend::create_device;
+    use crate::gfx::png;
+
+    const PIXELS: [u8; 16] = [
+        255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 128,
+    ];
+
+    /// GLB with a textured triangle under a node hierarchy; the triangle
+    /// has no normals
+    fn glb() -> Vec<u8> {
+        let mut bin = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [0.0f32, 1.0, 1.0, 1.0, 0.0, 0.0] {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2, 0] {
+            bin.extend_from_slice(&index.to_le_bytes());
+        }
+        let image = png::encode_rgba8(2, 2, &PIXELS);
+        let image_offset = bin.len();
+        bin.extend_from_slice(&image);
+        bin.resize(bin.len().next_multiple_of(4), 0);
+
+        let mut json = format!(
+            r#"{{
+                "asset": {{"version": "2.0"}},
+                "scene": 0,
+                "scenes": [{{"nodes": [0]}}],
+                "nodes": [
+                    {{"name": "parent", "translation": [1, 0, 0], "children": [1]}},
+                    {{"name": "child", "scale": [2, 2, 2], "mesh": 0}}
+                ],
+                "meshes": [{{"name": "triangle", "primitives": [{{
+                    "attributes": {{"POSITION": 0, "TEXCOORD_0": 1}},
+                    "indices": 2,
+                    "material": 0
+                }}]}}],
+                "materials": [{{
+                    "pbrMetallicRoughness": {{
+                        "baseColorTexture": {{"index": 0}},
+                        "metallicFactor": 0.25
+                    }},
+                    "alphaMode": "MASK",
+                    "alphaCutoff": 0.3
+                }}],
+                "textures": [{{"source": 0, "sampler": 0}}],
+                "samplers": [{{"magFilter": 9728, "wrapS": 33071}}],
+                "images": [{{"bufferView": 3, "mimeType": "image/png"}}],
+                "accessors": [
+                    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                      "min": [0, 0, 0], "max": [1, 1, 0]}},
+                    {{"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2"}},
+                    {{"bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+                ],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                    {{"buffer": 0, "byteOffset": 36, "byteLength": 24}},
+                    {{"buffer": 0, "byteOffset": 60, "byteLength": 6}},
+                    {{"buffer": 0, "byteOffset": {}, "byteLength": {}}}
+                ],
+                "buffers": [{{"byteLength": {}}}]
+            }}"#,
+            image_offset,
+            image.len(),
+            bin.len()
+        );
+        while !json.len().is_multiple_of(4) {
+            json.push(' ');
+        }
+
+        let mut glb = Vec::new();
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(json.as_bytes());
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&bin);
+        glb
+    }
+
+    #[test]
+    fn test_load_glb() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let scene = GltfScene::load_slice(&mut device, &glb()).unwrap();
+
+        // Hierarchy
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes[1].name.as_deref(), Some("child"));
+        assert_eq!(scene.nodes[1].parent, Some(0));
+        assert_eq!(scene.nodes[0].children, vec![1]);
+        let world = scene.world_transforms();
+        let corner = world[1].transform_point3(Vec3::new(1.0, 0.0, 0.0));
+        assert!((corner - Vec3::new(3.0, 0.0, 0.0)).length() < 1e-5);
+
+        // Mesh, with flat normals computed
+        assert_eq!(scene.meshes.len(), 1);
+        let primitive = scene.meshes[0].primitives[0];
+        assert_eq!(primitive.mesh.index_count, 3);
+        assert_eq!(primitive.material, Some(0));
+        assert_eq!(primitive.bounds.max, Vec3::new(1.0, 1.0, 0.0));
+        let vertices = device.map_buffer(primitive.mesh.vertex_buffer);
+        let normal = unsafe { std::slice::from_raw_parts(vertices.add(12) as *const f32, 3) };
+        assert_eq!(normal, &[0.0, 0.0, 1.0]);
+        device.unmap_buffer(primitive.mesh.vertex_buffer);
+
+        // Material and its texture
+        let material = &scene.materials[0];
+        assert_eq!(material.metallic_factor, 0.25);
+        assert_eq!(material.roughness_factor, 1.0);
+        assert_eq!(material.alpha_mode, AlphaMode::Mask(0.3));
+        assert_eq!(
+            material.base_color_texture,
+            Some(TextureRef {
+                texture: 0,
+                tex_coord: 0
+            })
+        );
+        assert_eq!(scene.textures.len(), 1);
+
+        scene.destroy(&mut device);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        assert!(matches!(
+            GltfScene::load_slice(&mut device, b"not glTF"),
+            Err(GltfError::Import(_))
+        ));
+        assert!(matches!(
+            GltfScene::load(&mut device, "/nonexistent/scene.gltf"),
+            Err(GltfError::Import(_))
+        ));
+    }
+}
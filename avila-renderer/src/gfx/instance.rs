@@ -0,0 +1,230 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Per-instance vertex data
+//!
+//! `InstanceBuffer` collects one plain-data value per instance on the CPU
+//! each frame and uploads them to a vertex buffer that grows as needed. A
+//! pipeline reads it through a `VertexLayout` buffer with
+//! `VertexStepMode::Instance`, bound at the slot after the mesh's:
+//!
+//! ```ignore
+//! let layout = Mesh::vertex_layout()
+//!     .with_instance_buffer(InstanceData::STRIDE, InstanceData::attributes(4));
+//!
+//! instances.clear();
+//! instances.extend(trees.iter().map(|tree| InstanceData::new(tree.transform, tree.tint)));
+//! instances.upload(&mut device)?;
+//! instances.bind(&mut cmd, 1);
+//! mesh.draw(&mut cmd, instances.uploaded());
+//! ```
+
+use crate::gfx::api::{
+    BufferDesc, BufferHandle, CommandList, GfxError, GpuDevice, VertexAttribute, VertexFormat,
+};
+use avila_math::Mat4;
+use bytemuck::{Pod, Zeroable};
+use std::mem::size_of;
+
+/// Instances the first buffer has room for
+const MIN_CAPACITY: usize = 64;
+
+/// Transform and color of one instance, the usual per-instance data
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InstanceData {
+    /// Model matrix, column by column
+    pub transform: [[f32; 4]; 4],
+    /// Linear RGBA tint
+    pub color: [f32; 4],
+}
+
+// SAFETY: `repr(C)` floats only, so no padding, and all zeros is valid
+unsafe impl Zeroable for InstanceData {}
+unsafe impl Pod for InstanceData {}
+
+impl InstanceData {
+    /// Bytes per instance
+    pub const STRIDE: u32 = size_of::<Self>() as u32;
+
+    pub fn new(transform: Mat4, color: [f32; 4]) -> Self {
+        Self {
+            transform: bytemuck::cast(transform.to_cols_array()),
+            color,
+        }
+    }
+
+    /// The transform's columns at `first_location` to `first_location + 3`
+    /// and the color at `first_location + 4`, all `Float4`
+    pub fn attributes(first_location: u32) -> Vec<VertexAttribute> {
+        (0..5)
+            .map(|i| VertexAttribute {
+                format: VertexFormat::Float4,
+                offset: i * 16,
+                location: first_location + i,
+            })
+            .collect()
+    }
+}
+
+/// Per-instance values staged on the CPU and uploaded to a vertex buffer
+///
+/// Fill it every frame, `upload` it, and bind it at the slot of the
+/// pipeline's per-instance buffer. The buffer is only replaced, at twice
+/// the size, when the instances outgrow it. Call `destroy` before dropping.
+pub struct InstanceBuffer<T: Pod> {
+    instances: Vec<T>,
+    buffer: BufferHandle,
+    /// Instances `buffer` has room for
+    capacity: usize,
+    /// Instances uploaded last
+    uploaded: usize,
+}
+
+impl<T: Pod> Default for InstanceBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Pod> InstanceBuffer<T> {
+    /// Empty buffer; nothing is created on the device before `upload`
+    pub fn new() -> Self {
+        Self {
+            instances: Vec::new(),
+            buffer: BufferHandle::INVALID,
+            capacity: 0,
+            uploaded: 0,
+        }
+    }
+
+    /// Bytes per instance, the stride of its `VertexLayout` buffer
+    pub fn stride() -> u32 {
+        size_of::<T>() as u32
+    }
+
+    pub fn push(&mut self, instance: T) {
+        self.instances.push(instance);
+    }
+
+    pub fn extend(&mut self, instances: impl IntoIterator<Item = T>) {
+        self.instances.extend(instances);
+    }
+
+    /// Drop the staged instances, to fill the next frame's
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    /// Instances staged for the next `upload`
+    pub fn instances(&self) -> &[T] {
+        &self.instances
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Write the staged instances to the device, growing the buffer first
+    /// when they do not fit
+    pub fn upload(&mut self, device: &mut dyn GpuDevice) -> Result<(), GfxError> {
+        if self.instances.len() > self.capacity {
+            let capacity = self.instances.len().next_power_of_two().max(MIN_CAPACITY);
+            let buffer =
+                device.create_buffer(&BufferDesc::vertex(capacity * size_of::<T>()), None)?;
+            if self.buffer != BufferHandle::INVALID {
+                device.destroy_buffer(self.buffer);
+            }
+            self.buffer = buffer;
+            self.capacity = capacity;
+        }
+        if !self.instances.is_empty() {
+            device.update_buffer(self.buffer, 0, bytemuck::cast_slice(&self.instances));
+        }
+        self.uploaded = self.instances.len();
+        Ok(())
+    }
+
+    /// Instances the last `upload` wrote, the `instance_count` to draw
+    pub fn uploaded(&self) -> u32 {
+        self.uploaded as u32
+    }
+
+    /// Device buffer, `BufferHandle::INVALID` before the first upload
+    pub fn buffer(&self) -> BufferHandle {
+        self.buffer
+    }
+
+    /// Bind the uploaded instances at vertex buffer `slot`
+    pub fn bind(&self, cmd: &mut CommandList, slot: u32) {
+        cmd.bind_vertex_buffer(slot, self.buffer, 0);
+    }
+
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        if self.buffer != BufferHandle::INVALID {
+            device.destroy_buffer(self.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::RendererConfig;
+    use crate::gfx::backend::{create_device, BackendDevice};
+
+    fn counts(device: &BackendDevice) -> (usize, usize) {
+        let stats = &device.memory_stats()[0];
+        (stats.allocation_count, stats.deallocation_count)
+    }
+
+    #[test]
+    fn test_instance_data_layout() {
+        let transform = Mat4::from_translation(avila_math::Vec3::new(1.0, 2.0, 3.0));
+        let data = InstanceData::new(transform, [0.5, 0.25, 1.0, 1.0]);
+        assert_eq!(InstanceData::STRIDE, 80);
+        assert_eq!(InstanceBuffer::<InstanceData>::stride(), 80);
+        assert_eq!(data.transform[3], [1.0, 2.0, 3.0, 1.0]);
+
+        let attributes = InstanceData::attributes(4);
+        assert_eq!(attributes.len(), 5);
+        assert_eq!((attributes[4].location, attributes[4].offset), (8, 64));
+    }
+
+    #[test]
+    fn test_upload_grows() {
+        let mut device = create_device(RendererConfig::default());
+        let mut instances = InstanceBuffer::<[f32; 4]>::new();
+
+        // Nothing to upload creates nothing
+        instances.upload(&mut device).unwrap();
+        assert_eq!(instances.buffer(), BufferHandle::INVALID);
+        assert_eq!(counts(&device), (0, 0));
+
+        instances.extend((0..10).map(|i| [i as f32; 4]));
+        instances.upload(&mut device).unwrap();
+        let first = instances.buffer();
+        assert_eq!(instances.uploaded(), 10);
+
+        // Fewer instances reuse the buffer
+        instances.clear();
+        instances.push([1.0; 4]);
+        instances.upload(&mut device).unwrap();
+        assert_eq!((instances.buffer(), instances.uploaded()), (first, 1));
+        assert_eq!(counts(&device), (1, 0));
+
+        // Outgrowing it replaces the buffer
+        instances.extend((0..100).map(|i| [i as f32; 4]));
+        instances.upload(&mut device).unwrap();
+        assert_ne!(instances.buffer(), first);
+        assert_eq!(instances.uploaded(), 101);
+        assert_eq!(counts(&device), (2, 1));
+
+        instances.destroy(&mut device);
+        assert_eq!(counts(&device), (2, 2));
+    }
+}
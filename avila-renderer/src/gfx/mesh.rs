@@ -0,0 +1,737 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Triangle meshes and procedural primitives
+//!
+//! `Mesh` is the CPU side of a mesh: vertices with the attributes lit and
+//! normal-mapped shading reads, and a triangle list. The generators build
+//! the usual primitives centered on the origin with Y up. Front faces wind
+//! counter-clockwise, the `RasterizerState` default, and texture
+//! coordinates start at the top left of the image, as in glTF.
+//! `Mesh::upload` copies a mesh into vertex and index buffers.
+
+use crate::gfx::api::{
+    BufferDesc, BufferHandle, CommandList, GfxError, GpuDevice, IndexType, VertexAttribute,
+    VertexFormat, VertexLayout,
+};
+use avila_math::{Aabb, Vec3, Vec4};
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+/// One vertex of a `Mesh`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub uv: [f32; 2],
+    /// Direction of increasing u; `w` is 1 or -1, and
+    /// `normal.cross(tangent.xyz) * w` points up the image (toward smaller
+    /// v), as in glTF
+    pub tangent: Vec4,
+}
+
+impl Vertex {
+    /// Vertex without a tangent yet; see `Mesh::compute_tangents`
+    pub fn new(position: Vec3, normal: Vec3, uv: [f32; 2]) -> Self {
+        Self {
+            position,
+            normal,
+            uv,
+            tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+        }
+    }
+
+    fn with_tangent(mut self, tangent: Vec3) -> Self {
+        self.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, 1.0);
+        self
+    }
+}
+
+/// Indexed triangle list
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    /// Three per triangle, counter-clockwise seen from the front
+    pub indices: Vec<u32>,
+}
+
+/// Buffers of a `Mesh` on the GPU
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GpuMesh {
+    pub vertex_buffer: BufferHandle,
+    /// `u32` indices
+    pub index_buffer: BufferHandle,
+    pub index_count: u32,
+}
+
+impl GpuMesh {
+    /// Bind the buffers, the vertices at slot 0, and draw the mesh
+    ///
+    /// Record it inside a render pass, with a pipeline whose vertex input
+    /// is `Mesh::vertex_layout`.
+    pub fn draw(&self, cmd: &mut CommandList, instance_count: u32) {
+        cmd.bind_vertex_buffer(0, self.vertex_buffer, 0);
+        cmd.bind_index_buffer(self.index_buffer, 0, IndexType::UInt32);
+        cmd.draw_indexed(self.index_count, instance_count, 0, 0, 0);
+    }
+
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        device.destroy_buffer(self.vertex_buffer);
+        device.destroy_buffer(self.index_buffer);
+    }
+}
+
+impl Mesh {
+    /// Bytes per vertex in `vertex_bytes`
+    pub const VERTEX_STRIDE: u32 = 48;
+
+    /// Layout of `vertex_bytes`: position, normal, uv and tangent at
+    /// locations 0 to 3
+    pub fn vertex_layout() -> VertexLayout {
+        let attributes = [
+            VertexFormat::Float3,
+            VertexFormat::Float3,
+            VertexFormat::Float2,
+            VertexFormat::Float4,
+        ]
+        .into_iter()
+        .enumerate()
+        .scan(0, |offset, (location, format)| {
+            let attribute = VertexAttribute {
+                format,
+                offset: *offset,
+                location: location as u32,
+            };
+            *offset += format.size();
+            Some(attribute)
+        })
+        .collect();
+        VertexLayout::new(Self::VERTEX_STRIDE, attributes)
+    }
+
+    /// Vertices packed as `vertex_layout` describes
+    pub fn vertex_bytes(&self) -> Vec<u8> {
+        self.vertices
+            .iter()
+            .flat_map(|v| {
+                let (p, n, t) = (v.position, v.normal, v.tangent);
+                [
+                    p.x, p.y, p.z, n.x, n.y, n.z, v.uv[0], v.uv[1], t.x, t.y, t.z, t.w,
+                ]
+            })
+            .flat_map(f32::to_le_bytes)
+            .collect()
+    }
+
+    pub fn index_bytes(&self) -> Vec<u8> {
+        self.indices.iter().flat_map(|i| i.to_le_bytes()).collect()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Box around the vertices; `Aabb::EMPTY` without any
+    pub fn bounds(&self) -> Aabb {
+        self.vertices.iter().fold(Aabb::EMPTY, |aabb, vertex| {
+            aabb.expand_to_include_point(vertex.position)
+        })
+    }
+
+    /// Create a vertex and an index buffer holding the mesh
+    pub fn upload(&self, device: &mut dyn GpuDevice) -> Result<GpuMesh, GfxError> {
+        let vertices = self.vertex_bytes();
+        let indices = self.index_bytes();
+        let vertex_buffer =
+            device.create_buffer(&BufferDesc::vertex(vertices.len()), Some(&vertices))?;
+        let index_buffer =
+            match device.create_buffer(&BufferDesc::index(indices.len()), Some(&indices)) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    device.destroy_buffer(vertex_buffer);
+                    return Err(err);
+                }
+            };
+        Ok(GpuMesh {
+            vertex_buffer,
+            index_buffer,
+            index_count: self.indices.len() as u32,
+        })
+    }
+
+    /// Derive the tangents from the positions, normals and texture
+    /// coordinates
+    ///
+    /// Each vertex averages the texture directions of its triangles, made
+    /// perpendicular to its normal. Vertices whose triangles have no usable
+    /// texture coordinates get any tangent perpendicular to the normal.
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::ZERO; self.vertices.len()];
+        // Direction of increasing v
+        let mut bitangents = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| self.vertices[triangle[k] as usize]);
+            let (e1, e2) = (b.position - a.position, c.position - a.position);
+            let (du1, dv1) = (b.uv[0] - a.uv[0], b.uv[1] - a.uv[1]);
+            let (du2, dv2) = (c.uv[0] - a.uv[0], c.uv[1] - a.uv[1]);
+            let det = du1 * dv2 - du2 * dv1;
+            if det.abs() < f32::EPSILON {
+                continue;
+            }
+            let tangent = (e1 * dv2 - e2 * dv1) / det;
+            let bitangent = (e2 * du1 - e1 * du2) / det;
+            for &index in triangle {
+                tangents[index as usize] = tangents[index as usize] + tangent;
+                bitangents[index as usize] = bitangents[index as usize] + bitangent;
+            }
+        }
+        for ((vertex, tangent), bitangent) in self.vertices.iter_mut().zip(tangents).zip(bitangents)
+        {
+            let normal = vertex.normal;
+            let mut t = (tangent - normal * normal.dot(tangent)).normalize();
+            if t == Vec3::ZERO {
+                t = perpendicular(normal);
+            }
+            // The bitangent of `tangent.w` points toward smaller v
+            let w = if normal.cross(t).dot(bitangent) > 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = Vec4::new(t.x, t.y, t.z, w);
+        }
+    }
+
+    // ========================================================================
+    // Primitives
+    // ========================================================================
+
+    /// Cube with edges of `size`; each face spans the whole texture
+    pub fn cube(size: f32) -> Self {
+        let half = size * 0.5;
+        let mut mesh = Self::default();
+        // Normal, then the directions of u and of up the image
+        let faces = [
+            (Vec3::X, -Vec3::Z, Vec3::Y),
+            (-Vec3::X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, -Vec3::Z),
+            (-Vec3::Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (-Vec3::Z, -Vec3::X, Vec3::Y),
+        ];
+        for (normal, u, up) in faces {
+            mesh.push_grid(normal * half, u * half, up * half, normal, 1);
+        }
+        mesh
+    }
+
+    /// `width` (along X) by `depth` (along Z) plane facing +Y, split into
+    /// `subdivisions` x `subdivisions` quads
+    ///
+    /// The top of the texture is toward -Z.
+    pub fn plane(width: f32, depth: f32, subdivisions: u32) -> Self {
+        let mut mesh = Self::default();
+        mesh.push_grid(
+            Vec3::ZERO,
+            Vec3::X * (width * 0.5),
+            -Vec3::Z * (depth * 0.5),
+            Vec3::Y,
+            subdivisions.max(1),
+        );
+        mesh
+    }
+
+    /// Sphere of `segments` slices around Y (at least 3) and `rings`
+    /// stacks from pole to pole (at least 2)
+    ///
+    /// u goes once around from +Z toward +X; v goes from the north pole to
+    /// the south pole.
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut mesh = Self::default();
+        for ring in 0..=rings {
+            let v = ring as f32 / rings as f32;
+            let (sin_theta, cos_theta) = (v * PI).sin_cos();
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+                let normal = Vec3::new(sin_theta * sin_phi, cos_theta, sin_theta * cos_phi);
+                mesh.vertices.push(
+                    Vertex::new(normal * radius, normal, [u, v])
+                        .with_tangent(Vec3::new(cos_phi, 0.0, -sin_phi)),
+                );
+            }
+        }
+        // The quads touching the poles lose their degenerate half
+        mesh.push_grid_indices(0, segments, rings, |ring, half| {
+            !((ring == 0 && half == 1) || (ring == rings - 1 && half == 0))
+        });
+        mesh
+    }
+
+    /// Sphere from an icosahedron whose triangles are split in four
+    /// `subdivisions` times, for even triangle sizes
+    ///
+    /// Texture coordinates are those of `uv_sphere`; vertices on the seam
+    /// and at the poles are duplicated to follow them.
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let (positions, triangles) = icosahedron(subdivisions);
+        let spherical = |p: Vec3| {
+            let u = (p.x.atan2(p.z) / TAU).rem_euclid(1.0);
+            [u, p.y.clamp(-1.0, 1.0).acos() / PI]
+        };
+        let at_pole = |p: Vec3| p.x.abs() < 1e-6 && p.z.abs() < 1e-6;
+
+        let mut mesh = Self::default();
+        // Vertex of each position, and of its copy with u past 1
+        let mut copies = HashMap::new();
+        for triangle in triangles {
+            let corners = triangle.map(|index| positions[index as usize]);
+            let mut uvs = corners.map(spherical);
+            // A triangle across the seam takes the u of its corners near 0
+            // past 1
+            let off_pole = (0..3).filter(|&k| !at_pole(corners[k]));
+            let (min, max) = off_pole.clone().fold((1.0f32, 0.0f32), |(min, max), k| {
+                (min.min(uvs[k][0]), max.max(uvs[k][0]))
+            });
+            let wrapped = max - min > 0.5;
+            for k in off_pole {
+                if wrapped && uvs[k][0] < 0.5 {
+                    uvs[k][0] += 1.0;
+                }
+            }
+            for k in 0..3 {
+                let pole = at_pole(corners[k]);
+                if pole {
+                    // Any u fits the pole; take the middle of the others
+                    let others: f32 = (0..3).filter(|&o| o != k).map(|o| uvs[o][0]).sum();
+                    uvs[k][0] = others * 0.5;
+                }
+                let key = (triangle[k], uvs[k][0] >= 1.0);
+                let index = match copies.get(&key) {
+                    Some(&index) if !pole => index,
+                    _ => {
+                        let normal = corners[k];
+                        let (sin_phi, cos_phi) = (uvs[k][0] * TAU).sin_cos();
+                        mesh.vertices.push(
+                            Vertex::new(normal * radius, normal, uvs[k])
+                                .with_tangent(Vec3::new(cos_phi, 0.0, -sin_phi)),
+                        );
+                        let index = mesh.vertices.len() as u32 - 1;
+                        copies.insert(key, index);
+                        index
+                    }
+                };
+                mesh.indices.push(index);
+            }
+        }
+        mesh
+    }
+
+    /// Cylinder along Y, with `segments` sides (at least 3) and caps
+    ///
+    /// The side wraps the texture once around as `uv_sphere` does; each
+    /// cap holds the disc inscribed in the texture.
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let half = height * 0.5;
+        let mut mesh = Self::default();
+        for (y, v) in [(half, 0.0), (-half, 1.0)] {
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+                let normal = Vec3::new(sin_phi, 0.0, cos_phi);
+                mesh.vertices.push(
+                    Vertex::new(normal * radius + Vec3::Y * y, normal, [u, v])
+                        .with_tangent(Vec3::new(cos_phi, 0.0, -sin_phi)),
+                );
+            }
+        }
+        mesh.push_grid_indices(0, segments, 1, |_, _| true);
+        mesh.push_cap(half, radius, segments, Vec3::Y);
+        mesh.push_cap(-half, radius, segments, -Vec3::Y);
+        mesh
+    }
+
+    /// Cone along Y with its apex at the top, `segments` sides (at least
+    /// 3) and a base cap
+    ///
+    /// The side is textured as the cylinder's, with the apex at v = 0.
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+        let segments = segments.max(3);
+        let half = height * 0.5;
+        let mut mesh = Self::default();
+        let side = |u: f32| {
+            let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+            let out = Vec3::new(sin_phi, 0.0, cos_phi);
+            // Perpendicular to the slope from the rim to the apex
+            let normal = (out * height + Vec3::Y * radius).normalize();
+            (out, normal, Vec3::new(cos_phi, 0.0, -sin_phi))
+        };
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let (out, normal, tangent) = side(u);
+            mesh.vertices.push(
+                Vertex::new(out * radius - Vec3::Y * half, normal, [u, 1.0]).with_tangent(tangent),
+            );
+        }
+        // One apex per side, with the normal of the middle of the side
+        for segment in 0..segments {
+            let u = (segment as f32 + 0.5) / segments as f32;
+            let (_, normal, tangent) = side(u);
+            mesh.vertices
+                .push(Vertex::new(Vec3::Y * half, normal, [u, 0.0]).with_tangent(tangent));
+        }
+        for segment in 0..segments {
+            let apex = segments + 1 + segment;
+            mesh.indices.extend([segment, segment + 1, apex]);
+        }
+        mesh.push_cap(-half, radius, segments, -Vec3::Y);
+        mesh
+    }
+
+    /// Torus around Y: a tube of `minor_radius` whose center runs along a
+    /// circle of `major_radius`
+    ///
+    /// u goes around Y as on `uv_sphere`, with `major_segments` steps; v
+    /// goes around the tube from its top, outward first, with
+    /// `minor_segments` steps (both at least 3).
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> Self {
+        let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+        let mut mesh = Self::default();
+        for ring in 0..=minor_segments {
+            let v = ring as f32 / minor_segments as f32;
+            let (sin_theta, cos_theta) = (v * TAU).sin_cos();
+            for segment in 0..=major_segments {
+                let u = segment as f32 / major_segments as f32;
+                let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+                let out = Vec3::new(sin_phi, 0.0, cos_phi);
+                let normal = Vec3::Y * cos_theta + out * sin_theta;
+                mesh.vertices.push(
+                    Vertex::new(out * major_radius + normal * minor_radius, normal, [u, v])
+                        .with_tangent(Vec3::new(cos_phi, 0.0, -sin_phi)),
+                );
+            }
+        }
+        mesh.push_grid_indices(0, major_segments, minor_segments, |_, _| true);
+        mesh
+    }
+
+    /// Flat grid of `cells` x `cells` quads facing `normal`, centered on
+    /// `center` and reaching `u` to the right and `up` to the top
+    ///
+    /// `u.cross(up)` points along `normal`.
+    fn push_grid(&mut self, center: Vec3, u: Vec3, up: Vec3, normal: Vec3, cells: u32) {
+        let base = self.vertices.len() as u32;
+        let tangent = u.normalize();
+        for row in 0..=cells {
+            let t = row as f32 / cells as f32;
+            for column in 0..=cells {
+                let s = column as f32 / cells as f32;
+                let position = center + u * (2.0 * s - 1.0) + up * (1.0 - 2.0 * t);
+                self.vertices
+                    .push(Vertex::new(position, normal, [s, t]).with_tangent(tangent));
+            }
+        }
+        self.push_grid_indices(base, cells, cells, |_, _| true);
+    }
+
+    /// Two triangles per quad of a grid of `(columns + 1) * (rows + 1)`
+    /// vertices from `base`, row by row from the top of the texture
+    ///
+    /// `keep(row, half)` says whether the quads of a row get their
+    /// lower-right (0) and upper-left (1) triangles.
+    fn push_grid_indices(
+        &mut self,
+        base: u32,
+        columns: u32,
+        rows: u32,
+        keep: impl Fn(u32, u32) -> bool,
+    ) {
+        for row in 0..rows {
+            for column in 0..columns {
+                let top_left = base + row * (columns + 1) + column;
+                let bottom_left = top_left + columns + 1;
+                if keep(row, 0) {
+                    self.indices
+                        .extend([bottom_left, bottom_left + 1, top_left + 1]);
+                }
+                if keep(row, 1) {
+                    self.indices.extend([bottom_left, top_left + 1, top_left]);
+                }
+            }
+        }
+    }
+
+    /// Disc at height `y` facing `normal` (+Y or -Y), textured with the
+    /// disc inscribed in the texture
+    fn push_cap(&mut self, y: f32, radius: f32, segments: u32, normal: Vec3) {
+        // Up the image is toward -Z on the top cap, +Z on the bottom one
+        let flip = normal.y;
+        let center = self.vertices.len() as u32;
+        self.vertices
+            .push(Vertex::new(Vec3::Y * y, normal, [0.5, 0.5]).with_tangent(Vec3::X));
+        for segment in 0..segments {
+            let (sin_phi, cos_phi) = (segment as f32 / segments as f32 * TAU).sin_cos();
+            let uv = [0.5 + 0.5 * sin_phi, 0.5 + 0.5 * cos_phi * flip];
+            self.vertices.push(
+                Vertex::new(Vec3::new(sin_phi * radius, y, cos_phi * radius), normal, uv)
+                    .with_tangent(Vec3::X),
+            );
+        }
+        for segment in 0..segments {
+            let (a, b) = (center + 1 + segment, center + 1 + (segment + 1) % segments);
+            // Counter-clockwise from above on the top cap, from below on
+            // the bottom one
+            if flip > 0.0 {
+                self.indices.extend([center, a, b]);
+            } else {
+                self.indices.extend([center, b, a]);
+            }
+        }
+    }
+}
+
+/// Unit vectors of an icosahedron subdivided `subdivisions` times, and its
+/// triangles, counter-clockwise from outside
+fn icosahedron(subdivisions: u32) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    let phi = (1.0 + 5.0f32.sqrt()) * 0.5;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, phi, 0.0),
+        (1.0, phi, 0.0),
+        (-1.0, -phi, 0.0),
+        (1.0, -phi, 0.0),
+        (0.0, -1.0, phi),
+        (0.0, 1.0, phi),
+        (0.0, -1.0, -phi),
+        (0.0, 1.0, -phi),
+        (phi, 0.0, -1.0),
+        (phi, 0.0, 1.0),
+        (-phi, 0.0, -1.0),
+        (-phi, 0.0, 1.0),
+    ]
+    .into_iter()
+    .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    let mut triangles = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for _ in 0..subdivisions {
+        // Midpoint of each edge, shared by the triangles on both sides
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let middle = (positions[a as usize] + positions[b as usize]).normalize();
+                positions.push(middle);
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    (positions, triangles)
+}
+
+/// Some unit vector perpendicular to `normal`
+fn perpendicular(normal: Vec3) -> Vec3 {
+    let axis = if normal.x.abs() < 0.9 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    normal.cross(axis).cross(normal).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{Backend, RendererConfig};
+    use crate::gfx::backend::create_device;
+
+    fn primitives() -> Vec<(&'static str, Mesh)> {
+        vec![
+            ("cube", Mesh::cube(2.0)),
+            ("plane", Mesh::plane(2.0, 4.0, 3)),
+            ("uv_sphere", Mesh::uv_sphere(1.0, 24, 12)),
+            ("icosphere", Mesh::icosphere(1.0, 2)),
+            ("cylinder", Mesh::cylinder(1.0, 2.0, 16)),
+            ("cone", Mesh::cone(1.0, 2.0, 16)),
+            ("torus", Mesh::torus(1.0, 0.25, 24, 12)),
+        ]
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_primitives_face_outward() {
+        for (name, mesh) in primitives() {
+            assert_eq!(mesh.indices.len() % 3, 0, "{}", name);
+            assert!(mesh
+                .indices
+                .iter()
+                .all(|&i| (i as usize) < mesh.vertices.len()));
+            for vertex in &mesh.vertices {
+                let tangent = Vec3::new(vertex.tangent.x, vertex.tangent.y, vertex.tangent.z);
+                assert!(close(vertex.normal.length(), 1.0), "{}", name);
+                assert!(close(tangent.length(), 1.0), "{}", name);
+                assert!(vertex.normal.dot(tangent).abs() < 1e-4, "{}", name);
+                assert_eq!(vertex.tangent.w.abs(), 1.0, "{}", name);
+            }
+            // Counter-clockwise seen from the side the normals point to
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| mesh.vertices[triangle[k] as usize]);
+                let face = (b.position - a.position).cross(c.position - a.position);
+                assert!(face.length() > 1e-6, "{} has a degenerate triangle", name);
+                let normal = a.normal + b.normal + c.normal;
+                assert!(face.dot(normal) > 0.0, "{} faces inward", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_primitive_sizes() {
+        let bounds = |mesh: Mesh| {
+            let aabb = mesh.bounds();
+            [aabb.min, aabb.max].map(|v| [v.x, v.y, v.z])
+        };
+        assert_eq!(bounds(Mesh::cube(2.0)), [[-1.0; 3], [1.0; 3]]);
+        assert_eq!(
+            bounds(Mesh::plane(2.0, 4.0, 3)),
+            [[-1.0, 0.0, -2.0], [1.0, 0.0, 2.0]]
+        );
+        assert_eq!(Mesh::cube(1.0).vertices.len(), 24);
+        assert_eq!(Mesh::plane(1.0, 1.0, 3).triangle_count(), 18);
+
+        let sphere = Mesh::uv_sphere(2.0, 8, 4);
+        assert_eq!(sphere.vertices.len(), 9 * 5);
+        // The rings at the poles have one triangle per segment
+        assert_eq!(sphere.triangle_count(), 8 * 2 * 4 - 2 * 8);
+        let torus = Mesh::torus(1.0, 0.5, 8, 8);
+        assert_eq!(torus.triangle_count(), 8 * 8 * 2);
+        let [min, max] = bounds(torus);
+        assert!(close(max[0], 1.5) && close(min[1], -0.5) && close(max[1], 0.5));
+        for (mesh, radius) in [(sphere, 2.0), (Mesh::icosphere(2.0, 1), 2.0)] {
+            assert!(mesh
+                .vertices
+                .iter()
+                .all(|v| close(v.position.length(), radius)));
+        }
+        let [min, max] = bounds(Mesh::cone(1.0, 3.0, 12));
+        assert!(close(min[1], -1.5) && close(max[1], 1.5) && close(max[2], 1.0));
+    }
+
+    #[test]
+    fn test_icosphere_seam() {
+        // Unsubdivided, the poles lie on edges that span half the seam
+        for subdivisions in 1..4 {
+            let mesh = Mesh::icosphere(1.0, subdivisions);
+            assert_eq!(mesh.triangle_count(), 20 * 4usize.pow(subdivisions));
+            for triangle in mesh.indices.chunks_exact(3) {
+                let u = triangle.iter().map(|&i| mesh.vertices[i as usize].uv[0]);
+                let (min, max) = u.fold((f32::MAX, f32::MIN), |(min, max), u| {
+                    (min.min(u), max.max(u))
+                });
+                assert!(max - min < 0.5, "triangle across the seam");
+            }
+        }
+        // Shared vertices are not duplicated away from the seam and poles
+        assert!(Mesh::icosphere(1.0, 2).vertices.len() < 200);
+    }
+
+    #[test]
+    fn test_compute_tangents_matches_generators() {
+        for (name, mesh) in primitives() {
+            let mut computed = mesh.clone();
+            computed.compute_tangents();
+            for (generated, computed) in mesh.vertices.iter().zip(&computed.vertices) {
+                // Texture directions bend fast near the poles of spheres
+                if name.contains("sphere") && generated.normal.y.abs() > 0.9 {
+                    continue;
+                }
+                let (a, b) = (generated.tangent, computed.tangent);
+                assert!(
+                    Vec3::new(a.x, a.y, a.z).dot(Vec3::new(b.x, b.y, b.z)) > 0.9,
+                    "{}: {:?} vs {:?}",
+                    name,
+                    a,
+                    b
+                );
+                assert_eq!(a.w, b.w, "{}", name);
+            }
+        }
+
+        // The glTF convention: u right, v down, bitangent up
+        let mut quad = Mesh {
+            vertices: vec![
+                Vertex::new(Vec3::new(0.0, 0.0, 0.0), Vec3::Z, [0.0, 1.0]),
+                Vertex::new(Vec3::new(1.0, 0.0, 0.0), Vec3::Z, [1.0, 1.0]),
+                Vertex::new(Vec3::new(0.0, 1.0, 0.0), Vec3::Z, [0.0, 0.0]),
+            ],
+            indices: vec![0, 1, 2],
+        };
+        quad.compute_tangents();
+        assert_eq!(quad.vertices[0].tangent, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        quad.vertices
+            .iter_mut()
+            .for_each(|v| v.uv[1] = 1.0 - v.uv[1]);
+        quad.compute_tangents();
+        assert_eq!(quad.vertices[0].tangent.w, -1.0);
+    }
+
+    #[test]
+    fn test_upload() {
+        let layout = Mesh::vertex_layout();
+        assert_eq!(layout.stride, Mesh::VERTEX_STRIDE);
+        let offsets: Vec<_> = layout.attributes.iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 12, 24, 32]);
+
+        let mesh = Mesh::cube(1.0);
+        assert_eq!(
+            mesh.vertex_bytes().len(),
+            mesh.vertices.len() * Mesh::VERTEX_STRIDE as usize
+        );
+        let mut device = create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let gpu = mesh.upload(&mut device).unwrap();
+        assert_eq!(gpu.index_count, 36);
+        let mut cmd = CommandList::new();
+        gpu.draw(&mut cmd, 1);
+        assert_eq!(cmd.commands.len(), 3);
+        gpu.destroy(&mut device);
+    }
+}
@@ -5,8 +5,60 @@
 
 pub mod api;
 pub mod backend;
+pub mod camera;
+#[cfg(feature = "egui")]
+pub mod egui_backend;
 pub mod framegraph;
+#[cfg(feature = "gltf")]
+pub mod gltf_loader;
+#[cfg(feature = "instancing")]
+pub mod instance;
+pub mod mesh;
+pub mod pipeline_cache;
+pub mod png;
+pub mod post_process;
+pub mod profiler;
+pub mod reflection;
+pub mod render_target_pool;
+#[cfg(feature = "shader-compiler")]
+pub mod shader_compiler;
+pub mod shadow;
+#[cfg(feature = "texture-loader")]
+pub mod texture_loader;
+pub mod uniform_allocator;
+pub mod upload;
 
 pub use api::*;
-pub use backend::create_device;
-pub use framegraph::{FrameGraphBuilder, CompiledFrameGraph};
+pub use backend::{create_device, create_device_with_window, enumerate_adapters};
+pub use camera::{Camera, Frustum, Plane, Projection, Ray};
+#[cfg(feature = "egui")]
+pub use egui_backend::{EguiInput, EguiRenderer};
+pub use framegraph::{FrameGraphBuilder, CompiledFrameGraph, FrameGraphError};
+#[cfg(feature = "gltf")]
+pub use gltf_loader::{
+    AlphaMode, GltfError, GltfMesh, GltfScene, GltfTexture, Material, Node, Primitive, TextureRef,
+};
+#[cfg(feature = "instancing")]
+pub use instance::{InstanceBuffer, InstanceData};
+pub use mesh::{GpuMesh, Mesh, Vertex};
+pub use pipeline_cache::{PipelineCache, PipelineCacheStats};
+pub use post_process::{
+    BloomSettings, PostProcessSettings, PostProcessStack, Tonemapper, VignetteSettings,
+};
+pub use profiler::GpuProfiler;
+pub use render_target_pool::{RenderTargetPool, RenderTargetPoolStats};
+pub use reflection::{
+    PushConstantRange, ReflectedBinding, ReflectedVertexInput, ScalarKind, ShaderReflection,
+};
+pub use shadow::{
+    add_shadow_pass, cascade_splits, fit_cascades, fit_light_projection, CascadeSettings,
+    ShadowCascade,
+};
+pub use uniform_allocator::{DynamicUniformAllocator, UniformSlice};
+pub use upload::UploadContext;
+#[cfg(feature = "shader-compiler")]
+pub use shader_compiler::{
+    ShaderCompileError, ShaderCompiler, ShaderDiagnostic, ShaderLanguage,
+};
+#[cfg(feature = "texture-loader")]
+pub use texture_loader::{TextureData, TextureLoadError, TextureLoader};
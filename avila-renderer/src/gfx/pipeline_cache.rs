@@ -0,0 +1,114 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pipeline cache
+//!
+//! Creating a pipeline compiles its shaders for the GPU, which can take long
+//! enough to drop frames. `PipelineCache` creates one pipeline per distinct
+//! `PipelineDesc`, and stores what the backend compiled on disk so the next
+//! run starts warm.
+
+use crate::gfx::api::{GfxError, GpuDevice, PipelineDesc, PipelineHandle};
+use avila_math::os::FileSystem;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Lookups a `PipelineCache` answered so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PipelineCacheStats {
+    /// `get_or_create` calls that returned a cached pipeline
+    pub hits: u64,
+    /// `get_or_create` calls that created a pipeline
+    pub misses: u64,
+    /// Pipelines held
+    pub pipelines: usize,
+}
+
+impl PipelineCacheStats {
+    /// Share of lookups that were hits, 0 before the first
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Pipelines by description
+///
+/// The cache owns the pipelines it creates: free them with `clear` rather
+/// than `destroy_pipeline`. Descriptions name shaders by handle, so keep a
+/// shader alive while pipelines made from it are cached.
+#[derive(Default)]
+pub struct PipelineCache {
+    pipelines: HashMap<PipelineDesc, PipelineHandle>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pipeline for `desc`, created the first time it is asked for
+    ///
+    /// Failed creations are not cached.
+    pub fn get_or_create(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        desc: &PipelineDesc,
+    ) -> Result<PipelineHandle, GfxError> {
+        if let Some(&handle) = self.pipelines.get(desc) {
+            self.hits += 1;
+            return Ok(handle);
+        }
+        self.misses += 1;
+        let handle = device.create_pipeline(desc)?;
+        self.pipelines.insert(desc.clone(), handle);
+        Ok(handle)
+    }
+
+    pub fn stats(&self) -> PipelineCacheStats {
+        PipelineCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            pipelines: self.pipelines.len(),
+        }
+    }
+
+    /// Destroy every cached pipeline; the hit and miss counts are kept
+    pub fn clear(&mut self, device: &mut dyn GpuDevice) {
+        for (_, handle) in self.pipelines.drain() {
+            device.destroy_pipeline(handle);
+        }
+    }
+
+    /// Seed the backend's pipeline cache from a file `save` wrote in an
+    /// earlier run
+    ///
+    /// Call it before creating pipelines. Returns false if the file does not
+    /// exist or was made by another backend, adapter or driver.
+    pub fn load(&mut self, device: &mut dyn GpuDevice, path: impl AsRef<Path>) -> io::Result<bool> {
+        let data = match FileSystem::read(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        Ok(device.load_pipeline_cache_data(&data))
+    }
+
+    /// Write the backend's pipeline cache to `path` for the next run
+    ///
+    /// Writes nothing on backends without a pipeline cache.
+    pub fn save(&self, device: &mut dyn GpuDevice, path: impl AsRef<Path>) -> io::Result<()> {
+        let data = device.pipeline_cache_data();
+        if data.is_empty() {
+            return Ok(());
+        }
+        FileSystem::write_atomic(path, data)
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Minimal PNG encoder for screenshots
+//!
+//! Writes 8-bit RGBA images with stored (uncompressed) deflate blocks. The
+//! files are larger than a real compressor would make them, but encoding
+//! needs no dependency and the pixels round-trip exactly, which is what
+//! image-diff tests want.
+
+use avila_math::os::hash::Crc32;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest stored deflate block
+const MAX_STORED_BLOCK: usize = 65535;
+
+/// Encode tightly packed RGBA8 rows, top row first, as a PNG file
+///
+/// # Panics
+///
+/// If `pixels` is not `width * height * 4` bytes long.
+pub fn encode_rgba8(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let row_bytes = width as usize * 4;
+    assert_eq!(
+        pixels.len(),
+        row_bytes * height as usize,
+        "pixel data does not match a {}x{} RGBA8 image",
+        width,
+        height
+    );
+
+    // Every row starts with its filter type; 0 leaves the bytes as they are
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    if row_bytes > 0 {
+        for row in pixels.chunks_exact(row_bytes) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+    }
+
+    let mut png = Vec::with_capacity(raw.len() + raw.len() / MAX_STORED_BLOCK * 5 + 64);
+    png.extend_from_slice(&SIGNATURE);
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filters, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.finish().to_be_bytes());
+}
+
+/// Wrap `data` in a zlib stream of stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32 KiB window, no preset dictionary
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // A stream needs at least one (final, empty) block
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // 5552 bytes is the most that can be summed before `b` may overflow
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,935 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Post-processing stack
+//!
+//! `PostProcessStack` turns the scene color into the image on screen with a
+//! chain of fullscreen passes declared in a frame graph: bloom (a bright
+//! pass, then a mip chain blurred down and back up), tonemapping with an
+//! exposure, FXAA and a vignette. Which of them run is chosen when the
+//! stack is created; their parameters can change every frame.
+//!
+//! `RendererConfig::hdr` picks the path. With it the scene is rendered to
+//! `Rgba16f` and tonemapped, and the swapchain is `Rgba16f` too, so the
+//! last pass writes linear values. Without it the scene is `Rgba8`, already
+//! display-encoded, and the tonemap pass only adds the bloom. Intermediate
+//! `Rgba8` targets between the tonemap pass and the last one hold
+//! sRGB-encoded values.
+//!
+//! Every pass draws one triangle covering the target, with the position at
+//! location 0 and the texture coordinate at location 2. The parameters sit
+//! in a uniform buffer at set 0 and the inputs are textures at set 1,
+//! bindings 0 and 2. The software backend runs no shaders and samples only
+//! set 0, so there the passes fill their targets with white.
+
+use crate::gfx::api::{
+    AddressMode, Backend, BindGroupDesc, BindGroupEntry, BindGroupHandle, BindGroupLayoutDesc,
+    BindGroupLayoutEntry, BindGroupLayoutHandle, BindingResource, BindingType, BufferDesc,
+    BufferHandle, ClearColor, ColorAttachment, CullMode, DepthStencilState, GfxError, GpuDevice,
+    LoadOp, PipelineDesc, PipelineHandle, PipelineLayoutDesc, PrimitiveTopology, RasterizerState,
+    RenderPassDesc, SamplerDesc, SamplerHandle, ShaderDesc, ShaderHandle, ShaderStage, StoreOp,
+    TextureBinding, TextureDesc, TextureFormat, TextureUsage, VertexAttribute, VertexFormat,
+    VertexLayout,
+};
+use crate::gfx::backend::BackendDevice;
+use crate::gfx::framegraph::{FrameGraphBuilder, PassId, ResourceId};
+use std::collections::HashMap;
+
+/// Most bloom mip levels a stack blurs through
+pub const MAX_BLOOM_LEVELS: u32 = 8;
+
+/// Bytes of the parameter block: three `vec4`s
+const PARAMS_SIZE: usize = 48;
+
+/// Curve mapping HDR color to the displayable range
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tonemapper {
+    /// `c / (1 + c)`: soft, keeps hues, washes out highlights
+    Reinhard,
+    /// Fit of the ACES filmic curve: more contrast, saturated highlights
+    #[default]
+    Aces,
+}
+
+/// Glow around bright areas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BloomSettings {
+    /// Brightness above which color starts to bloom, with a soft knee
+    pub threshold: f32,
+    /// Weight of the blurred bright color added to the scene
+    pub intensity: f32,
+    /// Half-resolution steps of the blur, 1 to `MAX_BLOOM_LEVELS`; more
+    /// spreads the glow wider
+    pub levels: u32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 0.05,
+            levels: 5,
+        }
+    }
+}
+
+/// Darkening towards the corners
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VignetteSettings {
+    /// How dark the corners get, 0 to 1
+    pub intensity: f32,
+    /// Distance from the center, 0 to 1 at the corners, where darkening
+    /// starts
+    pub radius: f32,
+    /// Distance over which it reaches full intensity
+    pub smoothness: f32,
+}
+
+impl Default for VignetteSettings {
+    fn default() -> Self {
+        Self {
+            intensity: 0.3,
+            radius: 0.6,
+            smoothness: 0.4,
+        }
+    }
+}
+
+/// Passes of a `PostProcessStack` and their parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostProcessSettings {
+    /// Scale applied to HDR color before tonemapping
+    pub exposure: f32,
+    pub tonemapper: Tonemapper,
+    pub bloom: Option<BloomSettings>,
+    pub fxaa: bool,
+    pub vignette: Option<VignetteSettings>,
+}
+
+impl Default for PostProcessSettings {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            tonemapper: Tonemapper::default(),
+            bloom: Some(BloomSettings::default()),
+            fxaa: true,
+            vignette: None,
+        }
+    }
+}
+
+/// A pass of the stack, each with its own pipeline
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Effect {
+    BloomPrefilter,
+    BloomDownsample,
+    BloomUpsample,
+    Tonemap,
+    Fxaa,
+    Vignette,
+}
+
+/// Built-in post-processing passes, declared into a frame graph
+pub struct PostProcessStack {
+    settings: PostProcessSettings,
+    hdr: bool,
+    output_format: TextureFormat,
+    vertex_shader: ShaderHandle,
+    fragment_shaders: Vec<ShaderHandle>,
+    pipelines: HashMap<Effect, PipelineHandle>,
+    vertex_buffer: BufferHandle,
+    params_buffer: BufferHandle,
+    params_layout: BindGroupLayoutHandle,
+    params_group: BindGroupHandle,
+    sampler: SamplerHandle,
+}
+
+impl PostProcessStack {
+    /// Stack for the device's `RendererConfig`: tonemapping from `Rgba16f`
+    /// when `hdr` is set, writing the swapchain format
+    ///
+    /// The passes enabled in `settings` are the ones `add_passes` declares
+    /// for the life of the stack.
+    pub fn new(
+        device: &mut BackendDevice,
+        settings: PostProcessSettings,
+    ) -> Result<Self, GfxError> {
+        let mut settings = settings;
+        if let Some(bloom) = &mut settings.bloom {
+            bloom.levels = bloom.levels.clamp(1, MAX_BLOOM_LEVELS);
+        }
+        let mut stack = Self {
+            settings,
+            hdr: device.config().hdr,
+            output_format: device.config().swapchain_format(),
+            vertex_shader: ShaderHandle::INVALID,
+            fragment_shaders: Vec::new(),
+            pipelines: HashMap::new(),
+            vertex_buffer: BufferHandle::INVALID,
+            params_buffer: BufferHandle::INVALID,
+            params_layout: BindGroupLayoutHandle::INVALID,
+            params_group: BindGroupHandle::INVALID,
+            sampler: SamplerHandle::INVALID,
+        };
+        match stack.create_resources(device) {
+            Ok(()) => Ok(stack),
+            Err(err) => {
+                stack.destroy(device);
+                Err(err)
+            }
+        }
+    }
+
+    fn create_resources(&mut self, device: &mut BackendDevice) -> Result<(), GfxError> {
+        let backend = device.adapter_info().backend;
+
+        // One triangle over the whole target, texture origin at the top left
+        let vertices: [[f32; 4]; 3] = [
+            [-1.0, -1.0, 0.0, 1.0],
+            [3.0, -1.0, 2.0, 1.0],
+            [-1.0, 3.0, 0.0, -1.0],
+        ];
+        let bytes: Vec<u8> = vertices
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        self.vertex_buffer =
+            device.create_buffer(&BufferDesc::vertex(bytes.len()), Some(&bytes))?;
+
+        self.params_buffer = device.create_buffer(&BufferDesc::uniform(PARAMS_SIZE), None)?;
+        self.params_layout = device.create_bind_group_layout(&BindGroupLayoutDesc {
+            entries: vec![BindGroupLayoutEntry {
+                binding: 0,
+                ty: BindingType::UniformBuffer,
+            }],
+        })?;
+        self.params_group = device.create_bind_group(&BindGroupDesc {
+            layout: self.params_layout,
+            entries: vec![BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer {
+                    buffer: self.params_buffer,
+                    offset: 0,
+                    size: None,
+                },
+            }],
+        })?;
+        self.sampler = device.create_sampler(&SamplerDesc::linear(AddressMode::ClampToEdge))?;
+        self.write_params(device);
+
+        self.vertex_shader = device.create_shader(&ShaderDesc {
+            stage: ShaderStage::Vertex,
+            entry_point: "main".to_string(),
+            code: VERTEX_SHADER.as_bytes().to_vec(),
+        })?;
+
+        let ldr = TextureFormat::Rgba8;
+        let mut effects = Vec::new();
+        if self.settings.bloom.is_some() {
+            effects.push((Effect::BloomPrefilter, TextureFormat::Rgba16f));
+            effects.push((Effect::BloomDownsample, TextureFormat::Rgba16f));
+            effects.push((Effect::BloomUpsample, TextureFormat::Rgba16f));
+        }
+        let chain = self.ldr_chain();
+        let last = chain.last().copied();
+        effects.push((
+            Effect::Tonemap,
+            if last.is_none() {
+                self.output_format
+            } else {
+                ldr
+            },
+        ));
+        for effect in chain {
+            let format = if Some(effect) == last {
+                self.output_format
+            } else {
+                ldr
+            };
+            effects.push((effect, format));
+        }
+
+        for (effect, format) in effects {
+            let two_inputs = reads_two(effect, &self.settings);
+            // Only the last pass writes a target that is not `Rgba8`
+            let linear_output = !matches!(format, TextureFormat::Rgba8 | TextureFormat::Bgra8);
+            let source = fragment_shader(backend, effect, two_inputs, self.hdr, linear_output);
+            let shader = device.create_shader(&ShaderDesc {
+                stage: ShaderStage::Fragment,
+                entry_point: "main".to_string(),
+                code: source.into_bytes(),
+            })?;
+            self.fragment_shaders.push(shader);
+            let pipeline = self.create_pipeline(device, shader, format, two_inputs)?;
+            self.pipelines.insert(effect, pipeline);
+        }
+        Ok(())
+    }
+
+    fn create_pipeline(
+        &self,
+        device: &mut dyn GpuDevice,
+        fragment_shader: ShaderHandle,
+        format: TextureFormat,
+        two_inputs: bool,
+    ) -> Result<PipelineHandle, GfxError> {
+        let mut textures = vec![TextureBinding::new_2d(1, 0)];
+        if two_inputs {
+            textures.push(TextureBinding::new_2d(1, 2));
+        }
+        device.create_pipeline(&PipelineDesc {
+            vertex_shader: self.vertex_shader,
+            fragment_shader,
+            vertex_layout: VertexLayout {
+                stride: 16,
+                attributes: vec![
+                    VertexAttribute {
+                        format: VertexFormat::Float2,
+                        offset: 0,
+                        location: 0,
+                    },
+                    VertexAttribute {
+                        format: VertexFormat::Float2,
+                        offset: 8,
+                        location: 2,
+                    },
+                ],
+                buffers: Vec::new(),
+            },
+            topology: PrimitiveTopology::TriangleList,
+            rasterizer: RasterizerState {
+                cull_mode: CullMode::None,
+                ..Default::default()
+            },
+            depth_stencil: DepthStencilState {
+                depth_test_enabled: false,
+                depth_write_enabled: false,
+                ..Default::default()
+            },
+            blend_states: vec![Default::default()],
+            color_formats: vec![format],
+            depth_format: None,
+            sample_count: 1,
+            layout: PipelineLayoutDesc {
+                bind_groups: vec![self.params_layout],
+                textures,
+            },
+        })
+    }
+
+    /// Passes after tonemapping, in order
+    fn ldr_chain(&self) -> Vec<Effect> {
+        let mut chain = Vec::new();
+        if self.settings.fxaa {
+            chain.push(Effect::Fxaa);
+        }
+        if self.settings.vignette.is_some() {
+            chain.push(Effect::Vignette);
+        }
+        chain
+    }
+
+    pub fn settings(&self) -> &PostProcessSettings {
+        &self.settings
+    }
+
+    /// Format the scene must be rendered to: `Rgba16f` with `hdr`,
+    /// `Rgba8` without
+    pub fn scene_format(&self) -> TextureFormat {
+        if self.hdr {
+            TextureFormat::Rgba16f
+        } else {
+            TextureFormat::Rgba8
+        }
+    }
+
+    /// Format of the target the last pass writes
+    pub fn output_format(&self) -> TextureFormat {
+        self.output_format
+    }
+
+    /// A scene color target the stack can read, for
+    /// `FrameGraphBuilder::create_screen_texture`
+    pub fn scene_desc(&self, width: u32, height: u32) -> TextureDesc {
+        TextureDesc::new_2d(
+            width,
+            height,
+            self.scene_format(),
+            TextureUsage::COLOR_ATTACHMENT | TextureUsage::SAMPLED,
+        )
+    }
+
+    /// Change exposure, tonemapper and the bloom and vignette parameters
+    /// for the next frames
+    ///
+    /// Passes stay the ones enabled in `new`: turning bloom, FXAA or the
+    /// vignette on or off here has no effect.
+    pub fn update(&mut self, device: &mut dyn GpuDevice, settings: &PostProcessSettings) {
+        self.settings.exposure = settings.exposure;
+        self.settings.tonemapper = settings.tonemapper;
+        if let (Some(current), Some(new)) = (&mut self.settings.bloom, settings.bloom) {
+            current.threshold = new.threshold;
+            current.intensity = new.intensity;
+        }
+        if let (Some(current), Some(new)) = (&mut self.settings.vignette, settings.vignette) {
+            *current = new;
+        }
+        self.write_params(device);
+    }
+
+    fn write_params(&self, device: &mut dyn GpuDevice) {
+        let settings = &self.settings;
+        let tonemapper = match settings.tonemapper {
+            Tonemapper::Reinhard => 0.0,
+            Tonemapper::Aces => 1.0,
+        };
+        let bloom = settings.bloom.unwrap_or_default();
+        let vignette = settings.vignette.unwrap_or_default();
+        let params: [f32; PARAMS_SIZE / 4] = [
+            settings.exposure,
+            tonemapper,
+            0.0,
+            0.0,
+            bloom.threshold,
+            bloom.intensity,
+            0.0,
+            0.0,
+            vignette.intensity,
+            vignette.radius,
+            vignette.smoothness,
+            0.0,
+        ];
+        let bytes: Vec<u8> = params.iter().flat_map(|v| v.to_le_bytes()).collect();
+        device.update_buffer(self.params_buffer, 0, &bytes);
+    }
+
+    /// Declare the passes reading `scene` and writing `output`, whose
+    /// format must be `output_format`
+    ///
+    /// `width` and `height` are the current swapchain size: bloom and
+    /// intermediate targets are screen textures, named `post_*`, that
+    /// follow `CompiledFrameGraph::resize`. Returns the last pass.
+    pub fn add_passes(
+        &self,
+        fg: &mut FrameGraphBuilder,
+        scene: &ResourceId,
+        output: &ResourceId,
+        width: u32,
+        height: u32,
+    ) -> PassId {
+        let texture = |format, divisor: u32| {
+            TextureDesc::new_2d(
+                (width / divisor).max(1),
+                (height / divisor).max(1),
+                format,
+                TextureUsage::COLOR_ATTACHMENT | TextureUsage::SAMPLED,
+            )
+        };
+
+        let bloom = self.settings.bloom.map(|bloom| {
+            let levels: Vec<ResourceId> = (0..bloom.levels)
+                .map(|level| {
+                    let divisor = 2 << level;
+                    fg.create_scaled_screen_texture(
+                        &format!("post_bloom_down{level}"),
+                        texture(TextureFormat::Rgba16f, divisor),
+                        divisor,
+                    )
+                })
+                .collect();
+            self.pass(Effect::BloomPrefilter)
+                .add(fg, "post_bloom_prefilter", &[scene], &levels[0]);
+            for level in 1..levels.len() {
+                self.pass(Effect::BloomDownsample).add(
+                    fg,
+                    &format!("post_bloom_downsample{level}"),
+                    &[&levels[level - 1]],
+                    &levels[level],
+                );
+            }
+            // Blur back up, adding each level to the one above
+            let mut blurred = levels[levels.len() - 1].clone();
+            for level in (0..levels.len() - 1).rev() {
+                let divisor = 2 << level;
+                let target = fg.create_scaled_screen_texture(
+                    &format!("post_bloom_up{level}"),
+                    texture(TextureFormat::Rgba16f, divisor),
+                    divisor,
+                );
+                self.pass(Effect::BloomUpsample).add(
+                    fg,
+                    &format!("post_bloom_upsample{level}"),
+                    &[&blurred, &levels[level]],
+                    &target,
+                );
+                blurred = target;
+            }
+            blurred
+        });
+
+        let chain = self.ldr_chain();
+        let mut target = if chain.is_empty() {
+            output.clone()
+        } else {
+            fg.create_screen_texture("post_tonemapped", texture(TextureFormat::Rgba8, 1))
+        };
+        let mut inputs = vec![scene];
+        inputs.extend(bloom.as_ref());
+        let mut last = self
+            .pass(Effect::Tonemap)
+            .add(fg, "post_tonemap", &inputs, &target);
+
+        for (index, &effect) in chain.iter().enumerate() {
+            let source = target;
+            let (name, intermediate) = match effect {
+                Effect::Fxaa => ("post_fxaa", "post_antialiased"),
+                _ => ("post_vignette", "post_vignetted"),
+            };
+            target = if index + 1 == chain.len() {
+                output.clone()
+            } else {
+                fg.create_screen_texture(intermediate, texture(TextureFormat::Rgba8, 1))
+            };
+            last = self.pass(effect).add(fg, name, &[&source], &target);
+        }
+        last
+    }
+
+    fn pass(&self, effect: Effect) -> FullscreenPass {
+        FullscreenPass {
+            pipeline: self.pipelines[&effect],
+            params: self.params_group,
+            vertex_buffer: self.vertex_buffer,
+            sampler: self.sampler,
+        }
+    }
+
+    /// Destroy the pipelines, shaders, buffers and sampler
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        for pipeline in self.pipelines.into_values() {
+            device.destroy_pipeline(pipeline);
+        }
+        for shader in self.fragment_shaders {
+            device.destroy_shader(shader);
+        }
+        if self.vertex_shader != ShaderHandle::INVALID {
+            device.destroy_shader(self.vertex_shader);
+        }
+        if self.params_group != BindGroupHandle::INVALID {
+            device.destroy_bind_group(self.params_group);
+        }
+        if self.params_layout != BindGroupLayoutHandle::INVALID {
+            device.destroy_bind_group_layout(self.params_layout);
+        }
+        for buffer in [self.vertex_buffer, self.params_buffer] {
+            if buffer != BufferHandle::INVALID {
+                device.destroy_buffer(buffer);
+            }
+        }
+        if self.sampler != SamplerHandle::INVALID {
+            device.destroy_sampler(self.sampler);
+        }
+    }
+}
+
+/// Whether `effect` reads a second texture: the level to add for the
+/// bloom upsample, the bloom for tonemapping
+fn reads_two(effect: Effect, settings: &PostProcessSettings) -> bool {
+    match effect {
+        Effect::BloomUpsample => true,
+        Effect::Tonemap => settings.bloom.is_some(),
+        _ => false,
+    }
+}
+
+/// What a fullscreen pass closure needs, all plain handles
+#[derive(Clone, Copy)]
+struct FullscreenPass {
+    pipeline: PipelineHandle,
+    params: BindGroupHandle,
+    vertex_buffer: BufferHandle,
+    sampler: SamplerHandle,
+}
+
+impl FullscreenPass {
+    /// Declare a pass drawing `target` from `inputs`, bound at set 1,
+    /// bindings 0 and 2
+    fn add(
+        self,
+        fg: &mut FrameGraphBuilder,
+        name: &str,
+        inputs: &[&ResourceId],
+        target: &ResourceId,
+    ) -> PassId {
+        let input_names: Vec<String> = inputs
+            .iter()
+            .map(|input| input.name().to_string())
+            .collect();
+        let target_name = target.name().to_string();
+        fg.add_pass(
+            name,
+            |pass| {
+                for input in inputs {
+                    pass.read(input);
+                }
+                pass.write(target);
+            },
+            Box::new(move |cmd, resources| {
+                cmd.begin_render_pass(RenderPassDesc {
+                    color_attachments: vec![ColorAttachment {
+                        texture: resources.get_texture(&target_name),
+                        load: LoadOp::Clear(ClearColor::BLACK),
+                        store: StoreOp::Store,
+                    }],
+                    depth_attachment: None,
+                });
+                cmd.bind_pipeline(self.pipeline);
+                cmd.bind_group(0, self.params);
+                for (index, input) in input_names.iter().enumerate() {
+                    cmd.bind_texture(
+                        1,
+                        2 * index as u32,
+                        resources.get_texture(input),
+                        self.sampler,
+                    );
+                }
+                cmd.bind_vertex_buffer(0, self.vertex_buffer, 0);
+                cmd.draw(3, 1, 0, 0);
+                cmd.end_render_pass();
+            }),
+        )
+    }
+}
+
+const VERTEX_SHADER: &str = "#version 450
+layout(location = 0) in vec2 position;
+layout(location = 2) in vec2 uv;
+layout(location = 0) out vec2 v_uv;
+
+void main() {
+    v_uv = uv;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+";
+
+/// Fragment shader of `effect` with its textures declared for OpenGL or
+/// for wgpu
+fn fragment_shader(
+    backend: Backend,
+    effect: Effect,
+    two_inputs: bool,
+    hdr: bool,
+    linear_output: bool,
+) -> String {
+    let mut declarations = String::new();
+    let inputs = [("source", 0), ("extra", 2)];
+    for (name, binding) in &inputs[..1 + two_inputs as usize] {
+        if backend == Backend::OpenGl {
+            declarations += &format!(
+                "layout(binding = {binding}) uniform sampler2D {name}_texture;
+vec4 {name}(vec2 uv) {{ return texture({name}_texture, uv); }}
+vec2 {name}_texel() {{ return 1.0 / vec2(textureSize({name}_texture, 0)); }}
+"
+            );
+        } else {
+            let sampler_binding = binding + 1;
+            declarations += &format!(
+                "layout(set = 1, binding = {binding}) uniform texture2D {name}_texture;
+layout(set = 1, binding = {sampler_binding}) uniform sampler {name}_sampler;
+vec4 {name}(vec2 uv) {{ return texture(sampler2D({name}_texture, {name}_sampler), uv); }}
+vec2 {name}_texel() {{ return 1.0 / vec2(textureSize(sampler2D({name}_texture, {name}_sampler), 0)); }}
+"
+            );
+        }
+    }
+    let params_layout = match backend {
+        Backend::OpenGl => "std140, binding = 0",
+        _ => "std140, set = 0, binding = 0",
+    };
+    let (helpers, main) = match effect {
+        Effect::BloomPrefilter => (DOWNSAMPLE, BLOOM_PREFILTER),
+        Effect::BloomDownsample => (DOWNSAMPLE, BLOOM_DOWNSAMPLE),
+        Effect::BloomUpsample => ("", BLOOM_UPSAMPLE),
+        // The bloom, when there is one, is the second input
+        Effect::Tonemap if two_inputs => {
+            ("\nvec3 bloom(vec2 uv) { return extra(uv).rgb; }\n", TONEMAP)
+        }
+        Effect::Tonemap => ("\nvec3 bloom(vec2 uv) { return vec3(0.0); }\n", TONEMAP),
+        Effect::Fxaa => ("", FXAA),
+        Effect::Vignette => ("", VIGNETTE),
+    };
+    format!(
+        "#version 450
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 out_color;
+layout({params_layout}) uniform Params {{
+    // exposure, tonemapper (0 Reinhard, 1 ACES)
+    vec4 grading;
+    // threshold, intensity
+    vec4 bloom_params;
+    // intensity, radius, smoothness
+    vec4 vignette_params;
+}};
+{declarations}
+const bool HDR = {hdr};
+const bool LINEAR_OUTPUT = {linear_output};
+
+vec3 linear_from_srgb(vec3 c) {{
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), c));
+}}
+
+vec3 srgb_from_linear(vec3 c) {{
+    c = clamp(c, 0.0, 1.0);
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(vec3(0.0031308), c));
+}}
+{helpers}{main}"
+    )
+}
+
+/// Four bilinear taps a texel apart: a 4x4 box filter
+const DOWNSAMPLE: &str = "
+vec3 downsample(vec2 uv) {
+    vec2 t = source_texel();
+    return 0.25 * (source(uv + vec2(-t.x, -t.y)).rgb + source(uv + vec2(t.x, -t.y)).rgb
+        + source(uv + vec2(-t.x, t.y)).rgb + source(uv + t).rgb);
+}
+";
+
+/// Bright pass at half resolution
+const BLOOM_PREFILTER: &str = "
+void main() {
+    vec3 color = downsample(v_uv);
+    float brightness = max(color.r, max(color.g, color.b));
+    // Soft knee around the threshold
+    float threshold = bloom_params.x;
+    float knee = 0.5 * threshold;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-4);
+    float weight = max(soft, brightness - threshold) / max(brightness, 1e-4);
+    out_color = vec4(color * weight, 1.0);
+}
+";
+
+const BLOOM_DOWNSAMPLE: &str = "
+void main() {
+    out_color = vec4(downsample(v_uv), 1.0);
+}
+";
+
+/// 3x3 tent filter over the smaller level, added to the level above
+const BLOOM_UPSAMPLE: &str = "
+void main() {
+    vec2 t = source_texel();
+    vec3 c = 4.0 * source(v_uv).rgb;
+    c += 2.0 * (source(v_uv + vec2(-t.x, 0.0)).rgb + source(v_uv + vec2(t.x, 0.0)).rgb
+        + source(v_uv + vec2(0.0, -t.y)).rgb + source(v_uv + vec2(0.0, t.y)).rgb);
+    c += source(v_uv - t).rgb + source(v_uv + t).rgb
+        + source(v_uv + vec2(-t.x, t.y)).rgb + source(v_uv + vec2(t.x, -t.y)).rgb;
+    out_color = vec4(extra(v_uv).rgb + c / 16.0, 1.0);
+}
+";
+
+const TONEMAP: &str = "
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+void main() {
+    vec3 color = source(v_uv).rgb;
+    if (HDR) {
+        color += bloom(v_uv) * bloom_params.y;
+        color *= grading.x;
+        color = grading.y < 0.5 ? color / (1.0 + color) : aces(color);
+        if (!LINEAR_OUTPUT) {
+            color = srgb_from_linear(color);
+        }
+    } else {
+        // Already display-encoded: add the bloom in linear space
+        color = linear_from_srgb(color) + bloom(v_uv) * bloom_params.y;
+        if (!LINEAR_OUTPUT) {
+            color = srgb_from_linear(color);
+        }
+    }
+    out_color = vec4(color, 1.0);
+}
+";
+
+/// FXAA 3.11, console variant: blend along the local edge direction
+const FXAA: &str = "
+float luma(vec3 c) {
+    return dot(c, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec2 t = source_texel();
+    vec3 rgb_nw = source(v_uv + vec2(-t.x, -t.y)).rgb;
+    vec3 rgb_ne = source(v_uv + vec2(t.x, -t.y)).rgb;
+    vec3 rgb_sw = source(v_uv + vec2(-t.x, t.y)).rgb;
+    vec3 rgb_se = source(v_uv + t).rgb;
+    vec3 rgb_m = source(v_uv).rgb;
+    float nw = luma(rgb_nw);
+    float ne = luma(rgb_ne);
+    float sw = luma(rgb_sw);
+    float se = luma(rgb_se);
+    float m = luma(rgb_m);
+    float luma_min = min(m, min(min(nw, ne), min(sw, se)));
+    float luma_max = max(m, max(max(nw, ne), max(sw, se)));
+
+    vec2 dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+    float reduce = max((nw + ne + sw + se) * (0.25 / 8.0), 1.0 / 128.0);
+    float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+    dir = clamp(dir * scale, vec2(-8.0), vec2(8.0)) * t;
+
+    vec3 rgb_a = 0.5 * (source(v_uv + dir * (1.0 / 3.0 - 0.5)).rgb
+        + source(v_uv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgb_b = 0.5 * rgb_a + 0.25 * (source(v_uv - 0.5 * dir).rgb + source(v_uv + 0.5 * dir).rgb);
+    float luma_b = luma(rgb_b);
+    vec3 color = (luma_b < luma_min || luma_b > luma_max) ? rgb_a : rgb_b;
+    if (LINEAR_OUTPUT) {
+        color = linear_from_srgb(color);
+    }
+    out_color = vec4(color, 1.0);
+}
+";
+
+const VIGNETTE: &str = "
+void main() {
+    vec3 color = source(v_uv).rgb;
+    // 0 at the center, 1 in the corners
+    float dist = length(v_uv - 0.5) * 1.41421356;
+    float start = vignette_params.y;
+    color *= 1.0 - vignette_params.x * smoothstep(start, start + vignette_params.z, dist);
+    if (LINEAR_OUTPUT) {
+        color = linear_from_srgb(color);
+    }
+    out_color = vec4(color, 1.0);
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::RendererConfig;
+    use crate::gfx::backend::create_device;
+
+    /// A scene pass that only clears the scene target
+    fn clear_scene(fg: &mut FrameGraphBuilder, scene: &ResourceId) {
+        fg.add_pass(
+            "scene",
+            |pass| pass.write(scene),
+            Box::new(|cmd, resources| {
+                cmd.begin_render_pass(RenderPassDesc {
+                    color_attachments: vec![ColorAttachment {
+                        texture: resources.get_texture("scene"),
+                        load: LoadOp::Clear(ClearColor::BLACK),
+                        store: StoreOp::Store,
+                    }],
+                    depth_attachment: None,
+                });
+                cmd.end_render_pass();
+            }),
+        );
+    }
+
+    #[test]
+    fn test_passes_follow_settings() {
+        let mut device = create_device(RendererConfig {
+            backend: Backend::Software,
+            hdr: true,
+            ..Default::default()
+        });
+        let settings = PostProcessSettings {
+            bloom: Some(BloomSettings {
+                levels: 3,
+                ..Default::default()
+            }),
+            vignette: Some(VignetteSettings::default()),
+            ..Default::default()
+        };
+        let stack = PostProcessStack::new(&mut device, settings).unwrap();
+        assert_eq!(stack.scene_format(), TextureFormat::Rgba16f);
+        assert_eq!(stack.output_format(), TextureFormat::Rgba16f);
+
+        let mut fg = FrameGraphBuilder::new();
+        let scene = fg.create_screen_texture("scene", stack.scene_desc(64, 64));
+        let output = fg.import_texture("backbuffer", device.get_swapchain_texture());
+        clear_scene(&mut fg, &scene);
+        stack.add_passes(&mut fg, &scene, &output, 64, 64);
+        let graph = fg.compile().unwrap();
+        assert_eq!(
+            graph.pass_names(),
+            [
+                "scene",
+                "post_bloom_prefilter",
+                "post_bloom_downsample1",
+                "post_bloom_downsample2",
+                "post_bloom_upsample1",
+                "post_bloom_upsample0",
+                "post_tonemap",
+                "post_fxaa",
+                "post_vignette",
+            ]
+        );
+        stack.destroy(&mut device);
+    }
+
+    #[test]
+    fn test_execute_writes_output() {
+        let mut device = create_device(RendererConfig {
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let settings = PostProcessSettings {
+            bloom: Some(BloomSettings {
+                levels: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut stack = PostProcessStack::new(&mut device, settings).unwrap();
+        assert_eq!(stack.scene_format(), TextureFormat::Rgba8);
+        stack.update(
+            &mut device,
+            &PostProcessSettings {
+                exposure: 2.0,
+                bloom: None,
+                fxaa: false,
+                ..settings
+            },
+        );
+        // Parameters change, the passes do not
+        assert_eq!(stack.settings().exposure, 2.0);
+        assert!(stack.settings().bloom.is_some() && stack.settings().fxaa);
+
+        let output = device
+            .create_texture(
+                &TextureDesc::new_2d(
+                    32,
+                    32,
+                    TextureFormat::Rgba8,
+                    TextureUsage::COLOR_ATTACHMENT | TextureUsage::TRANSFER_SRC,
+                ),
+                None,
+            )
+            .unwrap();
+        let mut fg = FrameGraphBuilder::new();
+        let scene = fg.create_screen_texture("scene", stack.scene_desc(32, 32));
+        let target = fg.import_texture("output", output);
+        clear_scene(&mut fg, &scene);
+        stack.add_passes(&mut fg, &scene, &target, 32, 32);
+        let mut graph = fg.compile().unwrap();
+        assert_eq!(graph.pass_names().len(), 6);
+        graph.execute(&mut device).unwrap();
+
+        // The software backend draws the fullscreen triangle untextured
+        let pixels = device.read_texture(output).unwrap();
+        assert!(pixels.iter().all(|&byte| byte == 255));
+
+        graph.release(&mut device);
+        stack.destroy(&mut device);
+        device.destroy_texture(output);
+        let stats = &device.memory_stats()[0];
+        assert_eq!(stats.allocation_count, stats.deallocation_count);
+    }
+}
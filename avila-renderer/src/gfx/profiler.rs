@@ -0,0 +1,66 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! GPU frame profiler
+//!
+//! Feeds the timestamps written with `CommandList::write_timestamp` into the
+//! CPU `Profiler`, so GPU time per pass shows up in the same report as the
+//! CPU sections.
+
+use crate::gfx::api::{FrameTimestamps, GpuDevice};
+use avila_math::os::Profiler;
+use std::time::Duration;
+
+/// Prefix of the sections recorded into the `Profiler`
+const SECTION_PREFIX: &str = "gpu/";
+
+/// Collects the timestamps of finished GPU frames
+///
+/// Write a timestamp at the start of each pass and one at the end of the
+/// frame: each section runs from its timestamp to the next. Call `update`
+/// once per frame; its results trail the CPU by a few frames.
+#[derive(Default)]
+pub struct GpuProfiler {
+    last_frame: Option<FrameTimestamps>,
+}
+
+impl GpuProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record every frame the GPU finished since the last call into
+    /// `profiler`
+    ///
+    /// Each section is recorded as `gpu/<name>`, and the time from the first
+    /// timestamp of a frame to its last as `gpu/frame`.
+    pub fn update(&mut self, device: &mut dyn GpuDevice, profiler: &mut Profiler) {
+        for frame in device.read_timestamps() {
+            for (name, duration) in frame.sections() {
+                profiler.record(format!("{}{}", SECTION_PREFIX, name), duration);
+            }
+            if let Some(duration) = frame_duration(&frame) {
+                profiler.record(format!("{}frame", SECTION_PREFIX), duration);
+            }
+            self.last_frame = Some(frame);
+        }
+    }
+
+    /// Timestamps of the latest frame `update` recorded
+    pub fn last_frame(&self) -> Option<&FrameTimestamps> {
+        self.last_frame.as_ref()
+    }
+
+    /// GPU time of the latest frame, from its first timestamp to its last
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.last_frame.as_ref().and_then(frame_duration)
+    }
+}
+
+/// Time from the first timestamp of `frame` to its last, with at least two
+fn frame_duration(frame: &FrameTimestamps) -> Option<Duration> {
+    match frame.timestamps.as_slice() {
+        [(_, first), .., (_, last)] => Some(Duration::from_nanos(last.saturating_sub(*first))),
+        _ => None,
+    }
+}
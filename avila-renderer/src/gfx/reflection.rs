@@ -0,0 +1,1114 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! SPIR-V reflection
+//!
+//! Reads what a SPIR-V shader expects from its pipeline: the resources it
+//! binds, its push constant ranges and, for vertex shaders, the vertex
+//! inputs it reads. Bind group layouts and vertex layouts can be derived
+//! from that, and `create_pipeline` checks SPIR-V shaders against the
+//! layouts it is given.
+//!
+//! The API has no combined texture-samplers: a `sampler2D` at binding `b`
+//! reflects as a texture at `b` and a sampler at `b + 1`, the slots a
+//! `TextureBinding` takes.
+
+use crate::gfx::api::{
+    BindGroupLayoutDesc, BindGroupLayoutEntry, BindingType, GfxError, GpuDevice,
+    PipelineLayoutDesc, ShaderDesc, ShaderStage, StorageTextureAccess, TextureDimension,
+    TextureFormat, VertexAttribute, VertexFormat, VertexLayout,
+};
+use std::collections::HashMap;
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// First SPIR-V version whose entry points list every global they use,
+/// not only inputs and outputs
+const SPIRV_1_4: u32 = 0x0001_0400;
+
+// Instructions
+const OP_NAME: u32 = 5;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLER: u32 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_FUNCTION: u32 = 54;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+// Decorations
+const BUFFER_BLOCK: u32 = 3;
+const ARRAY_STRIDE: u32 = 6;
+const MATRIX_STRIDE: u32 = 7;
+const BUILT_IN: u32 = 11;
+const NON_WRITABLE: u32 = 24;
+const NON_READABLE: u32 = 25;
+const LOCATION: u32 = 30;
+const BINDING: u32 = 33;
+const DESCRIPTOR_SET: u32 = 34;
+const OFFSET: u32 = 35;
+
+// Storage classes
+const UNIFORM_CONSTANT: u32 = 0;
+const INPUT: u32 = 1;
+const UNIFORM: u32 = 2;
+const PUSH_CONSTANT: u32 = 9;
+const STORAGE_BUFFER: u32 = 12;
+
+/// Whether `code` is a SPIR-V module rather than shader source
+pub fn is_spirv(code: &[u8]) -> bool {
+    code.len() >= 4 && u32::from_le_bytes([code[0], code[1], code[2], code[3]]) == SPIRV_MAGIC
+}
+
+/// Component type of a vertex input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarKind {
+    Float,
+    Sint,
+    Uint,
+}
+
+/// A vertex input of a vertex shader
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectedVertexInput {
+    pub location: u32,
+    /// Name in the shader, empty if stripped
+    pub name: String,
+    pub kind: ScalarKind,
+    /// 1 to 4
+    pub components: u32,
+}
+
+impl ReflectedVertexInput {
+    /// Vertex format that feeds this input exactly; signed integers have
+    /// none
+    pub fn format(&self) -> Option<VertexFormat> {
+        match (self.kind, self.components) {
+            (ScalarKind::Float, 1) => Some(VertexFormat::Float),
+            (ScalarKind::Float, 2) => Some(VertexFormat::Float2),
+            (ScalarKind::Float, 3) => Some(VertexFormat::Float3),
+            (ScalarKind::Float, 4) => Some(VertexFormat::Float4),
+            (ScalarKind::Uint, 1) => Some(VertexFormat::UInt),
+            (ScalarKind::Uint, 2) => Some(VertexFormat::UInt2),
+            (ScalarKind::Uint, 3) => Some(VertexFormat::UInt3),
+            (ScalarKind::Uint, 4) => Some(VertexFormat::UInt4),
+            _ => None,
+        }
+    }
+}
+
+/// A resource a shader binds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectedBinding {
+    pub set: u32,
+    pub binding: u32,
+    /// Name in the shader, empty if stripped
+    pub name: String,
+    /// Samplers reflect with `comparison: false`: SPIR-V does not say
+    pub ty: BindingType,
+}
+
+impl ReflectedBinding {
+    /// Whether a layout slot of type `ty` can serve this binding
+    ///
+    /// Writable slots serve read-only bindings; whether textures and
+    /// samplers compare is not checked.
+    pub fn fits(&self, ty: BindingType) -> bool {
+        match (ty, self.ty) {
+            (
+                BindingType::StorageBuffer { read_only: slot },
+                BindingType::StorageBuffer { read_only: shader },
+            ) => shader || !slot,
+            (
+                BindingType::Texture {
+                    dimension, array, ..
+                },
+                BindingType::Texture {
+                    dimension: shader_dimension,
+                    array: shader_array,
+                    ..
+                },
+            ) => dimension == shader_dimension && array == shader_array,
+            (BindingType::Sampler { .. }, BindingType::Sampler { .. }) => true,
+            (
+                BindingType::StorageTexture {
+                    format,
+                    dimension,
+                    access,
+                },
+                BindingType::StorageTexture {
+                    format: shader_format,
+                    dimension: shader_dimension,
+                    access: shader_access,
+                },
+            ) => {
+                format == shader_format
+                    && dimension == shader_dimension
+                    && (access == StorageTextureAccess::ReadWrite || access == shader_access)
+            }
+            (slot, shader) => slot == shader,
+        }
+    }
+}
+
+/// A block of push constants, in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PushConstantRange {
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// What one entry point of a SPIR-V module expects from its pipeline
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderReflection {
+    pub stage: ShaderStage,
+    pub entry_point: String,
+    /// By set, then binding
+    pub bindings: Vec<ReflectedBinding>,
+    pub push_constants: Vec<PushConstantRange>,
+    /// By location; empty for stages other than vertex
+    pub vertex_inputs: Vec<ReflectedVertexInput>,
+}
+
+impl ShaderReflection {
+    /// Reflect the `desc.entry_point` entry point of `desc.stage` in the
+    /// SPIR-V `desc.code`
+    pub fn new(desc: &ShaderDesc) -> Result<Self, GfxError> {
+        let module = Module::parse(&desc.code)?;
+        let model = match desc.stage {
+            ShaderStage::Vertex => 0,
+            ShaderStage::TessControl => 1,
+            ShaderStage::TessEvaluation => 2,
+            ShaderStage::Geometry => 3,
+            ShaderStage::Fragment => 4,
+            ShaderStage::Compute => 5,
+        };
+        let Some(entry) = module
+            .entry_points
+            .iter()
+            .find(|entry| entry.model == model && entry.name == desc.entry_point)
+        else {
+            return Err(GfxError::InvalidDesc(format!(
+                "SPIR-V module has no {:?} entry point named '{}'",
+                desc.stage, desc.entry_point
+            )));
+        };
+
+        let mut reflection = ShaderReflection {
+            stage: desc.stage,
+            entry_point: desc.entry_point.clone(),
+            bindings: Vec::new(),
+            push_constants: Vec::new(),
+            vertex_inputs: Vec::new(),
+        };
+        for variable in &module.variables {
+            let listed = module.version >= SPIRV_1_4 || variable.storage == INPUT;
+            if listed && !entry.interface.contains(&variable.id) {
+                continue;
+            }
+            let Some(&Type::Pointer { pointee }) = module.types.get(&variable.ty) else {
+                continue;
+            };
+            let name = module.names.get(&variable.id).cloned().unwrap_or_default();
+            match variable.storage {
+                INPUT if desc.stage == ShaderStage::Vertex => {
+                    if module.decoration(variable.id, BUILT_IN).is_some() {
+                        continue;
+                    }
+                    let Some(location) = module.decoration(variable.id, LOCATION) else {
+                        continue;
+                    };
+                    let Some((slots, kind, components)) = module.input_slots(pointee) else {
+                        return Err(GfxError::InvalidDesc(format!(
+                            "vertex input at location {} is not a number or vector",
+                            location
+                        )));
+                    };
+                    reflection
+                        .vertex_inputs
+                        .extend((0..slots).map(|slot| ReflectedVertexInput {
+                            location: location + slot,
+                            name: name.clone(),
+                            kind,
+                            components,
+                        }));
+                }
+                PUSH_CONSTANT => reflection
+                    .push_constants
+                    .push(module.push_constants(pointee)),
+                UNIFORM_CONSTANT | UNIFORM | STORAGE_BUFFER => {
+                    let (Some(set), Some(binding)) = (
+                        module.decoration(variable.id, DESCRIPTOR_SET),
+                        module.decoration(variable.id, BINDING),
+                    ) else {
+                        continue;
+                    };
+                    let types = module.binding_types(variable, pointee).ok_or_else(|| {
+                        GfxError::InvalidDesc(format!(
+                            "binding {} of set {} has a type bind groups cannot hold",
+                            binding, set
+                        ))
+                    })?;
+                    reflection
+                        .bindings
+                        .extend(types.into_iter().map(|(offset, ty)| ReflectedBinding {
+                            set,
+                            binding: binding + offset,
+                            name: name.clone(),
+                            ty,
+                        }));
+                }
+                _ => {}
+            }
+        }
+        reflection
+            .bindings
+            .sort_by_key(|binding| (binding.set, binding.binding));
+        reflection.vertex_inputs.sort_by_key(|input| input.location);
+        Ok(reflection)
+    }
+
+    /// Vertex layout feeding every input from one tightly packed buffer, in
+    /// location order
+    pub fn vertex_layout(&self) -> Result<VertexLayout, GfxError> {
+        let mut attributes = Vec::with_capacity(self.vertex_inputs.len());
+        let mut offset = 0;
+        for input in &self.vertex_inputs {
+            let Some(format) = input.format() else {
+                return Err(GfxError::InvalidDesc(format!(
+                    "no vertex format for the {:?} vertex input at {}",
+                    input.kind,
+                    describe_input(input)
+                )));
+            };
+            attributes.push(VertexAttribute {
+                format,
+                offset,
+                location: input.location,
+            });
+            offset += format.size();
+        }
+        Ok(VertexLayout::new(offset, attributes))
+    }
+
+    /// Check that `layout` feeds every vertex input with a format of its
+    /// component type
+    ///
+    /// Formats may have more or fewer components than the input: missing
+    /// ones read as 0, and the last as 1.
+    pub fn validate_vertex_layout(&self, layout: &VertexLayout) -> Result<(), GfxError> {
+        for input in &self.vertex_inputs {
+            let Some(attribute) = layout
+                .all_attributes()
+                .find(|attribute| attribute.location == input.location)
+            else {
+                return Err(GfxError::InvalidDesc(format!(
+                    "vertex shader reads {}, which the vertex layout does not provide",
+                    describe_input(input)
+                )));
+            };
+            let float = matches!(
+                attribute.format,
+                VertexFormat::Float
+                    | VertexFormat::Float2
+                    | VertexFormat::Float3
+                    | VertexFormat::Float4
+            );
+            let fits = match input.kind {
+                ScalarKind::Float => float,
+                ScalarKind::Uint => !float,
+                ScalarKind::Sint => false,
+            };
+            if !fits {
+                return Err(GfxError::InvalidDesc(format!(
+                    "vertex attribute {:?} cannot feed the {:?} vertex input at {}",
+                    attribute.format,
+                    input.kind,
+                    describe_input(input)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bind group layouts of sets 0 and up holding what `reflections`
+    /// bind between them
+    ///
+    /// Sets no shader uses get empty layouts. A binding read-only in one
+    /// stage and written in another is writable.
+    pub fn bind_group_layouts(
+        reflections: &[&ShaderReflection],
+    ) -> Result<Vec<BindGroupLayoutDesc>, GfxError> {
+        let mut layouts: Vec<BindGroupLayoutDesc> = Vec::new();
+        for binding in reflections
+            .iter()
+            .flat_map(|reflection| &reflection.bindings)
+        {
+            let set = binding.set as usize;
+            if set >= layouts.len() {
+                layouts.resize_with(set + 1, Default::default);
+            }
+            let entries = &mut layouts[set].entries;
+            match entries
+                .iter_mut()
+                .find(|entry| entry.binding == binding.binding)
+            {
+                Some(entry) => {
+                    entry.ty = merge_binding(entry.ty, binding.ty).ok_or_else(|| {
+                        GfxError::InvalidDesc(format!(
+                            "binding {} of set {} is {:?} in one shader and {:?} in another",
+                            binding.binding, binding.set, entry.ty, binding.ty
+                        ))
+                    })?;
+                }
+                None => entries.push(BindGroupLayoutEntry {
+                    binding: binding.binding,
+                    ty: binding.ty,
+                }),
+            }
+        }
+        for layout in &mut layouts {
+            layout.entries.sort_by_key(|entry| entry.binding);
+        }
+        Ok(layouts)
+    }
+
+    /// Create the bind group layouts `reflections` need and a pipeline
+    /// layout using them
+    ///
+    /// The caller owns the layouts in `bind_groups`.
+    pub fn create_pipeline_layout(
+        device: &mut dyn GpuDevice,
+        reflections: &[&ShaderReflection],
+    ) -> Result<PipelineLayoutDesc, GfxError> {
+        let mut bind_groups = Vec::new();
+        for desc in Self::bind_group_layouts(reflections)? {
+            match device.create_bind_group_layout(&desc) {
+                Ok(handle) => bind_groups.push(handle),
+                Err(err) => {
+                    for handle in bind_groups {
+                        device.destroy_bind_group_layout(handle);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(PipelineLayoutDesc {
+            bind_groups,
+            textures: Vec::new(),
+        })
+    }
+}
+
+/// "location N" with the input's name when it has one
+fn describe_input(input: &ReflectedVertexInput) -> String {
+    if input.name.is_empty() {
+        format!("location {}", input.location)
+    } else {
+        format!("location {} (`{}`)", input.location, input.name)
+    }
+}
+
+/// One slot serving two stages' view of a binding
+fn merge_binding(a: BindingType, b: BindingType) -> Option<BindingType> {
+    match (a, b) {
+        (
+            BindingType::StorageBuffer { read_only: a },
+            BindingType::StorageBuffer { read_only: b },
+        ) => Some(BindingType::StorageBuffer { read_only: a && b }),
+        (BindingType::Sampler { comparison: a }, BindingType::Sampler { comparison: b }) => {
+            Some(BindingType::Sampler { comparison: a || b })
+        }
+        (a, b) if a == b => Some(a),
+        _ => None,
+    }
+}
+
+/// Declarations of a SPIR-V module, up to its first function
+#[derive(Default)]
+struct Module {
+    version: u32,
+    names: HashMap<u32, String>,
+    entry_points: Vec<EntryPoint>,
+    /// Decorations and their first operand, 0 if they have none
+    decorations: HashMap<u32, Vec<(u32, u32)>>,
+    member_decorations: HashMap<(u32, u32), Vec<(u32, u32)>>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    variables: Vec<Variable>,
+}
+
+struct EntryPoint {
+    model: u32,
+    name: String,
+    interface: Vec<u32>,
+}
+
+struct Variable {
+    id: u32,
+    ty: u32,
+    storage: u32,
+}
+
+enum Type {
+    Bool,
+    Int {
+        width: u32,
+        signed: bool,
+    },
+    Float {
+        width: u32,
+    },
+    Vector {
+        component: u32,
+        count: u32,
+    },
+    Matrix {
+        column: u32,
+        count: u32,
+    },
+    Image(Image),
+    Sampler,
+    SampledImage {
+        image: u32,
+    },
+    /// `length` is the id of a constant
+    Array {
+        element: u32,
+        length: u32,
+    },
+    RuntimeArray {
+        element: u32,
+    },
+    Struct {
+        members: Vec<u32>,
+    },
+    Pointer {
+        pointee: u32,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Image {
+    dim: u32,
+    depth: u32,
+    arrayed: u32,
+    /// 1 for sampled images, 2 for storage images
+    sampled: u32,
+    format: u32,
+}
+
+impl Module {
+    fn parse(code: &[u8]) -> Result<Self, GfxError> {
+        if !is_spirv(code) || code.len() < 20 || !code.len().is_multiple_of(4) {
+            return Err(GfxError::InvalidDesc(
+                "shader code is not a SPIR-V module".to_string(),
+            ));
+        }
+        let words: Vec<u32> = code
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+        let mut module = Module {
+            version: words[1],
+            ..Default::default()
+        };
+
+        let mut rest = &words[5..];
+        while let Some(&first) = rest.first() {
+            let count = (first >> 16) as usize;
+            if count == 0 || count > rest.len() {
+                return Err(GfxError::InvalidDesc(
+                    "SPIR-V module ends inside an instruction".to_string(),
+                ));
+            }
+            let (instruction, next) = rest.split_at(count);
+            rest = next;
+            let (id, ty) = match (first & 0xffff, &instruction[1..]) {
+                // Every declaration comes before the functions
+                (OP_FUNCTION, _) => break,
+                (OP_NAME, [target, name @ ..]) => {
+                    module.names.insert(*target, literal_string(name).0);
+                    continue;
+                }
+                (OP_ENTRY_POINT, [model, _, operands @ ..]) => {
+                    let (name, used) = literal_string(operands);
+                    module.entry_points.push(EntryPoint {
+                        model: *model,
+                        name,
+                        interface: operands[used..].to_vec(),
+                    });
+                    continue;
+                }
+                (OP_DECORATE, [target, decoration, operands @ ..]) => {
+                    module
+                        .decorations
+                        .entry(*target)
+                        .or_default()
+                        .push((*decoration, operands.first().copied().unwrap_or(0)));
+                    continue;
+                }
+                (OP_MEMBER_DECORATE, [target, member, decoration, operands @ ..]) => {
+                    module
+                        .member_decorations
+                        .entry((*target, *member))
+                        .or_default()
+                        .push((*decoration, operands.first().copied().unwrap_or(0)));
+                    continue;
+                }
+                (OP_CONSTANT, [_, id, value, ..]) => {
+                    module.constants.insert(*id, *value);
+                    continue;
+                }
+                (OP_VARIABLE, [ty, id, storage, ..]) => {
+                    module.variables.push(Variable {
+                        id: *id,
+                        ty: *ty,
+                        storage: *storage,
+                    });
+                    continue;
+                }
+                (OP_TYPE_BOOL, [id]) => (id, Type::Bool),
+                (OP_TYPE_INT, [id, width, signed]) => (
+                    id,
+                    Type::Int {
+                        width: *width,
+                        signed: *signed != 0,
+                    },
+                ),
+                (OP_TYPE_FLOAT, [id, width, ..]) => (id, Type::Float { width: *width }),
+                (OP_TYPE_VECTOR, [id, component, count]) => (
+                    id,
+                    Type::Vector {
+                        component: *component,
+                        count: *count,
+                    },
+                ),
+                (OP_TYPE_MATRIX, [id, column, count]) => (
+                    id,
+                    Type::Matrix {
+                        column: *column,
+                        count: *count,
+                    },
+                ),
+                (OP_TYPE_IMAGE, [id, _, dim, depth, arrayed, _, sampled, format, ..]) => (
+                    id,
+                    Type::Image(Image {
+                        dim: *dim,
+                        depth: *depth,
+                        arrayed: *arrayed,
+                        sampled: *sampled,
+                        format: *format,
+                    }),
+                ),
+                (OP_TYPE_SAMPLER, [id]) => (id, Type::Sampler),
+                (OP_TYPE_SAMPLED_IMAGE, [id, image]) => (id, Type::SampledImage { image: *image }),
+                (OP_TYPE_ARRAY, [id, element, length]) => (
+                    id,
+                    Type::Array {
+                        element: *element,
+                        length: *length,
+                    },
+                ),
+                (OP_TYPE_RUNTIME_ARRAY, [id, element]) => {
+                    (id, Type::RuntimeArray { element: *element })
+                }
+                (OP_TYPE_STRUCT, [id, members @ ..]) => (
+                    id,
+                    Type::Struct {
+                        members: members.to_vec(),
+                    },
+                ),
+                (OP_TYPE_POINTER, [id, _, pointee]) => (id, Type::Pointer { pointee: *pointee }),
+                _ => continue,
+            };
+            module.types.insert(*id, ty);
+        }
+        Ok(module)
+    }
+
+    fn decoration(&self, id: u32, decoration: u32) -> Option<u32> {
+        find_decoration(self.decorations.get(&id)?, decoration)
+    }
+
+    fn member_decoration(&self, id: u32, member: u32, decoration: u32) -> Option<u32> {
+        find_decoration(self.member_decorations.get(&(id, member))?, decoration)
+    }
+
+    /// Locations a vertex input of type `ty` takes, and the component type
+    /// and count of each
+    fn input_slots(&self, ty: u32) -> Option<(u32, ScalarKind, u32)> {
+        match self.types.get(&ty)? {
+            Type::Vector { component, count } => {
+                let (_, kind, _) = self.input_slots(*component)?;
+                Some((1, kind, *count))
+            }
+            // A column per location
+            Type::Matrix { column, count } => {
+                let (_, kind, components) = self.input_slots(*column)?;
+                Some((*count, kind, components))
+            }
+            Type::Array { element, length } => {
+                let (slots, kind, components) = self.input_slots(*element)?;
+                Some((slots * self.constants.get(length)?, kind, components))
+            }
+            Type::Float { .. } => Some((1, ScalarKind::Float, 1)),
+            Type::Int { signed: true, .. } => Some((1, ScalarKind::Sint, 1)),
+            Type::Int { signed: false, .. } => Some((1, ScalarKind::Uint, 1)),
+            _ => None,
+        }
+    }
+
+    /// Slots a resource variable takes, relative to its binding
+    fn binding_types(&self, variable: &Variable, pointee: u32) -> Option<Vec<(u32, BindingType)>> {
+        // Arrays of resources take one slot; the API binds one resource each
+        let mut ty = pointee;
+        while let Type::Array { element, .. } | Type::RuntimeArray { element } =
+            self.types.get(&ty)?
+        {
+            ty = *element;
+        }
+        let storage_buffer = variable.storage == STORAGE_BUFFER
+            || (variable.storage == UNIFORM && self.decoration(ty, BUFFER_BLOCK).is_some());
+        if storage_buffer {
+            let read_only = self.decoration(variable.id, NON_WRITABLE).is_some()
+                || matches!(self.types.get(&ty), Some(Type::Struct { members })
+                    if (0..members.len() as u32)
+                        .all(|member| self.member_decoration(ty, member, NON_WRITABLE).is_some()));
+            return Some(vec![(0, BindingType::StorageBuffer { read_only })]);
+        }
+        if variable.storage == UNIFORM {
+            return Some(vec![(0, BindingType::UniformBuffer)]);
+        }
+
+        match self.types.get(&ty)? {
+            Type::Sampler => Some(vec![(0, BindingType::Sampler { comparison: false })]),
+            Type::Image(image) if image.sampled == 2 => {
+                let access = match (
+                    self.decoration(variable.id, NON_WRITABLE),
+                    self.decoration(variable.id, NON_READABLE),
+                ) {
+                    (Some(_), _) => StorageTextureAccess::ReadOnly,
+                    (None, Some(_)) => StorageTextureAccess::WriteOnly,
+                    (None, None) => StorageTextureAccess::ReadWrite,
+                };
+                let format = match image.format {
+                    1 => TextureFormat::Rgba32f,
+                    2 => TextureFormat::Rgba16f,
+                    4 => TextureFormat::Rgba8,
+                    _ => return None,
+                };
+                Some(vec![(
+                    0,
+                    BindingType::StorageTexture {
+                        format,
+                        dimension: image_dimension(image)?,
+                        access,
+                    },
+                )])
+            }
+            Type::Image(image) => Some(vec![(0, texture_binding(image)?)]),
+            Type::SampledImage { image } => {
+                let Some(Type::Image(image)) = self.types.get(image) else {
+                    return None;
+                };
+                Some(vec![
+                    (0, texture_binding(image)?),
+                    (
+                        1,
+                        BindingType::Sampler {
+                            comparison: image.depth == 1,
+                        },
+                    ),
+                ])
+            }
+            _ => None,
+        }
+    }
+
+    /// Bytes the members of a push constant block of type `ty` span
+    fn push_constants(&self, ty: u32) -> PushConstantRange {
+        let Some(Type::Struct { members }) = self.types.get(&ty) else {
+            return PushConstantRange {
+                offset: 0,
+                size: self.size_of(ty),
+            };
+        };
+        let spans = (0..members.len() as u32).map(|member| self.member_span(ty, member));
+        let offset = spans.clone().map(|(start, _)| start).min().unwrap_or(0);
+        let end = spans.map(|(_, end)| end).max().unwrap_or(0);
+        PushConstantRange {
+            offset,
+            size: end - offset,
+        }
+    }
+
+    /// Start and end of member `member` of the struct `ty`
+    fn member_span(&self, ty: u32, member: u32) -> (u32, u32) {
+        let Some(Type::Struct { members }) = self.types.get(&ty) else {
+            return (0, 0);
+        };
+        let member_ty = members[member as usize];
+        let start = self.member_decoration(ty, member, OFFSET).unwrap_or(0);
+        let size = match (
+            self.types.get(&member_ty),
+            self.member_decoration(ty, member, MATRIX_STRIDE),
+        ) {
+            (Some(Type::Matrix { count, .. }), Some(stride)) => count * stride,
+            _ => self.size_of(member_ty),
+        };
+        (start, start + size)
+    }
+
+    /// Size of a value of type `ty` in a buffer, in bytes
+    fn size_of(&self, ty: u32) -> u32 {
+        match self.types.get(&ty) {
+            Some(Type::Bool) => 4,
+            Some(Type::Int { width, .. } | Type::Float { width }) => width / 8,
+            Some(Type::Vector { component, count }) => count * self.size_of(*component),
+            Some(Type::Matrix { column, count }) => count * self.size_of(*column),
+            Some(Type::Array { element, length }) => {
+                let stride = self
+                    .decoration(ty, ARRAY_STRIDE)
+                    .unwrap_or_else(|| self.size_of(*element));
+                stride * self.constants.get(length).copied().unwrap_or(0)
+            }
+            Some(Type::Struct { members }) => (0..members.len() as u32)
+                .map(|member| self.member_span(ty, member).1)
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
+fn find_decoration(decorations: &[(u32, u32)], decoration: u32) -> Option<u32> {
+    decorations
+        .iter()
+        .find(|(found, _)| *found == decoration)
+        .map(|(_, operand)| *operand)
+}
+
+fn image_dimension(image: &Image) -> Option<TextureDimension> {
+    match image.dim {
+        0 => Some(TextureDimension::D1),
+        1 => Some(TextureDimension::D2),
+        2 => Some(TextureDimension::D3),
+        3 => Some(TextureDimension::Cube),
+        _ => None,
+    }
+}
+
+fn texture_binding(image: &Image) -> Option<BindingType> {
+    Some(BindingType::Texture {
+        dimension: image_dimension(image)?,
+        array: image.arrayed == 1,
+        comparison: image.depth == 1,
+    })
+}
+
+/// A string operand and the words it takes
+fn literal_string(words: &[u32]) -> (String, usize) {
+    let mut bytes = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        for byte in word.to_le_bytes() {
+            if byte == 0 {
+                return (String::from_utf8_lossy(&bytes).into_owned(), i + 1);
+            }
+            bytes.push(byte);
+        }
+    }
+    (String::from_utf8_lossy(&bytes).into_owned(), words.len())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Words of a SPIR-V module, header first
+    struct Assembler {
+        words: Vec<u32>,
+    }
+
+    impl Assembler {
+        fn new(version: u32) -> Self {
+            Self {
+                words: vec![SPIRV_MAGIC, version, 0, 100, 0],
+            }
+        }
+
+        fn op(&mut self, op: u32, operands: &[u32]) -> &mut Self {
+            self.words.push(((operands.len() as u32 + 1) << 16) | op);
+            self.words.extend_from_slice(operands);
+            self
+        }
+
+        fn string(text: &str) -> Vec<u32> {
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.resize(text.len() / 4 * 4 + 4, 0);
+            bytes
+                .chunks(4)
+                .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                .collect()
+        }
+
+        fn bytes(&self) -> Vec<u8> {
+            self.words
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect()
+        }
+    }
+
+    /// SPIR-V 1.0 vertex shader `main` reading a vec2 at location 0 and a
+    /// vec4 at location 1, with a uniform buffer at set 0 binding 0, a
+    /// read-only storage buffer at binding 2, a `sampler2D` at set 1
+    /// binding 0 and a mat4 + vec4 push constant block
+    pub(crate) fn vertex_module() -> Vec<u8> {
+        let (float, vec2, vec4, mat4, uint) = (2, 3, 4, 5, 6);
+        let (image, sampled_image, block, ssbo_block, push_block) = (7, 8, 9, 10, 11);
+        let (position, color, vertex_index, ubo, ssbo, texture, push) =
+            (20, 21, 22, 23, 24, 25, 26);
+        let mut asm = Assembler::new(0x0001_0000);
+        let mut entry = vec![0, 1];
+        entry.extend(Assembler::string("main"));
+        entry.extend([position, color, vertex_index]);
+        asm.op(OP_ENTRY_POINT, &entry);
+        let mut name = vec![position];
+        name.extend(Assembler::string("position"));
+        asm.op(OP_NAME, &name)
+            .op(OP_DECORATE, &[position, LOCATION, 0])
+            .op(OP_DECORATE, &[color, LOCATION, 1])
+            .op(OP_DECORATE, &[vertex_index, BUILT_IN, 42])
+            .op(OP_DECORATE, &[ubo, DESCRIPTOR_SET, 0])
+            .op(OP_DECORATE, &[ubo, BINDING, 0])
+            .op(OP_DECORATE, &[ssbo_block, BUFFER_BLOCK])
+            .op(OP_DECORATE, &[ssbo, DESCRIPTOR_SET, 0])
+            .op(OP_DECORATE, &[ssbo, BINDING, 2])
+            .op(OP_DECORATE, &[ssbo, NON_WRITABLE])
+            .op(OP_DECORATE, &[texture, DESCRIPTOR_SET, 1])
+            .op(OP_DECORATE, &[texture, BINDING, 0])
+            .op(OP_MEMBER_DECORATE, &[block, 0, OFFSET, 0])
+            .op(OP_MEMBER_DECORATE, &[ssbo_block, 0, OFFSET, 0])
+            .op(OP_MEMBER_DECORATE, &[push_block, 0, OFFSET, 0])
+            .op(OP_MEMBER_DECORATE, &[push_block, 0, MATRIX_STRIDE, 16])
+            .op(OP_MEMBER_DECORATE, &[push_block, 1, OFFSET, 64])
+            .op(OP_TYPE_FLOAT, &[float, 32])
+            .op(OP_TYPE_VECTOR, &[vec2, float, 2])
+            .op(OP_TYPE_VECTOR, &[vec4, float, 4])
+            .op(OP_TYPE_MATRIX, &[mat4, vec4, 4])
+            .op(OP_TYPE_INT, &[uint, 32, 0])
+            .op(OP_TYPE_IMAGE, &[image, float, 1, 0, 0, 0, 1, 0])
+            .op(OP_TYPE_SAMPLED_IMAGE, &[sampled_image, image])
+            .op(OP_TYPE_STRUCT, &[block, mat4])
+            .op(OP_TYPE_STRUCT, &[ssbo_block, vec4])
+            .op(OP_TYPE_STRUCT, &[push_block, mat4, vec4]);
+        for (pointer, storage, pointee, variable) in [
+            (30, INPUT, vec2, position),
+            (31, INPUT, vec4, color),
+            (32, INPUT, uint, vertex_index),
+            (33, UNIFORM, block, ubo),
+            (34, UNIFORM, ssbo_block, ssbo),
+            (35, UNIFORM_CONSTANT, sampled_image, texture),
+            (36, PUSH_CONSTANT, push_block, push),
+        ] {
+            asm.op(OP_TYPE_POINTER, &[pointer, storage, pointee])
+                .op(OP_VARIABLE, &[pointer, variable, storage]);
+        }
+        asm.op(OP_FUNCTION, &[]).bytes()
+    }
+
+    fn vertex_desc() -> ShaderDesc {
+        ShaderDesc {
+            stage: ShaderStage::Vertex,
+            entry_point: "main".to_string(),
+            code: vertex_module(),
+        }
+    }
+
+    #[test]
+    fn test_reflect_vertex_shader() {
+        let reflection = ShaderReflection::new(&vertex_desc()).unwrap();
+        assert_eq!(
+            reflection.vertex_inputs,
+            vec![
+                ReflectedVertexInput {
+                    location: 0,
+                    name: "position".to_string(),
+                    kind: ScalarKind::Float,
+                    components: 2,
+                },
+                ReflectedVertexInput {
+                    location: 1,
+                    name: String::new(),
+                    kind: ScalarKind::Float,
+                    components: 4,
+                },
+            ]
+        );
+        let slots: Vec<_> = reflection
+            .bindings
+            .iter()
+            .map(|binding| (binding.set, binding.binding, binding.ty))
+            .collect();
+        assert_eq!(
+            slots,
+            vec![
+                (0, 0, BindingType::UniformBuffer),
+                (0, 2, BindingType::StorageBuffer { read_only: true }),
+                (
+                    1,
+                    0,
+                    BindingType::Texture {
+                        dimension: TextureDimension::D2,
+                        array: false,
+                        comparison: false,
+                    }
+                ),
+                (1, 1, BindingType::Sampler { comparison: false }),
+            ]
+        );
+        assert_eq!(
+            reflection.push_constants,
+            vec![PushConstantRange {
+                offset: 0,
+                size: 80
+            }]
+        );
+
+        let layout = reflection.vertex_layout().unwrap();
+        assert_eq!(layout.stride, 24);
+        assert_eq!(layout.attributes[1].format, VertexFormat::Float4);
+        assert_eq!(layout.attributes[1].offset, 8);
+        reflection.validate_vertex_layout(&layout).unwrap();
+    }
+
+    #[test]
+    fn test_validate_vertex_layout() {
+        let reflection = ShaderReflection::new(&vertex_desc()).unwrap();
+        let mut layout = reflection.vertex_layout().unwrap();
+        // Fewer components than the shader reads are filled in
+        layout.attributes[1].format = VertexFormat::Float3;
+        reflection.validate_vertex_layout(&layout).unwrap();
+
+        layout.attributes[0].format = VertexFormat::UInt2;
+        let err = reflection.validate_vertex_layout(&layout).unwrap_err();
+        assert!(err.to_string().contains("location 0 (`position`)"));
+
+        layout.attributes.remove(0);
+        let err = reflection.validate_vertex_layout(&layout).unwrap_err();
+        assert!(err.to_string().contains("does not provide"));
+    }
+
+    #[test]
+    fn test_bind_group_layouts() {
+        let vertex = ShaderReflection::new(&vertex_desc()).unwrap();
+        let mut fragment = vertex.clone();
+        fragment.bindings = vec![
+            ReflectedBinding {
+                set: 0,
+                binding: 2,
+                name: String::new(),
+                ty: BindingType::StorageBuffer { read_only: false },
+            },
+            ReflectedBinding {
+                set: 2,
+                binding: 0,
+                name: String::new(),
+                ty: BindingType::UniformBuffer,
+            },
+        ];
+        let layouts = ShaderReflection::bind_group_layouts(&[&vertex, &fragment]).unwrap();
+        assert_eq!(layouts.len(), 3);
+        assert_eq!(
+            layouts[0].entries[1],
+            BindGroupLayoutEntry {
+                binding: 2,
+                ty: BindingType::StorageBuffer { read_only: false },
+            }
+        );
+        assert_eq!(layouts[1].entries.len(), 2);
+        assert_eq!(layouts[2].entries[0].ty, BindingType::UniformBuffer);
+
+        fragment.bindings[0].ty = BindingType::UniformBuffer;
+        assert!(ShaderReflection::bind_group_layouts(&[&vertex, &fragment]).is_err());
+    }
+
+    #[test]
+    fn test_reflect_errors() {
+        let mut desc = vertex_desc();
+        desc.stage = ShaderStage::Fragment;
+        let err = ShaderReflection::new(&desc).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no Fragment entry point named 'main'"));
+
+        desc.stage = ShaderStage::Vertex;
+        desc.code.truncate(desc.code.len() - 8);
+        assert!(ShaderReflection::new(&desc).is_err());
+        assert!(!is_spirv(b"#version 450"));
+    }
+
+    #[cfg(feature = "shader-compiler")]
+    #[test]
+    fn test_reflect_compiled_wgsl() {
+        use crate::gfx::shader_compiler::{ShaderCompiler, ShaderLanguage};
+
+        let source = "struct Globals { transform: mat4x4<f32> }
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(1) @binding(0) var albedo: texture_2d<f32>;
+@group(1) @binding(1) var albedo_sampler: sampler;
+
+struct Output { @builtin(position) position: vec4<f32>, @location(0) uv: vec2<f32> }
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) uv: vec2<f32>) -> Output {
+    return Output(globals.transform * vec4<f32>(position, 1.0), uv);
+}
+
+@fragment
+fn fs_main(input: Output) -> @location(0) vec4<f32> {
+    return textureSample(albedo, albedo_sampler, input.uv);
+}
+";
+        let compiler = ShaderCompiler::new();
+        let compile = |stage, entry_point| {
+            let desc = compiler
+                .compile(
+                    "mesh.wgsl",
+                    source,
+                    ShaderLanguage::Wgsl,
+                    stage,
+                    entry_point,
+                )
+                .unwrap();
+            ShaderReflection::new(&desc).unwrap()
+        };
+        let vertex = compile(ShaderStage::Vertex, "vs_main");
+        let fragment = compile(ShaderStage::Fragment, "fs_main");
+
+        let layout = vertex.vertex_layout().unwrap();
+        assert_eq!(layout.stride, 20);
+        assert_eq!(layout.attributes[0].format, VertexFormat::Float3);
+        assert_eq!(layout.attributes[1].format, VertexFormat::Float2);
+
+        let layouts = ShaderReflection::bind_group_layouts(&[&vertex, &fragment]).unwrap();
+        assert_eq!(layouts[0].entries[0].ty, BindingType::UniformBuffer);
+        assert_eq!(layouts[1].entries.len(), 2);
+        assert_eq!(
+            layouts[1].entries[1].ty,
+            BindingType::Sampler { comparison: false }
+        );
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Render target pool
+//!
+//! Post-processing chains and other work outside the frame graph need
+//! temporary targets every frame: a half-resolution blur, a ping-pong pair.
+//! `RenderTargetPool` hands them out by `TextureDesc` and takes them back
+//! when the pass is done, so the next request with the same description
+//! reuses the texture instead of creating one. Textures nobody asked for in
+//! a few frames, and the least recently used ones past a memory budget, are
+//! destroyed at the end of a frame.
+
+use crate::gfx::api::{GfxError, GpuDevice, TextureDesc, TextureHandle};
+use crate::gfx::backend::texture_size;
+use std::collections::HashMap;
+
+/// Frames a released texture is kept for when no budget evicts it sooner
+pub const DEFAULT_MAX_IDLE_FRAMES: u64 = 4;
+
+/// Requests a `RenderTargetPool` answered and what it holds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderTargetPoolStats {
+    /// `acquire` calls that reused a released texture
+    pub hits: u64,
+    /// `acquire` calls that created a texture
+    pub misses: u64,
+    /// Released textures destroyed for being idle or over the budget
+    pub evictions: u64,
+    /// Textures handed out and not released yet
+    pub in_use: usize,
+    /// Released textures kept for reuse
+    pub free: usize,
+    /// Device memory of the free textures
+    pub free_bytes: u64,
+}
+
+/// A released texture waiting to be reused
+struct FreeTarget {
+    desc: TextureDesc,
+    texture: TextureHandle,
+    size: u64,
+    /// Frame it was released in
+    released: u64,
+}
+
+/// Temporary textures by description, recycled across frames
+///
+/// `acquire` a target while recording, `release` it once the passes that
+/// use it are recorded, and call `end_frame` once per frame. Contents do not
+/// survive a release. The pool owns its textures: do not destroy them, and
+/// call `destroy` before dropping the pool.
+pub struct RenderTargetPool {
+    /// Least recently released first
+    free: Vec<FreeTarget>,
+    in_use: HashMap<TextureHandle, TextureDesc>,
+    frame: u64,
+    max_idle_frames: u64,
+    budget: u64,
+    free_bytes: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl Default for RenderTargetPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderTargetPool {
+    pub fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            in_use: HashMap::new(),
+            frame: 0,
+            max_idle_frames: DEFAULT_MAX_IDLE_FRAMES,
+            budget: u64::MAX,
+            free_bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Keep released textures for `frames` frames without a request
+    pub fn with_max_idle_frames(mut self, frames: u64) -> Self {
+        self.max_idle_frames = frames;
+        self
+    }
+
+    /// Keep at most `bytes` of released textures, evicting the least
+    /// recently used first
+    pub fn with_budget(mut self, bytes: u64) -> Self {
+        self.budget = bytes;
+        self
+    }
+
+    /// A texture matching `desc`: the most recently released one, or a new
+    /// one
+    pub fn acquire(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        desc: &TextureDesc,
+    ) -> Result<TextureHandle, GfxError> {
+        let texture = match self.free.iter().rposition(|target| target.desc == *desc) {
+            Some(index) => {
+                let target = self.free.remove(index);
+                self.free_bytes -= target.size;
+                self.hits += 1;
+                target.texture
+            }
+            None => {
+                let texture = device.create_texture(desc, None)?;
+                self.misses += 1;
+                texture
+            }
+        };
+        self.in_use.insert(texture, desc.clone());
+        Ok(texture)
+    }
+
+    /// Give a texture from `acquire` back for reuse
+    ///
+    /// It may be handed out again within the same frame; commands recorded
+    /// before still see it as theirs. Textures the pool did not hand out
+    /// are ignored.
+    pub fn release(&mut self, texture: TextureHandle) {
+        let Some(desc) = self.in_use.remove(&texture) else {
+            return;
+        };
+        let size = texture_size(&desc);
+        self.free_bytes += size;
+        self.free.push(FreeTarget {
+            desc,
+            texture,
+            size,
+            released: self.frame,
+        });
+    }
+
+    /// Finish the frame: destroy released textures idle for more than the
+    /// idle limit, then the least recently used ones past the budget
+    pub fn end_frame(&mut self, device: &mut dyn GpuDevice) {
+        self.frame += 1;
+        let idle = self
+            .free
+            .iter()
+            .take_while(|target| self.frame - target.released > self.max_idle_frames)
+            .count();
+        let mut bytes = self.free_bytes;
+        let over_budget = self
+            .free
+            .iter()
+            .take_while(|target| {
+                let evict = bytes > self.budget;
+                bytes -= target.size;
+                evict
+            })
+            .count();
+        for target in self.free.drain(..idle.max(over_budget)) {
+            self.free_bytes -= target.size;
+            self.evictions += 1;
+            device.destroy_texture(target.texture);
+        }
+    }
+
+    pub fn stats(&self) -> RenderTargetPoolStats {
+        RenderTargetPoolStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            in_use: self.in_use.len(),
+            free: self.free.len(),
+            free_bytes: self.free_bytes,
+        }
+    }
+
+    /// Destroy the released textures, after a resize for instance
+    pub fn clear(&mut self, device: &mut dyn GpuDevice) {
+        for target in self.free.drain(..) {
+            device.destroy_texture(target.texture);
+        }
+        self.free_bytes = 0;
+    }
+
+    /// Destroy every texture of the pool, those in use included
+    pub fn destroy(mut self, device: &mut dyn GpuDevice) {
+        self.clear(device);
+        for texture in self.in_use.into_keys() {
+            device.destroy_texture(texture);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{RendererConfig, TextureFormat, TextureUsage};
+    use crate::gfx::backend::{create_device, BackendDevice};
+
+    fn target(width: u32) -> TextureDesc {
+        TextureDesc::new_2d(
+            width,
+            width,
+            TextureFormat::Rgba16f,
+            TextureUsage::COLOR_ATTACHMENT | TextureUsage::SAMPLED,
+        )
+    }
+
+    fn counts(device: &BackendDevice) -> (usize, usize) {
+        let stats = &device.memory_stats()[0];
+        (stats.allocation_count, stats.deallocation_count)
+    }
+
+    #[test]
+    fn test_reuse_by_desc() {
+        let mut device = create_device(RendererConfig::default());
+        let mut pool = RenderTargetPool::new();
+
+        let a = pool.acquire(&mut device, &target(64)).unwrap();
+        let b = pool.acquire(&mut device, &target(64)).unwrap();
+        assert_ne!(a, b);
+        pool.release(a);
+        pool.release(b);
+
+        // The most recently released match comes back first
+        assert_eq!(pool.acquire(&mut device, &target(64)).unwrap(), b);
+        let c = pool.acquire(&mut device, &target(32)).unwrap();
+        assert_ne!(c, a);
+        assert_eq!(counts(&device), (3, 0));
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 3));
+        assert_eq!((stats.in_use, stats.free), (2, 1));
+        assert_eq!(stats.free_bytes, 64 * 64 * 8);
+
+        // Unknown and double releases are ignored
+        pool.release(TextureHandle(1234));
+        pool.release(c);
+        pool.release(c);
+        assert_eq!(pool.stats().free, 2);
+
+        pool.destroy(&mut device);
+        assert_eq!(counts(&device), (3, 3));
+    }
+
+    #[test]
+    fn test_idle_eviction() {
+        let mut device = create_device(RendererConfig::default());
+        let mut pool = RenderTargetPool::new().with_max_idle_frames(2);
+
+        let a = pool.acquire(&mut device, &target(16)).unwrap();
+        let b = pool.acquire(&mut device, &target(16)).unwrap();
+        pool.release(a);
+        pool.end_frame(&mut device);
+        pool.release(b);
+        pool.end_frame(&mut device);
+        pool.end_frame(&mut device);
+        assert_eq!(pool.stats().free, 1);
+        assert_eq!(counts(&device), (2, 1));
+
+        // A request resets the idle time
+        let b = pool.acquire(&mut device, &target(16)).unwrap();
+        pool.release(b);
+        pool.end_frame(&mut device);
+        pool.end_frame(&mut device);
+        assert_eq!(pool.stats().free, 1);
+        pool.end_frame(&mut device);
+        assert_eq!(pool.stats().free, 0);
+        assert_eq!(pool.stats().evictions, 2);
+        assert_eq!(counts(&device), (2, 2));
+    }
+
+    #[test]
+    fn test_budget_evicts_least_recently_used() {
+        let mut device = create_device(RendererConfig::default());
+        let size = 32 * 32 * 8;
+        let mut pool = RenderTargetPool::new().with_budget(2 * size);
+
+        let targets: Vec<_> = (0..3)
+            .map(|_| pool.acquire(&mut device, &target(32)).unwrap())
+            .collect();
+        for &texture in &targets {
+            pool.release(texture);
+        }
+        pool.end_frame(&mut device);
+        let stats = pool.stats();
+        assert_eq!(
+            (stats.free, stats.free_bytes, stats.evictions),
+            (2, 2 * size, 1)
+        );
+
+        // The first released was evicted
+        let again: Vec<_> = (0..3)
+            .map(|_| pool.acquire(&mut device, &target(32)).unwrap())
+            .collect();
+        assert_eq!(&again[..2], &[targets[2], targets[1]]);
+        assert_eq!(counts(&device), (4, 1));
+        pool.destroy(&mut device);
+    }
+}
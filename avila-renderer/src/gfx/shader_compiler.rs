@@ -0,0 +1,597 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Runtime shader compiler (feature `shader-compiler`)
+//!
+//! Compiles GLSL or WGSL to the SPIR-V that `ShaderDesc::code` takes, with
+//! naga, so shaders need no offline build step.
+//!
+//! Before compiling, `#include "name"` lines are replaced by the named file:
+//! an include registered with `with_include`, else a file next to the one
+//! including it, else one in an include directory. Each file is included
+//! once per shader, as if it began with `#pragma once`.
+//!
+//! Defines reach GLSL through its preprocessor. WGSL has none, so each
+//! define becomes `const NAME = VALUE;` at the top of the module (defines
+//! without a value only declare nothing), and `#ifdef NAME`, `#ifndef NAME`,
+//! `#else` and `#endif` lines keep or drop the lines between them.
+//!
+//! The SPIR-V keeps WebGPU's clip space (y up), as the wgpu backend
+//! expects. OpenGL takes GLSL source instead: pass it the output of
+//! `preprocess`.
+
+use crate::gfx::api::{GfxError, ShaderDesc, ShaderStage};
+use avila_math::os::FileSystem;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File name of the lines `const` declarations are made from in WGSL
+const DEFINES_FILE: &str = "<defines>";
+
+/// Source language of a shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderLanguage {
+    /// GLSL 4.50 with Vulkan semantics (`layout(set, binding)`); the entry
+    /// point is always `main`
+    Glsl,
+    Wgsl,
+}
+
+impl ShaderLanguage {
+    /// Language of a file by its extension: `.wgsl` is WGSL, `.glsl`,
+    /// `.vert`, `.frag` and `.comp` are GLSL
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "wgsl" => Some(ShaderLanguage::Wgsl),
+            "glsl" | "vert" | "frag" | "comp" => Some(ShaderLanguage::Glsl),
+            _ => None,
+        }
+    }
+}
+
+/// One problem found while compiling a shader
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    /// Name of the shader or path of the included file the problem is in
+    pub file: String,
+    /// 1-based line and column; 0 when the problem has no position
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (0, _) => write!(f, "{}: {}", self.file, self.message),
+            (line, 0) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            (line, column) => write!(f, "{}:{}:{}: {}", self.file, line, column, self.message),
+        }
+    }
+}
+
+/// Why a shader does not compile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderCompileError {
+    /// At least one
+    pub diagnostics: Vec<ShaderDiagnostic>,
+}
+
+impl fmt::Display for ShaderCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ShaderCompileError {}
+
+impl From<ShaderCompileError> for GfxError {
+    fn from(err: ShaderCompileError) -> Self {
+        GfxError::ShaderCompileError(err.to_string())
+    }
+}
+
+/// GLSL and WGSL to SPIR-V compiler
+///
+/// Configured once with include directories, in-memory includes and
+/// defines, then used for every shader.
+#[derive(Clone, Debug, Default)]
+pub struct ShaderCompiler {
+    include_dirs: Vec<PathBuf>,
+    includes: HashMap<String, String>,
+    defines: Vec<(String, String)>,
+}
+
+impl ShaderCompiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look for included files in `dir` too, after the directory of the
+    /// file including them
+    pub fn with_include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    /// Serve `#include "name"` from memory
+    pub fn with_include(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.includes.insert(name.into(), source.into());
+        self
+    }
+
+    /// Define the macro `name` as `value` (empty for a bare define)
+    pub fn with_define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.defines.retain(|(defined, _)| *defined != name);
+        self.defines.push((name, value.into()));
+        self
+    }
+
+    /// Compile `source` to a SPIR-V shader with one entry point
+    ///
+    /// `name` labels the source in diagnostics and locates its includes
+    /// when it is a path.
+    pub fn compile(
+        &self,
+        name: &str,
+        source: &str,
+        language: ShaderLanguage,
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<ShaderDesc, ShaderCompileError> {
+        let shader_stage = match stage {
+            ShaderStage::Vertex => naga::ShaderStage::Vertex,
+            ShaderStage::Fragment => naga::ShaderStage::Fragment,
+            ShaderStage::Compute => naga::ShaderStage::Compute,
+            ShaderStage::Geometry | ShaderStage::TessControl | ShaderStage::TessEvaluation => {
+                return Err(error(
+                    name,
+                    format!(
+                        "{:?} shaders cannot be compiled to SPIR-V at runtime",
+                        stage
+                    ),
+                ));
+            }
+        };
+        let unit = self.expand(name, source, language)?;
+
+        let module = match language {
+            ShaderLanguage::Glsl => {
+                let options = naga::front::glsl::Options {
+                    stage: shader_stage,
+                    defines: self.defines.iter().cloned().collect(),
+                };
+                naga::front::glsl::Frontend::default()
+                    .parse(&options, &unit.source)
+                    .map_err(|errors| ShaderCompileError {
+                        diagnostics: errors
+                            .errors
+                            .iter()
+                            .map(|err| unit.diagnostic(Some(err.meta), err.kind.to_string()))
+                            .collect(),
+                    })?
+            }
+            ShaderLanguage::Wgsl => naga::front::wgsl::parse_str(&unit.source).map_err(|err| {
+                let span = err.labels().next().map(|(span, _)| span);
+                ShaderCompileError {
+                    diagnostics: vec![unit.diagnostic(span, err.message().to_string())],
+                }
+            })?,
+        };
+
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| {
+            let span = err.spans().next().map(|(span, _)| *span);
+            ShaderCompileError {
+                diagnostics: vec![unit.diagnostic(span, error_chain(err.as_inner()))],
+            }
+        })?;
+
+        let entry_point = match language {
+            ShaderLanguage::Glsl => "main",
+            ShaderLanguage::Wgsl => entry_point,
+        };
+        if !module
+            .entry_points
+            .iter()
+            .any(|entry| entry.name == entry_point && entry.stage == shader_stage)
+        {
+            return Err(error(
+                name,
+                format!("no {:?} entry point named '{}'", stage, entry_point),
+            ));
+        }
+        let options = naga::back::spv::Options {
+            // Leave clip space as WebGPU has it; wgpu reads SPIR-V that way
+            flags: naga::back::spv::WriterFlags::LABEL_VARYINGS
+                | naga::back::spv::WriterFlags::CLAMP_FRAG_DEPTH,
+            ..Default::default()
+        };
+        let pipeline_options = naga::back::spv::PipelineOptions {
+            shader_stage,
+            entry_point: entry_point.to_string(),
+        };
+        let words = naga::back::spv::write_vec(&module, &info, &options, Some(&pipeline_options))
+            .map_err(|err| error(name, error_chain(&err)))?;
+
+        Ok(ShaderDesc {
+            stage,
+            entry_point: entry_point.to_string(),
+            code: words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+        })
+    }
+
+    /// Compile the file at `path`, in the language its extension names
+    pub fn compile_file(
+        &self,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<ShaderDesc, ShaderCompileError> {
+        let path = path.as_ref();
+        let name = path.to_string_lossy();
+        let language = ShaderLanguage::from_path(path)
+            .ok_or_else(|| error(&name, "unknown shader language; use .glsl or .wgsl"))?;
+        let source = FileSystem::read_to_string(path).map_err(|err| error(&name, err))?;
+        self.compile(&name, &source, language, stage, entry_point)
+    }
+
+    /// `source` with its includes expanded, and for WGSL its defines and
+    /// conditional lines applied
+    pub fn preprocess(
+        &self,
+        name: &str,
+        source: &str,
+        language: ShaderLanguage,
+    ) -> Result<String, ShaderCompileError> {
+        Ok(self.expand(name, source, language)?.source)
+    }
+
+    fn expand(
+        &self,
+        name: &str,
+        source: &str,
+        language: ShaderLanguage,
+    ) -> Result<Unit, ShaderCompileError> {
+        let mut unit = Unit {
+            source: String::new(),
+            lines: Vec::new(),
+        };
+        if language == ShaderLanguage::Wgsl {
+            for (define, value) in &self.defines {
+                if !value.is_empty() {
+                    unit.push(&format!("const {} = {};", define, value), DEFINES_FILE, 1);
+                }
+            }
+        }
+        let mut included = HashSet::new();
+        let mut conditions = Vec::new();
+        self.expand_file(
+            name,
+            source,
+            language,
+            &mut unit,
+            &mut included,
+            &mut conditions,
+        )?;
+        if !conditions.is_empty() {
+            return Err(error(name, "#ifdef without #endif"));
+        }
+        Ok(unit)
+    }
+
+    /// Append `source` to `unit`, expanding includes; `conditions` holds
+    /// whether each open WGSL `#ifdef` block is active
+    fn expand_file(
+        &self,
+        name: &str,
+        source: &str,
+        language: ShaderLanguage,
+        unit: &mut Unit,
+        included: &mut HashSet<String>,
+        conditions: &mut Vec<bool>,
+    ) -> Result<(), ShaderCompileError> {
+        included.insert(name.to_string());
+        for (index, line) in source.lines().enumerate() {
+            let number = index as u32 + 1;
+            let at = |message: String| ShaderCompileError {
+                diagnostics: vec![ShaderDiagnostic {
+                    file: name.to_string(),
+                    line: number,
+                    column: 1,
+                    message,
+                }],
+            };
+            let directive = line.trim_start();
+            if language == ShaderLanguage::Wgsl {
+                let mut words = directive.split_whitespace();
+                let keep = match (words.next(), words.next()) {
+                    (Some("#ifdef"), Some(define)) => Some(self.is_defined(define)),
+                    (Some("#ifndef"), Some(define)) => Some(!self.is_defined(define)),
+                    _ => None,
+                };
+                if let Some(keep) = keep {
+                    conditions.push(keep);
+                    unit.push("", name, number);
+                    continue;
+                }
+                match directive.split_whitespace().next() {
+                    Some("#else") => {
+                        let last = conditions
+                            .last_mut()
+                            .ok_or_else(|| at("#else without #ifdef".to_string()))?;
+                        *last = !*last;
+                        unit.push("", name, number);
+                        continue;
+                    }
+                    Some("#endif") => {
+                        conditions
+                            .pop()
+                            .ok_or_else(|| at("#endif without #ifdef".to_string()))?;
+                        unit.push("", name, number);
+                        continue;
+                    }
+                    _ => {}
+                }
+                if conditions.contains(&false) {
+                    unit.push("", name, number);
+                    continue;
+                }
+            }
+
+            let Some(include) = directive.strip_prefix("#include") else {
+                unit.push(line, name, number);
+                continue;
+            };
+            let include = include.trim();
+            let target = include
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+                .or_else(|| include.strip_prefix('<')?.strip_suffix('>'))
+                .ok_or_else(|| at(format!("malformed #include {}", include)))?;
+            let (path, source) = self
+                .find_include(name, target)
+                .ok_or_else(|| at(format!("cannot find include '{}'", target)))?;
+            if included.contains(&path) {
+                unit.push("", name, number);
+                continue;
+            }
+            self.expand_file(&path, &source, language, unit, included, conditions)?;
+        }
+        Ok(())
+    }
+
+    fn is_defined(&self, define: &str) -> bool {
+        self.defines.iter().any(|(name, _)| name == define)
+    }
+
+    /// Name and source of the file `#include "target"` in `from` means
+    fn find_include(&self, from: &str, target: &str) -> Option<(String, String)> {
+        if let Some(source) = self.includes.get(target) {
+            return Some((target.to_string(), source.clone()));
+        }
+        let beside = Path::new(from).parent().map(|dir| dir.join(target));
+        beside
+            .into_iter()
+            .chain(self.include_dirs.iter().map(|dir| dir.join(target)))
+            .find(|path| FileSystem::is_file(path))
+            .and_then(|path| {
+                let source = FileSystem::read_to_string(&path).ok()?;
+                Some((path.to_string_lossy().into_owned(), source))
+            })
+    }
+}
+
+/// Expanded source, with the file and line each of its lines came from
+struct Unit {
+    source: String,
+    lines: Vec<(String, u32)>,
+}
+
+impl Unit {
+    fn push(&mut self, line: &str, file: &str, number: u32) {
+        self.source.push_str(line);
+        self.source.push('\n');
+        self.lines.push((file.to_string(), number));
+    }
+
+    /// Diagnostic at `span` of the expanded source, in its original file
+    fn diagnostic(&self, span: Option<naga::Span>, message: String) -> ShaderDiagnostic {
+        let location = span
+            .filter(|span| span.is_defined())
+            .map(|span| span.location(&self.source));
+        let origin = location.and_then(|location| {
+            let (file, line) = self.lines.get(location.line_number as usize - 1)?;
+            Some((file.clone(), *line, location.line_position))
+        });
+        let (file, line, column) = origin
+            .or_else(|| Some((self.lines.last()?.0.clone(), 0, 0)))
+            .unwrap_or_default();
+        ShaderDiagnostic {
+            file,
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+/// Error without a position in `file`
+fn error(file: &str, message: impl fmt::Display) -> ShaderCompileError {
+    ShaderCompileError {
+        diagnostics: vec![ShaderDiagnostic {
+            file: file.to_string(),
+            line: 0,
+            column: 0,
+            message: message.to_string(),
+        }],
+    }
+}
+
+/// `err` followed by its causes
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPIRV_MAGIC: [u8; 4] = 0x0723_0203u32.to_le_bytes();
+
+    const VERTEX_GLSL: &str = "#version 450
+#include \"common.glsl\"
+layout(location = 0) in vec2 position;
+void main() {
+    gl_Position = vec4(position * SCALE, 0.0, 1.0);
+}
+";
+
+    const FRAGMENT_WGSL: &str = "#include \"color.wgsl\"
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+#ifdef RED
+    return vec4<f32>(RED, 0.0, 0.0, 1.0);
+#else
+    return color();
+#endif
+}
+";
+
+    fn compiler() -> ShaderCompiler {
+        ShaderCompiler::new()
+            // Including itself is a no-op
+            .with_include(
+                "common.glsl",
+                "#include \"common.glsl\"\nconst float BIAS = 0.0;\n",
+            )
+            .with_include(
+                "color.wgsl",
+                "fn color() -> vec4<f32> {\n    return vec4<f32>(1.0);\n}\n",
+            )
+    }
+
+    #[test]
+    fn test_compile_glsl_with_includes_and_defines() {
+        let err = compiler()
+            .compile(
+                "shader.vert",
+                VERTEX_GLSL,
+                ShaderLanguage::Glsl,
+                ShaderStage::Vertex,
+                "main",
+            )
+            .unwrap_err();
+        // SCALE is not defined yet
+        assert_eq!(err.diagnostics[0].file, "shader.vert");
+        assert_eq!(err.diagnostics[0].line, 5);
+
+        let desc = compiler()
+            .with_define("SCALE", "0.5")
+            .compile(
+                "shader.vert",
+                VERTEX_GLSL,
+                ShaderLanguage::Glsl,
+                ShaderStage::Vertex,
+                "main",
+            )
+            .unwrap();
+        assert_eq!(desc.stage, ShaderStage::Vertex);
+        assert_eq!(desc.entry_point, "main");
+        assert_eq!(desc.code[..4], SPIRV_MAGIC);
+        assert_eq!(desc.code.len() % 4, 0);
+    }
+
+    #[test]
+    fn test_compile_wgsl_conditionals() {
+        let compile = |compiler: ShaderCompiler| {
+            compiler.compile(
+                "shader.wgsl",
+                FRAGMENT_WGSL,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Fragment,
+                "fs_main",
+            )
+        };
+        assert!(compile(compiler()).is_ok());
+        assert!(compile(compiler().with_define("RED", "0.5")).is_ok());
+
+        let source = compiler()
+            .with_define("RED", "0.5")
+            .preprocess("shader.wgsl", FRAGMENT_WGSL, ShaderLanguage::Wgsl)
+            .unwrap();
+        assert!(source.starts_with("const RED = 0.5;\nfn color()"));
+        assert!(source.contains("return vec4<f32>(RED, 0.0, 0.0, 1.0);"));
+        assert!(!source.contains("return color();"));
+
+        let err = compiler()
+            .compile(
+                "shader.wgsl",
+                FRAGMENT_WGSL,
+                ShaderLanguage::Wgsl,
+                ShaderStage::Fragment,
+                "main",
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no Fragment entry point named 'main'"));
+    }
+
+    #[test]
+    fn test_diagnostics_point_into_includes() {
+        let compiler = ShaderCompiler::new().with_include(
+            "broken.wgsl",
+            "fn helper() -> f32 {\n    return undefined_value;\n}\n",
+        );
+        let err = compiler
+            .compile(
+                "shader.wgsl",
+                "#include \"broken.wgsl\"\n@compute @workgroup_size(1)\nfn main() {}\n",
+                ShaderLanguage::Wgsl,
+                ShaderStage::Compute,
+                "main",
+            )
+            .unwrap_err();
+        let diagnostic = &err.diagnostics[0];
+        assert_eq!(diagnostic.file, "broken.wgsl");
+        assert_eq!((diagnostic.line, diagnostic.column), (2, 12));
+        assert!(matches!(
+            GfxError::from(err.clone()),
+            GfxError::ShaderCompileError(_)
+        ));
+
+        let err = ShaderCompiler::new()
+            .compile(
+                "shader.wgsl",
+                "#include \"missing.wgsl\"\n",
+                ShaderLanguage::Wgsl,
+                ShaderStage::Compute,
+                "main",
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "shader.wgsl:1:1: cannot find include 'missing.wgsl'"
+        );
+    }
+}
@@ -0,0 +1,413 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cascaded shadow maps for directional lights
+//!
+//! The camera's view is cut into slices along its depth (`cascade_splits`)
+//! and each slice gets its own orthographic light projection fitted around
+//! it (`fit_light_projection`), so nearby geometry gets more shadow texels
+//! than distant geometry. The cascades share one depth atlas, side by side
+//! from left to right; `add_shadow_pass` declares the depth-only pass that
+//! fills it.
+//!
+//! Projections are fitted to the bounding sphere of a slice and snapped to
+//! whole texels, so they keep their size as the camera turns and only move
+//! in texel steps as it moves: shadow edges do not shimmer.
+
+use crate::gfx::api::{
+    ClearDepthStencil, CommandList, DepthAttachment, LoadOp, Rect, RenderPassDesc, StoreOp,
+    TextureDesc, TextureFormat, TextureUsage, Viewport,
+};
+use crate::gfx::camera::{Camera, Frustum};
+use crate::gfx::framegraph::{FrameGraphBuilder, PassResources, ResourceId};
+use avila_math::{Aabb, Mat4, Vec3, Vec4};
+
+/// Most cascades `fit_cascades` produces
+pub const MAX_CASCADES: usize = 4;
+
+/// How the view is divided into cascades
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CascadeSettings {
+    /// Number of cascades, 1 to `MAX_CASCADES`
+    pub count: usize,
+    /// Width and height of each cascade's tile in texels
+    pub resolution: u32,
+    /// Blend between uniform (0) and logarithmic (1) split distances
+    pub split_lambda: f32,
+    /// View distance where shadows end, when closer than the far plane
+    pub max_distance: f32,
+}
+
+impl Default for CascadeSettings {
+    fn default() -> Self {
+        Self {
+            count: 4,
+            resolution: 2048,
+            split_lambda: 0.75,
+            max_distance: 100.0,
+        }
+    }
+}
+
+impl CascadeSettings {
+    /// The depth atlas holding every cascade
+    pub fn atlas_desc(&self) -> TextureDesc {
+        TextureDesc::new_2d(
+            self.resolution * self.count() as u32,
+            self.resolution,
+            TextureFormat::Depth32f,
+            TextureUsage::DEPTH_ATTACHMENT | TextureUsage::SAMPLED,
+        )
+    }
+
+    /// Viewport of cascade `index`'s tile in the atlas
+    pub fn tile_viewport(&self, index: usize) -> Viewport {
+        Viewport {
+            x: (index as u32 * self.resolution) as f32,
+            y: 0.0,
+            width: self.resolution as f32,
+            height: self.resolution as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count.clamp(1, MAX_CASCADES)
+    }
+}
+
+/// One slice of the view and the light projection covering it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowCascade {
+    /// View distances the cascade covers
+    pub near: f32,
+    pub far: f32,
+    /// World to the light's clip space, depth 0..1, for rendering the
+    /// cascade
+    pub view_projection: Mat4,
+    /// World to the cascade's tile of the atlas, for sampling it: texture
+    /// coordinates in x and y, depth in z
+    pub atlas_matrix: Mat4,
+    /// World-space size of one texel, to scale normal-offset bias with
+    pub texel_size: f32,
+}
+
+impl ShadowCascade {
+    /// Volume the cascade's light projection sees, for culling casters
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&self.view_projection)
+    }
+}
+
+/// Far distance of each of `count` cascades between `near` and `far`
+///
+/// `lambda` blends uniform splits (0), which waste texels close to the
+/// camera, with logarithmic ones (1), which give distant cascades too
+/// little. The last distance is `far`.
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            if i == count {
+                return far;
+            }
+            let fraction = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(fraction);
+            let uniform = near + (far - near) * fraction;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// Orthographic view-projection for a light shining along `direction`
+/// that covers `points`, with a `resolution` texels wide shadow map
+///
+/// The projection spans the bounding sphere of `points` and is snapped to
+/// whole texels. Its depth range reaches toward the light to include
+/// `casters`, so objects outside the view still cast shadows into it.
+pub fn fit_light_projection(
+    direction: Vec3,
+    points: &[Vec3],
+    resolution: u32,
+    casters: Option<Aabb>,
+) -> Mat4 {
+    fit(direction, points, resolution, casters).0
+}
+
+/// Light view-projection and world-space texel size
+fn fit(direction: Vec3, points: &[Vec3], resolution: u32, casters: Option<Aabb>) -> (Mat4, f32) {
+    let center = points.iter().fold(Vec3::ZERO, |sum, &point| sum + point) / points.len() as f32;
+    let radius = points
+        .iter()
+        .map(|&point| (point - center).length())
+        .fold(0.0, f32::max);
+    // Rounded up so float noise does not change the size between frames
+    let radius = ((radius * 16.0).ceil() / 16.0).max(1.0 / 16.0);
+
+    let direction = direction.normalize();
+    let up = if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    // Rotation only, so the texel grid stays put in the world
+    let view = Mat4::look_at_rh(Vec3::ZERO, direction, up);
+    let texel_size = 2.0 * radius / resolution.max(1) as f32;
+    let light_center = view.transform_point3(center);
+    let x = (light_center.x / texel_size).floor() * texel_size;
+    let y = (light_center.y / texel_size).floor() * texel_size;
+
+    // The light looks down -Z: larger z is closer to it
+    let mut closest = light_center.z + radius;
+    let farthest = light_center.z - radius;
+    if let Some(casters) = casters {
+        for corner in casters.vertices() {
+            closest = closest.max(view.transform_point3(corner).z);
+        }
+    }
+
+    let projection = orthographic(
+        x - radius,
+        x + radius,
+        y - radius,
+        y + radius,
+        -closest,
+        -farthest,
+    );
+    (projection * view, texel_size)
+}
+
+/// Off-center orthographic projection with depth 0..1, like
+/// `Camera::projection_matrix`
+fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+    let depth = 1.0 / (near - far);
+    Mat4::from_cols(
+        Vec4::new(2.0 / (right - left), 0.0, 0.0, 0.0),
+        Vec4::new(0.0, 2.0 / (top - bottom), 0.0, 0.0),
+        Vec4::new(0.0, 0.0, depth, 0.0),
+        Vec4::new(
+            -(right + left) / (right - left),
+            -(top + bottom) / (top - bottom),
+            near * depth,
+            1.0,
+        ),
+    )
+}
+
+/// Cascades of a light shining along `direction` for `camera`'s view,
+/// nearest first
+///
+/// `casters` bounds the objects that cast shadows, typically the scene.
+pub fn fit_cascades(
+    camera: &Camera,
+    direction: Vec3,
+    settings: &CascadeSettings,
+    casters: Option<Aabb>,
+) -> Vec<ShadowCascade> {
+    let count = settings.count();
+    let far = settings.max_distance.min(camera.far);
+    let splits = cascade_splits(camera.near, far, count, settings.split_lambda);
+
+    let mut near = camera.near;
+    splits
+        .into_iter()
+        .enumerate()
+        .map(|(index, far)| {
+            let corners = camera.frustum_corners(near, far);
+            let (view_projection, texel_size) =
+                fit(direction, &corners, settings.resolution, casters);
+            // Clip x and y to the tile's texture coordinates, origin at the
+            // top left
+            let tile = Mat4::from_cols(
+                Vec4::new(0.5 / count as f32, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, -0.5, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, 1.0, 0.0),
+                Vec4::new((index as f32 + 0.5) / count as f32, 0.5, 0.0, 1.0),
+            );
+            let cascade = ShadowCascade {
+                near,
+                far,
+                view_projection,
+                atlas_matrix: tile * view_projection,
+                texel_size,
+            };
+            near = far;
+            cascade
+        })
+        .collect()
+}
+
+/// Declare `name`, a depth-only pass rendering every cascade into a
+/// transient shadow atlas, and return the atlas for the passes sampling it
+///
+/// The pass clears the atlas, then calls `draw` for each cascade index
+/// with the viewport and scissor on its tile. `draw` binds a pipeline with
+/// no color target and a `Depth32f` depth format, gives it the cascade's
+/// `view_projection`, and draws the casters. `reads` are resources `draw`
+/// uses, such as culled instance buffers. The atlas is named
+/// `{name}_atlas`.
+pub fn add_shadow_pass(
+    fg: &mut FrameGraphBuilder,
+    name: &str,
+    settings: &CascadeSettings,
+    reads: &[&ResourceId],
+    draw: impl Fn(&mut CommandList, &PassResources, usize) + 'static,
+) -> ResourceId {
+    let atlas = fg.create_texture(&format!("{name}_atlas"), settings.atlas_desc());
+    let atlas_name = atlas.name().to_string();
+    let settings = *settings;
+    fg.add_pass(
+        name,
+        |pass| {
+            for resource in reads {
+                pass.read(resource);
+            }
+            pass.write(&atlas);
+        },
+        Box::new(move |cmd, resources| {
+            cmd.begin_render_pass(RenderPassDesc {
+                color_attachments: Vec::new(),
+                depth_attachment: Some(DepthAttachment {
+                    texture: resources.get_texture(&atlas_name),
+                    load: LoadOp::Clear(ClearDepthStencil::default()),
+                    store: StoreOp::Store,
+                }),
+            });
+            for index in 0..settings.count() {
+                cmd.set_viewport(settings.tile_viewport(index));
+                cmd.set_scissor(Rect {
+                    x: (index as u32 * settings.resolution) as i32,
+                    y: 0,
+                    width: settings.resolution,
+                    height: settings.resolution,
+                });
+                draw(cmd, resources, index);
+            }
+            cmd.end_render_pass();
+        }),
+    );
+    atlas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{Backend, RendererConfig, TextureHandle};
+    use crate::gfx::backend::create_device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_cascade_splits() {
+        assert_eq!(cascade_splits(1.0, 9.0, 4, 0.0), [3.0, 5.0, 7.0, 9.0]);
+        let logarithmic = cascade_splits(1.0, 81.0, 4, 1.0);
+        for (split, expected) in logarithmic.iter().zip([3.0, 9.0, 27.0, 81.0]) {
+            assert!((split - expected).abs() < 1e-3, "{logarithmic:?}");
+        }
+        let blended = cascade_splits(0.1, 100.0, 4, 0.75);
+        assert!(blended.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(blended[3], 100.0);
+    }
+
+    #[test]
+    fn test_cascades_cover_the_view() {
+        let camera = Camera::perspective(1.0, 16.0 / 9.0, 0.1, 500.0)
+            .with_position(Vec3::new(3.0, 5.0, 10.0))
+            .looking_at(Vec3::ZERO, Vec3::Y);
+        let direction = Vec3::new(-1.0, -2.0, -0.5);
+        let settings = CascadeSettings::default();
+        let casters = Aabb::new(Vec3::new(-50.0, -1.0, -50.0), Vec3::new(50.0, 40.0, 50.0));
+        let cascades = fit_cascades(&camera, direction, &settings, Some(casters));
+        assert_eq!(cascades.len(), 4);
+        assert_eq!(cascades[0].near, 0.1);
+        assert_eq!(cascades[3].far, 100.0);
+
+        for (index, cascade) in cascades.iter().enumerate() {
+            for corner in camera.frustum_corners(cascade.near, cascade.far) {
+                let clip = cascade.view_projection.transform_point3(corner);
+                assert!(clip.x.abs() <= 1.0 && clip.y.abs() <= 1.0, "{clip:?}");
+                assert!((0.0..=1.0).contains(&clip.z), "{clip:?}");
+                let uv = cascade.atlas_matrix.transform_point3(corner);
+                let tile = index as f32 / 4.0;
+                assert!(uv.x >= tile && uv.x <= tile + 0.25, "{uv:?}");
+                assert!((0.0..=1.0).contains(&uv.y), "{uv:?}");
+            }
+            // Casters between the light and the view are in depth range
+            let above = cascade
+                .view_projection
+                .transform_point3(Vec3::new(0.0, 40.0, 0.0));
+            assert!(above.z >= 0.0, "{above:?}");
+            let middle = camera.position + camera.forward() * (cascade.near + cascade.far) / 2.0;
+            assert!(cascade.frustum().contains_point(middle));
+        }
+        assert!(cascades[0].texel_size < cascades[3].texel_size);
+    }
+
+    #[test]
+    fn test_projection_snaps_to_texels() {
+        let direction = Vec3::new(0.3, -1.0, 0.2);
+        let resolution = 1024;
+        for offset in [0.0, 0.013, 0.37] {
+            let points: Vec<Vec3> = Aabb::new(Vec3::splat(-4.0), Vec3::splat(4.0))
+                .vertices()
+                .iter()
+                .map(|&point| point + Vec3::new(offset, 0.0, offset))
+                .collect();
+            let projection = fit_light_projection(direction, &points, resolution, None);
+            // The world origin lands on a texel corner whatever the offset
+            let origin = projection.transform_point3(Vec3::ZERO);
+            for clip in [origin.x, origin.y] {
+                let texels = clip * resolution as f32 / 2.0;
+                assert!((texels - texels.round()).abs() < 1e-2, "{texels}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_shadow_pass_draws_each_cascade() {
+        let mut device = create_device(RendererConfig {
+            backend: Backend::Software,
+            ..Default::default()
+        });
+        let settings = CascadeSettings {
+            count: 3,
+            resolution: 64,
+            ..Default::default()
+        };
+        let drawn = Rc::new(RefCell::new(Vec::new()));
+        let mut fg = FrameGraphBuilder::new();
+        let record = drawn.clone();
+        let atlas = add_shadow_pass(
+            &mut fg,
+            "shadows",
+            &settings,
+            &[],
+            move |_, resources, index| {
+                assert_ne!(
+                    resources.get_texture("shadows_atlas"),
+                    TextureHandle::INVALID
+                );
+                record.borrow_mut().push(index);
+            },
+        );
+        assert_eq!(atlas.name(), "shadows_atlas");
+        assert_eq!(settings.atlas_desc().width, 192);
+
+        // The atlas is kept because a lighting pass samples it
+        fg.add_pass(
+            "lighting",
+            |pass| {
+                pass.read(&atlas);
+                pass.side_effect();
+            },
+            Box::new(|_, _| {}),
+        );
+        let mut graph = fg.compile().unwrap();
+        assert_eq!(graph.pass_names(), ["shadows", "lighting"]);
+        graph.execute(&mut device).unwrap();
+        assert_eq!(*drawn.borrow(), [0, 1, 2]);
+        graph.release(&mut device);
+        let stats = &device.memory_stats()[0];
+        assert_eq!(stats.allocation_count, stats.deallocation_count);
+    }
+}
@@ -0,0 +1,472 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Texture file loader (feature `texture-loader`)
+//!
+//! Reads PNG and JPEG images and the KTX2 and DDS containers into a
+//! `TextureDesc` and the texel data `create_texture` and `update_texture`
+//! take. The file type comes from its first bytes, not its extension.
+//!
+//! PNG and JPEG become `Rgba8Srgb` with a full mip chain, which
+//! `create_texture` generates; `TextureData::linear` makes them `Rgba8`
+//! for data such as normal maps. Containers keep their format and the
+//! mips they store. They may hold `Rgba8`, `Bgra8`, `Rgba16f`, `Rgba32f`,
+//! `Bc1`, `Bc3` or `Bc7` texels, as 2D textures, arrays, cube maps or 3D
+//! textures; sRGB variants of the block formats load as their linear
+//! `TextureFormat`. Supercompressed KTX2 (Basis Universal, Zstandard) is
+//! not supported.
+
+use crate::gfx::api::{
+    GfxError, GpuDevice, TextureDesc, TextureDimension, TextureFormat, TextureHandle,
+    TextureRegion, TextureUsage,
+};
+use crate::gfx::backend::{layer_count, region_layout};
+use std::fmt;
+use std::path::Path;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+/// Why a texture file does not load
+#[derive(Debug)]
+pub enum TextureLoadError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The PNG or JPEG image could not be decoded
+    Image(image::ImageError),
+    /// The file is truncated, of an unknown type or holds a format or
+    /// layout that is not supported; the message says which
+    Unsupported(String),
+}
+
+impl fmt::Display for TextureLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureLoadError::Io(err) => write!(f, "texture file could not be read: {}", err),
+            TextureLoadError::Image(err) => write!(f, "image decoding failed: {}", err),
+            TextureLoadError::Unsupported(reason) => {
+                write!(f, "unsupported texture file: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureLoadError {}
+
+impl From<std::io::Error> for TextureLoadError {
+    fn from(err: std::io::Error) -> Self {
+        TextureLoadError::Io(err)
+    }
+}
+
+impl From<image::ImageError> for TextureLoadError {
+    fn from(err: image::ImageError) -> Self {
+        TextureLoadError::Image(err)
+    }
+}
+
+fn unsupported<T>(reason: impl Into<String>) -> Result<T, TextureLoadError> {
+    Err(TextureLoadError::Unsupported(reason.into()))
+}
+
+/// Texture read from a file, ready to create on a device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureData {
+    /// Sampled texture with the file's size, format, layers and mips
+    pub desc: TextureDesc,
+    /// Texels of each mip stored in the file, in the layout of
+    /// `update_texture`, layer after layer
+    ///
+    /// `levels[0]` is `create_texture`'s initial data. The mips after the
+    /// stored ones are generated when the texture is created.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl TextureData {
+    /// Store color as linear `Rgba8` rather than `Rgba8Srgb`
+    pub fn linear(mut self) -> Self {
+        if self.desc.format == TextureFormat::Rgba8Srgb {
+            self.desc.format = TextureFormat::Rgba8;
+        }
+        self
+    }
+
+    /// Create the texture with every stored mip
+    pub fn create(&self, device: &mut dyn GpuDevice) -> Result<TextureHandle, GfxError> {
+        let texture = device.create_texture(&self.desc, Some(&self.levels[0]))?;
+        let layers = layer_count(&self.desc) as usize;
+        for (mip, level) in self.levels.iter().enumerate().skip(1) {
+            let region = TextureRegion::full(&self.desc, mip as u32);
+            for (layer, texels) in level.chunks_exact(level.len() / layers).enumerate() {
+                let updated =
+                    device.update_texture(texture, mip as u32, layer as u32, region, texels);
+                if let Err(err) = updated {
+                    device.destroy_texture(texture);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(texture)
+    }
+}
+
+/// Reads texture files into `TextureData`
+pub struct TextureLoader;
+
+impl TextureLoader {
+    /// Read and decode a PNG, JPEG, KTX2 or DDS file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<TextureData, TextureLoadError> {
+        Self::from_memory(&std::fs::read(path)?)
+    }
+
+    /// Decode a PNG, JPEG, KTX2 or DDS file held in memory
+    pub fn from_memory(bytes: &[u8]) -> Result<TextureData, TextureLoadError> {
+        if bytes.starts_with(&KTX2_IDENTIFIER) {
+            load_ktx2(bytes)
+        } else if bytes.starts_with(DDS_MAGIC) {
+            load_dds(bytes)
+        } else if bytes.starts_with(b"\x89PNG") {
+            load_image(bytes, image::ImageFormat::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            load_image(bytes, image::ImageFormat::Jpeg)
+        } else {
+            unsupported("not a PNG, JPEG, KTX2 or DDS file")
+        }
+    }
+}
+
+fn load_image(bytes: &[u8], format: image::ImageFormat) -> Result<TextureData, TextureLoadError> {
+    let image = image::load_from_memory_with_format(bytes, format)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    let mip_levels = u32::BITS - width.max(height).leading_zeros();
+    let desc = TextureDesc::new_2d(
+        width,
+        height,
+        TextureFormat::Rgba8Srgb,
+        TextureUsage::SAMPLED,
+    )
+    .with_mips(mip_levels);
+    Ok(TextureData {
+        desc,
+        levels: vec![image.into_raw()],
+    })
+}
+
+/// Little-endian reads that fail on truncated files
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&self, offset: usize, len: usize) -> Result<&[u8], TextureLoadError> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.0.len() => Ok(&self.0[offset..end]),
+            _ => unsupported(format!(
+                "file of {} bytes ends before {} bytes at {}",
+                self.0.len(),
+                len,
+                offset
+            )),
+        }
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, TextureLoadError> {
+        let bytes = self.bytes(offset, 4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&self, offset: usize) -> Result<usize, TextureLoadError> {
+        let bytes = self.bytes(offset, 8)?;
+        usize::try_from(u64::from_le_bytes(bytes.try_into().unwrap()))
+            .or_else(|_| unsupported("offset beyond the address space"))
+    }
+}
+
+/// Bytes of one layer of mip `mip`
+fn mip_size(desc: &TextureDesc, mip: u32) -> usize {
+    let region = TextureRegion::full(desc, mip);
+    let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+    row_bytes * rows * region.depth as usize
+}
+
+/// Description of a sampled texture; `faces` is 6 for cube maps, `depth`
+/// above 1 for 3D textures
+fn container_desc(
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    depth: u32,
+    layers: u32,
+    faces: u32,
+    mip_levels: u32,
+) -> Result<TextureDesc, TextureLoadError> {
+    let mut desc = TextureDesc::new_2d(width, height, format, TextureUsage::SAMPLED)
+        .with_mips(mip_levels.max(1));
+    match (faces, depth) {
+        (1, 0 | 1) => {}
+        (1, depth) if layers <= 1 => {
+            desc.dimension = TextureDimension::D3;
+            desc.depth = depth;
+        }
+        (6, 0 | 1) => desc.dimension = TextureDimension::Cube,
+        _ => {
+            return unsupported(format!(
+                "{} faces of depth {} in {} layers",
+                faces, depth, layers
+            ))
+        }
+    }
+    desc.array_layers = layers.max(1) * faces;
+    if width == 0 || height == 0 {
+        return unsupported("texture size is zero");
+    }
+    let max_mips = u32::BITS - width.max(height).max(desc.depth).leading_zeros();
+    if desc.mip_levels > max_mips {
+        return unsupported(format!(
+            "{} mips for a {}x{} texture",
+            desc.mip_levels, width, height
+        ));
+    }
+    Ok(desc)
+}
+
+fn load_ktx2(bytes: &[u8]) -> Result<TextureData, TextureLoadError> {
+    let file = Reader(bytes);
+    let vk_format = file.u32(12)?;
+    let format = match vk_format {
+        37 => TextureFormat::Rgba8,      // R8G8B8A8_UNORM
+        43 => TextureFormat::Rgba8Srgb,  // R8G8B8A8_SRGB
+        44 | 50 => TextureFormat::Bgra8, // B8G8R8A8_UNORM, _SRGB
+        97 => TextureFormat::Rgba16f,    // R16G16B16A16_SFLOAT
+        109 => TextureFormat::Rgba32f,   // R32G32B32A32_SFLOAT
+        131..=134 => TextureFormat::Bc1, // BC1_RGB(A)_UNORM, _SRGB
+        137 | 138 => TextureFormat::Bc3, // BC3_UNORM, _SRGB
+        145 | 146 => TextureFormat::Bc7, // BC7_UNORM, _SRGB
+        0 => return unsupported("KTX2 without a Vulkan format (Basis Universal)"),
+        other => return unsupported(format!("KTX2 Vulkan format {}", other)),
+    };
+    let [width, height, depth, layers, faces, levels, supercompression] =
+        [20, 24, 28, 32, 36, 40, 44].map(|offset| file.u32(offset));
+    if supercompression? != 0 {
+        return unsupported("supercompressed KTX2");
+    }
+    let desc = container_desc(format, width?, height?, depth?, layers?, faces?, levels?)?;
+    let layers = layer_count(&desc) as usize;
+
+    // The level index follows the 80-byte header, mip 0 first
+    let levels = (0..desc.mip_levels)
+        .map(|mip| {
+            let entry = 80 + mip as usize * 24;
+            let (offset, length) = (file.u64(entry)?, file.u64(entry + 8)?);
+            let expected = mip_size(&desc, mip) * layers;
+            if length != expected {
+                return unsupported(format!(
+                    "KTX2 mip {} of {} bytes, expected {}",
+                    mip, length, expected
+                ));
+            }
+            Ok(file.bytes(offset, length)?.to_vec())
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TextureData { desc, levels })
+}
+
+fn load_dds(bytes: &[u8]) -> Result<TextureData, TextureLoadError> {
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+    const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+    let file = Reader(bytes);
+    if file.u32(4)? != 124 {
+        return unsupported("DDS header size is not 124");
+    }
+    let (height, width, depth, mips) = (file.u32(12)?, file.u32(16)?, file.u32(24)?, file.u32(28)?);
+    let (pf_flags, four_cc) = (file.u32(80)?, file.bytes(84, 4)?);
+    let caps2 = file.u32(112)?;
+    let cube = caps2 & DDSCAPS2_CUBEMAP != 0;
+    let depth = if caps2 & DDSCAPS2_VOLUME != 0 {
+        depth
+    } else {
+        1
+    };
+
+    let (format, faces, layers, data_offset) = if pf_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DX10" => {
+                let format = match file.u32(128)? {
+                    28 => TextureFormat::Rgba8,      // R8G8B8A8_UNORM
+                    29 => TextureFormat::Rgba8Srgb,  // R8G8B8A8_UNORM_SRGB
+                    87 | 91 => TextureFormat::Bgra8, // B8G8R8A8_UNORM, _SRGB
+                    10 => TextureFormat::Rgba16f,    // R16G16B16A16_FLOAT
+                    2 => TextureFormat::Rgba32f,     // R32G32B32A32_FLOAT
+                    71 | 72 => TextureFormat::Bc1,   // BC1_UNORM, _SRGB
+                    77 | 78 => TextureFormat::Bc3,   // BC3_UNORM, _SRGB
+                    98 | 99 => TextureFormat::Bc7,   // BC7_UNORM, _SRGB
+                    other => return unsupported(format!("DDS DXGI format {}", other)),
+                };
+                let cube = file.u32(136)? & DDS_RESOURCE_MISC_TEXTURECUBE != 0;
+                (format, if cube { 6 } else { 1 }, file.u32(140)?, 148)
+            }
+            b"DXT1" => (TextureFormat::Bc1, if cube { 6 } else { 1 }, 1, 128),
+            b"DXT5" => (TextureFormat::Bc3, if cube { 6 } else { 1 }, 1, 128),
+            // D3DFMT_A16B16G16R16F, D3DFMT_A32B32G32R32F
+            [113, 0, 0, 0] => (TextureFormat::Rgba16f, if cube { 6 } else { 1 }, 1, 128),
+            [116, 0, 0, 0] => (TextureFormat::Rgba32f, if cube { 6 } else { 1 }, 1, 128),
+            other => return unsupported(format!("DDS FourCC {:?}", other)),
+        }
+    } else if pf_flags & DDPF_RGB != 0 && file.u32(88)? == 32 {
+        let format = match [file.u32(92)?, file.u32(100)?] {
+            [0xFF, 0xFF_0000] => TextureFormat::Rgba8,
+            [0xFF_0000, 0xFF] => TextureFormat::Bgra8,
+            masks => return unsupported(format!("DDS RGB masks {:x?}", masks)),
+        };
+        (format, if cube { 6 } else { 1 }, 1, 128)
+    } else {
+        return unsupported("DDS pixel format without FourCC or 32-bit RGB");
+    };
+    let desc = container_desc(format, width, height, depth, layers, faces, mips)?;
+
+    // DDS stores each layer with its mips; levels hold each mip with its
+    // layers
+    let mut levels = vec![Vec::new(); desc.mip_levels as usize];
+    let mut offset = data_offset;
+    for _ in 0..layer_count(&desc) {
+        for (mip, level) in levels.iter_mut().enumerate() {
+            let size = mip_size(&desc, mip as u32);
+            level.extend_from_slice(file.bytes(offset, size)?);
+            offset += size;
+        }
+    }
+    Ok(TextureData { desc, levels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::api::{Backend, RendererConfig};
+    use crate::gfx::backend::create_device;
+    use crate::gfx::png;
+
+    fn device() -> impl GpuDevice {
+        create_device(RendererConfig {
+            width: 4,
+            height: 4,
+            backend: Backend::Software,
+            ..Default::default()
+        })
+    }
+
+    /// DDS header of a texture with `mips` mips, followed by `data`
+    fn dds(width: u32, height: u32, mips: u32, four_cc: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut header = [0u32; 32];
+        header[0] = u32::from_le_bytes(*DDS_MAGIC);
+        header[1] = 124;
+        header[3] = height;
+        header[4] = width;
+        header[7] = mips;
+        header[19] = 32; // Pixel format size
+        header[20] = 0x4; // DDPF_FOURCC
+        header[21] = u32::from_le_bytes(*four_cc);
+        let mut file: Vec<u8> = header.iter().flat_map(|word| word.to_le_bytes()).collect();
+        file.extend_from_slice(data);
+        file
+    }
+
+    #[test]
+    fn test_load_png() {
+        let pixels: Vec<u8> = (0..4 * 3 * 4).map(|i| i as u8 * 5).collect();
+        let data = TextureLoader::from_memory(&png::encode_rgba8(4, 3, &pixels)).unwrap();
+        assert_eq!((data.desc.width, data.desc.height), (4, 3));
+        assert_eq!(data.desc.format, TextureFormat::Rgba8Srgb);
+        assert_eq!(data.desc.mip_levels, 3);
+        assert_eq!(data.levels, vec![pixels.clone()]);
+        assert_eq!(data.clone().linear().desc.format, TextureFormat::Rgba8);
+
+        let mut device = device();
+        let mut readable = data.linear();
+        readable.desc.usage = readable.desc.usage | TextureUsage::TRANSFER_SRC;
+        let texture = readable.create(&mut device).unwrap();
+        assert_eq!(device.read_texture(texture).unwrap(), pixels);
+    }
+
+    #[test]
+    fn test_load_dds_mips() {
+        // BC1 8x8: 4 blocks in mip 0, one in each of mips 1 to 3
+        let blocks: Vec<u8> = (0..7 * 8).map(|i| i as u8).collect();
+        let data = TextureLoader::from_memory(&dds(8, 8, 4, b"DXT1", &blocks)).unwrap();
+        assert_eq!(data.desc.format, TextureFormat::Bc1);
+        assert_eq!(data.desc.mip_levels, 4);
+        let sizes: Vec<usize> = data.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [32, 8, 8, 8]);
+        assert_eq!(data.levels[3], &blocks[48..]);
+
+        let mut device = device();
+        let texture = data.create(&mut device).unwrap();
+        device.destroy_texture(texture);
+
+        // One block short
+        let truncated = dds(8, 8, 4, b"DXT1", &blocks[..48]);
+        assert!(matches!(
+            TextureLoader::from_memory(&truncated),
+            Err(TextureLoadError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_ktx2_cube() {
+        // RGBA8 sRGB cube map of 2x2 faces with 2 mips
+        let level_sizes = [6 * 16, 6 * 4];
+        let data_start = 80 + 2 * 24;
+        let mut file = KTX2_IDENTIFIER.to_vec();
+        for value in [43u32, 1, 2, 2, 0, 0, 6, 2, 0] {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+        file.resize(80, 0);
+        // Smaller mips come first in the file
+        let offsets = [data_start + level_sizes[1], data_start];
+        for (offset, size) in offsets.into_iter().zip(level_sizes) {
+            for value in [offset, size, size] {
+                file.extend_from_slice(&(value as u64).to_le_bytes());
+            }
+        }
+        file.extend((0..level_sizes[1]).map(|i| 200 + i as u8 % 50));
+        file.extend((0..level_sizes[0]).map(|i| i as u8));
+
+        let data = TextureLoader::from_memory(&file).unwrap();
+        assert_eq!(data.desc.dimension, TextureDimension::Cube);
+        assert_eq!(data.desc.array_layers, 6);
+        assert_eq!(data.desc.format, TextureFormat::Rgba8Srgb);
+        assert_eq!(data.levels[0], (0..96).map(|i| i as u8).collect::<Vec<_>>());
+        assert_eq!(data.levels[1].len(), 24);
+        assert!(data.levels[1].iter().all(|&byte| byte >= 200));
+
+        let mut device = device();
+        let texture = data.create(&mut device).unwrap();
+        device.destroy_texture(texture);
+
+        // Basis Universal payloads carry no Vulkan format
+        file[12] = 0;
+        assert!(matches!(
+            TextureLoader::from_memory(&file),
+            Err(TextureLoadError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert!(matches!(
+            TextureLoader::from_file("/nonexistent/texture.png"),
+            Err(TextureLoadError::Io(_))
+        ));
+        assert!(matches!(
+            TextureLoader::from_memory(b"GIF89a"),
+            Err(TextureLoadError::Unsupported(_))
+        ));
+        assert!(matches!(
+            TextureLoader::from_memory(b"\x89PNG\r\n\x1a\n"),
+            Err(TextureLoadError::Image(_))
+        ));
+    }
+}
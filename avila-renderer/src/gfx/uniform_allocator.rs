@@ -0,0 +1,129 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Per-frame uniform data
+//!
+//! Updating a small uniform buffer per object makes the driver wait for, or
+//! copy around, the draws still reading it. `DynamicUniformAllocator`
+//! instead packs each frame's uniforms into one large buffer: slices are
+//! handed out from a kernel `RingAllocator` holding the CPU copy, and the
+//! frame's writes reach the GPU in one upload. The ring keeps the ranges of
+//! the last few frames alive, so a frame never writes where the GPU may
+//! still be reading.
+
+use crate::gfx::api::{BindingResource, BufferDesc, BufferHandle, GfxError, GpuDevice};
+use avila_math::memory::{AllocatorInfo, KernelAlloc, RingAllocator};
+
+/// Alignment of every slice, the largest uniform buffer offset alignment
+/// backends require
+pub const UNIFORM_ALIGNMENT: u64 = 256;
+
+/// Frames whose uniforms are kept while the GPU may still read them
+pub const UNIFORM_FRAMES: usize = 3;
+
+/// Uniform data for one draw, at `offset` in the allocator's buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UniformSlice {
+    pub buffer: BufferHandle,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl UniformSlice {
+    /// The slice as a bind group entry resource
+    pub fn binding(&self) -> BindingResource {
+        BindingResource::Buffer {
+            buffer: self.buffer,
+            offset: self.offset,
+            size: Some(self.size),
+        }
+    }
+}
+
+/// Ring of per-frame uniform slices in one uniform buffer
+///
+/// Call `allocate` while recording a frame and `end_frame` before
+/// submitting it. Slices stay valid for `UNIFORM_FRAMES` frames.
+pub struct DynamicUniformAllocator {
+    buffer: BufferHandle,
+    ring: RingAllocator,
+    /// Ranges of the ring written since the last upload, in order
+    pending: Vec<(usize, usize)>,
+}
+
+impl DynamicUniformAllocator {
+    /// Create the uniform buffer, `capacity` bytes shared by the frames in
+    /// flight
+    pub fn new(device: &mut dyn GpuDevice, capacity: usize) -> Result<Self, GfxError> {
+        let buffer = device.create_buffer(&BufferDesc::uniform(capacity), None)?;
+        Ok(Self {
+            buffer,
+            ring: RingAllocator::new(capacity),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Copy `data` into a slice of this frame
+    ///
+    /// Fails with `OutOfMemory` when the frames in flight fill the buffer.
+    pub fn allocate(&mut self, data: &[u8]) -> Result<UniformSlice, GfxError> {
+        // Whole words, so uploads need no read-modify-write
+        let size = data.len().next_multiple_of(4);
+        let ptr = self
+            .ring
+            .alloc(size, UNIFORM_ALIGNMENT as usize)
+            .ok_or(GfxError::OutOfMemory)?;
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), data.len());
+            std::ptr::write_bytes(ptr.as_ptr().add(data.len()), 0, size - data.len());
+        }
+        let start = self.ring.offset_of(ptr);
+        let end = start + size;
+        match self.pending.last_mut() {
+            // Padding between slices is uploaded with them
+            Some(range) if range.1 <= start => range.1 = end,
+            _ => self.pending.push((start, end)),
+        }
+        Ok(UniformSlice {
+            buffer: self.buffer,
+            offset: start as u64,
+            size: data.len() as u64,
+        })
+    }
+
+    /// Upload the slices allocated so far
+    ///
+    /// `end_frame` does this; call it earlier to submit work mid-frame.
+    pub fn flush(&mut self, device: &mut dyn GpuDevice) {
+        for (start, end) in self.pending.drain(..) {
+            let data = unsafe {
+                std::slice::from_raw_parts(self.ring.as_ptr().as_ptr().add(start), end - start)
+            };
+            device.update_buffer(self.buffer, start, data);
+        }
+    }
+
+    /// Upload this frame's slices and free those of the frame that is now
+    /// `UNIFORM_FRAMES` old
+    pub fn end_frame(&mut self, device: &mut dyn GpuDevice) {
+        self.flush(device);
+        self.ring.end_frame();
+        while self.ring.frames_in_flight() > UNIFORM_FRAMES {
+            self.ring.release_frame();
+        }
+    }
+
+    pub fn buffer(&self) -> BufferHandle {
+        self.buffer
+    }
+
+    /// Use of the ring, for `MemoryManager::register_allocator`
+    pub fn allocator_info(&self) -> AllocatorInfo {
+        self.ring.allocator_info()
+    }
+
+    /// Destroy the uniform buffer
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        device.destroy_buffer(self.buffer);
+    }
+}
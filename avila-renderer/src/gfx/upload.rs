@@ -0,0 +1,294 @@
+// Copyright (c) 2025 Avila Contributors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Batched uploads through a staging buffer
+//!
+//! Initial data and `update_texture` copy each resource through memory of
+//! its own. `UploadContext` instead writes the data of many uploads into
+//! one staging buffer, sub-allocated by a kernel `RingAllocator`, and
+//! `flush` submits a copy command per upload ahead of the frame that uses
+//! them. A fence follows each flush; its staging space is reused once the
+//! GPU has reached the fence.
+
+use crate::gfx::api::{
+    BufferDesc, BufferHandle, CommandList, FenceValue, GfxError, GpuDevice, TexelBufferLayout,
+    TextureDesc, TextureHandle, TextureRegion, TextureUsage, COPY_ROW_ALIGNMENT,
+};
+use crate::gfx::backend::{layer_count, region_layout};
+use avila_math::memory::{AllocatorInfo, KernelAlloc, RingAllocator};
+use std::collections::VecDeque;
+
+/// Uploads of buffers and textures, copied from a staging buffer at the
+/// start of a frame
+///
+/// Queue uploads while loading or recording, then call `flush` before
+/// submitting the frame that reads them.
+pub struct UploadContext {
+    staging: BufferHandle,
+    ring: RingAllocator,
+    /// Ranges of the ring written since the last flush, in order
+    pending: Vec<(usize, usize)>,
+    /// Copies of the uploads since the last flush
+    copies: CommandList,
+    /// Textures created here whose mips are generated after the copies
+    mipmaps: Vec<TextureHandle>,
+    /// Fence of each flush whose staging space is still in use, oldest
+    /// first
+    in_flight: VecDeque<FenceValue>,
+}
+
+impl UploadContext {
+    /// Create the staging buffer, `capacity` bytes shared by the flushes in
+    /// flight
+    pub fn new(device: &mut dyn GpuDevice, capacity: usize) -> Result<Self, GfxError> {
+        let staging = device.create_buffer(&BufferDesc::staging(capacity), None)?;
+        Ok(Self {
+            staging,
+            ring: RingAllocator::new(capacity),
+            pending: Vec::new(),
+            copies: CommandList::new(),
+            mipmaps: Vec::new(),
+            in_flight: VecDeque::new(),
+        })
+    }
+
+    /// Create a buffer holding `data`, in place of `create_buffer` with
+    /// initial data
+    ///
+    /// The size is rounded up to whole words, which copies move.
+    pub fn create_buffer(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        desc: &BufferDesc,
+        data: &[u8],
+    ) -> Result<BufferHandle, GfxError> {
+        let desc = BufferDesc {
+            size: desc.size.next_multiple_of(4),
+            ..desc.clone()
+        };
+        if data.len() > desc.size {
+            return Err(GfxError::InvalidDesc(format!(
+                "{} bytes of data for a {} byte buffer",
+                data.len(),
+                desc.size
+            )));
+        }
+        let buffer = device.create_buffer(&desc, None)?;
+        let size = data.len().next_multiple_of(4);
+        let start = match self.allocate(device, size, 4) {
+            Ok(start) => start,
+            Err(err) => {
+                device.destroy_buffer(buffer);
+                return Err(err);
+            }
+        };
+        let staged = self.staged(start, size);
+        staged[..data.len()].copy_from_slice(data);
+        staged[data.len()..].fill(0);
+        self.copies
+            .copy_buffer(self.staging, start as u64, buffer, 0, size as u64);
+        Ok(buffer)
+    }
+
+    /// Create a texture from mip 0 of each layer, in place of
+    /// `create_texture` with initial data
+    ///
+    /// As there, the other mips are generated where the format allows it,
+    /// after the copies of the next `flush`.
+    pub fn create_texture(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        desc: &TextureDesc,
+        data: &[u8],
+    ) -> Result<TextureHandle, GfxError> {
+        let mut desc = desc.clone();
+        desc.usage = desc.usage | TextureUsage::TRANSFER_DST;
+        let layers = layer_count(&desc) as usize;
+        if data.is_empty() || !data.len().is_multiple_of(layers) {
+            return Err(GfxError::InvalidDesc(format!(
+                "{} bytes of data do not split into {} layers",
+                data.len(),
+                layers
+            )));
+        }
+        let texture = device.create_texture(&desc, None)?;
+        for (layer, chunk) in data.chunks_exact(data.len() / layers).enumerate() {
+            let uploaded = self.upload_texture(device, texture, &desc, 0, layer as u32, chunk);
+            if let Err(err) = uploaded {
+                device.destroy_texture(texture);
+                return Err(err);
+            }
+        }
+        if desc.mip_levels > 1 && !desc.format.is_compressed() {
+            self.mipmaps.push(texture);
+        }
+        Ok(texture)
+    }
+
+    /// Copy `data` into `buffer` at `offset` with the next flush
+    ///
+    /// `offset` and the length of `data` are multiples of 4.
+    pub fn upload_buffer(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        buffer: BufferHandle,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        if !offset.is_multiple_of(4) || !data.len().is_multiple_of(4) {
+            return Err(GfxError::InvalidDesc(format!(
+                "{} bytes at {} are not aligned to 4 bytes",
+                data.len(),
+                offset
+            )));
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        let start = self.allocate(device, data.len(), 4)?;
+        self.staged(start, data.len()).copy_from_slice(data);
+        self.copies.copy_buffer(
+            self.staging,
+            start as u64,
+            buffer,
+            offset,
+            data.len() as u64,
+        );
+        Ok(())
+    }
+
+    /// Copy `data` into the whole of mip `mip` of layer `layer` with the
+    /// next flush
+    ///
+    /// `desc` is the texture's description; `data` is laid out as for
+    /// `update_texture`. Smaller regions go through `update_texture`.
+    pub fn upload_texture(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        texture: TextureHandle,
+        desc: &TextureDesc,
+        mip: u32,
+        layer: u32,
+        data: &[u8],
+    ) -> Result<(), GfxError> {
+        let region = TextureRegion::full(desc, mip);
+        let (row_bytes, rows) = region_layout(desc.format, region.width, region.height);
+        let rows = rows * region.depth as usize;
+        if data.len() != row_bytes * rows {
+            return Err(GfxError::InvalidDesc(format!(
+                "{} bytes of texel data for a mip of {} bytes",
+                data.len(),
+                row_bytes * rows
+            )));
+        }
+        // Copies read rows at aligned distances
+        let pitch = row_bytes.next_multiple_of(COPY_ROW_ALIGNMENT as usize);
+        let size = pitch * (rows - 1) + row_bytes;
+        let start = self.allocate(device, size, COPY_ROW_ALIGNMENT as usize)?;
+        let staged = self.staged(start, size);
+        for (row, texels) in data.chunks_exact(row_bytes).enumerate() {
+            staged[row * pitch..row * pitch + row_bytes].copy_from_slice(texels);
+        }
+        let layout = TexelBufferLayout {
+            offset: start as u64,
+            bytes_per_row: pitch as u32,
+        };
+        self.copies
+            .copy_buffer_to_texture(self.staging, layout, texture, mip, layer, region);
+        Ok(())
+    }
+
+    /// Submit the copies of the uploads so far
+    ///
+    /// Call it before submitting the frame that reads the uploads; later
+    /// submissions see the copied data.
+    pub fn flush(&mut self, device: &mut dyn GpuDevice) {
+        self.recycle(device);
+        if self.pending.is_empty() {
+            return;
+        }
+        for (start, end) in self.pending.drain(..) {
+            let data = unsafe {
+                std::slice::from_raw_parts(self.ring.as_ptr().as_ptr().add(start), end - start)
+            };
+            device.update_buffer(self.staging, start, data);
+        }
+        device.submit(std::mem::take(&mut self.copies));
+        for texture in self.mipmaps.drain(..) {
+            if let Err(err) = device.generate_mipmaps(texture) {
+                eprintln!("Mipmaps of an uploaded texture failed: {}", err);
+            }
+        }
+        self.ring.end_frame();
+        self.in_flight.push_back(device.signal_fence());
+    }
+
+    /// Free the staging space of the flushes the GPU has finished
+    pub fn recycle(&mut self, device: &mut dyn GpuDevice) {
+        while let Some(&fence) = self.in_flight.front() {
+            if !device.fence_reached(fence) {
+                break;
+            }
+            self.in_flight.pop_front();
+            self.ring.release_frame();
+        }
+    }
+
+    /// Flushes whose staging space the GPU may still be reading
+    pub fn flushes_in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Use of the staging ring, for `MemoryManager::register_allocator`
+    pub fn allocator_info(&self) -> AllocatorInfo {
+        self.ring.allocator_info()
+    }
+
+    /// Destroy the staging buffer, after the GPU is done with it
+    pub fn destroy(self, device: &mut dyn GpuDevice) {
+        if !self.in_flight.is_empty() {
+            device.wait_idle();
+        }
+        device.destroy_buffer(self.staging);
+    }
+
+    /// Offset of `size` bytes of staging space, recorded for the next flush
+    ///
+    /// When the space in flight fills the ring, the uploads so far are
+    /// flushed and the GPU is waited for.
+    fn allocate(
+        &mut self,
+        device: &mut dyn GpuDevice,
+        size: usize,
+        align: usize,
+    ) -> Result<usize, GfxError> {
+        if size > self.ring.capacity() {
+            return Err(GfxError::OutOfMemory);
+        }
+        let mut ptr = self.ring.alloc(size, align);
+        if ptr.is_none() {
+            self.recycle(device);
+            ptr = self.ring.alloc(size, align);
+        }
+        if ptr.is_none() {
+            self.flush(device);
+            device.wait_idle();
+            self.in_flight.clear();
+            self.ring.reset();
+            ptr = self.ring.alloc(size, align);
+        }
+        let start = self.ring.offset_of(ptr.ok_or(GfxError::OutOfMemory)?);
+        let end = start + size;
+        match self.pending.last_mut() {
+            // Padding between uploads is uploaded with them
+            Some(range) if range.1 <= start => range.1 = end,
+            _ => self.pending.push((start, end)),
+        }
+        Ok(start)
+    }
+
+    /// Staging memory allocated by `allocate`
+    fn staged(&mut self, start: usize, size: usize) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ring.as_ptr().as_ptr().add(start), size) }
+    }
+}
@@ -16,21 +16,58 @@
 //! ```rust
 //! use avila_renderer::gfx::*;
 //!
+//! # fn main() -> Result<(), GfxError> {
+//! # let (vertex_code, fragment_code): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
 //! // Create device
 //! let config = RendererConfig::default();
 //! let mut device = create_device(config);
 //!
-//! // Create resources
+//! // Create resources; creation fails with a `GfxError`
 //! let texture = device.create_texture(&TextureDesc::new_2d(
 //!     1280, 720,
 //!     TextureFormat::Rgba8,
 //!     TextureUsage::COLOR_ATTACHMENT,
-//! ));
+//! ), None)?;
 //!
+//! let vertex_data: Vec<u8> = [0.0f32, 0.5, -0.5, -0.5, 0.5, -0.5]
+//!     .iter()
+//!     .flat_map(|f| f.to_le_bytes())
+//!     .collect();
 //! let buffer = device.create_buffer(
-//!     &BufferDesc::vertex(1024),
+//!     &BufferDesc::vertex(vertex_data.len()),
 //!     Some(&vertex_data),
-//! );
+//! )?;
+//!
+//! let vertex_shader = device.create_shader(&ShaderDesc {
+//!     stage: ShaderStage::Vertex,
+//!     entry_point: "main".into(),
+//!     code: vertex_code,
+//! })?;
+//! let fragment_shader = device.create_shader(&ShaderDesc {
+//!     stage: ShaderStage::Fragment,
+//!     entry_point: "main".into(),
+//!     code: fragment_code,
+//! })?;
+//! let pipeline = device.create_pipeline(&PipelineDesc {
+//!     vertex_shader,
+//!     fragment_shader,
+//!     vertex_layout: VertexLayout::new(
+//!         8,
+//!         vec![VertexAttribute {
+//!             format: VertexFormat::Float2,
+//!             offset: 0,
+//!             location: 0,
+//!         }],
+//!     ),
+//!     topology: PrimitiveTopology::TriangleList,
+//!     rasterizer: RasterizerState::default(),
+//!     depth_stencil: DepthStencilState::default(),
+//!     blend_states: vec![BlendState::default()],
+//!     color_formats: vec![TextureFormat::Rgba8],
+//!     depth_format: None,
+//!     sample_count: 1,
+//!     layout: PipelineLayoutDesc::default(),
+//! })?;
 //!
 //! // Record commands
 //! let mut cmd = device.begin_frame();
@@ -38,7 +75,8 @@
 //!     color_attachments: vec![
 //!         ColorAttachment {
 //!             texture,
-//!             clear: Some(ClearColor::BLACK),
+//!             load: LoadOp::Clear(ClearColor::BLACK),
+//!             store: StoreOp::Store,
 //!         }
 //!     ],
 //!     depth_attachment: None,
@@ -51,6 +89,8 @@
 //! // Submit and present
 //! device.submit(cmd);
 //! device.present();
+//! # Ok(())
+//! # }
 //! ```
 
 pub mod gfx;